@@ -1,5 +1,5 @@
 -- Create rifts table
-CREATE TABLE rifts (
+CREATE TABLE IF NOT EXISTS rifts (
     id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
     project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
     name VARCHAR(64) NOT NULL,
@@ -33,7 +33,7 @@ CREATE TABLE IF NOT EXISTS user_rift_state (
 );
 
 -- Create indexes for better query performance
-CREATE INDEX idx_rifts_project_id ON rifts(project_id);
+CREATE INDEX IF NOT EXISTS idx_rifts_project_id ON rifts(project_id);
 CREATE INDEX IF NOT EXISTS idx_rift_files_rift_id ON rift_files(rift_id);
 CREATE INDEX IF NOT EXISTS idx_user_rift_state_user_project ON user_rift_state(user_id, project_id);
 
@@ -0,0 +1,15 @@
+-- Git mirrors: remotes that receive a rift's checkpoint history as Git commits
+CREATE TABLE IF NOT EXISTS git_mirrors (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    rift_id UUID NOT NULL REFERENCES rifts(id) ON DELETE CASCADE,
+    remote_url TEXT NOT NULL,
+    created_by UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    last_pushed_at TIMESTAMP WITH TIME ZONE,
+    last_error TEXT,
+    UNIQUE (rift_id, remote_url)
+);
+
+CREATE INDEX IF NOT EXISTS idx_git_mirrors_project_id ON git_mirrors(project_id);
+CREATE INDEX IF NOT EXISTS idx_git_mirrors_rift_id ON git_mirrors(rift_id);
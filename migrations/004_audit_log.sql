@@ -0,0 +1,18 @@
+-- Audit log: append-only, hash-chained record of security-relevant actions.
+-- Each row's hash covers its contents and the previous row's hash, so any
+-- edit or deletion breaks the chain (see GET /admin/audit/verify).
+CREATE TABLE IF NOT EXISTS audit_log (
+    id BIGSERIAL PRIMARY KEY,
+    occurred_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    actor_id UUID,
+    action VARCHAR(64) NOT NULL,
+    project_id UUID,
+    target TEXT,
+    details TEXT NOT NULL DEFAULT '{}',
+    prev_hash VARCHAR(64) NOT NULL,
+    hash VARCHAR(64) NOT NULL UNIQUE
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_occurred_at ON audit_log(occurred_at);
+CREATE INDEX IF NOT EXISTS idx_audit_log_actor_id ON audit_log(actor_id);
+CREATE INDEX IF NOT EXISTS idx_audit_log_project_id ON audit_log(project_id);
@@ -0,0 +1,11 @@
+-- Chat messages posted in rifts.
+-- Only the most recent messages per rift are kept (collaboration.max_chat_history).
+CREATE TABLE IF NOT EXISTS chat_messages (
+    id UUID PRIMARY KEY,
+    rift_id UUID NOT NULL REFERENCES rifts(id) ON DELETE CASCADE,
+    author_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    content TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_chat_messages_rift_created ON chat_messages(rift_id, created_at DESC);
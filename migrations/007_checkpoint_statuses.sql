@@ -0,0 +1,17 @@
+-- Status checks (CI builds, test runs, ...) reported against checkpoints.
+-- One row per (checkpoint, context); reporting the same context again replaces it.
+CREATE TABLE IF NOT EXISTS checkpoint_statuses (
+    id UUID PRIMARY KEY,
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    checkpoint_id UUID NOT NULL,
+    context VARCHAR(255) NOT NULL,
+    state VARCHAR(16) NOT NULL,
+    target_url TEXT,
+    description TEXT,
+    creator_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT checkpoint_statuses_context_unique UNIQUE (checkpoint_id, context),
+    CONSTRAINT checkpoint_statuses_state_check CHECK (state IN ('pending', 'success', 'failure', 'error'))
+);
@@ -0,0 +1,31 @@
+-- Per-rift access control.
+-- visibility: 'project' (any project member may join), 'invite' (only users the
+-- rift is shared with) or 'private' (owner only).
+ALTER TABLE rifts
+    ADD COLUMN IF NOT EXISTS owner_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    ADD COLUMN IF NOT EXISTS visibility VARCHAR(16) NOT NULL DEFAULT 'project';
+
+ALTER TABLE rifts DROP CONSTRAINT IF EXISTS rifts_visibility_check;
+ALTER TABLE rifts ADD CONSTRAINT rifts_visibility_check CHECK (visibility IN ('project', 'invite', 'private'));
+
+-- Existing rifts are owned by their first collaborator
+UPDATE rifts r
+SET owner_id = (
+    SELECT rc.user_id FROM rift_collaborators rc
+    WHERE rc.rift_id = r.id
+    ORDER BY rc.joined_at ASC
+    LIMIT 1
+)
+WHERE r.owner_id IS NULL;
+
+-- Users a rift has been shared with, and what they may do
+CREATE TABLE IF NOT EXISTS rift_permissions (
+    rift_id UUID NOT NULL REFERENCES rifts(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role VARCHAR(16) NOT NULL,
+    granted_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    granted_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (rift_id, user_id),
+    CONSTRAINT rift_permissions_role_check CHECK (role IN ('editor', 'observer'))
+);
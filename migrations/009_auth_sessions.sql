@@ -0,0 +1,19 @@
+-- Machine logins. Each JWT issued by the OAuth flow carries its session ID as
+-- the `jti` claim, so a session can be listed and revoked before it expires.
+CREATE TABLE IF NOT EXISTS auth_sessions (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    machine_id VARCHAR(255) NOT NULL,
+    machine_name VARCHAR(255) NOT NULL,
+    platform VARCHAR(64) NOT NULL,
+    hostname VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    last_seen_at TIMESTAMP WITH TIME ZONE,
+    revoked_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_auth_sessions_user_id ON auth_sessions(user_id);
+
+-- The revocation list loaded at startup: revoked sessions whose tokens are still unexpired
+CREATE INDEX IF NOT EXISTS idx_auth_sessions_revoked ON auth_sessions(expires_at) WHERE revoked_at IS NOT NULL;
@@ -0,0 +1,11 @@
+-- Service accounts: non-human users for automation (build bots, CI).
+-- Each is backed by a users row so checkpoints, statuses and audit entries are
+-- attributed to it; it authenticates only with personal access tokens (keys)
+-- and reaches projects through explicit project_members grants.
+CREATE TABLE IF NOT EXISTS service_accounts (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    description TEXT,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    disabled_at TIMESTAMP WITH TIME ZONE
+);
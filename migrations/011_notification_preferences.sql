@@ -0,0 +1,10 @@
+-- Per-user email notification preferences. Users without a row get every
+-- notification (all columns default to on).
+CREATE TABLE IF NOT EXISTS notification_preferences (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    invitations BOOLEAN NOT NULL DEFAULT TRUE,
+    mentions BOOLEAN NOT NULL DEFAULT TRUE,
+    conflict_rifts BOOLEAN NOT NULL DEFAULT TRUE,
+    checkpoint_restores BOOLEAN NOT NULL DEFAULT TRUE,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
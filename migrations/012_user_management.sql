@@ -0,0 +1,4 @@
+-- Admin user management: disabled users keep their data but cannot log in,
+-- use personal access tokens, or keep existing sessions.
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS disabled_at TIMESTAMP WITH TIME ZONE;
@@ -0,0 +1,4 @@
+-- Archived projects are read-only: history and beam still work, but no new
+-- checkpoints or sync writes are accepted until the project is unarchived.
+ALTER TABLE projects
+    ADD COLUMN IF NOT EXISTS archived_at TIMESTAMP WITH TIME ZONE;
@@ -0,0 +1,9 @@
+-- The rift each user last switched to in a project (`mothership rift switch`)
+CREATE TABLE IF NOT EXISTS current_rifts (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    rift_id UUID NOT NULL REFERENCES rifts(id) ON DELETE CASCADE,
+    switched_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (user_id, project_id)
+);
@@ -0,0 +1,7 @@
+-- Deleted projects go to the trash first: hidden from everyone, but an admin
+-- can restore them until the retention period runs out and they are purged.
+ALTER TABLE projects
+    ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN IF NOT EXISTS deleted_by UUID REFERENCES users(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_projects_deleted_at ON projects(deleted_at) WHERE deleted_at IS NOT NULL;
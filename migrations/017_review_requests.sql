@@ -0,0 +1,43 @@
+-- Requests to merge one rift into another once reviewers have approved it
+CREATE TABLE IF NOT EXISTS review_requests (
+    id UUID PRIMARY KEY,
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    source_rift_id UUID NOT NULL REFERENCES rifts(id) ON DELETE CASCADE,
+    target_rift_id UUID NOT NULL REFERENCES rifts(id) ON DELETE CASCADE,
+    title TEXT NOT NULL,
+    description TEXT,
+    author_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- 'open', 'merged' or 'closed'
+    state VARCHAR(16) NOT NULL DEFAULT 'open',
+    required_approvals INTEGER NOT NULL DEFAULT 1,
+    merge_checkpoint_id UUID,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT review_requests_state_check CHECK (state IN ('open', 'merged', 'closed')),
+    CONSTRAINT review_requests_rifts_check CHECK (source_rift_id <> target_rift_id)
+);
+
+-- Only one open request per source and target
+CREATE UNIQUE INDEX IF NOT EXISTS idx_review_requests_open
+    ON review_requests(source_rift_id, target_rift_id) WHERE state = 'open';
+CREATE INDEX IF NOT EXISTS idx_review_requests_project ON review_requests(project_id, created_at);
+
+-- Reviewers asked to look at a request; approved_at is set once they approve
+CREATE TABLE IF NOT EXISTS review_reviewers (
+    review_id UUID NOT NULL REFERENCES review_requests(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    approved_at TIMESTAMP WITH TIME ZONE,
+    added_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (review_id, user_id)
+);
+
+CREATE TABLE IF NOT EXISTS review_comments (
+    id UUID PRIMARY KEY,
+    review_id UUID NOT NULL REFERENCES review_requests(id) ON DELETE CASCADE,
+    author_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    body TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_review_comments_review ON review_comments(review_id, created_at);
@@ -0,0 +1,17 @@
+-- Features administrators turned on or off, for the whole server or for a
+-- project. Features without a row follow the server configuration.
+CREATE TABLE IF NOT EXISTS server_feature_flags (
+    feature VARCHAR(64) PRIMARY KEY,
+    enabled BOOLEAN NOT NULL,
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS project_feature_flags (
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    feature VARCHAR(64) NOT NULL,
+    enabled BOOLEAN NOT NULL,
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (project_id, feature)
+);
@@ -0,0 +1,23 @@
+-- Machines users have logged in from. Sessions are bound to their machine
+-- through (user_id, machine_id), so revoking a machine revokes every
+-- session it holds; logging in on it again brings it back.
+CREATE TABLE IF NOT EXISTS machines (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    machine_id VARCHAR(255) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    platform VARCHAR(64) NOT NULL,
+    hostname VARCHAR(255) NOT NULL,
+    first_seen_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    last_seen_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    revoked_at TIMESTAMP WITH TIME ZONE,
+    UNIQUE (user_id, machine_id)
+);
+
+-- Machines of sessions from before machines were recorded
+INSERT INTO machines (user_id, machine_id, name, platform, hostname, first_seen_at, last_seen_at)
+SELECT DISTINCT ON (user_id, machine_id)
+       user_id, machine_id, machine_name, platform, hostname, created_at, COALESCE(last_seen_at, created_at)
+FROM auth_sessions
+ORDER BY user_id, machine_id, created_at DESC
+ON CONFLICT (user_id, machine_id) DO NOTHING;
@@ -0,0 +1,4 @@
+-- The checkpoint holding a rift's files as they were when it was created
+-- from its parent. Merges use it as the common ancestor.
+ALTER TABLE rifts
+    ADD COLUMN IF NOT EXISTS fork_checkpoint_id UUID;
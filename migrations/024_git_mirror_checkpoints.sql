@@ -0,0 +1,4 @@
+-- The newest checkpoint each Git mirror has received, so pushes are
+-- skipped when nothing new was checkpointed since.
+ALTER TABLE git_mirrors
+    ADD COLUMN IF NOT EXISTS last_pushed_checkpoint_id UUID;
@@ -0,0 +1,181 @@
+-- SQLite schema for single-user and self-hosted servers.
+-- Mirrors the PostgreSQL migrations one directory up. UUIDs are stored as
+-- 16-byte blobs, timestamps as RFC 3339 text written by the server, and
+-- arrays/JSON as JSON text.
+
+CREATE TABLE users (
+    id BLOB PRIMARY KEY,
+    username TEXT NOT NULL UNIQUE,
+    email TEXT NOT NULL UNIQUE,
+    role TEXT NOT NULL DEFAULT 'user' CHECK (role IN ('user', 'admin', 'super_admin')),
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    disabled_at TEXT,
+
+    CHECK (length(username) >= 1 AND length(username) <= 255)
+);
+
+CREATE TABLE projects (
+    id BLOB PRIMARY KEY,
+    name TEXT NOT NULL,
+    description TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    archived_at TEXT,
+
+    CHECK (length(name) >= 1 AND length(name) <= 255)
+);
+
+CREATE TABLE project_members (
+    project_id BLOB NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role TEXT NOT NULL DEFAULT 'member',
+    joined_at TEXT NOT NULL,
+
+    PRIMARY KEY (project_id, user_id)
+);
+
+CREATE TABLE project_settings (
+    project_id BLOB PRIMARY KEY REFERENCES projects(id) ON DELETE CASCADE,
+    settings TEXT NOT NULL DEFAULT '{}'
+);
+
+CREATE TABLE rifts (
+    id BLOB PRIMARY KEY,
+    project_id BLOB NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    description TEXT,
+    parent_rift_id BLOB REFERENCES rifts(id) ON DELETE SET NULL,
+    author BLOB REFERENCES users(id),
+    owner_id BLOB REFERENCES users(id) ON DELETE SET NULL,
+    visibility TEXT NOT NULL DEFAULT 'project' CHECK (visibility IN ('project', 'invite', 'private')),
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    is_conflict_rift BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+
+    UNIQUE (project_id, name)
+);
+
+CREATE TABLE rift_collaborators (
+    rift_id BLOB NOT NULL REFERENCES rifts(id) ON DELETE CASCADE,
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    joined_at TEXT NOT NULL,
+
+    PRIMARY KEY (rift_id, user_id)
+);
+
+CREATE TABLE rift_permissions (
+    rift_id BLOB NOT NULL REFERENCES rifts(id) ON DELETE CASCADE,
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role TEXT NOT NULL CHECK (role IN ('editor', 'observer')),
+    granted_by BLOB REFERENCES users(id) ON DELETE SET NULL,
+    granted_at TEXT NOT NULL,
+
+    PRIMARY KEY (rift_id, user_id)
+);
+
+CREATE TABLE user_rift_state (
+    user_id BLOB NOT NULL REFERENCES users(id),
+    project_id BLOB NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    current_rift_id BLOB NOT NULL REFERENCES rifts(id) ON DELETE CASCADE,
+    PRIMARY KEY (user_id, project_id)
+);
+
+CREATE TABLE git_mirrors (
+    id BLOB PRIMARY KEY,
+    project_id BLOB NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    rift_id BLOB NOT NULL REFERENCES rifts(id) ON DELETE CASCADE,
+    remote_url TEXT NOT NULL,
+    created_by BLOB NOT NULL REFERENCES users(id),
+    created_at TEXT NOT NULL,
+    last_pushed_at TEXT,
+    last_error TEXT,
+    UNIQUE (rift_id, remote_url)
+);
+
+CREATE TABLE audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    occurred_at TEXT NOT NULL,
+    actor_id BLOB,
+    action TEXT NOT NULL,
+    project_id BLOB,
+    target TEXT,
+    details TEXT NOT NULL DEFAULT '{}',
+    prev_hash TEXT NOT NULL,
+    hash TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE personal_access_tokens (
+    id BLOB PRIMARY KEY,
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL CHECK (length(name) >= 1 AND length(name) <= 255),
+    token_hash TEXT NOT NULL UNIQUE,
+    scopes TEXT NOT NULL DEFAULT '[]',
+    created_at TEXT NOT NULL,
+    expires_at TEXT,
+    last_used_at TEXT,
+    revoked_at TEXT
+);
+
+CREATE TABLE chat_messages (
+    id BLOB PRIMARY KEY,
+    rift_id BLOB NOT NULL REFERENCES rifts(id) ON DELETE CASCADE,
+    author_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    content TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE TABLE checkpoint_statuses (
+    id BLOB PRIMARY KEY,
+    project_id BLOB NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    checkpoint_id BLOB NOT NULL,
+    context TEXT NOT NULL,
+    state TEXT NOT NULL CHECK (state IN ('pending', 'success', 'failure', 'error')),
+    target_url TEXT,
+    description TEXT,
+    creator_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+
+    UNIQUE (checkpoint_id, context)
+);
+
+CREATE TABLE auth_sessions (
+    id BLOB PRIMARY KEY,
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    machine_id TEXT NOT NULL,
+    machine_name TEXT NOT NULL,
+    platform TEXT NOT NULL,
+    hostname TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    expires_at TEXT NOT NULL,
+    last_seen_at TEXT,
+    revoked_at TEXT
+);
+
+CREATE TABLE service_accounts (
+    user_id BLOB PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    description TEXT,
+    created_by BLOB REFERENCES users(id) ON DELETE SET NULL,
+    created_at TEXT NOT NULL,
+    disabled_at TEXT
+);
+
+CREATE TABLE notification_preferences (
+    user_id BLOB PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    invitations BOOLEAN NOT NULL DEFAULT TRUE,
+    mentions BOOLEAN NOT NULL DEFAULT TRUE,
+    conflict_rifts BOOLEAN NOT NULL DEFAULT TRUE,
+    checkpoint_restores BOOLEAN NOT NULL DEFAULT TRUE,
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_project_members_user_id ON project_members(user_id);
+CREATE INDEX idx_rifts_project_id ON rifts(project_id);
+CREATE INDEX idx_rift_collaborators_user_id ON rift_collaborators(user_id);
+CREATE INDEX idx_git_mirrors_rift_id ON git_mirrors(rift_id);
+CREATE INDEX idx_audit_log_occurred_at ON audit_log(occurred_at);
+CREATE INDEX idx_personal_access_tokens_user_id ON personal_access_tokens(user_id);
+CREATE INDEX idx_chat_messages_rift_created ON chat_messages(rift_id, created_at);
+CREATE INDEX idx_auth_sessions_user_id ON auth_sessions(user_id);
@@ -0,0 +1,9 @@
+-- The rift each user last switched to in a project (`mothership rift switch`)
+CREATE TABLE current_rifts (
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    project_id BLOB NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    rift_id BLOB NOT NULL REFERENCES rifts(id) ON DELETE CASCADE,
+    switched_at TEXT NOT NULL,
+
+    PRIMARY KEY (user_id, project_id)
+);
@@ -0,0 +1,4 @@
+-- Deleted projects go to the trash first: hidden from everyone, but an admin
+-- can restore them until the retention period runs out and they are purged.
+ALTER TABLE projects ADD COLUMN deleted_at TEXT;
+ALTER TABLE projects ADD COLUMN deleted_by BLOB REFERENCES users(id) ON DELETE SET NULL;
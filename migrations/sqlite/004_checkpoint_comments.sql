@@ -0,0 +1,23 @@
+-- Comments on a line range of a file at a checkpoint. The first comment of a
+-- thread has thread_id = id; replies share it and copy its path and lines.
+-- Only the first comment's resolved_* columns are used.
+CREATE TABLE IF NOT EXISTS checkpoint_comments (
+    id BLOB PRIMARY KEY,
+    project_id BLOB NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    checkpoint_id BLOB NOT NULL,
+    thread_id BLOB NOT NULL,
+    path TEXT NOT NULL,
+    line_start INTEGER NOT NULL,
+    line_end INTEGER NOT NULL,
+    author_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    body TEXT NOT NULL,
+    resolved_by BLOB REFERENCES users(id) ON DELETE SET NULL,
+    resolved_at TEXT,
+    created_at TEXT NOT NULL,
+
+    CHECK (line_start >= 1 AND line_end >= line_start)
+);
+
+CREATE INDEX IF NOT EXISTS idx_checkpoint_comments_checkpoint ON checkpoint_comments(checkpoint_id);
+CREATE INDEX IF NOT EXISTS idx_checkpoint_comments_thread ON checkpoint_comments(thread_id);
+CREATE INDEX IF NOT EXISTS idx_checkpoint_comments_project ON checkpoint_comments(project_id, created_at);
@@ -0,0 +1,15 @@
+-- In-app notifications, kept for each user until they read them
+CREATE TABLE IF NOT EXISTS user_notifications (
+    id BLOB PRIMARY KEY,
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- 'mention', 'review_requested', 'conflict_rift' or 'invitation'
+    kind TEXT NOT NULL,
+    title TEXT NOT NULL,
+    body TEXT NOT NULL,
+    project_id BLOB REFERENCES projects(id) ON DELETE CASCADE,
+    actor_id BLOB REFERENCES users(id) ON DELETE SET NULL,
+    read_at TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_notifications_user ON user_notifications(user_id, created_at);
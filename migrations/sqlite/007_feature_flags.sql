@@ -0,0 +1,17 @@
+-- Features administrators turned on or off, for the whole server or for a
+-- project. Features without a row follow the server configuration.
+CREATE TABLE IF NOT EXISTS server_feature_flags (
+    feature TEXT PRIMARY KEY,
+    enabled BOOLEAN NOT NULL,
+    updated_by BLOB REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS project_feature_flags (
+    project_id BLOB NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    feature TEXT NOT NULL,
+    enabled BOOLEAN NOT NULL,
+    updated_by BLOB REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (project_id, feature)
+);
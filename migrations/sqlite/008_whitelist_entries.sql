@@ -0,0 +1,18 @@
+-- Users allowed on servers with the whitelist enabled. Seeded from the
+-- whitelist file on first start, then managed through the admin API.
+CREATE TABLE IF NOT EXISTS whitelist_entries (
+    id BLOB PRIMARY KEY,
+    kind TEXT NOT NULL,
+    value TEXT NOT NULL,
+    added_by BLOB REFERENCES users(id) ON DELETE SET NULL,
+    created_at TEXT NOT NULL,
+    UNIQUE (kind, value)
+);
+
+-- OAuth organizations and teams each user belonged to at their last login,
+-- matched against group entries of the whitelist
+CREATE TABLE IF NOT EXISTS user_oauth_groups (
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    group_name TEXT NOT NULL,
+    PRIMARY KEY (user_id, group_name)
+);
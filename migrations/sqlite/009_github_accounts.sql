@@ -0,0 +1,9 @@
+-- GitHub logins of users who signed in with GitHub, to re-check their
+-- organization and team membership between logins
+CREATE TABLE IF NOT EXISTS github_accounts (
+    user_id BLOB PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    login TEXT NOT NULL,
+    -- The user's role came from [github_access] roles, and goes when the membership does
+    role_granted BOOLEAN NOT NULL DEFAULT FALSE,
+    checked_at TEXT NOT NULL
+);
@@ -0,0 +1,21 @@
+-- Machines users have logged in from. Sessions are bound to their machine
+-- through (user_id, machine_id), so revoking a machine revokes every
+-- session it holds; logging in on it again brings it back.
+CREATE TABLE IF NOT EXISTS machines (
+    id BLOB PRIMARY KEY,
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    machine_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    platform TEXT NOT NULL,
+    hostname TEXT NOT NULL,
+    first_seen_at TEXT NOT NULL,
+    last_seen_at TEXT NOT NULL,
+    revoked_at TEXT,
+    UNIQUE (user_id, machine_id)
+);
+
+-- Machines of sessions from before machines were recorded
+INSERT OR IGNORE INTO machines (id, user_id, machine_id, name, platform, hostname, first_seen_at, last_seen_at)
+SELECT randomblob(16), user_id, machine_id, machine_name, platform, hostname, created_at, COALESCE(last_seen_at, created_at)
+FROM auth_sessions
+ORDER BY created_at DESC;
@@ -0,0 +1,3 @@
+-- The checkpoint holding a rift's files as they were when it was created
+-- from its parent. Merges use it as the common ancestor.
+ALTER TABLE rifts ADD COLUMN fork_checkpoint_id BLOB;
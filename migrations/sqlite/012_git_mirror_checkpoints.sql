@@ -0,0 +1,3 @@
+-- The newest checkpoint each Git mirror has received, so pushes are
+-- skipped when nothing new was checkpointed since.
+ALTER TABLE git_mirrors ADD COLUMN last_pushed_checkpoint_id BLOB;
@@ -0,0 +1,207 @@
+//! A simulated daemon: one WebSocket connection to the rift, joined like a
+//! real daemon joins it. It keeps the rift's files up to date from the
+//! server's broadcasts and notes when each edit by another daemon arrives;
+//! writing daemons also send their own edits at a steady rate.
+
+use anyhow::{anyhow, Result};
+use futures_util::stream::{SplitSink, SplitStream};
+use futures_util::{SinkExt, StreamExt};
+use mothership_common::protocol::{SyncMessage, PROTOCOL_VERSION};
+use mothership_common::wire::{self, WireEncoding, WireFrame};
+use mothership_common::{DiffEngine, FileDiff, RiftId};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::time::{timeout_at, Instant, MissedTickBehavior};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use crate::workload::{self, Writer};
+
+/// How long a daemon waits for the server to accept it into the rift
+const JOIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// When each edit was sent, by writer and edit sequence number
+pub type SentEdits = Arc<Mutex<HashMap<(usize, u64), Instant>>>;
+
+/// A daemon that has joined the rift
+pub struct Daemon {
+    pub index: usize,
+    rift_id: RiftId,
+    encoding: WireEncoding,
+    sink: SplitSink<Socket, Message>,
+    stream: SplitStream<Socket>,
+    files: HashMap<PathBuf, String>,
+}
+
+/// What a daemon saw of the other daemons' edits
+#[derive(Debug, Default)]
+pub struct Received {
+    /// Time from sending to arrival, for each edit by another daemon
+    pub latencies: Vec<Duration>,
+    pub messages: u64,
+    pub bytes: u64,
+    /// Broadcast changes that didn't apply to the daemon's copy of the file
+    pub apply_errors: u64,
+}
+
+fn encode(encoding: WireEncoding, message: &SyncMessage) -> Result<Message> {
+    Ok(match encoding.encode(message)? {
+        WireFrame::Text(text) => Message::Text(text),
+        WireFrame::Binary(bytes) => Message::Binary(bytes),
+    })
+}
+
+/// Decode a frame, unwrapping sequenced broadcasts. Control frames decode to nothing.
+fn decode(frame: &Message) -> Result<Option<SyncMessage>> {
+    let message = match frame {
+        Message::Text(text) => wire::decode_text(text)?,
+        Message::Binary(bytes) => wire::decode_binary(bytes)?,
+        _ => return Ok(None),
+    };
+    Ok(Some(match message {
+        SyncMessage::Sequenced { message, .. } => *message,
+        message => message,
+    }))
+}
+
+impl Daemon {
+    /// Connect and join the rift, asking for `encoding`
+    pub async fn join(index: usize, ws_url: &str, rift_id: RiftId, encoding: WireEncoding) -> Result<Self> {
+        let (socket, _) = tokio_tungstenite::connect_async(ws_url).await
+            .map_err(|e| anyhow!("Daemon {} could not connect: {}", index, e))?;
+        let (mut sink, mut stream) = socket.split();
+
+        // The handshake is always JSON; the encoding applies after it
+        let join = SyncMessage::JoinRift {
+            rift_id,
+            last_checkpoint: None,
+            protocol_version: PROTOCOL_VERSION,
+            min_protocol_version: PROTOCOL_VERSION,
+            encodings: vec![encoding],
+            resume: None,
+            sparse_paths: Vec::new(),
+            manifest: None,
+            observer: false,
+        };
+        sink.send(encode(WireEncoding::Json, &join)?).await?;
+
+        let deadline = Instant::now() + JOIN_TIMEOUT;
+        loop {
+            let frame = timeout_at(deadline, stream.next()).await
+                .map_err(|_| anyhow!("Daemon {} was not let into the rift within {}s", index, JOIN_TIMEOUT.as_secs()))?
+                .ok_or_else(|| anyhow!("Server closed daemon {}'s connection while joining", index))??;
+            match decode(&frame)? {
+                Some(SyncMessage::RiftJoined { current_files, encoding, .. }) => {
+                    return Ok(Self { index, rift_id, encoding, sink, stream, files: current_files });
+                }
+                Some(SyncMessage::Error { message, .. }) => return Err(anyhow!("Daemon {} could not join: {}", index, message)),
+                _ => {}
+            }
+        }
+    }
+
+    /// Split into the halves that send edits and receive broadcasts
+    pub fn split(self) -> (Sender, Receiver) {
+        let sender = Sender { index: self.index, rift_id: self.rift_id, encoding: self.encoding, sink: self.sink };
+        let receiver = Receiver { index: self.index, stream: self.stream, files: self.files };
+        (sender, receiver)
+    }
+}
+
+pub struct Sender {
+    index: usize,
+    rift_id: RiftId,
+    encoding: WireEncoding,
+    sink: SplitSink<Socket, Message>,
+}
+
+impl Sender {
+    /// Send `rate` edits a second until `until`, returning how many were sent
+    pub async fn write(&mut self, workload: workload::Workload, rate: f64, until: Instant, sent: &SentEdits) -> Result<u64> {
+        let mut writer = Writer::new(self.index, self.rift_id, workload);
+        let mut ticks = tokio::time::interval(Duration::from_secs_f64(1.0 / rate));
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+        let mut edits = 0;
+        loop {
+            ticks.tick().await;
+            if Instant::now() >= until {
+                return Ok(edits);
+            }
+            let (seq, message) = writer.next_edit();
+            let frame = encode(self.encoding, &message)?;
+            sent.lock().unwrap_or_else(|e| e.into_inner()).insert((self.index, seq), Instant::now());
+            self.sink.send(frame).await
+                .map_err(|e| anyhow!("Daemon {} could not send edit {}: {}", self.index, seq, e))?;
+            edits += 1;
+        }
+    }
+
+    pub async fn close(mut self) {
+        let _ = self.sink.close().await;
+    }
+}
+
+pub struct Receiver {
+    index: usize,
+    stream: SplitStream<Socket>,
+    files: HashMap<PathBuf, String>,
+}
+
+impl Receiver {
+    /// Apply broadcasts until `until`, timing the arrival of every edit by
+    /// another daemon
+    pub async fn receive(mut self, until: Instant, sent: SentEdits) -> Result<Received> {
+        let engine = DiffEngine::new();
+        let mut received = Received::default();
+        // Edits of a writer arrive in order, so older ones were already counted
+        let mut last_seen: HashMap<usize, u64> = HashMap::new();
+
+        while let Ok(frame) = timeout_at(until, self.stream.next()).await {
+            let Some(frame) = frame else {
+                return Err(anyhow!("Server closed daemon {}'s connection", self.index));
+            };
+            let frame = frame.map_err(|e| anyhow!("Daemon {} lost its connection: {}", self.index, e))?;
+            let arrived = Instant::now();
+            received.messages += 1;
+            received.bytes += frame.len() as u64;
+
+            let changes = match decode(&frame)? {
+                Some(SyncMessage::RiftDiffUpdate { diff_changes, .. }) => {
+                    diff_changes.into_iter().map(|change| (change.path, change.diff)).collect()
+                }
+                Some(SyncMessage::FileUpdate { path, content, .. }) => vec![(path, FileDiff::FullContent(content))],
+                _ => continue,
+            };
+
+            for (path, diff) in changes {
+                if matches!(diff, FileDiff::Deleted) {
+                    self.files.remove(&path);
+                    continue;
+                }
+                let original = self.files.get(&path).map(String::as_str).unwrap_or("");
+                let Ok(content) = engine.apply_diff(original, &diff) else {
+                    received.apply_errors += 1;
+                    continue;
+                };
+
+                if let Some((writer, seq)) = workload::parse_header(&content) {
+                    let last = last_seen.entry(writer).or_default();
+                    if writer != self.index && seq > *last {
+                        *last = seq;
+                        if let Some(sent_at) = sent.lock().unwrap_or_else(|e| e.into_inner()).get(&(writer, seq)) {
+                            received.latencies.push(arrived - *sent_at);
+                        }
+                    }
+                }
+                self.files.insert(path, content);
+            }
+        }
+        Ok(received)
+    }
+}
@@ -0,0 +1,240 @@
+//! Load-testing harness for the sync server.
+//!
+//! Connects a number of simulated daemons to one rift and has some of them
+//! edit files at a steady rate. Every daemon applies the broadcasts it gets,
+//! as a real daemon would, and times how long each edit by another daemon
+//! took to reach it. With `--server-pid`, the server process's CPU and
+//! memory are sampled over the run, which must then be on the same machine.
+
+use anyhow::{anyhow, Result};
+use clap::{Parser, ValueEnum};
+use mothership_common::wire::WireEncoding;
+use mothership_common::{ProjectId, RiftId};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+mod daemon;
+mod metrics;
+mod setup;
+mod workload;
+
+use daemon::{Daemon, Received, SentEdits};
+use metrics::{LatencySummary, ServerSampler, ServerUsage};
+use setup::Server;
+use workload::{EditMode, Workload};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Encoding {
+    Json,
+    Messagepack,
+}
+
+impl From<Encoding> for WireEncoding {
+    fn from(encoding: Encoding) -> Self {
+        match encoding {
+            Encoding::Json => WireEncoding::Json,
+            Encoding::Messagepack => WireEncoding::MessagePack,
+        }
+    }
+}
+
+#[derive(Parser)]
+#[command(name = "mothership-bench")]
+#[command(about = "Load-test a Mothership sync server with simulated daemons")]
+struct Args {
+    /// Server to load
+    #[arg(long, default_value = "http://localhost:7523")]
+    server: String,
+    /// Token to connect with (a session or personal access token)
+    #[arg(long, env = "MOTHERSHIP_TOKEN", hide_env_values = true)]
+    token: String,
+    /// Rift to load (default: the token's user's rift in `--project`)
+    #[arg(long)]
+    rift: Option<RiftId>,
+    /// Project to load (default: a new project for this run)
+    #[arg(long)]
+    project: Option<ProjectId>,
+    /// Simulated daemons connected to the rift
+    #[arg(long, default_value_t = 10)]
+    daemons: usize,
+    /// How many of the daemons edit files; the rest only receive
+    #[arg(long, default_value_t = 2)]
+    writers: usize,
+    /// Edits per second sent by each writer
+    #[arg(long, default_value_t = 5.0)]
+    rate: f64,
+    /// Seconds the writers keep editing
+    #[arg(long, default_value_t = 30)]
+    duration: u64,
+    /// Seconds to keep receiving after the writers stop
+    #[arg(long, default_value_t = 5)]
+    drain: u64,
+    /// How edits are sent
+    #[arg(long, value_enum, default_value_t = EditMode::Diff)]
+    mode: EditMode,
+    /// Files each writer cycles through
+    #[arg(long, default_value_t = 10)]
+    files: usize,
+    /// Approximate size of each file in bytes
+    #[arg(long, default_value_t = 4096)]
+    file_size: usize,
+    /// Files changed per message in batch mode
+    #[arg(long, default_value_t = 5)]
+    batch_size: usize,
+    /// Wire encoding the daemons ask for (the server may fall back to JSON)
+    #[arg(long, value_enum, default_value_t = Encoding::Json)]
+    encoding: Encoding,
+    /// Process ID of a server on this machine, to sample its CPU and memory
+    #[arg(long)]
+    server_pid: Option<u32>,
+    /// Print the report as JSON, for comparing runs
+    #[arg(long)]
+    json: bool,
+}
+
+/// The outcome of a run
+#[derive(Debug, Serialize)]
+struct Report {
+    rift_id: RiftId,
+    daemons: usize,
+    writers: usize,
+    rate: f64,
+    duration_secs: u64,
+    encoding: Encoding,
+    workload: Workload,
+    edits_sent: u64,
+    /// Edits times the daemons other than their writer
+    deliveries_expected: u64,
+    deliveries_received: u64,
+    apply_errors: u64,
+    messages_received: u64,
+    bytes_received: u64,
+    latency: LatencySummary,
+    server: Option<ServerUsage>,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    if args.daemons == 0 || args.writers > args.daemons {
+        return Err(anyhow!("Need at least one daemon, and no more writers than daemons"));
+    }
+    if args.rate <= 0.0 {
+        return Err(anyhow!("--rate must be above zero"));
+    }
+
+    let server = Server::new(&args.server, args.token.clone());
+    let rift_id = match args.rift {
+        Some(rift_id) => rift_id,
+        None => {
+            let project_id = match args.project {
+                Some(project_id) => project_id,
+                None => server.create_project().await?,
+            };
+            server.beam(project_id).await?
+        }
+    };
+    let ws_url = server.websocket_url(rift_id);
+    eprintln!("🚀 Connecting {} daemons to rift {}", args.daemons, rift_id);
+
+    let mut daemons = Vec::with_capacity(args.daemons);
+    for index in 0..args.daemons {
+        daemons.push(Daemon::join(index, &ws_url, rift_id, args.encoding.into()).await?);
+    }
+
+    let sampler = args.server_pid.map(|pid| ServerSampler::start(pid, Duration::from_secs(1)));
+    let workload = Workload { mode: args.mode, files: args.files, file_size: args.file_size, batch_size: args.batch_size };
+    let writing_until = Instant::now() + Duration::from_secs(args.duration);
+    let receiving_until = writing_until + Duration::from_secs(args.drain);
+    let sent: SentEdits = Arc::new(Mutex::new(HashMap::new()));
+    eprintln!("✏️ {} writers sending {} edits/s each for {}s", args.writers, args.rate, args.duration);
+
+    let mut writers = Vec::new();
+    let mut receivers = Vec::new();
+    for daemon in daemons {
+        let index = daemon.index;
+        let (mut sender, receiver) = daemon.split();
+        receivers.push(tokio::spawn(receiver.receive(receiving_until, sent.clone())));
+
+        let sent = sent.clone();
+        let rate = args.rate;
+        let writes = index < args.writers;
+        writers.push(tokio::spawn(async move {
+            let edits = if writes { sender.write(workload, rate, writing_until, &sent).await? } else { 0 };
+            Ok::<_, anyhow::Error>((edits, sender))
+        }));
+    }
+
+    let mut edits_sent = 0;
+    let mut senders = Vec::with_capacity(writers.len());
+    for writer in writers {
+        let (edits, sender) = writer.await??;
+        edits_sent += edits;
+        senders.push(sender);
+    }
+    let mut totals = Received::default();
+    for receiver in receivers {
+        let received = receiver.await??;
+        totals.latencies.extend(received.latencies);
+        totals.messages += received.messages;
+        totals.bytes += received.bytes;
+        totals.apply_errors += received.apply_errors;
+    }
+    // Only disconnect once every daemon has stopped receiving
+    for sender in senders {
+        sender.close().await;
+    }
+    let server_usage = match sampler {
+        Some(sampler) => Some(sampler.finish().await?),
+        None => None,
+    };
+
+    let report = Report {
+        rift_id,
+        daemons: args.daemons,
+        writers: args.writers,
+        rate: args.rate,
+        duration_secs: args.duration,
+        encoding: args.encoding,
+        workload,
+        edits_sent,
+        deliveries_expected: edits_sent * (args.daemons as u64 - 1),
+        deliveries_received: totals.latencies.len() as u64,
+        apply_errors: totals.apply_errors,
+        messages_received: totals.messages,
+        bytes_received: totals.bytes,
+        latency: LatencySummary::new(totals.latencies),
+        server: server_usage,
+    };
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_report(&report);
+    }
+    Ok(())
+}
+
+fn print_report(report: &Report) {
+    println!("📊 Rift {}: {} daemons, {} writers at {}/s for {}s ({:?} edits, {:?})",
+        report.rift_id, report.daemons, report.writers, report.rate, report.duration_secs, report.workload.mode, report.encoding);
+    println!("   Edits sent:          {}", report.edits_sent);
+    println!("   Deliveries:          {} of {}", report.deliveries_received, report.deliveries_expected);
+    if report.apply_errors > 0 {
+        println!("   ⚠️ Changes that didn't apply: {}", report.apply_errors);
+    }
+    println!("   Messages received:   {} ({:.1} MB)", report.messages_received, report.bytes_received as f64 / 1_000_000.0);
+
+    let latency = &report.latency;
+    println!("   Propagation latency: p50 {:.1} ms, p95 {:.1} ms, p99 {:.1} ms, max {:.1} ms (mean {:.1} ms)",
+        latency.p50_ms, latency.p95_ms, latency.p99_ms, latency.max_ms, latency.mean_ms);
+
+    if let Some(server) = &report.server {
+        println!("   Server CPU:          mean {:.0}%, peak {:.0}% of a core", server.mean_cpu_percent, server.peak_cpu_percent);
+        println!("   Server memory:       peak {:.1} MB resident", server.peak_rss_bytes as f64 / 1_000_000.0);
+    }
+}
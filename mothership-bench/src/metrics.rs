@@ -0,0 +1,164 @@
+//! Propagation latency statistics and server resource sampling.
+
+use anyhow::Result;
+use serde::Serialize;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+/// How edits spread over the time they took to reach other daemons
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct LatencySummary {
+    pub count: usize,
+    pub min_ms: f64,
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+impl LatencySummary {
+    pub fn new(mut latencies: Vec<Duration>) -> Self {
+        if latencies.is_empty() {
+            return Self::default();
+        }
+        latencies.sort();
+        // Nearest-rank percentile
+        let percentile = |p: f64| {
+            let rank = ((p / 100.0) * latencies.len() as f64).ceil() as usize;
+            millis(latencies[rank.clamp(1, latencies.len()) - 1])
+        };
+        let total: Duration = latencies.iter().sum();
+        Self {
+            count: latencies.len(),
+            min_ms: millis(latencies[0]),
+            mean_ms: millis(total) / latencies.len() as f64,
+            p50_ms: percentile(50.0),
+            p95_ms: percentile(95.0),
+            p99_ms: percentile(99.0),
+            max_ms: millis(latencies[latencies.len() - 1]),
+        }
+    }
+}
+
+/// CPU and memory of the server process over the run
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct ServerUsage {
+    pub samples: usize,
+    /// Share of one core, so a server busy on two cores shows 200
+    pub mean_cpu_percent: f64,
+    pub peak_cpu_percent: f64,
+    pub peak_rss_bytes: u64,
+}
+
+/// Sample a local server process until told to stop
+pub struct ServerSampler {
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<Result<ServerUsage>>,
+}
+
+impl ServerSampler {
+    pub fn start(pid: u32, interval: Duration) -> Self {
+        let (stop, mut stopped) = oneshot::channel();
+        let task = tokio::spawn(async move {
+            let mut usage = ServerUsage::default();
+            let mut cpu_total = 0.0;
+            let mut last = (tokio::time::Instant::now(), proc::cpu_time(pid)?);
+            let mut ticks = tokio::time::interval(interval);
+            ticks.tick().await;
+            loop {
+                tokio::select! {
+                    _ = ticks.tick() => {}
+                    _ = &mut stopped => break,
+                }
+                let now = (tokio::time::Instant::now(), proc::cpu_time(pid)?);
+                let cpu_percent = 100.0 * (now.1 - last.1).as_secs_f64() / (now.0 - last.0).as_secs_f64();
+                last = now;
+
+                usage.samples += 1;
+                cpu_total += cpu_percent;
+                usage.peak_cpu_percent = usage.peak_cpu_percent.max(cpu_percent);
+                usage.peak_rss_bytes = usage.peak_rss_bytes.max(proc::rss_bytes(pid)?);
+            }
+            if usage.samples > 0 {
+                usage.mean_cpu_percent = cpu_total / usage.samples as f64;
+            }
+            Ok(usage)
+        });
+        Self { stop, task }
+    }
+
+    pub async fn finish(self) -> Result<ServerUsage> {
+        let _ = self.stop.send(());
+        self.task.await?
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod proc {
+    use anyhow::{anyhow, Result};
+    use std::time::Duration;
+
+    /// User and system CPU time the process used so far
+    pub fn cpu_time(pid: u32) -> Result<Duration> {
+        let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid))
+            .map_err(|e| anyhow!("Can't read the server process {}: {}", pid, e))?;
+        // The command name may contain spaces; the fields after it don't
+        let fields: Vec<&str> = stat
+            .rsplit_once(')')
+            .map(|(_, rest)| rest.split_whitespace().collect())
+            .unwrap_or_default();
+        let (Some(utime), Some(stime)) = (fields.get(11), fields.get(12)) else {
+            return Err(anyhow!("Unexpected /proc/{}/stat format", pid));
+        };
+        let ticks_per_second = unsafe { libc::sysconf(libc::_SC_CLK_TCK) }.max(1) as f64;
+        let ticks = utime.parse::<u64>()? + stime.parse::<u64>()?;
+        Ok(Duration::from_secs_f64(ticks as f64 / ticks_per_second))
+    }
+
+    /// Resident memory of the process
+    pub fn rss_bytes(pid: u32) -> Result<u64> {
+        let status = std::fs::read_to_string(format!("/proc/{}/status", pid))?;
+        let kilobytes = status
+            .lines()
+            .find_map(|line| line.strip_prefix("VmRSS:"))
+            .and_then(|value| value.split_whitespace().next())
+            .ok_or_else(|| anyhow!("No VmRSS in /proc/{}/status", pid))?;
+        Ok(kilobytes.parse::<u64>()? * 1024)
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod proc {
+    use anyhow::{anyhow, Result};
+    use std::time::Duration;
+
+    pub fn cpu_time(_pid: u32) -> Result<Duration> {
+        Err(anyhow!("Sampling the server process needs /proc, which only Linux has"))
+    }
+
+    pub fn rss_bytes(_pid: u32) -> Result<u64> {
+        Err(anyhow!("Sampling the server process needs /proc, which only Linux has"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_percentiles() {
+        let latencies = (1..=200).rev().map(Duration::from_millis).collect();
+        let summary = LatencySummary::new(latencies);
+        assert_eq!(summary.count, 200);
+        assert_eq!((summary.min_ms, summary.max_ms), (1.0, 200.0));
+        assert_eq!((summary.p50_ms, summary.p95_ms, summary.p99_ms), (100.0, 190.0, 198.0));
+        assert_eq!(summary.mean_ms, 100.5);
+        assert_eq!(LatencySummary::new(Vec::new()).count, 0);
+    }
+}
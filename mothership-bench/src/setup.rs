@@ -0,0 +1,64 @@
+//! Finding the rift to load: a given one, or a rift in a given or freshly
+//! created project.
+
+use anyhow::{anyhow, Result};
+use mothership_common::protocol::{BeamRequest, BeamResponse};
+use mothership_common::{ApiResponse, Project, ProjectId, RiftId};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+pub struct Server {
+    http: reqwest::Client,
+    url: String,
+    token: String,
+}
+
+impl Server {
+    pub fn new(url: &str, token: String) -> Self {
+        Self { http: reqwest::Client::new(), url: url.trim_end_matches('/').to_string(), token }
+    }
+
+    /// WebSocket URL a daemon syncs the rift over
+    pub fn websocket_url(&self, rift_id: RiftId) -> String {
+        let base = match self.url.split_once("://") {
+            Some(("https", rest)) => format!("wss://{}", rest),
+            Some((_, rest)) => format!("ws://{}", rest),
+            None => format!("ws://{}", self.url),
+        };
+        format!("{}/ws/{}?token={}", base, rift_id, urlencoding::encode(&self.token))
+    }
+
+    async fn post<T: DeserializeOwned>(&self, path: &str, body: &impl Serialize) -> Result<T> {
+        let response = self.http
+            .post(format!("{}{}", self.url, path))
+            .bearer_auth(&self.token)
+            .json(body)
+            .send()
+            .await?;
+        let status = response.status();
+        let body: ApiResponse<T> = response.json().await
+            .map_err(|e| anyhow!("{} answered {} with an unexpected body: {}", path, status, e))?;
+        match body.data {
+            Some(data) if body.success => Ok(data),
+            _ => Err(anyhow!("{} failed ({}): {}", path, status, body.error.or(body.message).unwrap_or_default())),
+        }
+    }
+
+    /// A new project to run against, so the run starts from an empty rift
+    pub async fn create_project(&self) -> Result<ProjectId> {
+        let name = format!("bench-{}", &uuid::Uuid::new_v4().simple().to_string()[..8]);
+        let project: Project = self.post("/gateway/create", &serde_json::json!({
+            "name": name,
+            "description": "Created by mothership-bench",
+            "project_path": format!("/tmp/{}", name),
+        })).await?;
+        Ok(project.id)
+    }
+
+    /// The signed-in user's rift in a project, created if needed
+    pub async fn beam(&self, project_id: ProjectId) -> Result<RiftId> {
+        let request = BeamRequest { project_id, rift_name: None, force_sync: false };
+        let beam: BeamResponse = self.post(&format!("/projects/{}/beam", project_id), &request).await?;
+        Ok(beam.rift_id)
+    }
+}
@@ -0,0 +1,186 @@
+//! The edits simulated daemons make.
+//!
+//! Each writing daemon owns its own files, so edits never conflict. The
+//! first line of every file names the edit that produced it, which is how a
+//! receiving daemon knows which edit it is looking at; each edit also
+//! changes one body line, like someone typing.
+
+use chrono::Utc;
+use clap::ValueEnum;
+use mothership_common::protocol::{FileDiffChange, SyncMessage};
+use mothership_common::{DiffEngine, RiftId};
+use serde::Serialize;
+use std::path::PathBuf;
+
+const HEADER_PREFIX: &str = "# mothership-bench ";
+
+/// How a writer sends its edits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EditMode {
+    /// Whole files in `FileChanged`
+    Full,
+    /// Line diffs in `FileDiffChanged`
+    Diff,
+    /// Line diffs of several files in one `BatchDiffChanges`
+    Batch,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Workload {
+    pub mode: EditMode,
+    /// Files each writer cycles through
+    pub files: usize,
+    /// Approximate size of each file in bytes
+    pub file_size: usize,
+    /// Files changed per message in batch mode
+    pub batch_size: usize,
+}
+
+/// One body line, padded so every revision is the same length
+fn body_line(line: usize, seq: u64) -> String {
+    format!("line {:>6} rev {:>10}\n", line, seq)
+}
+
+fn header(writer: usize, seq: u64) -> String {
+    format!("{}{} {}\n", HEADER_PREFIX, writer, seq)
+}
+
+/// The writer and edit sequence number named by a file's first line
+pub fn parse_header(content: &str) -> Option<(usize, u64)> {
+    let first = content.lines().next()?.strip_prefix(HEADER_PREFIX)?;
+    let (writer, seq) = first.split_once(' ')?;
+    Some((writer.parse().ok()?, seq.parse().ok()?))
+}
+
+pub fn path(writer: usize, file: usize) -> PathBuf {
+    PathBuf::from(format!("bench/daemon-{}/file-{}.txt", writer, file))
+}
+
+/// A writing daemon's files, as the server has them after its last edit
+pub struct Writer {
+    index: usize,
+    rift_id: RiftId,
+    workload: Workload,
+    engine: DiffEngine,
+    /// Lines of each file, header first
+    files: Vec<Vec<String>>,
+    /// Whether each file was sent yet
+    created: Vec<bool>,
+    next_file: usize,
+    seq: u64,
+}
+
+impl Writer {
+    pub fn new(index: usize, rift_id: RiftId, workload: Workload) -> Self {
+        let body_lines = (workload.file_size / body_line(0, 0).len()).max(1);
+        let file = std::iter::once(header(index, 0))
+            .chain((0..body_lines).map(|line| body_line(line, 0)))
+            .collect();
+        Self {
+            index,
+            rift_id,
+            workload,
+            engine: DiffEngine::new(),
+            files: vec![file; workload.files.max(1)],
+            created: vec![false; workload.files.max(1)],
+            next_file: 0,
+            seq: 0,
+        }
+    }
+
+    /// The next edit: its sequence number and the message carrying it. The
+    /// first edit of a file creates it, so its diff is the whole file.
+    pub fn next_edit(&mut self) -> (u64, SyncMessage) {
+        self.seq += 1;
+        let seq = self.seq;
+        let timestamp = Utc::now();
+
+        let edited = match self.workload.mode {
+            EditMode::Full | EditMode::Diff => 1,
+            EditMode::Batch => self.workload.batch_size.clamp(1, self.files.len()),
+        };
+        let changes: Vec<(PathBuf, String, String)> = (0..edited).map(|_| self.edit_next_file(seq)).collect();
+
+        let message = match self.workload.mode {
+            EditMode::Full => {
+                let (path, _, content) = changes.into_iter().next().expect("one file is edited");
+                SyncMessage::FileChanged { rift_id: self.rift_id, path, content, timestamp, metadata: Default::default() }
+            }
+            EditMode::Diff => {
+                let change = self.diff_change(changes.into_iter().next().expect("one file is edited"));
+                SyncMessage::FileDiffChanged {
+                    rift_id: self.rift_id,
+                    path: change.path,
+                    diff: change.diff,
+                    file_size: change.file_size,
+                    timestamp,
+                    metadata: Default::default(),
+                }
+            }
+            EditMode::Batch => SyncMessage::BatchDiffChanges {
+                rift_id: self.rift_id,
+                changes: changes.into_iter().map(|change| self.diff_change(change)).collect(),
+                timestamp,
+                compressed: false,
+            },
+        };
+        (seq, message)
+    }
+
+    /// Edit the next file in turn, returning its path and old and new content.
+    /// Files not sent yet start out empty on the server.
+    fn edit_next_file(&mut self, seq: u64) -> (PathBuf, String, String) {
+        let file = self.next_file;
+        let rounds = seq as usize / self.files.len();
+        self.next_file = (file + 1) % self.files.len();
+
+        let lines = &mut self.files[file];
+        let old = if std::mem::replace(&mut self.created[file], true) { lines.concat() } else { String::new() };
+        let line = rounds % (lines.len() - 1);
+        lines[0] = header(self.index, seq);
+        lines[1 + line] = body_line(line, seq);
+        (path(self.index, file), old, lines.concat())
+    }
+
+    fn diff_change(&self, (path, old, new): (PathBuf, String, String)) -> FileDiffChange {
+        FileDiffChange {
+            path,
+            diff: self.engine.generate_line_diff(&old, &new),
+            file_size: new.len() as u64,
+            metadata: Default::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_batched_diffs_rebuild_the_writers_files() {
+        let workload = Workload { mode: EditMode::Batch, files: 3, file_size: 200, batch_size: 2 };
+        let mut writer = Writer::new(4, RiftId::new_v4(), workload);
+        let engine = DiffEngine::new();
+        let mut files: HashMap<PathBuf, String> = HashMap::new();
+
+        for expected_seq in 1..=7 {
+            let (seq, message) = writer.next_edit();
+            assert_eq!(seq, expected_seq);
+            let SyncMessage::BatchDiffChanges { changes, .. } = message else {
+                panic!("expected a batch");
+            };
+            assert_eq!(changes.len(), 2);
+            for change in changes {
+                let old = files.get(&change.path).map(String::as_str).unwrap_or("");
+                let new = engine.apply_diff(old, &change.diff).unwrap();
+                assert_eq!(parse_header(&new), Some((4, seq)));
+                assert_eq!(new.len() as u64, change.file_size);
+                files.insert(change.path, new);
+            }
+        }
+        assert_eq!(files.len(), 3);
+        assert_eq!(parse_header("fn main() {}\n"), None);
+    }
+}
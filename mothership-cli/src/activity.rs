@@ -0,0 +1,88 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use colored::*;
+use mothership_common::{project_root, protocol::{ActivityKind, ActivityPage, ApiResponse}};
+use serde::Deserialize;
+
+use crate::{config::ConfigManager, connections, get_http_client, http::{self, ResponseExt}, print_api_error, print_info, sync::format_time_ago};
+
+/// The parts of `.mothership/project.json` the feed needs
+#[derive(Deserialize)]
+struct ProjectMetadata {
+    project_id: String,
+    project_name: String,
+}
+
+/// Show recent activity across every rift of the current project
+pub async fn handle_activity(config_manager: &ConfigManager, limit: usize, before: Option<DateTime<Utc>>) -> Result<()> {
+    let project_file = project_root::find_project_root(&std::env::current_dir()?)
+        .map(|root| root.join(".mothership").join("project.json"))
+        .ok_or_else(|| anyhow!(
+            "Not in a Mothership project directory.\n\
+            Run this command from a project directory, or use 'mothership beam <project>' to enter a project."
+        ))?;
+    let metadata: ProjectMetadata = serde_json::from_str(&std::fs::read_to_string(&project_file)?)?;
+
+    let config = config_manager.load_config()?;
+    let server_url = connections::get_active_server_url().unwrap_or(config.mothership_url.clone());
+    let client = get_http_client(&config);
+
+    let mut request = client
+        .get(format!("{}/projects/{}/activity", server_url, metadata.project_id))
+        .query(&[("limit", limit)]);
+    if let Some(before) = before {
+        request = request.query(&[("before", before.to_rfc3339_opts(SecondsFormat::Micros, true))]);
+    }
+    let response = request.send().await?.check_session()?;
+    if !response.status().is_success() {
+        return Err(http::api_error(response, "Failed to load activity").await);
+    }
+
+    let result: ApiResponse<ActivityPage> = response.json().await?;
+    let Some(page) = result.data else {
+        print_api_error(&http::describe_failure(&result));
+        return Ok(());
+    };
+
+    if page.events.is_empty() {
+        print_info("No activity yet");
+        return Ok(());
+    }
+
+    println!("\n{} {}", "📰 Activity in".cyan().bold(), metadata.project_name.blue().bold());
+    for event in &page.events {
+        let icon = match event.kind {
+            ActivityKind::Checkpoint => "●".green(),
+            ActivityKind::Merge => "◆".magenta(),
+            ActivityKind::RiftCreated => "├".blue(),
+            ActivityKind::MemberAdded => "+".green(),
+            ActivityKind::MemberRemoved => "-".red(),
+            ActivityKind::Restore => "↺".yellow(),
+        };
+        let checkpoint = event
+            .checkpoint_id
+            .map(|id| format!("{} ", &id.to_string()[..8]))
+            .unwrap_or_default();
+        println!("\n{} {}{}", icon, checkpoint.yellow().bold(), event.summary.white());
+
+        let mut details = vec![event.actor.clone().unwrap_or_else(|| "unknown".to_string()).cyan().to_string()];
+        if let Some(rift_name) = &event.rift_name {
+            details.push(format!("rift {}", rift_name.blue()));
+        }
+        details.push(format_time_ago(event.occurred_at).dimmed().to_string());
+        println!("   {}", details.join(" • "));
+    }
+
+    if let Some(next_before) = page.next_before {
+        println!(
+            "\n{}",
+            format!(
+                "Older activity: mothership activity --before {}",
+                next_before.to_rfc3339_opts(SecondsFormat::Micros, true)
+            )
+            .dimmed()
+        );
+    }
+
+    Ok(())
+}
@@ -110,7 +110,7 @@ struct LocalConfig {
 }
 
 fn read_local_config() -> Result<LocalConfig> {
-    let path = std::env::current_dir()?.join(".mothership/config.json");
+    let path = crate::project_dir()?.join(".mothership/config.json");
     if path.exists() {
         let content = std::fs::read_to_string(path)?;
         Ok(serde_json::from_str(&content)?)
@@ -122,7 +122,7 @@ fn read_local_config() -> Result<LocalConfig> {
 }
 
 fn write_local_config(config: &LocalConfig) -> Result<()> {
-    let path = std::env::current_dir()?.join(".mothership/config.json");
+    let path = crate::project_dir()?.join(".mothership/config.json");
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)?;
     }
@@ -1,7 +1,8 @@
 use anyhow::Result;
 use colored::*;
-use mothership_common::auth::{AuthRequest, AuthResponse, TokenRequest, OAuthRequest, OAuthResponse, OAuthProvider, OAuthSource};
+use mothership_common::auth::{AuthRequest, AuthResponse, TokenRequest, OAuthRequest, OAuthResponse, OAuthProvider, OAuthSource, RefreshRequest, RefreshResponse};
 use mothership_common::protocol::ApiResponse;
+use mothership_common::{profile, tls};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
@@ -20,14 +21,22 @@ use hostname;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct StoredCredentials {
     access_token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    refresh_token: Option<String>,
     user_email: Option<String>,
     user_name: Option<String>,
     stored_at: String,
 }
 
 /// Get the server URL to use for authentication
-/// Prioritizes active server connection over config file
-fn get_server_url(config_manager: &ConfigManager) -> Result<String> {
+/// Prioritizes an explicit server, then the active profile's or connection's
+/// server, over config file
+fn get_server_url(config_manager: &ConfigManager, server: Option<&str>) -> Result<String> {
+    if let Some(server_url) = server {
+        println!("{}", format!("🌐 Using server: {}", server_url).dimmed());
+        return Ok(server_url.trim_end_matches('/').to_string());
+    }
+
     // First, check if there's an active server connection
     if let Some(server_url) = connections::get_active_server_url() {
         println!("{}", format!("🌐 Using connected server: {}", server_url).dimmed());
@@ -43,19 +52,22 @@ fn get_server_url(config_manager: &ConfigManager) -> Result<String> {
 /// Handle authentication with different methods
 pub async fn handle_auth(config_manager: &ConfigManager, method: Option<crate::AuthMethod>) -> Result<()> {
     match method {
-        Some(crate::AuthMethod::Google) | None => handle_oauth_auth(config_manager, OAuthProvider::Google).await,
-        Some(crate::AuthMethod::Github) => handle_oauth_auth(config_manager, OAuthProvider::GitHub).await,
+        Some(crate::AuthMethod::Google) | None => handle_oauth_auth(config_manager, OAuthProvider::Google, None).await,
+        Some(crate::AuthMethod::Github) => handle_oauth_auth(config_manager, OAuthProvider::GitHub, None).await,
+        Some(crate::AuthMethod::Token { .. }) => Err(anyhow::anyhow!("Token management is not an authentication method")),
+        Some(crate::AuthMethod::Sessions { .. }) => Err(anyhow::anyhow!("Session management is not an authentication method")),
+        Some(crate::AuthMethod::Machines { .. }) => Err(anyhow::anyhow!("Machine management is not an authentication method")),
     }
 }
 
 /// Handle OAuth authentication with local callback server (like GUI)
-async fn handle_oauth_auth(config_manager: &ConfigManager, provider: OAuthProvider) -> Result<()> {
+async fn handle_oauth_auth(config_manager: &ConfigManager, provider: OAuthProvider, server: Option<&str>) -> Result<()> {
     let provider_name = match provider {
         OAuthProvider::Google => "Google",
         OAuthProvider::GitHub => "GitHub",
     };
 
-    let server_url = get_server_url(config_manager)?;
+    let server_url = get_server_url(config_manager, server)?;
 
     // Start OAuth flow
     let oauth_request = OAuthRequest {
@@ -68,7 +80,7 @@ async fn handle_oauth_auth(config_manager: &ConfigManager, provider: OAuthProvid
         callback_url: None,
     };
 
-    let client = reqwest::Client::new();
+    let client = tls::http_client(&server_url)?;
     let response = client
         .post(&format!("{}/auth/oauth/start", server_url))
         .json(&oauth_request)
@@ -115,7 +127,7 @@ async fn handle_oauth_auth(config_manager: &ConfigManager, provider: OAuthProvid
     println!("{}", "🔍 Validating token with server...".dimmed());
 
     // Validate the token before saving
-    let client = reqwest::Client::new();
+    let client = tls::http_client(&server_url)?;
     let response = client
         .get(&format!("{}/auth/check", server_url))
         .bearer_auth(access_token)
@@ -128,10 +140,17 @@ async fn handle_oauth_auth(config_manager: &ConfigManager, provider: OAuthProvid
 
     // Save credentials in the same format as the GUI
     save_credentials(config_manager, access_token, None, None).await?;
+    let profile_name = remember_profile(&server_url)?;
 
     println!("{}", "✅ Authentication successful!".green().bold());
     println!("{}", format!("   Logged in via {}", provider_name).dimmed());
-    println!("{}", "   Credentials saved for future use".dimmed());
+    match profile_name {
+        Some(name) => {
+            println!("{}", format!("   Credentials saved to profile '{}' ({})", name, server_url).dimmed());
+            println!("{}", format!("   Use 'mothership profile pin {}' in a project to always use it there", name).dimmed());
+        }
+        None => println!("{}", "   Credentials saved for future use".dimmed()),
+    }
 
     Ok(())
 }
@@ -148,8 +167,8 @@ pub async fn try_auto_login(config_manager: &ConfigManager) -> Result<bool> {
     let creds: StoredCredentials = serde_json::from_str(&creds_json)?;
     
     // Verify the token is still valid
-    let server_url = get_server_url(config_manager)?;
-    let client = reqwest::Client::new();
+    let server_url = get_server_url(config_manager, None)?;
+    let client = tls::http_client(&server_url)?;
     let response = client
         .post(&format!("{}/auth/verify", server_url))
         .json(&creds.access_token)
@@ -159,6 +178,42 @@ pub async fn try_auto_login(config_manager: &ConfigManager) -> Result<bool> {
     Ok(response.status().is_success())
 }
 
+/// Swap the stored refresh token for a new access token. Returns false when
+/// there is no refresh token or the server won't take it.
+pub async fn refresh_credentials(config_manager: &ConfigManager) -> Result<bool> {
+    let creds_path = config_manager.get_credentials_path()?;
+    let Ok(creds_json) = fs::read_to_string(&creds_path) else {
+        return Ok(false);
+    };
+    let mut creds: StoredCredentials = serde_json::from_str(&creds_json)?;
+    let Some(refresh_token) = creds.refresh_token.clone() else {
+        return Ok(false);
+    };
+
+    let server_url = get_server_url(config_manager, None)?;
+    let client = tls::http_client(&server_url)?;
+    let response = client
+        .post(&format!("{}/auth/refresh", server_url))
+        .json(&RefreshRequest { refresh_token })
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Ok(false);
+    }
+    let refreshed: ApiResponse<RefreshResponse> = response.json().await?;
+    let Some(refreshed) = refreshed.data else {
+        return Ok(false);
+    };
+
+    creds.access_token = refreshed.access_token;
+    if refreshed.refresh_token.is_some() {
+        creds.refresh_token = refreshed.refresh_token;
+    }
+    creds.stored_at = chrono::Utc::now().to_rfc3339();
+    fs::write(creds_path, serde_json::to_string(&creds)?)?;
+    Ok(true)
+}
+
 /// Save credentials to disk
 async fn save_credentials(
     config_manager: &ConfigManager,
@@ -168,6 +223,7 @@ async fn save_credentials(
 ) -> Result<()> {
     let creds = StoredCredentials {
         access_token: access_token.to_string(),
+        refresh_token: None,
         user_email,
         user_name,
         stored_at: chrono::Utc::now().to_rfc3339(),
@@ -185,6 +241,22 @@ async fn save_credentials(
     Ok(())
 }
 
+/// Record the server of the profile in effect, so commands using the profile
+/// talk to the server it signed in to. Returns the profile's name.
+fn remember_profile(server_url: &str) -> Result<Option<String>> {
+    let Some(name) = profile::active_profile() else {
+        return Ok(None);
+    };
+    let mut profiles = profile::Profiles::load();
+    profiles
+        .profiles
+        .entry(name.clone())
+        .and_modify(|existing| existing.server_url = server_url.to_string())
+        .or_insert_with(|| profile::Profile { server_url: server_url.to_string(), created_at: chrono::Utc::now() });
+    profiles.save()?;
+    Ok(Some(name))
+}
+
 /// Get machine information for OAuth
 fn get_machine_info() -> OAuthRequest {
     let machine_id = uuid::Uuid::new_v4().to_string();
@@ -232,9 +304,9 @@ async fn clear_stored_credentials(config_manager: &ConfigManager) -> Result<()>
 }
 
 /// Authenticate with the Mothership server
-pub async fn authenticate(config_manager: &crate::ConfigManager) -> Result<()> {
+pub async fn authenticate(config_manager: &crate::ConfigManager, server: Option<String>) -> Result<()> {
     // Use the same OAuth flow as handle_oauth_auth
-    handle_oauth_auth(config_manager, OAuthProvider::Google).await
+    handle_oauth_auth(config_manager, OAuthProvider::Google, server.as_deref()).await
 }
 
 /// Check if we have a valid auth token
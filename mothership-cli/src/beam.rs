@@ -1,14 +1,16 @@
 use anyhow::{anyhow, Result};
 use colored::*;
 use mothership_common::{
-    protocol::{ApiResponse, BeamRequest, BeamResponse, SyncMessage},
+    protocol::{ApiResponse, BeamRequest, BeamResponse, SyncMessage, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION},
     Project, ProjectId, RiftId,
-    ClientConfig,
+    ClientConfig, DaemonConfig, DiscoverySettings, SparseSpec, ThrottleSettings,
+    daemon_log, profile,
+    discovery::{DecideDiscoveryRequest, DiscoveredProject, DiscoveryDecision},
 };
 use std::path::PathBuf;
 use std::fs;
 use serde::{Serialize, Deserialize};
-use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
+use tokio_tungstenite::tungstenite::protocol::Message;
 use futures_util::{SinkExt, StreamExt};
 use walkdir::WalkDir;
 
@@ -17,7 +19,7 @@ use uuid::Uuid;
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
 
-use crate::{config::ConfigManager, get_http_client, print_api_error, print_info, print_success, connections};
+use crate::{config::ConfigManager, daemon_client::DaemonClient, get_http_client, http::{self, ResponseExt}, print_api_error, print_info, print_success, connections};
 
 /// Check if daemon is running and start it if needed
 /// Try to start daemon from a specific path
@@ -53,13 +55,11 @@ fn try_start_daemon(daemon_path: &std::path::Path) -> Result<bool> {
 }
 
 async fn ensure_daemon_running() -> Result<()> {
-    let daemon_client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(2))
-        .build()?;
+    let daemon_client = DaemonClient::new(std::time::Duration::from_secs(2));
     
     // First, check if daemon is already running
-    match daemon_client.get("http://localhost:7525/health").send().await {
-        Ok(response) if response.status().is_success() => {
+    match daemon_client.get("/health").await {
+        Ok(response) if response.is_success() => {
             // Daemon is already running
             return Ok(());
         }
@@ -126,8 +126,8 @@ async fn ensure_daemon_running() -> Result<()> {
     while attempts < 10 {
         tokio::time::sleep(std::time::Duration::from_millis(500)).await;
         
-        match daemon_client.get("http://localhost:7525/health").send().await {
-            Ok(response) if response.status().is_success() => {
+        match daemon_client.get("/health").await {
+            Ok(response) if response.is_success() => {
                 print_success("Mothership daemon started successfully!");
                 return Ok(());
             }
@@ -146,7 +146,7 @@ async fn register_project_with_daemon(
     project_name: &str,
     project_path: &PathBuf,
 ) -> Result<()> {
-    let daemon_client = reqwest::Client::new();
+    let daemon_client = DaemonClient::new(std::time::Duration::from_secs(10));
     
     #[derive(serde::Serialize)]
     struct AddProjectRequest {
@@ -161,17 +161,12 @@ async fn register_project_with_daemon(
         project_path: project_path.clone(),
     };
     
-    let response = daemon_client
-        .post("http://localhost:7525/projects/add")
-        .json(&request)
-        .send()
-        .await?;
+    let response = daemon_client.post("/projects/add", &request).await?;
     
-    if response.status().is_success() {
+    if response.is_success() {
         Ok(())
     } else {
-        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        Err(anyhow!("Daemon registration failed: {}", error_text))
+        Err(anyhow!("Daemon registration failed: {}", response.text()))
     }
 }
 
@@ -237,6 +232,9 @@ struct ProjectMetadata {
     created_at: String,
     mothership_url: String,
     rift_id: Option<String>, // CRITICAL FIX: Store rift_id for daemon WebSocket connection
+    /// Beamed in with --observe: the daemon only receives changes
+    #[serde(default)]
+    observer: bool,
 }
 
 /// Load stored authentication token for WebSocket connection
@@ -251,9 +249,15 @@ fn load_auth_token() -> Option<String> {
         stored_at: String,
     }
     
+    // Personal access token from the environment (CI) takes precedence
+    if let Ok(token) = std::env::var("MOTHERSHIP_TOKEN") {
+        if !token.trim().is_empty() {
+            return Some(token.trim().to_string());
+        }
+    }
+
     // Try to load OAuth credentials first
-    if let Some(config_dir) = dirs::config_dir() {
-        let credentials_path = config_dir.join("mothership").join("credentials.json");
+    if let Some(credentials_path) = profile::credentials_path() {
         if credentials_path.exists() {
             if let Ok(credentials_content) = std::fs::read_to_string(&credentials_path) {
                 if let Ok(credentials) = serde_json::from_str::<StoredCredentials>(&credentials_content) {
@@ -280,15 +284,21 @@ fn load_auth_token() -> Option<String> {
     None
 }
 
+/// The rift a beam downloads, and where its files and metadata go
+struct InitialSync<'a> {
+    websocket_url: &'a str,
+    rift_id: &'a RiftId,
+    project_path: &'a PathBuf,
+    project_id: &'a ProjectId,
+    project_name: &'a str,
+    mothership_url: &'a str,
+    sparse: &'a SparseSpec,
+    observer: bool,
+}
+
 /// Perform initial sync by connecting to WebSocket and requesting all files
-async fn perform_initial_sync(
-    websocket_url: &str,
-    rift_id: &RiftId,
-    project_path: &PathBuf,
-    project_id: &ProjectId,
-    project_name: &str,
-    mothership_url: &str,
-) -> Result<()> {
+async fn perform_initial_sync(sync: InitialSync<'_>) -> Result<()> {
+    let InitialSync { websocket_url, rift_id, project_path, project_id, project_name, mothership_url, sparse, observer } = sync;
     print_info("Connecting to sync server...");
     
     // AUTHENTICATION FIX: Add auth token to WebSocket URL
@@ -302,7 +312,7 @@ async fn perform_initial_sync(
     };
     
     // Connect to WebSocket with authentication
-    let (ws_stream, _) = connect_async(&authenticated_url).await
+    let ws_stream = crate::connect_websocket(&authenticated_url).await
         .map_err(|e| anyhow!("Failed to connect to WebSocket: {}", e))?;
     
     let (mut ws_sender, mut ws_receiver) = ws_stream.split();
@@ -311,6 +321,13 @@ async fn perform_initial_sync(
     let join_rift = SyncMessage::JoinRift {
         rift_id: *rift_id,
         last_checkpoint: None, // Request all files from beginning
+        protocol_version: PROTOCOL_VERSION,
+        min_protocol_version: MIN_PROTOCOL_VERSION,
+        encodings: vec![],
+        resume: None,
+        sparse_paths: sparse.paths.clone(),
+        manifest: None,
+        observer,
     };
     
     let join_json = serde_json::to_string(&join_rift)?;
@@ -343,7 +360,7 @@ async fn perform_initial_sync(
                             }
                             
                             // Create .mothership metadata
-                            create_project_metadata(project_path, project_id, project_name, mothership_url, Some(rift_id))?;
+                            create_project_metadata(project_path, project_id, project_name, mothership_url, Some(rift_id), observer)?;
                             
                             print_success("Project files synchronized successfully!");
                             
@@ -373,7 +390,7 @@ async fn perform_initial_sync(
                             }
                             
                             // Create .mothership metadata
-                            create_project_metadata(project_path, project_id, project_name, mothership_url, Some(rift_id))?;
+                            create_project_metadata(project_path, project_id, project_name, mothership_url, Some(rift_id), observer)?;
                             
                             print_success("Project files synchronized successfully!");
                             
@@ -416,6 +433,7 @@ fn create_project_metadata(
     project_name: &str,
     mothership_url: &str,
     rift_id: Option<&uuid::Uuid>,
+    observer: bool,
 ) -> Result<()> {
     let mothership_dir = project_path.join(".mothership");
     
@@ -431,6 +449,7 @@ fn create_project_metadata(
         created_at: chrono::Utc::now().to_rfc3339(),
         mothership_url: mothership_url.to_string(),
         rift_id: rift_id.map(|id| id.to_string()), // CRITICAL FIX: Store rift_id
+        observer,
     };
     
     let metadata_file = mothership_dir.join("project.json");
@@ -444,7 +463,7 @@ fn create_project_metadata(
 
 /// Check if the current directory is a Mothership project and return its metadata
 fn get_current_project_metadata() -> Option<ProjectMetadata> {
-    let current_dir = std::env::current_dir().ok()?;
+    let current_dir = crate::project_dir().ok()?;
     let mothership_dir = current_dir.join(".mothership");
     
     if mothership_dir.exists() && mothership_dir.is_dir() {
@@ -466,13 +485,15 @@ pub async fn handle_beam(
     project: String,
     rift: Option<String>,
     local_dir: Option<std::path::PathBuf>,
+    paths: Vec<String>,
+    observe: bool,
     force_sync: bool,
 ) -> Result<()> {
     // If no project specified (empty string), try to detect from current directory
     let (project_name, project_path) = if project.is_empty() {
         if let Some(metadata) = get_current_project_metadata() {
             print_info("📍 Detected Mothership project in current directory");
-            (metadata.project_name, std::env::current_dir()?)
+            (metadata.project_name, crate::project_dir()?)
         } else {
             return Err(anyhow!("No project specified and not in a Mothership project directory.\nPlease specify a project name or run this command from a project directory."));
         }
@@ -542,10 +563,11 @@ pub async fn handle_beam(
             local_workspace: PathBuf::from("."),
             auth_token: active_server.auth_token.clone(),
             user_id: Some(Uuid::new_v4()),
+            ..ClientConfig::default()
         };
         
         let lookup_url = format!("{}/projects/{}", active_server.url, uuid);
-        let response = get_http_client(&client_config).get(&lookup_url).send().await?;
+        let response = get_http_client(&client_config).get(&lookup_url).send().await?.check_session()?;
         
         if !response.status().is_success() {
             return Err(anyhow!("Project ID {} not found", uuid));
@@ -578,10 +600,11 @@ pub async fn handle_beam(
             local_workspace: PathBuf::from("."),
             auth_token: active_server.auth_token.clone(),
             user_id: Some(Uuid::new_v4()),
+            ..ClientConfig::default()
         };
         
         let lookup_url = format!("{}/projects?name={}", active_server.url, project_name);
-        let response = get_http_client(&client_config).get(&lookup_url).send().await?;
+        let response = get_http_client(&client_config).get(&lookup_url).send().await?.check_session()?;
         
         if !response.status().is_success() {
             return Err(anyhow!("Project '{}' not found. Use 'mothership gateway list' to see available projects.", project_name));
@@ -613,16 +636,18 @@ pub async fn handle_beam(
         local_workspace: PathBuf::from("."),
         auth_token: active_server.auth_token.clone(),
         user_id: Some(Uuid::new_v4()),
+        ..ClientConfig::default()
     };
     
     let response = get_http_client(&client_config)
         .post(&beam_url)
         .json(&beam_request)
         .send()
-        .await?;
+        .await?
+        .check_session()?;
 
     if !response.status().is_success() {
-        return Err(anyhow!("Beam request failed: {}", response.status()));
+        return Err(http::api_error(response, "Beam request failed").await);
     }
 
     let beam_response: ApiResponse<BeamResponse> = response.json().await?;
@@ -639,21 +664,38 @@ pub async fn handle_beam(
     tokio::fs::create_dir_all(&project_path).await?;
 
     // Create project metadata regardless of sync requirements (using active server URL)
-    create_project_metadata(&project_path, &project_id, &project_name, &active_server.url, Some(&beam_data.rift_id))?;
+    create_project_metadata(&project_path, &project_id, &project_name, &active_server.url, Some(&beam_data.rift_id), observe)?;
+    if observe {
+        print_info("👁️ Observing: changes in this rift sync to you, but yours won't be sent");
+    }
+
+    // SPARSE BEAM: --path replaces the saved spec; without it the previous one is kept
+    let sparse = if paths.is_empty() {
+        SparseSpec::load(&project_path)
+    } else {
+        SparseSpec::new(&paths)
+    };
+    sparse.save(&project_path)?;
+    if !sparse.is_full() {
+        let scope: Vec<String> = sparse.paths.iter().map(|p| p.display().to_string()).collect();
+        print_info(&format!("🔭 Sparse beam: only syncing {}", scope.join(", ")));
+    }
     
     // CRITICAL FIX: Perform initial sync if required (download all files)
     if beam_data.initial_sync_required {
         print_info("Performing initial file download...");
         
         // Perform initial sync by downloading all project files
-        if let Err(e) = perform_initial_sync(
-            &beam_data.websocket_url,
-            &beam_data.rift_id,
-            &project_path,
-            &project_id,
-            &project_name,
-            &active_server.url,
-        ).await {
+        if let Err(e) = perform_initial_sync(InitialSync {
+            websocket_url: &beam_data.websocket_url,
+            rift_id: &beam_data.rift_id,
+            project_path: &project_path,
+            project_id: &project_id,
+            project_name: &project_name,
+            mothership_url: &active_server.url,
+            sparse: &sparse,
+            observer: observe,
+        }).await {
             print_api_error(&format!("Failed to download project files: {}", e));
             print_info("Project structure created, but files may be missing");
             print_info("Try running 'mothership sync' in the project directory");
@@ -696,12 +738,10 @@ pub async fn handle_disconnect(
     project: Option<String>,
 ) -> Result<()> {
     // Check if daemon is running
-    let daemon_client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(5))
-        .build()?;
+    let daemon_client = DaemonClient::new(std::time::Duration::from_secs(5));
     
-    match daemon_client.get("http://localhost:7525/health").send().await {
-        Ok(response) if response.status().is_success() => {
+    match daemon_client.get("/health").await {
+        Ok(response) if response.is_success() => {
             // Daemon is running
         }
         _ => {
@@ -715,7 +755,7 @@ pub async fn handle_disconnect(
         name
     } else {
         // Try to determine current project from directory
-        let current_dir = std::env::current_dir()?;
+        let current_dir = crate::project_dir()?;
         let mothership_dir = current_dir.join(".mothership");
         
         if mothership_dir.exists() {
@@ -748,7 +788,7 @@ pub async fn handle_disconnect(
     
     // Look up project by name to get ID
     let lookup_url = format!("{}/projects/name/{}", active_server.url, urlencoding::encode(&project_name));
-    let response = client.get(&lookup_url).send().await?;
+    let response = client.get(&lookup_url).send().await?.check_session()?;
     
     if !response.status().is_success() {
         return Err(anyhow!("Project '{}' not found on server.", project_name));
@@ -760,37 +800,33 @@ pub async fn handle_disconnect(
     })?;
     
     // Remove from daemon
-    let remove_url = format!("http://localhost:7525/projects/{}/remove", project_data.id);
-    let response = daemon_client.post(&remove_url).send().await?;
+    let remove_url = format!("/projects/{}/remove", project_data.id);
+    let response = daemon_client.post(&remove_url, &()).await?;
     
-    if response.status().is_success() {
+    if response.is_success() {
         print_success(&format!("Successfully disconnected from project '{}'", project_name));
         print_info("The project is no longer being tracked by the background daemon");
         print_info("Files will not sync automatically until you beam back in");
     } else {
-        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(anyhow!("Failed to disconnect from daemon: {}", error_text));
+        return Err(anyhow!("Failed to disconnect from daemon: {}", response.text()));
     }
     
     Ok(())
 }
 
 /// Handle daemon status command
-pub async fn handle_daemon_status() -> Result<()> {
-    let daemon_client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(5))
-        .build()?;
+pub async fn handle_daemon_status(verbose: bool) -> Result<()> {
+    let daemon_client = DaemonClient::new(std::time::Duration::from_secs(5));
     
-    match daemon_client.get("http://localhost:7525/health").send().await {
-        Ok(response) if response.status().is_success() => {
+    match daemon_client.get("/health").await {
+        Ok(response) if response.is_success() => {
             print_success("Mothership daemon is running");
             
             // Get detailed status
-            match daemon_client.get("http://localhost:7525/status").send().await {
-                Ok(status_response) if status_response.status().is_success() => {
-                    let status_text = status_response.text().await?;
+            match daemon_client.get("/status").await {
+                Ok(status_response) if status_response.is_success() => {
                     print_info("Daemon Status:");
-                    println!("{}", status_text);
+                    println!("{}", status_response.text());
                 }
                 _ => {
                     print_info("Could not get detailed daemon status");
@@ -798,16 +834,27 @@ pub async fn handle_daemon_status() -> Result<()> {
             }
             
             // List tracked projects
-            match daemon_client.get("http://localhost:7525/projects").send().await {
-                Ok(projects_response) if projects_response.status().is_success() => {
-                    let projects_text = projects_response.text().await?;
+            match daemon_client.get("/projects").await {
+                Ok(projects_response) if projects_response.is_success() => {
                     print_info("Tracked Projects:");
-                    println!("{}", projects_text);
+                    println!("{}", projects_response.text());
                 }
                 _ => {
                     print_info("Could not get tracked projects list");
                 }
             }
+
+            if verbose {
+                match daemon_client.get("/metrics").await {
+                    Ok(metrics_response) if metrics_response.is_success() => {
+                        let metrics: serde_json::Value = metrics_response.json()?;
+                        print_daemon_metrics(metrics.get("data").unwrap_or(&serde_json::Value::Null));
+                    }
+                    _ => {
+                        print_info("Could not get daemon metrics");
+                    }
+                }
+            }
         }
         _ => {
             print_api_error("Mothership daemon is not running");
@@ -818,19 +865,150 @@ pub async fn handle_daemon_status() -> Result<()> {
     Ok(())
 }
 
+/// Print each project's queue and connection health, and the daemon's recent errors
+fn print_daemon_metrics(metrics: &serde_json::Value) {
+    let field = |value: &serde_json::Value, name: &str| value.get(name).and_then(|v| v.as_str()).unwrap_or("never").to_string();
+    let count = |value: &serde_json::Value, name: &str| value.get(name).and_then(|v| v.as_u64()).unwrap_or(0);
+
+    print_info("Project Metrics:");
+    let projects = metrics.get("projects").and_then(|p| p.as_array()).cloned().unwrap_or_default();
+    if projects.is_empty() {
+        println!("  {}", "No projects tracked".dimmed());
+    }
+    for project in &projects {
+        println!("  {} {}", "•".cyan(), field(project, "project_name").bold());
+        println!("    {} {}", "Queue Depth:".dimmed(), count(project, "queue_depth"));
+        let Some(connection) = project.get("connection").filter(|c| !c.is_null()) else {
+            println!("    {} {}", "Connection:".dimmed(), "not started".yellow());
+            continue;
+        };
+        let state = if connection.get("connected").and_then(|v| v.as_bool()).unwrap_or(false) {
+            format!("connected since {}", field(connection, "connected_since")).green()
+        } else {
+            "disconnected".red()
+        };
+        println!("    {} {}", "Connection:".dimmed(), state);
+        println!("    {} {}", "Last Upload:".dimmed(), field(connection, "last_upload"));
+        println!("    {} {}", "Last Download:".dimmed(), field(connection, "last_download"));
+        println!(
+            "    {} {} sent, {} received, {} errors in a row, {} resets",
+            "Messages:".dimmed(),
+            count(connection, "messages_sent"),
+            count(connection, "messages_received"),
+            count(connection, "consecutive_errors"),
+            count(connection, "connection_resets"),
+        );
+    }
+
+    let errors = metrics.get("recent_errors").and_then(|e| e.as_array()).cloned().unwrap_or_default();
+    if !errors.is_empty() {
+        print_info("Recent Errors:");
+        for error in errors.iter().rev().take(10) {
+            let line = format!("{} {} {}", field(error, "at"), field(error, "level"), field(error, "message"));
+            if field(error, "level") == "ERROR" {
+                println!("  {}", line.red());
+            } else {
+                println!("  {}", line.yellow());
+            }
+        }
+    }
+}
+
+/// Print the daemon's logs, from `since` ago or the last `lines` lines, and
+/// with `follow` keep printing lines as they are written
+pub async fn handle_daemon_logs(follow: bool, since: Option<String>, lines: usize) -> Result<()> {
+    let log_dir = daemon_log::log_dir().ok_or_else(|| anyhow!("Could not determine the config directory"))?;
+    let since = since.map(|since| daemon_log::parse_duration(&since)).transpose()?;
+
+    let files = daemon_log::log_files();
+    if files.is_empty() && !follow {
+        print_info(&format!("No daemon logs yet in {}", log_dir.display()));
+        return Ok(());
+    }
+
+    let mut shown = std::collections::VecDeque::new();
+    for file in &files {
+        let content = fs::read_to_string(file).unwrap_or_default();
+        match since {
+            Some(since) => shown.extend(daemon_log::lines_since(&content, chrono::Utc::now() - since).into_iter().map(str::to_string)),
+            None => {
+                for line in content.lines() {
+                    if shown.len() == lines {
+                        shown.pop_front();
+                    }
+                    shown.push_back(line.to_string());
+                }
+            }
+        }
+    }
+    for line in &shown {
+        print_log_line(line);
+    }
+
+    if follow {
+        follow_daemon_log(files.last().cloned()).await?;
+    }
+    Ok(())
+}
+
+/// Print lines appended to the newest log file until interrupted, moving on to
+/// the next file when the log rotates
+async fn follow_daemon_log(mut current: Option<PathBuf>) -> Result<()> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut offset = current.as_ref().and_then(|file| fs::metadata(file).ok()).map_or(0, |meta| meta.len());
+    let mut partial = String::new();
+    loop {
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+
+        let newest = daemon_log::log_files().pop();
+        if newest != current {
+            current = newest;
+            offset = 0;
+            partial.clear();
+        }
+        let Some(file) = &current else {
+            continue;
+        };
+        let Ok(mut log) = fs::File::open(file) else {
+            continue;
+        };
+        if log.metadata().map_or(0, |meta| meta.len()) < offset {
+            offset = 0;
+        }
+        log.seek(SeekFrom::Start(offset))?;
+        let mut appended = Vec::new();
+        offset += log.read_to_end(&mut appended)? as u64;
+
+        partial.push_str(&String::from_utf8_lossy(&appended));
+        while let Some(end) = partial.find('\n') {
+            print_log_line(&partial[..end]);
+            partial.drain(..=end);
+        }
+    }
+}
+
+fn print_log_line(line: &str) {
+    if line.contains(" ERROR ") {
+        println!("{}", line.red());
+    } else if line.contains(" WARN ") {
+        println!("{}", line.yellow());
+    } else {
+        println!("{}", line);
+    }
+}
+
 /// Handle daemon stop command
 pub async fn handle_daemon_stop() -> Result<()> {
-    let daemon_client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(5))
-        .build()?;
+    let daemon_client = DaemonClient::new(std::time::Duration::from_secs(5));
     
-    match daemon_client.get("http://localhost:7525/health").send().await {
-        Ok(response) if response.status().is_success() => {
+    match daemon_client.get("/health").await {
+        Ok(response) if response.is_success() => {
             // Daemon is running, try to stop it
             print_info("Sending shutdown signal to daemon...");
             
             // Send shutdown request (we'll need to implement this endpoint)
-            match daemon_client.post("http://localhost:7525/shutdown").send().await {
+            match daemon_client.post("/shutdown", &()).await {
                 Ok(_) => {
                     print_success("Daemon shutdown signal sent");
                     print_info("All background file tracking has stopped");
@@ -866,4 +1044,198 @@ pub async fn handle_daemon_restart() -> Result<()> {
     print_info("Use 'mothership beam <project>' to re-register projects");
     
     Ok(())
-} 
\ No newline at end of file
+}
+
+/// Changes to the daemon's resource limits from `daemon config`
+#[derive(Debug, Default)]
+pub struct ThrottleChanges {
+    pub max_hash_jobs: Option<usize>,
+    /// Megabytes per second; 0 removes the limit
+    pub io_limit_mb: Option<u64>,
+    pub low_priority_on_battery: Option<bool>,
+}
+
+impl ThrottleChanges {
+    fn is_empty(&self) -> bool {
+        self.max_hash_jobs.is_none() && self.io_limit_mb.is_none() && self.low_priority_on_battery.is_none()
+    }
+
+    fn apply(self, throttle: &mut ThrottleSettings) {
+        if let Some(jobs) = self.max_hash_jobs {
+            throttle.max_hash_jobs = jobs;
+        }
+        if let Some(mb) = self.io_limit_mb {
+            throttle.io_bytes_per_sec = mb * 1024 * 1024;
+        }
+        if let Some(low_priority) = self.low_priority_on_battery {
+            throttle.low_priority_on_battery = low_priority;
+        }
+    }
+}
+
+/// Changes to where the daemon looks for projects from `daemon config`
+#[derive(Debug, Default)]
+pub struct DiscoveryChanges {
+    pub add_roots: Vec<PathBuf>,
+    pub remove_roots: Vec<PathBuf>,
+    pub max_depth: Option<usize>,
+}
+
+impl DiscoveryChanges {
+    fn is_empty(&self) -> bool {
+        self.add_roots.is_empty() && self.remove_roots.is_empty() && self.max_depth.is_none()
+    }
+
+    fn apply(self, discovery: &mut DiscoverySettings) -> Result<()> {
+        for root in self.remove_roots {
+            let root = root.canonicalize().unwrap_or(root);
+            discovery.scan_roots.retain(|existing| *existing != root);
+        }
+        for root in self.add_roots {
+            let root = root.canonicalize()
+                .map_err(|e| anyhow!("Can't scan {}: {}", root.display(), e))?;
+            if !discovery.scan_roots.contains(&root) {
+                discovery.scan_roots.push(root);
+            }
+        }
+        if let Some(depth) = self.max_depth {
+            discovery.max_depth = depth;
+        }
+        Ok(())
+    }
+}
+
+/// Show or change the daemon's resource limits and scan roots. A running
+/// daemon applies changes right away; otherwise they are saved for its next
+/// start.
+pub async fn handle_daemon_config(changes: ThrottleChanges, discovery: DiscoveryChanges) -> Result<()> {
+    let daemon_client = DaemonClient::new(std::time::Duration::from_secs(5));
+    let running = match daemon_client.get("/config").await {
+        Ok(response) if response.is_success() => response.json::<ApiResponse<DaemonConfig>>()?.data,
+        _ => None,
+    };
+    let mut config = running.clone().unwrap_or_else(DaemonConfig::load);
+
+    let changed = !changes.is_empty() || !discovery.is_empty();
+    if changed {
+        changes.apply(&mut config.throttle);
+        discovery.apply(&mut config.discovery)?;
+        config.validate()?;
+        if running.is_some() {
+            let response = daemon_client.post("/config", &config).await?;
+            let result: ApiResponse<DaemonConfig> = response.json()?;
+            if let Some(error) = result.error {
+                return Err(anyhow!("Daemon rejected the config: {}", error));
+            }
+            print_success("Daemon settings updated");
+        } else {
+            config.save()?;
+            print_success("Daemon settings saved; they apply when the daemon next starts");
+        }
+    } else {
+        print_info("Daemon settings:");
+    }
+
+    let throttle = &config.throttle;
+    println!("  {} Projects hashed at once: {}", "•".cyan(), throttle.max_hash_jobs);
+    match throttle.io_bytes_per_sec {
+        0 => println!("  {} Disk reads: unlimited", "•".cyan()),
+        rate => println!("  {} Disk reads: at most {:.1} MB/s", "•".cyan(), rate as f64 / (1024.0 * 1024.0)),
+    }
+    println!("  {} Low priority on battery: {}", "•".cyan(), if throttle.low_priority_on_battery { "on" } else { "off" });
+    let discovery = &config.discovery;
+    if discovery.scan_roots.is_empty() {
+        println!("  {} Scan roots: none, projects are only tracked when you beam into them", "•".cyan());
+    } else {
+        println!("  {} Scan roots, searched {} directories deep:", "•".cyan(), discovery.max_depth);
+        for root in &discovery.scan_roots {
+            println!("      {}", root.display());
+        }
+    }
+    if let Some(path) = DaemonConfig::path() {
+        println!("  {}", format!("Saved in {}", path.display()).dimmed());
+    }
+
+    Ok(())
+}
+
+/// List the projects the daemon found under its scan roots and choose which
+/// of them it tracks. Each choice is remembered, so a project is only asked
+/// about once.
+pub async fn handle_daemon_discover(scan: bool, track: Option<PathBuf>, ignore: Option<PathBuf>) -> Result<()> {
+    ensure_daemon_running().await?;
+    let daemon_client = DaemonClient::new(std::time::Duration::from_secs(60));
+
+    let choice = track.map(|path| (path, DiscoveryDecision::Track))
+        .or_else(|| ignore.map(|path| (path, DiscoveryDecision::Ignore)));
+    if let Some((path, decision)) = choice {
+        let project_path = path.canonicalize().map_err(|e| anyhow!("Can't read {}: {}", path.display(), e))?;
+        return decide_discovered(&daemon_client, project_path, decision).await;
+    }
+
+    let response = if scan {
+        print_info("Searching the scan roots...");
+        daemon_client.post("/discovery/scan", &serde_json::json!({})).await?
+    } else {
+        daemon_client.get("/discovery").await?
+    };
+    let result: ApiResponse<Vec<DiscoveredProject>> = response.json()?;
+    if let Some(error) = result.error {
+        return Err(anyhow!("Daemon failed to list discovered projects: {}", error));
+    }
+    let projects = result.data.unwrap_or_default();
+
+    if projects.is_empty() {
+        if DaemonConfig::load().discovery.scan_roots.is_empty() {
+            print_info("No scan roots are set; add one with 'mothership daemon config --scan-root <dir>'");
+        } else {
+            print_info("No projects found under the scan roots");
+        }
+        return Ok(());
+    }
+
+    print_info(&format!("Projects under the scan roots ({}):", projects.len()));
+    for project in &projects {
+        let state = match project.decision {
+            Some(DiscoveryDecision::Track) => "tracked".green(),
+            Some(DiscoveryDecision::Ignore) => "not tracked".dimmed(),
+            None => "new".yellow(),
+        };
+        println!("  {} {} {} [{}]", "•".cyan(), project.project_name.bold(), project.project_path.display().to_string().dimmed(), state);
+    }
+
+    for project in projects.into_iter().filter(|project| project.decision.is_none()) {
+        print!(
+            "{}",
+            format!("Track '{}' at {}? (y = track, n = never, Enter = ask later): ", project.project_name, project.project_path.display())
+                .white()
+                .bold()
+        );
+        std::io::Write::flush(&mut std::io::stdout())?;
+
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input)?;
+        let decision = match input.trim().to_lowercase().as_str() {
+            "y" | "yes" => DiscoveryDecision::Track,
+            "n" | "no" | "never" => DiscoveryDecision::Ignore,
+            _ => continue,
+        };
+        decide_discovered(&daemon_client, project.project_path, decision).await?;
+    }
+
+    Ok(())
+}
+
+async fn decide_discovered(daemon_client: &DaemonClient, project_path: PathBuf, decision: DiscoveryDecision) -> Result<()> {
+    let request = DecideDiscoveryRequest { project_path, decision };
+    let response = daemon_client.post("/discovery/decide", &request).await?;
+    let result: ApiResponse<String> = response.json()?;
+    match (result.data, result.error) {
+        (_, Some(error)) => Err(anyhow!("{}", error)),
+        (Some(message), None) => {
+            print_success(&message);
+            Ok(())
+        }
+        (None, None) => Ok(()),
+    }
+}
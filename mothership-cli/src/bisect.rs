@@ -0,0 +1,341 @@
+//! `mothership bisect`: binary-search the current rift's checkpoints for the
+//! one that introduced a regression.
+//!
+//! Bisecting restores the working directory to one candidate checkpoint at a
+//! time; the developer tests it and marks it `good` or `bad` until only the
+//! first bad checkpoint is left. The session is kept in `.mothership/bisect.json`
+//! and `bisect reset` puts the files back to where they started. Works against
+//! the server's history or, in local-only mode, the local one.
+
+use anyhow::{anyhow, Result};
+use colored::*;
+use mothership_common::{metadata::write_file, protocol::ApiResponse, Checkpoint, CheckpointHistoryEntry, CheckpointId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{config::ConfigManager, connections, daemon_client::DaemonClient, get_http_client, http::{self, ResponseExt}, local, print_info, print_success};
+
+/// File in `.mothership` holding the bisect session
+const BISECT_FILE: &str = "bisect.json";
+
+/// Checkpoints fetched per history request
+const HISTORY_PAGE: usize = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BisectCheckpoint {
+    id: CheckpointId,
+    message: Option<String>,
+}
+
+/// A bisect session in progress
+#[derive(Debug, Serialize, Deserialize)]
+struct BisectState {
+    /// The rift's checkpoints, oldest first
+    checkpoints: Vec<BisectCheckpoint>,
+    /// Checkpoint the working directory was at before bisecting
+    original: CheckpointId,
+    /// Checkpoint currently restored in the working directory
+    current: CheckpointId,
+    good: Option<CheckpointId>,
+    bad: Option<CheckpointId>,
+}
+
+/// The parts of `.mothership/project.json` bisecting needs
+#[derive(Deserialize)]
+struct ProjectMetadata {
+    project_id: String,
+}
+
+/// Files returned by the checkpoint restore endpoint
+#[derive(Deserialize)]
+struct RestoreData {
+    files: HashMap<PathBuf, String>,
+}
+
+/// Where checkpoints come from: the server, or the local history in local-only mode
+enum Source {
+    Server {
+        client: reqwest::Client,
+        server_url: String,
+        project_id: String,
+    },
+    Local,
+}
+
+impl Source {
+    fn new(config_manager: &ConfigManager, project_dir: &Path) -> Result<Self> {
+        if connections::is_local_only() {
+            return Ok(Source::Local);
+        }
+
+        let config = config_manager.load_config()?;
+        let metadata: ProjectMetadata = serde_json::from_str(&fs::read_to_string(project_file(project_dir))?)?;
+        Ok(Source::Server {
+            client: get_http_client(&config),
+            server_url: connections::get_active_server_url().unwrap_or(config.mothership_url.clone()),
+            project_id: metadata.project_id,
+        })
+    }
+
+    /// The current rift's checkpoints, oldest first
+    async fn history(&self, project_dir: &Path) -> Result<Vec<Checkpoint>> {
+        let (client, server_url, project_id) = match self {
+            Source::Local => return local::rift_history(project_dir),
+            Source::Server { client, server_url, project_id } => (client, server_url, project_id),
+        };
+
+        // The history endpoint pages newest first; keep going below the oldest one seen
+        let mut checkpoints: Vec<Checkpoint> = Vec::new();
+        loop {
+            let mut url = format!("{}/projects/{}/history?depth={}", server_url, project_id, HISTORY_PAGE);
+            if let Some(oldest) = checkpoints.last() {
+                url.push_str(&format!("&before={}", oldest.id));
+            }
+
+            let response = client.get(&url).send().await?.check_session()?;
+            if !response.status().is_success() {
+                return Err(http::api_error(response, "Failed to load history").await);
+            }
+            let page: ApiResponse<Vec<CheckpointHistoryEntry>> = response.json().await?;
+            let page = page.data.unwrap_or_default();
+            let done = page.len() < HISTORY_PAGE;
+            checkpoints.extend(page.into_iter().map(|entry| entry.checkpoint));
+            if done {
+                break;
+            }
+        }
+
+        checkpoints.reverse();
+        Ok(checkpoints)
+    }
+
+    /// Content of every file in a checkpoint
+    async fn files(&self, project_dir: &Path, checkpoint: CheckpointId) -> Result<HashMap<PathBuf, String>> {
+        let (client, server_url, project_id) = match self {
+            Source::Local => return local::checkpoint_contents(project_dir, checkpoint),
+            Source::Server { client, server_url, project_id } => (client, server_url, project_id),
+        };
+
+        let url = format!("{}/projects/{}/checkpoints/{}/restore", server_url, project_id, checkpoint);
+        // Only reads the files: no restore is recorded and no safety checkpoint made
+        let response = client.post(&url).query(&[("dry_run", true)]).send().await?.check_session()?;
+        if !response.status().is_success() {
+            return Err(http::api_error(response, "Failed to restore checkpoint").await);
+        }
+
+        let restore: ApiResponse<RestoreData> = response.json().await?;
+        restore.data
+            .map(|data| data.files)
+            .ok_or_else(|| anyhow!(restore.error.unwrap_or_else(|| "Unknown error".to_string())))
+    }
+}
+
+fn project_file(project_dir: &Path) -> PathBuf {
+    project_dir.join(".mothership").join("project.json")
+}
+
+fn state_file(project_dir: &Path) -> PathBuf {
+    project_dir.join(".mothership").join(BISECT_FILE)
+}
+
+fn load_state(project_dir: &Path) -> Result<BisectState> {
+    let path = state_file(project_dir);
+    if !path.exists() {
+        return Err(anyhow!("Not bisecting. Start with 'mothership bisect start'"));
+    }
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+fn save_state(project_dir: &Path, state: &BisectState) -> Result<()> {
+    fs::write(state_file(project_dir), serde_json::to_string_pretty(state)?)?;
+    Ok(())
+}
+
+fn current_project_dir() -> Result<PathBuf> {
+    let project_dir = crate::project_dir()?;
+    if !project_file(&project_dir).exists() {
+        return Err(anyhow!(
+            "Not in a Mothership project directory.\n\
+            Run this command from a project directory, or use 'mothership beam <project>' to enter a project."
+        ));
+    }
+    Ok(project_dir)
+}
+
+fn short_id(id: CheckpointId) -> String {
+    id.to_string()[..8].to_string()
+}
+
+impl BisectState {
+    fn position(&self, id: CheckpointId) -> Option<usize> {
+        self.checkpoints.iter().position(|c| c.id == id)
+    }
+
+    /// Find a checkpoint of the session by ID or unambiguous ID prefix
+    fn find(&self, id: &str) -> Result<CheckpointId> {
+        let mut matches = self.checkpoints.iter().filter(|c| c.id.to_string().starts_with(id));
+        match (matches.next(), matches.next()) {
+            (Some(checkpoint), None) if !id.is_empty() => Ok(checkpoint.id),
+            (Some(_), Some(_)) => Err(anyhow!("Checkpoint ID '{}' is ambiguous; use more characters", id)),
+            _ => Err(anyhow!("Checkpoint '{}' is not in this rift's history", id)),
+        }
+    }
+}
+
+/// Replace the working files with a checkpoint's, removing files it doesn't have
+async fn checkout(source: &Source, project_dir: &Path, checkpoint: CheckpointId) -> Result<()> {
+    let files = source.files(project_dir, checkpoint).await?;
+
+    for path in local::scan_working_files(project_dir)?.keys() {
+        if !files.contains_key(path) {
+            let _ = fs::remove_file(project_dir.join(path));
+        }
+    }
+    for (path, content) in &files {
+        write_file(&project_dir.join(path), content, None)?;
+    }
+    Ok(())
+}
+
+/// Whether the daemon is syncing this directory, in which case restored
+/// files would be pushed to the rift for everyone
+async fn tracked_by_daemon(project_dir: &Path) -> bool {
+    let Ok(response) = DaemonClient::new(std::time::Duration::from_secs(5)).get("/projects").await else {
+        return false;
+    };
+    let projects: serde_json::Value = response.json().unwrap_or_default();
+    projects["data"].as_array().is_some_and(|projects| {
+        projects.iter().any(|p| p["project_path"].as_str().map(Path::new) == Some(project_dir))
+    })
+}
+
+/// Restore the next checkpoint to test, or report the first bad one once
+/// good and bad are adjacent
+async fn step(source: &Source, project_dir: &Path, state: &mut BisectState) -> Result<()> {
+    let (Some(good), Some(bad)) = (state.good, state.bad) else {
+        let missing = if state.good.is_none() { "good" } else { "bad" };
+        print_info(&format!("Mark a {} checkpoint with 'mothership bisect {} <checkpoint-id>'", missing, missing));
+        return save_state(project_dir, state);
+    };
+    let good_index = state.position(good).ok_or_else(|| anyhow!("Bisect state is corrupted"))?;
+    let bad_index = state.position(bad).ok_or_else(|| anyhow!("Bisect state is corrupted"))?;
+
+    if good_index >= bad_index {
+        return Err(anyhow!(
+            "The good checkpoint {} is not older than the bad checkpoint {}",
+            short_id(good), short_id(bad)
+        ));
+    }
+
+    if bad_index - good_index == 1 {
+        let first_bad = &state.checkpoints[bad_index];
+        save_state(project_dir, state)?;
+        print_success(&format!(
+            "{} is the first bad checkpoint: {}",
+            short_id(first_bad.id),
+            first_bad.message.as_deref().unwrap_or("(no message)")
+        ));
+        println!("{}", format!("💡 Inspect it with 'mothership log' or undo it with 'mothership revert {}'", first_bad.id).dimmed());
+        println!("{}", "💡 Run 'mothership bisect reset' to go back to where you started".dimmed());
+        return Ok(());
+    }
+
+    let candidate = state.checkpoints[(good_index + bad_index) / 2].clone();
+    checkout(source, project_dir, candidate.id).await?;
+    state.current = candidate.id;
+    save_state(project_dir, state)?;
+
+    let remaining = bad_index - good_index - 1;
+    println!("{} {} {}",
+        "Testing".cyan().bold(),
+        short_id(candidate.id).yellow().bold(),
+        candidate.message.as_deref().unwrap_or("(no message)").white()
+    );
+    print_info(&format!(
+        "{} checkpoint{} left to test (about {} step{})",
+        remaining,
+        if remaining == 1 { "" } else { "s" },
+        steps_left(remaining),
+        if steps_left(remaining) == 1 { "" } else { "s" }
+    ));
+    Ok(())
+}
+
+/// Steps needed to narrow `remaining` candidates down to one
+fn steps_left(remaining: usize) -> u32 {
+    (remaining + 1).next_power_of_two().trailing_zeros()
+}
+
+/// Start bisecting the current rift's history. `bad` defaults to the latest checkpoint.
+pub async fn handle_start(config_manager: &ConfigManager, bad: Option<String>, good: Option<String>) -> Result<()> {
+    let project_dir = current_project_dir()?;
+    if state_file(&project_dir).exists() {
+        return Err(anyhow!("Already bisecting. Run 'mothership bisect reset' first to start over"));
+    }
+    if !connections::is_local_only() && tracked_by_daemon(&project_dir).await {
+        return Err(anyhow!(
+            "The daemon is syncing this project, so every checkpoint tested would be synced to the rift.\n\
+            Stop syncing with 'mothership disconnect' and beam back in when you're done."
+        ));
+    }
+    if crate::stash::has_local_changes(config_manager).await? {
+        return Err(anyhow!("You have changes since the last checkpoint; checkpoint or stash them first"));
+    }
+
+    let source = Source::new(config_manager, &project_dir)?;
+    let history = source.history(&project_dir).await?;
+    let Some(latest) = history.last().map(|c| c.id) else {
+        return Err(anyhow!("This rift has no checkpoints to bisect"));
+    };
+
+    let mut state = BisectState {
+        checkpoints: history.into_iter().map(|c| BisectCheckpoint { id: c.id, message: c.message }).collect(),
+        original: latest,
+        current: latest,
+        good: None,
+        bad: Some(latest),
+    };
+    if let Some(bad) = bad {
+        state.bad = Some(state.find(&bad)?);
+    }
+    if let Some(good) = good {
+        state.good = Some(state.find(&good)?);
+    }
+
+    print_info(&format!("Bisecting {} checkpoints", state.checkpoints.len()));
+    step(&source, &project_dir, &mut state).await
+}
+
+/// Mark a checkpoint (the one being tested by default) as good or bad and move on
+pub async fn handle_mark(config_manager: &ConfigManager, checkpoint_id: Option<String>, good: bool) -> Result<()> {
+    let project_dir = current_project_dir()?;
+    let mut state = load_state(&project_dir)?;
+    let checkpoint = match checkpoint_id {
+        Some(id) => state.find(&id)?,
+        None => state.current,
+    };
+
+    if good {
+        state.good = Some(checkpoint);
+    } else {
+        state.bad = Some(checkpoint);
+    }
+
+    let source = Source::new(config_manager, &project_dir)?;
+    step(&source, &project_dir, &mut state).await
+}
+
+/// End the session and restore the checkpoint bisecting started from
+pub async fn handle_reset(config_manager: &ConfigManager) -> Result<()> {
+    let project_dir = current_project_dir()?;
+    let state = load_state(&project_dir)?;
+
+    let source = Source::new(config_manager, &project_dir)?;
+    checkout(&source, &project_dir, state.original).await?;
+    fs::remove_file(state_file(&project_dir))?;
+
+    print_success(&format!("Bisect finished; files are back at checkpoint {}", short_id(state.original)));
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use mothership_common::{
+    bundle::BUNDLE_EXTENSION,
+    protocol::{ApiResponse, ProjectImportResult},
+    Project,
+};
+use std::path::PathBuf;
+
+use crate::{config::ConfigManager, connections, get_http_client, http::{self, ResponseExt}, print_api_error, print_info, print_success};
+
+/// Get the server URL to use for bundle operations
+/// Prioritizes active server connection over config file
+fn get_server_url(config_manager: &ConfigManager) -> Result<String> {
+    if let Some(server_url) = connections::get_active_server_url() {
+        return Ok(server_url);
+    }
+
+    let config = config_manager.load_config()?;
+    Ok(config.mothership_url)
+}
+
+/// Download a project as a portable bundle file
+pub async fn handle_export(config_manager: &ConfigManager, project_name: String, output: Option<PathBuf>) -> Result<()> {
+    let server_url = get_server_url(config_manager)?;
+    let config = config_manager.load_config()?;
+    let client = get_http_client(&config);
+
+    let project_url = format!("{}/projects/name/{}", server_url, urlencoding::encode(&project_name));
+    let response = client.get(&project_url).send().await?.check_session()?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        print_api_error(&format!("Project '{}' not found", project_name));
+        return Ok(());
+    }
+    if !response.status().is_success() {
+        return Err(http::api_error(response, "Failed to find project").await);
+    }
+
+    let project_response: ApiResponse<Project> = response.json().await?;
+    let project = project_response.data.ok_or_else(|| anyhow!("No project data received"))?;
+
+    print_info(&format!("Exporting '{}' with its full history...", project.name));
+    let response = client
+        .get(format!("{}/projects/{}/export", server_url, project.id))
+        .send()
+        .await?
+        .check_session()?;
+    if !response.status().is_success() {
+        return Err(http::api_error(response, "Failed to export project").await);
+    }
+    let bytes = response.bytes().await?;
+
+    let output = output.unwrap_or_else(|| PathBuf::from(format!("{}.{}", project.name, BUNDLE_EXTENSION)));
+    tokio::fs::write(&output, &bytes).await?;
+
+    print_success(&format!("Exported '{}' to {} ({} KB)", project.name, output.display(), bytes.len() / 1024));
+    println!("{}", format!("Import it elsewhere with: mothership import {}", output.display()).dimmed());
+
+    Ok(())
+}
+
+/// Create a new project on the server from a bundle file
+pub async fn handle_import(config_manager: &ConfigManager, file: PathBuf, name: Option<String>) -> Result<()> {
+    let bytes = tokio::fs::read(&file).await
+        .map_err(|e| anyhow!("Failed to read {}: {}", file.display(), e))?;
+
+    let server_url = get_server_url(config_manager)?;
+    let config = config_manager.load_config()?;
+    let client = get_http_client(&config);
+
+    let mut request = client
+        .post(format!("{}/projects/import", server_url))
+        .header(reqwest::header::CONTENT_TYPE, "application/gzip")
+        .body(bytes);
+    if let Some(name) = &name {
+        request = request.query(&[("name", name)]);
+    }
+
+    let response = request.send().await?.check_session()?;
+    if response.status() == reqwest::StatusCode::PAYLOAD_TOO_LARGE {
+        print_api_error("Bundle is larger than the server accepts");
+        return Ok(());
+    }
+    if !response.status().is_success() {
+        return Err(http::api_error(response, "Failed to import project").await);
+    }
+
+    let result: ApiResponse<ProjectImportResult> = response.json().await?;
+    let Some(imported) = result.data else {
+        print_api_error(&http::describe_failure(&result));
+        return Ok(());
+    };
+
+    print_success(&format!(
+        "Imported '{}' ({} rifts, {} checkpoints)",
+        imported.name, imported.rifts, imported.checkpoints
+    ));
+    println!("{}", format!("Beam into it with: mothership beam \"{}\" --local-dir <path>", imported.name).dimmed());
+
+    Ok(())
+}
@@ -0,0 +1,153 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use futures_util::StreamExt;
+use mothership_common::protocol::{ApiResponse, ChatMessage, SendChatRequest, SyncMessage};
+use serde::Deserialize;
+use tokio_tungstenite::tungstenite::Message;
+use uuid::Uuid;
+
+use crate::{config::ConfigManager, get_http_client, http::{self, ResponseExt}, print_api_error, print_info, connections};
+
+/// Local project metadata stored in .mothership directory
+#[derive(Deserialize)]
+struct ProjectMetadata {
+    project_id: String,
+    project_name: String,
+    rift_id: Option<String>,
+}
+
+/// Get the server URL to use for chat operations
+/// Prioritizes active server connection over config file
+fn get_server_url(config_manager: &ConfigManager) -> Result<String> {
+    if let Some(server_url) = connections::get_active_server_url() {
+        return Ok(server_url);
+    }
+
+    let config = config_manager.load_config()?;
+    Ok(config.mothership_url)
+}
+
+/// Find the project and rift of the current directory
+fn find_current_rift() -> Result<(Uuid, Uuid, String)> {
+    let project_file = crate::project_dir()?.join(".mothership").join("project.json");
+    if !project_file.exists() {
+        return Err(anyhow!(
+            "Not in a Mothership project directory.\n\
+            Run this command from a project directory, or use 'mothership beam <project>' to enter a project."
+        ));
+    }
+
+    let metadata: ProjectMetadata = serde_json::from_str(&std::fs::read_to_string(&project_file)?)?;
+    let rift_id = metadata.rift_id
+        .ok_or_else(|| anyhow!("No rift for this project yet. Run 'mothership beam' to join one."))?;
+
+    Ok((Uuid::parse_str(&metadata.project_id)?, Uuid::parse_str(&rift_id)?, metadata.project_name))
+}
+
+fn print_chat_message(message: &ChatMessage) {
+    println!(
+        "{} {} {}",
+        message.timestamp.with_timezone(&chrono::Local).format("%H:%M").to_string().dimmed(),
+        format!("{}:", message.username).cyan().bold(),
+        message.content
+    );
+}
+
+/// Send a message to the current rift, or show its recent history
+pub async fn handle_chat(
+    config_manager: &ConfigManager,
+    message: Option<String>,
+    limit: usize,
+    follow: bool,
+) -> Result<()> {
+    let (project_id, rift_id, project_name) = find_current_rift()?;
+    let config = config_manager.load_config()?;
+    let server_url = get_server_url(config_manager)?;
+    let client = get_http_client(&config);
+    let chat_url = format!("{}/projects/{}/rifts/{}/chat", server_url, project_id, rift_id);
+
+    if let Some(content) = message {
+        let response = client
+            .post(&chat_url)
+            .json(&SendChatRequest { content })
+            .send()
+            .await?
+            .check_session()?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            print_api_error("Chat is turned off for this project");
+            return Ok(());
+        }
+        if !response.status().is_success() {
+            return Err(http::api_error(response, "Chat request failed").await);
+        }
+
+        let result: ApiResponse<ChatMessage> = response.json().await?;
+        match result.data {
+            Some(message) => print_chat_message(&message),
+            None => print_api_error(&result.error.unwrap_or_else(|| "Unknown error".to_string())),
+        }
+    } else {
+        let response = client
+            .get(&chat_url)
+            .query(&[("limit", limit)])
+            .send()
+            .await?
+            .check_session()?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            print_api_error("Chat is turned off for this project");
+            return Ok(());
+        }
+        if !response.status().is_success() {
+            return Err(http::api_error(response, "Chat request failed").await);
+        }
+
+        let result: ApiResponse<Vec<ChatMessage>> = response.json().await?;
+        let messages = result.data.unwrap_or_default();
+
+        println!("\n{} {}", "💬 Rift chat for".cyan().bold(), project_name.blue().bold());
+        if messages.is_empty() {
+            print_info("No messages yet. Say hello with 'mothership chat \"message\"'.");
+        }
+        for message in &messages {
+            print_chat_message(message);
+        }
+    }
+
+    if follow {
+        follow_chat(&server_url, rift_id).await?;
+    }
+
+    Ok(())
+}
+
+/// Print chat messages from the rift's sync channel as they arrive
+async fn follow_chat(server_url: &str, rift_id: Uuid) -> Result<()> {
+    let token = crate::get_oauth_token()
+        .ok_or_else(|| anyhow!("Not authenticated. Run 'mothership auth' to get started."))?;
+
+    let ws_base = if let Some(rest) = server_url.strip_prefix("https://") {
+        format!("wss://{}", rest)
+    } else if let Some(rest) = server_url.strip_prefix("http://") {
+        format!("ws://{}", rest)
+    } else {
+        format!("wss://{}", server_url)
+    };
+    let ws_url = format!("{}/ws/{}?token={}", ws_base, rift_id, urlencoding::encode(&token));
+
+    let mut socket = crate::connect_websocket(&ws_url).await
+        .map_err(|e| anyhow!("Failed to connect to rift: {}", e))?;
+    print_info("Watching for new messages (Ctrl+C to stop)...");
+
+    while let Some(msg) = socket.next().await {
+        if let Message::Text(text) = msg? {
+            if let Ok(SyncMessage::ChatMessage { message, .. }) = serde_json::from_str(&text) {
+                print_chat_message(&message);
+            }
+        }
+    }
+
+    print_info("Disconnected from rift");
+    Ok(())
+}
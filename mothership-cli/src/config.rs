@@ -1,5 +1,5 @@
 use anyhow::{anyhow, Result};
-use mothership_common::ClientConfig;
+use mothership_common::{profile, ClientConfig};
 use std::fs;
 use std::path::PathBuf;
 
@@ -48,11 +48,13 @@ impl ConfigManager {
 
     /// Check if user is authenticated (check both old config and new credentials format)
     pub fn is_authenticated(&self) -> Result<bool> {
+        // CI and scripts authenticate with a personal access token from the environment
+        if std::env::var("MOTHERSHIP_TOKEN").is_ok_and(|t| !t.trim().is_empty()) {
+            return Ok(true);
+        }
+
         // First check new credentials format
-        let credentials_path = dirs::config_dir()
-            .ok_or_else(|| anyhow::anyhow!("Could not find config directory"))?
-            .join("mothership")
-            .join("credentials.json");
+        let credentials_path = self.get_credentials_path()?;
             
         if credentials_path.exists() {
             return Ok(true);
@@ -173,12 +175,8 @@ impl ConfigManager {
         Ok(creds.access_token)
     }
 
-    /// Get path to credentials file
+    /// Get path to the credentials file of the active profile
     pub fn get_credentials_path(&self) -> Result<PathBuf> {
-        let config_dir = dirs::config_dir()
-            .ok_or_else(|| anyhow!("Could not find config directory"))?
-            .join("mothership");
-            
-        Ok(config_dir.join("credentials.json"))
+        profile::credentials_path().ok_or_else(|| anyhow!("Could not find config directory"))
     }
 } 
\ No newline at end of file
@@ -4,6 +4,11 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::fs;
+use mothership_common::features::Feature;
+use mothership_common::protocol::{
+    negotiate_protocol_version, protocol_mismatch_message, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION,
+};
+use mothership_common::{profile, tls, TlsSettings};
 
 use crate::{config::ConfigManager, print_api_error, print_info, print_success};
 
@@ -16,6 +21,9 @@ pub struct ServerConnection {
     pub auth_method: String,
     pub connected_at: chrono::DateTime<chrono::Utc>,
     pub capabilities: Option<ServerCapabilities>,
+    /// Custom CA, client certificate and verification options for this server
+    #[serde(default, skip_serializing_if = "TlsSettings::is_default")]
+    pub tls: TlsSettings,
 }
 
 /// Server capabilities response
@@ -25,8 +33,28 @@ pub struct ServerCapabilities {
     pub sso_domain: Option<String>,
     pub oauth_providers: Vec<String>,
     pub features: Vec<String>,
+    /// Features the server has turned off (empty on servers without feature flags)
+    #[serde(default)]
+    pub disabled_features: Vec<Feature>,
     pub name: String,
     pub version: String,
+    /// Newest sync protocol version the server speaks (absent on servers that predate versioning)
+    #[serde(default)]
+    pub protocol_version: Option<u32>,
+    /// Oldest sync protocol version the server still accepts
+    #[serde(default)]
+    pub min_protocol_version: Option<u32>,
+}
+
+impl ServerCapabilities {
+    /// The sync protocol version this CLI would use with the server, or an
+    /// upgrade message if they share none
+    pub fn negotiate_protocol(&self) -> Result<u32, String> {
+        let max = self.protocol_version.unwrap_or(1);
+        let min = self.min_protocol_version.unwrap_or(1);
+        negotiate_protocol_version(min, max)
+            .ok_or_else(|| protocol_mismatch_message(MIN_PROTOCOL_VERSION, PROTOCOL_VERSION, min, max))
+    }
 }
 
 /// Connections configuration
@@ -34,6 +62,10 @@ pub struct ServerCapabilities {
 pub struct ConnectionsConfig {
     pub active_server: Option<String>,
     pub servers: HashMap<String, ServerConnection>,
+    /// Set by 'mothership server disconnect': checkpoints and rifts are kept
+    /// in the project's .mothership directory until pushed
+    #[serde(default)]
+    pub local_only: bool,
 }
 
 impl Default for ConnectionsConfig {
@@ -41,6 +73,7 @@ impl Default for ConnectionsConfig {
         Self {
             active_server: None,
             servers: HashMap::new(),
+            local_only: false,
         }
     }
 }
@@ -79,9 +112,25 @@ pub fn save_connections_config(config: &ConnectionsConfig) -> Result<()> {
     Ok(())
 }
 
-/// Get the currently active server connection
+/// Get the currently active server connection: the active profile's server,
+/// otherwise the one chosen with 'mothership connect'
 pub fn get_active_server() -> Result<Option<ServerConnection>> {
     let config = load_connections_config()?;
+
+    if let Some(name) = profile::active_profile() {
+        if let Some(server_url) = profile::server_url_for(&name) {
+            let known = config.servers.values().find(|server| server.url.trim_end_matches('/') == server_url.trim_end_matches('/'));
+            return Ok(Some(known.cloned().unwrap_or_else(|| ServerConnection {
+                name: format!("{} (profile)", name),
+                url: server_url,
+                auth_token: None,
+                auth_method: "oauth".to_string(),
+                connected_at: chrono::Utc::now(),
+                capabilities: None,
+                tls: TlsSettings::default(),
+            })));
+        }
+    }
     
     if let Some(active_url) = config.active_server {
         if let Some(server) = config.servers.get(&active_url) {
@@ -99,7 +148,7 @@ const MOTHERSHIP_DEFAULT_PORT: u16 = 7523;
 const MOTHERSHIP_FALLBACK_PORTS: &[u16] = &[443, 80, 8080];
 
 /// Smart server discovery with automatic port detection
-async fn discover_server_with_ports(server_input: &str) -> Result<(String, ServerCapabilities)> {
+async fn discover_server_with_ports(server_input: &str, tls: &TlsSettings) -> Result<(String, ServerCapabilities)> {
     // If input already has a specific port, try it with both protocols first
     if server_input.contains(":") && !server_input.contains("://") {
         // Extract host and port from "hostname:port"
@@ -113,7 +162,7 @@ async fn discover_server_with_ports(server_input: &str) -> Result<(String, Serve
                 let url = format!("{}://{}:{}", protocol, host, port);
                 print_info(&format!("Trying {}...", url));
                 
-                if let Ok(capabilities) = discover_server_capabilities(&url).await {
+                if let Ok(capabilities) = discover_server_capabilities(&url, tls).await {
                     print_success(&format!("Found Mothership server at {}!", url));
                     return Ok((url, capabilities));
                 }
@@ -124,7 +173,7 @@ async fn discover_server_with_ports(server_input: &str) -> Result<(String, Serve
     // If input already has protocol and port, try it directly
     if server_input.contains("://") {
         print_info(&format!("Trying {}...", server_input));
-        if let Ok(capabilities) = discover_server_capabilities(server_input).await {
+        if let Ok(capabilities) = discover_server_capabilities(server_input, tls).await {
             print_success(&format!("Found Mothership server at {}!", server_input));
             return Ok((server_input.to_string(), capabilities));
         }
@@ -143,7 +192,7 @@ async fn discover_server_with_ports(server_input: &str) -> Result<(String, Serve
         let url = format!("{}://{}:{}", protocol, base_host, MOTHERSHIP_DEFAULT_PORT);
         print_info(&format!("Trying {}...", url));
         
-        if let Ok(capabilities) = discover_server_capabilities(&url).await {
+        if let Ok(capabilities) = discover_server_capabilities(&url, tls).await {
             print_success(&format!("Found Mothership server at {}!", url));
             return Ok((url, capabilities));
         }
@@ -155,7 +204,7 @@ async fn discover_server_with_ports(server_input: &str) -> Result<(String, Serve
             let url = format!("{}://{}:{}", protocol, base_host, port);
             print_info(&format!("Trying {}...", url));
             
-            if let Ok(capabilities) = discover_server_capabilities(&url).await {
+            if let Ok(capabilities) = discover_server_capabilities(&url, tls).await {
                 print_success(&format!("Found Mothership server at {}!", url));
                 return Ok((url, capabilities));
             }
@@ -169,7 +218,7 @@ async fn discover_server_with_ports(server_input: &str) -> Result<(String, Serve
         format!("https://{}", server_input)
     };
     
-    match discover_server_capabilities(&fallback_url).await {
+    match discover_server_capabilities(&fallback_url, tls).await {
         Ok(capabilities) => Ok((fallback_url, capabilities)),
         Err(_) => {
             let mut all_ports = vec![MOTHERSHIP_DEFAULT_PORT.to_string()];
@@ -183,8 +232,8 @@ async fn discover_server_with_ports(server_input: &str) -> Result<(String, Serve
 }
 
 /// Discover server capabilities
-async fn discover_server_capabilities(server_url: &str) -> Result<ServerCapabilities> {
-    let client = reqwest::Client::builder()
+async fn discover_server_capabilities(server_url: &str, tls: &TlsSettings) -> Result<ServerCapabilities> {
+    let client = tls.client_builder()?
         .timeout(std::time::Duration::from_secs(3))
         .build()?;
     
@@ -235,11 +284,16 @@ async fn authenticate_with_server(_server_url: &str, capabilities: &ServerCapabi
 }
 
 /// Handle connect to server command
-pub async fn handle_connect(_config_manager: &ConfigManager, server_url: String) -> Result<()> {
+pub async fn handle_connect(_config_manager: &ConfigManager, server_url: String, tls: TlsSettings) -> Result<()> {
     print_info(&format!("Discovering Mothership server at {}...", server_url));
+    if tls.insecure {
+        print_api_error("⚠️ Certificate verification is disabled for this server (--insecure)");
+    }
+    // Catch unreadable certificate files before trying every port
+    tls.connector()?;
     
     // Try to discover server with smart port detection
-    let (final_url, capabilities) = match discover_server_with_ports(&server_url).await {
+    let (final_url, capabilities) = match discover_server_with_ports(&server_url, &tls).await {
         Ok((url, caps)) => (url, caps),
         Err(e) => {
             print_api_error(&format!("Failed to connect to server: {}", e));
@@ -251,6 +305,11 @@ pub async fn handle_connect(_config_manager: &ConfigManager, server_url: String)
     
     print_success(&format!("Connected to {} ({})", capabilities.name, capabilities.version));
     print_info(&format!("Supported authentication: {}", capabilities.auth_methods.join(", ")));
+
+    if let Err(message) = capabilities.negotiate_protocol() {
+        print_api_error(&format!("Incompatible server: {}", message));
+        return Ok(());
+    }
     
     // Authenticate with server
     let auth_token = match authenticate_with_server(&final_url, &capabilities).await {
@@ -269,12 +328,14 @@ pub async fn handle_connect(_config_manager: &ConfigManager, server_url: String)
         auth_method: "oauth".to_string(), // TODO: Use actual method
         connected_at: chrono::Utc::now(),
         capabilities: Some(capabilities),
+        tls,
     };
     
     // Save connection
     let mut config = load_connections_config()?;
     config.servers.insert(final_url.clone(), connection);
     config.active_server = Some(final_url.clone());
+    config.local_only = false;
     save_connections_config(&config)?;
     
     print_success(&format!("Successfully connected to {}!", final_url));
@@ -291,12 +352,12 @@ pub async fn handle_connect(_config_manager: &ConfigManager, server_url: String)
 async fn offer_to_sync_existing_projects() -> Result<()> {
     // TODO: Scan for .mothership directories and offer to sync them
     // For now, just show info message
-    print_info("💡 Tip: Run 'mothership init' in existing project directories to sync them to the server");
+    print_info("💡 Tip: Run 'mothership push' in projects you worked on offline to upload their local checkpoints");
     Ok(())
 }
 
 /// Handle server status command
-pub async fn handle_server_status(_config_manager: &ConfigManager) -> Result<()> {
+pub async fn handle_server_status(config_manager: &ConfigManager) -> Result<()> {
     let config = load_connections_config()?;
     
     match config.active_server {
@@ -309,12 +370,29 @@ pub async fn handle_server_status(_config_manager: &ConfigManager) -> Result<()>
                 if let Some(capabilities) = &server.capabilities {
                     print_info(&format!("Server version: {}", capabilities.version));
                     print_info(&format!("Features: {}", capabilities.features.join(", ")));
+                    if !capabilities.disabled_features.is_empty() {
+                        let disabled: Vec<&str> = capabilities.disabled_features.iter().map(Feature::as_str).collect();
+                        print_info(&format!("Turned off: {}", disabled.join(", ")));
+                    }
+                    match capabilities.negotiate_protocol() {
+                        Ok(version) => print_info(&format!("Sync protocol: v{}", version)),
+                        Err(message) => print_api_error(&format!("Incompatible server: {}", message)),
+                    }
+                }
+                if !server.tls.is_default() {
+                    print_info(&format!("TLS: {}", describe_tls(&server.tls)));
                 }
                 
                 // Test connection
                 print_info("Testing connection...");
                 match test_server_connection(&server.url).await {
-                    Ok(()) => print_success("✅ Server is reachable"),
+                    Ok(()) => {
+                        print_success("✅ Server is reachable");
+                        if let Ok(Some(machines)) = crate::sessions::fetch_machines(config_manager).await {
+                            print_info(&format!("Your machines ({}):", machines.len()));
+                            crate::sessions::print_machines(&machines);
+                        }
+                    }
                     Err(e) => {
                         print_api_error(&format!("❌ Server connection failed: {}", e));
                         print_info("Use 'mothership server disconnect' if the server is no longer available");
@@ -336,7 +414,7 @@ pub async fn handle_server_status(_config_manager: &ConfigManager) -> Result<()>
 
 /// Test connection to a server
 async fn test_server_connection(server_url: &str) -> Result<()> {
-    let client = reqwest::Client::builder()
+    let client = tls::settings_for(server_url).client_builder()?
         .timeout(std::time::Duration::from_secs(5))
         .build()?;
     
@@ -353,6 +431,9 @@ async fn test_server_connection(server_url: &str) -> Result<()> {
 pub async fn handle_server_disconnect(_config_manager: &ConfigManager) -> Result<()> {
     let mut config = load_connections_config()?;
     
+    let already_local = config.local_only;
+    config.local_only = true;
+
     match config.active_server.take() {
         Some(server_url) => {
             if let Some(server) = config.servers.get(&server_url) {
@@ -364,12 +445,16 @@ pub async fn handle_server_disconnect(_config_manager: &ConfigManager) -> Result
             save_connections_config(&config)?;
             
             print_info("Switched to local-only mode");
-            print_info("All future operations will be stored locally");
-            print_info("Existing projects remain available locally");
+            print_info("Checkpoints and rifts will be stored in each project's .mothership directory");
+            print_info("Use 'mothership push' after reconnecting to upload them");
+        }
+        None if already_local => {
+            print_info("Already operating in local-only mode");
         }
         None => {
+            save_connections_config(&config)?;
             print_info("Not currently connected to any server");
-            print_info("Already operating in local-only mode");
+            print_info("Switched to local-only mode");
         }
     }
     
@@ -404,6 +489,9 @@ pub async fn handle_server_list(_config_manager: &ConfigManager) -> Result<()> {
         if let Some(capabilities) = &server.capabilities {
             println!("   Version: {}", capabilities.version);
         }
+        if !server.tls.is_default() {
+            println!("   TLS: {}", describe_tls(&server.tls));
+        }
         
         if is_active {
             println!("   {} Currently active", "🟢".green());
@@ -416,6 +504,55 @@ pub async fn handle_server_list(_config_manager: &ConfigManager) -> Result<()> {
     Ok(())
 }
 
+/// One-line summary of a server's custom TLS settings
+fn describe_tls(tls: &TlsSettings) -> String {
+    let mut parts = Vec::new();
+    if let Some(ca_bundle) = &tls.ca_bundle {
+        parts.push(format!("trusts CAs in {}", ca_bundle.display()));
+    }
+    if let Some(client_cert) = &tls.client_cert {
+        parts.push(format!("client certificate {}", client_cert.display()));
+    }
+    if tls.insecure {
+        parts.push("certificate verification disabled".to_string());
+    }
+    parts.join(", ")
+}
+
+/// Check if the CLI was switched to local-only mode
+pub fn is_local_only() -> bool {
+    load_connections_config().map_or(false, |config| config.local_only)
+}
+
+/// Features the active server had turned off when its capabilities were
+/// last fetched, without contacting it
+pub fn cached_disabled_features() -> Vec<Feature> {
+    get_active_server()
+        .ok()
+        .flatten()
+        .and_then(|server| server.capabilities)
+        .map(|capabilities| capabilities.disabled_features)
+        .unwrap_or_default()
+}
+
+/// Whether the active server has turned a feature off. Refreshes the cached
+/// capabilities when the server answers, otherwise goes by the cache.
+pub async fn feature_disabled(feature: Feature) -> bool {
+    let Ok(mut config) = load_connections_config() else { return false };
+    let Some(server) = get_active_server().ok().flatten() else { return false };
+
+    if let Ok(capabilities) = discover_server_capabilities(&server.url, &server.tls).await {
+        let disabled = capabilities.disabled_features.contains(&feature);
+        if let Some(known) = config.servers.get_mut(&server.url) {
+            known.capabilities = Some(capabilities);
+            let _ = save_connections_config(&config);
+        }
+        return disabled;
+    }
+
+    server.capabilities.map_or(false, |capabilities| capabilities.disabled_features.contains(&feature))
+}
+
 /// Check if we're connected to a server
 pub fn is_connected_to_server() -> bool {
     get_active_server().unwrap_or(None).is_some()
@@ -0,0 +1,150 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use mothership_common::{
+    project_root,
+    protocol::{ApiResponse, FileBlame, FileRevision},
+    ChangeType,
+};
+use serde::Deserialize;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+use crate::{config::ConfigManager, get_http_client, http::{self, ResponseExt}, print_api_error, print_info, connections, project_relative_path, sync::format_time_ago};
+
+/// Local project metadata stored in .mothership directory
+#[derive(Deserialize)]
+struct ProjectMetadata {
+    project_id: String,
+}
+
+/// Get the server URL to use for file history operations
+/// Prioritizes active server connection over config file
+fn get_server_url(config_manager: &ConfigManager) -> Result<String> {
+    if let Some(server_url) = connections::get_active_server_url() {
+        return Ok(server_url);
+    }
+
+    let config = config_manager.load_config()?;
+    Ok(config.mothership_url)
+}
+
+/// Find the project containing the current directory and the project root
+fn find_project_root() -> Result<(Uuid, PathBuf)> {
+    let current_dir = std::env::current_dir()?;
+
+    if let Some(root) = project_root::find_project_root(&current_dir) {
+        let project_file = root.join(".mothership").join("project.json");
+        let metadata: ProjectMetadata = serde_json::from_str(&std::fs::read_to_string(&project_file)?)?;
+        return Ok((Uuid::parse_str(&metadata.project_id)?, root));
+    }
+
+    Err(anyhow!(
+        "Not in a Mothership project directory.\n\
+        Run this command from a project directory, or use 'mothership beam <project>' to enter a project."
+    ))
+}
+
+/// Build the URL of a file endpoint, encoding each path segment
+fn file_url(server_url: &str, project_id: Uuid, path: &str, endpoint: &str) -> String {
+    let encoded: Vec<String> = path.split('/').map(|s| urlencoding::encode(s).into_owned()).collect();
+    format!("{}/projects/{}/files/{}/{}", server_url, project_id, encoded.join("/"), endpoint)
+}
+
+/// Show the checkpoints that changed a file
+pub async fn handle_log(config_manager: &ConfigManager, file: PathBuf, limit: usize) -> Result<()> {
+    let (project_id, root) = find_project_root()?;
+    let path = project_relative_path(&root, &file)?;
+    let config = config_manager.load_config()?;
+    let client = get_http_client(&config);
+
+    let url = file_url(&get_server_url(config_manager)?, project_id, &path, "history");
+    let response = client.get(&url).query(&[("limit", limit)]).send().await?.check_session()?;
+    if !response.status().is_success() {
+        return Err(http::api_error(response, "Failed to load file history").await);
+    }
+
+    let result: ApiResponse<Vec<FileRevision>> = response.json().await?;
+    let Some(revisions) = result.data else {
+        print_api_error(&http::describe_failure(&result));
+        return Ok(());
+    };
+
+    if revisions.is_empty() {
+        print_info(&format!("No checkpoints have touched {} yet", path));
+        return Ok(());
+    }
+
+    println!("\n{} {}", "📜 History of".cyan().bold(), path.blue().bold());
+    for revision in &revisions {
+        let change_icon = match revision.change_type {
+            ChangeType::Created => "+".green(),
+            ChangeType::Modified => "~".yellow(),
+            ChangeType::Deleted => "-".red(),
+            ChangeType::Moved { .. } => "→".blue(),
+        };
+        println!(
+            "\n{} {} {}",
+            change_icon,
+            revision.checkpoint_id.to_string()[..8].yellow().bold(),
+            revision.message.as_deref().unwrap_or("(no message)").white()
+        );
+        println!(
+            "   {} • {} • {} bytes",
+            revision.username.cyan(),
+            format_time_ago(revision.timestamp).dimmed(),
+            revision.size
+        );
+        if let ChangeType::Moved { from } = &revision.change_type {
+            println!("   {} {}", "moved from".dimmed(), from.display().to_string().blue());
+        }
+    }
+
+    Ok(())
+}
+
+/// Show which checkpoint and author introduced each line of a file
+pub async fn handle_blame(config_manager: &ConfigManager, file: PathBuf, checkpoint: Option<String>) -> Result<()> {
+    let (project_id, root) = find_project_root()?;
+    let path = project_relative_path(&root, &file)?;
+    let config = config_manager.load_config()?;
+    let client = get_http_client(&config);
+
+    let url = file_url(&get_server_url(config_manager)?, project_id, &path, "blame");
+    let mut request = client.get(&url);
+    if let Some(checkpoint) = checkpoint {
+        let checkpoint_id = Uuid::parse_str(&checkpoint)
+            .map_err(|_| anyhow!("Invalid checkpoint ID: {}", checkpoint))?;
+        request = request.query(&[("checkpoint", checkpoint_id)]);
+    }
+
+    let response = request.send().await?.check_session()?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        print_api_error("Checkpoint not found in this rift");
+        return Ok(());
+    }
+    if !response.status().is_success() {
+        return Err(http::api_error(response, "Failed to load blame").await);
+    }
+
+    let result: ApiResponse<FileBlame> = response.json().await?;
+    let Some(blame) = result.data else {
+        print_api_error(&http::describe_failure(&result));
+        return Ok(());
+    };
+
+    let name_width = blame.lines.iter().map(|l| l.username.len()).max().unwrap_or(0);
+    let number_width = blame.lines.len().to_string().len();
+
+    for line in &blame.lines {
+        println!(
+            "{} {} {} {} {}",
+            line.checkpoint_id.to_string()[..8].yellow(),
+            format!("{:<name_width$}", line.username).cyan(),
+            line.timestamp.with_timezone(&chrono::Local).format("%Y-%m-%d").to_string().dimmed(),
+            format!("{:>number_width$}", line.line_number).dimmed(),
+            line.content,
+        );
+    }
+
+    Ok(())
+}
@@ -1,8 +1,10 @@
 use anyhow::{anyhow, Result};
 use colored::*;
 use mothership_common::{
-    protocol::{ApiResponse, GatewayRequest},
-    GatewayProject, Project, ClientConfig,
+    project_root,
+    protocol::{ApiResponse, ArchiveProjectRequest, GatewayRequest, ProjectArchiveStatus, UpdateProjectSettingsRequest},
+    upload::{CreateUploadRequest, UploadCompletion, UploadFileManifest, UploadStatus, UPLOAD_CHUNK_SIZE},
+    ConflictPolicy, GatewayProject, IgnoreMatcher, Project, ProjectSettings, ProjectVisibility, PushRules, SecretFinding, SecretScanMode, ClientConfig, RiftSummary,
 };
 use std::path::PathBuf;
 use std::fs;
@@ -11,7 +13,7 @@ use std::io::{self, Write};
 use serde::{Serialize, Deserialize};
 use walkdir::WalkDir;
 
-use crate::{config::ConfigManager, get_http_client, print_api_error, print_info, print_success, connections};
+use crate::{config::ConfigManager, get_http_client, http::{self, ResponseExt}, print_api_error, print_info, print_success, connections};
 
 /// Local status of a project
 #[derive(Debug, Clone)]
@@ -74,7 +76,10 @@ fn find_project_by_metadata(search_dir: &PathBuf, target_project_name: &str) ->
     Err(anyhow!("Project not found in search directory"))
 }
 
-pub async fn handle_gateway(config_manager: &ConfigManager, include_inactive: bool) -> Result<()> {
+/// Projects requested per gateway page
+const GATEWAY_PAGE: usize = 100;
+
+pub async fn handle_gateway(config_manager: &ConfigManager, include_inactive: bool, archived: bool) -> Result<()> {
     // Check if authenticated
     if !config_manager.is_authenticated()? {
         print_api_error("Not authenticated. Please run 'mothership auth' first.");
@@ -88,35 +93,50 @@ pub async fn handle_gateway(config_manager: &ConfigManager, include_inactive: bo
     let config = config_manager.load_config()?;
     let client = get_http_client(&config);
 
-    let gateway_request = GatewayRequest {
-        include_inactive,
-    };
-
+    // The server lists projects a page at a time; keep going until a short page
     let gateway_url = format!("{}/gateway", active_server.url);
-    let response = client
-        .post(&gateway_url)
-        .json(&gateway_request)
-        .send()
-        .await?;
+    let mut projects: Vec<GatewayProject> = Vec::new();
+    loop {
+        let gateway_request = GatewayRequest {
+            include_inactive,
+            archived,
+            limit: Some(GATEWAY_PAGE),
+            before: projects.last().map(|p| p.project.id),
+        };
+        let response = client
+            .post(&gateway_url)
+            .json(&gateway_request)
+            .send()
+            .await?
+            .check_session()?;
 
-    if !response.status().is_success() {
-        return Err(anyhow!("Gateway request failed: {}", response.status()));
-    }
+        if !response.status().is_success() {
+            return Err(http::api_error(response, "Gateway request failed").await);
+        }
 
-    let gateway_response: ApiResponse<Vec<GatewayProject>> = response.json().await?;
-    
-    let projects = gateway_response.data.ok_or_else(|| {
-        anyhow!("No gateway data received: {}", gateway_response.error.unwrap_or_else(|| "Unknown error".to_string()))
-    })?;
+        let gateway_response: ApiResponse<Vec<GatewayProject>> = response.json().await?;
+        let page = gateway_response.data.ok_or_else(|| {
+            anyhow!("No gateway data received: {}", gateway_response.error.unwrap_or_else(|| "Unknown error".to_string()))
+        })?;
+        let done = page.len() < GATEWAY_PAGE;
+        projects.extend(page);
+        if done {
+            break;
+        }
+    }
 
     if projects.is_empty() {
-        print_info("No projects available. Contact your administrator to get access to projects.");
+        if archived {
+            print_info("No archived projects.");
+        } else {
+            print_info("No projects available. Contact your administrator to get access to projects.");
+        }
         return Ok(());
     }
 
     // Display projects
     println!("\n{}", "🌌 Your Development Gateway".cyan().bold());
-    println!("{}", "Available projects:".white());
+    println!("{}", if archived { "Archived projects (read-only):" } else { "Available projects:" }.white());
 
     for gateway_project in projects {
         let project = &gateway_project.project;
@@ -130,6 +150,12 @@ pub async fn handle_gateway(config_manager: &ConfigManager, include_inactive: bo
         
         println!("\n{} {} {}", status_indicator, project_name_colored, local_info.dimmed());
         println!("   {}", project.description.dimmed());
+        if let Some(archived_at) = gateway_project.archived_at {
+            println!("   {} Archived {}", "🗄️".yellow(), archived_at.with_timezone(&chrono::Local).format("%Y-%m-%d").to_string().dimmed());
+        }
+        if let Some(last_activity) = gateway_project.last_activity {
+            println!("   {} Last activity {}", "🕒".dimmed(), last_activity.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string().dimmed());
+        }
         
         if !gateway_project.your_rifts.is_empty() {
             println!("   {} Your rifts:", "📂".yellow());
@@ -139,7 +165,7 @@ pub async fn handle_gateway(config_manager: &ConfigManager, include_inactive: bo
                 } else {
                     String::new()
                 };
-                println!("     • {}{}", rift.name.cyan(), collaborators.dimmed());
+                println!("     • {}{}{}", rift.name.cyan(), collaborators.dimmed(), rift_activity(rift).dimmed());
             }
         }
 
@@ -147,7 +173,7 @@ pub async fn handle_gateway(config_manager: &ConfigManager, include_inactive: bo
             println!("   {} Active rifts:", "⚡".yellow());
             for rift in &gateway_project.active_rifts {
                 let collaborators = rift.collaborators.join(", ");
-                println!("     • {} ({})", rift.name.cyan(), collaborators.dimmed());
+                println!("     • {} ({}){}", rift.name.cyan(), collaborators.dimmed(), rift_activity(rift).dimmed());
             }
         }
 
@@ -168,6 +194,19 @@ pub async fn handle_gateway(config_manager: &ConfigManager, include_inactive: bo
     Ok(())
 }
 
+/// Checkpoint count and latest checkpoint of a rift, for the gateway listing
+fn rift_activity(rift: &RiftSummary) -> String {
+    match rift.last_checkpoint {
+        Some(last_checkpoint) => format!(
+            " — {} checkpoint{}, last {}",
+            rift.change_count,
+            if rift.change_count == 1 { "" } else { "s" },
+            last_checkpoint.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"),
+        ),
+        None => " — no checkpoints yet".to_string(),
+    }
+}
+
 pub async fn handle_gateway_create(
     config_manager: &ConfigManager, 
     name: String, 
@@ -188,11 +227,18 @@ pub async fn handle_gateway_create(
         return Err(anyhow!("Path is not a directory: {}", dir.display()));
     }
 
-    // Check if we're already inside a gateway
-    if let Some(gateway_root) = find_gateway_root(&dir) {
+    // Gateways can nest, but a directory holds one at most
+    if project_root::is_project_root(&dir) {
         return Err(anyhow!(
-            "Cannot create gateway inside another gateway.\nExisting gateway found at: {}\n\nTo create a new gateway, choose a directory outside of any existing gateway.",
-            gateway_root.display()
+            "{} is already a gateway.\n\nTo create a new gateway, choose a directory that isn't one, such as a subdirectory.",
+            dir.display()
+        ));
+    }
+    if let Some(gateway_root) = find_gateway_root(&dir) {
+        print_info(&format!(
+            "Nesting inside the gateway at {}; files under {} will sync with the new gateway only",
+            gateway_root.display(),
+            dir.display()
         ));
     }
 
@@ -218,11 +264,11 @@ pub async fn handle_gateway_create(
         .post(&create_url)
         .json(&create_request)
         .send()
-        .await?;
+        .await?
+        .check_session()?;
 
     if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(anyhow!("Gateway creation failed: {}", error_text));
+        return Err(http::api_error(response, "Gateway creation failed").await);
     }
 
     let create_response: ApiResponse<Project> = response.json().await?;
@@ -273,7 +319,7 @@ pub async fn handle_delete(
 
     // First, get the project by name to verify it exists
     let project_url = format!("{}/projects/name/{}", active_server.url, urlencoding::encode(&project_name));
-    let response = client.get(&project_url).send().await?;
+    let response = client.get(&project_url).send().await?.check_session()?;
 
     if !response.status().is_success() {
         if response.status() == 404 {
@@ -296,10 +342,11 @@ pub async fn handle_delete(
         println!("{}", format!("Description: {}", project.description.dimmed()));
         println!("{}", format!("Project ID: {}", project.id.to_string().dimmed()));
         
-        println!("\n{}", "This will permanently delete:".yellow());
-        println!("{}", "  • The project from Mothership servers".dimmed());
+        println!("\n{}", "This will move to the trash:".yellow());
+        println!("{}", "  • The project on the Mothership server".dimmed());
         println!("{}", "  • All project history and checkpoints".dimmed());
         println!("{}", "  • All associated rifts and collaboration data".dimmed());
+        println!("{}", "A server admin can restore it until the trash is emptied; after that it's gone for good.".dimmed());
         
         println!("\n{}", "Local files will NOT be deleted - they remain on your machine.".green());
         
@@ -319,14 +366,14 @@ pub async fn handle_delete(
 
     // Delete the project
     let delete_url = format!("{}/projects/{}", active_server.url, project.id);
-    let response = client.delete(&delete_url).send().await?;
+    let response = client.delete(&delete_url).send().await?.check_session()?;
 
     if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(anyhow!("Failed to delete project: {}", error_text));
+        return Err(http::api_error(response, "Failed to delete project").await);
     }
 
-    print_success(&format!("Project '{}' successfully deleted from Mothership server!", project.name));
+    let deleted: ApiResponse<String> = response.json().await?;
+    print_success(&deleted.data.unwrap_or_else(|| format!("Project '{}' deleted from the Mothership server", project.name)));
     
     // Check if there's a local .mothership directory and offer to clean it up
     let current_dir = std::env::current_dir()?;
@@ -388,6 +435,461 @@ pub async fn handle_delete(
     Ok(())
 }
 
+/// Archive (read-only) or unarchive a project
+pub async fn handle_archive(config_manager: &ConfigManager, project_name: String, archive: bool, compact: bool) -> Result<()> {
+    let active_server = connections::get_active_server()?
+        .ok_or_else(|| anyhow!("No active server connection. Please run 'mothership connect <server-url>' first."))?;
+
+    let config = config_manager.load_config()?;
+    let client = get_http_client(&config);
+
+    let project_url = format!("{}/projects/name/{}", active_server.url, urlencoding::encode(&project_name));
+    let response = client.get(&project_url).send().await?.check_session()?;
+
+    if !response.status().is_success() {
+        if response.status() == 404 {
+            print_api_error(&format!("Project '{}' not found", project_name));
+        } else {
+            print_api_error(&format!("Failed to find project: {}", response.status()));
+        }
+        return Ok(());
+    }
+
+    let project_response: ApiResponse<Project> = response.json().await?;
+    let project = project_response.data.ok_or_else(|| {
+        anyhow!("No project data received")
+    })?;
+
+    let response = if archive {
+        client
+            .post(format!("{}/projects/{}/archive", active_server.url, project.id))
+            .json(&ArchiveProjectRequest { compact })
+            .send()
+            .await?
+            .check_session()?
+    } else {
+        client
+            .post(format!("{}/projects/{}/unarchive", active_server.url, project.id))
+            .send()
+            .await?
+            .check_session()?
+    };
+
+    if !response.status().is_success() {
+        return Err(http::api_error(response, "Failed to update project").await);
+    }
+
+    let result: ApiResponse<ProjectArchiveStatus> = response.json().await?;
+    let Some(status) = result.data else {
+        print_api_error(&http::describe_failure(&result));
+        return Ok(());
+    };
+
+    if archive {
+        print_success(&format!("Project '{}' archived - history and beam still work, changes are refused", project.name));
+        if compact {
+            print_info(&format!("Pruned {} auto-generated checkpoints", status.checkpoints_pruned));
+        }
+        println!("{}", format!("Use 'mothership gateway unarchive \"{}\"' to make it writable again.", project.name).dimmed());
+    } else {
+        print_success(&format!("Project '{}' is writable again", project.name));
+    }
+
+    Ok(())
+}
+
+/// Show or change how sync conflicts are resolved in a project
+pub async fn handle_conflict_policy(config_manager: &ConfigManager, project_name: String, policy: Option<String>) -> Result<()> {
+    let policy = match policy {
+        Some(policy) => Some(ConflictPolicy::parse(&policy.to_lowercase()).ok_or_else(|| {
+            anyhow!("Unknown conflict policy '{}' (use server-wins, client-wins, always-create-conflict-rift or prompt)", policy)
+        })?),
+        None => None,
+    };
+
+    let active_server = connections::get_active_server()?
+        .ok_or_else(|| anyhow!("No active server connection. Please run 'mothership connect <server-url>' first."))?;
+
+    let config = config_manager.load_config()?;
+    let client = get_http_client(&config);
+
+    let project_url = format!("{}/projects/name/{}", active_server.url, urlencoding::encode(&project_name));
+    let response = client.get(&project_url).send().await?.check_session()?;
+
+    if !response.status().is_success() {
+        if response.status() == 404 {
+            print_api_error(&format!("Project '{}' not found", project_name));
+        } else {
+            print_api_error(&format!("Failed to find project: {}", response.status()));
+        }
+        return Ok(());
+    }
+
+    let project_response: ApiResponse<Project> = response.json().await?;
+    let project = project_response.data.ok_or_else(|| {
+        anyhow!("No project data received")
+    })?;
+
+    let settings_url = format!("{}/projects/{}/settings", active_server.url, project.id);
+    let response = match policy {
+        Some(policy) => client
+            .patch(&settings_url)
+            .json(&UpdateProjectSettingsRequest { conflict_policy: Some(policy), ..Default::default() })
+            .send()
+            .await?
+            .check_session()?,
+        None => client.get(&settings_url).send().await?.check_session()?,
+    };
+
+    if !response.status().is_success() {
+        return Err(http::api_error(response, "Failed to access project settings").await);
+    }
+
+    let result: ApiResponse<ProjectSettings> = response.json().await?;
+    let Some(settings) = result.data else {
+        print_api_error(&http::describe_failure(&result));
+        return Ok(());
+    };
+
+    if policy.is_some() {
+        print_success(&format!("Conflicts in '{}' are now resolved with {}", project.name, settings.conflict_policy.as_str()));
+    } else {
+        print_info(&format!("Conflicts in '{}' are resolved with {}", project.name, settings.conflict_policy.as_str()));
+    }
+    println!("{}", "Override it for one checkout with {\"conflict_policy\": \"<policy>\"} in .mothership/config.json".dimmed());
+
+    Ok(())
+}
+
+/// Show or set how often the server takes automatic checkpoints of a project's rifts
+pub async fn handle_auto_checkpoint(config_manager: &ConfigManager, project_name: String, seconds: Option<u64>) -> Result<()> {
+    let active_server = connections::get_active_server()?
+        .ok_or_else(|| anyhow!("No active server connection. Please run 'mothership connect <server-url>' first."))?;
+
+    let config = config_manager.load_config()?;
+    let client = get_http_client(&config);
+
+    let project_url = format!("{}/projects/name/{}", active_server.url, urlencoding::encode(&project_name));
+    let response = client.get(&project_url).send().await?.check_session()?;
+
+    if !response.status().is_success() {
+        if response.status() == 404 {
+            print_api_error(&format!("Project '{}' not found", project_name));
+        } else {
+            print_api_error(&format!("Failed to find project: {}", response.status()));
+        }
+        return Ok(());
+    }
+
+    let project_response: ApiResponse<Project> = response.json().await?;
+    let project = project_response.data.ok_or_else(|| {
+        anyhow!("No project data received")
+    })?;
+
+    let settings_url = format!("{}/projects/{}/settings", active_server.url, project.id);
+    let response = match seconds {
+        Some(seconds) => client
+            .patch(&settings_url)
+            .json(&UpdateProjectSettingsRequest { auto_checkpoint_interval: Some(seconds), ..Default::default() })
+            .send()
+            .await?
+            .check_session()?,
+        None => client.get(&settings_url).send().await?.check_session()?,
+    };
+
+    if !response.status().is_success() {
+        return Err(http::api_error(response, "Failed to access project settings").await);
+    }
+
+    let result: ApiResponse<ProjectSettings> = response.json().await?;
+    let Some(settings) = result.data else {
+        print_api_error(&http::describe_failure(&result));
+        return Ok(());
+    };
+
+    let interval = match settings.auto_checkpoint_interval {
+        0 => "off".to_string(),
+        seconds => format!("every {}", format_interval(seconds)),
+    };
+    if seconds.is_some() {
+        print_success(&format!("Automatic checkpoints in '{}' are now {}", project.name, interval));
+    } else {
+        print_info(&format!("Automatic checkpoints in '{}' are {}", project.name, interval));
+    }
+    println!("{}", "Rifts that haven't changed since their last checkpoint are skipped; servers may enforce a minimum interval".dimmed());
+
+    Ok(())
+}
+
+fn format_interval(seconds: u64) -> String {
+    match seconds {
+        s if s % 3600 == 0 => format!("{}h", s / 3600),
+        s if s % 60 == 0 => format!("{}m", s / 60),
+        s => format!("{}s", s),
+    }
+}
+
+/// Show or change who can read a project
+pub async fn handle_visibility(config_manager: &ConfigManager, project_name: String, visibility: Option<String>) -> Result<()> {
+    let visibility = match visibility {
+        Some(visibility) => Some(ProjectVisibility::parse(&visibility.to_lowercase()).ok_or_else(|| {
+            anyhow!("Unknown visibility '{}' (use private, internal or public)", visibility)
+        })?),
+        None => None,
+    };
+
+    let active_server = connections::get_active_server()?
+        .ok_or_else(|| anyhow!("No active server connection. Please run 'mothership connect <server-url>' first."))?;
+
+    let config = config_manager.load_config()?;
+    let client = get_http_client(&config);
+
+    let project_url = format!("{}/projects/name/{}", active_server.url, urlencoding::encode(&project_name));
+    let response = client.get(&project_url).send().await?.check_session()?;
+
+    if !response.status().is_success() {
+        if response.status() == 404 {
+            print_api_error(&format!("Project '{}' not found", project_name));
+        } else {
+            print_api_error(&format!("Failed to find project: {}", response.status()));
+        }
+        return Ok(());
+    }
+
+    let project_response: ApiResponse<Project> = response.json().await?;
+    let project = project_response.data.ok_or_else(|| {
+        anyhow!("No project data received")
+    })?;
+
+    let settings_url = format!("{}/projects/{}/settings", active_server.url, project.id);
+    let response = match visibility {
+        Some(visibility) => client
+            .patch(&settings_url)
+            .json(&UpdateProjectSettingsRequest { visibility: Some(visibility), ..Default::default() })
+            .send()
+            .await?
+            .check_session()?,
+        None => client.get(&settings_url).send().await?.check_session()?,
+    };
+
+    if !response.status().is_success() {
+        return Err(http::api_error(response, "Failed to access project settings").await);
+    }
+
+    let result: ApiResponse<ProjectSettings> = response.json().await?;
+    let Some(settings) = result.data else {
+        print_api_error(&http::describe_failure(&result));
+        return Ok(());
+    };
+
+    let readers = match settings.visibility {
+        ProjectVisibility::Private => "only its members can read it",
+        ProjectVisibility::Internal => "anyone signed in to the server can read it",
+        ProjectVisibility::Public => "anyone can read it, without signing in",
+    };
+    if visibility.is_some() {
+        print_success(&format!("'{}' is now {}: {}", project.name, settings.visibility.as_str(), readers));
+    } else {
+        print_info(&format!("'{}' is {}: {}", project.name, settings.visibility.as_str(), readers));
+    }
+    if settings.visibility != ProjectVisibility::Private {
+        println!("{}", format!("Share it with /browse?project={} on the server's web UI; only members can change it", project.id).dimmed());
+    }
+
+    Ok(())
+}
+
+/// Changes to a project's push rules from `gateway push-rules`
+#[derive(Debug, Default)]
+pub struct PushRuleChanges {
+    pub require_message: Option<bool>,
+    /// Megabytes; 0 removes the limit
+    pub max_file_size_mb: Option<u64>,
+    pub deny: Vec<String>,
+    pub remove_deny: Vec<String>,
+    pub restrict_file_types: Option<bool>,
+}
+
+impl PushRuleChanges {
+    fn is_empty(&self) -> bool {
+        self.require_message.is_none()
+            && self.max_file_size_mb.is_none()
+            && self.deny.is_empty()
+            && self.remove_deny.is_empty()
+            && self.restrict_file_types.is_none()
+    }
+
+    fn apply(self, rules: &mut PushRules) {
+        if let Some(require_message) = self.require_message {
+            rules.require_checkpoint_message = require_message;
+        }
+        if let Some(mb) = self.max_file_size_mb {
+            rules.max_file_size = (mb > 0).then(|| mb * 1024 * 1024);
+        }
+        rules.deny_paths.retain(|pattern| !self.remove_deny.contains(pattern));
+        for pattern in self.deny {
+            if !rules.deny_paths.contains(&pattern) {
+                rules.deny_paths.push(pattern);
+            }
+        }
+        if let Some(restrict) = self.restrict_file_types {
+            rules.restrict_file_types = restrict;
+        }
+    }
+}
+
+/// Show or change the rules the server enforces on a project's checkpoints and synced changes
+pub async fn handle_push_rules(config_manager: &ConfigManager, project_name: String, changes: PushRuleChanges) -> Result<()> {
+    let active_server = connections::get_active_server()?
+        .ok_or_else(|| anyhow!("No active server connection. Please run 'mothership connect <server-url>' first."))?;
+
+    let config = config_manager.load_config()?;
+    let client = get_http_client(&config);
+
+    let project_url = format!("{}/projects/name/{}", active_server.url, urlencoding::encode(&project_name));
+    let response = client.get(&project_url).send().await?.check_session()?;
+
+    if !response.status().is_success() {
+        if response.status() == 404 {
+            print_api_error(&format!("Project '{}' not found", project_name));
+        } else {
+            print_api_error(&format!("Failed to find project: {}", response.status()));
+        }
+        return Ok(());
+    }
+
+    let project_response: ApiResponse<Project> = response.json().await?;
+    let project = project_response.data.ok_or_else(|| {
+        anyhow!("No project data received")
+    })?;
+
+    let settings_url = format!("{}/projects/{}/settings", active_server.url, project.id);
+    let response = client.get(&settings_url).send().await?.check_session()?;
+    if !response.status().is_success() {
+        return Err(http::api_error(response, "Failed to access project settings").await);
+    }
+    let result: ApiResponse<ProjectSettings> = response.json().await?;
+    let Some(mut settings) = result.data else {
+        print_api_error(&http::describe_failure(&result));
+        return Ok(());
+    };
+
+    let changed = !changes.is_empty();
+    if changed {
+        changes.apply(&mut settings.push_rules);
+        let response = client
+            .patch(&settings_url)
+            .json(&UpdateProjectSettingsRequest { push_rules: Some(settings.push_rules.clone()), ..Default::default() })
+            .send()
+            .await?
+            .check_session()?;
+        if !response.status().is_success() {
+            return Err(http::api_error(response, "Failed to update push rules").await);
+        }
+        print_success(&format!("Push rules of '{}' updated", project.name));
+    }
+
+    let rules = &settings.push_rules;
+    if !changed {
+        print_info(&format!("Push rules of '{}':", project.name));
+    }
+    if *rules == PushRules::default() {
+        println!("  {}", "No push rules - every change is accepted".dimmed());
+        return Ok(());
+    }
+    if rules.require_checkpoint_message {
+        println!("  {} Checkpoints need a message", "•".cyan());
+    }
+    if let Some(max) = rules.max_file_size {
+        println!("  {} Files may be at most {:.1} MB", "•".cyan(), max as f64 / (1024.0 * 1024.0));
+    }
+    for pattern in &rules.deny_paths {
+        println!("  {} Denied: {}", "•".cyan(), pattern.yellow());
+    }
+    if rules.restrict_file_types {
+        println!("  {} Only these file types: {}", "•".cyan(), settings.allowed_file_types.join(" "));
+    }
+
+    Ok(())
+}
+
+/// Show a project's secret scanning mode and findings, or change the mode
+pub async fn handle_secrets(config_manager: &ConfigManager, project_name: String, mode: Option<String>) -> Result<()> {
+    let mode = match mode {
+        Some(mode) => Some(SecretScanMode::parse(&mode.to_lowercase()).ok_or_else(|| {
+            anyhow!("Unknown secret scanning mode '{}' (use off, warn or reject)", mode)
+        })?),
+        None => None,
+    };
+
+    let active_server = connections::get_active_server()?
+        .ok_or_else(|| anyhow!("No active server connection. Please run 'mothership connect <server-url>' first."))?;
+
+    let config = config_manager.load_config()?;
+    let client = get_http_client(&config);
+
+    let project_url = format!("{}/projects/name/{}", active_server.url, urlencoding::encode(&project_name));
+    let response = client.get(&project_url).send().await?.check_session()?;
+
+    if !response.status().is_success() {
+        if response.status() == 404 {
+            print_api_error(&format!("Project '{}' not found", project_name));
+        } else {
+            print_api_error(&format!("Failed to find project: {}", response.status()));
+        }
+        return Ok(());
+    }
+
+    let project_response: ApiResponse<Project> = response.json().await?;
+    let project = project_response.data.ok_or_else(|| {
+        anyhow!("No project data received")
+    })?;
+
+    let settings_url = format!("{}/projects/{}/settings", active_server.url, project.id);
+    let response = match mode {
+        Some(mode) => client
+            .patch(&settings_url)
+            .json(&UpdateProjectSettingsRequest { secret_scanning: Some(mode), ..Default::default() })
+            .send()
+            .await?
+            .check_session()?,
+        None => client.get(&settings_url).send().await?.check_session()?,
+    };
+    if !response.status().is_success() {
+        return Err(http::api_error(response, "Failed to access project settings").await);
+    }
+    let result: ApiResponse<ProjectSettings> = response.json().await?;
+    let Some(settings) = result.data else {
+        print_api_error(&http::describe_failure(&result));
+        return Ok(());
+    };
+
+    if mode.is_some() {
+        print_success(&format!("Secret scanning in '{}' is now {}", project.name, settings.secret_scanning.as_str()));
+        return Ok(());
+    }
+    print_info(&format!("Secret scanning in '{}' is {}", project.name, settings.secret_scanning.as_str()));
+
+    let findings_url = format!("{}/projects/{}/secrets", active_server.url, project.id);
+    let response = client.get(&findings_url).send().await?.check_session()?;
+    if !response.status().is_success() {
+        return Err(http::api_error(response, "Failed to load secret findings").await);
+    }
+    let result: ApiResponse<Vec<SecretFinding>> = response.json().await?;
+    let findings = result.data.unwrap_or_default();
+
+    if findings.is_empty() {
+        println!("  {}", "No secrets found".dimmed());
+    }
+    for finding in &findings {
+        let age = crate::sync::format_time_ago(finding.detected_at);
+        let status = if finding.blocked { "blocked".red() } else { "synced".yellow() };
+        println!("  {} {} {}", format!("[{}]", status).bold(), finding.describe(), age.dimmed());
+    }
+
+    Ok(())
+}
+
 #[derive(serde::Serialize)]
 struct CreateGatewayRequest {
     name: String,
@@ -404,25 +906,9 @@ pub struct ProjectMetadata {
     mothership_url: String,
 }
 
-/// Check if we're already inside a gateway by looking for .mothership directory
+/// The nearest gateway enclosing a directory, the directory included
 fn find_gateway_root(start_dir: &PathBuf) -> Option<PathBuf> {
-    let mut current_dir = start_dir.clone();
-    
-    loop {
-        let mothership_dir = current_dir.join(".mothership");
-        if mothership_dir.exists() && mothership_dir.is_dir() {
-            return Some(current_dir);
-        }
-        
-        // Move to parent directory
-        if let Some(parent) = current_dir.parent() {
-            current_dir = parent.to_path_buf();
-        } else {
-            break;
-        }
-    }
-    
-    None
+    project_root::find_project_root(start_dir)
 }
 
 /// Create .mothership directory with project metadata
@@ -492,27 +978,28 @@ async fn upload_initial_files(
     dir: &PathBuf,
     server_url: &str,
 ) -> Result<()> {
-    let mut files = HashMap::new();
-    let mut file_count = 0;
+    let mut manifests = Vec::new();
+    let mut total_bytes: u64 = 0;
     
-    // Scan directory for files (excluding .mothership and common ignore patterns)
+    // Scan directory for files, honouring .mothershipignore and the default ignore patterns
+    let ignore_matcher = IgnoreMatcher::load(dir);
     for entry in WalkDir::new(dir)
         .follow_links(false)
         .into_iter()
-        .filter_entry(|e| !should_ignore_file(e.path())) 
+        .filter_entry(|e| !ignore_matcher.is_ignored(e.path(), e.file_type().is_dir()))
     {
         let entry = entry?;
         let path = entry.path();
         
         if path.is_file() {
             if let Ok(relative_path) = path.strip_prefix(dir) {
-                match fs::read_to_string(path) {
-                    Ok(content) => {
-                        files.insert(relative_path.to_path_buf(), content);
-                        file_count += 1;
+                match fs::read(path) {
+                    Ok(bytes) if std::str::from_utf8(&bytes).is_ok() => {
+                        total_bytes += bytes.len() as u64;
+                        manifests.push(UploadFileManifest::from_bytes(relative_path.to_path_buf(), &bytes));
                         print_info(&format!("Found: {}", relative_path.display()));
                     }
-                    Err(_) => {
+                    _ => {
                         // Skip binary files or files we can't read
                         print_info(&format!("Skipped (binary): {}", relative_path.display()));
                     }
@@ -521,67 +1008,175 @@ async fn upload_initial_files(
         }
     }
     
-    if files.is_empty() {
+    if manifests.is_empty() {
         print_info("No text files found to upload");
         return Ok(());
     }
     
-    print_info(&format!("Uploading {} files to server...", file_count));
-    
-    // Send files to server
-    let upload_request = UploadInitialFilesRequest {
-        project_id: project.id,
-        files,
-    };
+    let file_count = manifests.len();
+    print_info(&format!("Uploading {} files ({}) to server...", file_count, format_bytes(total_bytes)));
     
     let client = get_http_client(config);
-    let upload_url = format!("{}/projects/{}/files", server_url, project.id);
+    let uploads_url = format!("{}/projects/{}/uploads", server_url, project.id);
+    
+    // Open a chunked upload session; the server only asks for chunks it doesn't already have
     let response = client
-        .post(&upload_url)
-        .json(&upload_request)
+        .post(&uploads_url)
+        .json(&CreateUploadRequest { files: manifests.clone() })
         .send()
-        .await?;
+        .await?
+        .check_session()?;
     
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        // Older servers only accept the single-request upload
+        print_info("Server does not support chunked uploads, falling back to a single request");
+        return upload_initial_files_legacy(&client, project, dir, server_url, &manifests).await;
+    }
     if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(anyhow!("Failed to upload initial files: {}", error_text));
+        return Err(http::api_error(response, "Failed to start upload").await);
     }
     
-    print_success(&format!("Successfully uploaded {} files to server!", file_count));
+    let status_response: ApiResponse<UploadStatus> = response.json().await?;
+    let status = status_response.data.ok_or_else(|| {
+        anyhow!("Failed to start upload: {}", status_response.error.unwrap_or_else(|| "Unknown error".to_string()))
+    })?;
+    
+    // Locate each chunk on disk so it can be read back without holding every file in memory
+    let mut chunk_locations: HashMap<&str, (&PathBuf, usize)> = HashMap::new();
+    for manifest in &manifests {
+        for (index, hash) in manifest.chunks.iter().enumerate() {
+            chunk_locations.entry(hash.as_str()).or_insert((&manifest.path, index));
+        }
+    }
+    
+    let already_uploaded = status.total_chunks - status.missing_chunks.len();
+    if already_uploaded > 0 {
+        print_info(&format!("Resuming: {} of {} chunks already on server", already_uploaded, status.total_chunks));
+    }
+    
+    let chunk_count = status.missing_chunks.len();
+    for (done, hash) in status.missing_chunks.iter().enumerate() {
+        let (path, index) = chunk_locations.get(hash.as_str())
+            .ok_or_else(|| anyhow!("Server requested unknown chunk {}", hash))?;
+        let bytes = read_chunk(&dir.join(path), *index)?;
+        
+        let chunk_url = format!("{}/{}/chunks/{}", uploads_url, status.upload_id, hash);
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let result = client.put(&chunk_url).body(bytes.clone()).send().await;
+            match result {
+                Ok(resp) if resp.status().is_success() => break,
+                Ok(resp) if attempt >= CHUNK_UPLOAD_ATTEMPTS => {
+                    println!();
+                    return Err(anyhow!("Chunk upload failed ({}). Re-run to resume the upload.", resp.status()));
+                }
+                Err(e) if attempt >= CHUNK_UPLOAD_ATTEMPTS => {
+                    println!();
+                    return Err(anyhow!("Chunk upload failed ({}). Re-run to resume the upload.", e));
+                }
+                _ => tokio::time::sleep(std::time::Duration::from_millis(500 * attempt as u64)).await,
+            }
+        }
+        
+        print!("\r   {} chunk {}/{} ({}%)", "📤".cyan(), done + 1, chunk_count, (done + 1) * 100 / chunk_count);
+        io::stdout().flush().ok();
+    }
+    if chunk_count > 0 {
+        println!();
+    }
+    
+    // Ask the server to reassemble and verify every file
+    let complete_url = format!("{}/{}/complete", uploads_url, status.upload_id);
+    let response = client.post(&complete_url).send().await?.check_session()?;
+    if !response.status().is_success() {
+        return Err(http::api_error(response, "Failed to complete upload").await);
+    }
+    
+    let completion_response: ApiResponse<UploadCompletion> = response.json().await?;
+    let completion = completion_response.data.ok_or_else(|| {
+        anyhow!("Failed to complete upload: {}", completion_response.error.unwrap_or_else(|| "Unknown error".to_string()))
+    })?;
+    
+    print_upload_report(&completion);
     Ok(())
 }
 
-/// Check if a file should be ignored during initial scan
-fn should_ignore_file(path: &std::path::Path) -> bool {
-    let path_str = path.to_string_lossy();
+/// Report how many files the server stored, and the ones it skipped or refused
+fn print_upload_report(completion: &UploadCompletion) {
+    for path in &completion.skipped {
+        print_info(&format!("Server skipped: {}", path.display()));
+    }
+    if !completion.rejected.is_empty() {
+        println!("\n{}", format!("⚠️  The server refused {} files:", completion.rejected.len()).yellow());
+        crate::sync::print_push_rule_violations(&completion.rejected);
+    }
+    
+    print_success(&format!("Successfully uploaded {} files to server!", completion.files_stored));
+}
+
+/// Number of tries for each chunk before giving up
+const CHUNK_UPLOAD_ATTEMPTS: u32 = 3;
+
+/// Read the `index`-th upload chunk of a file
+fn read_chunk(path: &PathBuf, index: usize) -> Result<Vec<u8>> {
+    use std::io::{Read, Seek, SeekFrom};
     
-    // Ignore .mothership directory
-    if path_str.contains(".mothership") {
-        return true;
+    let mut file = fs::File::open(path)?;
+    file.seek(SeekFrom::Start((index * UPLOAD_CHUNK_SIZE) as u64))?;
+    let mut bytes = Vec::with_capacity(UPLOAD_CHUNK_SIZE);
+    file.take(UPLOAD_CHUNK_SIZE as u64).read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+pub(crate) fn format_bytes(bytes: u64) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    } else if bytes >= 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{} B", bytes)
     }
+}
+
+/// Upload all files as JSON in one request (servers without chunked upload support)
+async fn upload_initial_files_legacy(
+    client: &reqwest::Client,
+    project: &Project,
+    dir: &PathBuf,
+    server_url: &str,
+    manifests: &[UploadFileManifest],
+) -> Result<()> {
+    let mut files = HashMap::new();
+    for manifest in manifests {
+        files.insert(manifest.path.clone(), fs::read_to_string(dir.join(&manifest.path))?);
+    }
+    let file_count = files.len();
     
-    // Ignore common patterns
-    let ignore_patterns = [
-        ".git", ".svn", ".hg",
-        "node_modules", "target", "build", "dist", 
-        ".DS_Store", "Thumbs.db",
-        ".env", ".env.local", ".env.production",
-        "*.log", "*.tmp", "*.temp",
-    ];
+    let upload_request = UploadInitialFilesRequest {
+        project_id: project.id,
+        files,
+    };
     
-    for pattern in &ignore_patterns {
-        if pattern.contains("*") {
-            // Simple wildcard matching
-            let pattern = pattern.replace("*", "");
-            if path_str.ends_with(&pattern) {
-                return true;
-            }
-        } else if path_str.contains(pattern) {
-            return true;
-        }
+    let upload_url = format!("{}/projects/{}/files", server_url, project.id);
+    let response = client
+        .post(&upload_url)
+        .json(&upload_request)
+        .send()
+        .await?
+        .check_session()?;
+    
+    if !response.status().is_success() {
+        return Err(http::api_error(response, "Failed to upload initial files").await);
     }
     
-    false
+    // Servers that don't check files only answer with a message
+    let upload_response: ApiResponse<serde_json::Value> = response.json().await?;
+    match upload_response.data.and_then(|data| serde_json::from_value::<UploadCompletion>(data).ok()) {
+        Some(completion) => print_upload_report(&completion),
+        None => print_success(&format!("Successfully uploaded {} files to server!", file_count)),
+    }
+    Ok(())
 }
 
 #[derive(Serialize)]
@@ -0,0 +1,234 @@
+//! `mothership history --graph`: draw the project's rifts and checkpoints as
+//! an ASCII graph, newest first, one column per rift.
+//!
+//! Each rift gets a lane next to the rift it was created from. Rows are
+//! checkpoints (`●`, or `◆` for merges, with a line from the merged rift) and
+//! the points where rifts branched off (`├─╯`).
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use colored::*;
+use mothership_common::{
+    protocol::{ApiResponse, HistoryGraph, HistoryGraphRift},
+    Checkpoint, RiftId,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::{config::ConfigManager, connections, get_http_client, http::{self, ResponseExt}, local, print_api_error, print_info};
+
+/// Lane colors, cycled through in lane order; conflict rifts are always red
+const LANE_COLORS: [Color; 5] = [Color::Green, Color::Blue, Color::Magenta, Color::Cyan, Color::Yellow];
+
+/// The parts of `.mothership/project.json` the graph needs
+#[derive(Deserialize)]
+struct ProjectMetadata {
+    project_id: String,
+    project_name: String,
+}
+
+/// Draw the history graph of the current project
+pub async fn handle_history_graph(config_manager: &ConfigManager, limit: usize) -> Result<()> {
+    if !config_manager.is_authenticated()? {
+        print_api_error("Not authenticated. Run 'mothership auth' to get started.");
+        return Ok(());
+    }
+
+    let project_file = crate::project_dir()?.join(".mothership").join("project.json");
+    if !project_file.exists() {
+        return Err(anyhow!(
+            "Not in a Mothership project directory.\n\
+            Run this command from a project directory, or use 'mothership beam <project>' to enter a project."
+        ));
+    }
+    let metadata: ProjectMetadata = serde_json::from_str(&std::fs::read_to_string(&project_file)?)?;
+
+    let config = config_manager.load_config()?;
+    let server_url = connections::get_active_server_url().unwrap_or(config.mothership_url.clone());
+    let client = get_http_client(&config);
+
+    let graph_url = format!("{}/projects/{}/history/graph?depth={}", server_url, metadata.project_id, limit);
+    let response = client.get(&graph_url).send().await?.check_session()?;
+    if !response.status().is_success() {
+        return Err(http::api_error(response, "Failed to load history graph").await);
+    }
+
+    let graph_response: ApiResponse<HistoryGraph> = response.json().await?;
+    let graph = graph_response.data.ok_or_else(|| {
+        anyhow!("No history data received: {}", graph_response.error.unwrap_or_else(|| "Unknown error".to_string()))
+    })?;
+
+    render(&metadata.project_name, &graph);
+    Ok(())
+}
+
+/// Draw the history graph of a local-only project
+pub fn handle_local_history_graph(limit: usize) -> Result<()> {
+    let (project_name, graph) = local::history_graph(&crate::project_dir()?, limit)?;
+    render(&project_name, &graph);
+    Ok(())
+}
+
+enum Row<'a> {
+    Checkpoint(&'a Checkpoint),
+    /// A rift branching off its parent at the given time
+    Fork(&'a HistoryGraphRift, DateTime<Utc>),
+}
+
+impl Row<'_> {
+    /// Sort key: newest first, and a rift's fork below its first checkpoint
+    fn order(&self) -> std::cmp::Reverse<(DateTime<Utc>, bool)> {
+        match self {
+            Row::Checkpoint(checkpoint) => std::cmp::Reverse((checkpoint.timestamp, true)),
+            Row::Fork(_, at) => std::cmp::Reverse((*at, false)),
+        }
+    }
+}
+
+/// Rifts in lane order: each rift followed by the rifts created from it
+fn lane_order(rifts: &[HistoryGraphRift]) -> Vec<&HistoryGraphRift> {
+    fn visit<'a>(rift: &'a HistoryGraphRift, rifts: &'a [HistoryGraphRift], order: &mut Vec<&'a HistoryGraphRift>) {
+        order.push(rift);
+        let mut children: Vec<_> = rifts.iter().filter(|r| r.parent_rift == Some(rift.id)).collect();
+        children.sort_by_key(|r| r.created_at);
+        for child in children {
+            visit(child, rifts, order);
+        }
+    }
+
+    // Rifts whose parent isn't visible are drawn as roots
+    let mut roots: Vec<_> = rifts.iter()
+        .filter(|r| !r.parent_rift.is_some_and(|p| rifts.iter().any(|other| other.id == p)))
+        .collect();
+    roots.sort_by_key(|r| r.created_at);
+
+    let mut order = Vec::new();
+    for root in roots {
+        visit(root, rifts, &mut order);
+    }
+    order
+}
+
+/// Lane glyphs for one row: `marks` are drawn as given, and the lanes
+/// between `span` are joined with horizontal lines
+fn lanes_row(
+    starts: &[DateTime<Utc>],
+    colors: &[Color],
+    at: DateTime<Utc>,
+    marks: &[(usize, &str)],
+    span: Option<(usize, usize, Color)>,
+) -> String {
+    let mut row = String::new();
+    for (i, start) in starts.iter().enumerate() {
+        let alive = *start <= at;
+        let in_span = span.is_some_and(|(from, to, _)| i > from && i < to);
+        let glyph = match marks.iter().find(|(lane, _)| *lane == i) {
+            Some((_, mark)) => mark.color(colors[i]).bold(),
+            None if in_span && alive => "┼".color(span.map(|(_, _, c)| c).unwrap_or(colors[i])),
+            None if in_span => "─".color(span.map(|(_, _, c)| c).unwrap_or(colors[i])),
+            None if alive => "│".color(colors[i]),
+            None => " ".normal(),
+        };
+        row.push_str(&glyph.to_string());
+
+        // Horizontal line to the next lane when it's inside the span
+        let joined = span.is_some_and(|(from, to, _)| i >= from && i < to);
+        if joined {
+            row.push_str(&"─".color(span.map(|(_, _, c)| c).unwrap_or(colors[i])).to_string());
+        } else {
+            row.push(' ');
+        }
+    }
+    row
+}
+
+fn render(project_name: &str, graph: &HistoryGraph) {
+    if graph.checkpoints.is_empty() {
+        print_info("No checkpoints found. Create your first checkpoint with 'mothership checkpoint \"message\"'");
+        return;
+    }
+
+    let lanes = lane_order(&graph.rifts);
+    let lane_of: HashMap<RiftId, usize> = lanes.iter().enumerate().map(|(i, r)| (r.id, i)).collect();
+    let colors: Vec<Color> = lanes.iter().enumerate()
+        .map(|(i, r)| if r.is_conflict_rift { Color::Red } else { LANE_COLORS[i % LANE_COLORS.len()] })
+        .collect();
+
+    // A lane starts when its rift was created, or at its first checkpoint if
+    // that's older (imported rifts are created after their history)
+    let starts: Vec<DateTime<Utc>> = lanes.iter()
+        .map(|rift| graph.checkpoints.iter()
+            .filter(|c| c.rift_id == rift.id)
+            .map(|c| c.timestamp)
+            .fold(rift.created_at, |start, t| start.min(t)))
+        .collect();
+
+    let mut rows: Vec<Row> = graph.checkpoints.iter()
+        .filter(|c| lane_of.contains_key(&c.rift_id))
+        .map(Row::Checkpoint)
+        .collect();
+    // Forks below the oldest checkpoint shown would only add noise
+    let oldest = graph.checkpoints.iter().map(|c| c.timestamp).min();
+    rows.extend(lanes.iter()
+        .zip(&starts)
+        .filter(|(r, _)| r.parent_rift.is_some_and(|p| lane_of.contains_key(&p)))
+        .filter(|(_, start)| oldest.is_some_and(|oldest| **start >= oldest))
+        .map(|(r, start)| Row::Fork(r, *start)));
+    rows.sort_by_key(|r| r.order());
+
+    println!("\n{}", "📈 History Graph".cyan().bold());
+    println!("{} rifts in {}", lanes.len(), project_name.blue().bold());
+    println!();
+
+    let mut labelled: Vec<RiftId> = Vec::new();
+    for row in &rows {
+        match row {
+            Row::Checkpoint(checkpoint) => {
+                let lane = lane_of[&checkpoint.rift_id];
+                let merged = checkpoint.merged_from.and_then(|r| lane_of.get(&r).copied());
+                let (marks, span) = match merged {
+                    Some(source) => {
+                        let edge = if source > lane { "┤" } else { "├" };
+                        (vec![(lane, "◆"), (source, edge)], Some((lane.min(source), lane.max(source), colors[source])))
+                    }
+                    None => (vec![(lane, "●")], None),
+                };
+
+                // The newest checkpoint of each rift carries its name
+                let rift = lanes[lane];
+                let decoration = if labelled.contains(&rift.id) {
+                    String::new()
+                } else {
+                    labelled.push(rift.id);
+                    format!(" ({})", rift.name).color(colors[lane]).bold().to_string()
+                };
+
+                println!("{}{} {}{} {}{}",
+                    lanes_row(&starts, &colors, checkpoint.timestamp, &marks, span),
+                    checkpoint.id.to_string()[..8].yellow(),
+                    checkpoint.message.as_deref().unwrap_or("(no message)").white(),
+                    decoration,
+                    crate::sync::format_time_ago(checkpoint.timestamp).dimmed(),
+                    if checkpoint.auto_generated { " [auto]" } else { "" }.dimmed()
+                );
+            }
+            Row::Fork(rift, at) => {
+                let lane = lane_of[&rift.id];
+                let Some(parent) = rift.parent_rift.and_then(|p| lane_of.get(&p).copied()) else {
+                    continue;
+                };
+                let (parent_mark, child_mark) = if lane > parent { ("├", "╯") } else { ("┤", "╰") };
+                let marks = [(parent, parent_mark), (lane, child_mark)];
+                let span = Some((lane.min(parent), lane.max(parent), colors[lane]));
+
+                let mut label = format!("rift '{}' created from '{}'", rift.name, lanes[parent].name);
+                if rift.is_conflict_rift {
+                    label.push_str(" ⚠️  conflict");
+                }
+                println!("{}{}", lanes_row(&starts, &colors, *at, &marks, span), label.dimmed());
+            }
+        }
+    }
+
+    println!("\n{}", "💡 Use 'mothership history --graph --limit <n>' to show more checkpoints per rift".dimmed());
+}
@@ -0,0 +1,110 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use mothership_common::{ApiResponse, ErrorCode};
+use reqwest::StatusCode;
+use std::fmt;
+use std::io::IsTerminal;
+
+use crate::auth;
+use crate::config::ConfigManager;
+
+/// The server turned down our credentials (HTTP 401)
+#[derive(Debug)]
+pub struct SessionExpired;
+
+impl fmt::Display for SessionExpired {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Your session has expired or was revoked. Run 'mothership auth' to sign in again")
+    }
+}
+
+impl std::error::Error for SessionExpired {}
+
+/// Checks applied to every authenticated response
+pub trait ResponseExt: Sized {
+    /// Fail with [`SessionExpired`] if the server rejected our credentials
+    fn check_session(self) -> Result<Self>;
+}
+
+impl ResponseExt for reqwest::Response {
+    fn check_session(self) -> Result<Self> {
+        if self.status() == StatusCode::UNAUTHORIZED {
+            return Err(SessionExpired.into());
+        }
+        Ok(self)
+    }
+}
+
+/// What the user can do about a failed request
+fn guidance(code: ErrorCode) -> Option<&'static str> {
+    match code {
+        ErrorCode::Unauthorized => Some("Run 'mothership auth' to sign in again"),
+        ErrorCode::Forbidden => Some("Ask the project or rift owner for access"),
+        ErrorCode::NotFound => Some("Check the name, or run 'mothership gateway' to see what you can access"),
+        ErrorCode::Conflict => Some("Something changed in the meantime; run 'mothership sync' or pick another name, then try again"),
+        ErrorCode::PayloadTooLarge => Some("Leave large files out with .mothershipignore"),
+        ErrorCode::QuotaExceeded => Some("The project is out of storage; delete old rifts or ask an admin to raise its quota"),
+        ErrorCode::RateLimited => Some("Too many requests; wait a moment and try again"),
+        ErrorCode::ReadOnly => Some("The project is archived or this server is a read-only replica; unarchive it or use the primary server"),
+        ErrorCode::Unavailable => Some("The server is temporarily unavailable; try again shortly"),
+        ErrorCode::BadRequest | ErrorCode::Internal => None,
+    }
+}
+
+/// A failed response's message, followed by what to do about it
+pub fn describe_failure<T>(response: &ApiResponse<T>) -> String {
+    let message = response.error.clone().unwrap_or_else(|| "Unknown error".to_string());
+    match response.code.and_then(guidance) {
+        Some(hint) => format!("{}\n💡 {}", message, hint),
+        None => message,
+    }
+}
+
+/// Turn an unsuccessful HTTP response into an error with the server's
+/// message and guidance for its error code
+pub async fn api_error(response: reqwest::Response, context: &str) -> anyhow::Error {
+    let status = response.status();
+    let status_code = ErrorCode::from_http_status(status.as_u16());
+    let body = response.text().await.unwrap_or_default();
+
+    // Servers predating error codes answer with a bare status or plain text
+    let failure = match serde_json::from_str::<ApiResponse<serde_json::Value>>(&body) {
+        Ok(failure) if failure.error.is_some() => ApiResponse { code: failure.code.or(Some(status_code)), ..failure },
+        _ if body.trim().is_empty() => ApiResponse::failure(status_code, status.to_string()),
+        _ => ApiResponse::failure(status_code, body),
+    };
+    anyhow!("{}: {}", context, describe_failure(&failure))
+}
+
+/// Whether a command failed because its session expired
+pub fn is_session_expired(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause.is::<SessionExpired>()
+            || cause
+                .downcast_ref::<reqwest::Error>()
+                .is_some_and(|e| e.status() == Some(StatusCode::UNAUTHORIZED))
+    })
+}
+
+/// Get fresh credentials after the server rejected ours: use the refresh
+/// token if there is one, otherwise sign in through the browser when someone
+/// is at the terminal. Returns whether the failed request is worth retrying.
+pub async fn renew_session(config_manager: &ConfigManager) -> Result<bool> {
+    match auth::refresh_credentials(config_manager).await {
+        Ok(true) => {
+            println!("{}", "🔄 Session refreshed".dimmed());
+            return Ok(true);
+        }
+        Ok(false) => {}
+        Err(e) => println!("{}", format!("⚠️  Could not refresh the session: {}", e).dimmed()),
+    }
+
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        return Ok(false);
+    }
+
+    println!("{}", "🔒 Your session has expired. Sign in again to continue.".yellow().bold());
+    auth::authenticate(config_manager, None).await?;
+    println!();
+    Ok(true)
+}
@@ -0,0 +1,101 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use mothership_common::protocol::{ApiResponse, InboxKind, InboxNotification, InboxPage};
+use serde::de::DeserializeOwned;
+use uuid::Uuid;
+
+use crate::{config::ConfigManager, get_http_client, http::{self, ResponseExt}, print_api_error, print_info, print_success, connections};
+
+/// Base URL of the user's notification endpoints
+fn notifications_url(config_manager: &ConfigManager) -> Result<String> {
+    let server_url = match connections::get_active_server_url() {
+        Some(server_url) => server_url,
+        None => config_manager.load_config()?.mothership_url,
+    };
+    Ok(format!("{}/users/me/notifications", server_url))
+}
+
+/// Send a request and read the data out of the response, printing the
+/// server's explanation when there is none
+async fn send<T: DeserializeOwned>(request: reqwest::RequestBuilder, context: &str) -> Result<Option<T>> {
+    let response = request.send().await?.check_session()?;
+    if !response.status().is_success() {
+        return Err(http::api_error(response, context).await);
+    }
+
+    let result: ApiResponse<T> = response.json().await?;
+    if result.data.is_none() {
+        print_api_error(&http::describe_failure(&result));
+    }
+    Ok(result.data)
+}
+
+fn kind_icon(kind: InboxKind) -> &'static str {
+    match kind {
+        InboxKind::Mention => "💬",
+        InboxKind::ReviewRequested => "👀",
+        InboxKind::ConflictRift => "🔀",
+        InboxKind::Invitation => "🤝",
+    }
+}
+
+fn print_notification(notification: &InboxNotification) {
+    let marker = if notification.is_read() { " ".normal() } else { "●".yellow().bold() };
+    let title = if notification.is_read() { notification.title.normal() } else { notification.title.white().bold() };
+    println!("\n{} {} {}", marker, kind_icon(notification.kind), title);
+    for line in notification.body.lines().take(3) {
+        println!("     {}", line.dimmed());
+    }
+    println!("     {} {}",
+        crate::sync::format_time_ago(notification.created_at).dimmed(),
+        notification.id.to_string().dimmed());
+}
+
+/// List notifications, unread ones only unless `all` is set
+pub async fn handle_list(config_manager: &ConfigManager, all: bool, limit: usize) -> Result<()> {
+    let client = get_http_client(&config_manager.load_config()?);
+    let url = notifications_url(config_manager)?;
+    let request = client.get(url).query(&[("unread", (!all).to_string()), ("limit", limit.to_string())]);
+
+    let Some(page) = send::<InboxPage>(request, "Failed to load notifications").await? else {
+        return Ok(());
+    };
+
+    if page.notifications.is_empty() {
+        print_info(if all { "No notifications yet" } else { "No unread notifications" });
+        return Ok(());
+    }
+
+    println!("{}", format!("🔔 Inbox ({} unread)", page.unread).cyan().bold());
+    for notification in &page.notifications {
+        print_notification(notification);
+    }
+    if page.unread > 0 {
+        println!();
+        print_info("Mark them read with 'mothership inbox --read-all'");
+    }
+    Ok(())
+}
+
+/// Mark one notification read, or all of them without an ID
+pub async fn handle_mark_read(config_manager: &ConfigManager, id: Option<String>) -> Result<()> {
+    let client = get_http_client(&config_manager.load_config()?);
+    let url = match &id {
+        Some(id) => {
+            let id = Uuid::parse_str(id)
+                .map_err(|_| anyhow!("Invalid notification ID. Use the full ID from 'mothership inbox'"))?;
+            format!("{}/{}/read", notifications_url(config_manager)?, id)
+        }
+        None => format!("{}/read", notifications_url(config_manager)?),
+    };
+
+    if let Some(marked) = send::<u64>(client.post(url), "Failed to mark notifications read").await? {
+        match (id, marked) {
+            (Some(_), 0) => print_info("Notification was already read"),
+            (Some(_), _) => print_success("Marked notification read"),
+            (None, 0) => print_info("No unread notifications"),
+            (None, marked) => print_success(&format!("Marked {} notification{} read", marked, if marked == 1 { "" } else { "s" })),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,759 @@
+//! Local-only mode: checkpoints and rifts kept in the project's
+//! `.mothership/local` directory while the CLI isn't connected to a server.
+//!
+//! History is recorded the way a server records it (full-snapshot
+//! checkpoints, content stored by hash), so `mothership push` can upload it
+//! as a project bundle once a server is available again.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use colored::*;
+use mothership_common::{
+    bundle::{BundleProject, BundleRift, ProjectBundle, BUNDLE_FORMAT_VERSION},
+    protocol::{ApiResponse, HistoryGraph, HistoryGraphRift, ProjectImportResult},
+    upload::content_hash,
+    metadata::{read_file, write_file},
+    worktree::{working_tree_status, FileStatus},
+    ChangeType, Checkpoint, CheckpointId, FileChange, FileMetadata, Hook, IgnoreMatcher, ProjectSettings, RiftId,
+};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+use walkdir::WalkDir;
+
+use crate::{config::ConfigManager, connections, get_http_client, http::{self, ResponseExt}, print_api_error, print_info, print_success, run_pre_hook, sync::{print_restore_preview, print_working_tree_status}};
+
+/// Directory under `.mothership` holding local history
+const LOCAL_DIR: &str = "local";
+
+/// The rift every local project starts in
+const MAIN_RIFT: &str = "main";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LocalRift {
+    id: RiftId,
+    name: String,
+    description: Option<String>,
+    parent_rift: Option<RiftId>,
+    created_at: DateTime<Utc>,
+    /// Files the rift started from (path -> content hash), used until its
+    /// first checkpoint
+    #[serde(default)]
+    base_files: HashMap<PathBuf, String>,
+}
+
+/// Everything recorded offline, saved as `.mothership/local/state.json`
+#[derive(Debug, Serialize, Deserialize)]
+struct LocalState {
+    current_rift: RiftId,
+    rifts: Vec<LocalRift>,
+    /// Oldest first
+    checkpoints: Vec<Checkpoint>,
+    /// How many of `checkpoints` have already been pushed to a server
+    #[serde(default)]
+    pushed: usize,
+}
+
+/// The parts of `.mothership/project.json` local mode needs. Projects created
+/// offline have an empty `mothership_url` until their first push.
+#[derive(Serialize, Deserialize)]
+struct ProjectMetadata {
+    project_id: String,
+    project_name: String,
+    created_at: String,
+    mothership_url: String,
+}
+
+/// A project's local history
+struct LocalStore {
+    project_dir: PathBuf,
+    root: PathBuf,
+    state: LocalState,
+}
+
+impl LocalStore {
+    /// Open the local history of the project in `project_dir`, starting an
+    /// empty one if there is none yet
+    fn open(project_dir: &Path) -> Result<Self> {
+        let root = project_dir.join(".mothership").join(LOCAL_DIR);
+        let state_path = root.join("state.json");
+
+        let state = if state_path.exists() {
+            serde_json::from_str(&fs::read_to_string(&state_path)?)
+                .map_err(|e| anyhow!("Local history in {} is corrupted: {}", root.display(), e))?
+        } else {
+            let main = LocalRift {
+                id: Uuid::new_v4(),
+                name: MAIN_RIFT.to_string(),
+                description: None,
+                parent_rift: None,
+                created_at: Utc::now(),
+                base_files: HashMap::new(),
+            };
+            LocalState {
+                current_rift: main.id,
+                rifts: vec![main],
+                checkpoints: Vec::new(),
+                pushed: 0,
+            }
+        };
+
+        Ok(Self {
+            project_dir: project_dir.to_path_buf(),
+            root,
+            state,
+        })
+    }
+
+    fn save(&self) -> Result<()> {
+        fs::create_dir_all(self.root.join("blobs"))?;
+        let json = serde_json::to_string_pretty(&self.state)?;
+        fs::write(self.root.join("state.json"), json)?;
+        Ok(())
+    }
+
+    /// Store file content by hash, returning the hash
+    fn store_blob(&self, content: &str) -> Result<String> {
+        let hash = content_hash(content.as_bytes());
+        let path = self.root.join("blobs").join(&hash);
+        if !path.exists() {
+            fs::create_dir_all(self.root.join("blobs"))?;
+            fs::write(&path, content)?;
+        }
+        Ok(hash)
+    }
+
+    fn load_blob(&self, hash: &str) -> Result<String> {
+        fs::read_to_string(self.root.join("blobs").join(hash))
+            .map_err(|_| anyhow!("Local content {} is missing", hash))
+    }
+
+    fn current_rift(&self) -> &LocalRift {
+        self.state.rifts
+            .iter()
+            .find(|r| r.id == self.state.current_rift)
+            .unwrap_or(&self.state.rifts[0])
+    }
+
+    fn find_rift(&self, name: &str) -> Option<&LocalRift> {
+        self.state.rifts.iter().find(|r| r.name == name)
+    }
+
+    /// Checkpoints of a rift, oldest first
+    fn rift_checkpoints(&self, rift_id: RiftId) -> impl DoubleEndedIterator<Item = &Checkpoint> {
+        self.state.checkpoints.iter().filter(move |c| c.rift_id == rift_id)
+    }
+
+    /// Files of a rift's latest checkpoint (path -> content hash)
+    fn latest_files(&self, rift_id: RiftId) -> HashMap<PathBuf, String> {
+        match self.rift_checkpoints(rift_id).last() {
+            Some(checkpoint) => checkpoint_files(checkpoint),
+            None => self.state.rifts
+                .iter()
+                .find(|r| r.id == rift_id)
+                .map(|r| r.base_files.clone())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Metadata of the files of a rift's latest checkpoint
+    fn latest_metadata(&self, rift_id: RiftId) -> HashMap<PathBuf, FileMetadata> {
+        self.rift_checkpoints(rift_id)
+            .last()
+            .map(|checkpoint| checkpoint.changes.iter().map(|c| (c.path.clone(), c.metadata.clone())).collect())
+            .unwrap_or_default()
+    }
+
+    /// Find a checkpoint by ID or unambiguous ID prefix
+    fn find_checkpoint(&self, id: &str) -> Result<&Checkpoint> {
+        let mut matches = self.state.checkpoints.iter().filter(|c| c.id.to_string().starts_with(id));
+        match (matches.next(), matches.next()) {
+            (Some(checkpoint), None) if !id.is_empty() => Ok(checkpoint),
+            (Some(_), Some(_)) => Err(anyhow!("Checkpoint ID '{}' is ambiguous; use more characters", id)),
+            _ => Err(anyhow!("No local checkpoint matches '{}'", id)),
+        }
+    }
+
+    /// Snapshot the working directory into a new checkpoint on the current
+    /// rift. Returns `None` if nothing changed since the rift's last checkpoint.
+    fn checkpoint(&mut self, message: String) -> Result<Option<Checkpoint>> {
+        let rift_id = self.state.current_rift;
+        let previous = self.latest_files(rift_id);
+        let previous_metadata = self.latest_metadata(rift_id);
+        let files = scan_working_files(&self.project_dir)?;
+
+        let mut changes = Vec::new();
+        for (path, content) in files {
+            let content_hash = self.store_blob(&content)?;
+            let change_type = if previous.contains_key(&path) { ChangeType::Modified } else { ChangeType::Created };
+            changes.push(FileChange {
+                metadata: FileMetadata::read(&self.project_dir.join(&path)).unwrap_or_default(),
+                path,
+                change_type,
+                content_hash,
+                diff: None,
+                size: content.len() as u64,
+            });
+        }
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let unchanged = changes.len() == previous.len()
+            && changes.iter().all(|c| {
+                previous.get(&c.path) == Some(&c.content_hash)
+                    && !c.metadata.differs_from(&previous_metadata.get(&c.path).cloned().unwrap_or_default())
+            });
+        if unchanged && self.rift_checkpoints(rift_id).next().is_some() {
+            return Ok(None);
+        }
+
+        let checkpoint = Checkpoint {
+            id: Uuid::new_v4(),
+            rift_id,
+            // Local checkpoints have no author yet; the server attributes them to whoever pushes them
+            author: Uuid::nil(),
+            timestamp: Utc::now(),
+            changes,
+            parent: self.rift_checkpoints(rift_id).last().map(|c| c.id),
+            message: Some(message),
+            auto_generated: false,
+            merged_from: None,
+            squashed: 0,
+        };
+        self.state.checkpoints.push(checkpoint.clone());
+        self.save()?;
+
+        Ok(Some(checkpoint))
+    }
+
+    /// Whether the working directory differs from the current rift's last checkpoint
+    fn has_uncheckpointed_changes(&self) -> Result<bool> {
+        Ok(!self.working_tree_status()?.is_empty())
+    }
+
+    /// Files created, modified or deleted since the current rift's last checkpoint
+    fn working_tree_status(&self) -> Result<Vec<(PathBuf, FileStatus)>> {
+        let previous = self.latest_files(self.state.current_rift);
+        let files: HashMap<PathBuf, String> = scan_working_files(&self.project_dir)?
+            .into_iter()
+            .map(|(path, content)| (path, content_hash(content.as_bytes())))
+            .collect();
+        Ok(working_tree_status(&previous, &files))
+    }
+
+    /// Create a rift branching from the current rift's last checkpoint
+    fn create_rift(&mut self, name: &str, description: Option<String>) -> Result<()> {
+        if self.find_rift(name).is_some() {
+            return Err(anyhow!("A rift named '{}' already exists", name));
+        }
+
+        let parent = self.state.current_rift;
+        self.state.rifts.push(LocalRift {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            description,
+            parent_rift: Some(parent),
+            created_at: Utc::now(),
+            base_files: self.latest_files(parent),
+        });
+        self.save()
+    }
+
+    /// Make `name` the current rift and replace the working files with its
+    /// last checkpoint
+    fn switch_rift(&mut self, name: &str) -> Result<()> {
+        let target = self.find_rift(name)
+            .ok_or_else(|| anyhow!("Rift '{}' not found", name))?
+            .id;
+        let current_files = self.latest_files(self.state.current_rift);
+        let target_files = self.latest_files(target);
+        let target_metadata = self.latest_metadata(target);
+
+        for path in current_files.keys().filter(|path| !target_files.contains_key(*path)) {
+            let _ = fs::remove_file(self.project_dir.join(path));
+        }
+        for (path, hash) in &target_files {
+            write_project_file(&self.project_dir, path, &self.load_blob(hash)?, target_metadata.get(path))?;
+        }
+
+        self.state.current_rift = target;
+        self.save()
+    }
+
+    /// Checkpoints not yet pushed, with the rifts and content they need
+    fn to_bundle(&self, metadata: &ProjectMetadata) -> Result<ProjectBundle> {
+        let mut blobs = HashMap::new();
+        let mut rifts = Vec::new();
+
+        // Rifts are only ever created from an existing one, so parents come first
+        for rift in &self.state.rifts {
+            let live_files = self.latest_files(rift.id);
+            for hash in live_files.values() {
+                blobs.insert(hash.clone(), self.load_blob(hash)?);
+            }
+            rifts.push(BundleRift {
+                id: rift.id,
+                name: rift.name.clone(),
+                parent_rift: rift.parent_rift,
+                created_at: rift.created_at,
+                live_files,
+                // Local rifts don't record one; the server falls back to the parent's history
+                fork_point: None,
+            });
+        }
+
+        let checkpoints = self.state.checkpoints[self.state.pushed..].to_vec();
+        for change in checkpoints.iter().flat_map(|c| &c.changes) {
+            if !blobs.contains_key(&change.content_hash) {
+                blobs.insert(change.content_hash.clone(), self.load_blob(&change.content_hash)?);
+            }
+        }
+
+        Ok(ProjectBundle {
+            format_version: BUNDLE_FORMAT_VERSION,
+            exported_at: Utc::now(),
+            project: BundleProject {
+                name: metadata.project_name.clone(),
+                description: String::new(),
+                created_at: DateTime::parse_from_rfc3339(&metadata.created_at)
+                    .map(|t| t.with_timezone(&Utc))
+                    .unwrap_or_else(|_| Utc::now()),
+                settings: ProjectSettings::default(),
+            },
+            rifts,
+            checkpoints,
+            authors: HashMap::new(),
+            blobs,
+        })
+    }
+
+    fn unpushed(&self) -> usize {
+        self.state.checkpoints.len() - self.state.pushed
+    }
+}
+
+/// Files of a checkpoint (path -> content hash)
+fn checkpoint_files(checkpoint: &Checkpoint) -> HashMap<PathBuf, String> {
+    checkpoint.changes
+        .iter()
+        .map(|c| (c.path.clone(), c.content_hash.clone()))
+        .collect()
+}
+
+/// The current rift's name, latest checkpoint and that checkpoint's files
+/// (path -> content), which local changes are stashed against
+pub(crate) fn stash_base(project_dir: &Path) -> Result<(String, Option<CheckpointId>, HashMap<PathBuf, String>)> {
+    let store = LocalStore::open(project_dir)?;
+    let rift = store.current_rift();
+
+    let mut files = HashMap::new();
+    for (path, hash) in store.latest_files(rift.id) {
+        files.insert(path, store.load_blob(&hash)?);
+    }
+
+    Ok((rift.name.clone(), store.rift_checkpoints(rift.id).last().map(|c| c.id), files))
+}
+
+/// Checkpoints of the current rift, oldest first
+pub(crate) fn rift_history(project_dir: &Path) -> Result<Vec<Checkpoint>> {
+    let store = LocalStore::open(project_dir)?;
+    Ok(store.rift_checkpoints(store.state.current_rift).cloned().collect())
+}
+
+/// The project's name and its local rifts and checkpoints (at most `depth`
+/// per rift) as a history graph
+pub(crate) fn history_graph(project_dir: &Path, depth: usize) -> Result<(String, HistoryGraph)> {
+    let metadata = read_project_metadata(project_dir)?;
+    let store = LocalStore::open(project_dir)?;
+
+    let mut checkpoints: Vec<Checkpoint> = store.state.rifts.iter()
+        .flat_map(|rift| store.rift_checkpoints(rift.id).rev().take(depth).cloned())
+        .collect();
+    checkpoints.sort_by_key(|checkpoint| Reverse(checkpoint.timestamp));
+
+    let rifts = store.state.rifts.iter()
+        .map(|rift| HistoryGraphRift {
+            id: rift.id,
+            name: rift.name.clone(),
+            parent_rift: rift.parent_rift,
+            created_at: rift.created_at,
+            is_conflict_rift: false,
+        })
+        .collect();
+
+    Ok((metadata.project_name, HistoryGraph { rifts, checkpoints }))
+}
+
+/// Files of a local checkpoint (path -> content)
+pub(crate) fn checkpoint_contents(project_dir: &Path, checkpoint_id: CheckpointId) -> Result<HashMap<PathBuf, String>> {
+    let store = LocalStore::open(project_dir)?;
+    let checkpoint = store.state.checkpoints.iter()
+        .find(|c| c.id == checkpoint_id)
+        .ok_or_else(|| anyhow!("No local checkpoint {}", checkpoint_id))?;
+
+    let mut files = HashMap::new();
+    for (path, hash) in checkpoint_files(checkpoint) {
+        files.insert(path, store.load_blob(&hash)?);
+    }
+    Ok(files)
+}
+
+/// Text files in the project, honouring .mothershipignore and the default
+/// ignore patterns. Symlinks are read as their target path.
+pub(crate) fn scan_working_files(project_dir: &Path) -> Result<HashMap<PathBuf, String>> {
+    let ignore_matcher = IgnoreMatcher::load(project_dir);
+    let mut files = HashMap::new();
+
+    for entry in WalkDir::new(project_dir)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|e| !ignore_matcher.is_ignored(e.path(), e.file_type().is_dir()))
+    {
+        let entry = entry?;
+        if !entry.file_type().is_file() && !entry.file_type().is_symlink() {
+            continue;
+        }
+        if let Ok(relative_path) = entry.path().strip_prefix(project_dir) {
+            // Binary files aren't tracked, as with server-side sync
+            if let Ok(content) = read_file(entry.path()) {
+                files.insert(relative_path.to_path_buf(), content);
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+fn write_project_file(project_dir: &Path, path: &Path, content: &str, metadata: Option<&FileMetadata>) -> Result<()> {
+    write_file(&project_dir.join(path), content, metadata)?;
+    Ok(())
+}
+
+fn read_project_metadata(project_dir: &Path) -> Result<ProjectMetadata> {
+    let project_file = project_dir.join(".mothership").join("project.json");
+    if !project_file.exists() {
+        return Err(anyhow!(
+            "Not in a Mothership project directory.\n\
+            Run 'mothership init' to start a local project here."
+        ));
+    }
+    Ok(serde_json::from_str(&fs::read_to_string(&project_file)?)?)
+}
+
+/// Open the local history of the project in the current directory
+fn open_current() -> Result<(ProjectMetadata, LocalStore)> {
+    let project_dir = crate::project_dir()?;
+    let metadata = read_project_metadata(&project_dir)?;
+    let store = LocalStore::open(&project_dir)?;
+    Ok((metadata, store))
+}
+
+/// Start a project in the current directory without a server
+pub fn handle_init(name: String) -> Result<()> {
+    let project_dir = std::env::current_dir()?;
+    let mothership_dir = project_dir.join(".mothership");
+    if mothership_dir.join("project.json").exists() {
+        print_info("This directory is already a Mothership project");
+        return Ok(());
+    }
+
+    fs::create_dir_all(&mothership_dir)?;
+    let metadata = ProjectMetadata {
+        project_id: Uuid::new_v4().to_string(),
+        project_name: name.clone(),
+        created_at: Utc::now().to_rfc3339(),
+        mothership_url: String::new(),
+    };
+    fs::write(mothership_dir.join("project.json"), serde_json::to_string_pretty(&metadata)?)?;
+    LocalStore::open(&project_dir)?.save()?;
+
+    print_success(&format!("Initialized local project '{}'", name));
+    print_info("Create checkpoints with 'mothership checkpoint \"message\"'");
+    print_info("Run 'mothership push' after connecting to a server to upload them");
+    Ok(())
+}
+
+/// Show the local project, its current rift, what changed since the last
+/// checkpoint and what hasn't been pushed
+pub fn handle_status(porcelain: bool) -> Result<()> {
+    let (metadata, store) = open_current()?;
+    let changes = store.working_tree_status()?;
+    if porcelain {
+        print_working_tree_status(&changes, true);
+        return Ok(());
+    }
+
+    println!("\n{} {}", "Project:".bold(), metadata.project_name.blue().bold());
+    println!("{} {}", "Mode:".bold(), "local-only".yellow());
+    println!("{} {}", "Rift:".bold(), store.current_rift().name.green());
+    println!("{} {}", "Unpushed Checkpoints:".bold(), store.unpushed());
+    print_working_tree_status(&changes, false);
+
+    let recent: Vec<&Checkpoint> = store.rift_checkpoints(store.state.current_rift).rev().take(3).collect();
+    if !recent.is_empty() {
+        println!("\n{}", "Recent Checkpoints:".bold());
+        for checkpoint in recent {
+            let age = crate::sync::format_time_ago(checkpoint.timestamp);
+            let message = checkpoint.message.as_deref().unwrap_or("(no message)");
+            println!("  {} {} {}", checkpoint.id.to_string()[..8].yellow(), message.white(), age.dimmed());
+        }
+    }
+
+    Ok(())
+}
+
+/// Checkpoint the working directory into the local history
+pub fn handle_checkpoint(message: Option<String>) -> Result<()> {
+    let (metadata, mut store) = open_current()?;
+    let message = message.unwrap_or_else(|| "Manual checkpoint".to_string());
+
+    run_pre_hook(Hook::PreCheckpoint, &[
+        ("MOTHERSHIP_PROJECT_ID", metadata.project_id.clone()),
+        ("MOTHERSHIP_PROJECT_NAME", metadata.project_name.clone()),
+        ("MOTHERSHIP_CHECKPOINT_MESSAGE", message.clone()),
+    ])?;
+
+    print_info(&format!("Creating local checkpoint for {}: {}", metadata.project_name, message));
+    match store.checkpoint(message)? {
+        Some(checkpoint) => {
+            print_success(&format!("Checkpoint {} created locally", &checkpoint.id.to_string()[..8]));
+            print_info(&format!("📸 Captured {} files", checkpoint.changes.len()));
+        }
+        None => print_info("No changes since the last checkpoint"),
+    }
+
+    Ok(())
+}
+
+/// List the current rift's local checkpoints, newest first
+pub fn handle_history(limit: usize) -> Result<()> {
+    let (metadata, store) = open_current()?;
+    let rift = store.current_rift();
+    let checkpoints: Vec<&Checkpoint> = store.rift_checkpoints(rift.id).rev().take(limit).collect();
+
+    if checkpoints.is_empty() {
+        print_info("No local checkpoints yet. Create one with 'mothership checkpoint \"message\"'");
+        return Ok(());
+    }
+
+    println!("\n{}", "📜 Local History".cyan().bold());
+    println!("Showing {} most recent checkpoints for {} ({})", checkpoints.len(), metadata.project_name.blue().bold(), rift.name);
+
+    let pushed: Vec<_> = store.state.checkpoints[..store.state.pushed].iter().map(|c| c.id).collect();
+    for (i, checkpoint) in checkpoints.iter().enumerate() {
+        let age = crate::sync::format_time_ago(checkpoint.timestamp);
+        let message = checkpoint.message.as_deref().unwrap_or("(no message)");
+        let pushed_marker = if pushed.contains(&checkpoint.id) { " [pushed]" } else { "" };
+
+        println!("\n{} {} {}{}",
+            if i == 0 { "●".green() } else { "○".dimmed() },
+            checkpoint.id.to_string()[..8].yellow().bold(),
+            message.white(),
+            pushed_marker.dimmed()
+        );
+        println!("   {} • {} file{}",
+            age.dimmed(),
+            checkpoint.changes.len(),
+            if checkpoint.changes.len() == 1 { "" } else { "s" }
+        );
+    }
+
+    println!("\n{}", "💡 Use 'mothership restore <checkpoint-id>' to restore to a specific point".dimmed());
+    Ok(())
+}
+
+/// Restore files from a local checkpoint
+pub fn handle_restore(checkpoint_id: String, paths: Vec<String>, force: bool, dry_run: bool) -> Result<()> {
+    let (metadata, mut store) = open_current()?;
+    let checkpoint = store.find_checkpoint(&checkpoint_id)?.clone();
+
+    // Paths are given relative to the current directory
+    let paths = crate::project_relative_paths(&store.project_dir, &paths)?;
+
+    if dry_run {
+        let mut files = HashMap::new();
+        for change in &checkpoint.changes {
+            if paths.is_empty() || paths.iter().any(|p| change.path.starts_with(p)) {
+                files.insert(change.path.clone(), store.load_blob(&change.content_hash)?);
+            }
+        }
+        println!("Project: {}", metadata.project_name.blue().bold());
+        println!("Checkpoint: {}", checkpoint.id.to_string().yellow());
+        return print_restore_preview(&store.project_dir, &files, &paths);
+    }
+
+    if !force {
+        println!("\n{}", "⚠️  This will overwrite your current files with the checkpoint state.".yellow().bold());
+        println!("Project: {}", metadata.project_name.blue().bold());
+        println!("Checkpoint: {}", checkpoint.id.to_string().yellow());
+        print!("{}", "Are you sure you want to continue? (y/N): ".white().bold());
+        io::stdout().flush()?;
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+
+        if !input.trim().to_lowercase().starts_with('y') {
+            print_info("Restore cancelled.");
+            return Ok(());
+        }
+    }
+
+    run_pre_hook(Hook::PreRestore, &[
+        ("MOTHERSHIP_PROJECT_ID", metadata.project_id.clone()),
+        ("MOTHERSHIP_PROJECT_NAME", metadata.project_name.clone()),
+        ("MOTHERSHIP_CHECKPOINT_ID", checkpoint.id.to_string()),
+        ("MOTHERSHIP_RESTORE_PATHS", paths.iter().map(|p| p.display().to_string()).collect::<Vec<_>>().join("\n")),
+    ])?;
+
+    // Keep what's there now, so the restore can be undone
+    let short_id = checkpoint.id.to_string()[..8].to_string();
+    if let Some(safety) = store.checkpoint(format!("Before restoring checkpoint {}", short_id))? {
+        print_info(&format!("🛟 Saved your current files as checkpoint {}; undo with 'mothership restore {}'", &safety.id.to_string()[..8], safety.id));
+    }
+
+    let mut restored = 0;
+    for change in &checkpoint.changes {
+        let path = &change.path;
+        if !paths.is_empty() && !paths.iter().any(|p| path.starts_with(p)) {
+            continue;
+        }
+        write_project_file(&store.project_dir, path, &store.load_blob(&change.content_hash)?, Some(&change.metadata))?;
+        print_info(&format!("Restored: {}", path.display()));
+        restored += 1;
+    }
+
+    print_success(&format!("Restored {} files from checkpoint {}", restored, short_id));
+    Ok(())
+}
+
+/// List local rifts, marking the current one
+pub fn handle_rift_list(detailed: bool) -> Result<()> {
+    let (_, store) = open_current()?;
+
+    println!("\nAvailable Rifts (local):");
+    for rift in &store.state.rifts {
+        let marker = if rift.id == store.state.current_rift { "*" } else { "-" };
+        println!("{} {}", marker, rift.name);
+        if detailed {
+            if let Some(desc) = &rift.description {
+                println!("  Description: {}", desc);
+            }
+            println!("  Created: {}", rift.created_at.format("%Y-%m-%d %H:%M:%S"));
+            println!("  Checkpoints: {}", store.rift_checkpoints(rift.id).count());
+        }
+    }
+
+    Ok(())
+}
+
+pub fn handle_rift_new(name: String, description: Option<String>) -> Result<()> {
+    let (_, mut store) = open_current()?;
+    store.create_rift(&name, description)?;
+
+    println!("✨ Created new local rift: {}", name);
+    println!("🔀 Switch to it with: mothership rift switch \"{}\"", name);
+    Ok(())
+}
+
+pub fn handle_rift_switch(name: String) -> Result<()> {
+    let (_, mut store) = open_current()?;
+
+    if store.current_rift().name == name {
+        println!("Already in rift: {}", name);
+        return Ok(());
+    }
+    if store.has_uncheckpointed_changes()? {
+        return Err(anyhow!("You have changes since the last checkpoint; create a checkpoint before switching rifts"));
+    }
+
+    store.switch_rift(&name)?;
+    println!("🔄 Switched to local rift: {}", name);
+    Ok(())
+}
+
+/// Upload local checkpoints and rifts to the connected server. Projects
+/// started offline are created there; others get the history appended.
+pub async fn handle_push(config_manager: &ConfigManager) -> Result<()> {
+    let project_dir = crate::project_dir()?;
+    let metadata = read_project_metadata(&project_dir)?;
+    let mut store = LocalStore::open(&project_dir)?;
+
+    if store.unpushed() == 0 {
+        print_info("No local checkpoints to push");
+        return Ok(());
+    }
+
+    let config = config_manager.load_config()?;
+    let server_url = connections::get_active_server_url().unwrap_or(config.mothership_url.clone());
+    let client = get_http_client(&config);
+    let bytes = store.to_bundle(&metadata)?.encode()?;
+
+    let is_new = metadata.mothership_url.is_empty();
+    let request = if is_new {
+        print_info(&format!("Creating '{}' on {}...", metadata.project_name, server_url));
+        client
+            .post(format!("{}/projects/import", server_url))
+            .query(&[("name", &metadata.project_name)])
+    } else {
+        print_info(&format!("Pushing {} checkpoints to {}...", store.unpushed(), server_url));
+        client.post(format!("{}/projects/{}/push", server_url, metadata.project_id))
+    };
+
+    let response = request
+        .header(reqwest::header::CONTENT_TYPE, "application/gzip")
+        .body(bytes)
+        .send()
+        .await?
+        .check_session()?;
+    match response.status() {
+        reqwest::StatusCode::PAYLOAD_TOO_LARGE => {
+            print_api_error("Local history is larger than the server accepts");
+            return Ok(());
+        }
+        reqwest::StatusCode::NOT_FOUND => {
+            print_api_error(&format!("Project '{}' doesn't exist on {}", metadata.project_name, server_url));
+            print_info(&format!("It was created on {}; connect to that server to push", metadata.mothership_url));
+            return Ok(());
+        }
+        reqwest::StatusCode::LOCKED => {
+            print_api_error("The project is archived; unarchive it before pushing");
+            return Ok(());
+        }
+        status if !status.is_success() => {
+            return Err(anyhow!("Failed to push: {}", status));
+        }
+        _ => {}
+    }
+
+    let result: ApiResponse<ProjectImportResult> = response.json().await?;
+    let Some(pushed) = result.data else {
+        print_api_error(&http::describe_failure(&result));
+        return Ok(());
+    };
+
+    // The project now lives on the server; point project.json at it, keeping
+    // any other fields beam wrote
+    if is_new {
+        let project_file = project_dir.join(".mothership").join("project.json");
+        let mut json: serde_json::Value = serde_json::from_str(&fs::read_to_string(&project_file)?)?;
+        json["project_id"] = serde_json::Value::String(pushed.project_id.to_string());
+        json["mothership_url"] = serde_json::Value::String(server_url.clone());
+        fs::write(&project_file, serde_json::to_string_pretty(&json)?)?;
+    }
+
+    store.state.pushed = store.state.checkpoints.len();
+    store.save()?;
+
+    print_success(&format!(
+        "Pushed {} checkpoints ({} rifts) to '{}'",
+        pushed.checkpoints, pushed.rifts, pushed.name
+    ));
+    if is_new {
+        println!("{}", format!("Start syncing with: mothership beam \"{}\"", pushed.name).dimmed());
+    }
+
+    Ok(())
+}
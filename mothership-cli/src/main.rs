@@ -1,21 +1,44 @@
-use mothership_common::ClientConfig;
+use mothership_common::{
+    features::Feature,
+    hooks,
+    protocol::{RenameRiftRequest, RiftDeletion},
+    profile, tls, ApiResponse, ClientConfig, Hook, TlsSettings,
+};
 use clap::{Parser, Subcommand};
 use anyhow::{Result, anyhow};
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 use serde::{Serialize, Deserialize};
 use colored::Colorize;
 
+mod activity;
 mod auth;
 mod beam;
+mod bisect;
+mod bundles;
+mod chat;
 mod config;
 mod connections;
+mod daemon_client;
+mod file_history;
+mod graph;
+mod http;
 mod gateway;
+mod inbox;
+mod local;
+mod profiles;
+mod reviews;
+mod rift_access;
+mod sessions;
+mod stash;
 mod sync;
+mod terminal_share;
+mod tokens;
 mod update;
 
 use crate::config::ConfigManager;
+use crate::http::ResponseExt;
 
 #[derive(Parser)]
 #[command(name = "mothership")]
@@ -26,13 +49,24 @@ struct Cli {
     command: Commands,
 }
 
-#[derive(Subcommand)]
+#[derive(Clone, Subcommand)]
 enum Commands {
     /// Authenticate this machine with Mothership
     Auth {
         /// Authentication method
         #[clap(subcommand)]
         method: Option<AuthMethod>,
+        /// Sign in as (or act as) this named profile instead of the active one
+        #[arg(long, global = true)]
+        profile: Option<String>,
+        /// Server to sign in to, e.g. for a new profile (defaults to the active server)
+        #[arg(long)]
+        server: Option<String>,
+    },
+    /// Named authentication profiles (server + account)
+    Profile {
+        #[command(subcommand)]
+        action: ProfileAction,
     },
     /// Gateway operations (list, create projects)
     Gateway {
@@ -57,9 +91,21 @@ enum Commands {
         /// Local directory to use (required for new projects)
         #[arg(long)]
         local_dir: Option<PathBuf>,
+
+        /// Only sync this file or directory (repeatable; '.' syncs everything again)
+        #[arg(long = "path")]
+        paths: Vec<String>,
+
+        /// Watch the rift without sending changes (for demos and spectators)
+        #[arg(long)]
+        observe: bool,
     },
     /// Status of current Mothership environment
-    Status,
+    Status {
+        /// Only list changed files, one per line with an A/M/D code, for scripts
+        #[arg(long)]
+        porcelain: bool,
+    },
     /// Create a checkpoint (commit changes)
     Checkpoint {
         /// Checkpoint message
@@ -67,19 +113,92 @@ enum Commands {
     },
     /// Sync with remote Mothership
     Sync,
+    /// Upload checkpoints made in local-only mode to the connected server
+    Push,
     /// View project history and checkpoints
     History {
+        #[command(subcommand)]
+        action: Option<HistoryAction>,
+
+        /// Limit number of checkpoints to show
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+
+        /// Fetch the next page of older checkpoints after those already shown
+        #[arg(long)]
+        deepen: bool,
+
+        /// Draw every rift's checkpoints as a graph, with branches and merges
+        #[arg(long, conflicts_with = "deepen")]
+        graph: bool,
+
+        /// Show the comment threads on each checkpoint
+        #[arg(long, conflicts_with = "graph")]
+        comments: bool,
+    },
+    /// Show recent activity across every rift of the project
+    Activity {
+        /// Limit number of events to show
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+
+        /// Only show events older than this time (RFC 3339), to page back
+        #[arg(long)]
+        before: Option<chrono::DateTime<chrono::Utc>>,
+    },
+    /// Show the checkpoints that changed a file
+    Log {
+        /// File to show the history of
+        file: PathBuf,
+
         /// Limit number of checkpoints to show
         #[arg(short, long, default_value = "20")]
         limit: usize,
     },
+    /// Show which checkpoint and author introduced each line of a file
+    Blame {
+        /// File to annotate
+        file: PathBuf,
+
+        /// Annotate the file as of this checkpoint instead of the latest one
+        #[arg(long)]
+        checkpoint: Option<String>,
+    },
+    /// Chat with collaborators in the current rift
+    Chat {
+        /// Message to send (shows recent messages if omitted)
+        message: Option<String>,
+        /// Number of recent messages to show
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+        /// Keep watching for new messages
+        #[arg(short, long)]
+        follow: bool,
+    },
+    /// Stream a command's terminal into the current rift for collaborators to watch
+    ShareTerminal {
+        /// Command to run (defaults to your shell)
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        command: Vec<String>,
+    },
     /// Restore to a specific checkpoint
     Restore {
         /// Checkpoint ID to restore to
         checkpoint_id: String,
+        /// Restore only this file or directory, relative to the current directory (repeatable)
+        #[arg(short, long = "path")]
+        paths: Vec<String>,
         /// Force restore without confirmation
         #[arg(short, long)]
         force: bool,
+        /// Show which files would be added or modified, with line counts, without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Undo a checkpoint's changes with a new checkpoint
+    Revert {
+        /// Checkpoint ID to revert
+        checkpoint_id: String,
     },
     /// Delete a gateway project
     Delete {
@@ -89,10 +208,38 @@ enum Commands {
         #[arg(short, long)]
         force: bool,
     },
+    /// Export a project and its history to a portable bundle file
+    Export {
+        /// Project name to export
+        project_name: String,
+        /// Output file (defaults to <project>.mothership)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Create a project from a bundle file
+    Import {
+        /// Bundle file produced by 'mothership export'
+        file: PathBuf,
+        /// Name for the new project (defaults to the exported name)
+        #[arg(short, long)]
+        name: Option<String>,
+    },
     /// Connect to a Mothership server
     Connect {
         /// Server URL (e.g., https://mothership.company.com)
         server_url: String,
+        /// PEM file of CA certificates to trust, for servers with an internal CA
+        #[arg(long)]
+        ca_bundle: Option<PathBuf>,
+        /// Accept any certificate (self-signed or expired); only for testing
+        #[arg(long)]
+        insecure: bool,
+        /// PEM client certificate, for servers that require one
+        #[arg(long, requires = "client_key")]
+        client_cert: Option<PathBuf>,
+        /// PEM PKCS#8 private key of --client-cert
+        #[arg(long, requires = "client_cert")]
+        client_key: Option<PathBuf>,
     },
     /// Disconnect from the current Mothership server (switch to local-only mode)
     Server {
@@ -116,23 +263,241 @@ enum Commands {
         #[command(subcommand)]
         action: RiftAction,
     },
+    /// Set aside changes made since the last checkpoint
+    Stash {
+        #[command(subcommand)]
+        action: StashAction,
+    },
+    /// Binary-search checkpoints for the one that introduced a regression
+    Bisect {
+        #[command(subcommand)]
+        action: BisectAction,
+    },
+    /// Ask for a review before merging a rift
+    Review {
+        #[command(subcommand)]
+        action: ReviewAction,
+    },
+    /// Show your mentions, review requests and other notifications
+    Inbox {
+        /// Include notifications already read
+        #[arg(short, long)]
+        all: bool,
+        /// Number of notifications to show
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+        /// Mark a notification read
+        #[arg(long, value_name = "ID", conflicts_with = "read_all")]
+        read: Option<String>,
+        /// Mark every notification read
+        #[arg(long)]
+        read_all: bool,
+    },
 }
 
-#[derive(Subcommand)]
+#[derive(Clone, Subcommand)]
+pub enum HistoryAction {
+    /// Fold runs of old automatic checkpoints into one checkpoint each
+    Compact {
+        /// Only fold checkpoints older than this many days (defaults to the server's setting)
+        #[arg(long)]
+        older_than_days: Option<u32>,
+    },
+}
+
+#[derive(Clone, Subcommand)]
 pub enum AuthMethod {
     /// Login with Google OAuth
     Google,
     /// Login with GitHub OAuth
     Github,
+    /// Manage personal access tokens for CI and scripting
+    Token {
+        #[command(subcommand)]
+        action: TokenAction,
+    },
+    /// Manage the machines you are logged in on
+    Sessions {
+        #[command(subcommand)]
+        action: SessionAction,
+    },
+    /// List or revoke the machines you have logged in from
+    Machines {
+        #[command(subcommand)]
+        action: MachineAction,
+    },
+}
+
+#[derive(Clone, Subcommand)]
+pub enum TokenAction {
+    /// Create a new personal access token
+    Create {
+        /// Name describing where the token is used (e.g. "github-actions")
+        name: String,
+        /// Scope to grant: read, write or admin (repeatable, defaults to read + write)
+        #[arg(long = "scope")]
+        scopes: Vec<String>,
+        /// Days until the token expires (never expires if omitted)
+        #[arg(long)]
+        expires_in_days: Option<i64>,
+    },
+    /// List your personal access tokens
+    List,
+    /// Revoke a personal access token
+    Revoke {
+        /// Token ID (from 'mothership auth token list')
+        id: String,
+    },
 }
 
-#[derive(Subcommand)]
+#[derive(Clone, Subcommand)]
+pub enum SessionAction {
+    /// List active login sessions
+    List {
+        /// List another user's sessions (admins only)
+        #[arg(long)]
+        user: Option<String>,
+    },
+    /// Log a machine out remotely
+    Revoke {
+        /// Session ID, machine name or hostname (from 'mothership auth sessions list')
+        machine: String,
+        /// Revoke a session of another user (admins only)
+        #[arg(long)]
+        user: Option<String>,
+    },
+}
+
+#[derive(Clone, Subcommand)]
+pub enum MachineAction {
+    /// List the machines you have logged in from
+    List,
+    /// Revoke a machine, e.g. a lost laptop, signing out every session on it
+    Revoke {
+        /// Machine ID, name or hostname (from 'mothership auth machines list')
+        machine: String,
+    },
+}
+
+#[derive(Clone, Subcommand)]
+enum StashAction {
+    /// Stash local changes and put the files back to the last checkpoint
+    Push {
+        /// Describe what's being stashed
+        #[arg(short, long)]
+        message: Option<String>,
+
+        /// Also back the stash up to the server
+        #[arg(long)]
+        backup: bool,
+    },
+    /// Bring back the most recent stash
+    Pop,
+    /// List stashes, newest first
+    List,
+}
+
+#[derive(Clone, Subcommand)]
+enum ReviewAction {
+    /// Open a request to merge a rift (the current one by default)
+    Open {
+        /// What the change is
+        title: String,
+
+        /// Rift to merge (default: the current rift)
+        #[arg(long)]
+        rift: Option<String>,
+
+        /// Rift to merge into (default: its parent, or main)
+        #[arg(long)]
+        into: Option<String>,
+
+        /// Longer explanation for reviewers
+        #[arg(short, long)]
+        description: Option<String>,
+
+        /// Ask a project member to review (repeatable)
+        #[arg(long = "reviewer")]
+        reviewers: Vec<String>,
+
+        /// Approvals to wait for (never fewer than the server requires)
+        #[arg(long)]
+        approvals: Option<u32>,
+    },
+    /// List open review requests
+    List {
+        /// Include merged and closed requests
+        #[arg(long)]
+        all: bool,
+    },
+    /// Show a review request with its reviewers and comments
+    Show {
+        id: String,
+    },
+    /// Ask more project members to review
+    Request {
+        id: String,
+
+        #[arg(required = true)]
+        reviewers: Vec<String>,
+    },
+    /// Approve merging a review request's rift
+    Approve {
+        id: String,
+
+        /// Leave a comment with the approval
+        #[arg(short, long)]
+        comment: Option<String>,
+    },
+    /// Comment on a review request
+    Comment {
+        id: String,
+        body: String,
+    },
+    /// Merge an approved review request's rift into its target
+    Merge {
+        id: String,
+    },
+    /// Close a review request without merging
+    Close {
+        id: String,
+    },
+}
+
+#[derive(Clone, Subcommand)]
+enum BisectAction {
+    /// Start bisecting the current rift's checkpoints
+    Start {
+        /// A checkpoint with the regression (default: the latest)
+        #[arg(long)]
+        bad: Option<String>,
+
+        /// A checkpoint without it
+        #[arg(long)]
+        good: Option<String>,
+    },
+    /// Mark a checkpoint (default: the one being tested) as working
+    Good {
+        checkpoint_id: Option<String>,
+    },
+    /// Mark a checkpoint (default: the one being tested) as broken
+    Bad {
+        checkpoint_id: Option<String>,
+    },
+    /// Stop bisecting and restore the files from before it started
+    Reset,
+}
+
+#[derive(Clone, Subcommand)]
 enum GatewayAction {
     /// List available projects (default)
     List {
         /// Include inactive projects
         #[arg(long)]
         include_inactive: bool,
+        /// List archived projects instead of active ones
+        #[arg(long)]
+        archived: bool,
     },
     /// Create a new gateway project
     Create {
@@ -147,9 +512,71 @@ enum GatewayAction {
         /// Project name to disconnect from (optional, defaults to current project)
         project: Option<String>,
     },
+    /// Archive a project, making it read-only
+    Archive {
+        /// Project name to archive
+        project: String,
+        /// Also prune auto-generated checkpoints to save storage
+        #[arg(long)]
+        compact: bool,
+    },
+    /// Unarchive a project, making it writable again
+    Unarchive {
+        /// Project name to unarchive
+        project: String,
+    },
+    /// Show or set how sync conflicts are resolved
+    ConflictPolicy {
+        /// Project name
+        project: String,
+        /// server-wins, client-wins, always-create-conflict-rift or prompt (omit to show the current policy)
+        policy: Option<String>,
+    },
+    /// Show or set the rules the server enforces on checkpoints and synced changes
+    PushRules {
+        /// Project name
+        project: String,
+        /// Require a message on manual checkpoints
+        #[arg(long)]
+        require_message: Option<bool>,
+        /// Refuse files larger than this many MB (0 removes the limit)
+        #[arg(long)]
+        max_file_size_mb: Option<u64>,
+        /// Refuse paths matching this .mothershipignore-style pattern (repeatable)
+        #[arg(long)]
+        deny: Vec<String>,
+        /// Remove a deny pattern (repeatable)
+        #[arg(long)]
+        remove_deny: Vec<String>,
+        /// Only accept the project's allowed file types
+        #[arg(long)]
+        restrict_file_types: Option<bool>,
+    },
+    /// Show secrets found in a project's changes, or set what happens to them
+    Secrets {
+        /// Project name
+        project: String,
+        /// off, warn or reject (omit to list findings)
+        #[arg(long)]
+        mode: Option<String>,
+    },
+    /// Show or set how often the server checkpoints the project's rifts
+    AutoCheckpoint {
+        /// Project name
+        project: String,
+        /// Seconds between automatic checkpoints, 0 to turn them off (omit to show the current interval)
+        seconds: Option<u64>,
+    },
+    /// Show or set who can read the project
+    Visibility {
+        /// Project name
+        project: String,
+        /// private, internal or public (omit to show the current visibility)
+        visibility: Option<String>,
+    },
 }
 
-#[derive(Subcommand)]
+#[derive(Clone, Subcommand)]
 enum ServerAction {
     /// Show current server connection status
     Status,
@@ -159,17 +586,84 @@ enum ServerAction {
     List,
 }
 
-#[derive(Subcommand)]
+#[derive(Clone, Subcommand)]
+enum ProfileAction {
+    /// List profiles and show which one is in effect here
+    List,
+    /// Make a profile the default outside pinned projects
+    Use {
+        /// Profile name
+        name: String,
+    },
+    /// Pin the current project to a profile
+    Pin {
+        /// Profile name
+        name: String,
+    },
+    /// Remove the current project's profile pin
+    Unpin,
+}
+
+#[derive(Clone, Subcommand)]
 enum DaemonAction {
     /// Show daemon status and tracked projects
-    Status,
+    Status {
+        /// Also show each project's queue, connection health and the daemon's recent errors
+        #[arg(short, long)]
+        verbose: bool,
+    },
     /// Stop the background daemon
     Stop,
     /// Restart the background daemon
     Restart,
+    /// Show the daemon's logs
+    Logs {
+        /// Keep printing new log lines as they are written
+        #[arg(short, long)]
+        follow: bool,
+        /// Only show lines from this long ago, e.g. 30m, 1h or 2d
+        #[arg(long)]
+        since: Option<String>,
+        /// Lines to show when --since isn't given
+        #[arg(short = 'n', long, default_value_t = 100)]
+        lines: usize,
+    },
+    /// Show or change the daemon's resource limits
+    Config {
+        /// Projects scanned and hashed at once
+        #[arg(long)]
+        max_hash_jobs: Option<usize>,
+        /// Read project files at most this many MB per second (0 removes the limit)
+        #[arg(long)]
+        io_limit_mb: Option<u64>,
+        /// Scan one project at a time and read more slowly while on battery
+        #[arg(long)]
+        low_priority_on_battery: Option<bool>,
+        /// Look for projects under this directory (repeatable)
+        #[arg(long = "scan-root")]
+        scan_roots: Vec<PathBuf>,
+        /// Stop looking for projects under this directory (repeatable)
+        #[arg(long = "remove-scan-root")]
+        remove_scan_roots: Vec<PathBuf>,
+        /// How many directories below a scan root to look
+        #[arg(long)]
+        scan_depth: Option<usize>,
+    },
+    /// Choose which projects found under the scan roots the daemon tracks
+    Discover {
+        /// Search the scan roots again first
+        #[arg(long)]
+        scan: bool,
+        /// Track the project in this directory
+        #[arg(long, conflicts_with = "ignore")]
+        track: Option<PathBuf>,
+        /// Never track the project in this directory
+        #[arg(long)]
+        ignore: Option<PathBuf>,
+    },
 }
 
-#[derive(Subcommand)]
+#[derive(Clone, Subcommand)]
 enum RiftAction {
     /// List all rifts in the current project
     List {
@@ -191,6 +685,22 @@ enum RiftAction {
         /// Name or ID of the rift to switch to
         name: String,
     },
+    /// Rename a rift you own
+    Rename {
+        /// Current name of the rift
+        name: String,
+        /// New name for the rift
+        new_name: String,
+    },
+    /// Delete a rift you own and its history
+    Delete {
+        /// Name of the rift to delete
+        name: String,
+
+        /// Delete even if the rift has changes that were never merged
+        #[arg(long)]
+        force: bool,
+    },
     /// Show current rift status
     Status,
     /// Compare rifts (flexible arguments)
@@ -201,6 +711,26 @@ enum RiftAction {
         /// Second rift to compare (optional)
         to: Option<String>,
     },
+    /// Share the current rift with a project member
+    Share {
+        /// Username to share the rift with
+        user: String,
+
+        /// Let them watch the rift without changing files
+        #[arg(long)]
+        observer: bool,
+    },
+    /// Stop sharing the current rift with a user
+    Unshare {
+        /// Username to remove
+        user: String,
+    },
+    /// Set who may join the current rift: project, invite or private
+    Visibility {
+        level: String,
+    },
+    /// Show who may join the current rift
+    Access,
 }
 
 // Local types
@@ -221,13 +751,6 @@ struct RiftInfo {
     is_conflict_rift: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct ApiResponse<T> {
-    success: bool,
-    data: Option<T>,
-    error: Option<String>,
-}
-
 #[tokio::main]
 async fn main() -> Result<()> {
     // Load environment variables
@@ -243,40 +766,158 @@ async fn main() -> Result<()> {
     let cli = Cli::parse();
     let config_manager = ConfigManager::new()?;
 
-    match cli.command {
-        Commands::Auth { method: _ } => {
+    // PROFILES: --profile applies to everything this command does
+    if let Commands::Auth { profile: Some(name), .. } = &cli.command {
+        profile::select(name)?;
+    }
+
+    // SESSION: a command that hit an expired session signs in again. Only read-only
+    // commands run once more; others may have changed something before they failed.
+    match run(cli.command.clone(), &config_manager).await {
+        Err(e) if http::is_session_expired(&e) && http::renew_session(&config_manager).await? => {
+            if cli.command.is_read_only() {
+                run(cli.command, &config_manager).await
+            } else {
+                Err(anyhow!("The session expired partway through. You are signed in again; run the command once more to finish it"))
+            }
+        }
+        result => result,
+    }
+}
+
+impl Commands {
+    /// Commands that only read from the server, so running them again from the start is harmless
+    fn is_read_only(&self) -> bool {
+        matches!(
+            self,
+            Commands::Status { .. }
+                | Commands::History { .. }
+                | Commands::Activity { .. }
+                | Commands::Log { .. }
+                | Commands::Blame { .. }
+                | Commands::Export { .. }
+                | Commands::Gateway { action: GatewayAction::List { .. } }
+                | Commands::Rift { action: RiftAction::List { .. } }
+                | Commands::Inbox { read: None, read_all: false, .. }
+        )
+    }
+}
+
+async fn run(command: Commands, config_manager: &ConfigManager) -> Result<()> {
+    match command {
+        Commands::Auth { method: Some(AuthMethod::Token { action }), .. } => {
+            // Validate authentication before token operations
+            if let Err(e) = validate_authentication(config_manager).await {
+                print_auth_error(&e.to_string());
+                return Ok(());
+            }
+
+            match action {
+                TokenAction::Create { name, scopes, expires_in_days } => {
+                    println!("{}", format!("🔑 Creating access token: {}...", name).cyan().bold());
+                    tokens::handle_token_create(config_manager, name, scopes, expires_in_days).await?;
+                }
+                TokenAction::List => {
+                    tokens::handle_token_list(config_manager).await?;
+                }
+                TokenAction::Revoke { id } => {
+                    println!("{}", "🗑️  Revoking access token...".cyan().bold());
+                    tokens::handle_token_revoke(config_manager, id).await?;
+                }
+            }
+        }
+        Commands::Auth { method: Some(AuthMethod::Sessions { action }), .. } => {
+            // Validate authentication before session operations
+            if let Err(e) = validate_authentication(config_manager).await {
+                print_auth_error(&e.to_string());
+                return Ok(());
+            }
+
+            match action {
+                SessionAction::List { user } => {
+                    sessions::handle_sessions_list(config_manager, user).await?;
+                }
+                SessionAction::Revoke { machine, user } => {
+                    println!("{}", "🚫 Revoking session...".cyan().bold());
+                    sessions::handle_session_revoke(config_manager, machine, user).await?;
+                }
+            }
+        }
+        Commands::Auth { method: Some(AuthMethod::Machines { action }), .. } => {
+            if let Err(e) = validate_authentication(config_manager).await {
+                print_auth_error(&e.to_string());
+                return Ok(());
+            }
+
+            match action {
+                MachineAction::List => {
+                    sessions::handle_machines_list(config_manager).await?;
+                }
+                MachineAction::Revoke { machine } => {
+                    println!("{}", "🚫 Revoking machine...".cyan().bold());
+                    sessions::handle_machine_revoke(config_manager, machine).await?;
+                }
+            }
+        }
+        Commands::Auth { method: _, server, .. } => {
             println!("{}", "🔐 Starting Mothership authentication...".cyan().bold());
-            auth::authenticate(&config_manager).await?;
+            auth::authenticate(config_manager, server).await?;
+        }
+        Commands::Profile { action } => {
+            match action {
+                ProfileAction::List => profiles::handle_profile_list()?,
+                ProfileAction::Use { name } => profiles::handle_profile_use(name)?,
+                ProfileAction::Pin { name } => profiles::handle_profile_pin(Some(name))?,
+                ProfileAction::Unpin => profiles::handle_profile_pin(None)?,
+            }
         }
         Commands::Gateway { action } => {
             // Validate authentication before gateway operations
-            if let Err(e) = validate_authentication(&config_manager).await {
+            if let Err(e) = validate_authentication(config_manager).await {
                 print_auth_error(&e.to_string());
                 return Ok(());
             }
 
             match action {
-                GatewayAction::List { include_inactive } => {
+                GatewayAction::List { include_inactive, archived } => {
                     println!("{}", "🌌 Accessing your development gateway...".cyan().bold());
-                    gateway::handle_gateway(&config_manager, include_inactive).await?;
+                    gateway::handle_gateway(config_manager, include_inactive, archived).await?;
                 }
                 GatewayAction::Create { name, dir } => {
                     println!("{}", format!("🏗️  Creating new gateway: {}...", name).cyan().bold());
-                    gateway::handle_gateway_create(&config_manager, name, dir).await?;
+                    gateway::handle_gateway_create(config_manager, name, dir).await?;
                 }
                 GatewayAction::Disconnect { project } => {
                     println!("{}", "🔌 Disconnecting from project...".cyan().bold());
-                    beam::handle_disconnect(&config_manager, project).await?;
+                    beam::handle_disconnect(config_manager, project).await?;
+                }
+                GatewayAction::Archive { project, compact } => {
+                    println!("{}", format!("🗄️  Archiving project {}...", project).cyan().bold());
+                    gateway::handle_archive(config_manager, project, true, compact).await?;
+                }
+                GatewayAction::Unarchive { project } => {
+                    println!("{}", format!("📂 Unarchiving project {}...", project).cyan().bold());
+                    gateway::handle_archive(config_manager, project, false, false).await?;
+                }
+                GatewayAction::ConflictPolicy { project, policy } => {
+                    gateway::handle_conflict_policy(config_manager, project, policy).await?;
+                }
+                GatewayAction::PushRules { project, require_message, max_file_size_mb, deny, remove_deny, restrict_file_types } => {
+                    let changes = gateway::PushRuleChanges { require_message, max_file_size_mb, deny, remove_deny, restrict_file_types };
+                    gateway::handle_push_rules(config_manager, project, changes).await?;
+                }
+                GatewayAction::Secrets { project, mode } => {
+                    gateway::handle_secrets(config_manager, project, mode).await?;
+                }
+                GatewayAction::AutoCheckpoint { project, seconds } => {
+                    gateway::handle_auto_checkpoint(config_manager, project, seconds).await?;
+                }
+                GatewayAction::Visibility { project, visibility } => {
+                    gateway::handle_visibility(config_manager, project, visibility).await?;
                 }
             }
         }
         Commands::Init { name } => {
-            // Validate authentication before init operations
-            if let Err(e) = validate_authentication(&config_manager).await {
-                print_auth_error(&e.to_string());
-                return Ok(());
-            }
-
             let current_dir = std::env::current_dir()?;
             let project_name = name.unwrap_or_else(|| {
                 current_dir.file_name()
@@ -285,113 +926,290 @@ async fn main() -> Result<()> {
                     .to_string()
             });
 
+            if connections::is_local_only() {
+                println!("{}", format!("🚀 Initializing {} locally...", project_name).cyan().bold());
+                local::handle_init(project_name)?;
+                return Ok(());
+            }
+
+            // Validate authentication before init operations
+            if let Err(e) = validate_authentication(config_manager).await {
+                print_auth_error(&e.to_string());
+                return Ok(());
+            }
+
             println!("{}", format!("🚀 Initializing {}...", project_name).cyan().bold());
             
             // Create the gateway/project (CRITICAL FIX: Properly handle errors)
-            match gateway::handle_gateway_create(&config_manager, project_name.clone(), current_dir).await {
+            match gateway::handle_gateway_create(config_manager, project_name.clone(), current_dir).await {
                 Ok(_project) => {
                     // Automatically beam into the newly created project
                     println!("\n{}", "🎯 Automatically beaming into your new project...".cyan().bold());
-                    if let Err(e) = beam::handle_beam(&config_manager, project_name, None, None, false).await {
+                    if let Err(e) = beam::handle_beam(config_manager, project_name, None, None, Vec::new(), false, false).await {
                         print_api_error(&format!("Failed to beam into project: {}", e));
                         print_info("You can manually beam into your project later.");
                     }
                 }
-                Err(e) => {
-                    print_api_error(&format!("Failed to initialize project: {}", e));
-                    return Err(e);
+                Err(e) => {
+                    print_api_error(&format!("Failed to initialize project: {}", e));
+                    return Err(e);
+                }
+            }
+        }
+        Commands::Beam { project, rift, local_dir, paths, observe } => {
+            // Validate authentication before beam operations
+            if let Err(e) = validate_authentication(config_manager).await {
+                print_auth_error(&e.to_string());
+                return Ok(());
+            }
+
+            println!("{}", format!("🚀 Beaming into {}...", project).cyan().bold());
+            beam::handle_beam(config_manager, project, rift, local_dir, paths, observe, false).await?;
+        }
+        Commands::Status { porcelain } => {
+            if connections::is_local_only() {
+                if !porcelain {
+                    println!("{}", "📊 Checking local status...".cyan().bold());
+                }
+                local::handle_status(porcelain)?;
+                return Ok(());
+            }
+
+            // Validate authentication before status operations
+            if let Err(e) = validate_authentication(config_manager).await {
+                print_auth_error(&e.to_string());
+                return Ok(());
+            }
+
+            if !porcelain {
+                println!("{}", "📊 Checking sync status...".cyan().bold());
+            }
+            sync::handle_status(config_manager, porcelain).await?;
+        }
+        Commands::Checkpoint { message } => {
+            if connections::is_local_only() {
+                println!("{}", "📸 Creating local checkpoint...".cyan().bold());
+                local::handle_checkpoint(Some(message))?;
+                return Ok(());
+            }
+
+            // Validate authentication before checkpoint operations
+            if let Err(e) = validate_authentication(config_manager).await {
+                print_auth_error(&e.to_string());
+                return Ok(());
+            }
+
+            println!("{}", "📸 Creating checkpoint...".cyan().bold());
+            sync::handle_checkpoint(config_manager, Some(message)).await?;
+        }
+        Commands::Sync => {
+            println!("{}", "📦 Syncing with remote Mothership...".cyan().bold());
+            handle_sync_internal().await?;
+        }
+        Commands::Push => {
+            if connections::is_local_only() {
+                print_api_error("Not connected to a server. Run 'mothership connect <server-url>' first.");
+                return Ok(());
+            }
+
+            // Validate authentication before push operations
+            if let Err(e) = validate_authentication(config_manager).await {
+                print_auth_error(&e.to_string());
+                return Ok(());
+            }
+
+            println!("{}", "⬆️  Pushing local history...".cyan().bold());
+            local::handle_push(config_manager).await?;
+        }
+        Commands::History { action: Some(HistoryAction::Compact { older_than_days }), .. } => {
+            if connections::is_local_only() {
+                print_info("Local checkpoints are all manual; there is nothing to compact");
+                return Ok(());
+            }
+
+            // Validate authentication before history operations
+            if let Err(e) = validate_authentication(config_manager).await {
+                print_auth_error(&e.to_string());
+                return Ok(());
+            }
+
+            println!("{}", "🗜️  Compacting project history...".cyan().bold());
+            sync::handle_history_compact(config_manager, older_than_days).await?;
+        }
+        Commands::History { action: None, limit, deepen, graph, comments } => {
+            if connections::is_local_only() {
+                if comments {
+                    print_info("Comments are kept on the server; local checkpoints have none");
+                }
+                if graph {
+                    graph::handle_local_history_graph(limit)?;
+                } else {
+                    local::handle_history(limit)?;
                 }
+                return Ok(());
+            }
+
+            // Validate authentication before history operations
+            if let Err(e) = validate_authentication(config_manager).await {
+                print_auth_error(&e.to_string());
+                return Ok(());
+            }
+
+            println!("{}", "📜 Loading project history...".cyan().bold());
+            if graph {
+                graph::handle_history_graph(config_manager, limit).await?;
+            } else {
+                sync::handle_history(config_manager, limit, deepen, comments).await?;
             }
         }
-        Commands::Beam { project, rift, local_dir } => {
-            // Validate authentication before beam operations
-            if let Err(e) = validate_authentication(&config_manager).await {
+        Commands::Activity { limit, before } => {
+            // Validate authentication before history operations
+            if let Err(e) = validate_authentication(config_manager).await {
                 print_auth_error(&e.to_string());
                 return Ok(());
             }
 
-            println!("{}", format!("🚀 Beaming into {}...", project).cyan().bold());
-            beam::handle_beam(&config_manager, project, rift, local_dir, false).await?;
+            activity::handle_activity(config_manager, limit, before).await?;
         }
-        Commands::Status => {
-            // Validate authentication before status operations
-            if let Err(e) = validate_authentication(&config_manager).await {
+        Commands::Log { file, limit } => {
+            // Validate authentication before history operations
+            if let Err(e) = validate_authentication(config_manager).await {
                 print_auth_error(&e.to_string());
                 return Ok(());
             }
 
-            println!("{}", "📊 Checking sync status...".cyan().bold());
-            sync::handle_status(&config_manager).await?;
+            file_history::handle_log(config_manager, file, limit).await?;
         }
-        Commands::Checkpoint { message } => {
-            // Validate authentication before checkpoint operations
-            if let Err(e) = validate_authentication(&config_manager).await {
+        Commands::Blame { file, checkpoint } => {
+            // Validate authentication before history operations
+            if let Err(e) = validate_authentication(config_manager).await {
                 print_auth_error(&e.to_string());
                 return Ok(());
             }
 
-            println!("{}", "📸 Creating checkpoint...".cyan().bold());
-            sync::handle_checkpoint(&config_manager, Some(message)).await?;
+            file_history::handle_blame(config_manager, file, checkpoint).await?;
         }
-        Commands::Sync => {
-            println!("{}", "📦 Syncing with remote Mothership...".cyan().bold());
-            handle_sync_internal().await?;
+        Commands::Chat { message, limit, follow } => {
+            // Validate authentication before chat operations
+            if let Err(e) = validate_authentication(config_manager).await {
+                print_auth_error(&e.to_string());
+                return Ok(());
+            }
+            if connections::feature_disabled(Feature::Chat).await {
+                print_info("Chat is turned off on this server");
+                return Ok(());
+            }
+
+            chat::handle_chat(config_manager, message, limit, follow).await?;
         }
-        Commands::History { limit } => {
-            // Validate authentication before history operations
-            if let Err(e) = validate_authentication(&config_manager).await {
+        Commands::ShareTerminal { command } => {
+            if connections::is_local_only() {
+                print_info("Terminal sharing goes through a server; connect one to use it");
+                return Ok(());
+            }
+
+            if let Err(e) = validate_authentication(config_manager).await {
                 print_auth_error(&e.to_string());
                 return Ok(());
             }
+            if connections::feature_disabled(Feature::TerminalSharing).await {
+                print_info("Terminal sharing is turned off on this server");
+                return Ok(());
+            }
 
-            println!("{}", "📜 Loading project history...".cyan().bold());
-            sync::handle_history(&config_manager, limit).await?;
+            terminal_share::handle_share_terminal(config_manager, command).await?;
         }
-        Commands::Restore { checkpoint_id, force } => {
+        Commands::Restore { checkpoint_id, paths, force, dry_run } => {
+            if connections::is_local_only() {
+                if !dry_run {
+                    println!("{}", format!("🔄 Restoring to local checkpoint {}...", checkpoint_id).cyan().bold());
+                }
+                local::handle_restore(checkpoint_id, paths, force, dry_run)?;
+                return Ok(());
+            }
+
             // Validate authentication before restore operations
-            if let Err(e) = validate_authentication(&config_manager).await {
+            if let Err(e) = validate_authentication(config_manager).await {
+                print_auth_error(&e.to_string());
+                return Ok(());
+            }
+
+            if !dry_run {
+                println!("{}", format!("🔄 Restoring to checkpoint {}...", checkpoint_id).cyan().bold());
+            }
+            sync::handle_restore(config_manager, checkpoint_id, paths, force, dry_run).await?;
+        }
+        Commands::Revert { checkpoint_id } => {
+            if connections::is_local_only() {
+                print_api_error("Not connected to a server. Run 'mothership connect <server-url>' first.");
+                return Ok(());
+            }
+
+            // Validate authentication before revert operations
+            if let Err(e) = validate_authentication(config_manager).await {
                 print_auth_error(&e.to_string());
                 return Ok(());
             }
 
-            println!("{}", format!("🔄 Restoring to checkpoint {}...", checkpoint_id).cyan().bold());
-            sync::handle_restore(&config_manager, checkpoint_id, force).await?;
+            println!("{}", format!("↩️  Reverting checkpoint {}...", checkpoint_id).cyan().bold());
+            sync::handle_revert(config_manager, checkpoint_id).await?;
         }
         Commands::Delete { project_name, force } => {
             // Validate authentication before delete operations
-            if let Err(e) = validate_authentication(&config_manager).await {
+            if let Err(e) = validate_authentication(config_manager).await {
                 print_auth_error(&e.to_string());
                 return Ok(());
             }
 
             println!("{}", format!("🗑️  Deleting project {}...", project_name).cyan().bold());
-            gateway::handle_delete(&config_manager, project_name, force).await?;
+            gateway::handle_delete(config_manager, project_name, force).await?;
+        }
+        Commands::Export { project_name, output } => {
+            // Validate authentication before export operations
+            if let Err(e) = validate_authentication(config_manager).await {
+                print_auth_error(&e.to_string());
+                return Ok(());
+            }
+
+            println!("{}", format!("📦 Exporting project {}...", project_name).cyan().bold());
+            bundles::handle_export(config_manager, project_name, output).await?;
+        }
+        Commands::Import { file, name } => {
+            // Validate authentication before import operations
+            if let Err(e) = validate_authentication(config_manager).await {
+                print_auth_error(&e.to_string());
+                return Ok(());
+            }
+
+            println!("{}", format!("📦 Importing {}...", file.display()).cyan().bold());
+            bundles::handle_import(config_manager, file, name).await?;
         }
-        Commands::Connect { server_url } => {
+        Commands::Connect { server_url, ca_bundle, insecure, client_cert, client_key } => {
             println!("{}", format!("🔗 Connecting to {}...", server_url).cyan().bold());
-            connections::handle_connect(&config_manager, server_url).await?;
+            let tls = TlsSettings { ca_bundle, insecure, client_cert, client_key };
+            connections::handle_connect(config_manager, server_url, tls).await?;
         }
         Commands::Server { action } => {
             match action {
                 ServerAction::Status => {
                     println!("{}", "📡 Checking server connection status...".cyan().bold());
-                    connections::handle_server_status(&config_manager).await?;
+                    connections::handle_server_status(config_manager).await?;
                 }
                 ServerAction::Disconnect => {
                     println!("{}", "🔌 Disconnecting from server...".cyan().bold());
-                    connections::handle_server_disconnect(&config_manager).await?;
+                    connections::handle_server_disconnect(config_manager).await?;
                 }
                 ServerAction::List => {
                     println!("{}", "📋 Listing configured servers...".cyan().bold());
-                    connections::handle_server_list(&config_manager).await?;
+                    connections::handle_server_list(config_manager).await?;
                 }
             }
         }
         Commands::Daemon { action } => {
             match action {
-                DaemonAction::Status => {
+                DaemonAction::Status { verbose } => {
                     println!("{}", "🤖 Checking daemon status...".cyan().bold());
-                    beam::handle_daemon_status().await?;
+                    beam::handle_daemon_status(verbose).await?;
                 }
                 DaemonAction::Stop => {
                     println!("{}", "⏹️  Stopping daemon...".cyan().bold());
@@ -401,17 +1219,156 @@ async fn main() -> Result<()> {
                     println!("{}", "🔄 Restarting daemon...".cyan().bold());
                     beam::handle_daemon_restart().await?;
                 }
+                DaemonAction::Logs { follow, since, lines } => {
+                    beam::handle_daemon_logs(follow, since, lines).await?;
+                }
+                DaemonAction::Config {
+                    max_hash_jobs,
+                    io_limit_mb,
+                    low_priority_on_battery,
+                    scan_roots,
+                    remove_scan_roots,
+                    scan_depth,
+                } => {
+                    let changes = beam::ThrottleChanges { max_hash_jobs, io_limit_mb, low_priority_on_battery };
+                    let discovery = beam::DiscoveryChanges { add_roots: scan_roots, remove_roots: remove_scan_roots, max_depth: scan_depth };
+                    beam::handle_daemon_config(changes, discovery).await?;
+                }
+                DaemonAction::Discover { scan, track, ignore } => {
+                    beam::handle_daemon_discover(scan, track, ignore).await?;
+                }
             }
         }
         Commands::Logout => {
             println!("{}", "🔓 Logging out...".cyan().bold());
-            auth::handle_logout(&config_manager).await?;
+            auth::handle_logout(config_manager).await?;
         }
         Commands::Update { args } => {
             update::handle_update(args).await?;
         }
+        Commands::Stash { action } => {
+            // Connected projects compare against the server's last checkpoint
+            if !connections::is_local_only() {
+                if let Err(e) = validate_authentication(config_manager).await {
+                    print_auth_error(&e.to_string());
+                    return Ok(());
+                }
+            }
+
+            match action {
+                StashAction::Push { message, backup } => {
+                    println!("{}", "🗃️  Stashing local changes...".cyan().bold());
+                    stash::handle_push(config_manager, message, backup).await?;
+                }
+                StashAction::Pop => {
+                    println!("{}", "🗃️  Restoring stashed changes...".cyan().bold());
+                    stash::handle_pop(config_manager).await?;
+                }
+                StashAction::List => {
+                    stash::handle_list()?;
+                }
+            }
+        }
+        Commands::Bisect { action } => {
+            // Local-only projects bisect their local history
+            if !connections::is_local_only() {
+                if let Err(e) = validate_authentication(config_manager).await {
+                    print_auth_error(&e.to_string());
+                    return Ok(());
+                }
+            }
+
+            match action {
+                BisectAction::Start { bad, good } => {
+                    println!("{}", "🔎 Starting bisect...".cyan().bold());
+                    bisect::handle_start(config_manager, bad, good).await?;
+                }
+                BisectAction::Good { checkpoint_id } => {
+                    bisect::handle_mark(config_manager, checkpoint_id, true).await?;
+                }
+                BisectAction::Bad { checkpoint_id } => {
+                    bisect::handle_mark(config_manager, checkpoint_id, false).await?;
+                }
+                BisectAction::Reset => {
+                    bisect::handle_reset(config_manager).await?;
+                }
+            }
+        }
+        Commands::Review { action } => {
+            if connections::is_local_only() {
+                print_info("Review requests are kept on the server; connect one to use them");
+                return Ok(());
+            }
+
+            if let Err(e) = validate_authentication(config_manager).await {
+                print_auth_error(&e.to_string());
+                return Ok(());
+            }
+
+            match action {
+                ReviewAction::Open { title, rift, into, description, reviewers, approvals } => {
+                    println!("{}", "👀 Opening review request...".cyan().bold());
+                    reviews::handle_open(config_manager, title, rift, into, description, reviewers, approvals).await?;
+                }
+                ReviewAction::List { all } => {
+                    reviews::handle_list(config_manager, all).await?;
+                }
+                ReviewAction::Show { id } => {
+                    reviews::handle_show(config_manager, id).await?;
+                }
+                ReviewAction::Request { id, reviewers } => {
+                    reviews::handle_add_reviewers(config_manager, id, reviewers).await?;
+                }
+                ReviewAction::Approve { id, comment } => {
+                    reviews::handle_approve(config_manager, id, comment).await?;
+                }
+                ReviewAction::Comment { id, body } => {
+                    reviews::handle_comment(config_manager, id, body).await?;
+                }
+                ReviewAction::Merge { id } => {
+                    println!("{}", "🔀 Merging...".cyan().bold());
+                    reviews::handle_merge(config_manager, id).await?;
+                }
+                ReviewAction::Close { id } => {
+                    reviews::handle_close(config_manager, id).await?;
+                }
+            }
+        }
+        Commands::Inbox { all, limit, read, read_all } => {
+            if connections::is_local_only() {
+                print_info("Notifications are kept on the server; connect one to use them");
+                return Ok(());
+            }
+
+            if let Err(e) = validate_authentication(config_manager).await {
+                print_auth_error(&e.to_string());
+                return Ok(());
+            }
+
+            if read.is_some() || read_all {
+                inbox::handle_mark_read(config_manager, read).await?;
+            } else {
+                inbox::handle_list(config_manager, all, limit).await?;
+            }
+        }
         Commands::Rift { action } => {
+            // Rifts are kept in the project directory in local-only mode
+            let local_only = connections::is_local_only();
+
             match action {
+                RiftAction::List { detailed } if local_only => {
+                    local::handle_rift_list(detailed)?;
+                }
+                RiftAction::New { name, description } if local_only => {
+                    if !is_valid_rift_name(&name) {
+                        anyhow::bail!("Invalid rift name. Use only letters, numbers, dashes, and underscores.");
+                    }
+                    local::handle_rift_new(name, description)?;
+                }
+                RiftAction::Switch { name } if local_only => {
+                    stash::auto_stash(config_manager, &name).await?;
+                    local::handle_rift_switch(name)?;
+                }
                 RiftAction::List { detailed } => {
                     handle_rifts_command(detailed).await?;
                 }
@@ -419,7 +1376,13 @@ async fn main() -> Result<()> {
                     handle_create_rift_command(name, description).await?;
                 }
                 RiftAction::Switch { name } => {
-                    handle_switch_rift_command(name).await?;
+                    handle_switch_rift_command(config_manager, name).await?;
+                }
+                RiftAction::Rename { name, new_name } => {
+                    handle_rename_rift_command(name, new_name).await?;
+                }
+                RiftAction::Delete { name, force } => {
+                    handle_delete_rift_command(name, force).await?;
                 }
                 RiftAction::Status => {
                     handle_rift_status_command().await?;
@@ -427,6 +1390,18 @@ async fn main() -> Result<()> {
                 RiftAction::Diff { from, to } => {
                     handle_rift_diff_command(from, to).await?;
                 }
+                RiftAction::Share { user, observer } => {
+                    rift_access::handle_share(config_manager, user, observer).await?;
+                }
+                RiftAction::Unshare { user } => {
+                    rift_access::handle_unshare(config_manager, user).await?;
+                }
+                RiftAction::Visibility { level } => {
+                    rift_access::handle_visibility(config_manager, level).await?;
+                }
+                RiftAction::Access => {
+                    rift_access::handle_access(config_manager).await?;
+                }
             }
         }
     }
@@ -462,6 +1437,9 @@ async fn validate_authentication(config_manager: &ConfigManager) -> Result<()> {
             if resp.status().is_success() {
                 Ok(())
             } else if resp.status() == 401 {
+                if http::renew_session(config_manager).await? {
+                    return Ok(());
+                }
                 Err(anyhow!("Authentication token expired or invalid. Please run 'mothership auth' again."))
             } else if resp.status() == 404 {
                 Err(anyhow!("User not found on server. Please run 'mothership auth' again."))
@@ -495,14 +1473,35 @@ fn get_http_client(config: &ClientConfig) -> reqwest::Client {
         );
     }
 
-    reqwest::Client::builder()
+    let server_url = connections::get_active_server_url().unwrap_or_else(|| config.mothership_url.clone());
+    let builder = tls::settings_for(&server_url).client_builder().unwrap_or_else(|e| {
+        print_api_error(&format!("Ignoring TLS settings of {}: {}", server_url, e));
+        reqwest::Client::builder()
+    });
+    builder
         .default_headers(headers)
         .build()
         .unwrap_or_else(|_| reqwest::Client::new())
 }
 
-/// Helper function to get OAuth token from credentials.json
+/// Open a WebSocket to a server, honoring its TLS settings
+async fn connect_websocket(
+    url: &str,
+) -> Result<tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>> {
+    let connector = tls::settings_for(url).connector()?.map(tokio_tungstenite::Connector::NativeTls);
+    let (stream, _) = tokio_tungstenite::connect_async_tls_with_config(url, None, false, connector).await?;
+    Ok(stream)
+}
+
+/// Helper function to get OAuth token from the active profile's credentials.json
+/// (or a personal access token from MOTHERSHIP_TOKEN, for CI)
 fn get_oauth_token() -> Option<String> {
+    if let Ok(token) = std::env::var("MOTHERSHIP_TOKEN") {
+        if !token.trim().is_empty() {
+            return Some(token.trim().to_string());
+        }
+    }
+
     use serde::{Deserialize, Serialize};
     
     #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -513,9 +1512,7 @@ fn get_oauth_token() -> Option<String> {
         stored_at: String,
     }
     
-    let credentials_path = dirs::config_dir()?
-        .join("mothership")
-        .join("credentials.json");
+    let credentials_path = profile::credentials_path()?;
         
     if !credentials_path.exists() {
         return None;
@@ -550,6 +1547,72 @@ fn print_info(message: &str) {
     println!("{} {}", "ℹ️".blue().bold(), message);
 }
 
+/// Root of the project the current directory is in: the nearest enclosing
+/// directory with a `.mothership/project.json`, so commands work from any
+/// subdirectory and projects nested in others resolve to themselves. Outside
+/// every project it's the current directory, and callers report that.
+fn project_dir() -> Result<PathBuf> {
+    let current_dir = std::env::current_dir()?;
+    Ok(mothership_common::project_root::find_project_root(&current_dir).unwrap_or(current_dir))
+}
+
+/// Turn a path given on the command line into the project-relative form the
+/// server stores, with `/` separators
+pub(crate) fn project_relative_path(root: &Path, file: &Path) -> Result<String> {
+    let absolute = std::env::current_dir()?.join(file);
+
+    // Resolve `.` and `..` without touching the filesystem (the file may be deleted)
+    let mut normalized = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+
+    let relative = normalized.strip_prefix(root)
+        .map_err(|_| anyhow!("{} is outside the project", file.display()))?;
+
+    Ok(relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join("/"))
+}
+
+/// Paths given on the command line as the project-relative paths the server
+/// stores. The project root stands for everything, so it empties the list.
+pub(crate) fn project_relative_paths(root: &Path, paths: &[String]) -> Result<Vec<PathBuf>> {
+    let mut relative = Vec::new();
+    for path in paths {
+        let path = project_relative_path(root, Path::new(path))?;
+        if path.is_empty() {
+            return Ok(Vec::new());
+        }
+        relative.push(PathBuf::from(path));
+    }
+    Ok(relative)
+}
+
+/// Run one of the project's `pre-*` hooks, failing if it vetoes the operation
+fn run_pre_hook(hook: Hook, env: &[(&str, String)]) -> Result<()> {
+    let project_dir = crate::project_dir()?;
+    if hooks::find_hook(&project_dir, hook).is_none() {
+        return Ok(());
+    }
+
+    print_info(&format!("Running {} hook...", hook.as_str()));
+    let outcome = hooks::run_hook(&project_dir, hook, env)?;
+    if outcome.allows() {
+        Ok(())
+    } else {
+        Err(outcome.veto_error(hook))
+    }
+}
+
 async fn handle_rifts_command(detailed: bool) -> Result<()> {
     let rifts = get_rifts().await?;
     
@@ -599,7 +1662,7 @@ async fn handle_create_rift_command(name: String, description: Option<String>) -
     Ok(())
 }
 
-async fn handle_switch_rift_command(rift: String) -> Result<()> {
+async fn handle_switch_rift_command(config_manager: &ConfigManager, rift: String) -> Result<()> {
     let current_rift = get_current_rift().await?;
     
     // Check if we're already in this rift
@@ -610,6 +1673,9 @@ async fn handle_switch_rift_command(rift: String) -> Result<()> {
         }
     }
 
+    // Set aside uncheckpointed changes so they don't follow us into the other rift
+    stash::auto_stash(config_manager, &rift).await?;
+
     switch_to_rift(&rift).await?;
     println!("🔄 Switched to rift: {}", rift);
     
@@ -622,6 +1688,34 @@ async fn handle_switch_rift_command(rift: String) -> Result<()> {
     Ok(())
 }
 
+async fn handle_rename_rift_command(name: String, new_name: String) -> Result<()> {
+    // Validate rift name
+    if !is_valid_rift_name(&new_name) {
+        anyhow::bail!("Invalid rift name. Use only letters, numbers, dashes, and underscores.");
+    }
+
+    rename_rift(&name, &new_name).await?;
+    println!("✏️ Renamed rift {} to {}", name, new_name);
+
+    // Keep the local metadata pointing at the renamed rift
+    if get_current_project_metadata()?.current_rift.as_deref() == Some(name.as_str()) {
+        update_local_rift_metadata(&new_name)?;
+    }
+
+    Ok(())
+}
+
+async fn handle_delete_rift_command(name: String, force: bool) -> Result<()> {
+    let deletion = delete_rift(&name, force).await?;
+    println!("🗑️ Deleted rift {} ({} checkpoints removed)", deletion.name, deletion.checkpoints_removed);
+
+    if get_current_project_metadata()?.rift_id.as_deref() == Some(deletion.rift_id.to_string().as_str()) {
+        println!("⚠️ That was your current rift - switch to another with: mothership rift switch <name>");
+    }
+
+    Ok(())
+}
+
 async fn handle_rift_status_command() -> Result<()> {
     let current = get_current_rift().await?;
     
@@ -696,7 +1790,7 @@ fn is_valid_rift_name(name: &str) -> bool {
 /// Get list of rifts for current project
 async fn get_rifts() -> Result<Vec<RiftInfo>> {
     // Check if we're in a project directory
-    let _project_metadata = get_current_project_metadata()?;
+    let project_metadata = get_current_project_metadata()?;
     
     // Get active server connection
     let active_server = connections::get_active_server()?
@@ -707,25 +1801,26 @@ async fn get_rifts() -> Result<Vec<RiftInfo>> {
         .ok_or_else(|| anyhow!("Not authenticated. Please run 'mothership auth' first."))?;
     
     // Make API call to get rifts
-    let client = reqwest::Client::new();
+    let client = tls::http_client(&active_server.url)?;
     let url = format!("{}/api/rifts", active_server.url);
     
     let response = client
         .get(&url)
         .header("Authorization", format!("Bearer {}", auth_token))
+        .query(&[("project_id", &project_metadata.project_id)])
         .send()
-        .await?;
+        .await?
+        .check_session()?;
     
     if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(anyhow!("Failed to get rifts: {}", error_text));
+        return Err(http::api_error(response, "Failed to get rifts").await);
     }
     
     // Parse ApiResponse format
     let api_response: ApiResponse<Vec<RiftInfo>> = response.json().await?;
     
     if !api_response.success {
-        let error_msg = api_response.error.unwrap_or_else(|| "Unknown error".to_string());
+        let error_msg = http::describe_failure(&api_response);
         return Err(anyhow!("Server error: {}", error_msg));
     }
     
@@ -736,7 +1831,7 @@ async fn get_rifts() -> Result<Vec<RiftInfo>> {
 /// Create a new rift
 async fn create_rift(name: &str, description: Option<String>) -> Result<uuid::Uuid> {
     // Check if we're in a project directory
-    let _project_metadata = get_current_project_metadata()?;
+    let project_metadata = get_current_project_metadata()?;
     
     // Get active server connection
     let active_server = connections::get_active_server()?
@@ -747,16 +1842,18 @@ async fn create_rift(name: &str, description: Option<String>) -> Result<uuid::Uu
         .ok_or_else(|| anyhow!("Not authenticated. Please run 'mothership auth' first."))?;
     
     // Make API call to create rift
-    let client = reqwest::Client::new();
+    let client = tls::http_client(&active_server.url)?;
     let url = format!("{}/api/rifts", active_server.url);
     
     #[derive(serde::Serialize)]
     struct CreateRiftRequest {
+        project_id: String,
         name: String,
         description: Option<String>,
     }
     
     let request = CreateRiftRequest {
+        project_id: project_metadata.project_id,
         name: name.to_string(),
         description,
     };
@@ -766,18 +1863,18 @@ async fn create_rift(name: &str, description: Option<String>) -> Result<uuid::Uu
         .header("Authorization", format!("Bearer {}", auth_token))
         .json(&request)
         .send()
-        .await?;
+        .await?
+        .check_session()?;
     
     if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(anyhow!("Failed to create rift: {}", error_text));
+        return Err(http::api_error(response, "Failed to create rift").await);
     }
     
     // Parse ApiResponse format
     let api_response: ApiResponse<uuid::Uuid> = response.json().await?;
     
     if !api_response.success {
-        let error_msg = api_response.error.unwrap_or_else(|| "Unknown error".to_string());
+        let error_msg = http::describe_failure(&api_response);
         return Err(anyhow!("Server error: {}", error_msg));
     }
     
@@ -788,7 +1885,7 @@ async fn create_rift(name: &str, description: Option<String>) -> Result<uuid::Uu
 /// Get current rift information
 async fn get_current_rift() -> Result<Option<RiftInfo>> {
     // Check if we're in a project directory
-    let _project_metadata = get_current_project_metadata()?;
+    let project_metadata = get_current_project_metadata()?;
     
     // Get active server connection
     let active_server = connections::get_active_server()?
@@ -799,25 +1896,26 @@ async fn get_current_rift() -> Result<Option<RiftInfo>> {
         .ok_or_else(|| anyhow!("Not authenticated. Please run 'mothership auth' first."))?;
     
     // Make API call to get current rift
-    let client = reqwest::Client::new();
+    let client = tls::http_client(&active_server.url)?;
     let url = format!("{}/api/rifts/current", active_server.url);
     
     let response = client
         .get(&url)
         .header("Authorization", format!("Bearer {}", auth_token))
+        .query(&[("project_id", &project_metadata.project_id)])
         .send()
-        .await?;
+        .await?
+        .check_session()?;
     
     if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(anyhow!("Failed to get current rift: {}", error_text));
+        return Err(http::api_error(response, "Failed to get current rift").await);
     }
     
     // Parse ApiResponse format
     let api_response: ApiResponse<Option<RiftInfo>> = response.json().await?;
     
     if !api_response.success {
-        let error_msg = api_response.error.unwrap_or_else(|| "Unknown error".to_string());
+        let error_msg = http::describe_failure(&api_response);
         return Err(anyhow!("Server error: {}", error_msg));
     }
     
@@ -828,7 +1926,7 @@ async fn get_current_rift() -> Result<Option<RiftInfo>> {
 /// Switch to a different rift
 async fn switch_to_rift(rift_name: &str) -> Result<()> {
     // Check if we're in a project directory
-    let _project_metadata = get_current_project_metadata()?;
+    let project_metadata = get_current_project_metadata()?;
     
     // Get active server connection
     let active_server = connections::get_active_server()?
@@ -839,15 +1937,17 @@ async fn switch_to_rift(rift_name: &str) -> Result<()> {
         .ok_or_else(|| anyhow!("Not authenticated. Please run 'mothership auth' first."))?;
     
     // Make API call to switch rift
-    let client = reqwest::Client::new();
+    let client = tls::http_client(&active_server.url)?;
     let url = format!("{}/api/rifts/switch", active_server.url);
     
     #[derive(serde::Serialize)]
     struct SwitchRiftRequest {
+        project_id: String,
         rift_name: String,
     }
     
     let request = SwitchRiftRequest {
+        project_id: project_metadata.project_id,
         rift_name: rift_name.to_string(),
     };
     
@@ -856,18 +1956,18 @@ async fn switch_to_rift(rift_name: &str) -> Result<()> {
         .header("Authorization", format!("Bearer {}", auth_token))
         .json(&request)
         .send()
-        .await?;
+        .await?
+        .check_session()?;
     
     if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(anyhow!("Failed to switch rift: {}", error_text));
+        return Err(http::api_error(response, "Failed to switch rift").await);
     }
     
     // Parse ApiResponse format
     let api_response: ApiResponse<String> = response.json().await?;
     
     if !api_response.success {
-        let error_msg = api_response.error.unwrap_or_else(|| "Unknown error".to_string());
+        let error_msg = http::describe_failure(&api_response);
         return Err(anyhow!("Server error: {}", error_msg));
     }
     
@@ -877,10 +1977,104 @@ async fn switch_to_rift(rift_name: &str) -> Result<()> {
     Ok(())
 }
 
+/// Look up a rift's ID by name
+async fn find_rift_id(rift_name: &str) -> Result<Uuid> {
+    get_rifts().await?
+        .into_iter()
+        .find(|rift| rift.name == rift_name)
+        .map(|rift| rift.id)
+        .ok_or_else(|| anyhow!("Rift '{}' not found", rift_name))
+}
+
+/// Rename a rift
+async fn rename_rift(rift_name: &str, new_name: &str) -> Result<()> {
+    let rift_id = find_rift_id(rift_name).await?;
+    
+    // Get active server connection
+    let active_server = connections::get_active_server()?
+        .ok_or_else(|| anyhow!("No active server connection. Please run 'mothership connect <server-url>' first."))?;
+    
+    // Get auth token
+    let auth_token = get_oauth_token()
+        .ok_or_else(|| anyhow!("Not authenticated. Please run 'mothership auth' first."))?;
+    
+    // Make API call to rename rift
+    let client = tls::http_client(&active_server.url)?;
+    let url = format!("{}/api/rifts/{}", active_server.url, rift_id);
+    
+    let response = client
+        .patch(&url)
+        .header("Authorization", format!("Bearer {}", auth_token))
+        .json(&RenameRiftRequest { name: new_name.to_string() })
+        .send()
+        .await?
+        .check_session()?;
+    
+    if response.status() == reqwest::StatusCode::FORBIDDEN {
+        return Err(anyhow!("Only the rift's owner can rename it"));
+    }
+    if !response.status().is_success() {
+        return Err(http::api_error(response, "Failed to rename rift").await);
+    }
+    
+    // Parse ApiResponse format
+    let api_response: ApiResponse<serde_json::Value> = response.json().await?;
+    
+    if !api_response.success {
+        let error_msg = http::describe_failure(&api_response);
+        return Err(anyhow!("Server error: {}", error_msg));
+    }
+    
+    Ok(())
+}
+
+/// Delete a rift; the server refuses if it has unmerged changes unless forced
+async fn delete_rift(rift_name: &str, force: bool) -> Result<RiftDeletion> {
+    let rift_id = find_rift_id(rift_name).await?;
+    
+    // Get active server connection
+    let active_server = connections::get_active_server()?
+        .ok_or_else(|| anyhow!("No active server connection. Please run 'mothership connect <server-url>' first."))?;
+    
+    // Get auth token
+    let auth_token = get_oauth_token()
+        .ok_or_else(|| anyhow!("Not authenticated. Please run 'mothership auth' first."))?;
+    
+    // Make API call to delete rift
+    let client = tls::http_client(&active_server.url)?;
+    let url = format!("{}/api/rifts/{}", active_server.url, rift_id);
+    
+    let response = client
+        .delete(&url)
+        .header("Authorization", format!("Bearer {}", auth_token))
+        .query(&[("force", force)])
+        .send()
+        .await?
+        .check_session()?;
+    
+    if response.status() == reqwest::StatusCode::FORBIDDEN {
+        return Err(anyhow!("Only the rift's owner can delete it"));
+    }
+    if !response.status().is_success() {
+        return Err(http::api_error(response, "Failed to delete rift").await);
+    }
+    
+    // Parse ApiResponse format
+    let api_response: ApiResponse<RiftDeletion> = response.json().await?;
+    
+    if !api_response.success {
+        let error_msg = http::describe_failure(&api_response);
+        return Err(anyhow!("Server error: {}", error_msg));
+    }
+    
+    let deletion = api_response.data.ok_or_else(|| anyhow!("No deletion data received"))?;
+    Ok(deletion)
+}
+
 /// Get differences between two rifts
 async fn get_rift_diffs(from_rift: &str, to_rift: &str) -> Result<Vec<RiftDiff>> {
     // Check if we're in a project directory
-    let _project_metadata = get_current_project_metadata()?;
+    let project_metadata = get_current_project_metadata()?;
     
     // Get active server connection
     let active_server = connections::get_active_server()?
@@ -891,26 +2085,26 @@ async fn get_rift_diffs(from_rift: &str, to_rift: &str) -> Result<Vec<RiftDiff>>
         .ok_or_else(|| anyhow!("Not authenticated. Please run 'mothership auth' first."))?;
     
     // Make API call to get rift diffs
-    let client = reqwest::Client::new();
+    let client = tls::http_client(&active_server.url)?;
     let url = format!("{}/api/rifts/diff", active_server.url);
     
     let response = client
         .get(&url)
         .header("Authorization", format!("Bearer {}", auth_token))
-        .query(&[("from", from_rift), ("to", to_rift)])
+        .query(&[("project_id", project_metadata.project_id.as_str()), ("from", from_rift), ("to", to_rift)])
         .send()
-        .await?;
+        .await?
+        .check_session()?;
     
     if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(anyhow!("Failed to get rift diffs: {}", error_text));
+        return Err(http::api_error(response, "Failed to get rift diffs").await);
     }
     
     // Parse ApiResponse format
     let api_response: ApiResponse<Vec<RiftDiff>> = response.json().await?;
     
     if !api_response.success {
-        let error_msg = api_response.error.unwrap_or_else(|| "Unknown error".to_string());
+        let error_msg = http::describe_failure(&api_response);
         return Err(anyhow!("Server error: {}", error_msg));
     }
     
@@ -920,7 +2114,7 @@ async fn get_rift_diffs(from_rift: &str, to_rift: &str) -> Result<Vec<RiftDiff>>
 
 /// Helper function to get current project metadata
 fn get_current_project_metadata() -> Result<ProjectMetadata> {
-    let current_dir = std::env::current_dir()?;
+    let current_dir = crate::project_dir()?;
     let mothership_dir = current_dir.join(".mothership");
     let project_file = mothership_dir.join("project.json");
 
@@ -938,7 +2132,7 @@ fn get_current_project_metadata() -> Result<ProjectMetadata> {
 
 /// Helper function to update local rift metadata
 fn update_local_rift_metadata(rift_name: &str) -> Result<()> {
-    let current_dir = std::env::current_dir()?;
+    let current_dir = crate::project_dir()?;
     let mothership_dir = current_dir.join(".mothership");
     let project_file = mothership_dir.join("project.json");
 
@@ -968,6 +2162,9 @@ struct ProjectMetadata {
     mothership_url: String,
     rift_id: Option<String>,
     current_rift: Option<String>,
+    /// Profile pinned with 'mothership profile pin'
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    profile: Option<String>,
 }
 
 async fn handle_daemon_status() -> Result<()> {
@@ -1031,26 +2228,43 @@ fn print_verbose_help() {
     println!("    automatic conflict resolution, and seamless project synchronization across teams.");
     println!();
     
+    // Commands for features the server has turned off aren't listed
+    let disabled_features = connections::cached_disabled_features();
+    
     println!("{}", "CORE COMMANDS:".bright_yellow().bold());
     print_command_section("🔐", "auth", "Authentication & Setup", &[
         ("google", "Login with Google OAuth", None),
         ("github", "Login with GitHub OAuth", None),
+        ("token create", "Create a personal access token", Some("<name> --scope <scope> --expires-in-days <n>")),
+        ("token list", "List personal access tokens", None),
+        ("token revoke", "Revoke a personal access token", Some("<id>")),
+        ("sessions list", "List machines you are logged in on", Some("--user <username>")),
+        ("sessions revoke", "Log a machine out remotely", Some("<machine> --user <username>")),
     ]);
     
     print_command_section("🌌", "gateway", "Project Management", &[
-        ("list", "List available projects", Some("--include-inactive")),
+        ("list", "List available projects", Some("--include-inactive --archived")),
         ("create", "Create a new project", Some("<name> --dir <path>")),
         ("disconnect", "Stop tracking a project", Some("[project]")),
+        ("archive", "Make a project read-only", Some("<project> --compact")),
+        ("unarchive", "Make an archived project writable", Some("<project>")),
+        ("conflict-policy", "Show or set conflict resolution", Some("<project> [policy]")),
+        ("push-rules", "Show or set checkpoint and sync rules", Some("<project> --require-message <bool> --max-file-size-mb <n> --deny <pattern>")),
+        ("secrets", "List secrets found in changes", Some("<project> --mode <off|warn|reject>")),
+        ("auto-checkpoint", "Show or set the automatic checkpoint interval", Some("<project> [seconds]")),
     ]);
     
     print_command_section("🚀", "beam", "Project Development", &[]);
     println!("    {} {}", "mothership beam".green().bold(), "<project> [OPTIONS]".white());
     println!("    {} {}", "   --rift".bright_blue(), "<name>                Specify rift to join".dimmed());
     println!("    {} {}", "   --local-dir".bright_blue(), "<path>           Local directory for project".dimmed());
+    println!("    {} {}", "   --path".bright_blue(), "<path>                Only sync this path (repeatable)".dimmed());
+    println!("    {} {}", "   --observe".bright_blue(), "                    Watch the rift without sending changes".dimmed());
     println!();
     
     print_command_section("📊", "status", "Project Status", &[]);
     println!("    {} {}", "mothership status".green().bold(), "                        Check sync status".dimmed());
+    println!("    {} {}", "   --porcelain".bright_blue(), "                 Changed files only, for scripts".dimmed());
     println!();
     
     print_command_section("📸", "checkpoint", "Version Control", &[]);
@@ -1061,14 +2275,88 @@ fn print_verbose_help() {
     println!("    {} {}", "mothership sync".green().bold(), "                         Sync with remote".dimmed());
     println!();
     
+    print_command_section("⬆️", "push", "Offline Work", &[]);
+    println!("    {} {}", "mothership push".green().bold(), "                         Upload local-only checkpoints".dimmed());
+    println!();
+    
+    print_command_section("🗃️", "stash", "Set Changes Aside", &[
+        ("push", "Stash changes since the last checkpoint", Some("[-m <message>] [--backup]")),
+        ("pop", "Bring back the latest stash", None),
+        ("list", "List stashes", None),
+    ]);
+    
+    print_command_section("🔎", "bisect", "Find Regressions", &[
+        ("start", "Start bisecting the current rift", Some("[--bad <id>] [--good <id>]")),
+        ("good", "Mark the tested checkpoint as working", Some("[<checkpoint-id>]")),
+        ("bad", "Mark the tested checkpoint as broken", Some("[<checkpoint-id>]")),
+        ("reset", "Stop and restore the original files", None),
+    ]);
+    
+    print_command_section("👀", "review", "Review Before Merging", &[
+        ("open", "Ask to merge the current rift", Some("\"<title>\" [--into <rift>] [--reviewer <user>]")),
+        ("list", "List open review requests", Some("[--all]")),
+        ("show", "Show reviewers and comments", Some("<id>")),
+        ("request", "Ask more people to review", Some("<id> <user>...")),
+        ("approve", "Approve a review request", Some("<id> [-c <comment>]")),
+        ("comment", "Comment on a review request", Some("<id> <text>")),
+        ("merge", "Merge once approved", Some("<id>")),
+        ("close", "Close without merging", Some("<id>")),
+    ]);
+    
     print_command_section("📜", "history", "Project History", &[]);
     println!("    {} {}", "mothership history".green().bold(), "[OPTIONS]            View checkpoints".dimmed());
     println!("    {} {}", "   --limit".bright_blue(), "<num>               Limit results (default: 20)".dimmed());
+    println!("    {} {}", "   --deepen".bright_blue(), "                    Load the next page of older checkpoints".dimmed());
+    println!("    {} {}", "   --graph".bright_blue(), "                     Draw rifts, branches and merges".dimmed());
+    println!("    {} {}", "   --comments".bright_blue(), "                  Show comment threads on checkpoints".dimmed());
+    println!("    {} {}", "mothership history compact".green().bold(), "[OPTIONS]    Fold old automatic checkpoints together".dimmed());
+    println!("    {} {}", "   --older-than-days".bright_blue(), "<n>         Only fold checkpoints older than this".dimmed());
+    println!();
+    
+    print_command_section("📰", "activity", "Project Activity", &[]);
+    println!("    {} {}", "mothership activity".green().bold(), "[OPTIONS]           Checkpoints, merges, rifts and members".dimmed());
+    println!("    {} {}", "   --limit".bright_blue(), "<num>               Limit results (default: 20)".dimmed());
+    println!("    {} {}", "   --before".bright_blue(), "<time>             Show older activity".dimmed());
+    println!();
+    
+    print_command_section("🔍", "log", "File History", &[]);
+    println!("    {} {}", "mothership log".green().bold(), "<file> [--limit <num>]    Checkpoints that changed a file".dimmed());
+    println!("    {} {}", "mothership blame".green().bold(), "<file>                 Author of each line".dimmed());
+    println!("    {} {}", "   --checkpoint".bright_blue(), "<id>             Annotate as of a checkpoint".dimmed());
     println!();
     
+    print_command_section("🔔", "inbox", "Notifications", &[]);
+    println!("    {} {}", "mothership inbox".green().bold(), "[OPTIONS]              Mentions, review requests and more".dimmed());
+    println!("    {} {}", "   --all".bright_blue(), "                        Include read notifications".dimmed());
+    println!("    {} {}", "   --read".bright_blue(), "<id>                 Mark a notification read".dimmed());
+    println!("    {} {}", "   --read-all".bright_blue(), "                   Mark everything read".dimmed());
+    println!();
+    
+    if !disabled_features.contains(&Feature::Chat) {
+        print_command_section("💬", "chat", "Rift Chat", &[]);
+        println!("    {} {}", "mothership chat".green().bold(), "[message]               Send or view rift chat".dimmed());
+        println!("    {} {}", "   --limit".bright_blue(), "<num>               Messages to show (default: 20)".dimmed());
+        println!("    {} {}", "   --follow".bright_blue(), "                      Watch for new messages".dimmed());
+        println!();
+    }
+    
+    if !disabled_features.contains(&Feature::TerminalSharing) {
+        print_command_section("🖥️", "share-terminal", "Terminal Sharing", &[]);
+        println!("    {} {}", "mothership share-terminal".green().bold(), "[command...]  Stream a terminal into the rift".dimmed());
+        println!();
+    }
+    
     print_command_section("🔄", "restore", "Time Travel", &[]);
     println!("    {} {}", "mothership restore".green().bold(), "<checkpoint-id>      Restore to checkpoint".dimmed());
+    println!("    {} {}", "   --path".bright_blue(), "<path>               Restore only a file or directory".dimmed());
     println!("    {} {}", "   --force".bright_blue(), "                       Skip confirmation".dimmed());
+    println!("    {} {}", "   --dry-run".bright_blue(), "                     Preview what would change".dimmed());
+    println!("    {} {}", "mothership revert".green().bold(), "<checkpoint-id>       Undo a checkpoint with a new one".dimmed());
+    println!();
+    
+    print_command_section("📦", "export", "Backup & Migration", &[]);
+    println!("    {} {}", "mothership export".green().bold(), "<project> [--output <file>]  Save project and history".dimmed());
+    println!("    {} {}", "mothership import".green().bold(), "<file> [--name <name>]       Create project from bundle".dimmed());
     println!();
     
     print_command_section("🗑️", "delete", "Project Cleanup", &[]);
@@ -1081,7 +2369,7 @@ fn print_verbose_help() {
     
     print_command_section("📡", "server", "Server Operations", &[
         ("status", "Check connection status", None),
-        ("disconnect", "Disconnect from server", None),
+        ("disconnect", "Switch to local-only mode", None),
         ("list", "List configured servers", None),
     ]);
     
@@ -1095,8 +2383,14 @@ fn print_verbose_help() {
         ("list", "List project rifts", Some("--detailed")),
         ("new", "Create a new rift", Some("<name> --description <desc>")),
         ("switch", "Switch to a rift", Some("<name>")),
+        ("rename", "Rename a rift you own", Some("<name> <new-name>")),
+        ("delete", "Delete a rift you own", Some("<name> [--force]")),
         ("status", "Show current rift", None),
         ("diff", "Compare rifts", Some("[from] [to]")),
+        ("share", "Share rift with a user", Some("<user> [--observer]")),
+        ("unshare", "Stop sharing rift", Some("<user>")),
+        ("visibility", "Set who may join", Some("<project|invite|private>")),
+        ("access", "Show who may join", None),
     ]);
     
     print_command_section("🚀", "init", "Quick Init", &[]);
@@ -0,0 +1,75 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use mothership_common::{profile::{self, Profiles, PROFILE_ENV_VAR}, project_root};
+use std::path::PathBuf;
+
+use crate::{print_info, print_success};
+
+/// Root of the project containing the current directory
+fn find_project_root() -> Result<PathBuf> {
+    let current_dir = std::env::current_dir()?;
+    project_root::find_project_root(&current_dir)
+        .ok_or_else(|| anyhow!("Not in a Mothership project directory"))
+}
+
+/// List profiles, marking the default and the one in effect here
+pub fn handle_profile_list() -> Result<()> {
+    let profiles = Profiles::load();
+    if profiles.profiles.is_empty() {
+        print_info("No profiles yet");
+        print_info("Use 'mothership auth --profile <name> --server <url>' to create one");
+        return Ok(());
+    }
+
+    let active = profile::active_profile();
+    println!("\n{}", "👤 Profiles".cyan().bold());
+    for (name, details) in &profiles.profiles {
+        let is_active = active.as_deref() == Some(name.as_str());
+        let marker = if is_active { "🟢" } else { "⚪" };
+        let display_name = if is_active { name.green().bold() } else { name.normal() };
+        let default = if profiles.default.as_deref() == Some(name.as_str()) { " (default)".dimmed().to_string() } else { String::new() };
+        let signed_in = profile::credentials_path_for(Some(name)).is_some_and(|path| path.exists());
+        let credentials = if signed_in { "signed in".green() } else { "not signed in".yellow() };
+        println!("{} {}{} {} [{}]", marker, display_name, default, details.server_url.dimmed(), credentials);
+    }
+
+    let current_dir = std::env::current_dir()?;
+    if let Ok(name) = std::env::var(PROFILE_ENV_VAR) {
+        println!("\n{}", format!("Selected by {}={}", PROFILE_ENV_VAR, name).dimmed());
+    } else if let Some(name) = profile::pinned_profile(&current_dir) {
+        println!("\n{}", format!("This project is pinned to '{}'", name).dimmed());
+    }
+    Ok(())
+}
+
+/// Make a profile the default outside pinned projects
+pub fn handle_profile_use(name: String) -> Result<()> {
+    let mut profiles = Profiles::load();
+    if !profiles.profiles.contains_key(&name) {
+        return Err(anyhow!("No profile named '{}'. Use 'mothership auth --profile {} --server <url>' to create it", name, name));
+    }
+    profiles.default = Some(name.clone());
+    profiles.save()?;
+    print_success(&format!("Using profile '{}' by default", name));
+    Ok(())
+}
+
+/// Pin the current project to a profile, or remove its pin
+pub fn handle_profile_pin(name: Option<String>) -> Result<()> {
+    let project_root = find_project_root()?;
+    match name {
+        Some(name) => {
+            if !Profiles::load().profiles.contains_key(&name) {
+                return Err(anyhow!("No profile named '{}'. Use 'mothership auth --profile {} --server <url>' to create it", name, name));
+            }
+            profile::pin(&project_root, Some(&name))?;
+            print_success(&format!("Pinned {} to profile '{}'", project_root.display(), name));
+            print_info("Commands run in this project now use its server and credentials");
+        }
+        None => {
+            profile::pin(&project_root, None)?;
+            print_success(&format!("Removed the profile pin of {}", project_root.display()));
+        }
+    }
+    Ok(())
+}
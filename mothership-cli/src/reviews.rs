@@ -0,0 +1,265 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use mothership_common::{
+    protocol::{
+        AddReviewersRequest, ApiResponse, ApproveReviewRequest, OpenReviewRequest, ReviewCommentRequest, ReviewRequest,
+        RiftMergeResult,
+    },
+    ReviewState,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{config::ConfigManager, get_http_client, http::{self, ResponseExt}, print_api_error, print_info, print_success, connections};
+
+/// Local project metadata stored in .mothership directory
+#[derive(Deserialize)]
+struct ProjectMetadata {
+    project_id: String,
+}
+
+/// Base URL of the current project's review request endpoints
+fn reviews_url(config_manager: &ConfigManager) -> Result<String> {
+    let project_file = crate::project_dir()?.join(".mothership").join("project.json");
+    if !project_file.exists() {
+        return Err(anyhow!(
+            "Not in a Mothership project directory.\n\
+            Run this command from a project directory, or use 'mothership beam <project>' to enter a project."
+        ));
+    }
+    let metadata: ProjectMetadata = serde_json::from_str(&std::fs::read_to_string(&project_file)?)?;
+
+    let server_url = match connections::get_active_server_url() {
+        Some(server_url) => server_url,
+        None => config_manager.load_config()?.mothership_url,
+    };
+    Ok(format!("{}/projects/{}/reviews", server_url, metadata.project_id))
+}
+
+fn parse_review_id(id: &str) -> Result<Uuid> {
+    Uuid::parse_str(id).map_err(|_| anyhow!("Invalid review ID. Use the full ID from 'mothership review list'"))
+}
+
+/// Send a request and read the data out of the response, printing the
+/// server's explanation when there is none
+async fn send<T: DeserializeOwned>(request: reqwest::RequestBuilder, context: &str) -> Result<Option<T>> {
+    let response = request.send().await?.check_session()?;
+    if !response.status().is_success() {
+        return Err(http::api_error(response, context).await);
+    }
+
+    let result: ApiResponse<T> = response.json().await?;
+    if result.data.is_none() {
+        print_api_error(&http::describe_failure(&result));
+    }
+    Ok(result.data)
+}
+
+async fn post<B: Serialize, T: DeserializeOwned>(config_manager: &ConfigManager, path: &str, body: &B, context: &str) -> Result<Option<T>> {
+    let client = get_http_client(&config_manager.load_config()?);
+    let url = format!("{}{}", reviews_url(config_manager)?, path);
+    send(client.post(url).json(body), context).await
+}
+
+fn state_label(review: &ReviewRequest) -> ColoredString {
+    match review.state {
+        ReviewState::Open if review.is_approved() => "approved".green().bold(),
+        ReviewState::Open => "open".yellow().bold(),
+        ReviewState::Merged => "merged".magenta().bold(),
+        ReviewState::Closed => "closed".dimmed(),
+    }
+}
+
+fn print_summary(review: &ReviewRequest) {
+    println!("\n{} {} {}", state_label(review), review.title.white().bold(), format!("({})", review.id).dimmed());
+    println!("   {} → {} • by {} • {}/{} approvals",
+        review.source_rift_name.cyan(),
+        review.target_rift_name.cyan(),
+        review.author_name,
+        review.approvals(),
+        review.required_approvals,
+    );
+}
+
+fn print_review(review: &ReviewRequest) {
+    print_summary(review);
+    if let Some(description) = &review.description {
+        println!();
+        for line in description.lines() {
+            println!("   {}", line);
+        }
+    }
+
+    if !review.reviewers.is_empty() {
+        println!("\n{}", "Reviewers:".cyan().bold());
+        for reviewer in &review.reviewers {
+            match reviewer.approved_at {
+                Some(approved_at) => println!("  {} {} {}", "✓".green(), reviewer.username.white().bold(),
+                    format!("approved {}", approved_at.format("%Y-%m-%d %H:%M")).dimmed()),
+                None => println!("  {} {} {}", "●".yellow(), reviewer.username.white().bold(), "waiting".dimmed()),
+            }
+        }
+    }
+
+    if !review.comments.is_empty() {
+        println!("\n{}", "Comments:".cyan().bold());
+        for comment in &review.comments {
+            println!("  {} {}", comment.author_name.white().bold(), comment.created_at.format("%Y-%m-%d %H:%M").to_string().dimmed());
+            for line in comment.body.lines() {
+                println!("    {}", line);
+            }
+        }
+    }
+
+    if let Some(checkpoint) = review.merge_checkpoint {
+        println!("\n{} {}", "Merged as checkpoint".dimmed(), checkpoint.to_string().yellow());
+    }
+}
+
+/// Open a request to merge a rift (the current one by default)
+pub async fn handle_open(
+    config_manager: &ConfigManager,
+    title: String,
+    rift: Option<String>,
+    into: Option<String>,
+    description: Option<String>,
+    reviewers: Vec<String>,
+    approvals: Option<u32>,
+) -> Result<()> {
+    let source_rift = match rift {
+        Some(rift) => rift,
+        None => crate::get_current_rift().await?
+            .ok_or_else(|| anyhow!("Not currently in any rift; name the rift to merge with --rift"))?
+            .name,
+    };
+
+    let request = OpenReviewRequest {
+        source_rift,
+        target_rift: into,
+        title,
+        description,
+        reviewers,
+        required_approvals: approvals,
+    };
+    if let Some(review) = post::<_, ReviewRequest>(config_manager, "", &request, "Failed to open review request").await? {
+        print_success(&format!("Opened review request to merge {} into {}", review.source_rift_name, review.target_rift_name));
+        print_review(&review);
+        if review.reviewers.is_empty() {
+            print_info("No reviewers yet. Anyone in the project can approve it, or ask someone with --reviewer.");
+        }
+    }
+    Ok(())
+}
+
+/// List the project's open review requests, or all of them
+pub async fn handle_list(config_manager: &ConfigManager, all: bool) -> Result<()> {
+    let client = get_http_client(&config_manager.load_config()?);
+    let url = reviews_url(config_manager)?;
+    let state = if all { "all" } else { "open" };
+
+    let Some(reviews) = send::<Vec<ReviewRequest>>(client.get(url).query(&[("state", state)]), "Failed to list review requests").await? else {
+        return Ok(());
+    };
+
+    if reviews.is_empty() {
+        print_info(if all { "No review requests yet" } else { "No open review requests" });
+        print_info("Open one with 'mothership review open \"<title>\"'");
+        return Ok(());
+    }
+
+    println!("{}", "👀 Review Requests".cyan().bold());
+    for review in &reviews {
+        print_summary(review);
+    }
+    Ok(())
+}
+
+pub async fn handle_show(config_manager: &ConfigManager, id: String) -> Result<()> {
+    let review_id = parse_review_id(&id)?;
+    let client = get_http_client(&config_manager.load_config()?);
+    let url = format!("{}/{}", reviews_url(config_manager)?, review_id);
+
+    if let Some(review) = send::<ReviewRequest>(client.get(url), "Failed to load review request").await? {
+        print_review(&review);
+    }
+    Ok(())
+}
+
+pub async fn handle_add_reviewers(config_manager: &ConfigManager, id: String, reviewers: Vec<String>) -> Result<()> {
+    let review_id = parse_review_id(&id)?;
+    let request = AddReviewersRequest { reviewers: reviewers.clone() };
+    let path = format!("/{}/reviewers", review_id);
+
+    if post::<_, ReviewRequest>(config_manager, &path, &request, "Failed to add reviewers").await?.is_some() {
+        print_success(&format!("Asked {} for a review", reviewers.join(", ")));
+    }
+    Ok(())
+}
+
+pub async fn handle_approve(config_manager: &ConfigManager, id: String, comment: Option<String>) -> Result<()> {
+    let review_id = parse_review_id(&id)?;
+    let path = format!("/{}/approve", review_id);
+
+    if let Some(review) = post::<_, ReviewRequest>(config_manager, &path, &ApproveReviewRequest { comment }, "Failed to approve review request").await? {
+        print_success(&format!("Approved '{}' ({}/{} approvals)", review.title, review.approvals(), review.required_approvals));
+        if review.is_approved() {
+            print_info(&format!("Ready to merge with 'mothership review merge {}'", review.id));
+        }
+    }
+    Ok(())
+}
+
+pub async fn handle_comment(config_manager: &ConfigManager, id: String, body: String) -> Result<()> {
+    let review_id = parse_review_id(&id)?;
+    let path = format!("/{}/comments", review_id);
+
+    if post::<_, ReviewRequest>(config_manager, &path, &ReviewCommentRequest { body }, "Failed to comment").await?.is_some() {
+        print_success("Comment added");
+    }
+    Ok(())
+}
+
+/// Merge an approved review request's rift into its target
+pub async fn handle_merge(config_manager: &ConfigManager, id: String) -> Result<()> {
+    let review_id = parse_review_id(&id)?;
+    let client = get_http_client(&config_manager.load_config()?);
+    let url = format!("{}/{}/merge", reviews_url(config_manager)?, review_id);
+
+    let response = client.post(url).send().await?.check_session()?;
+    if !response.status().is_success() {
+        return Err(http::api_error(response, "Failed to merge").await);
+    }
+
+    // A merge with conflicts comes back unsuccessful, with the conflicting files
+    let result: ApiResponse<RiftMergeResult> = response.json().await?;
+    match &result.data {
+        Some(merge) if merge.merged => {
+            print_success(&format!("Merged {} file{}", merge.merged_files, if merge.merged_files == 1 { "" } else { "s" }));
+            if let Some(checkpoint) = merge.checkpoint_id {
+                println!("{} {}", "Merge checkpoint:".dimmed(), checkpoint.to_string().yellow());
+            }
+        }
+        Some(merge) => {
+            print_api_error(&http::describe_failure(&result));
+            for conflict in &merge.conflicts {
+                match &conflict.content {
+                    Some(_) => println!("  {} {} ({} conflicting hunk{})", "✗".red(), conflict.path.display(),
+                        conflict.conflicts, if conflict.conflicts == 1 { "" } else { "s" }),
+                    None => println!("  {} {} (changed on one side, deleted on the other)", "✗".red(), conflict.path.display()),
+                }
+            }
+        }
+        None => print_api_error(&http::describe_failure(&result)),
+    }
+    Ok(())
+}
+
+pub async fn handle_close(config_manager: &ConfigManager, id: String) -> Result<()> {
+    let review_id = parse_review_id(&id)?;
+    let path = format!("/{}/close", review_id);
+
+    if let Some(review) = post::<_, ReviewRequest>(config_manager, &path, &serde_json::json!({}), "Failed to close review request").await? {
+        print_success(&format!("Closed '{}' without merging", review.title));
+    }
+    Ok(())
+}
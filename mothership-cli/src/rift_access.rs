@@ -0,0 +1,166 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use mothership_common::{
+    protocol::{ApiResponse, RiftAccessInfo, ShareRiftRequest, UpdateRiftVisibilityRequest},
+    RiftRole, RiftVisibility,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{config::ConfigManager, get_http_client, http::{self, ResponseExt}, print_api_error, print_info, print_success, connections};
+
+/// Local project metadata stored in .mothership directory
+#[derive(Deserialize)]
+struct ProjectMetadata {
+    project_id: String,
+    rift_id: Option<String>,
+}
+
+/// Get the server URL to use for rift access operations
+/// Prioritizes active server connection over config file
+fn get_server_url(config_manager: &ConfigManager) -> Result<String> {
+    if let Some(server_url) = connections::get_active_server_url() {
+        return Ok(server_url);
+    }
+
+    let config = config_manager.load_config()?;
+    Ok(config.mothership_url)
+}
+
+/// Find the project and rift of the current directory
+fn find_current_rift() -> Result<(Uuid, Uuid)> {
+    let project_file = crate::project_dir()?.join(".mothership").join("project.json");
+    if !project_file.exists() {
+        return Err(anyhow!(
+            "Not in a Mothership project directory.\n\
+            Run this command from a project directory, or use 'mothership beam <project>' to enter a project."
+        ));
+    }
+
+    let metadata: ProjectMetadata = serde_json::from_str(&std::fs::read_to_string(&project_file)?)?;
+    let rift_id = metadata.rift_id
+        .ok_or_else(|| anyhow!("No rift for this project yet. Run 'mothership beam' to join one."))?;
+
+    Ok((Uuid::parse_str(&metadata.project_id)?, Uuid::parse_str(&rift_id)?))
+}
+
+/// Base URL of the current rift's access endpoints
+fn access_url(config_manager: &ConfigManager) -> Result<String> {
+    let (project_id, rift_id) = find_current_rift()?;
+    let server_url = get_server_url(config_manager)?;
+    Ok(format!("{}/projects/{}/rifts/{}/access", server_url, project_id, rift_id))
+}
+
+/// Turn an access endpoint response into the rift's access settings
+async fn read_access_response(response: reqwest::Response) -> Result<Option<RiftAccessInfo>> {
+    if !response.status().is_success() {
+        return Err(http::api_error(response, "Rift access request failed").await);
+    }
+
+    let result: ApiResponse<RiftAccessInfo> = response.json().await?;
+    if result.data.is_none() {
+        print_api_error(&http::describe_failure(&result));
+    }
+    Ok(result.data)
+}
+
+fn print_access(info: &RiftAccessInfo) {
+    let visibility = match info.visibility {
+        RiftVisibility::Project => "project (any project member can join)",
+        RiftVisibility::Invite => "invite-only",
+        RiftVisibility::Private => "private (owner only)",
+    };
+    println!("\n{} {}", "🔒 Visibility:".cyan().bold(), visibility.white().bold());
+
+    if info.shared_with.is_empty() {
+        print_info("Not shared with anyone. Use 'mothership rift share <user>' to invite someone.");
+        return;
+    }
+
+    println!("{}", "Shared with:".cyan().bold());
+    for permission in &info.shared_with {
+        let role = match permission.role {
+            RiftRole::Observer => permission.role.as_str().yellow(),
+            _ => permission.role.as_str().green(),
+        };
+        println!("  {} {} {}", "•".dimmed(), permission.username.white().bold(), format!("({})", role).dimmed());
+    }
+}
+
+/// Show who may join the current rift
+pub async fn handle_access(config_manager: &ConfigManager) -> Result<()> {
+    let url = access_url(config_manager)?;
+    let client = get_http_client(&config_manager.load_config()?);
+
+    if let Some(info) = read_access_response(client.get(&url).send().await?.check_session()?).await? {
+        print_access(&info);
+    }
+    Ok(())
+}
+
+/// Share the current rift with a project member
+pub async fn handle_share(config_manager: &ConfigManager, username: String, observer: bool) -> Result<()> {
+    let url = format!("{}/shares", access_url(config_manager)?);
+    let client = get_http_client(&config_manager.load_config()?);
+    let role = if observer { RiftRole::Observer } else { RiftRole::Editor };
+
+    let response = client
+        .post(&url)
+        .json(&ShareRiftRequest { username: username.clone(), role })
+        .send()
+        .await?
+        .check_session()?;
+
+    if let Some(info) = read_access_response(response).await? {
+        print_success(&format!("Shared rift with {} as {}", username, role.as_str()));
+        if info.visibility == RiftVisibility::Project && role == RiftRole::Editor {
+            print_info("This rift is open to the whole project. Use 'mothership rift visibility invite' to restrict it.");
+        }
+    }
+    Ok(())
+}
+
+/// Stop sharing the current rift with a user
+pub async fn handle_unshare(config_manager: &ConfigManager, username: String) -> Result<()> {
+    let url = access_url(config_manager)?;
+    let client = get_http_client(&config_manager.load_config()?);
+
+    let Some(info) = read_access_response(client.get(&url).send().await?.check_session()?).await? else {
+        return Ok(());
+    };
+    let Some(permission) = info.shared_with.iter().find(|p| p.username == username) else {
+        print_api_error(&format!("Rift is not shared with {}", username));
+        return Ok(());
+    };
+
+    let response = client
+        .delete(format!("{}/shares/{}", url, permission.user_id))
+        .send()
+        .await?
+        .check_session()?;
+
+    if read_access_response(response).await?.is_some() {
+        print_success(&format!("Rift is no longer shared with {}", username));
+    }
+    Ok(())
+}
+
+/// Change who may join the current rift
+pub async fn handle_visibility(config_manager: &ConfigManager, level: String) -> Result<()> {
+    let visibility = RiftVisibility::parse(&level.to_lowercase())
+        .ok_or_else(|| anyhow!("Unknown visibility '{}'. Use project, invite or private.", level))?;
+    let url = access_url(config_manager)?;
+    let client = get_http_client(&config_manager.load_config()?);
+
+    let response = client
+        .put(&url)
+        .json(&UpdateRiftVisibilityRequest { visibility })
+        .send()
+        .await?
+        .check_session()?;
+
+    if let Some(info) = read_access_response(response).await? {
+        print_access(&info);
+    }
+    Ok(())
+}
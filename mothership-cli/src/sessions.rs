@@ -0,0 +1,246 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use mothership_common::{auth::{AuthSession, Machine}, protocol::ApiResponse};
+
+use crate::{config::ConfigManager, get_http_client, http::{self, ResponseExt}, print_api_error, print_info, print_success, connections};
+
+/// Get the server URL to use for session operations
+/// Prioritizes active server connection over config file
+fn get_server_url(config_manager: &ConfigManager) -> Result<String> {
+    if let Some(server_url) = connections::get_active_server_url() {
+        return Ok(server_url);
+    }
+
+    let config = config_manager.load_config()?;
+    Ok(config.mothership_url)
+}
+
+/// Fetch active sessions, of another user if `user` is set
+async fn fetch_sessions(config_manager: &ConfigManager, user: Option<&str>) -> Result<Option<Vec<AuthSession>>> {
+    let config = config_manager.load_config()?;
+    let server_url = get_server_url(config_manager)?;
+    let client = get_http_client(&config);
+
+    let mut request = client.get(format!("{}/auth/sessions", server_url));
+    if let Some(user) = user {
+        request = request.query(&[("user", user)]);
+    }
+    let response = request.send().await?.check_session()?;
+
+    if response.status() == reqwest::StatusCode::FORBIDDEN {
+        print_api_error("Only admins can manage other users' sessions");
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        return Err(http::api_error(response, "Session request failed").await);
+    }
+
+    let result: ApiResponse<Vec<AuthSession>> = response.json().await?;
+    match result.data {
+        Some(sessions) => Ok(Some(sessions)),
+        None => {
+            print_api_error(&http::describe_failure(&result));
+            Ok(None)
+        }
+    }
+}
+
+/// List active login sessions
+pub async fn handle_sessions_list(config_manager: &ConfigManager, user: Option<String>) -> Result<()> {
+    let Some(sessions) = fetch_sessions(config_manager, user.as_deref()).await? else {
+        return Ok(());
+    };
+
+    if sessions.is_empty() {
+        print_info("No active sessions.");
+        return Ok(());
+    }
+
+    let owner = user.map(|u| format!(" of {}", u)).unwrap_or_default();
+    println!("\n{}", format!("🖥️  Active Sessions{}", owner).cyan().bold());
+    for session in sessions {
+        let marker = if session.current { " (this machine)".green().to_string() } else { String::new() };
+        println!("\n  {} {}{}", session.id.to_string().dimmed(), session.machine_name.white().bold(), marker);
+        println!("    {} {} ({})", "Host:".dimmed(), session.hostname, session.platform);
+        println!("    {} {}", "Logged in:".dimmed(), crate::sync::format_time_ago(session.created_at));
+        let last_seen = session.last_seen_at
+            .map(crate::sync::format_time_ago)
+            .unwrap_or_else(|| "never".to_string());
+        println!("    {} {}", "Last seen:".dimmed(), last_seen);
+        println!("    {} {}", "Expires:".dimmed(), session.expires_at.format("%Y-%m-%d %H:%M UTC"));
+    }
+
+    Ok(())
+}
+
+/// Revoke a session, identified by ID (or ID prefix), machine name or hostname
+pub async fn handle_session_revoke(config_manager: &ConfigManager, machine: String, user: Option<String>) -> Result<()> {
+    let Some(sessions) = fetch_sessions(config_manager, user.as_deref()).await? else {
+        return Ok(());
+    };
+
+    let needle = machine.to_lowercase();
+    let matches: Vec<&AuthSession> = sessions.iter()
+        .filter(|s| {
+            s.id.to_string().starts_with(&needle)
+                || s.machine_id.to_lowercase() == needle
+                || s.machine_name.to_lowercase() == needle
+                || s.hostname.to_lowercase() == needle
+        })
+        .collect();
+
+    let session = match matches.as_slice() {
+        [] => {
+            print_api_error(&format!("No active session matches '{}'. Use 'mothership auth sessions list'.", machine));
+            return Ok(());
+        }
+        [session] => *session,
+        several => {
+            print_api_error(&format!("'{}' matches {} sessions; use the session ID instead:", machine, several.len()));
+            for s in several {
+                println!("  {} {} ({})", s.id.to_string().dimmed(), s.machine_name, s.hostname);
+            }
+            return Ok(());
+        }
+    };
+
+    let config = config_manager.load_config()?;
+    let server_url = get_server_url(config_manager)?;
+    let client = get_http_client(&config);
+
+    let response = client
+        .delete(format!("{}/auth/sessions/{}", server_url, session.id))
+        .send()
+        .await?
+        .check_session()?;
+
+    match response.status() {
+        status if status.is_success() => {
+            print_success(&format!("Revoked session on {} ({})", session.machine_name, session.hostname));
+            if session.current {
+                print_info("This was the current machine. Run 'mothership auth' to log in again.");
+            }
+        }
+        reqwest::StatusCode::NOT_FOUND => print_api_error("Session not found or already revoked"),
+        status => return Err(anyhow!("Session request failed: {}", status)),
+    }
+
+    Ok(())
+}
+
+/// Fetch the machines the user has logged in from
+pub async fn fetch_machines(config_manager: &ConfigManager) -> Result<Option<Vec<Machine>>> {
+    let config = config_manager.load_config()?;
+    let server_url = get_server_url(config_manager)?;
+    let client = get_http_client(&config);
+
+    let response = client
+        .get(format!("{}/users/me/machines", server_url))
+        .send()
+        .await?
+        .check_session()?;
+    if !response.status().is_success() {
+        return Err(http::api_error(response, "Machine request failed").await);
+    }
+
+    let result: ApiResponse<Vec<Machine>> = response.json().await?;
+    match result.data {
+        Some(machines) => Ok(Some(machines)),
+        None => {
+            print_api_error(&http::describe_failure(&result));
+            Ok(None)
+        }
+    }
+}
+
+/// Print one line per machine
+pub fn print_machines(machines: &[Machine]) {
+    for machine in machines {
+        let marker = if machine.current { " (this machine)".green().to_string() } else { String::new() };
+        let state = match machine.revoked_at {
+            Some(revoked_at) => format!("revoked {}", crate::sync::format_time_ago(revoked_at)).red().to_string(),
+            None => format!("{} active sessions", machine.active_sessions),
+        };
+        println!(
+            "  {} {}{} {} ({}), last seen {}, {}",
+            machine.id.to_string()[..8].dimmed(),
+            machine.name.white().bold(),
+            marker,
+            machine.hostname,
+            machine.platform,
+            crate::sync::format_time_ago(machine.last_seen_at),
+            state,
+        );
+    }
+}
+
+/// List the machines the user has logged in from
+pub async fn handle_machines_list(config_manager: &ConfigManager) -> Result<()> {
+    let Some(machines) = fetch_machines(config_manager).await? else {
+        return Ok(());
+    };
+
+    if machines.is_empty() {
+        print_info("No machines recorded yet.");
+        return Ok(());
+    }
+
+    println!("\n{}", "🖥️  Machines".cyan().bold());
+    print_machines(&machines);
+    Ok(())
+}
+
+/// Revoke a machine, identified by ID (or ID prefix), machine name or hostname
+pub async fn handle_machine_revoke(config_manager: &ConfigManager, machine: String) -> Result<()> {
+    let Some(machines) = fetch_machines(config_manager).await? else {
+        return Ok(());
+    };
+
+    let needle = machine.to_lowercase();
+    let matches: Vec<&Machine> = machines.iter()
+        .filter(|m| {
+            m.id.to_string().starts_with(&needle)
+                || m.machine_id.to_lowercase() == needle
+                || m.name.to_lowercase() == needle
+                || m.hostname.to_lowercase() == needle
+        })
+        .collect();
+
+    let target = match matches.as_slice() {
+        [] => {
+            print_api_error(&format!("No machine matches '{}'. Use 'mothership auth machines list'.", machine));
+            return Ok(());
+        }
+        [target] => *target,
+        several => {
+            print_api_error(&format!("'{}' matches {} machines; use the machine ID instead:", machine, several.len()));
+            for m in several {
+                println!("  {} {} ({})", m.id.to_string().dimmed(), m.name, m.hostname);
+            }
+            return Ok(());
+        }
+    };
+
+    let config = config_manager.load_config()?;
+    let server_url = get_server_url(config_manager)?;
+    let client = get_http_client(&config);
+
+    let response = client
+        .delete(format!("{}/users/me/machines/{}", server_url, target.id))
+        .send()
+        .await?
+        .check_session()?;
+
+    match response.status() {
+        status if status.is_success() => {
+            print_success(&format!("Revoked {} ({}); its sessions are signed out", target.name, target.hostname));
+            if target.current {
+                print_info("This was the current machine. Run 'mothership auth' to log in again.");
+            }
+        }
+        reqwest::StatusCode::NOT_FOUND => print_api_error("Machine not found"),
+        status => return Err(anyhow!("Machine request failed: {}", status)),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,440 @@
+//! `mothership stash`: set aside changes made since the current rift's last
+//! checkpoint and bring them back later.
+//!
+//! Stashes are kept in the project's `.mothership/stash` directory, one JSON
+//! file each, and can optionally be backed up to the server. Pushing a stash
+//! puts the changed files back to the checkpoint; popping it writes them out
+//! again. This works the same in local-only mode, where the checkpoint comes
+//! from the local history instead of the server.
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use colored::*;
+use mothership_common::{
+    metadata::write_file,
+    protocol::{ApiResponse, RestorePathsRequest},
+    upload::content_hash,
+    ChangeType, CheckpointHistoryEntry, CheckpointId, ClientConfig, Stash, StashedFile,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::{config::ConfigManager, connections, get_http_client, http::{self, ResponseExt}, local, print_api_error, print_info, print_success};
+
+/// Directory under `.mothership` holding stashes
+const STASH_DIR: &str = "stash";
+
+/// The parts of `.mothership/project.json` stashing needs
+#[derive(Deserialize)]
+struct ProjectMetadata {
+    project_id: String,
+    current_rift: Option<String>,
+}
+
+/// Files returned by the checkpoint path restore endpoint
+#[derive(Deserialize)]
+struct RestoredFiles {
+    files: HashMap<PathBuf, String>,
+}
+
+/// What local changes are compared against: the current rift's last checkpoint
+struct StashBase {
+    rift: Option<String>,
+    checkpoint: Option<CheckpointId>,
+    /// Path -> content hash
+    hashes: HashMap<PathBuf, String>,
+    /// Path -> content, when the checkpoint is stored locally
+    contents: Option<HashMap<PathBuf, String>>,
+}
+
+/// Talks to the server for a connected project
+struct Remote {
+    client: reqwest::Client,
+    server_url: String,
+    project_id: String,
+}
+
+impl Remote {
+    fn new(config: &ClientConfig, project_dir: &Path) -> Result<Self> {
+        let metadata = read_project_metadata(project_dir)?;
+        Ok(Self {
+            client: get_http_client(config),
+            server_url: connections::get_active_server_url().unwrap_or(config.mothership_url.clone()),
+            project_id: metadata.project_id,
+        })
+    }
+
+    fn project_url(&self, path: &str) -> String {
+        format!("{}/projects/{}/{}", self.server_url, self.project_id, path)
+    }
+
+    /// The rift's latest checkpoint on the server
+    async fn base(&self, rift: Option<String>) -> Result<StashBase> {
+        let response = self.client.get(self.project_url("history?depth=1")).send().await?.check_session()?;
+        if !response.status().is_success() {
+            return Err(http::api_error(response, "Failed to load the last checkpoint").await);
+        }
+
+        let history: ApiResponse<Vec<CheckpointHistoryEntry>> = response.json().await?;
+        let latest = history.data.unwrap_or_default().into_iter().next();
+        let hashes = latest.as_ref()
+            .map(|entry| entry.checkpoint.changes.iter()
+                .filter(|c| !matches!(c.change_type, ChangeType::Deleted))
+                .map(|c| (c.path.clone(), c.content_hash.clone()))
+                .collect())
+            .unwrap_or_default();
+
+        Ok(StashBase {
+            rift,
+            checkpoint: latest.map(|entry| entry.checkpoint.id),
+            hashes,
+            contents: None,
+        })
+    }
+
+    /// Content of some of the checkpoint's files
+    async fn checkpoint_files(&self, checkpoint: CheckpointId, paths: Vec<PathBuf>) -> Result<HashMap<PathBuf, String>> {
+        if paths.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let response = self.client
+            .post(self.project_url(&format!("checkpoints/{}/restore/paths", checkpoint)))
+            .query(&[("dry_run", true)])
+            .json(&RestorePathsRequest { paths })
+            .send()
+            .await?
+            .check_session()?;
+        if !response.status().is_success() {
+            return Err(http::api_error(response, "Failed to load checkpoint files").await);
+        }
+
+        let restored: ApiResponse<RestoredFiles> = response.json().await?;
+        restored.data
+            .map(|data| data.files)
+            .ok_or_else(|| anyhow!(restored.error.unwrap_or_else(|| "Unknown error".to_string())))
+    }
+
+    async fn backup(&self, stash: &Stash) -> Result<()> {
+        let response = self.client.post(self.project_url("stashes")).json(stash).send().await?.check_session()?;
+        if !response.status().is_success() {
+            return Err(http::api_error(response, "Failed to back up stash").await);
+        }
+        Ok(())
+    }
+
+    /// The newest stash backed up from any machine
+    async fn latest_backup(&self) -> Result<Option<Stash>> {
+        let response = self.client.get(self.project_url("stashes")).send().await?.check_session()?;
+        if !response.status().is_success() {
+            return Err(http::api_error(response, "Failed to list stash backups").await);
+        }
+
+        let stashes: ApiResponse<Vec<Stash>> = response.json().await?;
+        Ok(stashes.data.unwrap_or_default().pop())
+    }
+
+    async fn remove_backup(&self, stash_id: Uuid) -> Result<()> {
+        self.client.delete(self.project_url(&format!("stashes/{}", stash_id))).send().await?.check_session()?;
+        Ok(())
+    }
+}
+
+fn read_project_metadata(project_dir: &Path) -> Result<ProjectMetadata> {
+    let project_file = project_dir.join(".mothership").join("project.json");
+    if !project_file.exists() {
+        return Err(anyhow!(
+            "Not in a Mothership project directory.\n\
+            Run this command from a project directory, or use 'mothership beam <project>' to enter a project."
+        ));
+    }
+    Ok(serde_json::from_str(&fs::read_to_string(&project_file)?)?)
+}
+
+/// The remote for connected projects, or `None` in local-only mode
+fn remote(config_manager: &ConfigManager, project_dir: &Path) -> Result<Option<Remote>> {
+    if connections::is_local_only() {
+        return Ok(None);
+    }
+    let config = config_manager.load_config()?;
+    Ok(Some(Remote::new(&config, project_dir)?))
+}
+
+async fn load_base(remote: Option<&Remote>, project_dir: &Path) -> Result<StashBase> {
+    match remote {
+        Some(remote) => remote.base(read_project_metadata(project_dir)?.current_rift).await,
+        None => {
+            let (rift, checkpoint, contents) = local::stash_base(project_dir)?;
+            Ok(StashBase {
+                rift: Some(rift),
+                checkpoint,
+                hashes: contents.iter().map(|(path, content)| (path.clone(), content_hash(content.as_bytes()))).collect(),
+                contents: Some(contents),
+            })
+        }
+    }
+}
+
+/// Files that differ from the base, with their current content (`None` if deleted)
+fn changed_files(base: &StashBase, working: &HashMap<PathBuf, String>) -> Vec<StashedFile> {
+    let mut files: Vec<StashedFile> = working.iter()
+        .filter(|(path, content)| base.hashes.get(*path) != Some(&content_hash(content.as_bytes())))
+        .map(|(path, content)| StashedFile { path: path.clone(), content: Some(content.clone()) })
+        .chain(base.hashes.keys()
+            .filter(|path| !working.contains_key(*path))
+            .map(|path| StashedFile { path: path.clone(), content: None }))
+        .collect();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    files
+}
+
+fn stash_dir(project_dir: &Path) -> PathBuf {
+    project_dir.join(".mothership").join(STASH_DIR)
+}
+
+/// Stashes saved in the project, oldest first
+fn load_stashes(project_dir: &Path) -> Result<Vec<(PathBuf, Stash)>> {
+    let dir = stash_dir(project_dir);
+    let mut stashes = Vec::new();
+    if !dir.exists() {
+        return Ok(stashes);
+    }
+
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) == Some("json") {
+            let stash: Stash = serde_json::from_str(&fs::read_to_string(&path)?)
+                .map_err(|e| anyhow!("Stash {} is corrupted: {}", path.display(), e))?;
+            stashes.push((path, stash));
+        }
+    }
+    stashes.sort_by_key(|(_, stash)| stash.created_at);
+    Ok(stashes)
+}
+
+impl StashBase {
+    /// Whether there is anything to stash changes against
+    fn exists(&self) -> bool {
+        self.checkpoint.is_some() || !self.hashes.is_empty()
+    }
+}
+
+/// Save the working directory's changes as a stash and put the changed
+/// files back to the base checkpoint. Returns `None` if nothing changed.
+async fn stash_changes(
+    remote: Option<&Remote>,
+    project_dir: &Path,
+    base: StashBase,
+    message: Option<String>,
+) -> Result<Option<Stash>> {
+    let working = local::scan_working_files(project_dir)?;
+    let files = changed_files(&base, &working);
+    if files.is_empty() {
+        return Ok(None);
+    }
+
+    let stash = Stash {
+        id: Uuid::new_v4(),
+        message,
+        rift: base.rift.clone(),
+        base_checkpoint: base.checkpoint,
+        created_at: Utc::now(),
+        files,
+    };
+
+    // Save the stash before touching any files
+    fs::create_dir_all(stash_dir(project_dir))?;
+    fs::write(
+        stash_dir(project_dir).join(format!("{}.json", stash.id)),
+        serde_json::to_string_pretty(&stash)?,
+    )?;
+
+    // Files the checkpoint has are restored to it; files it doesn't have are removed
+    let restore_paths: Vec<PathBuf> = stash.files.iter()
+        .map(|f| f.path.clone())
+        .filter(|path| base.hashes.contains_key(path))
+        .collect();
+    let originals = match (&base.contents, remote, base.checkpoint) {
+        (Some(contents), _, _) => contents.clone(),
+        (None, Some(remote), Some(checkpoint)) => remote.checkpoint_files(checkpoint, restore_paths).await?,
+        _ => HashMap::new(),
+    };
+
+    for file in &stash.files {
+        let file_path = project_dir.join(&file.path);
+        match originals.get(&file.path) {
+            Some(content) => {
+                write_file(&file_path, content, None)?;
+            }
+            None => {
+                let _ = fs::remove_file(&file_path);
+            }
+        }
+    }
+
+    Ok(Some(stash))
+}
+
+/// Stash local changes, optionally backing the stash up to the server
+pub async fn handle_push(config_manager: &ConfigManager, message: Option<String>, backup: bool) -> Result<()> {
+    let project_dir = crate::project_dir()?;
+    let remote = remote(config_manager, &project_dir)?;
+    if backup && remote.is_none() {
+        return Err(anyhow!("Stash backups need a server connection; run 'mothership connect <server-url>' first"));
+    }
+
+    let base = load_base(remote.as_ref(), &project_dir).await?;
+    if !base.exists() {
+        return Err(anyhow!("There is no checkpoint to stash changes against yet; create one with 'mothership checkpoint'"));
+    }
+
+    let Some(stash) = stash_changes(remote.as_ref(), &project_dir, base, message).await? else {
+        print_info("No local changes to stash");
+        return Ok(());
+    };
+
+    print_success(&format!("Stashed {} changed files", stash.files.len()));
+    if backup {
+        if let Some(remote) = &remote {
+            match remote.backup(&stash).await {
+                Ok(()) => print_info("Backed up to the server"),
+                Err(e) => print_api_error(&format!("{}; the stash is still saved locally", e)),
+            }
+        }
+    }
+    print_info("Bring the changes back with 'mothership stash pop'");
+    Ok(())
+}
+
+/// Stash local changes before switching rifts. Returns whether anything was stashed.
+pub async fn auto_stash(config_manager: &ConfigManager, target_rift: &str) -> Result<bool> {
+    let project_dir = crate::project_dir()?;
+    let remote = remote(config_manager, &project_dir)?;
+    let base = load_base(remote.as_ref(), &project_dir).await?;
+    if !base.exists() || base.rift.as_deref() == Some(target_rift) {
+        return Ok(false);
+    }
+
+    let message = Some(format!("Auto-stash before switching to {}", target_rift));
+    match stash_changes(remote.as_ref(), &project_dir, base, message).await? {
+        Some(stash) => {
+            print_info(&format!(
+                "Stashed {} changed files; run 'mothership stash pop' to bring them back",
+                stash.files.len()
+            ));
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Whether the working directory differs from the current rift's last checkpoint
+pub async fn has_local_changes(config_manager: &ConfigManager) -> Result<bool> {
+    let project_dir = crate::project_dir()?;
+    let remote = remote(config_manager, &project_dir)?;
+    let base = load_base(remote.as_ref(), &project_dir).await?;
+    let working = local::scan_working_files(&project_dir)?;
+    Ok(!changed_files(&base, &working).is_empty())
+}
+
+/// Apply the newest stash and drop it. Uses the newest server backup if
+/// there are no stashes in the project.
+pub async fn handle_pop(config_manager: &ConfigManager) -> Result<()> {
+    let project_dir = crate::project_dir()?;
+    let remote = remote(config_manager, &project_dir)?;
+
+    let (stash_file, stash) = match load_stashes(&project_dir)?.pop() {
+        Some((path, stash)) => (Some(path), stash),
+        None => {
+            let backup = match &remote {
+                Some(remote) => remote.latest_backup().await?,
+                None => None,
+            };
+            match backup {
+                Some(stash) => {
+                    print_info("No local stashes; using the newest backup from the server");
+                    (None, stash)
+                }
+                None => {
+                    print_info("No stashes to pop");
+                    return Ok(());
+                }
+            }
+        }
+    };
+
+    // Refuse to overwrite files that have changed again since the stash was made
+    let base = load_base(remote.as_ref(), &project_dir).await?;
+    let working = local::scan_working_files(&project_dir)?;
+    let dirty: Vec<PathBuf> = changed_files(&base, &working).into_iter().map(|f| f.path).collect();
+    let overlapping: Vec<String> = stash.files.iter()
+        .filter(|f| dirty.contains(&f.path))
+        .map(|f| f.path.display().to_string())
+        .collect();
+    if !overlapping.is_empty() {
+        return Err(anyhow!(
+            "Local changes to {} would be overwritten; checkpoint or stash them first",
+            overlapping.join(", ")
+        ));
+    }
+
+    for file in &stash.files {
+        let file_path = project_dir.join(&file.path);
+        match &file.content {
+            Some(content) => {
+                write_file(&file_path, content, None)?;
+            }
+            None => {
+                let _ = fs::remove_file(&file_path);
+            }
+        }
+    }
+
+    if let Some(stash_file) = stash_file {
+        fs::remove_file(stash_file)?;
+    }
+    if let Some(remote) = &remote {
+        // Only stashes pushed with --backup have one; a missing backup is fine
+        let _ = remote.remove_backup(stash.id).await;
+    }
+
+    print_success(&format!("Restored {} stashed files", stash.files.len()));
+    if let (Some(stashed_in), Some(current)) = (&stash.rift, &base.rift) {
+        if stashed_in != current {
+            print_info(&format!("These changes were stashed in rift '{}'", stashed_in));
+        }
+    }
+    Ok(())
+}
+
+/// List the project's stashes, newest first
+pub fn handle_list() -> Result<()> {
+    let project_dir = crate::project_dir()?;
+    let stashes = load_stashes(&project_dir)?;
+
+    if stashes.is_empty() {
+        print_info("No stashes. Set aside local changes with 'mothership stash push'");
+        return Ok(());
+    }
+
+    println!("\n{}", "🗃️  Stashes".cyan().bold());
+    for (i, (_, stash)) in stashes.iter().rev().enumerate() {
+        let age = crate::sync::format_time_ago(stash.created_at);
+        let message = stash.message.as_deref().unwrap_or("(no message)");
+        println!("{} {} {}",
+            format!("stash@{{{}}}", i).yellow().bold(),
+            message.white(),
+            age.dimmed()
+        );
+        println!("   {} file{} • rift {}",
+            stash.files.len(),
+            if stash.files.len() == 1 { "" } else { "s" },
+            stash.rift.as_deref().unwrap_or("unknown").green()
+        );
+    }
+
+    Ok(())
+}
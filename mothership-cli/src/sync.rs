@@ -1,11 +1,19 @@
 use anyhow::{anyhow, Result};
 use colored::*;
-use mothership_common::{Checkpoint, protocol::ApiResponse};
+use mothership_common::{
+    Checkpoint, CheckpointConflict, CheckpointConflictReason, CheckpointHistoryEntry, Hook, SecretFinding, SparseSpec, StatusState,
+    protocol::{ApiResponse, CheckpointRevertResult, FileActivity, CommentThread, CompactHistoryRequest, HistoryCompaction, RestorePathsRequest, RestoreStreamLine, RevertCheckpointRequest},
+    metadata::{read_file, write_file},
+    push_rules::{PushRule, PushRuleViolation},
+    worktree::{CheckpointManifest, FileStatus},
+};
 use serde::{Serialize, Deserialize};
 use std::io::{self, Write};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use uuid;
 
-use crate::{config::ConfigManager, get_http_client, print_api_error, print_info, print_success, connections};
+use crate::{config::ConfigManager, daemon_client::DaemonClient, get_http_client, http::{self, ResponseExt}, print_api_error, print_info, print_success, connections, run_pre_hook};
 
 /// Get the server URL to use for sync operations
 /// Prioritizes active server connection over config file
@@ -20,10 +28,16 @@ fn get_server_url(config_manager: &ConfigManager) -> Result<String> {
     Ok(config.mothership_url)
 }
 
-pub async fn handle_status(config_manager: &ConfigManager) -> Result<()> {
-    use reqwest::StatusCode;
+pub async fn handle_status(config_manager: &ConfigManager, porcelain: bool) -> Result<()> {
     use std::fs;
 
+    if porcelain {
+        let (project_id, _) = find_current_project()?;
+        let changes = working_tree_changes(config_manager, project_id).await?;
+        print_working_tree_status(&changes, true);
+        return Ok(());
+    }
+
     // Check if authenticated
     if !config_manager.is_authenticated()? {
         print_info("Not authenticated. Run 'mothership auth' to get started.");
@@ -37,6 +51,7 @@ pub async fn handle_status(config_manager: &ConfigManager) -> Result<()> {
     let local_metadata: Option<crate::sync::ProjectMetadata> = fs::read_to_string(".mothership/project.json")
         .ok()
         .and_then(|s| serde_json::from_str(&s).ok());
+    let mut local_changes = Vec::new();
     if let Some((project_id, ref project_name)) = project_metadata {
         println!("\n{} {}", "Project:".bold(), project_name.blue().bold());
         println!("{} {}", "ID:".bold(), project_id.to_string().dimmed());
@@ -45,16 +60,24 @@ pub async fn handle_status(config_manager: &ConfigManager) -> Result<()> {
         if let Some(meta) = local_metadata {
             println!("{} {}", "Server:".bold(), meta.mothership_url.dimmed());
         }
+
+        match working_tree_changes(config_manager, project_id).await {
+            Ok(changes) => {
+                print_working_tree_status(&changes, false);
+                local_changes = changes;
+            }
+            Err(e) => println!("\n{}", format!("Could not compare with the last checkpoint: {}", e).yellow()),
+        }
     } else {
         println!("\n{} {}", "Project:".bold(), "Not in a project directory".red());
         println!("{}", "Run 'mothership beam <project>' to enter a project".dimmed());
     }
 
     // 2. Query daemon for status
-    let daemon_status = reqwest::get("http://127.0.0.1:7525/status").await;
+    let daemon_status = DaemonClient::new(std::time::Duration::from_secs(5)).get("/status").await;
     match daemon_status {
-        Ok(resp) if resp.status() == StatusCode::OK => {
-            let json: serde_json::Value = resp.json().await.unwrap_or_default();
+        Ok(resp) if resp.is_success() => {
+            let json: serde_json::Value = resp.json().unwrap_or_default();
             if let Some(data) = json.get("data") {
                 println!("\n{}", "Daemon Status:".bold());
                 println!("  {} {}", "Running:".dimmed(), data.get("is_running").unwrap_or(&serde_json::Value::Null));
@@ -62,6 +85,20 @@ pub async fn handle_status(config_manager: &ConfigManager) -> Result<()> {
                 println!("  {} {}", "Files Syncing:".dimmed(), data.get("files_syncing").unwrap_or(&serde_json::Value::Null));
                 println!("  {} {}", "Last Sync:".dimmed(), data.get("last_sync").unwrap_or(&serde_json::Value::Null));
                 println!("  {} {}", "Server Connected:".dimmed(), data.get("server_connected").unwrap_or(&serde_json::Value::Null));
+                println!("  {} {}", "Queued Offline:".dimmed(), data.get("queued_changes").unwrap_or(&serde_json::Value::Null));
+                if let Some(behind_by) = data.get("behind_by").and_then(|v| v.as_u64()).filter(|&n| n > 0) {
+                    println!("  {} {}", "Behind By:".dimmed(), format!("{} changes", behind_by).yellow());
+                }
+                if let Some(skipped) = data.get("skipped_files").and_then(|v| v.as_object()).filter(|s| !s.is_empty()) {
+                    let total: u64 = skipped.values().filter_map(|n| n.as_u64()).sum();
+                    let reasons: Vec<String> = skipped.iter()
+                        .map(|(reason, count)| format!("{} {}", count, reason.replace('-', " ")))
+                        .collect();
+                    println!("  {} {}", "Skipped:".dimmed(), format!("{} files ({})", total, reasons.join(", ")).yellow());
+                }
+                if let Some(protocol_error) = data.get("protocol_error").and_then(|v| v.as_str()) {
+                    println!("  {} {}", "Protocol:".dimmed(), protocol_error.red());
+                }
             }
         }
         _ => {
@@ -70,7 +107,7 @@ pub async fn handle_status(config_manager: &ConfigManager) -> Result<()> {
     }
 
     // 3. Show recent checkpoints (last 3)
-    if let Some((project_id, project_name)) = project_metadata {
+    if let Some((project_id, ref project_name)) = project_metadata {
         let config = config_manager.load_config()?;
         let server_url = get_server_url(config_manager)?;
         let client = get_http_client(&config);
@@ -82,6 +119,7 @@ pub async fn handle_status(config_manager: &ConfigManager) -> Result<()> {
                     success: false, 
                     data: None, 
                     error: Some("Failed to parse response".to_string()),
+                    code: None,
                     message: Some("Failed to parse response".to_string()),
                 });
                 if let Some(checkpoints) = checkpoints.data {
@@ -97,14 +135,64 @@ pub async fn handle_status(config_manager: &ConfigManager) -> Result<()> {
         }
     }
 
-    // 4. Show connected collaborators (if available)
-    // Placeholder: This would require a new API endpoint or WebSocket presence tracking
-    println!("\n{}", "Connected Collaborators:".bold());
-    println!("  {}", "(Feature coming soon: will show live users in this rift)".dimmed());
+    // 4. Show which files collaborators are working on, warning about the ones we changed too
+    if let Some((project_id, _)) = project_metadata {
+        match fetch_file_activity(config_manager, project_id).await {
+            Ok(activity) => print_file_activity(&activity, &local_changes),
+            Err(e) => println!("\n{}", format!("Could not load what collaborators are editing: {}", e).dimmed()),
+        }
+    }
 
     Ok(())
 }
 
+/// What others are doing with files in the user's current rift of a project
+async fn fetch_file_activity(config_manager: &ConfigManager, project_id: uuid::Uuid) -> Result<Vec<FileActivity>> {
+    let config = config_manager.load_config()?;
+    let server_url = get_server_url(config_manager)?;
+    let client = get_http_client(&config);
+
+    let url = format!("{}/projects/{}/file-activity", server_url, project_id);
+    let response = client.get(&url).send().await?.check_session()?;
+    if !response.status().is_success() {
+        return Err(http::api_error(response, "Failed to load file activity").await);
+    }
+
+    let activity: ApiResponse<Vec<FileActivity>> = response.json().await?;
+    Ok(activity.data.unwrap_or_default())
+}
+
+/// List the files collaborators have open or changed, first warning about
+/// the ones also changed here
+fn print_file_activity(activity: &[FileActivity], local_changes: &[(PathBuf, FileStatus)]) {
+    let changed_here: HashSet<&Path> = local_changes.iter().map(|(path, _)| path.as_path()).collect();
+    let overlapping: Vec<&FileActivity> = activity.iter()
+        .filter(|activity| changed_here.contains(activity.path.as_path()))
+        .collect();
+
+    if !overlapping.is_empty() {
+        println!();
+        for activity in overlapping {
+            let doing = if activity.dirty { "is also editing" } else { "has open" };
+            println!("{} {} {} {}", "⚠️ ".yellow(), activity.username.bold(), doing, activity.path.display().to_string().yellow());
+        }
+    }
+
+    println!("\n{}", "Collaborators Editing:".bold());
+    if activity.is_empty() {
+        println!("  {}", "nobody else has files open or changed in this rift".dimmed());
+        return;
+    }
+    for activity in activity {
+        let state = match (activity.dirty, activity.open) {
+            (true, true) => "changed, open",
+            (true, false) => "changed",
+            _ => "open",
+        };
+        println!("  {} {} {}", activity.username.cyan(), activity.path.display(), format!("({})", state).dimmed());
+    }
+}
+
 pub async fn handle_checkpoint(config_manager: &ConfigManager, message: Option<String>) -> Result<()> {
     // Check if authenticated
     if !config_manager.is_authenticated()? {
@@ -115,6 +203,12 @@ pub async fn handle_checkpoint(config_manager: &ConfigManager, message: Option<S
     // Find the current project
     let (project_id, project_name) = find_current_project()?;
     let checkpoint_msg = message.unwrap_or_else(|| "Manual checkpoint".to_string());
+
+    run_pre_hook(Hook::PreCheckpoint, &[
+        ("MOTHERSHIP_PROJECT_ID", project_id.to_string()),
+        ("MOTHERSHIP_PROJECT_NAME", project_name.clone()),
+        ("MOTHERSHIP_CHECKPOINT_MESSAGE", checkpoint_msg.clone()),
+    ])?;
     
     print_info(&format!("Creating checkpoint for {}: {}", project_name, checkpoint_msg));
 
@@ -122,19 +216,55 @@ pub async fn handle_checkpoint(config_manager: &ConfigManager, message: Option<S
     let server_url = get_server_url(config_manager)?;
     let client = get_http_client(&config);
 
+    // Build on the checkpoint this working copy last saw, so one that landed
+    // first from someone else is reported rather than silently forked from
+    let mut parent = CheckpointManifest::load(&crate::project_dir()?).and_then(|manifest| manifest.checkpoint_id);
+
     // Create checkpoint via API
-    let checkpoint_url = format!("{}/projects/{}/checkpoint", server_url, project_id);
-    let response = client
-        .post(&checkpoint_url)
-        .json(&serde_json::json!({
-            "message": checkpoint_msg,
-            "timestamp": chrono::Utc::now()
-        }))
-        .send()
-        .await?;
+    let checkpoint_url = format!("{}/projects/{}/checkpoints", server_url, project_id);
+    let mut attempt = 1;
+    let response = loop {
+        let response = client
+            .post(&checkpoint_url)
+            .json(&serde_json::json!({
+                "message": checkpoint_msg,
+                "timestamp": chrono::Utc::now(),
+                "parent": parent,
+            }))
+            .send()
+            .await?
+            .check_session()?;
+        if response.status() != reqwest::StatusCode::CONFLICT || attempt == CHECKPOINT_ATTEMPTS {
+            break response;
+        }
+
+        let conflict: ApiResponse<CheckpointConflict> = response.json().await?;
+        let conflict = conflict.data.ok_or_else(|| {
+            anyhow!("Checkpoint refused: {}", conflict.error.unwrap_or_else(|| "conflict".to_string()))
+        })?;
+        match conflict.reason {
+            CheckpointConflictReason::ParentMoved => {
+                let head = conflict.head.map(|id| id.to_string()[..8].to_string()).unwrap_or_default();
+                print_info(&format!("⚠️  The rift moved on to checkpoint {} since you last synced; checkpointing on top of it", head));
+                parent = conflict.head;
+            }
+            CheckpointConflictReason::Busy => {
+                print_info("Another checkpoint on this rift is still being created; retrying");
+                tokio::time::sleep(std::time::Duration::from_millis(500 * attempt as u64)).await;
+            }
+        }
+        attempt += 1;
+    };
+
+    if response.status() == reqwest::StatusCode::UNPROCESSABLE_ENTITY {
+        let rejection: ApiResponse<Vec<PushRuleViolation>> = response.json().await?;
+        print_api_error(&format!("Checkpoint rejected: {}", rejection.error.unwrap_or_else(|| "push rules not met".to_string())));
+        print_push_rule_violations(&rejection.data.unwrap_or_default());
+        return Ok(());
+    }
 
     if !response.status().is_success() {
-        return Err(anyhow!("Failed to create checkpoint: {}", response.status()));
+        return Err(http::api_error(response, "Failed to create checkpoint").await);
     }
 
     let checkpoint_response: ApiResponse<CheckpointData> = response.json().await?;
@@ -144,10 +274,107 @@ pub async fn handle_checkpoint(config_manager: &ConfigManager, message: Option<S
 
     print_success(&format!("✅ Checkpoint {} created", &checkpoint_data.checkpoint_id.to_string()[..8]));
     print_info(&format!("📸 Captured {} file changes", checkpoint_data.file_count));
+
+    // Status compares the working tree with this checkpoint from now on
+    let recorded = match fetch_checkpoint_manifest(config_manager, project_id).await {
+        Ok(manifest) => manifest.save(&crate::project_dir()?),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = recorded {
+        println!("{}", format!("⚠️  Could not record the checkpoint's files for 'mothership status': {}", e).dimmed());
+    }
+    if !checkpoint_data.secret_findings.is_empty() {
+        println!("\n{}", "🔑 Possible secrets in this checkpoint:".yellow().bold());
+        for finding in &checkpoint_data.secret_findings {
+            println!("  {} {}", "•".yellow(), finding.describe());
+        }
+        println!("{}", "Remove and rotate them - they are now part of the rift's history.".dimmed());
+    }
     
     Ok(())
 }
 
+/// Hashes of the files in the rift's latest checkpoint on the server
+async fn fetch_checkpoint_manifest(config_manager: &ConfigManager, project_id: uuid::Uuid) -> Result<CheckpointManifest> {
+    let config = config_manager.load_config()?;
+    let server_url = get_server_url(config_manager)?;
+    let client = get_http_client(&config);
+
+    let history_url = format!("{}/projects/{}/history?depth=1", server_url, project_id);
+    let response = client.get(&history_url).send().await?.check_session()?;
+    if !response.status().is_success() {
+        return Err(http::api_error(response, "Failed to load the last checkpoint").await);
+    }
+
+    let history: ApiResponse<Vec<CheckpointHistoryEntry>> = response.json().await?;
+    Ok(match history.data.unwrap_or_default().into_iter().next() {
+        Some(entry) => CheckpointManifest::from_checkpoint(&entry.checkpoint),
+        None => CheckpointManifest { recorded_at: chrono::Utc::now(), ..Default::default() },
+    })
+}
+
+/// Files created, modified or deleted since the rift's last checkpoint.
+/// The checkpoint's hashes come from `.mothership/checkpoint_manifest.json`,
+/// fetched from the server the first time.
+async fn working_tree_changes(config_manager: &ConfigManager, project_id: uuid::Uuid) -> Result<Vec<(PathBuf, FileStatus)>> {
+    let project_dir = crate::project_dir()?;
+    let mut manifest = match CheckpointManifest::load(&project_dir) {
+        Some(manifest) => manifest,
+        None => {
+            let manifest = fetch_checkpoint_manifest(config_manager, project_id).await?;
+            manifest.save(&project_dir)?;
+            manifest
+        }
+    };
+
+    // Files outside a sparse beam aren't on disk but haven't been deleted
+    let sparse = SparseSpec::load(&project_dir);
+    manifest.files.retain(|path, _| sparse.includes(path));
+    Ok(manifest.status(&crate::local::scan_working_files(&project_dir)?))
+}
+
+/// List changes since the last checkpoint, as `M path` lines with
+/// `porcelain` and as a colored list otherwise
+pub(crate) fn print_working_tree_status(changes: &[(PathBuf, FileStatus)], porcelain: bool) {
+    if porcelain {
+        for (path, status) in changes {
+            println!("{} {}", status.code(), path.display());
+        }
+        return;
+    }
+
+    if changes.is_empty() {
+        println!("\n{} {}", "Working Tree:".bold(), "no changes since the last checkpoint".green());
+        return;
+    }
+
+    println!("\n{}", format!("Changes Since Last Checkpoint ({}):", changes.len()).bold());
+    for (path, status) in changes {
+        let label = match status {
+            FileStatus::Created => "created: ".green(),
+            FileStatus::Modified => "modified:".yellow(),
+            FileStatus::Deleted => "deleted: ".red(),
+        };
+        println!("  {} {}", label, path.display());
+    }
+}
+
+/// List the push rules a change broke, one per line
+pub fn print_push_rule_violations(violations: &[PushRuleViolation]) {
+    for violation in violations {
+        let rule = match violation.rule {
+            PushRule::CheckpointMessage => "message",
+            PushRule::MaxFileSize => "size",
+            PushRule::DenyPath => "denied",
+            PushRule::FileType => "file type",
+            PushRule::Secret => "secret",
+            PushRule::InvalidPath => "path",
+        };
+        println!("  {} {}", format!("[{}]", rule).red(), violation.message);
+    }
+    println!("\n{}", "💡 Run 'mothership gateway push-rules <project>' to see the project's rules".dimmed());
+}
+
 pub async fn handle_sync(config_manager: &ConfigManager) -> Result<()> {
     // Check if authenticated
     if !config_manager.is_authenticated()? {
@@ -166,7 +393,65 @@ pub async fn handle_sync(config_manager: &ConfigManager) -> Result<()> {
     Ok(())
 }
 
-pub async fn handle_history(config_manager: &ConfigManager, limit: usize) -> Result<()> {
+/// File in `.mothership` recording the oldest checkpoint `history` has
+/// fetched, so `history --deepen` can continue from it like a shallow clone
+const SHALLOW_FILE: &str = "shallow";
+
+/// Number of tries for a checkpoint when its rift moves on underneath it
+const CHECKPOINT_ATTEMPTS: u32 = 3;
+
+fn load_shallow_boundary() -> Option<uuid::Uuid> {
+    let path = crate::project_dir().ok()?.join(".mothership").join(SHALLOW_FILE);
+    uuid::Uuid::parse_str(std::fs::read_to_string(path).ok()?.trim()).ok()
+}
+
+fn save_shallow_boundary(checkpoint_id: uuid::Uuid) -> Result<()> {
+    let path = crate::project_dir()?.join(".mothership").join(SHALLOW_FILE);
+    std::fs::write(path, checkpoint_id.to_string())?;
+    Ok(())
+}
+
+/// Fold runs of old automatic checkpoints in the current project's history
+pub async fn handle_history_compact(config_manager: &ConfigManager, older_than_days: Option<u32>) -> Result<()> {
+    let (project_id, project_name) = find_current_project()?;
+
+    let config = config_manager.load_config()?;
+    let server_url = get_server_url(config_manager)?;
+    let client = get_http_client(&config);
+
+    let response = client
+        .post(format!("{}/projects/{}/history/compact", server_url, project_id))
+        .json(&CompactHistoryRequest { older_than_days })
+        .send()
+        .await?
+        .check_session()?;
+    if !response.status().is_success() {
+        return Err(http::api_error(response, "Failed to compact history").await);
+    }
+
+    let result: ApiResponse<HistoryCompaction> = response.json().await?;
+    let Some(compaction) = result.data else {
+        print_api_error(&http::describe_failure(&result));
+        return Ok(());
+    };
+
+    if compaction.checkpoints_removed == 0 {
+        print_info(&format!("Nothing to compact in {}", project_name));
+    } else {
+        print_success(&format!(
+            "Folded {} automatic checkpoint{} of {} into {}",
+            compaction.checkpoints_removed,
+            if compaction.checkpoints_removed == 1 { "" } else { "s" },
+            project_name,
+            compaction.summaries,
+        ));
+    }
+    println!("{}", "Manual checkpoints, merges and labelled automatic checkpoints are always kept".dimmed());
+
+    Ok(())
+}
+
+pub async fn handle_history(config_manager: &ConfigManager, limit: usize, deepen: bool, comments: bool) -> Result<()> {
     // Check if authenticated
     if !config_manager.is_authenticated()? {
         print_api_error("Not authenticated. Run 'mothership auth' to get started.");
@@ -181,35 +466,72 @@ pub async fn handle_history(config_manager: &ConfigManager, limit: usize) -> Res
     let server_url = get_server_url(config_manager)?;
     let client = get_http_client(&config);
 
-    // Get checkpoint history from server
-    let history_url = format!("{}/projects/{}/history?limit={}", server_url, project_id, limit);
-    let response = client.get(&history_url).send().await?;
+    // SHALLOW HISTORY: Only fetch one page; --deepen continues below the oldest checkpoint seen so far
+    let boundary = if deepen { load_shallow_boundary() } else { None };
+    if deepen && boundary.is_none() {
+        print_info("No history loaded yet; showing the most recent checkpoints");
+    }
+
+    let mut history_url = format!("{}/projects/{}/history?depth={}", server_url, project_id, limit);
+    if let Some(boundary) = boundary {
+        history_url.push_str(&format!("&before={}", boundary));
+    }
+    let response = client.get(&history_url).send().await?.check_session()?;
+
+    if response.status() == reqwest::StatusCode::NOT_FOUND && boundary.is_some() {
+        // The boundary checkpoint was pruned or belongs to another rift; start over
+        let _ = std::fs::remove_file(crate::project_dir()?.join(".mothership").join(SHALLOW_FILE));
+        return Err(anyhow!("Previously loaded history is no longer available. Run 'mothership history' to start again."));
+    }
 
     if !response.status().is_success() {
-        return Err(anyhow!("Failed to load history: {}", response.status()));
+        return Err(http::api_error(response, "Failed to load history").await);
     }
 
-    let history_response: ApiResponse<Vec<Checkpoint>> = response.json().await?;
+    let history_response: ApiResponse<Vec<CheckpointHistoryEntry>> = response.json().await?;
     let checkpoints = history_response.data.ok_or_else(|| {
         anyhow!("No history data received: {}", history_response.error.unwrap_or_else(|| "Unknown error".to_string()))
     })?;
 
     if checkpoints.is_empty() {
-        print_info("No checkpoints found. Create your first checkpoint with 'mothership checkpoint \"message\"'");
+        if boundary.is_some() {
+            print_info("No older checkpoints; the full history has been loaded");
+        } else {
+            print_info("No checkpoints found. Create your first checkpoint with 'mothership checkpoint \"message\"'");
+        }
         return Ok(());
     }
 
+    if let Some(oldest) = checkpoints.last() {
+        save_shallow_boundary(oldest.checkpoint.id)?;
+    }
+
+    let threads = if comments {
+        load_comment_threads(&client, &server_url, project_id).await?
+    } else {
+        Vec::new()
+    };
+
     // Display checkpoint history
     println!("\n{}", "📜 Project History".cyan().bold());
-    println!("{}", format!("Showing {} most recent checkpoints for {}", checkpoints.len(), project_name.blue().bold()));
+    if boundary.is_some() {
+        println!("{}", format!("Showing {} older checkpoints for {}", checkpoints.len(), project_name.blue().bold()));
+    } else {
+        println!("{}", format!("Showing {} most recent checkpoints for {}", checkpoints.len(), project_name.blue().bold()));
+    }
 
-    for (i, checkpoint) in checkpoints.iter().enumerate() {
+    for (i, entry) in checkpoints.iter().enumerate() {
+        let checkpoint = &entry.checkpoint;
         let age = format_time_ago(checkpoint.timestamp);
         let message = checkpoint.message.as_deref().unwrap_or("(no message)");
-        let auto_marker = if checkpoint.auto_generated { " [auto]" } else { "" };
+        let auto_marker = match (checkpoint.auto_generated, checkpoint.squashed) {
+            (true, 0) => " [auto]".to_string(),
+            (true, squashed) => format!(" [auto, {} earlier folded in]", squashed),
+            _ => String::new(),
+        };
         
         println!("\n{} {} {} {}", 
-            if i == 0 { "●".green() } else { "○".dimmed() },
+            if i == 0 && boundary.is_none() { "●".green() } else { "○".dimmed() },
             checkpoint.id.to_string()[..8].yellow().bold(),
             message.white(),
             auto_marker.dimmed()
@@ -235,13 +557,75 @@ pub async fn handle_history(config_manager: &ConfigManager, limit: usize) -> Res
         if checkpoint.changes.len() > 3 {
             println!("     {} {} more files...", "...".dimmed(), checkpoint.changes.len() - 3);
         }
+
+        // Show status checks reported by CI and other external systems
+        for status in &entry.statuses {
+            let state_icon = match status.state {
+                StatusState::Success => "✓".green(),
+                StatusState::Pending => "●".yellow(),
+                StatusState::Failure | StatusState::Error => "✗".red(),
+            };
+            let detail = status.description.as_deref().or(status.target_url.as_deref()).unwrap_or("");
+            println!("     {} {} {}", state_icon, status.context.white(), detail.dimmed());
+        }
+
+        for thread in threads.iter().filter(|thread| thread.checkpoint_id == checkpoint.id) {
+            print_comment_thread(thread);
+        }
     }
 
-    println!("\n{}", "💡 Use 'mothership restore <checkpoint-id>' to restore to a specific point".dimmed());
+    if checkpoints.len() == limit {
+        println!("\n{}", "💡 Use 'mothership history --deepen' to load older checkpoints".dimmed());
+    }
+    println!("{}", "💡 Use 'mothership restore <checkpoint-id>' to restore to a specific point".dimmed());
+    println!("{}", format!(
+        "💡 Download a checkpoint as a zip (or ?format=tar.gz) with an access token: {}/projects/{}/checkpoints/<checkpoint-id>/archive?format=zip",
+        server_url, project_id
+    ).dimmed());
     Ok(())
 }
 
-pub async fn handle_restore(config_manager: &ConfigManager, checkpoint_id: String, force: bool) -> Result<()> {
+/// Every comment thread of the project, across all checkpoints
+async fn load_comment_threads(client: &reqwest::Client, server_url: &str, project_id: uuid::Uuid) -> Result<Vec<CommentThread>> {
+    let response = client
+        .get(format!("{}/projects/{}/comments", server_url, project_id))
+        .send()
+        .await?
+        .check_session()?;
+    if !response.status().is_success() {
+        return Err(http::api_error(response, "Failed to load comments").await);
+    }
+
+    let result: ApiResponse<Vec<CommentThread>> = response.json().await?;
+    match result.data {
+        Some(threads) => Ok(threads),
+        None => Err(anyhow!("Failed to load comments: {}", http::describe_failure(&result))),
+    }
+}
+
+fn print_comment_thread(thread: &CommentThread) {
+    let lines = if thread.line_start == thread.line_end {
+        thread.line_start.to_string()
+    } else {
+        format!("{}-{}", thread.line_start, thread.line_end)
+    };
+    let state = if thread.is_resolved() { " (resolved)".dimmed() } else { "".normal() };
+    println!("     💬 {}{}", format!("{}:{}", thread.path.display(), lines).cyan(), state);
+    for comment in &thread.comments {
+        println!("        {} {}", format!("{}:", comment.author_name).bold(), comment.body.lines().next().unwrap_or(""));
+        for line in comment.body.lines().skip(1) {
+            println!("          {}", line);
+        }
+    }
+}
+
+pub async fn handle_restore(
+    config_manager: &ConfigManager,
+    checkpoint_id: String,
+    paths: Vec<String>,
+    force: bool,
+    dry_run: bool,
+) -> Result<()> {
     // Check if authenticated
     if !config_manager.is_authenticated()? {
         print_api_error("Not authenticated. Run 'mothership auth' to get started.");
@@ -255,8 +639,31 @@ pub async fn handle_restore(config_manager: &ConfigManager, checkpoint_id: Strin
     let checkpoint_uuid = uuid::Uuid::parse_str(&checkpoint_id)
         .map_err(|_| anyhow!("Invalid checkpoint ID format. Use the full checkpoint ID from 'mothership history'"))?;
 
+    // Paths are given relative to the current directory and sent
+    // project-relative with `/` separators, as stored on the server
+    let paths = crate::project_relative_paths(&crate::project_dir()?, &paths)?;
+
+    if dry_run {
+        let mut stream = RestoreStream::open(config_manager, project_id, checkpoint_uuid, &paths, true).await?;
+        println!("{}", format!("Project: {}", project_name.blue().bold()));
+        println!("{}", format!("Checkpoint: {} ({})", checkpoint_id.yellow(), stream.checkpoint.message.as_deref().unwrap_or("no message")));
+        let project_dir = crate::project_dir()?;
+        let mut preview = RestorePreview::new(&project_dir, &paths);
+        while let Some((path, content)) = stream.next_file().await? {
+            preview.file(&path, &content);
+        }
+        return preview.finish();
+    }
+
     if !force {
-        println!("\n{}", "⚠️  This will overwrite your current files with the checkpoint state.".yellow().bold());
+        if paths.is_empty() {
+            println!("\n{}", "⚠️  This will overwrite your current files with the checkpoint state.".yellow().bold());
+        } else {
+            println!("\n{}", "⚠️  This will overwrite the following paths with the checkpoint state:".yellow().bold());
+            for path in &paths {
+                println!("   {}", path.display().to_string().white());
+            }
+        }
         println!("{}", format!("Project: {}", project_name.blue().bold()));
         println!("{}", format!("Checkpoint: {}", checkpoint_id.yellow()));
         print!("{}", "Are you sure you want to continue? (y/N): ".white().bold());
@@ -271,56 +678,315 @@ pub async fn handle_restore(config_manager: &ConfigManager, checkpoint_id: Strin
         }
     }
 
-    let config = config_manager.load_config()?;
-    let server_url = get_server_url(config_manager)?;
-    let client = get_http_client(&config);
+    run_pre_hook(Hook::PreRestore, &[
+        ("MOTHERSHIP_PROJECT_ID", project_id.to_string()),
+        ("MOTHERSHIP_PROJECT_NAME", project_name.clone()),
+        ("MOTHERSHIP_CHECKPOINT_ID", checkpoint_uuid.to_string()),
+        ("MOTHERSHIP_RESTORE_PATHS", paths.iter().map(|p| p.display().to_string()).collect::<Vec<_>>().join("\n")),
+    ])?;
 
     print_info(&format!("Restoring to checkpoint {}...", &checkpoint_id[..8]));
+    let mut stream = RestoreStream::open(config_manager, project_id, checkpoint_uuid, &paths, false).await?;
 
-    // Request checkpoint files from server
-    let restore_url = format!("{}/projects/{}/restore/{}", server_url, project_id, checkpoint_uuid);
-    let response = client.post(&restore_url).send().await?;
-
-    if !response.status().is_success() {
-        return Err(anyhow!("Failed to restore checkpoint: {}", response.status()));
-    }
-
-    let restore_response: ApiResponse<RestoreData> = response.json().await?;
-    let restore_data = restore_response.data.ok_or_else(|| {
-        anyhow!("No restore data received: {}", restore_response.error.unwrap_or_else(|| "Unknown error".to_string()))
-    })?;
+    // Files are restored relative to the project root
+    let current_dir = crate::project_dir()?;
 
-    // Get current directory (should be project root)
-    let current_dir = std::env::current_dir()?;
+    print_info(&format!("Restoring {} files ({})...", stream.file_count, crate::gateway::format_bytes(stream.total_bytes)));
 
-    print_info(&format!("Restoring {} files...", restore_data.files.len()));
-
-    // Write files to disk
-    for (relative_path, content) in restore_data.files {
+    // Write files to disk as they arrive, with the permissions and symlinks
+    // they were checkpointed with
+    let metadata: HashMap<_, _> = stream.checkpoint.changes
+        .iter()
+        .map(|change| (change.path.clone(), change.metadata.clone()))
+        .collect();
+    let (mut written, mut written_bytes) = (0, 0u64);
+    while let Some((relative_path, content)) = stream.next_file().await? {
         let file_path = current_dir.join(&relative_path);
-        
-        // Create parent directories if needed
-        if let Some(parent) = file_path.parent() {
-            std::fs::create_dir_all(parent)?;
+        if let Err(e) = write_file(&file_path, &content, metadata.get(&relative_path)) {
+            println!();
+            return Err(anyhow!("Failed to write {}: {}", relative_path.display(), e));
         }
-        
-        // Write file content
-        std::fs::write(&file_path, &content)?;
-        print_info(&format!("Restored: {}", relative_path.display()));
+        written += 1;
+        written_bytes += content.len() as u64;
+        let percent = if stream.total_bytes > 0 { (written_bytes * 100 / stream.total_bytes).min(100) } else { 100 };
+        print!("\r   {} {}/{} files ({}%)", "📥".cyan(), written, stream.file_count, percent);
+        io::stdout().flush().ok();
+    }
+    if written > 0 {
+        println!();
     }
 
     print_success(&format!("Successfully restored to checkpoint {} ({})", 
         &checkpoint_id[..8], 
-        restore_data.checkpoint.message.as_deref().unwrap_or("no message")
+        stream.checkpoint.message.as_deref().unwrap_or("no message")
     ));
     
     print_info("Files have been restored. Use 'mothership status' to see current state.");
+    if let Some(safety) = stream.safety_checkpoint {
+        println!("{}", format!("💡 Your previous state is saved as checkpoint {}; undo with 'mothership restore {}'", &safety.to_string()[..8], safety).dimmed());
+    }
+    Ok(())
+}
+
+/// A checkpoint's files (only the selected paths, if any) as the server
+/// streams them, one JSON line per file. A dry run leaves no trace on the server.
+struct RestoreStream {
+    lines: StreamedLines,
+    checkpoint: Checkpoint,
+    file_count: usize,
+    total_bytes: u64,
+    /// Checkpoint of the rift from just before the restore
+    safety_checkpoint: Option<uuid::Uuid>,
+    received: usize,
+}
+
+impl RestoreStream {
+    async fn open(
+        config_manager: &ConfigManager,
+        project_id: uuid::Uuid,
+        checkpoint_id: uuid::Uuid,
+        paths: &[PathBuf],
+        dry_run: bool,
+    ) -> Result<Self> {
+        let config = config_manager.load_config()?;
+        let server_url = get_server_url(config_manager)?;
+        let client = get_http_client(&config);
+
+        let response = client
+            .post(format!("{}/projects/{}/checkpoints/{}/restore/stream", server_url, project_id, checkpoint_id))
+            .json(&RestorePathsRequest { paths: paths.to_vec() })
+            .query(&[("dry_run", dry_run)])
+            .send()
+            .await?
+            .check_session()?;
+
+        if !response.status().is_success() {
+            return Err(http::api_error(response, "Failed to restore checkpoint").await);
+        }
+
+        let mut lines = StreamedLines { response, buffer: Vec::new() };
+        match lines.next().await? {
+            Some(RestoreStreamLine::Header { checkpoint, file_count, total_bytes, safety_checkpoint }) => {
+                Ok(Self { lines, checkpoint, file_count, total_bytes, safety_checkpoint, received: 0 })
+            }
+            Some(RestoreStreamLine::Error { message }) => Err(anyhow!("Failed to restore checkpoint: {}", message)),
+            _ => Err(anyhow!("No restore data received")),
+        }
+    }
+
+    /// The next file, or `None` once the server has sent them all
+    async fn next_file(&mut self) -> Result<Option<(PathBuf, String)>> {
+        match self.lines.next().await? {
+            Some(RestoreStreamLine::File { path, content }) => {
+                self.received += 1;
+                Ok(Some((path, content)))
+            }
+            Some(RestoreStreamLine::Done) => Ok(None),
+            Some(RestoreStreamLine::Error { message }) => {
+                Err(anyhow!("Restore failed after {} of {} files: {}", self.received, self.file_count, message))
+            }
+            Some(RestoreStreamLine::Header { .. }) => Err(anyhow!("Unexpected restore data received")),
+            None => Err(anyhow!("Restore was cut off after {} of {} files", self.received, self.file_count)),
+        }
+    }
+
+}
+
+/// Newline-delimited JSON read from a response as it arrives
+struct StreamedLines {
+    response: reqwest::Response,
+    buffer: Vec<u8>,
+}
+
+impl StreamedLines {
+    /// The next line, or `None` at the end of the response
+    async fn next(&mut self) -> Result<Option<RestoreStreamLine>> {
+        loop {
+            if let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = self.buffer.drain(..=end).collect();
+                return Ok(Some(serde_json::from_slice(&line)?));
+            }
+            match self.response.chunk().await? {
+                Some(chunk) => self.buffer.extend_from_slice(&chunk),
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+/// Lines added and removed going from one version of a file to another
+fn line_stats(from: &str, to: &str) -> (usize, usize) {
+    let mut counts: HashMap<&str, isize> = HashMap::new();
+    for line in from.lines() {
+        *counts.entry(line).or_default() -= 1;
+    }
+    for line in to.lines() {
+        *counts.entry(line).or_default() += 1;
+    }
+    let added = counts.values().filter(|&&count| count > 0).map(|&count| count as usize).sum();
+    let removed = counts.values().filter(|&&count| count < 0).map(|&count| count.unsigned_abs()).sum();
+    (added, removed)
+}
+
+/// Show what restoring a checkpoint's files (path to content) would change in
+/// the working directory, without writing anything
+pub(crate) fn print_restore_preview(project_dir: &Path, files: &HashMap<PathBuf, String>, paths: &[PathBuf]) -> Result<()> {
+    let mut preview = RestorePreview::new(project_dir, paths);
+    let mut sorted: Vec<(&PathBuf, &String)> = files.iter().collect();
+    sorted.sort();
+    for (path, content) in sorted {
+        preview.file(path, content);
+    }
+    preview.finish()
+}
+
+/// Dry-run summary of a restore, printed a file at a time so the
+/// checkpoint's files never have to be held at once
+pub(crate) struct RestorePreview<'a> {
+    project_dir: &'a Path,
+    paths: &'a [PathBuf],
+    restored: HashSet<PathBuf>,
+    added: usize,
+    modified: usize,
+    unchanged: usize,
+    lines_added: usize,
+    lines_removed: usize,
+}
+
+impl<'a> RestorePreview<'a> {
+    pub(crate) fn new(project_dir: &'a Path, paths: &'a [PathBuf]) -> Self {
+        println!("\n{}", "🔍 Dry run: nothing will be written".cyan().bold());
+        Self {
+            project_dir,
+            paths,
+            restored: HashSet::new(),
+            added: 0,
+            modified: 0,
+            unchanged: 0,
+            lines_added: 0,
+            lines_removed: 0,
+        }
+    }
+
+    pub(crate) fn file(&mut self, path: &Path, content: &str) {
+        self.restored.insert(path.to_path_buf());
+        let local_path = self.project_dir.join(path);
+        if local_path.symlink_metadata().is_err() {
+            let lines = content.lines().count();
+            println!("  {} {} {}", "added:   ".green(), path.display(), format!("+{}", lines).green());
+            self.added += 1;
+            self.lines_added += lines;
+            return;
+        }
+
+        match read_file(&local_path) {
+            Ok(local) if local == content => self.unchanged += 1,
+            Ok(local) => {
+                let (plus, minus) = line_stats(&local, content);
+                println!("  {} {} {} {}", "modified:".yellow(), path.display(), format!("+{}", plus).green(), format!("-{}", minus).red());
+                self.modified += 1;
+                self.lines_added += plus;
+                self.lines_removed += minus;
+            }
+            // Binary files aren't tracked, so the checkpoint's text replaces whatever is there
+            Err(_) => {
+                println!("  {} {} {}", "modified:".yellow(), path.display(), "(binary)".dimmed());
+                self.modified += 1;
+            }
+        }
+    }
+
+    pub(crate) fn finish(self) -> Result<()> {
+        println!(
+            "\n{} added, {} modified, {} unchanged ({}, {})",
+            self.added,
+            self.modified,
+            self.unchanged,
+            format!("+{} lines", self.lines_added).green(),
+            format!("-{} lines", self.lines_removed).red()
+        );
+
+        // Restoring only writes the checkpoint's files; anything else stays
+        let untouched = crate::local::scan_working_files(self.project_dir)?
+            .into_keys()
+            .filter(|path| !self.restored.contains(path))
+            .filter(|path| self.paths.is_empty() || self.paths.iter().any(|p| path.starts_with(p)))
+            .count();
+        if untouched > 0 {
+            println!("{}", format!("{} local files aren't in the checkpoint and would be left as they are", untouched).dimmed());
+        }
+        println!("{}", "💡 Run the same command without --dry-run to restore".dimmed());
+        Ok(())
+    }
+}
+
+/// Undo a checkpoint's changes with a new checkpoint on the current rift
+pub async fn handle_revert(config_manager: &ConfigManager, checkpoint_id: String) -> Result<()> {
+    // Check if authenticated
+    if !config_manager.is_authenticated()? {
+        print_api_error("Not authenticated. Run 'mothership auth' to get started.");
+        return Ok(());
+    }
+
+    let (project_id, project_name) = find_current_project()?;
+    let checkpoint_uuid = uuid::Uuid::parse_str(&checkpoint_id)
+        .map_err(|_| anyhow!("Invalid checkpoint ID format. Use the full checkpoint ID from 'mothership history'"))?;
+
+    let rift_id = std::fs::read_to_string(crate::project_dir()?.join(".mothership").join("project.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<ProjectMetadata>(&content).ok())
+        .and_then(|metadata| metadata.rift_id)
+        .and_then(|rift_id| uuid::Uuid::parse_str(&rift_id).ok());
+
+    let config = config_manager.load_config()?;
+    let server_url = get_server_url(config_manager)?;
+    let client = get_http_client(&config);
+
+    print_info(&format!("Reverting checkpoint {} in {}...", &checkpoint_id[..8.min(checkpoint_id.len())], project_name));
+
+    let revert_url = format!("{}/projects/{}/checkpoints/{}/revert", server_url, project_id, checkpoint_uuid);
+    let response = client
+        .post(&revert_url)
+        .json(&RevertCheckpointRequest { rift_id })
+        .send()
+        .await?
+        .check_session()?;
+
+    match response.status() {
+        reqwest::StatusCode::NOT_FOUND => return Err(anyhow!("Checkpoint {} not found in this project", checkpoint_id)),
+        reqwest::StatusCode::FORBIDDEN => return Err(anyhow!("You don't have write access to this rift")),
+        status if !status.is_success() => return Err(anyhow!("Failed to revert checkpoint: {}", status)),
+        _ => {}
+    }
+
+    let revert_response: ApiResponse<CheckpointRevertResult> = response.json().await?;
+    let Some(result) = revert_response.data else {
+        return Err(anyhow!(revert_response.error.unwrap_or_else(|| "Unknown error".to_string())));
+    };
+
+    if !result.conflicts.is_empty() {
+        print_api_error("Files changed since the checkpoint overlap the changes being reverted; nothing was changed:");
+        for path in &result.conflicts {
+            println!("   {} {}", "✗".red(), path.display().to_string().white());
+        }
+        println!("{}", "💡 Edit these files by hand, or restore them with 'mothership restore <checkpoint-id> --path <file>'".dimmed());
+        return Ok(());
+    }
+
+    match result.checkpoint_id {
+        Some(revert_id) => {
+            print_success(&format!("Reverted {} files in checkpoint {}", result.reverted_files, &revert_id.to_string()[..8]));
+            print_info("The daemon will pull the reverted files into your working directory");
+        }
+        None => print_info("The checkpoint's changes are already undone; nothing to revert"),
+    }
+
     Ok(())
 }
 
 /// Find the current project by looking for .mothership/project.json
 fn find_current_project() -> Result<(uuid::Uuid, String)> {
-    let current_dir = std::env::current_dir()?;
+    let current_dir = crate::project_dir()?;
     let mothership_dir = current_dir.join(".mothership");
     let project_file = mothership_dir.join("project.json");
 
@@ -339,7 +1005,7 @@ fn find_current_project() -> Result<(uuid::Uuid, String)> {
 }
 
 /// Format timestamp as "X minutes/hours/days ago"
-fn format_time_ago(timestamp: chrono::DateTime<chrono::Utc>) -> String {
+pub(crate) fn format_time_ago(timestamp: chrono::DateTime<chrono::Utc>) -> String {
     let now = chrono::Utc::now();
     let duration = now.signed_duration_since(timestamp);
 
@@ -366,16 +1032,14 @@ struct ProjectMetadata {
     project_name: String,
     created_at: String,
     mothership_url: String,
-}
-
-#[derive(Serialize, Deserialize)]
-struct RestoreData {
-    checkpoint: Checkpoint,
-    files: std::collections::HashMap<std::path::PathBuf, String>,
+    #[serde(default)]
+    rift_id: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
 struct CheckpointData {
     checkpoint_id: uuid::Uuid,
     file_count: usize,
+    #[serde(default)]
+    secret_findings: Vec<SecretFinding>,
 } 
\ No newline at end of file
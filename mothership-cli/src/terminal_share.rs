@@ -0,0 +1,345 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use futures_util::{SinkExt, StreamExt};
+use mothership_common::protocol::SyncMessage;
+use serde::Deserialize;
+use std::io::{Read, Write};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+use uuid::Uuid;
+
+use crate::{config::ConfigManager, print_info, print_success, connections};
+
+/// How long to wait for the server to accept the share
+const START_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often collected output is sent to the rift
+const FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Output sent in one message at most, in bytes (the server accepts up to 64KB)
+const MAX_CHUNK: usize = 32 * 1024;
+
+/// Local project metadata stored in .mothership directory
+#[derive(Deserialize)]
+struct ProjectMetadata {
+    project_name: String,
+    rift_id: Option<String>,
+}
+
+/// Find the rift of the current directory
+fn find_current_rift() -> Result<(Uuid, String)> {
+    let project_file = crate::project_dir()?.join(".mothership").join("project.json");
+    if !project_file.exists() {
+        return Err(anyhow!(
+            "Not in a Mothership project directory.\n\
+            Run this command from a project directory, or use 'mothership beam <project>' to enter a project."
+        ));
+    }
+
+    let metadata: ProjectMetadata = serde_json::from_str(&std::fs::read_to_string(&project_file)?)?;
+    let rift_id = metadata.rift_id
+        .ok_or_else(|| anyhow!("No rift for this project yet. Run 'mothership beam' to join one."))?;
+
+    Ok((Uuid::parse_str(&rift_id)?, metadata.project_name))
+}
+
+fn sync_url(config_manager: &ConfigManager, rift_id: Uuid) -> Result<String> {
+    let server_url = match connections::get_active_server_url() {
+        Some(server_url) => server_url,
+        None => config_manager.load_config()?.mothership_url,
+    };
+    let token = crate::get_oauth_token()
+        .ok_or_else(|| anyhow!("Not authenticated. Run 'mothership auth' to get started."))?;
+
+    let ws_base = if let Some(rest) = server_url.strip_prefix("https://") {
+        format!("wss://{}", rest)
+    } else if let Some(rest) = server_url.strip_prefix("http://") {
+        format!("ws://{}", rest)
+    } else {
+        format!("wss://{}", server_url)
+    };
+    Ok(format!("{}/ws/{}?token={}", ws_base, rift_id, urlencoding::encode(&token)))
+}
+
+fn to_frame(message: &SyncMessage) -> Result<Message> {
+    Ok(Message::Text(serde_json::to_string(message)?))
+}
+
+/// Run a command (the user's shell by default) and stream its terminal into
+/// the current rift until it exits
+pub async fn handle_share_terminal(config_manager: &ConfigManager, command: Vec<String>) -> Result<()> {
+    let (rift_id, project_name) = find_current_rift()?;
+    let command = if command.is_empty() {
+        vec![std::env::var("SHELL").unwrap_or_else(|_| default_shell().to_string())]
+    } else {
+        command
+    };
+    let (cols, rows) = terminal_size();
+
+    let mut socket = crate::connect_websocket(&sync_url(config_manager, rift_id)?).await
+        .map_err(|e| anyhow!("Failed to connect to rift: {}", e))?;
+
+    let share_id = Uuid::new_v4();
+    socket.send(to_frame(&SyncMessage::StartTerminalShare {
+        rift_id,
+        share_id,
+        command: command.join(" "),
+        cols,
+        rows,
+    })?).await?;
+
+    // Wait for the server to accept the share before starting the command
+    let started = tokio::time::timeout(START_TIMEOUT, async {
+        while let Some(msg) = socket.next().await {
+            let Message::Text(text) = msg? else { continue };
+            match serde_json::from_str(&text) {
+                Ok(SyncMessage::TerminalShareStarted { share, .. }) if share.id == share_id => return Ok(()),
+                Ok(SyncMessage::Error { message, .. }) => return Err(anyhow!("Server refused the terminal share: {}", message)),
+                _ => {}
+            }
+        }
+        Err(anyhow!("Disconnected from rift before the terminal share started"))
+    }).await;
+    match started {
+        Ok(result) => result?,
+        Err(_) => return Err(anyhow!("The server didn't accept the terminal share. It may not support terminal sharing.")),
+    }
+
+    print_success(&format!("Sharing '{}' with {} (exit the command to stop)", command.join(" "), project_name.blue().bold()));
+
+    let (output_sender, mut output_receiver) = mpsc::unbounded_channel::<Vec<u8>>();
+    let mut process = spawn_shared(&command, cols, rows, output_sender)?;
+
+    let mut pending = Vec::new();
+    let mut carry = Vec::new();
+    let mut flush = tokio::time::interval(FLUSH_INTERVAL);
+    let mut connected = true;
+    loop {
+        tokio::select! {
+            chunk = output_receiver.recv() => match chunk {
+                Some(chunk) => pending.extend_from_slice(&chunk),
+                None => break,
+            },
+            _ = flush.tick() => {
+                while connected && !pending.is_empty() {
+                    let take = pending.len().min(MAX_CHUNK);
+                    let data = decode_utf8(&mut carry, &pending.drain(..take).collect::<Vec<_>>());
+                    let message = SyncMessage::TerminalOutput { rift_id, share_id, data };
+                    connected = socket.send(to_frame(&message)?).await.is_ok();
+                }
+            },
+            msg = socket.next(), if connected => {
+                // Nothing to act on from the rift; just notice when the connection drops
+                if matches!(msg, None | Some(Err(_)) | Some(Ok(Message::Close(_)))) {
+                    connected = false;
+                }
+            },
+        }
+    }
+
+    let status = tokio::task::spawn_blocking(move || process.wait()).await;
+    process_finished();
+    let exit_code = status??.code();
+
+    if !pending.is_empty() || !carry.is_empty() {
+        let mut data = decode_utf8(&mut carry, &pending);
+        data.push_str(&String::from_utf8_lossy(&carry));
+        let _ = socket.send(to_frame(&SyncMessage::TerminalOutput { rift_id, share_id, data })?).await;
+    }
+    let _ = socket.send(to_frame(&SyncMessage::EndTerminalShare { rift_id, share_id, exit_code })?).await;
+    let _ = socket.close(None).await;
+
+    if connected {
+        print_info(&format!("Stopped sharing (exit code {})", exit_code.map_or("unknown".to_string(), |code| code.to_string())));
+    } else {
+        print_info("Lost the connection to the rift; collaborators saw output up to the disconnect");
+    }
+    Ok(())
+}
+
+/// Turn bytes into text, keeping an incomplete character at the end for the next chunk
+fn decode_utf8(carry: &mut Vec<u8>, bytes: &[u8]) -> String {
+    carry.extend_from_slice(bytes);
+    match std::str::from_utf8(carry) {
+        Ok(text) => {
+            let text = text.to_string();
+            carry.clear();
+            text
+        }
+        Err(e) if e.error_len().is_none() => {
+            let valid = e.valid_up_to();
+            let text = String::from_utf8_lossy(&carry[..valid]).into_owned();
+            carry.drain(..valid);
+            text
+        }
+        Err(_) => {
+            let text = String::from_utf8_lossy(carry).into_owned();
+            carry.clear();
+            text
+        }
+    }
+}
+
+/// Copy everything read from `reader` to our own terminal and to the sender
+fn forward_output(mut reader: impl Read + Send + 'static, sender: mpsc::UnboundedSender<Vec<u8>>) {
+    std::thread::spawn(move || {
+        let mut buffer = [0u8; 8192];
+        loop {
+            match reader.read(&mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(read) => {
+                    let mut stdout = std::io::stdout();
+                    let _ = stdout.write_all(&buffer[..read]);
+                    let _ = stdout.flush();
+                    if sender.send(buffer[..read].to_vec()).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
+
+#[cfg(unix)]
+fn default_shell() -> &'static str {
+    "sh"
+}
+
+#[cfg(not(unix))]
+fn default_shell() -> &'static str {
+    "cmd"
+}
+
+#[cfg(unix)]
+use pty::{process_finished, spawn_shared, terminal_size};
+
+#[cfg(not(unix))]
+use pipes::{process_finished, spawn_shared, terminal_size};
+
+/// Commands run in a pseudo-terminal, so they behave (colors, prompts, line
+/// editing) as they would in the user's own terminal
+#[cfg(unix)]
+mod pty {
+    use anyhow::{anyhow, Result};
+    use std::fs::File;
+    use std::io::{Read, Write};
+    use std::os::unix::io::FromRawFd;
+    use std::os::unix::process::CommandExt;
+    use std::process::{Child, Command};
+    use std::sync::Mutex;
+    use tokio::sync::mpsc;
+
+    /// Settings of our terminal before it was put in raw mode
+    static ORIGINAL_TERMIOS: Mutex<Option<libc::termios>> = Mutex::new(None);
+
+    pub fn terminal_size() -> (u16, u16) {
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        if unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } == 0 && size.ws_col > 0 && size.ws_row > 0 {
+            (size.ws_col, size.ws_row)
+        } else {
+            (80, 24)
+        }
+    }
+
+    /// Keystrokes go to the command untouched while it runs
+    fn enter_raw_mode() {
+        if unsafe { libc::isatty(libc::STDIN_FILENO) } != 1 {
+            return;
+        }
+        let mut termios: libc::termios = unsafe { std::mem::zeroed() };
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut termios) } != 0 {
+            return;
+        }
+        *ORIGINAL_TERMIOS.lock().unwrap() = Some(termios);
+        let mut raw = termios;
+        unsafe {
+            libc::cfmakeraw(&mut raw);
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw);
+        }
+    }
+
+    /// Put our terminal back the way it was
+    pub fn process_finished() {
+        if let Some(termios) = ORIGINAL_TERMIOS.lock().unwrap().take() {
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios);
+            }
+        }
+    }
+
+    pub fn spawn_shared(command: &[String], cols: u16, rows: u16, output: mpsc::UnboundedSender<Vec<u8>>) -> Result<Child> {
+        let (mut master, mut slave) = (0, 0);
+        let size = libc::winsize { ws_row: rows, ws_col: cols, ws_xpixel: 0, ws_ypixel: 0 };
+        if unsafe { libc::openpty(&mut master, &mut slave, std::ptr::null_mut(), std::ptr::null_mut(), &size) } != 0 {
+            return Err(anyhow!("Failed to open a terminal: {}", std::io::Error::last_os_error()));
+        }
+        let master = unsafe { File::from_raw_fd(master) };
+        let slave = unsafe { File::from_raw_fd(slave) };
+
+        let mut process = Command::new(&command[0]);
+        process.args(&command[1..])
+            .stdin(slave.try_clone()?)
+            .stdout(slave.try_clone()?)
+            .stderr(slave.try_clone()?);
+        // Run the command in its own session with the pseudo-terminal as its controlling terminal
+        unsafe {
+            process.pre_exec(|| {
+                if libc::setsid() == -1 || libc::ioctl(libc::STDIN_FILENO, libc::TIOCSCTTY as _, 0) == -1 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        let child = process.spawn().map_err(|e| anyhow!("Failed to run '{}': {}", command[0], e))?;
+        // Only the command may hold the terminal open, so reading ends when it exits
+        drop(process);
+        drop(slave);
+
+        enter_raw_mode();
+        let mut input = master.try_clone()?;
+        std::thread::spawn(move || {
+            let mut buffer = [0u8; 1024];
+            let mut stdin = std::io::stdin();
+            while let Ok(read) = stdin.read(&mut buffer) {
+                if read == 0 || input.write_all(&buffer[..read]).is_err() {
+                    break;
+                }
+            }
+        });
+        super::forward_output(master, output);
+        Ok(child)
+    }
+}
+
+/// Without pseudo-terminals the command's output is captured through pipes
+#[cfg(not(unix))]
+mod pipes {
+    use anyhow::{anyhow, Result};
+    use std::process::{Child, Command, Stdio};
+    use tokio::sync::mpsc;
+
+    pub fn terminal_size() -> (u16, u16) {
+        (80, 24)
+    }
+
+    pub fn process_finished() {}
+
+    pub fn spawn_shared(command: &[String], _cols: u16, _rows: u16, output: mpsc::UnboundedSender<Vec<u8>>) -> Result<Child> {
+        let mut child = Command::new(&command[0])
+            .args(&command[1..])
+            .stdin(Stdio::inherit())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| anyhow!("Failed to run '{}': {}", command[0], e))?;
+
+        if let Some(stdout) = child.stdout.take() {
+            super::forward_output(stdout, output.clone());
+        }
+        if let Some(stderr) = child.stderr.take() {
+            super::forward_output(stderr, output);
+        }
+        Ok(child)
+    }
+}
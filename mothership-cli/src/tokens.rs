@@ -0,0 +1,167 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use colored::*;
+use mothership_common::protocol::ApiResponse;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{config::ConfigManager, get_http_client, http::{self, ResponseExt}, print_api_error, print_info, print_success, connections};
+
+/// Personal access token as returned by the server (never includes the secret)
+#[derive(Debug, Deserialize)]
+struct AccessTokenInfo {
+    id: Uuid,
+    name: String,
+    scopes: Vec<String>,
+    created_at: DateTime<Utc>,
+    expires_at: Option<DateTime<Utc>>,
+    last_used_at: Option<DateTime<Utc>>,
+    revoked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CreatedToken {
+    token: String,
+    #[serde(flatten)]
+    info: AccessTokenInfo,
+}
+
+/// Get the server URL to use for token operations
+/// Prioritizes active server connection over config file
+fn get_server_url(config_manager: &ConfigManager) -> Result<String> {
+    if let Some(server_url) = connections::get_active_server_url() {
+        return Ok(server_url);
+    }
+
+    let config = config_manager.load_config()?;
+    Ok(config.mothership_url)
+}
+
+/// Mint a new personal access token and print it once
+pub async fn handle_token_create(
+    config_manager: &ConfigManager,
+    name: String,
+    scopes: Vec<String>,
+    expires_in_days: Option<i64>,
+) -> Result<()> {
+    let config = config_manager.load_config()?;
+    let server_url = get_server_url(config_manager)?;
+    let client = get_http_client(&config);
+
+    let mut body = serde_json::json!({
+        "name": name,
+        "expires_in_days": expires_in_days,
+    });
+    if !scopes.is_empty() {
+        body["scopes"] = serde_json::json!(scopes);
+    }
+
+    let response = client
+        .post(format!("{}/auth/tokens", server_url))
+        .json(&body)
+        .send()
+        .await?
+        .check_session()?;
+
+    if response.status() == reqwest::StatusCode::FORBIDDEN {
+        print_api_error("Not allowed to create this token. Tokens can only be created from an interactive login, and the admin scope requires an admin account.");
+        return Ok(());
+    }
+    if !response.status().is_success() {
+        return Err(http::api_error(response, "Token request failed").await);
+    }
+
+    let result: ApiResponse<CreatedToken> = response.json().await?;
+    let created = match result.data {
+        Some(created) => created,
+        None => {
+            print_api_error(&http::describe_failure(&result));
+            return Ok(());
+        }
+    };
+
+    print_success(&format!("Created token '{}' ({})", created.info.name, created.info.scopes.join(", ")));
+    println!("\n    {}\n", created.token.yellow().bold());
+    print_info("Copy this token now - it will not be shown again.");
+    print_info("Use it in CI by setting MOTHERSHIP_TOKEN in the environment.");
+
+    if let Some(expires_at) = created.info.expires_at {
+        println!("{} {}", "Expires:".bold(), expires_at.format("%Y-%m-%d %H:%M UTC").to_string().dimmed());
+    }
+
+    Ok(())
+}
+
+/// List the current user's personal access tokens
+pub async fn handle_token_list(config_manager: &ConfigManager) -> Result<()> {
+    let config = config_manager.load_config()?;
+    let server_url = get_server_url(config_manager)?;
+    let client = get_http_client(&config);
+
+    let response = client
+        .get(format!("{}/auth/tokens", server_url))
+        .send()
+        .await?
+        .check_session()?;
+
+    if !response.status().is_success() {
+        return Err(http::api_error(response, "Token request failed").await);
+    }
+
+    let result: ApiResponse<Vec<AccessTokenInfo>> = response.json().await?;
+    let tokens = result.data.unwrap_or_default();
+
+    if tokens.is_empty() {
+        print_info("No personal access tokens. Create one with 'mothership auth token create <name>'.");
+        return Ok(());
+    }
+
+    println!("\n{}", "🔑 Personal Access Tokens".cyan().bold());
+    for token in tokens {
+        let now = Utc::now();
+        let state = if token.revoked_at.is_some() {
+            "revoked".red()
+        } else if token.expires_at.is_some_and(|t| t <= now) {
+            "expired".yellow()
+        } else {
+            "active".green()
+        };
+
+        println!("\n  {} {} [{}]", token.id.to_string().dimmed(), token.name.white().bold(), state);
+        println!("    {} {}", "Scopes:".dimmed(), token.scopes.join(", "));
+        println!("    {} {}", "Created:".dimmed(), crate::sync::format_time_ago(token.created_at));
+        if let Some(expires_at) = token.expires_at {
+            println!("    {} {}", "Expires:".dimmed(), expires_at.format("%Y-%m-%d %H:%M UTC"));
+        }
+        let last_used = token.last_used_at
+            .map(crate::sync::format_time_ago)
+            .unwrap_or_else(|| "never".to_string());
+        println!("    {} {}", "Last used:".dimmed(), last_used);
+    }
+
+    Ok(())
+}
+
+/// Revoke one of the current user's personal access tokens
+pub async fn handle_token_revoke(config_manager: &ConfigManager, token_id: String) -> Result<()> {
+    let token_id = Uuid::parse_str(&token_id)
+        .map_err(|_| anyhow!("Invalid token ID '{}'. Use 'mothership auth token list' to find it.", token_id))?;
+
+    let config = config_manager.load_config()?;
+    let server_url = get_server_url(config_manager)?;
+    let client = get_http_client(&config);
+
+    let response = client
+        .delete(format!("{}/auth/tokens/{}", server_url, token_id))
+        .send()
+        .await?
+        .check_session()?;
+
+    match response.status() {
+        status if status.is_success() => print_success(&format!("Revoked token {}", token_id)),
+        reqwest::StatusCode::NOT_FOUND => print_api_error("Token not found or already revoked"),
+        status => return Err(anyhow!("Token request failed: {}", status)),
+    }
+
+    Ok(())
+}
@@ -2,6 +2,10 @@ use anyhow::Result;
 use clap::Args;
 use colored::*;
 use mothership_common::protocol::ApiResponse;
+use mothership_common::release::{
+    apply_delta, compare_versions, sha256_hex, verify_signature, ReleaseArtifact, ReleaseChannel, ReleaseManifest,
+};
+use mothership_common::{profile, tls};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
@@ -24,7 +28,7 @@ fn get_server_url(config_manager: &ConfigManager) -> Result<String> {
 }
 
 /// Update command arguments
-#[derive(Args)]
+#[derive(Clone, Args)]
 pub struct UpdateArgs {
     /// Check for updates without installing
     #[arg(long)]
@@ -41,6 +45,10 @@ pub struct UpdateArgs {
     /// Update to specific version
     #[arg(long)]
     pub version: Option<String>,
+
+    /// Release channel to follow from now on: stable, beta or nightly
+    #[arg(long)]
+    pub channel: Option<ReleaseChannel>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -55,6 +63,8 @@ struct UpdateCheckResponse {
 #[derive(Debug, Serialize, Deserialize)]
 struct VersionInfo {
     version: String,
+    #[serde(default)]
+    channel: ReleaseChannel,
     platforms: Vec<String>,
     release_date: chrono::DateTime<chrono::Utc>,
     changes: Vec<String>,
@@ -68,87 +78,103 @@ pub async fn handle_update(args: UpdateArgs) -> Result<()> {
     if args.list_versions {
         return list_available_versions(&server_url).await;
     }
+
+    // A channel picked with --channel sticks for later updates
+    let mut config = config_manager.load_config()?;
+    let channel = match args.channel {
+        Some(channel) if channel != config.update_channel => {
+            config.update_channel = channel;
+            config_manager.save_config(&config)?;
+            channel
+        }
+        Some(channel) => channel,
+        None => config.update_channel,
+    };
     
     let current_version = env!("CARGO_PKG_VERSION");
     let platform = detect_platform();
     
     println!("{}", "🔍 Getting latest version...".blue());
     println!("Current version: {}", current_version.green());
+    println!("Channel: {}", channel.to_string().cyan());
     println!("Platform: {}", platform.cyan());
     println!("Server: {}", server_url.cyan());
     println!();
     
-    // Always get the absolute latest version directly from server
-    let latest_info = get_latest_version_direct(&server_url).await?;
-    let latest_version = latest_info.version.clone();
-    
-    // Compare versions using semantic version comparison
-    let update_available = current_version != latest_version;
+    // The requested version, or the newest on the channel
+    let release = get_release_manifest(&server_url, channel, args.version.as_deref(), &platform, current_version).await?;
+    let target_version = release.version.clone();
     
-    // Check if specific version was requested
+    let update_available = compare_versions(&target_version, current_version).is_gt();
     let version_specified = args.version.is_some();
     
-    // Determine target version - use specified version or latest
-    let target_version = args.version.unwrap_or(latest_version.clone());
-    
     if !update_available && !args.force && !version_specified {
-        println!("{}", "✅ You're running the latest version!".green());
+        println!("{}", format!("✅ You're running the latest {} version!", channel).green());
         return Ok(());
     }
     
     if args.check_only {
-        if update_available || version_specified {
-            println!("{}", format!("🆕 Update available: {} → {}", 
-                current_version, target_version).yellow());
-            
-            if target_version == latest_version && !latest_info.changes.is_empty() {
-                println!("\n📝 Changes:");
-                for change in &latest_info.changes {
-                    println!("  • {}", change);
-                }
+        println!("{}", format!("🆕 Update available: {} → {} ({})", 
+            current_version, target_version, release.channel).yellow());
+        
+        if !release.changes.is_empty() {
+            println!("\n📝 Changes:");
+            for change in &release.changes {
+                println!("  • {}", change);
             }
-            
-            println!("\n💡 Run 'mothership update' to install the update");
-        } else {
-            println!("{}", "✅ No updates available".green());
         }
+        
+        let download_size: u64 = release.artifacts.iter()
+            .map(|artifact| artifact.delta.as_ref().map_or(artifact.size, |delta| delta.size))
+            .sum();
+        println!("\n📦 Download size: {:.1} MB", download_size as f64 / 1_048_576.0);
+        println!("\n💡 Run 'mothership update' to install the update");
         return Ok(());
     }
     
-    // Perform update if needed or forced
-    if args.force || update_available || version_specified {
-        println!("{}", format!("⬇️  Updating to version {}...", target_version).yellow());
-        
-
-        
-        download_and_install_update(&server_url, &target_version, &platform).await?;
-        
-        println!("{}", "✅ Update completed successfully!".green());
-        println!("🔄 Please restart any running mothership processes");
-    }
+    println!("{}", format!("⬇️  Updating to version {}...", target_version).yellow());
+    download_and_install_update(&server_url, &release).await?;
+    
+    println!("{}", "✅ Update completed successfully!".green());
+    println!("🔄 Please restart any running mothership processes");
     
     Ok(())
 }
 
-/// Get the latest version directly from the server (bypasses incremental updates)
-async fn get_latest_version_direct(server_url: &str) -> Result<VersionInfo> {
-    // Get authentication token
+/// Get a release's artifacts, checksums and signatures: a specific version
+/// or the newest on a channel, with delta patches from the version we run
+async fn get_release_manifest(
+    server_url: &str,
+    channel: ReleaseChannel,
+    version: Option<&str>,
+    platform: &str,
+    current_version: &str,
+) -> Result<ReleaseManifest> {
     let token = get_auth_token()?;
+    let client = tls::http_client(server_url)?;
     
-    let client = reqwest::Client::new();
-    
-    let url = format!("{}/cli/latest", server_url);
+    let url = match version {
+        Some(version) => format!("{}/cli/releases/{}", server_url, version),
+        None => format!("{}/cli/channels/{}/latest", server_url, channel),
+    };
     let response = client
         .get(&url)
         .header("Authorization", format!("Bearer {}", token))
+        .query(&[("platform", platform), ("from_version", current_version)])
         .send()
         .await?;
 
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(match version {
+            Some(version) => anyhow::anyhow!("Version {} is not available for {}", version, platform),
+            None => anyhow::anyhow!("No {} release is available for {}", channel, platform),
+        });
+    }
     if !response.status().is_success() {
         return Err(anyhow::anyhow!("Server error: {}", response.status()));
     }
 
-    let api_response: ApiResponse<VersionInfo> = response.json().await?;
+    let api_response: ApiResponse<ReleaseManifest> = response.json().await?;
 
     match api_response {
         ApiResponse { success: true, data: Some(data), .. } => Ok(data),
@@ -166,7 +192,7 @@ async fn check_for_updates(
     // Get authentication token
     let token = get_auth_token()?;
     
-    let client = reqwest::Client::new();
+    let client = tls::http_client(server_url)?;
     let binary_name = if cfg!(windows) { "mothership.exe" } else { "mothership" };
     
     let url = format!("{}/cli/update-check", server_url);
@@ -199,7 +225,7 @@ async fn list_available_versions(server_url: &str) -> Result<()> {
     // Get authentication token
     let token = get_auth_token()?;
     
-    let client = reqwest::Client::new();
+    let client = tls::http_client(server_url)?;
     
     let url = format!("{}/cli/versions", server_url);
     let response = client
@@ -220,7 +246,7 @@ async fn list_available_versions(server_url: &str) -> Result<()> {
             println!();
             
             for version in versions.iter().rev() { // Show newest first
-                println!("{} {}", "Version:".bold(), version.version.green());
+                println!("{} {} ({})", "Version:".bold(), version.version.green(), version.channel);
                 println!("  Released: {}", version.release_date.format("%Y-%m-%d %H:%M UTC"));
                 println!("  Platforms: {}", version.platforms.join(", ").cyan());
                 
@@ -244,58 +270,131 @@ async fn list_available_versions(server_url: &str) -> Result<()> {
     Ok(())
 }
 
-/// Download and install update
-async fn download_and_install_update(
-    server_url: &str,
-    version: &str,
-    platform: &str,
-) -> Result<()> {
+/// Download, verify and install a release's binaries. Nothing is
+/// installed unless every binary passes its checks.
+async fn download_and_install_update(server_url: &str, release: &ReleaseManifest) -> Result<()> {
     // Get authentication token
     let token = get_auth_token()?;
     
-    let client = reqwest::Client::new();
+    let client = tls::http_client(server_url)?;
+    
+    let public_key = release_public_key();
+    if public_key.is_none() {
+        println!("{}", "⚠️  No release signing key is configured; checking checksums only".yellow());
+    }
     
-    // Download CLI binary
+    let mut binaries = Vec::new();
+    for artifact in &release.artifacts {
+        println!("⬇️  Downloading {}...", artifact.binary);
+        let binary_data = download_artifact(&client, server_url, &token, release, artifact).await?;
+        verify_artifact(artifact, &binary_data, public_key.as_deref())?;
+        binaries.push((artifact.binary.as_str(), binary_data));
+    }
+    println!("{}", "🔏 Checksums and signatures verified".green());
+    
+    for (binary_name, binary_data) in &binaries {
+        install_binary(binary_name, binary_data)?;
+    }
+    
+    // Handle self-update for CLI binary
     let cli_binary = if cfg!(windows) { "mothership.exe" } else { "mothership" };
-    let daemon_binary = if cfg!(windows) { "mothership-daemon.exe" } else { "mothership-daemon" };
+    let cli_install_path = get_binary_install_path(cli_binary)?;
+    if is_self_update(&cli_install_path)? {
+        return perform_self_update(&cli_install_path).await;
+    }
     
-    // Verify binaries exist on server before downloading
-    let cli_url = format!("{}/cli/download/{}/{}/{}", server_url, version, platform, cli_binary);
-    let daemon_url = format!("{}/cli/download/{}/{}/{}", server_url, version, platform, daemon_binary);
+    Ok(())
+}
+
+/// Key the server's release binaries are signed with: set for self-hosted
+/// builds at run time, or built in
+fn release_public_key() -> Option<String> {
+    std::env::var("MOTHERSHIP_RELEASE_PUBLIC_KEY")
+        .ok()
+        .or_else(|| option_env!("MOTHERSHIP_RELEASE_PUBLIC_KEY").map(str::to_string))
+        .filter(|key| !key.trim().is_empty())
+}
+
+/// Get an artifact, from a delta patch against the installed binary when
+/// the server offers one, otherwise in full
+async fn download_artifact(
+    client: &reqwest::Client,
+    server_url: &str,
+    token: &str,
+    release: &ReleaseManifest,
+    artifact: &ReleaseArtifact,
+) -> Result<Vec<u8>> {
+    if artifact.delta.is_some() {
+        match download_from_delta(client, server_url, token, release, artifact).await {
+            Ok(binary_data) => return Ok(binary_data),
+            Err(e) => println!("{}", format!("⚠️  Delta update unavailable ({}); downloading the full binary", e).yellow()),
+        }
+    }
     
-    // Check CLI binary
-    let cli_response = client.head(&cli_url)
+    let url = format!("{}/cli/download/{}/{}/{}", server_url, release.version, release.platform, artifact.binary);
+    let response = client
+        .get(&url)
         .header("Authorization", format!("Bearer {}", token))
         .send()
         .await?;
     
-    if !cli_response.status().is_success() {
-        return Err(anyhow::anyhow!("CLI binary not found on server for version {} ({})", version, platform));
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("Failed to download {}: {}", artifact.binary, response.status()));
+    }
+    
+    Ok(response.bytes().await?.to_vec())
+}
+
+async fn download_from_delta(
+    client: &reqwest::Client,
+    server_url: &str,
+    token: &str,
+    release: &ReleaseManifest,
+    artifact: &ReleaseArtifact,
+) -> Result<Vec<u8>> {
+    let delta = artifact.delta.as_ref().ok_or_else(|| anyhow::anyhow!("no patch offered"))?;
+    let installed = fs::read(get_binary_install_path(&artifact.binary)?)?;
+    if sha256_hex(&installed) != delta.from_sha256 {
+        return Err(anyhow::anyhow!("installed binary differs from {}", delta.from_version));
     }
     
-    // Check daemon binary
-    let daemon_response = client.head(&daemon_url)
+    let url = format!("{}/cli/delta/{}/{}/{}/{}", server_url, delta.from_version, release.version, release.platform, artifact.binary);
+    let response = client
+        .get(&url)
         .header("Authorization", format!("Bearer {}", token))
         .send()
         .await?;
-    
-    if !daemon_response.status().is_success() {
-        return Err(anyhow::anyhow!("Daemon binary not found on server for version {} ({})", version, platform));
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("server returned {}", response.status()));
     }
     
-    println!("⬇️  Downloading CLI...");
-    download_binary_safe(&client, server_url, version, platform, cli_binary, &token).await?;
-    
-    println!("⬇️  Downloading daemon...");
-    download_binary_safe(&client, server_url, version, platform, daemon_binary, &token).await?;
+    let patch = response.bytes().await?;
+    if sha256_hex(&patch) != delta.sha256 {
+        return Err(anyhow::anyhow!("patch checksum mismatch"));
+    }
+    let binary_data = apply_delta(&installed, &patch).map_err(|e| anyhow::anyhow!(e))?;
+    if sha256_hex(&binary_data) != artifact.sha256 {
+        return Err(anyhow::anyhow!("patched binary checksum mismatch"));
+    }
     
-    // Handle self-update for CLI binary
-    let cli_install_path = get_binary_install_path(cli_binary)?;
-    if is_self_update(&cli_install_path)? {
-        return perform_self_update(&cli_install_path).await;
+    println!("  Patched from {} ({:.1} KB instead of {:.1} MB)",
+        delta.from_version, delta.size as f64 / 1024.0, artifact.size as f64 / 1_048_576.0);
+    Ok(binary_data)
+}
+
+/// Check a downloaded binary against its published checksum and, when a
+/// release key is configured, its signature
+fn verify_artifact(artifact: &ReleaseArtifact, binary_data: &[u8], public_key: Option<&str>) -> Result<()> {
+    if sha256_hex(binary_data) != artifact.sha256 {
+        return Err(anyhow::anyhow!("Checksum mismatch for {}; not installing", artifact.binary));
     }
     
-    Ok(())
+    match (public_key, &artifact.signature) {
+        (Some(public_key), Some(signature)) => verify_signature(public_key, binary_data, signature)
+            .map_err(|e| anyhow::anyhow!("{}: {}; not installing", artifact.binary, e)),
+        (Some(_), None) => Err(anyhow::anyhow!("{} is not signed; not installing", artifact.binary)),
+        (None, _) => Ok(()),
+    }
 }
 
 /// Check if this is a self-update (CLI updating itself)
@@ -363,29 +462,8 @@ pause\r\n",
     std::process::exit(0)
 }
 
-/// Download a single binary with safe self-update handling
-async fn download_binary_safe(
-    client: &reqwest::Client,
-    server_url: &str,
-    version: &str,
-    platform: &str,
-    binary_name: &str,
-    token: &str,
-) -> Result<()> {
-    let url = format!("{}/cli/download/{}/{}/{}", server_url, version, platform, binary_name);
-    
-    let response = client
-        .get(&url)
-        .header("Authorization", format!("Bearer {}", token))
-        .send()
-        .await?;
-    
-    if !response.status().is_success() {
-        return Err(anyhow::anyhow!("Failed to download {}: {}", binary_name, response.status()));
-    }
-    
-    let binary_data = response.bytes().await?;
-    
+/// Install a verified binary with safe self-update handling
+fn install_binary(binary_name: &str, binary_data: &[u8]) -> Result<()> {
     // Determine installation path
     let install_path = get_binary_install_path(binary_name)?;
     
@@ -475,10 +553,8 @@ fn get_auth_token() -> Result<String> {
         access_token: String,
     }
     
-    let credentials_path = dirs::config_dir()
-        .ok_or_else(|| anyhow::anyhow!("Could not find config directory"))?
-        .join("mothership")
-        .join("credentials.json");
+    let credentials_path = profile::credentials_path()
+        .ok_or_else(|| anyhow::anyhow!("Could not find config directory"))?;
     
     if !credentials_path.exists() {
         // Try old config format
@@ -0,0 +1,108 @@
+//! Compare JSON text frames with MessagePack binary frames for large sync payloads.
+//!
+//! Run with `cargo bench -p mothership-common --bench wire_encoding`. Encoded
+//! sizes are printed once before the timing runs.
+
+use chrono::Utc;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use mothership_common::protocol::SyncMessage;
+use mothership_common::FileMetadata;
+use mothership_common::wire::{self, WireEncoding, WireFrame};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// Source-like text: quotes, backslashes, tabs and newlines all need escaping in JSON
+fn source_file(size: usize) -> String {
+    let line = "\tlet message = format!(\"{}: \\\"{}\\\"\", key, value); // 🚀 sync\n";
+    line.repeat(size / line.len() + 1)[..size].to_string()
+}
+
+fn file_changed(size: usize) -> SyncMessage {
+    SyncMessage::FileChanged {
+        rift_id: Uuid::new_v4(),
+        path: PathBuf::from("src/generated/large_module.rs"),
+        content: source_file(size),
+        timestamp: Utc::now(),
+        metadata: FileMetadata::default(),
+    }
+}
+
+/// Initial sync of a whole project, the largest message a client receives
+fn rift_joined(files: usize, size: usize) -> SyncMessage {
+    let current_files: HashMap<PathBuf, String> = (0..files)
+        .map(|i| (PathBuf::from(format!("src/module_{}.rs", i)), source_file(size)))
+        .collect();
+
+    SyncMessage::RiftJoined {
+        rift_id: Uuid::new_v4(),
+        current_files,
+        participants: vec!["alice".to_string(), "bob".to_string()],
+        last_checkpoint: None,
+        protocol_version: 3,
+        encoding: WireEncoding::MessagePack,
+        session_id: None,
+        seq: 0,
+        sparse_paths: Vec::new(),
+        file_metadata: HashMap::new(),
+        reconcile: None,
+    }
+}
+
+fn frame_len(frame: &WireFrame) -> usize {
+    match frame {
+        WireFrame::Text(text) => text.len(),
+        WireFrame::Binary(bytes) => bytes.len(),
+    }
+}
+
+fn decode(frame: &WireFrame) -> SyncMessage {
+    match frame {
+        WireFrame::Text(text) => wire::decode_text(text).unwrap(),
+        WireFrame::Binary(bytes) => wire::decode_binary(bytes).unwrap(),
+    }
+}
+
+fn bench_wire_encoding(c: &mut Criterion) {
+    let payloads = [
+        ("file_64k", file_changed(64 * 1024)),
+        ("file_1m", file_changed(1024 * 1024)),
+        ("rift_joined_200x16k", rift_joined(200, 16 * 1024)),
+    ];
+    let encodings = [WireEncoding::Json, WireEncoding::MessagePack];
+
+    for (name, message) in &payloads {
+        for encoding in encodings {
+            let size = frame_len(&encoding.encode(message).unwrap());
+            println!("{:<22} {:<12} {:>10} bytes", name, encoding.as_str(), size);
+        }
+    }
+
+    let mut encode_group = c.benchmark_group("encode");
+    for (name, message) in &payloads {
+        let size = frame_len(&WireEncoding::Json.encode(message).unwrap());
+        encode_group.throughput(Throughput::Bytes(size as u64));
+        for encoding in encodings {
+            encode_group.bench_with_input(BenchmarkId::new(encoding.as_str(), name), message, |b, message| {
+                b.iter(|| encoding.encode(black_box(message)).unwrap())
+            });
+        }
+    }
+    encode_group.finish();
+
+    let mut decode_group = c.benchmark_group("decode");
+    for (name, message) in &payloads {
+        let size = frame_len(&WireEncoding::Json.encode(message).unwrap());
+        decode_group.throughput(Throughput::Bytes(size as u64));
+        for encoding in encodings {
+            let frame = encoding.encode(message).unwrap();
+            decode_group.bench_with_input(BenchmarkId::new(encoding.as_str(), name), &frame, |b, frame| {
+                b.iter(|| decode(black_box(frame)))
+            });
+        }
+    }
+    decode_group.finish();
+}
+
+criterion_group!(benches, bench_wire_encoding);
+criterion_main!(benches);
@@ -73,6 +73,10 @@ pub struct OAuthProfile {
     pub name: String,
     pub username: Option<String>,
     pub avatar_url: Option<String>,
+    /// Organizations and teams the provider reports, like `github:acme`,
+    /// `github:acme/platform` or `google:company.com`
+    #[serde(default)]
+    pub groups: Vec<String>,
 }
 
 #[deprecated(since = "0.1.0", note = "Use OAuth authentication instead")]
@@ -119,6 +123,19 @@ pub struct MachineAuth {
     pub created_at: DateTime<Utc>,
 }
 
+/// Exchange a refresh token for a new access token (`POST /auth/refresh`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshResponse {
+    pub access_token: String,
+    /// Replacement refresh token, if the server rotates them
+    pub refresh_token: Option<String>,
+}
+
 /// JWT Claims structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
@@ -130,6 +147,8 @@ pub struct Claims {
     pub exp: i64,             // Expiration time
     pub aud: String,          // Audience (mothership)
     pub iss: String,          // Issuer (mothership-server)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,  // Token ID (login session), checked against the revocation list
 }
 
 /// Authentication errors
@@ -141,6 +160,8 @@ pub enum AuthError {
     InvalidToken,
     #[error("Expired token")]
     ExpiredToken,
+    #[error("Revoked token")]
+    RevokedToken,
     #[error("Authorization pending")]
     AuthorizationPending,
     #[error("Access denied")]
@@ -162,4 +183,42 @@ pub struct MachineInfo {
     pub hostname: String,
     pub last_seen: DateTime<Utc>,
     pub is_active: bool,
-} 
\ No newline at end of file
+} 
+
+/// A machine login, as listed by `GET /auth/sessions`
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct AuthSession {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub username: String,
+    pub machine_id: String,
+    pub machine_name: String,
+    pub platform: String,
+    pub hostname: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub last_seen_at: Option<DateTime<Utc>>,
+    /// True for the session the request was made with
+    #[serde(default)]
+    pub current: bool,
+}
+
+/// A machine a user has logged in from, as listed by `GET /users/me/machines`
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Machine {
+    pub id: Uuid,
+    /// The identifier the machine logs in with
+    pub machine_id: String,
+    pub name: String,
+    pub platform: String,
+    pub hostname: String,
+    pub first_seen_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    /// Set once the machine was revoked; its sessions stop working
+    pub revoked_at: Option<DateTime<Utc>>,
+    /// Sessions still logged in on the machine
+    pub active_sessions: i64,
+    /// True for the machine the request was made from
+    #[serde(default)]
+    pub current: bool,
+}
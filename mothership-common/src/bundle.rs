@@ -0,0 +1,187 @@
+//! Portable project bundles for moving projects between servers or keeping
+//! offline backups.
+//!
+//! A bundle is gzip-compressed JSON holding the project metadata, the rift
+//! graph, every checkpoint and the content blobs they reference. IDs inside a
+//! bundle are the exporting server's; the importer assigns fresh ones.
+
+use crate::upload::content_hash;
+use crate::{Checkpoint, CheckpointId, ProjectSettings, RiftId, UserId};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+/// Bundle format written by this build
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// File extension used for bundles
+pub const BUNDLE_EXTENSION: &str = "mothership";
+
+/// A whole project, ready to be written to disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectBundle {
+    pub format_version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub project: BundleProject,
+    /// Rifts, parents before children
+    pub rifts: Vec<BundleRift>,
+    pub checkpoints: Vec<Checkpoint>,
+    /// Usernames of checkpoint authors, so the importer can match local users
+    pub authors: HashMap<UserId, String>,
+    /// Content blobs by content hash
+    pub blobs: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleProject {
+    pub name: String,
+    pub description: String,
+    pub created_at: DateTime<Utc>,
+    pub settings: ProjectSettings,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleRift {
+    pub id: RiftId,
+    pub name: String,
+    pub parent_rift: Option<RiftId>,
+    pub created_at: DateTime<Utc>,
+    /// Working state at export time: path -> content hash
+    pub live_files: HashMap<PathBuf, String>,
+    /// The checkpoint merges back into the parent start from (absent in
+    /// bundles exported before fork points were recorded)
+    #[serde(default)]
+    pub fork_point: Option<CheckpointId>,
+}
+
+impl ProjectBundle {
+    /// Serialize and compress the bundle
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        serde_json::to_writer(&mut encoder, self)?;
+        encoder.flush()?;
+        Ok(encoder.finish()?)
+    }
+
+    /// Decompress and parse a bundle, then check it is complete and consistent
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let mut json = Vec::new();
+        flate2::read::GzDecoder::new(bytes)
+            .read_to_end(&mut json)
+            .map_err(|_| anyhow!("Not a Mothership project bundle"))?;
+        let bundle: ProjectBundle = serde_json::from_slice(&json)?;
+        bundle.validate()?;
+        Ok(bundle)
+    }
+
+    /// Every reference resolves and every blob matches its hash
+    pub fn validate(&self) -> Result<()> {
+        if self.format_version > BUNDLE_FORMAT_VERSION {
+            return Err(anyhow!(
+                "Bundle format {} is newer than this server supports ({})",
+                self.format_version,
+                BUNDLE_FORMAT_VERSION
+            ));
+        }
+
+        for (hash, content) in &self.blobs {
+            if &content_hash(content.as_bytes()) != hash {
+                return Err(anyhow!("Blob {} is corrupt", hash));
+            }
+        }
+
+        let mut seen: Vec<RiftId> = Vec::new();
+        for rift in &self.rifts {
+            if let Some(parent) = rift.parent_rift {
+                if !seen.contains(&parent) {
+                    return Err(anyhow!("Rift '{}' appears before its parent", rift.name));
+                }
+            }
+            if let Some((path, hash)) = rift.live_files.iter().find(|(_, hash)| !self.blobs.contains_key(*hash)) {
+                return Err(anyhow!("Rift '{}' is missing content for {} ({})", rift.name, path.display(), hash));
+            }
+            seen.push(rift.id);
+        }
+
+        for checkpoint in &self.checkpoints {
+            if !seen.contains(&checkpoint.rift_id) {
+                return Err(anyhow!("Checkpoint {} belongs to an unknown rift", checkpoint.id));
+            }
+            if let Some(change) = checkpoint.changes.iter().find(|c| !self.blobs.contains_key(&c.content_hash)) {
+                return Err(anyhow!(
+                    "Checkpoint {} is missing content for {}",
+                    checkpoint.id,
+                    change.path.display()
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChangeType, FileChange};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_round_trip_and_validation() {
+        let content = "fn main() {}\n".to_string();
+        let hash = content_hash(content.as_bytes());
+        let main = BundleRift {
+            id: Uuid::new_v4(),
+            name: "main".to_string(),
+            parent_rift: None,
+            created_at: Utc::now(),
+            live_files: HashMap::from([(PathBuf::from("src/main.rs"), hash.clone())]),
+            fork_point: None,
+        };
+        let checkpoint = Checkpoint {
+            id: Uuid::new_v4(),
+            rift_id: main.id,
+            author: Uuid::new_v4(),
+            timestamp: Utc::now(),
+            changes: vec![FileChange {
+                path: PathBuf::from("src/main.rs"),
+                change_type: ChangeType::Modified,
+                content_hash: hash.clone(),
+                diff: None,
+                size: content.len() as u64,
+                metadata: Default::default(),
+            }],
+            parent: None,
+            message: Some("initial".to_string()),
+            auto_generated: false,
+            merged_from: None,
+            squashed: 0,
+        };
+        let bundle = ProjectBundle {
+            format_version: BUNDLE_FORMAT_VERSION,
+            exported_at: Utc::now(),
+            project: BundleProject {
+                name: "demo".to_string(),
+                description: String::new(),
+                created_at: Utc::now(),
+                settings: ProjectSettings::default(),
+            },
+            rifts: vec![main],
+            checkpoints: vec![checkpoint],
+            authors: HashMap::new(),
+            blobs: HashMap::from([(hash.clone(), content)]),
+        };
+
+        let decoded = ProjectBundle::decode(&bundle.encode().unwrap()).unwrap();
+        assert_eq!(decoded.rifts[0].live_files, bundle.rifts[0].live_files);
+        assert_eq!(decoded.checkpoints[0].id, bundle.checkpoints[0].id);
+
+        let mut corrupt = bundle.clone();
+        corrupt.blobs.insert(hash, "tampered".to_string());
+        assert!(ProjectBundle::decode(&corrupt.encode().unwrap()).is_err());
+        assert!(ProjectBundle::decode(b"not a bundle").is_err());
+    }
+}
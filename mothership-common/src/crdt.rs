@@ -11,7 +11,7 @@ pub struct RiftCRDT {
     tombstones: HashMap<OperationId, bool>,
 }
 
-#[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct OperationId {
     timestamp: u64,
     site_id: Uuid,
@@ -51,7 +51,7 @@ impl RiftCRDT {
             position,
             content,
             dependencies: self.operations.iter()
-                .map(|op| op.id.clone())
+                .map(|op| op.id)
                 .collect(),
         };
         self.operations.push(op.clone());
@@ -75,7 +75,7 @@ impl RiftCRDT {
 
         // Merge tombstones
         for (op_id, deleted) in &other.tombstones {
-            self.tombstones.insert(op_id.clone(), *deleted);
+            self.tombstones.insert(*op_id, *deleted);
         }
 
         // Sort operations by position and timestamp
@@ -120,4 +120,240 @@ impl LogicalPosition {
 
         LogicalPosition { path, site_id }
     }
-} 
\ No newline at end of file
+}
+
+/// Character-level replicated text (an RGA sequence). Every character has a
+/// unique id and is placed after a reference character, so concurrent edits
+/// from any number of sites converge to the same text in every replica.
+/// Deleted characters are kept as tombstones so later operations can still
+/// reference them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextCRDT {
+    site_id: Uuid,
+    lamport_clock: u64,
+    elements: Vec<TextElement>,
+    /// Operations that reference characters this replica has not seen yet
+    #[serde(skip)]
+    pending: Vec<TextOperation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TextElement {
+    id: OperationId,
+    value: char,
+    deleted: bool,
+}
+
+/// An edit to a [`TextCRDT`], exchanged between replicas
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum TextOperation {
+    /// Insert `text` after the character `after` (None = start of the text).
+    /// Character `i` of `text` gets id `(id.timestamp + i, id.site_id)`.
+    Insert {
+        id: OperationId,
+        after: Option<OperationId>,
+        text: String,
+    },
+    /// Delete the characters with these ids
+    Delete {
+        ids: Vec<OperationId>,
+    },
+}
+
+impl TextCRDT {
+    /// Start a document from existing content
+    pub fn from_text(site_id: Uuid, text: &str) -> Self {
+        let mut doc = Self {
+            site_id,
+            lamport_clock: 0,
+            elements: Vec::new(),
+            pending: Vec::new(),
+        };
+        doc.insert_local(0, text);
+        doc
+    }
+
+    /// Copy of this document for another site (used to hand a snapshot to a new replica)
+    pub fn fork(&self, site_id: Uuid) -> Self {
+        Self {
+            site_id,
+            lamport_clock: self.lamport_clock,
+            elements: self.elements.clone(),
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn site_id(&self) -> Uuid {
+        self.site_id
+    }
+
+    /// Current visible text
+    pub fn text(&self) -> String {
+        self.elements.iter()
+            .filter(|e| !e.deleted)
+            .map(|e| e.value)
+            .collect()
+    }
+
+    /// Insert text at a character offset of the visible text
+    pub fn insert_local(&mut self, pos: usize, text: &str) -> Option<TextOperation> {
+        let len = text.chars().count() as u64;
+        if len == 0 {
+            return None;
+        }
+
+        let after = match pos {
+            0 => None,
+            _ => self.visible_ids().nth(pos - 1),
+        };
+        let op = TextOperation::Insert {
+            id: OperationId { timestamp: self.lamport_clock + 1, site_id: self.site_id },
+            after,
+            text: text.to_string(),
+        };
+        self.apply(&op);
+        Some(op)
+    }
+
+    /// Delete `len` characters starting at a character offset of the visible text
+    pub fn delete_local(&mut self, pos: usize, len: usize) -> Option<TextOperation> {
+        let ids: Vec<OperationId> = self.visible_ids().skip(pos).take(len).collect();
+        if ids.is_empty() {
+            return None;
+        }
+
+        let op = TextOperation::Delete { ids };
+        self.apply(&op);
+        Some(op)
+    }
+
+    /// Replace the whole visible text, returning the operations that describe the
+    /// change. Used for edits that arrive as full content (e.g. a saved file).
+    pub fn update_text(&mut self, new_text: &str) -> Vec<TextOperation> {
+        let old: Vec<char> = self.text().chars().collect();
+        let new: Vec<char> = new_text.chars().collect();
+
+        let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+        let suffix = old[prefix..].iter().rev()
+            .zip(new[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        let mut ops = Vec::new();
+        ops.extend(self.delete_local(prefix, old.len() - prefix - suffix));
+        let inserted: String = new[prefix..new.len() - suffix].iter().collect();
+        ops.extend(self.insert_local(prefix, &inserted));
+        ops
+    }
+
+    /// Apply operations from another replica. Operations may arrive more than once
+    /// or before the characters they reference; those are retried later.
+    pub fn apply_remote(&mut self, ops: &[TextOperation]) {
+        self.pending.extend(ops.iter().cloned());
+
+        // Keep retrying while operations make progress
+        loop {
+            let pending = std::mem::take(&mut self.pending);
+            let before = pending.len();
+            for op in pending {
+                if let Some(rest) = self.try_apply(&op) {
+                    self.pending.push(rest);
+                }
+            }
+            if self.pending.is_empty() || self.pending.len() == before {
+                break;
+            }
+        }
+    }
+
+    fn apply(&mut self, op: &TextOperation) {
+        if let Some(rest) = self.try_apply(op) {
+            self.pending.push(rest);
+        }
+    }
+
+    /// Apply an operation, returning whatever part of it cannot be applied yet
+    fn try_apply(&mut self, op: &TextOperation) -> Option<TextOperation> {
+        match op {
+            TextOperation::Insert { id, after, text } => {
+                if after.is_some_and(|after| self.index_of(&after).is_none()) {
+                    return Some(op.clone());
+                }
+
+                let mut previous = *after;
+                for (i, value) in text.chars().enumerate() {
+                    let char_id = OperationId { timestamp: id.timestamp + i as u64, site_id: id.site_id };
+                    if self.index_of(&char_id).is_none() {
+                        self.integrate(char_id, previous, value);
+                    }
+                    previous = Some(char_id);
+                }
+
+                let last = id.timestamp + text.chars().count().saturating_sub(1) as u64;
+                self.lamport_clock = self.lamport_clock.max(last);
+                None
+            }
+            TextOperation::Delete { ids } => {
+                let mut missing = Vec::new();
+                for id in ids {
+                    match self.index_of(id) {
+                        Some(index) => self.elements[index].deleted = true,
+                        None => missing.push(*id),
+                    }
+                }
+                (!missing.is_empty()).then_some(TextOperation::Delete { ids: missing })
+            }
+        }
+    }
+
+    /// Place a character after its reference. Concurrent inserts at the same spot
+    /// are ordered by id (newest first), which every replica agrees on.
+    fn integrate(&mut self, id: OperationId, after: Option<OperationId>, value: char) {
+        let mut index = match after {
+            Some(after) => self.index_of(&after).map_or(0, |i| i + 1),
+            None => 0,
+        };
+        while index < self.elements.len() && self.elements[index].id > id {
+            index += 1;
+        }
+        self.elements.insert(index, TextElement { id, value, deleted: false });
+    }
+
+    fn index_of(&self, id: &OperationId) -> Option<usize> {
+        self.elements.iter().position(|e| e.id == *id)
+    }
+
+    fn visible_ids(&self) -> impl Iterator<Item = OperationId> + '_ {
+        self.elements.iter().filter(|e| !e.deleted).map(|e| e.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_concurrent_text_edits_converge() {
+        let base = TextCRDT::from_text(Uuid::nil(), "hello world");
+        let mut alice = base.fork(Uuid::new_v4());
+        let mut bob = base.fork(Uuid::new_v4());
+
+        // Both type at the same position and edit overlapping ranges
+        let mut alice_ops = Vec::new();
+        alice_ops.extend(alice.insert_local(5, ","));
+        alice_ops.extend(alice.update_text("hello, there world"));
+        let mut bob_ops = Vec::new();
+        bob_ops.extend(bob.insert_local(5, "!"));
+        bob_ops.extend(bob.delete_local(6, 5));
+
+        // Deliver in different orders (and twice) to each replica
+        alice.apply_remote(&bob_ops);
+        bob.apply_remote(&alice_ops);
+        bob.apply_remote(&alice_ops);
+
+        assert_eq!(alice.text(), bob.text());
+        assert!(alice.text().starts_with("hello"));
+        assert!(alice.text().contains("there"));
+        assert!(!alice.text().contains("world"));
+    }
+}
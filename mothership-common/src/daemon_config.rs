@@ -0,0 +1,161 @@
+//! Daemon-wide settings, kept in `daemon.json` in the Mothership config
+//! directory. `mothership daemon config` edits them through the daemon's
+//! `GET`/`POST /config` IPC endpoint, which applies them without a restart.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+const CONFIG_FILE_NAME: &str = "daemon.json";
+
+/// Highest `max_hash_jobs` accepted
+const MAX_HASH_JOBS: usize = 64;
+
+/// Read rate while on battery when no limit is set (8MB/s)
+const BATTERY_IO_BYTES_PER_SEC: u64 = 8 * 1024 * 1024;
+
+/// On battery, a configured read rate is divided by this
+const BATTERY_IO_DIVISOR: u64 = 4;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DaemonConfig {
+    pub throttle: ThrottleSettings,
+    pub discovery: DiscoverySettings,
+}
+
+/// Limits on the CPU and disk time the daemon spends scanning and hashing
+/// project files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThrottleSettings {
+    /// Projects scanned and hashed at once
+    pub max_hash_jobs: usize,
+    /// Bytes per second read from project files; 0 means unlimited
+    pub io_bytes_per_sec: u64,
+    /// While on battery, scan one project at a time and read more slowly
+    pub low_priority_on_battery: bool,
+}
+
+impl Default for ThrottleSettings {
+    fn default() -> Self {
+        Self {
+            max_hash_jobs: 2,
+            io_bytes_per_sec: 0,
+            low_priority_on_battery: true,
+        }
+    }
+}
+
+/// Where the daemon looks for projects it hasn't been told about (see
+/// `discovery`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiscoverySettings {
+    /// Directories searched for projects; none turns discovery off
+    pub scan_roots: Vec<PathBuf>,
+    /// How many directories below a scan root to look
+    pub max_depth: usize,
+}
+
+impl Default for DiscoverySettings {
+    fn default() -> Self {
+        Self {
+            scan_roots: Vec::new(),
+            max_depth: 4,
+        }
+    }
+}
+
+impl ThrottleSettings {
+    pub fn validate(&self) -> Result<()> {
+        if self.max_hash_jobs == 0 || self.max_hash_jobs > MAX_HASH_JOBS {
+            return Err(anyhow!("max_hash_jobs must be between 1 and {}", MAX_HASH_JOBS));
+        }
+        Ok(())
+    }
+
+    /// Whether low-priority mode is on, given the power source
+    pub fn is_low_priority(&self, on_battery: bool) -> bool {
+        self.low_priority_on_battery && on_battery
+    }
+
+    /// Concurrent hash jobs allowed, given the power source
+    pub fn hash_jobs(&self, on_battery: bool) -> usize {
+        if self.is_low_priority(on_battery) {
+            1
+        } else {
+            self.max_hash_jobs
+        }
+    }
+
+    /// Read rate allowed in bytes per second, given the power source; 0 means unlimited
+    pub fn io_rate(&self, on_battery: bool) -> u64 {
+        match (self.is_low_priority(on_battery), self.io_bytes_per_sec) {
+            (false, rate) => rate,
+            (true, 0) => BATTERY_IO_BYTES_PER_SEC,
+            (true, rate) => (rate / BATTERY_IO_DIVISOR).max(1),
+        }
+    }
+}
+
+impl DaemonConfig {
+    /// Path of `daemon.json`
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("mothership").join(CONFIG_FILE_NAME))
+    }
+
+    /// Load the config, falling back to the defaults if it is missing or invalid
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str::<Self>(&json).ok())
+            .filter(|config| config.validate().is_ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        self.validate()?;
+        let path = Self::path().ok_or_else(|| anyhow!("Could not determine the config directory"))?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        self.throttle.validate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_battery_lowers_limits_only_when_enabled() {
+        let throttle = ThrottleSettings { max_hash_jobs: 4, io_bytes_per_sec: 40_000_000, low_priority_on_battery: true };
+        assert_eq!(throttle.hash_jobs(false), 4);
+        assert_eq!(throttle.io_rate(false), 40_000_000);
+        assert_eq!(throttle.hash_jobs(true), 1);
+        assert_eq!(throttle.io_rate(true), 10_000_000);
+
+        let unlimited = ThrottleSettings { io_bytes_per_sec: 0, ..throttle };
+        assert_eq!(unlimited.io_rate(false), 0);
+        assert_eq!(unlimited.io_rate(true), BATTERY_IO_BYTES_PER_SEC);
+
+        let always_full = ThrottleSettings { low_priority_on_battery: false, ..throttle };
+        assert_eq!(always_full.hash_jobs(true), 4);
+        assert_eq!(always_full.io_rate(true), 40_000_000);
+    }
+
+    #[test]
+    fn test_partial_config_keeps_defaults() {
+        let config: DaemonConfig = serde_json::from_str(r#"{"throttle": {"max_hash_jobs": 1}}"#).unwrap();
+        assert_eq!(config.throttle.max_hash_jobs, 1);
+        assert!(config.throttle.low_priority_on_battery);
+        assert!(ThrottleSettings { max_hash_jobs: 0, ..Default::default() }.validate().is_err());
+    }
+}
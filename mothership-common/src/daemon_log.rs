@@ -0,0 +1,104 @@
+//! The daemon's log files. It writes one file a day to the `logs` directory
+//! under the Mothership config directory and keeps the last
+//! [`MAX_LOG_FILES`]; `mothership daemon logs` reads them back.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use std::fs;
+use std::path::PathBuf;
+
+/// Log files are named `daemon.<date>.log`
+pub const LOG_FILE_PREFIX: &str = "daemon";
+pub const LOG_FILE_SUFFIX: &str = "log";
+
+/// Days of logs kept
+pub const MAX_LOG_FILES: usize = 7;
+
+/// Directory holding the daemon's log files
+pub fn log_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("mothership").join("logs"))
+}
+
+/// The daemon's log files, oldest first
+pub fn log_files() -> Vec<PathBuf> {
+    let Some(entries) = log_dir().and_then(|dir| fs::read_dir(dir).ok()) else {
+        return Vec::new();
+    };
+    let prefix = format!("{}.", LOG_FILE_PREFIX);
+    let suffix = format!(".{}", LOG_FILE_SUFFIX);
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(&prefix) && name.ends_with(&suffix))
+        })
+        .collect();
+    // Dates in the names sort chronologically
+    files.sort();
+    files
+}
+
+/// Parse a duration such as `90s`, `30m`, `1h` or `2d`
+pub fn parse_duration(text: &str) -> Result<Duration> {
+    let text = text.trim();
+    let split = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+    let (amount, unit) = text.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| anyhow!("Invalid duration '{}': expected e.g. 30m, 1h or 2d", text))?;
+    match unit {
+        "s" => Ok(Duration::seconds(amount)),
+        "m" => Ok(Duration::minutes(amount)),
+        "h" => Ok(Duration::hours(amount)),
+        "d" => Ok(Duration::days(amount)),
+        _ => Err(anyhow!("Invalid duration '{}': use s, m, h or d", text)),
+    }
+}
+
+/// When a log line was written; `None` for lines continuing a multi-line entry
+pub fn line_timestamp(line: &str) -> Option<DateTime<Utc>> {
+    let stamp = line.split_whitespace().next()?;
+    DateTime::parse_from_rfc3339(stamp).ok().map(|at| at.with_timezone(&Utc))
+}
+
+/// The lines of entries logged at or after `since`. Continuation lines go with
+/// the entry they belong to.
+pub fn lines_since(content: &str, since: DateTime<Utc>) -> Vec<&str> {
+    let mut keep = false;
+    content
+        .lines()
+        .filter(|line| {
+            if let Some(at) = line_timestamp(line) {
+                keep = at >= since;
+            }
+            keep
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90s").unwrap(), Duration::seconds(90));
+        assert_eq!(parse_duration("30m").unwrap(), Duration::minutes(30));
+        assert_eq!(parse_duration("1h").unwrap(), Duration::hours(1));
+        assert_eq!(parse_duration("2d").unwrap(), Duration::days(2));
+        assert!(parse_duration("1w").is_err());
+        assert!(parse_duration("h").is_err());
+    }
+
+    #[test]
+    fn test_lines_since_keeps_continuation_lines() {
+        let content = "\
+2026-10-17T09:00:00.000000Z  INFO mothership_daemon: old
+2026-10-17T10:30:00.000000Z  INFO mothership_daemon: Connection Health Report:
+ - Messages Sent: 3
+2026-10-17T11:00:00.000000Z ERROR mothership_daemon: new";
+        let since = DateTime::parse_from_rfc3339("2026-10-17T10:00:00Z").unwrap().with_timezone(&Utc);
+        let lines = lines_since(content, since);
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].starts_with(" - Messages Sent"));
+    }
+}
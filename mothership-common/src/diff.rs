@@ -4,10 +4,16 @@
 //! - Line-based diff generation (like git)
 //! - Efficient diff application 
 //! - Smart diff size optimization
+//! - rsync-style rolling-hash block deltas for large files
 //! - Compression integration
 
-use crate::protocol::{FileDiff, DiffOperation, FileDiffChange};
+use crate::protocol::{BlockOperation, DiffHunk, FileDiff, DiffOperation, FileDiffChange, HunkLine};
+use crate::upload::content_hash;
 use anyhow::Result;
+use std::collections::HashMap;
+
+/// Files at least this large are synced as block deltas instead of full content
+pub const BLOCK_DELTA_MIN_SIZE: usize = 16 * 1024;
 
 /// PERFORMANCE FIX: Diff engine for minimal network usage
 pub struct DiffEngine;
@@ -185,9 +191,388 @@ impl DiffEngine {
             FileDiff::BinaryDiff { .. } => {
                 Err(anyhow::anyhow!("Binary diff application not yet implemented"))
             }
+            FileDiff::BlockDelta { block_size, base_hash, operations, new_size } => {
+                if *block_size == 0 {
+                    return Err(anyhow::anyhow!("Block delta has a block size of zero"));
+                }
+                if content_hash(original.as_bytes()) != *base_hash {
+                    return Err(anyhow::anyhow!("Block delta base does not match current content"));
+                }
+
+                // The sizes come from the sender: nothing is allocated up front, and
+                // building stops as soon as the result outgrows the size it claims
+                let base = original.as_bytes();
+                let block_size = *block_size as usize;
+                let mut result = Vec::new();
+                for op in operations {
+                    match op {
+                        BlockOperation::Copy { block, count } => {
+                            let start = (*block as usize).saturating_mul(block_size);
+                            let end = start.saturating_add((*count as usize).saturating_mul(block_size)).min(base.len());
+                            if start >= end {
+                                return Err(anyhow::anyhow!("Block delta references block {} outside the base", block));
+                            }
+                            result.extend_from_slice(&base[start..end]);
+                        }
+                        BlockOperation::Literal(text) => result.extend_from_slice(text.as_bytes()),
+                    }
+                    if result.len() as u64 > *new_size {
+                        return Err(anyhow::anyhow!("Block delta produces more than the {} bytes it claims", new_size));
+                    }
+                }
+
+                if result.len() as u64 != *new_size {
+                    return Err(anyhow::anyhow!("Block delta produced {} bytes, expected {}", result.len(), new_size));
+                }
+                String::from_utf8(result).map_err(|_| anyhow::anyhow!("Block delta produced invalid UTF-8"))
+            }
             FileDiff::Deleted => Ok(String::new()),
         }
     }
+
+    /// Generate an rsync-style block delta from `base` to `new`.
+    ///
+    /// The base is split into fixed-size blocks indexed by a weak rolling checksum.
+    /// A window slides over the new content one byte at a time; whenever its weak
+    /// checksum and then its strong hash match a base block, the block is referenced
+    /// instead of sent. Falls back to full content when the delta would not be smaller.
+    pub fn generate_block_delta(&self, base: &str, new: &str) -> FileDiff {
+        let base_bytes = base.as_bytes();
+        let new_bytes = new.as_bytes();
+        let block_size = Self::block_size_for(base_bytes.len());
+
+        let mut blocks: HashMap<u32, Vec<(u32, String)>> = HashMap::new();
+        for (index, block) in base_bytes.chunks_exact(block_size).enumerate() {
+            blocks
+                .entry(RollingChecksum::new(block).value())
+                .or_default()
+                .push((index as u32, content_hash(block)));
+        }
+
+        let mut operations: Vec<BlockOperation> = Vec::new();
+        let mut literal_start = 0;
+        let mut pos = 0;
+        let mut checksum = (new_bytes.len() >= block_size)
+            .then(|| RollingChecksum::new(&new_bytes[..block_size]));
+
+        while let Some(current) = checksum.as_mut() {
+            let matched = blocks.get(&current.value()).and_then(|candidates| {
+                let strong = content_hash(&new_bytes[pos..pos + block_size]);
+                candidates.iter().find(|(_, hash)| *hash == strong).map(|(index, _)| *index)
+            });
+
+            if let Some(block) = matched {
+                if literal_start < pos {
+                    match std::str::from_utf8(&new_bytes[literal_start..pos]) {
+                        Ok(text) => operations.push(BlockOperation::Literal(text.to_string())),
+                        Err(_) => return FileDiff::FullContent(new.to_string()),
+                    }
+                }
+                match operations.last_mut() {
+                    Some(BlockOperation::Copy { block: first, count }) if *first + *count == block => *count += 1,
+                    _ => operations.push(BlockOperation::Copy { block, count: 1 }),
+                }
+
+                pos += block_size;
+                literal_start = pos;
+                checksum = (pos + block_size <= new_bytes.len())
+                    .then(|| RollingChecksum::new(&new_bytes[pos..pos + block_size]));
+            } else if pos + block_size < new_bytes.len() {
+                current.roll(new_bytes[pos], new_bytes[pos + block_size]);
+                pos += 1;
+            } else {
+                break;
+            }
+        }
+
+        if literal_start < new_bytes.len() {
+            match std::str::from_utf8(&new_bytes[literal_start..]) {
+                Ok(text) => operations.push(BlockOperation::Literal(text.to_string())),
+                Err(_) => return FileDiff::FullContent(new.to_string()),
+            }
+        }
+
+        // Rough wire size: literal bytes plus a small fixed cost per operation
+        let delta_size: usize = operations.iter().map(|op| match op {
+            BlockOperation::Copy { .. } => 32,
+            BlockOperation::Literal(text) => text.len() + 16,
+        }).sum();
+        if delta_size >= new_bytes.len() {
+            return FileDiff::FullContent(new.to_string());
+        }
+
+        FileDiff::BlockDelta {
+            block_size: block_size as u32,
+            base_hash: content_hash(base_bytes),
+            operations,
+            new_size: new_bytes.len() as u64,
+        }
+    }
+
+    /// Block size grows with the square root of the file size, like rsync
+    fn block_size_for(len: usize) -> usize {
+        ((len as f64).sqrt() as usize).clamp(512, 64 * 1024)
+    }
+
+    /// Three-way merge of `ours` and `theirs` against their common `base`.
+    ///
+    /// Regions changed on only one side are taken from that side; regions changed
+    /// identically on both sides are taken once. Regions changed differently on both
+    /// sides are emitted with git-style conflict markers and counted as conflicts.
+    pub fn three_way_merge(&self, base: &str, ours: &str, theirs: &str) -> MergeResult {
+        let base_lines: Vec<&str> = base.lines().collect();
+        let our_lines: Vec<&str> = ours.lines().collect();
+        let their_lines: Vec<&str> = theirs.lines().collect();
+
+        let ours_map = Self::lcs_matches(&base_lines, &our_lines);
+        let theirs_map = Self::lcs_matches(&base_lines, &their_lines);
+
+        let mut merged: Vec<&str> = Vec::new();
+        let mut conflicts = 0;
+        let (mut b, mut o, mut t) = (0, 0, 0);
+
+        loop {
+            // Copy lines that are unchanged on both sides
+            while b < base_lines.len() && ours_map[b] == Some(o) && theirs_map[b] == Some(t) {
+                merged.push(base_lines[b]);
+                b += 1;
+                o += 1;
+                t += 1;
+            }
+
+            if b >= base_lines.len() && o >= our_lines.len() && t >= their_lines.len() {
+                break;
+            }
+
+            // Find the next base line that both sides still share
+            let sync = (b..base_lines.len())
+                .find(|&k| ours_map[k].is_some() && theirs_map[k].is_some());
+            let (b_end, o_end, t_end) = match sync {
+                Some(k) => (k, ours_map[k].unwrap(), theirs_map[k].unwrap()),
+                None => (base_lines.len(), our_lines.len(), their_lines.len()),
+            };
+
+            let base_chunk = &base_lines[b..b_end];
+            let our_chunk = &our_lines[o..o_end];
+            let their_chunk = &their_lines[t..t_end];
+
+            if our_chunk == base_chunk || our_chunk == their_chunk {
+                merged.extend_from_slice(their_chunk);
+            } else if their_chunk == base_chunk {
+                merged.extend_from_slice(our_chunk);
+            } else {
+                conflicts += 1;
+                merged.push("<<<<<<< ours");
+                merged.extend_from_slice(our_chunk);
+                merged.push("=======");
+                merged.extend_from_slice(their_chunk);
+                merged.push(">>>>>>> theirs");
+            }
+
+            b = b_end;
+            o = o_end;
+            t = t_end;
+        }
+
+        let mut content = merged.join("\n");
+        if !content.is_empty() && (ours.ends_with('\n') || theirs.ends_with('\n')) {
+            content.push('\n');
+        }
+
+        if conflicts == 0 {
+            MergeResult::Clean(content)
+        } else {
+            MergeResult::Conflicted { content, conflicts }
+        }
+    }
+
+    /// Carry per-line attribution from `original` over to `new` (as in blame): lines
+    /// that survive keep their attribution, added lines are attributed to `origin`.
+    pub fn carry_attribution<T: Clone>(&self, original: &str, attribution: &[T], new: &str, origin: T) -> Vec<T> {
+        let original_lines: Vec<&str> = original.lines().collect();
+        let new_lines: Vec<&str> = new.lines().collect();
+
+        let mut carried: Vec<Option<T>> = vec![None; new_lines.len()];
+        for (i, matched) in Self::lcs_matches(&original_lines, &new_lines).into_iter().enumerate() {
+            if let (Some(j), Some(attr)) = (matched, attribution.get(i)) {
+                carried[j] = Some(attr.clone());
+            }
+        }
+
+        carried.into_iter().map(|attr| attr.unwrap_or_else(|| origin.clone())).collect()
+    }
+
+    /// Hunks turning `original` into `new`, with up to `context` unchanged
+    /// lines around each change. Changes closer together than twice the
+    /// context share a hunk.
+    pub fn hunks(&self, original: &str, new: &str, context: usize) -> Vec<DiffHunk> {
+        let original_lines: Vec<&str> = original.lines().collect();
+        let new_lines: Vec<&str> = new.lines().collect();
+        let matches = Self::lcs_matches(&original_lines, &new_lines);
+
+        // Every line of both sides in order, with the original and new line
+        // index it sits at
+        let mut script = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < original_lines.len() || j < new_lines.len() {
+            if i < original_lines.len() && matches[i] == Some(j) {
+                script.push((i, j, HunkLine::Context(original_lines[i].to_string())));
+                i += 1;
+                j += 1;
+            } else if i < original_lines.len() && matches[i].is_none() {
+                script.push((i, j, HunkLine::Removed(original_lines[i].to_string())));
+                i += 1;
+            } else {
+                script.push((i, j, HunkLine::Added(new_lines[j].to_string())));
+                j += 1;
+            }
+        }
+
+        let changed: Vec<usize> = script.iter()
+            .enumerate()
+            .filter(|(_, (_, _, line))| !matches!(line, HunkLine::Context(_)))
+            .map(|(index, _)| index)
+            .collect();
+
+        let mut hunks = Vec::new();
+        let mut next = 0;
+        while next < changed.len() {
+            let first = changed[next];
+            let mut last = first;
+            next += 1;
+            while next < changed.len() && changed[next] - last <= 2 * context + 1 {
+                last = changed[next];
+                next += 1;
+            }
+
+            let start = first.saturating_sub(context);
+            let end = (last + context + 1).min(script.len());
+            let lines: Vec<HunkLine> = script[start..end].iter().map(|(_, _, line)| line.clone()).collect();
+            let (old_index, new_index, _) = &script[start];
+            hunks.push(DiffHunk {
+                old_start: old_index + 1,
+                old_lines: lines.iter().filter(|line| !matches!(line, HunkLine::Added(_))).count(),
+                new_start: new_index + 1,
+                new_lines: lines.iter().filter(|line| !matches!(line, HunkLine::Removed(_))).count(),
+                lines,
+            });
+        }
+        hunks
+    }
+
+    /// Apply some of the hunks [`hunks`](Self::hunks) found to `original`,
+    /// leaving the rest of it unchanged. Fails if a hunk doesn't match the
+    /// original, e.g. because it was computed against different content.
+    pub fn apply_hunks(&self, original: &str, hunks: &[DiffHunk]) -> Result<String> {
+        let original_lines: Vec<&str> = original.lines().collect();
+        let mut hunks: Vec<&DiffHunk> = hunks.iter().collect();
+        hunks.sort_by_key(|hunk| hunk.old_start);
+
+        let mut result: Vec<&str> = Vec::new();
+        let mut position = 0;
+        for hunk in hunks {
+            let start = hunk.old_start.saturating_sub(1);
+            if start < position || start > original_lines.len() {
+                return Err(anyhow::anyhow!("Hunk at line {} overlaps another or lies outside the file", hunk.old_start));
+            }
+            result.extend_from_slice(&original_lines[position..start]);
+            position = start;
+
+            for line in &hunk.lines {
+                match line {
+                    HunkLine::Context(text) | HunkLine::Removed(text) => {
+                        if original_lines.get(position) != Some(&text.as_str()) {
+                            return Err(anyhow::anyhow!("Hunk at line {} doesn't match the file", hunk.old_start));
+                        }
+                        if matches!(line, HunkLine::Context(_)) {
+                            result.push(text);
+                        }
+                        position += 1;
+                    }
+                    HunkLine::Added(text) => result.push(text),
+                }
+            }
+        }
+        result.extend_from_slice(&original_lines[position..]);
+
+        let mut content = result.join("\n");
+        if !content.is_empty() && (original.ends_with('\n') || original.is_empty()) {
+            content.push('\n');
+        }
+        Ok(content)
+    }
+
+    /// Longest-common-subsequence alignment: for each line of `base`, the index of the
+    /// matching line in `other` (if any).
+    fn lcs_matches(base: &[&str], other: &[&str]) -> Vec<Option<usize>> {
+        let (n, m) = (base.len(), other.len());
+        let mut table = vec![vec![0u32; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                table[i][j] = if base[i] == other[j] {
+                    table[i + 1][j + 1] + 1
+                } else {
+                    table[i + 1][j].max(table[i][j + 1])
+                };
+            }
+        }
+
+        let mut matches = vec![None; n];
+        let (mut i, mut j) = (0, 0);
+        while i < n && j < m {
+            if base[i] == other[j] {
+                matches[i] = Some(j);
+                i += 1;
+                j += 1;
+            } else if table[i + 1][j] >= table[i][j + 1] {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        matches
+    }
+}
+
+/// Outcome of a three-way merge
+#[derive(Debug, Clone, PartialEq)]
+pub enum MergeResult {
+    /// Both sides merged without overlapping edits
+    Clean(String),
+    /// Overlapping edits were found; `content` contains conflict markers
+    Conflicted { content: String, conflicts: usize },
+}
+
+/// Adler-style weak checksum that can slide one byte at a time (as in rsync)
+struct RollingChecksum {
+    a: u32,
+    b: u32,
+    len: u32,
+}
+
+impl RollingChecksum {
+    fn new(window: &[u8]) -> Self {
+        let len = window.len() as u32;
+        let mut a: u32 = 0;
+        let mut b: u32 = 0;
+        for (i, &byte) in window.iter().enumerate() {
+            a = a.wrapping_add(byte as u32);
+            b = b.wrapping_add((len - i as u32).wrapping_mul(byte as u32));
+        }
+        Self { a: a & 0xffff, b: b & 0xffff, len }
+    }
+
+    fn value(&self) -> u32 {
+        self.a | (self.b << 16)
+    }
+
+    /// Drop `outgoing` from the front of the window and append `incoming`
+    fn roll(&mut self, outgoing: u8, incoming: u8) {
+        self.a = self.a.wrapping_sub(outgoing as u32).wrapping_add(incoming as u32) & 0xffff;
+        self.b = self.b
+            .wrapping_sub(self.len.wrapping_mul(outgoing as u32))
+            .wrapping_add(self.a) & 0xffff;
+    }
 }
 
 /// PERFORMANCE FIX: Compression utilities
@@ -268,6 +653,101 @@ mod tests {
         assert_eq!(applied, new);
     }
     
+    #[test]
+    fn test_three_way_merge() {
+        let engine = DiffEngine::new();
+        let base = "a\nb\nc\nd\n";
+
+        let clean = engine.three_way_merge(base, "a\nB\nc\nd\n", "a\nb\nc\nD\n");
+        assert_eq!(clean, MergeResult::Clean("a\nB\nc\nD\n".to_string()));
+
+        let conflicted = engine.three_way_merge(base, "a\nX\nc\nd\n", "a\nY\nc\nd\n");
+        match conflicted {
+            MergeResult::Conflicted { content, conflicts } => {
+                assert_eq!(conflicts, 1);
+                assert!(content.contains("<<<<<<< ours\nX\n=======\nY\n>>>>>>> theirs"));
+            }
+            other => panic!("expected conflict, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_carry_attribution() {
+        let engine = DiffEngine::new();
+        let original = "a\nb\nc\n";
+        let new = "a\nx\nc\nd\n";
+
+        let carried = engine.carry_attribution(original, &[1, 1, 1], new, 2);
+        assert_eq!(carried, vec![1, 2, 1, 2]);
+    }
+
+    #[test]
+    fn test_hunks_and_partial_apply() {
+        let engine = DiffEngine::new();
+        let original: String = (1..=20).map(|i| format!("line {}\n", i)).collect();
+        let new = original.replace("line 2\n", "line two\n").replace("line 18\n", "line 18\nextra\n");
+
+        let hunks = engine.hunks(&original, &new, 3);
+        assert_eq!(hunks.len(), 2);
+        assert_eq!((hunks[0].old_start, hunks[0].old_lines, hunks[0].new_start, hunks[0].new_lines), (1, 5, 1, 5));
+        assert_eq!((hunks[1].old_start, hunks[1].old_lines, hunks[1].new_lines), (16, 5, 6));
+        assert!(hunks[1].lines.contains(&HunkLine::Added("extra".to_string())));
+
+        assert_eq!(engine.apply_hunks(&original, &hunks).unwrap(), new);
+        let first_only = engine.apply_hunks(&original, &hunks[..1]).unwrap();
+        assert_eq!(first_only, original.replace("line 2\n", "line two\n"));
+        assert_eq!(engine.apply_hunks(&original, &[]).unwrap(), original);
+
+        // Hunks only apply to the content they were computed against
+        assert!(engine.apply_hunks(&new, &hunks[..1]).is_err());
+    }
+
+    #[test]
+    fn test_block_delta_round_trip() {
+        let engine = DiffEngine::new();
+        let base: String = (0..4000).map(|i| format!("line number {}\n", i)).collect();
+        let new = base.replacen("line number 2000\n", "an edited line in the middle\n", 1);
+
+        let diff = engine.generate_block_delta(&base, &new);
+        match &diff {
+            FileDiff::BlockDelta { operations, .. } => {
+                let literal_bytes: usize = operations.iter().map(|op| match op {
+                    BlockOperation::Literal(text) => text.len(),
+                    BlockOperation::Copy { .. } => 0,
+                }).sum();
+                assert!(literal_bytes < new.len() / 10);
+            }
+            other => panic!("expected block delta, got {:?}", other),
+        }
+        assert_eq!(engine.apply_diff(&base, &diff).unwrap(), new);
+
+        // A delta must not be applied to content other than its base
+        assert!(engine.apply_diff(&new, &diff).is_err());
+    }
+
+    #[test]
+    fn test_block_delta_sizes_are_not_trusted() {
+        let engine = DiffEngine::new();
+        let base = "abcd".repeat(1024);
+        let base_hash = content_hash(base.as_bytes());
+        let delta = |block_size, operations, new_size| FileDiff::BlockDelta {
+            block_size,
+            base_hash: base_hash.clone(),
+            operations,
+            new_size,
+        };
+
+        // Claiming a huge file must not allocate it
+        assert!(engine.apply_diff(&base, &delta(512, vec![BlockOperation::Copy { block: 0, count: 1 }], u64::MAX)).is_err());
+        assert!(engine.apply_diff(&base, &delta(0, vec![BlockOperation::Copy { block: 0, count: 1 }], 0)).is_err());
+        // Copies past the base or overflowing the offset are refused
+        let far = vec![BlockOperation::Copy { block: u32::MAX, count: u32::MAX }];
+        assert!(engine.apply_diff(&base, &delta(u32::MAX, far, 512)).is_err());
+        // Repeated copies stop once they outgrow the claimed size
+        let repeated = vec![BlockOperation::Copy { block: 0, count: 8 }; 1000];
+        assert!(engine.apply_diff(&base, &delta(512, repeated, 4096)).is_err());
+    }
+
     #[test]
     fn test_compression() {
         let data = b"This is a test string that should compress well when repeated. ".repeat(100);
@@ -0,0 +1,153 @@
+//! Finding projects the daemon hasn't been told about.
+//!
+//! The daemon searches the `discovery.scan_roots` of `daemon.json` when it
+//! starts and whenever a filesystem is mounted, for directories with a
+//! `.mothership/project.json`. It tracks none of them until the user says so
+//! from the tray or with `mothership daemon discover`. Each answer is kept in
+//! `discovered-projects.json` next to `daemon.json`, so a project is only
+//! asked about once and projects the user chose to track are picked up again
+//! at every start.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::project_root;
+
+const DECISIONS_FILE_NAME: &str = "discovered-projects.json";
+
+/// What the user chose for a discovered project
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiscoveryDecision {
+    Track,
+    Ignore,
+}
+
+/// A project found under a scan root
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiscoveredProject {
+    pub project_id: Uuid,
+    pub project_name: String,
+    pub project_path: PathBuf,
+    /// `None` until the user decides
+    pub decision: Option<DiscoveryDecision>,
+}
+
+/// Body of the daemon's `POST /discovery/decide`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecideDiscoveryRequest {
+    pub project_path: PathBuf,
+    pub decision: DiscoveryDecision,
+}
+
+/// The user's answers, by project path
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiscoveryDecisions {
+    decisions: BTreeMap<PathBuf, DiscoveryDecision>,
+}
+
+impl DiscoveryDecisions {
+    /// Path of `discovered-projects.json`
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("mothership").join(DECISIONS_FILE_NAME))
+    }
+
+    /// Load the answers, starting over if the file is missing or invalid
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path().ok_or_else(|| anyhow!("Could not determine the config directory"))?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn get(&self, project_path: &Path) -> Option<DiscoveryDecision> {
+        self.decisions.get(project_path).copied()
+    }
+
+    pub fn set(&mut self, project_path: PathBuf, decision: DiscoveryDecision) {
+        self.decisions.insert(project_path, decision);
+    }
+}
+
+/// Project roots under `root`, at most `max_depth` directories down. Hidden
+/// directories and symlinks are skipped; projects nested in a found project
+/// are found too.
+pub fn find_projects(root: &Path, max_depth: usize) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let mut pending = vec![(root.to_path_buf(), 0)];
+    while let Some((dir, depth)) = pending.pop() {
+        if project_root::is_project_root(&dir) {
+            found.push(dir.clone());
+        }
+        if depth >= max_depth {
+            continue;
+        }
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let hidden = entry.file_name().to_string_lossy().starts_with('.');
+            if !hidden && entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+                pending.push((entry.path(), depth + 1));
+            }
+        }
+    }
+    found.sort();
+    found
+}
+
+/// Read the project at `project_path` from its `.mothership/project.json`
+pub fn read_project(project_path: &Path) -> Option<DiscoveredProject> {
+    let json = fs::read_to_string(project_path.join(".mothership").join("project.json")).ok()?;
+    let metadata: serde_json::Value = serde_json::from_str(&json).ok()?;
+    Some(DiscoveredProject {
+        project_id: metadata.get("project_id")?.as_str()?.parse().ok()?,
+        project_name: metadata.get("project_name")?.as_str()?.to_string(),
+        project_path: project_path.to_path_buf(),
+        decision: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_project(dir: &Path) {
+        fs::create_dir_all(dir.join(".mothership")).unwrap();
+        let metadata = serde_json::json!({ "project_id": Uuid::new_v4(), "project_name": "app" });
+        fs::write(dir.join(".mothership").join("project.json"), metadata.to_string()).unwrap();
+    }
+
+    #[test]
+    fn test_find_projects_within_depth() {
+        let root = std::env::temp_dir().join(format!("mothership-discovery-{}", Uuid::new_v4()));
+        let app = root.join("app");
+        let nested = app.join("services").join("api");
+        let deep = root.join("a").join("b").join("c").join("deep");
+        let hidden = root.join(".cache").join("copy");
+        for dir in [&app, &nested, &deep, &hidden] {
+            make_project(dir);
+        }
+
+        assert_eq!(find_projects(&root, 3), vec![app.clone(), nested.clone()]);
+        assert_eq!(find_projects(&root, 4), vec![deep, app.clone(), nested]);
+        assert_eq!(read_project(&app).map(|project| project.project_name), Some("app".to_string()));
+        assert!(read_project(&root).is_none());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
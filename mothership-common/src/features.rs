@@ -0,0 +1,105 @@
+//! Features an administrator can turn off, for the whole server or for a
+//! single project. The server enforces them; clients hide what is off
+//! instead of letting users run into errors.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// A feature behind a flag
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Feature {
+    Chat,
+    FileUploads,
+    TerminalSharing,
+    ProjectSearch,
+}
+
+impl Feature {
+    pub const ALL: [Feature; 4] = [Feature::Chat, Feature::FileUploads, Feature::TerminalSharing, Feature::ProjectSearch];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Feature::Chat => "chat",
+            Feature::FileUploads => "file_uploads",
+            Feature::TerminalSharing => "terminal_sharing",
+            Feature::ProjectSearch => "project_search",
+        }
+    }
+
+    /// The feature's name in messages to users
+    pub fn title(&self) -> &'static str {
+        match self {
+            Feature::Chat => "Chat",
+            Feature::FileUploads => "File uploads",
+            Feature::TerminalSharing => "Terminal sharing",
+            Feature::ProjectSearch => "Project search",
+        }
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Feature {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Feature::ALL.into_iter()
+            .find(|feature| feature.as_str() == s)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Feature::ALL.iter().map(Feature::as_str).collect();
+                format!("Unknown feature '{}'; expected one of {}", s, names.join(", "))
+            })
+    }
+}
+
+/// Whether a feature is on, and whether an administrator decided that
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureFlag {
+    pub feature: Feature,
+    pub enabled: bool,
+    /// Set by an administrator (for the server, or for the project when
+    /// listing a project's flags) rather than inherited
+    pub overridden: bool,
+}
+
+/// Turn a feature on or off
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetFeatureRequest {
+    pub enabled: bool,
+}
+
+/// Whether a feature is on: the server configuration decides, unless an
+/// administrator overrode it server-wide. A project can turn off a feature
+/// the server offers, but not turn on one the server has off.
+pub fn resolve(configured: bool, server: Option<bool>, project: Option<bool>) -> bool {
+    server.unwrap_or(configured) && project.unwrap_or(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_round_trip() {
+        for feature in Feature::ALL {
+            assert_eq!(feature.as_str().parse::<Feature>(), Ok(feature));
+            assert_eq!(serde_json::to_string(&feature).unwrap(), format!("\"{}\"", feature));
+        }
+        assert!("voice".parse::<Feature>().is_err());
+    }
+
+    #[test]
+    fn projects_can_only_narrow_the_server() {
+        assert!(resolve(true, None, None));
+        assert!(!resolve(true, Some(false), None));
+        assert!(resolve(false, Some(true), None));
+        assert!(!resolve(true, None, Some(false)));
+        assert!(!resolve(true, Some(false), Some(true)));
+    }
+}
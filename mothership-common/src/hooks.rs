@@ -0,0 +1,223 @@
+//! Client-side hooks: user scripts in a project's `.mothership/hooks`
+//! directory run around CLI and daemon operations.
+//!
+//! A hook is a file named after the event (`pre-checkpoint`, `post-sync`,
+//! `pre-restore`), optionally with an extension: `.ps1` runs with PowerShell,
+//! `.bat`/`.cmd` with `cmd`, anything else is executed directly. Hooks run in
+//! the project directory with `MOTHERSHIP_*` environment variables describing
+//! the operation. A `pre-*` hook that exits non-zero or times out vetoes the
+//! operation; `post-*` hooks only report. Timeouts are read from
+//! `.mothership/hooks/config.json`.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Directory under `.mothership` holding hook scripts
+pub const HOOKS_DIR: &str = "hooks";
+
+/// File in the hooks directory holding timeouts
+pub const HOOKS_CONFIG_FILE: &str = "config.json";
+
+/// Timeout used when the config doesn't set one
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// Operations hooks can run around
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hook {
+    /// Before a manual checkpoint; may veto it
+    PreCheckpoint,
+    /// After the daemon writes changes from the server to disk
+    PostSync,
+    /// Before files are restored from a checkpoint; may veto it
+    PreRestore,
+}
+
+impl Hook {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Hook::PreCheckpoint => "pre-checkpoint",
+            Hook::PostSync => "post-sync",
+            Hook::PreRestore => "pre-restore",
+        }
+    }
+
+    /// Whether a failing run stops the operation
+    pub fn can_veto(&self) -> bool {
+        !matches!(self, Hook::PostSync)
+    }
+}
+
+/// Timeouts from `.mothership/hooks/config.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HooksConfig {
+    /// Seconds any hook may run before it is killed
+    pub timeout_secs: u64,
+    /// Per-hook overrides, keyed by hook name
+    pub timeouts: HashMap<String, u64>,
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+            timeouts: HashMap::new(),
+        }
+    }
+}
+
+impl HooksConfig {
+    /// Load the project's hook config, falling back to the defaults
+    pub fn load(project_dir: &Path) -> Self {
+        fs::read_to_string(hooks_dir(project_dir).join(HOOKS_CONFIG_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn timeout(&self, hook: Hook) -> Duration {
+        Duration::from_secs(self.timeouts.get(hook.as_str()).copied().unwrap_or(self.timeout_secs))
+    }
+}
+
+/// How a hook run ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HookOutcome {
+    /// The project has no script for the hook
+    NotInstalled,
+    Passed,
+    /// Exited non-zero (`None` if killed by a signal)
+    Failed { code: Option<i32> },
+    TimedOut { after: Duration },
+}
+
+impl HookOutcome {
+    /// Whether the operation may go ahead
+    pub fn allows(&self) -> bool {
+        matches!(self, HookOutcome::NotInstalled | HookOutcome::Passed)
+    }
+
+    /// Error for a vetoed operation, e.g. "pre-checkpoint hook failed with exit code 1"
+    pub fn veto_error(&self, hook: Hook) -> anyhow::Error {
+        match self {
+            HookOutcome::Failed { code: Some(code) } => anyhow!("{} hook failed with exit code {}", hook.as_str(), code),
+            HookOutcome::Failed { code: None } => anyhow!("{} hook was terminated", hook.as_str()),
+            HookOutcome::TimedOut { after } => anyhow!("{} hook timed out after {}s", hook.as_str(), after.as_secs()),
+            _ => anyhow!("{} hook did not allow the operation", hook.as_str()),
+        }
+    }
+}
+
+fn hooks_dir(project_dir: &Path) -> PathBuf {
+    project_dir.join(".mothership").join(HOOKS_DIR)
+}
+
+/// The script installed for a hook, if any
+pub fn find_hook(project_dir: &Path, hook: Hook) -> Option<PathBuf> {
+    let dir = hooks_dir(project_dir);
+    let exact = dir.join(hook.as_str());
+    if exact.is_file() {
+        return Some(exact);
+    }
+
+    ["sh", "ps1", "bat", "cmd"]
+        .iter()
+        .map(|ext| dir.join(format!("{}.{}", hook.as_str(), ext)))
+        .find(|path| path.is_file())
+}
+
+fn command_for(script: &Path) -> Command {
+    match script.extension().and_then(|e| e.to_str()) {
+        Some("ps1") => {
+            let mut command = Command::new("powershell");
+            command.args(["-NoProfile", "-ExecutionPolicy", "Bypass", "-File"]).arg(script);
+            command
+        }
+        Some("bat") | Some("cmd") => {
+            let mut command = Command::new("cmd");
+            command.arg("/C").arg(script);
+            command
+        }
+        _ => Command::new(script),
+    }
+}
+
+/// Run a hook in the project directory with `MOTHERSHIP_HOOK`,
+/// `MOTHERSHIP_PROJECT_DIR` and the given variables set. Output goes to the
+/// caller's stdout and stderr. Blocks until the hook exits or times out.
+pub fn run_hook(project_dir: &Path, hook: Hook, env: &[(&str, String)]) -> Result<HookOutcome> {
+    let Some(script) = find_hook(project_dir, hook) else {
+        return Ok(HookOutcome::NotInstalled);
+    };
+    let timeout = HooksConfig::load(project_dir).timeout(hook);
+
+    let mut command = command_for(&script);
+    command
+        .current_dir(project_dir)
+        .env("MOTHERSHIP_HOOK", hook.as_str())
+        .env("MOTHERSHIP_PROJECT_DIR", project_dir)
+        .stdin(Stdio::null());
+    for (name, value) in env {
+        command.env(name, value);
+    }
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| anyhow!("Failed to run {} hook {}: {}", hook.as_str(), script.display(), e))?;
+
+    let started = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(if status.success() {
+                HookOutcome::Passed
+            } else {
+                HookOutcome::Failed { code: status.code() }
+            });
+        }
+        if started.elapsed() >= timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(HookOutcome::TimedOut { after: timeout });
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn install(project_dir: &Path, hook: Hook, script: &str) {
+        let dir = hooks_dir(project_dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(hook.as_str());
+        fs::write(&path, script).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[test]
+    fn hooks_pass_veto_and_time_out() {
+        let project_dir = std::env::temp_dir().join(format!("mothership-hooks-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&project_dir).unwrap();
+
+        assert_eq!(run_hook(&project_dir, Hook::PreCheckpoint, &[]).unwrap(), HookOutcome::NotInstalled);
+
+        install(&project_dir, Hook::PreCheckpoint, "#!/bin/sh\n[ \"$MOTHERSHIP_CHECKPOINT_MESSAGE\" = ok ]\n");
+        let ok = [("MOTHERSHIP_CHECKPOINT_MESSAGE", "ok".to_string())];
+        let bad = [("MOTHERSHIP_CHECKPOINT_MESSAGE", "wip".to_string())];
+        assert_eq!(run_hook(&project_dir, Hook::PreCheckpoint, &ok).unwrap(), HookOutcome::Passed);
+        assert_eq!(run_hook(&project_dir, Hook::PreCheckpoint, &bad).unwrap(), HookOutcome::Failed { code: Some(1) });
+
+        install(&project_dir, Hook::PreRestore, "#!/bin/sh\nsleep 5\n");
+        fs::write(hooks_dir(&project_dir).join(HOOKS_CONFIG_FILE), r#"{"timeouts": {"pre-restore": 0}}"#).unwrap();
+        assert!(matches!(run_hook(&project_dir, Hook::PreRestore, &[]).unwrap(), HookOutcome::TimedOut { .. }));
+
+        fs::remove_dir_all(&project_dir).unwrap();
+    }
+}
@@ -0,0 +1,386 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Name of the per-directory ignore file (gitignore syntax)
+pub const IGNORE_FILE_NAME: &str = ".mothershipignore";
+
+/// Patterns every project ignores unless re-included with `!pattern`
+pub const DEFAULT_IGNORE_PATTERNS: &[&str] = &[
+    ".mothership/",
+    ".git/", ".svn/", ".hg/",
+    "target/", "node_modules/", "dist/", "build/",
+    ".vscode/", ".idea/", "__pycache__/", ".cache/",
+    ".env", ".env.*",
+    "*.tmp", "*.temp", "*.log", "*~",
+    ".DS_Store", "Thumbs.db", "desktop.ini",
+];
+
+/// A single parsed line from an ignore file
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+    /// Directory (relative to the project root) the rule was declared in
+    base: PathBuf,
+    pattern: String,
+    negated: bool,
+    dir_only: bool,
+    /// Anchored patterns match the full path relative to `base`,
+    /// unanchored ones match the file name at any depth
+    anchored: bool,
+}
+
+impl IgnoreRule {
+    fn parse(base: &Path, line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+
+        let (anchored, pattern) = match line.strip_prefix('/') {
+            Some(rest) => (true, rest),
+            None => (line.contains('/'), line),
+        };
+
+        if pattern.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            base: base.to_path_buf(),
+            pattern: pattern.to_string(),
+            negated,
+            dir_only,
+            anchored,
+        })
+    }
+
+    fn matches(&self, relative: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+
+        let relative = if self.base.as_os_str().is_empty() {
+            relative
+        } else {
+            let base = to_slash_path(&self.base);
+            match relative.strip_prefix(base.as_str()).and_then(|rest| rest.strip_prefix('/')) {
+                Some(rest) => rest,
+                None => return false,
+            }
+        };
+
+        if self.anchored {
+            glob_match(self.pattern.as_bytes(), relative.as_bytes())
+        } else {
+            let name = relative.rsplit('/').next().unwrap_or(relative);
+            glob_match(self.pattern.as_bytes(), name.as_bytes())
+        }
+    }
+}
+
+/// Gitignore-style matcher shared by the daemon and CLI.
+///
+/// Rules come from `DEFAULT_IGNORE_PATTERNS`, then the project's root
+/// `.mothershipignore`, then nested ignore files; later rules win. Projects
+/// nested inside the project are ignored as a whole: their files belong to
+/// them (see `project_root`).
+#[derive(Debug, Clone)]
+pub struct IgnoreMatcher {
+    root: PathBuf,
+    rules: Vec<IgnoreRule>,
+    /// Roots of nested projects, relative to the project root with `/` separators
+    nested_projects: Vec<String>,
+}
+
+impl IgnoreMatcher {
+    /// Create a matcher with only the default patterns
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let mut matcher = Self {
+            root: root.into(),
+            rules: Vec::new(),
+            nested_projects: Vec::new(),
+        };
+        for pattern in DEFAULT_IGNORE_PATTERNS {
+            matcher.add_pattern(Path::new(""), pattern);
+        }
+        matcher
+    }
+
+    /// Create a matcher with only the given patterns and none of the defaults
+    pub fn from_patterns<I, S>(root: impl Into<PathBuf>, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut matcher = Self {
+            root: root.into(),
+            rules: Vec::new(),
+            nested_projects: Vec::new(),
+        };
+        for pattern in patterns {
+            matcher.add_pattern(Path::new(""), pattern.as_ref());
+        }
+        matcher
+    }
+
+    /// Create a matcher for a project, reading every `.mothershipignore` under
+    /// it and finding the projects nested in it
+    pub fn load(root: impl Into<PathBuf>) -> Self {
+        let mut matcher = Self::new(root);
+        let root = matcher.root.clone();
+        matcher.load_dir(&root, Path::new(""));
+        matcher
+    }
+
+    /// Add a single pattern declared in `base` (relative to the project root)
+    pub fn add_pattern(&mut self, base: &Path, line: &str) {
+        if let Some(rule) = IgnoreRule::parse(base, line) {
+            self.rules.push(rule);
+        }
+    }
+
+    /// Add every pattern from the contents of an ignore file declared in `base`
+    pub fn add_patterns(&mut self, base: &Path, contents: &str) {
+        for line in contents.lines() {
+            self.add_pattern(base, line);
+        }
+    }
+
+    /// Ignore a project nested in this one, at `relative` to the project root
+    pub fn add_nested_project(&mut self, relative: &Path) {
+        self.nested_projects.push(to_slash_path(relative));
+    }
+
+    /// Project root this matcher was built for
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Check whether a path (absolute, or relative to the project root) is ignored.
+    /// A path is also ignored when any of its parent directories is.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let relative = match path.strip_prefix(&self.root) {
+            Ok(relative) => relative,
+            Err(_) if path.is_relative() => path,
+            Err(_) => return false,
+        };
+
+        let components: Vec<String> = relative
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+                _ => None,
+            })
+            .collect();
+
+        let mut current = String::new();
+        for (i, part) in components.iter().enumerate() {
+            if !current.is_empty() {
+                current.push('/');
+            }
+            current.push_str(part);
+
+            if self.nested_projects.contains(&current) {
+                return true;
+            }
+            let last = i + 1 == components.len();
+            if self.matches(&current, if last { is_dir } else { true }) {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn matches(&self, relative: &str, is_dir: bool) -> bool {
+        let mut ignored = false;
+        for rule in &self.rules {
+            if rule.matches(relative, is_dir) {
+                ignored = !rule.negated;
+            }
+        }
+        ignored
+    }
+
+    /// Recursively read ignore files, skipping directories that are already
+    /// ignored and nested projects
+    fn load_dir(&mut self, dir: &Path, relative: &Path) {
+        if let Ok(contents) = fs::read_to_string(dir.join(IGNORE_FILE_NAME)) {
+            self.add_patterns(relative, &contents);
+        }
+
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+
+        for entry in entries.flatten() {
+            let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            if !is_dir {
+                continue;
+            }
+
+            let child = relative.join(entry.file_name());
+            if self.is_ignored(&child, true) {
+                continue;
+            }
+            if crate::project_root::is_project_root(&entry.path()) {
+                self.add_nested_project(&child);
+                continue;
+            }
+            self.load_dir(&entry.path(), &child);
+        }
+    }
+}
+
+fn to_slash_path(path: &Path) -> String {
+    path.components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Glob matching with `*`, `**`, `?` and `[...]` character classes.
+/// `*` and `?` never match `/`; `**` matches across directories.
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some(b'*') if pattern.get(1) == Some(&b'*') => {
+            let rest = &pattern[2..];
+            // "**/" may also match zero directories
+            if let Some(after_slash) = rest.strip_prefix(b"/") {
+                if glob_match(after_slash, text) {
+                    return true;
+                }
+            }
+            (0..=text.len()).any(|i| glob_match(rest, &text[i..]))
+        }
+        Some(b'*') => {
+            let rest = &pattern[1..];
+            for i in 0..=text.len() {
+                if glob_match(rest, &text[i..]) {
+                    return true;
+                }
+                if i < text.len() && text[i] == b'/' {
+                    break;
+                }
+            }
+            false
+        }
+        Some(b'?') => match text.first() {
+            Some(&c) if c != b'/' => glob_match(&pattern[1..], &text[1..]),
+            _ => false,
+        },
+        Some(b'[') => {
+            let c = match text.first() {
+                Some(&c) if c != b'/' => c,
+                _ => return false,
+            };
+            match match_class(&pattern[1..], c) {
+                Some((true, consumed)) => glob_match(&pattern[1 + consumed..], &text[1..]),
+                Some((false, _)) => false,
+                // Unterminated class: treat '[' literally
+                None => c == b'[' && glob_match(&pattern[1..], &text[1..]),
+            }
+        }
+        Some(b'\\') if pattern.len() > 1 => {
+            text.first() == Some(&pattern[1]) && glob_match(&pattern[2..], &text[1..])
+        }
+        Some(&p) => text.first() == Some(&p) && glob_match(&pattern[1..], &text[1..]),
+    }
+}
+
+/// Match `c` against a character class body (after the opening `[`).
+/// Returns whether it matched and how many pattern bytes the class used.
+fn match_class(class: &[u8], c: u8) -> Option<(bool, usize)> {
+    let (negated, mut i) = match class.first() {
+        Some(b'!') | Some(b'^') => (true, 1),
+        _ => (false, 0),
+    };
+
+    let mut matched = false;
+    let mut first = true;
+    while i < class.len() {
+        if class[i] == b']' && !first {
+            return Some((matched != negated, i + 1));
+        }
+        first = false;
+
+        if i + 2 < class.len() && class[i + 1] == b'-' && class[i + 2] != b']' {
+            if class[i] <= c && c <= class[i + 2] {
+                matched = true;
+            }
+            i += 3;
+        } else {
+            if class[i] == c {
+                matched = true;
+            }
+            i += 1;
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_patterns() {
+        let matcher = IgnoreMatcher::new("/project");
+        assert!(matcher.is_ignored(Path::new("/project/target/debug/app"), false));
+        assert!(matcher.is_ignored(Path::new("web/node_modules/react/index.js"), false));
+        assert!(matcher.is_ignored(Path::new("logs/server.log"), false));
+        assert!(!matcher.is_ignored(Path::new("src/main.rs"), false));
+        assert!(!matcher.is_ignored(Path::new("src/target.rs"), false));
+    }
+
+    #[test]
+    fn test_gitignore_semantics() {
+        let mut matcher = IgnoreMatcher::new("/project");
+        matcher.add_patterns(Path::new(""), "# comment\n/generated\n*.o\n!keep.o\ndocs/**/*.pdf\n");
+        matcher.add_patterns(Path::new("assets"), "*.png\n");
+
+        assert!(matcher.is_ignored(Path::new("generated/a.rs"), false));
+        assert!(!matcher.is_ignored(Path::new("src/generated/a.rs"), false));
+        assert!(matcher.is_ignored(Path::new("src/lib.o"), false));
+        assert!(!matcher.is_ignored(Path::new("src/keep.o"), false));
+        assert!(matcher.is_ignored(Path::new("docs/manual.pdf"), false));
+        assert!(matcher.is_ignored(Path::new("docs/a/b/manual.pdf"), false));
+        assert!(matcher.is_ignored(Path::new("assets/icons/logo.png"), false));
+        assert!(!matcher.is_ignored(Path::new("logo.png"), false));
+    }
+
+    #[test]
+    fn test_nested_projects() {
+        let mut matcher = IgnoreMatcher::new("/repo");
+        matcher.add_nested_project(Path::new("services/api"));
+
+        assert!(matcher.is_ignored(Path::new("services/api"), true));
+        assert!(matcher.is_ignored(Path::new("/repo/services/api/src/main.rs"), false));
+        assert!(!matcher.is_ignored(Path::new("services/api.rs"), false));
+        assert!(!matcher.is_ignored(Path::new("services/web/index.js"), false));
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match(b"*.rs", b"main.rs"));
+        assert!(!glob_match(b"*.rs", b"src/main.rs"));
+        assert!(glob_match(b"**/*.rs", b"src/main.rs"));
+        assert!(glob_match(b"file?.[ch]", b"file1.c"));
+        assert!(!glob_match(b"file[!0-9].c", b"file1.c"));
+    }
+}
@@ -0,0 +1,45 @@
+//! Where the daemon's local IPC API can be reached.
+//!
+//! On Unix the daemon serves it on a domain socket in a directory only the
+//! user can open, and drops connections from processes run by other users.
+//! Elsewhere it listens on localhost and requires the secret token it writes
+//! to the user's config directory when it starts; clients send it in the
+//! [`IPC_TOKEN_HEADER`] header.
+
+use std::fs;
+use std::path::PathBuf;
+
+/// Localhost address of the IPC API where Unix sockets aren't used
+pub const IPC_TCP_ADDR: &str = "127.0.0.1:7525";
+
+/// Header carrying the IPC token on the localhost transport
+pub const IPC_TOKEN_HEADER: &str = "x-mothership-ipc-token";
+
+const SOCKET_FILE_NAME: &str = "daemon.sock";
+const TOKEN_FILE_NAME: &str = "daemon-ipc-token";
+
+/// Directory holding the daemon's socket: the user's runtime directory where
+/// there is one, otherwise the Mothership config directory
+pub fn ipc_dir() -> Option<PathBuf> {
+    dirs::runtime_dir()
+        .or_else(dirs::config_dir)
+        .map(|dir| dir.join("mothership"))
+}
+
+/// Path of the daemon's Unix socket
+pub fn socket_path() -> Option<PathBuf> {
+    ipc_dir().map(|dir| dir.join(SOCKET_FILE_NAME))
+}
+
+/// Path of the file holding the localhost transport's token
+pub fn token_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("mothership").join(TOKEN_FILE_NAME))
+}
+
+/// The token of the running daemon, if it has written one
+pub fn load_token() -> Option<String> {
+    fs::read_to_string(token_path()?)
+        .ok()
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty())
+}
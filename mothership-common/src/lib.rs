@@ -4,15 +4,46 @@ use std::path::PathBuf;
 use uuid::Uuid;
 
 pub mod auth;
+pub mod bundle;
 pub mod crdt;
+pub mod daemon_config;
+pub mod daemon_log;
 pub mod diff;
+pub mod discovery;
+pub mod features;
+pub mod hooks;
+pub mod ignore;
+pub mod ipc;
+pub mod metadata;
+pub mod profile;
+pub mod project_root;
 pub mod protocol;
+pub mod push_rules;
+pub mod reconcile;
+pub mod release;
+pub mod rift_events;
+pub mod search;
+pub mod secrets;
+pub mod sparse;
+pub mod tls;
 pub mod transaction;
+pub mod upload;
+pub mod wire;
+pub mod worktree;
 
 pub use auth::*;
 pub use crdt::*;
+pub use daemon_config::{DaemonConfig, DiscoverySettings, ThrottleSettings};
 pub use diff::*;
+pub use hooks::{Hook, HookOutcome};
+pub use ignore::{IgnoreMatcher, IGNORE_FILE_NAME};
+pub use metadata::FileMetadata;
 pub use protocol::*;
+pub use push_rules::PushRules;
+pub use reconcile::{Manifest, ManifestEntry, ReconcilePlan};
+pub use secrets::{SecretFinding, SecretScanMode};
+pub use sparse::SparseSpec;
+pub use tls::TlsSettings;
 pub use transaction::{Transaction, TransactionStatus};
 
 // Re-export commonly used types
@@ -55,16 +86,89 @@ pub struct Project {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ProjectSettings {
-    pub auto_checkpoint_interval: u64, // seconds
+    pub auto_checkpoint_interval: u64, // seconds; 0 turns automatic checkpoints off
     pub max_checkpoint_history: u32,
     pub allowed_file_types: Vec<String>,
+    pub conflict_policy: ConflictPolicy,
+    pub push_rules: PushRules,
+    pub secret_scanning: SecretScanMode,
+    pub visibility: ProjectVisibility,
+}
+
+/// What to do when a client's change no longer applies to the server's copy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConflictPolicy {
+    /// Discard the local change and take the server's content
+    #[default]
+    ServerWins,
+    /// Resend the whole local file, overwriting the server's content
+    ClientWins,
+    /// Take the server's content and move the local change into a new conflict rift
+    AlwaysCreateConflictRift,
+    /// Keep both: the local file stays, the server's copy is saved for the user to reconcile
+    Prompt,
+}
+
+impl ConflictPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConflictPolicy::ServerWins => "server-wins",
+            ConflictPolicy::ClientWins => "client-wins",
+            ConflictPolicy::AlwaysCreateConflictRift => "always-create-conflict-rift",
+            ConflictPolicy::Prompt => "prompt",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "server-wins" => Some(ConflictPolicy::ServerWins),
+            "client-wins" => Some(ConflictPolicy::ClientWins),
+            "always-create-conflict-rift" => Some(ConflictPolicy::AlwaysCreateConflictRift),
+            "prompt" => Some(ConflictPolicy::Prompt),
+            _ => None,
+        }
+    }
+}
+
+/// Who can read a project. Only members ever change it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProjectVisibility {
+    /// Members only
+    #[default]
+    Private,
+    /// Anyone signed in to the server
+    Internal,
+    /// Anyone, without signing in
+    Public,
+}
+
+impl ProjectVisibility {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProjectVisibility::Private => "private",
+            ProjectVisibility::Internal => "internal",
+            ProjectVisibility::Public => "public",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "private" => Some(ProjectVisibility::Private),
+            "internal" => Some(ProjectVisibility::Internal),
+            "public" => Some(ProjectVisibility::Public),
+            _ => None,
+        }
+    }
 }
 
 impl Default for ProjectSettings {
     fn default() -> Self {
         Self {
-            auto_checkpoint_interval: 10, // 10 seconds
+            auto_checkpoint_interval: 300, // 5 minutes
             max_checkpoint_history: 1000,
             allowed_file_types: vec![
                 "*.rs".to_string(),
@@ -83,6 +187,10 @@ impl Default for ProjectSettings {
                 "*.yml".to_string(),
                 "*.toml".to_string(),
             ],
+            conflict_policy: ConflictPolicy::default(),
+            push_rules: PushRules::default(),
+            secret_scanning: SecretScanMode::default(),
+            visibility: ProjectVisibility::default(),
         }
     }
 }
@@ -109,6 +217,196 @@ pub struct Checkpoint {
     pub parent: Option<CheckpointId>,
     pub message: Option<String>, // Optional user annotation
     pub auto_generated: bool,    // True for automatic checkpoints, false for manual
+    /// Rift whose changes this checkpoint merged in
+    #[serde(default)]
+    pub merged_from: Option<RiftId>,
+    /// Earlier automatic checkpoints folded into this one by history compaction
+    #[serde(default)]
+    pub squashed: u32,
+}
+
+/// Local changes set aside with `mothership stash push`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Stash {
+    pub id: Uuid,
+    pub message: Option<String>,
+    /// Rift the changes were made in
+    pub rift: Option<String>,
+    /// Checkpoint the changes were made against
+    pub base_checkpoint: Option<CheckpointId>,
+    pub created_at: DateTime<Utc>,
+    pub files: Vec<StashedFile>,
+}
+
+/// A file changed since the stash's base checkpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StashedFile {
+    pub path: PathBuf,
+    /// New content, or `None` if the file was deleted
+    pub content: Option<String>,
+}
+
+/// Who may join a rift besides its owner
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RiftVisibility {
+    /// Every project member may join
+    Project,
+    /// Only users the rift has been shared with may join
+    Invite,
+    /// Only the owner may join
+    Private,
+}
+
+impl RiftVisibility {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RiftVisibility::Project => "project",
+            RiftVisibility::Invite => "invite",
+            RiftVisibility::Private => "private",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "project" => Some(RiftVisibility::Project),
+            "invite" => Some(RiftVisibility::Invite),
+            "private" => Some(RiftVisibility::Private),
+            _ => None,
+        }
+    }
+}
+
+/// What a user may do in a rift
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RiftRole {
+    /// Created the rift; manages who else may join
+    Owner,
+    /// Reads and writes files
+    Editor,
+    /// Receives updates but cannot change files
+    Observer,
+}
+
+impl RiftRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RiftRole::Owner => "owner",
+            RiftRole::Editor => "editor",
+            RiftRole::Observer => "observer",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "owner" => Some(RiftRole::Owner),
+            "editor" => Some(RiftRole::Editor),
+            "observer" => Some(RiftRole::Observer),
+            _ => None,
+        }
+    }
+
+    pub fn can_write(&self) -> bool {
+        !matches!(self, RiftRole::Observer)
+    }
+}
+
+/// State of an external status check (CI build, tests, ...) on a checkpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusState {
+    Pending,
+    Success,
+    Failure,
+    Error,
+}
+
+impl StatusState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StatusState::Pending => "pending",
+            StatusState::Success => "success",
+            StatusState::Failure => "failure",
+            StatusState::Error => "error",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(StatusState::Pending),
+            "success" => Some(StatusState::Success),
+            "failure" => Some(StatusState::Failure),
+            "error" => Some(StatusState::Error),
+            _ => None,
+        }
+    }
+}
+
+/// Where a request to merge one rift into another stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewState {
+    Open,
+    Merged,
+    Closed,
+}
+
+impl ReviewState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReviewState::Open => "open",
+            ReviewState::Merged => "merged",
+            ReviewState::Closed => "closed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "open" => Some(ReviewState::Open),
+            "merged" => Some(ReviewState::Merged),
+            "closed" => Some(ReviewState::Closed),
+            _ => None,
+        }
+    }
+}
+
+/// Latest result reported by one external system (identified by `context`) for a checkpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointStatus {
+    pub checkpoint_id: CheckpointId,
+    pub state: StatusState,
+    /// Name of the check, e.g. "ci/build"
+    pub context: String,
+    pub target_url: Option<String>,
+    pub description: Option<String>,
+    pub creator: UserId,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Combine the statuses of a checkpoint: any failure or error fails the whole
+/// checkpoint, otherwise it is pending until every check succeeds.
+/// Returns None when no checks have reported.
+pub fn combined_status(statuses: &[CheckpointStatus]) -> Option<StatusState> {
+    if statuses.is_empty() {
+        return None;
+    }
+    if statuses.iter().any(|s| matches!(s.state, StatusState::Failure | StatusState::Error)) {
+        return Some(StatusState::Failure);
+    }
+    if statuses.iter().any(|s| s.state == StatusState::Pending) {
+        return Some(StatusState::Pending);
+    }
+    Some(StatusState::Success)
+}
+
+/// A checkpoint together with its status checks, as returned by the history endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointHistoryEntry {
+    #[serde(flatten)]
+    pub checkpoint: Checkpoint,
+    #[serde(default)]
+    pub statuses: Vec<CheckpointStatus>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -118,6 +416,9 @@ pub struct FileChange {
     pub content_hash: String,
     pub diff: Option<String>, // Unified diff format
     pub size: u64,
+    /// Permissions, symlink target and mtime of the file
+    #[serde(default, skip_serializing_if = "FileMetadata::is_empty")]
+    pub metadata: FileMetadata,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -135,6 +436,9 @@ pub struct GatewayProject {
     pub active_rifts: Vec<RiftSummary>,
     pub your_rifts: Vec<RiftSummary>,
     pub last_activity: Option<DateTime<Utc>>,
+    /// Set when the project is archived (read-only)
+    #[serde(default)]
+    pub archived_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -153,6 +457,9 @@ pub struct ClientConfig {
     pub auth_token: Option<String>,
     pub local_workspace: PathBuf,
     pub user_id: Option<UserId>,
+    /// Release channel `mothership update` follows
+    #[serde(default)]
+    pub update_channel: release::ReleaseChannel,
 }
 
 impl Default for ClientConfig {
@@ -170,6 +477,7 @@ impl Default for ClientConfig {
                 .unwrap_or_else(|| PathBuf::from("."))
                 .join("mothership"),
             user_id: None,
+            update_channel: release::ReleaseChannel::default(),
         }
     }
 } 
\ No newline at end of file
@@ -0,0 +1,181 @@
+//! File metadata synced alongside content: permission bits (for the
+//! executable bit), symlink targets and modification times.
+//!
+//! A symlink is synced as a file whose content is the link target, like git
+//! does, with `symlink_target` set so clients that understand it recreate the
+//! link. Platforms without Unix permissions leave `mode` unset, and writing a
+//! file without a mode keeps whatever permissions it already had.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Metadata of a synced file. Every field is optional; an empty value means
+/// "a regular file with default permissions".
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileMetadata {
+    /// Unix permission bits, e.g. `0o755`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<u32>,
+    /// Set if the file is a symlink; its content is the same path as text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symlink_target: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mtime: Option<DateTime<Utc>>,
+}
+
+impl FileMetadata {
+    /// Read a file's metadata without following symlinks
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let metadata = fs::symlink_metadata(path)?;
+        let symlink_target = if metadata.file_type().is_symlink() {
+            Some(fs::read_link(path)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            mode: mode_of(&metadata).filter(|_| symlink_target.is_none()),
+            symlink_target,
+            mtime: metadata.modified().ok().map(DateTime::<Utc>::from),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn is_executable(&self) -> bool {
+        self.mode.is_some_and(|mode| mode & 0o111 != 0)
+    }
+
+    /// Whether the difference from `other` is worth syncing. Modification
+    /// times change with every write, so they don't count.
+    pub fn differs_from(&self, other: &FileMetadata) -> bool {
+        self.mode != other.mode || self.symlink_target != other.symlink_target
+    }
+}
+
+#[cfg(unix)]
+fn mode_of(metadata: &fs::Metadata) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(metadata.permissions().mode() & 0o7777)
+}
+
+#[cfg(not(unix))]
+fn mode_of(_metadata: &fs::Metadata) -> Option<u32> {
+    None
+}
+
+#[cfg(unix)]
+fn create_symlink(target: &Path, path: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, path)
+}
+
+#[cfg(windows)]
+fn create_symlink(target: &Path, path: &Path) -> io::Result<()> {
+    std::os::windows::fs::symlink_file(target, path)
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+}
+
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _mode: u32) -> io::Result<()> {
+    Ok(())
+}
+
+/// Read a file's synced content: its text, or its target if it is a symlink
+pub fn read_file(path: &Path) -> io::Result<String> {
+    if fs::symlink_metadata(path)?.file_type().is_symlink() {
+        return Ok(fs::read_link(path)?.to_string_lossy().into_owned());
+    }
+    fs::read_to_string(path)
+}
+
+/// Write a synced file to disk, creating parent directories as needed.
+///
+/// With metadata, symlinks are recreated as links (falling back to a text
+/// file holding the target where links can't be created) and regular files
+/// get their permission bits and modification time. Without metadata (or
+/// with empty metadata) an existing symlink whose target matches `content`
+/// is left alone, so tools that only know file contents don't flatten links.
+pub fn write_file(path: &Path, content: &str, metadata: Option<&FileMetadata>) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let existing_link = fs::symlink_metadata(path)
+        .ok()
+        .filter(|existing| existing.file_type().is_symlink())
+        .and_then(|_| fs::read_link(path).ok());
+
+    let Some(metadata) = metadata.filter(|metadata| !metadata.is_empty()) else {
+        if existing_link.as_deref() == Some(Path::new(content)) {
+            return Ok(());
+        }
+        if existing_link.is_some() {
+            fs::remove_file(path)?;
+        }
+        return fs::write(path, content);
+    };
+
+    if let Some(target) = &metadata.symlink_target {
+        if existing_link.as_ref() == Some(target) {
+            return Ok(());
+        }
+        if fs::symlink_metadata(path).is_ok() {
+            fs::remove_file(path)?;
+        }
+        return create_symlink(target, path).or_else(|_| fs::write(path, content));
+    }
+
+    // Replace a link that became a regular file rather than writing through it
+    if existing_link.is_some() {
+        fs::remove_file(path)?;
+    }
+    fs::write(path, content)?;
+    if let Some(mode) = metadata.mode {
+        set_mode(path, mode)?;
+    }
+    if let Some(mtime) = metadata.mtime {
+        fs::File::options().write(true).open(path)?.set_modified(mtime.into())?;
+    }
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metadata_round_trips_through_disk() {
+        let dir = std::env::temp_dir().join(format!("mothership-metadata-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let script = dir.join("bin/run.sh");
+        let mtime = DateTime::parse_from_rfc3339("2024-01-02T03:04:05Z").unwrap().with_timezone(&Utc);
+        let executable = FileMetadata { mode: Some(0o755), symlink_target: None, mtime: Some(mtime) };
+        write_file(&script, "#!/bin/sh\n", Some(&executable)).unwrap();
+        assert_eq!(FileMetadata::read(&script).unwrap(), executable);
+        assert!(FileMetadata::read(&script).unwrap().is_executable());
+
+        let link = dir.join("run");
+        let symlink = FileMetadata { symlink_target: Some(PathBuf::from("bin/run.sh")), ..Default::default() };
+        write_file(&link, "bin/run.sh", Some(&symlink)).unwrap();
+        assert_eq!(fs::read_link(&link).unwrap(), PathBuf::from("bin/run.sh"));
+        assert!(!FileMetadata::read(&link).unwrap().differs_from(&symlink));
+
+        // Content-only writers leave a matching link in place
+        write_file(&link, "bin/run.sh", None).unwrap();
+        assert!(fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+        assert_eq!(read_file(&link).unwrap(), "bin/run.sh");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,179 @@
+//! Named authentication profiles, for people who work against several
+//! servers with different accounts.
+//!
+//! A profile bundles a server URL with its own credentials. The profile in
+//! effect is, in order: the one picked for this command (`--profile`), the
+//! [`PROFILE_ENV_VAR`] environment variable, the one pinned by the project's
+//! `.mothership/project.json`, then the default profile. Without any of
+//! them the CLI uses the active server and `credentials.json` as before.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Environment variable selecting a profile
+pub const PROFILE_ENV_VAR: &str = "MOTHERSHIP_PROFILE";
+
+/// Key of the pinned profile in `.mothership/project.json`
+const PROJECT_PROFILE_KEY: &str = "profile";
+
+const PROFILES_FILE_NAME: &str = "profiles.json";
+const CREDENTIALS_FILE_NAME: &str = "credentials.json";
+
+/// The profiles set up on this machine, kept in `profiles.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profiles {
+    /// Profile used when nothing else picks one
+    pub default: Option<String>,
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub server_url: String,
+    pub created_at: DateTime<Utc>,
+}
+
+fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("mothership"))
+}
+
+impl Profiles {
+    pub fn path() -> Option<PathBuf> {
+        config_dir().map(|dir| dir.join(PROFILES_FILE_NAME))
+    }
+
+    /// Load the profiles; none if the file is missing or unreadable
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path().ok_or_else(|| anyhow!("Could not determine the config directory"))?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Profile names become directory names, so keep them simple
+pub fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(anyhow!("Invalid profile name '{}': use letters, digits, '-' and '_'", name));
+    }
+    Ok(())
+}
+
+static SELECTED: OnceLock<String> = OnceLock::new();
+
+/// Use `name` for the rest of this process, ahead of the environment and project pins
+pub fn select(name: &str) -> Result<()> {
+    validate_name(name)?;
+    SELECTED.set(name.to_string()).map_err(|_| anyhow!("A profile was already selected"))
+}
+
+/// The profile pinned by the project containing `dir`
+pub fn pinned_profile(dir: &Path) -> Option<String> {
+    dir.ancestors().find_map(|dir| {
+        let project_file = dir.join(".mothership").join("project.json");
+        let project: serde_json::Value = serde_json::from_str(&fs::read_to_string(project_file).ok()?).ok()?;
+        project.get(PROJECT_PROFILE_KEY)?.as_str().map(str::to_string)
+    })
+}
+
+/// Pin a project to a profile, or unpin it with `None`
+pub fn pin(project_root: &Path, profile: Option<&str>) -> Result<()> {
+    let project_file = project_root.join(".mothership").join("project.json");
+    let mut project: serde_json::Value = serde_json::from_str(&fs::read_to_string(&project_file)?)?;
+    let fields = project
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("{} is not a JSON object", project_file.display()))?;
+    match profile {
+        Some(profile) => {
+            validate_name(profile)?;
+            fields.insert(PROJECT_PROFILE_KEY.to_string(), profile.into());
+        }
+        None => {
+            fields.remove(PROJECT_PROFILE_KEY);
+        }
+    }
+    fs::write(&project_file, serde_json::to_string_pretty(&project)?)?;
+    Ok(())
+}
+
+/// The profile in effect for work in `dir`
+pub fn active_profile_in(dir: &Path) -> Option<String> {
+    SELECTED
+        .get()
+        .cloned()
+        .or_else(|| std::env::var(PROFILE_ENV_VAR).ok().filter(|name| !name.trim().is_empty()))
+        .or_else(|| pinned_profile(dir))
+        .or_else(|| Profiles::load().default)
+}
+
+/// The profile in effect for the current directory
+pub fn active_profile() -> Option<String> {
+    let dir = std::env::current_dir().unwrap_or_default();
+    active_profile_in(&dir)
+}
+
+/// Where a profile's credentials are kept; without a profile, `credentials.json`
+pub fn credentials_path_for(profile: Option<&str>) -> Option<PathBuf> {
+    let dir = config_dir()?;
+    Some(match profile {
+        Some(profile) => dir.join("profiles").join(profile).join(CREDENTIALS_FILE_NAME),
+        None => dir.join(CREDENTIALS_FILE_NAME),
+    })
+}
+
+/// Where the credentials of the profile in effect are kept
+pub fn credentials_path() -> Option<PathBuf> {
+    credentials_path_for(active_profile().as_deref())
+}
+
+/// The server a profile signs in to
+pub fn server_url_for(profile: &str) -> Option<String> {
+    Profiles::load().profiles.get(profile).map(|profile| profile.server_url.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pin_keeps_other_project_fields() {
+        let root = std::env::temp_dir().join(format!("mothership-profile-{}", uuid::Uuid::new_v4()));
+        let nested = root.join("src").join("deep");
+        fs::create_dir_all(&nested).unwrap();
+        fs::create_dir_all(root.join(".mothership")).unwrap();
+        fs::write(root.join(".mothership").join("project.json"), r#"{"project_id": "abc"}"#).unwrap();
+
+        pin(&root, Some("work")).unwrap();
+        assert_eq!(pinned_profile(&nested).as_deref(), Some("work"));
+        let project: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(root.join(".mothership").join("project.json")).unwrap()).unwrap();
+        assert_eq!(project["project_id"], "abc");
+
+        pin(&root, None).unwrap();
+        assert_eq!(pinned_profile(&nested), None);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_profile_names() {
+        assert!(validate_name("client-a_2").is_ok());
+        assert!(validate_name("../work").is_err());
+        assert!(validate_name("").is_err());
+    }
+}
@@ -0,0 +1,51 @@
+//! Finding the project a path belongs to.
+//!
+//! Projects can nest: a directory with its own `.mothership/project.json`
+//! inside another project's tree is a separate project, and the files under
+//! it belong to it alone. Every path belongs to the nearest project root
+//! enclosing it.
+
+use std::path::{Path, PathBuf};
+
+/// Whether `dir` is the root of a project
+pub fn is_project_root(dir: &Path) -> bool {
+    dir.join(".mothership").join("project.json").is_file()
+}
+
+/// The root of the nearest project enclosing `path`, `path` included
+pub fn find_project_root(path: &Path) -> Option<PathBuf> {
+    path.ancestors().find(|dir| is_project_root(dir)).map(Path::to_path_buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn make_project(dir: &Path) {
+        fs::create_dir_all(dir.join(".mothership")).unwrap();
+        fs::write(dir.join(".mothership").join("project.json"), "{}").unwrap();
+    }
+
+    #[test]
+    fn test_nearest_root_wins() {
+        let repo = std::env::temp_dir().join(format!("mothership-roots-{}", uuid::Uuid::new_v4()));
+        let service = repo.join("services").join("api");
+        fs::create_dir_all(service.join("src")).unwrap();
+        fs::create_dir_all(repo.join("docs")).unwrap();
+        make_project(&repo);
+        make_project(&service);
+
+        assert_eq!(find_project_root(&repo.join("docs")), Some(repo.clone()));
+        assert_eq!(find_project_root(&repo.join("services")), Some(repo.clone()));
+        assert_eq!(find_project_root(&service.join("src")), Some(service.clone()));
+        assert_eq!(find_project_root(&service), Some(service.clone()));
+
+        // The outer project leaves the nested one's files alone
+        let matcher = crate::IgnoreMatcher::load(&repo);
+        assert!(matcher.is_ignored(&service.join("src").join("main.rs"), false));
+        assert!(!matcher.is_ignored(&repo.join("docs").join("index.md"), false));
+
+        fs::remove_dir_all(repo).unwrap();
+    }
+}
@@ -4,8 +4,105 @@ use std::path::PathBuf;
 use std::collections::HashMap;
 use uuid::Uuid;
 use crate::transaction::TransactionStatus;
+use crate::crdt::{TextCRDT, TextOperation};
+use crate::reconcile::{Manifest, ReconcilePlan};
+use crate::wire::WireEncoding;
 
-use crate::{CheckpointId, FileChange, ProjectId, RiftId, UserId};
+use crate::{ChangeType, Checkpoint, CheckpointId, ConflictPolicy, FileChange, FileMetadata, Project, ProjectId, ProjectVisibility, PushRules, ReviewState, Rift, RiftId, RiftRole, RiftVisibility, SecretScanMode, StatusState, User, UserId};
+
+/// Sync protocol version spoken by this build.
+///
+/// 1: original protocol (JoinRift carries no version)
+/// 2: versioned JoinRift/RiftJoined handshake
+/// 3: optional binary (MessagePack) frames negotiated in JoinRift
+/// 4: resumable sessions (sequenced broadcasts, SessionResumed)
+/// 5: startup reconciliation (file manifest in JoinRift)
+/// 6: in-app notifications pushed to the user's connections
+pub const PROTOCOL_VERSION: u32 = 6;
+
+/// First protocol version with resumable sessions
+pub const RESUMABLE_SESSIONS_VERSION: u32 = 4;
+
+/// First protocol version with startup reconciliation
+pub const RECONCILIATION_VERSION: u32 = 5;
+
+/// First protocol version with pushed in-app notifications
+pub const NOTIFICATIONS_VERSION: u32 = 6;
+
+/// Oldest sync protocol version this build still understands
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+/// `error_code` of the Error sent when client and server share no protocol version
+pub const PROTOCOL_MISMATCH_ERROR: &str = "protocol_mismatch";
+
+/// `error_code` of the Error sent when a client writes to an archived project
+pub const PROJECT_ARCHIVED_ERROR: &str = "project_archived";
+
+/// `error_code` of the Error sent when a client writes to a read-only replica
+pub const READ_REPLICA_ERROR: &str = "read_replica";
+
+/// `error_code` of the Error sent when a client that joined as an observer writes
+pub const OBSERVER_ERROR: &str = "observer_read_only";
+
+/// `error_code` of the Error sent to clients of a rift that was deleted
+pub const RIFT_DELETED_ERROR: &str = "rift_deleted";
+
+/// `error_code` of the Error sent when a synced change breaks a project push rule
+pub const PUSH_RULE_ERROR: &str = "push_rule_violation";
+
+/// `error_code` of the Error sent when a checkpoint is refused to keep its rift's history linear
+pub const CHECKPOINT_CONFLICT_ERROR: &str = "checkpoint_conflict";
+
+/// `error_code` of the Error warning a client that a change it sent contains a secret
+pub const SECRET_FOUND_WARNING: &str = "secret_found";
+
+/// `error_code` of the Error sent when a sync message is bigger than the server accepts
+pub const MESSAGE_TOO_LARGE_ERROR: &str = "message_too_large";
+
+/// `error_code` of the Error sent when a batch names more files than the server accepts
+pub const BATCH_TOO_LARGE_ERROR: &str = "batch_too_large";
+
+/// `error_code` of the Error sent when a connection sends messages faster than the server accepts
+pub const RATE_LIMITED_ERROR: &str = "rate_limited";
+
+/// `error_code` of the Error telling a rift that a file was flagged by malware scanning and quarantined
+pub const FILE_QUARANTINED_WARNING: &str = "file_quarantined";
+
+/// Peers that predate versioning speak protocol 1
+fn legacy_protocol_version() -> u32 {
+    1
+}
+
+/// Pick the highest protocol version supported by both this build and a peer
+/// that speaks `peer_min..=peer_max`, or None if the ranges do not overlap
+pub fn negotiate_protocol_version(peer_min: u32, peer_max: u32) -> Option<u32> {
+    let version = PROTOCOL_VERSION.min(peer_max);
+    (version >= MIN_PROTOCOL_VERSION.max(peer_min)).then_some(version)
+}
+
+/// Explain why a client and a server share no protocol version, naming the
+/// side that needs upgrading
+pub fn protocol_mismatch_message(client_min: u32, client_max: u32, server_min: u32, server_max: u32) -> String {
+    if client_max < server_min {
+        format!(
+            "This client speaks sync protocol up to v{}, but the server requires v{} or newer. Run 'mothership update' to upgrade.",
+            client_max, server_min
+        )
+    } else {
+        format!(
+            "The server speaks sync protocol up to v{}, but this client requires v{} or newer. Ask the server administrator to upgrade.",
+            server_max, client_min
+        )
+    }
+}
+
+/// A session a reconnecting client wants to pick up where it left off
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionResume {
+    pub session_id: Uuid,
+    /// Sequence number of the last broadcast the client received
+    pub last_seq: u64,
+}
 
 /// WebSocket messages for real-time synchronization
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,6 +113,27 @@ pub enum SyncMessage {
     JoinRift {
         rift_id: RiftId,
         last_checkpoint: Option<CheckpointId>,
+        /// Newest protocol version the client speaks
+        #[serde(default = "legacy_protocol_version")]
+        protocol_version: u32,
+        /// Oldest protocol version the client still speaks
+        #[serde(default = "legacy_protocol_version")]
+        min_protocol_version: u32,
+        /// Wire encodings the client accepts, most preferred first
+        #[serde(default)]
+        encodings: Vec<WireEncoding>,
+        /// Session to resume after a reconnect instead of receiving the full rift state
+        #[serde(default)]
+        resume: Option<SessionResume>,
+        /// Only sync these project paths (sparse beam); empty means the whole project
+        #[serde(default)]
+        sparse_paths: Vec<PathBuf>,
+        /// The client's files, to reconcile changes made while it was offline (protocol 5+)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        manifest: Option<Manifest>,
+        /// Join to watch only; the server refuses any change from this connection
+        #[serde(default)]
+        observer: bool,
     },
     
     /// Client announces they're leaving a rift
@@ -29,6 +147,9 @@ pub enum SyncMessage {
         path: PathBuf,
         content: String,
         timestamp: DateTime<Utc>,
+        /// Permissions, symlink target and mtime of the file
+        #[serde(default, skip_serializing_if = "FileMetadata::is_empty")]
+        metadata: FileMetadata,
     },
     
     /// PERFORMANCE FIX: Client reports file change as diff only
@@ -38,6 +159,16 @@ pub enum SyncMessage {
         diff: FileDiff,
         file_size: u64,
         timestamp: DateTime<Utc>,
+        #[serde(default, skip_serializing_if = "FileMetadata::is_empty")]
+        metadata: FileMetadata,
+    },
+    
+    /// Client reports a file moved or renamed without changing its content
+    FileMoved {
+        rift_id: RiftId,
+        from: PathBuf,
+        to: PathBuf,
+        timestamp: DateTime<Utc>,
     },
     
     /// PERFORMANCE FIX: Client reports multiple file changes as diffs (batched)
@@ -125,6 +256,9 @@ pub enum SyncMessage {
         server_timestamp: DateTime<Utc>,
         client_timestamp: DateTime<Utc>,
         auto_created_rift: Option<ConflictRiftInfo>,
+        /// The project's conflict policy; a client's local override takes precedence
+        #[serde(default)]
+        policy: ConflictPolicy,
     },
 
     // Bidirectional
@@ -153,6 +287,50 @@ pub enum SyncMessage {
         current_files: HashMap<PathBuf, String>,
         participants: Vec<String>,
         last_checkpoint: Option<CheckpointId>,
+        /// Protocol version negotiated for this connection
+        #[serde(default = "legacy_protocol_version")]
+        protocol_version: u32,
+        /// Encoding the server uses for messages after this one
+        #[serde(default)]
+        encoding: WireEncoding,
+        /// Session the client can resume after a reconnect (protocol 4+)
+        #[serde(default)]
+        session_id: Option<Uuid>,
+        /// Sequence number of the last broadcast reflected in `current_files`
+        #[serde(default)]
+        seq: u64,
+        /// Paths this connection is limited to; the server drops broadcasts outside them
+        #[serde(default)]
+        sparse_paths: Vec<PathBuf>,
+        /// Metadata of the files in `current_files` that have any
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        file_metadata: HashMap<PathBuf, FileMetadata>,
+        /// Answer to the client's manifest. When present, `current_files` only
+        /// holds the files the client has to download; conflicts follow as
+        /// `ConflictDetected` messages
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reconcile: Option<ReconcilePlan>,
+    },
+
+    /// Server accepted a resumed session: instead of the full rift state it
+    /// sends the broadcasts the client missed while disconnected
+    SessionResumed {
+        rift_id: RiftId,
+        session_id: Uuid,
+        /// Missed broadcasts, oldest first, each a `Sequenced` message
+        missed: Vec<SyncMessage>,
+        /// Sequence number of the last broadcast the client now has
+        seq: u64,
+        protocol_version: u32,
+        encoding: WireEncoding,
+        #[serde(default)]
+        sparse_paths: Vec<PathBuf>,
+    },
+
+    /// A rift broadcast with its sequence number (protocol 4+)
+    Sequenced {
+        seq: u64,
+        message: Box<SyncMessage>,
     },
 
     /// Server broadcasts file updates with actual content (DEPRECATED: Use RiftDiffUpdate)
@@ -222,6 +400,28 @@ pub enum SyncMessage {
         timestamp: DateTime<Utc>,
     },
 
+    // Text CRDT messages (real-time co-editing)
+    /// Client opens a co-editing session for a file; the server answers with a TextSnapshot
+    JoinTextSession {
+        rift_id: RiftId,
+        path: PathBuf,
+    },
+
+    /// Server sends the shared document of a co-edited file
+    TextSnapshot {
+        rift_id: RiftId,
+        path: PathBuf,
+        document: TextCRDT,
+    },
+
+    /// Character-level edits to a co-edited file (client -> server and server -> clients)
+    TextOperations {
+        rift_id: RiftId,
+        path: PathBuf,
+        site_id: Uuid,
+        operations: Vec<TextOperation>,
+    },
+
     ForceSync {
         path: PathBuf,
         server_content: String,
@@ -252,6 +452,242 @@ pub enum SyncMessage {
         new_rift_id: Uuid,
         conflict_rift_name: String,
     },
+    
+    /// Server could not apply a block delta because its copy of the file does
+    /// not match the delta's base; the sender should resend full content
+    DeltaRejected {
+        rift_id: RiftId,
+        path: PathBuf,
+        base_hash: String,
+    },
+
+    /// Client posts a chat message to everyone in a rift
+    SendChatMessage {
+        rift_id: RiftId,
+        content: String,
+    },
+
+    /// Server broadcasts a chat message to everyone in a rift
+    ChatMessage {
+        rift_id: RiftId,
+        message: ChatMessage,
+    },
+
+    /// Server pushes a new in-app notification to its recipient's connections
+    Notification {
+        notification: InboxNotification,
+    },
+
+    // Terminal sharing is an optional extension: only connections that send
+    // WatchTerminalShares receive terminal output, and a server without it
+    // never answers StartTerminalShare.
+
+    /// Client starts streaming a terminal session into its rift
+    StartTerminalShare {
+        rift_id: RiftId,
+        share_id: Uuid,
+        /// What is running, shown to watchers
+        command: String,
+        cols: u16,
+        rows: u16,
+    },
+
+    /// Server announces a terminal share to everyone watching the rift's terminals
+    TerminalShareStarted {
+        rift_id: RiftId,
+        share: TerminalShare,
+    },
+
+    /// Output of a shared terminal, from its client and relayed to watchers
+    TerminalOutput {
+        rift_id: RiftId,
+        share_id: Uuid,
+        data: String,
+    },
+
+    /// Client stops sharing a terminal, e.g. because the command exited
+    EndTerminalShare {
+        rift_id: RiftId,
+        share_id: Uuid,
+        exit_code: Option<i32>,
+    },
+
+    /// Server tells watchers a terminal share ended
+    TerminalShareEnded {
+        rift_id: RiftId,
+        share_id: Uuid,
+        exit_code: Option<i32>,
+    },
+
+    /// Client asks to receive the rift's terminal shares
+    WatchTerminalShares {
+        rift_id: RiftId,
+    },
+
+    /// Server lists the terminals being shared in a rift, in reply to
+    /// WatchTerminalShares; their recent output follows as TerminalOutput
+    TerminalShares {
+        rift_id: RiftId,
+        shares: Vec<TerminalShare>,
+    },
+
+    // Cursor sharing works the same way: only connections that send
+    // WatchCursors receive collaborators' cursor positions.
+
+    /// Client moved its cursor; without a path it closed the file
+    UpdateCursor {
+        rift_id: RiftId,
+        path: Option<PathBuf>,
+        line: u32,
+        column: u32,
+    },
+
+    /// Server relays a collaborator's cursor to everyone watching the rift's cursors
+    CursorMoved {
+        rift_id: RiftId,
+        cursor: RemoteCursor,
+    },
+
+    /// Client asks to receive collaborators' cursors
+    WatchCursors {
+        rift_id: RiftId,
+    },
+
+    /// Server lists the cursors it knows in a rift, in reply to WatchCursors
+    Cursors {
+        rift_id: RiftId,
+        cursors: Vec<RemoteCursor>,
+    },
+
+    // File activity too: editors and the daemon say which files they have
+    // open or changed, and connections that send WatchFileActivity hear
+    // about the files collaborators are working on.
+
+    /// Client opened a file in an editor, or closed it
+    FileOpened {
+        rift_id: RiftId,
+        path: PathBuf,
+        open: bool,
+    },
+
+    /// Client changed files since the last checkpoint, or stopped counting
+    /// them as changed (e.g. discarded the edits)
+    FileDirty {
+        rift_id: RiftId,
+        paths: Vec<PathBuf>,
+        dirty: bool,
+    },
+
+    /// Server relays a collaborator's activity on a file to everyone
+    /// watching the rift's file activity
+    FileActivityChanged {
+        rift_id: RiftId,
+        activity: FileActivity,
+    },
+
+    /// Client asks to receive collaborators' file activity
+    WatchFileActivity {
+        rift_id: RiftId,
+    },
+
+    /// Server lists the files being worked on in a rift, in reply to
+    /// WatchFileActivity
+    FileActivities {
+        rift_id: RiftId,
+        activity: Vec<FileActivity>,
+    },
+}
+
+/// A terminal session someone is streaming into a rift
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerminalShare {
+    pub id: Uuid,
+    pub user_id: UserId,
+    pub username: String,
+    pub command: String,
+    pub cols: u16,
+    pub rows: u16,
+    pub started_at: DateTime<Utc>,
+}
+
+/// Where a collaborator's cursor is, with 1-based line and column
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteCursor {
+    pub user_id: UserId,
+    pub username: String,
+    /// File the cursor is in; None once the collaborator closed it
+    pub path: Option<PathBuf>,
+    pub line: u32,
+    pub column: u32,
+}
+
+/// A collaborator working on a file. Files that are neither open nor
+/// changed any more are reported once with both flags off, then forgotten.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileActivity {
+    pub user_id: UserId,
+    pub username: String,
+    pub path: PathBuf,
+    /// Open in an editor
+    pub open: bool,
+    /// Changed since the user's last checkpoint
+    pub dirty: bool,
+}
+
+impl FileActivity {
+    pub fn is_active(&self) -> bool {
+        self.open || self.dirty
+    }
+}
+
+/// A chat message posted in a rift
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub id: Uuid,
+    pub rift_id: RiftId,
+    pub author: UserId,
+    pub username: String,
+    pub content: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Request body for posting a chat message over HTTP
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SendChatRequest {
+    pub content: String,
+}
+
+/// A checkpoint that changed a file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileRevision {
+    pub checkpoint_id: CheckpointId,
+    pub author: UserId,
+    pub username: String,
+    pub timestamp: DateTime<Utc>,
+    pub message: Option<String>,
+    pub change_type: ChangeType,
+    /// Content hash of the file after this checkpoint (empty when deleted)
+    pub content_hash: String,
+    pub size: u64,
+}
+
+/// A line of a file with the checkpoint that introduced it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlameLine {
+    pub line_number: usize,
+    pub content: String,
+    pub checkpoint_id: CheckpointId,
+    pub author: UserId,
+    pub username: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Line-by-line attribution of a file at a checkpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileBlame {
+    pub path: PathBuf,
+    pub checkpoint_id: CheckpointId,
+    pub lines: Vec<BlameLine>,
 }
 
 /// PERFORMANCE FIX: Diff-based file change for minimal network usage
@@ -260,6 +696,8 @@ pub struct FileDiffChange {
     pub path: PathBuf,
     pub diff: FileDiff,
     pub file_size: u64,
+    #[serde(default, skip_serializing_if = "FileMetadata::is_empty")]
+    pub metadata: FileMetadata,
 }
 
 /// PERFORMANCE FIX: Diff representation for minimal data transfer
@@ -282,6 +720,16 @@ pub enum FileDiff {
         new_size: u64,
     },
     
+    /// rsync-style block delta for large files: blocks of the base content are
+    /// referenced by index so only the changed regions are sent
+    BlockDelta {
+        block_size: u32,
+        /// Hash of the content the delta applies to
+        base_hash: String,
+        operations: Vec<BlockOperation>,
+        new_size: u64,
+    },
+    
     /// File deletion
     Deleted,
 }
@@ -305,6 +753,49 @@ pub enum DiffOperation {
     },
 }
 
+/// A run of changed lines with the unchanged lines around them, as in a
+/// unified diff. Line numbers count from 1; a hunk that only adds lines
+/// starts at the original line the additions go before.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiffHunk {
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+    pub lines: Vec<HunkLine>,
+}
+
+/// One line of a diff hunk
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "text", rename_all = "snake_case")]
+pub enum HunkLine {
+    Context(String),
+    Added(String),
+    Removed(String),
+}
+
+/// A file before and after a checkpoint that changed it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileChangeDiff {
+    pub path: PathBuf,
+    pub checkpoint_id: CheckpointId,
+    /// The earlier checkpoint the file is compared with, if it existed before
+    pub previous_checkpoint_id: Option<CheckpointId>,
+    pub change_type: ChangeType,
+    /// Empty when the checkpoint created the file
+    pub old_content: String,
+    /// Empty when the checkpoint deleted the file
+    pub new_content: String,
+}
+
+/// A file's content to checkpoint in place of its live content, so a
+/// checkpoint can hold only some of the changes made since the last one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StagedFile {
+    pub path: PathBuf,
+    pub content: String,
+}
+
 /// Binary patch for efficient byte-level changes
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BinaryPatch {
@@ -319,6 +810,15 @@ pub enum BinaryOperation {
     Replace(Vec<u8>),
 }
 
+/// Block delta operation
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum BlockOperation {
+    /// Copy `count` consecutive blocks of the base starting at `block`
+    Copy { block: u32, count: u32 },
+    /// Content not found in the base
+    Literal(String),
+}
+
 /// File data for synchronization
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncFile {
@@ -360,12 +860,71 @@ pub enum ResolutionStrategy {
     SideBySide,                     // Present both versions
 }
 
+/// Why an API request failed, so clients can react without parsing the message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    BadRequest,
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    /// The request clashes with the current state, e.g. a name already taken
+    Conflict,
+    PayloadTooLarge,
+    QuotaExceeded,
+    RateLimited,
+    /// The project is archived or the server is a read-only replica
+    ReadOnly,
+    Unavailable,
+    /// Also used for codes from newer servers this client doesn't know
+    #[serde(other)]
+    Internal,
+}
+
+impl ErrorCode {
+    /// HTTP status a server answers with for this code
+    pub fn http_status(self) -> u16 {
+        match self {
+            Self::BadRequest => 400,
+            Self::Unauthorized => 401,
+            Self::Forbidden => 403,
+            Self::NotFound => 404,
+            Self::Conflict => 409,
+            Self::PayloadTooLarge => 413,
+            Self::ReadOnly => 423,
+            Self::RateLimited => 429,
+            Self::Internal => 500,
+            Self::Unavailable => 503,
+            Self::QuotaExceeded => 507,
+        }
+    }
+
+    /// The code for a response that only carries an HTTP status
+    pub fn from_http_status(status: u16) -> Self {
+        match status {
+            401 => Self::Unauthorized,
+            403 => Self::Forbidden,
+            404 => Self::NotFound,
+            409 => Self::Conflict,
+            413 => Self::PayloadTooLarge,
+            423 => Self::ReadOnly,
+            429 => Self::RateLimited,
+            502..=504 => Self::Unavailable,
+            507 => Self::QuotaExceeded,
+            400..=499 => Self::BadRequest,
+            _ => Self::Internal,
+        }
+    }
+}
+
 /// HTTP API messages (REST endpoints)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApiResponse<T> {
     pub success: bool,
     pub data: Option<T>,
     pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<ErrorCode>,
     pub message: Option<String>,
 }
 
@@ -375,6 +934,7 @@ impl<T> ApiResponse<T> {
             success: true,
             data: Some(data),
             error: None,
+            code: None,
             message: None,
         }
     }
@@ -384,24 +944,601 @@ impl<T> ApiResponse<T> {
             success: false,
             data: None,
             error: Some(error),
+            code: None,
             message: None,
         }
     }
+
+    /// An error clients can tell apart by its code
+    pub fn failure(code: ErrorCode, error: String) -> Self {
+        Self {
+            code: Some(code),
+            ..Self::error(error)
+        }
+    }
     
     pub fn message(message: String) -> Self {
         Self {
             success: true,
             data: None,
             error: None,
+            code: None,
             message: Some(message),
         }
     }
 }
 
+/// Request body for restoring selected files or directories from a checkpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestorePathsRequest {
+    /// Project-relative paths; a directory restores every file beneath it
+    pub paths: Vec<PathBuf>,
+}
+
+/// Content type of a streamed restore
+pub const RESTORE_STREAM_CONTENT_TYPE: &str = "application/x-ndjson";
+
+/// One line of a streamed restore: a header, a line per file, then `Done`
+/// or, if the server failed part way, `Error`. A stream that ends without
+/// either was cut off.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RestoreStreamLine {
+    Header {
+        checkpoint: Checkpoint,
+        /// Files that follow and their total size, for progress
+        file_count: usize,
+        total_bytes: u64,
+        /// Checkpoint of the rift from just before the restore
+        safety_checkpoint: Option<CheckpointId>,
+    },
+    File {
+        path: PathBuf,
+        content: String,
+    },
+    Done,
+    Error {
+        message: String,
+    },
+}
+
+/// Request body for reverting a checkpoint
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RevertCheckpointRequest {
+    /// Rift to record the revert in (defaults to the caller's rift in the project)
+    #[serde(default)]
+    pub rift_id: Option<RiftId>,
+}
+
+/// Result of undoing a checkpoint's changes with a new checkpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointRevertResult {
+    pub reverted_checkpoint: CheckpointId,
+    pub rift_id: RiftId,
+    /// The new checkpoint, or None if nothing was reverted
+    pub checkpoint_id: Option<CheckpointId>,
+    pub reverted_files: usize,
+    /// Files changed again since the checkpoint in ways the revert can't undo cleanly
+    pub conflicts: Vec<PathBuf>,
+}
+
+/// A checkpoint refused so the rift's history can't fork: the rift moved on
+/// from the parent the client expected, or another checkpoint on it took too
+/// long to finish
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointConflict {
+    pub rift_id: RiftId,
+    pub reason: CheckpointConflictReason,
+    /// The parent the client asked to build on, if it named one
+    pub expected_parent: Option<CheckpointId>,
+    /// The rift's latest checkpoint when the request was refused
+    pub head: Option<CheckpointId>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckpointConflictReason {
+    /// Another checkpoint landed after the expected parent
+    ParentMoved,
+    /// Another checkpoint on the rift was still being written; try again
+    Busy,
+}
+
+impl std::fmt::Display for CheckpointConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let short = |id: Option<CheckpointId>| id.map(|id| id.to_string()[..8].to_string()).unwrap_or_else(|| "none".to_string());
+        match self.reason {
+            CheckpointConflictReason::ParentMoved => write!(
+                f,
+                "rift {} moved on to checkpoint {} (expected {})",
+                self.rift_id, short(self.head), short(self.expected_parent)
+            ),
+            CheckpointConflictReason::Busy => write!(f, "another checkpoint on rift {} is still being created", self.rift_id),
+        }
+    }
+}
+
+impl std::error::Error for CheckpointConflict {}
+
+/// A rift in a project's history graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryGraphRift {
+    pub id: RiftId,
+    pub name: String,
+    /// Rift this one was created from
+    pub parent_rift: Option<RiftId>,
+    pub created_at: DateTime<Utc>,
+    /// Created to hold changes that conflicted with `parent_rift`
+    pub is_conflict_rift: bool,
+}
+
+/// Rifts and checkpoints of a project with enough topology to draw them as a graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryGraph {
+    pub rifts: Vec<HistoryGraphRift>,
+    /// Newest first, across all rifts; `parent` and `merged_from` link them
+    pub checkpoints: Vec<Checkpoint>,
+}
+
+/// What happened in a project activity event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityKind {
+    Checkpoint,
+    Merge,
+    RiftCreated,
+    MemberAdded,
+    MemberRemoved,
+    Restore,
+}
+
+/// One entry of a project's activity feed (`GET /projects/:id/activity`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityEvent {
+    pub kind: ActivityKind,
+    pub occurred_at: DateTime<Utc>,
+    /// Username of whoever did it, if known
+    pub actor: Option<String>,
+    pub rift_id: Option<RiftId>,
+    pub rift_name: Option<String>,
+    pub checkpoint_id: Option<CheckpointId>,
+    /// One line describing the event
+    pub summary: String,
+}
+
+/// A page of a project's activity feed, newest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityPage {
+    pub events: Vec<ActivityEvent>,
+    /// Pass as `before` to fetch the next, older page; None on the last page
+    pub next_before: Option<DateTime<Utc>>,
+}
+
+/// Request body for reporting a status check on a checkpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateStatusRequest {
+    pub state: StatusState,
+    pub context: String,
+    pub target_url: Option<String>,
+    pub description: Option<String>,
+}
+
+/// A comment thread on a line range of a file at a checkpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommentThread {
+    /// Also the id of the thread's first comment
+    pub id: Uuid,
+    pub checkpoint_id: CheckpointId,
+    pub path: PathBuf,
+    /// First and last line commented on, counting from 1
+    pub line_start: u32,
+    pub line_end: u32,
+    pub resolved_by: Option<UserId>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    /// Oldest first; the first one started the thread
+    pub comments: Vec<ThreadComment>,
+}
+
+impl CommentThread {
+    pub fn is_resolved(&self) -> bool {
+        self.resolved_at.is_some()
+    }
+}
+
+/// One comment in a thread
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadComment {
+    pub id: Uuid,
+    pub author: UserId,
+    pub author_name: String,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request body for starting a comment thread on a checkpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateCommentRequest {
+    pub path: PathBuf,
+    pub line_start: u32,
+    /// Defaults to `line_start`, commenting on a single line
+    #[serde(default)]
+    pub line_end: Option<u32>,
+    pub body: String,
+}
+
+/// Request body for replying to a comment thread
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplyCommentRequest {
+    pub body: String,
+}
+
+/// A file that could not be merged automatically
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeConflict {
+    pub path: PathBuf,
+    /// Number of conflicting hunks (0 for modify/delete conflicts)
+    pub conflicts: usize,
+    /// Merged content with conflict markers, or None if one side deleted the file
+    pub content: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiftMergeResult {
+    pub merged: bool,
+    pub source_rift_id: RiftId,
+    pub target_rift_id: RiftId,
+    pub checkpoint_id: Option<CheckpointId>,
+    pub merged_files: usize,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+/// A request to merge one rift into another, merged once enough reviewers approve
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewRequest {
+    pub id: Uuid,
+    pub project_id: ProjectId,
+    pub source_rift_id: RiftId,
+    pub source_rift_name: String,
+    pub target_rift_id: RiftId,
+    pub target_rift_name: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub author: UserId,
+    pub author_name: String,
+    pub state: ReviewState,
+    /// Approvals needed before the rift may be merged
+    pub required_approvals: u32,
+    pub reviewers: Vec<Reviewer>,
+    /// Oldest first
+    pub comments: Vec<ReviewComment>,
+    /// The checkpoint the merge created on the target rift
+    pub merge_checkpoint: Option<CheckpointId>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ReviewRequest {
+    pub fn approvals(&self) -> usize {
+        self.reviewers.iter().filter(|reviewer| reviewer.approved_at.is_some()).count()
+    }
+
+    pub fn is_approved(&self) -> bool {
+        self.approvals() >= self.required_approvals as usize
+    }
+}
+
+/// Someone asked to review a request, or who approved it unasked
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reviewer {
+    pub user_id: UserId,
+    pub username: String,
+    pub approved_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewComment {
+    pub id: Uuid,
+    pub author: UserId,
+    pub author_name: String,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request body for opening a review request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenReviewRequest {
+    /// Name of the rift to merge
+    pub source_rift: String,
+    /// Name of the rift to merge into; defaults to the source's parent, or "main"
+    #[serde(default)]
+    pub target_rift: Option<String>,
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Usernames of project members to ask for a review
+    #[serde(default)]
+    pub reviewers: Vec<String>,
+    /// Approvals to wait for; never fewer than the server requires
+    #[serde(default)]
+    pub required_approvals: Option<u32>,
+}
+
+/// Request body for asking more project members to review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddReviewersRequest {
+    pub reviewers: Vec<String>,
+}
+
+/// Request body for approving a review request, optionally with a comment
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApproveReviewRequest {
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+/// Request body for commenting on a review request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewCommentRequest {
+    pub body: String,
+}
+
+/// What an in-app notification is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InboxKind {
+    /// Someone @mentioned you in rift chat or a comment
+    Mention,
+    /// Someone asked you to review a merge
+    ReviewRequested,
+    /// A conflict rift was created from a rift you work in
+    ConflictRift,
+    /// A rift was shared with you
+    Invitation,
+}
+
+impl InboxKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InboxKind::Mention => "mention",
+            InboxKind::ReviewRequested => "review_requested",
+            InboxKind::ConflictRift => "conflict_rift",
+            InboxKind::Invitation => "invitation",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "mention" => Some(InboxKind::Mention),
+            "review_requested" => Some(InboxKind::ReviewRequested),
+            "conflict_rift" => Some(InboxKind::ConflictRift),
+            "invitation" => Some(InboxKind::Invitation),
+            _ => None,
+        }
+    }
+}
+
+/// A notification kept for a user until they read it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InboxNotification {
+    pub id: Uuid,
+    pub kind: InboxKind,
+    pub title: String,
+    pub body: String,
+    pub project_id: Option<ProjectId>,
+    /// Who caused it, if anyone
+    pub actor: Option<UserId>,
+    pub actor_name: Option<String>,
+    pub read_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl InboxNotification {
+    pub fn is_read(&self) -> bool {
+        self.read_at.is_some()
+    }
+}
+
+/// A page of `GET /users/me/notifications`, newest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InboxPage {
+    pub notifications: Vec<InboxNotification>,
+    /// Unread notifications in total, not just on this page
+    pub unread: u64,
+}
+
+/// A user a rift has been shared with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiftPermission {
+    pub user_id: UserId,
+    pub username: String,
+    pub role: RiftRole,
+    pub granted_at: DateTime<Utc>,
+}
+
+/// Access settings of a rift
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiftAccessInfo {
+    pub rift_id: RiftId,
+    pub visibility: RiftVisibility,
+    pub owner: Option<UserId>,
+    pub shared_with: Vec<RiftPermission>,
+}
+
+/// Request body for sharing a rift with a user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareRiftRequest {
+    pub username: String,
+    pub role: RiftRole,
+}
+
+/// Request body for changing who may join a rift
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateRiftVisibilityRequest {
+    pub visibility: RiftVisibility,
+}
+
+/// Request body for renaming a rift
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenameRiftRequest {
+    pub name: String,
+}
+
+/// Result of deleting a rift
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiftDeletion {
+    pub rift_id: RiftId,
+    pub name: String,
+    pub checkpoints_removed: usize,
+}
+
+/// Which events a user receives email notifications for
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, sqlx::FromRow)]
+pub struct NotificationPreferences {
+    /// A rift was shared with you
+    pub invitations: bool,
+    /// Someone @mentioned you in rift chat
+    pub mentions: bool,
+    /// A conflict rift was created from a rift you work in
+    pub conflict_rifts: bool,
+    /// A checkpoint of a rift you work in was restored
+    pub checkpoint_restores: bool,
+}
+
+impl Default for NotificationPreferences {
+    fn default() -> Self {
+        Self {
+            invitations: true,
+            mentions: true,
+            conflict_rifts: true,
+            checkpoint_restores: true,
+        }
+    }
+}
+
+/// Request body for `PATCH /users/me/notifications/preferences`; omitted fields are unchanged
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateNotificationPreferencesRequest {
+    pub invitations: Option<bool>,
+    pub mentions: Option<bool>,
+    pub conflict_rifts: Option<bool>,
+    pub checkpoint_restores: Option<bool>,
+}
+
 /// Gateway listing request
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GatewayRequest {
     pub include_inactive: bool,
+    /// List archived projects instead of active ones
+    #[serde(default)]
+    pub archived: bool,
+    /// Page size; the server picks one if unset and caps it. A page shorter
+    /// than asked for is the last one.
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// Cursor: list the projects after this one (the last of the previous page)
+    #[serde(default)]
+    pub before: Option<ProjectId>,
+}
+
+/// Archive project request
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArchiveProjectRequest {
+    /// Also prune auto-generated checkpoints (keeping each rift's latest)
+    #[serde(default)]
+    pub compact: bool,
+}
+
+/// Result of archiving or unarchiving a project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectArchiveStatus {
+    pub project_id: ProjectId,
+    pub archived_at: Option<DateTime<Utc>>,
+    /// Checkpoints removed by compaction
+    pub checkpoints_pruned: usize,
+}
+
+/// Partial update of a project's settings; omitted fields are left unchanged
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateProjectSettingsRequest {
+    pub conflict_policy: Option<ConflictPolicy>,
+    /// Replaces the project's push rules as a whole
+    #[serde(default)]
+    pub push_rules: Option<PushRules>,
+    #[serde(default)]
+    pub secret_scanning: Option<SecretScanMode>,
+    /// Seconds between automatic checkpoints (0 turns them off)
+    #[serde(default)]
+    pub auto_checkpoint_interval: Option<u64>,
+    /// Replaces the patterns `restrict_file_types` accepts
+    #[serde(default)]
+    pub allowed_file_types: Option<Vec<String>>,
+    #[serde(default)]
+    pub visibility: Option<ProjectVisibility>,
+}
+
+/// Request body for compacting a project's history
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CompactHistoryRequest {
+    /// Only fold checkpoints older than this many days (defaults to the server's setting)
+    #[serde(default)]
+    pub older_than_days: Option<u32>,
+}
+
+/// Result of compacting a project's history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryCompaction {
+    pub project_id: ProjectId,
+    /// Checkpoints left in place of runs of automatic checkpoints
+    pub summaries: usize,
+    /// Automatic checkpoints folded into them and removed
+    pub checkpoints_removed: usize,
+}
+
+/// Result of importing a project bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectImportResult {
+    pub project_id: ProjectId,
+    pub name: String,
+    pub rifts: usize,
+    pub checkpoints: usize,
+}
+
+/// A page of a primary server's checkpoint log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicationLogPage {
+    /// Checkpoints in the order they were written
+    pub checkpoints: Vec<Checkpoint>,
+    /// Log position to ask for next
+    pub next_position: u64,
+}
+
+/// Everything a secondary needs to serve beams and history of a rift
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicatedRift {
+    pub project: Project,
+    pub project_archived_at: Option<DateTime<Utc>>,
+    pub rift: Rift,
+    pub owner: Option<UserId>,
+    pub visibility: RiftVisibility,
+    pub permissions: Vec<RiftPermission>,
+    /// Project members, rift collaborators and users the rift is shared with
+    pub users: Vec<User>,
+    /// Working state: path -> content hash
+    pub live_files: HashMap<PathBuf, String>,
+}
+
+/// Where a server stands in replication
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicationStatus {
+    /// "standalone", "primary" or "secondary"
+    pub role: String,
+    pub region: String,
+    /// Primary being followed (secondaries only)
+    pub primary_url: Option<String>,
+    /// Checkpoint log position: written so far on a primary, applied so far on a secondary
+    pub position: u64,
+    pub last_synced_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
 }
 
 /// Beam (project join) request
@@ -482,4 +1619,22 @@ pub struct ConflictRiftInfo {
     pub rift_id: Uuid,
     pub rift_name: String,
     pub description: Option<String>,
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_codes_round_trip_and_tolerate_unknown_codes() {
+        let json = serde_json::to_string(&ApiResponse::<()>::failure(ErrorCode::QuotaExceeded, "Full".to_string())).unwrap();
+        assert!(json.contains(r#""code":"quota_exceeded""#));
+        assert_eq!(ErrorCode::from_http_status(ErrorCode::QuotaExceeded.http_status()), ErrorCode::QuotaExceeded);
+
+        let newer: ApiResponse<()> =
+            serde_json::from_str(r#"{"success":false,"data":null,"error":"x","code":"brand_new","message":null}"#).unwrap();
+        assert_eq!(newer.code, Some(ErrorCode::Internal));
+        let older: ApiResponse<()> = serde_json::from_str(r#"{"success":false,"data":null,"error":"x","message":null}"#).unwrap();
+        assert_eq!(older.code, None);
+    }
+}
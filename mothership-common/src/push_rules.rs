@@ -0,0 +1,203 @@
+//! Push rules: per-project checks the server applies before accepting
+//! checkpoints and synced file changes.
+//!
+//! Rules live in `ProjectSettings::push_rules`. Deny patterns use
+//! `.mothershipignore` (gitignore) syntax, and the file type restriction uses
+//! the project's `allowed_file_types` patterns. Whatever the project says,
+//! paths must stay inside the project, and the server may cap file sizes.
+
+use crate::ignore::IgnoreMatcher;
+use crate::secrets::SecretFinding;
+use crate::ProjectSettings;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+
+/// A project's push rules. The defaults enforce nothing.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PushRules {
+    /// Manual checkpoints must have a non-empty message
+    pub require_checkpoint_message: bool,
+    /// Largest file accepted, in bytes
+    pub max_file_size: Option<u64>,
+    /// Paths that may not be synced or checkpointed
+    pub deny_paths: Vec<String>,
+    /// Only accept files matching the project's `allowed_file_types`
+    pub restrict_file_types: bool,
+}
+
+/// Which rule a change broke
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PushRule {
+    CheckpointMessage,
+    MaxFileSize,
+    DenyPath,
+    FileType,
+    /// Secret scanning found a credential and the project rejects them
+    Secret,
+    /// Not a relative path inside the project
+    InvalidPath,
+}
+
+/// A change refused by a push rule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PushRuleViolation {
+    pub rule: PushRule,
+    /// The offending file, for file rules
+    pub path: Option<PathBuf>,
+    pub message: String,
+}
+
+impl From<&SecretFinding> for PushRuleViolation {
+    fn from(finding: &SecretFinding) -> Self {
+        Self {
+            rule: PushRule::Secret,
+            path: Some(finding.path.clone()),
+            message: format!("{} found", finding.describe()),
+        }
+    }
+}
+
+/// One line per violation, for error messages
+pub fn describe_violations(violations: &[PushRuleViolation]) -> String {
+    violations.iter().map(|v| v.message.as_str()).collect::<Vec<_>>().join("; ")
+}
+
+/// Push rules compiled for checking many files
+pub struct PushRuleChecker {
+    rules: PushRules,
+    deny: IgnoreMatcher,
+    allowed: Option<IgnoreMatcher>,
+    /// The server's own file size limit, applied on top of the project's
+    server_max_file_size: Option<u64>,
+}
+
+impl PushRuleChecker {
+    pub fn new(settings: &ProjectSettings) -> Self {
+        let rules = settings.push_rules.clone();
+        Self {
+            deny: IgnoreMatcher::from_patterns("", &rules.deny_paths),
+            allowed: rules.restrict_file_types
+                .then(|| IgnoreMatcher::from_patterns("", &settings.allowed_file_types)),
+            rules,
+            server_max_file_size: None,
+        }
+    }
+
+    /// Also refuse files over the server's limit, whatever the project allows
+    pub fn with_server_max_file_size(mut self, max: u64) -> Self {
+        self.server_max_file_size = Some(max);
+        self
+    }
+
+    /// Whether any rule is switched on
+    pub fn is_active(&self) -> bool {
+        self.rules != PushRules::default() || self.server_max_file_size.is_some()
+    }
+
+    /// Largest file accepted, by the project's rules or the server's limit
+    fn max_file_size(&self) -> Option<u64> {
+        match (self.rules.max_file_size, self.server_max_file_size) {
+            (Some(project), Some(server)) => Some(project.min(server)),
+            (project, server) => project.or(server),
+        }
+    }
+
+    /// Check a file about to be synced, uploaded or checkpointed
+    pub fn check_file(&self, path: &Path, size: u64) -> Vec<PushRuleViolation> {
+        if !is_project_path(path) {
+            return vec![PushRuleViolation {
+                rule: PushRule::InvalidPath,
+                path: Some(path.to_path_buf()),
+                message: format!("{} is not a path inside the project", path.display()),
+            }];
+        }
+
+        let mut violations = Vec::new();
+
+        if let Some(max) = self.max_file_size() {
+            if size > max {
+                violations.push(PushRuleViolation {
+                    rule: PushRule::MaxFileSize,
+                    path: Some(path.to_path_buf()),
+                    message: format!("{} is {} bytes; files may be at most {} bytes", path.display(), size, max),
+                });
+            }
+        }
+
+        if self.deny.is_ignored(path, false) {
+            violations.push(PushRuleViolation {
+                rule: PushRule::DenyPath,
+                path: Some(path.to_path_buf()),
+                message: format!("{} matches a denied path pattern", path.display()),
+            });
+        }
+
+        if let Some(allowed) = &self.allowed {
+            if !allowed.is_ignored(path, false) {
+                violations.push(PushRuleViolation {
+                    rule: PushRule::FileType,
+                    path: Some(path.to_path_buf()),
+                    message: format!("{} is not one of the project's allowed file types", path.display()),
+                });
+            }
+        }
+
+        violations
+    }
+
+    /// Check a checkpoint's message. Auto-generated checkpoints need none.
+    pub fn check_checkpoint_message(&self, message: Option<&str>, auto_generated: bool) -> Option<PushRuleViolation> {
+        let missing = message.map(str::trim).unwrap_or("").is_empty();
+        (self.rules.require_checkpoint_message && !auto_generated && missing).then(|| PushRuleViolation {
+            rule: PushRule::CheckpointMessage,
+            path: None,
+            message: "Checkpoints in this project need a message".to_string(),
+        })
+    }
+}
+
+/// Whether a path is relative and stays inside the project
+pub fn is_project_path(path: &Path) -> bool {
+    !path.as_os_str().is_empty()
+        && path.components().all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_rules() {
+        let mut settings = ProjectSettings::default();
+        assert!(!PushRuleChecker::new(&settings).is_active());
+
+        settings.push_rules = PushRules {
+            require_checkpoint_message: true,
+            max_file_size: Some(10),
+            deny_paths: vec!["secrets/".to_string(), "*.pem".to_string()],
+            restrict_file_types: true,
+        };
+        let checker = PushRuleChecker::new(&settings);
+
+        assert!(checker.check_file(Path::new("src/main.rs"), 5).is_empty());
+        assert_eq!(checker.check_file(Path::new("src/main.rs"), 11)[0].rule, PushRule::MaxFileSize);
+        assert_eq!(checker.check_file(Path::new("secrets/db.toml"), 5)[0].rule, PushRule::DenyPath);
+        let rules: Vec<PushRule> = checker.check_file(Path::new("certs/server.pem"), 5).iter().map(|v| v.rule).collect();
+        assert_eq!(rules, vec![PushRule::DenyPath, PushRule::FileType]);
+
+        assert_eq!(checker.check_file(Path::new("../outside.rs"), 5)[0].rule, PushRule::InvalidPath);
+        assert_eq!(checker.check_file(Path::new("/etc/passwd.txt"), 5)[0].rule, PushRule::InvalidPath);
+
+        let capped = PushRuleChecker::new(&ProjectSettings::default()).with_server_max_file_size(8);
+        assert!(capped.is_active());
+        assert!(capped.check_file(Path::new("notes.txt"), 8).is_empty());
+        assert_eq!(capped.check_file(Path::new("notes.txt"), 9)[0].rule, PushRule::MaxFileSize);
+        assert_eq!(PushRuleChecker::new(&settings).with_server_max_file_size(100).max_file_size(), Some(10));
+
+        assert!(checker.check_checkpoint_message(Some("Fix parser"), false).is_none());
+        assert!(checker.check_checkpoint_message(Some("  "), false).is_some());
+        assert!(checker.check_checkpoint_message(None, true).is_none());
+    }
+}
@@ -0,0 +1,134 @@
+//! Startup reconciliation: catching up after the daemon was stopped.
+//!
+//! When it joins a rift the daemon sends a manifest of its files: the hash of
+//! each file's current content and the hash it had when last synced with the
+//! server. Comparing both against the server's copy tells which side changed
+//! each file while they were apart, so the server can answer with exactly
+//! what each side has to take from the other.
+
+use crate::upload::content_hash;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+
+/// A client's view of one file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Hash of the file's content on disk, `None` if it was deleted
+    pub hash: Option<String>,
+    /// Hash of the content last synced with the server, `None` if never synced
+    pub base: Option<String>,
+}
+
+/// Files keyed by project-relative path
+pub type Manifest = HashMap<PathBuf, ManifestEntry>;
+
+/// What each side has to do for client and server to converge
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReconcilePlan {
+    /// Changed only on the client: it sends its copy, or a deletion
+    #[serde(default)]
+    pub upload: Vec<PathBuf>,
+    /// Changed only on the server: the client writes the server's copy
+    #[serde(default)]
+    pub download: Vec<PathBuf>,
+    /// Deleted on the server and unchanged on the client: the client deletes its copy
+    #[serde(default)]
+    pub delete: Vec<PathBuf>,
+    /// Changed differently on both sides: resolved with the conflict policy
+    #[serde(default)]
+    pub conflicts: Vec<PathBuf>,
+}
+
+impl ReconcilePlan {
+    pub fn is_empty(&self) -> bool {
+        self.upload.is_empty() && self.download.is_empty() && self.delete.is_empty() && self.conflicts.is_empty()
+    }
+
+    /// Drop every path `keep` rejects
+    pub fn retain(&mut self, mut keep: impl FnMut(&PathBuf) -> bool) {
+        self.upload.retain(&mut keep);
+        self.download.retain(&mut keep);
+        self.delete.retain(&mut keep);
+        self.conflicts.retain(&mut keep);
+    }
+}
+
+/// Compare a client's manifest with the server's files. Files the manifest
+/// doesn't mention are treated as never synced and missing on the client.
+pub fn plan(manifest: &Manifest, server_files: &HashMap<PathBuf, String>) -> ReconcilePlan {
+    let paths: BTreeSet<&PathBuf> = manifest.keys().chain(server_files.keys()).collect();
+    let mut plan = ReconcilePlan::default();
+
+    for path in paths {
+        let entry = manifest.get(path).cloned().unwrap_or_default();
+        let server = server_files.get(path).map(|content| content_hash(content.as_bytes()));
+        if entry.hash == server {
+            continue;
+        }
+
+        let client_changed = entry.hash != entry.base;
+        let server_changed = server != entry.base;
+        let target = match (client_changed, server_changed) {
+            (true, false) => &mut plan.upload,
+            (false, _) if server.is_some() => &mut plan.download,
+            (false, _) => &mut plan.delete,
+            // A deletion on one side loses to an edit on the other
+            (true, true) if server.is_none() => &mut plan.upload,
+            (true, true) if entry.hash.is_none() => &mut plan.download,
+            (true, true) => &mut plan.conflicts,
+        };
+        target.push(path.clone());
+    }
+
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(hash: Option<&str>, base: Option<&str>) -> ManifestEntry {
+        ManifestEntry {
+            hash: hash.map(|c| content_hash(c.as_bytes())),
+            base: base.map(|c| content_hash(c.as_bytes())),
+        }
+    }
+
+    #[test]
+    fn test_plan_sorts_each_change_to_the_side_that_missed_it() {
+        let manifest: Manifest = [
+            ("same.rs", entry(Some("a"), Some("a"))),
+            ("edited_offline.rs", entry(Some("new"), Some("old"))),
+            ("edited_on_server.rs", entry(Some("old"), Some("old"))),
+            ("edited_both.rs", entry(Some("mine"), Some("old"))),
+            ("deleted_offline.rs", entry(None, Some("old"))),
+            ("deleted_on_server.rs", entry(Some("old"), Some("old"))),
+            ("created_offline.rs", entry(Some("new"), None)),
+            ("deleted_offline_edited_on_server.rs", entry(None, Some("old"))),
+        ]
+        .into_iter()
+        .map(|(path, entry)| (PathBuf::from(path), entry))
+        .collect();
+
+        let server: HashMap<PathBuf, String> = [
+            ("same.rs", "a"),
+            ("edited_offline.rs", "old"),
+            ("edited_on_server.rs", "theirs"),
+            ("edited_both.rs", "theirs"),
+            ("deleted_offline.rs", "old"),
+            ("created_on_server.rs", "new"),
+            ("deleted_offline_edited_on_server.rs", "theirs"),
+        ]
+        .into_iter()
+        .map(|(path, content)| (PathBuf::from(path), content.to_string()))
+        .collect();
+
+        let paths = |names: &[&str]| names.iter().map(PathBuf::from).collect::<Vec<_>>();
+        let plan = plan(&manifest, &server);
+        assert_eq!(plan.upload, paths(&["created_offline.rs", "deleted_offline.rs", "edited_offline.rs"]));
+        assert_eq!(plan.download, paths(&["created_on_server.rs", "deleted_offline_edited_on_server.rs", "edited_on_server.rs"]));
+        assert_eq!(plan.delete, paths(&["deleted_on_server.rs"]));
+        assert_eq!(plan.conflicts, paths(&["edited_both.rs"]));
+    }
+}
@@ -0,0 +1,381 @@
+//! Release channels, artifact metadata and delta patches for self-hosted
+//! CLI and daemon updates.
+//!
+//! Servers publish each artifact's SHA-256 and, when the release is signed,
+//! an Ed25519 signature over the binary. A client updating from a version
+//! the server still has can download a delta patch instead of the whole
+//! binary; the rebuilt binary must match the full artifact's checksum.
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::upload::content_hash;
+
+/// How early a release is. Each channel also offers the releases of the
+/// steadier channels, so beta users get stable releases newer than the
+/// latest beta.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReleaseChannel {
+    #[default]
+    Stable,
+    Beta,
+    Nightly,
+}
+
+impl ReleaseChannel {
+    pub const ALL: [ReleaseChannel; 3] = [ReleaseChannel::Stable, ReleaseChannel::Beta, ReleaseChannel::Nightly];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReleaseChannel::Stable => "stable",
+            ReleaseChannel::Beta => "beta",
+            ReleaseChannel::Nightly => "nightly",
+        }
+    }
+
+    /// Whether users on this channel get releases published to `other`
+    pub fn includes(&self, other: ReleaseChannel) -> bool {
+        other <= *self
+    }
+}
+
+impl fmt::Display for ReleaseChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ReleaseChannel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ReleaseChannel::ALL
+            .into_iter()
+            .find(|channel| channel.as_str() == s.trim().to_lowercase())
+            .ok_or_else(|| format!("Unknown release channel '{}' (expected stable, beta or nightly)", s))
+    }
+}
+
+/// A release of the CLI and daemon for one platform
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseManifest {
+    pub version: String,
+    pub channel: ReleaseChannel,
+    pub platform: String,
+    pub release_date: chrono::DateTime<chrono::Utc>,
+    pub changes: Vec<String>,
+    pub artifacts: Vec<ReleaseArtifact>,
+}
+
+/// One binary of a release
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseArtifact {
+    pub binary: String,
+    pub size: u64,
+    pub sha256: String,
+    /// Hex Ed25519 signature over the binary, if the release is signed
+    pub signature: Option<String>,
+    /// Patch from the version the client asked about, when the server has
+    /// that version and the patch is smaller than the binary
+    pub delta: Option<DeltaPatch>,
+}
+
+/// A delta patch that turns one version's binary into another's
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeltaPatch {
+    pub from_version: String,
+    /// Checksum the local binary must have for the patch to apply
+    pub from_sha256: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// Hex-encoded SHA-256 of an artifact or patch
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    content_hash(bytes)
+}
+
+/// Order release versions like `0.0.41`, `0.1.0-beta.2`: numerically by
+/// component, with a pre-release before the release it leads up to
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let split = |v: &str| -> (Vec<u64>, Option<String>) {
+        let v = v.trim().trim_start_matches('v');
+        let (core, pre) = match v.split_once('-') {
+            Some((core, pre)) => (core, Some(pre.to_string())),
+            None => (v, None),
+        };
+        (core.split('.').map(|part| part.parse().unwrap_or(0)).collect(), pre)
+    };
+    let (a_core, a_pre) = split(a);
+    let (b_core, b_pre) = split(b);
+
+    for i in 0..a_core.len().max(b_core.len()) {
+        match a_core.get(i).unwrap_or(&0).cmp(b_core.get(i).unwrap_or(&0)) {
+            Ordering::Equal => continue,
+            other => return other,
+        }
+    }
+    match (a_pre, b_pre) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a_pre), Some(b_pre)) => compare_pre_release(&a_pre, &b_pre),
+    }
+}
+
+fn compare_pre_release(a: &str, b: &str) -> Ordering {
+    let mut a_parts = a.split('.');
+    let mut b_parts = b.split('.');
+    loop {
+        match (a_parts.next(), b_parts.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a_part), Some(b_part)) => {
+                let order = match (a_part.parse::<u64>(), b_part.parse::<u64>()) {
+                    (Ok(a_num), Ok(b_num)) => a_num.cmp(&b_num),
+                    _ => a_part.cmp(b_part),
+                };
+                if order != Ordering::Equal {
+                    return order;
+                }
+            }
+        }
+    }
+}
+
+/// Check a hex Ed25519 signature over `data` against a hex public key
+pub fn verify_signature(public_key: &str, data: &[u8], signature: &str) -> Result<(), String> {
+    let public_key = decode_hex(public_key.trim()).ok_or("Release public key is not valid hex")?;
+    let signature = decode_hex(signature.trim()).ok_or("Signature is not valid hex")?;
+    ring::signature::UnparsedPublicKey::new(&ring::signature::ED25519, public_key)
+        .verify(data, &signature)
+        .map_err(|_| "Signature does not match the release key".to_string())
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+const DELTA_MAGIC: &[u8; 8] = b"MSDELTA1";
+
+/// Length of the blocks matched between the old and new binary
+const DELTA_BLOCK_SIZE: usize = 64;
+
+/// Most places in the old binary remembered for one block hash
+const DELTA_MAX_CANDIDATES: usize = 8;
+
+const OP_COPY: u8 = 0;
+const OP_INSERT: u8 = 1;
+
+/// rsync-style weak checksum over a window, cheap to slide by one byte
+struct RollingHash {
+    a: u32,
+    b: u32,
+}
+
+impl RollingHash {
+    fn new(window: &[u8]) -> Self {
+        let len = window.len() as u32;
+        let mut hash = RollingHash { a: 0, b: 0 };
+        for (i, &byte) in window.iter().enumerate() {
+            hash.a = hash.a.wrapping_add(byte as u32);
+            hash.b = hash.b.wrapping_add((len - i as u32).wrapping_mul(byte as u32));
+        }
+        hash
+    }
+
+    fn roll(&mut self, out: u8, incoming: u8) {
+        self.a = self.a.wrapping_sub(out as u32).wrapping_add(incoming as u32);
+        self.b = self.b
+            .wrapping_sub((DELTA_BLOCK_SIZE as u32).wrapping_mul(out as u32))
+            .wrapping_add(self.a);
+    }
+
+    fn value(&self) -> u32 {
+        (self.a & 0xffff) | (self.b << 16)
+    }
+}
+
+/// A patch rebuilding `new` from `old`: the stretches `new` shares with
+/// `old` are copied, the rest is carried in the patch
+pub fn create_delta(old: &[u8], new: &[u8]) -> Vec<u8> {
+    let mut patch = DELTA_MAGIC.to_vec();
+    let mut literal_start = 0;
+
+    if old.len() >= DELTA_BLOCK_SIZE && new.len() >= DELTA_BLOCK_SIZE {
+        let mut blocks: HashMap<u32, Vec<usize>> = HashMap::new();
+        for offset in (0..=old.len() - DELTA_BLOCK_SIZE).step_by(DELTA_BLOCK_SIZE) {
+            let candidates = blocks.entry(RollingHash::new(&old[offset..offset + DELTA_BLOCK_SIZE]).value()).or_default();
+            if candidates.len() < DELTA_MAX_CANDIDATES {
+                candidates.push(offset);
+            }
+        }
+
+        let mut pos = 0;
+        let mut hash = RollingHash::new(&new[..DELTA_BLOCK_SIZE]);
+        loop {
+            let best = blocks.get(&hash.value()).and_then(|candidates| {
+                candidates.iter()
+                    .filter(|&&offset| old[offset..offset + DELTA_BLOCK_SIZE] == new[pos..pos + DELTA_BLOCK_SIZE])
+                    .map(|&offset| {
+                        let len = old[offset..].iter().zip(&new[pos..]).take_while(|(a, b)| a == b).count();
+                        (offset, len)
+                    })
+                    .max_by_key(|&(_, len)| len)
+            });
+
+            if let Some((offset, len)) = best {
+                push_insert(&mut patch, &new[literal_start..pos]);
+                push_copy(&mut patch, offset, len);
+                pos += len;
+                literal_start = pos;
+                if pos + DELTA_BLOCK_SIZE > new.len() {
+                    break;
+                }
+                hash = RollingHash::new(&new[pos..pos + DELTA_BLOCK_SIZE]);
+            } else {
+                if pos + DELTA_BLOCK_SIZE >= new.len() {
+                    break;
+                }
+                hash.roll(new[pos], new[pos + DELTA_BLOCK_SIZE]);
+                pos += 1;
+            }
+        }
+    }
+
+    push_insert(&mut patch, &new[literal_start..]);
+    patch
+}
+
+fn push_copy(patch: &mut Vec<u8>, offset: usize, len: usize) {
+    patch.push(OP_COPY);
+    patch.extend_from_slice(&(offset as u64).to_le_bytes());
+    patch.extend_from_slice(&(len as u64).to_le_bytes());
+}
+
+fn push_insert(patch: &mut Vec<u8>, bytes: &[u8]) {
+    if bytes.is_empty() {
+        return;
+    }
+    patch.push(OP_INSERT);
+    patch.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+    patch.extend_from_slice(bytes);
+}
+
+/// Rebuild a binary from the one it was diffed against and a patch made
+/// by [`create_delta`]
+pub fn apply_delta(old: &[u8], patch: &[u8]) -> Result<Vec<u8>, String> {
+    let mut rest = patch.strip_prefix(DELTA_MAGIC.as_slice()).ok_or("Not a delta patch")?;
+    let mut take = |len: usize| -> Result<&[u8], String> {
+        if rest.len() < len {
+            return Err("Delta patch is truncated".to_string());
+        }
+        let (head, tail) = rest.split_at(len);
+        rest = tail;
+        Ok(head)
+    };
+    let read_u64 = |bytes: &[u8]| u64::from_le_bytes(bytes.try_into().unwrap()) as usize;
+
+    let mut new = Vec::new();
+    while let Ok(op) = take(1) {
+        match op[0] {
+            OP_COPY => {
+                let offset = read_u64(take(8)?);
+                let len = read_u64(take(8)?);
+                let end = offset.checked_add(len).filter(|&end| end <= old.len())
+                    .ok_or("Delta patch copies past the end of the old binary")?;
+                new.extend_from_slice(&old[offset..end]);
+            }
+            OP_INSERT => {
+                let len = read_u64(take(8)?);
+                new.extend_from_slice(take(len)?);
+            }
+            other => return Err(format!("Unknown delta operation {}", other)),
+        }
+    }
+    Ok(new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_binary(seed: u32, len: usize) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                (state >> 16) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn channels_include_steadier_ones() {
+        assert_eq!("Beta".parse::<ReleaseChannel>().unwrap(), ReleaseChannel::Beta);
+        assert!("canary".parse::<ReleaseChannel>().is_err());
+        assert!(ReleaseChannel::Beta.includes(ReleaseChannel::Stable));
+        assert!(!ReleaseChannel::Beta.includes(ReleaseChannel::Nightly));
+        assert!(ReleaseChannel::Nightly.includes(ReleaseChannel::Beta));
+    }
+
+    #[test]
+    fn pre_releases_come_before_their_release() {
+        assert_eq!(compare_versions("0.0.26", "0.0.9"), Ordering::Greater);
+        assert_eq!(compare_versions("0.1.0-beta.2", "0.1.0"), Ordering::Less);
+        assert_eq!(compare_versions("0.1.0-beta.10", "0.1.0-beta.2"), Ordering::Greater);
+        assert_eq!(compare_versions("0.1.0", "0.1"), Ordering::Equal);
+    }
+
+    #[test]
+    fn delta_rebuilds_the_new_binary() {
+        let old = sample_binary(7, 20_000);
+        let mut new = old.clone();
+        new.splice(5_000..5_010, b"patched in the middle".iter().copied());
+        new.extend_from_slice(&sample_binary(9, 300));
+        new.drain(12_000..12_500);
+
+        let patch = create_delta(&old, &new);
+        assert!(patch.len() < new.len() / 10);
+        assert_eq!(apply_delta(&old, &patch).unwrap(), new);
+    }
+
+    #[test]
+    fn delta_handles_unrelated_and_tiny_inputs() {
+        let old = sample_binary(1, 1_000);
+        let new = sample_binary(2, 1_500);
+        assert_eq!(apply_delta(&old, &create_delta(&old, &new)).unwrap(), new);
+        assert_eq!(apply_delta(b"", &create_delta(b"", b"tiny")).unwrap(), b"tiny");
+        assert!(apply_delta(&old, b"garbage").is_err());
+
+        let mut truncated = create_delta(&old, &new);
+        truncated.truncate(truncated.len() - 1);
+        assert!(apply_delta(&old, &truncated).is_err());
+    }
+
+    #[test]
+    fn verifies_ed25519_signatures() {
+        use ring::signature::{Ed25519KeyPair, KeyPair};
+
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&ring::rand::SystemRandom::new()).unwrap();
+        let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        let hex = |bytes: &[u8]| bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+        let public_key = hex(key_pair.public_key().as_ref());
+        let signature = hex(key_pair.sign(b"binary").as_ref());
+
+        assert!(verify_signature(&public_key, b"binary", &signature).is_ok());
+        assert!(verify_signature(&public_key, b"tampered", &signature).is_err());
+        assert!(verify_signature(&public_key, b"binary", "zz").is_err());
+    }
+}
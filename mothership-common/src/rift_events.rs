@@ -0,0 +1,116 @@
+//! The per-rift event log.
+//!
+//! The server appends every message it broadcasts on a rift's channel to the
+//! rift's event log, numbered with the same sequence numbers clients see, so
+//! the log records each change applied to the rift's working files in the
+//! order it was applied. Replaying the logged changes from a known state
+//! rebuilds the files deterministically, which is how sync bugs are
+//! reproduced and what the tests below rely on.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::diff::DiffEngine;
+use crate::protocol::{FileDiff, SyncMessage};
+
+/// One logged broadcast on a rift's channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiftEvent {
+    /// Sequence number of the broadcast; increases by one per event
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    pub message: SyncMessage,
+}
+
+/// Apply the file changes of logged events to a rift's working files, in
+/// order. Events that don't change files (chat, presence, checkpoints) are
+/// skipped; events out of order are an error, since applying them would not
+/// give the state the rift went through.
+pub fn replay(files: &mut HashMap<PathBuf, String>, events: &[RiftEvent]) -> Result<()> {
+    let engine = DiffEngine::new();
+    let mut last_seq = None;
+    for event in events {
+        if last_seq.is_some_and(|last| event.seq <= last) {
+            return Err(anyhow!("Event {} is out of order after event {}", event.seq, last_seq.unwrap_or_default()));
+        }
+        last_seq = Some(event.seq);
+
+        let SyncMessage::RiftDiffUpdate { diff_changes, .. } = &event.message else {
+            continue;
+        };
+        for change in diff_changes {
+            if matches!(change.diff, FileDiff::Deleted) {
+                files.remove(&change.path);
+                continue;
+            }
+            let original = files.get(&change.path).map(String::as_str).unwrap_or("");
+            let content = engine.apply_diff(original, &change.diff)
+                .map_err(|e| anyhow!("Event {} doesn't apply to {}: {}", event.seq, change.path.display(), e))?;
+            files.insert(change.path.clone(), content);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::FileDiffChange;
+    use uuid::Uuid;
+
+    fn update(seq: u64, changes: Vec<(&str, FileDiff)>) -> RiftEvent {
+        RiftEvent {
+            seq,
+            timestamp: Utc::now(),
+            message: SyncMessage::RiftDiffUpdate {
+                rift_id: Uuid::nil(),
+                diff_changes: changes.into_iter()
+                    .map(|(path, diff)| FileDiffChange {
+                        path: PathBuf::from(path),
+                        diff,
+                        file_size: 0,
+                        metadata: Default::default(),
+                    })
+                    .collect(),
+                author: Uuid::nil(),
+                timestamp: Utc::now(),
+                compressed: false,
+            },
+        }
+    }
+
+    #[test]
+    fn test_replay_rebuilds_files_in_order() {
+        let engine = DiffEngine::new();
+        let events = vec![
+            update(1, vec![
+                ("main.rs", FileDiff::FullContent("fn main() {}\n".to_string())),
+                ("old.rs", FileDiff::FullContent("// old\n".to_string())),
+            ]),
+            RiftEvent {
+                seq: 2,
+                timestamp: Utc::now(),
+                message: SyncMessage::CollaboratorLeft { rift_id: Uuid::nil(), user_id: Uuid::nil() },
+            },
+            update(3, vec![
+                ("main.rs", engine.generate_line_diff("fn main() {}\n", "fn main() {\n    run();\n}\n")),
+                ("old.rs", FileDiff::Deleted),
+            ]),
+        ];
+
+        // The same events always give the same files
+        for _ in 0..2 {
+            let mut files = HashMap::new();
+            replay(&mut files, &events).unwrap();
+            assert_eq!(files.len(), 1);
+            assert_eq!(files[&PathBuf::from("main.rs")], "fn main() {\n    run();\n}\n");
+        }
+
+        let mut reversed = events.clone();
+        reversed.reverse();
+        assert!(replay(&mut HashMap::new(), &reversed).is_err());
+    }
+}
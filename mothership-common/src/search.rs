@@ -0,0 +1,144 @@
+//! Text search over project files, shared by the server's search endpoint
+//! and clients searching their local copy.
+
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Matches returned when the query doesn't say
+pub const DEFAULT_SEARCH_LIMIT: usize = 200;
+
+/// Most matches one search returns
+pub const MAX_SEARCH_LIMIT: usize = 2000;
+
+/// Longest line preview in a match, in characters
+const MAX_PREVIEW_CHARS: usize = 200;
+
+/// What to search for
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchQuery {
+    pub q: String,
+    /// Treat `q` as a regular expression instead of literal text
+    #[serde(default)]
+    pub regex: bool,
+    #[serde(default)]
+    pub case_sensitive: bool,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// One matching line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchMatch {
+    pub path: PathBuf,
+    /// 1-based line of the match
+    pub line: usize,
+    /// 1-based column (in characters) where the match starts
+    pub column: usize,
+    /// The matching line, trimmed and shortened
+    pub preview: String,
+}
+
+/// Matches of a search, in path then line order
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchResults {
+    pub matches: Vec<SearchMatch>,
+    pub files_searched: usize,
+    /// The limit was reached before every file was searched
+    pub truncated: bool,
+}
+
+/// A compiled search query
+pub struct TextSearch {
+    pattern: Regex,
+    limit: usize,
+}
+
+impl TextSearch {
+    pub fn new(query: &SearchQuery) -> Result<Self, String> {
+        if query.q.is_empty() {
+            return Err("Search for something".to_string());
+        }
+        let source = if query.regex { query.q.clone() } else { regex::escape(&query.q) };
+        let pattern = RegexBuilder::new(&source)
+            .case_insensitive(!query.case_sensitive)
+            .build()
+            .map_err(|e| format!("Invalid pattern: {}", e))?;
+
+        Ok(Self {
+            pattern,
+            limit: query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT).clamp(1, MAX_SEARCH_LIMIT),
+        })
+    }
+
+    /// Add a file's matching lines (the first match of each) to `results`.
+    /// Returns false once the limit is reached and searching should stop.
+    pub fn search_file(&self, path: &Path, content: &str, results: &mut SearchResults) -> bool {
+        if results.matches.len() >= self.limit {
+            results.truncated = true;
+            return false;
+        }
+        results.files_searched += 1;
+
+        for (index, line) in content.lines().enumerate() {
+            let Some(found) = self.pattern.find(line) else { continue };
+            if results.matches.len() >= self.limit {
+                results.truncated = true;
+                return false;
+            }
+            results.matches.push(SearchMatch {
+                path: path.to_path_buf(),
+                line: index + 1,
+                column: line[..found.start()].chars().count() + 1,
+                preview: line.trim().chars().take(MAX_PREVIEW_CHARS).collect(),
+            });
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search(query: SearchQuery, content: &str) -> SearchResults {
+        let mut results = SearchResults::default();
+        TextSearch::new(&query).unwrap().search_file(Path::new("src/lib.rs"), content, &mut results);
+        results
+    }
+
+    #[test]
+    fn literal_search_ignores_case_and_escapes_patterns() {
+        let query = SearchQuery { q: "foo(".to_string(), ..Default::default() };
+        let results = search(query, "let a = 1;\n    let b = FOO(2);\nfoo\n");
+
+        assert_eq!(results.matches.len(), 1);
+        assert_eq!(results.matches[0].line, 2);
+        assert_eq!(results.matches[0].column, 13);
+        assert_eq!(results.matches[0].preview, "let b = FOO(2);");
+    }
+
+    #[test]
+    fn regex_search_respects_case() {
+        let query = SearchQuery { q: r"fn \w+_test".to_string(), regex: true, case_sensitive: true, limit: None };
+        let results = search(query, "fn unit_test() {}\nFN OTHER_TEST() {}\n");
+
+        assert_eq!(results.matches.len(), 1);
+        assert_eq!(results.matches[0].line, 1);
+    }
+
+    #[test]
+    fn stops_at_the_limit() {
+        let query = SearchQuery { q: "x".to_string(), limit: Some(2), ..Default::default() };
+        let results = search(query, "x\nx\nx\n");
+
+        assert_eq!(results.matches.len(), 2);
+        assert!(results.truncated);
+    }
+
+    #[test]
+    fn rejects_bad_queries() {
+        assert!(TextSearch::new(&SearchQuery::default()).is_err());
+        assert!(TextSearch::new(&SearchQuery { q: "(".to_string(), regex: true, ..Default::default() }).is_err());
+    }
+}
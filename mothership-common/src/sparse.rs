@@ -0,0 +1,189 @@
+//! Sparse beams: syncing only part of a project.
+//!
+//! A sparse spec lists project-relative directories or files. A path is in
+//! scope if it is one of them or lies beneath one; an empty spec covers the
+//! whole project. The spec is saved in `.mothership/sparse.json`, sent to the
+//! server in `JoinRift`, and used by the server and daemon to drop
+//! out-of-scope files.
+
+use crate::protocol::SyncMessage;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File in a project's `.mothership` directory holding its sparse spec
+pub const SPARSE_SPEC_FILE: &str = "sparse.json";
+
+/// The paths a beam is limited to
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SparseSpec {
+    pub paths: Vec<PathBuf>,
+}
+
+/// Project-relative with `/` separators and no leading `./` or trailing `/`.
+/// The project root itself normalizes to an empty path.
+fn normalize(path: &Path) -> PathBuf {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let path = path.trim_start_matches("./").trim_matches('/');
+    PathBuf::from(if path == "." { "" } else { path })
+}
+
+impl SparseSpec {
+    pub fn new<I, P>(paths: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let mut normalized: Vec<PathBuf> = Vec::new();
+        for path in paths {
+            let path = normalize(path.as_ref());
+            if !path.as_os_str().is_empty() && !normalized.contains(&path) {
+                normalized.push(path);
+            }
+        }
+        Self { paths: normalized }
+    }
+
+    /// Whether the spec covers the whole project
+    pub fn is_full(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// Whether a project-relative path is in scope
+    pub fn includes(&self, path: &Path) -> bool {
+        if self.is_full() {
+            return true;
+        }
+        let path = normalize(path);
+        self.paths.iter().any(|scope| path.starts_with(scope))
+    }
+
+    /// Load a project's spec; projects without one are synced in full
+    pub fn load(project_dir: &Path) -> Self {
+        fs::read_to_string(project_dir.join(".mothership").join(SPARSE_SPEC_FILE))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save the spec for a project, removing it if the project is synced in full
+    pub fn save(&self, project_dir: &Path) -> Result<()> {
+        let path = project_dir.join(".mothership").join(SPARSE_SPEC_FILE);
+        if self.is_full() {
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+            return Ok(());
+        }
+        fs::create_dir_all(project_dir.join(".mothership"))?;
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Narrow a server message to the spec. Returns `None` if nothing in it
+    /// is in scope.
+    pub fn filter_message(&self, message: SyncMessage) -> Option<SyncMessage> {
+        if self.is_full() {
+            return Some(message);
+        }
+
+        match message {
+            SyncMessage::RiftJoined { mut current_files, rift_id, participants, last_checkpoint, protocol_version, encoding, session_id, seq, sparse_paths, mut file_metadata, mut reconcile } => {
+                current_files.retain(|path, _| self.includes(path));
+                file_metadata.retain(|path, _| self.includes(path));
+                if let Some(plan) = &mut reconcile {
+                    plan.retain(|path| self.includes(path));
+                }
+                Some(SyncMessage::RiftJoined { rift_id, current_files, participants, last_checkpoint, protocol_version, encoding, session_id, seq, sparse_paths, file_metadata, reconcile })
+            }
+            SyncMessage::SessionResumed { rift_id, session_id, missed, seq, protocol_version, encoding, sparse_paths } => {
+                let missed = missed.into_iter().filter_map(|m| self.filter_message(m)).collect();
+                Some(SyncMessage::SessionResumed { rift_id, session_id, missed, seq, protocol_version, encoding, sparse_paths })
+            }
+            SyncMessage::Sequenced { seq, message } => self
+                .filter_message(*message)
+                .map(|message| SyncMessage::Sequenced { seq, message: Box::new(message) }),
+            SyncMessage::RiftUpdate { rift_id, mut changes, author, timestamp } => {
+                changes.retain(|c| self.includes(&c.path));
+                (!changes.is_empty()).then_some(SyncMessage::RiftUpdate { rift_id, changes, author, timestamp })
+            }
+            SyncMessage::RiftDiffUpdate { rift_id, mut diff_changes, author, timestamp, compressed } => {
+                diff_changes.retain(|c| self.includes(&c.path));
+                (!diff_changes.is_empty()).then_some(SyncMessage::RiftDiffUpdate { rift_id, diff_changes, author, timestamp, compressed })
+            }
+            SyncMessage::SyncData { rift_id, checkpoint_id, mut files } => {
+                files.retain(|f| self.includes(&f.path));
+                Some(SyncMessage::SyncData { rift_id, checkpoint_id, files })
+            }
+            SyncMessage::FileUpdate { ref path, .. }
+            | SyncMessage::FileDiffUpdate { ref path, .. }
+            | SyncMessage::ConflictDetected { ref path, .. }
+            | SyncMessage::TextSnapshot { ref path, .. }
+            | SyncMessage::TextOperations { ref path, .. }
+            | SyncMessage::ForceSync { ref path, .. }
+            | SyncMessage::ContentResponse { ref path, .. }
+            | SyncMessage::DeltaRejected { ref path, .. } => self.includes(path).then_some(message),
+            other => Some(other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{FileDiff, FileDiffChange};
+    use chrono::Utc;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_scope_and_message_filtering() {
+        let spec = SparseSpec::new(["./services/api/", "docs\\guide.md", "services/api"]);
+        assert_eq!(spec.paths, vec![PathBuf::from("services/api"), PathBuf::from("docs/guide.md")]);
+        assert!(spec.includes(Path::new("services/api/src/main.rs")));
+        assert!(spec.includes(Path::new("docs/guide.md")));
+        assert!(!spec.includes(Path::new("services/api2/main.rs")));
+        assert!(!spec.includes(Path::new("README.md")));
+        assert!(SparseSpec::default().includes(Path::new("README.md")));
+        assert!(SparseSpec::new(["."]).is_full());
+
+        let joined = SyncMessage::RiftJoined {
+            rift_id: Uuid::new_v4(),
+            current_files: HashMap::from([
+                (PathBuf::from("services/api/lib.rs"), String::new()),
+                (PathBuf::from("services/web/lib.rs"), String::new()),
+            ]),
+            participants: vec![],
+            last_checkpoint: None,
+            protocol_version: 4,
+            encoding: Default::default(),
+            session_id: None,
+            seq: 0,
+            sparse_paths: spec.paths.clone(),
+            file_metadata: HashMap::new(),
+            reconcile: None,
+        };
+        match spec.filter_message(joined) {
+            Some(SyncMessage::RiftJoined { current_files, .. }) => {
+                assert_eq!(current_files.keys().collect::<Vec<_>>(), vec![&PathBuf::from("services/api/lib.rs")]);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let update = SyncMessage::RiftDiffUpdate {
+            rift_id: Uuid::new_v4(),
+            diff_changes: vec![FileDiffChange {
+                path: PathBuf::from("services/web/lib.rs"),
+                diff: FileDiff::FullContent("x".to_string()),
+                file_size: 1,
+                metadata: Default::default(),
+            }],
+            author: Uuid::new_v4(),
+            timestamp: Utc::now(),
+            compressed: false,
+        };
+        assert!(spec.filter_message(SyncMessage::Sequenced { seq: 1, message: Box::new(update) }).is_none());
+        assert!(spec.filter_message(SyncMessage::Heartbeat).is_some());
+    }
+}
@@ -0,0 +1,155 @@
+//! TLS options for self-hosted servers with internal CAs, self-signed
+//! certificates or client certificates. They are stored per server in
+//! `connections.json` and apply to every HTTP and WebSocket connection the
+//! CLI and daemon make to that server.
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+const PEM_CERTIFICATE_BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+const PEM_CERTIFICATE_END: &str = "-----END CERTIFICATE-----";
+
+/// A server's TLS options, stored under `tls` in its `connections.json` entry
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TlsSettings {
+    /// PEM file of CA certificates to trust in addition to the system's
+    pub ca_bundle: Option<PathBuf>,
+    /// Accept any certificate and host name; only for testing
+    pub insecure: bool,
+    /// PEM client certificate (chain) for servers that require one
+    pub client_cert: Option<PathBuf>,
+    /// PEM PKCS#8 private key of `client_cert`
+    pub client_key: Option<PathBuf>,
+}
+
+impl TlsSettings {
+    /// Whether the system's TLS defaults apply unchanged
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.client_cert.is_some() != self.client_key.is_some() {
+            return Err(anyhow!("A client certificate needs both a certificate and a key file"));
+        }
+        Ok(())
+    }
+
+    /// A TLS connector honoring these settings, or `None` if the defaults apply
+    pub fn connector(&self) -> Result<Option<native_tls::TlsConnector>> {
+        if self.is_default() {
+            return Ok(None);
+        }
+        self.validate()?;
+
+        let mut builder = native_tls::TlsConnector::builder();
+        if let Some(path) = &self.ca_bundle {
+            let bundle = fs::read_to_string(path)
+                .with_context(|| format!("Failed to read CA bundle {}", path.display()))?;
+            let certificates = pem_certificates(&bundle);
+            if certificates.is_empty() {
+                return Err(anyhow!("No certificates found in CA bundle {}", path.display()));
+            }
+            for pem in certificates {
+                builder.add_root_certificate(native_tls::Certificate::from_pem(pem.as_bytes())?);
+            }
+        }
+        if let (Some(cert), Some(key)) = (&self.client_cert, &self.client_key) {
+            let cert_pem = fs::read(cert).with_context(|| format!("Failed to read client certificate {}", cert.display()))?;
+            let key_pem = fs::read(key).with_context(|| format!("Failed to read client key {}", key.display()))?;
+            builder.identity(native_tls::Identity::from_pkcs8(&cert_pem, &key_pem)?);
+        }
+        if self.insecure {
+            builder.danger_accept_invalid_certs(true);
+            builder.danger_accept_invalid_hostnames(true);
+        }
+        Ok(Some(builder.build()?))
+    }
+
+    /// An HTTP client builder honoring these settings
+    pub fn client_builder(&self) -> Result<reqwest::ClientBuilder> {
+        let builder = reqwest::Client::builder();
+        Ok(match self.connector()? {
+            Some(connector) => builder.use_preconfigured_tls(connector),
+            None => builder,
+        })
+    }
+}
+
+/// The TLS settings stored for the server `url` belongs to, or the defaults
+/// for servers that aren't in `connections.json`
+pub fn settings_for(url: &str) -> TlsSettings {
+    #[derive(Deserialize)]
+    struct StoredServer {
+        url: String,
+        #[serde(default)]
+        tls: TlsSettings,
+    }
+
+    #[derive(Deserialize)]
+    struct StoredConnections {
+        #[serde(default)]
+        servers: HashMap<String, StoredServer>,
+    }
+
+    let Some(connections) = dirs::config_dir()
+        .and_then(|dir| fs::read_to_string(dir.join("mothership").join("connections.json")).ok())
+        .and_then(|json| serde_json::from_str::<StoredConnections>(&json).ok())
+    else {
+        return TlsSettings::default();
+    };
+
+    let url = url.replacen("wss://", "https://", 1).replacen("ws://", "http://", 1);
+    connections
+        .servers
+        .into_values()
+        .find(|server| {
+            let base = server.url.trim_end_matches('/');
+            url.strip_prefix(base).is_some_and(|rest| rest.is_empty() || rest.starts_with('/') || rest.starts_with('?'))
+        })
+        .map(|server| server.tls)
+        .unwrap_or_default()
+}
+
+/// An HTTP client for `url` that honors its server's TLS settings
+pub fn http_client(url: &str) -> Result<reqwest::Client> {
+    Ok(settings_for(url).client_builder()?.build()?)
+}
+
+/// The certificates in a PEM bundle
+fn pem_certificates(bundle: &str) -> Vec<String> {
+    bundle
+        .split(PEM_CERTIFICATE_BEGIN)
+        .skip(1)
+        .filter_map(|rest| {
+            let end = rest.find(PEM_CERTIFICATE_END)?;
+            Some(format!("{}{}{}\n", PEM_CERTIFICATE_BEGIN, &rest[..end], PEM_CERTIFICATE_END))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pem_certificates_splits_bundle() {
+        let bundle = "# Internal CA\n-----BEGIN CERTIFICATE-----\nAAAA\n-----END CERTIFICATE-----\n\
+                      -----BEGIN CERTIFICATE-----\nBBBB\n-----END CERTIFICATE-----\n-----BEGIN CERTIFICATE-----\ntruncated";
+        let certificates = pem_certificates(bundle);
+        assert_eq!(certificates.len(), 2);
+        assert!(certificates[1].contains("BBBB"));
+        assert!(certificates[1].ends_with("-----END CERTIFICATE-----\n"));
+    }
+
+    #[test]
+    fn test_client_cert_needs_key() {
+        let settings = TlsSettings { client_cert: Some("client.pem".into()), ..Default::default() };
+        assert!(settings.validate().is_err());
+        assert!(TlsSettings::default().connector().unwrap().is_none());
+    }
+}
@@ -174,7 +174,7 @@ impl TransactionManager {
                     }
                     std::fs::write(path, content)?;
                 }
-                FileDiff::LineDiff { .. } => {
+                FileDiff::LineDiff { .. } | FileDiff::BlockDelta { .. } => {
                     let current_content = std::fs::read_to_string(path)?;
                     let engine = DiffEngine::new();
                     let new_content = engine.apply_diff(&current_content, &file_state.diff)?;
@@ -210,7 +210,7 @@ impl TransactionManager {
                 FileDiff::FullContent(_) => {
                     if let Ok(_) = std::fs::remove_file(path) {}
                 }
-                FileDiff::LineDiff { .. } => {
+                FileDiff::LineDiff { .. } | FileDiff::BlockDelta { .. } => {
                     // Restore previous content if available
                     if let Ok(current_content) = std::fs::read_to_string(path) {
                         std::fs::write(path, current_content)?;
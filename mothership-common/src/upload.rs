@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use uuid::Uuid;
+
+use crate::push_rules::PushRuleViolation;
+
+/// Size of each content-addressed upload chunk (1 MiB)
+pub const UPLOAD_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Hex-encoded SHA-256 of a byte slice; used for both chunk and file hashes
+pub fn content_hash(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Whether a string looks like a hash produced by [`content_hash`]
+pub fn is_valid_content_hash(hash: &str) -> bool {
+    hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+}
+
+/// Describes one file of an upload as an ordered list of chunk hashes
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UploadFileManifest {
+    pub path: PathBuf,
+    pub size: u64,
+    /// Hash of the whole file, verified after reassembly
+    pub hash: String,
+    pub chunks: Vec<String>,
+}
+
+impl UploadFileManifest {
+    /// Split file contents into chunks and describe them
+    pub fn from_bytes(path: PathBuf, bytes: &[u8]) -> Self {
+        Self {
+            path,
+            size: bytes.len() as u64,
+            hash: content_hash(bytes),
+            chunks: bytes.chunks(UPLOAD_CHUNK_SIZE).map(content_hash).collect(),
+        }
+    }
+}
+
+/// Request to open an upload session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateUploadRequest {
+    pub files: Vec<UploadFileManifest>,
+}
+
+/// State of an upload session. Chunks the project already uploaded (in this
+/// or an earlier session) are never listed as missing, so re-running an
+/// interrupted upload resumes where it stopped.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadStatus {
+    pub upload_id: Uuid,
+    pub total_chunks: usize,
+    pub missing_chunks: Vec<String>,
+    /// Files the server refused, left out of the upload
+    #[serde(default)]
+    pub rejected: Vec<PushRuleViolation>,
+}
+
+/// Result of completing an upload session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadCompletion {
+    pub files_stored: usize,
+    /// Files that could not be stored (e.g. not valid UTF-8)
+    pub skipped: Vec<PathBuf>,
+    /// Files the server refused: outside the project, too large, or not one
+    /// of the project's allowed file types
+    #[serde(default)]
+    pub rejected: Vec<PushRuleViolation>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_chunks_and_hashes() {
+        let bytes = vec![b'a'; UPLOAD_CHUNK_SIZE * 2 + 10];
+        let manifest = UploadFileManifest::from_bytes(PathBuf::from("big.txt"), &bytes);
+
+        assert_eq!(manifest.size, bytes.len() as u64);
+        assert_eq!(manifest.chunks.len(), 3);
+        assert_eq!(manifest.chunks[0], manifest.chunks[1]);
+        assert_eq!(manifest.chunks[2], content_hash(&bytes[UPLOAD_CHUNK_SIZE * 2..]));
+        assert_eq!(manifest.hash, content_hash(&bytes));
+        assert!(manifest.chunks.iter().all(|h| is_valid_content_hash(h)));
+
+        let empty = UploadFileManifest::from_bytes(PathBuf::from("empty.txt"), b"");
+        assert!(empty.chunks.is_empty());
+        assert!(!is_valid_content_hash("../etc/passwd"));
+    }
+}
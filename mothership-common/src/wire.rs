@@ -0,0 +1,102 @@
+//! Encoding of sync messages on the WebSocket.
+//!
+//! JSON text frames are always understood. Peers that negotiate it (protocol
+//! v3+) may also send MessagePack in binary frames, which avoids escaping file
+//! contents and is noticeably smaller and faster for large payloads. The frame
+//! type tells the receiver which decoder to use, so switching is race-free.
+
+use crate::protocol::SyncMessage;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// First protocol version that may negotiate binary frames
+pub const BINARY_FRAMES_MIN_PROTOCOL: u32 = 3;
+
+/// How sync messages are encoded on the wire
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WireEncoding {
+    /// JSON text frames
+    #[default]
+    Json,
+    /// MessagePack binary frames
+    MessagePack,
+}
+
+/// An encoded sync message, ready to be wrapped in a WebSocket frame
+#[derive(Debug, Clone, PartialEq)]
+pub enum WireFrame {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+impl WireEncoding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WireEncoding::Json => "json",
+            WireEncoding::MessagePack => "messagepack",
+        }
+    }
+
+    /// Pick the first encoding in the client's preference list that the server
+    /// allows at the negotiated protocol version, falling back to JSON
+    pub fn negotiate(preferred: &[WireEncoding], protocol_version: u32, binary_allowed: bool) -> WireEncoding {
+        preferred
+            .iter()
+            .copied()
+            .find(|encoding| match encoding {
+                WireEncoding::Json => true,
+                WireEncoding::MessagePack => binary_allowed && protocol_version >= BINARY_FRAMES_MIN_PROTOCOL,
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn encode(&self, message: &SyncMessage) -> Result<WireFrame> {
+        match self {
+            WireEncoding::Json => Ok(WireFrame::Text(serde_json::to_string(message)?)),
+            // Named (map) encoding keeps the format self-describing, which the
+            // adjacently tagged SyncMessage needs to deserialize
+            WireEncoding::MessagePack => Ok(WireFrame::Binary(rmp_serde::to_vec_named(message)?)),
+        }
+    }
+}
+
+/// Decode a text frame
+pub fn decode_text(text: &str) -> Result<SyncMessage> {
+    Ok(serde_json::from_str(text)?)
+}
+
+/// Decode a binary frame
+pub fn decode_binary(bytes: &[u8]) -> Result<SyncMessage> {
+    Ok(rmp_serde::from_slice(bytes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use std::path::PathBuf;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_negotiate_and_round_trip() {
+        let preferred = [WireEncoding::MessagePack, WireEncoding::Json];
+        assert_eq!(WireEncoding::negotiate(&preferred, 3, true), WireEncoding::MessagePack);
+        assert_eq!(WireEncoding::negotiate(&preferred, 2, true), WireEncoding::Json);
+        assert_eq!(WireEncoding::negotiate(&preferred, 3, false), WireEncoding::Json);
+        assert_eq!(WireEncoding::negotiate(&[], 3, true), WireEncoding::Json);
+
+        let message = SyncMessage::FileChanged {
+            rift_id: Uuid::new_v4(),
+            path: PathBuf::from("src/main.rs"),
+            content: "fn main() {\n    println!(\"hi\");\n}\n".to_string(),
+            timestamp: Utc::now(),
+            metadata: crate::FileMetadata { mode: Some(0o755), ..Default::default() },
+        };
+        let WireFrame::Binary(bytes) = WireEncoding::MessagePack.encode(&message).unwrap() else {
+            panic!("expected a binary frame");
+        };
+        let decoded = decode_binary(&bytes).unwrap();
+        assert_eq!(serde_json::to_string(&decoded).unwrap(), serde_json::to_string(&message).unwrap());
+    }
+}
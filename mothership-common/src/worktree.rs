@@ -0,0 +1,138 @@
+//! Working-tree status: what changed on disk since the last checkpoint.
+//!
+//! The CLI and daemon record the content hash of every file in the latest
+//! checkpoint they know of in `.mothership/checkpoint_manifest.json`.
+//! `mothership status` hashes the working tree and compares it with that
+//! manifest, so it can answer without a round trip to the server.
+
+use crate::{upload::content_hash, Checkpoint, ChangeType, CheckpointId};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File in a project's `.mothership` directory holding its checkpoint manifest
+pub const CHECKPOINT_MANIFEST_FILE: &str = "checkpoint_manifest.json";
+
+/// Content hashes of the files in the last checkpoint
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckpointManifest {
+    /// `None` before the rift's first checkpoint
+    pub checkpoint_id: Option<CheckpointId>,
+    pub recorded_at: DateTime<Utc>,
+    pub files: HashMap<PathBuf, String>,
+}
+
+impl CheckpointManifest {
+    /// The files of a checkpoint; checkpoints are full snapshots, so anything
+    /// not deleted in it is part of it
+    pub fn from_checkpoint(checkpoint: &Checkpoint) -> Self {
+        let files = checkpoint.changes
+            .iter()
+            .filter(|c| !matches!(c.change_type, ChangeType::Deleted))
+            .map(|c| (c.path.clone(), c.content_hash.clone()))
+            .collect();
+        Self { checkpoint_id: Some(checkpoint.id), recorded_at: Utc::now(), files }
+    }
+
+    /// Load a project's manifest, if one has been recorded
+    pub fn load(project_dir: &Path) -> Option<Self> {
+        fs::read_to_string(project_dir.join(".mothership").join(CHECKPOINT_MANIFEST_FILE))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+    }
+
+    pub fn save(&self, project_dir: &Path) -> Result<()> {
+        fs::create_dir_all(project_dir.join(".mothership"))?;
+        fs::write(
+            project_dir.join(".mothership").join(CHECKPOINT_MANIFEST_FILE),
+            serde_json::to_string(self)?,
+        )?;
+        Ok(())
+    }
+
+    /// How the working tree (path to content) differs from the checkpoint
+    pub fn status(&self, working: &HashMap<PathBuf, String>) -> Vec<(PathBuf, FileStatus)> {
+        let hashes = working
+            .iter()
+            .map(|(path, content)| (path.clone(), content_hash(content.as_bytes())))
+            .collect();
+        working_tree_status(&self.files, &hashes)
+    }
+}
+
+/// How a file differs from the last checkpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileStatus {
+    Created,
+    Modified,
+    Deleted,
+}
+
+impl FileStatus {
+    /// Single-letter code used by `status --porcelain`
+    pub fn code(self) -> char {
+        match self {
+            FileStatus::Created => 'A',
+            FileStatus::Modified => 'M',
+            FileStatus::Deleted => 'D',
+        }
+    }
+}
+
+/// Compare two path-to-hash maps, sorted by path
+pub fn working_tree_status(
+    base: &HashMap<PathBuf, String>,
+    working: &HashMap<PathBuf, String>,
+) -> Vec<(PathBuf, FileStatus)> {
+    let mut changes: Vec<(PathBuf, FileStatus)> = working
+        .iter()
+        .filter_map(|(path, hash)| match base.get(path) {
+            None => Some((path.clone(), FileStatus::Created)),
+            Some(base_hash) if base_hash != hash => Some((path.clone(), FileStatus::Modified)),
+            Some(_) => None,
+        })
+        .chain(base.keys()
+            .filter(|path| !working.contains_key(*path))
+            .map(|path| (path.clone(), FileStatus::Deleted)))
+        .collect();
+    changes.sort();
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_against_manifest() {
+        let manifest = CheckpointManifest {
+            checkpoint_id: None,
+            recorded_at: Utc::now(),
+            files: HashMap::from([
+                (PathBuf::from("src/main.rs"), content_hash(b"fn main() {}")),
+                (PathBuf::from("README.md"), content_hash(b"# Project")),
+                (PathBuf::from("old.txt"), content_hash(b"gone")),
+            ]),
+        };
+        let working = HashMap::from([
+            (PathBuf::from("src/main.rs"), "fn main() { run() }".to_string()),
+            (PathBuf::from("README.md"), "# Project".to_string()),
+            (PathBuf::from("new.txt"), "hello".to_string()),
+        ]);
+
+        assert_eq!(manifest.status(&working), vec![
+            (PathBuf::from("new.txt"), FileStatus::Created),
+            (PathBuf::from("old.txt"), FileStatus::Deleted),
+            (PathBuf::from("src/main.rs"), FileStatus::Modified),
+        ]);
+        assert!(manifest.status(&HashMap::from([
+            (PathBuf::from("src/main.rs"), "fn main() {}".to_string()),
+            (PathBuf::from("README.md"), "# Project".to_string()),
+            (PathBuf::from("old.txt"), "gone".to_string()),
+        ])).is_empty());
+    }
+}
@@ -1,5 +1,5 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 // Tokio imports
@@ -16,17 +16,40 @@ use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
 // Internal imports
-use crate::file_watcher::{FileChangeEvent, FileWatcher};
+use crate::delta_sync::DeltaSync;
+use crate::file_watcher::{FileChangeBatch, FileChangeEvent, FileChangeType, FileWatcher};
 use crate::ipc_server::IpcServer;
+use crate::metrics::{metrics, ConnectionStats};
+use crate::offline_queue::OfflineQueue;
+use crate::outgoing::{self, OutgoingSender};
+use crate::project_scanner::ProjectScanner;
+use crate::sync_filter::SkipReason;
 use crate::system_tray::SystemTray;
 use mothership_common::{
     DiffEngine,
     LogicalPosition,
     CRDTOperationType,
     FileDiff,
+    ConflictPolicy,
+    Hook,
     ConflictRiftInfo,
+    SparseSpec,
     SyncMessage,
+    FileMetadata,
+    FileDiffChange,
+    ProjectSettings,
+    metadata::{read_file, write_file},
+    protocol::{
+        negotiate_protocol_version, protocol_mismatch_message, ApiResponse, ConflictingFile, SessionResume, BATCH_TOO_LARGE_ERROR,
+        FILE_QUARANTINED_WARNING, MESSAGE_TOO_LARGE_ERROR, MIN_PROTOCOL_VERSION, OBSERVER_ERROR, PROTOCOL_MISMATCH_ERROR, PROTOCOL_VERSION, PUSH_RULE_ERROR,
+        RATE_LIMITED_ERROR, RECONCILIATION_VERSION, SECRET_FOUND_WARNING,
+    },
+    hooks,
+    profile,
+    tls,
     transaction::TransactionManager,
+    wire::{self, WireEncoding, WireFrame},
+    worktree::{CheckpointManifest, CHECKPOINT_MANIFEST_FILE},
 };
 
 /// Information about a tracked project
@@ -36,6 +59,8 @@ pub struct TrackedProject {
     pub project_name: String,
     pub project_path: PathBuf,
     pub added_at: chrono::DateTime<chrono::Utc>,
+    /// Sync is paused: the project is neither watched nor connected
+    pub paused: bool,
 }
 
 /// The main Mothership daemon that coordinates all background services
@@ -53,10 +78,10 @@ pub struct MothershipDaemon {
     system_tray: Option<SystemTray>,
     
     /// Channel for receiving file change events
-    file_change_receiver: mpsc::UnboundedReceiver<FileChangeEvent>,
+    file_change_receiver: mpsc::UnboundedReceiver<FileChangeBatch>,
     
     /// Channel for sending file change events to watchers
-    file_change_sender: mpsc::UnboundedSender<FileChangeEvent>,
+    file_change_sender: mpsc::UnboundedSender<FileChangeBatch>,
     
     /// Current daemon status
     status: Arc<RwLock<DaemonStatus>>,
@@ -65,7 +90,7 @@ pub struct MothershipDaemon {
     websocket_listeners: Arc<RwLock<HashMap<Uuid, tokio::task::JoinHandle<()>>>>,
     
     /// Maps project ID to outgoing message channels (for sending to WebSocket)
-    outgoing_channels: Arc<RwLock<HashMap<Uuid, mpsc::UnboundedSender<SyncMessage>>>>,
+    outgoing_channels: Arc<RwLock<HashMap<Uuid, OutgoingSender>>>,
     
     transaction_manager: Arc<Mutex<TransactionManager>>,
     
@@ -80,6 +105,16 @@ pub struct DaemonStatus {
     pub files_syncing: usize,
     pub last_sync: Option<chrono::DateTime<chrono::Utc>>,
     pub server_connected: bool,
+    /// Changes buffered on disk while the server is unreachable
+    pub queued_changes: usize,
+    /// File changes waiting to go out over live connections
+    pub behind_by: usize,
+    /// Set when the server speaks an incompatible sync protocol
+    pub protocol_error: Option<String>,
+    /// Files left out of sync by the size and type limits, by reason
+    pub skipped_files: HashMap<SkipReason, usize>,
+    /// Scans are slowed down to save battery
+    pub low_priority: bool,
 }
 
 impl Default for DaemonStatus {
@@ -90,6 +125,11 @@ impl Default for DaemonStatus {
             files_syncing: 0,
             last_sync: None,
             server_connected: false,
+            queued_changes: 0,
+            behind_by: 0,
+            protocol_error: None,
+            skipped_files: HashMap::new(),
+            low_priority: false,
         }
     }
 }
@@ -108,6 +148,7 @@ impl MothershipDaemon {
         let websocket_listeners = Arc::new(RwLock::new(HashMap::new()));
         let outgoing_channels = Arc::new(RwLock::new(HashMap::new()));
         let server_write_flags = Arc::new(RwLock::new(HashMap::new()));
+        let scanner = Arc::new(ProjectScanner::new(mothership_common::DaemonConfig::load().discovery));
         
         // Create IPC server with access to daemon methods
         let ipc_server = IpcServer::new(
@@ -117,11 +158,12 @@ impl MothershipDaemon {
             websocket_listeners.clone(),
             outgoing_channels.clone(),
             server_write_flags.clone(),
+            scanner.clone(),
         ).await?;
         
         // Initialize system tray (Windows only)
         #[cfg(windows)]
-        let system_tray = Some(SystemTray::new(status.clone(), tracked_projects.clone())?);
+        let system_tray = Some(SystemTray::new(status.clone(), tracked_projects.clone(), scanner)?);
         #[cfg(not(windows))]
         let system_tray = None;
         
@@ -180,11 +222,11 @@ impl MothershipDaemon {
         
         // Main event loop - process file change events
         info!("✅ Mothership Daemon is running!");
-        info!("🔍 IPC server listening on http://localhost:7525");
+        info!("🔍 IPC server listening for the CLI (see mothership_common::ipc)");
         info!("⏳ Waiting for projects to be registered via CLI/GUI...");
         
-        while let Some(event) = file_change_receiver.recv().await {
-            if let Err(e) = Self::handle_file_change_static(event, &self.tracked_projects, &self.status, &self.outgoing_channels, &self.server_write_flags).await {
+        while let Some(changes) = file_change_receiver.recv().await {
+            if let Err(e) = Self::handle_file_change_static(changes, &self.tracked_projects, &self.status, &self.outgoing_channels, &self.server_write_flags).await {
                 error!("Error handling file change: {}", e);
             }
         }
@@ -225,20 +267,24 @@ impl MothershipDaemon {
         Ok(())
     }
     
-    /// Handle a file change event (static version for use after moving fields)
+    /// Handle a batch of file changes from one project (static version for use after moving fields)
     async fn handle_file_change_static(
-        event: FileChangeEvent,
+        changes: FileChangeBatch,
         tracked_projects: &Arc<RwLock<HashMap<Uuid, TrackedProject>>>,
         status: &Arc<RwLock<DaemonStatus>>,
-        outgoing_channels: &Arc<RwLock<HashMap<Uuid, mpsc::UnboundedSender<SyncMessage>>>>,
+        outgoing_channels: &Arc<RwLock<HashMap<Uuid, OutgoingSender>>>,
         server_write_flags: &Arc<RwLock<HashMap<Uuid, bool>>>,
     ) -> Result<()> {
+        let Some(project_id) = changes.first().map(|event| event.project_id) else {
+            return Ok(());
+        };
+
         // Check if server is currently writing files (prevents infinite loops)
         {
             let flags = server_write_flags.read().await;
-            if flags.get(&event.project_id).copied().unwrap_or(false) {
-                debug!("🔄 Skipping file change event during server write: {} in project {}", 
-                    event.file_path.display(), event.project_id);
+            if flags.get(&project_id).copied().unwrap_or(false) {
+                debug!("🔄 Skipping {} file change events during server write in project {}", 
+                    changes.len(), project_id);
                 return Ok(());
             }
         }
@@ -246,23 +292,25 @@ impl MothershipDaemon {
         // Get project info for better logging
         let project_name = {
             let projects = tracked_projects.read().await;
-            projects.get(&event.project_id)
+            projects.get(&project_id)
                 .map(|p| p.project_name.clone())
-                .unwrap_or_else(|| event.project_id.to_string())
+                .unwrap_or_else(|| project_id.to_string())
         };
         
-        info!("📝 File {:?}: {} ({} bytes) in project '{}'", 
-            event.change_type, event.file_path.display(), event.file_size, project_name);
+        for event in &changes {
+            info!("📝 File {:?}: {} ({} bytes) in project '{}'", 
+                event.change_type, event.file_path.display(), event.file_size, project_name);
+        }
         
         // Update sync status
         {
             let mut status_guard = status.write().await;
-            status_guard.files_syncing += 1;
+            status_guard.files_syncing += changes.len();
             status_guard.last_sync = Some(chrono::Utc::now());
         }
         
-        // PERSISTENT WEBSOCKET: Send file change via persistent connection
-        let sync_result = Self::send_file_change_via_persistent_websocket(&event, tracked_projects, outgoing_channels).await;
+        // PERSISTENT WEBSOCKET: Send file changes via persistent connection
+        let sync_result = Self::send_file_change_via_persistent_websocket(project_id, &changes, tracked_projects, status, outgoing_channels).await;
         match sync_result {
             Ok(()) => {
                 info!("✅ Successfully queued file change for persistent WebSocket");
@@ -275,28 +323,31 @@ impl MothershipDaemon {
         // Update sync status
         {
             let mut status_guard = status.write().await;
-            status_guard.files_syncing = status_guard.files_syncing.saturating_sub(1);
+            status_guard.files_syncing = status_guard.files_syncing.saturating_sub(changes.len());
         }
         
         Ok(())
     }
 
-    /// Handle a file change event
-    async fn handle_file_change(&self, event: FileChangeEvent) -> Result<()> {
-        Self::handle_file_change_static(event, &self.tracked_projects, &self.status, &self.outgoing_channels, &self.server_write_flags).await
+    /// Handle a batch of file changes
+    async fn handle_file_change(&self, changes: FileChangeBatch) -> Result<()> {
+        Self::handle_file_change_static(changes, &self.tracked_projects, &self.status, &self.outgoing_channels, &self.server_write_flags).await
     }
     
-    /// Send file change via persistent WebSocket connection
+    /// Send file changes via persistent WebSocket connection. Moves are sent
+    /// on their own; the other changes of a batch go as one message.
     async fn send_file_change_via_persistent_websocket(
-        event: &FileChangeEvent,
+        project_id: Uuid,
+        changes: &[FileChangeEvent],
         tracked_projects: &Arc<RwLock<HashMap<Uuid, TrackedProject>>>,
-        outgoing_channels: &Arc<RwLock<HashMap<Uuid, mpsc::UnboundedSender<SyncMessage>>>>,
+        status: &Arc<RwLock<DaemonStatus>>,
+        outgoing_channels: &Arc<RwLock<HashMap<Uuid, OutgoingSender>>>,
     ) -> Result<()> {
         // Get project metadata to determine rift_id
-        let rift_id = {
+        let (project_path, rift_id, observer) = {
             let projects = tracked_projects.read().await;
-            let project = projects.get(&event.project_id)
-                .ok_or_else(|| anyhow::anyhow!("Project not found in tracked projects: {}", event.project_id))?;
+            let project = projects.get(&project_id)
+                .ok_or_else(|| anyhow::anyhow!("Project not found in tracked projects: {}", project_id))?;
             
             // Load project metadata to get rift_id
             let metadata_file = project.project_path.join(".mothership").join("project.json");
@@ -306,32 +357,102 @@ impl MothershipDaemon {
             let metadata: ProjectMetadata = serde_json::from_str(&metadata_content)
                 .map_err(|e| anyhow::anyhow!("Failed to parse project metadata: {}", e))?;
             
-            if let Some(rift_id_str) = &metadata.rift_id {
+            let rift_id = if let Some(rift_id_str) = &metadata.rift_id {
                 uuid::Uuid::parse_str(rift_id_str)
                     .map_err(|e| anyhow::anyhow!("Invalid rift_id in metadata: {}", e))?
             } else {
-                event.project_id // Fallback to project_id
-            }
+                project_id // Fallback to project_id
+            };
+            
+            (project.project_path.clone(), rift_id, metadata.observer)
         };
         
-        // Create sync message
-        let sync_message = SyncMessage::FileChanged {
-            rift_id,
-            path: event.file_path.clone(),
-            content: event.content.clone(),
-            timestamp: event.timestamp,
-        };
+        // OBSERVERS: Changes made while watching a rift stay local
+        if observer {
+            debug!("👁️ Not sending {} changes in project {}: beamed in as an observer", changes.len(), project_id);
+            return Ok(());
+        }
         
-        // Send via persistent WebSocket channel
-        {
-            let channels = outgoing_channels.read().await;
-            if let Some(sender) = channels.get(&event.project_id) {
-                sender.send(sync_message)
-                    .map_err(|e| anyhow::anyhow!("Failed to queue message for persistent WebSocket: {}", e))?;
-            } else {
-                return Err(anyhow::anyhow!("No persistent WebSocket connection found for project {}", event.project_id));
+        // Create sync messages
+        let (moves, edits): (Vec<&FileChangeEvent>, Vec<&FileChangeEvent>) = changes.iter()
+            .partition(|event| matches!(event.change_type, FileChangeType::Moved { .. }));
+        let mut sync_messages: Vec<SyncMessage> = moves.into_iter()
+            .filter_map(|event| match &event.change_type {
+                FileChangeType::Moved { from } => Some(SyncMessage::FileMoved {
+                    rift_id,
+                    from: from.clone(),
+                    to: event.file_path.clone(),
+                    timestamp: event.timestamp,
+                }),
+                _ => None,
+            })
+            .collect();
+        
+        match edits.as_slice() {
+            [] => {}
+            [event] => sync_messages.push(match event.change_type {
+                FileChangeType::Deleted => SyncMessage::FileDiffChanged {
+                    rift_id,
+                    path: event.file_path.clone(),
+                    diff: FileDiff::Deleted,
+                    file_size: 0,
+                    timestamp: event.timestamp,
+                    metadata: FileMetadata::default(),
+                },
+                _ => SyncMessage::FileChanged {
+                    rift_id,
+                    path: event.file_path.clone(),
+                    content: event.content.clone(),
+                    timestamp: event.timestamp,
+                    metadata: event.metadata.clone(),
+                },
+            }),
+            // BATCHING: A burst of changes goes to the server as one message
+            edits => sync_messages.push(SyncMessage::BatchDiffChanges {
+                rift_id,
+                changes: edits.iter()
+                    .map(|event| FileDiffChange {
+                        path: event.file_path.clone(),
+                        diff: match event.change_type {
+                            FileChangeType::Deleted => FileDiff::Deleted,
+                            _ => FileDiff::FullContent(event.content.clone()),
+                        },
+                        file_size: event.file_size,
+                        metadata: event.metadata.clone(),
+                    })
+                    .collect(),
+                timestamp: chrono::Utc::now(),
+                compressed: false,
+            }),
+        }
+        
+        for sync_message in sync_messages {
+            // Send via persistent WebSocket channel
+            // BACKPRESSURE: Waits here while the connection's queue is full of changes that can't be merged
+            let sender = outgoing_channels.read().await.get(&project_id).cloned();
+            let undelivered = match sender {
+                Some(sender) => sender.send(sync_message).await.err(),
+                None => Some(sync_message),
+            };
+            
+            // OFFLINE QUEUE: No live listener for this project - persist for replay on reconnect
+            if let Some(sync_message) = undelivered {
+                warn!("📴 No persistent WebSocket for project {}, queueing change offline", project_id);
+                OfflineQueue::for_project(&project_path).push(&sync_message).await?;
+                status.write().await.queued_changes += 1;
             }
         }
+
+        // FILE ACTIVITY: Let collaborators know these files have changes of
+        // ours. It only matters while connected, so it's never queued offline.
+        let dirty = SyncMessage::FileDirty {
+            rift_id,
+            paths: changes.iter().map(|event| event.file_path.clone()).collect(),
+            dirty: true,
+        };
+        if let Some(sender) = outgoing_channels.read().await.get(&project_id).cloned() {
+            let _ = sender.send(dirty).await;
+        }
         
         Ok(())
     }
@@ -388,6 +509,7 @@ impl MothershipDaemon {
             project_name: project_name.clone(),
             project_path: project_path.clone(),
             added_at: chrono::Utc::now(),
+            paused: false,
         };
         
         {
@@ -470,6 +592,7 @@ impl MothershipDaemon {
             let mut channels = self.outgoing_channels.write().await;
             channels.remove(&project_id);
         }
+        metrics().forget(project_id);
         
         // Update status
         {
@@ -490,10 +613,13 @@ impl MothershipDaemon {
     /// Check server connectivity by making a simple HTTP health check
     async fn check_server_connectivity() -> bool {
         if let Some(server_url) = get_active_server_url() {
-            let client = reqwest::Client::builder()
-                .timeout(std::time::Duration::from_secs(5))
-                .build()
-                .unwrap_or_default();
+            let client = match tls::settings_for(&server_url).client_builder() {
+                Ok(builder) => builder.timeout(std::time::Duration::from_secs(5)).build().unwrap_or_default(),
+                Err(e) => {
+                    warn!("❌ Invalid TLS settings for {}: {}", server_url, e);
+                    return false;
+                }
+            };
             
             let health_url = format!("{}/health", server_url);
             
@@ -524,11 +650,11 @@ impl MothershipDaemon {
         tracked_projects: Arc<RwLock<HashMap<Uuid, TrackedProject>>>,
         status: Arc<RwLock<DaemonStatus>>,
         websocket_listeners: Arc<RwLock<HashMap<Uuid, tokio::task::JoinHandle<()>>>>,
-        outgoing_channels: Arc<RwLock<HashMap<Uuid, mpsc::UnboundedSender<SyncMessage>>>>,
+        outgoing_channels: Arc<RwLock<HashMap<Uuid, OutgoingSender>>>,
         server_write_flags: Arc<RwLock<HashMap<Uuid, bool>>>,
     ) -> Result<()> {
         // Get project information
-        let (project_path, rift_id) = {
+        let (project_path, rift_id, observer) = {
             let projects = tracked_projects.read().await;
             let project = projects.get(&project_id)
                 .ok_or_else(|| anyhow::anyhow!("Project not found: {}", project_id))?;
@@ -548,17 +674,17 @@ impl MothershipDaemon {
                 project_id // Fallback to project_id
             };
             
-            (project.project_path.clone(), rift_id)
+            (project.project_path.clone(), rift_id, metadata.observer)
         };
         
         // Get authentication token
-        let auth_token = load_auth_token()
+        let auth_token = load_auth_token(&project_path)
             .ok_or_else(|| anyhow::anyhow!("No authentication token found"))?;
         
         debug!("🔑 Loaded auth token: {}...", &auth_token.chars().take(10).collect::<String>());
         
         // Get server URL
-        let server_url = get_active_server_url()
+        let server_url = project_server_url(&project_path)
             .ok_or_else(|| anyhow::anyhow!("No active server connection found"))?;
         
         debug!("🌐 Active server URL: {}", server_url);
@@ -578,18 +704,54 @@ impl MothershipDaemon {
         info!("📡 WebSocket URL: {}", ws_url.replace(&auth_token, "***TOKEN***"));
         
         // Create channel for outgoing messages
-        let (outgoing_tx, mut outgoing_rx) = mpsc::unbounded_channel::<SyncMessage>();
+        let (outgoing_tx, mut outgoing_rx) = outgoing::channel(status.clone());
         {
             let mut channels = outgoing_channels.write().await;
             channels.insert(project_id, outgoing_tx);
         }
 
+        // OFFLINE QUEUE: Changes left over from a previous session are replayed on connect
+        let offline_queue = OfflineQueue::for_project(&project_path);
+        match offline_queue.load().await {
+            Ok(queued) if !queued.is_empty() => {
+                info!("💾 Found {} queued offline changes for project {}", queued.len(), project_id);
+                status.write().await.queued_changes += queued.len();
+            }
+            Ok(_) => {}
+            Err(e) => error!("Failed to read offline queue for project {}: {}", project_id, e),
+        }
+
+        // PROTOCOL: Refuse to sync with a server that speaks no protocol version we do
+        let protocol_version = match Self::negotiate_server_protocol(&server_url).await {
+            Ok(version) => {
+                info!("🤝 Using sync protocol v{} with {}", version, server_url);
+                status.write().await.protocol_error = None;
+                version
+            }
+            Err(message) => {
+                error!("⛔ Not syncing project {}: {}", project_id, message);
+                status.write().await.protocol_error = Some(message.clone());
+                return Err(anyhow!(message));
+            }
+        };
+
+        // SPARSE BEAM: Only the paths chosen at beam time are synced
+        let sparse = SparseSpec::load(&project_path);
+        if !sparse.is_full() {
+            info!("🔭 Project {} syncs only {:?}", project_id, sparse.paths);
+        }
+        if observer {
+            info!("👁️ Observing project {}: receiving changes only", project_id);
+        }
+
         let status_clone = status.clone();
         let listener_handle = tokio::spawn(async move {
             let ping_interval = Duration::from_secs(30);
             let health_log_interval = Duration::from_secs(300);
             let reconnect_delay = Duration::from_secs(5);
-            let mut health = ConnectionHealth::new();
+            let mut health = ConnectionHealth::new(project_id);
+            // RESUMPTION: Session to pick up again after a reconnect
+            let mut resume: Option<SessionResume> = None;
             
             // CRITICAL FIX: Add reconnection loop
             loop {
@@ -599,7 +761,7 @@ impl MothershipDaemon {
                 info!("🔌 Connecting to WebSocket: {}", ws_url);
                 
                 // CRITICAL FIX: Actually connect to the WebSocket server!
-                match tokio_tungstenite::connect_async(&ws_url).await {
+                match Self::connect_websocket(&ws_url, &server_url).await {
                     Ok((ws_stream, response)) => {
                         info!("✅ WebSocket connected successfully!");
                         debug!("📋 WebSocket response status: {}", response.status());
@@ -610,13 +772,32 @@ impl MothershipDaemon {
                             let mut status_guard = status_clone.write().await;
                             status_guard.server_connected = true;
                         }
+                        health.record_connected();
                         
                         let (mut ws_sender, mut ws_receiver) = ws_stream.split();
                         
+                        // DELTA SYNC: Track what the server holds so large files can be sent as block deltas
+                        let mut delta_sync = DeltaSync::for_project(&project_path);
+                        
+                        // RECONCILIATION: Tell the server what changed here while we were away.
+                        // Observers have nothing to upload and take the server's state as it is.
+                        let manifest = (protocol_version >= RECONCILIATION_VERSION && !observer)
+                            .then(|| tokio::task::block_in_place(|| delta_sync.manifest(&project_path, &sparse)));
+                        
+                        // Frames stay JSON until the server's RiftJoined confirms another encoding
+                        let mut wire_encoding = WireEncoding::Json;
+                        
                         // Send initial join message
                         let join_msg = SyncMessage::JoinRift { 
                             rift_id, 
-                            last_checkpoint: None 
+                            last_checkpoint: None,
+                            protocol_version,
+                            min_protocol_version: MIN_PROTOCOL_VERSION,
+                            encodings: vec![WireEncoding::MessagePack, WireEncoding::Json],
+                            resume: resume.clone(),
+                            sparse_paths: sparse.paths.clone(),
+                            manifest,
+                            observer,
                         };
                         if let Ok(join_json) = serde_json::to_string(&join_msg) {
                             debug!("📤 Sending join message: {}", join_json);
@@ -627,100 +808,154 @@ impl MothershipDaemon {
                             }
                         }
                         
-                        loop {
-                            tokio::select! {
-                                // Handle outgoing messages (from file watcher)
-                                msg = outgoing_rx.recv() => {
-                                    match msg {
-                                        Some(sync_msg) => {
-                                            if let Ok(json) = serde_json::to_string(&sync_msg) {
-                                                if let Err(e) = ws_sender.send(tokio_tungstenite::tungstenite::Message::Text(json)).await {
-                                                    error!("Failed to send WebSocket message: {}", e);
-                                                    health.record_error();
-                                                    break;
-                                                } else {
-                                                    health.record_message_sent();
-                                                    debug!("📤 Sent sync message to server");
+                        // OFFLINE QUEUE: Replay changes made while disconnected, in order, before any new ones
+                        let replayed = Self::replay_offline_queue(&offline_queue, &mut ws_sender, &mut health, &status_clone, &mut delta_sync).await;
+                        
+                        if replayed {
+                            loop {
+                                tokio::select! {
+                                    // Handle outgoing messages (from file watcher)
+                                    msg = outgoing_rx.recv() => {
+                                        match msg {
+                                            Some(sync_msg) => {
+                                                // Full content is kept for the offline queue; only the wire message is a delta
+                                                let wire_msg = delta_sync.encode(&sync_msg);
+                                                if let Ok(frame) = Self::encode_frame(wire_encoding, &wire_msg) {
+                                                    if let Err(e) = ws_sender.send(frame).await {
+                                                        error!("Failed to send WebSocket message: {}", e);
+                                                        health.record_error();
+                                                        Self::queue_offline(&offline_queue, &sync_msg, &status_clone).await;
+                                                        break;
+                                                    } else {
+                                                        health.record_message_sent();
+                                                        health.record_upload();
+                                                        delta_sync.save();
+                                                        debug!("📤 Sent sync message to server");
+                                                    }
                                                 }
                                             }
-                                        }
-                                        None => {
-                                            info!("Outgoing channel closed, stopping WebSocket");
-                                            break;
+                                            None => {
+                                                info!("Outgoing channel closed, stopping WebSocket");
+                                                break;
+                                            }
                                         }
                                     }
-                                }
                                 
-                                // Handle incoming messages (from server)
-                                msg = ws_receiver.next() => {
-                                    match msg {
-                                        Some(Ok(tokio_tungstenite::tungstenite::Message::Text(text))) => {
-                                            health.record_message_received();
-                                            debug!("📥 Received WebSocket message: {} chars", text.len());
+                                    // Handle incoming messages (from server)
+                                    msg = ws_receiver.next() => {
+                                        match msg {
+                                            Some(Ok(frame @ (tokio_tungstenite::tungstenite::Message::Text(_) | tokio_tungstenite::tungstenite::Message::Binary(_)))) => {
+                                                health.record_message_received();
+                                                debug!("📥 Received WebSocket message: {} bytes", frame.len());
+
+                                                let sync_message = match Self::decode_frame(frame) {
+                                                    Ok(sync_message) => sync_message,
+                                                    Err(e) => {
+                                                        error!("Failed to parse sync message: {}", e);
+                                                        continue;
+                                                    }
+                                                };
+
+                                                // PROTOCOL: The server rejected our handshake; reconnecting would not help
+                                                if let Some(message) = Self::protocol_rejection(&sync_message) {
+                                                    error!("⛔ Server rejected sync protocol for project {}: {}", project_id, message);
+                                                    let mut status_guard = status_clone.write().await;
+                                                    status_guard.protocol_error = Some(message);
+                                                    status_guard.server_connected = false;
+                                                    return;
+                                                }
+                                            
+                                                if let SyncMessage::RiftJoined { encoding, .. } | SyncMessage::SessionResumed { encoding, .. } = &sync_message {
+                                                    info!("📦 Using {} frames for project {}", encoding.as_str(), project_id);
+                                                    wire_encoding = *encoding;
+                                                }
                                             
-                                            // Handle incoming sync message
-                                            if let Err(e) = Self::handle_websocket_sync_message(&text, &project_path, &server_write_flags, project_id).await {
-                                                error!("Failed to handle incoming sync message: {}", e);
+                                                // Handle incoming sync messages (a resumed session delivers several at once)
+                                                // Servers that predate sparse beams send everything; drop what's out of scope
+                                                let messages = Self::track_session(sync_message, &mut resume, project_id)
+                                                    .into_iter()
+                                                    .filter_map(|message| sparse.filter_message(message));
+                                                for sync_message in messages {
+                                                    if !matches!(sync_message, SyncMessage::Heartbeat) {
+                                                        health.record_download();
+                                                    }
+                                                    match Self::handle_websocket_sync_message(sync_message, &project_path, &server_write_flags, project_id, &mut delta_sync).await {
+                                                        Ok(Some(reply)) => {
+                                                            if let Ok(frame) = Self::encode_frame(wire_encoding, &reply) {
+                                                                if let Err(e) = ws_sender.send(frame).await {
+                                                                    error!("Failed to send reply to server: {}", e);
+                                                                    health.record_error();
+                                                                    Self::queue_offline(&offline_queue, &reply, &status_clone).await;
+                                                                } else {
+                                                                    health.record_message_sent();
+                                                                }
+                                                            }
+                                                        }
+                                                        Ok(None) => {}
+                                                        Err(e) => error!("Failed to handle incoming sync message: {}", e),
+                                                    }
+                                                }
+                                                delta_sync.save();
                                             }
-                                        }
-                                        Some(Ok(tokio_tungstenite::tungstenite::Message::Close(close_frame))) => {
-                                            info!("WebSocket closed by server: {:?}", close_frame);
-                                            // Send close frame back to complete handshake
-                                            let _ = ws_sender.send(tokio_tungstenite::tungstenite::Message::Close(close_frame)).await;
-                                            break;
-                                        }
-                                        Some(Ok(tokio_tungstenite::tungstenite::Message::Ping(data))) => {
-                                            // Respond to ping with pong
-                                            if let Err(e) = ws_sender.send(tokio_tungstenite::tungstenite::Message::Pong(data)).await {
-                                                error!("Failed to send pong: {}", e);
+                                            Some(Ok(tokio_tungstenite::tungstenite::Message::Close(close_frame))) => {
+                                                info!("WebSocket closed by server: {:?}", close_frame);
+                                                // Send close frame back to complete handshake
+                                                let _ = ws_sender.send(tokio_tungstenite::tungstenite::Message::Close(close_frame)).await;
+                                                break;
+                                            }
+                                            Some(Ok(tokio_tungstenite::tungstenite::Message::Ping(data))) => {
+                                                // Respond to ping with pong
+                                                if let Err(e) = ws_sender.send(tokio_tungstenite::tungstenite::Message::Pong(data)).await {
+                                                    error!("Failed to send pong: {}", e);
+                                                    health.record_error();
+                                                } else {
+                                                    debug!("🏓 Sent pong response");
+                                                }
+                                            }
+                                            Some(Ok(tokio_tungstenite::tungstenite::Message::Pong(_))) => {
+                                                debug!("🏓 Received pong");
+                                                health.record_message_received();
+                                            }
+                                            Some(Err(e)) => {
+                                                error!("WebSocket error: {}", e);
                                                 health.record_error();
-                                            } else {
-                                                debug!("🏓 Sent pong response");
+                                                // Don't break immediately on error - let health check decide
+                                                if health.should_reset(3) {
+                                                    error!("Too many consecutive errors, closing connection");
+                                                    break;
+                                                }
                                             }
-                                        }
-                                        Some(Ok(tokio_tungstenite::tungstenite::Message::Pong(_))) => {
-                                            debug!("🏓 Received pong");
-                                            health.record_message_received();
-                                        }
-                                        Some(Err(e)) => {
-                                            error!("WebSocket error: {}", e);
-                                            health.record_error();
-                                            // Don't break immediately on error - let health check decide
-                                            if health.should_reset(3) {
-                                                error!("Too many consecutive errors, closing connection");
+                                            None => {
+                                                info!("WebSocket stream ended");
                                                 break;
                                             }
+                                            _ => {} // Ignore other message types
                                         }
-                                        None => {
-                                            info!("WebSocket stream ended");
-                                            break;
-                                        }
-                                        _ => {} // Ignore other message types
                                     }
-                                }
 
-                                // Send periodic ping to keep connection alive
-                                _ = sleep_until(next_ping) => {
-                                    let ping_msg = SyncMessage::Heartbeat;
-                                    if let Ok(ping_json) = serde_json::to_string(&ping_msg) {
-                                        if let Err(e) = ws_sender.send(tokio_tungstenite::tungstenite::Message::Text(ping_json)).await {
-                                            error!("Failed to send ping: {}", e);
-                                            health.record_error();
-                                            if health.should_reset(3) {
-                                                break;
+                                    // Send periodic ping to keep connection alive
+                                    _ = sleep_until(next_ping) => {
+                                        let ping_msg = SyncMessage::Heartbeat;
+                                        if let Ok(ping_json) = serde_json::to_string(&ping_msg) {
+                                            if let Err(e) = ws_sender.send(tokio_tungstenite::tungstenite::Message::Text(ping_json)).await {
+                                                error!("Failed to send ping: {}", e);
+                                                health.record_error();
+                                                if health.should_reset(3) {
+                                                    break;
+                                                }
+                                            } else {
+                                                debug!("🏓 Sent ping");
+                                                health.record_message_sent();
                                             }
-                                        } else {
-                                            debug!("🏓 Sent ping");
-                                            health.record_message_sent();
                                         }
+                                        next_ping = Instant::now() + ping_interval;
                                     }
-                                    next_ping = Instant::now() + ping_interval;
-                                }
 
-                                // Log connection health periodically
-                                _ = sleep_until(next_health_log) => {
-                                    info!("📊 Connection health: {}", health.get_health_report());
-                                    next_health_log = Instant::now() + health_log_interval;
+                                    // Log connection health periodically
+                                    _ = sleep_until(next_health_log) => {
+                                        info!("📊 Connection health: {}", health.get_health_report());
+                                        next_health_log = Instant::now() + health_log_interval;
+                                    }
                                 }
                             }
                         }
@@ -732,6 +967,7 @@ impl MothershipDaemon {
                             let mut status_guard = status_clone.write().await;
                             status_guard.server_connected = false;
                         }
+                        health.record_disconnected();
                     }
                     Err(e) => {
                         error!("❌ Failed to connect to WebSocket: {}", e);
@@ -766,9 +1002,23 @@ impl MothershipDaemon {
                     }
                 }
                 
-                // Wait before reconnecting
+                // Wait before reconnecting, persisting any changes made in the meantime
                 info!("⏱️  Waiting {} seconds before reconnecting...", reconnect_delay.as_secs());
-                tokio::time::sleep(reconnect_delay).await;
+                let reconnect_at = Instant::now() + reconnect_delay;
+                loop {
+                    tokio::select! {
+                        msg = outgoing_rx.recv() => {
+                            match msg {
+                                Some(sync_msg) => Self::queue_offline(&offline_queue, &sync_msg, &status_clone).await,
+                                None => {
+                                    sleep_until(reconnect_at).await;
+                                    break;
+                                }
+                            }
+                        }
+                        _ = sleep_until(reconnect_at) => break,
+                    }
+                }
                 
                 // Reset health on reconnection attempt
                 health.record_reset();
@@ -785,25 +1035,187 @@ impl MothershipDaemon {
         Ok(())
     }
     
+    /// Ask the server which sync protocol versions it speaks and pick the highest
+    /// one we share. Servers that predate versioning (or cannot be reached yet)
+    /// are assumed to speak protocol 1.
+    async fn negotiate_server_protocol(server_url: &str) -> std::result::Result<u32, String> {
+        let url = format!("{}/capabilities", server_url.trim_end_matches('/'));
+        let request = async { Ok::<_, anyhow::Error>(tls::http_client(server_url)?.get(&url).send().await?) };
+        let capabilities: Option<serde_json::Value> = match request.await {
+            Ok(response) => response.json().await.ok(),
+            Err(e) => {
+                warn!("Could not fetch server capabilities, assuming protocol v1: {}", e);
+                None
+            }
+        };
+
+        let version_field = |name: &str| {
+            capabilities.as_ref()
+                .and_then(|c| c.get("data"))
+                .and_then(|d| d.get(name))
+                .and_then(|v| v.as_u64())
+                .map(|v| v as u32)
+                .unwrap_or(1)
+        };
+        let server_max = version_field("protocol_version");
+        let server_min = version_field("min_protocol_version");
+
+        negotiate_protocol_version(server_min, server_max)
+            .ok_or_else(|| protocol_mismatch_message(MIN_PROTOCOL_VERSION, PROTOCOL_VERSION, server_min, server_max))
+    }
+
+    /// The server's explanation if a message rejects our protocol version
+    fn protocol_rejection(sync_message: &SyncMessage) -> Option<String> {
+        match sync_message {
+            SyncMessage::Error { message, error_code: Some(code) } if code == PROTOCOL_MISMATCH_ERROR => Some(message.clone()),
+            _ => None,
+        }
+    }
+
+    /// Open a project's WebSocket, honoring the server's TLS settings
+    async fn connect_websocket(
+        ws_url: &str,
+        server_url: &str,
+    ) -> Result<(
+        tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>,
+        tokio_tungstenite::tungstenite::handshake::client::Response,
+    )> {
+        let connector = tls::settings_for(server_url).connector()?.map(tokio_tungstenite::Connector::NativeTls);
+        Ok(tokio_tungstenite::connect_async_tls_with_config(ws_url, None, false, connector).await?)
+    }
+
+    /// Encode a sync message as a WebSocket frame in the negotiated encoding
+    fn encode_frame(encoding: WireEncoding, sync_message: &SyncMessage) -> Result<tokio_tungstenite::tungstenite::Message> {
+        Ok(match encoding.encode(sync_message)? {
+            WireFrame::Text(json) => tokio_tungstenite::tungstenite::Message::Text(json),
+            WireFrame::Binary(bytes) => tokio_tungstenite::tungstenite::Message::Binary(bytes),
+        })
+    }
+
+    /// Decode a text or binary frame; the frame type identifies the encoding
+    /// RESUMPTION: Remember where we are in the rift's broadcasts so a reconnect
+    /// can resume the session, and unwrap sequenced messages for handling
+    fn track_session(sync_message: SyncMessage, resume: &mut Option<SessionResume>, project_id: Uuid) -> Vec<SyncMessage> {
+        match sync_message {
+            SyncMessage::Sequenced { seq, message } => {
+                if let Some(resume) = resume.as_mut() {
+                    resume.last_seq = seq;
+                }
+                vec![*message]
+            }
+            SyncMessage::SessionResumed { session_id, missed, seq, .. } => {
+                info!("🔁 Resumed sync session for project {} ({} missed messages)", project_id, missed.len());
+                *resume = Some(SessionResume { session_id, last_seq: seq });
+                missed
+                    .into_iter()
+                    .map(|missed| match missed {
+                        SyncMessage::Sequenced { message, .. } => *message,
+                        other => other,
+                    })
+                    .collect()
+            }
+            SyncMessage::RiftJoined { session_id, seq, .. } => {
+                *resume = session_id.map(|session_id| SessionResume { session_id, last_seq: seq });
+                vec![sync_message]
+            }
+            other => vec![other],
+        }
+    }
+
+    fn decode_frame(frame: tokio_tungstenite::tungstenite::Message) -> Result<SyncMessage> {
+        match frame {
+            tokio_tungstenite::tungstenite::Message::Binary(bytes) => wire::decode_binary(&bytes),
+            other => wire::decode_text(other.to_text()?),
+        }
+    }
+
+    /// Send every message in the offline queue over a freshly connected WebSocket.
+    /// Returns false if the connection failed mid-replay; unsent messages stay queued.
+    async fn replay_offline_queue<S>(
+        offline_queue: &OfflineQueue,
+        ws_sender: &mut S,
+        health: &mut ConnectionHealth,
+        status: &Arc<RwLock<DaemonStatus>>,
+        delta_sync: &mut DeltaSync,
+    ) -> bool
+    where
+        S: futures_util::Sink<tokio_tungstenite::tungstenite::Message> + Unpin,
+        S::Error: std::fmt::Display,
+    {
+        let queued = match offline_queue.load().await {
+            Ok(queued) => queued,
+            Err(e) => {
+                error!("Failed to read offline queue: {}", e);
+                return true;
+            }
+        };
+        
+        if queued.is_empty() {
+            return true;
+        }
+        
+        info!("📼 Replaying {} queued offline changes", queued.len());
+        
+        let mut sent = 0;
+        for sync_msg in &queued {
+            let json = match serde_json::to_string(sync_msg) {
+                Ok(json) => json,
+                Err(e) => {
+                    warn!("Dropping unserializable queued message: {}", e);
+                    sent += 1;
+                    continue;
+                }
+            };
+            
+            if let Err(e) = ws_sender.send(tokio_tungstenite::tungstenite::Message::Text(json)).await {
+                error!("Failed to replay queued message: {}", e);
+                health.record_error();
+                break;
+            }
+            
+            health.record_message_sent();
+            health.record_upload();
+            if let SyncMessage::FileChanged { path, content, .. } = sync_msg {
+                delta_sync.record(path, content);
+            }
+            sent += 1;
+        }
+        
+        if let Err(e) = offline_queue.replace(&queued[sent..]).await {
+            error!("Failed to update offline queue after replay: {}", e);
+        }
+        
+        {
+            let mut status_guard = status.write().await;
+            status_guard.queued_changes = status_guard.queued_changes.saturating_sub(sent);
+        }
+        
+        info!("✅ Replayed {}/{} queued offline changes", sent, queued.len());
+        sent == queued.len()
+    }
+    
+    /// Persist a message that could not be delivered so it is replayed on reconnect
+    async fn queue_offline(offline_queue: &OfflineQueue, sync_msg: &SyncMessage, status: &Arc<RwLock<DaemonStatus>>) {
+        match offline_queue.push(sync_msg).await {
+            Ok(()) => {
+                status.write().await.queued_changes += 1;
+                info!("💾 Server unreachable, change queued for replay");
+            }
+            Err(e) => error!("Failed to queue change offline, it will be lost: {}", e),
+        }
+    }
+    
     /// Handle incoming sync messages from the server
     async fn handle_incoming_sync_message(text: &str, project_path: &PathBuf, state: &Arc<MothershipDaemon>) -> Result<()> {
         let sync_message: SyncMessage = serde_json::from_str(text)
             .map_err(|e| anyhow::anyhow!("Failed to parse sync message: {}", e))?;
         
         match sync_message {
-            SyncMessage::FileChanged { path, content, .. } => {
+            SyncMessage::FileChanged { path, content, metadata, .. } => {
                 info!("📥 Received file change: {} ({} bytes)", path.display(), content.len());
                 
-                // Write the file to disk
-                let file_path = project_path.join(&path);
-                
-                // Create parent directories if needed
-                if let Some(parent) = file_path.parent() {
-                    tokio::fs::create_dir_all(parent).await?;
-                }
-                
-                // Write file content
-                tokio::fs::write(&file_path, &content).await?;
+                // Write the file to disk with its permissions or as a symlink
+                write_file(&project_path.join(&path), &content, Some(&metadata))?;
                 info!("💾 Wrote incoming file change: {}", path.display());
                 Ok(())
             }
@@ -817,20 +1229,12 @@ impl MothershipDaemon {
                 }
                 Ok(())
             }
-            SyncMessage::RiftJoined { current_files, .. } => {
+            SyncMessage::RiftJoined { current_files, file_metadata, .. } => {
                 info!("📥 Received current rift state with {} files", current_files.len());
                 
                 // Write all current files (initial sync)
                 for (path, content) in current_files {
-                    let file_path = project_path.join(&path);
-                    
-                    // Create parent directories if needed
-                    if let Some(parent) = file_path.parent() {
-                        tokio::fs::create_dir_all(parent).await?;
-                    }
-                    
-                    // Write file content
-                    tokio::fs::write(&file_path, &content).await?;
+                    write_file(&project_path.join(&path), &content, file_metadata.get(&path))?;
                     info!("💾 Wrote initial file: {}", path.display());
                 }
                 Ok(())
@@ -845,6 +1249,7 @@ impl MothershipDaemon {
                 rift_id: _,
                 conflict: _,
                 suggestions: _,
+                policy: _,
             } => {
                 info!("🔄 Conflict detected for {}, accepting server version", path.display());
                 
@@ -994,16 +1399,14 @@ impl MothershipDaemon {
 
     /// Handle WebSocket sync message (simplified version for static context)
     async fn handle_websocket_sync_message(
-        text: &str, 
+        sync_message: SyncMessage, 
         project_path: &PathBuf,
         server_write_flags: &Arc<RwLock<HashMap<Uuid, bool>>>,
         project_id: Uuid,
-    ) -> Result<()> {
-        let sync_message: SyncMessage = serde_json::from_str(text)
-            .map_err(|e| anyhow::anyhow!("Failed to parse sync message: {}", e))?;
-        
+        delta_sync: &mut DeltaSync,
+    ) -> Result<Option<SyncMessage>> {
         match sync_message {
-            SyncMessage::FileChanged { path, content, .. } => {
+            SyncMessage::FileChanged { path, content, metadata, .. } => {
                 info!("📥 Received file change from collaborator: {} ({} bytes)", path.display(), content.len());
                 
                 // Set server write flag to prevent file watcher loops
@@ -1012,16 +1415,9 @@ impl MothershipDaemon {
                     flags.insert(project_id, true);
                 }
                 
-                // Write the file to disk
-                let file_path = project_path.join(&path);
-                
-                // Create parent directories if needed
-                if let Some(parent) = file_path.parent() {
-                    tokio::fs::create_dir_all(parent).await?;
-                }
-                
-                // Write file content
-                tokio::fs::write(&file_path, &content).await?;
+                // Write the file to disk with its permissions or as a symlink
+                write_file(&project_path.join(&path), &content, Some(&metadata))?;
+                delta_sync.record(&path, &content);
                 info!("💾 Applied file change from collaborator: {}", path.display());
                 
                 // Clear server write flag
@@ -1029,8 +1425,9 @@ impl MothershipDaemon {
                     let mut flags = server_write_flags.write().await;
                     flags.remove(&project_id);
                 }
-                
-                Ok(())
+
+                run_post_sync_hook(project_path, vec![path]);
+                Ok(None)
             }
             SyncMessage::RiftDiffUpdate { diff_changes, .. } => {
                 info!("📥 Received {} diff updates from collaborator", diff_changes.len());
@@ -1042,29 +1439,32 @@ impl MothershipDaemon {
                 }
                 
                 // Apply all diffs
+                let mut applied = Vec::new();
                 for change in diff_changes {
                     let file_path = project_path.join(&change.path);
                     
-                    // Read current content
-                    let current_content = if file_path.exists() {
-                        tokio::fs::read_to_string(&file_path).await.unwrap_or_default()
-                    } else {
-                        String::new()
-                    };
+                    if let FileDiff::Deleted = change.diff {
+                        if tokio::fs::remove_file(&file_path).await.is_ok() {
+                            info!("🗑️ Removed {}", change.path.display());
+                            applied.push(change.path.clone());
+                        }
+                        delta_sync.forget(&change.path);
+                        continue;
+                    }
+
+                    // Read current content (a symlink's is its target)
+                    let current_content = read_file(&file_path).unwrap_or_default();
                     
                     // Apply diff
                     let diff_engine = DiffEngine::new();
                     match diff_engine.apply_diff(&current_content, &change.diff) {
                         Ok(new_content) => {
-                            // Create parent directories if needed
-                            if let Some(parent) = file_path.parent() {
-                                tokio::fs::create_dir_all(parent).await?;
-                            }
-                            
-                            // Write updated content
-                            tokio::fs::write(&file_path, &new_content).await?;
+                            // Write updated content with its permissions or as a symlink
+                            write_file(&file_path, &new_content, Some(&change.metadata))?;
+                            delta_sync.record(&change.path, &new_content);
                             info!("💾 Applied diff to {}: {} -> {} bytes", 
                                 change.path.display(), current_content.len(), new_content.len());
+                            applied.push(change.path);
                         }
                         Err(e) => {
                             error!("Failed to apply diff to {}: {}", change.path.display(), e);
@@ -1077,11 +1477,18 @@ impl MothershipDaemon {
                     let mut flags = server_write_flags.write().await;
                     flags.remove(&project_id);
                 }
-                
-                Ok(())
+
+                run_post_sync_hook(project_path, applied);
+                Ok(None)
             }
-            SyncMessage::RiftJoined { current_files, .. } => {
-                info!("📥 Received initial rift state with {} files", current_files.len());
+            SyncMessage::RiftJoined { rift_id, current_files, protocol_version, file_metadata, reconcile, last_checkpoint, .. } => {
+                info!("📥 Received initial rift state with {} files (protocol v{})", current_files.len(), protocol_version);
+
+                // A manifest for another checkpoint (or rift) would make 'mothership status' misleading;
+                // without one the CLI fetches the right one
+                if CheckpointManifest::load(project_path).is_some_and(|m| m.checkpoint_id != last_checkpoint) {
+                    let _ = std::fs::remove_file(project_path.join(".mothership").join(CHECKPOINT_MANIFEST_FILE));
+                }
                 
                 // Set server write flag to prevent file watcher loops
                 {
@@ -1089,35 +1496,211 @@ impl MothershipDaemon {
                     flags.insert(project_id, true);
                 }
                 
-                // Write all current files (initial sync)
+                // Write all current files (initial sync, or what changed on the server while we were away)
+                let mut written: Vec<PathBuf> = current_files.keys().cloned().collect();
                 for (path, content) in current_files {
-                    let file_path = project_path.join(&path);
-                    
-                    // Create parent directories if needed
-                    if let Some(parent) = file_path.parent() {
-                        tokio::fs::create_dir_all(parent).await?;
-                    }
-                    
-                    // Write file content
-                    tokio::fs::write(&file_path, &content).await?;
+                    write_file(&project_path.join(&path), &content, file_metadata.get(&path))?;
+                    delta_sync.record(&path, &content);
                     info!("💾 Wrote initial file: {}", path.display());
                 }
                 
+                // RECONCILIATION: Drop files deleted on the server, and send back what changed here
+                let mut upload = Vec::new();
+                if let Some(plan) = reconcile {
+                    info!("🔄 Reconciling: {} to send, {} received, {} deleted on server, {} conflicts",
+                        plan.upload.len(), plan.download.len(), plan.delete.len(), plan.conflicts.len());
+                    for path in plan.delete {
+                        if tokio::fs::remove_file(project_path.join(&path)).await.is_ok() {
+                            info!("🗑️ Removed {}", path.display());
+                            written.push(path.clone());
+                        }
+                        delta_sync.forget(&path);
+                    }
+                    for path in plan.upload {
+                        let file_path = project_path.join(&path);
+                        let change = match read_file(&file_path) {
+                            Ok(content) => {
+                                delta_sync.record(&path, &content);
+                                FileDiffChange {
+                                    file_size: content.len() as u64,
+                                    diff: FileDiff::FullContent(content),
+                                    metadata: FileMetadata::read(&file_path).unwrap_or_default(),
+                                    path,
+                                }
+                            }
+                            Err(_) => {
+                                delta_sync.forget(&path);
+                                FileDiffChange { path, diff: FileDiff::Deleted, file_size: 0, metadata: FileMetadata::default() }
+                            }
+                        };
+                        upload.push(change);
+                    }
+                }
+                
                 // Clear server write flag
                 {
                     let mut flags = server_write_flags.write().await;
                     flags.remove(&project_id);
                 }
+
+                run_post_sync_hook(project_path, written);
+                if upload.is_empty() {
+                    return Ok(None);
+                }
+                info!("📤 Sending {} changes made while offline", upload.len());
+                Ok(Some(SyncMessage::BatchDiffChanges {
+                    rift_id,
+                    changes: upload,
+                    timestamp: chrono::Utc::now(),
+                    compressed: false,
+                }))
+            }
+            SyncMessage::ConflictDetected { rift_id, path, server_content, policy, .. } => {
+                // A policy in .mothership/config.json takes precedence over the project's
+                let policy = load_local_config(project_path).await.conflict_policy.unwrap_or(policy);
+                warn!("⚔️ Conflict on {}, resolving with policy {}", path.display(), policy.as_str());
+                let file_path = project_path.join(&path);
+                let local_content = tokio::fs::read_to_string(&file_path).await.unwrap_or_default();
                 
-                Ok(())
+                match policy {
+                    ConflictPolicy::ClientWins => {
+                        // Our copy replaces the server's
+                        delta_sync.record(&path, &local_content);
+                        Ok(Some(SyncMessage::FileChanged {
+                            rift_id,
+                            metadata: FileMetadata::read(&file_path).unwrap_or_default(),
+                            path,
+                            content: local_content,
+                            timestamp: chrono::Utc::now(),
+                        }))
+                    }
+                    ConflictPolicy::Prompt => {
+                        // Keep our copy and park the server's outside the synced tree; saving
+                        // the reconciled file sends it as a change on top of the server's copy
+                        let parked = project_path.join(".mothership").join(CONFLICTS_DIR).join(&path);
+                        if let Some(parent) = parked.parent() {
+                            tokio::fs::create_dir_all(parent).await?;
+                        }
+                        tokio::fs::write(&parked, &server_content).await?;
+                        delta_sync.record(&path, &server_content);
+                        warn!("📝 Server's version of {} saved to {} - merge it into your copy and save to sync",
+                            path.display(), parked.display());
+                        Ok(None)
+                    }
+                    ConflictPolicy::ServerWins | ConflictPolicy::AlwaysCreateConflictRift => {
+                        {
+                            let mut flags = server_write_flags.write().await;
+                            flags.insert(project_id, true);
+                        }
+                        if let Some(parent) = file_path.parent() {
+                            tokio::fs::create_dir_all(parent).await?;
+                        }
+                        tokio::fs::write(&file_path, &server_content).await?;
+                        delta_sync.record(&path, &server_content);
+                        {
+                            let mut flags = server_write_flags.write().await;
+                            flags.remove(&project_id);
+                        }
+                        
+                        if policy == ConflictPolicy::ServerWins || local_content == server_content {
+                            info!("💾 Took server's version of {}", path.display());
+                            return Ok(None);
+                        }
+                        
+                        // Our change moves to a new rift instead of being lost
+                        let diff = DiffEngine::new().generate_line_diff(&server_content, &local_content);
+                        Ok(Some(SyncMessage::CreateConflictRift {
+                            original_rift_id: rift_id,
+                            conflict_rift_name: format!("conflict-{}", chrono::Utc::now().format("%Y%m%d-%H%M%S")),
+                            conflicting_files: vec![ConflictingFile {
+                                path,
+                                content: local_content,
+                                original_content: server_content,
+                                diff,
+                            }],
+                            // The server attributes the rift to the connected user
+                            author: Uuid::nil(),
+                            timestamp: chrono::Utc::now(),
+                        }))
+                    }
+                }
+            }
+            SyncMessage::CheckpointCreated { checkpoint_id, timestamp, .. } => {
+                info!("📸 Checkpoint {} created", &checkpoint_id.to_string()[..8]);
+
+                // The checkpoint snapshots what the server holds, which is what was last synced.
+                // A manifest the CLI already fetched for it is exact, so keep that one.
+                if CheckpointManifest::load(project_path).map(|m| m.checkpoint_id) != Some(Some(checkpoint_id)) {
+                    let manifest = CheckpointManifest {
+                        checkpoint_id: Some(checkpoint_id),
+                        recorded_at: timestamp,
+                        files: delta_sync.synced_hashes().clone(),
+                    };
+                    if let Err(e) = manifest.save(project_path) {
+                        warn!("Failed to record checkpoint manifest: {}", e);
+                    }
+                }
+                Ok(None)
+            }
+            SyncMessage::ConflictRiftCreated { conflict_rift_name, .. } => {
+                info!("✨ Your conflicting changes are in rift '{}'", conflict_rift_name);
+                info!("🔀 Use 'mothership beam \"{}\"' to work on them", conflict_rift_name);
+                Ok(None)
+            }
+            SyncMessage::DeltaRejected { rift_id, path, base_hash } => {
+                if !delta_sync.take_rejected(&path, &base_hash) {
+                    return Ok(None);
+                }
+                
+                // DELTA SYNC: Server's copy diverged from our base - resend the whole file
+                warn!("⚠️ Server rejected block delta for {}, resending full content", path.display());
+                let file_path = project_path.join(&path);
+                let content = read_file(&file_path)?;
+                delta_sync.record(&path, &content);
+                Ok(Some(SyncMessage::FileChanged {
+                    rift_id,
+                    path,
+                    content,
+                    timestamp: chrono::Utc::now(),
+                    metadata: FileMetadata::read(&file_path).unwrap_or_default(),
+                }))
+            }
+            SyncMessage::Error { message, error_code: Some(code) } if code == PUSH_RULE_ERROR => {
+                // The change stays local; the user has to fix it before it can sync
+                warn!("🚫 {}", message);
+                Ok(None)
+            }
+            SyncMessage::Error { message, error_code: Some(code) } if code == OBSERVER_ERROR => {
+                warn!("👁️ {}", message);
+                Ok(None)
+            }
+            SyncMessage::Error { message, error_code: Some(code) } if code == SECRET_FOUND_WARNING => {
+                warn!("🔑 {}", message);
+                Ok(None)
+            }
+            SyncMessage::Error { message, error_code: Some(code) } if code == FILE_QUARANTINED_WARNING => {
+                // The file's removal arrives as a regular rift update
+                warn!("🦠 {}", message);
+                Ok(None)
+            }
+            SyncMessage::Error { message, error_code: Some(code) }
+                if code == MESSAGE_TOO_LARGE_ERROR || code == BATCH_TOO_LARGE_ERROR || code == RATE_LIMITED_ERROR =>
+            {
+                // The server dropped the change; it goes out again the next time the files change
+                warn!("🚧 {}", message);
+                Ok(None)
+            }
+            SyncMessage::Notification { notification } => {
+                info!("🔔 {}", notification.title);
+                Ok(None)
             }
             SyncMessage::Heartbeat => {
                 debug!("🏓 Received heartbeat from server");
-                Ok(())
+                Ok(None)
             }
             _ => {
                 debug!("📨 Received sync message: {:?} (not handled in WebSocket context)", std::mem::discriminant(&sync_message));
-                Ok(())
+                Ok(None)
             }
         }
     }
@@ -1175,6 +1758,37 @@ struct ProjectMetadata {
     created_at: String,
     mothership_url: String,
     rift_id: Option<String>, // CRITICAL FIX: Read rift_id for WebSocket connection
+    #[serde(default)]
+    observer: bool,
+}
+
+/// Where the `prompt` conflict policy parks the server's copy of a file, inside `.mothership/`
+const CONFLICTS_DIR: &str = "conflicts";
+
+/// Project-local settings in `.mothership/config.json`, overriding the project's
+#[derive(Debug, Default, serde::Deserialize)]
+struct LocalProjectConfig {
+    #[serde(default)]
+    conflict_policy: Option<ConflictPolicy>,
+}
+
+/// Load `.mothership/config.json`, if the project has one
+async fn load_local_config(project_path: &Path) -> LocalProjectConfig {
+    let config_path = project_path.join(".mothership").join("config.json");
+    match tokio::fs::read_to_string(&config_path).await {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+            warn!("Ignoring invalid {}: {}", config_path.display(), e);
+            LocalProjectConfig::default()
+        }),
+        Err(_) => LocalProjectConfig::default(),
+    }
+}
+
+/// The server a project syncs with: its profile's server, otherwise the active connection
+fn project_server_url(project_path: &Path) -> Option<String> {
+    profile::active_profile_in(project_path)
+        .and_then(|name| profile::server_url_for(&name))
+        .or_else(get_active_server_url)
 }
 
 /// Get the active server URL (prioritize active connection over project metadata)
@@ -1216,8 +1830,38 @@ fn get_active_server_url() -> Option<String> {
     None
 }
 
-/// Load stored authentication token for WebSocket connection
-fn load_auth_token() -> Option<String> {
+/// Fetch a project's settings from the active server
+pub(crate) async fn fetch_project_settings(project_id: Uuid, project_path: &Path) -> Option<ProjectSettings> {
+    let server_url = project_server_url(project_path)?;
+    let auth_token = load_auth_token(project_path)?;
+    let url = format!("{}/projects/{}/settings", server_url.trim_end_matches('/'), project_id);
+
+    let client = match tls::http_client(&server_url) {
+        Ok(client) => client,
+        Err(e) => {
+            warn!("Invalid TLS settings for {}: {}", server_url, e);
+            return None;
+        }
+    };
+    let response = match client.get(&url).bearer_auth(auth_token).send().await {
+        Ok(response) => response,
+        Err(e) => {
+            warn!("Could not fetch settings of project {}: {}", project_id, e);
+            return None;
+        }
+    };
+    match response.json::<ApiResponse<ProjectSettings>>().await {
+        Ok(body) => body.data,
+        Err(e) => {
+            warn!("Could not read settings of project {}: {}", project_id, e);
+            None
+        }
+    }
+}
+
+/// Load stored authentication token for WebSocket connection, from the
+/// credentials of the project's profile
+fn load_auth_token(project_path: &Path) -> Option<String> {
     use serde::{Deserialize, Serialize};
     
     #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1229,8 +1873,7 @@ fn load_auth_token() -> Option<String> {
     }
     
     // Try to load OAuth credentials first
-    if let Some(config_dir) = dirs::config_dir() {
-        let credentials_path = config_dir.join("mothership").join("credentials.json");
+    if let Some(credentials_path) = profile::credentials_path_for(profile::active_profile_in(project_path).as_deref()) {
         if credentials_path.exists() {
             if let Ok(credentials_content) = std::fs::read_to_string(&credentials_path) {
                 if let Ok(credentials) = serde_json::from_str::<StoredCredentials>(&credentials_content) {
@@ -1257,48 +1900,76 @@ fn load_auth_token() -> Option<String> {
     None
 }
 
+/// A project's connection health; every change is published to the daemon's
+/// metrics for the IPC `/metrics` route
 #[derive(Debug, Clone)]
 struct ConnectionHealth {
+    project_id: Uuid,
     last_ping_time: Instant,
-    consecutive_errors: u32,
-    total_messages_sent: u64,
-    total_messages_received: u64,
-    connection_resets: u32,
-    last_reset_time: Option<Instant>,
+    stats: ConnectionStats,
 }
 
 impl ConnectionHealth {
-    fn new() -> Self {
-        Self {
+    fn new(project_id: Uuid) -> Self {
+        let health = Self {
+            project_id,
             last_ping_time: Instant::now(),
-            consecutive_errors: 0,
-            total_messages_sent: 0,
-            total_messages_received: 0,
-            connection_resets: 0,
-            last_reset_time: None,
-        }
+            stats: ConnectionStats::default(),
+        };
+        health.publish();
+        health
+    }
+
+    fn publish(&self) {
+        metrics().update_connection(self.project_id, &self.stats);
+    }
+
+    fn record_connected(&mut self) {
+        self.stats.connected = true;
+        self.stats.connected_since = Some(chrono::Utc::now());
+        self.publish();
+    }
+
+    fn record_disconnected(&mut self) {
+        self.stats.connected = false;
+        self.stats.connected_since = None;
+        self.publish();
     }
 
     fn record_message_sent(&mut self) {
-        self.total_messages_sent += 1;
+        self.stats.messages_sent += 1;
+        self.publish();
     }
 
     fn record_message_received(&mut self) {
-        self.total_messages_received += 1;
-        self.consecutive_errors = 0; // Reset errors on successful receive
+        self.stats.messages_received += 1;
+        self.stats.consecutive_errors = 0; // Reset errors on successful receive
+        self.publish();
+    }
+
+    fn record_upload(&mut self) {
+        self.stats.last_upload = Some(chrono::Utc::now());
+        self.publish();
+    }
+
+    fn record_download(&mut self) {
+        self.stats.last_download = Some(chrono::Utc::now());
+        self.publish();
     }
 
     fn record_error(&mut self) {
-        self.consecutive_errors += 1;
+        self.stats.consecutive_errors += 1;
+        self.publish();
     }
 
     fn record_reset(&mut self) {
-        self.connection_resets += 1;
-        self.last_reset_time = Some(Instant::now());
+        self.stats.connection_resets += 1;
+        self.stats.last_reset = Some(chrono::Utc::now());
+        self.publish();
     }
 
     fn should_reset(&self, max_errors: u32) -> bool {
-        self.consecutive_errors >= max_errors
+        self.stats.consecutive_errors >= max_errors
     }
 
     fn get_health_report(&self) -> String {
@@ -1310,11 +1981,11 @@ impl ConnectionHealth {
              - Total Connection Resets: {}\n\
              - Time Since Last Reset: {}\n\
              - Time Since Last Ping: {}s",
-            self.total_messages_sent,
-            self.total_messages_received,
-            self.consecutive_errors,
-            self.connection_resets,
-            self.last_reset_time.map_or("Never".to_string(), |t| format!("{:?} ago", t.elapsed())),
+            self.stats.messages_sent,
+            self.stats.messages_received,
+            self.stats.consecutive_errors,
+            self.stats.connection_resets,
+            self.stats.last_reset.map_or("Never".to_string(), |t| format!("{}s ago", (chrono::Utc::now() - t).num_seconds())),
             self.last_ping_time.elapsed().as_secs()
         )
     }
@@ -1325,4 +1996,22 @@ fn crypto_hash(content: &str) -> String {
     let mut hasher = Sha256::new();
     hasher.update(content.as_bytes());
     format!("{:x}", hasher.finalize())
-} 
\ No newline at end of file
+} 
+
+/// Run the project's post-sync hook in the background on files just written
+/// from the server. Post hooks can't veto anything, so failures are only logged.
+fn run_post_sync_hook(project_path: &Path, paths: Vec<PathBuf>) {
+    if paths.is_empty() || hooks::find_hook(project_path, Hook::PostSync).is_none() {
+        return;
+    }
+
+    let project_path = project_path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let changed_files = paths.iter().map(|p| p.display().to_string()).collect::<Vec<_>>().join("\n");
+        match hooks::run_hook(&project_path, Hook::PostSync, &[("MOTHERSHIP_CHANGED_FILES", changed_files)]) {
+            Ok(outcome) if outcome.allows() => debug!("🪝 post-sync hook finished for {}", project_path.display()),
+            Ok(outcome) => warn!("🪝 {}", outcome.veto_error(Hook::PostSync)),
+            Err(e) => warn!("🪝 {}", e),
+        }
+    });
+}
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use mothership_common::{diff::BLOCK_DELTA_MIN_SIZE, DiffEngine, FileDiff, Manifest, SparseSpec, SyncMessage};
+
+use crate::sync_base::SyncBase;
+
+/// Tracks the content the server holds for each file of a connected rift so
+/// large file changes can be sent as block deltas instead of full content.
+///
+/// State is per connection: it is rebuilt from what is sent and received after
+/// every reconnect, so a delta is only ever computed against content the server
+/// has already seen. Only the hashes of that content outlive the connection,
+/// in the project's [`SyncBase`].
+#[derive(Default)]
+pub struct DeltaSync {
+    /// Last content exchanged with the server, by path
+    synced: HashMap<PathBuf, String>,
+    /// Base hash of the most recent delta sent for each path
+    sent_bases: HashMap<PathBuf, String>,
+    /// Hashes of the synced content, kept across restarts
+    base: SyncBase,
+}
+
+impl DeltaSync {
+    /// Start a connection for a project, picking up the hashes of the content
+    /// synced in earlier sessions
+    pub fn for_project(project_path: &Path) -> Self {
+        Self { base: SyncBase::for_project(project_path), ..Self::default() }
+    }
+
+    /// Record content known to match the server's copy
+    pub fn record(&mut self, path: &Path, content: &str) {
+        self.synced.insert(path.to_path_buf(), content.to_string());
+        self.base.record(path, content);
+    }
+
+    /// Forget a file that was deleted
+    pub fn forget(&mut self, path: &Path) {
+        self.synced.remove(path);
+        self.sent_bases.remove(path);
+        self.base.forget(path);
+    }
+
+    /// The project's files with the hashes they had when last synced
+    pub fn manifest(&self, project_path: &Path, sparse: &SparseSpec) -> Manifest {
+        self.base.manifest(project_path, sparse)
+    }
+
+    /// Hash of each file as last synced
+    pub fn synced_hashes(&self) -> &HashMap<PathBuf, String> {
+        self.base.hashes()
+    }
+
+    /// Persist what was synced since the last call
+    pub fn save(&mut self) {
+        self.base.save();
+    }
+
+    /// Convert a full-content change of a large file into a block delta when the
+    /// server's copy is known. Other messages are returned unchanged.
+    pub fn encode(&mut self, msg: &SyncMessage) -> SyncMessage {
+        let SyncMessage::FileChanged { rift_id, path, content, timestamp, metadata } = msg else {
+            match msg {
+                SyncMessage::FileMoved { from, to, .. } => {
+                    if let Some(content) = self.synced.remove(from) {
+                        self.synced.insert(to.clone(), content);
+                    }
+                    self.base.moved(from, to);
+                }
+                SyncMessage::FileDiffChanged { path, diff: FileDiff::Deleted, .. } => self.forget(path),
+                SyncMessage::BatchDiffChanges { changes, .. } => {
+                    for change in changes {
+                        match &change.diff {
+                            FileDiff::FullContent(content) => self.record(&change.path, content),
+                            FileDiff::Deleted => self.forget(&change.path),
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+            return msg.clone();
+        };
+
+        self.base.record(path, content);
+        let base = self.synced.insert(path.clone(), content.clone());
+        if content.len() < BLOCK_DELTA_MIN_SIZE {
+            return msg.clone();
+        }
+        let Some(base) = base else {
+            return msg.clone();
+        };
+
+        match DiffEngine::new().generate_block_delta(&base, content) {
+            FileDiff::BlockDelta { block_size, base_hash, operations, new_size } => {
+                self.sent_bases.insert(path.clone(), base_hash.clone());
+                SyncMessage::FileDiffChanged {
+                    rift_id: *rift_id,
+                    path: path.clone(),
+                    diff: FileDiff::BlockDelta { block_size, base_hash, operations, new_size },
+                    file_size: content.len() as u64,
+                    timestamp: *timestamp,
+                    metadata: metadata.clone(),
+                }
+            }
+            _ => msg.clone(),
+        }
+    }
+
+    /// Handle a rejected delta. Returns true if this connection sent it, in
+    /// which case the file must be resent in full.
+    pub fn take_rejected(&mut self, path: &Path, base_hash: &str) -> bool {
+        if self.sent_bases.get(path).map(String::as_str) != Some(base_hash) {
+            return false;
+        }
+        self.sent_bases.remove(path);
+        self.synced.remove(path);
+        true
+    }
+}
@@ -1,18 +1,37 @@
 use anyhow::Result;
+use mothership_common::{metadata::read_file, upload::content_hash, FileMetadata, IgnoreMatcher, SparseSpec, IGNORE_FILE_NAME};
+use notify::event::{ModifyKind, RenameMode};
 use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::sync::mpsc;
+use std::sync::{mpsc, Arc, Mutex, RwLock};
 use std::time::{Duration, Instant};
 use tokio::sync::mpsc as async_mpsc;
 use tracing::{error, info, warn, debug};
 use uuid::Uuid;
 
-/// Maximum file size to process (1MB limit)
-const MAX_FILE_SIZE: u64 = 1_048_576; // 1MB in bytes
+use crate::sync_filter::{SkipReason, SyncFilter, SyncFilterSettings};
+use crate::throttle::throttle;
 
-/// Minimum debounce interval between file events (100ms)
-const DEBOUNCE_INTERVAL: Duration = Duration::from_millis(100);
+/// Default minimum interval between events for the same file
+const DEFAULT_DEBOUNCE_MS: u64 = 100;
+
+/// Default window in which changes are coalesced into one batch
+const DEFAULT_BATCH_WINDOW_MS: u64 = 200;
+
+/// Default number of files that sends a batch before its window ends
+const DEFAULT_MAX_BATCH_SIZE: usize = 50;
+
+/// Longest debounce or batch window accepted, so changes can't be held back indefinitely
+const MAX_WINDOW_MS: u64 = 10_000;
+
+/// Key of the watcher settings in `.mothership/config.json`
+const SETTINGS_KEY: &str = "watcher";
+
+/// How long a deleted file waits for a create with the same content before
+/// it is reported as deleted rather than moved
+const MOVE_DETECTION_WINDOW: Duration = Duration::from_millis(500);
 
 /// File change event sent to the daemon
 #[derive(Debug, Clone)]
@@ -23,24 +42,151 @@ pub struct FileChangeEvent {
     pub file_size: u64,
     pub timestamp: chrono::DateTime<chrono::Utc>,
     pub change_type: FileChangeType,
+    /// Permissions, symlink target and mtime when the change was seen
+    pub metadata: FileMetadata,
 }
 
+/// Changes sent to the daemon together; all from the same project
+pub type FileChangeBatch = Vec<FileChangeEvent>;
+
 #[derive(Debug, Clone)]
 pub enum FileChangeType {
     Created,
     Modified,
     Deleted,
+    Moved { from: PathBuf },
+}
+
+/// How a project's watcher debounces and batches changes, set by the
+/// `watcher` key of `.mothership/config.json` or the daemon's IPC API
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatcherSettings {
+    /// Events for the same file closer together than this are dropped
+    pub debounce_ms: u64,
+    /// Changes within this long of the first are sent as one batch; 0 sends each change on its own
+    pub batch_window_ms: u64,
+    /// A batch holding this many files is sent without waiting for its window to end
+    pub max_batch_size: usize,
+}
+
+impl Default for WatcherSettings {
+    fn default() -> Self {
+        Self {
+            debounce_ms: DEFAULT_DEBOUNCE_MS,
+            batch_window_ms: DEFAULT_BATCH_WINDOW_MS,
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
+        }
+    }
+}
+
+impl WatcherSettings {
+    /// Load a project's settings, falling back to the defaults
+    pub fn load(project_path: &Path) -> Self {
+        read_config_section::<Self>(project_path, SETTINGS_KEY)
+            .filter(|settings| settings.validate().is_ok())
+            .unwrap_or_default()
+    }
+
+    /// Save the settings into `.mothership/config.json`, keeping its other keys
+    pub fn save(&self, project_path: &Path) -> Result<()> {
+        write_config_section(project_path, SETTINGS_KEY, self)
+    }
+
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        if self.max_batch_size == 0 {
+            return Err("max_batch_size must be at least 1".to_string());
+        }
+        if self.debounce_ms > MAX_WINDOW_MS || self.batch_window_ms > MAX_WINDOW_MS {
+            return Err(format!("debounce_ms and batch_window_ms can be at most {}", MAX_WINDOW_MS));
+        }
+        Ok(())
+    }
+
+    fn debounce(&self) -> Duration {
+        Duration::from_millis(self.debounce_ms)
+    }
+
+    fn batch_window(&self) -> Duration {
+        Duration::from_millis(self.batch_window_ms)
+    }
+}
+
+fn settings_file(project_path: &Path) -> PathBuf {
+    project_path.join(".mothership").join("config.json")
+}
+
+/// Read one key of a project's `.mothership/config.json`
+pub fn read_config_section<T: DeserializeOwned>(project_path: &Path, key: &str) -> Option<T> {
+    std::fs::read_to_string(settings_file(project_path))
+        .ok()
+        .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+        .and_then(|config| config.get(key).cloned())
+        .and_then(|section| serde_json::from_value(section).ok())
+}
+
+/// Write one key of a project's `.mothership/config.json`, keeping the others
+pub fn write_config_section<T: Serialize>(project_path: &Path, key: &str, section: &T) -> Result<()> {
+    let path = settings_file(project_path);
+    let mut config = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+        .filter(|config| config.is_object())
+        .unwrap_or_else(|| serde_json::json!({}));
+    config[key] = serde_json::to_value(section)?;
+    std::fs::write(path, serde_json::to_string_pretty(&config)?)?;
+    Ok(())
+}
+
+/// Changes waiting to be sent to the daemon together
+#[derive(Default)]
+struct ChangeBatch {
+    events: FileChangeBatch,
+    started: Option<Instant>,
+}
+
+impl ChangeBatch {
+    /// Add a change, dropping an earlier change of the same file it supersedes.
+    /// Moves are kept in order so the files they create exist.
+    fn push(&mut self, event: FileChangeEvent) {
+        let is_move = |e: &FileChangeEvent| matches!(e.change_type, FileChangeType::Moved { .. });
+        if !is_move(&event) {
+            self.events.retain(|e| e.file_path != event.file_path || is_move(e));
+        }
+        self.events.push(event);
+        self.started.get_or_insert_with(Instant::now);
+    }
+
+    /// Whether the batch should be sent now
+    fn is_due(&self, settings: &WatcherSettings) -> bool {
+        self.started.is_some_and(|started| {
+            started.elapsed() >= settings.batch_window() || self.events.len() >= settings.max_batch_size
+        })
+    }
+
+    /// Time until the batch's window ends, if it has changes
+    fn time_left(&self, settings: &WatcherSettings) -> Option<Duration> {
+        self.started.map(|started| settings.batch_window().saturating_sub(started.elapsed()))
+    }
+
+    fn take(&mut self) -> FileChangeBatch {
+        self.started = None;
+        std::mem::take(&mut self.events)
+    }
 }
 
 /// Debouncing state for file events
 struct FileDebouncer {
     last_event_time: HashMap<PathBuf, Instant>,
+    /// Minimum time between processed events for the same file
+    interval: Duration,
 }
 
 impl FileDebouncer {
     fn new() -> Self {
         Self {
             last_event_time: HashMap::new(),
+            interval: Duration::from_millis(DEFAULT_DEBOUNCE_MS),
         }
     }
 
@@ -49,7 +195,7 @@ impl FileDebouncer {
         let now = Instant::now();
         
         if let Some(&last_time) = self.last_event_time.get(path) {
-            if now.duration_since(last_time) < DEBOUNCE_INTERVAL {
+            if now.duration_since(last_time) < self.interval {
                 debug!("⏳ Debouncing file event for {}", path.display());
                 return false;
             }
@@ -70,10 +216,117 @@ impl FileDebouncer {
     }
 }
 
+/// Pairs deletes with creates of the same content, so a moved or renamed
+/// file is reported as a move instead of a delete plus a create
+struct MoveDetector {
+    /// Content hash of each tracked file, by relative path
+    known_hashes: HashMap<PathBuf, String>,
+    /// Deleted files waiting for their content to reappear elsewhere
+    pending_deletes: Vec<(PathBuf, String, Instant)>,
+}
+
+impl MoveDetector {
+    /// Start from the hashes of the project's tracked files
+    fn scan(project_path: &Path, ignore_matcher: &IgnoreMatcher, sparse: &SparseSpec, filter: &mut SyncFilter) -> Self {
+        Self { known_hashes: tracked_file_hashes(project_path, ignore_matcher, sparse, filter), pending_deletes: Vec::new() }
+    }
+
+    /// Remember a file's current content
+    fn record(&mut self, path: &Path, content: &str) {
+        self.known_hashes.insert(path.to_path_buf(), content_hash(content.as_bytes()));
+    }
+
+    /// Hold back the deletion of a tracked file until it expires or matches a create
+    fn deleted(&mut self, path: &Path) {
+        if let Some(hash) = self.known_hashes.remove(path) {
+            self.pending_deletes.retain(|(pending, _, _)| pending != path);
+            self.pending_deletes.push((path.to_path_buf(), hash, Instant::now()));
+        }
+    }
+
+    /// The deleted file a newly created file was moved from, if any
+    fn moved_from(&mut self, content: &str) -> Option<PathBuf> {
+        let hash = content_hash(content.as_bytes());
+        let index = self.pending_deletes.iter().position(|(_, pending, _)| *pending == hash)?;
+        Some(self.pending_deletes.remove(index).0)
+    }
+
+    /// Deleted files that weren't moved within the detection window
+    fn expired(&mut self) -> Vec<PathBuf> {
+        let (expired, pending) = std::mem::take(&mut self.pending_deletes)
+            .into_iter()
+            .partition(|(_, _, deleted_at)| deleted_at.elapsed() >= MOVE_DETECTION_WINDOW);
+        self.pending_deletes = pending;
+        expired.into_iter().map(|(path, _, _)| path).collect()
+    }
+}
+
+/// Content hash of every file the watcher syncs, by path relative to the
+/// project. Runs as a hash job of the daemon's throttle and blocks while it waits.
+pub fn tracked_file_hashes(
+    project_path: &Path,
+    ignore_matcher: &IgnoreMatcher,
+    sparse: &SparseSpec,
+    filter: &mut SyncFilter,
+) -> HashMap<PathBuf, String> {
+    let _job = throttle().hash_job();
+    let mut hashes = HashMap::new();
+
+    for entry in walkdir::WalkDir::new(project_path)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || (!is_hidden(e.path()) && !ignore_matcher.is_ignored(e.path(), e.file_type().is_dir())))
+        .filter_map(|e| e.ok())
+    {
+        if entry.file_type().is_dir() {
+            continue;
+        }
+        let Ok(relative_path) = entry.path().strip_prefix(project_path) else {
+            continue;
+        };
+        if !sparse.includes(relative_path) {
+            continue;
+        }
+        // Symlinks are synced as their target, which no limit applies to
+        let content = if entry.path_is_symlink() {
+            read_file(entry.path()).ok()
+        } else {
+            entry.metadata().ok()
+                .filter(|metadata| filter.admits(relative_path, metadata.len()))
+                .and_then(|_| throttle().read(entry.path()).ok())
+                .filter(|bytes| filter.admits_content(relative_path, bytes))
+                .and_then(|bytes| String::from_utf8(bytes).ok())
+        };
+        if let Some(content) = content {
+            hashes.insert(relative_path.to_path_buf(), content_hash(content.as_bytes()));
+        }
+    }
+
+    hashes
+}
+
+/// Whether a path is the `.mothership/project.json` of a project nested in
+/// the one at `project_path`
+fn is_nested_project_file(path: &Path, project_path: &Path) -> bool {
+    path.ends_with(Path::new(".mothership").join("project.json"))
+        && path.parent().and_then(Path::parent).is_some_and(|root| root != project_path)
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .map(|name| name.starts_with('.'))
+        .unwrap_or(false)
+}
+
 /// Background file watcher for a single project
 pub struct FileWatcher {
     project_path: PathBuf,
     project_id: Uuid,
+    /// Debounce and batch settings, read by the watcher thread as events arrive
+    settings: Arc<RwLock<WatcherSettings>>,
+    /// Size and type limits, and the files they leave out
+    filter: Arc<Mutex<SyncFilter>>,
     _watcher: RecommendedWatcher, // Keep alive to maintain watching
 }
 
@@ -82,7 +335,7 @@ impl FileWatcher {
     pub async fn new(
         project_path: PathBuf,
         project_id: Uuid,
-        change_sender: async_mpsc::UnboundedSender<FileChangeEvent>,
+        change_sender: async_mpsc::UnboundedSender<FileChangeBatch>,
     ) -> Result<Self> {
         info!("🔍 Setting up file watcher for project {} at {}", 
             project_id, project_path.display());
@@ -100,16 +353,16 @@ impl FileWatcher {
         watcher.watch(&project_path, RecursiveMode::Recursive)?;
         
         // CRITICAL FIX: Create a sync channel bridge for async/sync boundary
-        let (sync_tx, sync_rx) = mpsc::channel::<FileChangeEvent>();
+        let (sync_tx, sync_rx) = mpsc::channel::<FileChangeBatch>();
         
         // Spawn async task to bridge sync -> async channels
         let async_sender = change_sender.clone();
         tokio::spawn(async move {
             info!("🌉 Starting async/sync bridge for file watcher");
             // Convert sync receiver to iterator and process events
-            while let Ok(event) = sync_rx.recv() {
-                debug!("🔄 Forwarding file change event through async bridge");
-                if let Err(e) = async_sender.send(event) {
+            while let Ok(changes) = sync_rx.recv() {
+                debug!("🔄 Forwarding {} file changes through async bridge", changes.len());
+                if let Err(e) = async_sender.send(changes) {
                     error!("Failed to forward file change event to daemon: {}", e);
                     break; // Channel closed, stop the bridge
                 }
@@ -117,28 +370,86 @@ impl FileWatcher {
             info!("🌉 Async/sync bridge stopped");
         });
         
+        let settings = WatcherSettings::load(&project_path);
+        info!("⏱️ Watcher settings for project {}: debounce {}ms, batches of up to {} files within {}ms",
+            project_id, settings.debounce_ms, settings.max_batch_size, settings.batch_window_ms);
+        let settings = Arc::new(RwLock::new(settings));
+
+        let filter = Arc::new(Mutex::new(SyncFilter::load(&project_path)));
+
+        // The project's allowed file types come from the server
+        let project_filter = filter.clone();
+        let settings_path = project_path.clone();
+        tokio::spawn(async move {
+            if let Some(project_settings) = crate::daemon::fetch_project_settings(project_id, &settings_path).await {
+                lock(&project_filter).apply_project_settings(&project_settings);
+            }
+        });
+
         // Spawn background task to handle file system events
         let project_path_clone = project_path.clone();
+        let watcher_settings = settings.clone();
+        let watcher_filter = filter.clone();
         tokio::task::spawn_blocking(move || {
             info!("👀 File watcher blocking task started for project {}", project_id);
             let mut debouncer = FileDebouncer::new();
+            let mut ignore_matcher = IgnoreMatcher::load(&project_path_clone);
+            let sparse = SparseSpec::load(&project_path_clone);
+            // Scan with a filter of its own so status requests don't wait for the scan
+            let mut scan_filter = SyncFilter::load(&project_path_clone);
+            let mut moves = MoveDetector::scan(&project_path_clone, &ignore_matcher, &sparse, &mut scan_filter);
+            lock(&watcher_filter).absorb_skipped(scan_filter);
+            let mut batch = ChangeBatch::default();
             let mut cleanup_counter = 0;
             
-            for res in fs_rx {
-                match res {
-                    Ok(event) => {
+            loop {
+                let settings = *watcher_settings.read().unwrap_or_else(|e| e.into_inner());
+                debouncer.interval = settings.debounce();
+
+                // Wake up in time to send the batch and to report deletions that weren't moves
+                let timeout = batch.time_left(&settings).map_or(MOVE_DETECTION_WINDOW, |left| left.min(MOVE_DETECTION_WINDOW));
+                match fs_rx.recv_timeout(timeout) {
+                    Ok(Ok(event)) => {
                         debug!("🔔 Received file system event: {:?}", event.kind);
                         if let Err(e) = handle_file_event(
                             &event, 
                             &project_path_clone, 
                             project_id, 
-                            &sync_tx,  // Use sync channel here!
-                            &mut debouncer
+                            &mut batch,
+                            &mut debouncer,
+                            &mut ignore_matcher,
+                            &sparse,
+                            &mut moves,
+                            &mut lock(&watcher_filter),
                         ) {
                             error!("Error handling file event in project {}: {}", project_id, e);
                         }
                     }
-                    Err(e) => error!("File watcher error for project {}: {}", project_id, e),
+                    Ok(Err(e)) => error!("File watcher error for project {}: {}", project_id, e),
+                    Err(mpsc::RecvTimeoutError::Timeout) => {}
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                }
+
+                for path in moves.expired() {
+                    info!("🗑️ File deleted in project {}: {}", project_id, path.display());
+                    batch.push(FileChangeEvent {
+                        project_id,
+                        file_path: path,
+                        content: String::new(),
+                        file_size: 0,
+                        timestamp: chrono::Utc::now(),
+                        change_type: FileChangeType::Deleted,
+                        metadata: FileMetadata::default(),
+                    });
+                }
+
+                // Bursts of changes (an IDE saving many files) reach the daemon as one batch
+                if batch.is_due(&settings) {
+                    let changes = batch.take();
+                    debug!("📦 Sending {} file changes in project {}", changes.len(), project_id);
+                    if let Err(e) = sync_tx.send(changes) {
+                        error!("Failed to send file change event: {}", e);
+                    }
                 }
                 
                 // Periodic cleanup of debouncer to prevent memory leaks
@@ -156,9 +467,46 @@ impl FileWatcher {
         Ok(Self {
             project_path,
             project_id,
+            settings,
+            filter,
             _watcher: watcher,
         })
     }
+
+    pub fn settings(&self) -> WatcherSettings {
+        *self.settings.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Apply new settings to the running watcher and save them with the project
+    pub fn update_settings(&self, settings: WatcherSettings) -> Result<()> {
+        settings.validate().map_err(|e| anyhow::anyhow!(e))?;
+        settings.save(&self.project_path)?;
+        *self.settings.write().unwrap_or_else(|e| e.into_inner()) = settings;
+        info!("⏱️ Updated watcher settings for project {}: {:?}", self.project_id, settings);
+        Ok(())
+    }
+
+    pub fn sync_filter(&self) -> SyncFilterSettings {
+        lock(&self.filter).settings().clone()
+    }
+
+    /// Apply new size and type limits and save them with the project. Files
+    /// already skipped are synced once they change under the new limits.
+    pub fn update_sync_filter(&self, settings: SyncFilterSettings) -> Result<()> {
+        settings.save(&self.project_path)?;
+        info!("⏭️ Updated sync filter for project {}: {:?}", self.project_id, settings);
+        lock(&self.filter).set_settings(settings);
+        Ok(())
+    }
+
+    /// How many of the project's files are left out of sync, by reason
+    pub fn skipped_files(&self) -> HashMap<SkipReason, usize> {
+        lock(&self.filter).skipped_counts()
+    }
+}
+
+fn lock(filter: &Mutex<SyncFilter>) -> std::sync::MutexGuard<'_, SyncFilter> {
+    filter.lock().unwrap_or_else(|e| e.into_inner())
 }
 
 /// Handle a file system event and send change events to daemon
@@ -166,26 +514,43 @@ fn handle_file_event(
     event: &Event,
     project_path: &Path,
     project_id: Uuid,
-    change_sender: &mpsc::Sender<FileChangeEvent>,  // Now using sync channel!
+    batch: &mut ChangeBatch,
     debouncer: &mut FileDebouncer,
+    ignore_matcher: &mut IgnoreMatcher,
+    sparse: &SparseSpec,
+    moves: &mut MoveDetector,
+    filter: &mut SyncFilter,
 ) -> Result<()> {
-    // Determine change type and filter events
+    // Determine change type and filter events. Renames are handled as a delete
+    // of the old path and a create of the new one, which the move detector pairs up.
     let change_type = match event.kind {
-        EventKind::Create(_) => FileChangeType::Created,
+        EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(RenameMode::To)) => FileChangeType::Created,
+        EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(RenameMode::From)) => FileChangeType::Deleted,
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => return Ok(()),
+        // Platforms that can't tell the two ends of a rename apart
+        EventKind::Modify(ModifyKind::Name(_)) if event.paths.iter().all(|p| !p.exists()) => FileChangeType::Deleted,
+        EventKind::Modify(ModifyKind::Name(_)) => FileChangeType::Created,
         EventKind::Modify(_) => FileChangeType::Modified,
-        EventKind::Remove(_) => FileChangeType::Deleted,
         _ => {
             return Ok(()); // Ignore other event types
         }
     };
     
     for path in &event.paths {
+        // Reload ignore rules whenever a .mothershipignore file changes
+        if path.file_name().map(|name| name == IGNORE_FILE_NAME).unwrap_or(false) {
+            info!("🙈 Ignore rules changed in project {}, reloading", project_id);
+            *ignore_matcher = IgnoreMatcher::load(project_path);
+        }
+
+        // A project started or removed inside this one takes its files with it
+        if is_nested_project_file(path, project_path) {
+            info!("📂 Nested project changed at {} in project {}, reloading", path.display(), project_id);
+            *ignore_matcher = IgnoreMatcher::load(project_path);
+        }
+
         // Skip hidden files and directories
-        if path.file_name()
-            .and_then(|name| name.to_str())
-            .map(|name| name.starts_with('.'))
-            .unwrap_or(false)
-        {
+        if is_hidden(path) {
             continue;
         }
         
@@ -194,9 +559,9 @@ fn handle_file_event(
             continue;
         }
         
-        // Skip common build/cache directories and temporary files
-        let path_str = path.to_string_lossy();
-        if should_ignore_file(&path_str) {
+        // Skip paths matched by .mothershipignore rules and project defaults
+        if ignore_matcher.is_ignored(path, false) {
+            debug!("🙈 Ignoring file event for {}", path.display());
             continue;
         }
         
@@ -209,6 +574,12 @@ fn handle_file_event(
                 continue;
             }
         };
+
+        // Skip paths outside a sparse beam
+        if !sparse.includes(&relative_path) {
+            debug!("🔭 Ignoring out-of-scope file event for {}", relative_path.display());
+            continue;
+        }
         
         // Validate relative path isn't corrupted
         let relative_path_str = relative_path.to_string_lossy();
@@ -217,37 +588,70 @@ fn handle_file_event(
                 relative_path_str, path.display());
             continue;
         }
+
+        if let FileChangeType::Deleted = change_type {
+            moves.deleted(&relative_path);
+            filter.forget(&relative_path);
+            continue;
+        }
         
         // PERFORMANCE FIX: Apply debouncing
         if !debouncer.should_process_event(&relative_path) {
             continue;
         }
         
-        // PERFORMANCE FIX: Check file size without reading content
-        let file_size = match std::fs::metadata(path) {
-            Ok(metadata) => metadata.len(),
+        // Symlinks are synced as their target, not followed
+        let metadata = match FileMetadata::read(path) {
+            Ok(metadata) => metadata,
             Err(e) => {
                 debug!("Skipping file with unreadable metadata {}: {}", path.display(), e);
                 continue;
             }
         };
-        
-        // PERFORMANCE FIX: Skip files larger than 1MB
-        if file_size > MAX_FILE_SIZE {
-            debug!("⚠️ Skipping large file {} ({} bytes > {} bytes limit)", 
-                path.display(), file_size, MAX_FILE_SIZE);
-            continue;
-        }
-        
-        // Read file content for sync (CRITICAL: Restored for data safety)
-        let content = match std::fs::read_to_string(path) {
-            Ok(content) => content,
-            Err(e) => {
-                error!("Failed to read file content for {}: {}", path.display(), e);
+        let (content, file_size) = if let Some(target) = &metadata.symlink_target {
+            let content = target.to_string_lossy().into_owned();
+            let file_size = content.len() as u64;
+            (content, file_size)
+        } else {
+            // PERFORMANCE FIX: Check file size without reading content
+            let file_size = match std::fs::metadata(path) {
+                Ok(metadata) => metadata.len(),
+                Err(e) => {
+                    debug!("Skipping file with unreadable metadata {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            
+            // Skip files over the size limit and binary files
+            if !filter.admits(&relative_path, file_size) {
                 continue;
             }
+            
+            // Read file content for sync (CRITICAL: Restored for data safety)
+            let bytes = match throttle().read(path) {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    error!("Failed to read file content for {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            if !filter.admits_content(&relative_path, &bytes) {
+                continue;
+            }
+            match String::from_utf8(bytes) {
+                Ok(content) => (content, file_size),
+                Err(_) => continue,
+            }
         };
         
+        let change_type = match &change_type {
+            FileChangeType::Created => moves.moved_from(&content)
+                .map(|from| FileChangeType::Moved { from })
+                .unwrap_or(FileChangeType::Created),
+            other => other.clone(),
+        };
+        moves.record(&relative_path, &content);
+        
         info!("📝 File changed in project {}: {} ({} bytes)", 
             project_id, relative_path.display(), file_size);
         
@@ -257,40 +661,11 @@ fn handle_file_event(
             content,
             file_size,
             timestamp: chrono::Utc::now(),
-            change_type: change_type.clone(),
+            change_type,
+            metadata,
         };
-        
-        if let Err(e) = change_sender.send(change_event) {
-            error!("Failed to send file change event: {}", e);
-        }
+        batch.push(change_event);
     }
     
     Ok(())
 }
-
-/// Check if a file should be ignored during file watching
-fn should_ignore_file(path_str: &str) -> bool {
-    // Common patterns to ignore
-    let ignore_patterns = [
-        "target/", "node_modules/", ".git/", "dist/", "build/", 
-        ".mothership/", ".vscode/", ".idea/", "__pycache__/",
-        ".lock", "~", ".tmp", ".temp", ".log", ".cache",
-        ".DS_Store", "Thumbs.db", "desktop.ini",
-    ];
-    
-    for pattern in &ignore_patterns {
-        if pattern.ends_with('/') {
-            // Directory pattern
-            if path_str.contains(pattern) {
-                return true;
-            }
-        } else {
-            // File extension or suffix pattern
-            if path_str.ends_with(pattern) || path_str.contains(pattern) {
-                return true;
-            }
-        }
-    }
-    
-    false
-} 
\ No newline at end of file
@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use axum::{
     extract::{Path, State},
     http::StatusCode,
@@ -11,12 +11,20 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::{mpsc, RwLock};
-use tracing::{info, error};
+use tracing::{info, error, warn};
 use uuid::Uuid;
 
 use crate::daemon::{DaemonStatus, TrackedProject};
-use crate::file_watcher::FileChangeEvent;
-use mothership_common::protocol::SyncMessage;
+use crate::file_watcher::{FileChangeBatch, WatcherSettings};
+use crate::metrics::{metrics, ConnectionStats, RecentError};
+use crate::project_scanner::{self, ProjectScanner};
+use crate::sync_filter::{SkipReason, SyncFilterSettings};
+use crate::throttle::throttle;
+use crate::outgoing::OutgoingSender;
+use mothership_common::{
+    discovery::{DecideDiscoveryRequest, DiscoveredProject, DiscoveryDecision},
+    ipc, project_root, DaemonConfig,
+};
 
 /// IPC server for communication between CLI/GUI and daemon
 pub struct IpcServer {
@@ -25,15 +33,17 @@ pub struct IpcServer {
     /// Tracked projects registry
     tracked_projects: Arc<RwLock<HashMap<Uuid, TrackedProject>>>,
     /// Channel for sending file change events
-    file_change_sender: mpsc::UnboundedSender<FileChangeEvent>,
+    file_change_sender: mpsc::UnboundedSender<FileChangeBatch>,
     /// Active file watchers (CRITICAL: Must be kept alive!)
     file_watchers: Arc<RwLock<HashMap<Uuid, crate::file_watcher::FileWatcher>>>,
     /// Maps project ID to WebSocket listener task handles
     websocket_listeners: Arc<RwLock<HashMap<Uuid, tokio::task::JoinHandle<()>>>>,
     /// Maps project ID to outgoing message channels (for sending to WebSocket)
-    outgoing_channels: Arc<RwLock<HashMap<Uuid, mpsc::UnboundedSender<SyncMessage>>>>,
+    outgoing_channels: Arc<RwLock<HashMap<Uuid, OutgoingSender>>>,
     /// Maps project ID to server write flags (prevents file watcher loops)
     server_write_flags: Arc<RwLock<HashMap<Uuid, bool>>>,
+    /// Finds projects under the configured scan roots
+    scanner: Arc<ProjectScanner>,
 }
 
 /// Request to add a project for tracking
@@ -52,6 +62,46 @@ pub struct HealthResponse {
     pub daemon_status: DaemonStatus,
 }
 
+/// Per-project sync metrics
+#[derive(Debug, Serialize)]
+pub struct DaemonMetrics {
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub projects: Vec<ProjectMetrics>,
+    /// Warnings and errors logged lately, oldest first
+    pub recent_errors: Vec<RecentError>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProjectMetrics {
+    pub project_id: Uuid,
+    pub project_name: String,
+    /// File changes waiting to go out over the project's connection
+    pub queue_depth: usize,
+    /// `None` until the project's WebSocket listener has started
+    pub connection: Option<ConnectionStats>,
+}
+
+/// Everything the daemon holds, for debugging
+#[derive(Debug, Serialize)]
+pub struct DebugState {
+    pub status: DaemonStatus,
+    pub config: DaemonConfig,
+    pub metrics: DaemonMetrics,
+    pub projects: Vec<ProjectDebugState>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProjectDebugState {
+    pub project: TrackedProject,
+    pub watching: bool,
+    pub listener_running: bool,
+    /// Set while files from the server are being written
+    pub writing_from_server: bool,
+    pub watcher: Option<WatcherSettings>,
+    pub sync_filter: Option<SyncFilterSettings>,
+    pub skipped_files: HashMap<SkipReason, usize>,
+}
+
 /// API response wrapper
 #[derive(Debug, Serialize)]
 pub struct ApiResponse<T> {
@@ -83,10 +133,11 @@ impl IpcServer {
     pub async fn new(
         status: Arc<RwLock<DaemonStatus>>,
         tracked_projects: Arc<RwLock<HashMap<Uuid, TrackedProject>>>,
-        file_change_sender: mpsc::UnboundedSender<FileChangeEvent>,
+        file_change_sender: mpsc::UnboundedSender<FileChangeBatch>,
         websocket_listeners: Arc<RwLock<HashMap<Uuid, tokio::task::JoinHandle<()>>>>,
-        outgoing_channels: Arc<RwLock<HashMap<Uuid, mpsc::UnboundedSender<SyncMessage>>>>,
+        outgoing_channels: Arc<RwLock<HashMap<Uuid, OutgoingSender>>>,
         server_write_flags: Arc<RwLock<HashMap<Uuid, bool>>>,
+        scanner: Arc<ProjectScanner>,
     ) -> Result<Self> {
         Ok(Self {
             status,
@@ -96,12 +147,16 @@ impl IpcServer {
             websocket_listeners,
             outgoing_channels,
             server_write_flags,
+            scanner,
         })
     }
 
     /// Start the IPC server
     pub async fn start(self) -> Result<()> {
-        info!("🌐 Starting Mothership Daemon IPC server on port 7525...");
+        info!("🌐 Starting Mothership Daemon IPC server...");
+
+        let server = Arc::new(self);
+        tokio::spawn(run_discovery(server.clone()));
 
         let app = Router::new()
             .route("/health", get(health_check))
@@ -109,17 +164,113 @@ impl IpcServer {
             .route("/projects", get(list_projects))
             .route("/projects/add", post(add_project))
             .route("/projects/:id/remove", post(remove_project))
+            .route("/projects/:id/pause", post(pause_project))
+            .route("/projects/:id/resume", post(resume_project))
+            .route("/projects/:id/watcher", get(get_watcher_settings).post(update_watcher_settings))
+            .route("/projects/:id/sync-filter", get(get_sync_filter).post(update_sync_filter))
+            .route("/config", get(get_config).post(update_config))
+            .route("/discovery", get(list_discovered))
+            .route("/discovery/scan", post(scan_for_projects))
+            .route("/discovery/decide", post(decide_discovered))
+            .route("/metrics", get(get_metrics))
+            .route("/debug/state", get(get_debug_state))
             .route("/shutdown", post(shutdown_daemon))
-            .with_state(Arc::new(self));
+            .with_state(server);
+
+        Self::serve(app).await
+    }
+
+    /// Serve the API on a socket only the user can reach, to processes run by that user
+    #[cfg(unix)]
+    async fn serve(app: Router) -> Result<()> {
+        use std::os::unix::fs::{MetadataExt, PermissionsExt};
+        use tracing::debug;
+
+        let socket_path = ipc::socket_path()
+            .ok_or_else(|| anyhow!("Could not determine the IPC socket directory"))?;
+        if let Some(dir) = socket_path.parent() {
+            std::fs::create_dir_all(dir)?;
+            std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
+        }
+
+        // A socket left behind by a daemon that didn't shut down cleanly is replaced
+        if socket_path.exists() {
+            if tokio::net::UnixStream::connect(&socket_path).await.is_ok() {
+                return Err(anyhow!("Another daemon is already listening on {}", socket_path.display()));
+            }
+            std::fs::remove_file(&socket_path)?;
+        }
 
-        let listener = tokio::net::TcpListener::bind("127.0.0.1:7525").await?;
-        info!("✅ IPC server listening on http://127.0.0.1:7525");
+        let listener = tokio::net::UnixListener::bind(&socket_path)?;
+        std::fs::set_permissions(&socket_path, std::fs::Permissions::from_mode(0o600))?;
+        let owner = std::fs::metadata(&socket_path)?.uid();
+        info!("✅ IPC server listening on {}", socket_path.display());
+
+        loop {
+            let (stream, _) = listener.accept().await?;
+
+            // PEER CHECK: Only processes of the user running the daemon may use the API
+            match stream.peer_cred() {
+                Ok(peer) if peer.uid() == owner => {}
+                Ok(peer) => {
+                    warn!("🚫 Rejected IPC connection from uid {}", peer.uid());
+                    continue;
+                }
+                Err(e) => {
+                    warn!("🚫 Rejected IPC connection with unknown peer: {}", e);
+                    continue;
+                }
+            }
+
+            let service = hyper_util::service::TowerToHyperService::new(app.clone());
+            tokio::spawn(async move {
+                if let Err(e) = hyper::server::conn::http1::Builder::new()
+                    .serve_connection(hyper_util::rt::TokioIo::new(stream), service)
+                    .await
+                {
+                    debug!("IPC connection closed with error: {}", e);
+                }
+            });
+        }
+    }
+
+    /// Serve the API on localhost, to clients that present the token written for this run
+    #[cfg(not(unix))]
+    async fn serve(app: Router) -> Result<()> {
+        let token_path = ipc::token_path()
+            .ok_or_else(|| anyhow!("Could not determine the config directory for the IPC token"))?;
+        if let Some(dir) = token_path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let token = Uuid::new_v4().simple().to_string();
+        std::fs::write(&token_path, &token)?;
+
+        let app = app.layer(axum::middleware::from_fn_with_state(Arc::<str>::from(token), require_token));
+        let listener = tokio::net::TcpListener::bind(ipc::IPC_TCP_ADDR).await?;
+        info!("✅ IPC server listening on http://{} (token in {})", ipc::IPC_TCP_ADDR, token_path.display());
 
         axum::serve(listener, app).await?;
         Ok(())
     }
 }
 
+/// Reject localhost requests that don't carry this run's IPC token
+#[cfg(not(unix))]
+async fn require_token(
+    State(token): State<Arc<str>>,
+    request: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> std::result::Result<axum::response::Response, StatusCode> {
+    let provided = request.headers()
+        .get(ipc::IPC_TOKEN_HEADER)
+        .and_then(|value| value.to_str().ok());
+    if provided != Some(&*token) {
+        warn!("🚫 Rejected IPC request without a valid token: {}", request.uri());
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    Ok(next.run(request).await)
+}
+
 /// Health check endpoint
 async fn health_check(State(server): State<Arc<IpcServer>>) -> Json<HealthResponse> {
     let daemon_status = server.status.read().await.clone();
@@ -133,8 +284,81 @@ async fn health_check(State(server): State<Arc<IpcServer>>) -> Json<HealthRespon
 
 /// Get daemon status
 async fn get_status(State(server): State<Arc<IpcServer>>) -> Json<ApiResponse<DaemonStatus>> {
-    let status = server.status.read().await.clone();
-    Json(ApiResponse::success(status))
+    Json(ApiResponse::success(current_status(&server).await))
+}
+
+async fn current_status(server: &IpcServer) -> DaemonStatus {
+    let mut status = server.status.read().await.clone();
+    for watcher in server.file_watchers.read().await.values() {
+        for (reason, count) in watcher.skipped_files() {
+            *status.skipped_files.entry(reason).or_insert(0) += count;
+        }
+    }
+    status.low_priority = throttle().is_low_priority();
+    status
+}
+
+/// Get each project's queue depth and connection health, and recent errors
+async fn get_metrics(State(server): State<Arc<IpcServer>>) -> Json<ApiResponse<DaemonMetrics>> {
+    Json(ApiResponse::success(current_metrics(&server).await))
+}
+
+async fn current_metrics(server: &IpcServer) -> DaemonMetrics {
+    let mut projects: Vec<TrackedProject> = server.tracked_projects.read().await.values().cloned().collect();
+    projects.sort_by(|a, b| a.project_name.cmp(&b.project_name));
+
+    let channels = server.outgoing_channels.read().await;
+    let projects = projects
+        .into_iter()
+        .map(|project| ProjectMetrics {
+            queue_depth: channels.get(&project.project_id).map_or(0, |channel| channel.pending()),
+            connection: metrics().connection(project.project_id),
+            project_id: project.project_id,
+            project_name: project.project_name,
+        })
+        .collect();
+
+    DaemonMetrics {
+        started_at: metrics().started_at(),
+        projects,
+        recent_errors: metrics().recent_errors(),
+    }
+}
+
+/// Dump the daemon's state: status, config, metrics and each project's watcher,
+/// listener and settings
+async fn get_debug_state(State(server): State<Arc<IpcServer>>) -> Json<ApiResponse<DebugState>> {
+    let status = current_status(&server).await;
+    let metrics = current_metrics(&server).await;
+
+    let mut tracked: Vec<TrackedProject> = server.tracked_projects.read().await.values().cloned().collect();
+    tracked.sort_by(|a, b| a.project_name.cmp(&b.project_name));
+
+    let watchers = server.file_watchers.read().await;
+    let listeners = server.websocket_listeners.read().await;
+    let write_flags = server.server_write_flags.read().await;
+    let projects = tracked
+        .into_iter()
+        .map(|project| {
+            let watcher = watchers.get(&project.project_id);
+            ProjectDebugState {
+                watching: watcher.is_some(),
+                listener_running: listeners.get(&project.project_id).is_some_and(|handle| !handle.is_finished()),
+                writing_from_server: write_flags.get(&project.project_id).copied().unwrap_or(false),
+                watcher: watcher.map(|watcher| watcher.settings()),
+                sync_filter: watcher.map(|watcher| watcher.sync_filter()),
+                skipped_files: watcher.map(|watcher| watcher.skipped_files()).unwrap_or_default(),
+                project,
+            }
+        })
+        .collect();
+
+    Json(ApiResponse::success(DebugState {
+        status,
+        config: DaemonConfig { throttle: throttle().settings(), discovery: server.scanner.settings().await },
+        metrics,
+        projects,
+    }))
 }
 
 /// List tracked projects
@@ -149,33 +373,48 @@ async fn add_project(
     State(server): State<Arc<IpcServer>>,
     Json(req): Json<AddProjectRequest>,
 ) -> Result<Json<ApiResponse<String>>, StatusCode> {
-    info!("📁 Adding project for tracking: {} at {}", req.project_name, req.project_path.display());
+    match track_project(&server, req.project_id, &req.project_name, &req.project_path).await {
+        Ok(message) => Ok(Json(ApiResponse::success(message))),
+        Err(error_msg) => Ok(Json(ApiResponse::error(error_msg))),
+    }
+}
+
+/// Start tracking the project at `path`
+async fn track_project(
+    server: &IpcServer,
+    project_id: Uuid,
+    project_name: &str,
+    path: &std::path::Path,
+) -> std::result::Result<String, String> {
+    info!("📁 Adding project for tracking: {} at {}", project_name, path.display());
 
     // Validate project path exists
-    if !req.project_path.exists() {
-        let error_msg = format!("Project path does not exist: {}", req.project_path.display());
-        return Ok(Json(ApiResponse::error(error_msg)));
+    if !path.exists() {
+        return Err(format!("Project path does not exist: {}", path.display()));
     }
     
-    // Validate .mothership directory exists (critical for file watcher)
-    let mothership_dir = req.project_path.join(".mothership");
-    if !mothership_dir.exists() {
-        let error_msg = format!("No .mothership directory found at: {}", req.project_path.display());
-        return Ok(Json(ApiResponse::error(error_msg)));
+    // Watch the nearest project enclosing the path, which may be nested in
+    // another project (critical for file watcher)
+    let Some(project_path) = project_root::find_project_root(path) else {
+        return Err(format!("No .mothership directory found at: {}", path.display()));
+    };
+    if project_path != path {
+        info!("📂 {} is inside the project at {}, tracking that", path.display(), project_path.display());
     }
 
     // Create tracked project
     let tracked_project = TrackedProject {
-        project_id: req.project_id,
-        project_name: req.project_name.clone(),
-        project_path: req.project_path.clone(),
+        project_id,
+        project_name: project_name.to_string(),
+        project_path,
         added_at: chrono::Utc::now(),
+        paused: false,
     };
 
     // Add to registry
     {
         let mut projects = server.tracked_projects.write().await;
-        projects.insert(req.project_id, tracked_project);
+        projects.insert(project_id, tracked_project.clone());
     }
 
     // Update daemon status
@@ -184,30 +423,35 @@ async fn add_project(
         status.projects_tracked = server.tracked_projects.read().await.len();
     }
 
+    start_syncing(server, &tracked_project).await?;
+
+    info!("✅ Project '{}' added for tracking with active file watcher and WebSocket sync", project_name);
+    Ok(format!("Project '{}' successfully added for tracking", project_name))
+}
+
+/// Start a project's file watcher and WebSocket listener
+async fn start_syncing(server: &IpcServer, project: &TrackedProject) -> std::result::Result<(), String> {
     // CRITICAL FIX: Actually start file watcher for this project!
     let file_watcher = match crate::file_watcher::FileWatcher::new(
-        req.project_path.clone(),
-        req.project_id,
+        project.project_path.clone(),
+        project.project_id,
         server.file_change_sender.clone(),
     ).await {
         Ok(watcher) => watcher,
-        Err(e) => {
-            let error_msg = format!("Failed to start file watcher for '{}': {}", req.project_name, e);
-            return Ok(Json(ApiResponse::error(error_msg)));
-        }
+        Err(e) => return Err(format!("Failed to start file watcher for '{}': {}", project.project_name, e)),
     };
     
     // CRITICAL: Store the file watcher to keep it alive!
     {
         let mut watchers = server.file_watchers.write().await;
-        watchers.insert(req.project_id, file_watcher);
+        watchers.insert(project.project_id, file_watcher);
     }
     
-    info!("🔍 File watcher started and stored for project '{}'", req.project_name);
+    info!("🔍 File watcher started and stored for project '{}'", project.project_name);
 
     // CRITICAL FIX: Start WebSocket listener for real-time sync
     let websocket_handle = {
-        let project_id = req.project_id;
+        let project_id = project.project_id;
         let tracked_projects = server.tracked_projects.clone();
         let status = server.status.clone();
         let websocket_listeners = server.websocket_listeners.clone();
@@ -232,16 +476,31 @@ async fn add_project(
     // Store the WebSocket listener handle
     {
         let mut listeners = server.websocket_listeners.write().await;
-        listeners.insert(req.project_id, websocket_handle);
+        listeners.insert(project.project_id, websocket_handle);
     }
     
-    info!("🔄 WebSocket listener started for project '{}'", req.project_name);
+    info!("🔄 WebSocket listener started for project '{}'", project.project_name);
+    Ok(())
+}
+
+/// Stop a project's file watcher and WebSocket listener
+async fn stop_syncing(server: &IpcServer, project_id: Uuid, project_name: &str) {
+    // CRITICAL: Remove file watcher to stop watching
+    {
+        let mut watchers = server.file_watchers.write().await;
+        if watchers.remove(&project_id).is_some() {
+            info!("🔍 Stopped file watcher for project '{}'", project_name);
+        }
+    }
 
-    info!("✅ Project '{}' added for tracking with active file watcher and WebSocket sync", req.project_name);
-    Ok(Json(ApiResponse::success(format!(
-        "Project '{}' successfully added for tracking",
-        req.project_name
-    ))))
+    // CRITICAL: Remove WebSocket listener to stop sync
+    {
+        let mut listeners = server.websocket_listeners.write().await;
+        if let Some(handle) = listeners.remove(&project_id) {
+            handle.abort();
+            info!("🔄 Stopped WebSocket listener for project '{}'", project_name);
+        }
+    }
 }
 
 /// Remove a project from tracking
@@ -266,22 +525,8 @@ async fn remove_project(
         projects_count
     };
 
-    // CRITICAL: Remove file watcher to stop watching
-    {
-        let mut watchers = server.file_watchers.write().await;
-        if watchers.remove(&project_id).is_some() {
-            info!("🔍 Stopped file watcher for project '{}'", project_name);
-        }
-    }
-
-    // CRITICAL: Remove WebSocket listener to stop sync
-    {
-        let mut listeners = server.websocket_listeners.write().await;
-        if let Some(handle) = listeners.remove(&project_id) {
-            handle.abort();
-            info!("🔄 Stopped WebSocket listener for project '{}'", project_name);
-        }
-    }
+    stop_syncing(&server, project_id, &project_name).await;
+    metrics().forget(project_id);
 
     info!("✅ Project '{}' removed from tracking", project_name);
     
@@ -308,6 +553,258 @@ async fn remove_project(
     }
 }
 
+/// Stop syncing a project without forgetting it. Changes made while paused
+/// are reconciled when the project reconnects on resume.
+async fn pause_project(
+    State(server): State<Arc<IpcServer>>,
+    Path(project_id): Path<Uuid>,
+) -> Json<ApiResponse<String>> {
+    let project_name = {
+        let mut projects = server.tracked_projects.write().await;
+        match projects.get_mut(&project_id) {
+            Some(project) if project.paused => {
+                return Json(ApiResponse::success(format!("Project '{}' is already paused", project.project_name)));
+            }
+            Some(project) => {
+                project.paused = true;
+                project.project_name.clone()
+            }
+            None => return Json(ApiResponse::error(format!("Project {} is not being tracked", project_id))),
+        }
+    };
+
+    stop_syncing(&server, project_id, &project_name).await;
+    info!("⏸️ Paused sync of project '{}'", project_name);
+    Json(ApiResponse::success(format!("Paused sync of project '{}'", project_name)))
+}
+
+/// Start syncing a paused project again
+async fn resume_project(
+    State(server): State<Arc<IpcServer>>,
+    Path(project_id): Path<Uuid>,
+) -> Json<ApiResponse<String>> {
+    let project = {
+        let mut projects = server.tracked_projects.write().await;
+        match projects.get_mut(&project_id) {
+            Some(project) if !project.paused => {
+                return Json(ApiResponse::success(format!("Project '{}' is already syncing", project.project_name)));
+            }
+            Some(project) => {
+                project.paused = false;
+                project.clone()
+            }
+            None => return Json(ApiResponse::error(format!("Project {} is not being tracked", project_id))),
+        }
+    };
+
+    if let Err(e) = start_syncing(&server, &project).await {
+        error!("{}", e);
+        if let Some(tracked) = server.tracked_projects.write().await.get_mut(&project_id) {
+            tracked.paused = true;
+        }
+        return Json(ApiResponse::error(e));
+    }
+    info!("▶️ Resumed sync of project '{}'", project.project_name);
+    Json(ApiResponse::success(format!("Resumed sync of project '{}'", project.project_name)))
+}
+
+/// Get a project's file watcher debounce and batch settings
+async fn get_watcher_settings(
+    State(server): State<Arc<IpcServer>>,
+    Path(project_id): Path<Uuid>,
+) -> Json<ApiResponse<WatcherSettings>> {
+    let watchers = server.file_watchers.read().await;
+    match watchers.get(&project_id) {
+        Some(watcher) => Json(ApiResponse::success(watcher.settings())),
+        None => Json(ApiResponse::error(format!("Project {} is not being tracked", project_id))),
+    }
+}
+
+/// Change a project's file watcher settings; they apply immediately and are
+/// saved to the project's `.mothership/config.json`
+async fn update_watcher_settings(
+    State(server): State<Arc<IpcServer>>,
+    Path(project_id): Path<Uuid>,
+    Json(settings): Json<WatcherSettings>,
+) -> Json<ApiResponse<WatcherSettings>> {
+    let watchers = server.file_watchers.read().await;
+    let Some(watcher) = watchers.get(&project_id) else {
+        return Json(ApiResponse::error(format!("Project {} is not being tracked", project_id)));
+    };
+
+    match watcher.update_settings(settings) {
+        Ok(()) => Json(ApiResponse::success(settings)),
+        Err(e) => {
+            error!("Failed to update watcher settings for project {}: {}", project_id, e);
+            Json(ApiResponse::error(e.to_string()))
+        }
+    }
+}
+
+/// Get a project's file size and type limits
+async fn get_sync_filter(
+    State(server): State<Arc<IpcServer>>,
+    Path(project_id): Path<Uuid>,
+) -> Json<ApiResponse<SyncFilterSettings>> {
+    let watchers = server.file_watchers.read().await;
+    match watchers.get(&project_id) {
+        Some(watcher) => Json(ApiResponse::success(watcher.sync_filter())),
+        None => Json(ApiResponse::error(format!("Project {} is not being tracked", project_id))),
+    }
+}
+
+/// Change a project's file size and type limits; they apply to the next change
+/// of each file and are saved to the project's `.mothership/config.json`
+async fn update_sync_filter(
+    State(server): State<Arc<IpcServer>>,
+    Path(project_id): Path<Uuid>,
+    Json(settings): Json<SyncFilterSettings>,
+) -> Json<ApiResponse<SyncFilterSettings>> {
+    let watchers = server.file_watchers.read().await;
+    let Some(watcher) = watchers.get(&project_id) else {
+        return Json(ApiResponse::error(format!("Project {} is not being tracked", project_id)));
+    };
+
+    match watcher.update_sync_filter(settings.clone()) {
+        Ok(()) => Json(ApiResponse::success(settings)),
+        Err(e) => {
+            error!("Failed to update sync filter for project {}: {}", project_id, e);
+            Json(ApiResponse::error(e.to_string()))
+        }
+    }
+}
+
+/// Get the daemon-wide settings
+async fn get_config(State(server): State<Arc<IpcServer>>) -> Json<ApiResponse<DaemonConfig>> {
+    Json(ApiResponse::success(DaemonConfig {
+        throttle: throttle().settings(),
+        discovery: server.scanner.settings().await,
+    }))
+}
+
+/// Change the daemon-wide settings; they apply immediately and are saved to
+/// `daemon.json`. New scan roots are searched right away.
+async fn update_config(
+    State(server): State<Arc<IpcServer>>,
+    Json(config): Json<DaemonConfig>,
+) -> Json<ApiResponse<DaemonConfig>> {
+    if let Err(e) = config.save() {
+        error!("Failed to update daemon config: {}", e);
+        return Json(ApiResponse::error(e.to_string()));
+    }
+    throttle().update(config.throttle);
+    if server.scanner.update_settings(config.discovery.clone()).await {
+        let server = server.clone();
+        tokio::spawn(async move {
+            let projects = server.scanner.scan().await;
+            track_chosen(&server, &projects).await;
+        });
+    }
+    Json(ApiResponse::success(config))
+}
+
+/// Projects found under the scan roots by the last scan
+async fn list_discovered(State(server): State<Arc<IpcServer>>) -> Json<ApiResponse<Vec<DiscoveredProject>>> {
+    Json(ApiResponse::success(server.scanner.discovered().read().await.clone()))
+}
+
+/// Search the scan roots again
+async fn scan_for_projects(State(server): State<Arc<IpcServer>>) -> Json<ApiResponse<Vec<DiscoveredProject>>> {
+    let projects = server.scanner.scan().await;
+    track_chosen(&server, &projects).await;
+    Json(ApiResponse::success(projects))
+}
+
+/// Track a discovered project, or leave it alone for good
+async fn decide_discovered(
+    State(server): State<Arc<IpcServer>>,
+    Json(req): Json<DecideDiscoveryRequest>,
+) -> Json<ApiResponse<String>> {
+    match apply_decision(&server, &req.project_path, req.decision).await {
+        Ok(message) => Json(ApiResponse::success(message)),
+        Err(e) => Json(ApiResponse::error(e)),
+    }
+}
+
+async fn apply_decision(
+    server: &IpcServer,
+    project_path: &std::path::Path,
+    decision: DiscoveryDecision,
+) -> std::result::Result<String, String> {
+    let project = match server.scanner.decide(project_path, decision).await {
+        Ok(Some(project)) => project,
+        Ok(None) => return Err(format!("No project was discovered at {}", project_path.display())),
+        Err(e) => return Err(format!("Failed to save the decision: {}", e)),
+    };
+
+    match decision {
+        DiscoveryDecision::Track => {
+            if server.tracked_projects.read().await.contains_key(&project.project_id) {
+                return Ok(format!("Project '{}' is already being tracked", project.project_name));
+            }
+            track_project(server, project.project_id, &project.project_name, &project.project_path).await
+        }
+        DiscoveryDecision::Ignore => {
+            info!("🙈 Project '{}' at {} won't be tracked", project.project_name, project.project_path.display());
+            Ok(format!("Project '{}' won't be tracked", project.project_name))
+        }
+    }
+}
+
+/// Track the discovered projects the user chose to, and point out the ones
+/// still waiting for a decision
+async fn track_chosen(server: &IpcServer, projects: &[DiscoveredProject]) {
+    for project in projects {
+        if project.decision != Some(DiscoveryDecision::Track)
+            || server.tracked_projects.read().await.contains_key(&project.project_id)
+        {
+            continue;
+        }
+        if let Err(e) = track_project(server, project.project_id, &project.project_name, &project.project_path).await {
+            warn!("Failed to track discovered project '{}': {}", project.project_name, e);
+        }
+    }
+
+    let undecided = projects.iter().filter(|project| project.decision.is_none()).count();
+    if undecided > 0 {
+        info!("🔎 Found {} projects that aren't tracked yet; run 'mothership daemon discover' to choose", undecided);
+    }
+}
+
+/// Search the scan roots at startup and whenever filesystems are mounted or
+/// unmounted, and apply decisions made in the system tray
+async fn run_discovery(server: Arc<IpcServer>) {
+    let Some(mut decisions) = server.scanner.take_decisions() else {
+        return;
+    };
+
+    let projects = server.scanner.scan().await;
+    track_chosen(&server, &projects).await;
+
+    let mut mounts = project_scanner::mount_signature(&server.scanner.settings().await.scan_roots);
+    let mut poll = tokio::time::interval(project_scanner::MOUNT_POLL_INTERVAL);
+    loop {
+        tokio::select! {
+            Some((project_path, decision)) = decisions.recv() => {
+                match apply_decision(&server, &project_path, decision).await {
+                    Ok(message) => info!("{}", message),
+                    Err(e) => warn!("{}", e),
+                }
+            }
+            _ = poll.tick() => {
+                let scan_roots = server.scanner.settings().await.scan_roots;
+                let current = project_scanner::mount_signature(&scan_roots);
+                if current != mounts && !scan_roots.is_empty() {
+                    info!("💽 Mounted filesystems changed, searching the scan roots again");
+                    let projects = server.scanner.scan().await;
+                    track_chosen(&server, &projects).await;
+                }
+                mounts = current;
+            }
+        }
+    }
+}
+
 /// Shutdown the daemon gracefully
 async fn shutdown_daemon(State(_server): State<Arc<IpcServer>>) -> Json<ApiResponse<String>> {
     info!("🛑 Received shutdown request from CLI");
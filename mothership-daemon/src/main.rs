@@ -1,24 +1,46 @@
 use anyhow::Result;
+use mothership_common::daemon_log;
 use std::env;
 use tracing::info;
 
 mod daemon;
+mod delta_sync;
 mod file_watcher;
 mod ipc_server;
+mod metrics;
+mod offline_queue;
+mod outgoing;
 mod project_scanner;
+mod sync_base;
+mod sync_filter;
 mod system_tray;
+mod throttle;
+#[cfg(not(windows))]
+mod unix_service;
 mod windows_service;
 
 use daemon::MothershipDaemon;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize logging
-    tracing_subscriber::fmt()
-        .with_env_filter(
+    // Initialize logging to stdout and to daily log files, which a service has no console for;
+    // warnings and errors are also kept for the IPC `/metrics` route
+    use tracing_subscriber::prelude::*;
+    let (log_file, _log_guard) = match open_log_file() {
+        Some(appender) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            (Some(tracing_subscriber::fmt::layer().with_ansi(false).with_writer(writer)), Some(guard))
+        }
+        None => (None, None),
+    };
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::EnvFilter::new(
             std::env::var("RUST_LOG")
                 .unwrap_or_else(|_| "mothership_daemon=info,tower_http=debug".to_string()),
-        )
+        ))
+        .with(tracing_subscriber::fmt::layer())
+        .with(log_file)
+        .with(metrics::ErrorLayer)
         .init();
 
     info!("🚀 Mothership Daemon starting...");
@@ -38,7 +60,10 @@ async fn main() -> Result<()> {
             }
             #[cfg(not(windows))]
             {
-                error!("Service installation only supported on Windows");
+                info!("Installing Mothership Daemon as a user service...");
+                unix_service::install_service()?;
+                println!("✅ Mothership Daemon service installed and started!");
+                println!("💡 It will start automatically when you log in");
             }
         }
         Some("uninstall") => {
@@ -51,7 +76,19 @@ async fn main() -> Result<()> {
             }
             #[cfg(not(windows))]
             {
-                error!("Service uninstallation only supported on Windows");
+                info!("Uninstalling Mothership Daemon user service...");
+                unix_service::uninstall_service()?;
+                println!("✅ Mothership Daemon service uninstalled successfully!");
+            }
+        }
+        Some("status") => {
+            #[cfg(windows)]
+            {
+                println!("💡 Use 'sc query MothershipDaemon' to check the Windows service");
+            }
+            #[cfg(not(windows))]
+            {
+                println!("Mothership Daemon service: {}", unix_service::service_status()?);
             }
         }
         Some("service") => {
@@ -63,7 +100,7 @@ async fn main() -> Result<()> {
             }
             #[cfg(not(windows))]
             {
-                error!("Service mode only supported on Windows");
+                return Err(anyhow::anyhow!("Service mode is only used on Windows; run 'mothership-daemon install' to install a user service"));
             }
         }
         Some("--help") | Some("-h") => {
@@ -77,7 +114,6 @@ async fn main() -> Result<()> {
             let daemon = MothershipDaemon::new().await?;
             
             info!("✅ Mothership Daemon started successfully!");
-            info!("🔍 Scanning for Mothership projects...");
             info!("🎯 System tray icon should appear in notification area");
             
             // Run the daemon
@@ -88,6 +124,24 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Today's log file, rotated daily; older files beyond the last week are removed
+fn open_log_file() -> Option<tracing_appender::rolling::RollingFileAppender> {
+    let dir = daemon_log::log_dir()?;
+    match tracing_appender::rolling::Builder::new()
+        .rotation(tracing_appender::rolling::Rotation::DAILY)
+        .filename_prefix(daemon_log::LOG_FILE_PREFIX)
+        .filename_suffix(daemon_log::LOG_FILE_SUFFIX)
+        .max_log_files(daemon_log::MAX_LOG_FILES)
+        .build(&dir)
+    {
+        Ok(appender) => Some(appender),
+        Err(e) => {
+            eprintln!("Could not write logs to {}: {}", dir.display(), e);
+            None
+        }
+    }
+}
+
 fn print_help() {
     println!("Mothership Daemon - Background file synchronization service");
     println!();
@@ -95,8 +149,10 @@ fn print_help() {
     println!("    mothership-daemon [SUBCOMMAND]");
     println!();
     println!("SUBCOMMANDS:");
-    println!("    install      Install as Windows service (requires admin privileges)");
-    println!("    uninstall    Uninstall Windows service (requires admin privileges)");
+    println!("    install      Install as a service: Windows service (requires admin privileges),");
+    println!("                 systemd user unit on Linux, launchd agent on macOS");
+    println!("    uninstall    Stop and remove the installed service");
+    println!("    status       Show whether the service is installed and running");
     println!("    service      Run as Windows service (internal use by Service Control Manager)");
     println!("    --help, -h   Show this help message");
     println!();
@@ -104,15 +160,17 @@ fn print_help() {
     println!("    # Run as standalone application (for testing)");
     println!("    mothership-daemon");
     println!();
-    println!("    # Install as Windows service");
+    println!("    # Install as a service that starts at login");
     println!("    mothership-daemon install");
     println!();
-    println!("    # Start the service");
+    println!("    # Start the service (Windows; systemd and launchd start it on install)");
     println!("    sc start MothershipDaemon");
     println!();
     println!("NOTES:");
-    println!("    • The daemon automatically discovers Mothership projects in common directories");
+    println!("    • With scan roots set ('mothership daemon config --scan-root <dir>'), the daemon finds");
+    println!("      projects under them and tracks the ones you choose ('mothership daemon discover')");
     println!("    • A system tray icon provides status and controls");
     println!("    • File changes are synchronized in real-time with the Mothership server");
-    println!("    • The daemon listens on http://localhost:7525 for CLI communication");
+    println!("    • Logs are kept for a week; 'mothership daemon logs' shows them");
+    println!("    • The CLI talks to the daemon over a private Unix socket (a token-protected localhost port on Windows)");
 } 
\ No newline at end of file
@@ -0,0 +1,125 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use uuid::Uuid;
+
+/// Warnings and errors kept for `/metrics` and `/debug/state`
+const RECENT_ERRORS: usize = 50;
+
+/// Health of a project's WebSocket connection, as served by `/metrics`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConnectionStats {
+    pub connected: bool,
+    pub connected_since: Option<DateTime<Utc>>,
+    pub messages_sent: u64,
+    pub messages_received: u64,
+    /// Errors since the last message received
+    pub consecutive_errors: u32,
+    pub connection_resets: u32,
+    pub last_reset: Option<DateTime<Utc>>,
+    /// Last local change sent to the server
+    pub last_upload: Option<DateTime<Utc>>,
+    /// Last change received from the server
+    pub last_download: Option<DateTime<Utc>>,
+}
+
+/// A warning or error the daemon logged
+#[derive(Debug, Clone, Serialize)]
+pub struct RecentError {
+    pub at: DateTime<Utc>,
+    pub level: String,
+    /// Module that logged it
+    pub target: String,
+    pub message: String,
+}
+
+/// What the daemon knows about its connections and recent failures, beyond
+/// the summary in its status
+pub struct Metrics {
+    started_at: DateTime<Utc>,
+    connections: Mutex<HashMap<Uuid, ConnectionStats>>,
+    recent_errors: Mutex<VecDeque<RecentError>>,
+}
+
+/// The daemon's metrics
+pub fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(|| Metrics {
+        started_at: Utc::now(),
+        connections: Mutex::new(HashMap::new()),
+        recent_errors: Mutex::new(VecDeque::with_capacity(RECENT_ERRORS)),
+    })
+}
+
+impl Metrics {
+    pub fn started_at(&self) -> DateTime<Utc> {
+        self.started_at
+    }
+
+    pub fn update_connection(&self, project_id: Uuid, stats: &ConnectionStats) {
+        lock(&self.connections).insert(project_id, stats.clone());
+    }
+
+    /// A project's connection health; `None` until its listener has started
+    pub fn connection(&self, project_id: Uuid) -> Option<ConnectionStats> {
+        lock(&self.connections).get(&project_id).cloned()
+    }
+
+    /// Drop the stats of a project that is no longer tracked
+    pub fn forget(&self, project_id: Uuid) {
+        lock(&self.connections).remove(&project_id);
+    }
+
+    fn record_error(&self, error: RecentError) {
+        let mut recent = lock(&self.recent_errors);
+        if recent.len() == RECENT_ERRORS {
+            recent.pop_front();
+        }
+        recent.push_back(error);
+    }
+
+    /// Warnings and errors logged lately, oldest first
+    pub fn recent_errors(&self) -> Vec<RecentError> {
+        lock(&self.recent_errors).iter().cloned().collect()
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Tracing layer that keeps the daemon's recent warnings and errors in its metrics
+pub struct ErrorLayer;
+
+impl<S: Subscriber> Layer<S> for ErrorLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let level = *event.metadata().level();
+        if level > Level::WARN {
+            return;
+        }
+
+        let mut message = MessageVisitor(String::new());
+        event.record(&mut message);
+        metrics().record_error(RecentError {
+            at: Utc::now(),
+            level: level.to_string(),
+            target: event.metadata().target().to_string(),
+            message: message.0,
+        });
+    }
+}
+
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.0 = format!("{:?}", value);
+        }
+    }
+}
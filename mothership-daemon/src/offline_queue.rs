@@ -0,0 +1,100 @@
+use anyhow::Result;
+use mothership_common::SyncMessage;
+use std::path::{Path, PathBuf};
+use tokio::fs::{self, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tracing::{debug, warn};
+
+/// Journal file (one JSON-encoded SyncMessage per line) inside the project's .mothership directory
+const QUEUE_FILE_NAME: &str = "offline_queue.jsonl";
+
+/// Append-only on-disk queue of sync messages that could not be sent while offline.
+///
+/// Messages are replayed in the order they were written once the WebSocket
+/// connection is re-established, and survive daemon restarts.
+#[derive(Debug, Clone)]
+pub struct OfflineQueue {
+    path: PathBuf,
+}
+
+impl OfflineQueue {
+    /// Open (without creating) the queue for a project
+    pub fn for_project(project_path: &Path) -> Self {
+        Self {
+            path: project_path.join(".mothership").join(QUEUE_FILE_NAME),
+        }
+    }
+
+    /// Append a message to the end of the journal
+    pub async fn push(&self, message: &SyncMessage) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        let mut line = serde_json::to_string(message)?;
+        line.push('\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(line.as_bytes()).await?;
+        file.sync_data().await?;
+
+        debug!("💾 Queued offline sync message in {}", self.path.display());
+        Ok(())
+    }
+
+    /// Read every queued message in order. Corrupt lines (e.g. a torn
+    /// write from a crash) are skipped rather than failing the replay.
+    pub async fn load(&self) -> Result<Vec<SyncMessage>> {
+        let content = match fs::read_to_string(&self.path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut messages = Vec::new();
+        for (line_number, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<SyncMessage>(line) {
+                Ok(message) => messages.push(message),
+                Err(e) => warn!("Skipping corrupt offline queue entry {} in {}: {}",
+                    line_number + 1, self.path.display(), e),
+            }
+        }
+
+        Ok(messages)
+    }
+
+    /// Replace the journal contents with the given messages (used after a partial replay)
+    pub async fn replace(&self, messages: &[SyncMessage]) -> Result<()> {
+        if messages.is_empty() {
+            return self.clear().await;
+        }
+
+        let mut content = String::new();
+        for message in messages {
+            content.push_str(&serde_json::to_string(message)?);
+            content.push('\n');
+        }
+
+        // Write to a temporary file first so a crash never loses the queue
+        let tmp_path = self.path.with_extension("jsonl.tmp");
+        fs::write(&tmp_path, content).await?;
+        fs::rename(&tmp_path, &self.path).await?;
+        Ok(())
+    }
+
+    /// Remove the journal once everything has been replayed
+    pub async fn clear(&self) -> Result<()> {
+        match fs::remove_file(&self.path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
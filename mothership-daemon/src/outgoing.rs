@@ -0,0 +1,249 @@
+use mothership_common::{FileDiff, SyncMessage};
+use std::collections::{HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{Notify, RwLock};
+use tracing::debug;
+
+use crate::daemon::DaemonStatus;
+
+/// Messages a project's connection may hold before senders have to wait
+pub const OUTGOING_CAPACITY: usize = 256;
+
+/// Bounded queue of sync messages waiting to go out over a project's WebSocket.
+///
+/// A change to a file replaces changes to the same file still waiting in the
+/// queue, so a slow connection sends the latest content instead of every
+/// intermediate save. Messages that aren't whole-file changes (moves,
+/// checkpoints, replies) are never dropped and nothing queued before them is
+/// merged with anything after them. When the queue is full of changes that
+/// can't be merged, senders wait for the connection to catch up.
+///
+/// The number of file changes waiting is reported as `behind_by` in the
+/// daemon status.
+pub fn channel(status: Arc<RwLock<DaemonStatus>>) -> (OutgoingSender, OutgoingReceiver) {
+    let shared = Arc::new(Shared {
+        queue: Mutex::new(Queue::default()),
+        capacity: OUTGOING_CAPACITY,
+        senders: AtomicUsize::new(1),
+        receiver_alive: AtomicBool::new(true),
+        message_queued: Notify::new(),
+        space_freed: Notify::new(),
+        status,
+    });
+    (OutgoingSender { shared: shared.clone() }, OutgoingReceiver { shared })
+}
+
+struct Shared {
+    queue: Mutex<Queue>,
+    capacity: usize,
+    senders: AtomicUsize,
+    receiver_alive: AtomicBool,
+    message_queued: Notify,
+    space_freed: Notify,
+    status: Arc<RwLock<DaemonStatus>>,
+}
+
+#[derive(Default)]
+struct Queue {
+    messages: VecDeque<SyncMessage>,
+    /// File changes held by `messages`
+    changes: usize,
+    /// `changes` as last counted in the daemon status
+    reported: usize,
+}
+
+impl Shared {
+    /// Bring the daemon status up to date with the number of waiting file changes.
+    /// The queue is only touched once the status lock is held, so this can be
+    /// cancelled without losing count.
+    async fn report(&self) {
+        {
+            let queue = self.lock();
+            if queue.changes == queue.reported {
+                return;
+            }
+        }
+        let mut status = self.status.write().await;
+        let mut queue = self.lock();
+        status.behind_by = (status.behind_by + queue.changes).saturating_sub(queue.reported);
+        queue.reported = queue.changes;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Queue> {
+        self.queue.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// The file changes a message carries, or `None` if it must be kept as is
+fn replaceable_paths(message: &SyncMessage) -> Option<Vec<&PathBuf>> {
+    match message {
+        SyncMessage::FileChanged { path, .. }
+        | SyncMessage::FileDiffChanged { path, diff: FileDiff::FullContent(_) | FileDiff::Deleted, .. } => Some(vec![path]),
+        SyncMessage::BatchDiffChanges { changes, .. }
+            if changes.iter().all(|c| matches!(c.diff, FileDiff::FullContent(_) | FileDiff::Deleted)) =>
+        {
+            Some(changes.iter().map(|c| &c.path).collect())
+        }
+        _ => None,
+    }
+}
+
+fn change_count(message: &SyncMessage) -> usize {
+    match message {
+        SyncMessage::BatchDiffChanges { changes, .. } => changes.len(),
+        _ => 1,
+    }
+}
+
+impl Queue {
+    /// Drop queued changes to files `message` replaces, back to the last
+    /// message that can't be merged. Returns how many were dropped.
+    fn coalesce(&mut self, message: &SyncMessage) -> usize {
+        let Some(paths) = replaceable_paths(message) else {
+            return 0;
+        };
+        let paths: HashSet<PathBuf> = paths.into_iter().cloned().collect();
+
+        let mut dropped = 0;
+        for index in (0..self.messages.len()).rev() {
+            if replaceable_paths(&self.messages[index]).is_none() {
+                break;
+            }
+            let emptied = match &mut self.messages[index] {
+                SyncMessage::BatchDiffChanges { changes, .. } => {
+                    let before = changes.len();
+                    changes.retain(|c| !paths.contains(&c.path));
+                    dropped += before - changes.len();
+                    changes.is_empty()
+                }
+                SyncMessage::FileChanged { path, .. } | SyncMessage::FileDiffChanged { path, .. } if paths.contains(path) => {
+                    dropped += 1;
+                    true
+                }
+                _ => false,
+            };
+            if emptied {
+                self.messages.remove(index);
+            }
+        }
+        self.changes -= dropped;
+        dropped
+    }
+}
+
+/// Sending half of a project's outgoing queue
+pub struct OutgoingSender {
+    shared: Arc<Shared>,
+}
+
+impl OutgoingSender {
+    /// Queue a message, waiting while the queue is full. Gives the message
+    /// back if the connection task has stopped.
+    pub async fn send(&self, mut message: SyncMessage) -> Result<(), SyncMessage> {
+        loop {
+            if !self.shared.receiver_alive.load(Ordering::Acquire) {
+                return Err(message);
+            }
+
+            let space_freed = self.shared.space_freed.notified();
+            let rejected = {
+                let mut queue = self.shared.lock();
+                let dropped = queue.coalesce(&message);
+                if dropped > 0 {
+                    debug!("♻️ Replaced {} queued changes superseded by a newer one", dropped);
+                }
+                if queue.messages.len() < self.shared.capacity {
+                    queue.changes += change_count(&message);
+                    queue.messages.push_back(message);
+                    None
+                } else {
+                    Some(message)
+                }
+            };
+            self.shared.report().await;
+
+            let Some(rejected) = rejected else {
+                self.shared.message_queued.notify_one();
+                return Ok(());
+            };
+            message = rejected;
+
+            // BACKPRESSURE: Nothing left to merge; wait for the connection to drain the queue
+            debug!("⏳ Outgoing queue full, waiting for the connection to catch up");
+            space_freed.await;
+        }
+    }
+
+    /// File changes waiting in the queue
+    pub fn pending(&self) -> usize {
+        self.shared.lock().changes
+    }
+}
+
+impl Clone for OutgoingSender {
+    fn clone(&self) -> Self {
+        self.shared.senders.fetch_add(1, Ordering::AcqRel);
+        Self { shared: self.shared.clone() }
+    }
+}
+
+impl Drop for OutgoingSender {
+    fn drop(&mut self) {
+        if self.shared.senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.shared.message_queued.notify_one();
+        }
+    }
+}
+
+/// Receiving half of a project's outgoing queue, owned by its connection task
+pub struct OutgoingReceiver {
+    shared: Arc<Shared>,
+}
+
+impl OutgoingReceiver {
+    /// Take the next message; `None` once every sender is gone and the queue is empty
+    pub async fn recv(&mut self) -> Option<SyncMessage> {
+        loop {
+            // Nothing is taken from the queue before the last await, so this is safe to use in `select!`
+            self.shared.report().await;
+            let message_queued = self.shared.message_queued.notified();
+            let taken = {
+                let mut queue = self.shared.lock();
+                let taken = queue.messages.pop_front();
+                if let Some(message) = &taken {
+                    queue.changes -= change_count(message);
+                }
+                taken
+            };
+
+            if let Some(message) = taken {
+                self.shared.space_freed.notify_waiters();
+                return Some(message);
+            }
+            if self.shared.senders.load(Ordering::Acquire) == 0 {
+                return None;
+            }
+            message_queued.await;
+        }
+    }
+}
+
+impl Drop for OutgoingReceiver {
+    fn drop(&mut self) {
+        self.shared.receiver_alive.store(false, Ordering::Release);
+        self.shared.space_freed.notify_waiters();
+
+        // Changes left behind will never be sent by this connection
+        let mut queue = self.shared.lock();
+        queue.messages.clear();
+        queue.changes = 0;
+        if queue.reported > 0 {
+            if let Ok(handle) = tokio::runtime::Handle::try_current() {
+                let shared = self.shared.clone();
+                handle.spawn(async move { shared.report().await });
+            }
+        }
+    }
+}
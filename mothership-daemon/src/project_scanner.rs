@@ -1,31 +1,117 @@
 use anyhow::Result;
-use std::path::PathBuf;
+use mothership_common::{
+    discovery::{self, DiscoveredProject, DiscoveryDecision, DiscoveryDecisions},
+    DiscoverySettings,
+};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::{mpsc, RwLock};
 use tracing::info;
 
-/// Project scanner for automatically discovering Mothership projects
+/// How often the mounted filesystems are checked for changes
+pub const MOUNT_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// A decision made outside the runtime, waiting to be applied
+pub type PendingDecision = (PathBuf, DiscoveryDecision);
+
+/// Finds Mothership projects under the configured scan roots. Nothing found
+/// is tracked until the user decides (see `mothership_common::discovery`).
 pub struct ProjectScanner {
-    // TODO: Implement automatic project discovery
+    settings: RwLock<DiscoverySettings>,
+    /// Projects found by the last scan
+    discovered: Arc<RwLock<Vec<DiscoveredProject>>>,
+    /// Decisions made in the system tray
+    decisions: mpsc::UnboundedSender<PendingDecision>,
+    decision_receiver: std::sync::Mutex<Option<mpsc::UnboundedReceiver<PendingDecision>>>,
 }
 
 impl ProjectScanner {
     /// Create a new project scanner
-    pub async fn new() -> Result<Self> {
-        info!("📁 Initializing project scanner...");
-        Ok(Self {})
-    }
-
-    /// Scan for Mothership projects in common directories
-    pub async fn scan_common_directories(&self) -> Result<Vec<PathBuf>> {
-        // TODO: Implement scanning logic
-        // This would scan directories like:
-        // - ~/Code
-        // - ~/Projects  
-        // - ~/Development
-        // - Desktop
-        // - Documents
-        // And look for .mothership directories or common project patterns
-        
-        info!("🔍 Scanning for Mothership projects...");
-        Ok(vec![])
-    }
-} 
\ No newline at end of file
+    pub fn new(settings: DiscoverySettings) -> Self {
+        info!("📁 Initializing project scanner ({} scan roots)...", settings.scan_roots.len());
+        let (decisions, decision_receiver) = mpsc::unbounded_channel();
+        Self {
+            settings: RwLock::new(settings),
+            discovered: Arc::new(RwLock::new(Vec::new())),
+            decisions,
+            decision_receiver: std::sync::Mutex::new(Some(decision_receiver)),
+        }
+    }
+
+    pub async fn settings(&self) -> DiscoverySettings {
+        self.settings.read().await.clone()
+    }
+
+    /// Change where to look; returns whether anything changed
+    pub async fn update_settings(&self, settings: DiscoverySettings) -> bool {
+        let mut current = self.settings.write().await;
+        let changed = *current != settings;
+        *current = settings;
+        changed
+    }
+
+    /// Projects found by the last scan, shared with the system tray
+    pub fn discovered(&self) -> Arc<RwLock<Vec<DiscoveredProject>>> {
+        self.discovered.clone()
+    }
+
+    /// Search the scan roots for projects
+    pub async fn scan(&self) -> Vec<DiscoveredProject> {
+        let settings = self.settings().await;
+        if settings.scan_roots.is_empty() {
+            self.discovered.write().await.clear();
+            return Vec::new();
+        }
+
+        info!("🔍 Scanning {} directories for Mothership projects...", settings.scan_roots.len());
+        let projects = tokio::task::spawn_blocking(move || {
+            let decisions = DiscoveryDecisions::load();
+            settings.scan_roots.iter()
+                .flat_map(|root| discovery::find_projects(root, settings.max_depth))
+                .filter_map(|path| discovery::read_project(&path))
+                .map(|mut project| {
+                    project.decision = decisions.get(&project.project_path);
+                    project
+                })
+                .collect::<Vec<_>>()
+        }).await.unwrap_or_default();
+
+        *self.discovered.write().await = projects.clone();
+        projects
+    }
+
+    /// Save the user's decision on a project found by the last scan.
+    /// Returns `None` if the last scan didn't find it.
+    pub async fn decide(&self, project_path: &Path, decision: DiscoveryDecision) -> Result<Option<DiscoveredProject>> {
+        let mut discovered = self.discovered.write().await;
+        let Some(project) = discovered.iter_mut().find(|project| project.project_path == project_path) else {
+            return Ok(None);
+        };
+
+        let mut decisions = DiscoveryDecisions::load();
+        decisions.set(project_path.to_path_buf(), decision);
+        decisions.save()?;
+        project.decision = Some(decision);
+        Ok(Some(project.clone()))
+    }
+
+    /// Queue a decision from outside the runtime, for the daemon to apply
+    pub fn decide_later(&self, project_path: PathBuf, decision: DiscoveryDecision) {
+        let _ = self.decisions.send((project_path, decision));
+    }
+
+    /// The queue of decisions from outside the runtime; only the first caller gets it
+    pub fn take_decisions(&self) -> Option<mpsc::UnboundedReceiver<PendingDecision>> {
+        self.decision_receiver.lock().ok()?.take()
+    }
+}
+
+/// What is mounted, to notice filesystems coming and going: the mount table
+/// where the platform has one, and which scan roots exist
+pub fn mount_signature(scan_roots: &[PathBuf]) -> String {
+    let mut signature = std::fs::read_to_string("/proc/self/mounts").unwrap_or_default();
+    for root in scan_roots {
+        signature.push(if root.is_dir() { '1' } else { '0' });
+    }
+    signature
+}
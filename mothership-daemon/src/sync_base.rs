@@ -0,0 +1,98 @@
+use mothership_common::{upload::content_hash, IgnoreMatcher, Manifest, ManifestEntry, SparseSpec};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tracing::{error, warn};
+
+use crate::file_watcher::tracked_file_hashes;
+use crate::sync_filter::SyncFilter;
+
+/// File inside the project's .mothership directory
+const SYNC_BASE_FILE_NAME: &str = "sync_base.json";
+
+/// Hash of the content each file had when it was last exchanged with the
+/// server, persisted so that changes made while the daemon was stopped can be
+/// told apart from changes made on the server in the meantime.
+#[derive(Debug, Default)]
+pub struct SyncBase {
+    /// Where the hashes are saved; `None` keeps them in memory only
+    path: Option<PathBuf>,
+    hashes: HashMap<PathBuf, String>,
+    dirty: bool,
+}
+
+impl SyncBase {
+    /// Load the hashes saved for a project, starting empty if there are none
+    pub fn for_project(project_path: &Path) -> Self {
+        let path = project_path.join(".mothership").join(SYNC_BASE_FILE_NAME);
+        let hashes = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                warn!("Ignoring corrupt {}: {}", path.display(), e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+        Self { path: Some(path), hashes, dirty: false }
+    }
+
+    /// Record content known to match the server's copy
+    pub fn record(&mut self, path: &Path, content: &str) {
+        let hash = content_hash(content.as_bytes());
+        if self.hashes.get(path) != Some(&hash) {
+            self.hashes.insert(path.to_path_buf(), hash);
+            self.dirty = true;
+        }
+    }
+
+    /// Forget a file that was deleted on both sides
+    pub fn forget(&mut self, path: &Path) {
+        self.dirty |= self.hashes.remove(path).is_some();
+    }
+
+    /// Follow a file that was moved on both sides
+    pub fn moved(&mut self, from: &Path, to: &Path) {
+        if let Some(hash) = self.hashes.remove(from) {
+            self.hashes.insert(to.to_path_buf(), hash);
+            self.dirty = true;
+        }
+    }
+
+    /// Hash of each file as last synced
+    pub fn hashes(&self) -> &HashMap<PathBuf, String> {
+        &self.hashes
+    }
+
+    /// Write the hashes to disk if they changed since the last save
+    pub fn save(&mut self) {
+        let Some(path) = self.path.as_ref().filter(|_| self.dirty) else {
+            return;
+        };
+        let result = serde_json::to_string(&self.hashes)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                Ok(std::fs::write(path, json)?)
+            });
+        match result {
+            Ok(()) => self.dirty = false,
+            Err(e) => error!("Failed to save {}: {}", path.display(), e),
+        }
+    }
+
+    /// Pair every file on disk and every file last synced with both hashes,
+    /// for the server to work out what changed on which side
+    pub fn manifest(&self, project_path: &Path, sparse: &SparseSpec) -> Manifest {
+        let mut filter = SyncFilter::load(project_path);
+        let current = tracked_file_hashes(project_path, &IgnoreMatcher::load(project_path), sparse, &mut filter);
+        let mut manifest: Manifest = self.hashes
+            .iter()
+            .filter(|(path, _)| sparse.includes(path))
+            .map(|(path, base)| (path.clone(), ManifestEntry { hash: None, base: Some(base.clone()) }))
+            .collect();
+        for (path, hash) in current {
+            manifest.entry(path).or_default().hash = Some(hash);
+        }
+        manifest
+    }
+}
@@ -0,0 +1,180 @@
+use anyhow::Result;
+use mothership_common::{IgnoreMatcher, ProjectSettings};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+use crate::file_watcher::{read_config_section, write_config_section};
+
+/// Key of the filter settings in `.mothership/config.json`
+const SETTINGS_KEY: &str = "sync_filter";
+
+/// Default size above which files are not synced (1MB)
+const DEFAULT_MAX_FILE_SIZE: u64 = 1_048_576;
+
+/// File types skipped as binary unless allow-listed
+const BINARY_TYPES: &[&str] = &[
+    "*.png", "*.jpg", "*.jpeg", "*.gif", "*.bmp", "*.ico", "*.webp", "*.psd",
+    "*.mp3", "*.mp4", "*.mov", "*.avi", "*.wav", "*.flac",
+    "*.zip", "*.gz", "*.tgz", "*.tar", "*.7z", "*.rar",
+    "*.exe", "*.dll", "*.so", "*.dylib", "*.a", "*.lib", "*.o", "*.obj", "*.class", "*.jar", "*.wasm",
+    "*.pdf", "*.ttf", "*.otf", "*.woff", "*.woff2", "*.sqlite", "*.db", "*.bin",
+];
+
+/// How much of a file is searched for a NUL byte to tell binary content from text, like git
+const BINARY_SNIFF_LEN: usize = 8000;
+
+/// Which files a project's daemon leaves out of sync, set by the
+/// `sync_filter` key of `.mothership/config.json`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncFilterSettings {
+    /// Files larger than this many bytes are not synced
+    pub max_file_size: u64,
+    /// Binary file types to sync anyway, as `.mothershipignore` patterns
+    pub allow_binary: Vec<String>,
+}
+
+impl Default for SyncFilterSettings {
+    fn default() -> Self {
+        Self {
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+            allow_binary: Vec::new(),
+        }
+    }
+}
+
+impl SyncFilterSettings {
+    /// Load a project's settings, falling back to the defaults
+    pub fn load(project_path: &Path) -> Self {
+        read_config_section(project_path, SETTINGS_KEY).unwrap_or_default()
+    }
+
+    /// Save the settings into `.mothership/config.json`, keeping its other keys
+    pub fn save(&self, project_path: &Path) -> Result<()> {
+        write_config_section(project_path, SETTINGS_KEY, self)
+    }
+}
+
+/// Why a file isn't synced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SkipReason {
+    /// Larger than `max_file_size`
+    TooLarge,
+    /// A binary type or binary content
+    Binary,
+    /// Not one of the project's `allowed_file_types`
+    FileType,
+}
+
+impl SkipReason {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            SkipReason::TooLarge => "too large",
+            SkipReason::Binary => "binary",
+            SkipReason::FileType => "file type not allowed",
+        }
+    }
+}
+
+/// Size and type limits of one project, and the files they currently leave out
+pub struct SyncFilter {
+    settings: SyncFilterSettings,
+    binary_types: IgnoreMatcher,
+    allow_binary: IgnoreMatcher,
+    /// The project's `allowed_file_types`, if its push rules restrict file types
+    allowed_types: Option<IgnoreMatcher>,
+    /// Skipped files by path relative to the project
+    skipped: HashMap<PathBuf, SkipReason>,
+}
+
+impl SyncFilter {
+    pub fn new(settings: SyncFilterSettings) -> Self {
+        let binary_types: Vec<String> = BINARY_TYPES.iter().map(|pattern| pattern.to_string()).collect();
+        Self {
+            binary_types: IgnoreMatcher::from_patterns("", &binary_types),
+            allow_binary: IgnoreMatcher::from_patterns("", &settings.allow_binary),
+            allowed_types: None,
+            settings,
+            skipped: HashMap::new(),
+        }
+    }
+
+    /// The filter for a project, as set in its `.mothership/config.json`
+    pub fn load(project_path: &Path) -> Self {
+        Self::new(SyncFilterSettings::load(project_path))
+    }
+
+    pub fn settings(&self) -> &SyncFilterSettings {
+        &self.settings
+    }
+
+    pub fn set_settings(&mut self, settings: SyncFilterSettings) {
+        self.allow_binary = IgnoreMatcher::from_patterns("", &settings.allow_binary);
+        self.settings = settings;
+    }
+
+    /// Skip the file types the server would reject anyway
+    pub fn apply_project_settings(&mut self, settings: &ProjectSettings) {
+        self.allowed_types = settings.push_rules.restrict_file_types
+            .then(|| IgnoreMatcher::from_patterns("", &settings.allowed_file_types));
+    }
+
+    /// Whether a file may be synced, judging by its path and size.
+    /// Call before reading it, then [`Self::admits_content`] once read.
+    pub fn admits(&mut self, path: &Path, size: u64) -> bool {
+        let reason = if size > self.settings.max_file_size {
+            Some(SkipReason::TooLarge)
+        } else if self.binary_types.is_ignored(path, false) && !self.allow_binary.is_ignored(path, false) {
+            Some(SkipReason::Binary)
+        } else if self.allowed_types.as_ref().is_some_and(|allowed| !allowed.is_ignored(path, false)) {
+            Some(SkipReason::FileType)
+        } else {
+            None
+        };
+        self.record(path, reason)
+    }
+
+    /// Whether a file's content may be synced; content that isn't text can't be
+    pub fn admits_content(&mut self, path: &Path, content: &[u8]) -> bool {
+        let sniffed = &content[..content.len().min(BINARY_SNIFF_LEN)];
+        let binary = sniffed.contains(&0) || std::str::from_utf8(content).is_err();
+        self.record(path, binary.then_some(SkipReason::Binary))
+    }
+
+    /// Stop counting a deleted file as skipped
+    pub fn forget(&mut self, path: &Path) {
+        self.skipped.remove(path);
+    }
+
+    /// Take over the files another filter skipped
+    pub fn absorb_skipped(&mut self, other: SyncFilter) {
+        self.skipped.extend(other.skipped);
+    }
+
+    /// How many files are skipped for each reason
+    pub fn skipped_counts(&self) -> HashMap<SkipReason, usize> {
+        let mut counts = HashMap::new();
+        for reason in self.skipped.values() {
+            *counts.entry(*reason).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    fn record(&mut self, path: &Path, reason: Option<SkipReason>) -> bool {
+        match reason {
+            Some(reason) => {
+                if self.skipped.insert(path.to_path_buf(), reason) != Some(reason) {
+                    info!("⏭️ Not syncing {}: {}", path.display(), reason.describe());
+                }
+                false
+            }
+            None => {
+                self.skipped.remove(path);
+                true
+            }
+        }
+    }
+}
@@ -8,9 +8,11 @@ use std::ffi::CString;
 use std::ptr;
 
 use crate::daemon::{DaemonStatus, TrackedProject};
+use crate::project_scanner::ProjectScanner;
 
 #[cfg(windows)]
 use {
+    mothership_common::discovery::DiscoveryDecision,
     tray_icon::{
         menu::{Menu, MenuEvent, MenuItemBuilder, Submenu},
         TrayIconBuilder, TrayIconEvent, Icon,
@@ -27,6 +29,8 @@ use {
 pub struct SystemTray {
     status: Arc<RwLock<DaemonStatus>>,
     tracked_projects: Arc<RwLock<HashMap<Uuid, TrackedProject>>>,
+    /// Projects found under the scan roots, which the user can choose to track
+    scanner: Arc<ProjectScanner>,
 }
 
 #[cfg(windows)]
@@ -73,7 +77,9 @@ fn open_folder(path: &std::path::Path) -> Result<()> {
 #[cfg(windows)]
 fn get_dynamic_tooltip(status: &DaemonStatus) -> String {
     let connection_status = if status.server_connected { "🟢" } else { "🔴" };
-    let sync_status = if status.files_syncing > 0 { 
+    let sync_status = if status.behind_by > 0 {
+        format!("🐢 Behind by {} changes", status.behind_by)
+    } else if status.files_syncing > 0 { 
         format!("⏳ {} files syncing", status.files_syncing) 
     } else { 
         "✅ All synced".to_string() 
@@ -92,10 +98,11 @@ impl SystemTray {
     /// Create a new system tray instance
     pub fn new(
         status: Arc<RwLock<DaemonStatus>>, 
-        tracked_projects: Arc<RwLock<HashMap<Uuid, TrackedProject>>>
+        tracked_projects: Arc<RwLock<HashMap<Uuid, TrackedProject>>>,
+        scanner: Arc<ProjectScanner>,
     ) -> Result<Self> {
         info!("🖥️ Initializing enhanced system tray...");
-        Ok(Self { status, tracked_projects })
+        Ok(Self { status, tracked_projects, scanner })
     }
 
     /// Run the system tray (Windows only)
@@ -107,6 +114,7 @@ impl SystemTray {
             // Clone for thread-safe access
             let status = self.status.clone();
             let tracked_projects = self.tracked_projects.clone();
+            let scanner = self.scanner.clone();
             
             // Spawn the system tray in a dedicated std::thread
             let tray_handle = std::thread::spawn(move || {
@@ -173,11 +181,13 @@ impl SystemTray {
                                     Server Connected: {}\n\
                                     Projects Tracked: {}\n\
                                     Files Syncing: {}\n\
+                                    Behind By: {} changes\n\
                                     Last Sync: {}",
                                     if status.is_running { "✅ Yes" } else { "❌ No" },
                                     if status.server_connected { "🟢 Connected" } else { "🔴 Disconnected" },
                                     status.projects_tracked,
                                     status.files_syncing,
+                                    status.behind_by,
                                     status.last_sync
                                         .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
                                         .unwrap_or_else(|| "Never".to_string())
@@ -244,6 +254,18 @@ impl SystemTray {
                                     }
                                 }
                             }
+                            id if id.starts_with("track_discovered_") || id.starts_with("ignore_discovered_") => {
+                                let (decision, project_id_str) = match id.strip_prefix("track_discovered_") {
+                                    Some(project_id) => (DiscoveryDecision::Track, project_id),
+                                    None => (DiscoveryDecision::Ignore, &id["ignore_discovered_".len()..]),
+                                };
+                                let discovered = scanner.discovered();
+                                let discovered = discovered.blocking_read();
+                                if let Some(project) = discovered.iter().find(|project| project.project_id.to_string() == project_id_str) {
+                                    info!("🔎 {:?} discovered project '{}' requested from system tray", decision, project.project_name);
+                                    scanner.decide_later(project.project_path.clone(), decision);
+                                }
+                            }
                             "open_logs" => {
                                 info!("📜 Opening logs folder");
                                 // Try to open the logs directory
@@ -320,7 +342,7 @@ impl SystemTray {
                                 
                                 // Rebuild menu with current project list
                                 if last_menu_update.elapsed() > std::time::Duration::from_millis(500) {
-                                    let tray_menu = Self::build_context_menu(&status, &tracked_projects);
+                                    let tray_menu = Self::build_context_menu(&status, &tracked_projects, &scanner);
                                     let _ = tray_icon.set_menu(Some(Box::new(tray_menu)));
                                     last_menu_update = std::time::Instant::now();
                                 }
@@ -361,7 +383,8 @@ impl SystemTray {
     #[cfg(windows)]
     fn build_context_menu(
         _status: &Arc<RwLock<DaemonStatus>>, 
-        tracked_projects: &Arc<RwLock<HashMap<Uuid, TrackedProject>>>
+        tracked_projects: &Arc<RwLock<HashMap<Uuid, TrackedProject>>>,
+        scanner: &ProjectScanner,
     ) -> Menu {
         let tray_menu = Menu::new();
         
@@ -394,6 +417,31 @@ impl SystemTray {
             
             let _ = tray_menu.append(&projects_submenu);
         }
+
+        // Discovered projects waiting for the user to choose
+        let discovered = scanner.discovered();
+        let discovered = discovered.blocking_read();
+        let undecided: Vec<_> = discovered.iter().filter(|project| project.decision.is_none()).collect();
+        if !undecided.is_empty() {
+            let discovered_submenu = Submenu::new(&format!("🔎 Discovered Projects ({})", undecided.len()), true);
+
+            for project in undecided {
+                let project_submenu = Submenu::new(&format!("📁 {} ({})", project.project_name, project.project_path.display()), true);
+                let track_item = MenuItemBuilder::new()
+                    .text("✅ Track")
+                    .id(format!("track_discovered_{}", project.project_id).into())
+                    .build();
+                let ignore_item = MenuItemBuilder::new()
+                    .text("🙈 Don't Track")
+                    .id(format!("ignore_discovered_{}", project.project_id).into())
+                    .build();
+                let _ = project_submenu.append(&track_item);
+                let _ = project_submenu.append(&ignore_item);
+                let _ = discovered_submenu.append(&project_submenu);
+            }
+
+            let _ = tray_menu.append(&discovered_submenu);
+        }
         
         // Separator
         let _ = tray_menu.append(&tray_icon::menu::MenuItemBuilder::new().text("─────────────").enabled(false).build());
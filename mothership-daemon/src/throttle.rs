@@ -0,0 +1,146 @@
+use mothership_common::{DaemonConfig, ThrottleSettings};
+use std::path::Path;
+use std::sync::{Condvar, Mutex, MutexGuard, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+use tracing::info;
+
+/// How long a power source reading is trusted
+const POWER_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Reads are paced over windows this long
+const IO_WINDOW: Duration = Duration::from_secs(1);
+
+/// Limits how much CPU and disk the daemon's scans use. Shared by every
+/// project's watcher, so the limits hold for the daemon as a whole.
+pub struct Throttle {
+    settings: RwLock<ThrottleSettings>,
+    /// Hash jobs running
+    jobs: Mutex<usize>,
+    job_finished: Condvar,
+    /// Bytes read in the current window, and when it started
+    io: Mutex<(Instant, u64)>,
+    /// Last power source reading, and when it was taken
+    power: Mutex<Option<(Instant, bool)>>,
+}
+
+/// A running hash job; the slot frees up when it is dropped
+pub struct HashJob<'a> {
+    throttle: &'a Throttle,
+}
+
+impl Drop for HashJob<'_> {
+    fn drop(&mut self) {
+        *lock(&self.throttle.jobs) -= 1;
+        self.throttle.job_finished.notify_one();
+    }
+}
+
+/// The daemon's throttle, configured from `daemon.json`
+pub fn throttle() -> &'static Throttle {
+    static THROTTLE: OnceLock<Throttle> = OnceLock::new();
+    THROTTLE.get_or_init(|| Throttle::new(DaemonConfig::load().throttle))
+}
+
+impl Throttle {
+    fn new(settings: ThrottleSettings) -> Self {
+        Self {
+            settings: RwLock::new(settings),
+            jobs: Mutex::new(0),
+            job_finished: Condvar::new(),
+            io: Mutex::new((Instant::now(), 0)),
+            power: Mutex::new(None),
+        }
+    }
+
+    pub fn settings(&self) -> ThrottleSettings {
+        *self.settings.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Apply new limits; jobs already running finish first
+    pub fn update(&self, settings: ThrottleSettings) {
+        info!("🐢 Updated resource limits: {:?}", settings);
+        *self.settings.write().unwrap_or_else(|e| e.into_inner()) = settings;
+        self.job_finished.notify_all();
+    }
+
+    /// Whether low-priority mode is in effect now
+    pub fn is_low_priority(&self) -> bool {
+        self.settings().is_low_priority(self.on_battery())
+    }
+
+    /// Wait for a free hash job slot. Blocks, so call it off the async runtime.
+    pub fn hash_job(&self) -> HashJob<'_> {
+        let mut jobs = lock(&self.jobs);
+        while *jobs >= self.settings().hash_jobs(self.on_battery()) {
+            jobs = self.job_finished.wait(jobs).unwrap_or_else(|e| e.into_inner());
+        }
+        *jobs += 1;
+        HashJob { throttle: self }
+    }
+
+    /// Read a file, then wait as long as the IO rate limit asks. Blocks, so
+    /// call it off the async runtime.
+    pub fn read(&self, path: &Path) -> std::io::Result<Vec<u8>> {
+        let bytes = std::fs::read(path)?;
+        let rate = self.settings().io_rate(self.on_battery());
+        if rate > 0 {
+            std::thread::sleep(self.consume(bytes.len() as u64, rate));
+        }
+        Ok(bytes)
+    }
+
+    /// Count bytes read against the current window; returns how long to pause
+    /// so reads average out at `rate` bytes per second
+    fn consume(&self, bytes: u64, rate: u64) -> Duration {
+        let mut io = lock(&self.io);
+        if io.0.elapsed() >= IO_WINDOW {
+            *io = (Instant::now(), 0);
+        }
+        io.1 += bytes;
+        Duration::from_secs_f64(io.1 as f64 / rate as f64).saturating_sub(io.0.elapsed())
+    }
+
+    fn on_battery(&self) -> bool {
+        let mut power = lock(&self.power);
+        match *power {
+            Some((checked, on_battery)) if checked.elapsed() < POWER_CHECK_INTERVAL => on_battery,
+            _ => {
+                let on_battery = on_battery();
+                *power = Some((Instant::now(), on_battery));
+                on_battery
+            }
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Whether the machine runs on a discharging battery
+#[cfg(target_os = "linux")]
+fn on_battery() -> bool {
+    let Ok(supplies) = std::fs::read_dir("/sys/class/power_supply") else {
+        return false;
+    };
+    supplies.filter_map(|entry| entry.ok()).any(|supply| {
+        let read = |name: &str| std::fs::read_to_string(supply.path().join(name)).unwrap_or_default();
+        read("type").trim() == "Battery" && read("status").trim() == "Discharging"
+    })
+}
+
+/// Whether the machine runs on a discharging battery
+#[cfg(target_os = "macos")]
+fn on_battery() -> bool {
+    std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).contains("'Battery Power'"))
+        .unwrap_or(false)
+}
+
+/// Whether the machine runs on a discharging battery
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn on_battery() -> bool {
+    false
+}
@@ -0,0 +1,252 @@
+use anyhow::{anyhow, Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::{info, warn};
+
+/// Name of the systemd user unit
+#[cfg(target_os = "linux")]
+const SYSTEMD_UNIT: &str = "mothership-daemon.service";
+
+/// Label of the launchd agent
+#[cfg(target_os = "macos")]
+const LAUNCHD_LABEL: &str = "com.mothership.daemon";
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn home_dir() -> Result<PathBuf> {
+    std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .ok_or_else(|| anyhow!("HOME is not set"))
+}
+
+/// The installed daemon binary the service runs
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn daemon_executable() -> Result<PathBuf> {
+    let exe = std::env::current_exe().context("Failed to locate the daemon executable")?;
+    Ok(exe.canonicalize().unwrap_or(exe))
+}
+
+/// Run a service manager command, failing with its output if it fails
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn run(program: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .with_context(|| format!("Failed to run {}", program))?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "{} {} failed: {}",
+            program,
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Where the systemd user unit is installed
+#[cfg(target_os = "linux")]
+fn unit_path() -> Result<PathBuf> {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .map(Ok)
+        .unwrap_or_else(|| home_dir().map(|home| home.join(".config")))?;
+    Ok(config_dir.join("systemd").join("user").join(SYSTEMD_UNIT))
+}
+
+/// A systemd user unit that keeps the daemon running for the logged-in user
+#[cfg(target_os = "linux")]
+fn systemd_unit(exe: &Path) -> String {
+    format!(
+        "[Unit]\n\
+         Description=Mothership Daemon - background file synchronization\n\
+         After=network-online.target\n\
+         Wants=network-online.target\n\
+         \n\
+         [Service]\n\
+         Type=simple\n\
+         ExecStart=\"{}\"\n\
+         Restart=on-failure\n\
+         RestartSec=5\n\
+         \n\
+         [Install]\n\
+         WantedBy=default.target\n",
+        exe.display()
+    )
+}
+
+/// Install the daemon as a systemd user service and start it
+#[cfg(target_os = "linux")]
+pub fn install_service() -> Result<()> {
+    let path = unit_path()?;
+    info!("📦 Installing systemd user unit at {}", path.display());
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, systemd_unit(&daemon_executable()?))?;
+
+    run("systemctl", &["--user", "daemon-reload"])?;
+    run("systemctl", &["--user", "enable", "--now", SYSTEMD_UNIT])?;
+    Ok(())
+}
+
+/// Stop the systemd user service and remove its unit
+#[cfg(target_os = "linux")]
+pub fn uninstall_service() -> Result<()> {
+    let path = unit_path()?;
+    info!("🗑️ Removing systemd user unit at {}", path.display());
+
+    if let Err(e) = run("systemctl", &["--user", "disable", "--now", SYSTEMD_UNIT]) {
+        warn!("Could not stop the service: {}", e);
+    }
+    if path.exists() {
+        std::fs::remove_file(&path)?;
+    }
+    if let Err(e) = run("systemctl", &["--user", "daemon-reload"]) {
+        warn!("Could not reload systemd: {}", e);
+    }
+    Ok(())
+}
+
+/// Describe whether the systemd user service is installed, enabled and running
+#[cfg(target_os = "linux")]
+pub fn service_status() -> Result<String> {
+    let path = unit_path()?;
+    if !path.exists() {
+        return Ok("Not installed".to_string());
+    }
+
+    // `is-enabled` and `is-active` exit non-zero for "disabled" and "inactive"
+    let state = |command: &str| {
+        Command::new("systemctl")
+            .args(["--user", command, SYSTEMD_UNIT])
+            .output()
+            .ok()
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .filter(|state| !state.is_empty())
+            .unwrap_or_else(|| "unknown".to_string())
+    };
+    Ok(format!(
+        "Installed at {} ({}, {})",
+        path.display(),
+        state("is-enabled"),
+        state("is-active")
+    ))
+}
+
+/// Where the launchd agent is installed
+#[cfg(target_os = "macos")]
+fn plist_path() -> Result<PathBuf> {
+    Ok(home_dir()?
+        .join("Library")
+        .join("LaunchAgents")
+        .join(format!("{}.plist", LAUNCHD_LABEL)))
+}
+
+/// A launchd agent that starts the daemon at login and restarts it if it exits
+#[cfg(target_os = "macos")]
+fn launchd_plist(exe: &Path, log_path: &Path) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{label}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{exe}</string>
+    </array>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    <dict>
+        <key>SuccessfulExit</key>
+        <false/>
+    </dict>
+    <key>StandardOutPath</key>
+    <string>{log}</string>
+    <key>StandardErrorPath</key>
+    <string>{log}</string>
+</dict>
+</plist>
+"#,
+        label = LAUNCHD_LABEL,
+        exe = xml_escape(&exe.display().to_string()),
+        log = xml_escape(&log_path.display().to_string()),
+    )
+}
+
+#[cfg(target_os = "macos")]
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Install the daemon as a launchd agent and start it
+#[cfg(target_os = "macos")]
+pub fn install_service() -> Result<()> {
+    let path = plist_path()?;
+    info!("📦 Installing launchd agent at {}", path.display());
+
+    let log_dir = home_dir()?.join("Library").join("Logs");
+    std::fs::create_dir_all(&log_dir)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    // Reinstalling replaces a loaded agent
+    if path.exists() {
+        let _ = run("launchctl", &["unload", &path.to_string_lossy()]);
+    }
+    std::fs::write(&path, launchd_plist(&daemon_executable()?, &log_dir.join("mothership-daemon.log")))?;
+    run("launchctl", &["load", "-w", &path.to_string_lossy()])?;
+    Ok(())
+}
+
+/// Stop the launchd agent and remove its plist
+#[cfg(target_os = "macos")]
+pub fn uninstall_service() -> Result<()> {
+    let path = plist_path()?;
+    info!("🗑️ Removing launchd agent at {}", path.display());
+
+    if path.exists() {
+        if let Err(e) = run("launchctl", &["unload", "-w", &path.to_string_lossy()]) {
+            warn!("Could not stop the agent: {}", e);
+        }
+        std::fs::remove_file(&path)?;
+    }
+    Ok(())
+}
+
+/// Describe whether the launchd agent is installed and running
+#[cfg(target_os = "macos")]
+pub fn service_status() -> Result<String> {
+    let path = plist_path()?;
+    if !path.exists() {
+        return Ok("Not installed".to_string());
+    }
+
+    let state = match run("launchctl", &["list", LAUNCHD_LABEL]) {
+        Ok(info) if info.contains("\"PID\"") => "running",
+        Ok(_) => "loaded, not running",
+        Err(_) => "not loaded",
+    };
+    Ok(format!("Installed at {} ({})", path.display(), state))
+}
+
+/// Stub functions for platforms without a supported service manager
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn install_service() -> Result<()> {
+    Err(anyhow!("Service installation is supported on Windows, Linux (systemd) and macOS (launchd)"))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn uninstall_service() -> Result<()> {
+    Err(anyhow!("Service installation is supported on Windows, Linux (systemd) and macOS (launchd)"))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn service_status() -> Result<String> {
+    Err(anyhow!("Service installation is supported on Windows, Linux (systemd) and macOS (launchd)"))
+}
@@ -66,6 +66,26 @@ async function safeInvoke(command, args = {}) {
             case 'create_checkpoint':
                 console.log('Mock: Checkpoint created with message:', args.message)
                 return null
+            case 'load_chat_history':
+                return []
+            case 'load_notifications':
+                return { notifications: [], unread: 0 }
+            case 'live_open':
+                // Live editing needs the desktop app's sync socket
+                return null
+            case 'load_file_revisions':
+            case 'list_rifts':
+                return []
+            case 'daemon_sync_state':
+                return { running: false, server_connected: false, queued_changes: 0, protocol_error: null, projects: [] }
+            case 'current_rift':
+                return null
+            case 'load_working_diff':
+            case 'load_checkpoint_diff':
+                return { project_id: null, path: args.path, old_label: '', new_label: '', hunks: [], staged: [] }
+            case 'send_chat_message':
+                console.log('Mock: Chat message sent:', args.content)
+                return null
             case 'authenticate_with_mothership':
                 throw new Error('Authentication requires Tauri desktop app')
             case 'start_google_oauth':
@@ -73,6 +93,22 @@ async function safeInvoke(command, args = {}) {
                 const apiBase = window.MOTHERSHIP_API_BASE_URL || 'http://localhost:7523';
                 window.location.href = apiBase + '/auth/oauth/start';
                 return null;
+            case 'scan_project_directory':
+                return { kinds: [], ignore_file: '', has_ignore_file: false, allowed_file_types: ['*.md', '*.txt'], existing_gateway: null }
+            case 'upload_gateway_files':
+                return { files_stored: 0, skipped: [], rejected: [] }
+            case 'project_features':
+                return []
+            case 'list_servers':
+            case 'add_server':
+            case 'remove_server':
+                return [{ name: 'Mothership', url: 'https://api.mothershipproject.dev', signed_in: false, active: true }]
+            case 'switch_server':
+                return false
+            case 'check_for_update':
+                return null
+            case 'search_project':
+                return { matches: [], files_searched: 0, truncated: false, source: 'local' }
             case 'create_gateway':
                 console.log('Mock: Would create gateway:', args)
                 // Simulate gateway creation
@@ -125,6 +161,52 @@ let currentFile = null
 let isVimEnabled = true
 let projects = []
 let currentProject = null
+let currentRiftId = null
+let chatPollTimer = null
+
+// Rifts of the selected gateway, refreshed now and then so presence stays current
+let currentRiftName = null
+let projectRifts = []
+let riftPollTimer = null
+
+// Background sync as reported by the local daemon
+let daemonState = null
+let notificationPollTimer = null
+let commentThreads = []
+let commentDecorations = []
+
+// Co-editing state: one shared file at a time over the rift's sync socket
+let coeditSocket = null
+let coeditPath = null
+let coeditSiteId = null
+let applyingRemoteEdit = false
+
+// Live editing: the open project file synced over its rift's socket
+const LIVE_EDIT_DELAY_MS = 500
+const CURSOR_UPDATE_DELAY_MS = 150
+let liveSocket = null
+let liveSession = null
+let liveEditTimer = null
+let cursorUpdateTimer = null
+let remoteCursors = new Map()
+let cursorDecorations = []
+// What collaborators are doing with files of the rift, by user and path
+let fileActivity = new Map()
+
+// Shared terminals of the current rift, watched over their own sync socket
+let terminalSocket = null
+let terminalShares = new Map()
+
+// Gateway wizard state: the current step and what the directory scan suggested
+const UPLOAD_POLL_INTERVAL_MS = 250
+const PROJECT_KIND_NAMES = { rust: 'Rust', node: 'Node', python: 'Python' }
+let wizardStep = 1
+let wizardScan = null
+
+// Diff viewer state: the file being diffed and how its hunks are laid out
+let diffPath = null
+let diffView = null
+let diffInline = false
 
 // DOM elements
 const authOverlay = document.getElementById('auth-overlay')
@@ -132,13 +214,28 @@ const authMessage = document.getElementById('auth-message')
 const authBtn = document.getElementById('auth-btn')
 const refreshBtn = document.getElementById('refresh-btn')
 const checkpointBtn = document.getElementById('checkpoint-btn')
+const coeditBtn = document.getElementById('coedit-btn')
+const diffBtn = document.getElementById('diff-btn')
 const vimToggle = document.getElementById('vim-toggle')
+const serversBtn = document.getElementById('servers-btn')
+const updateBtn = document.getElementById('update-btn')
+const updateChannelSelect = document.getElementById('update-channel')
+const serversOverlay = document.getElementById('servers-overlay')
+const serversMessage = document.getElementById('servers-message')
+const serverList = document.getElementById('server-list')
+const addServerForm = document.getElementById('add-server-form')
+const serverUrlInput = document.getElementById('server-url')
+const serverNameInput = document.getElementById('server-name')
+const closeServersBtn = document.getElementById('close-servers-btn')
+const authServerName = document.getElementById('auth-server-name')
+const authChangeServer = document.getElementById('auth-change-server')
 const projectList = document.getElementById('project-list')
 const fileExplorer = document.getElementById('file-explorer')
 const editorTabs = document.getElementById('editor-tabs')
 const currentFileSpan = document.getElementById('current-file')
 const vimModeSpan = document.getElementById('vim-mode')
 const cursorPositionSpan = document.getElementById('cursor-position')
+const editingWarning = document.getElementById('editing-warning')
 
 // Authentication modal elements
 const googleLoginBtn = document.getElementById('google-login-btn')
@@ -159,6 +256,46 @@ const gatewayPathInput = document.getElementById('gateway-path')
 const browsePathBtn = document.getElementById('browse-path-btn')
 const cancelGatewayBtn = document.getElementById('cancel-gateway-btn')
 const createGatewayBtn = document.getElementById('create-gateway-btn')
+const wizardBackBtn = document.getElementById('wizard-back-btn')
+const wizardDetected = document.getElementById('wizard-detected')
+const gatewayIgnoreInput = document.getElementById('gateway-ignore')
+const gatewayRestrictTypesInput = document.getElementById('gateway-restrict-types')
+const gatewayFileTypesInput = document.getElementById('gateway-file-types')
+const gatewayCheckpointIntervalSelect = document.getElementById('gateway-checkpoint-interval')
+const uploadPhase = document.getElementById('upload-phase')
+const uploadProgressBar = document.getElementById('upload-progress-bar')
+const uploadDetail = document.getElementById('upload-detail')
+
+// Diff viewer elements
+const diffOverlay = document.getElementById('diff-overlay')
+const diffTitle = document.getElementById('diff-title')
+const diffSource = document.getElementById('diff-source')
+const diffLayoutBtn = document.getElementById('diff-layout-btn')
+const diffCloseBtn = document.getElementById('diff-close-btn')
+const diffContent = document.getElementById('diff-content')
+const diffStageForm = document.getElementById('diff-stage-form')
+const diffStageMessage = document.getElementById('diff-stage-message')
+const diffStageBtn = document.getElementById('diff-stage-btn')
+
+// Chat panel elements
+const activityFeed = document.getElementById('activity-feed')
+const chatMessages = document.getElementById('chat-messages')
+const notificationList = document.getElementById('notification-list')
+const notificationCount = document.getElementById('notification-count')
+const notificationsReadBtn = document.getElementById('notifications-read-btn')
+const terminalList = document.getElementById('terminal-list')
+const riftList = document.getElementById('rift-list')
+const riftDiff = document.getElementById('rift-diff')
+const searchForm = document.getElementById('search-form')
+const searchInput = document.getElementById('search-input')
+const searchCaseBtn = document.getElementById('search-case-btn')
+const searchRegexBtn = document.getElementById('search-regex-btn')
+const searchResults = document.getElementById('search-results')
+const newRiftBtn = document.getElementById('new-rift-btn')
+const daemonStatusSpan = document.getElementById('daemon-status')
+const chatForm = document.getElementById('chat-form')
+const chatInput = document.getElementById('chat-input')
+const chatSendBtn = document.getElementById('chat-send-btn')
 
 // Authentication state
 let isAuthenticated = false
@@ -218,7 +355,8 @@ function initializeEditor() {
         renderWhitespace: 'selection',
         scrollBeyondLastLine: false,
         smoothScrolling: true,
-        cursorBlinking: 'smooth'
+        cursorBlinking: 'smooth',
+        glyphMargin: true
     })
 
     // Initialize vim mode
@@ -229,6 +367,22 @@ function initializeEditor() {
     // Update cursor position
     editor.onDidChangeCursorPosition((e) => {
         cursorPositionSpan.textContent = `Ln ${e.position.lineNumber}, Col ${e.position.column}`
+        scheduleCursorUpdate()
+    })
+
+    // Send co-editing changes as they are typed
+    editor.onDidChangeModelContent(() => {
+        if (coeditPath && !applyingRemoteEdit) {
+            sendCoeditChange()
+        }
+    })
+
+    // Send live edits once typing pauses
+    editor.onDidChangeModelContent(() => {
+        if (liveSession && !applyingRemoteEdit) {
+            clearTimeout(liveEditTimer)
+            liveEditTimer = setTimeout(sendLiveEdit, LIVE_EDIT_DELAY_MS)
+        }
     })
 
     // Auto-save on content change
@@ -288,6 +442,9 @@ function disableVimMode() {
 
 // Load and display projects
 async function loadProjects() {
+    // Loading projects means we're signed in, so notifications can be fetched too
+    startNotificationPolling()
+
     try {
         projectList.innerHTML = '<div class="loading">Loading gateways...</div>'
         
@@ -304,6 +461,7 @@ async function loadProjects() {
             const project = gatewayProject.project
             const projectElement = document.createElement('div')
             projectElement.className = 'project-item'
+            projectElement.dataset.projectId = project.id
             projectElement.innerHTML = `
                 <span style="color: #007acc;">📁</span>
                 <div>
@@ -312,6 +470,7 @@ async function loadProjects() {
                         ${project.description || 'No description'}
                     </div>
                 </div>
+                <span class="sync-badge"></span>
             `
             
             projectElement.addEventListener('click', () => {
@@ -320,6 +479,7 @@ async function loadProjects() {
             
             projectList.appendChild(projectElement)
         })
+        renderDaemonState()
         
     } catch (error) {
         console.error('Failed to load gateways:', error)
@@ -327,32 +487,1343 @@ async function loadProjects() {
     }
 }
 
-// Select a project and load its files
-async function selectProject(gatewayProject) {
-    currentProject = gatewayProject
-    
-    // Update UI
-    document.querySelectorAll('.project-item').forEach(item => {
-        item.classList.remove('active')
-    })
-    event.currentTarget.classList.add('active')
-    
-    // For now, show project info since we don't have a local path
-    const project = gatewayProject.project
-    fileExplorer.innerHTML = `
-        <div style="padding: 20px; text-align: center;">
-            <h3>${project.name}</h3>
-            <p style="color: #888; margin: 10px 0;">${project.description}</p>
-            <p style="font-size: 12px; color: #666;">
-                Project ID: ${project.id}<br/>
-                Created: ${new Date(project.created_at).toLocaleDateString()}
-            </p>
-            <p style="font-size: 12px; color: #999; margin-top: 20px;">
-                File browsing will be available in a future update.<br/>
-                For now, you can create and manage gateways.
-            </p>
-        </div>
-    `
+// Select a project and load its files
+async function selectProject(gatewayProject) {
+    currentProject = gatewayProject
+    
+    // Update UI
+    document.querySelectorAll('.project-item').forEach(item => {
+        item.classList.remove('active')
+    })
+    event.currentTarget.classList.add('active')
+    
+    // For now, show project info since we don't have a local path
+    const project = gatewayProject.project
+    fileExplorer.innerHTML = `
+        <div style="padding: 20px; text-align: center;">
+            <h3>${project.name}</h3>
+            <p style="color: #888; margin: 10px 0;">${project.description}</p>
+            <p style="font-size: 12px; color: #666;">
+                Project ID: ${project.id}<br/>
+                Created: ${new Date(project.created_at).toLocaleDateString()}<br/>
+                Last activity: ${gatewayProject.last_activity ? new Date(gatewayProject.last_activity).toLocaleString() : 'never'}<br/>
+                Rifts: ${gatewayProject.your_rifts.length} yours, ${gatewayProject.active_rifts.length} others active
+            </p>
+            <p style="font-size: 12px; color: #999; margin-top: 20px;">
+                File browsing will be available in a future update.<br/>
+                For now, you can create and manage gateways.
+            </p>
+        </div>
+    `
+
+    await loadProjectFeatures(gatewayProject)
+    await loadRifts(gatewayProject)
+    loadActivity(gatewayProject)
+    loadComments(gatewayProject)
+}
+
+// Features the server has turned off for the open project are hidden
+// rather than left to fail
+let disabledFeatures = []
+
+async function loadProjectFeatures(gatewayProject) {
+    let disabled = []
+    try {
+        disabled = await safeInvoke('project_features', { projectId: gatewayProject.project.id }) || []
+    } catch (error) {
+        console.error('Failed to load feature flags:', error)
+    }
+    if (gatewayProject !== currentProject) return
+    applyFeatures(disabled)
+}
+
+function applyFeatures(disabled) {
+    disabledFeatures = disabled
+    document.querySelectorAll('[data-feature]').forEach(element => {
+        element.classList.toggle('feature-off', !featureEnabled(element.dataset.feature))
+    })
+}
+
+function featureEnabled(feature) {
+    return !disabledFeatures.includes(feature)
+}
+
+// Unresolved comment threads of the project, shown beside the lines they
+// were left on in the open file
+async function loadComments(gatewayProject) {
+    commentThreads = []
+    showComments()
+
+    try {
+        const threads = await safeInvoke('load_project_comments', {
+            projectId: gatewayProject.project.id,
+            unresolved: true
+        })
+        if (currentProject !== gatewayProject) return
+        commentThreads = threads
+        showComments()
+    } catch (error) {
+        console.error('Failed to load comments:', error)
+    }
+}
+
+function showComments() {
+    if (!editor) return
+
+    // Thread paths are project-relative with `/` separators
+    const openPath = currentFile ? currentFile.replace(/\\/g, '/') : null
+    const lineCount = editor.getModel().getLineCount()
+    const decorations = commentThreads
+        .filter(thread => openPath && (openPath === thread.path || openPath.endsWith('/' + thread.path)))
+        .filter(thread => thread.line_start <= lineCount)
+        .map(thread => ({
+            range: new monaco.Range(thread.line_start, 1, Math.min(thread.line_end, lineCount), 1),
+            options: {
+                isWholeLine: true,
+                className: 'comment-line',
+                glyphMarginClassName: 'comment-glyph',
+                hoverMessage: thread.comments.map(comment => ({
+                    value: `**${comment.author_name}** · ${new Date(comment.created_at).toLocaleString()}\n\n${comment.body}`
+                }))
+            }
+        }))
+
+    commentDecorations = editor.deltaDecorations(commentDecorations, decorations)
+}
+
+// Project activity feed, newest first; "Load older" fetches the next page
+const ACTIVITY_ICONS = {
+    checkpoint: '●',
+    merge: '◆',
+    rift_created: '├',
+    member_added: '+',
+    member_removed: '−',
+    restore: '↺'
+}
+
+async function loadActivity(gatewayProject, before = null) {
+    if (!before) {
+        activityFeed.innerHTML = '<div class="loading">Loading activity...</div>'
+    }
+
+    try {
+        const page = await safeInvoke('load_project_activity', {
+            projectId: gatewayProject.project.id,
+            before
+        })
+        if (currentProject !== gatewayProject) return
+        renderActivity(gatewayProject, page, !before)
+    } catch (error) {
+        console.error('Failed to load activity:', error)
+        activityFeed.innerHTML = `<div class="error">Failed to load activity: ${error}</div>`
+    }
+}
+
+function renderActivity(gatewayProject, page, replace) {
+    if (replace) {
+        activityFeed.innerHTML = ''
+    }
+    activityFeed.querySelector('.activity-more')?.remove()
+
+    if (replace && page.events.length === 0) {
+        activityFeed.innerHTML = '<div class="loading">No activity yet</div>'
+        return
+    }
+
+    page.events.forEach(event => {
+        const eventElement = document.createElement('div')
+        eventElement.className = 'activity-event'
+
+        const summary = document.createElement('div')
+        summary.textContent = `${ACTIVITY_ICONS[event.kind] || '•'} ${event.summary}`
+
+        const meta = document.createElement('div')
+        meta.className = 'activity-event-meta'
+        const parts = [event.actor || 'unknown']
+        if (event.rift_name) parts.push(event.rift_name)
+        parts.push(new Date(event.occurred_at).toLocaleString())
+        meta.textContent = parts.join(' · ')
+
+        eventElement.append(summary, meta)
+        activityFeed.appendChild(eventElement)
+    })
+
+    if (page.next_before) {
+        const more = document.createElement('button')
+        more.className = 'sidebar-header-btn activity-more'
+        more.textContent = 'Load older'
+        more.onclick = () => loadActivity(gatewayProject, page.next_before)
+        activityFeed.appendChild(more)
+    }
+}
+
+// Notifications: polled like chat, across all projects
+const NOTIFICATION_POLL_INTERVAL_MS = 30000
+const NOTIFICATION_ICONS = {
+    mention: '💬',
+    review_requested: '👀',
+    conflict_rift: '🔀',
+    invitation: '🤝'
+}
+
+function startNotificationPolling() {
+    if (notificationPollTimer) return
+    loadNotifications()
+    notificationPollTimer = setInterval(loadNotifications, NOTIFICATION_POLL_INTERVAL_MS)
+}
+
+async function loadNotifications() {
+    try {
+        const page = await safeInvoke('load_notifications', { unreadOnly: false })
+        renderNotifications(page)
+    } catch (error) {
+        console.error('Failed to load notifications:', error)
+    }
+}
+
+function renderNotifications(page) {
+    notificationCount.textContent = page.unread > 0 ? `(${page.unread})` : ''
+    if (page.notifications.length === 0) {
+        notificationList.innerHTML = '<div class="loading">No notifications</div>'
+        return
+    }
+
+    notificationList.innerHTML = ''
+    page.notifications.forEach(notification => {
+        const element = document.createElement('div')
+        element.className = notification.read_at ? 'notification' : 'notification unread'
+        element.title = notification.body
+
+        const title = document.createElement('div')
+        title.textContent = `${NOTIFICATION_ICONS[notification.kind] || '🔔'} ${notification.title}`
+
+        const meta = document.createElement('div')
+        meta.className = 'notification-meta'
+        meta.textContent = new Date(notification.created_at).toLocaleString()
+
+        element.append(title, meta)
+        if (!notification.read_at) {
+            element.onclick = async () => {
+                await safeInvoke('mark_notifications_read', { id: notification.id })
+                loadNotifications()
+            }
+        }
+        notificationList.appendChild(element)
+    })
+}
+
+notificationsReadBtn.addEventListener('click', async () => {
+    try {
+        await safeInvoke('mark_notifications_read', { id: null })
+        loadNotifications()
+    } catch (error) {
+        console.error('Failed to mark notifications read:', error)
+    }
+})
+
+// Daemon: the toolbar shows whether background sync is running and keeping
+// up, and each gateway the daemon tracks shows its own state
+const DAEMON_POLL_INTERVAL_MS = 5000
+const UPDATE_CHECK_INTERVAL_MS = 6 * 60 * 60 * 1000
+
+async function loadDaemonState() {
+    try {
+        daemonState = await safeInvoke('daemon_sync_state')
+    } catch (error) {
+        console.error('Failed to query the daemon:', error)
+        daemonState = null
+    }
+    renderDaemonState()
+}
+
+function renderDaemonState() {
+    const state = daemonState || { running: false, projects: [] }
+    const behindBy = state.queued_changes + state.projects.reduce((sum, p) => sum + p.queue_depth, 0)
+
+    daemonStatusSpan.className = 'daemon-status'
+    if (!state.running) {
+        daemonStatusSpan.textContent = '● Sync off'
+        daemonStatusSpan.title = 'The Mothership daemon is not running'
+    } else if (state.protocol_error) {
+        daemonStatusSpan.classList.add('failed')
+        daemonStatusSpan.textContent = '● Sync error'
+        daemonStatusSpan.title = state.protocol_error
+    } else if (!state.server_connected || behindBy > 0) {
+        daemonStatusSpan.classList.add('behind')
+        daemonStatusSpan.textContent = state.server_connected ? `● Syncing (${behindBy} behind)` : '● Offline'
+        daemonStatusSpan.title = `${state.projects.length} project(s) tracked, ${behindBy} change(s) waiting`
+    } else {
+        daemonStatusSpan.classList.add('syncing')
+        daemonStatusSpan.textContent = '● Synced'
+        daemonStatusSpan.title = `${state.projects.length} project(s) tracked`
+    }
+
+    document.querySelectorAll('#project-list .project-item').forEach(element => {
+        const badge = element.querySelector('.sync-badge')
+        if (!badge) return
+        badge.innerHTML = ''
+        badge.className = 'sync-badge'
+
+        const project = state.projects.find(p => p.project_id === element.dataset.projectId)
+        if (!project) return
+
+        const label = document.createElement('span')
+        if (project.paused) {
+            label.textContent = '⏸ paused'
+        } else if (!project.connected) {
+            badge.classList.add('failed')
+            label.textContent = '● offline'
+        } else if (project.queue_depth > 0) {
+            badge.classList.add('behind')
+            label.textContent = `● ${project.queue_depth} behind`
+        } else {
+            badge.classList.add('syncing')
+            label.textContent = '● synced'
+        }
+        badge.title = `${project.project_path}${project.last_upload ? `\nLast upload: ${new Date(project.last_upload).toLocaleString()}` : ''}`
+
+        const toggle = document.createElement('button')
+        toggle.textContent = project.paused ? 'Resume' : 'Pause'
+        toggle.addEventListener('click', (event) => {
+            event.stopPropagation()
+            toggleProjectSync(project)
+        })
+        badge.append(label, toggle)
+    })
+}
+
+async function toggleProjectSync(project) {
+    try {
+        const command = project.paused ? 'daemon_resume_project' : 'daemon_pause_project'
+        await safeInvoke(command, { projectId: project.project_id })
+    } catch (error) {
+        console.error('Failed to change sync:', error)
+        alert(`Failed to ${project.paused ? 'resume' : 'pause'} sync: ${error}`)
+    }
+    await loadDaemonState()
+}
+
+// Rifts: the gateway's rifts with who is active in each. The user's current
+// rift is the one chat, terminals and co-editing work in.
+const RIFT_POLL_INTERVAL_MS = 30000
+
+async function loadRifts(gatewayProject) {
+    if (riftPollTimer) {
+        clearInterval(riftPollTimer)
+        riftPollTimer = null
+    }
+    riftDiff.classList.add('hidden')
+    riftList.innerHTML = '<div class="loading">Loading rifts...</div>'
+
+    const projectId = gatewayProject.project.id
+    let current = null
+    try {
+        current = await safeInvoke('current_rift', { projectId })
+    } catch (error) {
+        console.error('Failed to load current rift:', error)
+    }
+    if (gatewayProject !== currentProject) return
+
+    // Without a current rift, fall back to one the gateway lists
+    const fallback = gatewayProject.your_rifts[0] || gatewayProject.active_rifts[0]
+    currentRiftName = current ? current.name : fallback ? fallback.name : null
+    openRiftChat(current ? current.id : fallback ? fallback.id : null)
+
+    await refreshRifts()
+    riftPollTimer = setInterval(refreshRifts, RIFT_POLL_INTERVAL_MS)
+}
+
+async function refreshRifts() {
+    if (!currentProject) return
+    const project = currentProject
+    try {
+        const rifts = await safeInvoke('list_rifts', { projectId: project.project.id })
+        if (project !== currentProject) return
+        projectRifts = rifts || []
+        renderRifts()
+    } catch (error) {
+        console.error('Failed to load rifts:', error)
+        riftList.innerHTML = '<div class="loading">Failed to load rifts</div>'
+    }
+}
+
+function renderRifts() {
+    if (projectRifts.length === 0) {
+        riftList.innerHTML = '<div class="loading">No rifts yet</div>'
+        return
+    }
+
+    riftList.innerHTML = ''
+    projectRifts.forEach(rift => {
+        const item = document.createElement('div')
+        item.className = 'project-item rift-item'
+        if (rift.id === currentRiftId) item.classList.add('active')
+        item.title = `Created by ${rift.author} on ${new Date(rift.created_at).toLocaleDateString()}, ${rift.file_count} files`
+
+        const name = document.createElement('span')
+        name.className = 'rift-name'
+        name.textContent = `${rift.is_conflict_rift ? '⚠️' : '🌀'} ${rift.name}`
+        item.appendChild(name)
+
+        if (rift.id !== currentRiftId && currentRiftName) {
+            const compare = document.createElement('button')
+            compare.textContent = 'Compare'
+            compare.title = `Files that differ from ${currentRiftName}`
+            compare.addEventListener('click', (event) => {
+                event.stopPropagation()
+                compareRift(rift.name)
+            })
+            item.appendChild(compare)
+        }
+
+        if (rift.active_collaborators.length > 0) {
+            const presence = document.createElement('div')
+            presence.className = 'rift-presence'
+            presence.textContent = `● ${rift.active_collaborators.join(', ')}`
+            item.appendChild(presence)
+        }
+
+        item.addEventListener('click', () => switchToRift(rift))
+        riftList.appendChild(item)
+    })
+}
+
+async function switchToRift(rift) {
+    if (!currentProject || rift.id === currentRiftId) return
+
+    try {
+        await safeInvoke('switch_rift', { projectId: currentProject.project.id, riftName: rift.name })
+    } catch (error) {
+        console.error('Failed to switch rift:', error)
+        alert(`Failed to switch rift: ${error}`)
+        return
+    }
+
+    if (coeditPath) await stopCoediting()
+    await stopLiveEditing()
+    currentRiftName = rift.name
+    riftDiff.classList.add('hidden')
+    openRiftChat(rift.id)
+    renderRifts()
+}
+
+async function compareRift(riftName) {
+    riftDiff.classList.remove('hidden')
+    riftDiff.textContent = 'Comparing...'
+
+    let diffs
+    try {
+        diffs = await safeInvoke('diff_rifts', { projectId: currentProject.project.id, from: currentRiftName, to: riftName }) || []
+    } catch (error) {
+        riftDiff.textContent = `Failed to compare rifts: ${error}`
+        return
+    }
+
+    riftDiff.innerHTML = ''
+    const header = document.createElement('div')
+    header.textContent = diffs.length === 0
+        ? `${riftName} matches ${currentRiftName}`
+        : `${diffs.length} file(s) differ in ${riftName}:`
+    riftDiff.appendChild(header)
+    diffs.forEach(diff => {
+        const line = document.createElement('div')
+        line.textContent = `${diff.path} (${diff.change_count} line${diff.change_count === 1 ? '' : 's'})`
+        riftDiff.appendChild(line)
+    })
+}
+
+// Project search: the rift on the server, or the local copy without it
+async function runSearch(event) {
+    event.preventDefault()
+    const q = searchInput.value
+    if (!q.trim()) {
+        searchResults.classList.add('hidden')
+        return
+    }
+    if (!currentProject) {
+        searchResults.classList.remove('hidden')
+        searchResults.textContent = 'Select a gateway to search'
+        return
+    }
+
+    searchResults.classList.remove('hidden')
+    searchResults.textContent = 'Searching...'
+    const project = daemonState && daemonState.projects.find(p => p.project_id === currentProject.project.id)
+    try {
+        const results = await safeInvoke('search_project', {
+            riftId: currentRiftId,
+            projectPath: project ? project.project_path : null,
+            query: {
+                q,
+                regex: searchRegexBtn.classList.contains('active'),
+                case_sensitive: searchCaseBtn.classList.contains('active')
+            }
+        })
+        renderSearchResults(results)
+    } catch (error) {
+        searchResults.textContent = `Search failed: ${error}`
+    }
+}
+
+function renderSearchResults(results) {
+    searchResults.innerHTML = ''
+    if (!results) {
+        searchResults.textContent = 'Search requires the desktop app'
+        return
+    }
+
+    const summary = document.createElement('div')
+    summary.className = 'search-summary'
+    const more = results.truncated ? '+' : ''
+    const where = results.source === 'local' ? ' in the local copy' : ''
+    summary.textContent = `${results.matches.length}${more} match(es) in ${results.files_searched} file(s)${where}`
+    searchResults.appendChild(summary)
+
+    let lastPath = null
+    results.matches.forEach(match => {
+        if (match.path !== lastPath) {
+            const file = document.createElement('div')
+            file.className = 'search-file'
+            file.textContent = match.path
+            searchResults.appendChild(file)
+            lastPath = match.path
+        }
+
+        const item = document.createElement('div')
+        item.className = 'search-match'
+        const line = document.createElement('span')
+        line.className = 'search-line'
+        line.textContent = match.line
+        const preview = document.createElement('span')
+        preview.textContent = match.preview
+        item.append(line, preview)
+
+        if (match.local_path) {
+            item.addEventListener('click', () => openSearchMatch(match))
+        } else {
+            item.classList.add('remote-only')
+            item.title = 'Not in your local copy'
+        }
+        searchResults.appendChild(item)
+    })
+}
+
+async function openSearchMatch(match) {
+    if (currentFile !== match.local_path) {
+        await openFile(match.local_path)
+    }
+    editor.setPosition({ lineNumber: match.line, column: match.column })
+    editor.revealLineInCenter(match.line)
+    editor.focus()
+}
+
+async function handleNewRift() {
+    if (!currentProject) {
+        alert('Select a gateway first')
+        return
+    }
+
+    const name = prompt(`New rift name (starts from ${currentRiftName || 'the current rift'}):`)
+    if (!name || !name.trim()) return
+
+    try {
+        const riftId = await safeInvoke('create_rift', { projectId: currentProject.project.id, name: name.trim() })
+        await refreshRifts()
+        const rift = projectRifts.find(r => r.id === riftId)
+        if (rift) await switchToRift(rift)
+    } catch (error) {
+        console.error('Failed to create rift:', error)
+        alert(`Failed to create rift: ${error}`)
+    }
+}
+
+// Rift chat: the server has no push channel to the GUI yet, so poll for new messages
+const CHAT_POLL_INTERVAL_MS = 5000
+
+function openRiftChat(riftId) {
+    if (chatPollTimer) {
+        clearInterval(chatPollTimer)
+        chatPollTimer = null
+    }
+
+    currentRiftId = riftId
+    chatInput.disabled = !currentRiftId
+    chatSendBtn.disabled = !currentRiftId
+
+    if (!currentRiftId) {
+        chatMessages.innerHTML = '<div class="loading">No rift to chat in yet</div>'
+        return
+    }
+
+    if (featureEnabled('chat')) {
+        chatMessages.innerHTML = '<div class="loading">Loading chat...</div>'
+        loadChatHistory()
+        chatPollTimer = setInterval(loadChatHistory, CHAT_POLL_INTERVAL_MS)
+    }
+    watchRiftTerminals(currentRiftId)
+}
+
+async function loadChatHistory() {
+    if (!currentProject || !currentRiftId) return
+
+    try {
+        const messages = await safeInvoke('load_chat_history', {
+            projectId: currentProject.project.id,
+            riftId: currentRiftId,
+            before: null
+        })
+        renderChatMessages(messages)
+    } catch (error) {
+        console.error('Failed to load chat:', error)
+        chatMessages.innerHTML = `<div class="error">Failed to load chat: ${error}</div>`
+    }
+}
+
+function renderChatMessages(messages) {
+    if (messages.length === 0) {
+        chatMessages.innerHTML = '<div class="loading">No messages yet</div>'
+        return
+    }
+
+    const atBottom = chatMessages.scrollTop + chatMessages.clientHeight >= chatMessages.scrollHeight - 10
+    chatMessages.innerHTML = ''
+    messages.forEach(message => {
+        const messageElement = document.createElement('div')
+        messageElement.className = 'chat-message'
+
+        const author = document.createElement('span')
+        author.className = 'chat-message-author'
+        author.textContent = message.username
+
+        const time = document.createElement('span')
+        time.className = 'chat-message-time'
+        time.textContent = new Date(message.timestamp).toLocaleTimeString([], { hour: '2-digit', minute: '2-digit' })
+
+        const content = document.createElement('div')
+        content.textContent = message.content
+
+        messageElement.append(author, time, content)
+        chatMessages.appendChild(messageElement)
+    })
+
+    if (atBottom) {
+        chatMessages.scrollTop = chatMessages.scrollHeight
+    }
+}
+
+async function handleSendChat(event) {
+    event.preventDefault()
+
+    const content = chatInput.value.trim()
+    if (!content || !currentProject || !currentRiftId) return
+
+    try {
+        chatSendBtn.disabled = true
+        await safeInvoke('send_chat_message', {
+            projectId: currentProject.project.id,
+            riftId: currentRiftId,
+            content
+        })
+        chatInput.value = ''
+        await loadChatHistory()
+        chatMessages.scrollTop = chatMessages.scrollHeight
+    } catch (error) {
+        console.error('Failed to send message:', error)
+        alert(`Failed to send message: ${error}`)
+    } finally {
+        chatSendBtn.disabled = false
+        chatInput.focus()
+    }
+}
+
+// Shared terminals: someone runs 'mothership share-terminal' in the rift and
+// its output streams here, read-only. The server replays recent output on watch.
+const MAX_TERMINAL_OUTPUT = 20000
+
+async function watchRiftTerminals(riftId) {
+    if (terminalSocket) {
+        terminalSocket.close()
+        terminalSocket = null
+    }
+    terminalShares.clear()
+    renderTerminalShares()
+    if (!featureEnabled('terminal_sharing')) return
+
+    let url
+    try {
+        url = await safeInvoke('get_sync_socket_url', { riftId })
+    } catch (error) {
+        console.error('Failed to watch shared terminals:', error)
+        return
+    }
+    // No socket in browser mode, or the rift changed while asking
+    if (!url || riftId !== currentRiftId) return
+
+    const socket = new WebSocket(url)
+    terminalSocket = socket
+
+    socket.addEventListener('open', () => {
+        socket.send(JSON.stringify({ type: 'WatchTerminalShares', data: { rift_id: riftId } }))
+    })
+
+    socket.addEventListener('message', (event) => {
+        let message
+        try {
+            message = JSON.parse(event.data)
+        } catch (error) {
+            return
+        }
+        if (socket !== terminalSocket || !message.data) return
+
+        // The socket also carries the rift's presence announcements
+        if (message.type === 'CollaboratorJoined' || message.type === 'CollaboratorLeft') {
+            refreshRifts()
+            return
+        }
+
+        switch (message.type) {
+            case 'TerminalShares':
+                message.data.shares.forEach(share => terminalShares.set(share.id, { share, output: '', exitCode: undefined }))
+                break
+            case 'TerminalShareStarted':
+                terminalShares.set(message.data.share.id, { share: message.data.share, output: '', exitCode: undefined })
+                break
+            case 'TerminalOutput': {
+                const entry = terminalShares.get(message.data.share_id)
+                if (!entry) return
+                entry.output = (entry.output + stripAnsi(message.data.data)).slice(-MAX_TERMINAL_OUTPUT)
+                break
+            }
+            case 'TerminalShareEnded': {
+                const entry = terminalShares.get(message.data.share_id)
+                if (!entry) return
+                entry.exitCode = message.data.exit_code
+                break
+            }
+            default:
+                return
+        }
+        renderTerminalShares()
+    })
+
+    socket.addEventListener('close', () => {
+        if (socket === terminalSocket) {
+            terminalSocket = null
+        }
+    })
+}
+
+// Terminal output is shown as plain text, so drop colors and cursor movement
+function stripAnsi(text) {
+    return text
+        .replace(/\x1b\[[0-?]*[ -\/]*[@-~]/g, '')
+        .replace(/\x1b\][^\x07\x1b]*(\x07|\x1b\\)/g, '')
+        .replace(/\r(?!\n)/g, '')
+}
+
+function renderTerminalShares() {
+    if (terminalShares.size === 0) {
+        terminalList.innerHTML = '<div class="loading">No shared terminals</div>'
+        return
+    }
+
+    terminalList.innerHTML = ''
+    terminalShares.forEach(({ share, output, exitCode }) => {
+        const element = document.createElement('div')
+        element.className = 'terminal-share'
+
+        const title = document.createElement('div')
+        const status = exitCode === undefined ? 'live' : `exited${exitCode === null ? '' : ` (${exitCode})`}`
+        title.textContent = `🖥️ ${share.username}: ${share.command} — ${status}`
+
+        const pre = document.createElement('pre')
+        pre.className = 'terminal-output'
+        pre.textContent = output
+
+        element.append(title, pre)
+        terminalList.appendChild(element)
+        pre.scrollTop = pre.scrollHeight
+    })
+}
+
+// Diff viewer: working changes against the last checkpoint, with hunks that
+// can be staged into the next checkpoint, or what any checkpoint changed
+async function openDiffViewer() {
+    diffPath = currentFile
+    diffTitle.textContent = currentFile
+    diffSource.innerHTML = ''
+    diffSource.appendChild(new Option('Working changes', ''))
+    diffOverlay.classList.remove('hidden')
+
+    try {
+        const revisions = await safeInvoke('load_file_revisions', { path: diffPath }) || []
+        revisions.forEach(revision => {
+            const when = new Date(revision.timestamp).toLocaleString()
+            const label = `${revision.checkpoint_id.slice(0, 8)} ${revision.username}, ${when}: ${revision.message || '(no message)'}`
+            diffSource.appendChild(new Option(label, revision.checkpoint_id))
+        })
+    } catch (error) {
+        console.error('Failed to load file history:', error)
+    }
+
+    await loadDiff()
+}
+
+function hideDiffViewer() {
+    diffOverlay.classList.add('hidden')
+    diffPath = null
+    diffView = null
+}
+
+async function loadDiff() {
+    const checkpointId = diffSource.value
+    diffContent.innerHTML = '<div class="loading">Loading diff...</div>'
+    try {
+        diffView = checkpointId
+            ? await safeInvoke('load_checkpoint_diff', { path: diffPath, checkpointId })
+            : await safeInvoke('load_working_diff', { path: diffPath })
+    } catch (error) {
+        diffView = null
+        diffContent.innerHTML = ''
+        diffContent.appendChild(Object.assign(document.createElement('div'), {
+            className: 'loading',
+            textContent: `Failed to load diff: ${error}`,
+        }))
+    }
+    diffStageForm.classList.toggle('hidden', Boolean(checkpointId))
+    renderDiff()
+}
+
+function renderDiff() {
+    if (!diffView) return
+    diffTitle.textContent = `${diffView.path}: ${diffView.old_label} → ${diffView.new_label}`
+    diffLayoutBtn.textContent = diffInline ? 'Side by side' : 'Inline'
+    diffContent.innerHTML = ''
+
+    if (diffView.hunks.length === 0) {
+        diffContent.innerHTML = '<div class="loading">No changes</div>'
+        return
+    }
+
+    const staging = !diffSource.value
+    diffView.hunks.forEach((hunk, index) => {
+        const element = document.createElement('div')
+        element.className = 'diff-hunk'
+
+        const header = document.createElement('div')
+        header.className = 'diff-hunk-header'
+        if (staging) {
+            const checkbox = document.createElement('input')
+            checkbox.type = 'checkbox'
+            checkbox.title = 'Stage this hunk'
+            checkbox.checked = diffView.staged.includes(index)
+            checkbox.addEventListener('change', () => stageHunk(index, checkbox))
+            header.appendChild(checkbox)
+        }
+        header.appendChild(document.createTextNode(
+            `@@ -${hunk.old_start},${hunk.old_lines} +${hunk.new_start},${hunk.new_lines} @@`))
+
+        const table = document.createElement('table')
+        const rows = diffInline ? inlineRows(hunk) : sideBySideRows(hunk)
+        rows.forEach(cells => {
+            const row = table.insertRow()
+            cells.forEach(({ text, kind, number }) => {
+                const cell = row.insertCell()
+                cell.textContent = text ?? ''
+                if (number) cell.className = 'diff-line-number'
+                else if (kind) cell.className = `diff-line-${kind}`
+            })
+        })
+
+        element.append(header, table)
+        diffContent.appendChild(element)
+    })
+}
+
+// Rows of [old number, new number, line] cells
+function inlineRows(hunk) {
+    let oldLine = hunk.old_start
+    let newLine = hunk.new_start
+    return hunk.lines.map(({ kind, text }) => {
+        const prefix = { context: ' ', added: '+', removed: '-' }[kind]
+        const oldNumber = kind === 'added' ? '' : oldLine++
+        const newNumber = kind === 'removed' ? '' : newLine++
+        return [
+            { text: oldNumber, number: true },
+            { text: newNumber, number: true },
+            { text: prefix + text, kind: kind === 'context' ? null : kind },
+        ]
+    })
+}
+
+// Rows of [old number, old line, new number, new line] cells, pairing each
+// run of removed lines with the added lines that replace it
+function sideBySideRows(hunk) {
+    const rows = []
+    let oldLine = hunk.old_start
+    let newLine = hunk.new_start
+    let removed = []
+    let added = []
+
+    const flush = () => {
+        for (let i = 0; i < Math.max(removed.length, added.length); i++) {
+            const left = i < removed.length
+                ? [{ text: oldLine++, number: true }, { text: removed[i], kind: 'removed' }]
+                : [{ number: true }, {}]
+            const right = i < added.length
+                ? [{ text: newLine++, number: true }, { text: added[i], kind: 'added' }]
+                : [{ number: true }, {}]
+            rows.push([...left, ...right])
+        }
+        removed = []
+        added = []
+    }
+
+    hunk.lines.forEach(({ kind, text }) => {
+        if (kind === 'removed') {
+            removed.push(text)
+        } else if (kind === 'added') {
+            added.push(text)
+        } else {
+            flush()
+            rows.push([
+                { text: oldLine++, number: true }, { text },
+                { text: newLine++, number: true }, { text },
+            ])
+        }
+    })
+    flush()
+    return rows
+}
+
+async function stageHunk(index, checkbox) {
+    try {
+        diffView.staged = await safeInvoke('stage_hunk', { path: diffPath, hunk: index, staged: checkbox.checked })
+    } catch (error) {
+        console.error('Failed to stage hunk:', error)
+        checkbox.checked = !checkbox.checked
+        alert(`Failed to stage hunk: ${error}`)
+    }
+}
+
+async function handleStagedCheckpoint(event) {
+    event.preventDefault()
+    if (!diffView) return
+
+    try {
+        diffStageBtn.disabled = true
+        const message = diffStageMessage.value.trim() || 'Staged changes'
+        const files = await safeInvoke('create_staged_checkpoint', { projectId: diffView.project_id, message })
+        diffStageMessage.value = ''
+        console.log(`Checkpointed staged hunks of ${files} file(s)`)
+        await loadDiff()
+    } catch (error) {
+        console.error('Staged checkpoint failed:', error)
+        alert(`Staged checkpoint failed: ${error}`)
+    } finally {
+        diffStageBtn.disabled = false
+    }
+}
+
+// Co-editing: the server keeps a character-level CRDT of the file and relays
+// operations to every editor; the local replica lives on the Rust side
+async function startCoediting(path) {
+    await stopCoediting()
+    await stopLiveEditing()
+
+    const url = await safeInvoke('get_sync_socket_url', { riftId: currentRiftId })
+    if (!url) {
+        throw new Error('Co-editing requires the desktop app')
+    }
+
+    const riftId = currentRiftId
+    const socket = new WebSocket(url)
+    coeditSocket = socket
+    coeditPath = path
+
+    socket.addEventListener('open', () => {
+        socket.send(JSON.stringify({ type: 'JoinTextSession', data: { rift_id: riftId, path } }))
+    })
+
+    socket.addEventListener('message', async (event) => {
+        let message
+        try {
+            message = JSON.parse(event.data)
+        } catch (error) {
+            return
+        }
+        if (socket !== coeditSocket || !message.data || message.data.path !== path) return
+
+        try {
+            if (message.type === 'TextSnapshot' && !coeditSiteId) {
+                const session = await safeInvoke('coedit_open', { path, document: message.data.document })
+                coeditSiteId = session.site_id
+                showCoeditDocument(path, session.text)
+            } else if (message.type === 'TextOperations' && coeditSiteId && message.data.site_id !== coeditSiteId) {
+                // Fold in any local typing first so the merged text keeps it
+                await sendCoeditChange()
+                const text = await safeInvoke('coedit_apply_remote', { path, operations: message.data.operations })
+                applyRemoteText(text)
+            }
+        } catch (error) {
+            console.error('Co-editing error:', error)
+        }
+    })
+
+    socket.addEventListener('close', () => {
+        if (socket === coeditSocket) {
+            currentFileSpan.textContent = `${path} (disconnected)`
+        }
+    })
+}
+
+async function stopCoediting() {
+    if (!coeditSocket) return
+
+    const path = coeditPath
+    coeditSocket.close()
+    coeditSocket = null
+    coeditPath = null
+    coeditSiteId = null
+    await safeInvoke('coedit_close', { path })
+}
+
+function showCoeditDocument(path, text) {
+    applyingRemoteEdit = true
+    editor.setValue(text)
+    applyingRemoteEdit = false
+    monaco.editor.setModelLanguage(editor.getModel(), detectLanguage(path))
+
+    // Not a local file - keep auto-save away from it
+    currentFile = null
+    const fileName = path.split(/[/\\]/).pop()
+    currentFileSpan.textContent = `${path} (co-editing)`
+    editorTabs.innerHTML = `
+        <div class="editor-tab active">
+            <span>✍️ ${fileName}</span>
+        </div>
+    `
+    editor.focus()
+}
+
+async function sendCoeditChange() {
+    if (!coeditSiteId || !coeditSocket || coeditSocket.readyState !== WebSocket.OPEN) return
+
+    const operations = await safeInvoke('coedit_local_edit', { path: coeditPath, text: editor.getValue() })
+    if (operations && operations.length > 0) {
+        coeditSocket.send(JSON.stringify({
+            type: 'TextOperations',
+            data: { rift_id: currentRiftId, path: coeditPath, site_id: coeditSiteId, operations }
+        }))
+    }
+}
+
+// Replace only the changed span so the local cursor and selection stay put
+function applyRemoteText(text) {
+    const model = editor.getModel()
+    const current = model.getValue()
+    if (current === text) return
+
+    let prefix = 0
+    while (prefix < current.length && prefix < text.length && current[prefix] === text[prefix]) {
+        prefix++
+    }
+    let suffix = 0
+    while (suffix < current.length - prefix && suffix < text.length - prefix &&
+           current[current.length - 1 - suffix] === text[text.length - 1 - suffix]) {
+        suffix++
+    }
+
+    const start = model.getPositionAt(prefix)
+    const end = model.getPositionAt(current.length - suffix)
+    applyingRemoteEdit = true
+    editor.executeEdits('coedit', [{
+        range: new monaco.Range(start.lineNumber, start.column, end.lineNumber, end.column),
+        text: text.substring(prefix, text.length - suffix)
+    }])
+    applyingRemoteEdit = false
+}
+
+// Live editing: a project file open in the editor follows its rift. Remote
+// changes merge into the buffer as they arrive, local typing goes out as
+// diffs once it pauses, and collaborators' cursors show where they are.
+async function startLiveEditing(localPath) {
+    await stopLiveEditing()
+
+    let session
+    try {
+        session = await safeInvoke('live_open', { path: localPath })
+    } catch (error) {
+        // Not a beamed project file, or signed out: plain local editing
+        return
+    }
+    // No socket in browser mode, or another file opened while asking
+    if (!session || localPath !== currentFile) return
+
+    const socket = new WebSocket(session.socket_url)
+    liveSocket = socket
+    liveSession = { ...session, localPath }
+
+    socket.addEventListener('open', () => {
+        socket.send(JSON.stringify({
+            type: 'JoinRift',
+            data: { rift_id: session.rift_id, last_checkpoint: null, sparse_paths: [session.path] }
+        }))
+        socket.send(JSON.stringify({ type: 'WatchCursors', data: { rift_id: session.rift_id } }))
+        socket.send(JSON.stringify({ type: 'WatchFileActivity', data: { rift_id: session.rift_id } }))
+        socket.send(JSON.stringify({ type: 'FileOpened', data: { rift_id: session.rift_id, path: session.path, open: true } }))
+    })
+
+    socket.addEventListener('message', async (event) => {
+        let message
+        try {
+            message = JSON.parse(event.data)
+        } catch (error) {
+            return
+        }
+        if (socket !== liveSocket || !message.data) return
+
+        try {
+            await handleLiveMessage(message)
+        } catch (error) {
+            // Out of step with the server: rejoin for a fresh copy of the file
+            console.error('Live editing error:', error)
+            if (socket === liveSocket) {
+                setTimeout(() => {
+                    if (socket === liveSocket) startLiveEditing(localPath)
+                }, 1000)
+            }
+        }
+    })
+
+    socket.addEventListener('close', () => {
+        if (socket === liveSocket) {
+            currentFileSpan.textContent = `${localPath.split(/[/\\]/).pop()} (offline)`
+        }
+    })
+}
+
+async function stopLiveEditing() {
+    clearTimeout(liveEditTimer)
+    clearTimeout(cursorUpdateTimer)
+    remoteCursors.clear()
+    renderRemoteCursors()
+    fileActivity.clear()
+    renderEditingWarning()
+    if (!liveSocket) return
+
+    const socket = liveSocket
+    const session = liveSession
+    liveSocket = null
+    liveSession = null
+    if (socket.readyState === WebSocket.OPEN) {
+        // Take our cursor out of the file for everyone else
+        socket.send(JSON.stringify({
+            type: 'UpdateCursor',
+            data: { rift_id: session.rift_id, path: null, line: 1, column: 1 }
+        }))
+        socket.send(JSON.stringify({
+            type: 'FileOpened',
+            data: { rift_id: session.rift_id, path: session.path, open: false }
+        }))
+    }
+    socket.close()
+    await safeInvoke('live_close', { path: session.localPath })
+}
+
+async function handleLiveMessage(message) {
+    const { localPath, path } = liveSession
+    const data = message.data
+
+    switch (message.type) {
+        case 'RiftJoined':
+            applyLiveUpdate(await safeInvoke('live_remote_content', {
+                path: localPath, content: data.current_files[path] ?? null, text: editor.getValue()
+            }))
+            break
+        case 'RiftDiffUpdate':
+            for (const change of data.diff_changes) {
+                if (change.path === path) await applyLiveDiff(change.diff)
+            }
+            break
+        case 'FileDiffUpdate':
+            if (data.path === path) await applyLiveDiff(data.diff)
+            break
+        case 'FileUpdate':
+        case 'ConflictDetected':
+            if (data.path !== path) break
+            applyLiveUpdate(await safeInvoke('live_remote_content', {
+                path: localPath, content: data.content ?? data.server_content, text: editor.getValue()
+            }))
+            break
+        case 'DeltaRejected':
+            // The server lost track of our base; send the whole file instead
+            if (data.path === path) {
+                sendLiveDiff(await safeInvoke('live_resend', { path: localPath, text: editor.getValue() }))
+            }
+            break
+        case 'Cursors':
+            remoteCursors = new Map(data.cursors.map(cursor => [cursor.user_id, cursor]))
+            renderRemoteCursors()
+            break
+        case 'CursorMoved':
+            if (data.cursor.path) {
+                remoteCursors.set(data.cursor.user_id, data.cursor)
+            } else {
+                remoteCursors.delete(data.cursor.user_id)
+            }
+            renderRemoteCursors()
+            break
+        case 'CheckpointCreated':
+            // The checkpoint may have taken in our edits; report the next one again
+            liveSession.dirty = false
+            break
+        case 'FileActivities':
+            fileActivity = new Map(data.activity.map(activity => [fileActivityKey(activity), activity]))
+            renderEditingWarning()
+            break
+        case 'FileActivityChanged':
+            if (data.activity.open || data.activity.dirty) {
+                fileActivity.set(fileActivityKey(data.activity), data.activity)
+            } else {
+                fileActivity.delete(fileActivityKey(data.activity))
+            }
+            renderEditingWarning()
+            break
+        case 'CollaboratorLeft':
+            remoteCursors.delete(data.user_id)
+            renderRemoteCursors()
+            for (const [key, activity] of fileActivity) {
+                if (activity.user_id === data.user_id) fileActivity.delete(key)
+            }
+            renderEditingWarning()
+            break
+    }
+}
+
+async function applyLiveDiff(diff) {
+    if (diff === 'Deleted') {
+        const { localPath } = liveSession
+        await stopLiveEditing()
+        currentFileSpan.textContent = `${localPath.split(/[/\\]/).pop()} (deleted in rift)`
+        return
+    }
+    applyLiveUpdate(await safeInvoke('live_apply_remote', {
+        path: liveSession.localPath, diff, text: editor.getValue()
+    }))
+}
+
+function applyLiveUpdate(update) {
+    if (update.text !== null && update.text !== undefined) {
+        applyRemoteText(update.text)
+    }
+    if (update.conflicted) {
+        currentFileSpan.textContent = `${liveSession.localPath.split(/[/\\]/).pop()} (conflicting edits - resolve the markers)`
+    }
+    renderRemoteCursors()
+}
+
+// The daemon uploads saved changes of the projects it syncs; sending them
+// from the editor too would apply them twice
+function daemonSyncsFile(localPath) {
+    return !!daemonState && daemonState.running &&
+        daemonState.projects.some(project => !project.paused && localPath.startsWith(project.project_path))
+}
+
+async function sendLiveEdit() {
+    if (!liveSession || !liveSocket || liveSocket.readyState !== WebSocket.OPEN) return
+    // Collaborators hear about the change even while it's only in the editor
+    if (!liveSession.dirty) {
+        liveSession.dirty = true
+        liveSocket.send(JSON.stringify({
+            type: 'FileDirty',
+            data: { rift_id: liveSession.rift_id, paths: [liveSession.path], dirty: true }
+        }))
+    }
+    if (daemonSyncsFile(liveSession.localPath)) return
+
+    const edit = await safeInvoke('live_local_edit', { path: liveSession.localPath, text: editor.getValue() })
+    if (edit) sendLiveDiff(edit)
+}
+
+function sendLiveDiff(edit) {
+    if (!liveSession || !liveSocket || liveSocket.readyState !== WebSocket.OPEN) return
+    liveSocket.send(JSON.stringify({
+        type: 'FileDiffChanged',
+        data: {
+            rift_id: liveSession.rift_id,
+            path: liveSession.path,
+            diff: edit.diff,
+            file_size: edit.file_size,
+            timestamp: new Date().toISOString()
+        }
+    }))
+}
+
+function scheduleCursorUpdate() {
+    if (!liveSession) return
+    clearTimeout(cursorUpdateTimer)
+    cursorUpdateTimer = setTimeout(() => {
+        const position = editor.getPosition()
+        if (!liveSession || !position || !liveSocket || liveSocket.readyState !== WebSocket.OPEN) return
+        liveSocket.send(JSON.stringify({
+            type: 'UpdateCursor',
+            data: { rift_id: liveSession.rift_id, path: liveSession.path, line: position.lineNumber, column: position.column }
+        }))
+    }, CURSOR_UPDATE_DELAY_MS)
+}
+
+function fileActivityKey(activity) {
+    return `${activity.user_id}:${activity.path}`
+}
+
+// Warn above the editor when collaborators are changing the open file too
+function renderEditingWarning() {
+    const editing = liveSession
+        ? [...fileActivity.values()].filter(activity => activity.path === liveSession.path)
+        : []
+    const changing = editing.filter(activity => activity.dirty).map(activity => activity.username)
+    const viewing = editing.filter(activity => !activity.dirty).map(activity => activity.username)
+
+    if (changing.length > 0) {
+        editingWarning.textContent = `⚠️ ${changing.join(', ')} ${changing.length === 1 ? 'is' : 'are'} also editing this file`
+    } else if (viewing.length > 0) {
+        editingWarning.textContent = `${viewing.join(', ')} also ${viewing.length === 1 ? 'has' : 'have'} this file open`
+    } else {
+        editingWarning.textContent = ''
+    }
+    editingWarning.hidden = editingWarning.textContent === ''
+}
+
+// Each collaborator keeps one of a few cursor colors
+function cursorColor(userId) {
+    let hash = 0
+    for (const char of userId) {
+        hash = (hash * 31 + char.charCodeAt(0)) >>> 0
+    }
+    return hash % 4
+}
+
+function renderRemoteCursors() {
+    if (!editor) return
+    const model = editor.getModel()
+    const decorations = []
+    if (liveSession) {
+        remoteCursors.forEach(cursor => {
+            if (cursor.path !== liveSession.path) return
+            const line = Math.min(cursor.line, model.getLineCount())
+            const column = Math.min(cursor.column, model.getLineMaxColumn(line))
+            decorations.push({
+                range: new monaco.Range(line, column, line, column),
+                options: {
+                    beforeContentClassName: `remote-cursor remote-cursor-${cursorColor(cursor.user_id)}`,
+                    hoverMessage: { value: `**${cursor.username}**` },
+                    stickiness: monaco.editor.TrackedRangeStickiness.NeverGrowsWhenTypingAtEdges
+                }
+            })
+        })
+    }
+    cursorDecorations = editor.deltaDecorations(cursorDecorations, decorations)
 }
 
 // Load files from a directory
@@ -403,6 +1874,9 @@ async function loadDirectoryFiles(dirPath) {
 // Open a file in the editor
 async function openFile(filePath) {
     try {
+        await stopCoediting()
+        await stopLiveEditing()
+        
         const content = await safeInvoke('read_file_content', { path: filePath })
         
         // Set file content in editor
@@ -415,6 +1889,7 @@ async function openFile(filePath) {
         // Update current file
         currentFile = filePath
         await safeInvoke('set_current_file', { path: filePath })
+        showComments()
         
         // Update UI
         const fileName = filePath.split(/[/\\]/).pop()
@@ -441,6 +1916,8 @@ async function openFile(filePath) {
         
         // Focus editor
         editor.focus()
+
+        startLiveEditing(filePath)
         
     } catch (error) {
         console.error('Failed to open file:', error)
@@ -840,24 +2317,160 @@ async function handleEmailLogin(event) {
 
 async function handleLogout() {
     try {
+        await stopLiveEditing()
         await safeInvoke('logout')
         isAuthenticated = false
         updateAuthUI()
-        
-        // Clear gateways and files
-        projects = []
-        currentProject = null
-        currentFile = null
-        projectList.innerHTML = '<div class="loading">No gateways loaded</div>'
-        fileExplorer.innerHTML = '<div class="loading">Select a gateway</div>'
-        editor.setValue('// Welcome to Mothership\n// Sign in to access your gateways')
-        currentFileSpan.textContent = 'No file selected'
-        
+        resetWorkspace()
+        loadServers()
     } catch (error) {
         console.error('Logout failed:', error)
     }
 }
 
+// Clear gateways and files
+function resetWorkspace() {
+    projects = []
+    currentProject = null
+    currentFile = null
+    projectList.innerHTML = '<div class="loading">No gateways loaded</div>'
+    fileExplorer.innerHTML = '<div class="loading">Select a gateway</div>'
+    searchResults.innerHTML = ''
+    searchResults.classList.add('hidden')
+    clearInterval(riftPollTimer)
+    riftPollTimer = null
+    projectRifts = []
+    riftList.innerHTML = '<div class="loading">Select a gateway</div>'
+    riftDiff.classList.add('hidden')
+    applyFeatures([])
+    editor.setValue('// Welcome to Mothership\n// Sign in to access your gateways')
+    currentFileSpan.textContent = 'No file selected'
+}
+
+// Servers: which one the app talks to, each with its own sign-in
+async function loadServers() {
+    try {
+        renderServers(await safeInvoke('list_servers'))
+    } catch (error) {
+        console.error('Failed to load servers:', error)
+    }
+}
+
+function renderServers(servers) {
+    if (!servers) return
+    const active = servers.find(server => server.active)
+    const label = active ? active.name : 'No server'
+    serversBtn.textContent = `🌐 ${label}`
+    authServerName.textContent = active ? `${active.name} (${active.url})` : label
+
+    serverList.innerHTML = ''
+    servers.forEach(server => {
+        const item = document.createElement('div')
+        item.className = server.active ? 'server-item active' : 'server-item'
+
+        const details = document.createElement('div')
+        details.className = 'server-details'
+        const name = document.createElement('div')
+        name.className = 'server-name'
+        name.textContent = server.name
+        const url = document.createElement('div')
+        url.className = 'server-url'
+        url.textContent = server.url
+        details.append(name, url)
+
+        const status = document.createElement('span')
+        status.className = server.signed_in ? 'server-status signed-in' : 'server-status'
+        status.textContent = server.signed_in ? 'Signed in' : 'Signed out'
+        item.append(details, status)
+
+        if (server.active) {
+            const current = document.createElement('span')
+            current.className = 'server-status'
+            current.textContent = 'Active'
+            item.appendChild(current)
+        } else {
+            const use = document.createElement('button')
+            use.type = 'button'
+            use.textContent = 'Use'
+            use.addEventListener('click', () => switchServer(server.url))
+            const remove = document.createElement('button')
+            remove.type = 'button'
+            remove.textContent = 'Remove'
+            remove.addEventListener('click', () => removeServer(server))
+            item.append(use, remove)
+        }
+        serverList.appendChild(item)
+    })
+}
+
+function showServersMessage(message, type = 'error') {
+    serversMessage.innerHTML = `<div class="gateway-${type}">${message}</div>`
+    setTimeout(() => {
+        serversMessage.innerHTML = ''
+    }, 5000)
+}
+
+function showServers() {
+    serversOverlay.classList.remove('hidden')
+    loadServers()
+    serverUrlInput.focus()
+}
+
+function hideServers() {
+    serversOverlay.classList.add('hidden')
+    addServerForm.reset()
+    serversMessage.innerHTML = ''
+}
+
+async function handleAddServer(event) {
+    event.preventDefault()
+    const addServerBtn = document.getElementById('add-server-btn')
+    addServerBtn.disabled = true
+    addServerBtn.textContent = 'Checking...'
+    try {
+        renderServers(await safeInvoke('add_server', {
+            url: serverUrlInput.value,
+            name: serverNameInput.value.trim() || null
+        }))
+        addServerForm.reset()
+        showServersMessage('Server added', 'success')
+    } catch (error) {
+        showServersMessage(`${error}`)
+    } finally {
+        addServerBtn.disabled = false
+        addServerBtn.textContent = 'Add'
+    }
+}
+
+async function switchServer(url) {
+    try {
+        await stopLiveEditing()
+        const signedIn = await safeInvoke('switch_server', { url })
+        resetWorkspace()
+        isAuthenticated = Boolean(signedIn)
+        updateAuthUI()
+        await loadServers()
+        if (isAuthenticated) {
+            hideServers()
+            await loadProjects()
+            checkForUpdate()
+        } else {
+            showServersMessage('Switched. Sign in to use this server.', 'success')
+        }
+    } catch (error) {
+        showServersMessage(`Failed to switch server: ${error}`)
+    }
+}
+
+async function removeServer(server) {
+    if (!confirm(`Remove ${server.name} (${server.url})? Its sign-in is forgotten too.`)) return
+    try {
+        renderServers(await safeInvoke('remove_server', { url: server.url }))
+    } catch (error) {
+        showServersMessage(`${error}`)
+    }
+}
+
 // Format file size
 function formatFileSize(bytes) {
     if (bytes === 0) return '0 B'
@@ -911,6 +2524,51 @@ createAccountLink.addEventListener('click', (e) => {
     showAuthMessage('Account creation is not yet implemented. Please use Google login.', 'error')
 })
 
+chatForm.addEventListener('submit', handleSendChat)
+
+newRiftBtn.addEventListener('click', handleNewRift)
+
+coeditBtn.addEventListener('click', async () => {
+    if (!currentProject || !currentRiftId) {
+        alert('Select a gateway with a rift to co-edit')
+        return
+    }
+
+    const path = prompt('File to co-edit (path within the rift):')
+    if (!path) return
+
+    try {
+        await startCoediting(path.trim())
+    } catch (error) {
+        console.error('Failed to start co-editing:', error)
+        alert(`Failed to start co-editing: ${error}`)
+    }
+})
+
+diffBtn.addEventListener('click', async () => {
+    if (!currentFile) {
+        alert('No file is currently open')
+        return
+    }
+
+    // The diff is of what's on disk
+    if (editor) {
+        await safeInvoke('write_file_content', { path: currentFile, content: editor.getValue() })
+    }
+    await openDiffViewer()
+})
+
+diffSource.addEventListener('change', loadDiff)
+
+diffLayoutBtn.addEventListener('click', () => {
+    diffInline = !diffInline
+    renderDiff()
+})
+
+diffCloseBtn.addEventListener('click', hideDiffViewer)
+
+diffStageForm.addEventListener('submit', handleStagedCheckpoint)
+
 refreshBtn.addEventListener('click', () => {
     loadProjects()
     // Note: File browsing not yet implemented for projects
@@ -968,6 +2626,8 @@ function showGatewayModal() {
 }
 
 function hideGatewayModal() {
+    // Creation under way: the modal stays until it finishes
+    if (cancelGatewayBtn.disabled) return
     gatewayOverlay.classList.add('hidden')
     clearGatewayForm()
 }
@@ -975,6 +2635,10 @@ function hideGatewayModal() {
 function clearGatewayForm() {
     gatewayForm.reset()
     gatewayMessage.innerHTML = ''
+    wizardScan = null
+    cancelGatewayBtn.textContent = 'Cancel'
+    setUploadProgress('', 0, '')
+    showWizardStep(1)
 }
 
 function showGatewayMessage(message, type = 'error') {
@@ -1056,75 +2720,222 @@ async function browseForDirectory() {
 
 async function handleCreateGateway(event) {
     event.preventDefault()
-    
+
+    if (wizardStep === 1) {
+        await showSyncRules()
+    } else if (wizardStep === 2) {
+        await createGateway()
+    }
+}
+
+// Wizard step 2: suggest ignore patterns and file types for the directory
+async function showSyncRules() {
     const name = gatewayNameInput.value.trim()
-    const description = gatewayDescriptionInput.value.trim()
     const projectPath = gatewayPathInput.value.trim()
-    
+
     if (!name || !projectPath) {
         showGatewayMessage('Please fill in all required fields', 'error')
         return
     }
-    
+
+    let scan
     try {
         createGatewayBtn.disabled = true
-        createGatewayBtn.textContent = 'Creating...'
-        
-        showGatewayMessage('Creating gateway...', 'success')
-        
-        // Create the gateway via Tauri command
-        const newProject = await safeInvoke('create_gateway', {
+        scan = await safeInvoke('scan_project_directory', { path: projectPath })
+    } catch (error) {
+        showGatewayMessage(`Can't use this directory: ${error}`, 'error')
+        return
+    } finally {
+        createGatewayBtn.disabled = false
+    }
+    if (scan.existing_gateway) {
+        showGatewayMessage(`This directory is already inside a gateway at ${scan.existing_gateway}`, 'error')
+        return
+    }
+
+    wizardScan = scan
+    const kinds = scan.kinds.map(kind => PROJECT_KIND_NAMES[kind] || kind)
+    wizardDetected.textContent = kinds.length > 0
+        ? `Detected a ${kinds.join(' + ')} project.`
+        : 'No project type detected; starting from general patterns.'
+    if (scan.has_ignore_file) {
+        wizardDetected.textContent += ' Using its existing .mothershipignore.'
+    }
+    gatewayIgnoreInput.value = scan.ignore_file
+    gatewayFileTypesInput.value = scan.allowed_file_types.join(', ')
+    gatewayRestrictTypesInput.checked = false
+    showWizardStep(2)
+}
+
+// Wizard step 3: create the gateway, apply its rules and upload its files
+async function createGateway() {
+    const name = gatewayNameInput.value.trim()
+    const description = gatewayDescriptionInput.value.trim()
+    const projectPath = gatewayPathInput.value.trim()
+    const ignoreFile = gatewayIgnoreInput.value
+    const allowedFileTypes = gatewayRestrictTypesInput.checked
+        ? gatewayFileTypesInput.value.split(',').map(pattern => pattern.trim()).filter(pattern => pattern)
+        : null
+    const checkpointInterval = parseInt(gatewayCheckpointIntervalSelect.value, 10)
+
+    showWizardStep(3)
+    cancelGatewayBtn.disabled = true
+    setUploadProgress('Creating gateway...', 0, '')
+
+    let newProject
+    try {
+        // The ignore file goes first so the upload already honours it
+        if (!wizardScan.has_ignore_file || ignoreFile !== wizardScan.ignore_file) {
+            await safeInvoke('write_ignore_file', { projectPath, contents: ignoreFile })
+        }
+
+        newProject = await safeInvoke('create_gateway', {
             request: {
                 name,
                 description: description || `Gateway for ${name}`,
                 project_path: projectPath
             }
         })
-        
         console.log('✅ Gateway created:', newProject)
-        showGatewayMessage('Gateway created successfully! Opening...', 'success')
-        
-        // Refresh the project list and auto-select the new gateway
-        setTimeout(async () => {
-            hideGatewayModal()
-            try {
-                await loadProjects()
-                
-                // Find and select the newly created gateway
-                const gatewayProject = projects.find(gp => gp.project.id === newProject.id)
-                if (gatewayProject) {
-                    console.log('🎯 Auto-selecting newly created gateway:', gatewayProject.project.name)
-                    
-                    // Find the corresponding project element and trigger click
-                    const projectElements = document.querySelectorAll('.project-item')
-                    projectElements.forEach((element, index) => {
-                        if (projects[index] && projects[index].project.id === newProject.id) {
-                            element.click()
-                            element.scrollIntoView({ behavior: 'smooth', block: 'center' })
-                            
-                            // Add a brief highlight effect
-                            element.style.border = '2px solid #007acc'
-                            element.style.background = 'rgba(0, 122, 204, 0.1)'
-                            setTimeout(() => {
-                                element.style.border = ''
-                                element.style.background = ''
-                            }, 3000)
-                        }
-                    })
-                } else {
-                    console.warn('⚠️ Could not find newly created gateway in projects list')
-                }
-            } catch (error) {
-                console.error('Failed to refresh projects:', error)
-            }
-        }, 1000)
-        
     } catch (error) {
         console.error('Gateway creation failed:', error)
+        setUploadProgress('Gateway creation failed', 0, String(error))
         showGatewayMessage(`Failed to create gateway: ${error}`, 'error')
-    } finally {
-        createGatewayBtn.disabled = false
-        createGatewayBtn.textContent = 'Create Gateway'
+        cancelGatewayBtn.disabled = false
+        wizardBackBtn.classList.remove('hidden')
+        return
+    }
+
+    const warnings = []
+    try {
+        await safeInvoke('apply_gateway_settings', {
+            projectId: newProject.id,
+            autoCheckpointInterval: checkpointInterval,
+            allowedFileTypes
+        })
+    } catch (error) {
+        console.error('Failed to apply gateway settings:', error)
+        warnings.push(`settings weren't saved (${error})`)
+    }
+
+    let uploadsEnabled = true
+    try {
+        const disabled = await safeInvoke('project_features', { projectId: newProject.id }) || []
+        uploadsEnabled = !disabled.includes('file_uploads')
+    } catch (error) {
+        console.error('Failed to load feature flags:', error)
+    }
+
+    if (uploadsEnabled) {
+        setUploadProgress('Uploading files...', 0, 'Scanning project...')
+        const progressTimer = setInterval(pollUploadProgress, UPLOAD_POLL_INTERVAL_MS)
+        try {
+            const result = await safeInvoke('upload_gateway_files', { projectId: newProject.id, projectPath })
+            const skipped = result.skipped.length > 0 ? `, ${result.skipped.length} skipped` : ''
+            setUploadProgress('Upload complete', 100, `${result.files_stored} file(s) uploaded${skipped}`)
+            if (result.rejected?.length > 0) {
+                const refused = result.rejected.map(violation => violation.message)
+                warnings.push(`the server refused ${refused.length} file(s): ${refused.join('; ')}`)
+            }
+        } catch (error) {
+            console.error('Initial upload failed:', error)
+            warnings.push(`the upload stopped (${error}); background sync will send the files`)
+        } finally {
+            clearInterval(progressTimer)
+        }
+    } else {
+        setUploadProgress('Upload skipped', 100, 'File uploads are turned off on this server; background sync will send the files')
+    }
+
+    // Keep the new gateway in sync in the background from now on
+    setUploadProgress('Starting background sync...', 100, uploadDetail.textContent)
+    try {
+        await safeInvoke('enroll_gateway', { projectId: newProject.id, projectName: newProject.name, projectPath })
+    } catch (error) {
+        console.error('Failed to enroll gateway with the daemon:', error)
+        warnings.push(`background sync couldn't start (${error})`)
+    }
+    await loadDaemonState()
+
+    cancelGatewayBtn.disabled = false
+    if (warnings.length > 0) {
+        setUploadProgress('Gateway created with problems', 100, `Gateway created, but ${warnings.join('; ')}.`)
+        createGatewayBtn.classList.add('hidden')
+        cancelGatewayBtn.textContent = 'Close'
+        await openNewGateway(newProject)
+        return
+    }
+
+    showGatewayMessage('Gateway created and syncing! Opening...', 'success')
+    setTimeout(async () => {
+        hideGatewayModal()
+        await openNewGateway(newProject)
+    }, 1000)
+}
+
+async function pollUploadProgress() {
+    const progress = await safeInvoke('initial_upload_progress')
+    if (!progress) return
+
+    if (progress.phase === 'scanning') {
+        setUploadProgress('Uploading files...', 0, 'Scanning project...')
+        return
+    }
+    const percent = progress.chunks_total > 0 ? Math.floor(progress.chunks_done * 100 / progress.chunks_total) : 100
+    const phase = progress.phase === 'finishing' ? 'Verifying files on the server...' : 'Uploading files...'
+    setUploadProgress(phase, percent,
+        `${progress.files} file(s), ${formatFileSize(progress.total_bytes)} · chunk ${progress.chunks_done} of ${progress.chunks_total}`)
+}
+
+function setUploadProgress(phase, percent, detail) {
+    uploadPhase.textContent = phase
+    uploadProgressBar.style.width = `${percent}%`
+    uploadDetail.textContent = detail
+}
+
+function showWizardStep(step) {
+    wizardStep = step
+    document.querySelectorAll('.wizard-step').forEach(element => {
+        element.classList.toggle('hidden', element.id !== `wizard-step-${step}`)
+    })
+    document.querySelectorAll('.wizard-step-label').forEach(label => {
+        label.classList.toggle('active', label.dataset.step === String(step))
+    })
+    wizardBackBtn.classList.toggle('hidden', step !== 2)
+    createGatewayBtn.classList.toggle('hidden', step === 3)
+    createGatewayBtn.textContent = step === 1 ? 'Next' : 'Create Gateway'
+}
+
+// Refresh the project list and auto-select the new gateway
+async function openNewGateway(newProject) {
+    try {
+        await loadProjects()
+
+        const gatewayProject = projects.find(gp => gp.project.id === newProject.id)
+        if (gatewayProject) {
+            console.log('🎯 Auto-selecting newly created gateway:', gatewayProject.project.name)
+
+            // Find the corresponding project element and trigger click
+            const projectElements = document.querySelectorAll('.project-item')
+            projectElements.forEach((element, index) => {
+                if (projects[index] && projects[index].project.id === newProject.id) {
+                    element.click()
+                    element.scrollIntoView({ behavior: 'smooth', block: 'center' })
+
+                    // Add a brief highlight effect
+                    element.style.border = '2px solid #007acc'
+                    element.style.background = 'rgba(0, 122, 204, 0.1)'
+                    setTimeout(() => {
+                        element.style.border = ''
+                        element.style.background = ''
+                    }, 3000)
+                }
+            })
+        } else {
+            console.warn('⚠️ Could not find newly created gateway in projects list')
+        }
+    } catch (error) {
+        console.error('Failed to refresh projects:', error)
     }
 }
 
@@ -1144,6 +2955,8 @@ cancelGatewayBtn.addEventListener('click', hideGatewayModal)
 
 browsePathBtn.addEventListener('click', browseForDirectory)
 
+wizardBackBtn.addEventListener('click', () => showWizardStep(1))
+
 // Close gateway modal when clicking outside
 gatewayOverlay.addEventListener('click', (e) => {
     if (e.target === gatewayOverlay) {
@@ -1151,11 +2964,96 @@ gatewayOverlay.addEventListener('click', (e) => {
     }
 })
 
+// App updates, from the active server's release channel
+let availableUpdate = null
+
+function updateChannel() {
+    return localStorage.getItem('updateChannel') || 'stable'
+}
+
+async function checkForUpdate() {
+    if (!isTauri || !isAuthenticated) return
+    try {
+        availableUpdate = await safeInvoke('check_for_update', { channel: updateChannel() })
+    } catch (error) {
+        console.error('Failed to check for updates:', error)
+        availableUpdate = null
+    }
+
+    if (availableUpdate) {
+        updateBtn.textContent = `⬆ Update to ${availableUpdate.version}`
+        updateBtn.title = availableUpdate.notes || ''
+        updateBtn.classList.remove('hidden')
+    } else {
+        updateBtn.classList.add('hidden')
+    }
+}
+
+async function installUpdate() {
+    if (!availableUpdate) return
+    const notes = availableUpdate.notes ? `\n\n${availableUpdate.notes}` : ''
+    const question = `Install Mothership ${availableUpdate.version} (you have ${availableUpdate.current_version})? ` +
+        `The app restarts when it's done.${notes}`
+    if (!confirm(question)) return
+
+    await stopLiveEditing()
+    if (editor && currentFile) {
+        await safeInvoke('write_file_content', { path: currentFile, content: editor.getValue() })
+    }
+    updateBtn.disabled = true
+    updateBtn.textContent = 'Downloading...'
+    const progressTimer = setInterval(async () => {
+        const progress = await safeInvoke('update_download_progress')
+        if (!progress) return
+        updateBtn.textContent = progress.total
+            ? `Downloading ${Math.round(progress.downloaded * 100 / progress.total)}%`
+            : `Downloading ${formatFileSize(progress.downloaded)}`
+    }, 500)
+    try {
+        // Resolves only if the install fails; otherwise the app restarts
+        await safeInvoke('install_update', { channel: updateChannel() })
+    } catch (error) {
+        alert(`Update failed: ${error}`)
+    } finally {
+        clearInterval(progressTimer)
+        updateBtn.disabled = false
+        checkForUpdate()
+    }
+}
+
+updateBtn.addEventListener('click', installUpdate)
+updateChannelSelect.value = updateChannel()
+updateChannelSelect.addEventListener('change', () => {
+    localStorage.setItem('updateChannel', updateChannelSelect.value)
+    checkForUpdate()
+})
+
+serversBtn.addEventListener('click', showServers)
+authChangeServer.addEventListener('click', (e) => {
+    e.preventDefault()
+    showServers()
+})
+closeServersBtn.addEventListener('click', hideServers)
+addServerForm.addEventListener('submit', handleAddServer)
+
+searchForm.addEventListener('submit', runSearch)
+;[searchCaseBtn, searchRegexBtn].forEach(button => {
+    button.addEventListener('click', () => button.classList.toggle('active'))
+})
+
 // Close gateway modal with Escape key
 document.addEventListener('keydown', (e) => {
+    if (e.key === 'F' && (e.ctrlKey || e.metaKey) && e.shiftKey && featureEnabled('project_search')) {
+        e.preventDefault()
+        searchInput.focus()
+        searchInput.select()
+    }
     if (e.key === 'Escape' && !gatewayOverlay.classList.contains('hidden')) {
         hideGatewayModal()
     }
+    if (e.key === 'Escape' && !diffOverlay.classList.contains('hidden')) {
+        hideDiffViewer()
+    }
 })
 
 // Initialize application
@@ -1168,6 +3066,11 @@ document.addEventListener('DOMContentLoaded', async () => {
     
     // Debug Tauri context
     console.log('🔍 Tauri context detected:', isTauri)
+
+    // The daemon is local, so its state is shown signed in or not
+    loadDaemonState()
+    setInterval(loadDaemonState, DAEMON_POLL_INTERVAL_MS)
+    loadServers()
     
     // Try auto-login first
     const autoLoginSuccess = await tryAutoLogin()
@@ -1191,6 +3094,9 @@ document.addEventListener('DOMContentLoaded', async () => {
         }
     }
     
+    checkForUpdate()
+    setInterval(checkForUpdate, UPDATE_CHECK_INTERVAL_MS)
+
     // Only show warning if actually not in Tauri
     if (!isTauri) {
         console.warn('⚠️ Running in browser mode - OAuth authentication requires the Tauri desktop app')
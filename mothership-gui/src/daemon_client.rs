@@ -0,0 +1,93 @@
+use anyhow::{anyhow, Result};
+use mothership_common::ipc;
+use serde::{de::DeserializeOwned, Serialize};
+use std::time::Duration;
+
+/// Client for the daemon's local IPC API, reached over the transport
+/// described in `mothership_common::ipc`
+pub struct DaemonClient {
+    timeout: Duration,
+}
+
+/// A response from the daemon
+pub struct DaemonResponse {
+    status: u16,
+    body: Vec<u8>,
+}
+
+impl DaemonResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T> {
+        Ok(serde_json::from_slice(&self.body)?)
+    }
+}
+
+impl DaemonClient {
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+
+    pub async fn get(&self, path: &str) -> Result<DaemonResponse> {
+        self.request("GET", path, None).await
+    }
+
+    pub async fn post<T: Serialize>(&self, path: &str, body: &T) -> Result<DaemonResponse> {
+        self.request("POST", path, Some(serde_json::to_vec(body)?)).await
+    }
+
+    async fn request(&self, method: &str, path: &str, body: Option<Vec<u8>>) -> Result<DaemonResponse> {
+        tokio::time::timeout(self.timeout, send(method, path, body))
+            .await
+            .map_err(|_| anyhow!("Daemon did not respond within {} seconds", self.timeout.as_secs()))?
+    }
+}
+
+/// Send a request over the daemon's Unix socket
+#[cfg(unix)]
+async fn send(method: &str, path: &str, body: Option<Vec<u8>>) -> Result<DaemonResponse> {
+    use http_body_util::{BodyExt, Full};
+    use hyper::body::Bytes;
+
+    let socket_path = ipc::socket_path().ok_or_else(|| anyhow!("Could not determine the daemon socket path"))?;
+    let stream = tokio::net::UnixStream::connect(&socket_path).await?;
+    let (mut sender, connection) = hyper::client::conn::http1::handshake(hyper_util::rt::TokioIo::new(stream)).await?;
+    tokio::spawn(connection);
+
+    let request = hyper::Request::builder()
+        .method(method)
+        .uri(path)
+        .header(hyper::header::HOST, "localhost")
+        .header(hyper::header::CONTENT_TYPE, "application/json")
+        .body(Full::new(Bytes::from(body.unwrap_or_default())))?;
+    let response = sender.send_request(request).await?;
+    let status = response.status().as_u16();
+    let body = response.into_body().collect().await?.to_bytes().to_vec();
+    Ok(DaemonResponse { status, body })
+}
+
+/// Send a request to the daemon's localhost port with its IPC token
+#[cfg(not(unix))]
+async fn send(method: &str, path: &str, body: Option<Vec<u8>>) -> Result<DaemonResponse> {
+    let url = format!("http://{}{}", ipc::IPC_TCP_ADDR, path);
+    let mut request = reqwest::Client::new()
+        .request(reqwest::Method::from_bytes(method.as_bytes())?, url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json");
+    if let Some(token) = ipc::load_token() {
+        request = request.header(ipc::IPC_TOKEN_HEADER, token);
+    }
+    if let Some(body) = body {
+        request = request.body(body);
+    }
+
+    let response = request.send().await?;
+    let status = response.status().as_u16();
+    let body = response.bytes().await?.to_vec();
+    Ok(DaemonResponse { status, body })
+}
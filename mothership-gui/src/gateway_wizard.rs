@@ -0,0 +1,178 @@
+//! Suggestions and file scanning for the gateway creation wizard: what kind
+//! of project a directory holds, the ignore patterns and file types that
+//! suit it, and the files its initial upload sends.
+
+use mothership_common::{
+    ignore::DEFAULT_IGNORE_PATTERNS,
+    upload::{UploadFileManifest, UPLOAD_CHUNK_SIZE},
+    IgnoreMatcher, ProjectSettings, IGNORE_FILE_NAME,
+};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// A kind of project the wizard has templates for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectKind {
+    Rust,
+    Node,
+    Python,
+}
+
+struct Template {
+    kind: ProjectKind,
+    title: &'static str,
+    /// Files in the project root that give the kind away
+    markers: &'static [&'static str],
+    ignore: &'static [&'static str],
+    file_types: &'static [&'static str],
+}
+
+const TEMPLATES: &[Template] = &[
+    Template {
+        kind: ProjectKind::Rust,
+        title: "Rust",
+        markers: &["Cargo.toml"],
+        ignore: &["target/", "*.rs.bk", "*.pdb"],
+        file_types: &["*.rs", "*.toml", "Cargo.lock"],
+    },
+    Template {
+        kind: ProjectKind::Node,
+        title: "Node",
+        markers: &["package.json"],
+        ignore: &[
+            "node_modules/", "dist/", "build/", "coverage/", ".next/", ".nuxt/", ".parcel-cache/",
+            "*.tsbuildinfo", "npm-debug.log*", "yarn-error.log*",
+        ],
+        file_types: &[
+            "*.js", "*.jsx", "*.mjs", "*.cjs", "*.ts", "*.tsx", "*.json", "*.css", "*.scss", "*.html", "*.vue", "*.svelte",
+        ],
+    },
+    Template {
+        kind: ProjectKind::Python,
+        title: "Python",
+        markers: &["pyproject.toml", "setup.py", "setup.cfg", "requirements.txt", "Pipfile"],
+        ignore: &[
+            "__pycache__/", "*.pyc", "*.pyo", ".venv/", "venv/", "env/", ".pytest_cache/", ".mypy_cache/",
+            ".ruff_cache/", ".tox/", "*.egg-info/", "dist/", "build/",
+        ],
+        file_types: &["*.py", "*.pyi", "*.toml", "*.cfg", "*.ini", "requirements*.txt"],
+    },
+];
+
+/// File types every project may want, whatever its kind
+const COMMON_FILE_TYPES: &[&str] = &["*.md", "*.txt", "*.yaml", "*.yml", ".gitignore", IGNORE_FILE_NAME];
+
+/// What the wizard suggests for a directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectScan {
+    pub kinds: Vec<ProjectKind>,
+    /// Contents for the .mothershipignore: the existing file if there is
+    /// one, otherwise the templates' patterns
+    pub ignore_file: String,
+    pub has_ignore_file: bool,
+    pub allowed_file_types: Vec<String>,
+    /// Gateway the directory already sits in, if any
+    pub existing_gateway: Option<PathBuf>,
+}
+
+/// Look at a directory and suggest how to set up its gateway
+pub fn scan(dir: &Path) -> ProjectScan {
+    let templates: Vec<&Template> = TEMPLATES.iter()
+        .filter(|template| template.markers.iter().any(|marker| dir.join(marker).exists()))
+        .collect();
+
+    let existing = fs::read_to_string(dir.join(IGNORE_FILE_NAME)).ok();
+    let ignore_file = match &existing {
+        Some(contents) => contents.clone(),
+        None => ignore_template(&templates),
+    };
+
+    let defaults = ProjectSettings::default().allowed_file_types;
+    let suggested: Vec<&str> = if templates.is_empty() {
+        defaults.iter().map(String::as_str).collect()
+    } else {
+        templates.iter().flat_map(|template| template.file_types.iter().copied()).collect()
+    };
+    let mut allowed_file_types: Vec<String> = Vec::new();
+    for pattern in suggested.into_iter().chain(COMMON_FILE_TYPES.iter().copied()) {
+        if !allowed_file_types.iter().any(|existing| existing == pattern) {
+            allowed_file_types.push(pattern.to_string());
+        }
+    }
+
+    ProjectScan {
+        kinds: templates.iter().map(|template| template.kind).collect(),
+        ignore_file,
+        has_ignore_file: existing.is_some(),
+        allowed_file_types,
+        existing_gateway: dir.ancestors()
+            .find(|ancestor| ancestor.join(".mothership").join("project.json").exists())
+            .map(Path::to_path_buf),
+    }
+}
+
+/// A .mothershipignore for the detected kinds, leaving out what every
+/// project ignores anyway
+fn ignore_template(templates: &[&Template]) -> String {
+    let mut contents = String::from(
+        "# Files Mothership doesn't sync (gitignore syntax).\n\
+         # .git/, build output, editor folders and .env files are always ignored.\n",
+    );
+    let mut written: Vec<&str> = Vec::new();
+    for template in templates {
+        let patterns: Vec<&str> = template.ignore.iter().copied()
+            .filter(|pattern| !DEFAULT_IGNORE_PATTERNS.contains(pattern) && !written.contains(pattern))
+            .collect();
+        if patterns.is_empty() {
+            continue;
+        }
+        contents.push_str(&format!("\n# {}\n", template.title));
+        for pattern in patterns {
+            contents.push_str(pattern);
+            contents.push('\n');
+            written.push(pattern);
+        }
+    }
+    contents
+}
+
+/// The text files a gateway's initial upload sends, honouring its ignore
+/// files. Binary files are left for the daemon to skip as well.
+pub fn upload_manifests(dir: &Path) -> Result<Vec<UploadFileManifest>, String> {
+    let ignore_matcher = IgnoreMatcher::load(dir);
+    let mut manifests = Vec::new();
+    for entry in WalkDir::new(dir)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|e| !ignore_matcher.is_ignored(e.path(), e.file_type().is_dir()))
+    {
+        let entry = entry.map_err(|e| format!("Failed to scan {}: {}", dir.display(), e))?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Ok(relative_path) = entry.path().strip_prefix(dir) else { continue };
+        if let Ok(bytes) = fs::read(entry.path()) {
+            if std::str::from_utf8(&bytes).is_ok() {
+                manifests.push(UploadFileManifest::from_bytes(relative_path.to_path_buf(), &bytes));
+            }
+        }
+    }
+    Ok(manifests)
+}
+
+/// Read the `index`-th upload chunk of a file
+pub fn read_chunk(path: &Path, index: usize) -> Result<Vec<u8>, String> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let read = || -> std::io::Result<Vec<u8>> {
+        let mut file = fs::File::open(path)?;
+        file.seek(SeekFrom::Start((index * UPLOAD_CHUNK_SIZE) as u64))?;
+        let mut bytes = Vec::with_capacity(UPLOAD_CHUNK_SIZE);
+        file.take(UPLOAD_CHUNK_SIZE as u64).read_to_end(&mut bytes)?;
+        Ok(bytes)
+    };
+    read().map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+}
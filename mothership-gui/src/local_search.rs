@@ -0,0 +1,33 @@
+//! Searching the local copy of a project, for when the server can't be asked.
+
+use mothership_common::{
+    search::{SearchQuery, SearchResults, TextSearch},
+    IgnoreMatcher,
+};
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// Search a project's text files, honouring its ignore files
+pub fn search(dir: &Path, query: &SearchQuery) -> Result<SearchResults, String> {
+    let search = TextSearch::new(query)?;
+    let ignore_matcher = IgnoreMatcher::load(dir);
+    let mut results = SearchResults::default();
+
+    for entry in WalkDir::new(dir)
+        .follow_links(false)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| !ignore_matcher.is_ignored(e.path(), e.file_type().is_dir()))
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_file())
+    {
+        let Ok(relative_path) = entry.path().strip_prefix(dir) else { continue };
+        // Binary files don't read as text and aren't searched
+        let Ok(content) = fs::read_to_string(entry.path()) else { continue };
+        if !search.search_file(relative_path, &content, &mut results) {
+            break;
+        }
+    }
+    Ok(results)
+}
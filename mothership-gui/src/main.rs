@@ -5,13 +5,22 @@ use std::path::PathBuf;
 use std::fs;
 use tauri::{State, Manager, AppHandle};
 use serde::{Deserialize, Serialize};
-use mothership_common::{auth::{TokenResponse, OAuthRequest, OAuthResponse, OAuthProvider}, GatewayProject};
+use mothership_common::{auth::{TokenResponse, OAuthRequest, OAuthResponse, OAuthProvider}, diff::{DiffEngine, MergeResult}, features::{Feature, FeatureFlag}, protocol::{ActivityPage, ChatMessage, CommentThread, DiffHunk, FileChangeDiff, FileDiff, FileRevision, InboxPage, StagedFile, UpdateProjectSettingsRequest}, search::{SearchMatch, SearchQuery, SearchResults, TextSearch}, upload::{CreateUploadRequest, UploadCompletion, UploadStatus}, GatewayProject, IGNORE_FILE_NAME, TextCRDT, TextOperation};
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use tauri_plugin_opener::open_url;
 use uuid;
 use axum::{extract::Json as AxumJson, response::Json as AxumResponseJson, routing::post, Router};
 use tower_http::cors::CorsLayer;
 
+mod daemon_client;
+mod gateway_wizard;
+mod local_search;
+mod servers;
+use daemon_client::DaemonClient;
+use gateway_wizard::ProjectScan;
+use servers::{ServerStore, ServerSummary};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileItem {
     pub name: String,
@@ -41,8 +50,184 @@ pub struct StoredCredentials {
 pub struct AppState {
     pub editor_state: Arc<Mutex<EditorState>>,
     pub auth_token: Arc<Mutex<Option<String>>>,
-    pub server_url: String,
+    /// The active server, switched from the settings screen
+    pub server_url: Arc<Mutex<String>>,
     pub app_handle: Option<Arc<Mutex<Option<AppHandle>>>>,
+    /// Local replicas of files being co-edited, by rift path
+    pub coedit_documents: Arc<Mutex<HashMap<String, TextCRDT>>>,
+    /// Working changes shown in the diff viewer and the hunks staged from
+    /// them, by local path
+    pub staging: Arc<Mutex<HashMap<String, StagingEntry>>>,
+    /// Local files synced live over their rift's socket, by local path
+    pub live_buffers: Arc<Mutex<HashMap<String, LiveBuffer>>>,
+    /// Progress of the gateway wizard's initial upload
+    pub upload_progress: Arc<Mutex<Option<UploadProgress>>>,
+    /// Progress of an app update being downloaded
+    pub update_progress: Arc<Mutex<Option<UpdateProgress>>>,
+    /// Feature flags last fetched for each project, by server and project,
+    /// for when the server can't be reached
+    pub feature_flags: Arc<Mutex<HashMap<String, Vec<FeatureFlag>>>>,
+}
+
+impl AppState {
+    pub fn server_url(&self) -> String {
+        self.server_url.lock().map(|url| url.clone()).unwrap_or_default()
+    }
+}
+
+/// A file's working changes against its last checkpoint, and which of
+/// them go into the next staged checkpoint
+pub struct StagingEntry {
+    pub project_id: String,
+    pub path: String,
+    pub base_checkpoint: Option<String>,
+    pub base_content: String,
+    pub hunks: Vec<DiffHunk>,
+    pub staged: Vec<usize>,
+}
+
+/// A local file being edited live: the server's copy of it, once known,
+/// and the diffs sent from it that the server hasn't echoed yet, each with
+/// the content it was made against
+pub struct LiveBuffer {
+    pub server_content: Option<String>,
+    pub in_flight: Vec<(String, String)>,
+}
+
+/// Where a live-edited file syncs to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiveSession {
+    pub rift_id: String,
+    /// Path within the rift
+    pub path: String,
+    pub socket_url: String,
+}
+
+/// A local edit ready to send as FileDiffChanged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiveEdit {
+    pub diff: FileDiff,
+    pub file_size: u64,
+}
+
+/// The editor's text after a remote change, if it changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiveRemoteUpdate {
+    pub text: Option<String>,
+    /// Local and remote edits overlapped; `text` has conflict markers
+    pub conflicted: bool,
+}
+
+/// How far a gateway's initial upload has got
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadProgress {
+    /// `scanning`, `uploading` or `finishing`
+    pub phase: String,
+    pub files: usize,
+    pub total_bytes: u64,
+    pub chunks_done: usize,
+    pub chunks_total: usize,
+}
+
+/// A newer version of the app the active server offers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AvailableUpdate {
+    pub version: String,
+    pub current_version: String,
+    pub notes: Option<String>,
+    pub date: Option<String>,
+}
+
+/// How much of an update has downloaded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateProgress {
+    pub downloaded: u64,
+    pub total: Option<u64>,
+}
+
+/// A search match, with where the file is on this machine if it is
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectSearchMatch {
+    #[serde(flatten)]
+    pub found: SearchMatch,
+    pub local_path: Option<String>,
+}
+
+/// Results of a project search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectSearchResults {
+    pub matches: Vec<ProjectSearchMatch>,
+    pub files_searched: usize,
+    pub truncated: bool,
+    /// `server` when the rift was searched, `local` for the local copy
+    pub source: String,
+}
+
+/// A file's changes as shown by the diff viewer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileDiffView {
+    pub project_id: String,
+    /// Path within the rift
+    pub path: String,
+    pub old_label: String,
+    pub new_label: String,
+    pub hunks: Vec<DiffHunk>,
+    /// Hunks staged for the next checkpoint (working changes only)
+    pub staged: Vec<usize>,
+}
+
+/// A rift of a project as listed by the server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiftListing {
+    pub id: String,
+    pub name: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub author: String,
+    pub file_count: usize,
+    pub is_conflict_rift: bool,
+    /// Users connected to the rift right now
+    #[serde(default)]
+    pub active_collaborators: Vec<String>,
+}
+
+/// A file that differs between two rifts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiftFileDiff {
+    pub path: String,
+    pub change_count: usize,
+}
+
+/// The daemon's view of background sync, for the status indicator
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DaemonSyncState {
+    /// False when the daemon isn't reachable; everything else is then empty
+    pub running: bool,
+    pub server_connected: bool,
+    /// Changes buffered on disk while the server is unreachable
+    pub queued_changes: usize,
+    pub protocol_error: Option<String>,
+    pub projects: Vec<ProjectSyncState>,
+}
+
+/// Sync state of one project the daemon tracks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectSyncState {
+    pub project_id: String,
+    pub project_name: String,
+    pub project_path: PathBuf,
+    pub paused: bool,
+    pub connected: bool,
+    /// File changes waiting to go out
+    pub queue_depth: usize,
+    pub last_upload: Option<chrono::DateTime<chrono::Utc>>,
+    pub last_download: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// A co-editing replica as seen by the editor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoeditSession {
+    pub site_id: uuid::Uuid,
+    pub text: String,
 }
 
 // Helper functions for credential storage
@@ -239,7 +424,7 @@ async fn authenticate_with_mothership(
     
     // Start device flow
     let device_response = client
-        .post(&format!("{}/auth/device", state.server_url))
+        .post(&format!("{}/auth/device", state.server_url()))
         .send()
         .await
         .map_err(|e| format!("Failed to start device flow: {}", e))?;
@@ -274,7 +459,7 @@ async fn authenticate_with_mothership(
         tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
 
         let token_response = client
-            .post(&format!("{}/auth/token", state.server_url))
+            .post(&format!("{}/auth/token", state.server_url()))
             .json(&serde_json::json!({
                 "device_code": device_code
             }))
@@ -292,6 +477,7 @@ async fn authenticate_with_mothership(
             let mut auth_token = state.auth_token.lock()
                 .map_err(|_| "Failed to lock auth token")?;
             *auth_token = Some(token_data.access_token.clone());
+            remember_server_login(&state, Some(token_data.access_token.clone()));
 
             return Ok(token_data);
         }
@@ -310,7 +496,7 @@ async fn load_projects(state: State<'_, AppState>) -> Result<Vec<GatewayProject>
     // First get user info from auth token (same as create_gateway)
     let client = reqwest::Client::new();
     let auth_check_response = client
-        .get(&format!("{}/auth/check", state.server_url))
+        .get(&format!("{}/auth/check", state.server_url()))
         .bearer_auth(&auth_token)
         .send()
         .await
@@ -345,35 +531,47 @@ async fn load_projects(state: State<'_, AppState>) -> Result<Vec<GatewayProject>
         return Err("User not authenticated".to_string());
     }
 
-    // Now load projects for the correct user
-    let gateway_request = mothership_common::protocol::GatewayRequest {
-        include_inactive: false,
-    };
+    // Now load projects for the correct user, a page at a time
+    const GATEWAY_PAGE: usize = 100;
+    let mut projects: Vec<GatewayProject> = Vec::new();
+    loop {
+        let gateway_request = mothership_common::protocol::GatewayRequest {
+            include_inactive: false,
+            archived: false,
+            limit: Some(GATEWAY_PAGE),
+            before: projects.last().map(|p| p.project.id),
+        };
+
+        let response = client
+            .post(&format!("{}/gateway", state.server_url()))
+            .bearer_auth(&auth_token)
+            .json(&gateway_request)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to load projects: {}", e))?;
 
-    let response = client
-        .post(&format!("{}/gateway", state.server_url))
-        .bearer_auth(&auth_token)
-        .json(&gateway_request)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to load projects: {}", e))?;
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(format!("Failed to load projects: {}", error_text));
+        }
 
-    if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("Failed to load projects: {}", error_text));
-    }
+        let api_response: mothership_common::protocol::ApiResponse<Vec<GatewayProject>> = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse projects response: {}", e))?;
 
-    let api_response: mothership_common::protocol::ApiResponse<Vec<GatewayProject>> = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse projects response: {}", e))?;
+        if !api_response.success {
+            return Err(api_response.error.unwrap_or("Unknown error".to_string()));
+        }
 
-    if !api_response.success {
-        return Err(api_response.error.unwrap_or("Unknown error".to_string()));
+        let page = api_response.data.unwrap_or_default();
+        let done = page.len() < GATEWAY_PAGE;
+        projects.extend(page);
+        if done {
+            break;
+        }
     }
 
-    let projects = api_response.data.unwrap_or_default();
-
     println!("✅ Loaded {} projects for user: {}", projects.len(), user_info.username);
 
     // Update editor state
@@ -398,7 +596,7 @@ async fn create_checkpoint(
 
     let client = reqwest::Client::new();
     let response = client
-        .post(&format!("{}/checkpoint", state.server_url))
+        .post(&format!("{}/checkpoint", state.server_url()))
         .bearer_auth(&auth_token)
         .json(&serde_json::json!({
             "message": message,
@@ -415,116 +613,1207 @@ async fn create_checkpoint(
     Ok(())
 }
 
+/// Features turned off in a project, as the server says or, when it can't
+/// be reached, as it last said. Servers without feature flags turn nothing off.
 #[tauri::command]
-async fn start_google_oauth(state: State<'_, AppState>) -> Result<OAuthResponse, String> {
-    let client = reqwest::Client::new();
-    
-    // Create OAuth request
-    let oauth_request = OAuthRequest {
-        provider: OAuthProvider::Google,
-        source: mothership_common::auth::OAuthSource::GUI,
-        machine_id: uuid::Uuid::new_v4().to_string(),
-        machine_name: "Mothership GUI".to_string(),
-        platform: std::env::consts::OS.to_string(),
-        hostname: "mothership-gui".to_string(),
-        callback_url: Some(format!("{}/auth/oauth/callback/google", state.server_url)),  // Use the correct callback URL
+async fn project_features(project_id: String, state: State<'_, AppState>) -> Result<Vec<Feature>, String> {
+    let auth_token = state.auth_token.lock()
+        .map_err(|_| "Failed to lock auth token")?
+        .clone()
+        .ok_or("Not authenticated")?;
+    let server_url = state.server_url();
+    let cache_key = format!("{}|{}", server_url, project_id);
+
+    let fetched = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(5))
+        .build()
+        .map_err(|e| format!("Failed to create client: {}", e))?
+        .get(&format!("{}/projects/{}/features", server_url, project_id))
+        .bearer_auth(&auth_token)
+        .send()
+        .await;
+    let flags = match fetched {
+        Ok(response) if response.status() == reqwest::StatusCode::NOT_FOUND => Vec::new(),
+        Ok(response) if response.status().is_success() => {
+            let api_response: mothership_common::protocol::ApiResponse<Vec<FeatureFlag>> = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse feature flags: {}", e))?;
+            let flags = api_response.data.unwrap_or_default();
+            if let Ok(mut cache) = state.feature_flags.lock() {
+                cache.insert(cache_key, flags.clone());
+            }
+            flags
+        }
+        _ => state.feature_flags.lock()
+            .ok()
+            .and_then(|cache| cache.get(&cache_key).cloned())
+            .unwrap_or_default(),
     };
-    
+
+    Ok(flags.into_iter().filter(|flag| !flag.enabled).map(|flag| flag.feature).collect())
+}
+
+#[tauri::command]
+async fn load_chat_history(
+    project_id: String,
+    rift_id: String,
+    before: Option<String>,
+    state: State<'_, AppState>
+) -> Result<Vec<ChatMessage>, String> {
+    let auth_token = state.auth_token.lock()
+        .map_err(|_| "Failed to lock auth token")?
+        .clone()
+        .ok_or("Not authenticated")?;
+
+    let mut query = vec![("limit", "50".to_string())];
+    if let Some(before) = before {
+        query.push(("before", before));
+    }
+
+    let client = reqwest::Client::new();
     let response = client
-        .post(&format!("{}/auth/oauth/start", state.server_url))
-        .json(&oauth_request)
+        .get(&format!("{}/projects/{}/rifts/{}/chat", state.server_url(), project_id, rift_id))
+        .bearer_auth(&auth_token)
+        .query(&query)
         .send()
         .await
-        .map_err(|e| format!("Failed to start OAuth: {}", e))?;
-    
+        .map_err(|e| format!("Failed to load chat: {}", e))?;
+
     if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("OAuth start failed: {}", error_text));
+        return Err(format!("Failed to load chat: {}", response.status()));
     }
-    
-    let oauth_response: mothership_common::protocol::ApiResponse<OAuthResponse> = response
+
+    let api_response: mothership_common::protocol::ApiResponse<Vec<ChatMessage>> = response
         .json()
         .await
-        .map_err(|e| format!("Failed to parse OAuth response: {}", e))?;
-    
-    if !oauth_response.success {
-        return Err(oauth_response.error.unwrap_or_default());
+        .map_err(|e| format!("Failed to parse chat response: {}", e))?;
+
+    if !api_response.success {
+        return Err(api_response.error.unwrap_or("Unknown error".to_string()));
     }
-    
-    let oauth_data = oauth_response.data.unwrap();
-    
-    // Open the OAuth URL in browser
-    open_url(&oauth_data.auth_url, None::<String>)
-        .map_err(|e| format!("Failed to open browser: {}", e))?;
-    
-    Ok(oauth_data)
+
+    Ok(api_response.data.unwrap_or_default())
 }
 
 #[tauri::command]
-async fn save_auth_token(
-    token: String, 
-    state: State<'_, AppState>,
-    app: AppHandle
-) -> Result<(), String> {
-    println!("🔐 save_auth_token called with token length: {}", token.len());
-    
-    {
-        let mut auth_token = state.auth_token.lock()
-            .map_err(|_| "Failed to lock auth token")?;
-        *auth_token = Some(token.clone());
-        println!("✅ Token saved to app state");
+async fn load_project_activity(
+    project_id: String,
+    before: Option<String>,
+    state: State<'_, AppState>
+) -> Result<ActivityPage, String> {
+    let auth_token = state.auth_token.lock()
+        .map_err(|_| "Failed to lock auth token")?
+        .clone()
+        .ok_or("Not authenticated")?;
+
+    let mut query = vec![("limit", "50".to_string())];
+    if let Some(before) = before {
+        query.push(("before", before));
     }
-    
-    // Also save to persistent storage
-    let credentials = StoredCredentials {
-        access_token: token,
-        user_email: None,
-        user_name: None,
-        stored_at: chrono::Utc::now().to_rfc3339(),
-    };
-    
-    println!("💾 Attempting to save credentials to persistent storage");
-    save_credentials(&app, &credentials)?;
-    println!("✅ save_auth_token completed successfully");
-    
-    Ok(())
+
+    let client = reqwest::Client::new();
+    let response = client
+        .get(&format!("{}/projects/{}/activity", state.server_url(), project_id))
+        .bearer_auth(&auth_token)
+        .query(&query)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to load activity: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Failed to load activity: {}", response.status()));
+    }
+
+    let api_response: mothership_common::protocol::ApiResponse<ActivityPage> = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse activity response: {}", e))?;
+
+    if !api_response.success {
+        return Err(api_response.error.unwrap_or("Unknown error".to_string()));
+    }
+
+    api_response.data.ok_or_else(|| "No activity data received".to_string())
 }
 
 #[tauri::command]
-async fn check_auth_status(state: State<'_, AppState>) -> Result<bool, String> {
+async fn load_project_comments(
+    project_id: String,
+    unresolved: bool,
+    state: State<'_, AppState>
+) -> Result<Vec<CommentThread>, String> {
     let auth_token = state.auth_token.lock()
-        .map_err(|_| "Failed to lock auth token")?;
-    Ok(auth_token.is_some())
+        .map_err(|_| "Failed to lock auth token")?
+        .clone()
+        .ok_or("Not authenticated")?;
+
+    let client = reqwest::Client::new();
+    let response = client
+        .get(&format!("{}/projects/{}/comments", state.server_url(), project_id))
+        .bearer_auth(&auth_token)
+        .query(&[("unresolved", unresolved)])
+        .send()
+        .await
+        .map_err(|e| format!("Failed to load comments: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Failed to load comments: {}", response.status()));
+    }
+
+    let api_response: mothership_common::protocol::ApiResponse<Vec<CommentThread>> = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse comments response: {}", e))?;
+
+    if !api_response.success {
+        return Err(api_response.error.unwrap_or("Unknown error".to_string()));
+    }
+
+    api_response.data.ok_or_else(|| "No comment data received".to_string())
 }
 
 #[tauri::command]
-async fn logout(state: State<'_, AppState>, app: AppHandle) -> Result<(), String> {
-    // Clear auth token
-    {
-        let mut auth_token = state.auth_token.lock()
-            .map_err(|_| "Failed to lock auth token")?;
-        *auth_token = None;
-    } // Drop the mutex guard here
-    
-    // Clear stored credentials
-    clear_stored_credentials(app).await?;
-    
-    // Clear editor state
-    {
-        let mut editor_state = state.editor_state.lock()
-            .map_err(|_| "Failed to lock editor state")?;
-        editor_state.projects = Vec::new();
-    } // Drop the mutex guard here too
-    
-    Ok(())
+async fn load_notifications(
+    unread_only: bool,
+    state: State<'_, AppState>
+) -> Result<InboxPage, String> {
+    let auth_token = state.auth_token.lock()
+        .map_err(|_| "Failed to lock auth token")?
+        .clone()
+        .ok_or("Not authenticated")?;
+
+    let client = reqwest::Client::new();
+    let response = client
+        .get(&format!("{}/users/me/notifications", state.server_url()))
+        .bearer_auth(&auth_token)
+        .query(&[("unread", unread_only)])
+        .send()
+        .await
+        .map_err(|e| format!("Failed to load notifications: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Failed to load notifications: {}", response.status()));
+    }
+
+    let api_response: mothership_common::protocol::ApiResponse<InboxPage> = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse notifications response: {}", e))?;
+
+    if !api_response.success {
+        return Err(api_response.error.unwrap_or("Unknown error".to_string()));
+    }
+
+    api_response.data.ok_or_else(|| "No notification data received".to_string())
 }
 
+/// Mark one notification read, or all of them without an ID
 #[tauri::command]
-async fn authenticate_with_username_password(
-    _email: String,
-    _password: String,
-    _state: State<'_, AppState>
-) -> Result<TokenResponse, String> {
+async fn mark_notifications_read(
+    id: Option<String>,
+    state: State<'_, AppState>
+) -> Result<(), String> {
+    let auth_token = state.auth_token.lock()
+        .map_err(|_| "Failed to lock auth token")?
+        .clone()
+        .ok_or("Not authenticated")?;
+
+    let url = match id {
+        Some(id) => format!("{}/users/me/notifications/{}/read", state.server_url(), id),
+        None => format!("{}/users/me/notifications/read", state.server_url()),
+    };
+    let client = reqwest::Client::new();
+    let response = client
+        .post(&url)
+        .bearer_auth(&auth_token)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to mark notifications read: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Failed to mark notifications read: {}", response.status()));
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn send_chat_message(
+    project_id: String,
+    rift_id: String,
+    content: String,
+    state: State<'_, AppState>
+) -> Result<ChatMessage, String> {
+    let auth_token = state.auth_token.lock()
+        .map_err(|_| "Failed to lock auth token")?
+        .clone()
+        .ok_or("Not authenticated")?;
+
+    let client = reqwest::Client::new();
+    let response = client
+        .post(&format!("{}/projects/{}/rifts/{}/chat", state.server_url(), project_id, rift_id))
+        .bearer_auth(&auth_token)
+        .json(&mothership_common::protocol::SendChatRequest { content })
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send message: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Failed to send message: {}", response.status()));
+    }
+
+    let api_response: mothership_common::protocol::ApiResponse<ChatMessage> = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse chat response: {}", e))?;
+
+    api_response.data.ok_or_else(|| api_response.error.unwrap_or("Unknown error".to_string()))
+}
+
+#[tauri::command]
+async fn get_sync_socket_url(rift_id: String, state: State<'_, AppState>) -> Result<String, String> {
+    sync_socket_url(&state, &rift_id)
+}
+
+/// URL of a rift's sync WebSocket, authenticated with the current token
+fn sync_socket_url(state: &AppState, rift_id: &str) -> Result<String, String> {
+    let auth_token = state.auth_token.lock()
+        .map_err(|_| "Failed to lock auth token")?
+        .clone()
+        .ok_or("Not authenticated")?;
+
+    let mut url = url::Url::parse(&format!("{}/ws/{}", state.server_url(), rift_id))
+        .map_err(|e| format!("Invalid server URL: {}", e))?;
+    let scheme = if url.scheme() == "https" { "wss" } else { "ws" };
+    url.set_scheme(scheme).map_err(|_| "Failed to build WebSocket URL")?;
+    url.query_pairs_mut().append_pair("token", &auth_token);
+
+    Ok(url.to_string())
+}
+
+/// Start a local replica of a co-edited file from the server's snapshot
+#[tauri::command]
+async fn coedit_open(path: String, document: TextCRDT, state: State<'_, AppState>) -> Result<CoeditSession, String> {
+    let replica = document.fork(uuid::Uuid::new_v4());
+    let session = CoeditSession {
+        site_id: replica.site_id(),
+        text: replica.text(),
+    };
+
+    state.coedit_documents.lock()
+        .map_err(|_| "Failed to lock co-editing documents")?
+        .insert(path, replica);
+
+    Ok(session)
+}
+
+/// Turn the editor's new text into operations to send to the server
+#[tauri::command]
+async fn coedit_local_edit(path: String, text: String, state: State<'_, AppState>) -> Result<Vec<TextOperation>, String> {
+    let mut documents = state.coedit_documents.lock()
+        .map_err(|_| "Failed to lock co-editing documents")?;
+    let document = documents.get_mut(&path).ok_or("File is not being co-edited")?;
+
+    Ok(document.update_text(&text))
+}
+
+/// Merge operations from other editors and return the resulting text
+#[tauri::command]
+async fn coedit_apply_remote(path: String, operations: Vec<TextOperation>, state: State<'_, AppState>) -> Result<String, String> {
+    let mut documents = state.coedit_documents.lock()
+        .map_err(|_| "Failed to lock co-editing documents")?;
+    let document = documents.get_mut(&path).ok_or("File is not being co-edited")?;
+
+    document.apply_remote(&operations);
+    Ok(document.text())
+}
+
+#[tauri::command]
+async fn coedit_close(path: String, state: State<'_, AppState>) -> Result<(), String> {
+    state.coedit_documents.lock()
+        .map_err(|_| "Failed to lock co-editing documents")?
+        .remove(&path);
+    Ok(())
+}
+
+/// Start live editing a local project file. The file's socket joins the
+/// rift limited to this one path; its server content arrives with the join.
+#[tauri::command]
+async fn live_open(path: String, state: State<'_, AppState>) -> Result<LiveSession, String> {
+    let file = locate_project_file(&path)?;
+    let socket_url = sync_socket_url(&state, &file.rift_id)?;
+
+    state.live_buffers.lock()
+        .map_err(|_| "Failed to lock live buffers")?
+        .insert(path, LiveBuffer { server_content: None, in_flight: Vec::new() });
+
+    Ok(LiveSession {
+        rift_id: file.rift_id,
+        path: file.path,
+        socket_url,
+    })
+}
+
+/// Take the file's full content from the server (on joining, or when it
+/// replaced the file outright) and work it into the editor's text. Without
+/// content the rift doesn't have the file yet and the editor's text stays.
+#[tauri::command]
+async fn live_remote_content(path: String, content: Option<String>, text: String, state: State<'_, AppState>) -> Result<LiveRemoteUpdate, String> {
+    let mut buffers = state.live_buffers.lock()
+        .map_err(|_| "Failed to lock live buffers")?;
+    let buffer = buffers.get_mut(&path).ok_or("File is not being edited live")?;
+
+    // Unconfirmed diffs may not have made it: merge from what the server last confirmed
+    let confirmed = match buffer.in_flight.drain(..).next() {
+        Some((_, base)) => Some(base),
+        None => buffer.server_content.take(),
+    };
+    let Some(content) = content else {
+        buffer.server_content = Some(String::new());
+        return Ok(LiveRemoteUpdate { text: None, conflicted: false });
+    };
+    buffer.server_content = Some(content.clone());
+
+    // Before the first content there's nothing to merge with: the rift wins
+    let old_base = confirmed.unwrap_or_else(|| text.clone());
+    Ok(rebase_live_text(&old_base, &content, &text))
+}
+
+/// Turn the editor's text into a diff against the server's copy, or None
+/// when there is nothing new to send
+#[tauri::command]
+async fn live_local_edit(path: String, text: String, state: State<'_, AppState>) -> Result<Option<LiveEdit>, String> {
+    let mut buffers = state.live_buffers.lock()
+        .map_err(|_| "Failed to lock live buffers")?;
+    let buffer = buffers.get_mut(&path).ok_or("File is not being edited live")?;
+    let Some(base) = &buffer.server_content else { return Ok(None) };
+    if *base == text {
+        return Ok(None);
+    }
+
+    let diff = DiffEngine::new().generate_block_delta(base, &text);
+    let encoded = serde_json::to_string(&diff).map_err(|e| format!("Failed to encode diff: {}", e))?;
+    let base = buffer.server_content.replace(text.clone()).unwrap_or_default();
+    buffer.in_flight.push((encoded, base));
+    Ok(Some(LiveEdit { diff, file_size: text.len() as u64 }))
+}
+
+/// Send the whole text after the server rejected a diff whose base it didn't have
+#[tauri::command]
+async fn live_resend(path: String, text: String, state: State<'_, AppState>) -> Result<LiveEdit, String> {
+    let mut buffers = state.live_buffers.lock()
+        .map_err(|_| "Failed to lock live buffers")?;
+    let buffer = buffers.get_mut(&path).ok_or("File is not being edited live")?;
+
+    buffer.in_flight.clear();
+    buffer.server_content = Some(text.clone());
+    Ok(LiveEdit { diff: FileDiff::FullContent(text.clone()), file_size: text.len() as u64 })
+}
+
+/// Apply someone else's change to the server's copy and work it into the
+/// editor's text. Errors when the change doesn't fit what this buffer
+/// last saw from the server, in which case the caller should rejoin.
+#[tauri::command]
+async fn live_apply_remote(path: String, diff: FileDiff, text: String, state: State<'_, AppState>) -> Result<LiveRemoteUpdate, String> {
+    let mut buffers = state.live_buffers.lock()
+        .map_err(|_| "Failed to lock live buffers")?;
+    let buffer = buffers.get_mut(&path).ok_or("File is not being edited live")?;
+    let old_base = buffer.server_content.clone().ok_or("File content hasn't arrived yet")?;
+
+    // The server echoes our own diffs back with everyone else's
+    let encoded = serde_json::to_string(&diff).map_err(|e| format!("Failed to encode diff: {}", e))?;
+    if let Some(sent) = buffer.in_flight.iter().position(|(d, _)| *d == encoded) {
+        buffer.in_flight.drain(..=sent);
+        return Ok(LiveRemoteUpdate { text: None, conflicted: false });
+    }
+    if matches!(diff, FileDiff::Deleted) {
+        return Err("The file was deleted from the rift".to_string());
+    }
+
+    let new_base = DiffEngine::new().apply_diff(&old_base, &diff)
+        .map_err(|e| format!("Remote change doesn't apply: {}", e))?;
+    buffer.server_content = Some(new_base.clone());
+    Ok(rebase_live_text(&old_base, &new_base, &text))
+}
+
+#[tauri::command]
+async fn live_close(path: String, state: State<'_, AppState>) -> Result<(), String> {
+    state.live_buffers.lock()
+        .map_err(|_| "Failed to lock live buffers")?
+        .remove(&path);
+    Ok(())
+}
+
+/// Carry the editor's unsent edits over from the old server content to the new
+fn rebase_live_text(old_base: &str, new_base: &str, text: &str) -> LiveRemoteUpdate {
+    if old_base == new_base {
+        return LiveRemoteUpdate { text: None, conflicted: false };
+    }
+    if text == old_base {
+        return LiveRemoteUpdate { text: Some(new_base.to_string()), conflicted: false };
+    }
+    match DiffEngine::new().three_way_merge(old_base, text, new_base) {
+        MergeResult::Clean(merged) => LiveRemoteUpdate { text: Some(merged), conflicted: false },
+        MergeResult::Conflicted { content, .. } => LiveRemoteUpdate { text: Some(content), conflicted: true },
+    }
+}
+
+/// Search a project: the rift's live files on the server, or the local
+/// copy when the server can't be reached
+#[tauri::command]
+async fn search_project(
+    rift_id: Option<String>,
+    project_path: Option<String>,
+    query: SearchQuery,
+    state: State<'_, AppState>
+) -> Result<ProjectSearchResults, String> {
+    // A bad pattern fails the same way everywhere, so report it straight away
+    TextSearch::new(&query)?;
+
+    let server_error = match rift_id {
+        Some(rift_id) => match search_rift(&state, &rift_id, &query).await {
+            Ok(results) => return Ok(with_local_paths(results, project_path.as_deref(), "server")),
+            Err(e) => e,
+        },
+        None => "No rift selected".to_string(),
+    };
+    let Some(project_path) = project_path else { return Err(server_error) };
+
+    println!("🔍 Server search unavailable ({}), searching {} locally", server_error, project_path);
+    let dir = PathBuf::from(&project_path);
+    let results = tokio::task::spawn_blocking(move || local_search::search(&dir, &query))
+        .await
+        .map_err(|e| format!("Local search failed: {}", e))??;
+    Ok(with_local_paths(results, Some(&project_path), "local"))
+}
+
+async fn search_rift(state: &AppState, rift_id: &str, query: &SearchQuery) -> Result<SearchResults, String> {
+    let auth_token = state.auth_token.lock()
+        .map_err(|_| "Failed to lock auth token")?
+        .clone()
+        .ok_or("Not authenticated")?;
+
+    let client = reqwest::Client::new();
+    let response = client
+        .get(&format!("{}/browse/api/rifts/{}/search", state.server_url(), rift_id))
+        .bearer_auth(&auth_token)
+        .query(query)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to search: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Failed to search: {}", response.status()));
+    }
+
+    let api_response: mothership_common::protocol::ApiResponse<SearchResults> = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse search results: {}", e))?;
+
+    api_response.data.ok_or_else(|| api_response.error.unwrap_or("Unknown error".to_string()))
+}
+
+/// Point each match at its file in the local copy, where there is one
+fn with_local_paths(results: SearchResults, project_path: Option<&str>, source: &str) -> ProjectSearchResults {
+    let matches = results.matches.into_iter()
+        .map(|found| {
+            let local_path = project_path
+                .map(|dir| PathBuf::from(dir).join(&found.path))
+                .filter(|path| path.is_file())
+                .map(|path| path.to_string_lossy().into_owned());
+            ProjectSearchMatch { found, local_path }
+        })
+        .collect();
+
+    ProjectSearchResults {
+        matches,
+        files_searched: results.files_searched,
+        truncated: results.truncated,
+        source: source.to_string(),
+    }
+}
+
+/// Lines of unchanged context around each change in the diff viewer
+const DIFF_CONTEXT_LINES: usize = 3;
+
+/// Where a local file sits in a beamed project
+struct ProjectFile {
+    project_id: String,
+    rift_id: String,
+    /// Path within the rift, with `/` separators
+    path: String,
+}
+
+#[derive(Deserialize)]
+struct LocalProjectMetadata {
+    project_id: String,
+    rift_id: Option<String>,
+}
+
+/// A file as of a rift's latest checkpoint, from the project browser API
+#[derive(Deserialize)]
+struct CheckpointedFile {
+    checkpoint_id: String,
+    /// Missing when the file is too large to view
+    content: Option<String>,
+}
+
+/// Find the project a local file belongs to from the nearest .mothership directory
+fn locate_project_file(local_path: &str) -> Result<ProjectFile, String> {
+    let local_path = PathBuf::from(local_path);
+    let mut dir = local_path.parent();
+    while let Some(candidate) = dir {
+        let metadata_file = candidate.join(".mothership").join("project.json");
+        if metadata_file.exists() {
+            let metadata: LocalProjectMetadata = fs::read_to_string(&metadata_file)
+                .map_err(|e| format!("Failed to read project metadata: {}", e))
+                .and_then(|json| serde_json::from_str(&json).map_err(|e| format!("Invalid project metadata: {}", e)))?;
+            let path = local_path.strip_prefix(candidate).map_err(|_| "File is outside its project")?;
+            return Ok(ProjectFile {
+                project_id: metadata.project_id,
+                rift_id: metadata.rift_id.ok_or("This project has no rift yet")?,
+                path: path.to_string_lossy().replace('\\', "/"),
+            });
+        }
+        dir = candidate.parent();
+    }
+    Err("This file isn't in a Mothership project".to_string())
+}
+
+/// URL of a file's history or diff endpoint
+fn file_endpoint_url(server_url: &str, file: &ProjectFile, endpoint: &str) -> Result<url::Url, String> {
+    let mut url = url::Url::parse(&format!("{}/projects/{}/files", server_url, file.project_id))
+        .map_err(|e| format!("Invalid server URL: {}", e))?;
+    url.path_segments_mut()
+        .map_err(|_| "Invalid server URL")?
+        .extend(file.path.split('/'))
+        .push(endpoint);
+    url.query_pairs_mut().append_pair("rift_id", &file.rift_id);
+    Ok(url)
+}
+
+/// The checkpoints that changed a local file, newest first
+#[tauri::command]
+async fn load_file_revisions(path: String, state: State<'_, AppState>) -> Result<Vec<FileRevision>, String> {
+    let auth_token = state.auth_token.lock()
+        .map_err(|_| "Failed to lock auth token")?
+        .clone()
+        .ok_or("Not authenticated")?;
+    let file = locate_project_file(&path)?;
+
+    let client = reqwest::Client::new();
+    let response = client
+        .get(file_endpoint_url(&state.server_url(), &file, "history")?)
+        .bearer_auth(&auth_token)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to load file history: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Failed to load file history: {}", response.status()));
+    }
+
+    let api_response: mothership_common::protocol::ApiResponse<Vec<FileRevision>> = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse file history: {}", e))?;
+
+    api_response.data.ok_or_else(|| api_response.error.unwrap_or("Unknown error".to_string()))
+}
+
+/// What a checkpoint changed in a local file
+#[tauri::command]
+async fn load_checkpoint_diff(path: String, checkpoint_id: String, state: State<'_, AppState>) -> Result<FileDiffView, String> {
+    let auth_token = state.auth_token.lock()
+        .map_err(|_| "Failed to lock auth token")?
+        .clone()
+        .ok_or("Not authenticated")?;
+    let file = locate_project_file(&path)?;
+
+    let mut url = file_endpoint_url(&state.server_url(), &file, "diff")?;
+    url.query_pairs_mut().append_pair("checkpoint", &checkpoint_id);
+    let client = reqwest::Client::new();
+    let response = client
+        .get(url)
+        .bearer_auth(&auth_token)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to load diff: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Failed to load diff: {}", response.status()));
+    }
+
+    let api_response: mothership_common::protocol::ApiResponse<FileChangeDiff> = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse diff: {}", e))?;
+    let diff = api_response.data.ok_or_else(|| api_response.error.unwrap_or("Unknown error".to_string()))?;
+
+    Ok(FileDiffView {
+        project_id: file.project_id,
+        path: file.path,
+        old_label: match diff.previous_checkpoint_id {
+            Some(previous) => format!("Checkpoint {}", previous),
+            None => "(new file)".to_string(),
+        },
+        new_label: format!("Checkpoint {}", diff.checkpoint_id),
+        hunks: DiffEngine::new().hunks(&diff.old_content, &diff.new_content, DIFF_CONTEXT_LINES),
+        staged: Vec::new(),
+    })
+}
+
+/// A local file's changes since the rift's last checkpoint. Hunks staged
+/// earlier stay staged as long as they are unchanged.
+#[tauri::command]
+async fn load_working_diff(path: String, state: State<'_, AppState>) -> Result<FileDiffView, String> {
+    let auth_token = state.auth_token.lock()
+        .map_err(|_| "Failed to lock auth token")?
+        .clone()
+        .ok_or("Not authenticated")?;
+    let file = locate_project_file(&path)?;
+    let local_content = fs::read_to_string(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+
+    let client = reqwest::Client::new();
+    let response = client
+        .get(&format!("{}/browse/api/rifts/{}/file", state.server_url(), file.rift_id))
+        .bearer_auth(&auth_token)
+        .query(&[("path", &file.path)])
+        .send()
+        .await
+        .map_err(|e| format!("Failed to load checkpointed file: {}", e))?;
+
+    // Not found: the file (or the whole rift) has no checkpoint yet
+    let (base_checkpoint, base_content) = if response.status() == reqwest::StatusCode::NOT_FOUND {
+        (None, String::new())
+    } else if !response.status().is_success() {
+        return Err(format!("Failed to load checkpointed file: {}", response.status()));
+    } else {
+        let api_response: mothership_common::protocol::ApiResponse<CheckpointedFile> = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse checkpointed file: {}", e))?;
+        let checkpointed = api_response.data.ok_or_else(|| api_response.error.unwrap_or("Unknown error".to_string()))?;
+        let content = checkpointed.content.ok_or("File is too large to diff")?;
+        (Some(checkpointed.checkpoint_id), content)
+    };
+
+    let hunks = DiffEngine::new().hunks(&base_content, &local_content, DIFF_CONTEXT_LINES);
+    let mut staging = state.staging.lock().map_err(|_| "Failed to lock staging area")?;
+    let staged = match staging.get(&path) {
+        Some(previous) if previous.base_checkpoint == base_checkpoint => previous.staged.iter()
+            .filter_map(|&index| hunks.iter().position(|hunk| Some(hunk) == previous.hunks.get(index)))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let view = FileDiffView {
+        project_id: file.project_id.clone(),
+        path: file.path.clone(),
+        old_label: match &base_checkpoint {
+            Some(checkpoint) => format!("Checkpoint {}", checkpoint),
+            None => "(not checkpointed)".to_string(),
+        },
+        new_label: "Working copy".to_string(),
+        hunks: hunks.clone(),
+        staged: staged.clone(),
+    };
+    staging.insert(path, StagingEntry {
+        project_id: file.project_id,
+        path: file.path,
+        base_checkpoint,
+        base_content,
+        hunks,
+        staged,
+    });
+    Ok(view)
+}
+
+/// Stage or unstage one hunk of a file's working changes; returns the
+/// indexes of the file's staged hunks
+#[tauri::command]
+async fn stage_hunk(path: String, hunk: usize, staged: bool, state: State<'_, AppState>) -> Result<Vec<usize>, String> {
+    let mut staging = state.staging.lock().map_err(|_| "Failed to lock staging area")?;
+    let entry = staging.get_mut(&path).ok_or("Load the file's working changes first")?;
+    if hunk >= entry.hunks.len() {
+        return Err("No such hunk".to_string());
+    }
+
+    entry.staged.retain(|&index| index != hunk);
+    if staged {
+        entry.staged.push(hunk);
+        entry.staged.sort_unstable();
+    }
+    Ok(entry.staged.clone())
+}
+
+/// Checkpoint the hunks staged in a project, leaving the rest of the
+/// working changes for later. Returns how many files were checkpointed.
+#[tauri::command]
+async fn create_staged_checkpoint(project_id: String, message: String, state: State<'_, AppState>) -> Result<usize, String> {
+    let auth_token = state.auth_token.lock()
+        .map_err(|_| "Failed to lock auth token")?
+        .clone()
+        .ok_or("Not authenticated")?;
+
+    let (parent, files, local_paths) = {
+        let staging = state.staging.lock().map_err(|_| "Failed to lock staging area")?;
+        let engine = DiffEngine::new();
+        let mut parent = None;
+        let mut files = Vec::new();
+        let mut local_paths = Vec::new();
+        for (local_path, entry) in staging.iter().filter(|(_, e)| e.project_id == project_id && !e.staged.is_empty()) {
+            // Everything staged must build on the same checkpoint; new files
+            // fit on any
+            if let Some(base) = &entry.base_checkpoint {
+                if parent.as_ref().is_some_and(|parent| parent != base) {
+                    return Err(format!("{} was staged against an older checkpoint; reload its changes", entry.path));
+                }
+                parent = Some(base.clone());
+            }
+
+            let hunks: Vec<DiffHunk> = entry.staged.iter().map(|&index| entry.hunks[index].clone()).collect();
+            let content = engine.apply_hunks(&entry.base_content, &hunks)
+                .map_err(|e| format!("Failed to stage {}: {}", entry.path, e))?;
+            files.push(StagedFile { path: PathBuf::from(&entry.path), content });
+            local_paths.push(local_path.clone());
+        }
+        (parent, files, local_paths)
+    };
+    if files.is_empty() {
+        return Err("Nothing is staged".to_string());
+    }
+
+    let client = reqwest::Client::new();
+    let response = client
+        .post(&format!("{}/projects/{}/checkpoints", state.server_url(), project_id))
+        .bearer_auth(&auth_token)
+        .json(&serde_json::json!({
+            "message": message,
+            "timestamp": chrono::Utc::now(),
+            "parent": parent,
+            "staged": files,
+        }))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to create checkpoint: {}", e))?;
+
+    if response.status() == reqwest::StatusCode::CONFLICT {
+        return Err("The rift has a newer checkpoint; reload the changes and stage them again".to_string());
+    }
+    if !response.status().is_success() {
+        return Err(format!("Failed to create checkpoint: {}", response.status()));
+    }
+
+    let mut staging = state.staging.lock().map_err(|_| "Failed to lock staging area")?;
+    for local_path in local_paths {
+        staging.remove(&local_path);
+    }
+    Ok(files.len())
+}
+
+/// Send a rift API request and read the data out of the response
+async fn send_rift_request<T: serde::de::DeserializeOwned>(request: reqwest::RequestBuilder, action: &str) -> Result<Option<T>, String> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Failed to {}: {}", action, e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Failed to {}: {}", action, response.status()));
+    }
+
+    let api_response: mothership_common::protocol::ApiResponse<T> = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to {}: {}", action, e))?;
+
+    if !api_response.success {
+        return Err(api_response.error.unwrap_or("Unknown error".to_string()));
+    }
+
+    Ok(api_response.data)
+}
+
+/// The rifts of a project the user can see, with who is active in each
+#[tauri::command]
+async fn list_rifts(project_id: String, state: State<'_, AppState>) -> Result<Vec<RiftListing>, String> {
+    let auth_token = state.auth_token.lock()
+        .map_err(|_| "Failed to lock auth token")?
+        .clone()
+        .ok_or("Not authenticated")?;
+
+    let request = reqwest::Client::new()
+        .get(&format!("{}/api/rifts", state.server_url()))
+        .bearer_auth(&auth_token)
+        .query(&[("project_id", &project_id)]);
+    Ok(send_rift_request(request, "load rifts").await?.unwrap_or_default())
+}
+
+/// The user's current rift in a project, if they have one
+#[tauri::command]
+async fn current_rift(project_id: String, state: State<'_, AppState>) -> Result<Option<RiftListing>, String> {
+    let auth_token = state.auth_token.lock()
+        .map_err(|_| "Failed to lock auth token")?
+        .clone()
+        .ok_or("Not authenticated")?;
+
+    let request = reqwest::Client::new()
+        .get(&format!("{}/api/rifts/current", state.server_url()))
+        .bearer_auth(&auth_token)
+        .query(&[("project_id", &project_id)]);
+    send_rift_request(request, "load the current rift").await
+}
+
+/// Create a rift from the user's current one; returns its ID
+#[tauri::command]
+async fn create_rift(project_id: String, name: String, state: State<'_, AppState>) -> Result<String, String> {
+    let auth_token = state.auth_token.lock()
+        .map_err(|_| "Failed to lock auth token")?
+        .clone()
+        .ok_or("Not authenticated")?;
+
+    let request = reqwest::Client::new()
+        .post(&format!("{}/api/rifts", state.server_url()))
+        .bearer_auth(&auth_token)
+        .json(&serde_json::json!({
+            "project_id": project_id,
+            "name": name.trim(),
+            "description": null,
+        }));
+    send_rift_request(request, "create rift").await?.ok_or_else(|| "Server didn't return the new rift".to_string())
+}
+
+/// Make a rift the user's current one in its project
+#[tauri::command]
+async fn switch_rift(project_id: String, rift_name: String, state: State<'_, AppState>) -> Result<String, String> {
+    let auth_token = state.auth_token.lock()
+        .map_err(|_| "Failed to lock auth token")?
+        .clone()
+        .ok_or("Not authenticated")?;
+
+    let request = reqwest::Client::new()
+        .post(&format!("{}/api/rifts/switch", state.server_url()))
+        .bearer_auth(&auth_token)
+        .json(&serde_json::json!({
+            "project_id": project_id,
+            "rift_name": rift_name,
+        }));
+    Ok(send_rift_request(request, "switch rift").await?.unwrap_or_default())
+}
+
+/// Files that differ between two rifts of a project
+#[tauri::command]
+async fn diff_rifts(project_id: String, from: String, to: String, state: State<'_, AppState>) -> Result<Vec<RiftFileDiff>, String> {
+    let auth_token = state.auth_token.lock()
+        .map_err(|_| "Failed to lock auth token")?
+        .clone()
+        .ok_or("Not authenticated")?;
+
+    let request = reqwest::Client::new()
+        .get(&format!("{}/api/rifts/diff", state.server_url()))
+        .bearer_auth(&auth_token)
+        .query(&[("project_id", &project_id), ("from", &from), ("to", &to)]);
+    Ok(send_rift_request(request, "compare rifts").await?.unwrap_or_default())
+}
+
+/// The daemon's `{status, data, error}` response wrapper
+#[derive(Deserialize)]
+struct DaemonApiResponse<T> {
+    data: Option<T>,
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct DaemonStatus {
+    server_connected: bool,
+    queued_changes: usize,
+    protocol_error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct DaemonProject {
+    project_id: String,
+    project_name: String,
+    project_path: PathBuf,
+    #[serde(default)]
+    paused: bool,
+}
+
+#[derive(Deserialize)]
+struct DaemonMetrics {
+    projects: Vec<DaemonProjectMetrics>,
+}
+
+#[derive(Deserialize)]
+struct DaemonProjectMetrics {
+    project_id: String,
+    queue_depth: usize,
+    connection: Option<DaemonConnectionStats>,
+}
+
+#[derive(Deserialize)]
+struct DaemonConnectionStats {
+    connected: bool,
+    last_upload: Option<chrono::DateTime<chrono::Utc>>,
+    last_download: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// How long to wait for the daemon to answer a request
+const DAEMON_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+async fn daemon_get<T: serde::de::DeserializeOwned>(path: &str) -> Result<T, String> {
+    read_daemon_response(DaemonClient::new(DAEMON_TIMEOUT).get(path).await)
+}
+
+async fn daemon_post<T: serde::de::DeserializeOwned>(path: &str, body: serde_json::Value) -> Result<T, String> {
+    read_daemon_response(DaemonClient::new(DAEMON_TIMEOUT).post(path, &body).await)
+}
+
+/// Read the data out of a daemon response
+fn read_daemon_response<T: serde::de::DeserializeOwned>(response: anyhow::Result<daemon_client::DaemonResponse>) -> Result<T, String> {
+    let response = response.map_err(|e| format!("Daemon is not reachable: {}", e))?;
+    if !response.is_success() {
+        return Err(format!("Daemon request failed: {}", response.text()));
+    }
+    let api_response: DaemonApiResponse<T> = response.json().map_err(|e| format!("Invalid daemon response: {}", e))?;
+    api_response.data.ok_or_else(|| api_response.error.unwrap_or("Unknown error".to_string()))
+}
+
+/// Start the daemon from PATH or next to the app, and wait for it to answer
+async fn ensure_daemon_running() -> Result<(), String> {
+    let client = DaemonClient::new(std::time::Duration::from_secs(2));
+    if client.get("/health").await.is_ok_and(|response| response.is_success()) {
+        return Ok(());
+    }
+
+    let daemon_binary = if cfg!(windows) { "mothership-daemon.exe" } else { "mothership-daemon" };
+    let beside_app = std::env::current_exe().ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(daemon_binary)));
+    let started = std::process::Command::new(daemon_binary).spawn().is_ok()
+        || beside_app.is_some_and(|path| std::process::Command::new(path).spawn().is_ok());
+    if !started {
+        return Err("Could not start the Mothership daemon; is mothership-daemon installed?".to_string());
+    }
+
+    for _ in 0..20 {
+        tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+        if client.get("/health").await.is_ok_and(|response| response.is_success()) {
+            return Ok(());
+        }
+    }
+    Err("The Mothership daemon did not start in time".to_string())
+}
+
+/// Background sync state of every project the daemon tracks
+#[tauri::command]
+async fn daemon_sync_state() -> Result<DaemonSyncState, String> {
+    // Not running is a state to show, not an error
+    let Ok(status) = daemon_get::<DaemonStatus>("/status").await else {
+        return Ok(DaemonSyncState::default());
+    };
+    let projects: Vec<DaemonProject> = daemon_get("/projects").await?;
+    let metrics: DaemonMetrics = daemon_get("/metrics").await?;
+
+    let mut projects: Vec<ProjectSyncState> = projects.into_iter()
+        .map(|project| {
+            let project_metrics = metrics.projects.iter().find(|m| m.project_id == project.project_id);
+            let connection = project_metrics.and_then(|m| m.connection.as_ref());
+            ProjectSyncState {
+                connected: connection.is_some_and(|c| c.connected),
+                queue_depth: project_metrics.map_or(0, |m| m.queue_depth),
+                last_upload: connection.and_then(|c| c.last_upload),
+                last_download: connection.and_then(|c| c.last_download),
+                project_id: project.project_id,
+                project_name: project.project_name,
+                project_path: project.project_path,
+                paused: project.paused,
+            }
+        })
+        .collect();
+    projects.sort_by(|a, b| a.project_name.cmp(&b.project_name));
+
+    Ok(DaemonSyncState {
+        running: true,
+        server_connected: status.server_connected,
+        queued_changes: status.queued_changes,
+        protocol_error: status.protocol_error,
+        projects,
+    })
+}
+
+/// Link a gateway's directory to its project and hand it to the daemon for
+/// background sync, starting the daemon if needed
+#[tauri::command]
+async fn enroll_gateway(project_id: String, project_name: String, project_path: String, state: State<'_, AppState>) -> Result<String, String> {
+    let auth_token = state.auth_token.lock()
+        .map_err(|_| "Failed to lock auth token")?
+        .clone()
+        .ok_or("Not authenticated")?;
+
+    let project_dir = PathBuf::from(&project_path);
+    let mothership_dir = project_dir.join(".mothership");
+    let metadata_file = mothership_dir.join("project.json");
+    if !metadata_file.exists() {
+        // The daemon syncs the project through the user's current rift
+        let request = reqwest::Client::new()
+            .get(&format!("{}/api/rifts/current", state.server_url()))
+            .bearer_auth(&auth_token)
+            .query(&[("project_id", &project_id)]);
+        let rift: Option<RiftListing> = send_rift_request(request, "load the current rift").await?;
+
+        fs::create_dir_all(&mothership_dir).map_err(|e| format!("Failed to create .mothership directory: {}", e))?;
+        let metadata = serde_json::json!({
+            "project_id": project_id,
+            "project_name": project_name,
+            "created_at": chrono::Utc::now().to_rfc3339(),
+            "mothership_url": state.server_url(),
+            "rift_id": rift.map(|rift| rift.id),
+        });
+        let metadata_json = serde_json::to_string_pretty(&metadata).map_err(|e| e.to_string())?;
+        fs::write(&metadata_file, metadata_json).map_err(|e| format!("Failed to write project metadata: {}", e))?;
+    }
+
+    ensure_daemon_running().await?;
+    daemon_post("/projects/add", serde_json::json!({
+        "project_id": project_id,
+        "project_name": project_name,
+        "project_path": project_dir,
+    })).await
+}
+
+/// Stop syncing a project in the background and forget it
+#[tauri::command]
+async fn daemon_unregister_project(project_id: String) -> Result<String, String> {
+    daemon_post(&format!("/projects/{}/remove", project_id), serde_json::json!({})).await
+}
+
+/// Pause a project's background sync
+#[tauri::command]
+async fn daemon_pause_project(project_id: String) -> Result<String, String> {
+    daemon_post(&format!("/projects/{}/pause", project_id), serde_json::json!({})).await
+}
+
+/// Resume a paused project's background sync
+#[tauri::command]
+async fn daemon_resume_project(project_id: String) -> Result<String, String> {
+    daemon_post(&format!("/projects/{}/resume", project_id), serde_json::json!({})).await
+}
+
+#[tauri::command]
+async fn start_google_oauth(state: State<'_, AppState>) -> Result<OAuthResponse, String> {
+    let client = reqwest::Client::new();
+    
+    // Create OAuth request
+    let oauth_request = OAuthRequest {
+        provider: OAuthProvider::Google,
+        source: mothership_common::auth::OAuthSource::GUI,
+        machine_id: uuid::Uuid::new_v4().to_string(),
+        machine_name: "Mothership GUI".to_string(),
+        platform: std::env::consts::OS.to_string(),
+        hostname: "mothership-gui".to_string(),
+        callback_url: Some(format!("{}/auth/oauth/callback/google", state.server_url())),  // Use the correct callback URL
+    };
+    
+    let response = client
+        .post(&format!("{}/auth/oauth/start", state.server_url()))
+        .json(&oauth_request)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to start OAuth: {}", e))?;
+    
+    if !response.status().is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("OAuth start failed: {}", error_text));
+    }
+    
+    let oauth_response: mothership_common::protocol::ApiResponse<OAuthResponse> = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse OAuth response: {}", e))?;
+    
+    if !oauth_response.success {
+        return Err(oauth_response.error.unwrap_or_default());
+    }
+    
+    let oauth_data = oauth_response.data.unwrap();
+    
+    // Open the OAuth URL in browser
+    open_url(&oauth_data.auth_url, None::<String>)
+        .map_err(|e| format!("Failed to open browser: {}", e))?;
+    
+    Ok(oauth_data)
+}
+
+#[tauri::command]
+async fn save_auth_token(
+    token: String, 
+    state: State<'_, AppState>,
+    app: AppHandle
+) -> Result<(), String> {
+    println!("🔐 save_auth_token called with token length: {}", token.len());
+    
+    {
+        let mut auth_token = state.auth_token.lock()
+            .map_err(|_| "Failed to lock auth token")?;
+        *auth_token = Some(token.clone());
+        println!("✅ Token saved to app state");
+    }
+    remember_server_login(&state, Some(token.clone()));
+    
+    // Also save to persistent storage
+    let credentials = StoredCredentials {
+        access_token: token,
+        user_email: None,
+        user_name: None,
+        stored_at: chrono::Utc::now().to_rfc3339(),
+    };
+    
+    println!("💾 Attempting to save credentials to persistent storage");
+    save_credentials(&app, &credentials)?;
+    println!("✅ save_auth_token completed successfully");
+    
+    Ok(())
+}
+
+#[tauri::command]
+async fn check_auth_status(state: State<'_, AppState>) -> Result<bool, String> {
+    let auth_token = state.auth_token.lock()
+        .map_err(|_| "Failed to lock auth token")?;
+    Ok(auth_token.is_some())
+}
+
+#[tauri::command]
+async fn logout(state: State<'_, AppState>, app: AppHandle) -> Result<(), String> {
+    // Clear auth token
+    {
+        let mut auth_token = state.auth_token.lock()
+            .map_err(|_| "Failed to lock auth token")?;
+        *auth_token = None;
+    } // Drop the mutex guard here
+    remember_server_login(&state, None);
+    
+    // Clear stored credentials
+    clear_stored_credentials(app).await?;
+    
+    // Clear editor state
+    {
+        let mut editor_state = state.editor_state.lock()
+            .map_err(|_| "Failed to lock editor state")?;
+        editor_state.projects = Vec::new();
+    } // Drop the mutex guard here too
+    
+    Ok(())
+}
+
+#[tauri::command]
+async fn authenticate_with_username_password(
+    _email: String,
+    _password: String,
+    _state: State<'_, AppState>
+) -> Result<TokenResponse, String> {
     // This is a placeholder - in a real implementation you'd hash the password
     // and verify against a database
     Err("Username/password authentication not yet implemented".to_string())
@@ -548,6 +1837,7 @@ async fn handle_oauth_callback(
         *auth_token = Some(token.clone());
         println!("✅ OAuth token saved to app state");
     }
+    remember_server_login(&state, Some(token.clone()));
     
     // Save credentials to file for persistence
     let credentials = StoredCredentials {
@@ -573,7 +1863,7 @@ async fn validate_token(
     
     // Try to make an authenticated request to validate the token
     let response = client
-        .get(&format!("{}/auth/check", state.server_url))
+        .get(&format!("{}/auth/check", state.server_url()))
         .bearer_auth(&token)
         .send()
         .await
@@ -591,12 +1881,22 @@ async fn auto_login(
     
     // Try to load stored credentials
     println!("📂 Step 1: Loading stored credentials...");
-    let credentials = match load_credentials(&app)? {
-        Some(creds) => {
+    let server_token = ServerStore::load().token_for(&state.server_url());
+    let credentials = match (server_token, load_credentials(&app)?) {
+        (Some(token), _) => {
+            println!("✅ Found a stored login for {}", state.server_url());
+            StoredCredentials {
+                access_token: token,
+                user_email: None,
+                user_name: None,
+                stored_at: chrono::Utc::now().to_rfc3339(),
+            }
+        },
+        (None, Some(creds)) => {
             println!("✅ Found stored credentials!");
             creds
         },
-        None => {
+        (None, None) => {
             println!("❌ No stored credentials found");
             println!("🔍 === AUTO-LOGIN PROCESS ENDED (NO CREDENTIALS) ===");
             return Ok(false);
@@ -615,6 +1915,7 @@ async fn auto_login(
     
     if !is_valid {
         println!("❌ Stored token is invalid, removing credentials");
+        remember_server_login(&state, None);
         clear_stored_credentials(app).await?;
         println!("🔍 === AUTO-LOGIN PROCESS ENDED (INVALID TOKEN) ===");
         return Ok(false);
@@ -627,9 +1928,10 @@ async fn auto_login(
     {
         let mut auth_token = state.auth_token.lock()
             .map_err(|_| "Failed to lock auth token")?;
-        *auth_token = Some(credentials.access_token);
+        *auth_token = Some(credentials.access_token.clone());
         println!("✅ Token restored to app state");
     }
+    remember_server_login(&state, Some(credentials.access_token));
     
     println!("🎉 === AUTO-LOGIN PROCESS COMPLETED SUCCESSFULLY ===");
     Ok(true)
@@ -648,6 +1950,112 @@ async fn clear_stored_credentials(app: AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+/// Remember the token signed in with (or that there is none) for the
+/// active server, so switching back to it keeps the login
+fn remember_server_login(state: &AppState, token: Option<String>) {
+    let mut store = ServerStore::load();
+    store.set_token(&state.server_url(), token);
+    if let Err(e) = store.save() {
+        eprintln!("❌ Failed to remember login for {}: {}", state.server_url(), e);
+    }
+}
+
+/// The name a server gives itself, if it answers as a Mothership server
+async fn probe_server_name(url: &str) -> Result<String, String> {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(5))
+        .build()
+        .map_err(|e| format!("Failed to create client: {}", e))?;
+    let response = client
+        .get(&format!("{}/capabilities", url))
+        .send()
+        .await
+        .map_err(|e| format!("Could not reach {}: {}", url, e))?;
+    if !response.status().is_success() {
+        return Err(format!("{} is not a Mothership server (HTTP {})", url, response.status()));
+    }
+    let capabilities: mothership_common::protocol::ApiResponse<serde_json::Value> = response
+        .json()
+        .await
+        .map_err(|_| format!("{} is not a Mothership server", url))?;
+    Ok(capabilities.data
+        .and_then(|data| data.get("name").and_then(|name| name.as_str()).map(str::to_string))
+        .unwrap_or_else(|| url.to_string()))
+}
+
+#[tauri::command]
+async fn list_servers() -> Result<Vec<ServerSummary>, String> {
+    Ok(ServerStore::load().summaries())
+}
+
+#[tauri::command]
+async fn add_server(url: String, name: Option<String>) -> Result<Vec<ServerSummary>, String> {
+    let url = servers::normalize_url(&url)?;
+    let probed = probe_server_name(&url).await?;
+    let name = name
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or(probed);
+
+    let mut store = ServerStore::load();
+    store.add(&url, &name);
+    store.save()?;
+    Ok(store.summaries())
+}
+
+#[tauri::command]
+async fn remove_server(url: String, state: State<'_, AppState>) -> Result<Vec<ServerSummary>, String> {
+    if url == state.server_url() {
+        return Err("Switch to another server before removing this one".to_string());
+    }
+    let mut store = ServerStore::load();
+    store.servers.remove(&url);
+    store.save()?;
+    Ok(store.summaries())
+}
+
+/// Make a server the active one, for the app and the CLI, restoring the
+/// login it had. Returns whether that login is still good.
+#[tauri::command]
+async fn switch_server(url: String, state: State<'_, AppState>, app: AppHandle) -> Result<bool, String> {
+    let mut store = ServerStore::load();
+    if !store.servers.contains_key(&url) && url != servers::DEFAULT_SERVER_URL {
+        return Err(format!("Unknown server: {}", url));
+    }
+    store.active_server = Some(url.clone());
+    store.save()?;
+
+    *state.server_url.lock().map_err(|_| "Failed to lock server URL")? = url.clone();
+    *state.auth_token.lock().map_err(|_| "Failed to lock auth token")? = None;
+    state.editor_state.lock().map_err(|_| "Failed to lock editor state")?.projects = Vec::new();
+    state.staging.lock().map_err(|_| "Failed to lock staging")?.clear();
+    state.live_buffers.lock().map_err(|_| "Failed to lock live buffers")?.clear();
+    state.coedit_documents.lock().map_err(|_| "Failed to lock co-edit documents")?.clear();
+
+    let token = match store.token_for(&url) {
+        Some(token) if validate_token(token.clone(), state.clone()).await.unwrap_or(false) => token,
+        Some(_) => {
+            remember_server_login(&state, None);
+            clear_stored_credentials(app).await?;
+            return Ok(false);
+        }
+        None => {
+            clear_stored_credentials(app).await?;
+            return Ok(false);
+        }
+    };
+
+    *state.auth_token.lock().map_err(|_| "Failed to lock auth token")? = Some(token.clone());
+    // The CLI and daemon read credentials.json, so it follows the active server
+    save_credentials(&app, &StoredCredentials {
+        access_token: token,
+        user_email: None,
+        user_name: None,
+        stored_at: chrono::Utc::now().to_rfc3339(),
+    })?;
+    Ok(true)
+}
+
 #[derive(Debug, serde::Deserialize)]
 struct CreateGatewayRequest {
     name: String,
@@ -668,7 +2076,7 @@ async fn create_gateway(
     // First get user info from auth token
     let client = reqwest::Client::new();
     let auth_check_response = client
-        .get(&format!("{}/auth/check", state.server_url))
+        .get(&format!("{}/auth/check", state.server_url()))
         .bearer_auth(&auth_token)
         .send()
         .await
@@ -718,7 +2126,7 @@ async fn create_gateway(
     };
 
     let response = client
-        .post(&format!("{}/gateway/create", state.server_url))
+        .post(&format!("{}/gateway/create", state.server_url()))
         .bearer_auth(&auth_token)
         .json(&gateway_request)
         .send()
@@ -747,6 +2155,266 @@ async fn create_gateway(
     Ok(project)
 }
 
+/// Suggest how to set up a gateway for a directory
+#[tauri::command]
+async fn scan_project_directory(path: String) -> Result<ProjectScan, String> {
+    let dir = PathBuf::from(&path);
+    if !dir.is_dir() {
+        return Err(format!("Not a directory: {}", path));
+    }
+    tokio::task::spawn_blocking(move || gateway_wizard::scan(&dir))
+        .await
+        .map_err(|e| format!("Failed to scan directory: {}", e))
+}
+
+/// Save the wizard's ignore patterns as the project's .mothershipignore
+#[tauri::command]
+async fn write_ignore_file(project_path: String, contents: String) -> Result<(), String> {
+    let path = PathBuf::from(project_path).join(IGNORE_FILE_NAME);
+    let contents = if contents.ends_with('\n') { contents } else { format!("{}\n", contents) };
+    fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Set a new gateway's automatic checkpoint interval and, when given, limit
+/// it to the chosen file types
+#[tauri::command]
+async fn apply_gateway_settings(
+    project_id: String,
+    auto_checkpoint_interval: u64,
+    allowed_file_types: Option<Vec<String>>,
+    state: State<'_, AppState>
+) -> Result<(), String> {
+    let auth_token = state.auth_token.lock()
+        .map_err(|_| "Failed to lock auth token")?
+        .clone()
+        .ok_or("Not authenticated")?;
+    let settings_url = format!("{}/projects/{}/settings", state.server_url(), project_id);
+    let client = reqwest::Client::new();
+
+    let mut request = UpdateProjectSettingsRequest {
+        auto_checkpoint_interval: Some(auto_checkpoint_interval),
+        ..Default::default()
+    };
+    if let Some(allowed_file_types) = allowed_file_types {
+        // Push rules are replaced as a whole, so start from the current ones
+        let response = client
+            .get(&settings_url)
+            .bearer_auth(&auth_token)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to load project settings: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("Failed to load project settings: {}", response.status()));
+        }
+        let api_response: mothership_common::protocol::ApiResponse<mothership_common::ProjectSettings> = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse project settings: {}", e))?;
+        let settings = api_response.data.ok_or_else(|| api_response.error.unwrap_or("Unknown error".to_string()))?;
+
+        request.push_rules = Some(mothership_common::PushRules { restrict_file_types: true, ..settings.push_rules });
+        request.allowed_file_types = Some(allowed_file_types);
+    }
+
+    let response = client
+        .patch(&settings_url)
+        .bearer_auth(&auth_token)
+        .json(&request)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to save project settings: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Failed to save project settings: {}", response.status()));
+    }
+    Ok(())
+}
+
+/// Number of tries for each chunk of the initial upload before giving up
+const CHUNK_UPLOAD_ATTEMPTS: u32 = 3;
+
+/// Upload a new gateway's files in resumable chunks, reporting progress
+/// through `initial_upload_progress` as it goes
+#[tauri::command]
+async fn upload_gateway_files(project_id: String, project_path: String, state: State<'_, AppState>) -> Result<UploadCompletion, String> {
+    let auth_token = state.auth_token.lock()
+        .map_err(|_| "Failed to lock auth token")?
+        .clone()
+        .ok_or("Not authenticated")?;
+    let set_progress = |progress: UploadProgress| {
+        if let Ok(mut current) = state.upload_progress.lock() {
+            *current = Some(progress);
+        }
+    };
+
+    let mut progress = UploadProgress { phase: "scanning".to_string(), files: 0, total_bytes: 0, chunks_done: 0, chunks_total: 0 };
+    set_progress(progress.clone());
+    let dir = PathBuf::from(&project_path);
+    let manifests = {
+        let dir = dir.clone();
+        tokio::task::spawn_blocking(move || gateway_wizard::upload_manifests(&dir))
+            .await
+            .map_err(|e| format!("Failed to scan project: {}", e))??
+    };
+    if manifests.is_empty() {
+        return Ok(UploadCompletion { files_stored: 0, skipped: Vec::new(), rejected: Vec::new() });
+    }
+    progress.files = manifests.len();
+    progress.total_bytes = manifests.iter().map(|manifest| manifest.size).sum();
+
+    // Open a chunked upload session; the server only asks for chunks it doesn't already have
+    let uploads_url = format!("{}/projects/{}/uploads", state.server_url(), project_id);
+    let client = reqwest::Client::new();
+    let response = client
+        .post(&uploads_url)
+        .bearer_auth(&auth_token)
+        .json(&CreateUploadRequest { files: manifests.clone() })
+        .send()
+        .await
+        .map_err(|e| format!("Failed to start upload: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to start upload: {}", response.status()));
+    }
+    let api_response: mothership_common::protocol::ApiResponse<UploadStatus> = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse upload status: {}", e))?;
+    let status = api_response.data.ok_or_else(|| api_response.error.unwrap_or("Unknown error".to_string()))?;
+
+    let mut chunk_locations: HashMap<&str, (&PathBuf, usize)> = HashMap::new();
+    for manifest in &manifests {
+        for (index, hash) in manifest.chunks.iter().enumerate() {
+            chunk_locations.entry(hash.as_str()).or_insert((&manifest.path, index));
+        }
+    }
+
+    progress.phase = "uploading".to_string();
+    progress.chunks_total = status.total_chunks;
+    progress.chunks_done = status.total_chunks - status.missing_chunks.len();
+    set_progress(progress.clone());
+    for hash in &status.missing_chunks {
+        let (path, index) = chunk_locations.get(hash.as_str())
+            .ok_or_else(|| format!("Server requested unknown chunk {}", hash))?;
+        let bytes = gateway_wizard::read_chunk(&dir.join(path), *index)?;
+
+        let chunk_url = format!("{}/{}/chunks/{}", uploads_url, status.upload_id, hash);
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            match client.put(&chunk_url).bearer_auth(&auth_token).body(bytes.clone()).send().await {
+                Ok(response) if response.status().is_success() => break,
+                Ok(response) if attempt >= CHUNK_UPLOAD_ATTEMPTS => {
+                    return Err(format!("Chunk upload failed ({}). Retry to resume the upload.", response.status()));
+                }
+                Err(e) if attempt >= CHUNK_UPLOAD_ATTEMPTS => {
+                    return Err(format!("Chunk upload failed ({}). Retry to resume the upload.", e));
+                }
+                _ => tokio::time::sleep(std::time::Duration::from_millis(500 * attempt as u64)).await,
+            }
+        }
+        progress.chunks_done += 1;
+        set_progress(progress.clone());
+    }
+
+    // Ask the server to reassemble and verify every file
+    progress.phase = "finishing".to_string();
+    set_progress(progress);
+    let response = client
+        .post(&format!("{}/{}/complete", uploads_url, status.upload_id))
+        .bearer_auth(&auth_token)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to complete upload: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to complete upload: {}", response.status()));
+    }
+    let api_response: mothership_common::protocol::ApiResponse<UploadCompletion> = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse upload result: {}", e))?;
+    api_response.data.ok_or_else(|| api_response.error.unwrap_or("Unknown error".to_string()))
+}
+
+/// How far the running initial upload has got
+#[tauri::command]
+async fn initial_upload_progress(state: State<'_, AppState>) -> Result<Option<UploadProgress>, String> {
+    Ok(state.upload_progress.lock()
+        .map_err(|_| "Failed to lock upload progress")?
+        .clone())
+}
+
+/// An updater asking the active server for releases on a channel. The
+/// server answers for this platform and version through the placeholders.
+fn app_updater(app: &AppHandle, state: &AppState, channel: &str) -> Result<tauri_plugin_updater::Updater, String> {
+    use tauri_plugin_updater::UpdaterExt;
+
+    let endpoint = url::Url::parse(&format!(
+        "{}/gui/update/{}/{{{{target}}}}/{{{{arch}}}}/{{{{current_version}}}}",
+        state.server_url(), channel
+    )).map_err(|e| format!("Invalid update URL: {}", e))?;
+    let mut builder = app.updater_builder()
+        .endpoints(vec![endpoint])
+        .map_err(|e| format!("Failed to set up updater: {}", e))?;
+    let auth_token = state.auth_token.lock()
+        .map_err(|_| "Failed to lock auth token")?
+        .clone();
+    if let Some(token) = auth_token {
+        builder = builder.header("Authorization", format!("Bearer {}", token))
+            .map_err(|e| format!("Failed to set up updater: {}", e))?;
+    }
+    builder.build().map_err(|e| format!("Failed to set up updater: {}", e))
+}
+
+/// The newer version on a release channel (`stable` or `beta`), if any
+#[tauri::command]
+async fn check_for_update(channel: String, state: State<'_, AppState>, app: AppHandle) -> Result<Option<AvailableUpdate>, String> {
+    let update = app_updater(&app, &state, &channel)?
+        .check()
+        .await
+        .map_err(|e| format!("Failed to check for updates: {}", e))?;
+    Ok(update.map(|update| AvailableUpdate {
+        version: update.version.clone(),
+        current_version: update.current_version.clone(),
+        notes: update.body.clone(),
+        date: update.date.map(|date| date.to_string()),
+    }))
+}
+
+/// Download and install the newer version on a channel, reporting progress
+/// through `update_download_progress`, then restart into it
+#[tauri::command]
+async fn install_update(channel: String, state: State<'_, AppState>, app: AppHandle) -> Result<(), String> {
+    let update = app_updater(&app, &state, &channel)?
+        .check()
+        .await
+        .map_err(|e| format!("Failed to check for updates: {}", e))?
+        .ok_or("No update available")?;
+
+    let progress = state.update_progress.clone();
+    let mut downloaded = 0u64;
+    update.download_and_install(
+        |chunk_length, total| {
+            downloaded += chunk_length as u64;
+            if let Ok(mut current) = progress.lock() {
+                *current = Some(UpdateProgress { downloaded, total });
+            }
+        },
+        || println!("✅ Update downloaded, installing"),
+    )
+    .await
+    .map_err(|e| format!("Failed to install update: {}", e))?;
+
+    app.restart();
+}
+
+/// How far the update being installed has downloaded
+#[tauri::command]
+async fn update_download_progress(state: State<'_, AppState>) -> Result<Option<UpdateProgress>, String> {
+    Ok(state.update_progress.lock()
+        .map_err(|_| "Failed to lock update progress")?
+        .clone())
+}
+
 #[tauri::command]
 async fn open_directory_dialog(app: AppHandle) -> Result<Option<String>, String> {
     use tauri_plugin_dialog::DialogExt;
@@ -806,13 +2474,20 @@ fn main() {
             projects: Vec::new(),
         })),
         auth_token: Arc::new(Mutex::new(None)),
-        server_url: "https://api.mothershipproject.dev".to_string(),
+        server_url: Arc::new(Mutex::new(ServerStore::load().active_url())),
         app_handle: None,
+        coedit_documents: Arc::new(Mutex::new(HashMap::new())),
+        staging: Arc::new(Mutex::new(HashMap::new())),
+        live_buffers: Arc::new(Mutex::new(HashMap::new())),
+        upload_progress: Arc::new(Mutex::new(None)),
+        update_progress: Arc::new(Mutex::new(None)),
+        feature_flags: Arc::new(Mutex::new(HashMap::new())),
     };
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_updater::Builder::new().build())
         .manage(app_state)
         .invoke_handler(tauri::generate_handler![
             read_file_content,
@@ -824,6 +2499,39 @@ fn main() {
             authenticate_with_mothership,
             load_projects,
             create_checkpoint,
+            load_chat_history,
+            load_project_activity,
+            load_project_comments,
+            load_notifications,
+            mark_notifications_read,
+            send_chat_message,
+            get_sync_socket_url,
+            coedit_open,
+            coedit_local_edit,
+            coedit_apply_remote,
+            coedit_close,
+            live_open,
+            live_remote_content,
+            live_local_edit,
+            live_resend,
+            live_apply_remote,
+            live_close,
+            search_project,
+            load_file_revisions,
+            load_checkpoint_diff,
+            load_working_diff,
+            stage_hunk,
+            create_staged_checkpoint,
+            list_rifts,
+            current_rift,
+            create_rift,
+            switch_rift,
+            diff_rifts,
+            daemon_sync_state,
+            enroll_gateway,
+            daemon_unregister_project,
+            daemon_pause_project,
+            daemon_resume_project,
             start_google_oauth,
             save_auth_token,
             check_auth_status,
@@ -831,9 +2539,22 @@ fn main() {
             authenticate_with_username_password,
             handle_oauth_callback,
             create_gateway,
+            scan_project_directory,
+            write_ignore_file,
+            apply_gateway_settings,
+            upload_gateway_files,
+            initial_upload_progress,
+            project_features,
+            check_for_update,
+            install_update,
+            update_download_progress,
             validate_token,
             auto_login,
             clear_stored_credentials,
+            list_servers,
+            add_server,
+            remove_server,
+            switch_server,
             open_directory_dialog,
             debug_credentials_file
         ])
@@ -848,6 +2569,12 @@ fn main() {
                 auth_token: app_state.auth_token.clone(),
                 server_url: app_state.server_url.clone(),
                 app_handle: Some(Arc::new(Mutex::new(Some(app_handle.clone())))),
+                coedit_documents: app_state.coedit_documents.clone(),
+                staging: app_state.staging.clone(),
+                live_buffers: app_state.live_buffers.clone(),
+                upload_progress: app_state.upload_progress.clone(),
+                update_progress: app_state.update_progress.clone(),
+                feature_flags: app_state.feature_flags.clone(),
             };
             
             // Start OAuth callback server after Tauri is initialized
@@ -878,6 +2605,7 @@ async fn start_oauth_callback_server(app_state: AppState) {
                     eprintln!("❌ Failed to lock auth token");
                     return AxumResponseJson(serde_json::json!({"success": false, "error": "Failed to save token"}));
                 }
+                remember_server_login(&state, Some(payload.token.clone()));
                 
                 // Also save to persistent storage
                 if let Some(app_handle_arc) = &state.app_handle {
@@ -0,0 +1,148 @@
+//! The servers the app knows and which one it talks to, kept in the same
+//! `connections.json` as the CLI's `mothership connect`, so both see the
+//! same servers and the same active one. Each server remembers the token
+//! last signed in with, so switching back doesn't mean signing in again.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// Server used until another is added and chosen
+pub const DEFAULT_SERVER_URL: &str = "https://api.mothershipproject.dev";
+
+/// A server entry. Fields the CLI keeps that the app doesn't use, like
+/// capabilities and TLS options, are carried through untouched.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerEntry {
+    pub name: String,
+    pub url: String,
+    pub auth_token: Option<String>,
+    pub auth_method: String,
+    pub connected_at: chrono::DateTime<chrono::Utc>,
+    #[serde(flatten)]
+    other: serde_json::Map<String, serde_json::Value>,
+}
+
+/// The contents of `connections.json`, servers keyed by URL
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ServerStore {
+    pub active_server: Option<String>,
+    #[serde(default)]
+    pub servers: HashMap<String, ServerEntry>,
+    #[serde(flatten)]
+    other: serde_json::Map<String, serde_json::Value>,
+}
+
+/// A server as the settings screen lists it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerSummary {
+    pub name: String,
+    pub url: String,
+    pub signed_in: bool,
+    pub active: bool,
+}
+
+fn store_path() -> Result<PathBuf, String> {
+    let dir = dirs::config_dir()
+        .ok_or_else(|| "Could not find config directory".to_string())?
+        .join("mothership");
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    Ok(dir.join("connections.json"))
+}
+
+/// Turn what the user typed into a server URL: `https://` unless a scheme
+/// is given, without a trailing slash
+pub fn normalize_url(input: &str) -> Result<String, String> {
+    let input = input.trim().trim_end_matches('/');
+    if input.is_empty() {
+        return Err("Enter a server URL".to_string());
+    }
+    let with_scheme = if input.contains("://") { input.to_string() } else { format!("https://{}", input) };
+    let url = url::Url::parse(&with_scheme).map_err(|e| format!("Invalid server URL: {}", e))?;
+    if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+        return Err(format!("Invalid server URL: {}", input));
+    }
+    Ok(url.as_str().trim_end_matches('/').to_string())
+}
+
+impl ServerStore {
+    /// Read the store; a missing or unreadable file is an empty store
+    pub fn load() -> Self {
+        store_path()
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize servers: {}", e))?;
+        fs::write(store_path()?, json)
+            .map_err(|e| format!("Failed to save servers: {}", e))
+    }
+
+    /// The server in use: the active one, or the default
+    pub fn active_url(&self) -> String {
+        self.active_server
+            .as_ref()
+            .filter(|url| self.servers.contains_key(*url))
+            .cloned()
+            .unwrap_or_else(|| DEFAULT_SERVER_URL.to_string())
+    }
+
+    /// The token last signed in with on a server
+    pub fn token_for(&self, url: &str) -> Option<String> {
+        self.servers.get(url).and_then(|server| server.auth_token.clone())
+    }
+
+    /// Add a server, or rename one already known
+    pub fn add(&mut self, url: &str, name: &str) {
+        self.servers
+            .entry(url.to_string())
+            .and_modify(|server| server.name = name.to_string())
+            .or_insert_with(|| ServerEntry {
+                name: name.to_string(),
+                url: url.to_string(),
+                auth_token: None,
+                auth_method: "oauth".to_string(),
+                connected_at: chrono::Utc::now(),
+                other: serde_json::Map::new(),
+            });
+    }
+
+    /// Remember (or forget, with `None`) the token for a server, adding
+    /// the server if it's the default one not yet in the file
+    pub fn set_token(&mut self, url: &str, token: Option<String>) {
+        if !self.servers.contains_key(url) {
+            self.add(url, url);
+        }
+        if let Some(server) = self.servers.get_mut(url) {
+            server.auth_token = token;
+        }
+    }
+
+    pub fn summaries(&self) -> Vec<ServerSummary> {
+        let active = self.active_url();
+        let mut summaries: Vec<ServerSummary> = self.servers.values()
+            .map(|server| ServerSummary {
+                name: server.name.clone(),
+                url: server.url.clone(),
+                signed_in: server.auth_token.is_some(),
+                active: server.url == active,
+            })
+            .collect();
+        if !self.servers.contains_key(&active) {
+            summaries.push(ServerSummary {
+                name: "Mothership".to_string(),
+                url: active,
+                signed_in: false,
+                active: true,
+            });
+        }
+        summaries.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()).then(a.url.cmp(&b.url)));
+        summaries
+    }
+}
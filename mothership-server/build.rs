@@ -0,0 +1,5 @@
+// sqlx::migrate! only notices changes to migrations it already embeds;
+// rebuild when one is added
+fn main() {
+    println!("cargo:rerun-if-changed=../migrations");
+}
@@ -0,0 +1,343 @@
+use axum::{
+    extract::{Path, Request, State},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::Response,
+    routing::{delete, get},
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use mothership_common::{auth::Claims, protocol::ApiResponse};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::api_error::ApiError;
+use crate::audit::{self, AuditAction, AuditEvent};
+use crate::database::AccessToken;
+use crate::handlers::authenticate_request;
+use crate::AppState;
+
+/// Prefix that distinguishes personal access tokens from JWTs
+pub const TOKEN_PREFIX: &str = "msp_";
+
+/// Scopes a personal access token can carry
+pub const SCOPE_READ: &str = "read";
+pub const SCOPE_WRITE: &str = "write";
+pub const SCOPE_ADMIN: &str = "admin";
+const VALID_SCOPES: [&str; 3] = [SCOPE_READ, SCOPE_WRITE, SCOPE_ADMIN];
+
+/// Longest lifetime a token can be created with
+const MAX_TOKEN_LIFETIME_DAYS: i64 = 365;
+
+/// Lifetime of the internal JWT a personal access token is exchanged for per request
+const EXCHANGED_TOKEN_LIFETIME_MINUTES: i64 = 5;
+
+/// Personal access token management endpoints
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/auth/tokens", get(list_tokens).post(create_token))
+        .route("/auth/tokens/:token_id", delete(revoke_token))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateTokenRequest {
+    pub name: String,
+    /// Defaults to read + write
+    pub scopes: Option<Vec<String>>,
+    /// Tokens without an expiry stay valid until revoked
+    pub expires_in_days: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreatedToken {
+    /// Plaintext token. Only returned once; the server keeps just its hash.
+    pub token: String,
+    #[serde(flatten)]
+    pub info: AccessToken,
+}
+
+/// SHA-256 of the plaintext token, as stored in the database
+pub(crate) fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+pub(crate) fn generate_token() -> String {
+    format!("{}{}{}", TOKEN_PREFIX, Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// Validate a token name and requested scopes/lifetime, returning the
+/// normalized scopes and expiry or a message for the caller
+pub(crate) fn validate_token_request(request: &CreateTokenRequest) -> Result<(Vec<String>, Option<DateTime<Utc>>), String> {
+    let name = request.name.trim();
+    if name.is_empty() || name.len() > 255 {
+        return Err("Token name must be between 1 and 255 characters".to_string());
+    }
+
+    let mut scopes = request.scopes.clone()
+        .unwrap_or_else(|| vec![SCOPE_READ.to_string(), SCOPE_WRITE.to_string()]);
+    scopes.sort();
+    scopes.dedup();
+    if scopes.is_empty() {
+        return Err("At least one scope is required".to_string());
+    }
+    if let Some(invalid) = scopes.iter().find(|s| !VALID_SCOPES.contains(&s.as_str())) {
+        return Err(format!("Unknown scope '{}'. Valid scopes: {}", invalid, VALID_SCOPES.join(", ")));
+    }
+
+    let expires_at = match request.expires_in_days {
+        Some(days) if !(1..=MAX_TOKEN_LIFETIME_DAYS).contains(&days) => {
+            return Err(format!("expires_in_days must be between 1 and {}", MAX_TOKEN_LIFETIME_DAYS));
+        }
+        Some(days) => Some(Utc::now() + chrono::Duration::days(days)),
+        None => None,
+    };
+
+    Ok((scopes, expires_at))
+}
+
+/// Scope a request needs, based on its route and method
+fn required_scope(method: &Method, path: &str) -> &'static str {
+    if path.starts_with("/admin") {
+        SCOPE_ADMIN
+    } else if path.starts_with("/ws/") {
+        SCOPE_WRITE
+    } else if method == Method::GET || method == Method::HEAD {
+        SCOPE_READ
+    } else {
+        SCOPE_WRITE
+    }
+}
+
+fn has_scope(scopes: &[String], required: &str) -> bool {
+    scopes.iter().any(|scope| {
+        scope == required || (required == SCOPE_READ && scope == SCOPE_WRITE)
+    })
+}
+
+/// Middleware: exchange a personal access token for a short-lived JWT so every
+/// existing handler accepts it unchanged. Scopes are enforced here.
+pub async fn exchange_access_token(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let header_token = request.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .filter(|t| t.starts_with(TOKEN_PREFIX))
+        .map(str::to_string);
+
+    // WebSocket clients pass their token as a query parameter
+    let query_token = request.uri().query().and_then(|query| {
+        url::form_urlencoded::parse(query.as_bytes())
+            .find(|(key, value)| key == "token" && value.starts_with(TOKEN_PREFIX))
+            .map(|(_, value)| value.into_owned())
+    });
+
+    let Some(plaintext) = header_token.clone().or(query_token) else {
+        return Ok(next.run(request).await);
+    };
+
+    let path = request.uri().path().to_string();
+    if path.starts_with("/auth/tokens") || path.starts_with("/auth/sessions") {
+        warn!("🔒 Personal access token used to manage tokens or sessions");
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let token = match state.db.use_access_token(&hash_token(&plaintext)).await {
+        Ok(Some(token)) => token,
+        Ok(None) => return Err(StatusCode::UNAUTHORIZED),
+        Err(e) => {
+            error!("Failed to look up personal access token: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let required = required_scope(request.method(), &path);
+    if !has_scope(&token.scopes, required) {
+        warn!("🔒 Token '{}' lacks '{}' scope for {} {}", token.name, required, request.method(), path);
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let user = match state.db.get_user(token.user_id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return Err(StatusCode::UNAUTHORIZED),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    let now = Utc::now();
+    let claims = Claims {
+        sub: user.id.to_string(),
+        machine_id: format!("pat:{}", token.id),
+        username: user.username,
+        email: Some(user.email),
+        iat: now.timestamp(),
+        exp: (now + chrono::Duration::minutes(EXCHANGED_TOKEN_LIFETIME_MINUTES)).timestamp(),
+        aud: "mothership".to_string(),
+        iss: "mothership-server".to_string(),
+        jti: None,
+    };
+    let jwt = state.auth.encode_token(&claims)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if header_token.is_some() {
+        let value = HeaderValue::from_str(&format!("Bearer {}", jwt))
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        request.headers_mut().insert(header::AUTHORIZATION, value);
+    } else {
+        let query = request.uri().query().unwrap_or_default();
+        let rewritten: String = url::form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(url::form_urlencoded::parse(query.as_bytes()).map(|(key, value)| {
+                if key == "token" {
+                    (key.into_owned(), jwt.clone())
+                } else {
+                    (key.into_owned(), value.into_owned())
+                }
+            }))
+            .finish();
+        *request.uri_mut() = format!("{}?{}", path, rewritten)
+            .parse()
+            .map_err(|_| StatusCode::BAD_REQUEST)?;
+    }
+
+    Ok(next.run(request).await)
+}
+
+/// Mint a new personal access token for the authenticated user
+async fn create_token(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<CreateTokenRequest>,
+) -> Result<Json<ApiResponse<CreatedToken>>, ApiError> {
+    let user_id = authenticate_request(&state, &headers)?;
+
+    let (scopes, expires_at) = match validate_token_request(&request) {
+        Ok(validated) => validated,
+        Err(message) => return Err(ApiError::bad_request(message)),
+    };
+    let name = request.name.trim();
+
+    if scopes.iter().any(|s| s == SCOPE_ADMIN) {
+        match state.db.user_is_admin(user_id).await {
+            Ok(true) => {}
+            Ok(false) => return Err(StatusCode::FORBIDDEN.into()),
+            Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR.into()),
+        }
+    }
+
+    let plaintext = generate_token();
+    let info = state.db
+        .create_access_token(user_id, name, &hash_token(&plaintext), &scopes, expires_at)
+        .await
+        .map_err(|e| {
+            error!("Failed to create personal access token: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    info!("🔑 Created personal access token '{}' for user {}", info.name, user_id);
+    audit::record(&state.db, AuditEvent::new(AuditAction::TokenCreated, Some(user_id))
+        .target(info.id.to_string())
+        .details(serde_json::json!({ "name": info.name, "scopes": info.scopes }))).await;
+
+    Ok(Json(ApiResponse::success(CreatedToken { token: plaintext, info })))
+}
+
+/// List the authenticated user's personal access tokens
+async fn list_tokens(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<ApiResponse<Vec<AccessToken>>>, StatusCode> {
+    let user_id = authenticate_request(&state, &headers)?;
+
+    let tokens = state.db.list_access_tokens(user_id).await
+        .map_err(|e| {
+            error!("Failed to list personal access tokens: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ApiResponse::success(tokens)))
+}
+
+/// Revoke one of the authenticated user's personal access tokens
+async fn revoke_token(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(token_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    let user_id = authenticate_request(&state, &headers)?;
+
+    let revoked = state.db.revoke_access_token(user_id, token_id).await
+        .map_err(|e| {
+            error!("Failed to revoke personal access token: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if !revoked {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    info!("🗑️ Revoked personal access token {} for user {}", token_id, user_id);
+    audit::record(&state.db, AuditEvent::new(AuditAction::TokenRevoked, Some(user_id))
+        .target(token_id.to_string())).await;
+
+    Ok(Json(ApiResponse::success("Token revoked".to_string())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_client::TestClient;
+    use mothership_common::Project;
+
+    #[test]
+    fn test_required_scope_follows_route_and_method() {
+        assert_eq!(required_scope(&Method::GET, "/admin/users"), SCOPE_ADMIN);
+        assert_eq!(required_scope(&Method::POST, "/admin/users"), SCOPE_ADMIN);
+        assert_eq!(required_scope(&Method::GET, "/ws/sync"), SCOPE_WRITE);
+        assert_eq!(required_scope(&Method::GET, "/projects"), SCOPE_READ);
+        assert_eq!(required_scope(&Method::HEAD, "/projects"), SCOPE_READ);
+        assert_eq!(required_scope(&Method::POST, "/gateway/create"), SCOPE_WRITE);
+        assert_eq!(required_scope(&Method::DELETE, "/projects/x"), SCOPE_WRITE);
+    }
+
+    #[test]
+    fn test_write_scope_implies_read_but_nothing_else() {
+        let write = vec![SCOPE_WRITE.to_string()];
+        assert!(has_scope(&write, SCOPE_READ));
+        assert!(has_scope(&write, SCOPE_WRITE));
+        assert!(!has_scope(&write, SCOPE_ADMIN));
+
+        let read = vec![SCOPE_READ.to_string()];
+        assert!(has_scope(&read, SCOPE_READ));
+        assert!(!has_scope(&read, SCOPE_WRITE));
+
+        let admin = vec![SCOPE_ADMIN.to_string()];
+        assert!(!has_scope(&admin, SCOPE_READ));
+        assert!(!has_scope(&[], SCOPE_READ));
+    }
+
+    #[tokio::test]
+    async fn test_access_token_cannot_manage_tokens_or_sessions() {
+        let mut client = TestClient::new().await;
+        client.sign_in("owner").await;
+        let project: Project = client.post("/gateway/create", &serde_json::json!({
+            "name": "tokens",
+            "description": "",
+            "project_path": "/tmp/tokens",
+        })).await.data();
+        let created: serde_json::Value = client.post("/auth/tokens", &serde_json::json!({
+            "name": "ci",
+            "scopes": ["read", "write"],
+        })).await.data();
+        client.use_token(created["token"].as_str().unwrap().to_string());
+
+        assert_eq!(client.get(&format!("/projects/{}", project.id)).await.status, StatusCode::OK);
+        assert_eq!(client.get("/auth/tokens").await.status, StatusCode::FORBIDDEN);
+        let minted = client.post("/auth/tokens", &serde_json::json!({ "name": "another" })).await;
+        assert_eq!(minted.status, StatusCode::FORBIDDEN);
+        assert_eq!(client.get("/auth/sessions").await.status, StatusCode::FORBIDDEN);
+    }
+}
@@ -0,0 +1,246 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    routing::get,
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use mothership_common::{
+    protocol::{ActivityEvent, ActivityKind, ActivityPage, ApiResponse},
+    CheckpointId, ProjectId, Rift, RiftId, UserId,
+};
+use serde::Deserialize;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use tracing::error;
+
+use crate::api_error::ApiError;
+use crate::audit::AuditAction;
+use crate::database::{AuditEntry, AuditFilter, Database};
+use crate::handlers::authenticate_request;
+use crate::rift_access;
+use crate::AppState;
+
+/// Default and maximum page size for the activity feed
+const DEFAULT_ACTIVITY_LIMIT: usize = 50;
+const MAX_ACTIVITY_LIMIT: usize = 500;
+
+/// Audit log actions that show up in the activity feed
+const AUDITED_ACTIVITY: [(AuditAction, ActivityKind); 3] = [
+    (AuditAction::MemberAdded, ActivityKind::MemberAdded),
+    (AuditAction::MemberRemoved, ActivityKind::MemberRemoved),
+    (AuditAction::CheckpointRestored, ActivityKind::Restore),
+];
+
+/// Project activity feed endpoint
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/projects/:id/activity", get(project_activity))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ActivityQuery {
+    pub limit: Option<usize>,
+    /// Only events older than this (the previous page's `next_before`)
+    pub before: Option<DateTime<Utc>>,
+}
+
+/// Checkpoints, merges, new rifts, membership changes and restores across
+/// the project's rifts, newest first
+async fn project_activity(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(project_id): Path<ProjectId>,
+    Query(query): Query<ActivityQuery>,
+) -> Result<Json<ApiResponse<ActivityPage>>, ApiError> {
+    let user_id = authenticate_request(&state, &headers)?;
+
+    match state.db.get_project(project_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return Err(StatusCode::NOT_FOUND.into()),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR.into()),
+    }
+    match state.db.user_has_project_access(user_id, project_id).await {
+        Ok(true) => {}
+        Ok(false) => return Err(StatusCode::FORBIDDEN.into()),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR.into()),
+    }
+
+    let limit = query.limit.unwrap_or(DEFAULT_ACTIVITY_LIMIT).clamp(1, MAX_ACTIVITY_LIMIT);
+    let events = collect_activity(&state, project_id, user_id, query.before, limit).await
+        .map_err(|e| {
+            error!("Failed to load activity for project {}: {}", project_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let next_before = match events.last() {
+        Some(oldest) if events.len() == limit => Some(oldest.occurred_at),
+        _ => None,
+    };
+    Ok(Json(ApiResponse::success(ActivityPage { events, next_before })))
+}
+
+/// Each source contributes its newest `limit` events older than `before`;
+/// the newest `limit` of them all make the page
+async fn collect_activity(
+    state: &AppState,
+    project_id: ProjectId,
+    user_id: UserId,
+    before: Option<DateTime<Utc>>,
+    limit: usize,
+) -> anyhow::Result<Vec<ActivityEvent>> {
+    let is_older = |at: DateTime<Utc>| before.is_none_or(|before| at < before);
+    let mut usernames = Usernames::default();
+
+    // Private rifts only show up for the people they're shared with
+    let mut rifts: HashMap<RiftId, Rift> = HashMap::new();
+    for rift in state.db.get_project_rifts(project_id).await? {
+        if rift_access::resolve_role(&state.db, &rift, user_id).await?.is_some() {
+            rifts.insert(rift.id, rift);
+        }
+    }
+
+    let mut events = Vec::new();
+    for rift in rifts.values() {
+        if is_older(rift.created_at) {
+            let owner = match state.db.get_rift_access(rift.id).await? {
+                Some((Some(owner), _)) => usernames.get(&state.db, owner).await?,
+                _ => None,
+            };
+            events.push(ActivityEvent {
+                kind: ActivityKind::RiftCreated,
+                occurred_at: rift.created_at,
+                actor: owner,
+                rift_id: Some(rift.id),
+                rift_name: Some(rift.name.clone()),
+                checkpoint_id: None,
+                summary: format!("Created rift '{}'", rift.name),
+            });
+        }
+
+        let mut checkpoints = state.sync.storage.list_checkpoints(rift.id).await?;
+        checkpoints.retain(|cp| is_older(cp.timestamp));
+        checkpoints.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then(b.id.cmp(&a.id)));
+        checkpoints.truncate(limit);
+        for checkpoint in checkpoints {
+            let (kind, summary) = match checkpoint.merged_from {
+                Some(source) => {
+                    let source = rifts.get(&source).map(|r| r.name.as_str()).unwrap_or("another rift");
+                    (ActivityKind::Merge, checkpoint.message.unwrap_or_else(|| format!("Merged '{}'", source)))
+                }
+                None => {
+                    let fallback = if checkpoint.auto_generated { "Automatic checkpoint" } else { "Checkpoint" };
+                    (ActivityKind::Checkpoint, checkpoint.message.unwrap_or_else(|| fallback.to_string()))
+                }
+            };
+            events.push(ActivityEvent {
+                kind,
+                occurred_at: checkpoint.timestamp,
+                actor: usernames.get(&state.db, checkpoint.author).await?,
+                rift_id: Some(rift.id),
+                rift_name: Some(rift.name.clone()),
+                checkpoint_id: Some(checkpoint.id),
+                summary,
+            });
+        }
+    }
+
+    for (action, kind) in AUDITED_ACTIVITY {
+        let filter = AuditFilter {
+            project_id: Some(project_id),
+            to: before,
+            action: Some(action.as_str().to_string()),
+            ..Default::default()
+        };
+        let (entries, _) = state.db.list_audit_entries(&filter, limit as i64, 0).await?;
+        for entry in entries {
+            if let Some(event) = audited_event(state, &rifts, &mut usernames, kind, entry).await? {
+                events.push(event);
+            }
+        }
+    }
+
+    events.sort_by_key(|event| Reverse(event.occurred_at));
+    events.truncate(limit);
+    Ok(events)
+}
+
+/// Describe a membership change or restore from the audit log. Restores of
+/// checkpoints in rifts the user can't see are left out.
+async fn audited_event(
+    state: &AppState,
+    rifts: &HashMap<RiftId, Rift>,
+    usernames: &mut Usernames,
+    kind: ActivityKind,
+    entry: AuditEntry,
+) -> anyhow::Result<Option<ActivityEvent>> {
+    let details: serde_json::Value = serde_json::from_str(&entry.details).unwrap_or_default();
+    let actor = match entry.actor_id {
+        Some(actor_id) => usernames.get(&state.db, actor_id).await?,
+        None => None,
+    };
+    let target = entry.target.unwrap_or_default();
+
+    let (rift, checkpoint_id, summary) = match kind {
+        ActivityKind::Restore => {
+            let Ok(checkpoint_id) = target.parse::<CheckpointId>() else {
+                return Ok(None);
+            };
+            let Some(checkpoint) = state.sync.storage.load_checkpoint(checkpoint_id).await? else {
+                return Ok(None);
+            };
+            let Some(rift) = rifts.get(&checkpoint.rift_id) else {
+                return Ok(None);
+            };
+            let files = details["file_count"].as_u64().unwrap_or(0);
+            let summary = format!(
+                "Restored {} file{} from checkpoint {}",
+                files,
+                if files == 1 { "" } else { "s" },
+                &checkpoint_id.to_string()[..8],
+            );
+            (Some(rift), Some(checkpoint_id), summary)
+        }
+        _ => {
+            let member = match details["service_account"].as_str() {
+                Some(account) => Some(format!("service account '{}'", account)),
+                None => match target.parse::<UserId>() {
+                    Ok(member_id) => usernames.get(&state.db, member_id).await?,
+                    Err(_) => None,
+                },
+            };
+            let member = member.unwrap_or_else(|| "a former user".to_string());
+            let summary = if kind == ActivityKind::MemberAdded {
+                format!("Added {} to the project", member)
+            } else {
+                format!("Removed {} from the project", member)
+            };
+            (None, None, summary)
+        }
+    };
+
+    Ok(Some(ActivityEvent {
+        kind,
+        occurred_at: entry.occurred_at,
+        actor,
+        rift_id: rift.map(|r| r.id),
+        rift_name: rift.map(|r| r.name.clone()),
+        checkpoint_id,
+        summary,
+    }))
+}
+
+/// Usernames looked up so far, so each author is fetched once per page
+#[derive(Default)]
+struct Usernames(HashMap<UserId, Option<String>>);
+
+impl Usernames {
+    async fn get(&mut self, db: &Database, user_id: UserId) -> anyhow::Result<Option<String>> {
+        if let Some(username) = self.0.get(&user_id) {
+            return Ok(username.clone());
+        }
+        let username = db.get_user(user_id).await?.map(|user| user.username);
+        self.0.insert(user_id, username.clone());
+        Ok(username)
+    }
+}
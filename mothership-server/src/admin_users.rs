@@ -0,0 +1,366 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, post, put},
+    Json, Router,
+};
+use mothership_common::{protocol::ApiResponse, User, UserId, UserRole};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::api_error::ApiError;
+use crate::audit::{self, AuditAction, AuditEvent};
+use crate::database::ManagedUser;
+use crate::handlers::require_admin;
+use crate::AppState;
+
+/// Users per page when the caller doesn't ask for a size
+const DEFAULT_PAGE_SIZE: i64 = 50;
+
+/// Largest page the list endpoint will return
+const MAX_PAGE_SIZE: i64 = 200;
+
+/// User management endpoints (admins only; admin roles are SuperAdmin-only)
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/admin/users", get(list_users))
+        .route("/admin/users/:user_id", get(get_user).delete(delete_user))
+        .route("/admin/users/:user_id/disable", post(disable_user))
+        .route("/admin/users/:user_id/enable", post(enable_user))
+        .route("/admin/users/:user_id/role", put(change_role))
+        .route("/admin/users/:user_id/logout", post(force_logout))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListUsersQuery {
+    /// 1-based page number
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserPage {
+    pub users: Vec<ManagedUser>,
+    pub page: i64,
+    pub per_page: i64,
+    pub total: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChangeRoleRequest {
+    pub role: UserRole,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteUserQuery {
+    /// Username that takes over the deleted user's rifts, memberships and mirrors
+    pub reassign_to: String,
+}
+
+fn is_admin_role(role: &UserRole) -> bool {
+    matches!(role, UserRole::Admin | UserRole::SuperAdmin)
+}
+
+/// Authenticate the request and load the calling admin
+async fn calling_admin(state: &AppState, headers: &HeaderMap) -> Result<User, StatusCode> {
+    let user_id = require_admin(state, headers, "manage users").await?;
+
+    match state.db.get_user(user_id).await {
+        Ok(Some(user)) => Ok(user),
+        Ok(None) => Err(StatusCode::UNAUTHORIZED),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// Load the user an admin wants to modify.
+///
+/// SECURITY CHECK: Admins can't modify themselves (no self-lockout) and only
+/// SuperAdmins can modify other admins.
+async fn modifiable_user(state: &AppState, admin: &User, user_id: UserId) -> Result<ManagedUser, StatusCode> {
+    if admin.id == user_id {
+        warn!("🔒 Admin {} attempted to modify their own account", admin.username);
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let target = match state.db.get_managed_user(user_id).await {
+        Ok(Some(target)) => target,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            error!("Failed to load user {}: {}", user_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    if is_admin_role(&target.role) && admin.role != UserRole::SuperAdmin {
+        warn!("🔒 Admin {} attempted to modify admin {}", admin.username, target.username);
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok(target)
+}
+
+/// Revoke every session of a user so their tokens stop working immediately
+async fn end_sessions(state: &AppState, user_id: UserId) -> Result<usize, StatusCode> {
+    let sessions = state.db.revoke_user_sessions(user_id).await
+        .map_err(|e| {
+            error!("Failed to revoke sessions of user {}: {}", user_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    for session_id in &sessions {
+        state.auth.revoke(session_id.to_string());
+    }
+
+    Ok(sessions.len())
+}
+
+/// Refetch a user after a change
+async fn reload(state: &AppState, user_id: UserId) -> Result<ManagedUser, StatusCode> {
+    state.db.get_managed_user(user_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// List users, a page at a time
+async fn list_users(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ListUsersQuery>,
+) -> Result<Json<ApiResponse<UserPage>>, StatusCode> {
+    require_admin(&state, &headers, "manage users").await?;
+
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+
+    let (users, total) = state.db.list_managed_users(per_page, (page - 1) * per_page).await
+        .map_err(|e| {
+            error!("Failed to list users: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ApiResponse::success(UserPage { users, page, per_page, total })))
+}
+
+async fn get_user(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(user_id): Path<UserId>,
+) -> Result<Json<ApiResponse<ManagedUser>>, StatusCode> {
+    require_admin(&state, &headers, "manage users").await?;
+    Ok(Json(ApiResponse::success(reload(&state, user_id).await?)))
+}
+
+/// Disable a user: they keep their data but can't log in, use tokens, or
+/// keep their current sessions
+async fn disable_user(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(user_id): Path<UserId>,
+) -> Result<Json<ApiResponse<ManagedUser>>, ApiError> {
+    let admin = calling_admin(&state, &headers).await?;
+    let target = modifiable_user(&state, &admin, user_id).await?;
+
+    let changed = state.db.set_user_disabled(target.id, true).await
+        .map_err(|e| {
+            error!("Failed to disable user {}: {}", target.id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if !changed {
+        return Err(ApiError::conflict(format!("User '{}' is already disabled", target.username)));
+    }
+    state.auth.set_user_disabled(target.id, true);
+    let sessions = end_sessions(&state, target.id).await?;
+
+    info!("⛔ {} disabled user {} ({} sessions ended)", admin.username, target.username, sessions);
+    audit::record(&state.db, AuditEvent::new(AuditAction::UserDisabled, Some(admin.id))
+        .target(target.id.to_string())
+        .details(serde_json::json!({ "username": target.username, "sessions_revoked": sessions }))).await;
+
+    Ok(Json(ApiResponse::success(reload(&state, target.id).await?)))
+}
+
+/// Re-enable a disabled user
+async fn enable_user(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(user_id): Path<UserId>,
+) -> Result<Json<ApiResponse<ManagedUser>>, ApiError> {
+    let admin = calling_admin(&state, &headers).await?;
+    let target = modifiable_user(&state, &admin, user_id).await?;
+
+    let changed = state.db.set_user_disabled(target.id, false).await
+        .map_err(|e| {
+            error!("Failed to enable user {}: {}", target.id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if !changed {
+        return Err(ApiError::conflict(format!("User '{}' is not disabled", target.username)));
+    }
+    state.auth.set_user_disabled(target.id, false);
+
+    info!("✅ {} re-enabled user {}", admin.username, target.username);
+    audit::record(&state.db, AuditEvent::new(AuditAction::UserEnabled, Some(admin.id))
+        .target(target.id.to_string())
+        .details(serde_json::json!({ "username": target.username }))).await;
+
+    Ok(Json(ApiResponse::success(reload(&state, target.id).await?)))
+}
+
+/// Change a user's role
+async fn change_role(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(user_id): Path<UserId>,
+    Json(request): Json<ChangeRoleRequest>,
+) -> Result<Json<ApiResponse<ManagedUser>>, ApiError> {
+    let admin = calling_admin(&state, &headers).await?;
+    let target = modifiable_user(&state, &admin, user_id).await?;
+
+    // SECURITY CHECK: Only SuperAdmins can hand out admin roles
+    if is_admin_role(&request.role) && admin.role != UserRole::SuperAdmin {
+        warn!("🔒 Admin {} attempted to grant {:?} to {}", admin.username, request.role, target.username);
+        return Err(StatusCode::FORBIDDEN.into());
+    }
+    if target.service_account && request.role != UserRole::User {
+        return Err(ApiError::bad_request("Service accounts can't be admins"));
+    }
+    if target.role == request.role {
+        return Ok(Json(ApiResponse::success(target)));
+    }
+
+    state.db.set_user_role(target.id, request.role.clone()).await
+        .map_err(|e| {
+            error!("Failed to change role of user {}: {}", target.id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    info!("👑 {} changed role of {} from {:?} to {:?}", admin.username, target.username, target.role, request.role);
+    audit::record(&state.db, AuditEvent::new(AuditAction::UserRoleChanged, Some(admin.id))
+        .target(target.id.to_string())
+        .details(serde_json::json!({
+            "username": target.username,
+            "from": target.role,
+            "to": request.role,
+        }))).await;
+
+    Ok(Json(ApiResponse::success(reload(&state, target.id).await?)))
+}
+
+/// End all of a user's login sessions
+async fn force_logout(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(user_id): Path<UserId>,
+) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    let admin = calling_admin(&state, &headers).await?;
+    let target = modifiable_user(&state, &admin, user_id).await?;
+
+    let sessions = end_sessions(&state, target.id).await?;
+
+    info!("🚫 {} logged out user {} ({} sessions)", admin.username, target.username, sessions);
+    audit::record(&state.db, AuditEvent::new(AuditAction::UserLoggedOut, Some(admin.id))
+        .target(target.id.to_string())
+        .details(serde_json::json!({ "username": target.username, "sessions_revoked": sessions }))).await;
+
+    Ok(Json(ApiResponse::success(format!("Revoked {} sessions of {}", sessions, target.username))))
+}
+
+/// Delete a user, reassigning the rifts, memberships and mirrors they own
+async fn delete_user(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(user_id): Path<UserId>,
+    Query(query): Query<DeleteUserQuery>,
+) -> Result<Json<ApiResponse<String>>, ApiError> {
+    let admin = calling_admin(&state, &headers).await?;
+    let target = modifiable_user(&state, &admin, user_id).await?;
+
+    let successor = match state.db.get_user_by_username(&query.reassign_to).await {
+        Ok(Some(user)) if user.id != target.id => user,
+        Ok(Some(_)) => return Err(ApiError::bad_request("Can't reassign a user's data to themselves")),
+        Ok(None) => return Err(ApiError::not_found(format!("User '{}' not found", query.reassign_to))),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR.into()),
+    };
+
+    // Revoke first so no token outlives the account
+    end_sessions(&state, target.id).await?;
+
+    state.db.delete_user_reassigning(target.id, successor.id).await
+        .map_err(|e| {
+            error!("Failed to delete user {}: {}", target.id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    info!("🗑️ {} deleted user {} (data reassigned to {})", admin.username, target.username, successor.username);
+    audit::record(&state.db, AuditEvent::new(AuditAction::UserDeleted, Some(admin.id))
+        .target(target.id.to_string())
+        .details(serde_json::json!({
+            "username": target.username,
+            "email": target.email,
+            "reassigned_to": successor.id,
+        }))).await;
+
+    Ok(Json(ApiResponse::success(format!(
+        "Deleted {}; their data now belongs to {}",
+        target.username, successor.username
+    ))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_client::TestClient;
+
+    fn role(role: &str) -> Vec<u8> {
+        serde_json::to_vec(&serde_json::json!({ "role": role })).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_only_super_admins_hand_out_or_touch_admin_roles() {
+        let mut client = TestClient::new().await;
+        let user = client.sign_in("user").await;
+        let other_admin = client.sign_in("other-admin").await;
+        client.state.db.set_user_role(other_admin.id, UserRole::Admin).await.unwrap();
+        let admin = client.sign_in("admin").await;
+        client.state.db.set_user_role(admin.id, UserRole::Admin).await.unwrap();
+
+        let promote = client.put(&format!("/admin/users/{}/role", user.id), &role("Admin")).await;
+        assert_eq!(promote.status, StatusCode::FORBIDDEN);
+        let demote = client.put(&format!("/admin/users/{}/role", other_admin.id), &role("User")).await;
+        assert_eq!(demote.status, StatusCode::FORBIDDEN);
+        let disable = client.post(&format!("/admin/users/{}/disable", other_admin.id), &()).await;
+        assert_eq!(disable.status, StatusCode::FORBIDDEN);
+
+        client.state.db.set_user_role(admin.id, UserRole::SuperAdmin).await.unwrap();
+        let promoted: serde_json::Value = client.put(&format!("/admin/users/{}/role", user.id), &role("Admin")).await.data();
+        assert_eq!(promoted["role"], "Admin");
+    }
+
+    #[tokio::test]
+    async fn test_admins_cannot_modify_their_own_account() {
+        let mut client = TestClient::new().await;
+        let admin = client.sign_in("admin").await;
+        client.state.db.set_user_role(admin.id, UserRole::SuperAdmin).await.unwrap();
+
+        let demote = client.put(&format!("/admin/users/{}/role", admin.id), &role("User")).await;
+        assert_eq!(demote.status, StatusCode::FORBIDDEN);
+        let disable = client.post(&format!("/admin/users/{}/disable", admin.id), &()).await;
+        assert_eq!(disable.status, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_users_token_is_refused() {
+        let mut client = TestClient::new().await;
+        let user = client.sign_in("user").await;
+        let user_token = client.token().unwrap();
+        assert_eq!(client.get("/auth/sessions").await.status, StatusCode::OK);
+
+        let admin = client.sign_in("admin").await;
+        client.state.db.set_user_role(admin.id, UserRole::Admin).await.unwrap();
+        let disabled: serde_json::Value = client.post(&format!("/admin/users/{}/disable", user.id), &()).await.data();
+        assert!(disabled["disabled_at"].is_string());
+
+        client.use_token(user_token);
+        assert_eq!(client.get("/auth/sessions").await.status, StatusCode::UNAUTHORIZED);
+    }
+}
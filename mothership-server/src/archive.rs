@@ -0,0 +1,110 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::post,
+    Json, Router,
+};
+use mothership_common::{
+    protocol::{ApiResponse, ArchiveProjectRequest, ProjectArchiveStatus},
+    Project, ProjectId, UserId,
+};
+use tracing::{error, info, warn};
+
+use crate::audit::{self, AuditAction, AuditEvent};
+use crate::handlers::authenticate_request;
+use crate::AppState;
+
+/// Project archive endpoints
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/projects/:id/archive", post(archive_project))
+        .route("/projects/:id/unarchive", post(unarchive_project))
+}
+
+/// Reject writes to an archived project with 423 Locked
+pub fn ensure_writable(state: &AppState, project_id: ProjectId) -> Result<(), StatusCode> {
+    if state.sync.archived_at(project_id).is_some() {
+        warn!("🗄️ Rejected write to archived project {}", project_id);
+        return Err(StatusCode::LOCKED);
+    }
+    Ok(())
+}
+
+/// Authenticate the request and load a project the caller is a member of
+async fn member_project(state: &AppState, headers: &HeaderMap, project_id: ProjectId) -> Result<(UserId, Project), StatusCode> {
+    let user_id = authenticate_request(state, headers)?;
+
+    let project = match state.db.get_project(project_id).await {
+        Ok(Some(project)) => project,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    if !state.db.user_has_project_access(user_id, project_id).await.unwrap_or(false) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok((user_id, project))
+}
+
+/// Archive a project: history and beam keep working, writes are refused
+async fn archive_project(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(project_id): Path<ProjectId>,
+    request: Option<Json<ArchiveProjectRequest>>,
+) -> Result<Json<ApiResponse<ProjectArchiveStatus>>, StatusCode> {
+    let (user_id, project) = member_project(&state, &headers, project_id).await?;
+    let Json(request) = request.unwrap_or_default();
+
+    let archived_at = state.db.set_project_archived(project_id, true).await
+        .map_err(|e| {
+            error!("Failed to archive project {}: {}", project_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    state.sync.set_archived(project_id, archived_at);
+
+    let mut checkpoints_pruned = 0;
+    if request.compact {
+        let rifts = state.db.get_project_rifts(project_id).await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        for rift in rifts {
+            match state.sync.storage.prune_auto_checkpoints(rift.id).await {
+                Ok(pruned) => checkpoints_pruned += pruned,
+                Err(e) => error!("Failed to compact rift {} of project {}: {}", rift.id, project_id, e),
+            }
+            // Archived rifts no longer need their co-editing documents
+            state.sync.text_sessions.write().await.retain(|(rift_id, _), _| *rift_id != rift.id);
+        }
+    }
+
+    info!("🗄️ Archived project {} ({} checkpoints pruned)", project.name, checkpoints_pruned);
+    audit::record(&state.db, AuditEvent::new(AuditAction::ProjectArchived, Some(user_id))
+        .project(project_id)
+        .details(serde_json::json!({ "name": project.name, "checkpoints_pruned": checkpoints_pruned }))).await;
+
+    Ok(Json(ApiResponse::success(ProjectArchiveStatus { project_id, archived_at, checkpoints_pruned })))
+}
+
+/// Make an archived project writable again
+async fn unarchive_project(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(project_id): Path<ProjectId>,
+) -> Result<Json<ApiResponse<ProjectArchiveStatus>>, StatusCode> {
+    let (user_id, project) = member_project(&state, &headers, project_id).await?;
+
+    let archived_at = state.db.set_project_archived(project_id, false).await
+        .map_err(|e| {
+            error!("Failed to unarchive project {}: {}", project_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    state.sync.set_archived(project_id, archived_at);
+
+    info!("📂 Unarchived project {}", project.name);
+    audit::record(&state.db, AuditEvent::new(AuditAction::ProjectUnarchived, Some(user_id))
+        .project(project_id)
+        .details(serde_json::json!({ "name": project.name }))).await;
+
+    Ok(Json(ApiResponse::success(ProjectArchiveStatus { project_id, archived_at, checkpoints_pruned: 0 })))
+}
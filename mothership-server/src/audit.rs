@@ -0,0 +1,347 @@
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    routing::get,
+    Json, Router,
+};
+use chrono::{DateTime, SubsecRound, Utc};
+use mothership_common::{protocol::ApiResponse, ProjectId, UserId};
+use serde::{Deserialize, Serialize};
+use tracing::{error, warn};
+
+use crate::database::{AuditEntry, AuditFilter, Database, NewAuditEntry};
+use crate::handlers::require_admin;
+use crate::AppState;
+
+/// Admin audit log endpoints
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/admin/audit", get(list_audit_log))
+        .route("/admin/audit/verify", get(verify_audit_log))
+}
+
+/// Actions recorded in the audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    ProjectCreated,
+    ProjectDeleted,
+    ProjectRestored,
+    ProjectPurged,
+    ProjectArchived,
+    ProjectUnarchived,
+    ProjectExported,
+    ProjectImported,
+    HistoryPushed,
+    HistoryCompacted,
+    ProjectSettingsUpdated,
+    CheckpointRestored,
+    CheckpointReverted,
+    CheckpointDownloaded,
+    MemberAdded,
+    MemberRemoved,
+    LoginSucceeded,
+    LoginDenied,
+    AdminUserCreated,
+    AdminSecretRejected,
+    TokenCreated,
+    TokenRevoked,
+    SessionRevoked,
+    MachineRevoked,
+    ServiceAccountCreated,
+    ServiceAccountDisabled,
+    UserDisabled,
+    UserEnabled,
+    UserRoleChanged,
+    UserLoggedOut,
+    UserDeleted,
+    RiftShared,
+    RiftUnshared,
+    RiftVisibilityChanged,
+    RiftRenamed,
+    RiftDeleted,
+    ReviewOpened,
+    ReviewApproved,
+    ReviewMerged,
+    ReviewClosed,
+    StorageRepaired,
+    FileQuarantined,
+    QuarantineReleased,
+    QuarantineDeleted,
+    FeatureFlagChanged,
+    ConfigReloaded,
+    WhitelistEntryAdded,
+    WhitelistEntryRemoved,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::ProjectCreated => "project.created",
+            AuditAction::ProjectDeleted => "project.deleted",
+            AuditAction::ProjectRestored => "project.restored",
+            AuditAction::ProjectPurged => "project.purged",
+            AuditAction::ProjectArchived => "project.archived",
+            AuditAction::ProjectUnarchived => "project.unarchived",
+            AuditAction::ProjectExported => "project.exported",
+            AuditAction::ProjectImported => "project.imported",
+            AuditAction::HistoryPushed => "project.history_pushed",
+            AuditAction::HistoryCompacted => "project.history_compacted",
+            AuditAction::ProjectSettingsUpdated => "project.settings_updated",
+            AuditAction::CheckpointRestored => "checkpoint.restored",
+            AuditAction::CheckpointReverted => "checkpoint.reverted",
+            AuditAction::CheckpointDownloaded => "checkpoint.downloaded",
+            AuditAction::MemberAdded => "member.added",
+            AuditAction::MemberRemoved => "member.removed",
+            AuditAction::LoginSucceeded => "auth.login",
+            AuditAction::LoginDenied => "auth.denied",
+            AuditAction::AdminUserCreated => "admin.user_created",
+            AuditAction::AdminSecretRejected => "admin.secret_rejected",
+            AuditAction::TokenCreated => "token.created",
+            AuditAction::TokenRevoked => "token.revoked",
+            AuditAction::SessionRevoked => "auth.session_revoked",
+            AuditAction::MachineRevoked => "auth.machine_revoked",
+            AuditAction::ServiceAccountCreated => "service_account.created",
+            AuditAction::ServiceAccountDisabled => "service_account.disabled",
+            AuditAction::UserDisabled => "admin.user_disabled",
+            AuditAction::UserEnabled => "admin.user_enabled",
+            AuditAction::UserRoleChanged => "admin.user_role_changed",
+            AuditAction::UserLoggedOut => "admin.user_logged_out",
+            AuditAction::UserDeleted => "admin.user_deleted",
+            AuditAction::RiftShared => "rift.shared",
+            AuditAction::RiftUnshared => "rift.unshared",
+            AuditAction::RiftVisibilityChanged => "rift.visibility_changed",
+            AuditAction::RiftRenamed => "rift.renamed",
+            AuditAction::RiftDeleted => "rift.deleted",
+            AuditAction::ReviewOpened => "review.opened",
+            AuditAction::ReviewApproved => "review.approved",
+            AuditAction::ReviewMerged => "review.merged",
+            AuditAction::ReviewClosed => "review.closed",
+            AuditAction::StorageRepaired => "storage.repaired",
+            AuditAction::FileQuarantined => "storage.file_quarantined",
+            AuditAction::QuarantineReleased => "admin.quarantine_released",
+            AuditAction::QuarantineDeleted => "admin.quarantine_deleted",
+            AuditAction::FeatureFlagChanged => "admin.feature_flag_changed",
+            AuditAction::ConfigReloaded => "admin.config_reloaded",
+            AuditAction::WhitelistEntryAdded => "admin.whitelist_entry_added",
+            AuditAction::WhitelistEntryRemoved => "admin.whitelist_entry_removed",
+        }
+    }
+}
+
+/// An event to be written to the audit log
+#[derive(Debug)]
+pub struct AuditEvent {
+    pub action: AuditAction,
+    pub actor_id: Option<UserId>,
+    pub project_id: Option<ProjectId>,
+    pub target: Option<String>,
+    pub details: serde_json::Value,
+}
+
+impl AuditEvent {
+    pub fn new(action: AuditAction, actor_id: Option<UserId>) -> Self {
+        Self {
+            action,
+            actor_id,
+            project_id: None,
+            target: None,
+            details: serde_json::json!({}),
+        }
+    }
+
+    pub fn project(mut self, project_id: ProjectId) -> Self {
+        self.project_id = Some(project_id);
+        self
+    }
+
+    pub fn target(mut self, target: impl Into<String>) -> Self {
+        self.target = Some(target.into());
+        self
+    }
+
+    pub fn details(mut self, details: serde_json::Value) -> Self {
+        self.details = details;
+        self
+    }
+}
+
+/// Record an audit event. Failures are logged but never fail the calling request.
+pub async fn record(db: &Database, event: AuditEvent) {
+    let details = event.details.to_string();
+    let entry = NewAuditEntry {
+        // PostgreSQL stores microseconds; truncate so the hash can be re-verified
+        occurred_at: Utc::now().trunc_subsecs(6),
+        actor_id: event.actor_id,
+        action: event.action.as_str(),
+        project_id: event.project_id,
+        target: event.target.as_deref(),
+        details: &details,
+    };
+
+    if let Err(e) = db.insert_audit_entry(entry).await {
+        error!("❌ Failed to write audit entry {}: {}", event.action.as_str(), e);
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditQuery {
+    pub user_id: Option<UserId>,
+    pub project_id: Option<ProjectId>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub action: Option<String>,
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditEntryInfo {
+    pub id: i64,
+    pub occurred_at: DateTime<Utc>,
+    pub actor_id: Option<UserId>,
+    pub action: String,
+    pub project_id: Option<ProjectId>,
+    pub target: Option<String>,
+    pub details: serde_json::Value,
+    pub hash: String,
+}
+
+impl From<AuditEntry> for AuditEntryInfo {
+    fn from(entry: AuditEntry) -> Self {
+        Self {
+            id: entry.id,
+            occurred_at: entry.occurred_at,
+            actor_id: entry.actor_id,
+            action: entry.action,
+            project_id: entry.project_id,
+            target: entry.target,
+            details: serde_json::from_str(&entry.details).unwrap_or(serde_json::Value::Null),
+            hash: entry.hash,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditPage {
+    pub entries: Vec<AuditEntryInfo>,
+    pub page: i64,
+    pub per_page: i64,
+    pub total: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditVerification {
+    pub entries_checked: i64,
+    pub intact: bool,
+    /// First entry whose hash or chain link does not match
+    pub first_invalid_id: Option<i64>,
+}
+
+/// List audit log entries, newest first
+async fn list_audit_log(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<ApiResponse<AuditPage>>, StatusCode> {
+    require_admin(&state, &headers, "access the audit log").await?;
+
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(50).clamp(1, 500);
+
+    let filter = AuditFilter {
+        user_id: query.user_id,
+        project_id: query.project_id,
+        from: query.from,
+        to: query.to,
+        action: query.action,
+    };
+
+    let (entries, total) = state.db
+        .list_audit_entries(&filter, per_page, (page - 1) * per_page)
+        .await
+        .map_err(|e| {
+            error!("Failed to list audit entries: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ApiResponse::success(AuditPage {
+        entries: entries.into_iter().map(AuditEntryInfo::from).collect(),
+        page,
+        per_page,
+        total,
+    })))
+}
+
+/// Recompute the hash chain to detect tampering
+async fn verify_audit_log(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<ApiResponse<AuditVerification>>, StatusCode> {
+    require_admin(&state, &headers, "access the audit log").await?;
+
+    let (entries_checked, first_invalid_id) = state.db.verify_audit_chain().await
+        .map_err(|e| {
+            error!("Failed to verify audit chain: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if let Some(id) = first_invalid_id {
+        warn!("🚨 Audit log chain broken at entry {}", id);
+    }
+
+    Ok(Json(ApiResponse::success(AuditVerification {
+        entries_checked,
+        intact: first_invalid_id.is_none(),
+        first_invalid_id,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_client::TestClient;
+    use mothership_common::UserRole;
+
+    #[tokio::test]
+    async fn test_verify_finds_the_entry_that_was_tampered_with() {
+        let mut client = TestClient::new().await;
+        let admin = client.sign_in("admin").await;
+        client.state.db.set_user_role(admin.id, UserRole::Admin).await.unwrap();
+        for target in ["alpha", "beta", "gamma"] {
+            record(&client.state.db, AuditEvent::new(AuditAction::ProjectCreated, Some(admin.id)).target(target)).await;
+        }
+
+        let report: serde_json::Value = client.get("/admin/audit/verify").await.data();
+        assert_eq!(report["entries_checked"], 3);
+        assert_eq!(report["intact"], true);
+
+        let (entries, _) = client.state.db.list_audit_entries(&AuditFilter::default(), 10, 0).await.unwrap();
+        let beta = entries.iter().find(|entry| entry.target.as_deref() == Some("beta")).unwrap();
+        client.state.db
+            .execute_raw(&format!("UPDATE audit_log SET target = 'delta' WHERE id = {}", beta.id))
+            .await
+            .unwrap();
+
+        let report: serde_json::Value = client.get("/admin/audit/verify").await.data();
+        assert_eq!(report["intact"], false);
+        assert_eq!(report["first_invalid_id"], beta.id);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_writers_keep_the_chain_intact() {
+        let client = TestClient::new().await;
+        let writers = (0..20).map(|i| {
+            let db = client.state.db.clone();
+            tokio::spawn(async move {
+                record(&db, AuditEvent::new(AuditAction::LoginSucceeded, None).target(format!("writer-{}", i))).await;
+            })
+        });
+        for writer in writers.collect::<Vec<_>>() {
+            writer.await.unwrap();
+        }
+
+        let (entries_checked, first_invalid_id) = client.state.db.verify_audit_chain().await.unwrap();
+        assert_eq!(entries_checked, 20);
+        assert_eq!(first_invalid_id, None);
+    }
+}
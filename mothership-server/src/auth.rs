@@ -4,13 +4,27 @@ use jsonwebtoken::{decode, Algorithm, DecodingKey, EncodingKey, Header, Validati
 use mothership_common::auth::{
     AuthError, Claims, OAuthProfile, OAuthProvider, OAuthRequest, OAuthResponse, OAuthSource,
 };
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
 use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::database::Database;
+
+/// How often revocations and disabled users are reloaded from the database,
+/// which is how those made on other instances reach this one
+const REVOCATION_REFRESH_INTERVAL: Duration = Duration::from_secs(15);
 
 /// Authentication service for handling JWT tokens
 #[derive(Clone)]
 pub struct AuthService {
     encoding_key: EncodingKey,
     decoding_key: DecodingKey,
+    /// `jti` claims of revoked sessions whose tokens have not expired yet
+    revoked: Arc<RwLock<HashSet<String>>>,
+    /// Disabled users, whose tokens are refused whether or not they carry a `jti`
+    disabled_users: Arc<RwLock<HashSet<Uuid>>>,
 }
 
 impl AuthService {
@@ -20,9 +34,46 @@ impl AuthService {
         Self {
             encoding_key,
             decoding_key,
+            revoked: Arc::new(RwLock::new(HashSet::new())),
+            disabled_users: Arc::new(RwLock::new(HashSet::new())),
         }
     }
 
+    /// Reject tokens carrying this `jti` from now on
+    pub fn revoke(&self, jti: impl Into<String>) {
+        self.revoked.write().unwrap_or_else(|e| e.into_inner()).insert(jti.into());
+    }
+
+    /// Seed the revocation list (on startup, from the database)
+    pub fn load_revoked(&self, jtis: impl IntoIterator<Item = String>) {
+        self.revoked.write().unwrap_or_else(|e| e.into_inner()).extend(jtis);
+    }
+
+    fn is_revoked(&self, jti: &str) -> bool {
+        self.revoked.read().unwrap_or_else(|e| e.into_inner()).contains(jti)
+    }
+
+    /// Refuse (or accept again) every token of a user
+    pub fn set_user_disabled(&self, user_id: Uuid, disabled: bool) {
+        let mut disabled_users = self.disabled_users.write().unwrap_or_else(|e| e.into_inner());
+        if disabled {
+            disabled_users.insert(user_id);
+        } else {
+            disabled_users.remove(&user_id);
+        }
+    }
+
+    /// Replace the disabled users with the database's current ones
+    pub fn load_disabled_users(&self, user_ids: impl IntoIterator<Item = Uuid>) {
+        *self.disabled_users.write().unwrap_or_else(|e| e.into_inner()) = user_ids.into_iter().collect();
+    }
+
+    fn is_user_disabled(&self, sub: &str) -> bool {
+        Uuid::parse_str(sub).is_ok_and(|user_id| {
+            self.disabled_users.read().unwrap_or_else(|e| e.into_inner()).contains(&user_id)
+        })
+    }
+
     /// Encode a JWT token with the given claims
     pub fn encode_token(&self, claims: &Claims) -> Result<String, AuthError> {
         let header = Header::new(Algorithm::HS256);
@@ -41,6 +92,17 @@ impl AuthService {
         let token_data = decode::<Claims>(token, &self.decoding_key, &validation)
             .map_err(|_| AuthError::InvalidToken)?;
 
+        // SECURITY CHECK: Tokens from revoked sessions are rejected even before they expire
+        if let Some(jti) = &token_data.claims.jti {
+            if self.is_revoked(jti) {
+                return Err(AuthError::RevokedToken);
+            }
+        }
+        // Tokens without a session (personal access token exchanges) stop with their user
+        if self.is_user_disabled(&token_data.claims.sub) {
+            return Err(AuthError::RevokedToken);
+        }
+
         Ok(token_data.claims)
     }
 
@@ -58,10 +120,57 @@ impl AuthService {
             exp: (now + chrono::Duration::days(30)).timestamp(),
             aud: "mothership".to_string(),
             iss: "mothership-server".to_string(),
+            jti: None,
         };
 
         // Encode token and store it (the actual storage would be handled by the sessions system)
         self.encode_token(&claims)?;
         Ok(())
     }
-} 
\ No newline at end of file
+} 
+
+/// Load revoked sessions and disabled users from the database
+pub async fn refresh_revocations(auth: &AuthService, db: &Database) -> Result<()> {
+    let revoked = db.list_revoked_session_ids().await?;
+    let disabled = db.list_disabled_user_ids().await?;
+    auth.load_revoked(revoked.into_iter().map(|id| id.to_string()));
+    auth.load_disabled_users(disabled);
+    Ok(())
+}
+
+/// Keep revocations current with the database, so sessions revoked and users
+/// disabled through another instance are refused here within seconds
+pub fn spawn_revocation_refresher(auth: AuthService, db: Database) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(REVOCATION_REFRESH_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = refresh_revocations(&auth, &db).await {
+                warn!("Failed to refresh revoked sessions: {}", e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_client::TestClient;
+    use axum::http::StatusCode;
+
+    #[tokio::test]
+    async fn test_users_disabled_elsewhere_are_refused_after_a_refresh() {
+        let mut client = TestClient::new().await;
+        let user = client.sign_in("roamer").await;
+        assert_eq!(client.get("/auth/sessions").await.status, StatusCode::OK);
+
+        // Another instance disables the user; this one only learns of it from the database
+        client.state.db.set_user_disabled(user.id, true).await.unwrap();
+        refresh_revocations(&client.state.auth, &client.state.db).await.unwrap();
+        assert_eq!(client.get("/auth/sessions").await.status, StatusCode::UNAUTHORIZED);
+
+        client.state.db.set_user_disabled(user.id, false).await.unwrap();
+        refresh_revocations(&client.state.auth, &client.state.db).await.unwrap();
+        assert_eq!(client.get("/auth/sessions").await.status, StatusCode::OK);
+    }
+}
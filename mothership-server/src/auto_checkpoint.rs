@@ -0,0 +1,114 @@
+use chrono::{DateTime, Duration, Utc};
+use mothership_common::{protocol::SyncMessage, ProjectId, RiftId, UserId};
+use std::collections::HashMap;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::git_export;
+use crate::AppState;
+
+/// How often rifts are checked for a due automatic checkpoint
+const SCHEDULER_TICK: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// Author of the checkpoints the server takes by itself
+const SCHEDULER_AUTHOR: UserId = Uuid::nil();
+
+/// Periodically checkpoint rifts in use at their project's
+/// `auto_checkpoint_interval`, skipping those unchanged since their last checkpoint
+pub fn spawn_scheduler(state: AppState) {
+    if !state.config().auto_checkpoint.enabled {
+        info!("⏱️ Automatic checkpoints are disabled");
+        return;
+    }
+
+    tokio::spawn(async move {
+        // When each rift was last found unchanged, so it isn't rehashed every tick
+        let mut last_checked: HashMap<RiftId, DateTime<Utc>> = HashMap::new();
+        let mut interval = tokio::time::interval(SCHEDULER_TICK);
+        loop {
+            interval.tick().await;
+            checkpoint_due_rifts(&state, &mut last_checked).await;
+        }
+    });
+}
+
+async fn checkpoint_due_rifts(state: &AppState, last_checked: &mut HashMap<RiftId, DateTime<Utc>>) {
+    let storage = &state.sync.storage;
+    let rifts = storage.active_rifts().await;
+    last_checked.retain(|rift_id, _| rifts.contains(rift_id));
+
+    // Intervals of the projects seen this tick; None if off
+    let mut intervals: HashMap<ProjectId, Option<Duration>> = HashMap::new();
+    for rift_id in rifts {
+        let rift = match state.db.get_rift(rift_id).await {
+            Ok(Some(rift)) => rift,
+            Ok(None) => continue,
+            Err(e) => {
+                warn!("Failed to load rift {} for automatic checkpoints: {}", rift_id, e);
+                continue;
+            }
+        };
+        if state.sync.archived_at(rift.project_id).is_some() {
+            continue;
+        }
+        let interval = match intervals.get(&rift.project_id) {
+            Some(interval) => *interval,
+            None => {
+                let interval = project_interval(state, rift.project_id).await;
+                intervals.insert(rift.project_id, interval);
+                interval
+            }
+        };
+        let Some(interval) = interval else {
+            continue;
+        };
+
+        let now = Utc::now();
+        let last_checkpoint = storage.latest_checkpoint(rift_id).await.map(|c| c.timestamp);
+        let since = last_checkpoint.into_iter().chain(last_checked.get(&rift_id).copied()).max();
+        if since.is_some_and(|since| now - since < interval) {
+            continue;
+        }
+        last_checked.insert(rift_id, now);
+        if storage.unchanged_since_checkpoint(rift_id).await.is_some() {
+            continue;
+        }
+
+        match storage.create_checkpoint(rift_id, SCHEDULER_AUTHOR, None, true).await {
+            Ok(checkpoint) => {
+                info!("⏱️ Automatic checkpoint {} of rift {} ({} files)", checkpoint.id, rift_id, checkpoint.changes.len());
+                state.sync.broadcast(format!("rift_{}", rift_id), SyncMessage::CheckpointCreated {
+                    rift_id,
+                    checkpoint_id: checkpoint.id,
+                    author: checkpoint.author,
+                    timestamp: checkpoint.timestamp,
+                    message: None,
+                });
+                git_export::schedule_mirror_push(state.clone(), rift_id);
+            }
+            Err(e) => error!("Failed to create automatic checkpoint of rift {}: {}", rift_id, e),
+        }
+    }
+}
+
+/// A project's automatic checkpoint interval, raised to the server's minimum
+async fn project_interval(state: &AppState, project_id: ProjectId) -> Option<Duration> {
+    // Trashed projects are hidden from get_project
+    match state.db.get_project(project_id).await {
+        Ok(Some(_)) => {}
+        _ => return None,
+    }
+    let settings = match state.db.get_project_settings(project_id).await {
+        Ok(settings) => settings,
+        Err(e) => {
+            warn!("Failed to load settings of project {}: {}", project_id, e);
+            return None;
+        }
+    };
+    if settings.auto_checkpoint_interval == 0 {
+        return None;
+    }
+
+    let seconds = settings.auto_checkpoint_interval.max(state.config().auto_checkpoint.min_interval_secs);
+    Some(Duration::seconds(seconds as i64))
+}
@@ -0,0 +1,378 @@
+use anyhow::{anyhow, Result};
+use axum::{
+    body::Bytes,
+    extract::{DefaultBodyLimit, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::Response,
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::Utc;
+use mothership_common::{
+    bundle::{BundleProject, BundleRift, ProjectBundle, BUNDLE_EXTENSION, BUNDLE_FORMAT_VERSION},
+    protocol::{ApiResponse, ProjectImportResult},
+    upload::content_hash,
+    Checkpoint, CheckpointId, Project, ProjectId, Rift, RiftId, UserId,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::api_error::ApiError;
+use crate::audit::{self, AuditAction, AuditEvent};
+use crate::handlers::authenticate_request;
+use crate::AppState;
+
+/// Largest bundle the import endpoint accepts
+const MAX_BUNDLE_SIZE: usize = 512 * 1024 * 1024;
+
+/// Project export, import and history push endpoints
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/projects/:id/export", get(export_project))
+        .route(
+            "/projects/import",
+            post(import_project).layer(DefaultBodyLimit::max(MAX_BUNDLE_SIZE)),
+        )
+        .route(
+            "/projects/:id/push",
+            post(push_history).layer(DefaultBodyLimit::max(MAX_BUNDLE_SIZE)),
+        )
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportQuery {
+    /// Name for the imported project (defaults to the name in the bundle)
+    pub name: Option<String>,
+}
+
+/// Order rifts so every parent comes before its children
+fn parents_first(mut rifts: Vec<Rift>) -> Vec<Rift> {
+    let mut ordered: Vec<Rift> = Vec::with_capacity(rifts.len());
+    while !rifts.is_empty() {
+        let before = rifts.len();
+        let mut index = 0;
+        while index < rifts.len() {
+            let ready = match rifts[index].parent_rift {
+                Some(parent) => ordered.iter().any(|r| r.id == parent) || !rifts.iter().any(|r| r.id == parent),
+                None => true,
+            };
+            if ready {
+                ordered.push(rifts.remove(index));
+            } else {
+                index += 1;
+            }
+        }
+        // A parent cycle can't be ordered; keep the rest as they are
+        if rifts.len() == before {
+            ordered.append(&mut rifts);
+        }
+    }
+    ordered
+}
+
+/// Collect everything about a project into a bundle
+async fn build_bundle(state: &AppState, project: Project) -> Result<ProjectBundle> {
+    let storage = &state.sync.storage;
+    let rifts = parents_first(state.db.get_project_rifts(project.id).await?);
+    let rift_ids: Vec<RiftId> = rifts.iter().map(|r| r.id).collect();
+
+    let mut blobs = HashMap::new();
+    let mut bundle_rifts = Vec::new();
+    let mut checkpoints = Vec::new();
+
+    for rift in rifts {
+        let mut live_files = HashMap::new();
+        for (path, content) in storage.get_live_state(rift.id).await? {
+            let hash = content_hash(content.as_bytes());
+            live_files.insert(path, hash.clone());
+            blobs.insert(hash, content);
+        }
+
+        let mut rift_checkpoints = storage.list_checkpoints(rift.id).await?;
+        rift_checkpoints.sort_by_key(|c| c.timestamp);
+        for checkpoint in &rift_checkpoints {
+            for change in &checkpoint.changes {
+                if blobs.contains_key(&change.content_hash) {
+                    continue;
+                }
+                let content = storage.get_content(&change.content_hash).await?
+                    .ok_or_else(|| anyhow!("Content {} of checkpoint {} is missing", change.content_hash, checkpoint.id))?;
+                blobs.insert(change.content_hash.clone(), content);
+            }
+        }
+        checkpoints.extend(rift_checkpoints);
+
+        bundle_rifts.push(BundleRift {
+            id: rift.id,
+            name: rift.name,
+            // Parents outside the project can't be carried along
+            parent_rift: rift.parent_rift.filter(|parent| rift_ids.contains(parent)),
+            created_at: rift.created_at,
+            live_files,
+            fork_point: state.db.get_rift_fork_point(rift.id).await?,
+        });
+    }
+
+    let mut authors = HashMap::new();
+    for checkpoint in &checkpoints {
+        if authors.contains_key(&checkpoint.author) {
+            continue;
+        }
+        if let Some(user) = state.db.get_user(checkpoint.author).await? {
+            authors.insert(checkpoint.author, user.username);
+        }
+    }
+
+    Ok(ProjectBundle {
+        format_version: BUNDLE_FORMAT_VERSION,
+        exported_at: Utc::now(),
+        project: BundleProject {
+            name: project.name,
+            description: project.description,
+            created_at: project.created_at,
+            settings: project.settings,
+        },
+        rifts: bundle_rifts,
+        checkpoints,
+        authors,
+        blobs,
+    })
+}
+
+/// Download a project as a portable bundle
+async fn export_project(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(project_id): Path<ProjectId>,
+) -> Result<Response, StatusCode> {
+    let user_id = authenticate_request(&state, &headers)?;
+
+    let project = match state.db.get_project(project_id).await {
+        Ok(Some(project)) => project,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    if !state.db.user_has_project_access(user_id, project_id).await.unwrap_or(false) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let name = project.name.clone();
+    let bundle = build_bundle(&state, project).await
+        .map_err(|e| {
+            error!("Failed to build bundle for project {}: {}", project_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    let (rifts, checkpoints) = (bundle.rifts.len(), bundle.checkpoints.len());
+    let bytes = bundle.encode().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    info!("📦 Exported project '{}' ({} rifts, {} checkpoints, {} bytes) for user {}",
+        name, rifts, checkpoints, bytes.len(), user_id);
+    audit::record(&state.db, AuditEvent::new(AuditAction::ProjectExported, Some(user_id))
+        .project(project_id)
+        .details(serde_json::json!({ "name": name, "rifts": rifts, "checkpoints": checkpoints }))).await;
+
+    Ok(Response::builder()
+        .header(header::CONTENT_TYPE, "application/gzip")
+        .header(header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}.{}\"", name, BUNDLE_EXTENSION))
+        .body(bytes.into())
+        .unwrap())
+}
+
+/// The checkpoint a bundled rift forked from. Bundles from before fork points
+/// were recorded fall back to its merge target's last checkpoint before the
+/// rift was created.
+fn bundle_fork_point(bundle: &ProjectBundle, rift: &BundleRift) -> Option<CheckpointId> {
+    if rift.fork_point.is_some() {
+        return rift.fork_point;
+    }
+    let target = rift.parent_rift
+        .or_else(|| bundle.rifts.iter().find(|r| r.name == "main" && r.id != rift.id).map(|r| r.id))?;
+    bundle.checkpoints.iter()
+        .filter(|c| c.rift_id == target && c.timestamp <= rift.created_at)
+        .max_by_key(|c| c.timestamp)
+        .map(|c| c.id)
+}
+
+/// Recreate a bundle's rifts and checkpoints in `project` on behalf of
+/// `user_id`. Rifts the project already has (by name) are reused, and their
+/// working state is replaced by the bundle's.
+async fn restore_bundle(state: &AppState, bundle: ProjectBundle, project: &Project, user_id: UserId) -> Result<()> {
+    let storage = &state.sync.storage;
+
+    for content in bundle.blobs.values() {
+        storage.store_content(content).await?;
+    }
+
+    let mut rift_ids: HashMap<RiftId, RiftId> = HashMap::new();
+    for bundle_rift in &bundle.rifts {
+        let rift = match state.db.get_rift_by_name(project.id, &bundle_rift.name).await? {
+            Some(rift) => rift,
+            None => {
+                let rift = state.db.create_rift(project.id, user_id, Some(bundle_rift.name.clone())).await?;
+                if let Some(parent) = bundle_rift.parent_rift.and_then(|p| rift_ids.get(&p).copied()) {
+                    state.db.set_rift_parent(rift.id, Some(parent)).await?;
+                }
+                rift
+            }
+        };
+        let live_files = bundle_rift.live_files
+            .iter()
+            .map(|(path, hash)| (path.clone(), bundle.blobs[hash].clone()))
+            .collect();
+        storage.replace_live_state(rift.id, live_files).await;
+        rift_ids.insert(bundle_rift.id, rift.id);
+    }
+
+    // Authors are matched to local users by username; anyone unknown here is
+    // attributed to the importing user
+    let mut author_ids: HashMap<UserId, UserId> = HashMap::new();
+    for (author, username) in &bundle.authors {
+        if let Some(user) = state.db.get_user_by_username(username).await? {
+            author_ids.insert(*author, user.id);
+        }
+    }
+
+    let checkpoint_ids: HashMap<CheckpointId, CheckpointId> = bundle.checkpoints
+        .iter()
+        .map(|c| (c.id, Uuid::new_v4()))
+        .collect();
+    for checkpoint in &bundle.checkpoints {
+        storage.import_checkpoint(&Checkpoint {
+            id: checkpoint_ids[&checkpoint.id],
+            rift_id: rift_ids[&checkpoint.rift_id],
+            author: author_ids.get(&checkpoint.author).copied().unwrap_or(user_id),
+            parent: checkpoint.parent.and_then(|p| checkpoint_ids.get(&p).copied()),
+            merged_from: checkpoint.merged_from.and_then(|r| rift_ids.get(&r).copied()),
+            ..checkpoint.clone()
+        }).await?;
+    }
+
+    // Merges back start from where each rift forked, not from when it was imported
+    for bundle_rift in &bundle.rifts {
+        if let Some(fork_point) = bundle_fork_point(&bundle, bundle_rift).and_then(|c| checkpoint_ids.get(&c)) {
+            state.db.set_rift_fork_point(rift_ids[&bundle_rift.id], *fork_point).await?;
+        }
+    }
+
+    // Working files keep the permissions and symlinks of their rift's latest checkpoint
+    for bundle_rift in &bundle.rifts {
+        let latest = bundle.checkpoints.iter()
+            .filter(|c| c.rift_id == bundle_rift.id)
+            .max_by_key(|c| c.timestamp);
+        for change in latest.map(|c| c.changes.as_slice()).unwrap_or_default() {
+            if bundle_rift.live_files.contains_key(&change.path) {
+                storage.update_file_metadata(rift_ids[&bundle_rift.id], &change.path, &change.metadata).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Create a new project from an uploaded bundle
+async fn import_project(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ImportQuery>,
+    body: Bytes,
+) -> Result<Json<ApiResponse<ProjectImportResult>>, ApiError> {
+    let user_id = authenticate_request(&state, &headers)?;
+
+    if !state.config().features.project_creation_enabled {
+        return Err(StatusCode::FORBIDDEN.into());
+    }
+
+    let bundle = match ProjectBundle::decode(&body) {
+        Ok(bundle) => bundle,
+        Err(e) => {
+            warn!("❌ Rejected project bundle from user {}: {}", user_id, e);
+            return Err(ApiError::bad_request(format!("Invalid bundle: {}", e)));
+        }
+    };
+
+    let name = query.name.unwrap_or_else(|| bundle.project.name.clone());
+    if state.db.project_exists_by_name(&name).await.unwrap_or(false) {
+        return Err(ApiError::conflict(format!(
+            "A project named '{}' already exists; import it under another name",
+            name
+        )));
+    }
+
+    let project = state.db.create_project(name.clone(), bundle.project.description.clone(), vec![user_id]).await
+        .map_err(|e| {
+            error!("Failed to create project {} for import: {}", name, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let (rifts, checkpoints) = (bundle.rifts.len(), bundle.checkpoints.len());
+    if let Err(e) = restore_bundle(&state, bundle, &project, user_id).await {
+        // Don't leave a half-imported project behind
+        error!("Failed to import bundle into project {}: {}", project.id, e);
+        let _ = state.db.delete_project(project.id).await;
+        return Err(StatusCode::INTERNAL_SERVER_ERROR.into());
+    }
+
+    info!("📦 Imported project '{}' ({} rifts, {} checkpoints) for user {}", name, rifts, checkpoints, user_id);
+    audit::record(&state.db, AuditEvent::new(AuditAction::ProjectImported, Some(user_id))
+        .project(project.id)
+        .details(serde_json::json!({ "name": name, "rifts": rifts, "checkpoints": checkpoints }))).await;
+
+    Ok(Json(ApiResponse::success(ProjectImportResult {
+        project_id: project.id,
+        name,
+        rifts,
+        checkpoints,
+    })))
+}
+
+/// Add the history in an uploaded bundle to an existing project, e.g.
+/// checkpoints a client made while working offline
+async fn push_history(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(project_id): Path<ProjectId>,
+    body: Bytes,
+) -> Result<Json<ApiResponse<ProjectImportResult>>, ApiError> {
+    let user_id = authenticate_request(&state, &headers)?;
+
+    let project = match state.db.get_project(project_id).await {
+        Ok(Some(project)) => project,
+        Ok(None) => return Err(StatusCode::NOT_FOUND.into()),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR.into()),
+    };
+
+    if !state.db.user_has_project_access(user_id, project_id).await.unwrap_or(false) {
+        return Err(StatusCode::FORBIDDEN.into());
+    }
+    crate::archive::ensure_writable(&state, project_id)?;
+
+    let bundle = match ProjectBundle::decode(&body) {
+        Ok(bundle) => bundle,
+        Err(e) => {
+            warn!("❌ Rejected pushed history from user {}: {}", user_id, e);
+            return Err(ApiError::bad_request(format!("Invalid bundle: {}", e)));
+        }
+    };
+
+    let (rifts, checkpoints) = (bundle.rifts.len(), bundle.checkpoints.len());
+    restore_bundle(&state, bundle, &project, user_id).await
+        .map_err(|e| {
+            error!("Failed to push history into project {}: {}", project_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    info!("⬆️ User {} pushed {} checkpoints ({} rifts) to project '{}'", user_id, checkpoints, rifts, project.name);
+    audit::record(&state.db, AuditEvent::new(AuditAction::HistoryPushed, Some(user_id))
+        .project(project_id)
+        .details(serde_json::json!({ "rifts": rifts, "checkpoints": checkpoints }))).await;
+
+    Ok(Json(ApiResponse::success(ProjectImportResult {
+        project_id,
+        name: project.name,
+        rifts,
+        checkpoints,
+    })))
+}
@@ -0,0 +1,261 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    routing::get,
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use mothership_common::{
+    features::Feature,
+    protocol::{ApiResponse, ChatMessage, InboxKind, SendChatRequest, SyncMessage},
+    ProjectId, RiftId, UserId,
+};
+use serde::Deserialize;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::api_error::ApiError;
+use crate::config::ServerConfig;
+use crate::handlers::authenticate_request;
+use crate::inbox;
+use crate::notifications::NotificationKind;
+use crate::sync::SyncState;
+use crate::AppState;
+
+/// Default and maximum page size for chat history
+const DEFAULT_HISTORY_LIMIT: i64 = 50;
+const MAX_HISTORY_LIMIT: i64 = 200;
+
+/// Rift chat endpoints
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/projects/:id/rifts/:rift_id/chat", get(get_chat_history).post(send_chat_message))
+}
+
+/// Chat limits taken from the server configuration
+#[derive(Debug, Clone)]
+pub struct ChatSettings {
+    pub max_message_length: usize,
+    pub store_history: bool,
+    pub max_history: usize,
+}
+
+impl ChatSettings {
+    pub fn from_config(config: &ServerConfig) -> Self {
+        Self {
+            max_message_length: config.collaboration.max_chat_message_length,
+            store_history: config.collaboration.store_chat_history,
+            max_history: config.collaboration.max_chat_history,
+        }
+    }
+}
+
+/// Validate a message, store it (if history is enabled) and broadcast it to the
+/// rift. Returns an error message suitable for the client when rejected.
+pub async fn post_message(
+    state: &SyncState,
+    rift_id: RiftId,
+    author: UserId,
+    username: String,
+    content: &str,
+) -> Result<ChatMessage, String> {
+    if !state.features.is_enabled_in_rift(Feature::Chat, rift_id).await {
+        return Err("Chat is turned off for this project".to_string());
+    }
+
+    let settings = ChatSettings::from_config(&state.settings.config());
+    let content = content.trim();
+    if content.is_empty() {
+        return Err("Message is empty".to_string());
+    }
+    if content.chars().count() > settings.max_message_length {
+        return Err(format!(
+            "Message is longer than {} characters",
+            settings.max_message_length
+        ));
+    }
+
+    let message = ChatMessage {
+        id: Uuid::new_v4(),
+        rift_id,
+        author,
+        username,
+        content: content.to_string(),
+        timestamp: Utc::now(),
+    };
+
+    // History is best-effort: a storage failure should not stop live delivery
+    if settings.store_history && settings.max_history > 0 {
+        if let Err(e) = state.db.insert_chat_message(&message, settings.max_history).await {
+            error!("❌ Failed to store chat message in rift {}: {}", rift_id, e);
+        }
+    }
+
+    let channel = format!("rift_{}", rift_id);
+    state.broadcast(channel, SyncMessage::ChatMessage { rift_id, message: message.clone() });
+    info!("💬 {} posted in rift {}", message.username, rift_id);
+
+    notify_mentions(state, &message).await;
+
+    Ok(message)
+}
+
+/// Usernames mentioned as `@name` in a message, without duplicates
+pub(crate) fn mentioned_usernames(content: &str) -> Vec<&str> {
+    let mut names: Vec<&str> = Vec::new();
+    for word in content.split_whitespace() {
+        let Some(name) = word.strip_prefix('@') else { continue };
+        let name = name.trim_end_matches(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'));
+        if !name.is_empty() && !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// Notify users mentioned in a message, if they can see the rift
+async fn notify_mentions(state: &SyncState, message: &ChatMessage) {
+    let names = mentioned_usernames(&message.content);
+    if names.is_empty() {
+        return;
+    }
+
+    let rift = match state.db.get_rift(message.rift_id).await {
+        Ok(Some(rift)) => rift,
+        _ => return,
+    };
+
+    for name in names {
+        let Ok(Some(user)) = state.db.get_user_by_username(name).await else { continue };
+        if user.id == message.author {
+            continue;
+        }
+        // SECURITY CHECK: never leak messages to users outside the rift
+        if !matches!(crate::rift_access::resolve_role(&state.db, &rift, user.id).await, Ok(Some(_))) {
+            continue;
+        }
+
+        state.notifier.notify(
+            user.id,
+            NotificationKind::Mention,
+            format!("{} mentioned you in rift '{}'", message.username, rift.name),
+            format!("{} wrote in rift '{}':\n\n{}", message.username, rift.name, message.content),
+        );
+        inbox::deliver(
+            state,
+            user.id,
+            Some(message.author),
+            InboxKind::Mention,
+            Some(rift.project_id),
+            format!("{} mentioned you in rift '{}'", message.username, rift.name),
+            message.content.clone(),
+        );
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChatHistoryQuery {
+    /// Only return messages posted before this time (for paging backwards)
+    pub before: Option<DateTime<Utc>>,
+    pub limit: Option<i64>,
+}
+
+/// Authenticate the request and check the user has a role in the rift, which
+/// must belong to the project. Observers may chat too.
+async fn authorize_rift(
+    state: &AppState,
+    headers: &HeaderMap,
+    project_id: ProjectId,
+    rift_id: RiftId,
+) -> Result<UserId, StatusCode> {
+    let user_id = authenticate_request(state, headers)?;
+
+    if !state.sync.features.is_enabled(Feature::Chat, Some(project_id)).await {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    if !state.db.user_has_project_access(user_id, project_id).await.unwrap_or(false) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let rift = match state.db.get_rift(rift_id).await {
+        Ok(Some(rift)) if rift.project_id == project_id => rift,
+        Ok(_) => return Err(StatusCode::NOT_FOUND),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    };
+    crate::rift_access::require_access(&state.db, &rift, user_id, false).await?;
+    Ok(user_id)
+}
+
+/// Page through a rift's chat history, oldest message first
+async fn get_chat_history(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((project_id, rift_id)): Path<(ProjectId, RiftId)>,
+    Query(query): Query<ChatHistoryQuery>,
+) -> Result<Json<ApiResponse<Vec<ChatMessage>>>, StatusCode> {
+    authorize_rift(&state, &headers, project_id, rift_id).await?;
+
+    let limit = query.limit.unwrap_or(DEFAULT_HISTORY_LIMIT).clamp(1, MAX_HISTORY_LIMIT);
+    let messages = state.db.list_chat_messages(rift_id, query.before, limit).await
+        .map_err(|e| {
+            error!("Failed to load chat history for rift {}: {}", rift_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ApiResponse::success(messages)))
+}
+
+/// Post a chat message over HTTP (for clients without a sync connection)
+async fn send_chat_message(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((project_id, rift_id)): Path<(ProjectId, RiftId)>,
+    Json(req): Json<SendChatRequest>,
+) -> Result<Json<ApiResponse<ChatMessage>>, ApiError> {
+    let user_id = authorize_rift(&state, &headers, project_id, rift_id).await?;
+
+    let username = match state.db.get_user(user_id).await {
+        Ok(Some(user)) => user.username,
+        Ok(None) => return Err(StatusCode::UNAUTHORIZED.into()),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR.into()),
+    };
+
+    match post_message(&state.sync, rift_id, user_id, username, &req.content).await {
+        Ok(message) => Ok(Json(ApiResponse::success(message))),
+        Err(e) => Err(ApiError::bad_request(e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_client::TestClient;
+    use mothership_common::protocol::BeamResponse;
+    use mothership_common::{Project, RiftVisibility};
+
+    #[tokio::test]
+    async fn test_private_rift_chat_is_closed_to_uninvited_members() {
+        let mut client = TestClient::new().await;
+        client.sign_in("owner").await;
+        let project: Project = client.post("/gateway/create", &serde_json::json!({
+            "name": "private-chat",
+            "description": "",
+            "project_path": "/tmp/private-chat",
+        })).await.data();
+        let beam: BeamResponse = client.post(&format!("/projects/{}/beam", project.id), &serde_json::json!({
+            "project_id": project.id,
+            "rift_name": null,
+            "force_sync": false,
+        })).await.data();
+        let chat = format!("/projects/{}/rifts/{}/chat", project.id, beam.rift_id);
+        client.state.db.set_rift_visibility(beam.rift_id, RiftVisibility::Private).await.unwrap();
+        assert_eq!(client.get(&chat).await.status, StatusCode::OK);
+
+        let member = client.sign_in("member").await;
+        client.state.db.add_project_member(project.id, member.id, "member").await.unwrap();
+        assert_eq!(client.get(&chat).await.status, StatusCode::FORBIDDEN);
+        let post = client.post(&chat, &serde_json::json!({ "content": "hello?" })).await;
+        assert_eq!(post.status, StatusCode::FORBIDDEN);
+    }
+}
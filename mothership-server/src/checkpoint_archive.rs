@@ -0,0 +1,291 @@
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::Response,
+    routing::get,
+    Router,
+};
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use flate2::{write::GzEncoder, Compression};
+use futures_util::stream;
+use mothership_common::{ChangeType, CheckpointId, FileChange, ProjectId};
+use serde::Deserialize;
+use std::io::{self, BufWriter, Seek, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::runtime::Handle;
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use crate::audit::{self, AuditAction, AuditEvent};
+use crate::project_visibility;
+use crate::storage::StorageEngine;
+use crate::AppState;
+
+/// Size of the pieces the archive is sent to the client in
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Pieces written ahead of the client before the writer waits
+const CHUNKS_IN_FLIGHT: usize = 8;
+
+/// Checkpoint archive download endpoint
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/projects/:id/checkpoints/:checkpoint_id/archive", get(download_archive))
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+enum ArchiveFormat {
+    #[default]
+    #[serde(rename = "zip")]
+    Zip,
+    #[serde(rename = "tar.gz")]
+    TarGz,
+}
+
+impl ArchiveFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => "zip",
+            ArchiveFormat::TarGz => "tar.gz",
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => "application/zip",
+            ArchiveFormat::TarGz => "application/gzip",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ArchiveQuery {
+    #[serde(default)]
+    format: ArchiveFormat,
+}
+
+/// What goes into an archive: every file of the checkpoint under one
+/// top-level directory
+struct ArchiveContents {
+    root: String,
+    files: Vec<FileChange>,
+    timestamp: DateTime<Utc>,
+}
+
+/// Download the tree at a checkpoint as a zip or gzipped tarball
+async fn download_archive(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((project_id, checkpoint_id)): Path<(ProjectId, CheckpointId)>,
+    Query(query): Query<ArchiveQuery>,
+) -> Result<Response, StatusCode> {
+    let user_id = project_visibility::optional_user(&state, &headers)?;
+    let reader = project_visibility::authorize_read(&state, user_id, project_id).await?;
+
+    let project = match state.db.get_project(project_id).await {
+        Ok(Some(project)) => project,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    // Load the checkpoint and make sure it belongs to this project
+    let checkpoint = match state.sync.storage.load_checkpoint(checkpoint_id).await {
+        Ok(Some(checkpoint)) => checkpoint,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            error!("Failed to load checkpoint: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+    let rift = match state.db.get_rift(checkpoint.rift_id).await {
+        Ok(Some(rift)) if rift.project_id == project_id => rift,
+        Ok(_) => return Err(StatusCode::NOT_FOUND),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    };
+    match project_visibility::can_read_rift(&state.db, &rift, reader).await {
+        Ok(true) => {}
+        Ok(false) => return Err(StatusCode::NOT_FOUND),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+
+    let root = format!("{}-{}", file_name_safe(&project.name), &checkpoint_id.to_string()[..8]);
+    // Checkpoints are full snapshots, so anything not deleted is part of the tree
+    let files: Vec<FileChange> = checkpoint.changes
+        .into_iter()
+        .filter(|change| !matches!(change.change_type, ChangeType::Deleted))
+        .collect();
+
+    info!("📦 Archiving checkpoint {} of project '{}' ({} files, {}) for {:?}",
+        checkpoint_id, project.name, files.len(), query.format.extension(), reader);
+    audit::record(&state.db, AuditEvent::new(AuditAction::CheckpointDownloaded, user_id)
+        .project(project_id)
+        .target(checkpoint_id.to_string())
+        .details(serde_json::json!({ "format": query.format.extension(), "file_count": files.len() }))).await;
+
+    let file_name = format!("{}.{}", root, query.format.extension());
+    let contents = ArchiveContents { root, files, timestamp: checkpoint.timestamp };
+    let body = spawn_archive_writer(state.sync.storage.clone(), contents, query.format);
+
+    Ok(Response::builder()
+        .header(header::CONTENT_TYPE, query.format.content_type())
+        .header(header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", file_name))
+        .body(body)
+        .unwrap())
+}
+
+/// Write the archive on a blocking thread, reading one file at a time from
+/// storage, and stream it out as it is written. A failure part way cuts the
+/// download off, so the client can't mistake it for a whole archive.
+fn spawn_archive_writer(storage: Arc<StorageEngine>, contents: ArchiveContents, format: ArchiveFormat) -> Body {
+    let (sender, receiver) = mpsc::channel::<io::Result<Vec<u8>>>(CHUNKS_IN_FLIGHT);
+    let handle = Handle::current();
+
+    tokio::task::spawn_blocking(move || {
+        let writer = BufWriter::with_capacity(CHUNK_SIZE, ChannelWriter { sender: sender.clone() });
+        let result = match format {
+            ArchiveFormat::Zip => write_zip(&handle, &storage, &contents, writer),
+            ArchiveFormat::TarGz => write_tar_gz(&handle, &storage, &contents, writer),
+        };
+        if let Err(e) = result {
+            // The client going away closes the channel; nothing to report then
+            if !sender.is_closed() {
+                error!("Failed to write archive {}: {}", contents.root, e);
+                let _ = sender.blocking_send(Err(e));
+            }
+        }
+    });
+
+    Body::from_stream(stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|chunk| (chunk, receiver))
+    }))
+}
+
+/// Blocking writer that hands everything written to the response body
+struct ChannelWriter {
+    sender: mpsc::Sender<io::Result<Vec<u8>>>,
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.sender
+            .blocking_send(Ok(buf.to_vec()))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "download cancelled"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A file's content, read from storage on the blocking thread. Files whose
+/// content is gone are skipped, as restores do.
+fn read_content(handle: &Handle, storage: &StorageEngine, change: &FileChange) -> io::Result<Option<String>> {
+    match handle.block_on(storage.get_content(&change.content_hash)) {
+        Ok(Some(content)) => Ok(Some(content)),
+        Ok(None) => {
+            warn!("Content of {} ({}) is missing from storage", change.path.display(), change.content_hash);
+            Ok(None)
+        }
+        Err(e) => Err(io::Error::other(format!("failed to read {}: {}", change.path.display(), e))),
+    }
+}
+
+fn write_tar_gz(handle: &Handle, storage: &StorageEngine, contents: &ArchiveContents, writer: impl Write) -> io::Result<()> {
+    let mut builder = tar::Builder::new(GzEncoder::new(writer, Compression::default()));
+    for change in &contents.files {
+        let Some(content) = read_content(handle, storage, change)? else {
+            continue;
+        };
+        let path = PathBuf::from(&contents.root).join(&change.path);
+        let mtime = change.metadata.mtime.unwrap_or(contents.timestamp).timestamp().max(0) as u64;
+
+        let mut header = tar::Header::new_gnu();
+        header.set_mtime(mtime);
+        if let Some(target) = &change.metadata.symlink_target {
+            header.set_entry_type(tar::EntryType::Symlink);
+            header.set_mode(0o777);
+            header.set_size(0);
+            builder.append_link(&mut header, &path, target)?;
+        } else {
+            header.set_entry_type(tar::EntryType::Regular);
+            header.set_mode(change.metadata.mode.unwrap_or(0o644));
+            header.set_size(content.len() as u64);
+            builder.append_data(&mut header, &path, content.as_bytes())?;
+        }
+    }
+    builder.into_inner()?.finish()?.flush()
+}
+
+/// Zip keeps each entry's size in front of it, which needs seeking back, so
+/// the zip is built in a temporary file and copied out once it's complete
+fn write_zip(handle: &Handle, storage: &StorageEngine, contents: &ArchiveContents, mut writer: impl Write) -> io::Result<()> {
+    let temp = TempFile::create(&format!("{}.zip", contents.root))?;
+    let mut zip = ZipWriter::new(temp.file.try_clone()?);
+
+    let timestamp = zip_time(contents.timestamp);
+    for change in &contents.files {
+        let Some(content) = read_content(handle, storage, change)? else {
+            continue;
+        };
+        let name = format!("{}/{}", contents.root, change.path.to_string_lossy().replace('\\', "/"));
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .last_modified_time(change.metadata.mtime.map(zip_time).unwrap_or(timestamp))
+            .large_file(content.len() as u64 >= u32::MAX as u64);
+
+        if let Some(target) = &change.metadata.symlink_target {
+            zip.add_symlink(name, target.to_string_lossy(), options)?;
+        } else {
+            zip.start_file(name, options.unix_permissions(change.metadata.mode.unwrap_or(0o644)))?;
+            zip.write_all(content.as_bytes())?;
+        }
+    }
+
+    let mut file = zip.finish()?;
+    file.rewind()?;
+    io::copy(&mut file, &mut writer)?;
+    writer.flush()
+}
+
+/// Zip timestamps only go back to 1980; older ones are clamped to that
+fn zip_time(time: DateTime<Utc>) -> zip::DateTime {
+    zip::DateTime::from_date_and_time(
+        time.year().clamp(1980, 2107) as u16,
+        time.month() as u8,
+        time.day() as u8,
+        time.hour() as u8,
+        time.minute() as u8,
+        time.second().min(59) as u8,
+    ).unwrap_or_default()
+}
+
+/// A file in the system's temporary directory, removed when dropped
+struct TempFile {
+    path: PathBuf,
+    file: std::fs::File,
+}
+
+impl TempFile {
+    fn create(name: &str) -> io::Result<Self> {
+        let path = std::env::temp_dir().join(format!("mothership-{}-{}", uuid::Uuid::new_v4(), name));
+        let file = std::fs::OpenOptions::new().read(true).write(true).create_new(true).open(&path)?;
+        Ok(Self { path, file })
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// A project name as it can appear in a file name
+fn file_name_safe(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' { c } else { '-' })
+        .collect()
+}
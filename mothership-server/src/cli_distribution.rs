@@ -7,11 +7,15 @@ use axum::{
     Router,
 };
 use mothership_common::protocol::ApiResponse;
+use mothership_common::release::{
+    self, compare_versions, sha256_hex, DeltaPatch, ReleaseArtifact, ReleaseChannel, ReleaseManifest,
+};
 use serde::{Deserialize, Serialize};
 use tokio::fs;
 use tracing::{info, warn, error};
 
-/// CLI distribution endpoints for self-hosted binary updates
+/// CLI distribution endpoints for self-hosted binary updates, and the
+/// desktop app's updater endpoints
 pub fn routes() -> Router<crate::AppState> {
     Router::new()
         .route("/cli/install", get(serve_install_script))
@@ -20,11 +24,17 @@ pub fn routes() -> Router<crate::AppState> {
         .route("/cli/latest", get(get_latest_version))
         .route("/cli/download/:version/:platform/:binary", get(download_binary))
         .route("/cli/update-check", get(check_for_updates))
+        .route("/cli/channels/:channel/latest", get(get_channel_release))
+        .route("/cli/releases/:version", get(get_release))
+        .route("/cli/delta/:from_version/:version/:platform/:binary", get(download_delta))
+        .route("/gui/update/:channel/:target/:arch/:current_version", get(check_for_gui_update))
+        .route("/gui/download/:channel/:version/:platform/:file", get(download_gui_artifact))
 }
 
 #[derive(Debug, Serialize, Clone)]
 struct VersionInfo {
     version: String,
+    channel: ReleaseChannel,
     platforms: Vec<String>,
     release_date: chrono::DateTime<chrono::Utc>,
     changes: Vec<String>,
@@ -39,6 +49,46 @@ struct UpdateCheckResponse {
     changes: Vec<String>,
 }
 
+/// An update in the format the Tauri updater expects
+#[derive(Debug, Serialize)]
+struct GuiUpdateManifest {
+    version: String,
+    notes: String,
+    pub_date: chrono::DateTime<chrono::Utc>,
+    url: String,
+    signature: String,
+}
+
+/// Optional `release.json` in a CLI version directory; versions without
+/// one are stable
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ReleaseNotes {
+    channel: ReleaseChannel,
+    changes: Vec<String>,
+    release_date: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReleaseQuery {
+    platform: String,
+    /// Version the client runs, to offer delta patches from
+    from_version: Option<String>,
+}
+
+/// A desktop app release for one platform
+#[derive(Debug)]
+struct GuiRelease {
+    channel: ReleaseChannel,
+    version: String,
+    /// File name of the updater bundle
+    bundle: String,
+    /// Contents of the bundle's `.sig` file
+    signature: String,
+    notes: String,
+    pub_date: chrono::DateTime<chrono::Utc>,
+}
+
 #[derive(Debug, Deserialize)]
 struct UpdateCheckQuery {
     current_version: Option<String>,
@@ -51,8 +101,8 @@ async fn serve_install_script(
     State(state): State<crate::AppState>,
     headers: HeaderMap,
 ) -> Result<Response, StatusCode> {
-    // Require authentication if whitelist is enabled (private deployment)
-    if state.whitelist.is_some() {
+    // Require authentication if access is restricted (private deployment)
+    if state.access_restricted() {
         let _user = verify_authenticated_user(&state, &headers).await?;
         info!("📋 Serving install script to authenticated user");
     } else {
@@ -155,8 +205,8 @@ async fn serve_install_script_platform(
     State(state): State<crate::AppState>,
     headers: HeaderMap,
 ) -> Result<Response, StatusCode> {
-    // Require authentication if whitelist is enabled (private deployment)
-    if state.whitelist.is_some() {
+    // Require authentication if access is restricted (private deployment)
+    if state.access_restricted() {
         let _user = verify_authenticated_user(&state, &headers).await?;
         info!("📋 Serving platform-specific install script to authenticated user");
     } else {
@@ -164,7 +214,7 @@ async fn serve_install_script_platform(
     }
     let server_url = get_server_url(&state).await;
     
-    let auth_required = state.config.cli_distribution.require_auth_for_downloads || state.whitelist.is_some();
+    let auth_required = state.config().cli_distribution.require_auth_for_downloads || state.access_restricted();
     
     let script = match platform.as_str() {
         "windows" => generate_windows_install_script(&server_url, auth_required),
@@ -212,6 +262,7 @@ async fn get_latest_version(
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
     let latest = versions.into_iter()
+        .filter(|version| version.channel == ReleaseChannel::Stable)
         .max_by(|a, b| compare_versions(&a.version, &b.version))
         .ok_or(StatusCode::NOT_FOUND)?;
     
@@ -231,7 +282,10 @@ async fn download_binary(
         return Err(StatusCode::BAD_REQUEST);
     }
     
-    let binary_path = format!("cli-binaries/{}/{}/{}", version, platform, binary);
+    let binary_path = std::path::Path::new(&state.config().cli_distribution.binaries_path)
+        .join(&version)
+        .join(&platform)
+        .join(&binary);
     
     match fs::read(&binary_path).await {
         Ok(data) => {
@@ -244,7 +298,7 @@ async fn download_binary(
                 .unwrap())
         }
         Err(_) => {
-            warn!("❌ Binary not found: {} (requested by user: {})", binary_path, username);
+            warn!("❌ Binary not found: {} (requested by user: {})", binary_path.display(), username);
             Err(StatusCode::NOT_FOUND)
         }
     }
@@ -286,6 +340,170 @@ async fn check_for_updates(
     Ok(axum::Json(ApiResponse::success(response)))
 }
 
+/// The newest release on a channel (or a steadier one) for a platform,
+/// with checksums, signatures and delta patches from `from_version`
+async fn get_channel_release(
+    State(state): State<crate::AppState>,
+    headers: HeaderMap,
+    Path(channel): Path<String>,
+    Query(query): Query<ReleaseQuery>,
+) -> Result<axum::Json<ApiResponse<ReleaseManifest>>, StatusCode> {
+    let (user_id, username, _) = verify_authenticated_user(&state, &headers).await?;
+    let channel: ReleaseChannel = channel.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    if !is_valid_platform(&query.platform) || !query.from_version.as_deref().is_none_or(is_valid_version) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    info!("📋 Getting latest {} release ({}) for user: {} ({})", channel, query.platform, username, user_id);
+
+    let root = std::path::PathBuf::from(&state.config().cli_distribution.binaries_path);
+    let manifest = tokio::task::spawn_blocking(move || {
+        let version = latest_cli_version(&root, channel, &query.platform)?;
+        build_release_manifest(&root, &version, &query.platform, query.from_version.as_deref())
+    })
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(axum::Json(ApiResponse::success(manifest)))
+}
+
+/// A specific release for a platform, with checksums, signatures and delta
+/// patches from `from_version`
+async fn get_release(
+    State(state): State<crate::AppState>,
+    headers: HeaderMap,
+    Path(version): Path<String>,
+    Query(query): Query<ReleaseQuery>,
+) -> Result<axum::Json<ApiResponse<ReleaseManifest>>, StatusCode> {
+    let (user_id, username, _) = verify_authenticated_user(&state, &headers).await?;
+    if !is_valid_version(&version)
+        || !is_valid_platform(&query.platform)
+        || !query.from_version.as_deref().is_none_or(is_valid_version)
+    {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    info!("📋 Getting release {} ({}) for user: {} ({})", version, query.platform, username, user_id);
+
+    let root = std::path::PathBuf::from(&state.config().cli_distribution.binaries_path);
+    let manifest = tokio::task::spawn_blocking(move || {
+        build_release_manifest(&root, &version, &query.platform, query.from_version.as_deref())
+    })
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(axum::Json(ApiResponse::success(manifest)))
+}
+
+/// Download a delta patch offered in a release manifest
+async fn download_delta(
+    State(state): State<crate::AppState>,
+    headers: HeaderMap,
+    Path((from_version, version, platform, binary)): Path<(String, String, String, String)>,
+) -> Result<Response, StatusCode> {
+    let (user_id, username, _) = verify_authenticated_user(&state, &headers).await?;
+    if !is_valid_version(&from_version) || !is_valid_version(&version) || !is_valid_platform(&platform) || !is_valid_binary(&binary) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let config = state.config();
+    let root = std::path::Path::new(&config.cli_distribution.binaries_path);
+    let patch_path = delta_path(root, &from_version, &version, &platform, &binary);
+    match fs::read(&patch_path).await {
+        Ok(data) => {
+            info!("📦 Serving delta: {} {} → {} ({}) to user: {} ({})", binary, from_version, version, platform, username, user_id);
+            Ok(Response::builder()
+                .header(header::CONTENT_TYPE, "application/octet-stream")
+                .body(data.into())
+                .unwrap())
+        }
+        Err(_) => {
+            warn!("❌ Delta not found: {} (requested by user: {})", patch_path.display(), username);
+            Err(StatusCode::NOT_FOUND)
+        }
+    }
+}
+
+/// Tell the desktop app's updater about a newer release, or answer 204
+/// No Content when it's up to date
+async fn check_for_gui_update(
+    State(state): State<crate::AppState>,
+    headers: HeaderMap,
+    Path((channel, target, arch, current_version)): Path<(String, String, String, String)>,
+) -> Result<Response, StatusCode> {
+    let (user_id, username, _) = verify_authenticated_user(&state, &headers).await?;
+    let channel: ReleaseChannel = channel.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    if !is_valid_gui_platform(&target, &arch) || !is_valid_version(&current_version) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    info!("🔄 Checking GUI updates ({}, {}-{}) for user: {} ({})", channel, target, arch, username, user_id);
+
+    let platform = format!("{}-{}", target, arch);
+    let root = std::path::PathBuf::from(&state.config().cli_distribution.gui_releases_path);
+    let release = tokio::task::spawn_blocking(move || find_gui_release(&root, channel, &platform))
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let Some(release) = release.filter(|release| compare_versions(&release.version, &current_version).is_gt()) else {
+        return Ok(Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .body(axum::body::Body::empty())
+            .unwrap());
+    };
+
+    let server_url = get_server_url(&state).await;
+    let manifest = GuiUpdateManifest {
+        url: format!(
+            "{}/gui/download/{}/{}/{}-{}/{}",
+            server_url, release.channel, release.version, target, arch, release.bundle
+        ),
+        version: release.version,
+        notes: release.notes,
+        pub_date: release.pub_date,
+        signature: release.signature,
+    };
+    let body = serde_json::to_vec(&manifest).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Response::builder()
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(body.into())
+        .unwrap())
+}
+
+/// Download a desktop app updater bundle
+async fn download_gui_artifact(
+    State(state): State<crate::AppState>,
+    headers: HeaderMap,
+    Path((channel, version, platform, file)): Path<(String, String, String, String)>,
+) -> Result<Response, StatusCode> {
+    let (user_id, username, _) = verify_authenticated_user(&state, &headers).await?;
+    let channel: ReleaseChannel = channel.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    let valid_platform = platform.split_once('-')
+        .is_some_and(|(target, arch)| is_valid_gui_platform(target, arch));
+    if !is_valid_version(&version) || !valid_platform || !is_valid_gui_file(&file) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let path = std::path::Path::new(&state.config().cli_distribution.gui_releases_path)
+        .join(channel.as_str())
+        .join(&version)
+        .join(&platform)
+        .join(&file);
+    match fs::read(&path).await {
+        Ok(data) => {
+            info!("📦 Serving GUI bundle: {} ({} {}) to user: {} ({})", file, version, platform, username, user_id);
+            Ok(Response::builder()
+                .header(header::CONTENT_TYPE, "application/octet-stream")
+                .header(header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", file))
+                .body(data.into())
+                .unwrap())
+        }
+        Err(_) => {
+            warn!("❌ GUI bundle not found: {} (requested by user: {})", path.display(), username);
+            Err(StatusCode::NOT_FOUND)
+        }
+    }
+}
+
 // Helper functions
 
 /// Verify authentication token and check whitelist
@@ -293,13 +511,13 @@ async fn verify_authenticated_user(
     state: &crate::AppState,
     headers: &HeaderMap,
 ) -> Result<(uuid::Uuid, String, String), StatusCode> {
-    // Always require auth if whitelist is enabled, regardless of config
-    if state.whitelist.is_some() && !state.config.cli_distribution.require_auth_for_downloads {
+    // Always require auth if access is restricted, regardless of config
+    if state.access_restricted() && !state.config().cli_distribution.require_auth_for_downloads {
         warn!("🔒 Whitelist enabled but CLI auth disabled - this is a security risk!");
     }
     
-    // Skip authentication only if both whitelist is disabled AND auth is disabled
-    if state.whitelist.is_none() && !state.config.cli_distribution.require_auth_for_downloads {
+    // Skip authentication only if both access restrictions AND auth are disabled
+    if !state.access_restricted() && !state.config().cli_distribution.require_auth_for_downloads {
         info!("🔓 CLI access allowed without authentication (no whitelist, auth disabled)");
         // Return a dummy user for logging purposes
         return Ok((
@@ -346,8 +564,8 @@ async fn verify_authenticated_user(
         })?;
 
     // Check whitelist if enabled
-    if let Some(whitelist) = &state.whitelist {
-        if !whitelist.is_user_allowed(&user.username, &user.email) {
+    if state.access_restricted() {
+        if !crate::whitelist::user_allowed(state, &user).await {
             warn!("❌ CLI download denied - user {} ({}) not in whitelist", user.username, user.email);
             return Err(StatusCode::FORBIDDEN);
         }
@@ -391,8 +609,10 @@ async fn get_available_versions() -> Result<Vec<VersionInfo>> {
                                     let daemon_path = windows_path.join("mothership-daemon.exe");
                                     
                                     if cli_path.exists() && daemon_path.exists() {
+                                        let notes = read_release_notes(&entry.path());
                                         versions.push(VersionInfo {
                                             version: version_name,
+                                            channel: notes.channel,
                                             platforms: vec![
                                                 "x86_64-unknown-linux-gnu".to_string(),
                                                 "aarch64-unknown-linux-gnu".to_string(),
@@ -400,13 +620,17 @@ async fn get_available_versions() -> Result<Vec<VersionInfo>> {
                                                 "aarch64-apple-darwin".to_string(),
                                                 "x86_64-pc-windows-msvc".to_string(),
                                             ],
-                                            release_date: chrono::Utc::now(),
-                                            changes: vec![
-                                                "🔥 Rift system for seamless project collaboration".to_string(),
-                                                "✅ Real-time collaboration working".to_string(),
-                                                "🚀 Self-hosted CLI distribution".to_string(),
-                                                "🔧 Enhanced project detection and management".to_string(),
-                                            ],
+                                            release_date: notes.release_date.unwrap_or_else(chrono::Utc::now),
+                                            changes: if notes.changes.is_empty() {
+                                                vec![
+                                                    "🔥 Rift system for seamless project collaboration".to_string(),
+                                                    "✅ Real-time collaboration working".to_string(),
+                                                    "🚀 Self-hosted CLI distribution".to_string(),
+                                                    "🔧 Enhanced project detection and management".to_string(),
+                                                ]
+                                            } else {
+                                                notes.changes
+                                            },
                                         });
                                     }
                                 }
@@ -427,6 +651,7 @@ async fn get_available_versions() -> Result<Vec<VersionInfo>> {
     // Fallback to current version if no binaries found
     Ok(vec![VersionInfo {
         version: "0.0.41".to_string(),
+        channel: ReleaseChannel::Stable,
         platforms: vec![
             "x86_64-unknown-linux-gnu".to_string(),
             "aarch64-unknown-linux-gnu".to_string(),
@@ -443,8 +668,173 @@ async fn get_available_versions() -> Result<Vec<VersionInfo>> {
     }])
 }
 
+fn read_release_notes(version_dir: &std::path::Path) -> ReleaseNotes {
+    std::fs::read_to_string(version_dir.join("release.json"))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// The CLI and daemon binaries of a platform
+fn platform_binaries(platform: &str) -> [&'static str; 2] {
+    if platform.contains("windows") {
+        ["mothership.exe", "mothership-daemon.exe"]
+    } else {
+        ["mothership", "mothership-daemon"]
+    }
+}
+
+/// The newest CLI version with binaries for a platform on a channel or a
+/// steadier one
+fn latest_cli_version(root: &std::path::Path, channel: ReleaseChannel, platform: &str) -> Option<String> {
+    std::fs::read_dir(root).ok()?
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|version| is_valid_version(version))
+        .filter(|version| channel.includes(read_release_notes(&root.join(version)).channel))
+        .filter(|version| {
+            platform_binaries(platform).iter().all(|binary| root.join(version).join(platform).join(binary).is_file())
+        })
+        .max_by(|a, b| compare_versions(a, b))
+}
+
+fn build_release_manifest(root: &std::path::Path, version: &str, platform: &str, from_version: Option<&str>) -> Option<ReleaseManifest> {
+    let version_dir = root.join(version);
+    let notes = read_release_notes(&version_dir);
+
+    let mut artifacts = Vec::new();
+    for binary in platform_binaries(platform) {
+        let path = version_dir.join(platform).join(binary);
+        let bytes = std::fs::read(&path).ok()?;
+        let signature = std::fs::read_to_string(version_dir.join(platform).join(format!("{}.sig", binary)))
+            .ok()
+            .map(|signature| signature.trim().to_string());
+        let delta = from_version
+            .filter(|from_version| *from_version != version)
+            .and_then(|from_version| prepare_delta(root, from_version, version, platform, binary, &bytes));
+        artifacts.push(ReleaseArtifact {
+            binary: binary.to_string(),
+            size: bytes.len() as u64,
+            sha256: sha256_hex(&bytes),
+            signature,
+            delta,
+        });
+    }
+
+    let release_date = notes.release_date
+        .or_else(|| std::fs::metadata(&version_dir).and_then(|metadata| metadata.modified()).ok().map(chrono::DateTime::from))
+        .unwrap_or_else(chrono::Utc::now);
+    Some(ReleaseManifest {
+        version: version.to_string(),
+        channel: notes.channel,
+        platform: platform.to_string(),
+        release_date,
+        changes: notes.changes,
+        artifacts,
+    })
+}
+
+/// Where the patch from one version's binary to another's is cached
+fn delta_path(root: &std::path::Path, from_version: &str, version: &str, platform: &str, binary: &str) -> std::path::PathBuf {
+    root.join(version).join(platform).join("deltas").join(format!("{}.from-{}.patch", binary, from_version))
+}
+
+/// The patch from `from_version`'s binary, made and cached the first time
+/// someone asks. None when the old binary is gone or the patch wouldn't
+/// save anything.
+fn prepare_delta(root: &std::path::Path, from_version: &str, version: &str, platform: &str, binary: &str, new: &[u8]) -> Option<DeltaPatch> {
+    let old = std::fs::read(root.join(from_version).join(platform).join(binary)).ok()?;
+    let patch_path = delta_path(root, from_version, version, platform, binary);
+    let patch = match std::fs::read(&patch_path) {
+        Ok(patch) => patch,
+        Err(_) => {
+            let patch = release::create_delta(&old, new);
+            // Written under a unique name first so concurrent requests never serve half a patch
+            let temp_path = patch_path.with_extension(format!("{}.tmp", uuid::Uuid::new_v4()));
+            let written = patch_path.parent().map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| std::fs::write(&temp_path, &patch))
+                .and_then(|_| std::fs::rename(&temp_path, &patch_path));
+            if let Err(e) = written {
+                warn!("Failed to cache delta {}: {}", patch_path.display(), e);
+                let _ = std::fs::remove_file(&temp_path);
+                return None;
+            }
+            patch
+        }
+    };
+    if patch.len() >= new.len() {
+        return None;
+    }
+
+    Some(DeltaPatch {
+        from_version: from_version.to_string(),
+        from_sha256: sha256_hex(&old),
+        size: patch.len() as u64,
+        sha256: sha256_hex(&patch),
+    })
+}
+
+/// The newest release for a platform in a channel or the steadier ones.
+/// Desktop app releases sit in a directory per channel.
+fn find_gui_release(root: &std::path::Path, channel: ReleaseChannel, platform: &str) -> Option<GuiRelease> {
+    let mut latest: Option<GuiRelease> = None;
+    for channel in ReleaseChannel::ALL.into_iter().filter(|other| channel.includes(*other)) {
+        let Ok(entries) = std::fs::read_dir(root.join(channel.as_str())) else { continue };
+        for entry in entries.flatten() {
+            let version = entry.file_name().to_string_lossy().to_string();
+            if !is_valid_version(&version) {
+                continue;
+            }
+            if latest.as_ref().is_some_and(|latest| !compare_versions(&version, &latest.version).is_gt()) {
+                continue;
+            }
+            if let Some(release) = read_gui_release(&entry.path(), channel, &version, platform) {
+                latest = Some(release);
+            }
+        }
+    }
+    latest
+}
+
+/// A release's bundle for a platform: the file in its platform directory
+/// that has a `.sig` next to it. Release notes come from `notes.md` in the
+/// version directory.
+fn read_gui_release(version_dir: &std::path::Path, channel: ReleaseChannel, version: &str, platform: &str) -> Option<GuiRelease> {
+    let platform_dir = version_dir.join(platform);
+    let bundle = std::fs::read_dir(&platform_dir).ok()?
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .find(|name| !name.ends_with(".sig") && platform_dir.join(format!("{}.sig", name)).is_file())?;
+    let signature = std::fs::read_to_string(platform_dir.join(format!("{}.sig", bundle))).ok()?;
+    let pub_date = std::fs::metadata(platform_dir.join(&bundle))
+        .and_then(|metadata| metadata.modified())
+        .map(chrono::DateTime::<chrono::Utc>::from)
+        .unwrap_or_else(|_| chrono::Utc::now());
+
+    Some(GuiRelease {
+        channel,
+        version: version.to_string(),
+        bundle,
+        signature: signature.trim().to_string(),
+        notes: std::fs::read_to_string(version_dir.join("notes.md")).unwrap_or_default(),
+        pub_date,
+    })
+}
+
+fn is_valid_gui_platform(target: &str, arch: &str) -> bool {
+    matches!(target, "linux" | "darwin" | "windows") && matches!(arch, "x86_64" | "aarch64")
+}
+
+fn is_valid_gui_file(file: &str) -> bool {
+    !file.is_empty()
+        && !file.starts_with('.')
+        && file.chars().all(|c| c.is_alphanumeric() || matches!(c, '.' | '-' | '_'))
+}
+
 fn is_valid_version(version: &str) -> bool {
-    version.chars().all(|c| c.is_alphanumeric() || c == '.' || c == '-')
+    !version.is_empty()
+        && !version.starts_with('.')
+        && version.chars().all(|c| c.is_alphanumeric() || c == '.' || c == '-')
 }
 
 fn is_valid_platform(platform: &str) -> bool {
@@ -466,32 +856,6 @@ fn version_compare(current: &str, latest: &str) -> bool {
     current != latest
 }
 
-/// Compare two semantic versions (e.g., "0.0.26" vs "0.0.9")
-/// Returns Ordering for use in sort functions
-fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
-    let parse_version = |v: &str| -> Vec<u32> {
-        v.split('.')
-            .map(|part| part.parse::<u32>().unwrap_or(0))
-            .collect()
-    };
-    
-    let a_parts = parse_version(a);
-    let b_parts = parse_version(b);
-    
-    // Compare each version component
-    for i in 0..std::cmp::max(a_parts.len(), b_parts.len()) {
-        let a_part = a_parts.get(i).unwrap_or(&0);
-        let b_part = b_parts.get(i).unwrap_or(&0);
-        
-        match a_part.cmp(b_part) {
-            std::cmp::Ordering::Equal => continue,
-            other => return other,
-        }
-    }
-    
-    std::cmp::Ordering::Equal
-}
-
 fn generate_windows_install_script(server_url: &str, auth_required: bool) -> String {
     if auth_required {
         format!(r#"# Mothership CLI Installation Script for Windows (Self-Hosted)
@@ -0,0 +1,277 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use mothership_common::{
+    protocol::{ApiResponse, CommentThread, CreateCommentRequest, ReplyCommentRequest},
+    ChangeType, Checkpoint, CheckpointId, ProjectId, UserId,
+};
+use serde::Deserialize;
+use std::path::PathBuf;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::api_error::ApiError;
+use crate::database::NewComment;
+use crate::handlers::authenticate_request;
+use crate::{archive, inbox, AppState};
+
+/// Longest accepted comment, in characters
+const MAX_COMMENT_LENGTH: usize = 10_000;
+
+/// Checkpoint comment endpoints
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/projects/:id/checkpoints/:checkpoint_id/comments",
+            get(list_checkpoint_comments).post(create_comment),
+        )
+        .route("/projects/:id/comments", get(list_project_comments))
+        .route("/projects/:id/comments/:thread_id/replies", post(reply_to_thread))
+        .route("/projects/:id/comments/:thread_id/resolve", post(resolve_thread))
+        .route("/projects/:id/comments/:thread_id/unresolve", post(unresolve_thread))
+}
+
+#[derive(Debug, Deserialize)]
+struct CheckpointCommentsQuery {
+    /// Only threads on this file
+    path: Option<PathBuf>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProjectCommentsQuery {
+    #[serde(default)]
+    unresolved: bool,
+}
+
+/// Authenticate the request and check the user can access the project
+async fn authorize_project(state: &AppState, headers: &HeaderMap, project_id: ProjectId) -> Result<UserId, StatusCode> {
+    let user_id = authenticate_request(state, headers)?;
+
+    match state.db.user_has_project_access(user_id, project_id).await {
+        Ok(true) => Ok(user_id),
+        Ok(false) => Err(StatusCode::FORBIDDEN),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// Load a checkpoint, making sure it belongs to the project
+async fn load_checkpoint(state: &AppState, project_id: ProjectId, checkpoint_id: CheckpointId) -> Result<Checkpoint, StatusCode> {
+    let checkpoint = match state.sync.storage.load_checkpoint(checkpoint_id).await {
+        Ok(Some(checkpoint)) => checkpoint,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            error!("Failed to load checkpoint {}: {}", checkpoint_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    match state.db.get_rift(checkpoint.rift_id).await {
+        Ok(Some(rift)) if rift.project_id == project_id => Ok(checkpoint),
+        Ok(_) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// Load one thread of a project
+async fn load_thread(state: &AppState, project_id: ProjectId, thread_id: Uuid) -> Result<CommentThread, StatusCode> {
+    let threads = state.db.list_comment_threads(project_id, None, Some(thread_id), false).await
+        .map_err(|e| {
+            error!("Failed to load comment thread {}: {}", thread_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    threads.into_iter().next().ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Trim a comment and check it isn't empty or too long
+fn comment_body(body: &str) -> Result<&str, ApiError> {
+    let body = body.trim();
+    if body.is_empty() || body.chars().count() > MAX_COMMENT_LENGTH {
+        return Err(ApiError::bad_request(format!(
+            "Comment must be between 1 and {} characters",
+            MAX_COMMENT_LENGTH
+        )));
+    }
+    Ok(body)
+}
+
+/// List the comment threads on a checkpoint, optionally only those on one file
+async fn list_checkpoint_comments(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((project_id, checkpoint_id)): Path<(ProjectId, CheckpointId)>,
+    Query(query): Query<CheckpointCommentsQuery>,
+) -> Result<Json<ApiResponse<Vec<CommentThread>>>, StatusCode> {
+    authorize_project(&state, &headers, project_id).await?;
+    load_checkpoint(&state, project_id, checkpoint_id).await?;
+
+    let mut threads = state.db.list_comment_threads(project_id, Some(checkpoint_id), None, false).await
+        .map_err(|e| {
+            error!("Failed to list comments for checkpoint {}: {}", checkpoint_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if let Some(path) = &query.path {
+        threads.retain(|thread| &thread.path == path);
+    }
+
+    Ok(Json(ApiResponse::success(threads)))
+}
+
+/// List a project's comment threads across all checkpoints, optionally only
+/// the ones still waiting to be resolved
+async fn list_project_comments(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(project_id): Path<ProjectId>,
+    Query(query): Query<ProjectCommentsQuery>,
+) -> Result<Json<ApiResponse<Vec<CommentThread>>>, StatusCode> {
+    authorize_project(&state, &headers, project_id).await?;
+
+    let threads = state.db.list_comment_threads(project_id, None, None, query.unresolved).await
+        .map_err(|e| {
+            error!("Failed to list comments for project {}: {}", project_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ApiResponse::success(threads)))
+}
+
+/// Start a comment thread on a line range of a file at a checkpoint
+async fn create_comment(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((project_id, checkpoint_id)): Path<(ProjectId, CheckpointId)>,
+    Json(req): Json<CreateCommentRequest>,
+) -> Result<Json<ApiResponse<CommentThread>>, ApiError> {
+    let user_id = authorize_project(&state, &headers, project_id).await?;
+    archive::ensure_writable(&state, project_id)?;
+    let checkpoint = load_checkpoint(&state, project_id, checkpoint_id).await?;
+    let body = comment_body(&req.body)?;
+
+    // Checkpoints are full snapshots, so the file must be in this one
+    let Some(change) = checkpoint.changes.iter()
+        .find(|change| change.path == req.path && !matches!(change.change_type, ChangeType::Deleted))
+    else {
+        return Err(ApiError::not_found(format!("{} is not in this checkpoint", req.path.display())));
+    };
+    let content = match state.sync.storage.get_content(&change.content_hash).await {
+        Ok(Some(content)) => content,
+        Ok(None) => return Err(ApiError::not_found(format!("Content of {} is missing", req.path.display()))),
+        Err(e) => {
+            error!("Failed to read {} to comment on it: {}", req.path.display(), e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into());
+        }
+    };
+
+    let line_count = content.lines().count().max(1) as u32;
+    let line_end = req.line_end.unwrap_or(req.line_start);
+    if req.line_start < 1 || req.line_start > line_end || line_end > line_count {
+        return Err(ApiError::bad_request(format!(
+            "Lines must be a range within 1-{} of {}",
+            line_count,
+            req.path.display()
+        )));
+    }
+
+    let id = Uuid::new_v4();
+    let path = req.path.to_string_lossy().replace('\\', "/");
+    state.db
+        .add_checkpoint_comment(NewComment {
+            id,
+            project_id,
+            checkpoint_id,
+            thread_id: id,
+            path: &path,
+            line_start: req.line_start,
+            line_end,
+            author_id: user_id,
+            body,
+        })
+        .await
+        .map_err(|e| {
+            error!("Failed to store comment on checkpoint {}: {}", checkpoint_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    info!("💬 New comment thread on {}:{}-{} at checkpoint {}", path, req.line_start, line_end, checkpoint_id);
+    inbox::deliver_mentions(&state.sync, project_id, user_id, body, &format!("You were mentioned on {}:{}", path, req.line_start)).await;
+    Ok(Json(ApiResponse::success(load_thread(&state, project_id, id).await?)))
+}
+
+/// Add a reply to the end of a thread
+async fn reply_to_thread(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((project_id, thread_id)): Path<(ProjectId, Uuid)>,
+    Json(req): Json<ReplyCommentRequest>,
+) -> Result<Json<ApiResponse<CommentThread>>, ApiError> {
+    let user_id = authorize_project(&state, &headers, project_id).await?;
+    archive::ensure_writable(&state, project_id)?;
+    let body = comment_body(&req.body)?;
+    let thread = load_thread(&state, project_id, thread_id).await?;
+
+    let path = thread.path.to_string_lossy();
+    state.db
+        .add_checkpoint_comment(NewComment {
+            id: Uuid::new_v4(),
+            project_id,
+            checkpoint_id: thread.checkpoint_id,
+            thread_id: thread.id,
+            path: &path,
+            line_start: thread.line_start,
+            line_end: thread.line_end,
+            author_id: user_id,
+            body,
+        })
+        .await
+        .map_err(|e| {
+            error!("Failed to store reply to comment thread {}: {}", thread_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    inbox::deliver_mentions(&state.sync, project_id, user_id, body, &format!("You were mentioned on {}:{}", path, thread.line_start)).await;
+
+    Ok(Json(ApiResponse::success(load_thread(&state, project_id, thread_id).await?)))
+}
+
+/// Mark a thread resolved
+async fn resolve_thread(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((project_id, thread_id)): Path<(ProjectId, Uuid)>,
+) -> Result<Json<ApiResponse<CommentThread>>, ApiError> {
+    let user_id = authorize_project(&state, &headers, project_id).await?;
+    set_resolved(&state, project_id, thread_id, Some(user_id)).await
+}
+
+/// Reopen a resolved thread
+async fn unresolve_thread(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((project_id, thread_id)): Path<(ProjectId, Uuid)>,
+) -> Result<Json<ApiResponse<CommentThread>>, ApiError> {
+    authorize_project(&state, &headers, project_id).await?;
+    set_resolved(&state, project_id, thread_id, None).await
+}
+
+async fn set_resolved(
+    state: &AppState,
+    project_id: ProjectId,
+    thread_id: Uuid,
+    resolved_by: Option<UserId>,
+) -> Result<Json<ApiResponse<CommentThread>>, ApiError> {
+    archive::ensure_writable(state, project_id)?;
+
+    let found = state.db.set_comment_thread_resolved(project_id, thread_id, resolved_by).await
+        .map_err(|e| {
+            error!("Failed to update comment thread {}: {}", thread_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if !found {
+        return Err(StatusCode::NOT_FOUND.into());
+    }
+
+    info!("💬 Comment thread {} {}", thread_id, if resolved_by.is_some() { "resolved" } else { "reopened" });
+    Ok(Json(ApiResponse::success(load_thread(state, project_id, thread_id).await?)))
+}
@@ -0,0 +1,132 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::post,
+    Json, Router,
+};
+use chrono::{Duration, Utc};
+use mothership_common::{
+    protocol::{ApiResponse, CompactHistoryRequest, HistoryCompaction},
+    ProjectId,
+};
+use tracing::{error, info, warn};
+
+use crate::audit::{self, AuditAction, AuditEvent};
+use crate::handlers::authenticate_request;
+use crate::AppState;
+
+/// How often every project's history is compacted in the background
+const COMPACTION_INTERVAL: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+
+/// Projects loaded at a time by the background job
+const PROJECT_PAGE_SIZE: usize = 100;
+
+/// History compaction endpoint
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/projects/:id/history/compact", post(compact_history))
+}
+
+/// Fold runs of old automatic checkpoints in every rift of a project
+async fn compact_history(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(project_id): Path<ProjectId>,
+    request: Option<Json<CompactHistoryRequest>>,
+) -> Result<Json<ApiResponse<HistoryCompaction>>, StatusCode> {
+    let user_id = authenticate_request(&state, &headers)?;
+    let Json(request) = request.unwrap_or_default();
+
+    let project = match state.db.get_project(project_id).await {
+        Ok(Some(project)) => project,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    };
+    match state.db.user_has_project_access(user_id, project_id).await {
+        Ok(true) => {}
+        Ok(false) => return Err(StatusCode::FORBIDDEN),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+
+    let older_than_days = request.older_than_days.unwrap_or(state.config().compaction.older_than_days);
+    let compaction = compact_project(&state, project_id, older_than_days).await
+        .map_err(|e| {
+            error!("Failed to compact history of project {}: {}", project_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    info!("🗜️ Compacted history of project {}: {} checkpoints folded into {}",
+        project.name, compaction.checkpoints_removed, compaction.summaries);
+    audit::record(&state.db, AuditEvent::new(AuditAction::HistoryCompacted, Some(user_id))
+        .project(project_id)
+        .details(serde_json::json!({
+            "older_than_days": older_than_days,
+            "summaries": compaction.summaries,
+            "checkpoints_removed": compaction.checkpoints_removed,
+        }))).await;
+
+    Ok(Json(ApiResponse::success(compaction)))
+}
+
+/// Compact the history of each of a project's rifts
+async fn compact_project(state: &AppState, project_id: ProjectId, older_than_days: u32) -> anyhow::Result<HistoryCompaction> {
+    let cutoff = Utc::now() - Duration::days(older_than_days as i64);
+    let mut compaction = HistoryCompaction { project_id, summaries: 0, checkpoints_removed: 0 };
+    for rift in state.db.get_project_rifts(project_id).await? {
+        let rift_compaction = state.sync.storage.compact_auto_checkpoints(rift.id, cutoff).await?;
+        compaction.summaries += rift_compaction.summaries;
+        compaction.checkpoints_removed += rift_compaction.removed;
+    }
+    Ok(compaction)
+}
+
+/// Periodically compact every project's history
+pub fn spawn_compactor(state: AppState) {
+    if !state.config().compaction.enabled {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(COMPACTION_INTERVAL);
+        loop {
+            interval.tick().await;
+            compact_all(&state).await;
+        }
+    });
+}
+
+async fn compact_all(state: &AppState) {
+    let older_than_days = state.config().compaction.older_than_days;
+    let mut before = None;
+    loop {
+        let projects = match state.db.list_projects(before, PROJECT_PAGE_SIZE).await {
+            Ok(projects) => projects,
+            Err(e) => {
+                error!("Failed to list projects to compact: {}", e);
+                return;
+            }
+        };
+        let Some(last) = projects.last() else {
+            return;
+        };
+        before = Some(last.id);
+
+        for project in &projects {
+            match compact_project(state, project.id, older_than_days).await {
+                Ok(compaction) if compaction.checkpoints_removed > 0 => {
+                    info!("🗜️ Compacted history of project {}: {} checkpoints folded into {}",
+                        project.name, compaction.checkpoints_removed, compaction.summaries);
+                    audit::record(&state.db, AuditEvent::new(AuditAction::HistoryCompacted, None)
+                        .project(project.id)
+                        .details(serde_json::json!({
+                            "older_than_days": older_than_days,
+                            "summaries": compaction.summaries,
+                            "checkpoints_removed": compaction.checkpoints_removed,
+                        }))).await;
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Failed to compact history of project {}: {}", project.name, e),
+            }
+        }
+    }
+}
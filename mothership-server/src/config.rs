@@ -1,6 +1,7 @@
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use mothership_common::UserRole;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 use tracing::{info, warn};
@@ -22,6 +23,58 @@ pub struct ServerConfig {
     
     /// CLI distribution settings
     pub cli_distribution: CliDistributionSettings,
+    
+    /// Git export and mirroring settings
+    #[serde(default)]
+    pub git_export: GitExportSettings,
+    
+    /// Checkpoint status check settings
+    #[serde(default)]
+    pub status_checks: StatusCheckSettings,
+    
+    /// Review request settings
+    #[serde(default)]
+    pub reviews: ReviewSettings,
+    
+    /// Email notification settings
+    #[serde(default)]
+    pub notifications: NotificationSettings,
+    
+    /// Multi-region replication settings
+    #[serde(default)]
+    pub replication: ReplicationSettings,
+    
+    /// Cross-instance sync relay settings
+    #[serde(default)]
+    pub relay: RelaySettings,
+    
+    /// Deleted project retention settings
+    #[serde(default)]
+    pub trash: TrashSettings,
+
+    /// Server-side automatic checkpoint settings
+    #[serde(default)]
+    pub auto_checkpoint: AutoCheckpointSettings,
+
+    /// History compaction settings
+    #[serde(default)]
+    pub compaction: CompactionSettings,
+
+    /// GitHub organization and team access settings
+    #[serde(default)]
+    pub github_access: GithubAccessSettings,
+
+    /// Limits on what sync connections and uploads may send
+    #[serde(default)]
+    pub sync_limits: SyncLimitSettings,
+
+    /// Malware scanning of uploaded and synced files
+    #[serde(default)]
+    pub malware_scan: MalwareScanSettings,
+
+    /// Database connection pool settings
+    #[serde(default)]
+    pub database: DatabaseSettings,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,6 +96,14 @@ pub struct ServerSettings {
     
     /// Enable detailed logging
     pub debug_logging: bool,
+
+    /// Origins allowed to call the API from a browser
+    #[serde(default = "default_cors_origins")]
+    pub cors_origins: Vec<String>,
+}
+
+fn default_cors_origins() -> Vec<String> {
+    vec!["https://app.mothershipproject.dev".to_string()]
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -64,6 +125,14 @@ pub struct FeatureSettings {
     
     /// Enable/disable WebSocket real-time sync
     pub websocket_sync_enabled: bool,
+    
+    /// Allow clients to negotiate binary (MessagePack) sync frames
+    #[serde(default = "default_binary_sync_enabled")]
+    pub binary_sync_enabled: bool,
+}
+
+fn default_binary_sync_enabled() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -121,10 +190,419 @@ pub struct CliDistributionSettings {
     
     /// Enable download statistics/analytics
     pub track_downloads: bool,
+
+    /// Directory containing desktop app releases, laid out as
+    /// `<channel>/<version>/<target>-<arch>/` (relative to server)
+    #[serde(default = "default_gui_releases_path")]
+    pub gui_releases_path: String,
+}
+
+fn default_gui_releases_path() -> String {
+    "gui-releases".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitExportSettings {
+    /// Enable Git bundle downloads and mirroring
+    pub enabled: bool,
+    
+    /// Path to the git executable
+    pub git_binary: String,
+    
+    /// Scratch directory used to build repositories (relative to server)
+    pub work_dir: String,
+    
+    /// Push to configured mirrors whenever a checkpoint is created
+    pub mirror_on_checkpoint: bool,
+
+    /// Hosts mirrors may push to; remotes on any other host are refused
+    #[serde(default = "default_allowed_remote_hosts")]
+    pub allowed_remote_hosts: Vec<String>,
+}
+
+fn default_allowed_remote_hosts() -> Vec<String> {
+    vec!["github.com".to_string(), "gitlab.com".to_string(), "bitbucket.org".to_string()]
+}
+
+impl Default for GitExportSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            git_binary: "git".to_string(),
+            work_dir: "storage/git-export".to_string(),
+            mirror_on_checkpoint: true,
+            allowed_remote_hosts: default_allowed_remote_hosts(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusCheckSettings {
+    /// Only allow a rift to be merged when its latest checkpoint's checks are green
+    /// and nothing changed since that checkpoint
+    pub require_green_for_merge: bool,
+    
+    /// Checks that must have reported success before a merge (e.g. "ci/build").
+    /// Only service accounts and admins may report them.
+    pub required_contexts: Vec<String>,
+}
+
+impl Default for StatusCheckSettings {
+    fn default() -> Self {
+        Self {
+            require_green_for_merge: false,
+            required_contexts: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewSettings {
+    /// Only allow a rift to be merged through an approved review request
+    pub require_review_for_merge: bool,
+    
+    /// Approvals a review request needs before it can be merged
+    pub required_approvals: u32,
+}
+
+impl Default for ReviewSettings {
+    fn default() -> Self {
+        Self {
+            require_review_for_merge: false,
+            required_approvals: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationSettings {
+    /// Send email notifications (invitations, mentions, conflict rifts, restores)
+    pub enabled: bool,
+    
+    /// SMTP server hostname
+    pub smtp_host: String,
+    
+    /// SMTP server port
+    pub smtp_port: u16,
+    
+    /// Use STARTTLS (usually port 587) instead of implicit TLS (usually port 465)
+    pub starttls: bool,
+    
+    /// SMTP username (empty for unauthenticated relays)
+    pub smtp_username: String,
+    
+    /// SMTP password (the SMTP_PASSWORD environment variable takes precedence)
+    pub smtp_password: String,
+    
+    /// Sender address, e.g. "Mothership <noreply@example.com>"
+    pub from_address: String,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            smtp_host: "localhost".to_string(),
+            smtp_port: 587,
+            starttls: true,
+            smtp_username: String::new(),
+            smtp_password: String::new(),
+            from_address: "Mothership <noreply@localhost>".to_string(),
+        }
+    }
+}
+
+/// What part a server plays in replication
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplicationRole {
+    /// No replication
+    Standalone,
+    /// Serves its checkpoint log and content to secondaries
+    Primary,
+    /// Follows a primary and serves read-only beams and history
+    Secondary,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicationSettings {
+    /// standalone, primary or secondary
+    pub role: ReplicationRole,
+    
+    /// Region label reported to clients (e.g. "eu-west")
+    pub region: String,
+    
+    /// Primary server to follow (secondary only)
+    pub primary_url: String,
+    
+    /// Secret shared by a primary and its secondaries (the REPLICATION_SECRET environment variable takes precedence)
+    pub shared_secret: String,
+    
+    /// Seconds between polls of the primary's checkpoint log
+    pub poll_interval_seconds: u64,
+    
+    /// Checkpoints fetched per poll
+    pub batch_size: usize,
+}
+
+impl Default for ReplicationSettings {
+    fn default() -> Self {
+        Self {
+            role: ReplicationRole::Standalone,
+            region: "default".to_string(),
+            primary_url: String::new(),
+            shared_secret: String::new(),
+            poll_interval_seconds: 5,
+            batch_size: 100,
+        }
+    }
+}
+
+impl ReplicationSettings {
+    /// Shared secret, preferring the environment over the config file
+    pub fn secret(&self) -> Option<String> {
+        let secret = std::env::var("REPLICATION_SECRET").unwrap_or_else(|_| self.shared_secret.clone());
+        (!secret.is_empty()).then_some(secret)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelaySettings {
+    /// Relay rift sync messages through Redis so several instances can serve the same rift
+    pub enabled: bool,
+    
+    /// Redis connection URL (the REDIS_URL environment variable takes precedence)
+    pub redis_url: String,
+    
+    /// Pub/sub channel shared by all instances
+    pub channel: String,
+}
+
+impl Default for RelaySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            redis_url: "redis://127.0.0.1:6379".to_string(),
+            channel: "mothership:sync".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashSettings {
+    /// Days a deleted project stays in the trash, restorable by an admin, before it is purged
+    pub retention_days: i64,
+}
+
+impl Default for TrashSettings {
+    fn default() -> Self {
+        Self { retention_days: 30 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoCheckpointSettings {
+    /// Take automatic checkpoints of rifts at their project's interval
+    pub enabled: bool,
+    /// Shortest interval honored, whatever a project asks for
+    pub min_interval_secs: u64,
+}
+
+impl Default for AutoCheckpointSettings {
+    fn default() -> Self {
+        Self { enabled: true, min_interval_secs: 60 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactionSettings {
+    /// Compact every project's history in the background once a day
+    pub enabled: bool,
+    /// Automatic checkpoints older than this many days are folded together
+    pub older_than_days: u32,
+}
+
+impl Default for CompactionSettings {
+    fn default() -> Self {
+        Self { enabled: true, older_than_days: 7 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncLimitSettings {
+    /// Largest sync message accepted, in bytes
+    pub max_message_bytes: usize,
+    /// Most files one FilesChanged or BatchDiffChanges message may carry
+    pub max_batch_files: usize,
+    /// Largest file accepted from sync connections and uploads, in bytes,
+    /// whatever a project's push rules allow
+    pub max_file_bytes: u64,
+    /// Messages a connection may send per second, once its burst is used up
+    pub max_messages_per_second: u32,
+    /// Messages a connection may send at once, e.g. when replaying changes
+    /// it queued while offline
+    pub message_burst: u32,
+}
+
+impl Default for SyncLimitSettings {
+    fn default() -> Self {
+        Self {
+            max_message_bytes: 16 * 1024 * 1024,
+            max_batch_files: 1000,
+            max_file_bytes: 10 * 1024 * 1024,
+            max_messages_per_second: 100,
+            message_burst: 1000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MalwareScanSettings {
+    /// Scanner command, e.g. "clamdscan --no-summary {path}". `{path}` is
+    /// replaced with a temporary copy of the file; without it the content is
+    /// written to the command's stdin. Exit code 0 means clean and 1 infected.
+    pub command: String,
+    /// Address of a clamd daemon ("127.0.0.1:3310"), used when no command is set
+    pub clamd_address: String,
+    /// How long one scan may take, in seconds
+    pub timeout_secs: u64,
+    /// Quarantine files the scanner failed to check
+    pub quarantine_on_error: bool,
+}
+
+impl Default for MalwareScanSettings {
+    fn default() -> Self {
+        Self {
+            command: String::new(),
+            clamd_address: String::new(),
+            timeout_secs: 60,
+            quarantine_on_error: false,
+        }
+    }
+}
+
+impl MalwareScanSettings {
+    /// Whether a scanner is configured
+    pub fn enabled(&self) -> bool {
+        !self.command.trim().is_empty() || !self.clamd_address.trim().is_empty()
+    }
+
+    pub fn timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.timeout_secs)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DatabaseSettings {
+    /// Most connections the pool opens
+    pub max_connections: u32,
+    /// Connections kept open even when idle
+    pub min_connections: u32,
+    /// How long a query waits for a free connection, in seconds
+    pub acquire_timeout_secs: u64,
+    /// How long an idle connection stays open, in seconds (0 keeps it open)
+    pub idle_timeout_secs: u64,
+    /// How long a connection is used before it is replaced, in seconds (0
+    /// keeps it indefinitely)
+    pub max_lifetime_secs: u64,
+}
+
+impl Default for DatabaseSettings {
+    fn default() -> Self {
+        Self {
+            max_connections: 10,
+            min_connections: 0,
+            acquire_timeout_secs: 30,
+            idle_timeout_secs: 600,
+            max_lifetime_secs: 1800,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GithubAccessSettings {
+    /// Organizations whose members may sign in with GitHub
+    pub orgs: Vec<String>,
+    /// Teams, as "org/team", whose members may sign in with GitHub
+    pub teams: Vec<String>,
+    /// Role given to members of an organization or team ("acme" or "acme/admins")
+    pub roles: HashMap<String, UserRole>,
+    /// GitHub token with read:org, used to re-check membership between
+    /// logins (the GITHUB_ACCESS_TOKEN environment variable takes precedence)
+    pub token: String,
+    /// How often to re-check membership, in minutes
+    pub revalidate_interval_minutes: u64,
+}
+
+impl Default for GithubAccessSettings {
+    fn default() -> Self {
+        Self {
+            orgs: Vec::new(),
+            teams: Vec::new(),
+            roles: HashMap::new(),
+            token: String::new(),
+            revalidate_interval_minutes: 60,
+        }
+    }
+}
+
+impl GithubAccessSettings {
+    /// Whether only members of the configured organizations and teams (and
+    /// whitelisted users) may sign in
+    pub fn restricts_login(&self) -> bool {
+        !self.orgs.is_empty() || !self.teams.is_empty()
+    }
+
+    /// Whether any of a user's OAuth groups is a configured organization or team
+    pub fn allows(&self, groups: &[String]) -> bool {
+        self.orgs.iter().chain(&self.teams).any(|allowed| groups.contains(&github_group(allowed)))
+    }
+
+    /// The highest role the user's groups map to
+    pub fn role_for(&self, groups: &[String]) -> Option<UserRole> {
+        self.roles.iter()
+            .filter(|(group, _)| groups.contains(&github_group(group)))
+            .map(|(_, role)| role.clone())
+            .min_by_key(role_rank)
+    }
+
+    /// Every organization and team named in the settings, as OAuth groups
+    pub fn groups(&self) -> Vec<String> {
+        let mut groups: Vec<String> = self.orgs.iter().chain(&self.teams).chain(self.roles.keys())
+            .map(|group| github_group(group))
+            .collect();
+        groups.sort();
+        groups.dedup();
+        groups
+    }
+
+    pub fn token(&self) -> Option<String> {
+        std::env::var("GITHUB_ACCESS_TOKEN").ok()
+            .filter(|token| !token.is_empty())
+            .or_else(|| (!self.token.is_empty()).then(|| self.token.clone()))
+    }
+}
+
+/// An organization ("acme") or team ("acme/admins") as an OAuth group
+pub fn github_group(name: &str) -> String {
+    format!("github:{}", name.trim().to_lowercase())
+}
+
+/// Lower ranks are more privileged
+fn role_rank(role: &UserRole) -> u8 {
+    match role {
+        UserRole::SuperAdmin => 0,
+        UserRole::Admin => 1,
+        UserRole::User => 2,
+    }
 }
 
-/// User whitelist loaded from whitelist file
-#[derive(Debug, Clone)]
+/// Users allowed on the server, from the database (seeded from the whitelist file)
+#[derive(Debug, Clone, Default)]
 pub struct UserWhitelist {
     /// Set of allowed usernames
     pub usernames: HashSet<String>,
@@ -134,6 +612,62 @@ pub struct UserWhitelist {
     
     /// Set of allowed email domains (e.g., "company.com")
     pub domains: HashSet<String>,
+
+    /// Set of allowed OAuth groups (e.g., "github:acme/platform")
+    pub groups: HashSet<String>,
+}
+
+/// What a whitelist entry matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WhitelistKind {
+    Username,
+    Email,
+    Domain,
+    Group,
+}
+
+impl WhitelistKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WhitelistKind::Username => "username",
+            WhitelistKind::Email => "email",
+            WhitelistKind::Domain => "domain",
+            WhitelistKind::Group => "group",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "username" => Some(WhitelistKind::Username),
+            "email" => Some(WhitelistKind::Email),
+            "domain" => Some(WhitelistKind::Domain),
+            "group" => Some(WhitelistKind::Group),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a whitelist entry as written in the whitelist file: `@company.com`
+/// or `*@company.com` for a domain, `group:github:acme` for an OAuth group,
+/// anything else with an `@` for an email address, and a username otherwise.
+/// Emails, domains and groups are lowercased.
+pub fn parse_whitelist_entry(entry: &str) -> Option<(WhitelistKind, String)> {
+    let entry = entry.trim();
+    if entry.is_empty() || entry.starts_with('#') {
+        return None;
+    }
+
+    let parsed = if let Some(domain) = entry.strip_prefix("*@").or_else(|| entry.strip_prefix('@')) {
+        (WhitelistKind::Domain, domain.to_lowercase())
+    } else if let Some(group) = entry.strip_prefix("group:") {
+        (WhitelistKind::Group, group.trim().to_lowercase())
+    } else if entry.contains('@') {
+        (WhitelistKind::Email, entry.to_lowercase())
+    } else {
+        (WhitelistKind::Username, entry.to_string())
+    };
+    (!parsed.1.is_empty()).then_some(parsed)
 }
 
 impl Default for ServerConfig {
@@ -146,6 +680,7 @@ impl Default for ServerConfig {
                 max_connections: 1000,
                 request_timeout: 30,
                 debug_logging: false,
+                cors_origins: default_cors_origins(),
             },
             features: FeatureSettings {
                 chat_enabled: true,
@@ -154,6 +689,7 @@ impl Default for ServerConfig {
                 cli_distribution_enabled: true,
                 oauth_enabled: true,
                 websocket_sync_enabled: true,
+                binary_sync_enabled: true,
             },
             auth: AuthSettings {
                 whitelist_enabled: false,
@@ -176,7 +712,21 @@ impl Default for ServerConfig {
                 require_auth_for_downloads: true,
                 max_downloads_per_hour: 100,
                 track_downloads: true,
+                gui_releases_path: default_gui_releases_path(),
             },
+            git_export: GitExportSettings::default(),
+            status_checks: StatusCheckSettings::default(),
+            reviews: ReviewSettings::default(),
+            notifications: NotificationSettings::default(),
+            replication: ReplicationSettings::default(),
+            relay: RelaySettings::default(),
+            trash: TrashSettings::default(),
+            auto_checkpoint: AutoCheckpointSettings::default(),
+            compaction: CompactionSettings::default(),
+            github_access: GithubAccessSettings::default(),
+            sync_limits: SyncLimitSettings::default(),
+            malware_scan: MalwareScanSettings::default(),
+            database: DatabaseSettings::default(),
         }
     }
 }
@@ -252,6 +802,23 @@ impl ServerConfig {
                 "debug_logging" => config.server.debug_logging = parse_bool(value)?,
                 "oauth_enabled" => config.features.oauth_enabled = parse_bool(value)?,
                 "cli_distribution_enabled" => config.features.cli_distribution_enabled = parse_bool(value)?,
+                "binary_sync_enabled" => config.features.binary_sync_enabled = parse_bool(value)?,
+                "git_export_enabled" => config.git_export.enabled = parse_bool(value)?,
+                "require_green_for_merge" => config.status_checks.require_green_for_merge = parse_bool(value)?,
+                "require_review_for_merge" => config.reviews.require_review_for_merge = parse_bool(value)?,
+                "required_approvals" => config.reviews.required_approvals = value.parse()?,
+                "notifications_enabled" => config.notifications.enabled = parse_bool(value)?,
+                "smtp_host" => config.notifications.smtp_host = value.to_string(),
+                "smtp_port" => config.notifications.smtp_port = value.parse()?,
+                "smtp_username" => config.notifications.smtp_username = value.to_string(),
+                "smtp_from" => config.notifications.from_address = value.to_string(),
+                "relay_enabled" => config.relay.enabled = parse_bool(value)?,
+                "redis_url" => config.relay.redis_url = value.to_string(),
+                "replication_region" => config.replication.region = value.to_string(),
+                "replication_primary_url" => {
+                    config.replication.role = ReplicationRole::Secondary;
+                    config.replication.primary_url = value.to_string();
+                }
                 _ => warn!("⚠️ Unknown config key: {}", key),
             }
         }
@@ -259,7 +826,8 @@ impl ServerConfig {
         Ok(config)
     }
     
-    /// Load user whitelist from file
+    /// Load user whitelist from file. The file seeds the whitelist kept in
+    /// the database the first time the server starts with it enabled.
     pub fn load_whitelist(&self) -> Result<Option<UserWhitelist>> {
         if !self.auth.whitelist_enabled {
             return Ok(None);
@@ -275,40 +843,42 @@ impl ServerConfig {
         let content = fs::read_to_string(whitelist_path)
             .map_err(|e| anyhow!("Failed to read whitelist file: {}", e))?;
         
-        let mut usernames = HashSet::new();
-        let mut emails = HashSet::new();
-        let mut domains = HashSet::new();
+        let whitelist = UserWhitelist::from_entries(content.lines().filter_map(parse_whitelist_entry));
         
-        for line in content.lines() {
-            let line = line.trim();
-            if line.is_empty() || line.starts_with('#') {
-                continue;
-            }
-            
-            if line.starts_with('@') {
-                // Domain (e.g., @company.com)
-                domains.insert(line[1..].to_string());
-            } else if line.contains('@') {
-                // Email address
-                emails.insert(line.to_string());
-            } else {
-                // Username
-                usernames.insert(line.to_string());
-            }
-        }
-        
-        info!("✅ Whitelist loaded: {} usernames, {} emails, {} domains", 
-            usernames.len(), emails.len(), domains.len());
+        info!("✅ Whitelist loaded: {} usernames, {} emails, {} domains, {} groups", 
+            whitelist.usernames.len(), whitelist.emails.len(), whitelist.domains.len(), whitelist.groups.len());
         
-        Ok(Some(UserWhitelist {
-            usernames,
-            emails,
-            domains,
-        }))
+        Ok(Some(whitelist))
     }
 }
 
 impl UserWhitelist {
+    pub fn from_entries(entries: impl IntoIterator<Item = (WhitelistKind, String)>) -> Self {
+        let mut whitelist = Self::default();
+        for (kind, value) in entries {
+            match kind {
+                WhitelistKind::Username => whitelist.usernames.insert(value),
+                WhitelistKind::Email => whitelist.emails.insert(value),
+                WhitelistKind::Domain => whitelist.domains.insert(value),
+                WhitelistKind::Group => whitelist.groups.insert(value),
+            };
+        }
+        whitelist
+    }
+
+    /// Every entry, for seeding the database
+    pub fn entries(&self) -> Vec<(WhitelistKind, String)> {
+        let kinds = [
+            (WhitelistKind::Username, &self.usernames),
+            (WhitelistKind::Email, &self.emails),
+            (WhitelistKind::Domain, &self.domains),
+            (WhitelistKind::Group, &self.groups),
+        ];
+        kinds.into_iter()
+            .flat_map(|(kind, values)| values.iter().map(move |value| (kind, value.clone())))
+            .collect()
+    }
+
     /// Check if a user is allowed based on username and email
     pub fn is_user_allowed(&self, username: &str, email: &str) -> bool {
         // Check exact username match
@@ -317,7 +887,8 @@ impl UserWhitelist {
         }
         
         // Check exact email match
-        if self.emails.contains(email) {
+        let email = email.to_lowercase();
+        if self.emails.contains(&email) {
             return true;
         }
         
@@ -330,6 +901,11 @@ impl UserWhitelist {
         
         false
     }
+
+    /// Check if any of a user's OAuth groups is allowed
+    pub fn is_group_allowed(&self, groups: &[String]) -> bool {
+        groups.iter().any(|group| self.groups.contains(&group.to_lowercase()))
+    }
 }
 
 /// Parse boolean value from string
@@ -357,11 +933,7 @@ mod tests {
     
     #[test]
     fn test_whitelist_user_allowed() {
-        let mut whitelist = UserWhitelist {
-            usernames: HashSet::new(),
-            emails: HashSet::new(),
-            domains: HashSet::new(),
-        };
+        let mut whitelist = UserWhitelist::default();
         
         whitelist.usernames.insert("alice".to_string());
         whitelist.emails.insert("bob@example.com".to_string());
@@ -372,4 +944,41 @@ mod tests {
         assert!(whitelist.is_user_allowed("charlie", "charlie@company.com"));
         assert!(!whitelist.is_user_allowed("eve", "eve@malicious.com"));
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_whitelist_entries_and_groups() {
+        assert_eq!(parse_whitelist_entry("*@Company.com"), Some((WhitelistKind::Domain, "company.com".to_string())));
+        assert_eq!(parse_whitelist_entry("@company.com"), Some((WhitelistKind::Domain, "company.com".to_string())));
+        assert_eq!(parse_whitelist_entry("group:GitHub:acme/platform"), Some((WhitelistKind::Group, "github:acme/platform".to_string())));
+        assert_eq!(parse_whitelist_entry("Bob@Example.com"), Some((WhitelistKind::Email, "bob@example.com".to_string())));
+        assert_eq!(parse_whitelist_entry("alice"), Some((WhitelistKind::Username, "alice".to_string())));
+        assert_eq!(parse_whitelist_entry("# comment"), None);
+        assert_eq!(parse_whitelist_entry("group:"), None);
+
+        let whitelist = UserWhitelist::from_entries(["group:github:acme", "*@company.com"].into_iter().filter_map(parse_whitelist_entry));
+        assert!(whitelist.is_group_allowed(&["github:Acme".to_string()]));
+        assert!(!whitelist.is_group_allowed(&["github:acme/platform".to_string()]));
+        assert!(whitelist.is_user_allowed("carol", "Carol@Company.com"));
+        assert_eq!(whitelist.entries().len(), 2);
+    }
+
+    #[test]
+    fn test_github_access() {
+        let mut github = GithubAccessSettings::default();
+        assert!(!github.restricts_login());
+
+        github.orgs.push("Acme".to_string());
+        github.roles.insert("acme/admins".to_string(), UserRole::Admin);
+        github.roles.insert("acme".to_string(), UserRole::User);
+        assert!(github.restricts_login());
+
+        let member = vec!["github:acme".to_string()];
+        let admin = vec!["github:acme".to_string(), "github:acme/admins".to_string()];
+        assert!(github.allows(&member));
+        assert!(!github.allows(&["github:other".to_string()]));
+        assert_eq!(github.role_for(&member), Some(UserRole::User));
+        assert_eq!(github.role_for(&admin), Some(UserRole::Admin));
+        assert_eq!(github.role_for(&[]), None);
+        assert_eq!(github.groups(), vec!["github:acme".to_string(), "github:acme/admins".to_string()]);
+    }
+}
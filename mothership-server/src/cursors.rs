@@ -0,0 +1,95 @@
+//! Collaborators' cursors.
+//!
+//! Editors report where their cursor is and the server relays it to
+//! connections that asked to watch cursors. Like terminal output, cursor
+//! moves travel on a channel of their own, so they aren't numbered or kept
+//! for replay. Only the latest position of each user is remembered, for
+//! watchers who start late, and it's dropped when the user leaves the rift.
+
+use mothership_common::{
+    protocol::{RemoteCursor, SyncMessage},
+    RiftId, UserId,
+};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+
+use crate::sync::SyncState;
+
+/// Channels carrying a rift's cursor moves
+pub const CURSOR_CHANNEL_PREFIX: &str = "cursor_";
+
+pub fn cursor_channel(rift_id: impl Display) -> String {
+    format!("{}{}", CURSOR_CHANNEL_PREFIX, rift_id)
+}
+
+/// Latest cursor of each user in each rift
+#[derive(Clone, Default)]
+pub struct Cursors {
+    rifts: Arc<Mutex<HashMap<RiftId, HashMap<UserId, RemoteCursor>>>>,
+}
+
+impl Cursors {
+    fn set(&self, rift_id: RiftId, cursor: RemoteCursor) {
+        let mut rifts = self.rifts.lock().unwrap();
+        let users = rifts.entry(rift_id).or_default();
+        if cursor.path.is_some() {
+            users.insert(cursor.user_id, cursor);
+        } else {
+            users.remove(&cursor.user_id);
+        }
+    }
+
+    /// Cursors of a rift that are in a file, by username
+    fn in_rift(&self, rift_id: RiftId) -> Vec<RemoteCursor> {
+        let rifts = self.rifts.lock().unwrap();
+        let mut cursors: Vec<RemoteCursor> = rifts.get(&rift_id)
+            .map(|users| users.values().cloned().collect())
+            .unwrap_or_default();
+        cursors.sort_by(|a, b| a.username.cmp(&b.username));
+        cursors
+    }
+
+    /// Forget the cursor of a user who left a rift
+    pub fn forget_user(&self, rift_id: RiftId, user_id: UserId) {
+        let mut rifts = self.rifts.lock().unwrap();
+        if let Some(users) = rifts.get_mut(&rift_id) {
+            users.remove(&user_id);
+            if users.is_empty() {
+                rifts.remove(&rift_id);
+            }
+        }
+    }
+
+    /// Drop the cursors of a rift that no longer exists
+    pub fn forget_rift(&self, rift_id: RiftId) {
+        self.rifts.lock().unwrap().remove(&rift_id);
+    }
+
+    /// Mirror a cursor move another instance broadcast
+    pub fn apply_relayed(&self, message: &SyncMessage) {
+        if let SyncMessage::CursorMoved { rift_id, cursor } = message {
+            self.set(*rift_id, cursor.clone());
+        }
+    }
+}
+
+/// Record a user's cursor and pass it on to watchers
+pub fn update(state: &SyncState, rift_id: RiftId, user_id: UserId, username: &str, path: Option<PathBuf>, line: u32, column: u32) {
+    let cursor = RemoteCursor {
+        user_id,
+        username: username.to_string(),
+        path,
+        line: line.max(1),
+        column: column.max(1),
+    };
+    state.cursors.set(rift_id, cursor.clone());
+    state.broadcast_unsequenced(cursor_channel(rift_id), SyncMessage::CursorMoved { rift_id, cursor });
+}
+
+/// Start a connection watching a rift's cursors by listing where they are now
+pub fn watch(state: &SyncState, rift_id: RiftId, reply: &mpsc::UnboundedSender<SyncMessage>) {
+    let _ = reply.send(SyncMessage::Cursors { rift_id, cursors: state.cursors.in_rift(rift_id) });
+}
@@ -1,105 +1,140 @@
 use anyhow::Result;
 use chrono::Utc;
 use mothership_common::{
-    Project, ProjectId, ProjectSettings, Rift, RiftId, User, UserId, UserRole,
+    auth::{AuthSession, Machine},
+    protocol::{
+        ChatMessage, CommentThread, InboxKind, InboxNotification, NotificationPreferences, ReplicatedRift, ReviewComment, ReviewRequest, Reviewer, RiftPermission,
+        ThreadComment,
+    },
+    CheckpointId, CheckpointStatus, Project, ProjectId, ProjectSettings, ProjectVisibility, ReviewState, Rift, RiftId, RiftRole, RiftVisibility, StatusState, User, UserId, UserRole,
 };
-use sqlx::PgPool;
+use sqlx::{
+    migrate::{Migrate, MigrateError, Migrator},
+    pool::PoolOptions,
+    PgPool, Pool,
+};
+use std::collections::HashMap;
+use std::time::Duration;
 use uuid::Uuid;
 
-/// PostgreSQL database implementation
+use crate::config::DatabaseSettings;
+use crate::oauth::LoginMachine;
+
+mod sqlite;
+
+use sqlite::SqliteDatabase;
+
+/// PostgreSQL migrations, embedded at build time
+static MIGRATOR: Migrator = sqlx::migrate!("../migrations");
+
+/// Database access. Backed by PostgreSQL, or by SQLite when DATABASE_URL
+/// starts with `sqlite:`.
 #[derive(Clone)]
 pub struct Database {
-    pool: PgPool,
+    backend: Backend,
+}
+
+#[derive(Clone)]
+enum Backend {
+    Postgres(PgPool),
+    Sqlite(SqliteDatabase),
+}
+
+/// The PostgreSQL pool of a `Database`; on SQLite, returns from the calling
+/// method with the SQLite backend's answer to the same call instead
+macro_rules! pg_pool {
+    ($db:expr, $method:ident($($arg:expr),*)) => {
+        match &$db.backend {
+            Backend::Postgres(pool) => pool,
+            Backend::Sqlite(sqlite) => return sqlite.$method($($arg),*).await,
+        }
+    };
 }
 
 impl Database {
     /// Create a new database connection pool
-    pub async fn new(database_url: &str) -> Result<Self> {
+    pub async fn new(database_url: &str, settings: &DatabaseSettings) -> Result<Self> {
+        if database_url.starts_with("sqlite:") {
+            let sqlite = SqliteDatabase::new(database_url, settings).await?;
+            return Ok(Self { backend: Backend::Sqlite(sqlite) });
+        }
+
         tracing::info!("🔗 Connecting to PostgreSQL database...");
         
-        let pool = PgPool::connect(database_url).await?;
+        let pool = pool_options(settings).connect(database_url).await?;
         
         tracing::info!("✅ Successfully connected to PostgreSQL database");
         
-        Ok(Self { pool })
+        Ok(Self { backend: Backend::Postgres(pool) })
     }
 
-    /// Run database migrations manually (since we can't use sqlx migrate in Docker build)
-    pub async fn ensure_schema(&self) -> Result<()> {
-        tracing::info!("🔄 Ensuring database schema exists...");
-        
-        // Create the schema manually using runtime queries
-        sqlx::query("CREATE EXTENSION IF NOT EXISTS \"uuid-ossp\"")
-            .execute(&self.pool).await?;
-            
-        // Create user_role enum type (ignore error if it already exists)
-        let _ = sqlx::query(r#"
-            DO $$ BEGIN
-                CREATE TYPE user_role AS ENUM ('user', 'admin', 'super_admin');
-            EXCEPTION
-                WHEN duplicate_object THEN null;
-            END $$;
-        "#)
-            .execute(&self.pool).await;
-            
-        // Create users table
-        sqlx::query(r#"
-            CREATE TABLE IF NOT EXISTS users (
-                id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
-                username VARCHAR(255) NOT NULL UNIQUE,
-                email VARCHAR(255) NOT NULL UNIQUE,
-                role user_role NOT NULL DEFAULT 'user',
-                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
-                updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
-                
-                CONSTRAINT users_username_check CHECK (length(username) >= 1 AND length(username) <= 255),
-                CONSTRAINT users_email_check CHECK (email ~* '^[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Z|a-z]{2,}$')
-            )
-        "#).execute(&self.pool).await?;
-        
-        // Create projects table  
-        sqlx::query(r#"
-            CREATE TABLE IF NOT EXISTS projects (
-                id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
-                name VARCHAR(255) NOT NULL,
-                description TEXT,
-                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
-                updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
-                
-                CONSTRAINT projects_name_check CHECK (length(name) >= 1 AND length(name) <= 255)
-            )
-        "#).execute(&self.pool).await?;
-        
-        // Create project_members table
-        sqlx::query(r#"
-            CREATE TABLE IF NOT EXISTS project_members (
-                project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
-                user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
-                role VARCHAR(50) NOT NULL DEFAULT 'member',
-                joined_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
-                
-                PRIMARY KEY (project_id, user_id)
-            )
-        "#).execute(&self.pool).await?;
-        
-        tracing::info!("✅ Database schema ready!");
+    /// A fresh, migrated SQLite database in memory, gone once the last clone
+    /// is dropped. For tests that shouldn't need a database server.
+    pub async fn new_in_memory() -> Result<Self> {
+        let db = Self { backend: Backend::Sqlite(SqliteDatabase::new_in_memory().await?) };
+        db.migrate().await?;
+        Ok(db)
+    }
+
+    /// Run a statement behind the typed methods' back, e.g. to tamper with rows
+    #[cfg(test)]
+    pub async fn execute_raw(&self, sql: &str) -> Result<()> {
+        let pool = pg_pool!(self, execute_raw(sql));
+        sqlx::query(sql).execute(pool).await?;
+        Ok(())
+    }
+
+    /// Check the database answers queries, for readiness probes
+    pub async fn ping(&self) -> Result<()> {
+        let pool = pg_pool!(self, ping());
+        sqlx::query("SELECT 1").execute(pool).await?;
+        Ok(())
+    }
+
+    /// How busy the connection pool is
+    pub fn pool_stats(&self) -> PoolStats {
+        match &self.backend {
+            Backend::Postgres(pool) => PoolStats::of(pool),
+            Backend::Sqlite(sqlite) => sqlite.pool_stats(),
+        }
+    }
+
+    /// Apply any pending migrations. Runs at startup, before the database is used.
+    pub async fn migrate(&self) -> Result<()> {
+        let pool = pg_pool!(self, migrate());
+
+        // Databases set up before migrations were tracked had the SQL files run
+        // by the Postgres container's init scripts; adopt what they created
+        let tracked: bool = sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+            .fetch_one(pool)
+            .await?;
+        let initialized: bool = sqlx::query_scalar("SELECT to_regclass('users') IS NOT NULL")
+            .fetch_one(pool)
+            .await?;
+        if !tracked && initialized {
+            adopt_untracked_schema(pool).await?;
+        }
+
+        MIGRATOR.run(pool).await?;
+        tracing::info!("✅ Database schema is up to date");
         Ok(())
     }
 
     /// Get all projects a user has access to (simplified version for rift handlers)
     pub async fn get_user_projects(&self, user_id: UserId) -> Result<Vec<Project>> {
+        let pool = pg_pool!(self, get_user_projects(user_id));
         // Get projects where user is a member
-        let projects = sqlx::query!(
+        let projects = sqlx::query_as::<_, ProjectRow>(
             r#"
             SELECT p.id, p.name, p.description, p.created_at
             FROM projects p
             INNER JOIN project_members pm ON p.id = pm.project_id
-            WHERE pm.user_id = $1
+            WHERE pm.user_id = $1 AND p.deleted_at IS NULL
             ORDER BY p.created_at DESC
             "#,
-            user_id
         )
-        .fetch_all(&self.pool)
+        .bind(user_id)
+        .fetch_all(pool)
         .await?;
 
         let mut result = Vec::new();
@@ -110,7 +145,7 @@ impl Database {
                 "SELECT user_id FROM project_members WHERE project_id = $1",
                 project_row.id
             )
-            .fetch_all(&self.pool)
+            .fetch_all(pool)
             .await?
             .into_iter()
             .map(|member_row| member_row.user_id)
@@ -131,6 +166,7 @@ impl Database {
 
     /// Get all rifts for a project
     pub async fn get_project_rifts(&self, project_id: ProjectId) -> Result<Vec<Rift>> {
+        let pool = pg_pool!(self, get_project_rifts(project_id));
         let rifts = sqlx::query!(
             r#"
             SELECT r.id, r.project_id, r.name, r.parent_rift_id, r.created_at, r.is_active,
@@ -143,7 +179,7 @@ impl Database {
             "#,
             project_id
         )
-        .fetch_all(&self.pool)
+        .fetch_all(pool)
         .await?;
 
         let mut result = Vec::new();
@@ -165,13 +201,38 @@ impl Database {
         Ok(result)
     }
 
+    /// Usernames of each rift's collaborators in a project, in the order they joined
+    pub async fn get_rift_collaborator_names(&self, project_id: ProjectId) -> Result<HashMap<RiftId, Vec<(UserId, String)>>> {
+        let pool = pg_pool!(self, get_rift_collaborator_names(project_id));
+        let rows: Vec<(RiftId, UserId, String)> = sqlx::query_as(
+            r#"
+            SELECT rc.rift_id, rc.user_id, u.username
+            FROM rift_collaborators rc
+            INNER JOIN rifts r ON r.id = rc.rift_id
+            INNER JOIN users u ON u.id = rc.user_id
+            WHERE r.project_id = $1
+            ORDER BY rc.joined_at ASC
+            "#,
+        )
+        .bind(project_id)
+        .fetch_all(pool)
+        .await?;
+
+        let mut collaborators: HashMap<RiftId, Vec<(UserId, String)>> = HashMap::new();
+        for (rift_id, user_id, username) in rows {
+            collaborators.entry(rift_id).or_default().push((user_id, username));
+        }
+        Ok(collaborators)
+    }
+
     /// Get a specific project
     pub async fn get_project(&self, project_id: ProjectId) -> Result<Option<Project>> {
-        let project_row = sqlx::query!(
-            "SELECT id, name, description, created_at FROM projects WHERE id = $1",
-            project_id
+        let pool = pg_pool!(self, get_project(project_id));
+        let project_row = sqlx::query_as::<_, ProjectRow>(
+            "SELECT id, name, description, created_at FROM projects WHERE id = $1 AND deleted_at IS NULL",
         )
-        .fetch_optional(&self.pool)
+        .bind(project_id)
+        .fetch_optional(pool)
         .await?;
 
         if let Some(row) = project_row {
@@ -180,7 +241,7 @@ impl Database {
                 "SELECT user_id FROM project_members WHERE project_id = $1",
                 project_id
             )
-            .fetch_all(&self.pool)
+            .fetch_all(pool)
             .await?
             .into_iter()
             .map(|row| row.user_id)
@@ -192,54 +253,85 @@ impl Database {
                 description: row.description.unwrap_or_default(),
                 members,
                 created_at: row.created_at,
-                settings: ProjectSettings::default(),
+                settings: self.get_project_settings(project_id).await?,
             }))
         } else {
             Ok(None)
         }
     }
 
-    /// List all projects (for testing)
-    pub async fn list_all_projects(&self) -> Result<Vec<Project>> {
-        let projects = sqlx::query!(
-            "SELECT id, name, description, created_at FROM projects ORDER BY created_at DESC"
+    /// A page of the projects a user is a member of, newest first: at most
+    /// `limit` of those created before the project `before`
+    pub async fn get_user_projects_page(&self, user_id: UserId, before: Option<ProjectId>, limit: usize) -> Result<Vec<Project>> {
+        let pool = pg_pool!(self, get_user_projects_page(user_id, before, limit));
+        let rows = sqlx::query_as::<_, ProjectRow>(
+            r#"
+            SELECT p.id, p.name, p.description, p.created_at
+            FROM projects p
+            INNER JOIN project_members pm ON p.id = pm.project_id
+            WHERE pm.user_id = $1 AND p.deleted_at IS NULL
+              AND ($2::uuid IS NULL OR (p.created_at, p.id) < (SELECT created_at, id FROM projects WHERE id = $2))
+            ORDER BY p.created_at DESC, p.id DESC
+            LIMIT $3
+            "#,
         )
-        .fetch_all(&self.pool)
+        .bind(user_id)
+        .bind(before)
+        .bind(limit as i64)
+        .fetch_all(pool)
         .await?;
 
-        let mut result = Vec::new();
-        for row in projects {
-            // Get project members for each project
-            let members = sqlx::query!(
-                "SELECT user_id FROM project_members WHERE project_id = $1",
-                row.id
-            )
-            .fetch_all(&self.pool)
-            .await?
-            .into_iter()
-            .map(|member_row| member_row.user_id)
-            .collect();
+        projects_from_rows(pool, rows).await
+    }
 
-            result.push(Project {
-                id: row.id,
-                name: row.name,
-                description: row.description.unwrap_or_default(),
-                members,
-                created_at: row.created_at,
-                settings: ProjectSettings::default(),
-            });
-        }
+    /// Projects with a visibility setting, newest first
+    pub async fn list_projects_with_visibility(&self, visibility: ProjectVisibility) -> Result<Vec<Project>> {
+        let pool = pg_pool!(self, list_projects_with_visibility(visibility));
+        let rows = sqlx::query_as::<_, ProjectRow>(
+            r#"
+            SELECT p.id, p.name, p.description, p.created_at
+            FROM projects p
+            INNER JOIN project_settings ps ON p.id = ps.project_id
+            WHERE ps.settings->>'visibility' = $1 AND p.deleted_at IS NULL
+            ORDER BY p.created_at DESC, p.id DESC
+            "#,
+        )
+        .bind(visibility.as_str())
+        .fetch_all(pool)
+        .await?;
 
-        Ok(result)
+        projects_from_rows(pool, rows).await
+    }
+
+    /// A page of all projects, newest first (for testing)
+    pub async fn list_projects(&self, before: Option<ProjectId>, limit: usize) -> Result<Vec<Project>> {
+        let pool = pg_pool!(self, list_projects(before, limit));
+        let rows = sqlx::query_as::<_, ProjectRow>(
+            r#"
+            SELECT id, name, description, created_at
+            FROM projects
+            WHERE deleted_at IS NULL
+              AND ($1::uuid IS NULL OR (created_at, id) < (SELECT created_at, id FROM projects WHERE id = $1))
+            ORDER BY created_at DESC, id DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(before)
+        .bind(limit as i64)
+        .fetch_all(pool)
+        .await?;
+
+        projects_from_rows(pool, rows).await
     }
 
     /// Get user by ID
     pub async fn get_user(&self, user_id: UserId) -> Result<Option<User>> {
+        let pool = pg_pool!(self, get_user(user_id));
         let user = sqlx::query!(
             "SELECT id, username, email, role as \"role: UserRole\", created_at FROM users WHERE id = $1",
             user_id
         )
-        .fetch_optional(&self.pool)
+        .fetch_optional(pool)
         .await?;
 
         Ok(user.map(|row| User {
@@ -253,11 +345,12 @@ impl Database {
 
     /// Get user by username
     pub async fn get_user_by_username(&self, username: &str) -> Result<Option<User>> {
+        let pool = pg_pool!(self, get_user_by_username(username));
         let user = sqlx::query!(
             "SELECT id, username, email, role as \"role: UserRole\", created_at FROM users WHERE username = $1",
             username
         )
-        .fetch_optional(&self.pool)
+        .fetch_optional(pool)
         .await?;
 
         Ok(user.map(|row| User {
@@ -271,11 +364,12 @@ impl Database {
 
     /// Get user by email
     pub async fn get_user_by_email(&self, email: &str) -> Result<Option<User>> {
+        let pool = pg_pool!(self, get_user_by_email(email));
         let user = sqlx::query!(
             "SELECT id, username, email, role as \"role: UserRole\", created_at FROM users WHERE email = $1",
             email
         )
-        .fetch_optional(&self.pool)
+        .fetch_optional(pool)
         .await?;
 
         Ok(user.map(|row| User {
@@ -294,20 +388,22 @@ impl Database {
         user_id: UserId,
         rift_name: Option<String>,
     ) -> Result<Rift> {
+        let pool = pg_pool!(self, create_rift(project_id, user_id, rift_name));
         let rift_id = Uuid::new_v4();
         let name = rift_name.unwrap_or_else(|| "main".to_string());
         
-        // Create the rift
-        sqlx::query!(
+        // Create the rift, owned by the user who created it
+        sqlx::query(
             r#"
-            INSERT INTO rifts (id, project_id, name, is_active)
-            VALUES ($1, $2, $3, true)
+            INSERT INTO rifts (id, project_id, name, is_active, owner_id)
+            VALUES ($1, $2, $3, true, $4)
             "#,
-            rift_id,
-            project_id,
-            name
         )
-        .execute(&self.pool)
+        .bind(rift_id)
+        .bind(project_id)
+        .bind(&name)
+        .bind(user_id)
+        .execute(pool)
         .await?;
 
         // Add user as collaborator
@@ -320,7 +416,7 @@ impl Database {
             rift_id,
             user_id
         )
-        .execute(&self.pool)
+        .execute(pool)
         .await?;
 
         // Return the created rift
@@ -338,6 +434,7 @@ impl Database {
 
     /// Get a rift by ID
     pub async fn get_rift(&self, rift_id: RiftId) -> Result<Option<Rift>> {
+        let pool = pg_pool!(self, get_rift(rift_id));
         let rift = sqlx::query!(
             r#"
             SELECT r.id, r.project_id, r.name, r.parent_rift_id, r.created_at, r.is_active,
@@ -349,7 +446,7 @@ impl Database {
             "#,
             rift_id
         )
-        .fetch_optional(&self.pool)
+        .fetch_optional(pool)
         .await?;
 
         if let Some(row) = rift {
@@ -373,6 +470,7 @@ impl Database {
 
     /// Get user's primary rift for a project
     pub async fn get_user_rift(&self, project_id: ProjectId, user_id: UserId) -> Result<Option<Rift>> {
+        let pool = pg_pool!(self, get_user_rift(project_id, user_id));
         // First, check if user has an existing rift for this project
         let rift = sqlx::query!(
             r#"
@@ -386,7 +484,7 @@ impl Database {
             project_id,
             user_id
         )
-        .fetch_optional(&self.pool)
+        .fetch_optional(pool)
         .await?;
 
         if let Some(row) = rift {
@@ -405,17 +503,270 @@ impl Database {
         }
     }
 
+    /// Find an active rift in a project by name
+    pub async fn get_rift_by_name(&self, project_id: ProjectId, name: &str) -> Result<Option<Rift>> {
+        let pool = pg_pool!(self, get_rift_by_name(project_id, name));
+        let rift_id: Option<RiftId> = sqlx::query_scalar(
+            "SELECT id FROM rifts WHERE project_id = $1 AND name = $2 AND is_active = true ORDER BY created_at ASC LIMIT 1",
+        )
+        .bind(project_id)
+        .bind(name)
+        .fetch_optional(pool)
+        .await?;
+
+        match rift_id {
+            Some(rift_id) => self.get_rift(rift_id).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Record which rift a rift was created from
+    pub async fn set_rift_parent(&self, rift_id: RiftId, parent: Option<RiftId>) -> Result<()> {
+        let pool = pg_pool!(self, set_rift_parent(rift_id, parent));
+        sqlx::query("UPDATE rifts SET parent_rift_id = $2 WHERE id = $1")
+            .bind(rift_id)
+            .bind(parent)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Record the checkpoint a rift was forked from its parent at
+    pub async fn set_rift_fork_point(&self, rift_id: RiftId, checkpoint_id: CheckpointId) -> Result<()> {
+        let pool = pg_pool!(self, set_rift_fork_point(rift_id, checkpoint_id));
+        sqlx::query("UPDATE rifts SET fork_checkpoint_id = $2 WHERE id = $1")
+            .bind(rift_id)
+            .bind(checkpoint_id)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// The checkpoint a rift was forked from its parent at, if it was recorded
+    pub async fn get_rift_fork_point(&self, rift_id: RiftId) -> Result<Option<CheckpointId>> {
+        let pool = pg_pool!(self, get_rift_fork_point(rift_id));
+        let fork_point: Option<Option<CheckpointId>> = sqlx::query_scalar("SELECT fork_checkpoint_id FROM rifts WHERE id = $1")
+            .bind(rift_id)
+            .fetch_optional(pool)
+            .await?;
+
+        Ok(fork_point.flatten())
+    }
+
+    /// Remember the rift a user switched to in a project
+    pub async fn set_current_rift(&self, user_id: UserId, project_id: ProjectId, rift_id: RiftId) -> Result<()> {
+        let pool = pg_pool!(self, set_current_rift(user_id, project_id, rift_id));
+        sqlx::query(
+            r#"
+            INSERT INTO current_rifts (user_id, project_id, rift_id, switched_at)
+            VALUES ($1, $2, $3, NOW())
+            ON CONFLICT (user_id, project_id) DO UPDATE SET rift_id = EXCLUDED.rift_id, switched_at = EXCLUDED.switched_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(project_id)
+        .bind(rift_id)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// The rift a user last switched to in a project, if any
+    pub async fn get_current_rift(&self, user_id: UserId, project_id: ProjectId) -> Result<Option<RiftId>> {
+        let pool = pg_pool!(self, get_current_rift(user_id, project_id));
+        let rift_id = sqlx::query_scalar("SELECT rift_id FROM current_rifts WHERE user_id = $1 AND project_id = $2")
+            .bind(user_id)
+            .bind(project_id)
+            .fetch_optional(pool)
+            .await?;
+
+        Ok(rift_id)
+    }
+
+    /// Give a rift a new name
+    pub async fn rename_rift(&self, rift_id: RiftId, name: &str) -> Result<()> {
+        let pool = pg_pool!(self, rename_rift(rift_id, name));
+        sqlx::query("UPDATE rifts SET name = $2, updated_at = NOW() WHERE id = $1")
+            .bind(rift_id)
+            .bind(name)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Delete a rift. Rifts created from it are moved onto its own parent.
+    pub async fn delete_rift(&self, rift_id: RiftId) -> Result<()> {
+        let pool = pg_pool!(self, delete_rift(rift_id));
+        let mut tx = pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            UPDATE rifts
+            SET parent_rift_id = (SELECT parent_rift_id FROM rifts WHERE id = $1)
+            WHERE parent_rift_id = $1
+            "#,
+        )
+        .bind(rift_id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM rifts WHERE id = $1")
+            .bind(rift_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Add a user to a rift's collaborators
+    pub async fn add_rift_collaborator(&self, rift_id: RiftId, user_id: UserId) -> Result<()> {
+        let pool = pg_pool!(self, add_rift_collaborator(rift_id, user_id));
+        sqlx::query(
+            r#"
+            INSERT INTO rift_collaborators (rift_id, user_id)
+            VALUES ($1, $2)
+            ON CONFLICT (rift_id, user_id) DO NOTHING
+            "#,
+        )
+        .bind(rift_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Get a rift's owner and visibility
+    pub async fn get_rift_access(&self, rift_id: RiftId) -> Result<Option<(Option<UserId>, RiftVisibility)>> {
+        let pool = pg_pool!(self, get_rift_access(rift_id));
+        let row: Option<(Option<UserId>, String)> = sqlx::query_as(
+            "SELECT owner_id, visibility FROM rifts WHERE id = $1",
+        )
+        .bind(rift_id)
+        .fetch_optional(pool)
+        .await?;
+
+        row.map(|(owner, visibility)| {
+            let visibility = RiftVisibility::parse(&visibility)
+                .ok_or_else(|| anyhow::anyhow!("Unknown rift visibility '{}'", visibility))?;
+            Ok((owner, visibility))
+        })
+        .transpose()
+    }
+
+    /// Change who may join a rift
+    pub async fn set_rift_visibility(&self, rift_id: RiftId, visibility: RiftVisibility) -> Result<()> {
+        let pool = pg_pool!(self, set_rift_visibility(rift_id, visibility));
+        sqlx::query("UPDATE rifts SET visibility = $2 WHERE id = $1")
+            .bind(rift_id)
+            .bind(visibility.as_str())
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Get the role a rift has been shared with a user under, if any
+    pub async fn get_rift_permission(&self, rift_id: RiftId, user_id: UserId) -> Result<Option<RiftRole>> {
+        let pool = pg_pool!(self, get_rift_permission(rift_id, user_id));
+        let role: Option<String> = sqlx::query_scalar(
+            "SELECT role FROM rift_permissions WHERE rift_id = $1 AND user_id = $2",
+        )
+        .bind(rift_id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(role.as_deref().and_then(RiftRole::parse))
+    }
+
+    /// List the users a rift has been shared with
+    pub async fn list_rift_permissions(&self, rift_id: RiftId) -> Result<Vec<RiftPermission>> {
+        let pool = pg_pool!(self, list_rift_permissions(rift_id));
+        let rows = sqlx::query_as::<_, RiftPermissionRow>(
+            r#"
+            SELECT p.user_id, u.username, p.role, p.granted_at
+            FROM rift_permissions p
+            JOIN users u ON u.id = p.user_id
+            WHERE p.rift_id = $1
+            ORDER BY p.granted_at ASC
+            "#,
+        )
+        .bind(rift_id)
+        .fetch_all(pool)
+        .await?;
+
+        rows.into_iter().map(RiftPermission::try_from).collect()
+    }
+
+    /// Share a rift with a user, replacing any earlier role
+    pub async fn grant_rift_permission(
+        &self,
+        rift_id: RiftId,
+        user_id: UserId,
+        role: RiftRole,
+        granted_by: UserId,
+    ) -> Result<()> {
+        let pool = pg_pool!(self, grant_rift_permission(rift_id, user_id, role, granted_by));
+        sqlx::query(
+            r#"
+            INSERT INTO rift_permissions (rift_id, user_id, role, granted_by)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (rift_id, user_id)
+            DO UPDATE SET role = EXCLUDED.role, granted_by = EXCLUDED.granted_by, granted_at = NOW()
+            "#,
+        )
+        .bind(rift_id)
+        .bind(user_id)
+        .bind(role.as_str())
+        .bind(granted_by)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Stop sharing a rift with a user. Returns false if it was not shared with them.
+    pub async fn revoke_rift_permission(&self, rift_id: RiftId, user_id: UserId) -> Result<bool> {
+        let pool = pg_pool!(self, revoke_rift_permission(rift_id, user_id));
+        let result = sqlx::query("DELETE FROM rift_permissions WHERE rift_id = $1 AND user_id = $2")
+            .bind(rift_id)
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+
+        // Unsharing also removes them from the rift's collaborators
+        sqlx::query("DELETE FROM rift_collaborators WHERE rift_id = $1 AND user_id = $2")
+            .bind(rift_id)
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     /// Check if user has access to a project
     pub async fn user_has_project_access(&self, user_id: UserId, project_id: ProjectId) -> Result<bool> {
-        let count = sqlx::query!(
-            "SELECT COUNT(*) as count FROM project_members WHERE user_id = $1 AND project_id = $2",
-            user_id,
-            project_id
+        let pool = pg_pool!(self, user_has_project_access(user_id, project_id));
+        let count = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT COUNT(*)
+            FROM project_members pm
+            INNER JOIN projects p ON p.id = pm.project_id
+            WHERE pm.user_id = $1 AND pm.project_id = $2 AND p.deleted_at IS NULL
+            "#,
         )
-        .fetch_one(&self.pool)
+        .bind(user_id)
+        .bind(project_id)
+        .fetch_one(pool)
         .await?;
 
-        Ok(count.count.unwrap_or(0) > 0)
+        Ok(count > 0)
     }
 
     /// Create a new user with specified role
@@ -426,6 +777,7 @@ impl Database {
 
     /// Create a new user with a specific user ID (for OAuth user recreation)
     pub async fn create_user_with_id(&self, user_id: UserId, username: String, email: String, role: UserRole) -> Result<User> {
+        let pool = pg_pool!(self, create_user_with_id(user_id, username, email, role));
         tracing::info!("🔄 Creating user with ID: {}, username: {}, email: {}", user_id, username, email);
         
         // First try to find existing user by email or username
@@ -450,7 +802,7 @@ impl Database {
             email,
             role as UserRole
         )
-        .fetch_one(&self.pool)
+        .fetch_one(pool)
         .await?;
 
         tracing::info!("✅ Successfully created/updated user: {} ({})", user.username, user.id);
@@ -466,11 +818,12 @@ impl Database {
 
     /// Check if user exists by email
     pub async fn user_exists_by_email(&self, email: &str) -> Result<bool> {
+        let pool = pg_pool!(self, user_exists_by_email(email));
         let count = sqlx::query!(
             "SELECT COUNT(*) as count FROM users WHERE email = $1",
             email
         )
-        .fetch_one(&self.pool)
+        .fetch_one(pool)
         .await?;
 
         Ok(count.count.unwrap_or(0) > 0)
@@ -478,11 +831,12 @@ impl Database {
 
     /// Check if user exists by username
     pub async fn user_exists_by_username(&self, username: &str) -> Result<bool> {
+        let pool = pg_pool!(self, user_exists_by_username(username));
         let count = sqlx::query!(
             "SELECT COUNT(*) as count FROM users WHERE username = $1",
             username
         )
-        .fetch_one(&self.pool)
+        .fetch_one(pool)
         .await?;
 
         Ok(count.count.unwrap_or(0) > 0)
@@ -490,35 +844,38 @@ impl Database {
 
     /// Check if user has admin permissions
     pub async fn user_is_admin(&self, user_id: UserId) -> Result<bool> {
+        let pool = pg_pool!(self, user_is_admin(user_id));
         let user = sqlx::query!(
             "SELECT role as \"role: UserRole\" FROM users WHERE id = $1",
             user_id
         )
-        .fetch_optional(&self.pool)
+        .fetch_optional(pool)
         .await?;
 
-        Ok(user.map_or(false, |u| matches!(u.role, UserRole::Admin | UserRole::SuperAdmin)))
+        Ok(user.is_some_and(|u| matches!(u.role, UserRole::Admin | UserRole::SuperAdmin)))
     }
 
     /// Check if user has super admin permissions
     pub async fn user_is_super_admin(&self, user_id: UserId) -> Result<bool> {
+        let pool = pg_pool!(self, user_is_super_admin(user_id));
         let user = sqlx::query!(
             "SELECT role as \"role: UserRole\" FROM users WHERE id = $1",
             user_id
         )
-        .fetch_optional(&self.pool)
+        .fetch_optional(pool)
         .await?;
 
-        Ok(user.map_or(false, |u| u.role == UserRole::SuperAdmin))
+        Ok(user.is_some_and(|u| u.role == UserRole::SuperAdmin))
     }
 
     /// Check if project exists by name
     pub async fn project_exists_by_name(&self, name: &str) -> Result<bool> {
+        let pool = pg_pool!(self, project_exists_by_name(name));
         let count = sqlx::query!(
             "SELECT COUNT(*) as count FROM projects WHERE name = $1",
             name
         )
-        .fetch_one(&self.pool)
+        .fetch_one(pool)
         .await?;
 
         Ok(count.count.unwrap_or(0) > 0)
@@ -526,10 +883,11 @@ impl Database {
 
     /// Create a new project
     pub async fn create_project(&self, name: String, description: String, members: Vec<UserId>) -> Result<Project> {
+        let pool = pg_pool!(self, create_project(name, description, members));
         let project_id = Uuid::new_v4();
         
         // Start transaction
-        let mut tx = self.pool.begin().await?;
+        let mut tx = pool.begin().await?;
 
         // Create the project
         let project = sqlx::query!(
@@ -570,11 +928,12 @@ impl Database {
 
     /// Get project by name
     pub async fn get_project_by_name(&self, name: &str) -> Result<Option<Project>> {
-        let project_row = sqlx::query!(
-            "SELECT id, name, description, created_at FROM projects WHERE name = $1",
-            name
+        let pool = pg_pool!(self, get_project_by_name(name));
+        let project_row = sqlx::query_as::<_, ProjectRow>(
+            "SELECT id, name, description, created_at FROM projects WHERE name = $1 AND deleted_at IS NULL",
         )
-        .fetch_optional(&self.pool)
+        .bind(name)
+        .fetch_optional(pool)
         .await?;
 
         if let Some(row) = project_row {
@@ -583,7 +942,7 @@ impl Database {
                 "SELECT user_id FROM project_members WHERE project_id = $1",
                 row.id
             )
-            .fetch_all(&self.pool)
+            .fetch_all(pool)
             .await?
             .into_iter()
             .map(|row| row.user_id)
@@ -602,19 +961,152 @@ impl Database {
         }
     }
 
+    /// Archive or unarchive a project, returning its new `archived_at`.
+    /// Archiving an already archived project keeps the original time.
+    pub async fn set_project_archived(&self, project_id: ProjectId, archived: bool) -> Result<Option<chrono::DateTime<Utc>>> {
+        let pool = pg_pool!(self, set_project_archived(project_id, archived));
+        let archived_at = sqlx::query_scalar::<_, Option<chrono::DateTime<Utc>>>(
+            r#"
+            UPDATE projects
+            SET archived_at = CASE WHEN $2 THEN COALESCE(archived_at, NOW()) ELSE NULL END,
+                updated_at = NOW()
+            WHERE id = $1
+            RETURNING archived_at
+            "#,
+        )
+        .bind(project_id)
+        .bind(archived)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(archived_at)
+    }
+
+    /// A project's settings; projects that never changed them get the defaults
+    pub async fn get_project_settings(&self, project_id: ProjectId) -> Result<ProjectSettings> {
+        let pool = pg_pool!(self, get_project_settings(project_id));
+        let settings = sqlx::query_scalar::<_, String>(
+            "SELECT settings::text FROM project_settings WHERE project_id = $1",
+        )
+        .bind(project_id)
+        .fetch_optional(pool)
+        .await?;
+
+        match settings {
+            Some(settings) => Ok(serde_json::from_str(&settings)?),
+            None => Ok(ProjectSettings::default()),
+        }
+    }
+
+    /// Replace a project's settings
+    pub async fn set_project_settings(&self, project_id: ProjectId, settings: &ProjectSettings) -> Result<()> {
+        let pool = pg_pool!(self, set_project_settings(project_id, settings));
+        sqlx::query(
+            r#"
+            INSERT INTO project_settings (project_id, settings)
+            VALUES ($1, $2::jsonb)
+            ON CONFLICT (project_id) DO UPDATE SET settings = EXCLUDED.settings
+            "#,
+        )
+        .bind(project_id)
+        .bind(serde_json::to_string(settings)?)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// All archived projects and when they were archived
+    pub async fn list_archived_projects(&self) -> Result<Vec<(ProjectId, chrono::DateTime<Utc>)>> {
+        let pool = pg_pool!(self, list_archived_projects());
+        let projects = sqlx::query_as::<_, (ProjectId, chrono::DateTime<Utc>)>(
+            "SELECT id, archived_at FROM projects WHERE archived_at IS NOT NULL",
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(projects)
+    }
+
+    /// Move a project to the trash, returning when it was deleted, or `None`
+    /// if it doesn't exist or is already in the trash
+    pub async fn trash_project(&self, project_id: ProjectId, deleted_by: UserId) -> Result<Option<chrono::DateTime<Utc>>> {
+        let pool = pg_pool!(self, trash_project(project_id, deleted_by));
+        let deleted_at = sqlx::query_scalar::<_, chrono::DateTime<Utc>>(
+            r#"
+            UPDATE projects
+            SET deleted_at = NOW(), deleted_by = $2, updated_at = NOW()
+            WHERE id = $1 AND deleted_at IS NULL
+            RETURNING deleted_at
+            "#,
+        )
+        .bind(project_id)
+        .bind(deleted_by)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(deleted_at)
+    }
+
+    /// Projects in the trash, most recently deleted first
+    pub async fn list_trashed_projects(&self) -> Result<Vec<TrashedProject>> {
+        let pool = pg_pool!(self, list_trashed_projects());
+        let projects = sqlx::query_as::<_, TrashedProject>(
+            r#"
+            SELECT p.id, p.name, p.deleted_at, p.deleted_by, u.username AS deleted_by_username
+            FROM projects p
+            LEFT JOIN users u ON u.id = p.deleted_by
+            WHERE p.deleted_at IS NOT NULL
+            ORDER BY p.deleted_at DESC
+            "#,
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(projects)
+    }
+
+    /// Take a project out of the trash. Returns false if it isn't in the trash.
+    pub async fn untrash_project(&self, project_id: ProjectId) -> Result<bool> {
+        let pool = pg_pool!(self, untrash_project(project_id));
+        let result = sqlx::query(
+            "UPDATE projects SET deleted_at = NULL, deleted_by = NULL, updated_at = NOW() WHERE id = $1 AND deleted_at IS NOT NULL",
+        )
+        .bind(project_id)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Projects that went to the trash before `cutoff`
+    pub async fn expired_trashed_projects(&self, cutoff: chrono::DateTime<Utc>) -> Result<Vec<ProjectId>> {
+        let pool = pg_pool!(self, expired_trashed_projects(cutoff));
+        let projects = sqlx::query_scalar::<_, ProjectId>(
+            "SELECT id FROM projects WHERE deleted_at IS NOT NULL AND deleted_at < $1",
+        )
+        .bind(cutoff)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(projects)
+    }
+
     /// Delete a project and all associated data
     pub async fn delete_project(&self, project_id: ProjectId) -> Result<()> {
+        let pool = pg_pool!(self, delete_project(project_id));
         // PostgreSQL will handle cascading deletes for:
         // - project_members (ON DELETE CASCADE)
         // - rifts (ON DELETE CASCADE) 
         // - rift_collaborators (through rifts CASCADE)
         // - project_settings (ON DELETE CASCADE)
+        // - project_feature_flags (ON DELETE CASCADE)
         
         let result = sqlx::query!(
             "DELETE FROM projects WHERE id = $1",
             project_id
         )
-        .execute(&self.pool)
+        .execute(pool)
         .await?;
 
         if result.rows_affected() == 0 {
@@ -624,4 +1116,2216 @@ impl Database {
         tracing::info!("Successfully deleted project {} and all associated data", project_id);
         Ok(())
     }
-} 
\ No newline at end of file
+
+    /// Register a Git mirror remote for a rift
+    pub async fn create_git_mirror(
+        &self,
+        project_id: ProjectId,
+        rift_id: RiftId,
+        remote_url: &str,
+        created_by: UserId,
+    ) -> Result<GitMirror> {
+        let pool = pg_pool!(self, create_git_mirror(project_id, rift_id, remote_url, created_by));
+        let mirror = sqlx::query_as::<_, GitMirror>(
+            r#"
+            INSERT INTO git_mirrors (id, project_id, rift_id, remote_url, created_by)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, project_id, rift_id, remote_url, created_by, created_at, last_pushed_at, last_pushed_checkpoint_id, last_error
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(project_id)
+        .bind(rift_id)
+        .bind(remote_url)
+        .bind(created_by)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(mirror)
+    }
+
+    /// List Git mirrors configured for a project
+    pub async fn get_project_git_mirrors(&self, project_id: ProjectId) -> Result<Vec<GitMirror>> {
+        let pool = pg_pool!(self, get_project_git_mirrors(project_id));
+        let mirrors = sqlx::query_as::<_, GitMirror>(
+            r#"
+            SELECT id, project_id, rift_id, remote_url, created_by, created_at, last_pushed_at, last_pushed_checkpoint_id, last_error
+            FROM git_mirrors
+            WHERE project_id = $1
+            ORDER BY created_at ASC
+            "#,
+        )
+        .bind(project_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(mirrors)
+    }
+
+    /// List Git mirrors that follow a specific rift
+    pub async fn get_rift_git_mirrors(&self, rift_id: RiftId) -> Result<Vec<GitMirror>> {
+        let pool = pg_pool!(self, get_rift_git_mirrors(rift_id));
+        let mirrors = sqlx::query_as::<_, GitMirror>(
+            r#"
+            SELECT id, project_id, rift_id, remote_url, created_by, created_at, last_pushed_at, last_pushed_checkpoint_id, last_error
+            FROM git_mirrors
+            WHERE rift_id = $1
+            "#,
+        )
+        .bind(rift_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(mirrors)
+    }
+
+    /// Remove a Git mirror; returns false if it did not exist in the project
+    pub async fn delete_git_mirror(&self, project_id: ProjectId, mirror_id: Uuid) -> Result<bool> {
+        let pool = pg_pool!(self, delete_git_mirror(project_id, mirror_id));
+        let result = sqlx::query("DELETE FROM git_mirrors WHERE id = $1 AND project_id = $2")
+            .bind(mirror_id)
+            .bind(project_id)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Record the outcome of the latest push of a checkpoint to a Git mirror
+    pub async fn record_git_mirror_push(&self, mirror_id: Uuid, checkpoint_id: CheckpointId, error: Option<&str>) -> Result<()> {
+        let pool = pg_pool!(self, record_git_mirror_push(mirror_id, checkpoint_id, error));
+        sqlx::query(
+            r#"
+            UPDATE git_mirrors
+            SET last_pushed_at = CASE WHEN $2::TEXT IS NULL THEN NOW() ELSE last_pushed_at END,
+                last_pushed_checkpoint_id = CASE WHEN $2::TEXT IS NULL THEN $3 ELSE last_pushed_checkpoint_id END,
+                last_error = $2
+            WHERE id = $1
+            "#,
+        )
+        .bind(mirror_id)
+        .bind(error)
+        .bind(checkpoint_id)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Append an audit entry, chaining its hash to the previous entry.
+    /// An advisory lock serializes writers so the chain never forks.
+    pub async fn insert_audit_entry(&self, entry: NewAuditEntry<'_>) -> Result<AuditEntry> {
+        let pool = pg_pool!(self, insert_audit_entry(entry));
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(AUDIT_LOG_LOCK_KEY)
+            .execute(&mut *tx)
+            .await?;
+
+        let prev_hash: Option<String> = sqlx::query_scalar("SELECT hash FROM audit_log ORDER BY id DESC LIMIT 1")
+            .fetch_optional(&mut *tx)
+            .await?;
+        let prev_hash = prev_hash.unwrap_or_else(|| AUDIT_GENESIS_HASH.to_string());
+
+        let hash = audit_entry_hash(
+            &prev_hash,
+            entry.occurred_at,
+            entry.actor_id,
+            entry.action,
+            entry.project_id,
+            entry.target,
+            entry.details,
+        );
+
+        let row = sqlx::query_as::<_, AuditEntry>(
+            r#"
+            INSERT INTO audit_log (occurred_at, actor_id, action, project_id, target, details, prev_hash, hash)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id, occurred_at, actor_id, action, project_id, target, details, prev_hash, hash
+            "#,
+        )
+        .bind(entry.occurred_at)
+        .bind(entry.actor_id)
+        .bind(entry.action)
+        .bind(entry.project_id)
+        .bind(entry.target)
+        .bind(entry.details)
+        .bind(&prev_hash)
+        .bind(&hash)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(row)
+    }
+
+    /// List audit entries (newest first) matching the filter, with the total match count
+    pub async fn list_audit_entries(
+        &self,
+        filter: &AuditFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<AuditEntry>, i64)> {
+        let pool = pg_pool!(self, list_audit_entries(filter, limit, offset));
+        let entries = sqlx::query_as::<_, AuditEntry>(
+            r#"
+            SELECT id, occurred_at, actor_id, action, project_id, target, details, prev_hash, hash
+            FROM audit_log
+            WHERE ($1::UUID IS NULL OR actor_id = $1)
+              AND ($2::UUID IS NULL OR project_id = $2)
+              AND ($3::TIMESTAMPTZ IS NULL OR occurred_at >= $3)
+              AND ($4::TIMESTAMPTZ IS NULL OR occurred_at < $4)
+              AND ($5::TEXT IS NULL OR action = $5)
+            ORDER BY id DESC
+            LIMIT $6 OFFSET $7
+            "#,
+        )
+        .bind(filter.user_id)
+        .bind(filter.project_id)
+        .bind(filter.from)
+        .bind(filter.to)
+        .bind(filter.action.as_deref())
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+
+        let total: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*)
+            FROM audit_log
+            WHERE ($1::UUID IS NULL OR actor_id = $1)
+              AND ($2::UUID IS NULL OR project_id = $2)
+              AND ($3::TIMESTAMPTZ IS NULL OR occurred_at >= $3)
+              AND ($4::TIMESTAMPTZ IS NULL OR occurred_at < $4)
+              AND ($5::TEXT IS NULL OR action = $5)
+            "#,
+        )
+        .bind(filter.user_id)
+        .bind(filter.project_id)
+        .bind(filter.from)
+        .bind(filter.to)
+        .bind(filter.action.as_deref())
+        .fetch_one(pool)
+        .await?;
+
+        Ok((entries, total))
+    }
+
+    /// Walk the whole audit chain. Returns the ID of the first entry whose
+    /// hash does not match, or None if the log is intact.
+    pub async fn verify_audit_chain(&self) -> Result<(i64, Option<i64>)> {
+        let pool = pg_pool!(self, verify_audit_chain());
+        let entries = sqlx::query_as::<_, AuditEntry>(
+            r#"
+            SELECT id, occurred_at, actor_id, action, project_id, target, details, prev_hash, hash
+            FROM audit_log
+            ORDER BY id ASC
+            "#,
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let mut expected_prev = AUDIT_GENESIS_HASH.to_string();
+        for entry in &entries {
+            let hash = audit_entry_hash(
+                &entry.prev_hash,
+                entry.occurred_at,
+                entry.actor_id,
+                &entry.action,
+                entry.project_id,
+                entry.target.as_deref(),
+                &entry.details,
+            );
+            if entry.prev_hash != expected_prev || entry.hash != hash {
+                return Ok((entries.len() as i64, Some(entry.id)));
+            }
+            expected_prev = entry.hash.clone();
+        }
+
+        Ok((entries.len() as i64, None))
+    }
+
+    /// Store a new personal access token. Only the token's hash is persisted.
+    pub async fn create_access_token(
+        &self,
+        user_id: UserId,
+        name: &str,
+        token_hash: &str,
+        scopes: &[String],
+        expires_at: Option<chrono::DateTime<Utc>>,
+    ) -> Result<AccessToken> {
+        let pool = pg_pool!(self, create_access_token(user_id, name, token_hash, scopes, expires_at));
+        let token = sqlx::query_as::<_, AccessToken>(
+            r#"
+            INSERT INTO personal_access_tokens (id, user_id, name, token_hash, scopes, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, user_id, name, scopes, created_at, expires_at, last_used_at, revoked_at
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(name)
+        .bind(token_hash)
+        .bind(scopes)
+        .bind(expires_at)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(token)
+    }
+
+    /// List a user's personal access tokens, including revoked ones
+    pub async fn list_access_tokens(&self, user_id: UserId) -> Result<Vec<AccessToken>> {
+        let pool = pg_pool!(self, list_access_tokens(user_id));
+        let tokens = sqlx::query_as::<_, AccessToken>(
+            r#"
+            SELECT id, user_id, name, scopes, created_at, expires_at, last_used_at, revoked_at
+            FROM personal_access_tokens
+            WHERE user_id = $1
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(tokens)
+    }
+
+    /// Revoke a personal access token; returns false if the user has no such active token
+    pub async fn revoke_access_token(&self, user_id: UserId, token_id: Uuid) -> Result<bool> {
+        let pool = pg_pool!(self, revoke_access_token(user_id, token_id));
+        let result = sqlx::query(
+            r#"
+            UPDATE personal_access_tokens
+            SET revoked_at = NOW()
+            WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(token_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Look up an active (unrevoked, unexpired) token by hash and mark it as used
+    pub async fn use_access_token(&self, token_hash: &str) -> Result<Option<AccessToken>> {
+        let pool = pg_pool!(self, use_access_token(token_hash));
+        let token = sqlx::query_as::<_, AccessToken>(
+            r#"
+            UPDATE personal_access_tokens
+            SET last_used_at = NOW()
+            WHERE token_hash = $1
+              AND revoked_at IS NULL
+              AND (expires_at IS NULL OR expires_at > NOW())
+              AND user_id NOT IN (SELECT id FROM users WHERE disabled_at IS NOT NULL)
+            RETURNING id, user_id, name, scopes, created_at, expires_at, last_used_at, revoked_at
+            "#,
+        )
+        .bind(token_hash)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(token)
+    }
+
+    /// Record a machine login; `session_id` is the `jti` of the token issued for it
+    pub async fn create_auth_session(
+        &self,
+        session_id: Uuid,
+        user_id: UserId,
+        machine: &LoginMachine,
+        expires_at: chrono::DateTime<Utc>,
+    ) -> Result<()> {
+        let pool = pg_pool!(self, create_auth_session(session_id, user_id, machine, expires_at));
+        sqlx::query(
+            r#"
+            INSERT INTO auth_sessions (id, user_id, machine_id, machine_name, platform, hostname, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(session_id)
+        .bind(user_id)
+        .bind(&machine.machine_id)
+        .bind(&machine.machine_name)
+        .bind(&machine.platform)
+        .bind(&machine.hostname)
+        .bind(expires_at)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// List a user's active (unrevoked, unexpired) sessions, flagging `current_session`
+    pub async fn list_auth_sessions(&self, user_id: UserId, current_session: Option<Uuid>) -> Result<Vec<AuthSession>> {
+        let pool = pg_pool!(self, list_auth_sessions(user_id, current_session));
+        let sessions = sqlx::query_as::<_, AuthSession>(
+            r#"
+            SELECT s.id, s.user_id, u.username, s.machine_id, s.machine_name, s.platform, s.hostname,
+                   s.created_at, s.expires_at, s.last_seen_at,
+                   COALESCE(s.id = $2, FALSE) AS current
+            FROM auth_sessions s
+            JOIN users u ON u.id = s.user_id
+            WHERE s.user_id = $1 AND s.revoked_at IS NULL AND s.expires_at > NOW()
+            ORDER BY COALESCE(s.last_seen_at, s.created_at) DESC
+            "#,
+        )
+        .bind(user_id)
+        .bind(current_session)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(sessions)
+    }
+
+    /// Revoke an active session. With `owner` set, only that user's sessions match.
+    /// Returns the revoked session's owner, or None if there was no such active session.
+    pub async fn revoke_auth_session(&self, session_id: Uuid, owner: Option<UserId>) -> Result<Option<UserId>> {
+        let pool = pg_pool!(self, revoke_auth_session(session_id, owner));
+        let user_id = sqlx::query_scalar::<_, UserId>(
+            r#"
+            UPDATE auth_sessions
+            SET revoked_at = NOW()
+            WHERE id = $1
+              AND ($2::uuid IS NULL OR user_id = $2)
+              AND revoked_at IS NULL
+            RETURNING user_id
+            "#,
+        )
+        .bind(session_id)
+        .bind(owner)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(user_id)
+    }
+
+    /// IDs of revoked sessions whose tokens would otherwise still be valid
+    pub async fn list_revoked_session_ids(&self) -> Result<Vec<Uuid>> {
+        let pool = pg_pool!(self, list_revoked_session_ids());
+        let ids = sqlx::query_scalar::<_, Uuid>(
+            "SELECT id FROM auth_sessions WHERE revoked_at IS NOT NULL AND expires_at > NOW()",
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(ids)
+    }
+
+    /// Note that a session, and the machine it's on, were just used
+    pub async fn touch_auth_session(&self, session_id: Uuid) -> Result<()> {
+        let pool = pg_pool!(self, touch_auth_session(session_id));
+        sqlx::query("UPDATE auth_sessions SET last_seen_at = NOW() WHERE id = $1")
+            .bind(session_id)
+            .execute(pool)
+            .await?;
+        sqlx::query(
+            r#"
+            UPDATE machines m
+            SET last_seen_at = NOW()
+            FROM auth_sessions s
+            WHERE s.id = $1 AND m.user_id = s.user_id AND m.machine_id = s.machine_id
+            "#,
+        )
+        .bind(session_id)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Record a login on a machine, bringing it back if it was revoked
+    pub async fn record_machine(&self, user_id: UserId, machine: &LoginMachine) -> Result<()> {
+        let pool = pg_pool!(self, record_machine(user_id, machine));
+        sqlx::query(
+            r#"
+            INSERT INTO machines (id, user_id, machine_id, name, platform, hostname)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (user_id, machine_id) DO UPDATE
+            SET name = EXCLUDED.name, platform = EXCLUDED.platform, hostname = EXCLUDED.hostname,
+                last_seen_at = NOW(), revoked_at = NULL
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(&machine.machine_id)
+        .bind(&machine.machine_name)
+        .bind(&machine.platform)
+        .bind(&machine.hostname)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// List a user's machines, most recently seen first, flagging the one
+    /// `current_session` is on
+    pub async fn list_machines(&self, user_id: UserId, current_session: Option<Uuid>) -> Result<Vec<Machine>> {
+        let pool = pg_pool!(self, list_machines(user_id, current_session));
+        let machines = sqlx::query_as::<_, Machine>(
+            r#"
+            SELECT m.id, m.machine_id, m.name, m.platform, m.hostname, m.first_seen_at, m.last_seen_at, m.revoked_at,
+                   (SELECT COUNT(*) FROM auth_sessions s
+                    WHERE s.user_id = m.user_id AND s.machine_id = m.machine_id
+                      AND s.revoked_at IS NULL AND s.expires_at > NOW()) AS active_sessions,
+                   EXISTS (SELECT 1 FROM auth_sessions s
+                           WHERE s.id = $2 AND s.user_id = m.user_id AND s.machine_id = m.machine_id) AS current
+            FROM machines m
+            WHERE m.user_id = $1
+            ORDER BY m.last_seen_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .bind(current_session)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(machines)
+    }
+
+    /// Revoke one of a user's machines and every live session on it.
+    /// Returns the machine's name and the revoked sessions' IDs (`jti`s), or
+    /// None if the user has no such machine.
+    pub async fn revoke_machine(&self, user_id: UserId, id: Uuid) -> Result<Option<(String, Vec<Uuid>)>> {
+        let pool = pg_pool!(self, revoke_machine(user_id, id));
+        let mut tx = pool.begin().await?;
+
+        let machine = sqlx::query_as::<_, (String, String)>(
+            r#"
+            UPDATE machines
+            SET revoked_at = COALESCE(revoked_at, NOW())
+            WHERE id = $1 AND user_id = $2
+            RETURNING machine_id, name
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some((machine_id, name)) = machine else {
+            return Ok(None);
+        };
+
+        let sessions = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            UPDATE auth_sessions
+            SET revoked_at = NOW()
+            WHERE user_id = $1 AND machine_id = $2 AND revoked_at IS NULL AND expires_at > NOW()
+            RETURNING id
+            "#,
+        )
+        .bind(user_id)
+        .bind(&machine_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some((name, sessions)))
+    }
+
+    /// Create a service account: a users row that can only authenticate with keys
+    pub async fn create_service_account(
+        &self,
+        name: &str,
+        email: &str,
+        description: Option<&str>,
+        created_by: UserId,
+    ) -> Result<ServiceAccount> {
+        let pool = pg_pool!(self, create_service_account(name, email, description, created_by));
+        let id = Uuid::new_v4();
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("INSERT INTO users (id, username, email) VALUES ($1, $2, $3)")
+            .bind(id)
+            .bind(name)
+            .bind(email)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("INSERT INTO service_accounts (user_id, description, created_by) VALUES ($1, $2, $3)")
+            .bind(id)
+            .bind(description)
+            .bind(created_by)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        self.get_service_account(id).await?
+            .ok_or_else(|| anyhow::anyhow!("Service account {} vanished after creation", id))
+    }
+
+    /// List service accounts with the projects they are granted, including disabled ones
+    pub async fn list_service_accounts(&self) -> Result<Vec<ServiceAccount>> {
+        let pool = pg_pool!(self, list_service_accounts());
+        let accounts = sqlx::query_as::<_, ServiceAccount>(&format!(
+            "{} GROUP BY u.id, sa.user_id ORDER BY u.username",
+            SERVICE_ACCOUNT_SELECT
+        ))
+        .fetch_all(pool)
+        .await?;
+
+        Ok(accounts)
+    }
+
+    pub async fn get_service_account(&self, id: UserId) -> Result<Option<ServiceAccount>> {
+        let pool = pg_pool!(self, get_service_account(id));
+        let account = sqlx::query_as::<_, ServiceAccount>(&format!(
+            "{} WHERE sa.user_id = $1 GROUP BY u.id, sa.user_id",
+            SERVICE_ACCOUNT_SELECT
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(account)
+    }
+
+    pub async fn is_service_account(&self, user_id: UserId) -> Result<bool> {
+        let pool = pg_pool!(self, is_service_account(user_id));
+        let exists = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (SELECT 1 FROM service_accounts WHERE user_id = $1)",
+        )
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(exists)
+    }
+
+    /// Disable a service account: revoke its keys and remove its project grants.
+    /// The account itself is kept so its checkpoints and audit entries stay attributed.
+    pub async fn disable_service_account(&self, id: UserId) -> Result<bool> {
+        let pool = pg_pool!(self, disable_service_account(id));
+        let mut tx = pool.begin().await?;
+
+        let result = sqlx::query(
+            "UPDATE service_accounts SET disabled_at = NOW() WHERE user_id = $1 AND disabled_at IS NULL",
+        )
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        sqlx::query("UPDATE personal_access_tokens SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("DELETE FROM project_members WHERE user_id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Add a user to a project, or update their role if already a member
+    pub async fn add_project_member(&self, project_id: ProjectId, user_id: UserId, role: &str) -> Result<()> {
+        let pool = pg_pool!(self, add_project_member(project_id, user_id, role));
+        sqlx::query(
+            r#"
+            INSERT INTO project_members (project_id, user_id, role)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (project_id, user_id) DO UPDATE SET role = EXCLUDED.role
+            "#,
+        )
+        .bind(project_id)
+        .bind(user_id)
+        .bind(role)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// The project's owner: the member who created it, i.e. joined first
+    pub async fn get_project_owner(&self, project_id: ProjectId) -> Result<Option<UserId>> {
+        let pool = pg_pool!(self, get_project_owner(project_id));
+        let owner = sqlx::query_scalar(
+            "SELECT user_id FROM project_members WHERE project_id = $1 ORDER BY joined_at ASC LIMIT 1",
+        )
+        .bind(project_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(owner)
+    }
+
+    /// Remove a user from a project; returns false if they were not a member
+    pub async fn remove_project_member(&self, project_id: ProjectId, user_id: UserId) -> Result<bool> {
+        let pool = pg_pool!(self, remove_project_member(project_id, user_id));
+        let result = sqlx::query("DELETE FROM project_members WHERE project_id = $1 AND user_id = $2")
+            .bind(project_id)
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// A user's notification preferences (all enabled if never changed)
+    pub async fn get_notification_preferences(&self, user_id: UserId) -> Result<NotificationPreferences> {
+        let pool = pg_pool!(self, get_notification_preferences(user_id));
+        let preferences = sqlx::query_as::<_, NotificationPreferences>(
+            r#"
+            SELECT invitations, mentions, conflict_rifts, checkpoint_restores
+            FROM notification_preferences
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(preferences.unwrap_or_default())
+    }
+
+    pub async fn set_notification_preferences(&self, user_id: UserId, preferences: &NotificationPreferences) -> Result<()> {
+        let pool = pg_pool!(self, set_notification_preferences(user_id, preferences));
+        sqlx::query(
+            r#"
+            INSERT INTO notification_preferences (user_id, invitations, mentions, conflict_rifts, checkpoint_restores)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (user_id) DO UPDATE
+            SET invitations = EXCLUDED.invitations,
+                mentions = EXCLUDED.mentions,
+                conflict_rifts = EXCLUDED.conflict_rifts,
+                checkpoint_restores = EXCLUDED.checkpoint_restores,
+                updated_at = NOW()
+            "#,
+        )
+        .bind(user_id)
+        .bind(preferences.invitations)
+        .bind(preferences.mentions)
+        .bind(preferences.conflict_rifts)
+        .bind(preferences.checkpoint_restores)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// One page of users for the admin API, oldest first
+    pub async fn list_managed_users(&self, limit: i64, offset: i64) -> Result<(Vec<ManagedUser>, i64)> {
+        let pool = pg_pool!(self, list_managed_users(limit, offset));
+        let users = sqlx::query_as::<_, ManagedUser>(&format!(
+            "{} ORDER BY u.created_at ASC, u.id ASC LIMIT $1 OFFSET $2",
+            MANAGED_USER_SELECT
+        ))
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+
+        let total = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM users")
+            .fetch_one(pool)
+            .await?;
+
+        Ok((users, total))
+    }
+
+    pub async fn get_managed_user(&self, user_id: UserId) -> Result<Option<ManagedUser>> {
+        let pool = pg_pool!(self, get_managed_user(user_id));
+        let user = sqlx::query_as::<_, ManagedUser>(&format!("{} WHERE u.id = $1", MANAGED_USER_SELECT))
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?;
+
+        Ok(user)
+    }
+
+    /// Whether an admin has disabled the user
+    pub async fn is_user_disabled(&self, user_id: UserId) -> Result<bool> {
+        let pool = pg_pool!(self, is_user_disabled(user_id));
+        let disabled = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS(SELECT 1 FROM users WHERE id = $1 AND disabled_at IS NOT NULL)",
+        )
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(disabled)
+    }
+
+    /// Every disabled user
+    pub async fn list_disabled_user_ids(&self) -> Result<Vec<UserId>> {
+        let pool = pg_pool!(self, list_disabled_user_ids());
+        let ids = sqlx::query_scalar::<_, Uuid>("SELECT id FROM users WHERE disabled_at IS NOT NULL")
+            .fetch_all(pool)
+            .await?;
+
+        Ok(ids)
+    }
+
+    /// Disable or re-enable a user. Returns false if nothing changed.
+    pub async fn set_user_disabled(&self, user_id: UserId, disabled: bool) -> Result<bool> {
+        let pool = pg_pool!(self, set_user_disabled(user_id, disabled));
+        let result = sqlx::query(
+            r#"
+            UPDATE users
+            SET disabled_at = CASE WHEN $2 THEN NOW() ELSE NULL END, updated_at = NOW()
+            WHERE id = $1 AND (disabled_at IS NOT NULL) <> $2
+            "#,
+        )
+        .bind(user_id)
+        .bind(disabled)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn set_user_role(&self, user_id: UserId, role: UserRole) -> Result<bool> {
+        let pool = pg_pool!(self, set_user_role(user_id, role));
+        let result = sqlx::query("UPDATE users SET role = $2, updated_at = NOW() WHERE id = $1")
+            .bind(user_id)
+            .bind(role)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Revoke every live session of a user, returning their IDs (`jti`s)
+    pub async fn revoke_user_sessions(&self, user_id: UserId) -> Result<Vec<Uuid>> {
+        let pool = pg_pool!(self, revoke_user_sessions(user_id));
+        let ids = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            UPDATE auth_sessions
+            SET revoked_at = NOW()
+            WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > NOW()
+            RETURNING id
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(ids)
+    }
+
+    /// Delete a user, handing the rifts, project memberships and Git mirrors
+    /// they own to `successor` first. Everything else keyed on the user
+    /// (sessions, tokens, chat, permissions) is removed by cascade.
+    pub async fn delete_user_reassigning(&self, user_id: UserId, successor: UserId) -> Result<()> {
+        let pool = pg_pool!(self, delete_user_reassigning(user_id, successor));
+        let mut tx = pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO project_members (project_id, user_id, role)
+            SELECT project_id, $2, role FROM project_members WHERE user_id = $1
+            ON CONFLICT (project_id, user_id) DO NOTHING
+            "#,
+        )
+        .bind(user_id)
+        .bind(successor)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO rift_collaborators (rift_id, user_id)
+            SELECT rift_id, $2 FROM rift_collaborators WHERE user_id = $1
+            ON CONFLICT (rift_id, user_id) DO NOTHING
+            "#,
+        )
+        .bind(user_id)
+        .bind(successor)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("UPDATE rifts SET owner_id = $2 WHERE owner_id = $1")
+            .bind(user_id)
+            .bind(successor)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("UPDATE rifts SET author = $2 WHERE author = $1")
+            .bind(user_id)
+            .bind(successor)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("UPDATE git_mirrors SET created_by = $2 WHERE created_by = $1")
+            .bind(user_id)
+            .bind(successor)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("DELETE FROM user_rift_state WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Mirror a rift, its project and the users they involve from a primary
+    /// server, keeping the primary's IDs. Runs in one transaction.
+    pub async fn apply_replicated_rift(&self, replicated: &ReplicatedRift) -> Result<()> {
+        let pool = pg_pool!(self, apply_replicated_rift(replicated));
+        let mut tx = pool.begin().await?;
+
+        for user in &replicated.users {
+            sqlx::query(
+                r#"
+                INSERT INTO users (id, username, email, role, created_at)
+                VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT (id) DO UPDATE
+                SET username = EXCLUDED.username, email = EXCLUDED.email, role = EXCLUDED.role, updated_at = NOW()
+                "#,
+            )
+            .bind(user.id)
+            .bind(&user.username)
+            .bind(&user.email)
+            .bind(user.role.clone())
+            .bind(user.created_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let project = &replicated.project;
+        sqlx::query(
+            r#"
+            INSERT INTO projects (id, name, description, created_at, archived_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (id) DO UPDATE
+            SET name = EXCLUDED.name, description = EXCLUDED.description,
+                archived_at = EXCLUDED.archived_at, updated_at = NOW()
+            "#,
+        )
+        .bind(project.id)
+        .bind(&project.name)
+        .bind(&project.description)
+        .bind(project.created_at)
+        .bind(replicated.project_archived_at)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO project_settings (project_id, settings)
+            VALUES ($1, $2::jsonb)
+            ON CONFLICT (project_id) DO UPDATE SET settings = EXCLUDED.settings
+            "#,
+        )
+        .bind(project.id)
+        .bind(serde_json::to_string(&project.settings)?)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM project_members WHERE project_id = $1")
+            .bind(project.id)
+            .execute(&mut *tx)
+            .await?;
+        for member in &project.members {
+            sqlx::query("INSERT INTO project_members (project_id, user_id) VALUES ($1, $2)")
+                .bind(project.id)
+                .bind(member)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let rift = &replicated.rift;
+        sqlx::query(
+            r#"
+            INSERT INTO rifts (id, project_id, name, parent_rift_id, is_active, owner_id, visibility, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (id) DO UPDATE
+            SET name = EXCLUDED.name, parent_rift_id = EXCLUDED.parent_rift_id, is_active = EXCLUDED.is_active,
+                owner_id = EXCLUDED.owner_id, visibility = EXCLUDED.visibility
+            "#,
+        )
+        .bind(rift.id)
+        .bind(rift.project_id)
+        .bind(&rift.name)
+        .bind(rift.parent_rift)
+        .bind(rift.is_active)
+        .bind(replicated.owner)
+        .bind(replicated.visibility.as_str())
+        .bind(rift.created_at)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM rift_collaborators WHERE rift_id = $1")
+            .bind(rift.id)
+            .execute(&mut *tx)
+            .await?;
+        for collaborator in &rift.collaborators {
+            sqlx::query("INSERT INTO rift_collaborators (rift_id, user_id) VALUES ($1, $2)")
+                .bind(rift.id)
+                .bind(collaborator)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        sqlx::query("DELETE FROM rift_permissions WHERE rift_id = $1")
+            .bind(rift.id)
+            .execute(&mut *tx)
+            .await?;
+        for permission in &replicated.permissions {
+            sqlx::query(
+                "INSERT INTO rift_permissions (rift_id, user_id, role, granted_at) VALUES ($1, $2, $3, $4)",
+            )
+            .bind(rift.id)
+            .bind(permission.user_id)
+            .bind(permission.role.as_str())
+            .bind(permission.granted_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Store a chat message and trim the rift's history to `max_history` messages
+    pub async fn insert_chat_message(&self, message: &ChatMessage, max_history: usize) -> Result<()> {
+        let pool = pg_pool!(self, insert_chat_message(message, max_history));
+        sqlx::query(
+            r#"
+            INSERT INTO chat_messages (id, rift_id, author_id, content, created_at)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(message.id)
+        .bind(message.rift_id)
+        .bind(message.author)
+        .bind(&message.content)
+        .bind(message.timestamp)
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM chat_messages
+            WHERE rift_id = $1 AND id NOT IN (
+                SELECT id FROM chat_messages
+                WHERE rift_id = $1
+                ORDER BY created_at DESC
+                LIMIT $2
+            )
+            "#,
+        )
+        .bind(message.rift_id)
+        .bind(max_history as i64)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// List up to `limit` chat messages posted before `before` (newest page by
+    /// default), returned oldest first
+    pub async fn list_chat_messages(
+        &self,
+        rift_id: RiftId,
+        before: Option<chrono::DateTime<Utc>>,
+        limit: i64,
+    ) -> Result<Vec<ChatMessage>> {
+        let pool = pg_pool!(self, list_chat_messages(rift_id, before, limit));
+        let rows = sqlx::query_as::<_, ChatMessageRow>(
+            r#"
+            SELECT m.id, m.rift_id, m.author_id, u.username, m.content, m.created_at
+            FROM chat_messages m
+            JOIN users u ON u.id = m.author_id
+            WHERE m.rift_id = $1 AND ($2::timestamptz IS NULL OR m.created_at < $2)
+            ORDER BY m.created_at DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(rift_id)
+        .bind(before)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.into_iter().rev().map(ChatMessage::from).collect())
+    }
+
+    /// Record a status check, replacing any earlier report for the same context
+    pub async fn upsert_checkpoint_status(&self, status: NewCheckpointStatus<'_>) -> Result<CheckpointStatus> {
+        let pool = pg_pool!(self, upsert_checkpoint_status(status));
+        let row = sqlx::query_as::<_, CheckpointStatusRow>(
+            r#"
+            INSERT INTO checkpoint_statuses (id, project_id, checkpoint_id, context, state, target_url, description, creator_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (checkpoint_id, context) DO UPDATE
+            SET state = EXCLUDED.state,
+                target_url = EXCLUDED.target_url,
+                description = EXCLUDED.description,
+                creator_id = EXCLUDED.creator_id,
+                updated_at = NOW()
+            RETURNING checkpoint_id, context, state, target_url, description, creator_id, updated_at
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(status.project_id)
+        .bind(status.checkpoint_id)
+        .bind(status.context)
+        .bind(status.state.as_str())
+        .bind(status.target_url)
+        .bind(status.description)
+        .bind(status.creator_id)
+        .fetch_one(pool)
+        .await?;
+
+        row.try_into()
+    }
+
+    /// Status checks for a set of checkpoints, ordered by context
+    pub async fn list_checkpoint_statuses(&self, checkpoint_ids: &[CheckpointId]) -> Result<Vec<CheckpointStatus>> {
+        let pool = pg_pool!(self, list_checkpoint_statuses(checkpoint_ids));
+        let rows = sqlx::query_as::<_, CheckpointStatusRow>(
+            r#"
+            SELECT checkpoint_id, context, state, target_url, description, creator_id, updated_at
+            FROM checkpoint_statuses
+            WHERE checkpoint_id = ANY($1)
+            ORDER BY context
+            "#,
+        )
+        .bind(checkpoint_ids)
+        .fetch_all(pool)
+        .await?;
+
+        rows.into_iter().map(CheckpointStatus::try_from).collect()
+    }
+
+    /// Add a comment to a checkpoint. A thread's first comment has
+    /// `thread_id == id`; replies pass the thread's id, path and lines.
+    pub async fn add_checkpoint_comment(&self, comment: NewComment<'_>) -> Result<()> {
+        let pool = pg_pool!(self, add_checkpoint_comment(comment));
+        sqlx::query(
+            r#"
+            INSERT INTO checkpoint_comments (id, project_id, checkpoint_id, thread_id, path, line_start, line_end, author_id, body)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+        )
+        .bind(comment.id)
+        .bind(comment.project_id)
+        .bind(comment.checkpoint_id)
+        .bind(comment.thread_id)
+        .bind(comment.path)
+        .bind(comment.line_start as i32)
+        .bind(comment.line_end as i32)
+        .bind(comment.author_id)
+        .bind(comment.body)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// A project's comment threads, oldest first: those on one checkpoint,
+    /// or just one thread, and optionally only the unresolved ones
+    pub async fn list_comment_threads(
+        &self,
+        project_id: ProjectId,
+        checkpoint_id: Option<CheckpointId>,
+        thread_id: Option<Uuid>,
+        unresolved_only: bool,
+    ) -> Result<Vec<CommentThread>> {
+        let pool = pg_pool!(self, list_comment_threads(project_id, checkpoint_id, thread_id, unresolved_only));
+        let rows = sqlx::query_as::<_, CommentRow>(
+            r#"
+            SELECT c.id, c.thread_id, c.checkpoint_id, c.path, c.line_start, c.line_end, c.author_id,
+                   u.username AS author_name, c.body, t.resolved_by, t.resolved_at, c.created_at
+            FROM checkpoint_comments c
+            INNER JOIN checkpoint_comments t ON t.id = c.thread_id
+            INNER JOIN users u ON u.id = c.author_id
+            WHERE c.project_id = $1
+              AND ($2::uuid IS NULL OR c.checkpoint_id = $2)
+              AND ($3::uuid IS NULL OR c.thread_id = $3)
+              AND (NOT $4 OR t.resolved_at IS NULL)
+            ORDER BY c.created_at, c.id
+            "#,
+        )
+        .bind(project_id)
+        .bind(checkpoint_id)
+        .bind(thread_id)
+        .bind(unresolved_only)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(comment_threads(rows))
+    }
+
+    /// Mark a thread resolved by `resolved_by`, or unresolved with `None`.
+    /// Returns false if the project has no such thread.
+    pub async fn set_comment_thread_resolved(
+        &self,
+        project_id: ProjectId,
+        thread_id: Uuid,
+        resolved_by: Option<UserId>,
+    ) -> Result<bool> {
+        let pool = pg_pool!(self, set_comment_thread_resolved(project_id, thread_id, resolved_by));
+        let result = sqlx::query(
+            r#"
+            UPDATE checkpoint_comments
+            SET resolved_by = $3, resolved_at = $4
+            WHERE project_id = $1 AND id = $2 AND thread_id = id
+            "#,
+        )
+        .bind(project_id)
+        .bind(thread_id)
+        .bind(resolved_by)
+        .bind(resolved_by.map(|_| Utc::now()))
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Open a request to merge `source_rift_id` into `target_rift_id`, asking
+    /// `reviewers` to review it
+    pub async fn create_review_request(&self, review: NewReviewRequest<'_>) -> Result<()> {
+        let pool = pg_pool!(self, create_review_request(review));
+        let mut tx = pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO review_requests (id, project_id, source_rift_id, target_rift_id, title, description, author_id, required_approvals)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(review.id)
+        .bind(review.project_id)
+        .bind(review.source_rift_id)
+        .bind(review.target_rift_id)
+        .bind(review.title)
+        .bind(review.description)
+        .bind(review.author_id)
+        .bind(review.required_approvals as i32)
+        .execute(&mut *tx)
+        .await?;
+
+        for reviewer in review.reviewers {
+            sqlx::query("INSERT INTO review_reviewers (review_id, user_id) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+                .bind(review.id)
+                .bind(reviewer)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// A project's review requests, newest first: those in `state` (any state
+    /// if None), or just one request
+    pub async fn list_review_requests(
+        &self,
+        project_id: ProjectId,
+        state: Option<ReviewState>,
+        review_id: Option<Uuid>,
+        limit: usize,
+    ) -> Result<Vec<ReviewRequest>> {
+        let pool = pg_pool!(self, list_review_requests(project_id, state, review_id, limit));
+        let rows = sqlx::query_as::<_, ReviewRequestRow>(
+            r#"
+            SELECT r.id, r.project_id, r.source_rift_id, s.name AS source_rift_name, r.target_rift_id,
+                   t.name AS target_rift_name, r.title, r.description, r.author_id, u.username AS author_name,
+                   r.state, r.required_approvals, r.merge_checkpoint_id, r.created_at, r.updated_at
+            FROM review_requests r
+            INNER JOIN rifts s ON s.id = r.source_rift_id
+            INNER JOIN rifts t ON t.id = r.target_rift_id
+            INNER JOIN users u ON u.id = r.author_id
+            WHERE r.project_id = $1
+              AND ($2::text IS NULL OR r.state = $2)
+              AND ($3::uuid IS NULL OR r.id = $3)
+            ORDER BY r.created_at DESC, r.id DESC
+            LIMIT $4
+            "#,
+        )
+        .bind(project_id)
+        .bind(state.map(|state| state.as_str()))
+        .bind(review_id)
+        .bind(limit as i64)
+        .fetch_all(pool)
+        .await?;
+
+        let review_ids: Vec<Uuid> = rows.iter().map(|row| row.id).collect();
+        let reviewers = sqlx::query_as::<_, ReviewerRow>(
+            r#"
+            SELECT rr.review_id, rr.user_id, u.username, rr.approved_at
+            FROM review_reviewers rr
+            INNER JOIN users u ON u.id = rr.user_id
+            WHERE rr.review_id = ANY($1)
+            ORDER BY rr.added_at, u.username
+            "#,
+        )
+        .bind(&review_ids)
+        .fetch_all(pool)
+        .await?;
+        let comments = sqlx::query_as::<_, ReviewCommentRow>(
+            r#"
+            SELECT c.id, c.review_id, c.author_id, u.username AS author_name, c.body, c.created_at
+            FROM review_comments c
+            INNER JOIN users u ON u.id = c.author_id
+            WHERE c.review_id = ANY($1)
+            ORDER BY c.created_at, c.id
+            "#,
+        )
+        .bind(&review_ids)
+        .fetch_all(pool)
+        .await?;
+
+        review_requests(rows, reviewers, comments)
+    }
+
+    /// The open review request to merge one rift into another, if there is one
+    pub async fn get_open_review_id(&self, source_rift_id: RiftId, target_rift_id: RiftId) -> Result<Option<Uuid>> {
+        let pool = pg_pool!(self, get_open_review_id(source_rift_id, target_rift_id));
+        let id = sqlx::query_scalar::<_, Uuid>(
+            "SELECT id FROM review_requests WHERE source_rift_id = $1 AND target_rift_id = $2 AND state = 'open'",
+        )
+        .bind(source_rift_id)
+        .bind(target_rift_id)
+        .fetch_optional(pool)
+        .await?;
+        Ok(id)
+    }
+
+    /// Ask more users to review a request; ones already asked are left as they are
+    pub async fn add_review_reviewers(&self, review_id: Uuid, reviewers: &[UserId]) -> Result<()> {
+        let pool = pg_pool!(self, add_review_reviewers(review_id, reviewers));
+        let mut tx = pool.begin().await?;
+        for reviewer in reviewers {
+            sqlx::query("INSERT INTO review_reviewers (review_id, user_id) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+                .bind(review_id)
+                .bind(reviewer)
+                .execute(&mut *tx)
+                .await?;
+        }
+        sqlx::query("UPDATE review_requests SET updated_at = NOW() WHERE id = $1")
+            .bind(review_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Record a user's approval, adding them as a reviewer if they weren't asked.
+    /// Approving twice keeps the first approval's time.
+    pub async fn approve_review_request(&self, review_id: Uuid, user_id: UserId) -> Result<()> {
+        let pool = pg_pool!(self, approve_review_request(review_id, user_id));
+        let mut tx = pool.begin().await?;
+        sqlx::query(
+            r#"
+            INSERT INTO review_reviewers (review_id, user_id, approved_at) VALUES ($1, $2, NOW())
+            ON CONFLICT (review_id, user_id)
+            DO UPDATE SET approved_at = COALESCE(review_reviewers.approved_at, EXCLUDED.approved_at)
+            "#,
+        )
+        .bind(review_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("UPDATE review_requests SET updated_at = NOW() WHERE id = $1")
+            .bind(review_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn add_review_comment(&self, review_id: Uuid, author_id: UserId, body: &str) -> Result<()> {
+        let pool = pg_pool!(self, add_review_comment(review_id, author_id, body));
+        let mut tx = pool.begin().await?;
+        sqlx::query("INSERT INTO review_comments (id, review_id, author_id, body) VALUES ($1, $2, $3, $4)")
+            .bind(Uuid::new_v4())
+            .bind(review_id)
+            .bind(author_id)
+            .bind(body)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE review_requests SET updated_at = NOW() WHERE id = $1")
+            .bind(review_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Mark an open review request merged (with the merge's checkpoint) or
+    /// closed. Returns false if it wasn't open.
+    pub async fn finish_review_request(
+        &self,
+        review_id: Uuid,
+        state: ReviewState,
+        merge_checkpoint_id: Option<CheckpointId>,
+    ) -> Result<bool> {
+        let pool = pg_pool!(self, finish_review_request(review_id, state, merge_checkpoint_id));
+        let result = sqlx::query(
+            r#"
+            UPDATE review_requests
+            SET state = $2, merge_checkpoint_id = $3, updated_at = NOW()
+            WHERE id = $1 AND state = 'open'
+            "#,
+        )
+        .bind(review_id)
+        .bind(state.as_str())
+        .bind(merge_checkpoint_id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Store an in-app notification for a user and return it as they'll see it
+    pub async fn create_user_notification(
+        &self,
+        user_id: UserId,
+        kind: InboxKind,
+        title: &str,
+        body: &str,
+        project_id: Option<ProjectId>,
+        actor_id: Option<UserId>,
+    ) -> Result<InboxNotification> {
+        let pool = pg_pool!(self, create_user_notification(user_id, kind, title, body, project_id, actor_id));
+        let row = sqlx::query_as::<_, UserNotificationRow>(
+            r#"
+            WITH inserted AS (
+                INSERT INTO user_notifications (id, user_id, kind, title, body, project_id, actor_id)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                RETURNING *
+            )
+            SELECT n.id, n.kind, n.title, n.body, n.project_id, n.actor_id, u.username AS actor_name, n.read_at, n.created_at
+            FROM inserted n
+            LEFT JOIN users u ON u.id = n.actor_id
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(kind.as_str())
+        .bind(title)
+        .bind(body)
+        .bind(project_id)
+        .bind(actor_id)
+        .fetch_one(pool)
+        .await?;
+        inbox_notification(row)
+    }
+
+    /// A user's notifications, newest first, optionally only unread ones or
+    /// ones older than a time (for paging back)
+    pub async fn list_user_notifications(
+        &self,
+        user_id: UserId,
+        unread_only: bool,
+        before: Option<chrono::DateTime<Utc>>,
+        limit: usize,
+    ) -> Result<Vec<InboxNotification>> {
+        let pool = pg_pool!(self, list_user_notifications(user_id, unread_only, before, limit));
+        let rows = sqlx::query_as::<_, UserNotificationRow>(
+            r#"
+            SELECT n.id, n.kind, n.title, n.body, n.project_id, n.actor_id, u.username AS actor_name, n.read_at, n.created_at
+            FROM user_notifications n
+            LEFT JOIN users u ON u.id = n.actor_id
+            WHERE n.user_id = $1
+              AND (NOT $2 OR n.read_at IS NULL)
+              AND ($3::TIMESTAMPTZ IS NULL OR n.created_at < $3)
+            ORDER BY n.created_at DESC, n.id DESC
+            LIMIT $4
+            "#,
+        )
+        .bind(user_id)
+        .bind(unread_only)
+        .bind(before)
+        .bind(limit as i64)
+        .fetch_all(pool)
+        .await?;
+        rows.into_iter().map(inbox_notification).collect()
+    }
+
+    pub async fn count_unread_notifications(&self, user_id: UserId) -> Result<u64> {
+        let pool = pg_pool!(self, count_unread_notifications(user_id));
+        let count = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM user_notifications WHERE user_id = $1 AND read_at IS NULL",
+        )
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?;
+        Ok(count as u64)
+    }
+
+    /// Mark one of a user's notifications read, or all of them when no ID is
+    /// given. Returns how many were unread.
+    pub async fn mark_notifications_read(&self, user_id: UserId, id: Option<Uuid>) -> Result<u64> {
+        let pool = pg_pool!(self, mark_notifications_read(user_id, id));
+        let result = sqlx::query(
+            r#"
+            UPDATE user_notifications SET read_at = NOW()
+            WHERE user_id = $1 AND read_at IS NULL AND ($2::UUID IS NULL OR id = $2)
+            "#,
+        )
+        .bind(user_id)
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Feature flags administrators set server-wide, as stored: feature
+    /// name and whether it's on
+    pub async fn get_server_feature_flags(&self) -> Result<Vec<(String, bool)>> {
+        let pool = pg_pool!(self, get_server_feature_flags());
+        let flags = sqlx::query_as::<_, (String, bool)>("SELECT feature, enabled FROM server_feature_flags")
+            .fetch_all(pool)
+            .await?;
+        Ok(flags)
+    }
+
+    /// Set a server-wide feature flag, or clear it with `None` so the
+    /// configuration decides again
+    pub async fn set_server_feature_flag(&self, feature: &str, enabled: Option<bool>, updated_by: UserId) -> Result<()> {
+        let pool = pg_pool!(self, set_server_feature_flag(feature, enabled, updated_by));
+        match enabled {
+            Some(enabled) => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO server_feature_flags (feature, enabled, updated_by)
+                    VALUES ($1, $2, $3)
+                    ON CONFLICT (feature) DO UPDATE
+                    SET enabled = EXCLUDED.enabled, updated_by = EXCLUDED.updated_by, updated_at = NOW()
+                    "#,
+                )
+                .bind(feature)
+                .bind(enabled)
+                .bind(updated_by)
+                .execute(pool)
+                .await?;
+            }
+            None => {
+                sqlx::query("DELETE FROM server_feature_flags WHERE feature = $1")
+                    .bind(feature)
+                    .execute(pool)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Feature flags administrators set for a project
+    pub async fn get_project_feature_flags(&self, project_id: ProjectId) -> Result<Vec<(String, bool)>> {
+        let pool = pg_pool!(self, get_project_feature_flags(project_id));
+        let flags = sqlx::query_as::<_, (String, bool)>("SELECT feature, enabled FROM project_feature_flags WHERE project_id = $1")
+            .bind(project_id)
+            .fetch_all(pool)
+            .await?;
+        Ok(flags)
+    }
+
+    /// Set a project's feature flag, or clear it with `None`
+    pub async fn set_project_feature_flag(&self, project_id: ProjectId, feature: &str, enabled: Option<bool>, updated_by: UserId) -> Result<()> {
+        let pool = pg_pool!(self, set_project_feature_flag(project_id, feature, enabled, updated_by));
+        match enabled {
+            Some(enabled) => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO project_feature_flags (project_id, feature, enabled, updated_by)
+                    VALUES ($1, $2, $3, $4)
+                    ON CONFLICT (project_id, feature) DO UPDATE
+                    SET enabled = EXCLUDED.enabled, updated_by = EXCLUDED.updated_by, updated_at = NOW()
+                    "#,
+                )
+                .bind(project_id)
+                .bind(feature)
+                .bind(enabled)
+                .bind(updated_by)
+                .execute(pool)
+                .await?;
+            }
+            None => {
+                sqlx::query("DELETE FROM project_feature_flags WHERE project_id = $1 AND feature = $2")
+                    .bind(project_id)
+                    .bind(feature)
+                    .execute(pool)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Whitelist entries, oldest first
+    pub async fn list_whitelist_entries(&self) -> Result<Vec<WhitelistEntry>> {
+        let pool = pg_pool!(self, list_whitelist_entries());
+        let entries = sqlx::query_as::<_, WhitelistEntry>(
+            "SELECT id, kind, value, added_by, created_at FROM whitelist_entries ORDER BY created_at ASC, kind ASC, value ASC",
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(entries)
+    }
+
+    /// Add a whitelist entry, or return the one already there
+    pub async fn add_whitelist_entry(&self, kind: &str, value: &str, added_by: Option<UserId>) -> Result<WhitelistEntry> {
+        let pool = pg_pool!(self, add_whitelist_entry(kind, value, added_by));
+        sqlx::query(
+            r#"
+            INSERT INTO whitelist_entries (id, kind, value, added_by)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (kind, value) DO NOTHING
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(kind)
+        .bind(value)
+        .bind(added_by)
+        .execute(pool)
+        .await?;
+
+        let entry = sqlx::query_as::<_, WhitelistEntry>(
+            "SELECT id, kind, value, added_by, created_at FROM whitelist_entries WHERE kind = $1 AND value = $2",
+        )
+        .bind(kind)
+        .bind(value)
+        .fetch_one(pool)
+        .await?;
+        Ok(entry)
+    }
+
+    /// Remove a whitelist entry; returns it if it existed
+    pub async fn remove_whitelist_entry(&self, id: Uuid) -> Result<Option<WhitelistEntry>> {
+        let pool = pg_pool!(self, remove_whitelist_entry(id));
+        let entry = sqlx::query_as::<_, WhitelistEntry>(
+            "DELETE FROM whitelist_entries WHERE id = $1 RETURNING id, kind, value, added_by, created_at",
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+        Ok(entry)
+    }
+
+    /// The OAuth groups a user belonged to at their last login
+    pub async fn get_user_oauth_groups(&self, user_id: UserId) -> Result<Vec<String>> {
+        let pool = pg_pool!(self, get_user_oauth_groups(user_id));
+        let groups = sqlx::query_scalar::<_, String>("SELECT group_name FROM user_oauth_groups WHERE user_id = $1 ORDER BY group_name")
+            .bind(user_id)
+            .fetch_all(pool)
+            .await?;
+        Ok(groups)
+    }
+
+    /// Replace the OAuth groups recorded for a user
+    pub async fn set_user_oauth_groups(&self, user_id: UserId, groups: &[String]) -> Result<()> {
+        let pool = pg_pool!(self, set_user_oauth_groups(user_id, groups));
+        let mut tx = pool.begin().await?;
+        sqlx::query("DELETE FROM user_oauth_groups WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        for group in groups {
+            sqlx::query("INSERT INTO user_oauth_groups (user_id, group_name) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+                .bind(user_id)
+                .bind(group)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Remember the GitHub login a user signed in with; returns whether
+    /// their role came from GitHub membership
+    pub async fn set_github_login(&self, user_id: UserId, login: &str) -> Result<bool> {
+        let pool = pg_pool!(self, set_github_login(user_id, login));
+        let granted = sqlx::query_scalar::<_, bool>(
+            r#"
+            INSERT INTO github_accounts (user_id, login)
+            VALUES ($1, $2)
+            ON CONFLICT (user_id) DO UPDATE
+            SET login = EXCLUDED.login, checked_at = NOW()
+            RETURNING role_granted
+            "#,
+        )
+        .bind(user_id)
+        .bind(login)
+        .fetch_one(pool)
+        .await?;
+        Ok(granted)
+    }
+
+    /// Users who signed in with GitHub: id, GitHub login, and whether their
+    /// role came from GitHub membership
+    pub async fn list_github_accounts(&self) -> Result<Vec<(UserId, String, bool)>> {
+        let pool = pg_pool!(self, list_github_accounts());
+        let accounts = sqlx::query_as::<_, (UserId, String, bool)>("SELECT user_id, login, role_granted FROM github_accounts ORDER BY checked_at ASC")
+            .fetch_all(pool)
+            .await?;
+        Ok(accounts)
+    }
+
+    /// Record whether a user's role came from GitHub membership, and that
+    /// their membership was just checked
+    pub async fn set_github_role_granted(&self, user_id: UserId, granted: bool) -> Result<()> {
+        let pool = pg_pool!(self, set_github_role_granted(user_id, granted));
+        sqlx::query("UPDATE github_accounts SET role_granted = $2, checked_at = NOW() WHERE user_id = $1")
+            .bind(user_id)
+            .bind(granted)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Record every migration as applied on a database whose schema was created
+/// without migration tracking. Migrations that fail because what they create
+/// already exists are marked applied; the rest run normally.
+async fn adopt_untracked_schema(pool: &PgPool) -> Result<()> {
+    tracing::info!("🔄 Adopting existing database schema into migration tracking...");
+
+    let mut conn = pool.acquire().await?;
+    conn.ensure_migrations_table().await?;
+
+    for migration in MIGRATOR.iter() {
+        match conn.apply(migration).await {
+            Ok(_) => tracing::info!("Applied migration {} ({})", migration.version, migration.description),
+            Err(MigrateError::Execute(sqlx::Error::Database(e))) if is_duplicate_object(e.code().as_deref()) => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time)
+                    VALUES ($1, $2, TRUE, $3, 0)
+                    "#,
+                )
+                .bind(migration.version)
+                .bind(&*migration.description)
+                .bind(&*migration.checksum)
+                .execute(&mut *conn)
+                .await?;
+                tracing::info!("Migration {} ({}) was already applied", migration.version, migration.description);
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    Ok(())
+}
+
+/// SQLSTATEs for "already exists": table, object, column, function
+fn is_duplicate_object(code: Option<&str>) -> bool {
+    matches!(code, Some("42P07" | "42710" | "42701" | "42723"))
+}
+
+#[derive(sqlx::FromRow)]
+struct ProjectRow {
+    id: ProjectId,
+    name: String,
+    description: Option<String>,
+    created_at: chrono::DateTime<Utc>,
+}
+
+/// Projects with their members, without settings
+async fn projects_from_rows(pool: &PgPool, rows: Vec<ProjectRow>) -> Result<Vec<Project>> {
+    let mut result = Vec::new();
+    for row in rows {
+        // Get project members for each project
+        let members = sqlx::query!(
+            "SELECT user_id FROM project_members WHERE project_id = $1",
+            row.id
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|member_row| member_row.user_id)
+        .collect();
+
+        result.push(Project {
+            id: row.id,
+            name: row.name,
+            description: row.description.unwrap_or_default(),
+            members,
+            created_at: row.created_at,
+            settings: ProjectSettings::default(),
+        });
+    }
+    Ok(result)
+}
+
+#[derive(sqlx::FromRow)]
+struct CheckpointStatusRow {
+    checkpoint_id: CheckpointId,
+    context: String,
+    state: String,
+    target_url: Option<String>,
+    description: Option<String>,
+    creator_id: UserId,
+    updated_at: chrono::DateTime<Utc>,
+}
+
+impl TryFrom<CheckpointStatusRow> for CheckpointStatus {
+    type Error = anyhow::Error;
+
+    fn try_from(row: CheckpointStatusRow) -> Result<Self> {
+        let state = StatusState::parse(&row.state)
+            .ok_or_else(|| anyhow::anyhow!("Invalid status state in database: {}", row.state))?;
+
+        Ok(Self {
+            checkpoint_id: row.checkpoint_id,
+            state,
+            context: row.context,
+            target_url: row.target_url,
+            description: row.description,
+            creator: row.creator_id,
+            updated_at: row.updated_at,
+        })
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct CommentRow {
+    id: Uuid,
+    thread_id: Uuid,
+    checkpoint_id: CheckpointId,
+    path: String,
+    line_start: i32,
+    line_end: i32,
+    author_id: UserId,
+    author_name: String,
+    body: String,
+    resolved_by: Option<UserId>,
+    resolved_at: Option<chrono::DateTime<Utc>>,
+    created_at: chrono::DateTime<Utc>,
+}
+
+/// Group comments, oldest first, into their threads
+fn comment_threads(rows: Vec<CommentRow>) -> Vec<CommentThread> {
+    let mut threads: Vec<CommentThread> = Vec::new();
+    let mut positions: HashMap<Uuid, usize> = HashMap::new();
+    for row in rows {
+        let position = *positions.entry(row.thread_id).or_insert_with(|| {
+            threads.push(CommentThread {
+                id: row.thread_id,
+                checkpoint_id: row.checkpoint_id,
+                path: row.path.clone().into(),
+                line_start: row.line_start as u32,
+                line_end: row.line_end as u32,
+                resolved_by: row.resolved_by,
+                resolved_at: row.resolved_at,
+                comments: Vec::new(),
+            });
+            threads.len() - 1
+        });
+        threads[position].comments.push(ThreadComment {
+            id: row.id,
+            author: row.author_id,
+            author_name: row.author_name,
+            body: row.body,
+            created_at: row.created_at,
+        });
+    }
+    threads
+}
+
+#[derive(sqlx::FromRow)]
+struct ReviewRequestRow {
+    id: Uuid,
+    project_id: ProjectId,
+    source_rift_id: RiftId,
+    source_rift_name: String,
+    target_rift_id: RiftId,
+    target_rift_name: String,
+    title: String,
+    description: Option<String>,
+    author_id: UserId,
+    author_name: String,
+    state: String,
+    required_approvals: i32,
+    merge_checkpoint_id: Option<CheckpointId>,
+    created_at: chrono::DateTime<Utc>,
+    updated_at: chrono::DateTime<Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+struct ReviewerRow {
+    review_id: Uuid,
+    user_id: UserId,
+    username: String,
+    approved_at: Option<chrono::DateTime<Utc>>,
+}
+
+#[derive(sqlx::FromRow)]
+struct ReviewCommentRow {
+    id: Uuid,
+    review_id: Uuid,
+    author_id: UserId,
+    author_name: String,
+    body: String,
+    created_at: chrono::DateTime<Utc>,
+}
+
+/// Put review requests together with their reviewers and comments
+fn review_requests(
+    rows: Vec<ReviewRequestRow>,
+    reviewers: Vec<ReviewerRow>,
+    comments: Vec<ReviewCommentRow>,
+) -> Result<Vec<ReviewRequest>> {
+    let mut reviewers_by_review: HashMap<Uuid, Vec<Reviewer>> = HashMap::new();
+    for row in reviewers {
+        reviewers_by_review.entry(row.review_id).or_default().push(Reviewer {
+            user_id: row.user_id,
+            username: row.username,
+            approved_at: row.approved_at,
+        });
+    }
+    let mut comments_by_review: HashMap<Uuid, Vec<ReviewComment>> = HashMap::new();
+    for row in comments {
+        comments_by_review.entry(row.review_id).or_default().push(ReviewComment {
+            id: row.id,
+            author: row.author_id,
+            author_name: row.author_name,
+            body: row.body,
+            created_at: row.created_at,
+        });
+    }
+
+    rows.into_iter()
+        .map(|row| {
+            Ok(ReviewRequest {
+                id: row.id,
+                project_id: row.project_id,
+                source_rift_id: row.source_rift_id,
+                source_rift_name: row.source_rift_name,
+                target_rift_id: row.target_rift_id,
+                target_rift_name: row.target_rift_name,
+                title: row.title,
+                description: row.description,
+                author: row.author_id,
+                author_name: row.author_name,
+                state: ReviewState::parse(&row.state)
+                    .ok_or_else(|| anyhow::anyhow!("Unknown review state '{}'", row.state))?,
+                required_approvals: row.required_approvals as u32,
+                reviewers: reviewers_by_review.remove(&row.id).unwrap_or_default(),
+                comments: comments_by_review.remove(&row.id).unwrap_or_default(),
+                merge_checkpoint: row.merge_checkpoint_id,
+                created_at: row.created_at,
+                updated_at: row.updated_at,
+            })
+        })
+        .collect()
+}
+
+#[derive(sqlx::FromRow)]
+struct UserNotificationRow {
+    id: Uuid,
+    kind: String,
+    title: String,
+    body: String,
+    project_id: Option<ProjectId>,
+    actor_id: Option<UserId>,
+    actor_name: Option<String>,
+    read_at: Option<chrono::DateTime<Utc>>,
+    created_at: chrono::DateTime<Utc>,
+}
+
+fn inbox_notification(row: UserNotificationRow) -> Result<InboxNotification> {
+    Ok(InboxNotification {
+        id: row.id,
+        kind: InboxKind::parse(&row.kind)
+            .ok_or_else(|| anyhow::anyhow!("Unknown notification kind '{}'", row.kind))?,
+        title: row.title,
+        body: row.body,
+        project_id: row.project_id,
+        actor: row.actor_id,
+        actor_name: row.actor_name,
+        read_at: row.read_at,
+        created_at: row.created_at,
+    })
+}
+
+#[derive(sqlx::FromRow)]
+struct RiftPermissionRow {
+    user_id: UserId,
+    username: String,
+    role: String,
+    granted_at: chrono::DateTime<Utc>,
+}
+
+impl TryFrom<RiftPermissionRow> for RiftPermission {
+    type Error = anyhow::Error;
+
+    fn try_from(row: RiftPermissionRow) -> Result<Self> {
+        let role = RiftRole::parse(&row.role)
+            .ok_or_else(|| anyhow::anyhow!("Unknown rift role '{}'", row.role))?;
+
+        Ok(Self {
+            user_id: row.user_id,
+            username: row.username,
+            role,
+            granted_at: row.granted_at,
+        })
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct ChatMessageRow {
+    id: Uuid,
+    rift_id: RiftId,
+    author_id: UserId,
+    username: String,
+    content: String,
+    created_at: chrono::DateTime<Utc>,
+}
+
+impl From<ChatMessageRow> for ChatMessage {
+    fn from(row: ChatMessageRow) -> Self {
+        Self {
+            id: row.id,
+            rift_id: row.rift_id,
+            author: row.author_id,
+            username: row.username,
+            content: row.content,
+            timestamp: row.created_at,
+        }
+    }
+}
+
+/// A Git remote that receives a rift's checkpoint history
+#[derive(Debug, Clone, serde::Serialize, sqlx::FromRow)]
+pub struct GitMirror {
+    pub id: Uuid,
+    pub project_id: ProjectId,
+    pub rift_id: RiftId,
+    pub remote_url: String,
+    pub created_by: UserId,
+    pub created_at: chrono::DateTime<Utc>,
+    pub last_pushed_at: Option<chrono::DateTime<Utc>>,
+    /// The newest checkpoint the mirror has received
+    pub last_pushed_checkpoint_id: Option<CheckpointId>,
+    pub last_error: Option<String>,
+}
+
+/// A personal access token (the token hash is never loaded)
+#[derive(Debug, Clone, serde::Serialize, sqlx::FromRow)]
+pub struct AccessToken {
+    pub id: Uuid,
+    pub user_id: UserId,
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub created_at: chrono::DateTime<Utc>,
+    pub expires_at: Option<chrono::DateTime<Utc>>,
+    pub last_used_at: Option<chrono::DateTime<Utc>>,
+    pub revoked_at: Option<chrono::DateTime<Utc>>,
+}
+
+/// A service account and the projects it has been granted
+#[derive(Debug, Clone, serde::Serialize, sqlx::FromRow)]
+pub struct ServiceAccount {
+    pub id: UserId,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_by: Option<UserId>,
+    pub created_at: chrono::DateTime<Utc>,
+    pub disabled_at: Option<chrono::DateTime<Utc>>,
+    pub projects: Vec<ProjectId>,
+}
+
+/// Shared SELECT for service accounts; callers append WHERE / GROUP BY
+const SERVICE_ACCOUNT_SELECT: &str = r#"
+    SELECT u.id, u.username AS name, sa.description, sa.created_by, sa.created_at, sa.disabled_at,
+           COALESCE(array_agg(pm.project_id) FILTER (WHERE pm.project_id IS NOT NULL), '{}') AS projects
+    FROM service_accounts sa
+    JOIN users u ON u.id = sa.user_id
+    LEFT JOIN project_members pm ON pm.user_id = sa.user_id
+"#;
+
+/// An entry of the user whitelist
+#[derive(Debug, Clone, serde::Serialize, sqlx::FromRow)]
+pub struct WhitelistEntry {
+    pub id: Uuid,
+    /// `username`, `email`, `domain` or `group`
+    pub kind: String,
+    pub value: String,
+    /// Admin who added it; none for entries seeded from the whitelist file
+    pub added_by: Option<UserId>,
+    pub created_at: chrono::DateTime<Utc>,
+}
+
+/// A project in the trash
+#[derive(Debug, Clone, serde::Serialize, sqlx::FromRow)]
+pub struct TrashedProject {
+    pub id: ProjectId,
+    pub name: String,
+    pub deleted_at: chrono::DateTime<Utc>,
+    pub deleted_by: Option<UserId>,
+    pub deleted_by_username: Option<String>,
+}
+
+/// A user as seen by the admin user management API
+#[derive(Debug, Clone, serde::Serialize, sqlx::FromRow)]
+pub struct ManagedUser {
+    pub id: UserId,
+    pub username: String,
+    pub email: String,
+    pub role: UserRole,
+    pub created_at: chrono::DateTime<Utc>,
+    pub disabled_at: Option<chrono::DateTime<Utc>>,
+    pub service_account: bool,
+    /// Unrevoked, unexpired login sessions
+    pub active_sessions: i64,
+}
+
+/// Shared SELECT for managed users; callers append WHERE / ORDER BY
+const MANAGED_USER_SELECT: &str = r#"
+    SELECT u.id, u.username, u.email, u.role, u.created_at, u.disabled_at,
+           EXISTS(SELECT 1 FROM service_accounts sa WHERE sa.user_id = u.id) AS service_account,
+           (SELECT COUNT(*) FROM auth_sessions s
+            WHERE s.user_id = u.id AND s.revoked_at IS NULL AND s.expires_at > NOW()) AS active_sessions
+    FROM users u
+"#;
+
+/// Advisory lock key serializing audit log writers
+const AUDIT_LOG_LOCK_KEY: i64 = 0x4d6f_7468_4175_6474; // "MothAudt"
+
+/// `prev_hash` of the first audit entry
+const AUDIT_GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Pool settings from `[database]`, for either backend
+pub(crate) fn pool_options<DB: sqlx::Database>(settings: &DatabaseSettings) -> PoolOptions<DB> {
+    let optional = |secs: u64| (secs > 0).then(|| Duration::from_secs(secs));
+    PoolOptions::new()
+        .max_connections(settings.max_connections.max(1))
+        .min_connections(settings.min_connections)
+        .acquire_timeout(Duration::from_secs(settings.acquire_timeout_secs))
+        .idle_timeout(optional(settings.idle_timeout_secs))
+        .max_lifetime(optional(settings.max_lifetime_secs))
+}
+
+/// Connections of the database pool
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+pub struct PoolStats {
+    /// Connections open, busy or idle
+    pub size: u32,
+    pub idle: u32,
+    /// Connections running a query
+    pub in_use: u32,
+    pub max_connections: u32,
+}
+
+impl PoolStats {
+    pub(crate) fn of<DB: sqlx::Database>(pool: &Pool<DB>) -> Self {
+        let size = pool.size();
+        let idle = pool.num_idle() as u32;
+        Self {
+            size,
+            idle,
+            in_use: size.saturating_sub(idle),
+            max_connections: pool.options().get_max_connections(),
+        }
+    }
+}
+
+/// A row of the audit log
+#[derive(Debug, Clone, serde::Serialize, sqlx::FromRow)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub occurred_at: chrono::DateTime<Utc>,
+    pub actor_id: Option<UserId>,
+    pub action: String,
+    pub project_id: Option<ProjectId>,
+    pub target: Option<String>,
+    /// JSON-encoded details
+    pub details: String,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+/// A status check as reported for a checkpoint
+pub struct NewCheckpointStatus<'a> {
+    pub project_id: ProjectId,
+    pub creator_id: UserId,
+    pub checkpoint_id: CheckpointId,
+    pub state: StatusState,
+    pub context: &'a str,
+    pub target_url: Option<&'a str>,
+    pub description: Option<&'a str>,
+}
+
+/// A comment to store. A thread's first comment has `thread_id == id`;
+/// replies carry the thread's id, path and lines.
+pub struct NewComment<'a> {
+    pub id: Uuid,
+    pub project_id: ProjectId,
+    pub checkpoint_id: CheckpointId,
+    pub thread_id: Uuid,
+    pub path: &'a str,
+    pub line_start: u32,
+    pub line_end: u32,
+    pub author_id: UserId,
+    pub body: &'a str,
+}
+
+/// A request to merge `source_rift_id` into `target_rift_id`, before it is opened
+pub struct NewReviewRequest<'a> {
+    pub id: Uuid,
+    pub project_id: ProjectId,
+    pub source_rift_id: RiftId,
+    pub target_rift_id: RiftId,
+    pub title: &'a str,
+    pub description: Option<&'a str>,
+    pub author_id: UserId,
+    pub required_approvals: u32,
+    pub reviewers: &'a [UserId],
+}
+
+/// Fields of an audit entry before it is chained into the log
+pub struct NewAuditEntry<'a> {
+    pub occurred_at: chrono::DateTime<Utc>,
+    pub actor_id: Option<UserId>,
+    pub action: &'a str,
+    pub project_id: Option<ProjectId>,
+    pub target: Option<&'a str>,
+    pub details: &'a str,
+}
+
+/// Filters for listing audit entries
+#[derive(Debug, Default)]
+pub struct AuditFilter {
+    pub user_id: Option<UserId>,
+    pub project_id: Option<ProjectId>,
+    pub from: Option<chrono::DateTime<Utc>>,
+    pub to: Option<chrono::DateTime<Utc>>,
+    pub action: Option<String>,
+}
+
+fn audit_entry_hash(
+    prev_hash: &str,
+    occurred_at: chrono::DateTime<Utc>,
+    actor_id: Option<UserId>,
+    action: &str,
+    project_id: Option<ProjectId>,
+    target: Option<&str>,
+    details: &str,
+) -> String {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    for field in [
+        prev_hash.to_string(),
+        occurred_at.timestamp_micros().to_string(),
+        actor_id.map(|id| id.to_string()).unwrap_or_default(),
+        action.to_string(),
+        project_id.map(|id| id.to_string()).unwrap_or_default(),
+        target.unwrap_or_default().to_string(),
+        details.to_string(),
+    ] {
+        // Length-prefix each field so values cannot bleed into each other
+        hasher.update((field.len() as u64).to_be_bytes());
+        hasher.update(field.as_bytes());
+    }
+    format!("{:x}", hasher.finalize())
+}
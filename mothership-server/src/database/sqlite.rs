@@ -0,0 +1,2536 @@
+//! SQLite backend, used when DATABASE_URL starts with `sqlite:`.
+//!
+//! Meant for single-user and self-hosted servers that don't want to run
+//! PostgreSQL. Every method mirrors the one of the same name on [`Database`];
+//! timestamps are always bound from Rust so they compare correctly as text.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use mothership_common::{
+    auth::{AuthSession, Machine},
+    protocol::{ChatMessage, CommentThread, InboxKind, InboxNotification, NotificationPreferences, ReplicatedRift, ReviewRequest, RiftPermission},
+    CheckpointId, CheckpointStatus, Project, ProjectId, ProjectSettings, ProjectVisibility, ReviewState, Rift, RiftId, RiftRole, RiftVisibility, User, UserId, UserRole,
+};
+use sqlx::{
+    migrate::Migrator,
+    pool::PoolOptions,
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePool},
+};
+use std::{collections::HashMap, str::FromStr, sync::Arc};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use super::{
+    audit_entry_hash, pool_options, PoolStats, comment_threads, inbox_notification, review_requests, AccessToken, AuditEntry, AuditFilter, ChatMessageRow, CheckpointStatusRow, CommentRow,
+    GitMirror, ManagedUser, NewAuditEntry, NewCheckpointStatus, NewComment, NewReviewRequest, ReviewCommentRow, ReviewRequestRow, ReviewerRow, RiftPermissionRow, ServiceAccount, TrashedProject, UserNotificationRow,
+    WhitelistEntry, AUDIT_GENESIS_HASH,
+};
+use crate::config::DatabaseSettings;
+use crate::oauth::LoginMachine;
+
+/// SQLite schema, embedded at build time
+static MIGRATOR: Migrator = sqlx::migrate!("../migrations/sqlite");
+
+#[derive(Clone)]
+pub(super) struct SqliteDatabase {
+    pool: SqlitePool,
+    /// Serializes audit log writers, like the advisory lock on PostgreSQL
+    audit_lock: Arc<Mutex<()>>,
+}
+
+#[derive(sqlx::FromRow)]
+struct ProjectRow {
+    id: ProjectId,
+    name: String,
+    description: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+struct RiftRow {
+    id: RiftId,
+    project_id: ProjectId,
+    name: String,
+    parent_rift_id: Option<RiftId>,
+    created_at: DateTime<Utc>,
+    is_active: bool,
+}
+
+#[derive(sqlx::FromRow)]
+struct UserRow {
+    id: UserId,
+    username: String,
+    email: String,
+    role: UserRole,
+    created_at: DateTime<Utc>,
+}
+
+impl From<UserRow> for User {
+    fn from(row: UserRow) -> Self {
+        Self {
+            id: row.id,
+            username: row.username,
+            email: row.email,
+            role: row.role,
+            created_at: row.created_at,
+        }
+    }
+}
+
+/// Token scopes are stored as a JSON array
+#[derive(sqlx::FromRow)]
+struct AccessTokenRow {
+    id: Uuid,
+    user_id: UserId,
+    name: String,
+    scopes: String,
+    created_at: DateTime<Utc>,
+    expires_at: Option<DateTime<Utc>>,
+    last_used_at: Option<DateTime<Utc>>,
+    revoked_at: Option<DateTime<Utc>>,
+}
+
+impl TryFrom<AccessTokenRow> for AccessToken {
+    type Error = anyhow::Error;
+
+    fn try_from(row: AccessTokenRow) -> Result<Self> {
+        Ok(Self {
+            id: row.id,
+            user_id: row.user_id,
+            name: row.name,
+            scopes: serde_json::from_str(&row.scopes)?,
+            created_at: row.created_at,
+            expires_at: row.expires_at,
+            last_used_at: row.last_used_at,
+            revoked_at: row.revoked_at,
+        })
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct ServiceAccountRow {
+    id: UserId,
+    name: String,
+    description: Option<String>,
+    created_by: Option<UserId>,
+    created_at: DateTime<Utc>,
+    disabled_at: Option<DateTime<Utc>>,
+}
+
+const USER_SELECT: &str = "SELECT id, username, email, role, created_at FROM users";
+
+const RIFT_SELECT: &str = "SELECT id, project_id, name, parent_rift_id, created_at, is_active FROM rifts";
+
+const ACCESS_TOKEN_COLUMNS: &str = "id, user_id, name, scopes, created_at, expires_at, last_used_at, revoked_at";
+
+const AUDIT_COLUMNS: &str = "id, occurred_at, actor_id, action, project_id, target, details, prev_hash, hash";
+
+const SERVICE_ACCOUNT_SELECT: &str = r#"
+    SELECT u.id, u.username AS name, sa.description, sa.created_by, sa.created_at, sa.disabled_at
+    FROM service_accounts sa
+    JOIN users u ON u.id = sa.user_id
+"#;
+
+/// Managed user columns; `$1` is the current time for counting live sessions
+const MANAGED_USER_SELECT: &str = r#"
+    SELECT u.id, u.username, u.email, u.role, u.created_at, u.disabled_at,
+           EXISTS(SELECT 1 FROM service_accounts sa WHERE sa.user_id = u.id) AS service_account,
+           (SELECT COUNT(*) FROM auth_sessions s
+            WHERE s.user_id = u.id AND s.revoked_at IS NULL AND s.expires_at > $1) AS active_sessions
+    FROM users u
+"#;
+
+impl SqliteDatabase {
+    pub async fn new(database_url: &str, settings: &DatabaseSettings) -> Result<Self> {
+        tracing::info!("🔗 Opening SQLite database...");
+
+        let options = SqliteConnectOptions::from_str(database_url)?
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal)
+            .foreign_keys(true);
+        let pool = pool_options(settings).connect_with(options).await?;
+
+        tracing::info!("✅ Successfully opened SQLite database");
+
+        Ok(Self {
+            pool,
+            audit_lock: Arc::new(Mutex::new(())),
+        })
+    }
+
+    /// A private database in memory. It lives on one connection that is
+    /// never closed, since each connection to `:memory:` gets its own.
+    pub async fn new_in_memory() -> Result<Self> {
+        let options = SqliteConnectOptions::from_str("sqlite::memory:")?.foreign_keys(true);
+        let pool = PoolOptions::new()
+            .max_connections(1)
+            .min_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect_with(options)
+            .await?;
+
+        Ok(Self {
+            pool,
+            audit_lock: Arc::new(Mutex::new(())),
+        })
+    }
+
+    pub async fn ping(&self) -> Result<()> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    #[cfg(test)]
+    pub async fn execute_raw(&self, sql: &str) -> Result<()> {
+        sqlx::query(sql).execute(&self.pool).await?;
+        Ok(())
+    }
+
+    pub fn pool_stats(&self) -> PoolStats {
+        PoolStats::of(&self.pool)
+    }
+
+    pub async fn migrate(&self) -> Result<()> {
+        MIGRATOR.run(&self.pool).await?;
+        Ok(())
+    }
+
+    async fn project_members(&self, project_id: ProjectId) -> Result<Vec<UserId>> {
+        let members = sqlx::query_scalar("SELECT user_id FROM project_members WHERE project_id = $1")
+            .bind(project_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(members)
+    }
+
+    async fn project_from_row(&self, row: ProjectRow, settings: bool) -> Result<Project> {
+        Ok(Project {
+            id: row.id,
+            members: self.project_members(row.id).await?,
+            name: row.name,
+            description: row.description.unwrap_or_default(),
+            created_at: row.created_at,
+            settings: if settings {
+                self.get_project_settings(row.id).await?
+            } else {
+                ProjectSettings::default()
+            },
+        })
+    }
+
+    async fn rift_from_row(&self, row: RiftRow) -> Result<Rift> {
+        let collaborators = sqlx::query_scalar("SELECT user_id FROM rift_collaborators WHERE rift_id = $1")
+            .bind(row.id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(Rift {
+            id: row.id,
+            project_id: row.project_id,
+            name: row.name,
+            parent_rift: row.parent_rift_id,
+            collaborators,
+            created_at: row.created_at,
+            last_checkpoint: None,
+            is_active: row.is_active,
+        })
+    }
+
+    pub async fn get_user_projects(&self, user_id: UserId) -> Result<Vec<Project>> {
+        let rows = sqlx::query_as::<_, ProjectRow>(
+            r#"
+            SELECT p.id, p.name, p.description, p.created_at
+            FROM projects p
+            INNER JOIN project_members pm ON p.id = pm.project_id
+            WHERE pm.user_id = $1 AND p.deleted_at IS NULL
+            ORDER BY p.created_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut projects = Vec::new();
+        for row in rows {
+            projects.push(self.project_from_row(row, false).await?);
+        }
+        Ok(projects)
+    }
+
+    pub async fn get_project_rifts(&self, project_id: ProjectId) -> Result<Vec<Rift>> {
+        let rows = sqlx::query_as::<_, RiftRow>(&format!("{} WHERE project_id = $1 ORDER BY created_at ASC", RIFT_SELECT))
+            .bind(project_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut rifts = Vec::new();
+        for row in rows {
+            rifts.push(self.rift_from_row(row).await?);
+        }
+        Ok(rifts)
+    }
+
+    pub async fn get_rift_collaborator_names(&self, project_id: ProjectId) -> Result<HashMap<RiftId, Vec<(UserId, String)>>> {
+        let rows: Vec<(RiftId, UserId, String)> = sqlx::query_as(
+            r#"
+            SELECT rc.rift_id, rc.user_id, u.username
+            FROM rift_collaborators rc
+            INNER JOIN rifts r ON r.id = rc.rift_id
+            INNER JOIN users u ON u.id = rc.user_id
+            WHERE r.project_id = $1
+            ORDER BY rc.joined_at ASC
+            "#,
+        )
+        .bind(project_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut collaborators: HashMap<RiftId, Vec<(UserId, String)>> = HashMap::new();
+        for (rift_id, user_id, username) in rows {
+            collaborators.entry(rift_id).or_default().push((user_id, username));
+        }
+        Ok(collaborators)
+    }
+
+    pub async fn get_project(&self, project_id: ProjectId) -> Result<Option<Project>> {
+        let row = sqlx::query_as::<_, ProjectRow>("SELECT id, name, description, created_at FROM projects WHERE id = $1 AND deleted_at IS NULL")
+            .bind(project_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match row {
+            Some(row) => Ok(Some(self.project_from_row(row, true).await?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn get_user_projects_page(&self, user_id: UserId, before: Option<ProjectId>, limit: usize) -> Result<Vec<Project>> {
+        let rows = sqlx::query_as::<_, ProjectRow>(
+            r#"
+            SELECT p.id, p.name, p.description, p.created_at
+            FROM projects p
+            INNER JOIN project_members pm ON p.id = pm.project_id
+            WHERE pm.user_id = $1 AND p.deleted_at IS NULL
+              AND ($2 IS NULL OR (p.created_at, p.id) < (SELECT created_at, id FROM projects WHERE id = $2))
+            ORDER BY p.created_at DESC, p.id DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(user_id)
+        .bind(before)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut projects = Vec::new();
+        for row in rows {
+            projects.push(self.project_from_row(row, false).await?);
+        }
+        Ok(projects)
+    }
+
+    pub async fn list_projects_with_visibility(&self, visibility: ProjectVisibility) -> Result<Vec<Project>> {
+        let rows = sqlx::query_as::<_, ProjectRow>(
+            r#"
+            SELECT p.id, p.name, p.description, p.created_at
+            FROM projects p
+            INNER JOIN project_settings ps ON p.id = ps.project_id
+            WHERE json_extract(ps.settings, '$.visibility') = $1 AND p.deleted_at IS NULL
+            ORDER BY p.created_at DESC, p.id DESC
+            "#,
+        )
+        .bind(visibility.as_str())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut projects = Vec::new();
+        for row in rows {
+            projects.push(self.project_from_row(row, false).await?);
+        }
+        Ok(projects)
+    }
+
+    pub async fn list_projects(&self, before: Option<ProjectId>, limit: usize) -> Result<Vec<Project>> {
+        let rows = sqlx::query_as::<_, ProjectRow>(
+            r#"
+            SELECT id, name, description, created_at
+            FROM projects
+            WHERE deleted_at IS NULL
+              AND ($1 IS NULL OR (created_at, id) < (SELECT created_at, id FROM projects WHERE id = $1))
+            ORDER BY created_at DESC, id DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(before)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut projects = Vec::new();
+        for row in rows {
+            projects.push(self.project_from_row(row, false).await?);
+        }
+        Ok(projects)
+    }
+
+    pub async fn get_user(&self, user_id: UserId) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, UserRow>(&format!("{} WHERE id = $1", USER_SELECT))
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(user.map(User::from))
+    }
+
+    pub async fn get_user_by_username(&self, username: &str) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, UserRow>(&format!("{} WHERE username = $1", USER_SELECT))
+            .bind(username)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(user.map(User::from))
+    }
+
+    pub async fn get_user_by_email(&self, email: &str) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, UserRow>(&format!("{} WHERE email = $1", USER_SELECT))
+            .bind(email)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(user.map(User::from))
+    }
+
+    pub async fn create_rift(&self, project_id: ProjectId, user_id: UserId, rift_name: Option<String>) -> Result<Rift> {
+        let rift_id = Uuid::new_v4();
+        let name = rift_name.unwrap_or_else(|| "main".to_string());
+        let now = Utc::now();
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO rifts (id, project_id, name, is_active, owner_id, created_at, updated_at)
+            VALUES ($1, $2, $3, TRUE, $4, $5, $5)
+            "#,
+        )
+        .bind(rift_id)
+        .bind(project_id)
+        .bind(&name)
+        .bind(user_id)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO rift_collaborators (rift_id, user_id, joined_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (rift_id, user_id) DO NOTHING
+            "#,
+        )
+        .bind(rift_id)
+        .bind(user_id)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(Rift {
+            id: rift_id,
+            project_id,
+            name,
+            parent_rift: None,
+            collaborators: vec![user_id],
+            created_at: now,
+            last_checkpoint: None,
+            is_active: true,
+        })
+    }
+
+    pub async fn get_rift(&self, rift_id: RiftId) -> Result<Option<Rift>> {
+        let row = sqlx::query_as::<_, RiftRow>(&format!("{} WHERE id = $1", RIFT_SELECT))
+            .bind(rift_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match row {
+            Some(row) => Ok(Some(self.rift_from_row(row).await?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn get_user_rift(&self, project_id: ProjectId, user_id: UserId) -> Result<Option<Rift>> {
+        let row = sqlx::query_as::<_, RiftRow>(
+            r#"
+            SELECT r.id, r.project_id, r.name, r.parent_rift_id, r.created_at, r.is_active
+            FROM rifts r
+            INNER JOIN rift_collaborators rc ON r.id = rc.rift_id
+            WHERE r.project_id = $1 AND rc.user_id = $2 AND r.is_active = TRUE
+            ORDER BY r.created_at ASC
+            LIMIT 1
+            "#,
+        )
+        .bind(project_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|row| Rift {
+            id: row.id,
+            project_id: row.project_id,
+            name: row.name,
+            parent_rift: row.parent_rift_id,
+            collaborators: vec![user_id],
+            created_at: row.created_at,
+            last_checkpoint: None,
+            is_active: row.is_active,
+        }))
+    }
+
+    pub async fn get_rift_by_name(&self, project_id: ProjectId, name: &str) -> Result<Option<Rift>> {
+        let row = sqlx::query_as::<_, RiftRow>(&format!(
+            "{} WHERE project_id = $1 AND name = $2 AND is_active = TRUE ORDER BY created_at ASC LIMIT 1",
+            RIFT_SELECT
+        ))
+        .bind(project_id)
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => Ok(Some(self.rift_from_row(row).await?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn set_rift_parent(&self, rift_id: RiftId, parent: Option<RiftId>) -> Result<()> {
+        sqlx::query("UPDATE rifts SET parent_rift_id = $2 WHERE id = $1")
+            .bind(rift_id)
+            .bind(parent)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn set_rift_fork_point(&self, rift_id: RiftId, checkpoint_id: CheckpointId) -> Result<()> {
+        sqlx::query("UPDATE rifts SET fork_checkpoint_id = $2 WHERE id = $1")
+            .bind(rift_id)
+            .bind(checkpoint_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_rift_fork_point(&self, rift_id: RiftId) -> Result<Option<CheckpointId>> {
+        let fork_point: Option<Option<CheckpointId>> = sqlx::query_scalar("SELECT fork_checkpoint_id FROM rifts WHERE id = $1")
+            .bind(rift_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(fork_point.flatten())
+    }
+
+    pub async fn set_current_rift(&self, user_id: UserId, project_id: ProjectId, rift_id: RiftId) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO current_rifts (user_id, project_id, rift_id, switched_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_id, project_id) DO UPDATE SET rift_id = excluded.rift_id, switched_at = excluded.switched_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(project_id)
+        .bind(rift_id)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_current_rift(&self, user_id: UserId, project_id: ProjectId) -> Result<Option<RiftId>> {
+        let rift_id = sqlx::query_scalar("SELECT rift_id FROM current_rifts WHERE user_id = $1 AND project_id = $2")
+            .bind(user_id)
+            .bind(project_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(rift_id)
+    }
+
+    pub async fn rename_rift(&self, rift_id: RiftId, name: &str) -> Result<()> {
+        sqlx::query("UPDATE rifts SET name = $2, updated_at = $3 WHERE id = $1")
+            .bind(rift_id)
+            .bind(name)
+            .bind(Utc::now())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_rift(&self, rift_id: RiftId) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            UPDATE rifts
+            SET parent_rift_id = (SELECT parent_rift_id FROM rifts WHERE id = $1)
+            WHERE parent_rift_id = $1
+            "#,
+        )
+        .bind(rift_id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM rifts WHERE id = $1")
+            .bind(rift_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn add_rift_collaborator(&self, rift_id: RiftId, user_id: UserId) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO rift_collaborators (rift_id, user_id, joined_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (rift_id, user_id) DO NOTHING
+            "#,
+        )
+        .bind(rift_id)
+        .bind(user_id)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_rift_access(&self, rift_id: RiftId) -> Result<Option<(Option<UserId>, RiftVisibility)>> {
+        let row: Option<(Option<UserId>, String)> = sqlx::query_as(
+            "SELECT owner_id, visibility FROM rifts WHERE id = $1",
+        )
+        .bind(rift_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|(owner, visibility)| {
+            let visibility = RiftVisibility::parse(&visibility)
+                .ok_or_else(|| anyhow::anyhow!("Unknown rift visibility '{}'", visibility))?;
+            Ok((owner, visibility))
+        })
+        .transpose()
+    }
+
+    pub async fn set_rift_visibility(&self, rift_id: RiftId, visibility: RiftVisibility) -> Result<()> {
+        sqlx::query("UPDATE rifts SET visibility = $2 WHERE id = $1")
+            .bind(rift_id)
+            .bind(visibility.as_str())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_rift_permission(&self, rift_id: RiftId, user_id: UserId) -> Result<Option<RiftRole>> {
+        let role: Option<String> = sqlx::query_scalar(
+            "SELECT role FROM rift_permissions WHERE rift_id = $1 AND user_id = $2",
+        )
+        .bind(rift_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(role.as_deref().and_then(RiftRole::parse))
+    }
+
+    pub async fn list_rift_permissions(&self, rift_id: RiftId) -> Result<Vec<RiftPermission>> {
+        let rows = sqlx::query_as::<_, RiftPermissionRow>(
+            r#"
+            SELECT p.user_id, u.username, p.role, p.granted_at
+            FROM rift_permissions p
+            JOIN users u ON u.id = p.user_id
+            WHERE p.rift_id = $1
+            ORDER BY p.granted_at ASC
+            "#,
+        )
+        .bind(rift_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(RiftPermission::try_from).collect()
+    }
+
+    pub async fn grant_rift_permission(
+        &self,
+        rift_id: RiftId,
+        user_id: UserId,
+        role: RiftRole,
+        granted_by: UserId,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO rift_permissions (rift_id, user_id, role, granted_by, granted_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (rift_id, user_id)
+            DO UPDATE SET role = excluded.role, granted_by = excluded.granted_by, granted_at = excluded.granted_at
+            "#,
+        )
+        .bind(rift_id)
+        .bind(user_id)
+        .bind(role.as_str())
+        .bind(granted_by)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn revoke_rift_permission(&self, rift_id: RiftId, user_id: UserId) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM rift_permissions WHERE rift_id = $1 AND user_id = $2")
+            .bind(rift_id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        sqlx::query("DELETE FROM rift_collaborators WHERE rift_id = $1 AND user_id = $2")
+            .bind(rift_id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn user_has_project_access(&self, user_id: UserId, project_id: ProjectId) -> Result<bool> {
+        let count: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*)
+            FROM project_members pm
+            INNER JOIN projects p ON p.id = pm.project_id
+            WHERE pm.user_id = $1 AND pm.project_id = $2 AND p.deleted_at IS NULL
+            "#,
+        )
+        .bind(user_id)
+        .bind(project_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count > 0)
+    }
+
+    pub async fn create_user_with_id(&self, user_id: UserId, username: String, email: String, role: UserRole) -> Result<User> {
+        if let Some(existing_user) = self.get_user_by_email(&email).await? {
+            return Ok(existing_user);
+        }
+        if let Some(existing_user) = self.get_user_by_username(&username).await? {
+            return Ok(existing_user);
+        }
+
+        let now = Utc::now();
+        sqlx::query("INSERT INTO users (id, username, email, role, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $5)")
+            .bind(user_id)
+            .bind(&username)
+            .bind(&email)
+            .bind(role.clone())
+            .bind(now)
+            .execute(&self.pool)
+            .await?;
+
+        tracing::info!("✅ Successfully created user: {} ({})", username, user_id);
+
+        Ok(User {
+            id: user_id,
+            username,
+            email,
+            role,
+            created_at: now,
+        })
+    }
+
+    pub async fn user_exists_by_email(&self, email: &str) -> Result<bool> {
+        Ok(self.get_user_by_email(email).await?.is_some())
+    }
+
+    pub async fn user_exists_by_username(&self, username: &str) -> Result<bool> {
+        Ok(self.get_user_by_username(username).await?.is_some())
+    }
+
+    pub async fn user_is_admin(&self, user_id: UserId) -> Result<bool> {
+        let user = self.get_user(user_id).await?;
+        Ok(user.is_some_and(|u| matches!(u.role, UserRole::Admin | UserRole::SuperAdmin)))
+    }
+
+    pub async fn user_is_super_admin(&self, user_id: UserId) -> Result<bool> {
+        let user = self.get_user(user_id).await?;
+        Ok(user.is_some_and(|u| u.role == UserRole::SuperAdmin))
+    }
+
+    pub async fn project_exists_by_name(&self, name: &str) -> Result<bool> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM projects WHERE name = $1")
+            .bind(name)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(count > 0)
+    }
+
+    pub async fn create_project(&self, name: String, description: String, members: Vec<UserId>) -> Result<Project> {
+        let project_id = Uuid::new_v4();
+        let now = Utc::now();
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("INSERT INTO projects (id, name, description, created_at, updated_at) VALUES ($1, $2, $3, $4, $4)")
+            .bind(project_id)
+            .bind(&name)
+            .bind(&description)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+
+        for member_id in &members {
+            sqlx::query("INSERT INTO project_members (project_id, user_id, joined_at) VALUES ($1, $2, $3)")
+                .bind(project_id)
+                .bind(member_id)
+                .bind(now)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(Project {
+            id: project_id,
+            name,
+            description,
+            members,
+            created_at: now,
+            settings: ProjectSettings::default(),
+        })
+    }
+
+    pub async fn get_project_by_name(&self, name: &str) -> Result<Option<Project>> {
+        let row = sqlx::query_as::<_, ProjectRow>("SELECT id, name, description, created_at FROM projects WHERE name = $1 AND deleted_at IS NULL")
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match row {
+            Some(row) => Ok(Some(self.project_from_row(row, false).await?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn set_project_archived(&self, project_id: ProjectId, archived: bool) -> Result<Option<DateTime<Utc>>> {
+        let archived_at = sqlx::query_scalar::<_, Option<DateTime<Utc>>>(
+            r#"
+            UPDATE projects
+            SET archived_at = CASE WHEN $2 THEN COALESCE(archived_at, $3) ELSE NULL END,
+                updated_at = $3
+            WHERE id = $1
+            RETURNING archived_at
+            "#,
+        )
+        .bind(project_id)
+        .bind(archived)
+        .bind(Utc::now())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(archived_at)
+    }
+
+    pub async fn get_project_settings(&self, project_id: ProjectId) -> Result<ProjectSettings> {
+        let settings = sqlx::query_scalar::<_, String>("SELECT settings FROM project_settings WHERE project_id = $1")
+            .bind(project_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match settings {
+            Some(settings) => Ok(serde_json::from_str(&settings)?),
+            None => Ok(ProjectSettings::default()),
+        }
+    }
+
+    pub async fn set_project_settings(&self, project_id: ProjectId, settings: &ProjectSettings) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO project_settings (project_id, settings)
+            VALUES ($1, $2)
+            ON CONFLICT (project_id) DO UPDATE SET settings = excluded.settings
+            "#,
+        )
+        .bind(project_id)
+        .bind(serde_json::to_string(settings)?)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn list_archived_projects(&self) -> Result<Vec<(ProjectId, DateTime<Utc>)>> {
+        let projects = sqlx::query_as::<_, (ProjectId, DateTime<Utc>)>(
+            "SELECT id, archived_at FROM projects WHERE archived_at IS NOT NULL",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(projects)
+    }
+
+    pub async fn trash_project(&self, project_id: ProjectId, deleted_by: UserId) -> Result<Option<DateTime<Utc>>> {
+        let deleted_at = sqlx::query_scalar::<_, DateTime<Utc>>(
+            r#"
+            UPDATE projects
+            SET deleted_at = $3, deleted_by = $2, updated_at = $3
+            WHERE id = $1 AND deleted_at IS NULL
+            RETURNING deleted_at
+            "#,
+        )
+        .bind(project_id)
+        .bind(deleted_by)
+        .bind(Utc::now())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(deleted_at)
+    }
+
+    pub async fn list_trashed_projects(&self) -> Result<Vec<TrashedProject>> {
+        let projects = sqlx::query_as::<_, TrashedProject>(
+            r#"
+            SELECT p.id, p.name, p.deleted_at, p.deleted_by, u.username AS deleted_by_username
+            FROM projects p
+            LEFT JOIN users u ON u.id = p.deleted_by
+            WHERE p.deleted_at IS NOT NULL
+            ORDER BY p.deleted_at DESC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(projects)
+    }
+
+    pub async fn untrash_project(&self, project_id: ProjectId) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE projects SET deleted_at = NULL, deleted_by = NULL, updated_at = $2 WHERE id = $1 AND deleted_at IS NOT NULL",
+        )
+        .bind(project_id)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn expired_trashed_projects(&self, cutoff: DateTime<Utc>) -> Result<Vec<ProjectId>> {
+        let projects = sqlx::query_scalar::<_, ProjectId>(
+            "SELECT id FROM projects WHERE deleted_at IS NOT NULL AND deleted_at < $1",
+        )
+        .bind(cutoff)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(projects)
+    }
+
+    pub async fn delete_project(&self, project_id: ProjectId) -> Result<()> {
+        let result = sqlx::query("DELETE FROM projects WHERE id = $1")
+            .bind(project_id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow::anyhow!("Project not found or already deleted"));
+        }
+
+        tracing::info!("Successfully deleted project {} and all associated data", project_id);
+        Ok(())
+    }
+
+    pub async fn create_git_mirror(
+        &self,
+        project_id: ProjectId,
+        rift_id: RiftId,
+        remote_url: &str,
+        created_by: UserId,
+    ) -> Result<GitMirror> {
+        let mirror = sqlx::query_as::<_, GitMirror>(
+            r#"
+            INSERT INTO git_mirrors (id, project_id, rift_id, remote_url, created_by, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, project_id, rift_id, remote_url, created_by, created_at, last_pushed_at, last_pushed_checkpoint_id, last_error
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(project_id)
+        .bind(rift_id)
+        .bind(remote_url)
+        .bind(created_by)
+        .bind(Utc::now())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(mirror)
+    }
+
+    pub async fn get_project_git_mirrors(&self, project_id: ProjectId) -> Result<Vec<GitMirror>> {
+        let mirrors = sqlx::query_as::<_, GitMirror>(
+            r#"
+            SELECT id, project_id, rift_id, remote_url, created_by, created_at, last_pushed_at, last_pushed_checkpoint_id, last_error
+            FROM git_mirrors
+            WHERE project_id = $1
+            ORDER BY created_at ASC
+            "#,
+        )
+        .bind(project_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(mirrors)
+    }
+
+    pub async fn get_rift_git_mirrors(&self, rift_id: RiftId) -> Result<Vec<GitMirror>> {
+        let mirrors = sqlx::query_as::<_, GitMirror>(
+            r#"
+            SELECT id, project_id, rift_id, remote_url, created_by, created_at, last_pushed_at, last_pushed_checkpoint_id, last_error
+            FROM git_mirrors
+            WHERE rift_id = $1
+            "#,
+        )
+        .bind(rift_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(mirrors)
+    }
+
+    pub async fn delete_git_mirror(&self, project_id: ProjectId, mirror_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM git_mirrors WHERE id = $1 AND project_id = $2")
+            .bind(mirror_id)
+            .bind(project_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn record_git_mirror_push(&self, mirror_id: Uuid, checkpoint_id: CheckpointId, error: Option<&str>) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE git_mirrors
+            SET last_pushed_at = CASE WHEN $2 IS NULL THEN $3 ELSE last_pushed_at END,
+                last_pushed_checkpoint_id = CASE WHEN $2 IS NULL THEN $4 ELSE last_pushed_checkpoint_id END,
+                last_error = $2
+            WHERE id = $1
+            "#,
+        )
+        .bind(mirror_id)
+        .bind(error)
+        .bind(Utc::now())
+        .bind(checkpoint_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn insert_audit_entry(&self, entry: NewAuditEntry<'_>) -> Result<AuditEntry> {
+        let _chain = self.audit_lock.lock().await;
+        let mut tx = self.pool.begin().await?;
+
+        let prev_hash: Option<String> = sqlx::query_scalar("SELECT hash FROM audit_log ORDER BY id DESC LIMIT 1")
+            .fetch_optional(&mut *tx)
+            .await?;
+        let prev_hash = prev_hash.unwrap_or_else(|| AUDIT_GENESIS_HASH.to_string());
+
+        let hash = audit_entry_hash(
+            &prev_hash,
+            entry.occurred_at,
+            entry.actor_id,
+            entry.action,
+            entry.project_id,
+            entry.target,
+            entry.details,
+        );
+
+        let row = sqlx::query_as::<_, AuditEntry>(&format!(
+            r#"
+            INSERT INTO audit_log (occurred_at, actor_id, action, project_id, target, details, prev_hash, hash)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING {}
+            "#,
+            AUDIT_COLUMNS
+        ))
+        .bind(entry.occurred_at)
+        .bind(entry.actor_id)
+        .bind(entry.action)
+        .bind(entry.project_id)
+        .bind(entry.target)
+        .bind(entry.details)
+        .bind(&prev_hash)
+        .bind(&hash)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(row)
+    }
+
+    pub async fn list_audit_entries(&self, filter: &AuditFilter, limit: i64, offset: i64) -> Result<(Vec<AuditEntry>, i64)> {
+        const AUDIT_FILTER: &str = r#"
+            WHERE ($1 IS NULL OR actor_id = $1)
+              AND ($2 IS NULL OR project_id = $2)
+              AND ($3 IS NULL OR occurred_at >= $3)
+              AND ($4 IS NULL OR occurred_at < $4)
+              AND ($5 IS NULL OR action = $5)
+        "#;
+
+        let entries = sqlx::query_as::<_, AuditEntry>(&format!(
+            "SELECT {} FROM audit_log {} ORDER BY id DESC LIMIT $6 OFFSET $7",
+            AUDIT_COLUMNS, AUDIT_FILTER
+        ))
+        .bind(filter.user_id)
+        .bind(filter.project_id)
+        .bind(filter.from)
+        .bind(filter.to)
+        .bind(filter.action.as_deref())
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM audit_log {}", AUDIT_FILTER))
+            .bind(filter.user_id)
+            .bind(filter.project_id)
+            .bind(filter.from)
+            .bind(filter.to)
+            .bind(filter.action.as_deref())
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok((entries, total))
+    }
+
+    pub async fn verify_audit_chain(&self) -> Result<(i64, Option<i64>)> {
+        let entries = sqlx::query_as::<_, AuditEntry>(&format!("SELECT {} FROM audit_log ORDER BY id ASC", AUDIT_COLUMNS))
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut expected_prev = AUDIT_GENESIS_HASH.to_string();
+        for entry in &entries {
+            let hash = audit_entry_hash(
+                &entry.prev_hash,
+                entry.occurred_at,
+                entry.actor_id,
+                &entry.action,
+                entry.project_id,
+                entry.target.as_deref(),
+                &entry.details,
+            );
+            if entry.prev_hash != expected_prev || entry.hash != hash {
+                return Ok((entries.len() as i64, Some(entry.id)));
+            }
+            expected_prev = entry.hash.clone();
+        }
+
+        Ok((entries.len() as i64, None))
+    }
+
+    pub async fn create_access_token(
+        &self,
+        user_id: UserId,
+        name: &str,
+        token_hash: &str,
+        scopes: &[String],
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<AccessToken> {
+        let row = sqlx::query_as::<_, AccessTokenRow>(&format!(
+            r#"
+            INSERT INTO personal_access_tokens (id, user_id, name, token_hash, scopes, expires_at, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING {}
+            "#,
+            ACCESS_TOKEN_COLUMNS
+        ))
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(name)
+        .bind(token_hash)
+        .bind(serde_json::to_string(scopes)?)
+        .bind(expires_at)
+        .bind(Utc::now())
+        .fetch_one(&self.pool)
+        .await?;
+
+        row.try_into()
+    }
+
+    pub async fn list_access_tokens(&self, user_id: UserId) -> Result<Vec<AccessToken>> {
+        let rows = sqlx::query_as::<_, AccessTokenRow>(&format!(
+            "SELECT {} FROM personal_access_tokens WHERE user_id = $1 ORDER BY created_at DESC",
+            ACCESS_TOKEN_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(AccessToken::try_from).collect()
+    }
+
+    pub async fn revoke_access_token(&self, user_id: UserId, token_id: Uuid) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE personal_access_tokens SET revoked_at = $3 WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL",
+        )
+        .bind(token_id)
+        .bind(user_id)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn use_access_token(&self, token_hash: &str) -> Result<Option<AccessToken>> {
+        let row = sqlx::query_as::<_, AccessTokenRow>(&format!(
+            r#"
+            UPDATE personal_access_tokens
+            SET last_used_at = $2
+            WHERE token_hash = $1
+              AND revoked_at IS NULL
+              AND (expires_at IS NULL OR expires_at > $2)
+              AND user_id NOT IN (SELECT id FROM users WHERE disabled_at IS NOT NULL)
+            RETURNING {}
+            "#,
+            ACCESS_TOKEN_COLUMNS
+        ))
+        .bind(token_hash)
+        .bind(Utc::now())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(AccessToken::try_from).transpose()
+    }
+
+    pub async fn create_auth_session(
+        &self,
+        session_id: Uuid,
+        user_id: UserId,
+        machine: &LoginMachine,
+        expires_at: DateTime<Utc>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO auth_sessions (id, user_id, machine_id, machine_name, platform, hostname, expires_at, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(session_id)
+        .bind(user_id)
+        .bind(&machine.machine_id)
+        .bind(&machine.machine_name)
+        .bind(&machine.platform)
+        .bind(&machine.hostname)
+        .bind(expires_at)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn list_auth_sessions(&self, user_id: UserId, current_session: Option<Uuid>) -> Result<Vec<AuthSession>> {
+        let sessions = sqlx::query_as::<_, AuthSession>(
+            r#"
+            SELECT s.id, s.user_id, u.username, s.machine_id, s.machine_name, s.platform, s.hostname,
+                   s.created_at, s.expires_at, s.last_seen_at,
+                   COALESCE(s.id = $2, FALSE) AS current
+            FROM auth_sessions s
+            JOIN users u ON u.id = s.user_id
+            WHERE s.user_id = $1 AND s.revoked_at IS NULL AND s.expires_at > $3
+            ORDER BY COALESCE(s.last_seen_at, s.created_at) DESC
+            "#,
+        )
+        .bind(user_id)
+        .bind(current_session)
+        .bind(Utc::now())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(sessions)
+    }
+
+    pub async fn revoke_auth_session(&self, session_id: Uuid, owner: Option<UserId>) -> Result<Option<UserId>> {
+        let user_id = sqlx::query_scalar::<_, UserId>(
+            r#"
+            UPDATE auth_sessions
+            SET revoked_at = $3
+            WHERE id = $1
+              AND ($2 IS NULL OR user_id = $2)
+              AND revoked_at IS NULL
+            RETURNING user_id
+            "#,
+        )
+        .bind(session_id)
+        .bind(owner)
+        .bind(Utc::now())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(user_id)
+    }
+
+    pub async fn list_revoked_session_ids(&self) -> Result<Vec<Uuid>> {
+        let ids = sqlx::query_scalar::<_, Uuid>(
+            "SELECT id FROM auth_sessions WHERE revoked_at IS NOT NULL AND expires_at > $1",
+        )
+        .bind(Utc::now())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(ids)
+    }
+
+    pub async fn touch_auth_session(&self, session_id: Uuid) -> Result<()> {
+        sqlx::query("UPDATE auth_sessions SET last_seen_at = $2 WHERE id = $1")
+            .bind(session_id)
+            .bind(Utc::now())
+            .execute(&self.pool)
+            .await?;
+        sqlx::query(
+            r#"
+            UPDATE machines
+            SET last_seen_at = $2
+            WHERE (user_id, machine_id) IN (SELECT user_id, machine_id FROM auth_sessions WHERE id = $1)
+            "#,
+        )
+        .bind(session_id)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn record_machine(&self, user_id: UserId, machine: &LoginMachine) -> Result<()> {
+        let now = Utc::now();
+        sqlx::query(
+            r#"
+            INSERT INTO machines (id, user_id, machine_id, name, platform, hostname, first_seen_at, last_seen_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $7)
+            ON CONFLICT (user_id, machine_id) DO UPDATE
+            SET name = excluded.name, platform = excluded.platform, hostname = excluded.hostname,
+                last_seen_at = excluded.last_seen_at, revoked_at = NULL
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(&machine.machine_id)
+        .bind(&machine.machine_name)
+        .bind(&machine.platform)
+        .bind(&machine.hostname)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn list_machines(&self, user_id: UserId, current_session: Option<Uuid>) -> Result<Vec<Machine>> {
+        let machines = sqlx::query_as::<_, Machine>(
+            r#"
+            SELECT m.id, m.machine_id, m.name, m.platform, m.hostname, m.first_seen_at, m.last_seen_at, m.revoked_at,
+                   (SELECT COUNT(*) FROM auth_sessions s
+                    WHERE s.user_id = m.user_id AND s.machine_id = m.machine_id
+                      AND s.revoked_at IS NULL AND s.expires_at > $3) AS active_sessions,
+                   EXISTS (SELECT 1 FROM auth_sessions s
+                           WHERE s.id = $2 AND s.user_id = m.user_id AND s.machine_id = m.machine_id) AS current
+            FROM machines m
+            WHERE m.user_id = $1
+            ORDER BY m.last_seen_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .bind(current_session)
+        .bind(Utc::now())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(machines)
+    }
+
+    pub async fn revoke_machine(&self, user_id: UserId, id: Uuid) -> Result<Option<(String, Vec<Uuid>)>> {
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+
+        let machine = sqlx::query_as::<_, (String, String)>(
+            r#"
+            UPDATE machines
+            SET revoked_at = COALESCE(revoked_at, $3)
+            WHERE id = $1 AND user_id = $2
+            RETURNING machine_id, name
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(now)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some((machine_id, name)) = machine else {
+            return Ok(None);
+        };
+
+        let sessions = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            UPDATE auth_sessions
+            SET revoked_at = $3
+            WHERE user_id = $1 AND machine_id = $2 AND revoked_at IS NULL AND expires_at > $3
+            RETURNING id
+            "#,
+        )
+        .bind(user_id)
+        .bind(&machine_id)
+        .bind(now)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some((name, sessions)))
+    }
+
+    pub async fn create_service_account(
+        &self,
+        name: &str,
+        email: &str,
+        description: Option<&str>,
+        created_by: UserId,
+    ) -> Result<ServiceAccount> {
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("INSERT INTO users (id, username, email, created_at, updated_at) VALUES ($1, $2, $3, $4, $4)")
+            .bind(id)
+            .bind(name)
+            .bind(email)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("INSERT INTO service_accounts (user_id, description, created_by, created_at) VALUES ($1, $2, $3, $4)")
+            .bind(id)
+            .bind(description)
+            .bind(created_by)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        self.get_service_account(id).await?
+            .ok_or_else(|| anyhow::anyhow!("Service account {} vanished after creation", id))
+    }
+
+    async fn service_account_from_row(&self, row: ServiceAccountRow) -> Result<ServiceAccount> {
+        Ok(ServiceAccount {
+            projects: sqlx::query_scalar("SELECT project_id FROM project_members WHERE user_id = $1")
+                .bind(row.id)
+                .fetch_all(&self.pool)
+                .await?,
+            id: row.id,
+            name: row.name,
+            description: row.description,
+            created_by: row.created_by,
+            created_at: row.created_at,
+            disabled_at: row.disabled_at,
+        })
+    }
+
+    pub async fn list_service_accounts(&self) -> Result<Vec<ServiceAccount>> {
+        let rows = sqlx::query_as::<_, ServiceAccountRow>(&format!("{} ORDER BY u.username", SERVICE_ACCOUNT_SELECT))
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut accounts = Vec::new();
+        for row in rows {
+            accounts.push(self.service_account_from_row(row).await?);
+        }
+        Ok(accounts)
+    }
+
+    pub async fn get_service_account(&self, id: UserId) -> Result<Option<ServiceAccount>> {
+        let row = sqlx::query_as::<_, ServiceAccountRow>(&format!("{} WHERE sa.user_id = $1", SERVICE_ACCOUNT_SELECT))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match row {
+            Some(row) => Ok(Some(self.service_account_from_row(row).await?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn is_service_account(&self, user_id: UserId) -> Result<bool> {
+        let exists = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (SELECT 1 FROM service_accounts WHERE user_id = $1)",
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(exists)
+    }
+
+    pub async fn disable_service_account(&self, id: UserId) -> Result<bool> {
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query(
+            "UPDATE service_accounts SET disabled_at = $2 WHERE user_id = $1 AND disabled_at IS NULL",
+        )
+        .bind(id)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        sqlx::query("UPDATE personal_access_tokens SET revoked_at = $2 WHERE user_id = $1 AND revoked_at IS NULL")
+            .bind(id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("DELETE FROM project_members WHERE user_id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    pub async fn add_project_member(&self, project_id: ProjectId, user_id: UserId, role: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO project_members (project_id, user_id, role, joined_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (project_id, user_id) DO UPDATE SET role = excluded.role
+            "#,
+        )
+        .bind(project_id)
+        .bind(user_id)
+        .bind(role)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_project_owner(&self, project_id: ProjectId) -> Result<Option<UserId>> {
+        let owner = sqlx::query_scalar(
+            "SELECT user_id FROM project_members WHERE project_id = $1 ORDER BY joined_at ASC LIMIT 1",
+        )
+        .bind(project_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(owner)
+    }
+
+    pub async fn remove_project_member(&self, project_id: ProjectId, user_id: UserId) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM project_members WHERE project_id = $1 AND user_id = $2")
+            .bind(project_id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn get_notification_preferences(&self, user_id: UserId) -> Result<NotificationPreferences> {
+        let preferences = sqlx::query_as::<_, NotificationPreferences>(
+            r#"
+            SELECT invitations, mentions, conflict_rifts, checkpoint_restores
+            FROM notification_preferences
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(preferences.unwrap_or_default())
+    }
+
+    pub async fn set_notification_preferences(&self, user_id: UserId, preferences: &NotificationPreferences) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO notification_preferences (user_id, invitations, mentions, conflict_rifts, checkpoint_restores, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (user_id) DO UPDATE
+            SET invitations = excluded.invitations,
+                mentions = excluded.mentions,
+                conflict_rifts = excluded.conflict_rifts,
+                checkpoint_restores = excluded.checkpoint_restores,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(preferences.invitations)
+        .bind(preferences.mentions)
+        .bind(preferences.conflict_rifts)
+        .bind(preferences.checkpoint_restores)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn list_managed_users(&self, limit: i64, offset: i64) -> Result<(Vec<ManagedUser>, i64)> {
+        let users = sqlx::query_as::<_, ManagedUser>(&format!(
+            "{} ORDER BY u.created_at ASC, u.id ASC LIMIT $2 OFFSET $3",
+            MANAGED_USER_SELECT
+        ))
+        .bind(Utc::now())
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let total = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM users")
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok((users, total))
+    }
+
+    pub async fn get_managed_user(&self, user_id: UserId) -> Result<Option<ManagedUser>> {
+        let user = sqlx::query_as::<_, ManagedUser>(&format!("{} WHERE u.id = $2", MANAGED_USER_SELECT))
+            .bind(Utc::now())
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(user)
+    }
+
+    pub async fn is_user_disabled(&self, user_id: UserId) -> Result<bool> {
+        let disabled = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS(SELECT 1 FROM users WHERE id = $1 AND disabled_at IS NOT NULL)",
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(disabled)
+    }
+
+    pub async fn list_disabled_user_ids(&self) -> Result<Vec<UserId>> {
+        let ids = sqlx::query_scalar::<_, Uuid>("SELECT id FROM users WHERE disabled_at IS NOT NULL")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(ids)
+    }
+
+    pub async fn set_user_disabled(&self, user_id: UserId, disabled: bool) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE users
+            SET disabled_at = CASE WHEN $2 THEN $3 ELSE NULL END, updated_at = $3
+            WHERE id = $1 AND (disabled_at IS NOT NULL) <> $2
+            "#,
+        )
+        .bind(user_id)
+        .bind(disabled)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn set_user_role(&self, user_id: UserId, role: UserRole) -> Result<bool> {
+        let result = sqlx::query("UPDATE users SET role = $2, updated_at = $3 WHERE id = $1")
+            .bind(user_id)
+            .bind(role)
+            .bind(Utc::now())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn revoke_user_sessions(&self, user_id: UserId) -> Result<Vec<Uuid>> {
+        let ids = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            UPDATE auth_sessions
+            SET revoked_at = $2
+            WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > $2
+            RETURNING id
+            "#,
+        )
+        .bind(user_id)
+        .bind(Utc::now())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(ids)
+    }
+
+    pub async fn delete_user_reassigning(&self, user_id: UserId, successor: UserId) -> Result<()> {
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO project_members (project_id, user_id, role, joined_at)
+            SELECT project_id, $2, role, $3 FROM project_members WHERE user_id = $1
+            ON CONFLICT (project_id, user_id) DO NOTHING
+            "#,
+        )
+        .bind(user_id)
+        .bind(successor)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO rift_collaborators (rift_id, user_id, joined_at)
+            SELECT rift_id, $2, $3 FROM rift_collaborators WHERE user_id = $1
+            ON CONFLICT (rift_id, user_id) DO NOTHING
+            "#,
+        )
+        .bind(user_id)
+        .bind(successor)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        for statement in [
+            "UPDATE rifts SET owner_id = $2 WHERE owner_id = $1",
+            "UPDATE rifts SET author = $2 WHERE author = $1",
+            "UPDATE git_mirrors SET created_by = $2 WHERE created_by = $1",
+        ] {
+            sqlx::query(statement)
+                .bind(user_id)
+                .bind(successor)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        sqlx::query("DELETE FROM user_rift_state WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn apply_replicated_rift(&self, replicated: &ReplicatedRift) -> Result<()> {
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+
+        for user in &replicated.users {
+            sqlx::query(
+                r#"
+                INSERT INTO users (id, username, email, role, created_at, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (id) DO UPDATE
+                SET username = excluded.username, email = excluded.email, role = excluded.role, updated_at = excluded.updated_at
+                "#,
+            )
+            .bind(user.id)
+            .bind(&user.username)
+            .bind(&user.email)
+            .bind(user.role.clone())
+            .bind(user.created_at)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let project = &replicated.project;
+        sqlx::query(
+            r#"
+            INSERT INTO projects (id, name, description, created_at, updated_at, archived_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (id) DO UPDATE
+            SET name = excluded.name, description = excluded.description,
+                archived_at = excluded.archived_at, updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(project.id)
+        .bind(&project.name)
+        .bind(&project.description)
+        .bind(project.created_at)
+        .bind(now)
+        .bind(replicated.project_archived_at)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO project_settings (project_id, settings)
+            VALUES ($1, $2)
+            ON CONFLICT (project_id) DO UPDATE SET settings = excluded.settings
+            "#,
+        )
+        .bind(project.id)
+        .bind(serde_json::to_string(&project.settings)?)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM project_members WHERE project_id = $1")
+            .bind(project.id)
+            .execute(&mut *tx)
+            .await?;
+        for member in &project.members {
+            sqlx::query("INSERT INTO project_members (project_id, user_id, joined_at) VALUES ($1, $2, $3)")
+                .bind(project.id)
+                .bind(member)
+                .bind(now)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let rift = &replicated.rift;
+        sqlx::query(
+            r#"
+            INSERT INTO rifts (id, project_id, name, parent_rift_id, is_active, owner_id, visibility, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (id) DO UPDATE
+            SET name = excluded.name, parent_rift_id = excluded.parent_rift_id, is_active = excluded.is_active,
+                owner_id = excluded.owner_id, visibility = excluded.visibility
+            "#,
+        )
+        .bind(rift.id)
+        .bind(rift.project_id)
+        .bind(&rift.name)
+        .bind(rift.parent_rift)
+        .bind(rift.is_active)
+        .bind(replicated.owner)
+        .bind(replicated.visibility.as_str())
+        .bind(rift.created_at)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM rift_collaborators WHERE rift_id = $1")
+            .bind(rift.id)
+            .execute(&mut *tx)
+            .await?;
+        for collaborator in &rift.collaborators {
+            sqlx::query("INSERT INTO rift_collaborators (rift_id, user_id, joined_at) VALUES ($1, $2, $3)")
+                .bind(rift.id)
+                .bind(collaborator)
+                .bind(now)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        sqlx::query("DELETE FROM rift_permissions WHERE rift_id = $1")
+            .bind(rift.id)
+            .execute(&mut *tx)
+            .await?;
+        for permission in &replicated.permissions {
+            sqlx::query(
+                "INSERT INTO rift_permissions (rift_id, user_id, role, granted_at) VALUES ($1, $2, $3, $4)",
+            )
+            .bind(rift.id)
+            .bind(permission.user_id)
+            .bind(permission.role.as_str())
+            .bind(permission.granted_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn insert_chat_message(&self, message: &ChatMessage, max_history: usize) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO chat_messages (id, rift_id, author_id, content, created_at)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(message.id)
+        .bind(message.rift_id)
+        .bind(message.author)
+        .bind(&message.content)
+        .bind(message.timestamp)
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM chat_messages
+            WHERE rift_id = $1 AND id NOT IN (
+                SELECT id FROM chat_messages
+                WHERE rift_id = $1
+                ORDER BY created_at DESC
+                LIMIT $2
+            )
+            "#,
+        )
+        .bind(message.rift_id)
+        .bind(max_history as i64)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn list_chat_messages(
+        &self,
+        rift_id: RiftId,
+        before: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> Result<Vec<ChatMessage>> {
+        let rows = sqlx::query_as::<_, ChatMessageRow>(
+            r#"
+            SELECT m.id, m.rift_id, m.author_id, u.username, m.content, m.created_at
+            FROM chat_messages m
+            JOIN users u ON u.id = m.author_id
+            WHERE m.rift_id = $1 AND ($2 IS NULL OR m.created_at < $2)
+            ORDER BY m.created_at DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(rift_id)
+        .bind(before)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().rev().map(ChatMessage::from).collect())
+    }
+
+    pub async fn upsert_checkpoint_status(&self, status: NewCheckpointStatus<'_>) -> Result<CheckpointStatus> {
+        let row = sqlx::query_as::<_, CheckpointStatusRow>(
+            r#"
+            INSERT INTO checkpoint_statuses (id, project_id, checkpoint_id, context, state, target_url, description, creator_id, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9)
+            ON CONFLICT (checkpoint_id, context) DO UPDATE
+            SET state = excluded.state,
+                target_url = excluded.target_url,
+                description = excluded.description,
+                creator_id = excluded.creator_id,
+                updated_at = excluded.updated_at
+            RETURNING checkpoint_id, context, state, target_url, description, creator_id, updated_at
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(status.project_id)
+        .bind(status.checkpoint_id)
+        .bind(status.context)
+        .bind(status.state.as_str())
+        .bind(status.target_url)
+        .bind(status.description)
+        .bind(status.creator_id)
+        .bind(Utc::now())
+        .fetch_one(&self.pool)
+        .await?;
+
+        row.try_into()
+    }
+
+    pub async fn list_checkpoint_statuses(&self, checkpoint_ids: &[CheckpointId]) -> Result<Vec<CheckpointStatus>> {
+        if checkpoint_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let placeholders: Vec<String> = (1..=checkpoint_ids.len()).map(|i| format!("${}", i)).collect();
+        let sql = format!(
+            r#"
+            SELECT checkpoint_id, context, state, target_url, description, creator_id, updated_at
+            FROM checkpoint_statuses
+            WHERE checkpoint_id IN ({})
+            ORDER BY context
+            "#,
+            placeholders.join(", ")
+        );
+
+        let mut query = sqlx::query_as::<_, CheckpointStatusRow>(&sql);
+        for checkpoint_id in checkpoint_ids {
+            query = query.bind(checkpoint_id);
+        }
+        let rows = query.fetch_all(&self.pool).await?;
+
+        rows.into_iter().map(CheckpointStatus::try_from).collect()
+    }
+
+    pub async fn add_checkpoint_comment(&self, comment: NewComment<'_>) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO checkpoint_comments (id, project_id, checkpoint_id, thread_id, path, line_start, line_end, author_id, body, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+        )
+        .bind(comment.id)
+        .bind(comment.project_id)
+        .bind(comment.checkpoint_id)
+        .bind(comment.thread_id)
+        .bind(comment.path)
+        .bind(comment.line_start as i32)
+        .bind(comment.line_end as i32)
+        .bind(comment.author_id)
+        .bind(comment.body)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn list_comment_threads(
+        &self,
+        project_id: ProjectId,
+        checkpoint_id: Option<CheckpointId>,
+        thread_id: Option<Uuid>,
+        unresolved_only: bool,
+    ) -> Result<Vec<CommentThread>> {
+        let rows = sqlx::query_as::<_, CommentRow>(
+            r#"
+            SELECT c.id, c.thread_id, c.checkpoint_id, c.path, c.line_start, c.line_end, c.author_id,
+                   u.username AS author_name, c.body, t.resolved_by, t.resolved_at, c.created_at
+            FROM checkpoint_comments c
+            INNER JOIN checkpoint_comments t ON t.id = c.thread_id
+            INNER JOIN users u ON u.id = c.author_id
+            WHERE c.project_id = $1
+              AND ($2 IS NULL OR c.checkpoint_id = $2)
+              AND ($3 IS NULL OR c.thread_id = $3)
+              AND ($4 = 0 OR t.resolved_at IS NULL)
+            ORDER BY c.created_at, c.id
+            "#,
+        )
+        .bind(project_id)
+        .bind(checkpoint_id)
+        .bind(thread_id)
+        .bind(unresolved_only)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(comment_threads(rows))
+    }
+
+    pub async fn set_comment_thread_resolved(
+        &self,
+        project_id: ProjectId,
+        thread_id: Uuid,
+        resolved_by: Option<UserId>,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE checkpoint_comments
+            SET resolved_by = $3, resolved_at = $4
+            WHERE project_id = $1 AND id = $2 AND thread_id = id
+            "#,
+        )
+        .bind(project_id)
+        .bind(thread_id)
+        .bind(resolved_by)
+        .bind(resolved_by.map(|_| Utc::now()))
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn create_review_request(&self, review: NewReviewRequest<'_>) -> Result<()> {
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO review_requests (id, project_id, source_rift_id, target_rift_id, title, description, author_id, required_approvals, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9)
+            "#,
+        )
+        .bind(review.id)
+        .bind(review.project_id)
+        .bind(review.source_rift_id)
+        .bind(review.target_rift_id)
+        .bind(review.title)
+        .bind(review.description)
+        .bind(review.author_id)
+        .bind(review.required_approvals as i32)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        for reviewer in review.reviewers {
+            sqlx::query("INSERT INTO review_reviewers (review_id, user_id, added_at) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING")
+                .bind(review.id)
+                .bind(reviewer)
+                .bind(now)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn list_review_requests(
+        &self,
+        project_id: ProjectId,
+        state: Option<ReviewState>,
+        review_id: Option<Uuid>,
+        limit: usize,
+    ) -> Result<Vec<ReviewRequest>> {
+        let rows = sqlx::query_as::<_, ReviewRequestRow>(
+            r#"
+            SELECT r.id, r.project_id, r.source_rift_id, s.name AS source_rift_name, r.target_rift_id,
+                   t.name AS target_rift_name, r.title, r.description, r.author_id, u.username AS author_name,
+                   r.state, r.required_approvals, r.merge_checkpoint_id, r.created_at, r.updated_at
+            FROM review_requests r
+            INNER JOIN rifts s ON s.id = r.source_rift_id
+            INNER JOIN rifts t ON t.id = r.target_rift_id
+            INNER JOIN users u ON u.id = r.author_id
+            WHERE r.project_id = $1
+              AND ($2 IS NULL OR r.state = $2)
+              AND ($3 IS NULL OR r.id = $3)
+            ORDER BY r.created_at DESC, r.id DESC
+            LIMIT $4
+            "#,
+        )
+        .bind(project_id)
+        .bind(state.map(|state| state.as_str()))
+        .bind(review_id)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        if rows.is_empty() {
+            return Ok(Vec::new());
+        }
+        let placeholders: Vec<String> = (1..=rows.len()).map(|i| format!("${}", i)).collect();
+
+        let sql = format!(
+            r#"
+            SELECT rr.review_id, rr.user_id, u.username, rr.approved_at
+            FROM review_reviewers rr
+            INNER JOIN users u ON u.id = rr.user_id
+            WHERE rr.review_id IN ({})
+            ORDER BY rr.added_at, u.username
+            "#,
+            placeholders.join(", ")
+        );
+        let mut query = sqlx::query_as::<_, ReviewerRow>(&sql);
+        for row in &rows {
+            query = query.bind(row.id);
+        }
+        let reviewers = query.fetch_all(&self.pool).await?;
+
+        let sql = format!(
+            r#"
+            SELECT c.id, c.review_id, c.author_id, u.username AS author_name, c.body, c.created_at
+            FROM review_comments c
+            INNER JOIN users u ON u.id = c.author_id
+            WHERE c.review_id IN ({})
+            ORDER BY c.created_at, c.id
+            "#,
+            placeholders.join(", ")
+        );
+        let mut query = sqlx::query_as::<_, ReviewCommentRow>(&sql);
+        for row in &rows {
+            query = query.bind(row.id);
+        }
+        let comments = query.fetch_all(&self.pool).await?;
+
+        review_requests(rows, reviewers, comments)
+    }
+
+    pub async fn get_open_review_id(&self, source_rift_id: RiftId, target_rift_id: RiftId) -> Result<Option<Uuid>> {
+        let id = sqlx::query_scalar::<_, Uuid>(
+            "SELECT id FROM review_requests WHERE source_rift_id = $1 AND target_rift_id = $2 AND state = 'open'",
+        )
+        .bind(source_rift_id)
+        .bind(target_rift_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(id)
+    }
+
+    pub async fn add_review_reviewers(&self, review_id: Uuid, reviewers: &[UserId]) -> Result<()> {
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+        for reviewer in reviewers {
+            sqlx::query("INSERT INTO review_reviewers (review_id, user_id, added_at) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING")
+                .bind(review_id)
+                .bind(reviewer)
+                .bind(now)
+                .execute(&mut *tx)
+                .await?;
+        }
+        sqlx::query("UPDATE review_requests SET updated_at = $2 WHERE id = $1")
+            .bind(review_id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn approve_review_request(&self, review_id: Uuid, user_id: UserId) -> Result<()> {
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            r#"
+            INSERT INTO review_reviewers (review_id, user_id, approved_at, added_at) VALUES ($1, $2, $3, $3)
+            ON CONFLICT (review_id, user_id)
+            DO UPDATE SET approved_at = COALESCE(review_reviewers.approved_at, excluded.approved_at)
+            "#,
+        )
+        .bind(review_id)
+        .bind(user_id)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("UPDATE review_requests SET updated_at = $2 WHERE id = $1")
+            .bind(review_id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn add_review_comment(&self, review_id: Uuid, author_id: UserId, body: &str) -> Result<()> {
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("INSERT INTO review_comments (id, review_id, author_id, body, created_at) VALUES ($1, $2, $3, $4, $5)")
+            .bind(Uuid::new_v4())
+            .bind(review_id)
+            .bind(author_id)
+            .bind(body)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE review_requests SET updated_at = $2 WHERE id = $1")
+            .bind(review_id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn finish_review_request(
+        &self,
+        review_id: Uuid,
+        state: ReviewState,
+        merge_checkpoint_id: Option<CheckpointId>,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE review_requests
+            SET state = $2, merge_checkpoint_id = $3, updated_at = $4
+            WHERE id = $1 AND state = 'open'
+            "#,
+        )
+        .bind(review_id)
+        .bind(state.as_str())
+        .bind(merge_checkpoint_id)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn create_user_notification(
+        &self,
+        user_id: UserId,
+        kind: InboxKind,
+        title: &str,
+        body: &str,
+        project_id: Option<ProjectId>,
+        actor_id: Option<UserId>,
+    ) -> Result<InboxNotification> {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            r#"
+            INSERT INTO user_notifications (id, user_id, kind, title, body, project_id, actor_id, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(kind.as_str())
+        .bind(title)
+        .bind(body)
+        .bind(project_id)
+        .bind(actor_id)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        let row = sqlx::query_as::<_, UserNotificationRow>(
+            r#"
+            SELECT n.id, n.kind, n.title, n.body, n.project_id, n.actor_id, u.username AS actor_name, n.read_at, n.created_at
+            FROM user_notifications n
+            LEFT JOIN users u ON u.id = n.actor_id
+            WHERE n.id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await?;
+        inbox_notification(row)
+    }
+
+    pub async fn list_user_notifications(
+        &self,
+        user_id: UserId,
+        unread_only: bool,
+        before: Option<DateTime<Utc>>,
+        limit: usize,
+    ) -> Result<Vec<InboxNotification>> {
+        let rows = sqlx::query_as::<_, UserNotificationRow>(
+            r#"
+            SELECT n.id, n.kind, n.title, n.body, n.project_id, n.actor_id, u.username AS actor_name, n.read_at, n.created_at
+            FROM user_notifications n
+            LEFT JOIN users u ON u.id = n.actor_id
+            WHERE n.user_id = $1
+              AND (NOT $2 OR n.read_at IS NULL)
+              AND ($3 IS NULL OR n.created_at < $3)
+            ORDER BY n.created_at DESC, n.id DESC
+            LIMIT $4
+            "#,
+        )
+        .bind(user_id)
+        .bind(unread_only)
+        .bind(before)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(inbox_notification).collect()
+    }
+
+    pub async fn count_unread_notifications(&self, user_id: UserId) -> Result<u64> {
+        let count = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM user_notifications WHERE user_id = $1 AND read_at IS NULL",
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(count as u64)
+    }
+
+    pub async fn mark_notifications_read(&self, user_id: UserId, id: Option<Uuid>) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            UPDATE user_notifications SET read_at = $3
+            WHERE user_id = $1 AND read_at IS NULL AND ($2 IS NULL OR id = $2)
+            "#,
+        )
+        .bind(user_id)
+        .bind(id)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn get_server_feature_flags(&self) -> Result<Vec<(String, bool)>> {
+        let flags = sqlx::query_as::<_, (String, bool)>("SELECT feature, enabled FROM server_feature_flags")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(flags)
+    }
+
+    pub async fn set_server_feature_flag(&self, feature: &str, enabled: Option<bool>, updated_by: UserId) -> Result<()> {
+        match enabled {
+            Some(enabled) => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO server_feature_flags (feature, enabled, updated_by, updated_at)
+                    VALUES ($1, $2, $3, $4)
+                    ON CONFLICT (feature) DO UPDATE
+                    SET enabled = excluded.enabled, updated_by = excluded.updated_by, updated_at = excluded.updated_at
+                    "#,
+                )
+                .bind(feature)
+                .bind(enabled)
+                .bind(updated_by)
+                .bind(Utc::now())
+                .execute(&self.pool)
+                .await?;
+            }
+            None => {
+                sqlx::query("DELETE FROM server_feature_flags WHERE feature = $1")
+                    .bind(feature)
+                    .execute(&self.pool)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn get_project_feature_flags(&self, project_id: ProjectId) -> Result<Vec<(String, bool)>> {
+        let flags = sqlx::query_as::<_, (String, bool)>("SELECT feature, enabled FROM project_feature_flags WHERE project_id = $1")
+            .bind(project_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(flags)
+    }
+
+    pub async fn set_project_feature_flag(&self, project_id: ProjectId, feature: &str, enabled: Option<bool>, updated_by: UserId) -> Result<()> {
+        match enabled {
+            Some(enabled) => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO project_feature_flags (project_id, feature, enabled, updated_by, updated_at)
+                    VALUES ($1, $2, $3, $4, $5)
+                    ON CONFLICT (project_id, feature) DO UPDATE
+                    SET enabled = excluded.enabled, updated_by = excluded.updated_by, updated_at = excluded.updated_at
+                    "#,
+                )
+                .bind(project_id)
+                .bind(feature)
+                .bind(enabled)
+                .bind(updated_by)
+                .bind(Utc::now())
+                .execute(&self.pool)
+                .await?;
+            }
+            None => {
+                sqlx::query("DELETE FROM project_feature_flags WHERE project_id = $1 AND feature = $2")
+                    .bind(project_id)
+                    .bind(feature)
+                    .execute(&self.pool)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn list_whitelist_entries(&self) -> Result<Vec<WhitelistEntry>> {
+        let entries = sqlx::query_as::<_, WhitelistEntry>(
+            "SELECT id, kind, value, added_by, created_at FROM whitelist_entries ORDER BY created_at ASC, kind ASC, value ASC",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(entries)
+    }
+
+    pub async fn add_whitelist_entry(&self, kind: &str, value: &str, added_by: Option<UserId>) -> Result<WhitelistEntry> {
+        sqlx::query(
+            r#"
+            INSERT INTO whitelist_entries (id, kind, value, added_by, created_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (kind, value) DO NOTHING
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(kind)
+        .bind(value)
+        .bind(added_by)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        let entry = sqlx::query_as::<_, WhitelistEntry>(
+            "SELECT id, kind, value, added_by, created_at FROM whitelist_entries WHERE kind = $1 AND value = $2",
+        )
+        .bind(kind)
+        .bind(value)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(entry)
+    }
+
+    pub async fn remove_whitelist_entry(&self, id: Uuid) -> Result<Option<WhitelistEntry>> {
+        let entry = sqlx::query_as::<_, WhitelistEntry>(
+            "DELETE FROM whitelist_entries WHERE id = $1 RETURNING id, kind, value, added_by, created_at",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(entry)
+    }
+
+    pub async fn get_user_oauth_groups(&self, user_id: UserId) -> Result<Vec<String>> {
+        let groups = sqlx::query_scalar::<_, String>("SELECT group_name FROM user_oauth_groups WHERE user_id = $1 ORDER BY group_name")
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(groups)
+    }
+
+    pub async fn set_user_oauth_groups(&self, user_id: UserId, groups: &[String]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM user_oauth_groups WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        for group in groups {
+            sqlx::query("INSERT INTO user_oauth_groups (user_id, group_name) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+                .bind(user_id)
+                .bind(group)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn set_github_login(&self, user_id: UserId, login: &str) -> Result<bool> {
+        let granted = sqlx::query_scalar::<_, bool>(
+            r#"
+            INSERT INTO github_accounts (user_id, login, checked_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (user_id) DO UPDATE
+            SET login = excluded.login, checked_at = excluded.checked_at
+            RETURNING role_granted
+            "#,
+        )
+        .bind(user_id)
+        .bind(login)
+        .bind(Utc::now())
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(granted)
+    }
+
+    pub async fn list_github_accounts(&self) -> Result<Vec<(UserId, String, bool)>> {
+        let accounts = sqlx::query_as::<_, (UserId, String, bool)>("SELECT user_id, login, role_granted FROM github_accounts ORDER BY checked_at ASC")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(accounts)
+    }
+
+    pub async fn set_github_role_granted(&self, user_id: UserId, granted: bool) -> Result<()> {
+        sqlx::query("UPDATE github_accounts SET role_granted = $2, checked_at = $3 WHERE user_id = $1")
+            .bind(user_id)
+            .bind(granted)
+            .bind(Utc::now())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
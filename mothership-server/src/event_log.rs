@@ -0,0 +1,141 @@
+//! Writing rift broadcasts to the storage engine's event logs.
+//!
+//! Every message broadcast on a rift's channel is numbered (see
+//! `SyncState::broadcast`) and handed here to be appended to the rift's log
+//! in the background, so broadcasting never waits on the disk. The logs let
+//! sessions resume after more than the in-memory replay buffer was missed,
+//! keep sequence numbers going across restarts, and show admins exactly what
+//! a rift went through (`GET /admin/rifts/:id/events`) when chasing a sync
+//! bug; `mothership_common::rift_events::replay` rebuilds files from them.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    routing::get,
+    Json, Router,
+};
+use chrono::Utc;
+use mothership_common::{protocol::ApiResponse, rift_events::RiftEvent, RiftId, SyncMessage};
+use serde::Deserialize;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tracing::{error, warn};
+
+use crate::handlers::require_admin;
+use crate::storage::StorageEngine;
+use crate::AppState;
+
+/// Most events returned by one request
+const MAX_EVENTS_PER_PAGE: usize = 1000;
+
+/// Event log endpoints (admins only)
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/admin/rifts/:id/events", get(list_events))
+}
+
+/// What the writer task does next, in the order it was asked
+pub enum LogOperation {
+    Append(RiftId, Box<RiftEvent>),
+    /// The rift was deleted; remove its log once earlier events are written
+    Remove(RiftId),
+}
+
+/// Broadcasts waiting to be written to their rift's event log
+#[derive(Clone)]
+pub struct EventLog {
+    operations: mpsc::UnboundedSender<LogOperation>,
+}
+
+impl EventLog {
+    pub fn new() -> (Self, mpsc::UnboundedReceiver<LogOperation>) {
+        let (operations, receiver) = mpsc::unbounded_channel();
+        (Self { operations }, receiver)
+    }
+
+    /// Log a numbered broadcast on a rift's channel
+    pub fn record(&self, rift_id: RiftId, seq: u64, message: SyncMessage) {
+        let event = RiftEvent { seq, timestamp: Utc::now(), message };
+        let _ = self.operations.send(LogOperation::Append(rift_id, Box::new(event)));
+    }
+
+    /// Drop a deleted rift's log
+    pub fn remove(&self, rift_id: RiftId) {
+        let _ = self.operations.send(LogOperation::Remove(rift_id));
+    }
+}
+
+/// The rift a broadcast channel belongs to, if it is a rift's channel
+pub fn rift_of_channel(channel: &str) -> Option<RiftId> {
+    channel.strip_prefix("rift_")?.parse().ok()
+}
+
+/// Write queued events, batching whatever has piled up per rift
+pub fn start_writer(storage: Arc<StorageEngine>, mut operations: mpsc::UnboundedReceiver<LogOperation>) {
+    tokio::spawn(async move {
+        while let Some(operation) = operations.recv().await {
+            let mut pending = vec![operation];
+            while let Ok(operation) = operations.try_recv() {
+                pending.push(operation);
+            }
+
+            let mut batch: Vec<RiftEvent> = Vec::new();
+            let mut batch_rift = None;
+            for operation in pending {
+                match operation {
+                    LogOperation::Append(rift_id, event) if batch_rift == Some(rift_id) => batch.push(*event),
+                    LogOperation::Append(rift_id, event) => {
+                        flush(&storage, batch_rift, &mut batch).await;
+                        batch_rift = Some(rift_id);
+                        batch.push(*event);
+                    }
+                    LogOperation::Remove(rift_id) => {
+                        flush(&storage, batch_rift.take(), &mut batch).await;
+                        if let Err(e) = storage.remove_rift_events(rift_id).await {
+                            warn!("Failed to remove the event log of rift {}: {}", rift_id, e);
+                        }
+                    }
+                }
+            }
+            flush(&storage, batch_rift, &mut batch).await;
+        }
+    });
+}
+
+async fn flush(storage: &StorageEngine, rift_id: Option<RiftId>, batch: &mut Vec<RiftEvent>) {
+    let Some(rift_id) = rift_id else {
+        return;
+    };
+    if batch.is_empty() {
+        return;
+    }
+    if let Err(e) = storage.append_rift_events(rift_id, batch).await {
+        error!("Failed to log {} events of rift {}: {}", batch.len(), rift_id, e);
+    }
+    batch.clear();
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EventQuery {
+    /// Only events after this sequence number
+    pub after: Option<u64>,
+    pub limit: Option<usize>,
+}
+
+/// A page of a rift's event log, oldest first
+async fn list_events(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(rift_id): Path<RiftId>,
+    Query(query): Query<EventQuery>,
+) -> Result<Json<ApiResponse<Vec<RiftEvent>>>, StatusCode> {
+    require_admin(&state, &headers, "read a rift's event log").await?;
+
+    let limit = query.limit.unwrap_or(MAX_EVENTS_PER_PAGE).clamp(1, MAX_EVENTS_PER_PAGE);
+    let events = state.sync.storage.read_rift_events(rift_id, query.after.unwrap_or(0), limit).await
+        .map_err(|e| {
+            error!("Failed to read the event log of rift {}: {}", rift_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    Ok(Json(ApiResponse::success(events)))
+}
@@ -0,0 +1,262 @@
+//! Feature flags.
+//!
+//! The server configuration decides which features are on. Administrators
+//! can override that at run time for the whole server, and turn features
+//! off for single projects. Clients learn what's off from `/capabilities`
+//! and `/projects/:id/features` and hide it; the endpoints behind a flag
+//! refuse requests while it's off.
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, put},
+    Json, Router,
+};
+use mothership_common::{
+    features::{self, Feature, FeatureFlag, SetFeatureRequest},
+    protocol::ApiResponse,
+    ProjectId, RiftId, UserId,
+};
+use std::collections::HashMap;
+use tracing::{error, info, warn};
+
+use crate::audit::{self, AuditAction, AuditEvent};
+use crate::database::Database;
+use crate::handlers::{authenticate_request, require_admin};
+use crate::live_config::LiveConfig;
+use crate::AppState;
+
+/// Feature flag endpoints: anyone in a project can see its flags, only
+/// admins change them
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/admin/features", get(list_server_flags))
+        .route("/admin/features/:feature", put(set_server_flag).delete(clear_server_flag))
+        .route("/projects/:id/features", get(list_project_flags))
+        .route("/admin/projects/:id/features/:feature", put(set_project_flag).delete(clear_project_flag))
+}
+
+/// Looks up whether features are on
+#[derive(Clone)]
+pub struct FeatureFlags {
+    db: Database,
+    settings: LiveConfig,
+}
+
+impl FeatureFlags {
+    pub fn new(db: Database, settings: LiveConfig) -> Self {
+        Self { db, settings }
+    }
+
+    /// Whether the server configuration turns a feature on
+    fn configured(&self, feature: Feature) -> bool {
+        let config = self.settings.config();
+        match feature {
+            Feature::Chat => config.features.chat_enabled,
+            Feature::FileUploads => config.features.file_uploads_enabled,
+            Feature::TerminalSharing | Feature::ProjectSearch => true,
+        }
+    }
+
+    /// Every feature's server-wide flag
+    pub async fn server_flags(&self) -> anyhow::Result<Vec<FeatureFlag>> {
+        let server = parse_flags(self.db.get_server_feature_flags().await?);
+        Ok(Feature::ALL.into_iter()
+            .map(|feature| FeatureFlag {
+                feature,
+                enabled: features::resolve(self.configured(feature), server.get(&feature).copied(), None),
+                overridden: server.contains_key(&feature),
+            })
+            .collect())
+    }
+
+    /// Every feature's flag for a project; `overridden` marks the ones the
+    /// project sets itself
+    pub async fn project_flags(&self, project_id: ProjectId) -> anyhow::Result<Vec<FeatureFlag>> {
+        let server = parse_flags(self.db.get_server_feature_flags().await?);
+        let project = parse_flags(self.db.get_project_feature_flags(project_id).await?);
+        Ok(Feature::ALL.into_iter()
+            .map(|feature| FeatureFlag {
+                feature,
+                enabled: features::resolve(
+                    self.configured(feature),
+                    server.get(&feature).copied(),
+                    project.get(&feature).copied(),
+                ),
+                overridden: project.contains_key(&feature),
+            })
+            .collect())
+    }
+
+    /// Whether a feature is on, in a project or server-wide. Falls back to
+    /// the configuration if the flags can't be read.
+    pub async fn is_enabled(&self, feature: Feature, project_id: Option<ProjectId>) -> bool {
+        let flags = match project_id {
+            Some(project_id) => self.project_flags(project_id).await,
+            None => self.server_flags().await,
+        };
+        match flags {
+            Ok(flags) => flags.iter().any(|flag| flag.feature == feature && flag.enabled),
+            Err(e) => {
+                warn!("Failed to read feature flags, using the configuration: {}", e);
+                self.configured(feature)
+            }
+        }
+    }
+
+    /// Whether a feature is on in the project a rift belongs to
+    pub async fn is_enabled_in_rift(&self, feature: Feature, rift_id: RiftId) -> bool {
+        let project_id = self.db.get_rift(rift_id).await.ok().flatten().map(|rift| rift.project_id);
+        self.is_enabled(feature, project_id).await
+    }
+
+    /// Features turned off server-wide, for `/capabilities`
+    pub async fn disabled(&self) -> Vec<Feature> {
+        match self.server_flags().await {
+            Ok(flags) => flags.into_iter().filter(|flag| !flag.enabled).map(|flag| flag.feature).collect(),
+            Err(e) => {
+                warn!("Failed to read feature flags, using the configuration: {}", e);
+                Feature::ALL.into_iter().filter(|feature| !self.configured(*feature)).collect()
+            }
+        }
+    }
+}
+
+/// Flags as stored, skipping features this server doesn't know
+fn parse_flags(rows: Vec<(String, bool)>) -> HashMap<Feature, bool> {
+    rows.into_iter()
+        .filter_map(|(feature, enabled)| Some((feature.parse().ok()?, enabled)))
+        .collect()
+}
+
+fn parse_feature(feature: &str) -> Result<Feature, StatusCode> {
+    feature.parse().map_err(|_| StatusCode::NOT_FOUND)
+}
+
+async fn existing_project(state: &AppState, project_id: ProjectId) -> Result<(), StatusCode> {
+    match state.db.get_project(project_id).await {
+        Ok(Some(_)) => Ok(()),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+fn internal_error(e: anyhow::Error) -> StatusCode {
+    error!("Failed to access feature flags: {}", e);
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+
+/// Every feature and whether it's on server-wide
+async fn list_server_flags(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<ApiResponse<Vec<FeatureFlag>>>, StatusCode> {
+    require_admin(&state, &headers, "change feature flags").await?;
+
+    let flags = state.sync.features.server_flags().await.map_err(internal_error)?;
+    Ok(Json(ApiResponse::success(flags)))
+}
+
+async fn set_server_flag(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(feature): Path<String>,
+    Json(request): Json<SetFeatureRequest>,
+) -> Result<Json<ApiResponse<Vec<FeatureFlag>>>, StatusCode> {
+    let admin_id = require_admin(&state, &headers, "change feature flags").await?;
+    let feature = parse_feature(&feature)?;
+
+    change_server_flag(&state, admin_id, feature, Some(request.enabled)).await
+}
+
+/// Let the configuration decide a feature again
+async fn clear_server_flag(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(feature): Path<String>,
+) -> Result<Json<ApiResponse<Vec<FeatureFlag>>>, StatusCode> {
+    let admin_id = require_admin(&state, &headers, "change feature flags").await?;
+    let feature = parse_feature(&feature)?;
+
+    change_server_flag(&state, admin_id, feature, None).await
+}
+
+async fn change_server_flag(
+    state: &AppState,
+    admin_id: UserId,
+    feature: Feature,
+    enabled: Option<bool>,
+) -> Result<Json<ApiResponse<Vec<FeatureFlag>>>, StatusCode> {
+    state.db.set_server_feature_flag(feature.as_str(), enabled, admin_id).await.map_err(internal_error)?;
+
+    info!("🚩 Admin {} set {} to {:?} server-wide", admin_id, feature, enabled);
+    audit::record(&state.db, AuditEvent::new(AuditAction::FeatureFlagChanged, Some(admin_id))
+        .target(feature.as_str())
+        .details(serde_json::json!({ "enabled": enabled }))).await;
+
+    let flags = state.sync.features.server_flags().await.map_err(internal_error)?;
+    Ok(Json(ApiResponse::success(flags)))
+}
+
+/// Every feature and whether it's on in a project
+async fn list_project_flags(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(project_id): Path<ProjectId>,
+) -> Result<Json<ApiResponse<Vec<FeatureFlag>>>, StatusCode> {
+    let user_id = authenticate_request(&state, &headers)?;
+    existing_project(&state, project_id).await?;
+    if !state.db.user_has_project_access(user_id, project_id).await.unwrap_or(false)
+        && !state.db.user_is_admin(user_id).await.unwrap_or(false)
+    {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let flags = state.sync.features.project_flags(project_id).await.map_err(internal_error)?;
+    Ok(Json(ApiResponse::success(flags)))
+}
+
+async fn set_project_flag(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((project_id, feature)): Path<(ProjectId, String)>,
+    Json(request): Json<SetFeatureRequest>,
+) -> Result<Json<ApiResponse<Vec<FeatureFlag>>>, StatusCode> {
+    let admin_id = require_admin(&state, &headers, "change feature flags").await?;
+    let feature = parse_feature(&feature)?;
+    existing_project(&state, project_id).await?;
+
+    change_project_flag(&state, admin_id, project_id, feature, Some(request.enabled)).await
+}
+
+/// Let a project follow the server's flag again
+async fn clear_project_flag(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((project_id, feature)): Path<(ProjectId, String)>,
+) -> Result<Json<ApiResponse<Vec<FeatureFlag>>>, StatusCode> {
+    let admin_id = require_admin(&state, &headers, "change feature flags").await?;
+    let feature = parse_feature(&feature)?;
+    existing_project(&state, project_id).await?;
+
+    change_project_flag(&state, admin_id, project_id, feature, None).await
+}
+
+async fn change_project_flag(
+    state: &AppState,
+    admin_id: UserId,
+    project_id: ProjectId,
+    feature: Feature,
+    enabled: Option<bool>,
+) -> Result<Json<ApiResponse<Vec<FeatureFlag>>>, StatusCode> {
+    state.db.set_project_feature_flag(project_id, feature.as_str(), enabled, admin_id).await.map_err(internal_error)?;
+
+    info!("🚩 Admin {} set {} to {:?} in project {}", admin_id, feature, enabled, project_id);
+    audit::record(&state.db, AuditEvent::new(AuditAction::FeatureFlagChanged, Some(admin_id))
+        .project(project_id)
+        .target(feature.as_str())
+        .details(serde_json::json!({ "enabled": enabled }))).await;
+
+    let flags = state.sync.features.project_flags(project_id).await.map_err(internal_error)?;
+    Ok(Json(ApiResponse::success(flags)))
+}
@@ -0,0 +1,191 @@
+//! Who is working on which file.
+//!
+//! Editors say which files they open and close, and editors and the daemon
+//! which files they changed. The server keeps that per rift and relays each
+//! change to connections that asked to watch file activity, on a channel of
+//! its own like cursors, so two people editing the same file find out before
+//! their changes collide. A user's changed files count as clean again once
+//! they create a checkpoint, and everything of theirs is forgotten when they
+//! leave the rift.
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::get,
+    Json, Router,
+};
+use mothership_common::{
+    protocol::{ApiResponse, FileActivity, SyncMessage},
+    ProjectId, RiftId, UserId,
+};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tracing::error;
+
+use crate::handlers::authenticate_request;
+use crate::sync::SyncState;
+use crate::AppState;
+
+/// Channels carrying a rift's file activity
+pub const FILE_ACTIVITY_CHANNEL_PREFIX: &str = "editing_";
+
+pub fn file_activity_channel(rift_id: impl Display) -> String {
+    format!("{}{}", FILE_ACTIVITY_CHANNEL_PREFIX, rift_id)
+}
+
+/// File activity endpoints
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/projects/:id/file-activity", get(list_activity))
+}
+
+/// A rift's activity, keyed by user and file
+type RiftActivity = HashMap<(UserId, PathBuf), FileActivity>;
+
+/// Open and changed files of each user in each rift
+#[derive(Clone, Default)]
+pub struct FileActivities {
+    rifts: Arc<Mutex<HashMap<RiftId, RiftActivity>>>,
+}
+
+impl FileActivities {
+    /// Record an activity; false if nothing changed
+    fn set(&self, rift_id: RiftId, activity: FileActivity) -> bool {
+        let mut rifts = self.rifts.lock().unwrap();
+        let files = rifts.entry(rift_id).or_default();
+        let key = (activity.user_id, activity.path.clone());
+        let changed = match files.get(&key) {
+            Some(known) => *known != activity,
+            None => activity.is_active(),
+        };
+        if activity.is_active() {
+            files.insert(key, activity);
+        } else {
+            files.remove(&key);
+        }
+        if files.is_empty() {
+            rifts.remove(&rift_id);
+        }
+        changed
+    }
+
+    fn get(&self, rift_id: RiftId, user_id: UserId, path: &std::path::Path) -> Option<FileActivity> {
+        let rifts = self.rifts.lock().unwrap();
+        rifts.get(&rift_id)?.get(&(user_id, path.to_path_buf())).cloned()
+    }
+
+    /// Files being worked on in a rift, by path and username
+    fn in_rift(&self, rift_id: RiftId) -> Vec<FileActivity> {
+        let rifts = self.rifts.lock().unwrap();
+        let mut activity: Vec<FileActivity> = rifts.get(&rift_id)
+            .map(|files| files.values().cloned().collect())
+            .unwrap_or_default();
+        activity.sort_by(|a, b| a.path.cmp(&b.path).then_with(|| a.username.cmp(&b.username)));
+        activity
+    }
+
+    /// Files a user changed in a rift
+    fn dirty_paths(&self, rift_id: RiftId, user_id: UserId) -> Vec<PathBuf> {
+        let rifts = self.rifts.lock().unwrap();
+        rifts.get(&rift_id)
+            .map(|files| files.values()
+                .filter(|activity| activity.user_id == user_id && activity.dirty)
+                .map(|activity| activity.path.clone())
+                .collect())
+            .unwrap_or_default()
+    }
+
+    /// Forget the files of a user who left a rift
+    pub fn forget_user(&self, rift_id: RiftId, user_id: UserId) {
+        let mut rifts = self.rifts.lock().unwrap();
+        if let Some(files) = rifts.get_mut(&rift_id) {
+            files.retain(|(id, _), _| *id != user_id);
+            if files.is_empty() {
+                rifts.remove(&rift_id);
+            }
+        }
+    }
+
+    /// Drop the file activity of a rift that no longer exists
+    pub fn forget_rift(&self, rift_id: RiftId) {
+        self.rifts.lock().unwrap().remove(&rift_id);
+    }
+
+    /// Mirror file activity another instance broadcast
+    pub fn apply_relayed(&self, message: &SyncMessage) {
+        if let SyncMessage::FileActivityChanged { rift_id, activity } = message {
+            self.set(*rift_id, activity.clone());
+        }
+    }
+}
+
+/// Change what a user is doing with a file, passing it on to watchers if
+/// anything changed
+fn change(state: &SyncState, rift_id: RiftId, user_id: UserId, username: &str, path: PathBuf, apply: impl FnOnce(&mut FileActivity)) {
+    let mut activity = state.file_activity.get(rift_id, user_id, &path).unwrap_or_else(|| FileActivity {
+        user_id,
+        username: username.to_string(),
+        path,
+        open: false,
+        dirty: false,
+    });
+    apply(&mut activity);
+    if state.file_activity.set(rift_id, activity.clone()) {
+        state.broadcast_unsequenced(file_activity_channel(rift_id), SyncMessage::FileActivityChanged { rift_id, activity });
+    }
+}
+
+/// Record that a user opened or closed a file
+pub fn opened(state: &SyncState, rift_id: RiftId, user_id: UserId, username: &str, path: PathBuf, open: bool) {
+    change(state, rift_id, user_id, username, path, |activity| activity.open = open);
+}
+
+/// Record that a user changed files, or no longer has changes in them
+pub fn dirtied(state: &SyncState, rift_id: RiftId, user_id: UserId, username: &str, paths: Vec<PathBuf>, dirty: bool) {
+    for path in paths {
+        change(state, rift_id, user_id, username, path, |activity| activity.dirty = dirty);
+    }
+}
+
+/// A user's checkpoint took in their changes: none of their files count as
+/// changed any more
+pub fn checkpointed(state: &SyncState, rift_id: RiftId, user_id: UserId) {
+    for path in state.file_activity.dirty_paths(rift_id, user_id) {
+        change(state, rift_id, user_id, "", path, |activity| activity.dirty = false);
+    }
+}
+
+/// Start a connection watching a rift's file activity by listing it now
+pub fn watch(state: &SyncState, rift_id: RiftId, reply: &mpsc::UnboundedSender<SyncMessage>) {
+    let _ = reply.send(SyncMessage::FileActivities { rift_id, activity: state.file_activity.in_rift(rift_id) });
+}
+
+/// What others are doing with files in the caller's current rift of a project
+async fn list_activity(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(project_id): Path<ProjectId>,
+) -> Result<Json<ApiResponse<Vec<FileActivity>>>, StatusCode> {
+    let user_id = authenticate_request(&state, &headers)?;
+    match state.db.user_has_project_access(user_id, project_id).await {
+        Ok(true) => {}
+        Ok(false) => return Err(StatusCode::FORBIDDEN),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+
+    let rift = match crate::handlers::current_rift(&state, project_id, user_id).await {
+        Ok(Some(rift)) => rift,
+        Ok(None) => return Ok(Json(ApiResponse::success(Vec::new()))),
+        Err(e) => {
+            error!("Failed to find the current rift of user {} in project {}: {}", user_id, project_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let mut activity = state.sync.file_activity.in_rift(rift.id);
+    activity.retain(|activity| activity.user_id != user_id);
+    Ok(Json(ApiResponse::success(activity)))
+}
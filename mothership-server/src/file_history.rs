@@ -0,0 +1,327 @@
+use anyhow::Result;
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use mothership_common::{
+    diff::DiffEngine,
+    protocol::{ApiResponse, BlameLine, FileBlame, FileChangeDiff, FileRevision},
+    ChangeType, Checkpoint, CheckpointId, ProjectId, Rift, RiftId, UserId,
+};
+use serde::Deserialize;
+use std::collections::{hash_map::Entry, HashMap};
+use std::path::PathBuf;
+use tracing::{error, info};
+
+use crate::api_error::ApiError;
+use crate::project_visibility;
+use crate::AppState;
+
+/// Default number of revisions returned by the history endpoint
+const DEFAULT_HISTORY_LIMIT: usize = 50;
+
+/// Files larger than this (on either side) aren't diffed
+const MAX_DIFFED_FILE_SIZE: u64 = 1024 * 1024;
+
+/// File history, blame and diff endpoints.
+///
+/// Paths can contain slashes, so all of them live under one wildcard route
+/// and are told apart by their `/history`, `/blame` or `/diff` suffix.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/projects/:id/files/*path", get(file_endpoint))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FileHistoryQuery {
+    /// Rift to read (defaults to the caller's rift in the project, or its
+    /// main rift for readers who aren't members)
+    pub rift_id: Option<RiftId>,
+    /// Maximum number of revisions to return (history only)
+    pub limit: Option<usize>,
+    /// Blame the file as of this checkpoint instead of the latest one, or
+    /// the checkpoint whose change to diff (defaults to the latest change)
+    pub checkpoint: Option<CheckpointId>,
+}
+
+async fn file_endpoint(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((project_id, path)): Path<(ProjectId, String)>,
+    Query(query): Query<FileHistoryQuery>,
+) -> Result<Response, ApiError> {
+    let path = path.trim_start_matches('/');
+
+    if let Some(file) = path.strip_suffix("/history") {
+        Ok(file_history(&state, &headers, project_id, PathBuf::from(file), query).await?.into_response())
+    } else if let Some(file) = path.strip_suffix("/blame") {
+        Ok(file_blame(&state, &headers, project_id, PathBuf::from(file), query).await?.into_response())
+    } else if let Some(file) = path.strip_suffix("/diff") {
+        Ok(file_diff(&state, &headers, project_id, PathBuf::from(file), query).await?.into_response())
+    } else {
+        Err(StatusCode::NOT_FOUND.into())
+    }
+}
+
+/// Check the caller may read the project and pick the rift to read
+async fn authorize_rift(
+    state: &AppState,
+    headers: &HeaderMap,
+    project_id: ProjectId,
+    rift_id: Option<RiftId>,
+) -> Result<Option<Rift>, StatusCode> {
+    let user_id = project_visibility::optional_user(state, headers)?;
+    let reader = project_visibility::authorize_read(state, user_id, project_id).await?;
+
+    let Some(rift_id) = rift_id else {
+        return project_visibility::default_rift(&state.db, project_id, reader).await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR);
+    };
+
+    let rift = match state.db.get_rift(rift_id).await {
+        Ok(Some(rift)) if rift.project_id == project_id => rift,
+        Ok(_) => return Err(StatusCode::NOT_FOUND),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    match project_visibility::can_read_rift(&state.db, &rift, reader).await {
+        Ok(true) => Ok(Some(rift)),
+        Ok(false) => Err(StatusCode::FORBIDDEN),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// Every checkpoint that changed `path`, oldest first.
+///
+/// Checkpoints snapshot the whole rift, so a file counts as changed when its
+/// content hash differs from the previous checkpoint, and as deleted when it
+/// disappears from the snapshot. Moves are followed back to the file's
+/// earlier paths.
+async fn collect_revisions(state: &AppState, rift_id: RiftId, path: &std::path::Path) -> Result<Vec<(Checkpoint, FileRevision)>> {
+    let mut checkpoints = state.sync.storage.list_checkpoints(rift_id).await?;
+    checkpoints.sort_by_key(|c| c.timestamp);
+
+    // The file's path in each checkpoint, found walking back from the newest
+    let mut paths = vec![path.to_path_buf(); checkpoints.len()];
+    let mut current = path.to_path_buf();
+    for (index, checkpoint) in checkpoints.iter().enumerate().rev() {
+        paths[index] = current.clone();
+        if let Some(ChangeType::Moved { from }) = checkpoint.changes.iter()
+            .find(|c| c.path == current)
+            .map(|c| &c.change_type)
+        {
+            current = from.clone();
+        }
+    }
+
+    let mut usernames: HashMap<UserId, String> = HashMap::new();
+    let mut revisions = Vec::new();
+    let mut previous_hash: Option<String> = None;
+
+    for (checkpoint, path) in checkpoints.into_iter().zip(paths) {
+        let change = checkpoint.changes.iter().find(|c| c.path == path);
+        let moved_from = match change.map(|c| &c.change_type) {
+            Some(ChangeType::Moved { from }) if previous_hash.is_some() => Some(from.clone()),
+            _ => None,
+        };
+
+        let (change_type, content_hash, size) = match (change, &previous_hash, moved_from) {
+            (Some(change), _, Some(from)) => (ChangeType::Moved { from }, change.content_hash.clone(), change.size),
+            (Some(change), Some(hash), None) if &change.content_hash == hash => continue,
+            (Some(change), Some(_), None) => (ChangeType::Modified, change.content_hash.clone(), change.size),
+            (Some(change), None, None) => (ChangeType::Created, change.content_hash.clone(), change.size),
+            (None, Some(_), _) => (ChangeType::Deleted, String::new(), 0),
+            (None, None, _) => continue,
+        };
+        previous_hash = change.map(|c| c.content_hash.clone());
+
+        if let Entry::Vacant(entry) = usernames.entry(checkpoint.author) {
+            let username = state.db.get_user(checkpoint.author).await?
+                .map(|user| user.username)
+                .unwrap_or_else(|| "unknown".to_string());
+            entry.insert(username);
+        }
+
+        let revision = FileRevision {
+            checkpoint_id: checkpoint.id,
+            author: checkpoint.author,
+            username: usernames[&checkpoint.author].clone(),
+            timestamp: checkpoint.timestamp,
+            message: checkpoint.message.clone(),
+            change_type,
+            content_hash,
+            size,
+        };
+        revisions.push((checkpoint, revision));
+    }
+
+    Ok(revisions)
+}
+
+/// List the checkpoints that changed a file, newest first
+async fn file_history(
+    state: &AppState,
+    headers: &HeaderMap,
+    project_id: ProjectId,
+    path: PathBuf,
+    query: FileHistoryQuery,
+) -> Result<Json<ApiResponse<Vec<FileRevision>>>, StatusCode> {
+    let Some(rift) = authorize_rift(state, headers, project_id, query.rift_id).await? else {
+        return Ok(Json(ApiResponse::success(vec![])));
+    };
+
+    let mut revisions: Vec<FileRevision> = collect_revisions(state, rift.id, &path).await
+        .map_err(|e| {
+            error!("Failed to load history of {} in rift {}: {}", path.display(), rift.id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .into_iter()
+        .map(|(_, revision)| revision)
+        .collect();
+
+    revisions.reverse();
+    revisions.truncate(query.limit.unwrap_or(DEFAULT_HISTORY_LIMIT));
+
+    info!("📜 Found {} revisions of {} in rift {}", revisions.len(), path.display(), rift.id);
+    Ok(Json(ApiResponse::success(revisions)))
+}
+
+/// Attribute each line of a file to the checkpoint that introduced it
+async fn file_blame(
+    state: &AppState,
+    headers: &HeaderMap,
+    project_id: ProjectId,
+    path: PathBuf,
+    query: FileHistoryQuery,
+) -> Result<Json<ApiResponse<FileBlame>>, ApiError> {
+    let Some(rift) = authorize_rift(state, headers, project_id, query.rift_id).await? else {
+        return Err(ApiError::not_found("No rift for this project yet"));
+    };
+
+    let mut revisions = collect_revisions(state, rift.id, &path).await
+        .map_err(|e| {
+            error!("Failed to load history of {} in rift {}: {}", path.display(), rift.id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    // Blame as of the requested checkpoint: drop everything after it
+    if let Some(checkpoint_id) = query.checkpoint {
+        let Some(target) = state.sync.storage.load_checkpoint(checkpoint_id).await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .filter(|c| c.rift_id == rift.id)
+        else {
+            return Err(StatusCode::NOT_FOUND.into());
+        };
+        revisions.retain(|(checkpoint, _)| checkpoint.timestamp <= target.timestamp);
+    }
+
+    let Some((last_checkpoint, last_revision)) = revisions.last() else {
+        return Err(ApiError::not_found(format!("{} has no checkpointed history", path.display())));
+    };
+    if matches!(last_revision.change_type, ChangeType::Deleted) {
+        return Err(ApiError::not_found(format!(
+            "{} was deleted in checkpoint {}",
+            path.display(),
+            last_checkpoint.id
+        )));
+    }
+
+    // Walk the revisions oldest first, carrying each line's origin forward
+    let engine = DiffEngine::new();
+    let mut content = String::new();
+    let mut origins: Vec<usize> = Vec::new();
+
+    for (index, (_, revision)) in revisions.iter().enumerate() {
+        let new_content = match revision.change_type {
+            ChangeType::Deleted => String::new(),
+            _ => state.sync.storage.get_content(&revision.content_hash).await
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+                .unwrap_or_default(),
+        };
+        origins = engine.carry_attribution(&content, &origins, &new_content, index);
+        content = new_content;
+    }
+
+    let lines = content
+        .lines()
+        .zip(origins)
+        .enumerate()
+        .map(|(i, (line, origin))| {
+            let revision = &revisions[origin].1;
+            BlameLine {
+                line_number: i + 1,
+                content: line.to_string(),
+                checkpoint_id: revision.checkpoint_id,
+                author: revision.author,
+                username: revision.username.clone(),
+                timestamp: revision.timestamp,
+            }
+        })
+        .collect();
+
+    Ok(Json(ApiResponse::success(FileBlame {
+        path,
+        checkpoint_id: last_checkpoint.id,
+        lines,
+    })))
+}
+
+/// The file before and after a checkpoint that changed it
+async fn file_diff(
+    state: &AppState,
+    headers: &HeaderMap,
+    project_id: ProjectId,
+    path: PathBuf,
+    query: FileHistoryQuery,
+) -> Result<Json<ApiResponse<FileChangeDiff>>, ApiError> {
+    let Some(rift) = authorize_rift(state, headers, project_id, query.rift_id).await? else {
+        return Err(ApiError::not_found("No rift for this project yet"));
+    };
+
+    let revisions = collect_revisions(state, rift.id, &path).await
+        .map_err(|e| {
+            error!("Failed to load history of {} in rift {}: {}", path.display(), rift.id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let index = match query.checkpoint {
+        Some(checkpoint_id) => revisions.iter().position(|(checkpoint, _)| checkpoint.id == checkpoint_id)
+            .ok_or_else(|| ApiError::not_found(format!("Checkpoint {} didn't change {}", checkpoint_id, path.display())))?,
+        None => revisions.len().checked_sub(1)
+            .ok_or_else(|| ApiError::not_found(format!("{} has no checkpointed history", path.display())))?,
+    };
+    let (checkpoint, revision) = &revisions[index];
+    let previous = index.checked_sub(1).map(|i| &revisions[i].1);
+
+    if revision.size > MAX_DIFFED_FILE_SIZE || previous.is_some_and(|p| p.size > MAX_DIFFED_FILE_SIZE) {
+        return Err(ApiError::bad_request(format!("{} is too large to diff", path.display())));
+    }
+
+    let content_of = |revision: Option<&FileRevision>| {
+        let hash = revision
+            .filter(|r| !matches!(r.change_type, ChangeType::Deleted))
+            .map(|r| r.content_hash.clone());
+        async move {
+            match hash {
+                Some(hash) => state.sync.storage.get_content(&hash).await
+                    .map(Option::unwrap_or_default)
+                    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR),
+                None => Ok(String::new()),
+            }
+        }
+    };
+    let old_content = content_of(previous).await?;
+    let new_content = content_of(Some(revision)).await?;
+
+    Ok(Json(ApiResponse::success(FileChangeDiff {
+        path,
+        checkpoint_id: checkpoint.id,
+        previous_checkpoint_id: previous.map(|p| p.checkpoint_id),
+        change_type: revision.change_type.clone(),
+        old_content,
+        new_content,
+    })))
+}
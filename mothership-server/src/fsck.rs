@@ -0,0 +1,103 @@
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::post,
+    Json, Router,
+};
+use mothership_common::protocol::ApiResponse;
+use tracing::{error, info};
+
+use crate::audit::{self, AuditAction, AuditEvent};
+use crate::handlers::require_admin;
+use crate::storage::{IntegrityOptions, IntegrityReport, StorageEngine};
+use crate::AppState;
+
+/// Command that checks storage instead of starting the server
+pub const COMMAND: &str = "fsck";
+
+/// Storage integrity endpoint (admins only)
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/admin/storage/fsck", post(check_storage))
+}
+
+/// Options if the server was started as `mothership-server fsck [--repair] [--prune-orphans]`
+pub fn requested() -> Option<IntegrityOptions> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.iter().any(|arg| arg == COMMAND) {
+        return None;
+    }
+    Some(IntegrityOptions {
+        repair: args.iter().any(|arg| arg == "--repair"),
+        prune_orphans: args.iter().any(|arg| arg == "--prune-orphans"),
+    })
+}
+
+/// Check storage from the command line and print what was found. Returns
+/// whether it was free of damage.
+pub async fn run_command(storage: &StorageEngine, options: IntegrityOptions) -> anyhow::Result<bool> {
+    println!("🔍 Checking storage...");
+    let report = storage.verify_integrity(options).await?;
+    print_report(&report);
+
+    if !report.is_clean() && !options.repair {
+        println!("Run 'mothership-server fsck --repair' with the server stopped to fix what can be fixed.");
+    }
+    Ok(report.is_clean())
+}
+
+fn print_report(report: &IntegrityReport) {
+    println!("Checked {} checkpoints and {} blobs", report.checkpoints_checked, report.blobs_checked);
+
+    for path in &report.unreadable_checkpoints {
+        println!("  ✗ unreadable checkpoint file {}", path.display());
+    }
+    for hash in &report.corrupt_blobs {
+        println!("  ✗ corrupt blob {}", hash);
+    }
+    for missing in &report.missing_blobs {
+        println!("  ✗ missing blob {} (needed by {} checkpoint{})",
+            missing.hash, missing.checkpoints.len(), if missing.checkpoints.len() == 1 { "" } else { "s" });
+    }
+    for checkpoint_id in &report.dangling_parents {
+        println!("  ✗ checkpoint {} has a parent that no longer exists", checkpoint_id);
+    }
+    if !report.orphaned_blobs.is_empty() {
+        println!("  • {} orphaned blob{} nothing refers to", report.orphaned_blobs.len(), if report.orphaned_blobs.len() == 1 { "" } else { "s" });
+    }
+    for repair in &report.repairs {
+        println!("  ✓ {}", repair);
+    }
+
+    if report.is_clean() {
+        println!("✅ No damage found");
+    }
+}
+
+/// Check storage while the server runs; repairs can also restore lost blobs
+/// from live files
+async fn check_storage(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    options: Option<Json<IntegrityOptions>>,
+) -> Result<Json<ApiResponse<IntegrityReport>>, StatusCode> {
+    let admin_id = require_admin(&state, &headers, "check storage").await?;
+    let Json(options) = options.unwrap_or_default();
+
+    let report = state.sync.storage.verify_integrity(options).await.map_err(|e| {
+        error!("Failed to check storage: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    info!("🔍 Storage check: {} checkpoints, {} blobs, {} problems, {} repairs",
+        report.checkpoints_checked,
+        report.blobs_checked,
+        report.unreadable_checkpoints.len() + report.corrupt_blobs.len() + report.missing_blobs.len() + report.dangling_parents.len(),
+        report.repairs.len());
+    if !report.repairs.is_empty() {
+        audit::record(&state.db, AuditEvent::new(AuditAction::StorageRepaired, Some(admin_id))
+            .details(serde_json::json!({ "repairs": report.repairs }))).await;
+    }
+
+    Ok(Json(ApiResponse::success(report)))
+}
@@ -0,0 +1,188 @@
+//! Access for GitHub organizations and teams.
+//!
+//! `[github_access]` lets members of organizations or teams sign in, and
+//! gives them roles. Membership comes from GitHub at each login, and with a
+//! token configured is re-checked in the background: users who left lose
+//! their sessions, and the roles their membership gave them.
+
+use mothership_common::{auth::OAuthProfile, User, UserRole};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::audit::{self, AuditAction, AuditEvent};
+use crate::whitelist;
+use crate::AppState;
+
+/// Record a GitHub login and apply the roles its groups map to
+pub async fn record_login(state: &AppState, user: &User, profile: &OAuthProfile) {
+    let Some(login) = profile.username.as_deref() else {
+        return;
+    };
+    match state.db.set_github_login(user.id, login).await {
+        Ok(granted) => sync_role(state, user, &profile.groups, granted).await,
+        Err(e) => warn!("Failed to record GitHub login of {}: {}", user.username, e),
+    }
+}
+
+/// Give a user the role their groups map to, or take back a role an
+/// earlier membership gave them. SuperAdmins are left alone.
+async fn sync_role(state: &AppState, user: &User, groups: &[String], granted: bool) {
+    if user.role == UserRole::SuperAdmin {
+        return;
+    }
+
+    let (role, granted_now) = match state.config().github_access.role_for(groups) {
+        Some(UserRole::SuperAdmin) => {
+            warn!("Ignoring [github_access] role SuperAdmin for {}: only admins can grant it", user.username);
+            return;
+        }
+        Some(role) => (role, true),
+        None if granted => (UserRole::User, false),
+        None => return,
+    };
+
+    if role != user.role {
+        match state.db.set_user_role(user.id, role.clone()).await {
+            Ok(_) => {
+                info!("👑 GitHub membership changed role of {} from {:?} to {:?}", user.username, user.role, role);
+                audit::record(&state.db, AuditEvent::new(AuditAction::UserRoleChanged, None)
+                    .target(user.id.to_string())
+                    .details(serde_json::json!({
+                        "username": user.username,
+                        "from": user.role,
+                        "to": role,
+                        "source": "github",
+                    }))).await;
+            }
+            Err(e) => {
+                warn!("Failed to change role of {}: {}", user.username, e);
+                return;
+            }
+        }
+    }
+
+    if let Err(e) = state.db.set_github_role_granted(user.id, granted_now).await {
+        warn!("Failed to record GitHub role of {}: {}", user.username, e);
+    }
+}
+
+/// Re-check GitHub membership every `revalidate_interval_minutes`
+pub fn spawn_revalidator(state: AppState) {
+    tokio::spawn(async move {
+        loop {
+            let minutes = state.config().github_access.revalidate_interval_minutes.max(1);
+            tokio::time::sleep(Duration::from_secs(minutes * 60)).await;
+            if let Err(e) = revalidate(&state).await {
+                warn!("Failed to re-check GitHub membership: {}", e);
+            }
+        }
+    });
+}
+
+/// The groups worth asking GitHub about: the configured organizations and
+/// teams, and GitHub groups on the whitelist
+fn checked_groups(state: &AppState) -> Vec<String> {
+    let mut groups = state.config().github_access.groups();
+    if let Some(whitelist) = state.whitelist() {
+        groups.extend(whitelist.groups.iter().filter(|group| group.starts_with("github:")).cloned());
+    }
+    groups.sort();
+    groups.dedup();
+    groups
+}
+
+async fn revalidate(state: &AppState) -> anyhow::Result<()> {
+    let groups = checked_groups(state);
+    if groups.is_empty() {
+        return Ok(());
+    }
+    let Some(token) = state.config().github_access.token() else {
+        return Ok(());
+    };
+
+    let client = reqwest::Client::new();
+    'accounts: for (user_id, login, granted) in state.db.list_github_accounts().await? {
+        let Some(user) = state.db.get_user(user_id).await? else {
+            continue;
+        };
+
+        let mut member_of = Vec::new();
+        for group in &groups {
+            match is_member(&client, &token, group, &login).await {
+                Ok(true) => member_of.push(group.clone()),
+                Ok(false) => {}
+                Err(e) => {
+                    warn!("Failed to check {} membership of {}: {}", group, login, e);
+                    continue 'accounts;
+                }
+            }
+        }
+
+        // Groups GitHub wasn't asked about keep what the last login reported
+        let mut recorded = state.db.get_user_oauth_groups(user_id).await?;
+        recorded.retain(|group| !groups.contains(group));
+        recorded.extend(member_of);
+        state.db.set_user_oauth_groups(user_id, &recorded).await?;
+
+        sync_role(state, &user, &recorded, granted).await;
+
+        if !whitelist::user_allowed(state, &user).await {
+            end_sessions(state, &user).await;
+        }
+    }
+    Ok(())
+}
+
+/// Whether a GitHub user is in an organization (`github:org`) or team
+/// (`github:org/team`)
+async fn is_member(client: &reqwest::Client, token: &str, group: &str, login: &str) -> anyhow::Result<bool> {
+    let name = group.trim_start_matches("github:");
+    let url = match name.split_once('/') {
+        Some((org, team)) => format!("https://api.github.com/orgs/{}/teams/{}/memberships/{}", org, team, login),
+        None => format!("https://api.github.com/orgs/{}/members/{}", name, login),
+    };
+
+    let response = client
+        .get(&url)
+        .bearer_auth(token)
+        .header("User-Agent", "Mothership/1.0")
+        .send()
+        .await?;
+
+    match response.status() {
+        reqwest::StatusCode::NO_CONTENT => Ok(true),
+        reqwest::StatusCode::OK => {
+            let membership: serde_json::Value = response.json().await?;
+            Ok(membership["state"].as_str() == Some("active"))
+        }
+        reqwest::StatusCode::NOT_FOUND => Ok(false),
+        status => Err(anyhow::anyhow!("HTTP {}", status)),
+    }
+}
+
+/// Sign a user out everywhere after their membership ended
+async fn end_sessions(state: &AppState, user: &User) {
+    let sessions = match state.db.revoke_user_sessions(user.id).await {
+        Ok(sessions) => sessions,
+        Err(e) => {
+            warn!("Failed to revoke sessions of {}: {}", user.username, e);
+            return;
+        }
+    };
+    if sessions.is_empty() {
+        return;
+    }
+
+    for session_id in &sessions {
+        state.auth.revoke(session_id.to_string());
+    }
+    info!("🚫 Signed out {} ({} sessions): no longer allowed by GitHub membership", user.username, sessions.len());
+    audit::record(&state.db, AuditEvent::new(AuditAction::UserLoggedOut, None)
+        .target(user.id.to_string())
+        .details(serde_json::json!({
+            "username": user.username,
+            "sessions_revoked": sessions.len(),
+            "reason": "github_membership_ended",
+        }))).await;
+}
+
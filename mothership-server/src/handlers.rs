@@ -1,11 +1,15 @@
 use anyhow::{anyhow, Result};
 use mothership_common::{
-    protocol::{BeamRequest, BeamResponse, ApiResponse},
-    ProjectId, UserId,
+    diff::{DiffEngine, MergeResult},
+    protocol::{
+        BeamRequest, BeamResponse, ApiResponse, CheckpointConflict, CheckpointRevertResult, ErrorCode, FileDiff, FileDiffChange,
+        MergeConflict, RevertCheckpointRequest, RiftMergeResult, SyncMessage,
+    },
+    CheckpointId, ProjectId, RiftRole, UserId,
 };
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use axum::{
-    extract::{Query, State},
+    extract::{Path, Query, State},
     Json,
     http::{HeaderMap, StatusCode},
     response::Json as ResponseJson,
@@ -13,8 +17,11 @@ use axum::{
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 
+use crate::api_error::ApiError;
+use crate::storage::MergedFiles;
 use crate::AppState;
 
 /// Handle beam request - joining/syncing with a project
@@ -40,18 +47,31 @@ pub async fn handle_beam(
 
     // Get or create user's rift for this project
     info!("🔍 DEBUG: Checking for existing rift for user {} in project {}", user_id, project_id);
+    // Replicas only serve rifts replicated from the primary
+    let replica = crate::replication::is_replica(state);
     
     let rift = if let Some(rift_name) = request.rift_name {
         info!("🔍 DEBUG: Specific rift name requested: {}", rift_name);
-        // Check if rift with specific name exists for user
-        match state.db.get_user_rift(project_id, user_id).await {
+        // Join the named rift if it exists and the user may enter it
+        match state.db.get_rift_by_name(project_id, &rift_name).await {
             Ok(Some(existing_rift)) => {
-                info!("✅ Found existing rift: {} for user {} in project: {}", existing_rift.id, user_id, project.name);
+                let role = crate::rift_access::resolve_role(&state.db, &existing_rift, user_id)
+                    .await?
+                    .ok_or(crate::rift_access::RiftAccessDenied)?;
+
+                // Observers watch the rift without becoming collaborators
+                if role != RiftRole::Observer && !replica {
+                    state.db.add_rift_collaborator(existing_rift.id, user_id).await?;
+                }
+                info!("✅ Found existing rift '{}': {} for user {} ({}) in project: {}", rift_name, existing_rift.id, user_id, role.as_str(), project.name);
                 existing_rift
             }
+            Ok(None) if replica => return Err(crate::replication::ReplicaReadOnly.into()),
             Ok(None) => {
-                info!("❌ No existing rift found, creating new rift with name '{}' for user {} in project: {}", rift_name, user_id, project.name);
-                state.db.create_rift(project_id, user_id, Some(rift_name)).await?
+                info!("❌ No rift named '{}' found, creating it for user {} in project: {}", rift_name, user_id, project.name);
+                let rift = state.db.create_rift(project_id, user_id, Some(rift_name)).await?;
+                record_fork_point_at_target(state, &rift).await?;
+                rift
             }
             Err(e) => {
                 error!("🚨 Error checking for existing rift: {}", e);
@@ -66,6 +86,7 @@ pub async fn handle_beam(
                 info!("✅ Found existing default rift: {} for user {} in project: {}", existing_rift.id, user_id, project.name);
                 existing_rift
             }
+            Ok(None) if replica => return Err(crate::replication::ReplicaReadOnly.into()),
             Ok(None) => {
                 info!("❌ No existing default rift found, creating new default rift for user {} in project: {}", user_id, project.name);
                 state.db.create_rift(project_id, user_id, None).await?
@@ -87,19 +108,19 @@ pub async fn handle_beam(
         format!("{}/ws/{}", base_url.trim_end_matches('/'), rift.id)
     } else {
         // Development: use server config
-        let protocol = if state.config.server.host == "127.0.0.1" || state.config.server.host == "localhost" {
+        let protocol = if state.config().server.host == "127.0.0.1" || state.config().server.host == "localhost" {
             "ws"
         } else {
             "wss"
         };
         
-        let host = if state.config.server.host == "0.0.0.0" {
+        let host = if state.config().server.host == "0.0.0.0" {
             "localhost"
         } else {
-            &state.config.server.host
+            &state.config().server.host
         };
         
-        format!("{}://{}:{}/ws/{}", protocol, host, state.config.server.port, rift.id)
+        format!("{}://{}:{}/ws/{}", protocol, host, state.config().server.port, rift.id)
     };
 
     // For now, always require initial sync
@@ -124,19 +145,28 @@ pub struct RiftInfo {
     pub author: String,
     pub file_count: usize,
     pub is_conflict_rift: bool,
+    /// Users connected to the rift right now
+    pub active_collaborators: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateRiftRequest {
+    pub project_id: ProjectId,
     pub name: String,
     pub description: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SwitchRiftRequest {
+    pub project_id: ProjectId,
     pub rift_name: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct RiftProjectQuery {
+    pub project_id: ProjectId,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RiftDiff {
     pub path: PathBuf,
@@ -145,189 +175,265 @@ pub struct RiftDiff {
 
 #[derive(Debug, Deserialize)]
 pub struct RiftDiffQuery {
+    pub project_id: ProjectId,
     pub from: String,
     pub to: String,
 }
 
-// Real rift handlers with proper authentication and database integration
-pub async fn list_rifts(
-    State(state): State<AppState>,
-    headers: HeaderMap,
-) -> Result<ResponseJson<ApiResponse<Vec<RiftInfo>>>, StatusCode> {
-    // Extract user ID from JWT token
-    let auth_header = headers.get("authorization")
-        .and_then(|v| v.to_str().ok())
-        .ok_or(StatusCode::UNAUTHORIZED)?;
+/// Authenticate the request and require membership of the project
+async fn project_member(state: &AppState, headers: &HeaderMap, project_id: ProjectId) -> Result<UserId, StatusCode> {
+    let user_id = authenticate_request(state, headers)?;
 
-    if !auth_header.starts_with("Bearer ") {
-        return Err(StatusCode::UNAUTHORIZED);
+    match state.db.get_project(project_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
+    match state.db.user_has_project_access(user_id, project_id).await {
+        Ok(true) => Ok(user_id),
+        Ok(false) => Err(StatusCode::FORBIDDEN),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
 
-    let token = auth_header.trim_start_matches("Bearer ");
-    let claims = match state.auth.verify_token(token) {
-        Ok(claims) => claims,
-        Err(_) => return Err(StatusCode::UNAUTHORIZED),
+/// Load a rift of the project by name, if the user may see it
+pub(crate) async fn visible_rift(state: &AppState, project_id: ProjectId, user_id: UserId, name: &str) -> Result<Option<mothership_common::Rift>> {
+    let Some(rift) = state.db.get_rift_by_name(project_id, name).await? else {
+        return Ok(None);
     };
-    
-    let user_id = uuid::Uuid::parse_str(&claims.sub)
-        .map_err(|_| StatusCode::UNAUTHORIZED)?;
+    // Private rifts only show up for the people they're shared with
+    match crate::rift_access::resolve_role(&state.db, &rift, user_id).await? {
+        Some(_) => Ok(Some(rift)),
+        None => Ok(None),
+    }
+}
 
-    // Get user's projects
-    let projects = match state.db.get_user_projects(user_id).await {
-        Ok(projects) => projects,
-        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+/// The rift the user last switched to, falling back to the first rift they
+/// joined
+pub(crate) async fn current_rift(state: &AppState, project_id: ProjectId, user_id: UserId) -> Result<Option<mothership_common::Rift>> {
+    if let Some(rift_id) = state.db.get_current_rift(user_id, project_id).await? {
+        if let Some(rift) = state.db.get_rift(rift_id).await?.filter(|rift| rift.is_active) {
+            return Ok(Some(rift));
+        }
+    }
+    state.db.get_user_rift(project_id, user_id).await
+}
+
+async fn rift_info(state: &AppState, rift: mothership_common::Rift) -> Result<RiftInfo> {
+    let file_count = state.sync.storage.get_live_state(rift.id).await.map(|files| files.len()).unwrap_or(0);
+
+    // Rifts created before ownership was recorded are credited to their first collaborator
+    let author_id = match state.db.get_rift_access(rift.id).await? {
+        Some((Some(owner), _)) => Some(owner),
+        _ => rift.collaborators.first().copied(),
+    };
+    let author = match author_id {
+        Some(author_id) => state.db.get_user(author_id).await?.map(|user| user.username),
+        None => None,
     };
 
-    let mut all_rifts = Vec::new();
+    Ok(RiftInfo {
+        id: rift.id,
+        // Conflict rifts are named by the daemon as "conflict-<timestamp>"
+        is_conflict_rift: rift.name.starts_with("conflict-"),
+        name: rift.name,
+        description: None,
+        created_at: rift.created_at,
+        author: author.unwrap_or_else(|| "Unknown".to_string()),
+        file_count,
+        active_collaborators: state.sync.presence.in_rift(rift.id),
+    })
+}
 
-    // For each project, get the rifts
-    for project in projects {
-        let rifts = match state.db.get_project_rifts(project.id).await {
-            Ok(rifts) => rifts,
-            Err(_) => continue, // Skip projects with errors
-        };
+/// Number of lines only in one of the two versions of a file
+fn line_change_count(from: &str, to: &str) -> usize {
+    let mut counts: HashMap<&str, isize> = HashMap::new();
+    for line in from.lines() {
+        *counts.entry(line).or_default() += 1;
+    }
+    for line in to.lines() {
+        *counts.entry(line).or_default() -= 1;
+    }
+    counts.values().map(|count| count.unsigned_abs()).sum()
+}
 
-        for rift in rifts {
-            // Get file count from storage engine
-            let file_count = match state.sync.storage.get_live_state(rift.id).await {
-                Ok(files) => files.len(),
-                Err(_) => 0, // Default to 0 if storage error
-            };
-
-            // Get user info for author
-            let author = match state.db.get_user(rift.collaborators.first().copied().unwrap_or(user_id)).await {
-                Ok(Some(user)) => user.username,
-                _ => "Unknown".to_string(),
-            };
-
-            let rift_info = RiftInfo {
-                id: rift.id,
-                name: rift.name,
-                description: None, // TODO: Add description field to database
-                created_at: rift.created_at,
-                author,
-                file_count,
-                is_conflict_rift: false, // TODO: Add conflict rift detection
-            };
-
-            all_rifts.push(rift_info);
+/// List the project's rifts the user may see
+pub async fn list_rifts(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<RiftProjectQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<RiftInfo>>>, ApiError> {
+    let user_id = project_member(&state, &headers, query.project_id).await?;
+
+    let rifts = state.db.get_project_rifts(query.project_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let mut infos = Vec::new();
+    for rift in rifts {
+        match crate::rift_access::resolve_role(&state.db, &rift, user_id).await {
+            Ok(Some(_)) => {}
+            Ok(None) => continue,
+            Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR.into()),
         }
+        infos.push(rift_info(&state, rift).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?);
     }
 
-    Ok(ResponseJson(ApiResponse::success(all_rifts)))
+    Ok(ResponseJson(ApiResponse::success(infos)))
 }
 
+/// Create a rift from the user's current rift, starting with its files
 pub async fn create_rift(
     State(state): State<AppState>,
     headers: HeaderMap,
     Json(req): Json<CreateRiftRequest>,
-) -> Result<ResponseJson<ApiResponse<Uuid>>, StatusCode> {
-    // Extract user ID from JWT token
-    let auth_header = headers.get("authorization")
-        .and_then(|v| v.to_str().ok())
-        .ok_or(StatusCode::UNAUTHORIZED)?;
+) -> Result<ResponseJson<ApiResponse<Uuid>>, ApiError> {
+    let user_id = project_member(&state, &headers, req.project_id).await?;
+    crate::archive::ensure_writable(&state, req.project_id)?;
 
-    if !auth_header.starts_with("Bearer ") {
-        return Err(StatusCode::UNAUTHORIZED);
-    }
-
-    let token = auth_header.trim_start_matches("Bearer ");
-    let claims = match state.auth.verify_token(token) {
-        Ok(claims) => claims,
-        Err(_) => return Err(StatusCode::UNAUTHORIZED),
-    };
-    
-    let user_id = uuid::Uuid::parse_str(&claims.sub)
-        .map_err(|_| StatusCode::UNAUTHORIZED)?;
-
-    // Validate rift name
     if !is_valid_rift_name(&req.name) {
-        return Err(StatusCode::BAD_REQUEST);
+        return Err(ApiError::bad_request("Invalid rift name. Use only letters, numbers, dashes, and underscores."));
     }
-
-    // For now, create rift in the first project the user has access to
-    // TODO: Add project_id to request or get from context
-    let projects = match state.db.get_user_projects(user_id).await {
-        Ok(projects) => projects,
-        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
-    };
-
-    if projects.is_empty() {
-        return Err(StatusCode::BAD_REQUEST);
+    match state.db.get_rift_by_name(req.project_id, &req.name).await {
+        Ok(Some(_)) => return Err(ApiError::conflict(format!("A rift named '{}' already exists", req.name))),
+        Ok(None) => {}
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR.into()),
     }
 
-    let project_id = projects[0].id;
+    let parent = current_rift(&state, req.project_id, user_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    // Create the rift
-    let rift = match state.db.create_rift(project_id, user_id, Some(req.name.clone())).await {
-        Ok(rift) => rift,
-        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
-    };
+    let rift = state.db.create_rift(req.project_id, user_id, Some(req.name.clone())).await
+        .map_err(|e| {
+            error!("Failed to create rift '{}': {}", req.name, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
 
-    info!("Created rift: {} for user: {} in project: {}", rift.id, user_id, project_id);
+    if let Some(parent) = parent {
+        if let Err(e) = state.db.set_rift_parent(rift.id, Some(parent.id)).await {
+            error!("Failed to record parent of rift {}: {}", rift.id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into());
+        }
+        let files = state.sync.storage.get_live_state(parent.id).await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        state.sync.storage.replace_live_state(rift.id, files).await;
+
+        // Snapshot the fork point, so a merge back knows what both sides started from
+        let message = format!("Forked from rift '{}'", parent.name);
+        let fork_point = state.sync.storage.create_checkpoint(rift.id, user_id, Some(message), false).await
+            .map_err(|e| {
+                error!("Failed to snapshot fork point of rift {}: {}", rift.id, e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+        if let Err(e) = state.db.set_rift_fork_point(rift.id, fork_point.id).await {
+            error!("Failed to record fork point of rift {}: {}", rift.id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into());
+        }
+    }
+
+    info!("✨ Created rift '{}' ({}) for user {} in project {}", rift.name, rift.id, user_id, req.project_id);
 
     Ok(ResponseJson(ApiResponse::success(rift.id)))
 }
 
+/// Make a rift the user's current rift in its project
 pub async fn switch_rift(
     State(state): State<AppState>,
     headers: HeaderMap,
     Json(req): Json<SwitchRiftRequest>,
-) -> Result<ResponseJson<ApiResponse<String>>, StatusCode> {
-    // Extract user ID from JWT token
-    let auth_header = headers.get("authorization")
-        .and_then(|v| v.to_str().ok())
-        .ok_or(StatusCode::UNAUTHORIZED)?;
-
-    if !auth_header.starts_with("Bearer ") {
-        return Err(StatusCode::UNAUTHORIZED);
-    }
+) -> Result<ResponseJson<ApiResponse<String>>, ApiError> {
+    let user_id = project_member(&state, &headers, req.project_id).await?;
 
-    let token = auth_header.trim_start_matches("Bearer ");
-    let claims = match state.auth.verify_token(token) {
-        Ok(claims) => claims,
-        Err(_) => return Err(StatusCode::UNAUTHORIZED),
+    let rift = match state.db.get_rift_by_name(req.project_id, &req.rift_name).await {
+        Ok(Some(rift)) => rift,
+        Ok(None) => return Err(ApiError::not_found(format!("Rift '{}' not found", req.rift_name))),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR.into()),
     };
-    
-    let user_id = uuid::Uuid::parse_str(&claims.sub)
-        .map_err(|_| StatusCode::UNAUTHORIZED)?;
-
-    // Find the rift by name in user's projects
-    let projects = match state.db.get_user_projects(user_id).await {
-        Ok(projects) => projects,
-        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    let role = match crate::rift_access::resolve_role(&state.db, &rift, user_id).await {
+        Ok(Some(role)) => role,
+        Ok(None) => return Err(ApiError::forbidden(format!("You don't have access to rift '{}'", rift.name))),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR.into()),
     };
 
-    let mut target_rift = None;
-    for project in projects {
-        let rifts = match state.db.get_project_rifts(project.id).await {
-            Ok(rifts) => rifts,
-            Err(_) => continue,
-        };
-
-        for rift in rifts {
-            if rift.name == req.rift_name && rift.collaborators.contains(&user_id) {
-                target_rift = Some(rift);
-                break;
-            }
-        }
-        if target_rift.is_some() {
-            break;
+    // Observers watch the rift without becoming collaborators
+    if role != RiftRole::Observer {
+        if let Err(e) = state.db.add_rift_collaborator(rift.id, user_id).await {
+            error!("Failed to add {} to rift {}: {}", user_id, rift.id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into());
         }
     }
+    if let Err(e) = state.db.set_current_rift(user_id, req.project_id, rift.id).await {
+        error!("Failed to record current rift for {}: {}", user_id, e);
+        return Err(StatusCode::INTERNAL_SERVER_ERROR.into());
+    }
 
-    let rift = target_rift.ok_or(StatusCode::NOT_FOUND)?;
-
-    info!("User {} switched to rift: {} in project: {}", user_id, rift.id, rift.project_id);
+    info!("🔄 User {} switched to rift '{}' ({}) in project {}", user_id, rift.name, rift.id, rift.project_id);
 
-    Ok(ResponseJson(ApiResponse::success("Rift switched successfully".to_string())))
+    Ok(ResponseJson(ApiResponse::success(format!("Switched to rift '{}'", rift.name))))
 }
 
+/// The user's current rift in the project
 pub async fn get_current_rift(
     State(state): State<AppState>,
     headers: HeaderMap,
-) -> Result<ResponseJson<ApiResponse<Option<RiftInfo>>>, StatusCode> {
-    // Extract user ID from JWT token
+    Query(query): Query<RiftProjectQuery>,
+) -> Result<ResponseJson<ApiResponse<Option<RiftInfo>>>, ApiError> {
+    let user_id = project_member(&state, &headers, query.project_id).await?;
+
+    let rift = current_rift(&state, query.project_id, user_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let info = match rift {
+        Some(rift) => Some(rift_info(&state, rift).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?),
+        None => None,
+    };
+
+    Ok(ResponseJson(ApiResponse::success(info)))
+}
+
+/// Files that differ between two rifts of the project, with the number of
+/// changed lines in each
+pub async fn get_rift_diffs(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<RiftDiffQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<RiftDiff>>>, ApiError> {
+    let user_id = project_member(&state, &headers, query.project_id).await?;
+
+    let mut rifts = Vec::new();
+    for name in [&query.from, &query.to] {
+        match visible_rift(&state, query.project_id, user_id, name).await {
+            Ok(Some(rift)) => rifts.push(rift),
+            Ok(None) => return Err(ApiError::not_found(format!("Rift '{}' not found", name))),
+            Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR.into()),
+        }
+    }
+
+    let from_files = state.sync.storage.get_live_state(rifts[0].id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let to_files = state.sync.storage.get_live_state(rifts[1].id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let all_paths: HashSet<&PathBuf> = from_files.keys().chain(to_files.keys()).collect();
+    let mut diffs: Vec<RiftDiff> = all_paths
+        .into_iter()
+        .filter_map(|path| {
+            let from = from_files.get(path).map(String::as_str);
+            let to = to_files.get(path).map(String::as_str);
+            if from == to {
+                return None;
+            }
+            Some(RiftDiff {
+                path: path.clone(),
+                change_count: line_change_count(from.unwrap_or_default(), to.unwrap_or_default()),
+            })
+        })
+        .collect();
+    diffs.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(ResponseJson(ApiResponse::success(diffs)))
+}
+
+/// Extract and verify the Bearer token, returning the authenticated user ID
+pub fn authenticate_request(state: &AppState, headers: &HeaderMap) -> Result<UserId, StatusCode> {
     let auth_header = headers.get("authorization")
         .and_then(|v| v.to_str().ok())
         .ok_or(StatusCode::UNAUTHORIZED)?;
@@ -337,146 +443,647 @@ pub async fn get_current_rift(
     }
 
     let token = auth_header.trim_start_matches("Bearer ");
-    let claims = match state.auth.verify_token(token) {
-        Ok(claims) => claims,
-        Err(_) => return Err(StatusCode::UNAUTHORIZED),
-    };
-    
-    let user_id = uuid::Uuid::parse_str(&claims.sub)
+    let claims = state.auth.verify_token(token)
         .map_err(|_| StatusCode::UNAUTHORIZED)?;
 
-    // Get user's projects and find the first one with a rift
-    let projects = match state.db.get_user_projects(user_id).await {
-        Ok(projects) => projects,
-        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
-    };
+    Uuid::parse_str(&claims.sub).map_err(|_| StatusCode::UNAUTHORIZED)
+}
+
+/// Authenticate the request and require an admin role. `action` says what a
+/// turned-away user attempted, for the log.
+pub async fn require_admin(state: &AppState, headers: &HeaderMap, action: &str) -> Result<UserId, StatusCode> {
+    let user_id = authenticate_request(state, headers)?;
 
-    for project in projects {
-        if let Ok(Some(rift)) = state.db.get_user_rift(project.id, user_id).await {
-            // Get file count from storage engine
-            let file_count = match state.sync.storage.get_live_state(rift.id).await {
-                Ok(files) => files.len(),
-                Err(_) => 0,
-            };
-
-            // Get user info for author
-            let author = match state.db.get_user(rift.collaborators.first().copied().unwrap_or(user_id)).await {
-                Ok(Some(user)) => user.username,
-                _ => "Unknown".to_string(),
-            };
-
-            let rift_info = RiftInfo {
-                id: rift.id,
-                name: rift.name,
-                description: None,
-                created_at: rift.created_at,
-                author,
-                file_count,
-                is_conflict_rift: false,
-            };
-
-            return Ok(ResponseJson(ApiResponse::success(Some(rift_info))));
+    match state.db.user_is_admin(user_id).await {
+        Ok(true) => Ok(user_id),
+        Ok(false) => {
+            warn!("🔒 Non-admin user {} attempted to {}", user_id, action);
+            Err(StatusCode::FORBIDDEN)
         }
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
-
-    Ok(ResponseJson(ApiResponse::success(None)))
 }
 
-pub async fn get_rift_diffs(
+/// Merge a rift back into its parent rift (or the project's main rift)
+pub async fn merge_rift(
     State(state): State<AppState>,
     headers: HeaderMap,
-    Query(query): Query<RiftDiffQuery>,
-) -> Result<ResponseJson<ApiResponse<Vec<RiftDiff>>>, StatusCode> {
-    // Extract user ID from JWT token
-    let auth_header = headers.get("authorization")
-        .and_then(|v| v.to_str().ok())
-        .ok_or(StatusCode::UNAUTHORIZED)?;
-
-    if !auth_header.starts_with("Bearer ") {
-        return Err(StatusCode::UNAUTHORIZED);
+    Path((project_id, rift_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<RiftMergeResult>>, ApiError> {
+    let user_id = authenticate_request(&state, &headers)?;
+
+    match state.db.user_has_project_access(user_id, project_id).await {
+        Ok(true) => {}
+        Ok(false) => return Err(StatusCode::FORBIDDEN.into()),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR.into()),
     }
+    crate::archive::ensure_writable(&state, project_id)?;
 
-    let token = auth_header.trim_start_matches("Bearer ");
-    let claims = match state.auth.verify_token(token) {
-        Ok(claims) => claims,
-        Err(_) => return Err(StatusCode::UNAUTHORIZED),
+    let source = match state.db.get_rift(rift_id).await {
+        Ok(Some(rift)) if rift.project_id == project_id => rift,
+        Ok(_) => return Err(StatusCode::NOT_FOUND.into()),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR.into()),
     };
-    
-    let user_id = uuid::Uuid::parse_str(&claims.sub)
-        .map_err(|_| StatusCode::UNAUTHORIZED)?;
 
-    // Find the rifts by name in user's projects
-    let projects = match state.db.get_user_projects(user_id).await {
-        Ok(projects) => projects,
-        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    let target = match resolve_merge_target(&state, &source).await {
+        Ok(Some(target)) => target,
+        Ok(None) => return Err(StatusCode::NOT_FOUND.into()),
+        Err(e) => {
+            error!("Failed to resolve merge target for rift {}: {}", rift_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into());
+        }
     };
 
-    let mut from_rift = None;
-    let mut to_rift = None;
+    if target.id == source.id {
+        return Err(StatusCode::BAD_REQUEST.into());
+    }
 
-    for project in projects {
-        let rifts = match state.db.get_project_rifts(project.id).await {
-            Ok(rifts) => rifts,
-            Err(_) => continue,
-        };
+    merge_rifts(&state, user_id, &source, &target).await
+}
 
-        for rift in rifts {
-            if rift.collaborators.contains(&user_id) {
-                if rift.name == query.from {
-                    from_rift = Some(rift.clone());
-                }
-                if rift.name == query.to {
-                    to_rift = Some(rift.clone());
+/// Merge one rift into another, once status checks and any review request
+/// allow it. The caller has checked the user may write to the project; the
+/// user must also be able to edit both rifts.
+pub async fn merge_rifts(
+    state: &AppState,
+    user_id: UserId,
+    source: &mothership_common::Rift,
+    target: &mothership_common::Rift,
+) -> Result<ResponseJson<ApiResponse<RiftMergeResult>>, ApiError> {
+    crate::rift_access::require_access(&state.db, source, user_id, true).await?;
+    crate::rift_access::require_access(&state.db, target, user_id, true).await?;
+    match crate::statuses::merge_blocked_reason(state, source).await {
+        Ok(None) => {}
+        Ok(Some(reason)) => {
+            warn!("🚦 Merge of rift {} blocked: {}", source.id, reason);
+            return Err(ApiError::conflict(format!("Merge blocked: {}", reason)));
+        }
+        Err(e) => {
+            error!("Failed to check statuses for rift {}: {}", source.id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into());
+        }
+    }
+    match crate::reviews::merge_blocked_reason(state, source, target).await {
+        Ok(None) => {}
+        Ok(Some(reason)) => {
+            warn!("👀 Merge of rift {} blocked: {}", source.id, reason);
+            return Err(ApiError::conflict(format!("Merge blocked: {}", reason)));
+        }
+        Err(e) => {
+            error!("Failed to check review requests for rift {}: {}", source.id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into());
+        }
+    }
+
+    info!("🔀 Merging rift '{}' ({}) into '{}' ({})", source.name, source.id, target.name, target.id);
+
+    let theirs = state.sync.storage.get_live_state(source.id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let ours = state.sync.storage.get_live_state(target.id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let base = merge_base_files(state, source, target).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let mut all_paths = HashSet::new();
+    all_paths.extend(base.keys().cloned());
+    all_paths.extend(ours.keys().cloned());
+    all_paths.extend(theirs.keys().cloned());
+
+    let diff_engine = DiffEngine::new();
+    let mut updates: HashMap<PathBuf, Option<String>> = HashMap::new();
+    let mut conflicts = Vec::new();
+
+    for path in all_paths {
+        let base_content = base.get(&path);
+        let our_content = ours.get(&path);
+        let their_content = theirs.get(&path);
+
+        // Unchanged on the source side, or both sides already agree
+        if their_content == base_content || our_content == their_content {
+            continue;
+        }
+
+        // Only the source side changed - take it as-is
+        if our_content == base_content {
+            updates.insert(path, their_content.cloned());
+            continue;
+        }
+
+        match (our_content, their_content) {
+            (Some(our_content), Some(their_content)) => {
+                let base_content = base_content.map(String::as_str).unwrap_or("");
+                match diff_engine.three_way_merge(base_content, our_content, their_content) {
+                    MergeResult::Clean(content) => {
+                        updates.insert(path, Some(content));
+                    }
+                    MergeResult::Conflicted { content, conflicts: count } => {
+                        conflicts.push(MergeConflict {
+                            path,
+                            conflicts: count,
+                            content: Some(content),
+                        });
+                    }
                 }
             }
+            // One side modified the file while the other deleted it
+            _ => conflicts.push(MergeConflict {
+                path,
+                conflicts: 0,
+                content: None,
+            }),
+        }
+    }
+
+    if !conflicts.is_empty() {
+        warn!("⚠️ Merge of rift {} into {} has {} conflicting files", source.id, target.id, conflicts.len());
+        return Ok(ResponseJson(ApiResponse {
+            success: false,
+            data: Some(RiftMergeResult {
+                merged: false,
+                source_rift_id: source.id,
+                target_rift_id: target.id,
+                checkpoint_id: None,
+                merged_files: 0,
+                conflicts,
+            }),
+            error: Some("Merge has conflicts that must be resolved manually".to_string()),
+            code: Some(ErrorCode::Conflict),
+            message: None,
+        }));
+    }
+
+    let mut merged = MergedFiles { contents: updates.clone(), metadata: HashMap::new() };
+    for (path, content) in &updates {
+        if content.is_some() {
+            merged.metadata.insert(path.clone(), state.sync.storage.get_file_metadata(source.id, path).await);
         }
     }
 
-    let from_rift = from_rift.ok_or(StatusCode::NOT_FOUND)?;
-    let to_rift = to_rift.ok_or(StatusCode::NOT_FOUND)?;
+    // The checkpoint comes first: if it is refused, the target is left untouched
+    let message = format!("Merge rift '{}' into '{}'", source.name, target.name);
+    let checkpoint = state.sync.storage
+        .create_merge_checkpoint(target.id, user_id, Some(message.clone()), source.id, merged.clone())
+        .await
+        .map_err(|e| {
+            error!("Failed to create merge checkpoint: {}", e);
+            if e.is::<CheckpointConflict>() { StatusCode::CONFLICT } else { StatusCode::INTERNAL_SERVER_ERROR }
+        })?;
+
+    // Apply merged files to the target rift's live state
+    let mut diff_changes = Vec::new();
+    for (path, content) in &updates {
+        let result = match content {
+            Some(content) => state.sync.storage.update_live_state(target.id, path.clone(), content.clone()).await,
+            None => state.sync.storage.remove_live_file(target.id, path).await,
+        };
+        if let Err(e) = result {
+            error!("Failed to apply merged file {}: {}", path.display(), e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into());
+        }
+        let metadata = merged.metadata.remove(path).unwrap_or_default();
+        state.sync.storage.update_file_metadata(target.id, path, &metadata).await;
+
+        diff_changes.push(FileDiffChange {
+            path: path.clone(),
+            diff: match content {
+                Some(content) => FileDiff::FullContent(content.clone()),
+                None => FileDiff::Deleted,
+            },
+            file_size: content.as_ref().map(|c| c.len() as u64).unwrap_or(0),
+            metadata,
+        });
+    }
 
-    // Get file states from storage engine
-    let from_files = match state.sync.storage.get_live_state(from_rift.id).await {
-        Ok(files) => files,
-        Err(_) => return Ok(ResponseJson(ApiResponse::success(vec![]))),
+    // Later merges of the source only bring what changed after this one
+    if let Err(e) = state.db.set_rift_fork_point(source.id, checkpoint.id).await {
+        error!("Failed to move fork point of rift {} to merge checkpoint {}: {}", source.id, checkpoint.id, e);
+    }
+
+    // Notify collaborators on the target rift
+    let channel = format!("rift_{}", target.id);
+    if !diff_changes.is_empty() {
+        state.sync.broadcast(channel.clone(), SyncMessage::RiftDiffUpdate {
+            rift_id: target.id,
+            diff_changes,
+            author: user_id,
+            timestamp: Utc::now(),
+            compressed: false,
+        });
+    }
+    state.sync.broadcast(channel, SyncMessage::CheckpointCreated {
+        rift_id: target.id,
+        checkpoint_id: checkpoint.id,
+        author: user_id,
+        timestamp: checkpoint.timestamp,
+        message: Some(message),
+    });
+
+    info!("✅ Merged {} files from rift {} into {} (checkpoint {})", updates.len(), source.id, target.id, checkpoint.id);
+    crate::git_export::schedule_mirror_push(state.clone(), target.id);
+    crate::reviews::mark_merged(state, user_id, source, target, checkpoint.id).await;
+
+    Ok(ResponseJson(ApiResponse::success(RiftMergeResult {
+        merged: true,
+        source_rift_id: source.id,
+        target_rift_id: target.id,
+        checkpoint_id: Some(checkpoint.id),
+        merged_files: updates.len(),
+        conflicts: Vec::new(),
+    })))
+}
+
+/// Find the rift a merge should land in: the parent rift, falling back to "main"
+pub(crate) async fn resolve_merge_target(state: &AppState, source: &mothership_common::Rift) -> Result<Option<mothership_common::Rift>> {
+    if let Some(parent_id) = source.parent_rift {
+        return state.db.get_rift(parent_id).await;
+    }
+
+    let rifts = state.db.get_project_rifts(source.project_id).await?;
+    Ok(rifts.into_iter().find(|rift| rift.name == "main"))
+}
+
+/// Record where a rift that starts from the client's own files forked: its
+/// merge target's latest checkpoint, which is what those files came from
+async fn record_fork_point_at_target(state: &AppState, rift: &mothership_common::Rift) -> Result<()> {
+    let Some(target) = resolve_merge_target(state, rift).await? else {
+        return Ok(());
     };
+    if target.id == rift.id {
+        return Ok(());
+    }
+    if let Some(latest) = state.sync.storage.latest_checkpoint(target.id).await {
+        state.db.set_rift_fork_point(rift.id, latest.id).await?;
+    }
+    Ok(())
+}
+
+/// Files both rifts started from: the source rift's recorded fork point. Rifts
+/// forked before fork points were recorded fall back to the target's latest
+/// checkpoint from before the source rift was created, or to no files at all,
+/// so neither side's changes are mistaken for the starting point.
+async fn merge_base_files(
+    state: &AppState,
+    source: &mothership_common::Rift,
+    target: &mothership_common::Rift,
+) -> Result<HashMap<PathBuf, String>> {
+    if let Some(fork_point) = state.db.get_rift_fork_point(source.id).await? {
+        return state.sync.storage.get_checkpoint_files(fork_point).await;
+    }
 
-    let to_files = match state.sync.storage.get_live_state(to_rift.id).await {
-        Ok(files) => files,
-        Err(_) => return Ok(ResponseJson(ApiResponse::success(vec![]))),
+    let checkpoints = state.sync.storage.list_checkpoints(target.id).await?;
+    let forked_from = checkpoints
+        .iter()
+        .filter(|cp| cp.timestamp <= source.created_at)
+        .max_by_key(|cp| cp.timestamp);
+    match forked_from {
+        Some(checkpoint) => state.sync.storage.get_checkpoint_files(checkpoint.id).await,
+        None => Ok(HashMap::new()),
+    }
+}
+
+/// Undo a checkpoint's changes with a new checkpoint, without rewriting history.
+///
+/// The inverse of the checkpoint is applied to the rift's current files as a
+/// three-way merge (base: the checkpoint, theirs: the checkpoint before it), so
+/// later edits elsewhere in the same files are kept. If later edits overlap
+/// the reverted lines nothing is changed and the files are reported.
+pub async fn revert_checkpoint(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((project_id, checkpoint_id)): Path<(ProjectId, CheckpointId)>,
+    request: Option<Json<RevertCheckpointRequest>>,
+) -> Result<ResponseJson<ApiResponse<CheckpointRevertResult>>, ApiError> {
+    let user_id = authenticate_request(&state, &headers)?;
+
+    match state.db.user_has_project_access(user_id, project_id).await {
+        Ok(true) => {}
+        Ok(false) => return Err(StatusCode::FORBIDDEN.into()),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR.into()),
+    }
+    crate::archive::ensure_writable(&state, project_id)?;
+
+    let checkpoint = match state.sync.storage.load_checkpoint(checkpoint_id).await {
+        Ok(Some(checkpoint)) => checkpoint,
+        Ok(None) => return Err(StatusCode::NOT_FOUND.into()),
+        Err(e) => {
+            error!("Failed to load checkpoint {}: {}", checkpoint_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into());
+        }
     };
+    match state.db.get_rift(checkpoint.rift_id).await {
+        Ok(Some(rift)) if rift.project_id == project_id => {}
+        Ok(_) => return Err(StatusCode::NOT_FOUND.into()),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR.into()),
+    }
 
-    // Calculate differences
-    let mut diffs = Vec::new();
-    let mut all_paths = std::collections::HashSet::new();
-    
-    for path in from_files.keys() {
-        all_paths.insert(path.clone());
+    let target = match request.and_then(|Json(request)| request.rift_id) {
+        Some(rift_id) => state.db.get_rift(rift_id).await,
+        None => state.db.get_user_rift(project_id, user_id).await,
+    };
+    let target = match target {
+        Ok(Some(rift)) if rift.project_id == project_id => rift,
+        Ok(_) => return Err(StatusCode::NOT_FOUND.into()),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR.into()),
+    };
+    match crate::rift_access::resolve_role(&state.db, &target, user_id).await {
+        Ok(Some(RiftRole::Owner | RiftRole::Editor)) => {}
+        Ok(_) => return Err(StatusCode::FORBIDDEN.into()),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR.into()),
     }
-    for path in to_files.keys() {
-        all_paths.insert(path.clone());
+
+    // The checkpoint before it on its own rift is what the files go back to
+    let parent = match checkpoint.parent {
+        Some(parent) => Some(parent),
+        None => {
+            let mut history = state.sync.storage.list_checkpoints(checkpoint.rift_id).await
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+            history.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then(a.id.cmp(&b.id)));
+            history.iter().take_while(|c| c.id != checkpoint.id).last().map(|c| c.id)
+        }
+    };
+    let Some(parent) = parent else {
+        return Err(ApiError::bad_request(
+            "This is the rift's first checkpoint, so there is nothing to revert it to; use restore instead",
+        ));
+    };
+
+    info!("↩️ Reverting checkpoint {} in rift '{}' ({})", checkpoint.id, target.name, target.id);
+
+    let base = state.sync.storage.get_checkpoint_files(checkpoint.id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let theirs = state.sync.storage.get_checkpoint_files(parent).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let ours = state.sync.storage.get_live_state(target.id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    // Only files the checkpoint changed are touched
+    let changed: HashSet<PathBuf> = base.keys().chain(theirs.keys())
+        .filter(|path| base.get(*path) != theirs.get(*path))
+        .cloned()
+        .collect();
+
+    let diff_engine = DiffEngine::new();
+    let mut updates: HashMap<PathBuf, Option<String>> = HashMap::new();
+    let mut conflicts = Vec::new();
+
+    for path in changed {
+        let base_content = base.get(&path);
+        let our_content = ours.get(&path);
+        let their_content = theirs.get(&path);
+
+        // Already undone
+        if our_content == their_content {
+            continue;
+        }
+
+        // Untouched since the checkpoint - put it straight back
+        if our_content == base_content {
+            updates.insert(path, their_content.cloned());
+            continue;
+        }
+
+        match (our_content, their_content) {
+            (Some(our_content), Some(their_content)) => {
+                let base_content = base_content.map(String::as_str).unwrap_or("");
+                match diff_engine.three_way_merge(base_content, our_content, their_content) {
+                    MergeResult::Clean(content) => {
+                        updates.insert(path, Some(content));
+                    }
+                    MergeResult::Conflicted { .. } => conflicts.push(path),
+                }
+            }
+            // Changed again since the checkpoint on one side and deleted on the other
+            _ => conflicts.push(path),
+        }
     }
 
-    for path in all_paths {
-        let from_content = from_files.get(&path);
-        let to_content = to_files.get(&path);
+    if !conflicts.is_empty() {
+        conflicts.sort();
+        warn!("⚠️ Revert of checkpoint {} has {} conflicting files", checkpoint.id, conflicts.len());
+        return Ok(ResponseJson(ApiResponse {
+            success: false,
+            data: Some(CheckpointRevertResult {
+                reverted_checkpoint: checkpoint.id,
+                rift_id: target.id,
+                checkpoint_id: None,
+                reverted_files: 0,
+                conflicts,
+            }),
+            error: Some("Files changed since the checkpoint conflict with the revert".to_string()),
+            code: Some(ErrorCode::Conflict),
+            message: None,
+        }));
+    }
 
-        if from_content != to_content {
-            // Simple change count - in reality this would be more sophisticated
-            let change_count = 1; // Placeholder
-            diffs.push(RiftDiff {
-                path,
-                change_count,
-            });
+    if updates.is_empty() {
+        return Ok(ResponseJson(ApiResponse::success(CheckpointRevertResult {
+            reverted_checkpoint: checkpoint.id,
+            rift_id: target.id,
+            checkpoint_id: None,
+            reverted_files: 0,
+            conflicts: Vec::new(),
+        })));
+    }
+
+    // Apply the reverted files to the rift's live state
+    let mut diff_changes = Vec::new();
+    for (path, content) in &updates {
+        let result = match content {
+            Some(content) => state.sync.storage.update_live_state(target.id, path.clone(), content.clone()).await,
+            None => state.sync.storage.remove_live_file(target.id, path).await,
+        };
+        if let Err(e) = result {
+            error!("Failed to apply reverted file {}: {}", path.display(), e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into());
         }
+
+        diff_changes.push(FileDiffChange {
+            path: path.clone(),
+            diff: match content {
+                Some(content) => FileDiff::FullContent(content.clone()),
+                None => FileDiff::Deleted,
+            },
+            file_size: content.as_ref().map(|c| c.len() as u64).unwrap_or(0),
+            metadata: state.sync.storage.get_file_metadata(target.id, path).await,
+        });
     }
 
-    Ok(ResponseJson(ApiResponse::success(diffs)))
+    let message = format!(
+        "Revert \"{}\" ({})",
+        checkpoint.message.as_deref().unwrap_or("no message"),
+        &checkpoint.id.to_string()[..8]
+    );
+    let revert = state.sync.storage
+        .create_checkpoint(target.id, user_id, Some(message.clone()), false)
+        .await
+        .map_err(|e| {
+            error!("Failed to create revert checkpoint: {}", e);
+            if e.is::<CheckpointConflict>() { StatusCode::CONFLICT } else { StatusCode::INTERNAL_SERVER_ERROR }
+        })?;
+
+    // Collaborators and the caller's daemon pick the reverted files up from the rift channel
+    let channel = format!("rift_{}", target.id);
+    state.sync.broadcast(channel.clone(), SyncMessage::RiftDiffUpdate {
+        rift_id: target.id,
+        diff_changes,
+        author: user_id,
+        timestamp: Utc::now(),
+        compressed: false,
+    });
+    state.sync.broadcast(channel, SyncMessage::CheckpointCreated {
+        rift_id: target.id,
+        checkpoint_id: revert.id,
+        author: user_id,
+        timestamp: revert.timestamp,
+        message: Some(message),
+    });
+
+    crate::audit::record(&state.db, crate::audit::AuditEvent::new(crate::audit::AuditAction::CheckpointReverted, Some(user_id))
+        .project(project_id)
+        .target(checkpoint.id.to_string())
+        .details(serde_json::json!({ "rift_id": target.id, "checkpoint_id": revert.id, "file_count": updates.len() }))).await;
+    info!("✅ Reverted {} files from checkpoint {} (checkpoint {})", updates.len(), checkpoint.id, revert.id);
+    crate::git_export::schedule_mirror_push(state.clone(), target.id);
+
+    Ok(ResponseJson(ApiResponse::success(CheckpointRevertResult {
+        reverted_checkpoint: checkpoint.id,
+        rift_id: target.id,
+        checkpoint_id: Some(revert.id),
+        reverted_files: updates.len(),
+        conflicts: Vec::new(),
+    })))
+}
+
+/// Files a rift changed that its merge target doesn't have yet
+pub(crate) async fn unmerged_paths(state: &AppState, source: &mothership_common::Rift) -> Result<Vec<PathBuf>> {
+    let Some(target) = resolve_merge_target(state, source).await?.filter(|target| target.id != source.id) else {
+        // Nothing to merge into, so nothing can be lost
+        return Ok(Vec::new());
+    };
+
+    let theirs = state.sync.storage.get_live_state(source.id).await?;
+    let ours = state.sync.storage.get_live_state(target.id).await?;
+    let base = merge_base_files(state, source, &target).await?;
+
+    let mut paths: Vec<PathBuf> = base.keys().chain(theirs.keys())
+        .filter(|path| theirs.get(*path) != base.get(*path) && theirs.get(*path) != ours.get(*path))
+        .cloned()
+        .collect();
+    paths.sort();
+    paths.dedup();
+    Ok(paths)
 }
 
-fn is_valid_rift_name(name: &str) -> bool {
+pub(crate) fn is_valid_rift_name(name: &str) -> bool {
     let valid_chars = name.chars().all(|c| {
         c.is_alphanumeric() || c == '-' || c == '_'
     });
     valid_chars && !name.is_empty() && name.len() <= 64
-} 
\ No newline at end of file
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_client::TestClient;
+    use mothership_common::Project;
+
+    #[tokio::test]
+    async fn test_merge_keeps_checkpointed_rift_edits_and_later_target_files() {
+        let mut client = TestClient::new().await;
+        client.sign_in("forker").await;
+        let project: Project = client.post("/gateway/create", &serde_json::json!({
+            "name": "fork-point",
+            "description": "",
+            "project_path": "/tmp/fork-point",
+        })).await.data();
+        let beam: BeamResponse = client.post(&format!("/projects/{}/beam", project.id), &serde_json::json!({
+            "project_id": project.id,
+            "rift_name": null,
+            "force_sync": false,
+        })).await.data();
+        let storage = client.state.sync.storage.clone();
+        storage.update_live_state(beam.rift_id, "app.rs".into(), "fn main() {}\n".to_string()).await.unwrap();
+
+        let feature: Uuid = client.post("/api/rifts", &CreateRiftRequest {
+            project_id: project.id,
+            name: "feature".to_string(),
+            description: None,
+        }).await.data();
+
+        // The rift's edit is checkpointed before the merge
+        storage.update_live_state(feature, "app.rs".into(), "fn main() { run() }\n".to_string()).await.unwrap();
+        storage.create_checkpoint(feature, Uuid::new_v4(), None, true).await.unwrap();
+        // The target gains a file after the fork
+        storage.update_live_state(beam.rift_id, "notes.md".into(), "# Notes\n".to_string()).await.unwrap();
+
+        let result: RiftMergeResult = client.post(&format!("/projects/{}/rifts/{}/merge", project.id, feature), &()).await.data();
+        assert!(result.merged);
+        let merged = storage.get_live_state(beam.rift_id).await.unwrap();
+        assert_eq!(merged.get(&PathBuf::from("app.rs")).map(String::as_str), Some("fn main() { run() }\n"));
+        assert_eq!(merged.get(&PathBuf::from("notes.md")).map(String::as_str), Some("# Notes\n"));
+    }
+
+    #[tokio::test]
+    async fn test_merge_moves_the_fork_point_to_the_merge_checkpoint() {
+        let mut client = TestClient::new().await;
+        client.sign_in("forker").await;
+        let project: Project = client.post("/gateway/create", &serde_json::json!({
+            "name": "repeat-merge",
+            "description": "",
+            "project_path": "/tmp/repeat-merge",
+        })).await.data();
+        let beam: BeamResponse = client.post(&format!("/projects/{}/beam", project.id), &serde_json::json!({
+            "project_id": project.id,
+            "rift_name": null,
+            "force_sync": false,
+        })).await.data();
+        let storage = client.state.sync.storage.clone();
+        storage.update_live_state(beam.rift_id, "app.rs".into(), "fn main() {}\n".to_string()).await.unwrap();
+        let feature: Uuid = client.post("/api/rifts", &CreateRiftRequest {
+            project_id: project.id,
+            name: "feature".to_string(),
+            description: None,
+        }).await.data();
+        storage.update_live_state(feature, "app.rs".into(), "fn main() { run() }\n".to_string()).await.unwrap();
+
+        let merge = format!("/projects/{}/rifts/{}/merge", project.id, feature);
+        let first: RiftMergeResult = client.post(&merge, &()).await.data();
+        let checkpoint = first.checkpoint_id.unwrap();
+        let recorded = storage.get_checkpoint_files(checkpoint).await.unwrap();
+        assert_eq!(recorded.get(&PathBuf::from("app.rs")).map(String::as_str), Some("fn main() { run() }\n"));
+        assert_eq!(client.state.db.get_rift_fork_point(feature).await.unwrap(), Some(checkpoint));
+
+        // The target drops the file the rift already merged; merging again must not bring it back
+        storage.remove_live_file(beam.rift_id, &PathBuf::from("app.rs")).await.unwrap();
+        storage.update_live_state(feature, "lib.rs".into(), "pub fn run() {}\n".to_string()).await.unwrap();
+        let second: RiftMergeResult = client.post(&merge, &()).await.data();
+        assert!(second.merged && second.conflicts.is_empty());
+        let merged = storage.get_live_state(beam.rift_id).await.unwrap();
+        assert!(!merged.contains_key(&PathBuf::from("app.rs")));
+        assert!(merged.contains_key(&PathBuf::from("lib.rs")));
+    }
+
+    #[tokio::test]
+    async fn test_rift_beamed_by_name_forks_from_the_latest_main_checkpoint() {
+        let mut client = TestClient::new().await;
+        let user = client.sign_in("beamer").await;
+        let project: Project = client.post("/gateway/create", &serde_json::json!({
+            "name": "named-beam",
+            "description": "",
+            "project_path": "/tmp/named-beam",
+        })).await.data();
+        let beam = |rift_name: Option<&str>| serde_json::json!({
+            "project_id": project.id,
+            "rift_name": rift_name,
+            "force_sync": false,
+        });
+        let main: BeamResponse = client.post(&format!("/projects/{}/beam", project.id), &beam(Some("main"))).await.data();
+        assert_eq!(client.state.db.get_rift_fork_point(main.rift_id).await.unwrap(), None);
+
+        let storage = client.state.sync.storage.clone();
+        storage.update_live_state(main.rift_id, "app.rs".into(), "fn main() {}\n".to_string()).await.unwrap();
+        let checkpoint = storage.create_checkpoint(main.rift_id, user.id, None, false).await.unwrap();
+
+        let named: BeamResponse = client.post(&format!("/projects/{}/beam", project.id), &beam(Some("experiment"))).await.data();
+        assert_eq!(client.state.db.get_rift_fork_point(named.rift_id).await.unwrap(), Some(checkpoint.id));
+    }
+}
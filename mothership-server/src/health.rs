@@ -0,0 +1,98 @@
+//! Readiness probe and server metrics.
+//!
+//! `/health` only says the process is up. `/health/ready` checks that the
+//! database answers and storage can be written, each on its own, so load
+//! balancers and orchestrators stop sending traffic to an instance that
+//! can't serve it without restarting it. `/metrics` shows how busy the
+//! database pool is, to spot saturation before queries start timing out.
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::get,
+    Json, Router,
+};
+use mothership_common::protocol::ApiResponse;
+use serde::Serialize;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+use crate::database::PoolStats;
+use crate::handlers::require_admin;
+use crate::AppState;
+
+/// How long each readiness check may take before it counts as failed
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Readiness and metrics endpoints
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/health/ready", get(readiness))
+        .route("/metrics", get(metrics))
+}
+
+/// Outcome of checking one dependency
+#[derive(Debug, Serialize)]
+pub struct DependencyCheck {
+    pub ok: bool,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Readiness {
+    pub ready: bool,
+    pub database: DependencyCheck,
+    pub storage: DependencyCheck,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ServerMetrics {
+    pub database_pool: PoolStats,
+    /// Rifts with working files in memory
+    pub live_rifts: usize,
+}
+
+/// Run a check, timing it and giving up after `CHECK_TIMEOUT`
+async fn check(name: &str, probe: impl Future<Output = anyhow::Result<()>>) -> DependencyCheck {
+    let started = Instant::now();
+    let error = match tokio::time::timeout(CHECK_TIMEOUT, probe).await {
+        Ok(Ok(())) => None,
+        Ok(Err(e)) => Some(e.to_string()),
+        Err(_) => Some(format!("No answer within {}s", CHECK_TIMEOUT.as_secs())),
+    };
+    if let Some(error) = &error {
+        warn!("🩺 Readiness check of {} failed: {}", name, error);
+    }
+    DependencyCheck {
+        ok: error.is_none(),
+        latency_ms: started.elapsed().as_millis() as u64,
+        error,
+    }
+}
+
+/// Whether the server can serve requests: 200 if so, 503 if the database or
+/// storage is unreachable
+async fn readiness(State(state): State<AppState>) -> (StatusCode, Json<ApiResponse<Readiness>>) {
+    let (database, storage) = tokio::join!(
+        check("the database", state.db.ping()),
+        check("storage", state.sync.storage.probe()),
+    );
+    let ready = database.ok && storage.ok;
+    let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(ApiResponse::success(Readiness { ready, database, storage })))
+}
+
+/// Database pool usage and in-memory state (admins only)
+async fn metrics(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<ApiResponse<ServerMetrics>>, StatusCode> {
+    require_admin(&state, &headers, "read server metrics").await?;
+
+    Ok(Json(ApiResponse::success(ServerMetrics {
+        database_pool: state.db.pool_stats(),
+        live_rifts: state.sync.storage.active_rifts().await.len(),
+    })))
+}
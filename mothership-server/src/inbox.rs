@@ -0,0 +1,153 @@
+//! In-app notifications.
+//!
+//! Events call [`deliver`], which stores the notification for the recipient
+//! and pushes it to any sync connection they have open. Unlike emails these
+//! aren't subject to preferences; they wait in the inbox until read.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use mothership_common::{
+    protocol::{ApiResponse, InboxKind, InboxPage, SyncMessage},
+    ProjectId, UserId,
+};
+use serde::Deserialize;
+use std::collections::HashSet;
+use tracing::{debug, error, warn};
+use uuid::Uuid;
+
+use crate::handlers::authenticate_request;
+use crate::sync::SyncState;
+use crate::AppState;
+
+/// Notifications listed when no limit is given, and the most that can be asked for
+const DEFAULT_LIST_LIMIT: usize = 50;
+const MAX_LIST_LIMIT: usize = 200;
+
+/// Inbox endpoints
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/users/me/notifications", get(list_notifications))
+        .route("/users/me/notifications/read", post(mark_all_read))
+        .route("/users/me/notifications/:notification_id/read", post(mark_read))
+}
+
+#[derive(Debug, Deserialize)]
+struct ListNotificationsQuery {
+    #[serde(default)]
+    unread: bool,
+    /// Only notifications older than this, to page back
+    before: Option<DateTime<Utc>>,
+    limit: Option<usize>,
+}
+
+/// Store a notification for `recipient` and push it to their connections.
+/// Returns immediately.
+pub fn deliver(
+    sync: &SyncState,
+    recipient: UserId,
+    actor: Option<UserId>,
+    kind: InboxKind,
+    project_id: Option<ProjectId>,
+    title: impl Into<String>,
+    body: impl Into<String>,
+) {
+    let sync = sync.clone();
+    let title = title.into();
+    let body = body.into();
+    tokio::spawn(async move {
+        match sync.db.create_user_notification(recipient, kind, &title, &body, project_id, actor).await {
+            Ok(notification) => {
+                debug!("🔔 Notified {} ({})", recipient, kind.as_str());
+                sync.send_to_user(recipient, SyncMessage::Notification { notification });
+            }
+            Err(e) => warn!("🔔 Failed to store {} notification for {}: {}", kind.as_str(), recipient, e),
+        }
+    });
+}
+
+/// Notify several users about something `actor` did, once each, skipping the actor
+pub fn deliver_all(
+    sync: &SyncState,
+    recipients: impl IntoIterator<Item = UserId>,
+    actor: UserId,
+    kind: InboxKind,
+    project_id: Option<ProjectId>,
+    title: &str,
+    body: &str,
+) {
+    let mut seen = HashSet::new();
+    for recipient in recipients.into_iter().filter(|r| *r != actor) {
+        if seen.insert(recipient) {
+            deliver(sync, recipient, Some(actor), kind, project_id, title, body);
+        }
+    }
+}
+
+/// Notify users `@mentioned` in a comment, if they can see the project
+pub async fn deliver_mentions(sync: &SyncState, project_id: ProjectId, actor: UserId, text: &str, title: &str) {
+    for name in crate::chat::mentioned_usernames(text) {
+        let Ok(Some(user)) = sync.db.get_user_by_username(name).await else { continue };
+        if user.id == actor {
+            continue;
+        }
+        // SECURITY CHECK: never leak comments to users outside the project
+        if !sync.db.user_has_project_access(user.id, project_id).await.unwrap_or(false) {
+            continue;
+        }
+        deliver(sync, user.id, Some(actor), InboxKind::Mention, Some(project_id), title, text);
+    }
+}
+
+/// The authenticated user's notifications, newest first, with the unread count
+async fn list_notifications(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ListNotificationsQuery>,
+) -> Result<Json<ApiResponse<InboxPage>>, StatusCode> {
+    let user_id = authenticate_request(&state, &headers)?;
+    let limit = query.limit.unwrap_or(DEFAULT_LIST_LIMIT).clamp(1, MAX_LIST_LIMIT);
+
+    let notifications = state.db.list_user_notifications(user_id, query.unread, query.before, limit).await
+        .map_err(|e| {
+            error!("Failed to list notifications of {}: {}", user_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    let unread = state.db.count_unread_notifications(user_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(ApiResponse::success(InboxPage { notifications, unread })))
+}
+
+/// Mark one notification read
+async fn mark_read(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(notification_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<u64>>, StatusCode> {
+    let user_id = authenticate_request(&state, &headers)?;
+    set_read(&state, user_id, Some(notification_id)).await
+}
+
+/// Mark every notification read
+async fn mark_all_read(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<ApiResponse<u64>>, StatusCode> {
+    let user_id = authenticate_request(&state, &headers)?;
+    set_read(&state, user_id, None).await
+}
+
+/// Mark notifications read, answering with how many were still unread
+async fn set_read(state: &AppState, user_id: UserId, notification_id: Option<Uuid>) -> Result<Json<ApiResponse<u64>>, StatusCode> {
+    let marked = state.db.mark_notifications_read(user_id, notification_id).await
+        .map_err(|e| {
+            error!("Failed to mark notifications of {} read: {}", user_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    Ok(Json(ApiResponse::success(marked)))
+}
@@ -0,0 +1,249 @@
+//! The server configuration and whitelist, reloadable while running.
+//!
+//! Both are read at startup and again on SIGHUP or `POST /admin/config/reload`,
+//! then swapped in together. Requests in flight finish with the versions they
+//! started with, and WebSocket connections stay open. Feature toggles, chat
+//! limits, CORS origins, the whitelist and the settings background tasks read
+//! on each run apply at once; the listening addresses, notifications, relay
+//! and replication are set up at startup and take a restart.
+
+use arc_swap::ArcSwap;
+use axum::{
+    extract::State,
+    http::{HeaderMap, HeaderValue, StatusCode},
+    routing::post,
+    Json, Router,
+};
+use mothership_common::protocol::ApiResponse;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tracing::{error, info, warn};
+
+use crate::audit::{self, AuditAction, AuditEvent};
+use crate::config::{ServerConfig, UserWhitelist};
+use crate::database::Database;
+use crate::handlers::require_admin;
+use crate::whitelist;
+use crate::AppState;
+
+/// Reads the configuration from wherever the server got it at startup
+pub type ConfigLoader = Arc<dyn Fn() -> anyhow::Result<ServerConfig> + Send + Sync>;
+
+/// Configuration reload endpoint (admins only)
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/admin/config/reload", post(reload_config))
+}
+
+/// The configuration and whitelist in use
+struct Snapshot {
+    config: Arc<ServerConfig>,
+    whitelist: Option<Arc<UserWhitelist>>,
+}
+
+/// What a reload changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReloadSummary {
+    /// Configuration sections that changed
+    pub changed: Vec<String>,
+    /// Changed sections that only take effect after a restart
+    pub restart_required: Vec<String>,
+    /// Whether a whitelist is enforced now
+    pub whitelist_enabled: bool,
+}
+
+#[derive(Clone)]
+pub struct LiveConfig {
+    current: Arc<ArcSwap<Snapshot>>,
+    loader: ConfigLoader,
+    db: Database,
+}
+
+impl LiveConfig {
+    pub fn new(config: ServerConfig, whitelist: Option<UserWhitelist>, loader: ConfigLoader, db: Database) -> Self {
+        let snapshot = Snapshot {
+            config: Arc::new(config),
+            whitelist: whitelist.map(Arc::new),
+        };
+        Self {
+            current: Arc::new(ArcSwap::from_pointee(snapshot)),
+            loader,
+            db,
+        }
+    }
+
+    pub fn config(&self) -> Arc<ServerConfig> {
+        self.current.load().config.clone()
+    }
+
+    pub fn whitelist(&self) -> Option<Arc<UserWhitelist>> {
+        self.current.load().whitelist.clone()
+    }
+
+    /// Read the configuration and whitelist again and swap both in. If
+    /// either can't be read, the running versions stay.
+    pub async fn reload(&self) -> anyhow::Result<ReloadSummary> {
+        let config = (self.loader)()?;
+        let whitelist = whitelist::load(&self.db, &config).await?;
+
+        let old = self.config();
+        let summary = ReloadSummary {
+            changed: changed_sections(&old, &config),
+            restart_required: restart_sections(&old, &config),
+            whitelist_enabled: whitelist.is_some(),
+        };
+
+        self.current.store(Arc::new(Snapshot {
+            config: Arc::new(config),
+            whitelist: whitelist.map(Arc::new),
+        }));
+        Ok(summary)
+    }
+
+    /// Read the whitelist again after its entries changed
+    pub async fn refresh_whitelist(&self) -> anyhow::Result<()> {
+        let config = self.config();
+        let whitelist = whitelist::load(&self.db, &config).await?;
+        self.current.store(Arc::new(Snapshot {
+            config,
+            whitelist: whitelist.map(Arc::new),
+        }));
+        Ok(())
+    }
+
+    /// CORS for the API, allowing the origins configured when each request arrives
+    pub fn cors_layer(&self) -> CorsLayer {
+        let live = self.clone();
+        CorsLayer::new()
+            .allow_origin(AllowOrigin::predicate(move |origin: &HeaderValue, _| {
+                live.config().server.cors_origins.iter().any(|allowed| allowed.as_bytes() == origin.as_bytes())
+            }))
+            .allow_methods([
+                axum::http::Method::GET,
+                axum::http::Method::POST,
+                axum::http::Method::PUT,
+                axum::http::Method::PATCH,
+                axum::http::Method::DELETE,
+                axum::http::Method::OPTIONS,
+            ])
+            .allow_headers([
+                axum::http::header::AUTHORIZATION,
+                axum::http::header::CONTENT_TYPE,
+                axum::http::header::ACCEPT,
+            ])
+            .allow_credentials(true)
+    }
+}
+
+/// Top-level sections that differ between two configurations
+fn changed_sections(old: &ServerConfig, new: &ServerConfig) -> Vec<String> {
+    let (Ok(serde_json::Value::Object(old)), Ok(serde_json::Value::Object(new))) =
+        (serde_json::to_value(old), serde_json::to_value(new))
+    else {
+        return Vec::new();
+    };
+    new.iter()
+        .filter(|(section, value)| old.get(*section) != Some(*value))
+        .map(|(section, _)| section.clone())
+        .collect()
+}
+
+/// Changed sections that were set up at startup
+fn restart_sections(old: &ServerConfig, new: &ServerConfig) -> Vec<String> {
+    fn differs<T: Serialize>(a: &T, b: &T) -> bool {
+        serde_json::to_value(a).ok() != serde_json::to_value(b).ok()
+    }
+
+    let mut sections = Vec::new();
+    if (&old.server.host, old.server.port, old.server.web_port) != (&new.server.host, new.server.port, new.server.web_port) {
+        sections.push("server".to_string());
+    }
+    if old.features.binary_sync_enabled != new.features.binary_sync_enabled {
+        sections.push("features.binary_sync_enabled".to_string());
+    }
+    if old.auto_checkpoint.enabled != new.auto_checkpoint.enabled {
+        sections.push("auto_checkpoint.enabled".to_string());
+    }
+    if old.compaction.enabled != new.compaction.enabled {
+        sections.push("compaction.enabled".to_string());
+    }
+    if differs(&old.notifications, &new.notifications) {
+        sections.push("notifications".to_string());
+    }
+    if differs(&old.relay, &new.relay) {
+        sections.push("relay".to_string());
+    }
+    if differs(&old.replication, &new.replication) {
+        sections.push("replication".to_string());
+    }
+    if differs(&old.database, &new.database) {
+        sections.push("database".to_string());
+    }
+    sections
+}
+
+/// Reload, logging what changed
+pub async fn reload_and_log(live: &LiveConfig) -> anyhow::Result<ReloadSummary> {
+    match live.reload().await {
+        Ok(summary) => {
+            if summary.changed.is_empty() {
+                info!("🔧 Reloaded configuration: nothing changed");
+            } else {
+                info!("🔧 Reloaded configuration: {} changed", summary.changed.join(", "));
+            }
+            if !summary.restart_required.is_empty() {
+                warn!("⚠️ Restart the server to apply: {}", summary.restart_required.join(", "));
+            }
+            Ok(summary)
+        }
+        Err(e) => {
+            error!("❌ Failed to reload configuration, keeping the running one: {}", e);
+            Err(e)
+        }
+    }
+}
+
+/// Reload the configuration whenever the process gets SIGHUP
+#[cfg(unix)]
+pub fn spawn_sighup_handler(live: LiveConfig) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangups = match signal(SignalKind::hangup()) {
+        Ok(hangups) => hangups,
+        Err(e) => {
+            warn!("Failed to listen for SIGHUP, reload with POST /admin/config/reload instead: {}", e);
+            return;
+        }
+    };
+    tokio::spawn(async move {
+        while hangups.recv().await.is_some() {
+            info!("🔧 SIGHUP received, reloading configuration");
+            let _ = reload_and_log(&live).await;
+        }
+    });
+}
+
+#[cfg(not(unix))]
+pub fn spawn_sighup_handler(_live: LiveConfig) {}
+
+/// Reload server.config and the whitelist
+async fn reload_config(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<ApiResponse<ReloadSummary>>, StatusCode> {
+    let admin_id = require_admin(&state, &headers, "reload the configuration").await?;
+
+    let summary = match reload_and_log(&state.settings).await {
+        Ok(summary) => summary,
+        Err(e) => return Ok(Json(ApiResponse::error(format!("Failed to reload configuration: {}", e)))),
+    };
+
+    audit::record(&state.db, AuditEvent::new(AuditAction::ConfigReloaded, Some(admin_id))
+        .details(serde_json::json!({
+            "changed": summary.changed,
+            "restart_required": summary.restart_required,
+        }))).await;
+
+    Ok(Json(ApiResponse::success(summary)))
+}
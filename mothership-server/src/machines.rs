@@ -0,0 +1,75 @@
+//! The machines each user has logged in from.
+//!
+//! Every login records its machine, and every session is bound to the
+//! machine it was issued on. Revoking a machine, say a lost laptop, signs out
+//! all of its sessions at once; logging in on it again brings it back.
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{delete, get},
+    Json, Router,
+};
+use mothership_common::{auth::Machine, protocol::ApiResponse};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::audit::{self, AuditAction, AuditEvent};
+use crate::handlers::authenticate_request;
+use crate::sessions::current_session;
+use crate::AppState;
+
+/// Machine registry endpoints
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/users/me/machines", get(list_machines))
+        .route("/users/me/machines/:id", delete(revoke_machine))
+}
+
+/// The machines the authenticated user has logged in from
+async fn list_machines(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<ApiResponse<Vec<Machine>>>, StatusCode> {
+    let user_id = authenticate_request(&state, &headers)?;
+
+    let machines = state.db.list_machines(user_id, current_session(&headers, &state)).await
+        .map_err(|e| {
+            error!("Failed to list machines of user {}: {}", user_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ApiResponse::success(machines)))
+}
+
+/// Revoke one of the authenticated user's machines, signing out every
+/// session on it
+async fn revoke_machine(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    let user_id = authenticate_request(&state, &headers)?;
+
+    let (name, sessions) = state.db.revoke_machine(user_id, id).await
+        .map_err(|e| {
+            error!("Failed to revoke machine {}: {}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    // Takes effect immediately: the sessions' jtis are now rejected by verify_token
+    for session_id in &sessions {
+        state.auth.revoke(session_id.to_string());
+    }
+
+    info!("🚫 Revoked machine {} ({}) of user {}, {} sessions signed out", name, id, user_id, sessions.len());
+    audit::record(&state.db, AuditEvent::new(AuditAction::MachineRevoked, Some(user_id))
+        .target(id.to_string())
+        .details(serde_json::json!({
+            "name": name,
+            "sessions_revoked": sessions.len(),
+        }))).await;
+
+    Ok(Json(ApiResponse::success(format!("Machine '{}' revoked", name))))
+}
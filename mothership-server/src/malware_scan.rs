@@ -0,0 +1,366 @@
+//! Malware scanning of files sent to the server.
+//!
+//! With a scanner configured in `[malware_scan]`, every file uploaded or
+//! synced into a rift is queued for scanning once it is stored, so senders
+//! never wait on the scanner. The scanner is either a command (exit code 1
+//! means infected, as with `clamscan` and `clamdscan`) or a clamd daemon
+//! spoken to over its INSTREAM protocol. A flagged file is taken out of its
+//! rift, unless it changed again in the meantime, and its content is kept in
+//! `storage/malware/` where nothing serves it. Admins review what was caught
+//! through `/admin/quarantine`, and either release a file back into its rift
+//! or delete it.
+
+use anyhow::{anyhow, Result};
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{delete, get, post},
+    Json, Router,
+};
+use chrono::Utc;
+use mothership_common::{
+    protocol::{ApiResponse, FileDiff, FileDiffChange, SyncMessage, FILE_QUARANTINED_WARNING},
+    RiftId, UserId,
+};
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::process::Command;
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::audit::{self, AuditAction, AuditEvent};
+use crate::config::MalwareScanSettings;
+use crate::handlers::require_admin;
+use crate::storage::{QuarantineStatus, QuarantinedFile};
+use crate::sync::SyncState;
+use crate::AppState;
+
+/// Largest chunk sent to clamd in one INSTREAM frame
+const CLAMD_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Quarantine review endpoints (admins only)
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/admin/quarantine", get(list_quarantined))
+        .route("/admin/quarantine/:id", delete(delete_file))
+        .route("/admin/quarantine/:id/release", post(release_file))
+}
+
+/// A stored file waiting to be scanned
+pub struct ScanJob {
+    rift_id: RiftId,
+    path: PathBuf,
+    content: String,
+    author: Option<UserId>,
+}
+
+/// Files waiting to be scanned, handed to the scanning task
+#[derive(Clone)]
+pub struct MalwareScanQueue {
+    jobs: mpsc::UnboundedSender<ScanJob>,
+}
+
+impl MalwareScanQueue {
+    pub fn new() -> (Self, mpsc::UnboundedReceiver<ScanJob>) {
+        let (jobs, receiver) = mpsc::unbounded_channel();
+        (Self { jobs }, receiver)
+    }
+}
+
+/// Queue a file just stored in a rift for scanning, if a scanner is configured
+pub fn queue(state: &SyncState, rift_id: RiftId, path: &std::path::Path, content: &str, author: Option<UserId>) {
+    if content.is_empty() || !state.settings.config().malware_scan.enabled() {
+        return;
+    }
+    let _ = state.malware_scan.jobs.send(ScanJob {
+        rift_id,
+        path: path.to_path_buf(),
+        content: content.to_string(),
+        author,
+    });
+}
+
+/// Scan queued files one at a time, quarantining the ones flagged
+pub fn start_scanner(state: SyncState, mut jobs: mpsc::UnboundedReceiver<ScanJob>) {
+    tokio::spawn(async move {
+        while let Some(job) = jobs.recv().await {
+            // The scanner may have been changed or turned off since the file was queued
+            let settings = state.settings.config().malware_scan.clone();
+            if !settings.enabled() {
+                continue;
+            }
+
+            let signature = match scan(&settings, &job.content).await {
+                Ok(None) => continue,
+                Ok(Some(signature)) => signature,
+                Err(e) if settings.quarantine_on_error => format!("Scan failed: {}", e),
+                Err(e) => {
+                    warn!("🦠 Could not scan {} in rift {}, letting it through: {}", job.path.display(), job.rift_id, e);
+                    continue;
+                }
+            };
+            quarantine(&state, job, signature).await;
+        }
+    });
+}
+
+/// What the configured scanner found: the signature of what it flagged, or
+/// `None` if the content is clean
+async fn scan(settings: &MalwareScanSettings, content: &str) -> Result<Option<String>> {
+    let timeout = settings.timeout();
+    if !settings.command.trim().is_empty() {
+        scan_with_command(&settings.command, content, timeout).await
+    } else {
+        tokio::time::timeout(timeout, scan_with_clamd(settings.clamd_address.trim(), content)).await
+            .map_err(|_| anyhow!("clamd took longer than {}s", timeout.as_secs()))?
+    }
+}
+
+/// Run the scanner command on the content, through a temporary file if the
+/// command names `{path}` and on its stdin otherwise
+async fn scan_with_command(command: &str, content: &str, timeout: Duration) -> Result<Option<String>> {
+    let mut words = command.split_whitespace();
+    let program = words.next().ok_or_else(|| anyhow!("No scanner command"))?;
+    let args: Vec<&str> = words.collect();
+
+    let temp_path = args.iter().any(|arg| arg.contains("{path}"))
+        .then(|| std::env::temp_dir().join(format!("mothership-scan-{}", Uuid::new_v4())));
+    if let Some(temp_path) = &temp_path {
+        tokio::fs::write(temp_path, content).await?;
+    }
+
+    let run = async {
+        let mut child = Command::new(program)
+            .args(args.iter().map(|arg| match &temp_path {
+                Some(temp_path) => arg.replace("{path}", &temp_path.display().to_string()),
+                None => arg.to_string(),
+            }))
+            .stdin(if temp_path.is_some() { Stdio::null() } else { Stdio::piped() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(content.as_bytes()).await?;
+        }
+        Ok::<_, anyhow::Error>(child.wait_with_output().await?)
+    };
+    let output = tokio::time::timeout(timeout, run).await;
+    if let Some(temp_path) = &temp_path {
+        let _ = tokio::fs::remove_file(temp_path).await;
+    }
+    let output = output.map_err(|_| anyhow!("'{}' took longer than {}s", program, timeout.as_secs()))??;
+
+    match output.status.code() {
+        Some(0) => Ok(None),
+        Some(1) => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let signature = stdout.lines().map(str::trim).rfind(|line| !line.is_empty())
+                .unwrap_or("Flagged by the scanner command");
+            Ok(Some(signature.to_string()))
+        }
+        _ => Err(anyhow!("'{}' failed ({}): {}", program, output.status, String::from_utf8_lossy(&output.stderr).trim())),
+    }
+}
+
+/// Stream the content to clamd with INSTREAM and read its verdict
+async fn scan_with_clamd(address: &str, content: &str) -> Result<Option<String>> {
+    let mut stream = TcpStream::connect(address).await?;
+    stream.write_all(b"zINSTREAM\0").await?;
+    for chunk in content.as_bytes().chunks(CLAMD_CHUNK_SIZE) {
+        stream.write_all(&(chunk.len() as u32).to_be_bytes()).await?;
+        stream.write_all(chunk).await?;
+    }
+    stream.write_all(&0u32.to_be_bytes()).await?;
+
+    let mut reply = Vec::new();
+    stream.read_to_end(&mut reply).await?;
+    let reply = String::from_utf8_lossy(&reply);
+    let reply = reply.trim_end_matches('\0').trim();
+
+    // "stream: OK", "stream: Eicar-Signature FOUND" or "... ERROR"
+    let verdict = reply.split_once(": ").map_or(reply, |(_, verdict)| verdict);
+    if verdict == "OK" {
+        Ok(None)
+    } else if let Some(signature) = verdict.strip_suffix(" FOUND") {
+        Ok(Some(signature.to_string()))
+    } else {
+        Err(anyhow!("clamd replied '{}'", reply))
+    }
+}
+
+/// Take a flagged file out of its rift and keep it for review
+async fn quarantine(state: &SyncState, job: ScanJob, signature: String) {
+    let rift = match state.db.get_rift(job.rift_id).await {
+        Ok(Some(rift)) => rift,
+        Ok(None) => return,
+        Err(e) => {
+            error!("Failed to load rift {} to quarantine {}: {}", job.rift_id, job.path.display(), e);
+            return;
+        }
+    };
+
+    let removed = state.storage.remove_live_file_if_unchanged(rift.id, &job.path, &job.content).await;
+    if removed {
+        state.text_sessions.write().await.remove(&(rift.id, job.path.clone()));
+    }
+
+    let record = QuarantinedFile {
+        id: Uuid::new_v4(),
+        project_id: rift.project_id,
+        rift_id: rift.id,
+        path: job.path.clone(),
+        content_hash: mothership_common::upload::content_hash(job.content.as_bytes()),
+        size: job.content.len() as u64,
+        author: job.author,
+        signature: signature.clone(),
+        detected_at: Utc::now(),
+        removed_from_rift: removed,
+        status: QuarantineStatus::Quarantined,
+        reviewed_by: None,
+        reviewed_at: None,
+    };
+    if let Err(e) = state.storage.quarantine_file(&record, &job.content).await {
+        error!("Failed to keep quarantined file {} of rift {}: {}", job.path.display(), rift.id, e);
+    }
+    warn!("🦠 Quarantined {} in rift {}: {}", job.path.display(), rift.id, signature);
+
+    let channel = format!("rift_{}", rift.id);
+    if removed {
+        state.broadcast(channel.clone(), SyncMessage::RiftDiffUpdate {
+            rift_id: rift.id,
+            diff_changes: vec![FileDiffChange {
+                path: job.path.clone(),
+                diff: FileDiff::Deleted,
+                file_size: 0,
+                metadata: Default::default(),
+            }],
+            author: Uuid::nil(),
+            timestamp: Utc::now(),
+            compressed: false,
+        });
+    }
+    state.broadcast(channel, SyncMessage::Error {
+        message: format!("{} was quarantined by malware scanning ({})", job.path.display(), signature),
+        error_code: Some(FILE_QUARANTINED_WARNING.to_string()),
+    });
+
+    audit::record(&state.db, AuditEvent::new(AuditAction::FileQuarantined, None)
+        .project(rift.project_id)
+        .target(record.id.to_string())
+        .details(serde_json::json!({
+            "rift_id": rift.id,
+            "path": job.path,
+            "signature": signature,
+            "author": job.author,
+            "removed_from_rift": removed,
+        }))).await;
+}
+
+fn internal_error(e: anyhow::Error) -> StatusCode {
+    error!("Failed to access quarantined files: {}", e);
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+
+/// A quarantined file still awaiting review
+async fn pending_record(state: &AppState, id: Uuid) -> Result<QuarantinedFile, StatusCode> {
+    let record = state.sync.storage.load_quarantine_record(id).await
+        .map_err(internal_error)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    if record.status != QuarantineStatus::Quarantined {
+        return Err(StatusCode::CONFLICT);
+    }
+    Ok(record)
+}
+
+/// Every file malware scanning flagged, newest first
+async fn list_quarantined(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<ApiResponse<Vec<QuarantinedFile>>>, StatusCode> {
+    require_admin(&state, &headers, "review quarantined files").await?;
+
+    let mut records = state.sync.storage.list_quarantine_records().await.map_err(internal_error)?;
+    records.reverse();
+    Ok(Json(ApiResponse::success(records)))
+}
+
+/// Let a file through after all: put it back into its rift. Refused if
+/// something else has been written to its path since.
+async fn release_file(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<QuarantinedFile>>, StatusCode> {
+    let admin_id = require_admin(&state, &headers, "review quarantined files").await?;
+    let mut record = pending_record(&state, id).await?;
+
+    if record.removed_from_rift {
+        let content = state.sync.storage.get_quarantined_content(id).await
+            .map_err(internal_error)?
+            .ok_or(StatusCode::NOT_FOUND)?;
+        match state.db.get_rift(record.rift_id).await {
+            Ok(Some(_)) => {}
+            Ok(None) => return Err(StatusCode::GONE),
+            Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+        }
+        if !state.sync.storage.restore_live_file(record.rift_id, record.path.clone(), content.clone()).await {
+            return Err(StatusCode::CONFLICT);
+        }
+
+        state.sync.broadcast(format!("rift_{}", record.rift_id), SyncMessage::RiftDiffUpdate {
+            rift_id: record.rift_id,
+            diff_changes: vec![FileDiffChange {
+                path: record.path.clone(),
+                file_size: content.len() as u64,
+                diff: FileDiff::FullContent(content),
+                metadata: Default::default(),
+            }],
+            author: admin_id,
+            timestamp: Utc::now(),
+            compressed: false,
+        });
+    }
+
+    record.status = QuarantineStatus::Released;
+    record.reviewed_by = Some(admin_id);
+    record.reviewed_at = Some(Utc::now());
+    state.sync.storage.save_quarantine_record(&record).await.map_err(internal_error)?;
+    state.sync.storage.discard_quarantined_content(id).await.map_err(internal_error)?;
+
+    info!("🦠 Admin {} released {} into rift {}", admin_id, record.path.display(), record.rift_id);
+    audit::record(&state.db, AuditEvent::new(AuditAction::QuarantineReleased, Some(admin_id))
+        .project(record.project_id)
+        .target(record.id.to_string())
+        .details(serde_json::json!({ "rift_id": record.rift_id, "path": record.path, "signature": record.signature }))).await;
+
+    Ok(Json(ApiResponse::success(record)))
+}
+
+/// Delete a quarantined file's content for good, keeping its record
+async fn delete_file(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<QuarantinedFile>>, StatusCode> {
+    let admin_id = require_admin(&state, &headers, "review quarantined files").await?;
+    let mut record = pending_record(&state, id).await?;
+
+    state.sync.storage.discard_quarantined_content(id).await.map_err(internal_error)?;
+    record.status = QuarantineStatus::Deleted;
+    record.reviewed_by = Some(admin_id);
+    record.reviewed_at = Some(Utc::now());
+    state.sync.storage.save_quarantine_record(&record).await.map_err(internal_error)?;
+
+    info!("🦠 Admin {} deleted quarantined {} of rift {}", admin_id, record.path.display(), record.rift_id);
+    audit::record(&state.db, AuditEvent::new(AuditAction::QuarantineDeleted, Some(admin_id))
+        .project(record.project_id)
+        .target(record.id.to_string())
+        .details(serde_json::json!({ "rift_id": record.rift_id, "path": record.path, "signature": record.signature }))).await;
+
+    Ok(Json(ApiResponse::success(record)))
+}
@@ -0,0 +1,210 @@
+//! Email notifications.
+//!
+//! Events call [`Notifier::notify`], which checks the recipient's preferences
+//! and sends the email in the background, so requests never wait on SMTP.
+
+use anyhow::{anyhow, Result};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::get,
+    Json, Router,
+};
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use mothership_common::{
+    protocol::{ApiResponse, NotificationPreferences, UpdateNotificationPreferencesRequest},
+    UserId,
+};
+use std::sync::Arc;
+use tracing::{debug, error, info, warn};
+
+use crate::config::NotificationSettings;
+use crate::database::Database;
+use crate::handlers::authenticate_request;
+use crate::AppState;
+
+/// Events a user can be emailed about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationKind {
+    Invitation,
+    Mention,
+    ConflictRift,
+    CheckpointRestore,
+}
+
+impl NotificationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationKind::Invitation => "invitation",
+            NotificationKind::Mention => "mention",
+            NotificationKind::ConflictRift => "conflict_rift",
+            NotificationKind::CheckpointRestore => "checkpoint_restore",
+        }
+    }
+
+    fn enabled_in(&self, preferences: &NotificationPreferences) -> bool {
+        match self {
+            NotificationKind::Invitation => preferences.invitations,
+            NotificationKind::Mention => preferences.mentions,
+            NotificationKind::ConflictRift => preferences.conflict_rifts,
+            NotificationKind::CheckpointRestore => preferences.checkpoint_restores,
+        }
+    }
+}
+
+struct Mailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+/// Sends notification emails; a no-op when notifications are disabled
+#[derive(Clone)]
+pub struct Notifier {
+    db: Database,
+    mailer: Option<Arc<Mailer>>,
+}
+
+impl Notifier {
+    pub fn new(db: Database, settings: &NotificationSettings) -> Result<Self> {
+        if !settings.enabled {
+            return Ok(Self { db, mailer: None });
+        }
+
+        let mut builder = if settings.starttls {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&settings.smtp_host)?
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::relay(&settings.smtp_host)?
+        }
+        .port(settings.smtp_port);
+
+        if !settings.smtp_username.is_empty() {
+            let password = std::env::var("SMTP_PASSWORD").unwrap_or_else(|_| settings.smtp_password.clone());
+            builder = builder.credentials(Credentials::new(settings.smtp_username.clone(), password));
+        }
+
+        let from = settings.from_address.parse::<Mailbox>()
+            .map_err(|e| anyhow!("Invalid notifications.from_address '{}': {}", settings.from_address, e))?;
+
+        info!("📧 Email notifications enabled via {}:{}", settings.smtp_host, settings.smtp_port);
+        Ok(Self {
+            db,
+            mailer: Some(Arc::new(Mailer { transport: builder.build(), from })),
+        })
+    }
+
+    /// Email `recipient` about an event unless they opted out. Returns immediately.
+    pub fn notify(&self, recipient: UserId, kind: NotificationKind, subject: impl Into<String>, body: impl Into<String>) {
+        let Some(mailer) = self.mailer.clone() else {
+            return;
+        };
+
+        let db = self.db.clone();
+        let subject = subject.into();
+        let body = body.into();
+        tokio::spawn(async move {
+            if let Err(e) = deliver(&db, &mailer, recipient, kind, subject, body).await {
+                warn!("📧 Failed to send {} notification to {}: {}", kind.as_str(), recipient, e);
+            }
+        });
+    }
+
+    /// Notify several users about something `actor` did, skipping the actor
+    pub fn notify_all(
+        &self,
+        recipients: impl IntoIterator<Item = UserId>,
+        actor: UserId,
+        kind: NotificationKind,
+        subject: &str,
+        body: &str,
+    ) {
+        for recipient in recipients.into_iter().filter(|r| *r != actor) {
+            self.notify(recipient, kind, subject, body);
+        }
+    }
+}
+
+async fn deliver(
+    db: &Database,
+    mailer: &Mailer,
+    recipient: UserId,
+    kind: NotificationKind,
+    subject: String,
+    body: String,
+) -> Result<()> {
+    if !kind.enabled_in(&db.get_notification_preferences(recipient).await?) {
+        debug!("📧 {} opted out of {} notifications", recipient, kind.as_str());
+        return Ok(());
+    }
+    // Service accounts have placeholder addresses
+    if db.is_service_account(recipient).await? {
+        return Ok(());
+    }
+    let Some(user) = db.get_user(recipient).await? else {
+        return Ok(());
+    };
+
+    let message = Message::builder()
+        .from(mailer.from.clone())
+        .to(Mailbox::new(Some(user.username.clone()), user.email.parse()?))
+        .subject(subject)
+        .header(ContentType::TEXT_PLAIN)
+        .body(format!(
+            "{}\n\n--\nYou can turn these emails off with PATCH /users/me/notifications/preferences.\n",
+            body
+        ))?;
+
+    mailer.transport.send(message).await?;
+    info!("📧 Sent {} notification to {}", kind.as_str(), user.username);
+    Ok(())
+}
+
+/// Notification preference endpoints
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/users/me/notifications/preferences", get(get_preferences).patch(update_preferences))
+}
+
+/// The authenticated user's notification preferences
+async fn get_preferences(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<ApiResponse<NotificationPreferences>>, StatusCode> {
+    let user_id = authenticate_request(&state, &headers)?;
+
+    let preferences = state.db.get_notification_preferences(user_id).await
+        .map_err(|e| {
+            error!("Failed to load notification preferences of {}: {}", user_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ApiResponse::success(preferences)))
+}
+
+/// Turn individual notifications on or off
+async fn update_preferences(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<UpdateNotificationPreferencesRequest>,
+) -> Result<Json<ApiResponse<NotificationPreferences>>, StatusCode> {
+    let user_id = authenticate_request(&state, &headers)?;
+
+    let mut preferences = state.db.get_notification_preferences(user_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    preferences.invitations = request.invitations.unwrap_or(preferences.invitations);
+    preferences.mentions = request.mentions.unwrap_or(preferences.mentions);
+    preferences.conflict_rifts = request.conflict_rifts.unwrap_or(preferences.conflict_rifts);
+    preferences.checkpoint_restores = request.checkpoint_restores.unwrap_or(preferences.checkpoint_restores);
+
+    state.db.set_notification_preferences(user_id, &preferences).await
+        .map_err(|e| {
+            error!("Failed to save notification preferences of {}: {}", user_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    info!("📧 Updated notification preferences of {}", user_id);
+    Ok(Json(ApiResponse::success(preferences)))
+}
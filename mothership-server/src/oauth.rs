@@ -7,6 +7,7 @@ use oauth2::{
 use serde::Deserialize;
 use std::collections::HashMap;
 use tokio::sync::RwLock;
+use tracing::warn;
 
 /// OAuth configuration for a provider
 #[derive(Clone)]
@@ -16,11 +17,23 @@ struct OAuthConfig {
     user_info_url: String,
 }
 
+/// The machine an OAuth login was started from, recorded with its session
+#[derive(Debug, Clone)]
+pub struct LoginMachine {
+    pub machine_id: String,
+    pub machine_name: String,
+    pub platform: String,
+    pub hostname: String,
+}
+
+/// A login in progress: where it came from and where its token should go
+type PendingLogin = (OAuthProvider, OAuthSource, Option<String>, LoginMachine);
+
 /// OAuth service for handling Google and GitHub authentication
 #[derive(Clone)]
 pub struct OAuthService {
     providers: HashMap<OAuthProvider, OAuthConfig>,
-    pending_states: std::sync::Arc<RwLock<HashMap<String, (OAuthProvider, OAuthSource, Option<String>)>>>,
+    pending_states: std::sync::Arc<RwLock<HashMap<String, PendingLogin>>>,
 }
 
 impl OAuthService {
@@ -70,7 +83,7 @@ impl OAuthService {
                     Some(TokenUrl::new("https://github.com/login/oauth/access_token".to_string())?),
                 )
                 .set_redirect_uri(RedirectUrl::new(format!("{}/auth/oauth/callback/github", oauth_base_url))?),
-                scopes: vec!["user:email".to_string()],
+                scopes: vec!["user:email".to_string(), "read:org".to_string()],
                 user_info_url: "https://api.github.com/user".to_string(),
             };
             providers.insert(OAuthProvider::GitHub, github_config);
@@ -83,7 +96,13 @@ impl OAuthService {
     }
 
     /// Generate authorization URL for OAuth flow
-    pub async fn get_authorization_url(&self, provider: OAuthProvider, source: OAuthSource, callback_url: Option<String>) -> Result<(String, String), AuthError> {
+    pub async fn get_authorization_url(
+        &self,
+        provider: OAuthProvider,
+        source: OAuthSource,
+        callback_url: Option<String>,
+        machine: LoginMachine,
+    ) -> Result<(String, String), AuthError> {
         let config = self.providers.get(&provider)
             .ok_or_else(|| AuthError::OAuthError(format!("Provider {:?} not configured", provider)))?;
 
@@ -102,19 +121,19 @@ impl OAuthService {
 
         let state = csrf_token.secret().clone();
         
-        // Store the state for validation along with source and callback URL
+        // Store the state for validation along with source, callback URL and machine
         {
             let mut pending_states = self.pending_states.write().await;
-            pending_states.insert(state.clone(), (provider, source, callback_url));
+            pending_states.insert(state.clone(), (provider, source, callback_url, machine));
         }
 
         Ok((auth_url.to_string(), state))
     }
 
     /// Exchange authorization code for user profile
-    pub async fn exchange_code(&self, code: String, state: String) -> Result<(OAuthProfile, OAuthSource, Option<String>), AuthError> {
+    pub async fn exchange_code(&self, code: String, state: String) -> Result<(OAuthProfile, OAuthSource, Option<String>, LoginMachine), AuthError> {
         // Validate state and get provider
-        let (provider, source, callback_url) = {
+        let (provider, source, callback_url, machine) = {
             let mut pending_states = self.pending_states.write().await;
             pending_states.remove(&state)
                 .ok_or_else(|| AuthError::OAuthError("Invalid or expired state".to_string()))?
@@ -133,7 +152,7 @@ impl OAuthService {
         // Fetch user profile
         let profile = self.fetch_user_profile(&provider, token.access_token().secret()).await?;
         
-        Ok((profile, source, callback_url))
+        Ok((profile, source, callback_url, machine))
     }
 
     /// Fetch user profile from OAuth provider
@@ -169,6 +188,10 @@ impl OAuthService {
                     name: user_data["name"].as_str().unwrap_or("").to_string(),
                     username: None, // Google doesn't provide username
                     avatar_url: user_data["picture"].as_str().map(|s| s.to_string()),
+                    // Google Workspace accounts carry their hosted domain
+                    groups: user_data["hd"].as_str()
+                        .map(|domain| vec![format!("google:{}", domain.to_lowercase())])
+                        .unwrap_or_default(),
                 })
             }
             OAuthProvider::GitHub => {
@@ -186,6 +209,7 @@ impl OAuthService {
                     name: user_data["name"].as_str().unwrap_or("").to_string(),
                     username: user_data["login"].as_str().map(|s| s.to_string()),
                     avatar_url: user_data["avatar_url"].as_str().map(|s| s.to_string()),
+                    groups: self.fetch_github_groups(access_token).await,
                 })
             }
         }
@@ -218,6 +242,48 @@ impl OAuthService {
             .ok_or_else(|| AuthError::OAuthError("No email found".to_string()))
     }
 
+    /// Fetch the GitHub organizations and teams a user belongs to, as
+    /// `github:org` and `github:org/team`. Best-effort: a user without any,
+    /// or who didn't grant `read:org`, just has no groups.
+    async fn fetch_github_groups(&self, access_token: &str) -> Vec<String> {
+        let client = reqwest::Client::new();
+        let mut groups = Vec::new();
+
+        for url in ["https://api.github.com/user/orgs", "https://api.github.com/user/teams"] {
+            let response = client
+                .get(url)
+                .query(&[("per_page", "100")])
+                .bearer_auth(access_token)
+                .header("User-Agent", "Mothership/1.0")
+                .send()
+                .await;
+            let entries: Vec<serde_json::Value> = match response {
+                Ok(response) if response.status().is_success() => response.json().await.unwrap_or_default(),
+                Ok(response) => {
+                    warn!("Failed to fetch GitHub groups from {}: HTTP {}", url, response.status());
+                    continue;
+                }
+                Err(e) => {
+                    warn!("Failed to fetch GitHub groups from {}: {}", url, e);
+                    continue;
+                }
+            };
+
+            for entry in entries {
+                let group = match (entry["login"].as_str(), entry["organization"]["login"].as_str(), entry["slug"].as_str()) {
+                    (Some(org), _, _) => format!("github:{}", org),
+                    (None, Some(org), Some(team)) => format!("github:{}/{}", org, team),
+                    _ => continue,
+                };
+                groups.push(group.to_lowercase());
+            }
+        }
+
+        groups.sort();
+        groups.dedup();
+        groups
+    }
+
     /// Clean up expired states
     pub async fn cleanup_expired_states(&self) {
         // For now, just clear all states older than 10 minutes
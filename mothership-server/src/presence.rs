@@ -0,0 +1,104 @@
+//! Who is connected to each rift.
+//!
+//! Every editing connection (not observers) counts toward its user's presence
+//! in the rift. A user's first connection announces `CollaboratorJoined` on the
+//! rift channel and their last one `CollaboratorLeft`; other instances mirror
+//! those broadcasts, so every instance knows who is active everywhere.
+
+use mothership_common::{protocol::SyncMessage, RiftId, UserId};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tracing::debug;
+
+use crate::sync::SyncState;
+
+struct Collaborator {
+    username: String,
+    connections: usize,
+}
+
+/// Users connected to each rift
+#[derive(Clone, Default)]
+pub struct Presence {
+    rifts: Arc<Mutex<HashMap<RiftId, HashMap<UserId, Collaborator>>>>,
+}
+
+impl Presence {
+    /// Count a connection; true if it's the user's first in the rift
+    fn connect(&self, rift_id: RiftId, user_id: UserId, username: &str) -> bool {
+        let mut rifts = self.rifts.lock().unwrap();
+        let collaborator = rifts.entry(rift_id).or_default().entry(user_id).or_insert_with(|| Collaborator {
+            username: username.to_string(),
+            connections: 0,
+        });
+        collaborator.connections += 1;
+        collaborator.connections == 1
+    }
+
+    /// Stop counting a connection; true if it was the user's last in the rift
+    fn disconnect(&self, rift_id: RiftId, user_id: UserId) -> bool {
+        let mut rifts = self.rifts.lock().unwrap();
+        let Some(users) = rifts.get_mut(&rift_id) else { return false };
+        let Some(collaborator) = users.get_mut(&user_id) else { return false };
+
+        collaborator.connections -= 1;
+        if collaborator.connections > 0 {
+            return false;
+        }
+        users.remove(&user_id);
+        if users.is_empty() {
+            rifts.remove(&rift_id);
+        }
+        true
+    }
+
+    /// Usernames of everyone connected to a rift, sorted
+    pub fn in_rift(&self, rift_id: RiftId) -> Vec<String> {
+        let rifts = self.rifts.lock().unwrap();
+        let mut usernames: Vec<String> = rifts.get(&rift_id)
+            .map(|users| users.values().map(|c| c.username.clone()).collect())
+            .unwrap_or_default();
+        usernames.sort();
+        usernames
+    }
+
+    /// Drop the presence of a rift that no longer exists
+    pub fn forget_rift(&self, rift_id: RiftId) {
+        self.rifts.lock().unwrap().remove(&rift_id);
+    }
+
+    /// Mirror a presence change another instance broadcast
+    pub fn apply_relayed(&self, message: &SyncMessage) {
+        match message {
+            SyncMessage::CollaboratorJoined { rift_id, user_id, username } => {
+                self.connect(*rift_id, *user_id, username);
+            }
+            SyncMessage::CollaboratorLeft { rift_id, user_id } => {
+                self.disconnect(*rift_id, *user_id);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Count a new editing connection, announcing the user if they just arrived
+pub fn join(state: &SyncState, rift_id: RiftId, user_id: UserId, username: &str) {
+    if state.presence.connect(rift_id, user_id, username) {
+        debug!("👋 {} is active in rift {}", username, rift_id);
+        state.broadcast(format!("rift_{}", rift_id), SyncMessage::CollaboratorJoined {
+            rift_id,
+            user_id,
+            username: username.to_string(),
+        });
+    }
+}
+
+/// Stop counting a closed connection, announcing the user's departure with their last one
+pub fn leave(state: &SyncState, rift_id: RiftId, user_id: UserId) {
+    if state.presence.disconnect(rift_id, user_id) {
+        state.cursors.forget_user(rift_id, user_id);
+        state.file_activity.forget_user(rift_id, user_id);
+        debug!("👋 User {} left rift {}", user_id, rift_id);
+        state.broadcast(format!("rift_{}", rift_id), SyncMessage::CollaboratorLeft { rift_id, user_id });
+    }
+}
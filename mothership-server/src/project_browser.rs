@@ -0,0 +1,687 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::Html,
+    routing::get,
+    Json, Router,
+};
+use axum_extra::extract::cookie::CookieJar;
+use chrono::{DateTime, Utc};
+use mothership_common::{
+    features::Feature,
+    protocol::ApiResponse,
+    search::{SearchQuery, SearchResults, TextSearch},
+    ChangeType, Checkpoint, CheckpointId, ProjectId, ProjectVisibility, Rift, RiftId, UserId,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tracing::error;
+
+use crate::api_error::ApiError;
+use crate::handlers::authenticate_request;
+use crate::project_visibility::{self, ProjectReader};
+use crate::AppState;
+
+/// Files larger than this are listed but not shown in the viewer
+const MAX_VIEWED_FILE_SIZE: usize = 1024 * 1024;
+
+/// Default and largest number of checkpoints in a history page
+const DEFAULT_HISTORY_PAGE: usize = 50;
+const MAX_HISTORY_PAGE: usize = 200;
+
+/// Read-only project browser: the page and the JSON endpoints behind it.
+///
+/// Served with the web UI, so the endpoints accept the web session cookie as
+/// well as a bearer token. Public projects can be browsed without either
+/// (see `project_visibility`).
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/browse", get(browser_page))
+        .route("/browse/api/projects", get(list_projects))
+        .route("/browse/api/projects/:id/rifts", get(list_rifts))
+        .route("/browse/api/rifts/:rift_id/tree", get(rift_tree))
+        .route("/browse/api/rifts/:rift_id/file", get(rift_file))
+        .route("/browse/api/rifts/:rift_id/history", get(rift_history))
+        .route("/browse/api/rifts/:rift_id/search", get(rift_search))
+}
+
+#[derive(Debug, Serialize)]
+struct BrowserProject {
+    id: ProjectId,
+    name: String,
+    description: String,
+    /// Whether the user is a member; other projects are public or internal
+    member: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct BrowserRift {
+    id: RiftId,
+    name: String,
+    parent_rift: Option<RiftId>,
+    last_checkpoint: Option<CheckpointId>,
+    /// The rift the user is working in
+    current: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct CheckpointSummary {
+    id: CheckpointId,
+    author: String,
+    timestamp: DateTime<Utc>,
+    message: Option<String>,
+    auto_generated: bool,
+    squashed: u32,
+    file_count: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct TreeEntry {
+    path: PathBuf,
+    size: u64,
+    executable: bool,
+    symlink_target: Option<PathBuf>,
+}
+
+#[derive(Debug, Serialize)]
+struct BrowserTree {
+    /// `None` before the rift's first checkpoint
+    checkpoint: Option<CheckpointSummary>,
+    files: Vec<TreeEntry>,
+}
+
+#[derive(Debug, Serialize)]
+struct BrowserFile {
+    path: PathBuf,
+    checkpoint_id: CheckpointId,
+    size: u64,
+    symlink_target: Option<PathBuf>,
+    /// `None` if the file is too large to view
+    content: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TreeQuery {
+    /// Checkpoint to show instead of the rift's latest
+    checkpoint: Option<CheckpointId>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FileQuery {
+    path: PathBuf,
+    checkpoint: Option<CheckpointId>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HistoryQuery {
+    before: Option<CheckpointId>,
+    limit: Option<usize>,
+}
+
+/// The signed-in user, if any: a bearer token if one is sent, otherwise the
+/// web session
+async fn browser_user(state: &AppState, headers: &HeaderMap, jar: &CookieJar) -> Result<Option<UserId>, StatusCode> {
+    if headers.contains_key(header::AUTHORIZATION) {
+        return authenticate_request(state, headers).map(Some);
+    }
+    Ok(crate::web_ui::session_user(state, jar).await)
+}
+
+/// Load a rift the user may read
+async fn readable_rift(state: &AppState, user_id: Option<UserId>, rift_id: RiftId) -> Result<Rift, StatusCode> {
+    let rift = match state.db.get_rift(rift_id).await {
+        Ok(Some(rift)) => rift,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    };
+    let reader = project_visibility::authorize_read(state, user_id, rift.project_id).await?;
+    match project_visibility::can_read_rift(&state.db, &rift, reader).await {
+        Ok(true) => Ok(rift),
+        Ok(false) => Err(StatusCode::FORBIDDEN),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// The requested checkpoint of a rift, or its latest
+async fn rift_checkpoint(state: &AppState, rift: &Rift, checkpoint_id: Option<CheckpointId>) -> Result<Option<Checkpoint>, StatusCode> {
+    let Some(checkpoint_id) = checkpoint_id else {
+        return Ok(state.sync.storage.latest_checkpoint(rift.id).await);
+    };
+    match state.sync.storage.load_checkpoint(checkpoint_id).await {
+        Ok(Some(checkpoint)) if checkpoint.rift_id == rift.id => Ok(Some(checkpoint)),
+        Ok(_) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            error!("Failed to load checkpoint {}: {}", checkpoint_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Summaries of checkpoints, with authors' names looked up once each
+async fn summarize(state: &AppState, checkpoints: Vec<Checkpoint>) -> Vec<CheckpointSummary> {
+    let mut authors: HashMap<UserId, String> = HashMap::new();
+    let mut summaries = Vec::with_capacity(checkpoints.len());
+    for checkpoint in checkpoints {
+        if !authors.contains_key(&checkpoint.author) {
+            let name = match state.db.get_user(checkpoint.author).await {
+                Ok(Some(user)) => user.username,
+                _ if checkpoint.author.is_nil() => "mothership".to_string(),
+                _ => "unknown".to_string(),
+            };
+            authors.insert(checkpoint.author, name);
+        }
+        summaries.push(CheckpointSummary {
+            id: checkpoint.id,
+            author: authors[&checkpoint.author].clone(),
+            timestamp: checkpoint.timestamp,
+            message: checkpoint.message,
+            auto_generated: checkpoint.auto_generated,
+            squashed: checkpoint.squashed,
+            file_count: checkpoint.changes.iter().filter(|c| !matches!(c.change_type, ChangeType::Deleted)).count(),
+        });
+    }
+    summaries
+}
+
+/// The browser page; it loads everything else from the JSON endpoints.
+/// Without a session it only shows public projects.
+async fn browser_page() -> Html<&'static str> {
+    Html(BROWSER_HTML)
+}
+
+/// Projects the user is a member of, then the others they may read:
+/// internal ones once signed in, and public ones
+async fn list_projects(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    jar: CookieJar,
+) -> Result<Json<ApiResponse<Vec<BrowserProject>>>, StatusCode> {
+    let user_id = browser_user(&state, &headers, &jar).await?;
+
+    let list_failed = |e: anyhow::Error| {
+        error!("Failed to list projects for the browser: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+    let mut projects = match user_id {
+        Some(user_id) => state.db.get_user_projects(user_id).await.map_err(list_failed)?,
+        None => Vec::new(),
+    };
+    projects.sort_by_key(|project| project.name.to_lowercase());
+    let member_of = projects.len();
+
+    let mut visible = state.db.list_projects_with_visibility(ProjectVisibility::Public).await.map_err(list_failed)?;
+    if user_id.is_some() {
+        visible.extend(state.db.list_projects_with_visibility(ProjectVisibility::Internal).await.map_err(list_failed)?);
+    }
+    visible.retain(|project| !projects.iter().any(|member| member.id == project.id));
+    visible.sort_by_key(|project| project.name.to_lowercase());
+    projects.extend(visible);
+
+    Ok(Json(ApiResponse::success(projects
+        .into_iter()
+        .enumerate()
+        .map(|(index, project)| BrowserProject {
+            id: project.id,
+            name: project.name,
+            description: project.description,
+            member: index < member_of,
+        })
+        .collect())))
+}
+
+/// Rifts of a project the user may read
+async fn list_rifts(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    jar: CookieJar,
+    Path(project_id): Path<ProjectId>,
+) -> Result<Json<ApiResponse<Vec<BrowserRift>>>, StatusCode> {
+    let user_id = browser_user(&state, &headers, &jar).await?;
+    let reader = project_visibility::authorize_read(&state, user_id, project_id).await?;
+
+    let current = match reader {
+        ProjectReader::Member(user_id) => state.db.get_user_rift(project_id, user_id).await.ok().flatten().map(|rift| rift.id),
+        ProjectReader::Visitor(_) => None,
+    };
+    let rifts = state.db.get_project_rifts(project_id).await.map_err(|e| {
+        error!("Failed to list rifts of project {}: {}", project_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let mut readable = Vec::new();
+    for rift in rifts {
+        if !matches!(project_visibility::can_read_rift(&state.db, &rift, reader).await, Ok(true)) {
+            continue;
+        }
+        readable.push(BrowserRift {
+            current: Some(rift.id) == current,
+            id: rift.id,
+            name: rift.name,
+            parent_rift: rift.parent_rift,
+            last_checkpoint: rift.last_checkpoint,
+        });
+    }
+
+    Ok(Json(ApiResponse::success(readable)))
+}
+
+/// Every file in a checkpoint of a rift (its latest by default)
+async fn rift_tree(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    jar: CookieJar,
+    Path(rift_id): Path<RiftId>,
+    Query(query): Query<TreeQuery>,
+) -> Result<Json<ApiResponse<BrowserTree>>, StatusCode> {
+    let user_id = browser_user(&state, &headers, &jar).await?;
+    let rift = readable_rift(&state, user_id, rift_id).await?;
+
+    let Some(checkpoint) = rift_checkpoint(&state, &rift, query.checkpoint).await? else {
+        return Ok(Json(ApiResponse::success(BrowserTree { checkpoint: None, files: Vec::new() })));
+    };
+
+    let mut files: Vec<TreeEntry> = checkpoint.changes
+        .iter()
+        .filter(|change| !matches!(change.change_type, ChangeType::Deleted))
+        .map(|change| TreeEntry {
+            path: change.path.clone(),
+            size: change.size,
+            executable: change.metadata.is_executable(),
+            symlink_target: change.metadata.symlink_target.clone(),
+        })
+        .collect();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let checkpoint = summarize(&state, vec![checkpoint]).await.pop();
+    Ok(Json(ApiResponse::success(BrowserTree { checkpoint, files })))
+}
+
+/// One file's content at a checkpoint of a rift (its latest by default)
+async fn rift_file(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    jar: CookieJar,
+    Path(rift_id): Path<RiftId>,
+    Query(query): Query<FileQuery>,
+) -> Result<Json<ApiResponse<BrowserFile>>, StatusCode> {
+    let user_id = browser_user(&state, &headers, &jar).await?;
+    let rift = readable_rift(&state, user_id, rift_id).await?;
+
+    let checkpoint = rift_checkpoint(&state, &rift, query.checkpoint).await?.ok_or(StatusCode::NOT_FOUND)?;
+    let change = checkpoint.changes
+        .iter()
+        .find(|change| change.path == query.path && !matches!(change.change_type, ChangeType::Deleted))
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let content = if change.size as usize > MAX_VIEWED_FILE_SIZE {
+        None
+    } else {
+        match state.sync.storage.get_content(&change.content_hash).await {
+            Ok(Some(content)) => Some(content),
+            Ok(None) => return Err(StatusCode::NOT_FOUND),
+            Err(e) => {
+                error!("Failed to read {} of checkpoint {}: {}", change.path.display(), checkpoint.id, e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        }
+    };
+
+    Ok(Json(ApiResponse::success(BrowserFile {
+        path: change.path.clone(),
+        checkpoint_id: checkpoint.id,
+        size: change.size,
+        symlink_target: change.metadata.symlink_target.clone(),
+        content,
+    })))
+}
+
+/// Search the text of a rift's live files, including changes not yet checkpointed
+async fn rift_search(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    jar: CookieJar,
+    Path(rift_id): Path<RiftId>,
+    Query(query): Query<SearchQuery>,
+) -> Result<Json<ApiResponse<SearchResults>>, ApiError> {
+    let user_id = browser_user(&state, &headers, &jar).await?;
+    let rift = readable_rift(&state, user_id, rift_id).await?;
+    if !state.sync.features.is_enabled(Feature::ProjectSearch, Some(rift.project_id)).await {
+        return Err(ApiError::not_found("Project search is turned off for this project"));
+    }
+
+    let search = TextSearch::new(&query).map_err(ApiError::bad_request)?;
+    let mut files: Vec<_> = match state.sync.storage.get_live_state(rift.id).await {
+        Ok(files) => files.into_iter().collect(),
+        Err(e) => {
+            error!("Failed to load live files of rift {}: {}", rift.id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into());
+        }
+    };
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut results = SearchResults::default();
+    for (path, content) in &files {
+        if !search.search_file(path, content, &mut results) {
+            break;
+        }
+    }
+    Ok(Json(ApiResponse::success(results)))
+}
+
+/// A page of a rift's checkpoints, newest first
+async fn rift_history(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    jar: CookieJar,
+    Path(rift_id): Path<RiftId>,
+    Query(query): Query<HistoryQuery>,
+) -> Result<Json<ApiResponse<Vec<CheckpointSummary>>>, StatusCode> {
+    let user_id = browser_user(&state, &headers, &jar).await?;
+    let rift = readable_rift(&state, user_id, rift_id).await?;
+
+    let limit = query.limit.unwrap_or(DEFAULT_HISTORY_PAGE).clamp(1, MAX_HISTORY_PAGE);
+    let checkpoints = match state.sync.storage.list_checkpoints_page(rift.id, query.before, limit).await {
+        Ok(Some(checkpoints)) => checkpoints,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            error!("Failed to list checkpoints of rift {}: {}", rift.id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    Ok(Json(ApiResponse::success(summarize(&state, checkpoints).await)))
+}
+
+const BROWSER_HTML: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>Mothership - Projects</title>
+    <link rel="icon" type="image/png" href="/static/icon.png">
+    <style>
+        body {
+            font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif;
+            margin: 0;
+            background: #1a1b26;
+            color: #c0caf5;
+            display: flex;
+            flex-direction: column;
+            height: 100vh;
+        }
+        header {
+            display: flex;
+            align-items: center;
+            gap: 1rem;
+            padding: 0.75rem 1rem;
+            background: linear-gradient(135deg, #667eea 0%, #764ba2 100%);
+            color: white;
+        }
+        header img { height: 32px; }
+        header h1 { font-size: 1.2rem; margin: 0 1rem 0 0; }
+        select {
+            background: rgba(255, 255, 255, 0.2);
+            color: white;
+            border: 1px solid rgba(255, 255, 255, 0.4);
+            border-radius: 6px;
+            padding: 0.3rem 0.5rem;
+        }
+        select option { color: black; }
+        #checkpoint-label { margin-left: auto; font-size: 0.9rem; opacity: 0.9; }
+        main { display: flex; flex: 1; min-height: 0; }
+        aside { width: 300px; overflow: auto; border-right: 1px solid #2f334d; padding: 0.5rem 0; }
+        #history { border-right: none; border-left: 1px solid #2f334d; }
+        aside h2 { font-size: 0.8rem; text-transform: uppercase; opacity: 0.6; margin: 0.5rem 1rem; }
+        .entry { padding: 0.2rem 1rem; cursor: pointer; white-space: nowrap; font-size: 0.9rem; }
+        .entry:hover { background: #24283b; }
+        .entry.selected { background: #364a82; }
+        .dir { opacity: 0.8; }
+        .muted { opacity: 0.6; font-size: 0.8rem; }
+        #viewer { flex: 1; overflow: auto; }
+        #viewer-title { padding: 0.5rem 1rem; border-bottom: 1px solid #2f334d; font-family: monospace; }
+        pre { margin: 0; font-family: 'Monaco', 'Courier New', monospace; font-size: 0.85rem; line-height: 1.5; }
+        .line { display: block; padding-right: 1rem; }
+        .line::before {
+            content: attr(data-n);
+            display: inline-block;
+            width: 3.5rem;
+            padding-right: 1rem;
+            text-align: right;
+            color: #565f89;
+            user-select: none;
+        }
+        .kw { color: #bb9af7; }
+        .str { color: #9ece6a; }
+        .com { color: #565f89; font-style: italic; }
+        .num { color: #ff9e64; }
+        .message { padding: 2rem; opacity: 0.7; }
+    </style>
+</head>
+<body>
+    <header>
+        <a href="/"><img src="/static/icon.png" alt="Mothership"></a>
+        <h1>Projects</h1>
+        <select id="projects"></select>
+        <select id="rifts"></select>
+        <span id="checkpoint-label"></span>
+    </header>
+    <main>
+        <aside id="tree"><h2>Files</h2><div id="files"></div></aside>
+        <section id="viewer"><div class="message">Pick a file to view it.</div></section>
+        <aside id="history"><h2>History</h2><div id="checkpoints"></div><div id="more"></div></aside>
+    </main>
+    <script>
+        const state = { rift: null, checkpoint: null, path: null, oldest: null };
+
+        async function api(path) {
+            const response = await fetch(path, { credentials: 'same-origin' });
+            if (response.status === 401) {
+                window.location = '/login';
+                throw new Error('Signed out');
+            }
+            const body = await response.json().catch(() => ({}));
+            if (!response.ok || !body.success) {
+                throw new Error(body.error || response.statusText);
+            }
+            return body.data;
+        }
+
+        function escapeHtml(text) {
+            return text.replace(/&/g, '&amp;').replace(/</g, '&lt;').replace(/>/g, '&gt;');
+        }
+
+        function element(tag, className, text) {
+            const node = document.createElement(tag);
+            if (className) node.className = className;
+            if (text !== undefined) node.textContent = text;
+            return node;
+        }
+
+        function showMessage(text) {
+            const viewer = document.getElementById('viewer');
+            viewer.innerHTML = '';
+            viewer.appendChild(element('div', 'message', text));
+        }
+
+        async function loadProjects() {
+            const projects = await api('/browse/api/projects');
+            const select = document.getElementById('projects');
+            select.innerHTML = '';
+            const linked = new URLSearchParams(window.location.search).get('project');
+            for (const project of projects) {
+                const option = element('option', null, project.member ? project.name : `${project.name} (read-only)`);
+                option.value = project.id;
+                option.selected = project.id === linked;
+                select.appendChild(option);
+            }
+            if (projects.length === 0) {
+                showMessage('There are no projects to show yet.');
+                return;
+            }
+            select.onchange = () => loadRifts(select.value);
+            await loadRifts(select.value);
+        }
+
+        async function loadRifts(projectId) {
+            const rifts = await api(`/browse/api/projects/${projectId}/rifts`);
+            const select = document.getElementById('rifts');
+            select.innerHTML = '';
+            for (const rift of rifts) {
+                const option = element('option', null, rift.current ? `${rift.name} (current)` : rift.name);
+                option.value = rift.id;
+                option.selected = rift.current;
+                select.appendChild(option);
+            }
+            select.onchange = () => openRift(select.value);
+            if (rifts.length > 0) {
+                await openRift(select.value);
+            }
+        }
+
+        async function openRift(riftId) {
+            state.rift = riftId;
+            state.oldest = null;
+            document.getElementById('checkpoints').innerHTML = '';
+            await Promise.all([loadTree(null), loadHistory()]);
+        }
+
+        async function loadTree(checkpointId) {
+            const query = checkpointId ? `?checkpoint=${checkpointId}` : '';
+            const tree = await api(`/browse/api/rifts/${state.rift}/tree${query}`);
+            state.checkpoint = tree.checkpoint ? tree.checkpoint.id : null;
+            document.getElementById('checkpoint-label').textContent = tree.checkpoint
+                ? `${tree.checkpoint.id.slice(0, 8)} · ${tree.checkpoint.message || '(no message)'} · ${new Date(tree.checkpoint.timestamp).toLocaleString()}`
+                : 'No checkpoints yet';
+            for (const entry of document.querySelectorAll('#checkpoints .entry')) {
+                entry.classList.toggle('selected', entry.dataset.id === state.checkpoint);
+            }
+            renderTree(tree.files);
+            if (state.path && tree.files.some(file => file.path === state.path)) {
+                await openFile(state.path);
+            } else {
+                state.path = null;
+                showMessage(tree.checkpoint ? 'Pick a file to view it.' : 'This rift has no checkpoints yet.');
+            }
+        }
+
+        // Nest the flat, sorted path list into directories
+        function renderTree(files) {
+            const root = { dirs: {}, files: [] };
+            for (const file of files) {
+                const parts = file.path.split('/');
+                let node = root;
+                for (const dir of parts.slice(0, -1)) {
+                    node = node.dirs[dir] = node.dirs[dir] || { dirs: {}, files: [] };
+                }
+                node.files.push({ name: parts[parts.length - 1], file });
+            }
+            const container = document.getElementById('files');
+            container.innerHTML = '';
+            renderNode(root, container, 0);
+        }
+
+        function renderNode(node, container, depth) {
+            for (const name of Object.keys(node.dirs).sort()) {
+                const dir = element('div', 'entry dir', `▸ ${name}/`);
+                dir.style.paddingLeft = `${1 + depth}rem`;
+                const children = element('div');
+                children.style.display = 'none';
+                dir.onclick = () => {
+                    const open = children.style.display === 'none';
+                    children.style.display = open ? 'block' : 'none';
+                    dir.textContent = `${open ? '▾' : '▸'} ${name}/`;
+                };
+                container.appendChild(dir);
+                container.appendChild(children);
+                renderNode(node.dirs[name], children, depth + 1);
+            }
+            for (const { name, file } of node.files) {
+                const label = file.symlink_target ? `${name} → ${file.symlink_target}` : name;
+                const entry = element('div', 'entry', label);
+                entry.style.paddingLeft = `${1 + depth}rem`;
+                entry.dataset.path = file.path;
+                entry.onclick = () => openFile(file.path);
+                container.appendChild(entry);
+            }
+        }
+
+        async function openFile(path) {
+            state.path = path;
+            for (const entry of document.querySelectorAll('#files .entry')) {
+                entry.classList.toggle('selected', entry.dataset.path === path);
+            }
+            const query = `path=${encodeURIComponent(path)}&checkpoint=${state.checkpoint}`;
+            const file = await api(`/browse/api/rifts/${state.rift}/file?${query}`);
+            const viewer = document.getElementById('viewer');
+            viewer.innerHTML = '';
+            const title = element('div', null, `${file.path} · ${file.size} bytes`);
+            title.id = 'viewer-title';
+            viewer.appendChild(title);
+            if (file.content === null) {
+                viewer.appendChild(element('div', 'message', 'This file is too large to show here.'));
+                return;
+            }
+            const pre = element('pre');
+            pre.innerHTML = highlight(file.content, file.path.split('.').pop())
+                .split('\n')
+                .map((line, i) => `<span class="line" data-n="${i + 1}">${line}</span>`)
+                .join('');
+            viewer.appendChild(pre);
+        }
+
+        // A small highlighter: keywords, strings, comments and numbers for
+        // common languages; anything else is shown as plain text
+        const KEYWORDS = 'as async await break case catch class const continue def default defer else enum export extends false fn for from func function go if impl import in interface let loop match mod mut new nil none null package pub return self static struct super switch this throw true try type use var where while with yield';
+        const HASH_COMMENTS = ['py', 'sh', 'bash', 'rb', 'toml', 'yaml', 'yml', 'r', 'pl', 'conf'];
+        const SLASH_COMMENTS = ['rs', 'js', 'mjs', 'ts', 'tsx', 'jsx', 'go', 'c', 'h', 'cc', 'cpp', 'hpp', 'java', 'kt', 'cs', 'swift', 'scala', 'dart', 'php', 'css', 'scss'];
+
+        function highlight(text, extension) {
+            const slash = SLASH_COMMENTS.includes(extension);
+            const hash = HASH_COMMENTS.includes(extension);
+            if (!slash && !hash) {
+                return escapeHtml(text);
+            }
+            const comment = slash ? String.raw`\/\/[^\n]*|\/\*[\s\S]*?\*\/` : String.raw`#[^\n]*`;
+            const token = new RegExp(`(${comment})|("(?:\\\\.|[^"\\\\\n])*"|'(?:\\\\.|[^'\\\\\n])*'|\`(?:\\\\.|[^\`\\\\])*\`)|\\b(\\d[\\d_.]*)\\b|\\b(${KEYWORDS.split(' ').join('|')})\\b`, 'g');
+            let html = '';
+            let last = 0;
+            for (const match of text.matchAll(token)) {
+                html += escapeHtml(text.slice(last, match.index));
+                const className = match[1] ? 'com' : match[2] ? 'str' : match[3] ? 'num' : 'kw';
+                // Keep spans within a line so line numbering can split on newlines
+                html += escapeHtml(match[0]).split('\n').map(part => `<span class="${className}">${part}</span>`).join('\n');
+                last = match.index + match[0].length;
+            }
+            return html + escapeHtml(text.slice(last));
+        }
+
+        async function loadHistory() {
+            const query = state.oldest ? `&before=${state.oldest}` : '';
+            const checkpoints = await api(`/browse/api/rifts/${state.rift}/history?limit=50${query}`);
+            const container = document.getElementById('checkpoints');
+            for (const checkpoint of checkpoints) {
+                const entry = element('div', 'entry');
+                entry.dataset.id = checkpoint.id;
+                entry.classList.toggle('selected', checkpoint.id === state.checkpoint);
+                const auto = checkpoint.auto_generated ? ' [auto]' : '';
+                entry.appendChild(element('div', null, `${checkpoint.id.slice(0, 8)} ${checkpoint.message || '(no message)'}${auto}`));
+                entry.appendChild(element('div', 'muted', `${checkpoint.author} · ${new Date(checkpoint.timestamp).toLocaleString()} · ${checkpoint.file_count} files`));
+                entry.onclick = () => loadTree(checkpoint.id);
+                container.appendChild(entry);
+            }
+            const more = document.getElementById('more');
+            more.innerHTML = '';
+            if (checkpoints.length === 50) {
+                state.oldest = checkpoints[checkpoints.length - 1].id;
+                const button = element('div', 'entry muted', 'Load older checkpoints…');
+                button.onclick = loadHistory;
+                more.appendChild(button);
+            }
+        }
+
+        loadProjects().catch(error => showMessage(`Failed to load: ${error.message}`));
+    </script>
+</body>
+</html>
+"##;
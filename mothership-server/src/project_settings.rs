@@ -0,0 +1,154 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::get,
+    Json, Router,
+};
+use mothership_common::{
+    protocol::{ApiResponse, UpdateProjectSettingsRequest},
+    push_rules::{PushRuleChecker, PushRuleViolation},
+    ProjectId, ProjectSettings, RiftId, SecretFinding, SecretScanMode, UserId,
+};
+use tracing::{error, info};
+
+use crate::audit::{self, AuditAction, AuditEvent};
+use crate::database::Database;
+use crate::handlers::authenticate_request;
+use crate::secret_scan;
+use crate::storage::StorageEngine;
+use crate::AppState;
+
+/// Project settings endpoints
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/projects/:id/settings", get(get_settings).patch(update_settings))
+}
+
+/// Authenticate the request and check the caller is a member of the project
+async fn member_of(state: &AppState, headers: &HeaderMap, project_id: ProjectId) -> Result<UserId, StatusCode> {
+    let user_id = authenticate_request(state, headers)?;
+
+    match state.db.get_project(project_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+
+    if !state.db.user_has_project_access(user_id, project_id).await.unwrap_or(false) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok(user_id)
+}
+
+/// What a manual checkpoint of a rift's live files runs into
+#[derive(Default)]
+pub struct CheckpointReview {
+    /// Push rules it breaks, including secrets if the project rejects them
+    pub violations: Vec<PushRuleViolation>,
+    /// Secrets found in the files
+    pub secrets: Vec<SecretFinding>,
+}
+
+/// Check a manual checkpoint of the rift's live files against the project's
+/// push rules and scan it for secrets, recording what's found
+pub async fn review_checkpoint(
+    db: &Database,
+    storage: &StorageEngine,
+    project_id: ProjectId,
+    rift_id: RiftId,
+    author: UserId,
+    message: Option<&str>,
+) -> anyhow::Result<CheckpointReview> {
+    let settings = db.get_project_settings(project_id).await?;
+    let checker = PushRuleChecker::new(&settings);
+    if !checker.is_active() && settings.secret_scanning == SecretScanMode::Off {
+        return Ok(CheckpointReview::default());
+    }
+
+    let mut files: Vec<_> = storage.get_live_state(rift_id).await?.into_iter().collect();
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut violations: Vec<_> = checker.check_checkpoint_message(message, false).into_iter().collect();
+    for (path, content) in &files {
+        violations.extend(checker.check_file(path, content.len() as u64));
+    }
+
+    let secrets = secret_scan::scan_files(
+        files.iter().map(|(path, content)| (path.as_path(), content.as_str())),
+        rift_id,
+        author,
+        settings.secret_scanning,
+    );
+    if settings.secret_scanning == SecretScanMode::Reject {
+        violations.extend(secrets.iter().map(PushRuleViolation::from));
+    }
+    secret_scan::record(storage, project_id, &secrets).await;
+
+    Ok(CheckpointReview { violations, secrets })
+}
+
+/// Get a project's settings
+async fn get_settings(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(project_id): Path<ProjectId>,
+) -> Result<Json<ApiResponse<ProjectSettings>>, StatusCode> {
+    member_of(&state, &headers, project_id).await?;
+
+    let settings = state.db.get_project_settings(project_id).await
+        .map_err(|e| {
+            error!("Failed to load settings of project {}: {}", project_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ApiResponse::success(settings)))
+}
+
+/// Change some of a project's settings
+async fn update_settings(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(project_id): Path<ProjectId>,
+    Json(request): Json<UpdateProjectSettingsRequest>,
+) -> Result<Json<ApiResponse<ProjectSettings>>, StatusCode> {
+    let user_id = member_of(&state, &headers, project_id).await?;
+
+    let mut settings = state.db.get_project_settings(project_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    settings.conflict_policy = request.conflict_policy.unwrap_or(settings.conflict_policy);
+    if let Some(push_rules) = request.push_rules {
+        settings.push_rules = push_rules;
+    }
+    settings.secret_scanning = request.secret_scanning.unwrap_or(settings.secret_scanning);
+    settings.auto_checkpoint_interval = request.auto_checkpoint_interval.unwrap_or(settings.auto_checkpoint_interval);
+    settings.visibility = request.visibility.unwrap_or(settings.visibility);
+    if let Some(allowed_file_types) = request.allowed_file_types {
+        settings.allowed_file_types = allowed_file_types.into_iter()
+            .map(|pattern| pattern.trim().to_string())
+            .filter(|pattern| !pattern.is_empty())
+            .collect();
+    }
+
+    state.db.set_project_settings(project_id, &settings).await
+        .map_err(|e| {
+            error!("Failed to save settings of project {}: {}", project_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    info!("⚙️ Project {} conflict policy is now {}, push rules {:?}, secret scanning {}, automatic checkpoints every {}s, visibility {}",
+        project_id, settings.conflict_policy.as_str(), settings.push_rules, settings.secret_scanning.as_str(), settings.auto_checkpoint_interval,
+        settings.visibility.as_str());
+    audit::record(&state.db, AuditEvent::new(AuditAction::ProjectSettingsUpdated, Some(user_id))
+        .project(project_id)
+        .details(serde_json::json!({
+            "conflict_policy": settings.conflict_policy.as_str(),
+            "push_rules": settings.push_rules,
+            "secret_scanning": settings.secret_scanning.as_str(),
+            "auto_checkpoint_interval": settings.auto_checkpoint_interval,
+            "allowed_file_types": settings.allowed_file_types,
+            "visibility": settings.visibility.as_str(),
+        }))).await;
+
+    Ok(Json(ApiResponse::success(settings)))
+}
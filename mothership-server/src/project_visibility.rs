@@ -0,0 +1,83 @@
+//! Who may read a project.
+//!
+//! Members read and write. Internal projects can also be read by anyone
+//! signed in, and public projects by anyone at all, through project
+//! details, history, checkpoint archives, file history and the project
+//! browser. Readers who aren't members only see rifts shared with the whole
+//! project, and every write still needs membership.
+
+use anyhow::Result;
+use axum::http::{header, HeaderMap, StatusCode};
+use mothership_common::{ProjectId, ProjectVisibility, Rift, RiftVisibility, UserId};
+use tracing::error;
+
+use crate::database::Database;
+use crate::handlers::authenticate_request;
+use crate::AppState;
+
+/// How the caller reads a project
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectReader {
+    Member(UserId),
+    /// A signed-in user outside the project, or nobody signed in
+    Visitor(Option<UserId>),
+}
+
+/// The user a request is signed in as, or `None` without an Authorization
+/// header. A token that doesn't verify is still refused.
+pub fn optional_user(state: &AppState, headers: &HeaderMap) -> Result<Option<UserId>, StatusCode> {
+    if !headers.contains_key(header::AUTHORIZATION) {
+        return Ok(None);
+    }
+    authenticate_request(state, headers).map(Some)
+}
+
+/// Check the caller may read a project
+pub async fn authorize_read(state: &AppState, user_id: Option<UserId>, project_id: ProjectId) -> Result<ProjectReader, StatusCode> {
+    match state.db.get_project(project_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+
+    if let Some(user_id) = user_id {
+        if state.db.user_has_project_access(user_id, project_id).await.unwrap_or(false) {
+            return Ok(ProjectReader::Member(user_id));
+        }
+    }
+
+    let visibility = match state.db.get_project_settings(project_id).await {
+        Ok(settings) => settings.visibility,
+        Err(e) => {
+            error!("Failed to load settings of project {}: {}", project_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+    match (visibility, user_id) {
+        (ProjectVisibility::Public, _) | (ProjectVisibility::Internal, Some(_)) => Ok(ProjectReader::Visitor(user_id)),
+        (_, None) => Err(StatusCode::UNAUTHORIZED),
+        (_, Some(_)) => Err(StatusCode::FORBIDDEN),
+    }
+}
+
+/// Whether a reader may see a rift: members by their role in it, visitors
+/// only rifts shared with the whole project
+pub async fn can_read_rift(db: &Database, rift: &Rift, reader: ProjectReader) -> Result<bool> {
+    match reader {
+        ProjectReader::Member(user_id) => Ok(crate::rift_access::resolve_role(db, rift, user_id).await?.is_some()),
+        ProjectReader::Visitor(_) => Ok(matches!(db.get_rift_access(rift.id).await?, Some((_, RiftVisibility::Project)))),
+    }
+}
+
+/// The rift to read when none is named: a member's own rift, or the
+/// project's main rift for visitors
+pub async fn default_rift(db: &Database, project_id: ProjectId, reader: ProjectReader) -> Result<Option<Rift>> {
+    let rift = match reader {
+        ProjectReader::Member(user_id) => return db.get_user_rift(project_id, user_id).await,
+        ProjectReader::Visitor(_) => db.get_rift_by_name(project_id, "main").await?,
+    };
+    match rift {
+        Some(rift) if can_read_rift(db, &rift, reader).await? => Ok(Some(rift)),
+        _ => Ok(None),
+    }
+}
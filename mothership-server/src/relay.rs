@@ -0,0 +1,136 @@
+//! Redis pub/sub fan-out of rift sync messages between server instances.
+//!
+//! Every message broadcast to a rift channel on one instance is published to
+//! a shared Redis channel; the other instances deliver it to their own
+//! WebSocket clients and apply it to their working state. Instances must share
+//! the database and STORAGE_ROOT.
+
+use anyhow::Result;
+use futures_util::StreamExt;
+use mothership_common::protocol::SyncMessage;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::config::RelaySettings;
+use crate::sync::SyncState;
+
+/// How long to wait before reconnecting to Redis
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// A sync message as it travels between instances
+#[derive(Debug, Serialize, Deserialize)]
+struct RelayEnvelope {
+    /// Instance that published it, so it isn't delivered twice there
+    origin: Uuid,
+    channel: String,
+    message: SyncMessage,
+}
+
+/// Publishes this instance's rift broadcasts to the other instances
+#[derive(Clone)]
+pub struct SyncRelay {
+    instance_id: Uuid,
+    outgoing: mpsc::UnboundedSender<RelayEnvelope>,
+}
+
+impl SyncRelay {
+    /// Connect to Redis and start relaying in both directions. Messages from
+    /// other instances are delivered through `sync`.
+    pub fn start(settings: &RelaySettings, sync: SyncState) -> Result<Self> {
+        let redis_url = std::env::var("REDIS_URL").unwrap_or_else(|_| settings.redis_url.clone());
+        let client = redis::Client::open(redis_url.as_str())?;
+        let instance_id = Uuid::new_v4();
+        let (outgoing, receiver) = mpsc::unbounded_channel();
+
+        tokio::spawn(publish_loop(client.clone(), settings.channel.clone(), receiver));
+        tokio::spawn(subscribe_loop(client, settings.channel.clone(), instance_id, sync));
+
+        info!("📡 Relaying sync messages through Redis channel {} as instance {}", settings.channel, instance_id);
+        Ok(Self { instance_id, outgoing })
+    }
+
+    /// Queue a broadcast for the other instances
+    pub fn publish(&self, channel: &str, message: &SyncMessage) {
+        let _ = self.outgoing.send(RelayEnvelope {
+            origin: self.instance_id,
+            channel: channel.to_string(),
+            message: message.clone(),
+        });
+    }
+}
+
+/// Publish queued broadcasts, reconnecting when Redis goes away
+async fn publish_loop(client: redis::Client, channel: String, mut receiver: mpsc::UnboundedReceiver<RelayEnvelope>) {
+    'connect: loop {
+        let mut connection = match client.get_multiplexed_tokio_connection().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                error!("📡 Relay could not connect to Redis for publishing: {}", e);
+                tokio::time::sleep(RECONNECT_DELAY).await;
+                continue;
+            }
+        };
+
+        while let Some(envelope) = receiver.recv().await {
+            let payload = match serde_json::to_vec(&envelope) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    error!("📡 Failed to encode relayed message: {}", e);
+                    continue;
+                }
+            };
+
+            let published: redis::RedisResult<()> = redis::cmd("PUBLISH")
+                .arg(&channel)
+                .arg(payload)
+                .query_async(&mut connection)
+                .await;
+            if let Err(e) = published {
+                // Dropped rather than retried: a late sync message is worse than a missing one
+                warn!("📡 Lost relayed message for {}: {}", envelope.channel, e);
+                tokio::time::sleep(RECONNECT_DELAY).await;
+                continue 'connect;
+            }
+        }
+
+        // Every sender is gone; the server is shutting down
+        return;
+    }
+}
+
+/// Deliver messages published by other instances, resubscribing when Redis goes away
+async fn subscribe_loop(client: redis::Client, channel: String, instance_id: Uuid, sync: SyncState) {
+    loop {
+        if let Err(e) = subscribe(&client, &channel, instance_id, &sync).await {
+            error!("📡 Relay subscription to {} failed: {}", channel, e);
+        }
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+async fn subscribe(client: &redis::Client, channel: &str, instance_id: Uuid, sync: &SyncState) -> Result<()> {
+    let mut pubsub = client.get_async_connection().await?.into_pubsub();
+    pubsub.subscribe(channel).await?;
+    info!("📡 Subscribed to relay channel {}", channel);
+
+    let mut messages = pubsub.on_message();
+    while let Some(message) = messages.next().await {
+        let payload: Vec<u8> = message.get_payload()?;
+        let envelope: RelayEnvelope = match serde_json::from_slice(&payload) {
+            Ok(envelope) => envelope,
+            Err(e) => {
+                warn!("📡 Ignoring malformed relayed message: {}", e);
+                continue;
+            }
+        };
+
+        if envelope.origin != instance_id {
+            sync.deliver_relayed(envelope.channel, envelope.message).await;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,448 @@
+//! Asynchronous primary/secondary replication.
+//!
+//! A primary appends every checkpoint it writes to a log in storage. A
+//! secondary polls that log, mirrors the rifts, projects and users the
+//! checkpoints belong to, copies the content they reference and then serves
+//! beams and history locally without accepting writes. Secondaries must share
+//! the primary's JWT_SECRET so sessions issued by the primary work everywhere.
+
+use anyhow::{anyhow, Result};
+use axum::{
+    extract::{Path, Query, Request, State},
+    http::{HeaderMap, Method, StatusCode},
+    middleware::Next,
+    response::Response,
+    routing::get,
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use mothership_common::{
+    protocol::{ApiResponse, ReplicatedRift, ReplicationLogPage, ReplicationStatus},
+    upload::content_hash,
+    RiftId,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::config::ReplicationRole;
+use crate::handlers::authenticate_request;
+use crate::AppState;
+
+/// Header a secondary sends its shared secret in
+const SECRET_HEADER: &str = "x-replication-secret";
+
+/// Most checkpoints returned by one log request
+const MAX_LOG_PAGE: usize = 1000;
+
+/// Replication endpoints. Everything but the status is for secondaries only.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/replication/status", get(replication_status))
+        .route("/replication/log", get(read_log))
+        .route("/replication/rifts/:rift_id", get(rift_snapshot))
+        .route("/replication/blobs/:hash", get(get_blob))
+}
+
+/// Returned when a write reaches a read-only secondary
+#[derive(Debug)]
+pub struct ReplicaReadOnly;
+
+impl std::fmt::Display for ReplicaReadOnly {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "This server is a read-only replica; make changes on the primary")
+    }
+}
+
+impl std::error::Error for ReplicaReadOnly {}
+
+/// How far a secondary has caught up with its primary
+#[derive(Debug, Default)]
+pub struct FollowerProgress {
+    pub position: u64,
+    pub last_synced_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+}
+
+/// Follower progress shared between the follower task and the status endpoint
+pub type FollowerHandle = Arc<std::sync::RwLock<FollowerProgress>>;
+
+/// Whether this server is a read-only secondary
+pub fn is_replica(state: &AppState) -> bool {
+    state.config().replication.role == ReplicationRole::Secondary
+}
+
+/// Middleware: secondaries refuse anything that would change data. Logging in,
+/// listing the gateway and beaming into replicated rifts still work.
+pub async fn reject_replica_writes(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    if is_replica(&state) && !allowed_on_replica(request.method(), request.uri().path()) {
+        warn!("🛰️ Rejected {} {} on read-only replica", request.method(), request.uri().path());
+        return Err(StatusCode::LOCKED);
+    }
+
+    Ok(next.run(request).await)
+}
+
+fn allowed_on_replica(method: &Method, path: &str) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+        || path.starts_with("/auth/")
+        || path == "/gateway"
+        || path.ends_with("/beam")
+}
+
+/// SECURITY CHECK: Only servers holding the shared secret may read the
+/// replication feed, and only from a primary
+fn authorize_secondary(state: &AppState, headers: &HeaderMap) -> Result<(), StatusCode> {
+    if state.config().replication.role != ReplicationRole::Primary {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    let Some(secret) = state.config().replication.secret() else {
+        warn!("🛰️ Replication request refused: no shared secret is configured");
+        return Err(StatusCode::NOT_FOUND);
+    };
+
+    let provided = headers.get(SECRET_HEADER).and_then(|v| v.to_str().ok());
+    if provided != Some(secret.as_str()) {
+        warn!("🔒 Replication request with a missing or wrong secret");
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    Ok(())
+}
+
+/// Where this server stands in replication
+async fn replication_status(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<ApiResponse<ReplicationStatus>>, StatusCode> {
+    authenticate_request(&state, &headers)?;
+    let settings = &state.config().replication;
+
+    let status = match settings.role {
+        ReplicationRole::Secondary => {
+            let progress = state.replication.read().unwrap();
+            ReplicationStatus {
+                role: "secondary".to_string(),
+                region: settings.region.clone(),
+                primary_url: Some(settings.primary_url.clone()),
+                position: progress.position,
+                last_synced_at: progress.last_synced_at,
+                last_error: progress.last_error.clone(),
+            }
+        }
+        role => ReplicationStatus {
+            role: if role == ReplicationRole::Primary { "primary" } else { "standalone" }.to_string(),
+            region: settings.region.clone(),
+            primary_url: None,
+            position: state.sync.storage.replication_log_position().await
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+            last_synced_at: None,
+            last_error: None,
+        },
+    };
+
+    Ok(Json(ApiResponse::success(status)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LogQuery {
+    /// Log position to read from
+    #[serde(default)]
+    pub after: u64,
+    pub limit: Option<usize>,
+}
+
+/// A page of the checkpoint log
+async fn read_log(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<LogQuery>,
+) -> Result<Json<ApiResponse<ReplicationLogPage>>, StatusCode> {
+    authorize_secondary(&state, &headers)?;
+
+    let limit = query.limit.unwrap_or(state.config().replication.batch_size).clamp(1, MAX_LOG_PAGE);
+    let (checkpoints, next_position) = state.sync.storage.read_replication_log(query.after, limit).await
+        .map_err(|e| {
+            error!("Failed to read replication log at {}: {}", query.after, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ApiResponse::success(ReplicationLogPage { checkpoints, next_position })))
+}
+
+/// Snapshot of a rift and everything needed to serve it
+async fn rift_snapshot(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(rift_id): Path<RiftId>,
+) -> Result<Json<ApiResponse<ReplicatedRift>>, StatusCode> {
+    authorize_secondary(&state, &headers)?;
+
+    build_snapshot(&state, rift_id).await
+        .map_err(|e| {
+            error!("Failed to snapshot rift {} for replication: {}", rift_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .map(|snapshot| Json(ApiResponse::success(snapshot)))
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+async fn build_snapshot(state: &AppState, rift_id: RiftId) -> Result<Option<ReplicatedRift>> {
+    let Some(rift) = state.db.get_rift(rift_id).await? else {
+        return Ok(None);
+    };
+    let Some(project) = state.db.get_project(rift.project_id).await? else {
+        return Ok(None);
+    };
+    let Some((owner, visibility)) = state.db.get_rift_access(rift_id).await? else {
+        return Ok(None);
+    };
+    let permissions = state.db.list_rift_permissions(rift_id).await?;
+
+    let user_ids: HashSet<_> = project.members.iter()
+        .chain(rift.collaborators.iter())
+        .chain(permissions.iter().map(|p| &p.user_id))
+        .chain(owner.iter())
+        .copied()
+        .collect();
+    let mut users = Vec::new();
+    for user_id in user_ids {
+        if let Some(user) = state.db.get_user(user_id).await? {
+            users.push(user);
+        }
+    }
+
+    // Live files aren't necessarily in content storage yet
+    let mut live_files = HashMap::new();
+    for (path, content) in state.sync.storage.get_live_state(rift_id).await? {
+        live_files.insert(path, state.sync.storage.store_content(&content).await?);
+    }
+
+    Ok(Some(ReplicatedRift {
+        project_archived_at: state.sync.archived_at(project.id),
+        project,
+        rift,
+        owner,
+        visibility,
+        permissions,
+        users,
+        live_files,
+    }))
+}
+
+/// Raw content by hash
+async fn get_blob(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(hash): Path<String>,
+) -> Result<String, StatusCode> {
+    authorize_secondary(&state, &headers)?;
+
+    // SECURITY CHECK: The hash becomes a file name
+    if hash.len() != 64 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    match state.sync.storage.get_content(&hash).await {
+        Ok(Some(content)) => Ok(content),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// Client for a primary's replication endpoints
+struct Primary {
+    client: reqwest::Client,
+    url: String,
+    secret: String,
+}
+
+impl Primary {
+    /// GET a replication endpoint; `None` if the primary no longer has it
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<Option<T>> {
+        let response = self.client
+            .get(format!("{}{}", self.url, path))
+            .header(SECRET_HEADER, &self.secret)
+            .send()
+            .await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(anyhow!("Primary answered {} for {}", response.status(), path));
+        }
+
+        let body: ApiResponse<T> = response.json().await?;
+        body.data.map(Some).ok_or_else(|| anyhow!(body.error.unwrap_or_else(|| "Empty response".to_string())))
+    }
+
+    async fn get_blob(&self, hash: &str) -> Result<String> {
+        let response = self.client
+            .get(format!("{}/replication/blobs/{}", self.url, hash))
+            .header(SECRET_HEADER, &self.secret)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(anyhow!("Primary answered {} for content {}", response.status(), hash));
+        }
+        Ok(response.text().await?)
+    }
+}
+
+/// Start following the primary in the background (secondaries only)
+pub fn spawn_follower(state: AppState) {
+    let settings = state.config().replication.clone();
+    let Some(secret) = settings.secret() else {
+        error!("🛰️ Replication secondary has no shared secret; not following {}", settings.primary_url);
+        return;
+    };
+    let primary = Primary {
+        client: reqwest::Client::new(),
+        url: settings.primary_url.trim_end_matches('/').to_string(),
+        secret,
+    };
+
+    tokio::spawn(async move {
+        let mut position = match state.sync.storage.load_replication_cursor().await {
+            Ok(position) => position,
+            Err(e) => {
+                error!("Failed to load replication cursor: {}", e);
+                return;
+            }
+        };
+        state.replication.write().unwrap().position = position;
+        info!("🛰️ Following primary {} from log position {} (region {})", primary.url, position, settings.region);
+
+        let mut interval = tokio::time::interval(Duration::from_secs(settings.poll_interval_seconds.max(1)));
+        loop {
+            interval.tick().await;
+
+            // Drain everything available before waiting again
+            loop {
+                match pull_batch(&state, &primary, position, settings.batch_size).await {
+                    Ok((applied, next_position)) => {
+                        position = next_position;
+                        let mut progress = state.replication.write().unwrap();
+                        progress.position = position;
+                        progress.last_synced_at = Some(Utc::now());
+                        progress.last_error = None;
+                        if applied < settings.batch_size {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        warn!("🛰️ Replication from {} failed at position {}: {}", primary.url, position, e);
+                        state.replication.write().unwrap().last_error = Some(e.to_string());
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Apply one page of the primary's log. Returns the number of checkpoints
+/// applied and the position to continue from.
+async fn pull_batch(state: &AppState, primary: &Primary, position: u64, batch_size: usize) -> Result<(usize, u64)> {
+    let Some(page) = primary
+        .get::<ReplicationLogPage>(&format!("/replication/log?after={}&limit={}", position, batch_size))
+        .await?
+    else {
+        return Err(anyhow!("Primary does not serve a replication log; is it configured as a primary?"));
+    };
+
+    let storage = &state.sync.storage;
+    let mut snapshots: HashMap<RiftId, ReplicatedRift> = HashMap::new();
+    let mut gone: HashSet<RiftId> = HashSet::new();
+
+    for checkpoint in &page.checkpoints {
+        if gone.contains(&checkpoint.rift_id) {
+            continue;
+        }
+        if !snapshots.contains_key(&checkpoint.rift_id) && !replicate_rift(state, primary, checkpoint.rift_id, &mut snapshots).await? {
+            // Deleted on the primary since; nothing to serve
+            gone.insert(checkpoint.rift_id);
+            continue;
+        }
+
+        for change in &checkpoint.changes {
+            ensure_content(state, primary, &change.content_hash).await?;
+        }
+        storage.import_checkpoint(checkpoint).await?;
+    }
+
+    // Beams are served from working state, so bring touched rifts up to date
+    for snapshot in snapshots.values() {
+        let mut files = HashMap::new();
+        for (path, hash) in &snapshot.live_files {
+            files.insert(path.clone(), ensure_content(state, primary, hash).await?);
+        }
+        storage.replace_live_state(snapshot.rift.id, files).await;
+        state.sync.set_archived(snapshot.project.id, snapshot.project_archived_at);
+    }
+
+    if page.next_position != position {
+        storage.save_replication_cursor(page.next_position).await?;
+    }
+    if !page.checkpoints.is_empty() {
+        info!("🛰️ Replicated {} checkpoints across {} rifts (log position {})",
+            page.checkpoints.len(), snapshots.len(), page.next_position);
+    }
+
+    Ok((page.checkpoints.len(), page.next_position))
+}
+
+/// Mirror a rift, and any parents this server doesn't have yet. Returns false
+/// if the primary no longer has the rift.
+async fn replicate_rift(
+    state: &AppState,
+    primary: &Primary,
+    rift_id: RiftId,
+    snapshots: &mut HashMap<RiftId, ReplicatedRift>,
+) -> Result<bool> {
+    let mut chain: Vec<ReplicatedRift> = Vec::new();
+    let mut next = Some(rift_id);
+    while let Some(id) = next {
+        let Some(snapshot) = primary.get::<ReplicatedRift>(&format!("/replication/rifts/{}", id)).await? else {
+            // A missing parent can't be linked to
+            if let Some(child) = chain.last_mut() {
+                child.rift.parent_rift = None;
+            }
+            break;
+        };
+        next = match snapshot.rift.parent_rift {
+            Some(parent) if !snapshots.contains_key(&parent) && state.db.get_rift(parent).await?.is_none() => Some(parent),
+            _ => None,
+        };
+        chain.push(snapshot);
+    }
+
+    // Parents first, so every parent link resolves
+    for snapshot in chain.into_iter().rev() {
+        state.db.apply_replicated_rift(&snapshot).await?;
+        snapshots.insert(snapshot.rift.id, snapshot);
+    }
+
+    Ok(snapshots.contains_key(&rift_id))
+}
+
+/// Make sure content is stored locally, fetching it from the primary if not
+async fn ensure_content(state: &AppState, primary: &Primary, hash: &str) -> Result<String> {
+    if let Some(content) = state.sync.storage.get_content(hash).await? {
+        return Ok(content);
+    }
+
+    let content = primary.get_blob(hash).await?;
+    if content_hash(content.as_bytes()) != hash {
+        return Err(anyhow!("Content {} from primary does not match its hash", hash));
+    }
+    state.sync.storage.store_content(&content).await?;
+
+    Ok(content)
+}
@@ -0,0 +1,145 @@
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::Response,
+    routing::post,
+    Json, Router,
+};
+use futures_util::{stream, Stream, StreamExt};
+use mothership_common::{
+    protocol::{RestorePathsRequest, RestoreStreamLine, RESTORE_STREAM_CONTENT_TYPE},
+    ChangeType, CheckpointId, FileChange, ProjectId,
+};
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+use crate::api_error::ApiError;
+use crate::audit::{self, AuditAction, AuditEvent};
+use crate::handlers::authenticate_request;
+use crate::storage::StorageEngine;
+use crate::{archive, AppState, RestoreQuery};
+
+/// Streamed checkpoint restore endpoint
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/projects/:id/checkpoints/:checkpoint_id/restore/stream", post(restore_stream))
+}
+
+/// Restore a checkpoint, or only the requested paths of it, as newline
+/// delimited JSON: files are read from storage one at a time as the client
+/// takes them, so big projects never have to fit in memory
+async fn restore_stream(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((project_id, checkpoint_id)): Path<(ProjectId, CheckpointId)>,
+    Query(query): Query<RestoreQuery>,
+    request: Option<Json<RestorePathsRequest>>,
+) -> Result<Response, ApiError> {
+    let user_id = authenticate_request(&state, &headers)?;
+    let paths = request.map(|Json(request)| request.paths).unwrap_or_default();
+
+    info!("Streamed restore request for project: {} checkpoint: {} by user: {}", project_id, checkpoint_id, user_id);
+
+    if !state.db.user_has_project_access(user_id, project_id).await.unwrap_or(false) {
+        return Err(StatusCode::FORBIDDEN.into());
+    }
+    if !query.dry_run {
+        archive::ensure_writable(&state, project_id)?;
+    }
+
+    // Load the checkpoint and make sure it belongs to this project
+    let checkpoint = match state.sync.storage.load_checkpoint(checkpoint_id).await {
+        Ok(Some(checkpoint)) => checkpoint,
+        Ok(None) => return Err(StatusCode::NOT_FOUND.into()),
+        Err(e) => {
+            error!("Failed to load checkpoint: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into());
+        }
+    };
+    let rift = match state.db.get_rift(checkpoint.rift_id).await {
+        Ok(Some(rift)) if rift.project_id == project_id => rift,
+        Ok(_) => return Err(StatusCode::NOT_FOUND.into()),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR.into()),
+    };
+    // Previewing needs to see the rift, restoring to edit it
+    crate::rift_access::require_access(&state.db, &rift, user_id, !query.dry_run).await?;
+
+    // Checkpoints are full snapshots; a requested path matches the file
+    // itself or any file beneath it
+    let files: Vec<FileChange> = checkpoint.changes
+        .iter()
+        .filter(|change| !matches!(change.change_type, ChangeType::Deleted))
+        .filter(|change| paths.is_empty() || paths.iter().any(|requested| change.path.starts_with(requested)))
+        .cloned()
+        .collect();
+
+    let missing: Vec<String> = paths.iter()
+        .filter(|requested| !files.iter().any(|change| change.path.starts_with(requested)))
+        .map(|requested| requested.display().to_string())
+        .collect();
+    if !missing.is_empty() {
+        return Err(ApiError::not_found(format!(
+            "Not found in checkpoint: {}",
+            missing.join(", ")
+        )));
+    }
+
+    let file_count = files.len();
+    let total_bytes = files.iter().map(|change| change.size).sum();
+    info!("Streaming {} files ({} bytes) of checkpoint {}", file_count, total_bytes, checkpoint_id);
+
+    let safety_checkpoint = if query.dry_run {
+        None
+    } else {
+        let safety_checkpoint = crate::safety_checkpoint(&state, user_id, &checkpoint).await;
+        let mut details = serde_json::json!({ "file_count": file_count, "streamed": true });
+        if !paths.is_empty() {
+            details["paths"] = serde_json::json!(paths);
+        }
+        audit::record(&state.db, AuditEvent::new(AuditAction::CheckpointRestored, Some(user_id))
+            .project(project_id)
+            .target(checkpoint_id.to_string())
+            .details(details)).await;
+        crate::notify_checkpoint_restored(&state, user_id, &checkpoint, file_count).await;
+        safety_checkpoint
+    };
+
+    let first = RestoreStreamLine::Header { checkpoint, file_count, total_bytes, safety_checkpoint };
+    let lines = stream::once(async move { first })
+        .chain(file_lines(state.sync.storage.clone(), files))
+        .map(|line| serde_json::to_vec(&line).map(|mut bytes| {
+            bytes.push(b'\n');
+            bytes
+        }));
+
+    Ok(Response::builder()
+        .header(header::CONTENT_TYPE, RESTORE_STREAM_CONTENT_TYPE)
+        .body(Body::from_stream(lines))
+        .unwrap())
+}
+
+/// A line per file, each read from storage only when the previous one has
+/// been sent, then `Done`. Files whose content is gone are skipped, as the
+/// JSON restore does; failing to read one ends the stream with `Error`.
+fn file_lines(storage: Arc<StorageEngine>, files: Vec<FileChange>) -> impl Stream<Item = RestoreStreamLine> {
+    stream::unfold(Some((storage, files.into_iter())), |remaining| async move {
+        let (storage, mut files) = remaining?;
+        loop {
+            let Some(change) = files.next() else {
+                return Some((RestoreStreamLine::Done, None));
+            };
+            match storage.get_content(&change.content_hash).await {
+                Ok(Some(content)) => {
+                    return Some((RestoreStreamLine::File { path: change.path, content }, Some((storage, files))));
+                }
+                Ok(None) => warn!("Content of {} ({}) is missing from storage", change.path.display(), change.content_hash),
+                Err(e) => {
+                    error!("Failed to read {} for a streamed restore: {}", change.path.display(), e);
+                    let message = format!("Failed to read {}", change.path.display());
+                    return Some((RestoreStreamLine::Error { message }, None));
+                }
+            }
+        }
+    })
+}
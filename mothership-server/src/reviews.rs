@@ -0,0 +1,439 @@
+use anyhow::Result;
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use mothership_common::{
+    protocol::{
+        AddReviewersRequest, ApiResponse, ApproveReviewRequest, InboxKind, OpenReviewRequest, ReviewCommentRequest, ReviewRequest,
+        RiftMergeResult,
+    },
+    CheckpointId, ProjectId, ReviewState, Rift, UserId,
+};
+use serde::Deserialize;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::api_error::ApiError;
+use crate::audit::{self, AuditAction, AuditEvent};
+use crate::database::NewReviewRequest;
+use crate::handlers::{self, authenticate_request};
+use crate::{archive, inbox, AppState};
+
+/// Longest accepted review request title, in characters
+const MAX_TITLE_LENGTH: usize = 200;
+
+/// Longest accepted description or comment, in characters
+const MAX_TEXT_LENGTH: usize = 10_000;
+
+/// Review requests listed when no limit is given, and the most that can be asked for
+const DEFAULT_LIST_LIMIT: usize = 50;
+const MAX_LIST_LIMIT: usize = 200;
+
+/// Review request endpoints
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/projects/:id/reviews", get(list_reviews).post(open_review))
+        .route("/projects/:id/reviews/:review_id", get(get_review))
+        .route("/projects/:id/reviews/:review_id/reviewers", post(add_reviewers))
+        .route("/projects/:id/reviews/:review_id/approve", post(approve_review))
+        .route("/projects/:id/reviews/:review_id/comments", post(comment_on_review))
+        .route("/projects/:id/reviews/:review_id/merge", post(merge_review))
+        .route("/projects/:id/reviews/:review_id/close", post(close_review))
+}
+
+#[derive(Debug, Deserialize)]
+struct ListReviewsQuery {
+    /// "open" (the default), "merged", "closed" or "all"
+    state: Option<String>,
+    limit: Option<usize>,
+}
+
+/// Authenticate the request and check the user can access the project
+async fn authorize_project(state: &AppState, headers: &HeaderMap, project_id: ProjectId) -> Result<UserId, StatusCode> {
+    let user_id = authenticate_request(state, headers)?;
+
+    match state.db.user_has_project_access(user_id, project_id).await {
+        Ok(true) => Ok(user_id),
+        Ok(false) => Err(StatusCode::FORBIDDEN),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn load_review(state: &AppState, project_id: ProjectId, review_id: Uuid) -> Result<ReviewRequest, StatusCode> {
+    let reviews = state.db.list_review_requests(project_id, None, Some(review_id), 1).await
+        .map_err(|e| {
+            error!("Failed to load review request {}: {}", review_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    reviews.into_iter().next().ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Load a review request that is still open
+async fn load_open_review(state: &AppState, project_id: ProjectId, review_id: Uuid) -> Result<ReviewRequest, ApiError> {
+    let review = load_review(state, project_id, review_id).await?;
+    if review.state != ReviewState::Open {
+        return Err(ApiError::conflict(format!("Review request is already {}", review.state.as_str())));
+    }
+    Ok(review)
+}
+
+/// Look up project members by username to ask them for a review
+async fn resolve_reviewers(
+    state: &AppState,
+    project_id: ProjectId,
+    author: UserId,
+    usernames: &[String],
+) -> Result<Vec<UserId>, ApiError> {
+    let mut reviewers = Vec::new();
+    for username in usernames {
+        let user = match state.db.get_user_by_username(username.trim()).await {
+            Ok(Some(user)) => user,
+            Ok(None) => return Err(ApiError::not_found(format!("No user named '{}'", username))),
+            Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR.into()),
+        };
+        if user.id == author {
+            return Err(ApiError::bad_request("You can't review your own request"));
+        }
+        if !state.db.user_has_project_access(user.id, project_id).await.unwrap_or(false) {
+            return Err(ApiError::bad_request(format!("{} is not a member of this project", user.username)));
+        }
+        reviewers.push(user.id);
+    }
+    Ok(reviewers)
+}
+
+/// Let newly asked reviewers know about a request
+fn notify_reviewers(state: &AppState, review: &ReviewRequest, actor: UserId, reviewers: &[UserId]) {
+    inbox::deliver_all(
+        &state.sync,
+        reviewers.iter().copied(),
+        actor,
+        InboxKind::ReviewRequested,
+        Some(review.project_id),
+        &format!("{} asked you to review '{}'", review.author_name, review.title),
+        &format!("Merge '{}' into '{}' ({})", review.source_rift_name, review.target_rift_name, review.id),
+    );
+}
+
+/// Trim a comment or description and check it isn't too long
+fn text_field<'a>(text: &'a str, what: &str) -> Result<&'a str, ApiError> {
+    let text = text.trim();
+    if text.chars().count() > MAX_TEXT_LENGTH {
+        return Err(ApiError::bad_request(format!("{} must be at most {} characters", what, MAX_TEXT_LENGTH)));
+    }
+    Ok(text)
+}
+
+/// Open a request to merge one rift into another
+async fn open_review(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(project_id): Path<ProjectId>,
+    Json(req): Json<OpenReviewRequest>,
+) -> Result<Json<ApiResponse<ReviewRequest>>, ApiError> {
+    let user_id = authorize_project(&state, &headers, project_id).await?;
+    archive::ensure_writable(&state, project_id)?;
+
+    let title = req.title.trim();
+    if title.is_empty() || title.chars().count() > MAX_TITLE_LENGTH {
+        return Err(ApiError::bad_request(format!("Title must be between 1 and {} characters", MAX_TITLE_LENGTH)));
+    }
+    let description = match req.description.as_deref().map(|text| text_field(text, "Description")).transpose()? {
+        Some("") | None => None,
+        Some(description) => Some(description),
+    };
+
+    let source = match handlers::visible_rift(&state, project_id, user_id, &req.source_rift).await {
+        Ok(Some(rift)) => rift,
+        Ok(None) => return Err(ApiError::not_found(format!("No rift named '{}'", req.source_rift))),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR.into()),
+    };
+    let target = match &req.target_rift {
+        Some(name) => handlers::visible_rift(&state, project_id, user_id, name).await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .ok_or_else(|| ApiError::not_found(format!("No rift named '{}'", name)))?,
+        None => handlers::resolve_merge_target(&state, &source).await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .ok_or_else(|| ApiError::not_found(format!("Rift '{}' has no parent or main rift to merge into", source.name)))?,
+    };
+    if source.id == target.id {
+        return Err(ApiError::bad_request("A rift can't be merged into itself"));
+    }
+
+    match state.db.get_open_review_id(source.id, target.id).await {
+        Ok(None) => {}
+        Ok(Some(existing)) => {
+            return Err(ApiError::conflict(format!(
+                "Review request {} is already open for merging '{}' into '{}'",
+                existing, source.name, target.name
+            )));
+        }
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR.into()),
+    }
+
+    let reviewers = resolve_reviewers(&state, project_id, user_id, &req.reviewers).await?;
+    let required_approvals = req.required_approvals.unwrap_or(0).max(state.config().reviews.required_approvals);
+
+    let review_id = Uuid::new_v4();
+    state.db
+        .create_review_request(NewReviewRequest {
+            id: review_id,
+            project_id,
+            source_rift_id: source.id,
+            target_rift_id: target.id,
+            title,
+            description,
+            author_id: user_id,
+            required_approvals,
+            reviewers: &reviewers,
+        })
+        .await
+        .map_err(|e| {
+            error!("Failed to open review request for rift {}: {}", source.id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    info!("👀 Review request {} opened to merge '{}' into '{}'", review_id, source.name, target.name);
+    audit::record(&state.db, AuditEvent::new(AuditAction::ReviewOpened, Some(user_id))
+        .project(project_id)
+        .target(review_id.to_string())
+        .details(serde_json::json!({
+            "source_rift": source.name,
+            "target_rift": target.name,
+            "reviewers": reviewers.len(),
+            "required_approvals": required_approvals,
+        }))).await;
+
+    let review = load_review(&state, project_id, review_id).await?;
+    notify_reviewers(&state, &review, user_id, &reviewers);
+    Ok(Json(ApiResponse::success(review)))
+}
+
+/// List a project's review requests, newest first
+async fn list_reviews(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(project_id): Path<ProjectId>,
+    Query(query): Query<ListReviewsQuery>,
+) -> Result<Json<ApiResponse<Vec<ReviewRequest>>>, ApiError> {
+    authorize_project(&state, &headers, project_id).await?;
+
+    let review_state = match query.state.as_deref().unwrap_or("open") {
+        "all" => None,
+        value => Some(ReviewState::parse(value).ok_or_else(|| {
+            ApiError::bad_request("State must be one of open, merged, closed or all")
+        })?),
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_LIST_LIMIT).clamp(1, MAX_LIST_LIMIT);
+
+    let reviews = state.db.list_review_requests(project_id, review_state, None, limit).await
+        .map_err(|e| {
+            error!("Failed to list review requests for project {}: {}", project_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ApiResponse::success(reviews)))
+}
+
+async fn get_review(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((project_id, review_id)): Path<(ProjectId, Uuid)>,
+) -> Result<Json<ApiResponse<ReviewRequest>>, StatusCode> {
+    authorize_project(&state, &headers, project_id).await?;
+    Ok(Json(ApiResponse::success(load_review(&state, project_id, review_id).await?)))
+}
+
+/// Ask more project members to review
+async fn add_reviewers(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((project_id, review_id)): Path<(ProjectId, Uuid)>,
+    Json(req): Json<AddReviewersRequest>,
+) -> Result<Json<ApiResponse<ReviewRequest>>, ApiError> {
+    let user_id = authorize_project(&state, &headers, project_id).await?;
+    archive::ensure_writable(&state, project_id)?;
+    let review = load_open_review(&state, project_id, review_id).await?;
+
+    let reviewers = resolve_reviewers(&state, project_id, review.author, &req.reviewers).await?;
+    state.db.add_review_reviewers(review_id, &reviewers).await
+        .map_err(|e| {
+            error!("Failed to add reviewers to review request {}: {}", review_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    // Only the ones who weren't already asked hear about it
+    let added: Vec<UserId> = reviewers.into_iter()
+        .filter(|id| !review.reviewers.iter().any(|reviewer| reviewer.user_id == *id))
+        .collect();
+    let review = load_review(&state, project_id, review_id).await?;
+    notify_reviewers(&state, &review, user_id, &added);
+    Ok(Json(ApiResponse::success(review)))
+}
+
+/// Approve merging the rift, optionally leaving a comment with the approval
+async fn approve_review(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((project_id, review_id)): Path<(ProjectId, Uuid)>,
+    request: Option<Json<ApproveReviewRequest>>,
+) -> Result<Json<ApiResponse<ReviewRequest>>, ApiError> {
+    let user_id = authorize_project(&state, &headers, project_id).await?;
+    archive::ensure_writable(&state, project_id)?;
+    let Json(request) = request.unwrap_or_default();
+    let review = load_open_review(&state, project_id, review_id).await?;
+
+    if review.author == user_id {
+        return Err(ApiError::forbidden("You can't approve your own review request"));
+    }
+    let comment = request.comment.as_deref().map(|text| text_field(text, "Comment")).transpose()?;
+
+    state.db.approve_review_request(review_id, user_id).await
+        .map_err(|e| {
+            error!("Failed to approve review request {}: {}", review_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if let Some(comment) = comment.filter(|comment| !comment.is_empty()) {
+        state.db.add_review_comment(review_id, user_id, comment).await
+            .map_err(|e| {
+                error!("Failed to comment on review request {}: {}", review_id, e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+    }
+
+    let review = load_review(&state, project_id, review_id).await?;
+    info!("👍 Review request {} approved by {} ({} of {})", review_id, user_id, review.approvals(), review.required_approvals);
+    audit::record(&state.db, AuditEvent::new(AuditAction::ReviewApproved, Some(user_id))
+        .project(project_id)
+        .target(review_id.to_string())).await;
+
+    Ok(Json(ApiResponse::success(review)))
+}
+
+async fn comment_on_review(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((project_id, review_id)): Path<(ProjectId, Uuid)>,
+    Json(req): Json<ReviewCommentRequest>,
+) -> Result<Json<ApiResponse<ReviewRequest>>, ApiError> {
+    let user_id = authorize_project(&state, &headers, project_id).await?;
+    archive::ensure_writable(&state, project_id)?;
+    load_review(&state, project_id, review_id).await?;
+
+    let body = text_field(&req.body, "Comment")?;
+    if body.is_empty() {
+        return Err(ApiError::bad_request("Comment can't be empty"));
+    }
+    state.db.add_review_comment(review_id, user_id, body).await
+        .map_err(|e| {
+            error!("Failed to comment on review request {}: {}", review_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let review = load_review(&state, project_id, review_id).await?;
+    inbox::deliver_mentions(&state.sync, project_id, user_id, body, &format!("You were mentioned on review '{}'", review.title)).await;
+    Ok(Json(ApiResponse::success(review)))
+}
+
+/// Merge the request's rift into its target, once it has enough approvals
+async fn merge_review(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((project_id, review_id)): Path<(ProjectId, Uuid)>,
+) -> Result<Json<ApiResponse<RiftMergeResult>>, ApiError> {
+    let user_id = authorize_project(&state, &headers, project_id).await?;
+    archive::ensure_writable(&state, project_id)?;
+    let review = load_open_review(&state, project_id, review_id).await?;
+
+    let (source, target) = match (
+        state.db.get_rift(review.source_rift_id).await,
+        state.db.get_rift(review.target_rift_id).await,
+    ) {
+        (Ok(Some(source)), Ok(Some(target))) => (source, target),
+        (Ok(_), Ok(_)) => return Err(StatusCode::NOT_FOUND.into()),
+        _ => return Err(StatusCode::INTERNAL_SERVER_ERROR.into()),
+    };
+
+    // Approvals are checked, and the request marked merged, by the merge itself
+    handlers::merge_rifts(&state, user_id, &source, &target).await
+}
+
+/// Close a request without merging; only its author or an admin may
+async fn close_review(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((project_id, review_id)): Path<(ProjectId, Uuid)>,
+) -> Result<Json<ApiResponse<ReviewRequest>>, ApiError> {
+    let user_id = authorize_project(&state, &headers, project_id).await?;
+    archive::ensure_writable(&state, project_id)?;
+    let review = load_open_review(&state, project_id, review_id).await?;
+
+    if review.author != user_id && !state.db.user_is_admin(user_id).await.unwrap_or(false) {
+        return Err(ApiError::forbidden("Only the author can close a review request"));
+    }
+
+    if !state.db.finish_review_request(review_id, ReviewState::Closed, None).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    {
+        return Err(ApiError::conflict("Review request is no longer open"));
+    }
+
+    info!("👀 Review request {} closed", review_id);
+    audit::record(&state.db, AuditEvent::new(AuditAction::ReviewClosed, Some(user_id))
+        .project(project_id)
+        .target(review_id.to_string())).await;
+
+    Ok(Json(ApiResponse::success(load_review(&state, project_id, review_id).await?)))
+}
+
+/// Check whether merging `source` into `target` is held back by review.
+/// Returns the reason the merge is blocked, or None if it may proceed.
+pub async fn merge_blocked_reason(state: &AppState, source: &Rift, target: &Rift) -> Result<Option<String>> {
+    let Some(review_id) = state.db.get_open_review_id(source.id, target.id).await? else {
+        if state.config().reviews.require_review_for_merge {
+            return Ok(Some(format!(
+                "Rift '{}' needs an approved review request to merge into '{}'",
+                source.name, target.name
+            )));
+        }
+        return Ok(None);
+    };
+
+    let reviews = state.db.list_review_requests(source.project_id, None, Some(review_id), 1).await?;
+    match reviews.first() {
+        Some(review) if !review.is_approved() => Ok(Some(format!(
+            "Review request '{}' has {} of {} required approvals",
+            review.title,
+            review.approvals(),
+            review.required_approvals
+        ))),
+        _ => Ok(None),
+    }
+}
+
+/// Mark the open review request for a merge that just happened as merged
+pub async fn mark_merged(state: &AppState, user_id: UserId, source: &Rift, target: &Rift, checkpoint_id: CheckpointId) {
+    let review_id = match state.db.get_open_review_id(source.id, target.id).await {
+        Ok(Some(review_id)) => review_id,
+        Ok(None) => return,
+        Err(e) => {
+            warn!("Failed to look up the review request for merging rift {}: {}", source.id, e);
+            return;
+        }
+    };
+
+    match state.db.finish_review_request(review_id, ReviewState::Merged, Some(checkpoint_id)).await {
+        Ok(true) => {
+            info!("👀 Review request {} merged (checkpoint {})", review_id, checkpoint_id);
+            audit::record(&state.db, AuditEvent::new(AuditAction::ReviewMerged, Some(user_id))
+                .project(source.project_id)
+                .target(review_id.to_string())
+                .details(serde_json::json!({ "checkpoint_id": checkpoint_id }))).await;
+        }
+        Ok(false) => {}
+        Err(e) => warn!("Failed to mark review request {} merged: {}", review_id, e),
+    }
+}
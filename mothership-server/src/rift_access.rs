@@ -0,0 +1,276 @@
+use anyhow::Result;
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{delete, get, post},
+    Json, Router,
+};
+use mothership_common::{
+    protocol::{ApiResponse, InboxKind, RiftAccessInfo, ShareRiftRequest, UpdateRiftVisibilityRequest},
+    ProjectId, Rift, RiftId, RiftRole, RiftVisibility, UserId,
+};
+use tracing::{error, info};
+
+use crate::api_error::ApiError;
+use crate::audit::{self, AuditAction, AuditEvent};
+use crate::database::Database;
+use crate::handlers::authenticate_request;
+use crate::inbox;
+use crate::notifications::NotificationKind;
+use crate::AppState;
+
+/// Rift access management endpoints
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/projects/:id/rifts/:rift_id/access",
+            get(get_access).put(update_visibility),
+        )
+        .route("/projects/:id/rifts/:rift_id/access/shares", post(share_rift))
+        .route("/projects/:id/rifts/:rift_id/access/shares/:user_id", delete(unshare_rift))
+}
+
+/// Returned when a user tries to enter a rift they may not join
+#[derive(Debug)]
+pub struct RiftAccessDenied;
+
+impl std::fmt::Display for RiftAccessDenied {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "You do not have access to this rift")
+    }
+}
+
+impl std::error::Error for RiftAccessDenied {}
+
+/// Work out what a user may do in a rift, or None if they may not join it.
+///
+/// The owner always has full access. Otherwise an explicit share decides the
+/// role, and project-visible rifts let any other project member edit.
+pub async fn resolve_role(db: &Database, rift: &Rift, user_id: UserId) -> Result<Option<RiftRole>> {
+    let Some((owner, visibility)) = db.get_rift_access(rift.id).await? else {
+        return Ok(None);
+    };
+
+    if owner == Some(user_id) {
+        return Ok(Some(RiftRole::Owner));
+    }
+    if !db.user_has_project_access(user_id, rift.project_id).await? {
+        return Ok(None);
+    }
+
+    match visibility {
+        RiftVisibility::Private => Ok(None),
+        RiftVisibility::Invite => db.get_rift_permission(rift.id, user_id).await,
+        RiftVisibility::Project => Ok(Some(
+            db.get_rift_permission(rift.id, user_id).await?.unwrap_or(RiftRole::Editor),
+        )),
+    }
+}
+
+/// Check a user may read a rift (any role) or, with `writes`, change it (owner
+/// or editor). Forbidden for observers changing it and for users without a role.
+pub async fn require_access(db: &Database, rift: &Rift, user_id: UserId, writes: bool) -> Result<(), StatusCode> {
+    match resolve_role(db, rift, user_id).await {
+        Ok(Some(role)) if role.can_write() || !writes => Ok(()),
+        Ok(_) => Err(StatusCode::FORBIDDEN),
+        Err(e) => {
+            error!("Failed to resolve access to rift {}: {}", rift.id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Authenticate the request and resolve the caller's role in a rift of the project
+async fn authorize_rift(
+    state: &AppState,
+    headers: &HeaderMap,
+    project_id: ProjectId,
+    rift_id: RiftId,
+) -> Result<(UserId, RiftRole), StatusCode> {
+    let user_id = authenticate_request(state, headers)?;
+
+    let rift = match state.db.get_rift(rift_id).await {
+        Ok(Some(rift)) if rift.project_id == project_id => rift,
+        Ok(_) => return Err(StatusCode::NOT_FOUND),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    match resolve_role(&state.db, &rift, user_id).await {
+        Ok(Some(role)) => Ok((user_id, role)),
+        Ok(None) => Err(StatusCode::FORBIDDEN),
+        Err(e) => {
+            error!("Failed to resolve access to rift {}: {}", rift_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Load the current access settings of a rift
+async fn load_access_info(state: &AppState, rift_id: RiftId) -> Result<RiftAccessInfo, StatusCode> {
+    let (owner, visibility) = match state.db.get_rift_access(rift_id).await {
+        Ok(Some(access)) => access,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    let shared_with = state.db.list_rift_permissions(rift_id).await
+        .map_err(|e| {
+            error!("Failed to list permissions for rift {}: {}", rift_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(RiftAccessInfo {
+        rift_id,
+        visibility,
+        owner,
+        shared_with,
+    })
+}
+
+/// Show who may join a rift
+async fn get_access(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((project_id, rift_id)): Path<(ProjectId, RiftId)>,
+) -> Result<Json<ApiResponse<RiftAccessInfo>>, StatusCode> {
+    authorize_rift(&state, &headers, project_id, rift_id).await?;
+
+    Ok(Json(ApiResponse::success(load_access_info(&state, rift_id).await?)))
+}
+
+/// Change a rift's visibility (owner only)
+async fn update_visibility(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((project_id, rift_id)): Path<(ProjectId, RiftId)>,
+    Json(req): Json<UpdateRiftVisibilityRequest>,
+) -> Result<Json<ApiResponse<RiftAccessInfo>>, ApiError> {
+    let (user_id, role) = authorize_rift(&state, &headers, project_id, rift_id).await?;
+    if role != RiftRole::Owner {
+        return Err(ApiError::forbidden("Only the rift owner can change its visibility"));
+    }
+
+    state.db.set_rift_visibility(rift_id, req.visibility).await
+        .map_err(|e| {
+            error!("Failed to update visibility of rift {}: {}", rift_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    info!("🔒 Rift {} is now {}", rift_id, req.visibility.as_str());
+    audit::record(&state.db, AuditEvent::new(AuditAction::RiftVisibilityChanged, Some(user_id))
+        .project(project_id)
+        .target(rift_id.to_string())
+        .details(serde_json::json!({ "visibility": req.visibility.as_str() }))).await;
+
+    Ok(Json(ApiResponse::success(load_access_info(&state, rift_id).await?)))
+}
+
+/// Share a rift with another project member (owner only)
+async fn share_rift(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((project_id, rift_id)): Path<(ProjectId, RiftId)>,
+    Json(req): Json<ShareRiftRequest>,
+) -> Result<Json<ApiResponse<RiftAccessInfo>>, ApiError> {
+    let (user_id, role) = authorize_rift(&state, &headers, project_id, rift_id).await?;
+    if role != RiftRole::Owner {
+        return Err(ApiError::forbidden("Only the rift owner can share it"));
+    }
+    if req.role == RiftRole::Owner {
+        return Err(ApiError::bad_request("A rift can only be shared with editors or observers"));
+    }
+
+    let target = match state.db.get_user_by_username(&req.username).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return Err(ApiError::not_found(format!("User '{}' not found", req.username))),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR.into()),
+    };
+    if target.id == user_id {
+        return Err(ApiError::bad_request("You already own this rift"));
+    }
+    if !state.db.user_has_project_access(target.id, project_id).await.unwrap_or(false) {
+        return Err(ApiError::bad_request(format!(
+            "User '{}' is not a member of this project",
+            target.username
+        )));
+    }
+
+    if let Ok(Some((_, RiftVisibility::Private))) = state.db.get_rift_access(rift_id).await {
+        return Err(ApiError::bad_request(
+            "Private rifts cannot be shared. Make the rift invite-only first.",
+        ));
+    }
+
+    state.db.grant_rift_permission(rift_id, target.id, req.role, user_id).await
+        .map_err(|e| {
+            error!("Failed to share rift {} with {}: {}", rift_id, target.username, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    info!("🤝 Rift {} shared with {} as {}", rift_id, target.username, req.role.as_str());
+    audit::record(&state.db, AuditEvent::new(AuditAction::RiftShared, Some(user_id))
+        .project(project_id)
+        .target(rift_id.to_string())
+        .details(serde_json::json!({ "user": target.username, "role": req.role.as_str() }))).await;
+
+    if let (Ok(Some(rift)), Ok(Some(project)), Ok(Some(sharer))) = (
+        state.db.get_rift(rift_id).await,
+        state.db.get_project(project_id).await,
+        state.db.get_user(user_id).await,
+    ) {
+        state.sync.notifier.notify(
+            target.id,
+            NotificationKind::Invitation,
+            format!("{} shared rift '{}' with you", sharer.username, rift.name),
+            format!(
+                "{} invited you to rift '{}' of project '{}' as {}.\n\nJoin it with: mothership beam {} --rift {}",
+                sharer.username,
+                rift.name,
+                project.name,
+                req.role.as_str(),
+                project.name,
+                rift.name
+            ),
+        );
+        inbox::deliver(
+            &state.sync,
+            target.id,
+            Some(user_id),
+            InboxKind::Invitation,
+            Some(project_id),
+            format!("{} shared rift '{}' with you", sharer.username, rift.name),
+            format!("You can work in rift '{}' of project '{}' as {}", rift.name, project.name, req.role.as_str()),
+        );
+    }
+
+    Ok(Json(ApiResponse::success(load_access_info(&state, rift_id).await?)))
+}
+
+/// Stop sharing a rift with a user (owner only)
+async fn unshare_rift(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((project_id, rift_id, target_id)): Path<(ProjectId, RiftId, UserId)>,
+) -> Result<Json<ApiResponse<RiftAccessInfo>>, ApiError> {
+    let (user_id, role) = authorize_rift(&state, &headers, project_id, rift_id).await?;
+    if role != RiftRole::Owner {
+        return Err(ApiError::forbidden("Only the rift owner can unshare it"));
+    }
+
+    let removed = state.db.revoke_rift_permission(rift_id, target_id).await
+        .map_err(|e| {
+            error!("Failed to unshare rift {} from {}: {}", rift_id, target_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if !removed {
+        return Err(ApiError::not_found("Rift is not shared with that user"));
+    }
+
+    info!("🚫 Rift {} no longer shared with {}", rift_id, target_id);
+    audit::record(&state.db, AuditEvent::new(AuditAction::RiftUnshared, Some(user_id))
+        .project(project_id)
+        .target(rift_id.to_string())
+        .details(serde_json::json!({ "user_id": target_id }))).await;
+
+    Ok(Json(ApiResponse::success(load_access_info(&state, rift_id).await?)))
+}
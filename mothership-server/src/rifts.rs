@@ -0,0 +1,169 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, patch, post},
+    Json, Router,
+};
+use mothership_common::{
+    protocol::{ApiResponse, RenameRiftRequest, RiftDeletion, SyncMessage, RIFT_DELETED_ERROR},
+    Rift, RiftId, UserId,
+};
+use serde::Deserialize;
+use tracing::{error, info, warn};
+
+use crate::api_error::ApiError;
+use crate::audit::{self, AuditAction, AuditEvent};
+use crate::handlers::{self, authenticate_request, is_valid_rift_name, unmerged_paths};
+use crate::AppState;
+
+/// The rift every project starts with; it can't be renamed or deleted
+const MAIN_RIFT: &str = "main";
+
+/// Rift endpoints: list, create, switch, diff, rename and delete
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/rifts", get(handlers::list_rifts).post(handlers::create_rift))
+        .route("/api/rifts/current", get(handlers::get_current_rift))
+        .route("/api/rifts/switch", post(handlers::switch_rift))
+        .route("/api/rifts/diff", get(handlers::get_rift_diffs))
+        .route("/api/rifts/:id", patch(rename_rift).delete(delete_rift))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteRiftQuery {
+    /// Delete even if the rift has changes that were never merged
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// Authenticate the request and load a rift the caller owns. Rifts created
+/// before ownership was recorded may be managed by any project member.
+async fn owned_rift(state: &AppState, headers: &HeaderMap, rift_id: RiftId) -> Result<(UserId, Rift), StatusCode> {
+    let user_id = authenticate_request(state, headers)?;
+
+    let rift = match state.db.get_rift(rift_id).await {
+        Ok(Some(rift)) => rift,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    let owner = match state.db.get_rift_access(rift_id).await {
+        Ok(Some((owner, _))) => owner,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    };
+    let allowed = match owner {
+        Some(owner) => owner == user_id,
+        None => state.db.user_has_project_access(user_id, rift.project_id).await.unwrap_or(false),
+    };
+    if !allowed {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    crate::archive::ensure_writable(state, rift.project_id)?;
+    Ok((user_id, rift))
+}
+
+/// Give a rift a new name
+async fn rename_rift(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(rift_id): Path<RiftId>,
+    Json(request): Json<RenameRiftRequest>,
+) -> Result<Json<ApiResponse<Rift>>, ApiError> {
+    let (user_id, rift) = owned_rift(&state, &headers, rift_id).await?;
+
+    if rift.name == MAIN_RIFT {
+        return Err(ApiError::bad_request("The main rift can't be renamed"));
+    }
+    if !is_valid_rift_name(&request.name) {
+        return Err(ApiError::bad_request(
+            "Invalid rift name. Use only letters, numbers, dashes, and underscores.",
+        ));
+    }
+    match state.db.get_rift_by_name(rift.project_id, &request.name).await {
+        Ok(None) => {}
+        Ok(Some(_)) => {
+            return Err(ApiError::conflict(format!("A rift named '{}' already exists", request.name)));
+        }
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR.into()),
+    }
+
+    state.db.rename_rift(rift_id, &request.name).await
+        .map_err(|e| {
+            error!("Failed to rename rift {}: {}", rift_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    info!("✏️ Renamed rift '{}' to '{}' ({})", rift.name, request.name, rift_id);
+    audit::record(&state.db, AuditEvent::new(AuditAction::RiftRenamed, Some(user_id))
+        .project(rift.project_id)
+        .target(rift_id.to_string())
+        .details(serde_json::json!({ "from": rift.name, "to": request.name }))).await;
+
+    Ok(Json(ApiResponse::success(Rift { name: request.name, ..rift })))
+}
+
+/// Delete a rift and its history. Refused while the rift has changes its
+/// merge target doesn't have, unless forced.
+async fn delete_rift(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(rift_id): Path<RiftId>,
+    Query(query): Query<DeleteRiftQuery>,
+) -> Result<Json<ApiResponse<RiftDeletion>>, ApiError> {
+    let (user_id, rift) = owned_rift(&state, &headers, rift_id).await?;
+
+    if rift.name == MAIN_RIFT {
+        return Err(ApiError::bad_request("The main rift can't be deleted"));
+    }
+
+    if !query.force {
+        let unmerged = unmerged_paths(&state, &rift).await
+            .map_err(|e| {
+                error!("Failed to check rift {} for unmerged changes: {}", rift_id, e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+        if !unmerged.is_empty() {
+            warn!("🛑 Refused to delete rift {} with {} unmerged files", rift_id, unmerged.len());
+            let shown: Vec<String> = unmerged.iter().take(5).map(|p| p.display().to_string()).collect();
+            return Err(ApiError::conflict(format!(
+                "Rift '{}' has {} unmerged files ({}{}); merge it first or delete it with --force",
+                rift.name,
+                unmerged.len(),
+                shown.join(", "),
+                if unmerged.len() > shown.len() { ", ..." } else { "" }
+            )));
+        }
+    }
+
+    state.db.delete_rift(rift_id).await
+        .map_err(|e| {
+            error!("Failed to delete rift {}: {}", rift_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let checkpoints_removed = state.sync.storage.remove_rift(rift_id).await.unwrap_or_else(|e| {
+        error!("Failed to clean up storage of deleted rift {}: {}", rift_id, e);
+        0
+    });
+
+    // Anyone still connected is told before the rift's state is dropped
+    state.sync.broadcast(format!("rift_{}", rift_id), SyncMessage::Error {
+        message: format!("Rift '{}' was deleted", rift.name),
+        error_code: Some(RIFT_DELETED_ERROR.to_string()),
+    });
+    state.sync.forget_rift(rift_id).await;
+
+    info!("🗑️ Deleted rift '{}' ({}, {} checkpoints)", rift.name, rift_id, checkpoints_removed);
+    audit::record(&state.db, AuditEvent::new(AuditAction::RiftDeleted, Some(user_id))
+        .project(rift.project_id)
+        .target(rift_id.to_string())
+        .details(serde_json::json!({ "name": rift.name, "forced": query.force, "checkpoints_removed": checkpoints_removed }))).await;
+
+    Ok(Json(ApiResponse::success(RiftDeletion {
+        rift_id,
+        name: rift.name,
+        checkpoints_removed,
+    })))
+}
@@ -0,0 +1,80 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::get,
+    Json, Router,
+};
+use mothership_common::{
+    protocol::ApiResponse,
+    secrets, ProjectId, RiftId, SecretFinding, SecretScanMode, UserId,
+};
+use tracing::{error, warn};
+
+use crate::handlers::authenticate_request;
+use crate::storage::StorageEngine;
+use crate::AppState;
+
+/// Secret scanning endpoints
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/projects/:id/secrets", get(list_findings))
+}
+
+/// Scan files sent to a rift for secrets. Findings are tagged with the rift,
+/// the author, and whether the project's mode blocks them.
+pub fn scan_files<'a>(
+    files: impl IntoIterator<Item = (&'a std::path::Path, &'a str)>,
+    rift_id: RiftId,
+    author: UserId,
+    mode: SecretScanMode,
+) -> Vec<SecretFinding> {
+    if mode == SecretScanMode::Off {
+        return Vec::new();
+    }
+
+    files.into_iter()
+        .flat_map(|(path, content)| secrets::scan(path, content))
+        .map(|finding| SecretFinding {
+            rift_id: Some(rift_id),
+            author: Some(author),
+            blocked: mode == SecretScanMode::Reject,
+            ..finding
+        })
+        .collect()
+}
+
+/// Record findings so the project's members can review them. Failures are
+/// logged: losing a finding must not lose the change.
+pub async fn record(storage: &StorageEngine, project_id: ProjectId, findings: &[SecretFinding]) {
+    if findings.is_empty() {
+        return;
+    }
+
+    for finding in findings {
+        warn!("🔑 {} in project {}{}", finding.describe(), project_id, if finding.blocked { " (blocked)" } else { "" });
+    }
+    if let Err(e) = storage.record_secret_findings(project_id, findings).await {
+        error!("Failed to record secret findings of project {}: {}", project_id, e);
+    }
+}
+
+/// List a project's secret scanning findings, newest first
+async fn list_findings(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(project_id): Path<ProjectId>,
+) -> Result<Json<ApiResponse<Vec<SecretFinding>>>, StatusCode> {
+    let user_id = authenticate_request(&state, &headers)?;
+    if !state.db.user_has_project_access(user_id, project_id).await.unwrap_or(false) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let mut findings = state.sync.storage.list_secret_findings(project_id).await
+        .map_err(|e| {
+            error!("Failed to load secret findings of project {}: {}", project_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    findings.reverse();
+
+    Ok(Json(ApiResponse::success(findings)))
+}
@@ -0,0 +1,278 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{delete, get, put},
+    Json, Router,
+};
+use mothership_common::{protocol::ApiResponse, ProjectId, UserId};
+use serde::Deserialize;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::api_error::ApiError;
+use crate::access_tokens::{generate_token, hash_token, validate_token_request, CreateTokenRequest, CreatedToken, SCOPE_ADMIN};
+use crate::audit::{self, AuditAction, AuditEvent};
+use crate::database::{AccessToken, ServiceAccount};
+use crate::handlers::require_admin;
+use crate::AppState;
+
+/// Domain of the placeholder email addresses given to service accounts
+const SERVICE_ACCOUNT_EMAIL_DOMAIN: &str = "service-accounts.mothership.local";
+
+/// Project membership role recorded for service account grants
+const SERVICE_ACCOUNT_MEMBER_ROLE: &str = "service";
+
+/// Service account management endpoints (admins only)
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/admin/service-accounts", get(list_service_accounts).post(create_service_account))
+        .route("/admin/service-accounts/:account_id", delete(disable_service_account))
+        .route("/admin/service-accounts/:account_id/keys", get(list_keys).post(create_key))
+        .route("/admin/service-accounts/:account_id/keys/:key_id", delete(revoke_key))
+        .route("/admin/service-accounts/:account_id/projects/:project_id", put(grant_project).delete(revoke_project))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateServiceAccountRequest {
+    /// Username of the account, e.g. "ci-bot"
+    pub name: String,
+    pub description: Option<String>,
+}
+
+/// Load an enabled service account or fail with 404
+async fn active_account(state: &AppState, account_id: UserId) -> Result<ServiceAccount, StatusCode> {
+    match state.db.get_service_account(account_id).await {
+        Ok(Some(account)) if account.disabled_at.is_none() => Ok(account),
+        Ok(_) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            error!("Failed to load service account {}: {}", account_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+fn valid_account_name(name: &str) -> bool {
+    (1..=64).contains(&name.len())
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+/// Create a service account
+async fn create_service_account(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<CreateServiceAccountRequest>,
+) -> Result<Json<ApiResponse<ServiceAccount>>, ApiError> {
+    let admin_id = require_admin(&state, &headers, "manage service accounts").await?;
+
+    let name = request.name.trim().to_lowercase();
+    if !valid_account_name(&name) {
+        return Err(ApiError::bad_request(
+            "Service account names must be 1-64 characters of a-z, 0-9, '-' or '_'",
+        ));
+    }
+    if state.db.user_exists_by_username(&name).await.unwrap_or(false) {
+        return Err(ApiError::conflict(format!("A user named '{}' already exists", name)));
+    }
+
+    let email = format!("{}@{}", name, SERVICE_ACCOUNT_EMAIL_DOMAIN);
+    let description = request.description.as_deref().map(str::trim).filter(|d| !d.is_empty());
+    let account = state.db.create_service_account(&name, &email, description, admin_id).await
+        .map_err(|e| {
+            error!("Failed to create service account {}: {}", name, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    info!("🤖 Created service account {} ({})", account.name, account.id);
+    audit::record(&state.db, AuditEvent::new(AuditAction::ServiceAccountCreated, Some(admin_id))
+        .target(account.id.to_string())
+        .details(serde_json::json!({ "name": account.name }))).await;
+
+    Ok(Json(ApiResponse::success(account)))
+}
+
+/// List all service accounts
+async fn list_service_accounts(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<ApiResponse<Vec<ServiceAccount>>>, StatusCode> {
+    require_admin(&state, &headers, "manage service accounts").await?;
+
+    let accounts = state.db.list_service_accounts().await
+        .map_err(|e| {
+            error!("Failed to list service accounts: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ApiResponse::success(accounts)))
+}
+
+/// Disable a service account, revoking all of its keys and project grants
+async fn disable_service_account(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(account_id): Path<UserId>,
+) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    let admin_id = require_admin(&state, &headers, "manage service accounts").await?;
+
+    let disabled = state.db.disable_service_account(account_id).await
+        .map_err(|e| {
+            error!("Failed to disable service account {}: {}", account_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if !disabled {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    info!("🤖 Disabled service account {}", account_id);
+    audit::record(&state.db, AuditEvent::new(AuditAction::ServiceAccountDisabled, Some(admin_id))
+        .target(account_id.to_string())).await;
+
+    Ok(Json(ApiResponse::success("Service account disabled".to_string())))
+}
+
+/// Issue a key (personal access token) for a service account
+async fn create_key(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(account_id): Path<UserId>,
+    Json(request): Json<CreateTokenRequest>,
+) -> Result<Json<ApiResponse<CreatedToken>>, ApiError> {
+    let admin_id = require_admin(&state, &headers, "manage service accounts").await?;
+    let account = active_account(&state, account_id).await?;
+
+    let (scopes, expires_at) = match validate_token_request(&request) {
+        Ok(validated) => validated,
+        Err(message) => return Err(ApiError::bad_request(message)),
+    };
+    // SECURITY CHECK: Automation never needs the admin API
+    if scopes.iter().any(|s| s == SCOPE_ADMIN) {
+        return Err(ApiError::bad_request("Service account keys cannot have the admin scope"));
+    }
+
+    let plaintext = generate_token();
+    let info = state.db
+        .create_access_token(account.id, request.name.trim(), &hash_token(&plaintext), &scopes, expires_at)
+        .await
+        .map_err(|e| {
+            error!("Failed to create key for service account {}: {}", account.id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    info!("🔑 Created key '{}' for service account {}", info.name, account.name);
+    audit::record(&state.db, AuditEvent::new(AuditAction::TokenCreated, Some(admin_id))
+        .target(info.id.to_string())
+        .details(serde_json::json!({
+            "name": info.name,
+            "scopes": info.scopes,
+            "service_account": account.id,
+        }))).await;
+
+    Ok(Json(ApiResponse::success(CreatedToken { token: plaintext, info })))
+}
+
+/// List a service account's keys, including revoked ones
+async fn list_keys(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(account_id): Path<UserId>,
+) -> Result<Json<ApiResponse<Vec<AccessToken>>>, StatusCode> {
+    require_admin(&state, &headers, "manage service accounts").await?;
+    if !state.db.is_service_account(account_id).await.unwrap_or(false) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let keys = state.db.list_access_tokens(account_id).await
+        .map_err(|e| {
+            error!("Failed to list keys of service account {}: {}", account_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ApiResponse::success(keys)))
+}
+
+/// Revoke one of a service account's keys
+async fn revoke_key(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((account_id, key_id)): Path<(UserId, Uuid)>,
+) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    let admin_id = require_admin(&state, &headers, "manage service accounts").await?;
+    if !state.db.is_service_account(account_id).await.unwrap_or(false) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let revoked = state.db.revoke_access_token(account_id, key_id).await
+        .map_err(|e| {
+            error!("Failed to revoke key {} of service account {}: {}", key_id, account_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if !revoked {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    info!("🗑️ Revoked key {} of service account {}", key_id, account_id);
+    audit::record(&state.db, AuditEvent::new(AuditAction::TokenRevoked, Some(admin_id))
+        .target(key_id.to_string())
+        .details(serde_json::json!({ "service_account": account_id }))).await;
+
+    Ok(Json(ApiResponse::success("Key revoked".to_string())))
+}
+
+/// Grant a service account access to a project
+async fn grant_project(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((account_id, project_id)): Path<(UserId, ProjectId)>,
+) -> Result<Json<ApiResponse<ServiceAccount>>, StatusCode> {
+    let admin_id = require_admin(&state, &headers, "manage service accounts").await?;
+    let account = active_account(&state, account_id).await?;
+
+    match state.db.get_project(project_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+
+    state.db.add_project_member(project_id, account.id, SERVICE_ACCOUNT_MEMBER_ROLE).await
+        .map_err(|e| {
+            error!("Failed to grant project {} to service account {}: {}", project_id, account.id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    info!("🤖 Granted project {} to service account {}", project_id, account.name);
+    audit::record(&state.db, AuditEvent::new(AuditAction::MemberAdded, Some(admin_id))
+        .project(project_id)
+        .target(account.id.to_string())
+        .details(serde_json::json!({ "service_account": account.name }))).await;
+
+    let account = active_account(&state, account_id).await?;
+    Ok(Json(ApiResponse::success(account)))
+}
+
+/// Remove a service account's access to a project
+async fn revoke_project(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((account_id, project_id)): Path<(UserId, ProjectId)>,
+) -> Result<Json<ApiResponse<ServiceAccount>>, StatusCode> {
+    let admin_id = require_admin(&state, &headers, "manage service accounts").await?;
+    let account = active_account(&state, account_id).await?;
+
+    let removed = state.db.remove_project_member(project_id, account.id).await
+        .map_err(|e| {
+            error!("Failed to remove service account {} from project {}: {}", account.id, project_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if !removed {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    info!("🤖 Removed service account {} from project {}", account.name, project_id);
+    audit::record(&state.db, AuditEvent::new(AuditAction::MemberRemoved, Some(admin_id))
+        .project(project_id)
+        .target(account.id.to_string())
+        .details(serde_json::json!({ "service_account": account.name }))).await;
+
+    let account = active_account(&state, account_id).await?;
+    Ok(Json(ApiResponse::success(account)))
+}
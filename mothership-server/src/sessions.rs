@@ -0,0 +1,95 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    routing::{delete, get},
+    Json, Router,
+};
+use mothership_common::{auth::AuthSession, protocol::ApiResponse};
+use serde::Deserialize;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::api_error::ApiError;
+use crate::audit::{self, AuditAction, AuditEvent};
+use crate::handlers::{authenticate_request, require_admin};
+use crate::AppState;
+
+/// Login session management endpoints
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/auth/sessions", get(list_sessions))
+        .route("/auth/sessions/:session_id", delete(revoke_session))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SessionsQuery {
+    /// List another user's sessions (admins only)
+    pub user: Option<String>,
+}
+
+/// The session (`jti`) the request was authenticated with, if any
+pub fn current_session(headers: &HeaderMap, state: &AppState) -> Option<Uuid> {
+    let token = headers.get("authorization")?.to_str().ok()?.strip_prefix("Bearer ")?;
+    let claims = state.auth.verify_token(token).ok()?;
+    Uuid::parse_str(claims.jti.as_deref()?).ok()
+}
+
+/// List the active sessions (machines) of the authenticated user, or of
+/// another user for admins
+async fn list_sessions(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<SessionsQuery>,
+) -> Result<Json<ApiResponse<Vec<AuthSession>>>, ApiError> {
+    let user_id = authenticate_request(&state, &headers)?;
+
+    let target = match query.user {
+        Some(username) => {
+            require_admin(&state, &headers, "list another user's sessions").await?;
+            match state.db.get_user_by_username(&username).await {
+                Ok(Some(user)) => user.id,
+                Ok(None) => return Err(ApiError::not_found(format!("User '{}' not found", username))),
+                Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR.into()),
+            }
+        }
+        None => user_id,
+    };
+
+    let sessions = state.db.list_auth_sessions(target, current_session(&headers, &state)).await
+        .map_err(|e| {
+            error!("Failed to list sessions of user {}: {}", target, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ApiResponse::success(sessions)))
+}
+
+/// Revoke a session. Users can revoke their own sessions; admins can revoke anyone's.
+async fn revoke_session(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(session_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    let user_id = authenticate_request(&state, &headers)?;
+
+    let is_admin = state.db.user_is_admin(user_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let owner_filter = if is_admin { None } else { Some(user_id) };
+
+    let owner = state.db.revoke_auth_session(session_id, owner_filter).await
+        .map_err(|e| {
+            error!("Failed to revoke session {}: {}", session_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    // Takes effect immediately: the token's jti is now rejected by verify_token
+    state.auth.revoke(session_id.to_string());
+
+    info!("🚫 Revoked session {} of user {} (by {})", session_id, owner, user_id);
+    audit::record(&state.db, AuditEvent::new(AuditAction::SessionRevoked, Some(user_id))
+        .target(session_id.to_string())
+        .details(serde_json::json!({ "owner": owner }))).await;
+
+    Ok(Json(ApiResponse::success("Session revoked".to_string())))
+}
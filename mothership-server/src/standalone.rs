@@ -0,0 +1,139 @@
+//! `mothership-server --standalone`: a personal sync server with no external
+//! dependencies.
+//!
+//! Everything lives in one data directory: a SQLite database, the content
+//! store, the generated JWT secret and the owner's access token. Web login
+//! sessions are kept in memory, no Redis relay or replication is started,
+//! and the server listens on localhost unless its config says otherwise.
+
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::access_tokens::{generate_token, hash_token, SCOPE_ADMIN, SCOPE_READ, SCOPE_WRITE};
+use crate::config::{ReplicationRole, ServerConfig};
+use crate::database::Database;
+use mothership_common::UserRole;
+
+/// Command-line flag that enables standalone mode
+pub const FLAG: &str = "--standalone";
+
+/// File in the data directory holding the owner's personal access token
+const OWNER_TOKEN_FILE: &str = "owner-token";
+
+/// File in the data directory holding the JWT signing secret
+const JWT_SECRET_FILE: &str = "jwt-secret";
+
+/// Whether the server was started with `--standalone`
+pub fn requested() -> bool {
+    std::env::args().skip(1).any(|arg| arg == FLAG)
+}
+
+/// Paths and settings of a standalone server
+#[derive(Clone)]
+pub struct Standalone {
+    data_dir: PathBuf,
+}
+
+impl Standalone {
+    /// Use `MOTHERSHIP_DATA_DIR`, or a `mothership-server` directory in the
+    /// platform's data directory, creating it if needed
+    pub fn new() -> Result<Self> {
+        let data_dir = match std::env::var("MOTHERSHIP_DATA_DIR") {
+            Ok(dir) if !dir.trim().is_empty() => PathBuf::from(dir),
+            _ => dirs::data_dir()
+                .ok_or_else(|| anyhow!("No data directory on this platform; set MOTHERSHIP_DATA_DIR"))?
+                .join("mothership-server"),
+        };
+        std::fs::create_dir_all(&data_dir)?;
+
+        info!("🏠 Standalone mode, data in {}", data_dir.display());
+        Ok(Self { data_dir })
+    }
+
+    /// The data directory's `server.config`, created on first start to listen
+    /// on localhost only. Relay and replication are always off.
+    pub fn config(&self) -> Result<ServerConfig> {
+        let path = self.data_dir.join("server.config");
+        if !path.exists() {
+            let mut config = ServerConfig::default();
+            config.server.host = "127.0.0.1".to_string();
+            config.save_to_file(&path)?;
+        }
+
+        let mut config = ServerConfig::load_from_file(&path)?;
+        if config.relay.enabled {
+            warn!("Ignoring [relay]: standalone servers don't use Redis");
+            config.relay.enabled = false;
+        }
+        if config.replication.role != ReplicationRole::Standalone {
+            warn!("Ignoring [replication]: standalone servers don't replicate");
+            config.replication.role = ReplicationRole::Standalone;
+        }
+        Ok(config)
+    }
+
+    pub fn database_url(&self) -> String {
+        format!("sqlite://{}", self.data_dir.join("mothership.db").display())
+    }
+
+    pub fn storage_root(&self) -> PathBuf {
+        self.data_dir.join("storage")
+    }
+
+    /// The JWT signing secret, generated on first start so tokens survive restarts
+    pub fn jwt_secret(&self) -> Result<String> {
+        let path = self.data_dir.join(JWT_SECRET_FILE);
+        if let Ok(secret) = std::fs::read_to_string(&path) {
+            if !secret.trim().is_empty() {
+                return Ok(secret.trim().to_string());
+            }
+        }
+
+        let secret = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        write_private(&path, &secret)?;
+        Ok(secret)
+    }
+
+    /// Make sure the server has an owner: a super admin with a personal access
+    /// token saved in the data directory, and say how to connect with it
+    pub async fn ensure_owner(&self, db: &Database, port: u16) -> Result<()> {
+        let path = self.data_dir.join(OWNER_TOKEN_FILE);
+
+        if !path.exists() {
+            let username = std::env::var("USER")
+                .or_else(|_| std::env::var("USERNAME"))
+                .unwrap_or_else(|_| "owner".to_string());
+            let owner = db.create_user(username.clone(), format!("{}@localhost", username), UserRole::SuperAdmin).await?;
+
+            let token = generate_token();
+            let scopes = [SCOPE_READ, SCOPE_WRITE, SCOPE_ADMIN].map(String::from);
+            db.create_access_token(owner.id, "standalone owner", &hash_token(&token), &scopes, None).await?;
+            write_private(&path, &token)?;
+
+            info!("👤 Created owner account '{}'", owner.username);
+        }
+
+        info!("🔑 Owner token saved in {}", path.display());
+        info!(
+            "🔑 Connect with: MOTHERSHIP_TOKEN=$(cat {}) mothership server connect http://127.0.0.1:{}",
+            path.display(),
+            port
+        );
+        Ok(())
+    }
+}
+
+/// Write a secret only the current user can read
+fn write_private(path: &Path, contents: &str) -> Result<()> {
+    std::fs::write(path, contents)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{delete, get},
+    Json, Router,
+};
+use mothership_common::{protocol::ApiResponse, ProjectId, Stash, UserId};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::handlers::authenticate_request;
+use crate::AppState;
+
+/// Stash backup endpoints. Stashes are private: each user only sees their own.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/projects/:id/stashes", get(list_stashes).post(backup_stash))
+        .route("/projects/:id/stashes/:stash_id", delete(delete_stash))
+}
+
+/// Authenticate the request and check the caller is a member of the project
+async fn member_of(state: &AppState, headers: &HeaderMap, project_id: ProjectId) -> Result<UserId, StatusCode> {
+    let user_id = authenticate_request(state, headers)?;
+
+    if !state.db.user_has_project_access(user_id, project_id).await.unwrap_or(false) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok(user_id)
+}
+
+/// List the caller's stash backups for a project, oldest first
+async fn list_stashes(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(project_id): Path<ProjectId>,
+) -> Result<Json<ApiResponse<Vec<Stash>>>, StatusCode> {
+    let user_id = member_of(&state, &headers, project_id).await?;
+
+    let stashes = state.sync.storage.list_stashes(user_id, project_id).await
+        .map_err(|e| {
+            error!("Failed to list stashes of user {} in project {}: {}", user_id, project_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ApiResponse::success(stashes)))
+}
+
+/// Back up a stash made on the caller's machine
+async fn backup_stash(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(project_id): Path<ProjectId>,
+    Json(stash): Json<Stash>,
+) -> Result<Json<ApiResponse<Uuid>>, StatusCode> {
+    let user_id = member_of(&state, &headers, project_id).await?;
+
+    state.sync.storage.save_stash(user_id, project_id, &stash).await
+        .map_err(|e| {
+            error!("Failed to save stash {}: {}", stash.id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    info!("🗃️ Backed up stash {} ({} files) for user {} in project {}", stash.id, stash.files.len(), user_id, project_id);
+    Ok(Json(ApiResponse::success(stash.id)))
+}
+
+/// Remove a stash backup once it has been popped
+async fn delete_stash(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((project_id, stash_id)): Path<(ProjectId, Uuid)>,
+) -> Result<Json<ApiResponse<()>>, StatusCode> {
+    let user_id = member_of(&state, &headers, project_id).await?;
+
+    match state.sync.storage.remove_stash(user_id, project_id, stash_id).await {
+        Ok(true) => Ok(Json(ApiResponse::success(()))),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            error!("Failed to remove stash {}: {}", stash_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use anyhow::Result;
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::get,
+    Json, Router,
+};
+use mothership_common::{
+    combined_status, protocol::{ApiResponse, CreateStatusRequest}, CheckpointId, CheckpointStatus, ProjectId, Rift,
+    StatusState, UserId,
+};
+use tracing::{error, info};
+
+use crate::api_error::ApiError;
+use crate::database::NewCheckpointStatus;
+use crate::handlers::authenticate_request;
+use crate::AppState;
+
+/// Longest accepted status context name
+const MAX_CONTEXT_LENGTH: usize = 255;
+
+/// Checkpoint status check endpoints
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/projects/:id/checkpoints/:checkpoint_id/statuses",
+            get(list_statuses).post(create_status),
+        )
+}
+
+/// Authenticate the request and check the checkpoint belongs to an accessible project
+async fn authorize_checkpoint(
+    state: &AppState,
+    headers: &HeaderMap,
+    project_id: ProjectId,
+    checkpoint_id: CheckpointId,
+) -> Result<UserId, StatusCode> {
+    let user_id = authenticate_request(state, headers)?;
+
+    if !state.db.user_has_project_access(user_id, project_id).await.unwrap_or(false) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let checkpoint = match state.sync.storage.load_checkpoint(checkpoint_id).await {
+        Ok(Some(checkpoint)) => checkpoint,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    match state.db.get_rift(checkpoint.rift_id).await {
+        Ok(Some(rift)) if rift.project_id == project_id => Ok(user_id),
+        Ok(_) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// List the status checks reported for a checkpoint
+async fn list_statuses(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((project_id, checkpoint_id)): Path<(ProjectId, CheckpointId)>,
+) -> Result<Json<ApiResponse<Vec<CheckpointStatus>>>, StatusCode> {
+    authorize_checkpoint(&state, &headers, project_id, checkpoint_id).await?;
+
+    let statuses = state.db.list_checkpoint_statuses(&[checkpoint_id]).await
+        .map_err(|e| {
+            error!("Failed to list statuses for checkpoint {}: {}", checkpoint_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ApiResponse::success(statuses)))
+}
+
+/// Report the result of an external check (CI build, tests, ...) for a checkpoint
+async fn create_status(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((project_id, checkpoint_id)): Path<(ProjectId, CheckpointId)>,
+    Json(req): Json<CreateStatusRequest>,
+) -> Result<Json<ApiResponse<CheckpointStatus>>, ApiError> {
+    let user_id = authorize_checkpoint(&state, &headers, project_id, checkpoint_id).await?;
+
+    let context = req.context.trim();
+    if context.is_empty() || context.len() > MAX_CONTEXT_LENGTH {
+        return Err(ApiError::bad_request(format!(
+            "Context must be between 1 and {} characters",
+            MAX_CONTEXT_LENGTH
+        )));
+    }
+    if let Some(url) = &req.target_url {
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(ApiError::bad_request("Target URL must be an http(s) URL"));
+        }
+    }
+    // Otherwise whoever wants to merge could pass the checks that gate it themselves
+    if state.config().status_checks.required_contexts.iter().any(|required| required == context) {
+        match may_report_required_checks(&state, user_id).await {
+            Ok(true) => {}
+            Ok(false) => {
+                return Err(ApiError::forbidden(format!(
+                    "Only service accounts and admins may report the required check '{}'",
+                    context
+                )));
+            }
+            Err(e) => {
+                error!("Failed to check who may report status '{}': {}", context, e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR.into());
+            }
+        }
+    }
+
+    let status = state.db
+        .upsert_checkpoint_status(NewCheckpointStatus {
+            project_id,
+            creator_id: user_id,
+            checkpoint_id,
+            state: req.state,
+            context,
+            target_url: req.target_url.as_deref(),
+            description: req.description.as_deref(),
+        })
+        .await
+        .map_err(|e| {
+            error!("Failed to store status for checkpoint {}: {}", checkpoint_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    info!("🚦 Status '{}' is {} for checkpoint {}", status.context, status.state.as_str(), checkpoint_id);
+    Ok(Json(ApiResponse::success(status)))
+}
+
+/// Required checks gate merges, so only automation and admins report them
+async fn may_report_required_checks(state: &AppState, user_id: UserId) -> Result<bool> {
+    Ok(state.db.is_service_account(user_id).await? || state.db.user_is_admin(user_id).await?)
+}
+
+/// Check whether a rift may be merged under the configured status requirements.
+/// The checks only cover the files of the checkpoint they ran on, so a rift
+/// changed since its latest checkpoint is blocked until it is checkpointed
+/// and checked again.
+/// Returns the reason the merge is blocked, or None if it may proceed.
+pub async fn merge_blocked_reason(state: &AppState, rift: &Rift) -> Result<Option<String>> {
+    let settings = &state.config().status_checks;
+    if !settings.require_green_for_merge {
+        return Ok(None);
+    }
+
+    let Some(latest) = state.sync.storage.latest_checkpoint(rift.id).await else {
+        return Ok(Some("Rift has no checkpoints with status checks".to_string()));
+    };
+
+    // Merges take the live files, which must be the ones that were checked
+    if state.sync.storage.unchanged_since_checkpoint(rift.id).await.map(|checked| checked.id) != Some(latest.id) {
+        return Ok(Some(format!("Rift has changed since checkpoint {}, so its status checks don't cover it", latest.id)));
+    }
+
+    let statuses = state.db.list_checkpoint_statuses(&[latest.id]).await?;
+
+    for context in &settings.required_contexts {
+        match statuses.iter().find(|s| &s.context == context) {
+            Some(status) if status.state == StatusState::Success => {}
+            Some(status) => {
+                return Ok(Some(format!("Required check '{}' is {}", context, status.state.as_str())));
+            }
+            None => return Ok(Some(format!("Required check '{}' has not reported", context))),
+        }
+    }
+
+    match combined_status(&statuses) {
+        Some(StatusState::Success) => Ok(None),
+        Some(state) => Ok(Some(format!("Status checks are {} for checkpoint {}", state.as_str(), latest.id))),
+        None => Ok(Some(format!("No status checks reported for checkpoint {}", latest.id))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServerConfig;
+    use crate::handlers::CreateRiftRequest;
+    use crate::test_client::TestClient;
+    use mothership_common::protocol::BeamResponse;
+    use mothership_common::{Project, UserRole};
+
+    #[tokio::test]
+    async fn test_required_checks_gate_the_files_they_ran_on() {
+        let mut config = ServerConfig::default();
+        config.status_checks.require_green_for_merge = true;
+        config.status_checks.required_contexts = vec!["ci/build".to_string()];
+        let mut client = TestClient::with_config(config).await;
+        let user = client.sign_in("merger").await;
+        let project: Project = client.post("/gateway/create", &serde_json::json!({
+            "name": "gated",
+            "description": "",
+            "project_path": "/tmp/gated",
+        })).await.data();
+        let _: BeamResponse = client.post(&format!("/projects/{}/beam", project.id), &serde_json::json!({
+            "project_id": project.id,
+            "rift_name": null,
+            "force_sync": false,
+        })).await.data();
+        let feature: uuid::Uuid = client.post("/api/rifts", &CreateRiftRequest {
+            project_id: project.id,
+            name: "feature".to_string(),
+            description: None,
+        }).await.data();
+
+        let storage = client.state.sync.storage.clone();
+        storage.update_live_state(feature, "app.rs".into(), "fn main() {}\n".to_string()).await.unwrap();
+        let checkpoint = storage.create_checkpoint(feature, user.id, None, false).await.unwrap();
+        let statuses = format!("/projects/{}/checkpoints/{}/statuses", project.id, checkpoint.id);
+        let success = CreateStatusRequest {
+            state: StatusState::Success,
+            context: "ci/build".to_string(),
+            target_url: None,
+            description: None,
+        };
+
+        // The person merging can't pass the required check themselves
+        assert_eq!(client.post(&statuses, &success).await.status, StatusCode::FORBIDDEN);
+        client.state.db.set_user_role(user.id, UserRole::Admin).await.unwrap();
+        let _: CheckpointStatus = client.post(&statuses, &success).await.data();
+
+        // Edits after the checked checkpoint weren't checked
+        storage.update_live_state(feature, "app.rs".into(), "fn main() { unchecked() }\n".to_string()).await.unwrap();
+        let merge = format!("/projects/{}/rifts/{}/merge", project.id, feature);
+        assert_eq!(client.post(&merge, &()).await.status, StatusCode::CONFLICT);
+
+        storage.update_live_state(feature, "app.rs".into(), "fn main() {}\n".to_string()).await.unwrap();
+        assert_eq!(client.post(&merge, &()).await.status, StatusCode::OK);
+    }
+}
@@ -1,21 +1,97 @@
 use anyhow::Result;
-use chrono::Utc;
-use mothership_common::{Checkpoint, CheckpointId, FileChange, ChangeType, RiftId, UserId};
+use chrono::{DateTime, Utc};
+use mothership_common::{Checkpoint, CheckpointConflict, CheckpointConflictReason, CheckpointId, FileChange, FileMetadata, ChangeType, ProjectId, RiftId, SecretFinding, Stash, UserId};
+use mothership_common::rift_events::RiftEvent;
+use mothership_common::upload::{content_hash, UploadFileManifest};
+use mothership_common::push_rules::PushRuleViolation;
+use mothership_common::{DiffEngine, FileDiff};
+use serde::{Deserialize, Serialize};
 use sha2::{Sha256, Digest};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::fs;
-use tokio::sync::RwLock;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncSeekExt};
+use tokio::sync::{Mutex, RwLock};
 use uuid::Uuid;
 
+mod files;
+
+use files::{FileReader, Files};
+
+/// Secret scanning findings kept per project
+const MAX_SECRET_FINDINGS: usize = 1000;
+
+/// How long a checkpoint waits for another one on the same rift to finish
+const CHECKPOINT_LOCK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Blobs younger than this may belong to a checkpoint still being written,
+/// so they are never counted as orphaned
+const ORPHAN_GRACE_PERIOD: Duration = Duration::from_secs(60 * 60);
+
+/// What compacting a rift's history did
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RiftCompaction {
+    /// Checkpoints that now stand for a run of automatic checkpoints
+    pub summaries: usize,
+    /// Checkpoints folded into them and removed
+    pub removed: usize,
+}
+
+/// The files a merge changes in its target rift
+#[derive(Debug, Default, Clone)]
+pub struct MergedFiles {
+    /// New content for each changed file, or `None` where the merge deletes it
+    pub contents: HashMap<PathBuf, Option<String>>,
+    /// Permissions and symlinks of the merged files, taken from the source rift
+    pub metadata: HashMap<PathBuf, FileMetadata>,
+}
+
+/// Where a new checkpoint's files come from
+enum SnapshotFiles {
+    /// The rift's live files
+    Live,
+    /// The latest checkpoint's files with staged content in place of theirs
+    Staged(HashMap<PathBuf, String>),
+    /// The live files with a merge applied, before it is written to them
+    Merged(MergedFiles),
+}
+
+/// A checkpoint to take of a rift
+struct Snapshot {
+    rift_id: RiftId,
+    author: UserId,
+    message: Option<String>,
+    auto_generated: bool,
+    merged_from: Option<RiftId>,
+    /// Refuse the checkpoint unless this is still the rift's latest one
+    expected_parent: Option<CheckpointId>,
+    files: SnapshotFiles,
+}
+
 /// Content-Addressable Storage + Checkpoint Management
 pub struct StorageEngine {
     /// Base directory for all storage
     storage_root: PathBuf,
+    /// Disk, or memory for tests
+    files: Files,
     /// In-memory checkpoint index for fast lookups
     checkpoint_index: RwLock<HashMap<CheckpointId, Checkpoint>>,
     /// In-memory rift state (current working files)
     live_state: RwLock<HashMap<RiftId, HashMap<PathBuf, String>>>,
+    /// Permissions, symlink targets and mtimes of live files that have any
+    live_metadata: RwLock<HashMap<RiftId, HashMap<PathBuf, FileMetadata>>>,
+    /// Files moved since a rift's last checkpoint (new path -> path at that checkpoint)
+    live_moves: RwLock<HashMap<RiftId, HashMap<PathBuf, PathBuf>>>,
+    /// Serializes appends to the replication log
+    replication_log: Mutex<()>,
+    /// Serializes checkpoint creation per rift, so each one's parent is the last
+    checkpoint_locks: Mutex<HashMap<RiftId, Arc<Mutex<()>>>>,
+    /// Serializes updates of secret scanning findings
+    secret_findings: Mutex<()>,
+    /// Serializes appends to rift event logs
+    event_logs: Mutex<()>,
 }
 
 impl StorageEngine {
@@ -25,12 +101,34 @@ impl StorageEngine {
         fs::create_dir_all(storage_root.join("content")).await?;  // CAS storage
         fs::create_dir_all(storage_root.join("checkpoints")).await?;  // Checkpoint metadata
         fs::create_dir_all(storage_root.join("live")).await?;  // Working state
+        fs::create_dir_all(storage_root.join("chunks")).await?;  // Upload chunks (CAS)
+        fs::create_dir_all(storage_root.join("uploads")).await?;  // Upload session manifests
+        fs::create_dir_all(storage_root.join("replication")).await?;  // Checkpoint log + follower cursor
+        fs::create_dir_all(storage_root.join("secrets")).await?;  // Secret scanning findings
+        fs::create_dir_all(storage_root.join("malware")).await?;  // Files flagged by malware scanning
+        fs::create_dir_all(storage_root.join("events")).await?;  // Per-rift event logs
         
-        Ok(Self {
+        Ok(Self::with_files(storage_root, Files::Disk))
+    }
+
+    /// Storage that keeps everything in memory and is gone once dropped, for tests
+    pub fn new_in_memory() -> Self {
+        Self::with_files(PathBuf::new(), Files::memory())
+    }
+
+    fn with_files(storage_root: PathBuf, files: Files) -> Self {
+        Self {
             storage_root,
+            files,
             checkpoint_index: RwLock::new(HashMap::new()),
             live_state: RwLock::new(HashMap::new()),
-        })
+            live_metadata: RwLock::new(HashMap::new()),
+            live_moves: RwLock::new(HashMap::new()),
+            replication_log: Mutex::new(()),
+            checkpoint_locks: Mutex::new(HashMap::new()),
+            secret_findings: Mutex::new(()),
+            event_logs: Mutex::new(()),
+        }
     }
 
     /// Store file content using content-addressable storage
@@ -44,8 +142,8 @@ impl StorageEngine {
         let content_path = self.storage_root.join("content").join(&hash);
         
         // Only write if file doesn't exist (deduplication)
-        if !content_path.exists() {
-            fs::write(&content_path, content).await?;
+        if !self.files.exists(&content_path) {
+            self.files.write(&content_path, content).await?;
         }
         
         Ok(hash)
@@ -55,8 +153,8 @@ impl StorageEngine {
     pub async fn get_content(&self, hash: &str) -> Result<Option<String>> {
         let content_path = self.storage_root.join("content").join(hash);
         
-        if content_path.exists() {
-            let content = fs::read_to_string(&content_path).await?;
+        if self.files.exists(&content_path) {
+            let content = self.files.read_to_string(&content_path).await?;
             Ok(Some(content))
         } else {
             Ok(None)
@@ -73,6 +171,149 @@ impl StorageEngine {
         Ok(())
     }
 
+    /// Apply a diff to a file's live state and return the reconstructed content.
+    /// The lock is held throughout so block deltas are always applied to the base
+    /// they were computed against.
+    pub async fn apply_file_diff(&self, rift_id: RiftId, path: &PathBuf, diff: &FileDiff) -> Result<String> {
+        let mut live_state = self.live_state.write().await;
+
+        let rift_files = live_state.entry(rift_id).or_insert_with(HashMap::new);
+        if matches!(diff, FileDiff::Deleted) {
+            rift_files.remove(path);
+            self.forget_live_file(rift_id, path).await;
+            return Ok(String::new());
+        }
+        let original = rift_files.get(path).map(String::as_str).unwrap_or(""); // New file
+        let new_content = DiffEngine::new().apply_diff(original, diff)?;
+        rift_files.insert(path.clone(), new_content.clone());
+
+        Ok(new_content)
+    }
+
+    /// What a file would contain after applying a diff, without changing it
+    pub async fn preview_file_diff(&self, rift_id: RiftId, path: &PathBuf, diff: &FileDiff) -> Result<String> {
+        let live_state = self.live_state.read().await;
+        let original = live_state.get(&rift_id)
+            .and_then(|files| files.get(path))
+            .map(String::as_str)
+            .unwrap_or(""); // New file
+        DiffEngine::new().apply_diff(original, diff)
+    }
+
+    /// Replace a rift's whole working state
+    pub async fn replace_live_state(&self, rift_id: RiftId, files: HashMap<PathBuf, String>) {
+        let mut live_state = self.live_state.write().await;
+        if let Some(metadata) = self.live_metadata.write().await.get_mut(&rift_id) {
+            metadata.retain(|path, _| files.contains_key(path));
+        }
+        self.live_moves.write().await.remove(&rift_id);
+        live_state.insert(rift_id, files);
+    }
+
+    /// Remove a live file only if it still has the given content. Returns
+    /// whether it was removed.
+    pub async fn remove_live_file_if_unchanged(&self, rift_id: RiftId, path: &PathBuf, content: &str) -> bool {
+        let mut live_state = self.live_state.write().await;
+        let Some(rift_files) = live_state.get_mut(&rift_id) else {
+            return false;
+        };
+        if rift_files.get(path).map(String::as_str) != Some(content) {
+            return false;
+        }
+        rift_files.remove(path);
+        self.forget_live_file(rift_id, path).await;
+        true
+    }
+
+    /// Put a file back into a rift's live state unless something else has
+    /// been written to its path since. Returns whether it was restored.
+    pub async fn restore_live_file(&self, rift_id: RiftId, path: PathBuf, content: String) -> bool {
+        let mut live_state = self.live_state.write().await;
+        let rift_files = live_state.entry(rift_id).or_default();
+        if rift_files.contains_key(&path) {
+            return false;
+        }
+        rift_files.insert(path, content);
+        true
+    }
+
+    /// Remove a file from the live working state of a rift
+    pub async fn remove_live_file(&self, rift_id: RiftId, path: &PathBuf) -> Result<()> {
+        let mut live_state = self.live_state.write().await;
+
+        if let Some(rift_files) = live_state.get_mut(&rift_id) {
+            rift_files.remove(path);
+        }
+        self.forget_live_file(rift_id, path).await;
+
+        Ok(())
+    }
+
+    /// Drop what is tracked about a removed live file besides its content
+    async fn forget_live_file(&self, rift_id: RiftId, path: &PathBuf) {
+        if let Some(metadata) = self.live_metadata.write().await.get_mut(&rift_id) {
+            metadata.remove(path);
+        }
+        if let Some(moves) = self.live_moves.write().await.get_mut(&rift_id) {
+            moves.remove(path);
+        }
+    }
+
+    /// Move a live file to a new path, keeping its metadata. Moving onto an
+    /// existing file replaces it (as editors saving through a temporary file
+    /// do) and isn't recorded as a move. Returns the file's content, or `None`
+    /// if there is no file at `from`.
+    pub async fn move_live_file(&self, rift_id: RiftId, from: &PathBuf, to: &PathBuf) -> Option<String> {
+        let mut live_state = self.live_state.write().await;
+        let rift_files = live_state.get_mut(&rift_id)?;
+        let content = rift_files.remove(from)?;
+        let replaced = rift_files.insert(to.clone(), content.clone()).is_some();
+
+        let mut all_metadata = self.live_metadata.write().await;
+        let metadata = all_metadata.entry(rift_id).or_default();
+        match metadata.remove(from) {
+            Some(moved) => { metadata.insert(to.clone(), moved); }
+            None => { metadata.remove(to); }
+        }
+
+        // Chains of moves between checkpoints are recorded from the original path
+        let mut all_moves = self.live_moves.write().await;
+        let moves = all_moves.entry(rift_id).or_default();
+        let original = moves.remove(from).unwrap_or_else(|| from.clone());
+        moves.remove(to);
+        if !replaced && &original != to {
+            moves.insert(to.clone(), original);
+        }
+
+        Some(content)
+    }
+
+    /// Record a live file's metadata. Clients that don't send metadata leave
+    /// what is recorded untouched.
+    pub async fn update_file_metadata(&self, rift_id: RiftId, path: &PathBuf, metadata: &FileMetadata) {
+        if metadata.is_empty() {
+            return;
+        }
+        self.live_metadata.write().await
+            .entry(rift_id)
+            .or_default()
+            .insert(path.clone(), metadata.clone());
+    }
+
+    /// Metadata of a live file, empty if none was recorded
+    pub async fn get_file_metadata(&self, rift_id: RiftId, path: &PathBuf) -> FileMetadata {
+        self.live_metadata.read().await
+            .get(&rift_id)
+            .and_then(|files| files.get(path))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Metadata of every live file of a rift that has any
+    pub async fn get_live_metadata(&self, rift_id: RiftId) -> HashMap<PathBuf, FileMetadata> {
+        self.live_metadata.read().await.get(&rift_id).cloned().unwrap_or_default()
+    }
+
     /// Get current live state for a rift
     pub async fn get_live_state(&self, rift_id: RiftId) -> Result<HashMap<PathBuf, String>> {
         let live_state = self.live_state.read().await;
@@ -100,38 +341,174 @@ impl StorageEngine {
         message: Option<String>,
         auto_generated: bool,
     ) -> Result<Checkpoint> {
+        self.snapshot_live_state(Snapshot {
+            rift_id,
+            author,
+            message,
+            auto_generated,
+            merged_from: None,
+            expected_parent: None,
+            files: SnapshotFiles::Live,
+        }).await
+    }
+
+    /// Create a checkpoint only if the rift's latest checkpoint is still
+    /// `expected_parent`; fails with a `CheckpointConflict` otherwise
+    pub async fn create_checkpoint_after(
+        &self,
+        rift_id: RiftId,
+        author: UserId,
+        message: Option<String>,
+        auto_generated: bool,
+        expected_parent: Option<CheckpointId>,
+    ) -> Result<Checkpoint> {
+        self.snapshot_live_state(Snapshot {
+            rift_id,
+            author,
+            message,
+            auto_generated,
+            merged_from: None,
+            expected_parent,
+            files: SnapshotFiles::Live,
+        }).await
+    }
+
+    /// Create a checkpoint holding only staged changes: the files of the
+    /// rift's latest checkpoint, with `staged` content in place of theirs.
+    /// Live files are left as they are for a later checkpoint.
+    pub async fn create_staged_checkpoint(
+        &self,
+        rift_id: RiftId,
+        author: UserId,
+        message: Option<String>,
+        staged: HashMap<PathBuf, String>,
+        expected_parent: Option<CheckpointId>,
+    ) -> Result<Checkpoint> {
+        self.snapshot_live_state(Snapshot {
+            rift_id,
+            author,
+            message,
+            auto_generated: false,
+            merged_from: None,
+            expected_parent,
+            files: SnapshotFiles::Staged(staged),
+        }).await
+    }
+
+    /// Create the checkpoint recording a merge of `source` into `rift_id`: the
+    /// rift's live files with `merged` applied. The live files themselves are
+    /// left for the caller to update once the checkpoint exists, so a refused
+    /// checkpoint leaves the rift as it was.
+    pub async fn create_merge_checkpoint(
+        &self,
+        rift_id: RiftId,
+        author: UserId,
+        message: Option<String>,
+        source: RiftId,
+        merged: MergedFiles,
+    ) -> Result<Checkpoint> {
+        self.snapshot_live_state(Snapshot {
+            rift_id,
+            author,
+            message,
+            auto_generated: false,
+            merged_from: Some(source),
+            expected_parent: None,
+            files: SnapshotFiles::Merged(merged),
+        }).await
+    }
+
+    /// Held while a rift's history is written
+    async fn checkpoint_lock(&self, rift_id: RiftId) -> Arc<Mutex<()>> {
+        self.checkpoint_locks.lock().await
+            .entry(rift_id)
+            .or_default()
+            .clone()
+    }
+
+    /// The rift's latest checkpoint
+    pub async fn latest_checkpoint(&self, rift_id: RiftId) -> Option<Checkpoint> {
+        self.checkpoint_index.read().await
+            .values()
+            .filter(|cp| cp.rift_id == rift_id)
+            .max_by(|a, b| a.timestamp.cmp(&b.timestamp).then(a.id.cmp(&b.id)))
+            .cloned()
+    }
+
+    async fn head_checkpoint(&self, rift_id: RiftId) -> Option<CheckpointId> {
+        self.latest_checkpoint(rift_id).await.map(|c| c.id)
+    }
+
+    /// The rift's latest checkpoint, if its live files haven't changed since
+    pub async fn unchanged_since_checkpoint(&self, rift_id: RiftId) -> Option<Checkpoint> {
+        let latest = self.latest_checkpoint(rift_id).await?;
+        let checkpointed: HashMap<&PathBuf, &str> = latest.changes.iter()
+            .filter(|c| !matches!(c.change_type, ChangeType::Deleted))
+            .map(|c| (&c.path, c.content_hash.as_str()))
+            .collect();
+
+        let live_state = self.live_state.read().await;
+        let unchanged = match live_state.get(&rift_id) {
+            Some(live) => live.len() == checkpointed.len()
+                && live.iter().all(|(path, content)| checkpointed.get(path) == Some(&content_hash(content.as_bytes()).as_str())),
+            None => checkpointed.is_empty(),
+        };
+        unchanged.then_some(latest)
+    }
+
+    /// Rifts with live files in memory
+    pub async fn active_rifts(&self) -> Vec<RiftId> {
+        self.live_state.read().await
+            .iter()
+            .filter(|(_, files)| !files.is_empty())
+            .map(|(rift_id, _)| *rift_id)
+            .collect()
+    }
+
+    async fn snapshot_live_state(&self, snapshot: Snapshot) -> Result<Checkpoint> {
+        let Snapshot { rift_id, author, message, auto_generated, merged_from, expected_parent, files } = snapshot;
+
+        // One checkpoint at a time per rift, or two could share a parent and fork the history
+        let lock = self.checkpoint_lock(rift_id).await;
+        let Ok(_guard) = tokio::time::timeout(CHECKPOINT_LOCK_TIMEOUT, lock.lock_owned()).await else {
+            return Err(CheckpointConflict {
+                rift_id,
+                reason: CheckpointConflictReason::Busy,
+                expected_parent,
+                head: self.head_checkpoint(rift_id).await,
+            }.into());
+        };
+
+        let parent = self.head_checkpoint(rift_id).await;
+        if expected_parent.is_some() && expected_parent != parent {
+            return Err(CheckpointConflict {
+                rift_id,
+                reason: CheckpointConflictReason::ParentMoved,
+                expected_parent,
+                head: parent,
+            }.into());
+        }
+
         let checkpoint_id = Uuid::new_v4();
         let timestamp = Utc::now();
-        
-        // Get current live state
-        let live_files = self.get_live_state(rift_id).await?;
-        
-        // TODO: For now, treat all files as new/modified
-        // In production, this would diff against parent checkpoint
-        let mut changes = Vec::new();
-        
-        for (path, content) in live_files {
-            let content_hash = self.store_content(&content).await?;
-            let size = content.len() as u64;
-            
-            changes.push(FileChange {
-                path: path.clone(),
-                change_type: ChangeType::Modified, // Simplified for now
-                content_hash,
-                diff: None, // TODO: Generate diff
-                size,
-            });
-        }
-        
+
+        let changes = match files {
+            SnapshotFiles::Live => self.live_changes(rift_id).await?,
+            SnapshotFiles::Staged(staged) => self.staged_changes(rift_id, staged).await?,
+            SnapshotFiles::Merged(merged) => self.merged_changes(rift_id, merged).await?,
+        };
+
         let checkpoint = Checkpoint {
             id: checkpoint_id,
             rift_id,
             author,
             timestamp,
             changes,
-            parent: None, // TODO: Link to parent checkpoint
+            parent,
             message,
             auto_generated,
+            merged_from,
+            squashed: 0,
         };
         
         // Store checkpoint metadata
@@ -146,6 +523,100 @@ impl StorageEngine {
         Ok(checkpoint)
     }
 
+    /// The rift's live files as checkpoint changes
+    async fn live_changes(&self, rift_id: RiftId) -> Result<Vec<FileChange>> {
+        // Get current live state
+        let live_files = self.get_live_state(rift_id).await?;
+        let mut live_metadata = self.get_live_metadata(rift_id).await;
+        let moves = self.live_moves.write().await.remove(&rift_id).unwrap_or_default();
+        
+        // TODO: For now, treat all files as new/modified
+        // In production, this would diff against parent checkpoint
+        let mut changes = Vec::new();
+        
+        for (path, content) in live_files {
+            let content_hash = self.store_content(&content).await?;
+            let size = content.len() as u64;
+            
+            let change_type = match moves.get(&path) {
+                Some(from) => ChangeType::Moved { from: from.clone() },
+                None => ChangeType::Modified, // Simplified for now
+            };
+            changes.push(FileChange {
+                path: path.clone(),
+                change_type,
+                content_hash,
+                diff: None, // TODO: Generate diff
+                size,
+                metadata: live_metadata.remove(&path).unwrap_or_default(),
+            });
+        }
+
+        Ok(changes)
+    }
+
+    /// The rift's live files as checkpoint changes, with a merge applied
+    async fn merged_changes(&self, rift_id: RiftId, merged: MergedFiles) -> Result<Vec<FileChange>> {
+        let MergedFiles { contents, mut metadata } = merged;
+        let mut changes = self.live_changes(rift_id).await?;
+        changes.retain(|change| !contents.contains_key(&change.path));
+
+        for (path, content) in contents {
+            let Some(content) = content else { continue };
+            changes.push(FileChange {
+                content_hash: self.store_content(&content).await?,
+                size: content.len() as u64,
+                change_type: ChangeType::Modified,
+                diff: None,
+                metadata: metadata.remove(&path).unwrap_or_default(),
+                path,
+            });
+        }
+        Ok(changes)
+    }
+
+    /// The files of the rift's latest checkpoint with staged content swapped in
+    async fn staged_changes(&self, rift_id: RiftId, mut staged: HashMap<PathBuf, String>) -> Result<Vec<FileChange>> {
+        let mut changes = Vec::new();
+        if let Some(latest) = self.latest_checkpoint(rift_id).await {
+            for change in latest.changes.into_iter().filter(|c| !matches!(c.change_type, ChangeType::Deleted)) {
+                let change = match staged.remove(&change.path) {
+                    Some(content) => FileChange {
+                        content_hash: self.store_content(&content).await?,
+                        size: content.len() as u64,
+                        change_type: ChangeType::Modified,
+                        diff: None,
+                        ..change
+                    },
+                    None => FileChange { change_type: ChangeType::Modified, diff: None, ..change },
+                };
+                changes.push(change);
+            }
+        }
+
+        // Staged files the last checkpoint didn't have yet
+        let mut live_metadata = self.get_live_metadata(rift_id).await;
+        for (path, content) in staged {
+            changes.push(FileChange {
+                content_hash: self.store_content(&content).await?,
+                size: content.len() as u64,
+                change_type: ChangeType::Modified,
+                diff: None,
+                metadata: live_metadata.remove(&path).unwrap_or_default(),
+                path,
+            });
+        }
+        Ok(changes)
+    }
+
+    /// Add an existing checkpoint (e.g. from an imported bundle) to storage.
+    /// Its content must already be stored.
+    pub async fn import_checkpoint(&self, checkpoint: &Checkpoint) -> Result<()> {
+        self.store_checkpoint(checkpoint).await?;
+        self.checkpoint_index.write().await.insert(checkpoint.id, checkpoint.clone());
+        Ok(())
+    }
+
     /// Store checkpoint metadata to disk
     async fn store_checkpoint(&self, checkpoint: &Checkpoint) -> Result<()> {
         let checkpoint_path = self.storage_root
@@ -153,8 +624,78 @@ impl StorageEngine {
             .join(format!("{}.json", checkpoint.id));
         
         let json = serde_json::to_string_pretty(checkpoint)?;
-        fs::write(&checkpoint_path, json).await?;
+        self.files.write(&checkpoint_path, json).await?;
         
+        self.append_replication_log(checkpoint).await
+    }
+
+    fn replication_log_path(&self) -> PathBuf {
+        self.storage_root.join("replication").join("checkpoints.log")
+    }
+
+    /// Append a checkpoint to the log secondaries replicate from (one JSON line each)
+    async fn append_replication_log(&self, checkpoint: &Checkpoint) -> Result<()> {
+        let mut line = serde_json::to_vec(checkpoint)?;
+        line.push(b'\n');
+
+        let _guard = self.replication_log.lock().await;
+        self.files.append(self.replication_log_path(), &line).await?;
+
+        Ok(())
+    }
+
+    /// Current end of the replication log
+    pub async fn replication_log_position(&self) -> Result<u64> {
+        match self.files.len(self.replication_log_path()).await {
+            Ok(len) => Ok(len),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Read up to `limit` checkpoints written at or after `position`.
+    /// Returns them with the position to resume from.
+    pub async fn read_replication_log(&self, position: u64, limit: usize) -> Result<(Vec<Checkpoint>, u64)> {
+        let mut log = match self.files.open(self.replication_log_path()).await {
+            Ok(log) => log,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((Vec::new(), 0)),
+            Err(e) => return Err(e.into()),
+        };
+        log.seek(std::io::SeekFrom::Start(position)).await?;
+
+        let mut reader = tokio::io::BufReader::new(log);
+        let mut checkpoints = Vec::new();
+        let mut next_position = position;
+        let mut line = String::new();
+        while checkpoints.len() < limit {
+            line.clear();
+            let read = reader.read_line(&mut line).await?;
+            // Stop at the end, or at a line that is still being written
+            if read == 0 || !line.ends_with('\n') {
+                break;
+            }
+            checkpoints.push(serde_json::from_str(&line)?);
+            next_position += read as u64;
+        }
+
+        Ok((checkpoints, next_position))
+    }
+
+    fn replication_cursor_path(&self) -> PathBuf {
+        self.storage_root.join("replication").join("cursor")
+    }
+
+    /// How far into the primary's log this secondary has replicated
+    pub async fn load_replication_cursor(&self) -> Result<u64> {
+        match self.files.read_to_string(self.replication_cursor_path()).await {
+            Ok(cursor) => Ok(cursor.trim().parse()?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub async fn save_replication_cursor(&self, position: u64) -> Result<()> {
+        self.files.write(self.replication_cursor_path(), position.to_string()).await?;
         Ok(())
     }
 
@@ -173,8 +714,8 @@ impl StorageEngine {
             .join("checkpoints")
             .join(format!("{}.json", checkpoint_id));
         
-        if checkpoint_path.exists() {
-            let json = fs::read_to_string(&checkpoint_path).await?;
+        if self.files.exists(&checkpoint_path) {
+            let json = self.files.read_to_string(&checkpoint_path).await?;
             let checkpoint: Checkpoint = serde_json::from_str(&json)?;
             
             // Cache in memory
@@ -216,6 +757,603 @@ impl StorageEngine {
         Ok(checkpoints)
     }
 
+    /// A page of a rift's checkpoints, newest first: at most `limit` of those
+    /// older than `before`. `None` if `before` isn't one of the rift's checkpoints.
+    pub async fn list_checkpoints_page(
+        &self,
+        rift_id: RiftId,
+        before: Option<CheckpointId>,
+        limit: usize,
+    ) -> Result<Option<Vec<Checkpoint>>> {
+        let index = self.checkpoint_index.read().await;
+        let mut checkpoints: Vec<&Checkpoint> = index.values().filter(|cp| cp.rift_id == rift_id).collect();
+        checkpoints.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then(b.id.cmp(&a.id)));
+
+        let start = match before {
+            Some(before) => match checkpoints.iter().position(|cp| cp.id == before) {
+                Some(position) => position + 1,
+                None => return Ok(None),
+            },
+            None => 0,
+        };
+        Ok(Some(checkpoints.into_iter().skip(start).take(limit).cloned().collect()))
+    }
+
+    /// Checkpoint count and newest checkpoint time of each of the given rifts
+    /// that has any checkpoints
+    pub async fn checkpoint_activity(&self, rift_ids: &[RiftId]) -> HashMap<RiftId, (u32, DateTime<Utc>)> {
+        let index = self.checkpoint_index.read().await;
+        let mut activity: HashMap<RiftId, (u32, DateTime<Utc>)> = HashMap::new();
+        for checkpoint in index.values().filter(|cp| rift_ids.contains(&cp.rift_id)) {
+            let entry = activity.entry(checkpoint.rift_id).or_insert((0, checkpoint.timestamp));
+            entry.0 += 1;
+            entry.1 = entry.1.max(checkpoint.timestamp);
+        }
+        activity
+    }
+
+    /// Remove a rift's auto-generated checkpoints, keeping its latest
+    /// checkpoint so the rift can still be restored. Content is shared across
+    /// rifts, so blobs are left in place. Returns the number removed.
+    pub async fn prune_auto_checkpoints(&self, rift_id: RiftId) -> Result<usize> {
+        let mut checkpoints = self.list_checkpoints(rift_id).await?;
+        checkpoints.sort_by_key(|cp| cp.timestamp);
+        checkpoints.pop(); // Always keep the latest
+
+        let mut removed = 0;
+        for checkpoint in checkpoints.into_iter().filter(|cp| cp.auto_generated) {
+            let checkpoint_path = self.storage_root
+                .join("checkpoints")
+                .join(format!("{}.json", checkpoint.id));
+            if self.files.exists(&checkpoint_path) {
+                self.files.remove_file(&checkpoint_path).await?;
+            }
+            self.checkpoint_index.write().await.remove(&checkpoint.id);
+            removed += 1;
+        }
+
+        Ok(removed)
+    }
+
+    /// Collapse each run of consecutive automatic checkpoints older than
+    /// `cutoff` into the run's latest checkpoint, which takes over the run's
+    /// parent. Manual checkpoints, merges and automatic checkpoints with a
+    /// message (like those taken before a restore) are kept and end runs.
+    pub async fn compact_auto_checkpoints(&self, rift_id: RiftId, cutoff: DateTime<Utc>) -> Result<RiftCompaction> {
+        let lock = self.checkpoint_lock(rift_id).await;
+        let _guard = lock.lock().await;
+
+        let mut checkpoints = self.list_checkpoints(rift_id).await?;
+        checkpoints.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then(a.id.cmp(&b.id)));
+        let foldable = |cp: &Checkpoint| {
+            cp.auto_generated && cp.message.is_none() && cp.merged_from.is_none() && cp.timestamp < cutoff
+        };
+
+        let mut runs = vec![Vec::new()];
+        for checkpoint in &checkpoints {
+            match runs.last_mut() {
+                Some(run) if foldable(checkpoint) => run.push(checkpoint),
+                _ => runs.push(Vec::new()),
+            }
+        }
+
+        // Folded checkpoint -> the summary replacing it
+        let mut replaced: HashMap<CheckpointId, CheckpointId> = HashMap::new();
+        let mut summaries: HashMap<CheckpointId, Checkpoint> = HashMap::new();
+        for run in runs.into_iter().filter(|run| run.len() > 1) {
+            let (last, folded) = run.split_last().expect("runs have several checkpoints");
+            let mut summary = (*last).clone();
+            summary.parent = run[0].parent;
+            summary.squashed = run.iter().map(|cp| cp.squashed + 1).sum::<u32>() - 1;
+            replaced.extend(folded.iter().map(|cp| (cp.id, summary.id)));
+            summaries.insert(summary.id, summary);
+        }
+        if summaries.is_empty() {
+            return Ok(RiftCompaction::default());
+        }
+        let compaction = RiftCompaction { summaries: summaries.len(), removed: replaced.len() };
+
+        // Point whatever followed a folded checkpoint (more than one thing in
+        // a forked history) at its summary
+        for checkpoint in checkpoints.iter().filter(|cp| !replaced.contains_key(&cp.id)) {
+            let (mut checkpoint, is_summary) = match summaries.remove(&checkpoint.id) {
+                Some(summary) => (summary, true),
+                None => (checkpoint.clone(), false),
+            };
+            let parent = checkpoint.parent.map(|parent| replaced.get(&parent).copied().unwrap_or(parent));
+            if is_summary || parent != checkpoint.parent {
+                checkpoint.parent = parent;
+                self.store_checkpoint(&checkpoint).await?;
+                self.checkpoint_index.write().await.insert(checkpoint.id, checkpoint);
+            }
+        }
+
+        for checkpoint_id in replaced.keys() {
+            let checkpoint_path = self.storage_root
+                .join("checkpoints")
+                .join(format!("{}.json", checkpoint_id));
+            if self.files.exists(&checkpoint_path) {
+                self.files.remove_file(&checkpoint_path).await?;
+            }
+            self.checkpoint_index.write().await.remove(checkpoint_id);
+        }
+
+        Ok(compaction)
+    }
+
+    /// Remove a rift's working state and every checkpoint of it. Content is
+    /// shared across rifts, so blobs are left in place. Returns the number of
+    /// checkpoints removed.
+    pub async fn remove_rift(&self, rift_id: RiftId) -> Result<usize> {
+        self.live_state.write().await.remove(&rift_id);
+        self.live_metadata.write().await.remove(&rift_id);
+        self.live_moves.write().await.remove(&rift_id);
+        self.checkpoint_locks.lock().await.remove(&rift_id);
+
+        self.remove_rift_events(rift_id).await?;
+
+        let checkpoints = self.list_checkpoints(rift_id).await?;
+        for checkpoint in &checkpoints {
+            let checkpoint_path = self.storage_root
+                .join("checkpoints")
+                .join(format!("{}.json", checkpoint.id));
+            if self.files.exists(&checkpoint_path) {
+                self.files.remove_file(&checkpoint_path).await?;
+            }
+            self.checkpoint_index.write().await.remove(&checkpoint.id);
+        }
+
+        Ok(checkpoints.len())
+    }
+
+    fn rift_event_log_path(&self, rift_id: RiftId) -> PathBuf {
+        self.storage_root.join("events").join(format!("{}.jsonl", rift_id))
+    }
+
+    /// Append events to a rift's event log, one JSON line each
+    pub async fn append_rift_events(&self, rift_id: RiftId, events: &[RiftEvent]) -> Result<()> {
+        let mut lines = String::new();
+        for event in events {
+            lines.push_str(&serde_json::to_string(event)?);
+            lines.push('\n');
+        }
+
+        let _guard = self.event_logs.lock().await;
+        self.files.append(self.rift_event_log_path(rift_id), lines.as_bytes()).await?;
+        Ok(())
+    }
+
+    /// Remove a deleted rift's event log
+    pub async fn remove_rift_events(&self, rift_id: RiftId) -> Result<()> {
+        let _guard = self.event_logs.lock().await;
+        let path = self.rift_event_log_path(rift_id);
+        if self.files.exists(&path) {
+            self.files.remove_file(path).await?;
+        }
+        Ok(())
+    }
+
+    /// Events of a rift after sequence number `after`, oldest first, at most `limit`
+    pub async fn read_rift_events(&self, rift_id: RiftId, after: u64, limit: usize) -> Result<Vec<RiftEvent>> {
+        let path = self.rift_event_log_path(rift_id);
+        if !self.files.exists(&path) {
+            return Ok(Vec::new());
+        }
+
+        let mut events = Vec::new();
+        let mut lines = tokio::io::BufReader::new(self.files.open(path).await?).lines();
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let event: RiftEvent = serde_json::from_str(&line)?;
+            if event.seq > after {
+                events.push(event);
+                if events.len() >= limit {
+                    break;
+                }
+            }
+        }
+        Ok(events)
+    }
+
+    /// Sequence number of the last event logged for each rift, read from the
+    /// end of each log
+    pub async fn rift_event_heads(&self) -> Result<HashMap<RiftId, u64>> {
+        let mut heads = HashMap::new();
+        for entry in self.files.list(self.storage_root.join("events")).await? {
+            let path = entry.path;
+            let Some(rift_id) = path.file_stem().and_then(|stem| stem.to_str()).and_then(|stem| stem.parse::<RiftId>().ok()) else {
+                continue;
+            };
+            if let Some(line) = last_line(self.files.open(&path).await?, entry.len).await? {
+                let event: RiftEvent = serde_json::from_str(&line)?;
+                heads.insert(rift_id, event.seq);
+            }
+        }
+        Ok(heads)
+    }
+
+    /// Where the chunks a project has uploaded are recorded
+    fn chunk_refs_dir(&self, project_id: ProjectId) -> PathBuf {
+        self.storage_root.join("chunk_refs").join(project_id.to_string())
+    }
+
+    /// Store an upload chunk for a project after verifying it matches its content hash
+    pub async fn store_chunk(&self, project_id: ProjectId, hash: &str, bytes: &[u8]) -> Result<()> {
+        if content_hash(bytes) != hash {
+            return Err(anyhow::anyhow!("Chunk content does not match hash {}", hash));
+        }
+
+        let chunk_path = self.storage_root.join("chunks").join(hash);
+        if !self.files.exists(&chunk_path) {
+            // Write to a temp file first so an interrupted write never leaves a corrupt chunk
+            let tmp_path = self.storage_root.join("chunks").join(format!("{}.{}.tmp", hash, Uuid::new_v4()));
+            self.files.write(&tmp_path, bytes).await?;
+            self.files.rename(&tmp_path, &chunk_path).await?;
+        }
+
+        let refs_dir = self.chunk_refs_dir(project_id);
+        self.files.create_dir_all(&refs_dir).await?;
+        self.files.write(refs_dir.join(hash), b"").await?;
+
+        Ok(())
+    }
+
+    /// Check whether a project already uploaded a chunk. Chunks stored for
+    /// other projects do not count: skipping them would let a manifest claim
+    /// content its sender never had.
+    pub fn has_chunk(&self, project_id: ProjectId, hash: &str) -> bool {
+        self.files.exists(self.chunk_refs_dir(project_id).join(hash))
+            && self.files.exists(self.storage_root.join("chunks").join(hash))
+    }
+
+    /// Reassemble a file from its chunks and verify the whole-file hash
+    pub async fn assemble_chunks(&self, manifest: &UploadFileManifest) -> Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(manifest.size as usize);
+        for hash in &manifest.chunks {
+            let chunk = self.files.read(self.storage_root.join("chunks").join(hash)).await
+                .map_err(|e| anyhow::anyhow!("Missing chunk {}: {}", hash, e))?;
+            bytes.extend_from_slice(&chunk);
+        }
+
+        if bytes.len() as u64 != manifest.size || content_hash(&bytes) != manifest.hash {
+            return Err(anyhow::anyhow!("Integrity check failed for {}", manifest.path.display()));
+        }
+
+        Ok(bytes)
+    }
+
+    /// Persist an upload session so it survives server restarts
+    pub async fn save_upload_session(&self, session: &UploadSession) -> Result<()> {
+        let session_path = self.storage_root
+            .join("uploads")
+            .join(format!("{}.json", session.id));
+
+        let json = serde_json::to_string(session)?;
+        self.files.write(&session_path, json).await?;
+
+        Ok(())
+    }
+
+    /// Load an upload session
+    pub async fn load_upload_session(&self, upload_id: Uuid) -> Result<Option<UploadSession>> {
+        let session_path = self.storage_root
+            .join("uploads")
+            .join(format!("{}.json", upload_id));
+
+        if self.files.exists(&session_path) {
+            let json = self.files.read_to_string(&session_path).await?;
+            Ok(Some(serde_json::from_str(&json)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Remove a finished or expired upload session
+    pub async fn remove_upload_session(&self, upload_id: Uuid) -> Result<()> {
+        let session_path = self.storage_root
+            .join("uploads")
+            .join(format!("{}.json", upload_id));
+
+        if self.files.exists(&session_path) {
+            self.files.remove_file(&session_path).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Directory holding a user's stash backups for one project
+    fn stash_dir(&self, user_id: UserId, project_id: ProjectId) -> PathBuf {
+        self.storage_root
+            .join("stashes")
+            .join(user_id.to_string())
+            .join(project_id.to_string())
+    }
+
+    /// Back up a stash pushed by a user
+    pub async fn save_stash(&self, user_id: UserId, project_id: ProjectId, stash: &Stash) -> Result<()> {
+        let dir = self.stash_dir(user_id, project_id);
+        self.files.create_dir_all(&dir).await?;
+
+        let json = serde_json::to_string(stash)?;
+        self.files.write(dir.join(format!("{}.json", stash.id)), json).await?;
+
+        Ok(())
+    }
+
+    /// A user's stash backups for a project, oldest first
+    pub async fn list_stashes(&self, user_id: UserId, project_id: ProjectId) -> Result<Vec<Stash>> {
+        let dir = self.stash_dir(user_id, project_id);
+        let mut stashes = Vec::new();
+        if !self.files.exists(&dir) {
+            return Ok(stashes);
+        }
+
+        for entry in self.files.list(&dir).await? {
+            let json = self.files.read_to_string(entry.path).await?;
+            stashes.push(serde_json::from_str::<Stash>(&json)?);
+        }
+        stashes.sort_by_key(|stash| stash.created_at);
+
+        Ok(stashes)
+    }
+
+    /// Remove a stash backup, returning whether it existed
+    pub async fn remove_stash(&self, user_id: UserId, project_id: ProjectId, stash_id: Uuid) -> Result<bool> {
+        let path = self.stash_dir(user_id, project_id).join(format!("{}.json", stash_id));
+        if !self.files.exists(&path) {
+            return Ok(false);
+        }
+
+        self.files.remove_file(&path).await?;
+        Ok(true)
+    }
+
+    fn secret_findings_path(&self, project_id: ProjectId) -> PathBuf {
+        self.storage_root.join("secrets").join(format!("{}.json", project_id))
+    }
+
+    /// Record secret scanning findings for a project. A finding replaces an
+    /// earlier one of the same secret in the same file; the oldest findings
+    /// are dropped beyond `MAX_SECRET_FINDINGS`.
+    pub async fn record_secret_findings(&self, project_id: ProjectId, findings: &[SecretFinding]) -> Result<()> {
+        let _guard = self.secret_findings.lock().await;
+
+        let mut recorded = self.list_secret_findings(project_id).await?;
+        recorded.retain(|old| !findings.iter().any(|new| {
+            new.rift_id == old.rift_id && new.path == old.path && new.kind == old.kind && new.redacted == old.redacted
+        }));
+        recorded.extend_from_slice(findings);
+        recorded.sort_by_key(|finding| finding.detected_at);
+        let excess = recorded.len().saturating_sub(MAX_SECRET_FINDINGS);
+        recorded.drain(..excess);
+
+        self.files.write(self.secret_findings_path(project_id), serde_json::to_string(&recorded)?).await?;
+        Ok(())
+    }
+
+    /// A project's secret scanning findings, oldest first
+    pub async fn list_secret_findings(&self, project_id: ProjectId) -> Result<Vec<SecretFinding>> {
+        let path = self.secret_findings_path(project_id);
+        if !self.files.exists(&path) {
+            return Ok(Vec::new());
+        }
+        Ok(serde_json::from_str(&self.files.read_to_string(path).await?)?)
+    }
+
+    fn quarantine_record_path(&self, id: Uuid) -> PathBuf {
+        self.storage_root.join("malware").join(format!("{}.json", id))
+    }
+
+    fn quarantined_content_path(&self, id: Uuid) -> PathBuf {
+        self.storage_root.join("malware").join(format!("{}.content", id))
+    }
+
+    /// Keep a file flagged by malware scanning. Its content stays out of the
+    /// content store, so nothing can serve it until an admin releases it.
+    pub async fn quarantine_file(&self, record: &QuarantinedFile, content: &str) -> Result<()> {
+        self.files.write(self.quarantined_content_path(record.id), content).await?;
+        self.save_quarantine_record(record).await
+    }
+
+    /// Save a quarantine record after it was reviewed
+    pub async fn save_quarantine_record(&self, record: &QuarantinedFile) -> Result<()> {
+        self.files.write(self.quarantine_record_path(record.id), serde_json::to_string(record)?).await?;
+        Ok(())
+    }
+
+    pub async fn load_quarantine_record(&self, id: Uuid) -> Result<Option<QuarantinedFile>> {
+        let path = self.quarantine_record_path(id);
+        if !self.files.exists(&path) {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&self.files.read_to_string(path).await?)?))
+    }
+
+    /// Every file malware scanning flagged, oldest first
+    pub async fn list_quarantine_records(&self) -> Result<Vec<QuarantinedFile>> {
+        let mut records = Vec::new();
+        for entry in self.files.list(self.storage_root.join("malware")).await? {
+            let path = entry.path;
+            if path.extension().is_some_and(|ext| ext == "json") {
+                records.push(serde_json::from_str::<QuarantinedFile>(&self.files.read_to_string(path).await?)?);
+            }
+        }
+        records.sort_by_key(|record| record.detected_at);
+        Ok(records)
+    }
+
+    /// Content of a quarantined file, unless it was deleted
+    pub async fn get_quarantined_content(&self, id: Uuid) -> Result<Option<String>> {
+        let path = self.quarantined_content_path(id);
+        if !self.files.exists(&path) {
+            return Ok(None);
+        }
+        Ok(Some(self.files.read_to_string(path).await?))
+    }
+
+    /// Delete the content of a quarantined file, keeping its record
+    pub async fn discard_quarantined_content(&self, id: Uuid) -> Result<()> {
+        let path = self.quarantined_content_path(id);
+        if self.files.exists(&path) {
+            self.files.remove_file(path).await?;
+        }
+        Ok(())
+    }
+
+    /// Walk every checkpoint on disk and the content store: verify each blob
+    /// matches its hash, and find blobs checkpoints need but lack, blobs
+    /// nothing needs, and checkpoints whose parent is gone
+    pub async fn verify_integrity(&self, options: IntegrityOptions) -> Result<IntegrityReport> {
+        let mut report = IntegrityReport::default();
+
+        // The files are the source of truth; the index only caches them
+        let mut checkpoints = Vec::new();
+        for entry in self.files.list(self.storage_root.join("checkpoints")).await? {
+            let path = entry.path;
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let parsed = match self.files.read_to_string(&path).await {
+                Ok(json) => serde_json::from_str::<Checkpoint>(&json).ok(),
+                Err(_) => None,
+            };
+            if let Some(checkpoint) = parsed {
+                checkpoints.push(checkpoint);
+                continue;
+            }
+
+            report.unreadable_checkpoints.push(path.clone());
+            if !options.repair {
+                continue;
+            }
+            // A copy still in memory can be written back
+            let cached = match path.file_stem().and_then(|stem| stem.to_str()).and_then(|stem| stem.parse::<CheckpointId>().ok()) {
+                Some(id) => self.checkpoint_index.read().await.get(&id).cloned(),
+                None => None,
+            };
+            match cached {
+                Some(checkpoint) => {
+                    self.files.write(&path, serde_json::to_string_pretty(&checkpoint)?).await?;
+                    report.repairs.push(format!("Rewrote checkpoint {} from memory", checkpoint.id));
+                    checkpoints.push(checkpoint);
+                }
+                None => {
+                    self.quarantine(&path).await?;
+                    report.repairs.push(format!("Quarantined unreadable checkpoint file {}", path.display()));
+                }
+            }
+        }
+        report.checkpoints_checked = checkpoints.len();
+
+        let mut referenced: HashMap<&str, Vec<CheckpointId>> = HashMap::new();
+        for checkpoint in &checkpoints {
+            for change in checkpoint.changes.iter().filter(|c| !matches!(c.change_type, ChangeType::Deleted)) {
+                referenced.entry(change.content_hash.as_str()).or_default().push(checkpoint.id);
+            }
+        }
+        // Live files can stand in for lost blobs with the same content
+        let live: HashMap<String, String> = self.live_state.read().await
+            .values()
+            .flat_map(|files| files.values())
+            .map(|content| (content_hash(content.as_bytes()), content.clone()))
+            .collect();
+
+        let content_dir = self.storage_root.join("content");
+        let grace_cutoff = std::time::SystemTime::now() - ORPHAN_GRACE_PERIOD;
+        let mut present = HashSet::new();
+        let mut valid = HashSet::new();
+        for entry in self.files.list(&content_dir).await? {
+            let path = entry.path;
+            let Some(hash) = path.file_name().and_then(|name| name.to_str()).map(str::to_string) else {
+                continue;
+            };
+            report.blobs_checked += 1;
+            present.insert(hash.clone());
+
+            if content_hash(&self.files.read(&path).await?) != hash {
+                report.corrupt_blobs.push(hash.clone());
+                if options.repair {
+                    self.quarantine(&path).await?;
+                    report.repairs.push(format!("Quarantined corrupt blob {}", hash));
+                }
+                continue;
+            }
+            valid.insert(hash.clone());
+
+            let old_enough = entry.modified.is_some_and(|modified| modified < grace_cutoff);
+            if !referenced.contains_key(hash.as_str()) && !live.contains_key(&hash) && old_enough {
+                report.orphaned_blobs.push(hash.clone());
+                if options.prune_orphans {
+                    self.files.remove_file(&path).await?;
+                    report.repairs.push(format!("Removed orphaned blob {}", hash));
+                }
+            }
+        }
+
+        for (hash, checkpoint_ids) in &referenced {
+            if !present.contains(*hash) {
+                report.missing_blobs.push(MissingBlob { hash: hash.to_string(), checkpoints: checkpoint_ids.clone() });
+            }
+            if options.repair && !valid.contains(*hash) {
+                if let Some(content) = live.get(*hash) {
+                    self.files.write(content_dir.join(hash), content).await?;
+                    report.repairs.push(format!("Restored blob {} from live files", hash));
+                }
+            }
+        }
+        report.missing_blobs.sort_by(|a, b| a.hash.cmp(&b.hash));
+
+        let ids: HashSet<CheckpointId> = checkpoints.iter().map(|c| c.id).collect();
+        for checkpoint in &checkpoints {
+            if checkpoint.parent.is_none_or(|parent| ids.contains(&parent)) {
+                continue;
+            }
+            report.dangling_parents.push(checkpoint.id);
+            if !options.repair {
+                continue;
+            }
+            // Fall back to the rift's previous checkpoint that still exists
+            let parent = checkpoints.iter()
+                .filter(|other| other.rift_id == checkpoint.rift_id && (other.timestamp, other.id) < (checkpoint.timestamp, checkpoint.id))
+                .max_by(|a, b| a.timestamp.cmp(&b.timestamp).then(a.id.cmp(&b.id)))
+                .map(|other| other.id);
+            let lock = self.checkpoint_lock(checkpoint.rift_id).await;
+            let _guard = lock.lock().await;
+            let relinked = Checkpoint { parent, ..checkpoint.clone() };
+            self.store_checkpoint(&relinked).await?;
+            self.checkpoint_index.write().await.insert(relinked.id, relinked);
+            report.repairs.push(match parent {
+                Some(parent) => format!("Relinked checkpoint {} to parent {}", checkpoint.id, parent),
+                None => format!("Made checkpoint {} the start of its rift's history", checkpoint.id),
+            });
+        }
+
+        Ok(report)
+    }
+
+    /// Move a damaged file out of the way, keeping it for inspection
+    async fn quarantine(&self, path: &Path) -> Result<()> {
+        let dir = self.storage_root.join("quarantine");
+        self.files.create_dir_all(&dir).await?;
+        let name = path.file_name().and_then(|name| name.to_str()).unwrap_or("unknown");
+        self.files.rename(path, dir.join(format!("{}-{}", Utc::now().format("%Y%m%d%H%M%S"), name))).await?;
+        Ok(())
+    }
+
+    /// Check storage can be written and read back, for readiness probes
+    pub async fn probe(&self) -> Result<()> {
+        let path = self.storage_root.join(format!(".probe-{}", Uuid::new_v4()));
+        self.files.write(&path, b"ok").await?;
+        let read = self.files.read(&path).await;
+        let _ = self.files.remove_file(&path).await;
+        if read? != b"ok" {
+            return Err(anyhow::anyhow!("Storage returned different bytes than were written"));
+        }
+        Ok(())
+    }
+
     /// Calculate storage statistics
     pub async fn get_stats(&self) -> Result<StorageStats> {
         let content_dir = self.storage_root.join("content");
@@ -234,36 +1372,25 @@ impl StorageEngine {
     }
 
     async fn count_files(&self, dir: &Path) -> Result<usize> {
-        if !dir.exists() {
+        if !self.files.exists(dir) {
             return Ok(0);
         }
         
-        let mut count = 0;
-        let mut entries = fs::read_dir(dir).await?;
-        
-        while let Some(entry) = entries.next_entry().await? {
-            if entry.file_type().await?.is_file() {
-                count += 1;
-            }
-        }
-        
-        Ok(count)
+        let entries = self.files.list(dir).await?;
+        Ok(entries.iter().filter(|entry| !entry.is_dir).count())
     }
 
     async fn calculate_dir_size(&self, dir: &Path) -> Result<u64> {
-        if !dir.exists() {
+        if !self.files.exists(dir) {
             return Ok(0);
         }
         
         let mut total_size = 0;
-        let mut entries = fs::read_dir(dir).await?;
-        
-        while let Some(entry) = entries.next_entry().await? {
-            let metadata = entry.metadata().await?;
-            if metadata.is_file() {
-                total_size += metadata.len();
-            } else if metadata.is_dir() {
-                total_size += Box::pin(self.calculate_dir_size(&entry.path())).await?;
+        for entry in self.files.list(dir).await? {
+            if entry.is_dir {
+                total_size += Box::pin(self.calculate_dir_size(&entry.path)).await?;
+            } else {
+                total_size += entry.len;
             }
         }
         
@@ -271,6 +1398,139 @@ impl StorageEngine {
     }
 }
 
+/// The last non-empty line of a file `len` bytes long, read backwards from its end
+async fn last_line(mut file: Box<dyn FileReader>, len: u64) -> Result<Option<String>> {
+    const BLOCK: u64 = 64 * 1024;
+
+    let mut tail = Vec::new();
+    let mut start = len;
+    while start > 0 {
+        let read_from = start.saturating_sub(BLOCK);
+        let mut block = vec![0; (start - read_from) as usize];
+        file.seek(std::io::SeekFrom::Start(read_from)).await?;
+        file.read_exact(&mut block).await?;
+        block.extend_from_slice(&tail);
+        tail = block;
+        start = read_from;
+
+        // A newline before the last line's content means the line is complete
+        let content_end = tail.iter().rposition(|byte| !byte.is_ascii_whitespace());
+        if let Some(end) = content_end {
+            if let Some(newline) = tail[..end].iter().rposition(|byte| *byte == b'\n') {
+                return Ok(Some(String::from_utf8_lossy(&tail[newline + 1..=end]).into_owned()));
+            }
+        }
+    }
+
+    let line = String::from_utf8_lossy(&tail).trim().to_string();
+    Ok((!line.is_empty()).then_some(line))
+}
+
+/// Where a file flagged by malware scanning stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuarantineStatus {
+    Quarantined,
+    Released,
+    Deleted,
+}
+
+/// A file malware scanning flagged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantinedFile {
+    pub id: Uuid,
+    pub project_id: ProjectId,
+    pub rift_id: RiftId,
+    pub path: PathBuf,
+    pub content_hash: String,
+    pub size: u64,
+    /// Who sent the file, when known
+    pub author: Option<UserId>,
+    /// What the scanner reported
+    pub signature: String,
+    pub detected_at: DateTime<Utc>,
+    /// Whether the file was taken out of the rift; it isn't if it had
+    /// already changed again
+    pub removed_from_rift: bool,
+    pub status: QuarantineStatus,
+    pub reviewed_by: Option<UserId>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+}
+
+/// A chunked upload in progress
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadSession {
+    pub id: Uuid,
+    pub project_id: ProjectId,
+    pub user_id: UserId,
+    pub files: Vec<UploadFileManifest>,
+    /// Files refused when the session was opened
+    #[serde(default)]
+    pub rejected: Vec<PushRuleViolation>,
+    pub created_at: chrono::DateTime<Utc>,
+}
+
+impl UploadSession {
+    /// Unique chunk hashes referenced by the session, in first-seen order
+    pub fn chunk_hashes(&self) -> Vec<String> {
+        let mut seen = std::collections::HashSet::new();
+        self.files
+            .iter()
+            .flat_map(|file| file.chunks.iter())
+            .filter(|hash| seen.insert(hash.as_str()))
+            .cloned()
+            .collect()
+    }
+}
+
+/// What `verify_integrity` fixes besides reporting
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct IntegrityOptions {
+    /// Quarantine corrupt blobs and unreadable checkpoints, restore missing
+    /// blobs from live files and relink checkpoints whose parent is gone
+    #[serde(default)]
+    pub repair: bool,
+    /// Delete blobs nothing refers to
+    #[serde(default)]
+    pub prune_orphans: bool,
+}
+
+/// Inconsistencies found in storage
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IntegrityReport {
+    pub checkpoints_checked: usize,
+    pub blobs_checked: usize,
+    /// Checkpoint files that can't be read or parsed
+    pub unreadable_checkpoints: Vec<PathBuf>,
+    /// Blobs whose content doesn't match their hash
+    pub corrupt_blobs: Vec<String>,
+    /// Blobs checkpoints refer to that aren't stored
+    pub missing_blobs: Vec<MissingBlob>,
+    /// Blobs older than an hour that nothing refers to
+    pub orphaned_blobs: Vec<String>,
+    /// Checkpoints whose parent no longer exists
+    pub dangling_parents: Vec<CheckpointId>,
+    /// What was fixed
+    pub repairs: Vec<String>,
+}
+
+impl IntegrityReport {
+    /// Whether nothing is damaged (orphaned blobs only waste space)
+    pub fn is_clean(&self) -> bool {
+        self.unreadable_checkpoints.is_empty()
+            && self.corrupt_blobs.is_empty()
+            && self.missing_blobs.is_empty()
+            && self.dangling_parents.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MissingBlob {
+    pub hash: String,
+    /// Checkpoints with a file of this content
+    pub checkpoints: Vec<CheckpointId>,
+}
+
 #[derive(Debug, Clone)]
 pub struct StorageStats {
     pub content_files: usize,
@@ -0,0 +1,199 @@
+//! Where a [`StorageEngine`](super::StorageEngine) keeps its files: in a
+//! directory on disk, or in a map in memory for tests that should not touch
+//! the filesystem. Missing files fail with `NotFound` either way, so callers
+//! handle both alike.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Cursor};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tokio::fs;
+use tokio::io::{AsyncRead, AsyncSeek, AsyncWriteExt};
+
+/// A file opened for reading
+pub(super) trait FileReader: AsyncRead + AsyncSeek + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncSeek + Unpin + Send> FileReader for T {}
+
+/// A file or directory found by [`Files::list`]
+pub(super) struct Entry {
+    pub path: PathBuf,
+    pub is_dir: bool,
+    pub len: u64,
+    pub modified: Option<SystemTime>,
+}
+
+pub(super) enum Files {
+    Disk,
+    /// Directories exist implicitly, as long as some file is below them
+    Memory(Mutex<HashMap<PathBuf, MemoryFile>>),
+}
+
+pub(super) struct MemoryFile {
+    bytes: Vec<u8>,
+    modified: SystemTime,
+}
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("{} does not exist", path.display()))
+}
+
+impl Files {
+    pub fn memory() -> Self {
+        Files::Memory(Mutex::new(HashMap::new()))
+    }
+
+    fn memory_files(files: &Mutex<HashMap<PathBuf, MemoryFile>>) -> std::sync::MutexGuard<'_, HashMap<PathBuf, MemoryFile>> {
+        files.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub async fn create_dir_all(&self, dir: impl AsRef<Path>) -> io::Result<()> {
+        let dir = dir.as_ref();
+        match self {
+            Files::Disk => fs::create_dir_all(dir).await,
+            Files::Memory(_) => Ok(()),
+        }
+    }
+
+    pub fn exists(&self, path: impl AsRef<Path>) -> bool {
+        let path = path.as_ref();
+        match self {
+            Files::Disk => path.exists(),
+            Files::Memory(files) => Self::memory_files(files).keys().any(|file| file.starts_with(path)),
+        }
+    }
+
+    pub async fn read(&self, path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+        let path = path.as_ref();
+        match self {
+            Files::Disk => fs::read(path).await,
+            Files::Memory(files) => Self::memory_files(files)
+                .get(path)
+                .map(|file| file.bytes.clone())
+                .ok_or_else(|| not_found(path)),
+        }
+    }
+
+    pub async fn read_to_string(&self, path: impl AsRef<Path>) -> io::Result<String> {
+        let path = path.as_ref();
+        match self {
+            Files::Disk => fs::read_to_string(path).await,
+            Files::Memory(_) => String::from_utf8(self.read(path).await?)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+        }
+    }
+
+    pub async fn write(&self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+        let path = path.as_ref();
+        match self {
+            Files::Disk => fs::write(path, contents).await,
+            Files::Memory(files) => {
+                let file = MemoryFile { bytes: contents.as_ref().to_vec(), modified: SystemTime::now() };
+                Self::memory_files(files).insert(path.to_path_buf(), file);
+                Ok(())
+            }
+        }
+    }
+
+    /// Add to the end of a file, creating it if needed
+    pub async fn append(&self, path: impl AsRef<Path>, contents: &[u8]) -> io::Result<()> {
+        let path = path.as_ref();
+        match self {
+            Files::Disk => {
+                let mut file = fs::OpenOptions::new().create(true).append(true).open(path).await?;
+                file.write_all(contents).await?;
+                file.flush().await
+            }
+            Files::Memory(files) => {
+                let mut files = Self::memory_files(files);
+                let file = files.entry(path.to_path_buf()).or_insert_with(|| MemoryFile { bytes: Vec::new(), modified: SystemTime::now() });
+                file.bytes.extend_from_slice(contents);
+                file.modified = SystemTime::now();
+                Ok(())
+            }
+        }
+    }
+
+    pub async fn remove_file(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        match self {
+            Files::Disk => fs::remove_file(path).await,
+            Files::Memory(files) => Self::memory_files(files).remove(path).map(|_| ()).ok_or_else(|| not_found(path)),
+        }
+    }
+
+    pub async fn rename(&self, from: impl AsRef<Path>, to: impl AsRef<Path>) -> io::Result<()> {
+        let (from, to) = (from.as_ref(), to.as_ref());
+        match self {
+            Files::Disk => fs::rename(from, to).await,
+            Files::Memory(files) => {
+                let mut files = Self::memory_files(files);
+                let file = files.remove(from).ok_or_else(|| not_found(from))?;
+                files.insert(to.to_path_buf(), file);
+                Ok(())
+            }
+        }
+    }
+
+    /// Size of a file in bytes
+    pub async fn len(&self, path: impl AsRef<Path>) -> io::Result<u64> {
+        let path = path.as_ref();
+        match self {
+            Files::Disk => Ok(fs::metadata(path).await?.len()),
+            Files::Memory(files) => Self::memory_files(files)
+                .get(path)
+                .map(|file| file.bytes.len() as u64)
+                .ok_or_else(|| not_found(path)),
+        }
+    }
+
+    /// Open a file for reading. A file in memory is read as it was when opened.
+    pub async fn open(&self, path: impl AsRef<Path>) -> io::Result<Box<dyn FileReader>> {
+        let path = path.as_ref();
+        match self {
+            Files::Disk => Ok(Box::new(fs::File::open(path).await?)),
+            Files::Memory(_) => Ok(Box::new(Cursor::new(self.read(path).await?))),
+        }
+    }
+
+    /// Files and directories directly inside a directory
+    pub async fn list(&self, dir: impl AsRef<Path>) -> io::Result<Vec<Entry>> {
+        let dir = dir.as_ref();
+        match self {
+            Files::Disk => {
+                let mut listed = Vec::new();
+                let mut entries = fs::read_dir(dir).await?;
+                while let Some(entry) = entries.next_entry().await? {
+                    let metadata = entry.metadata().await?;
+                    listed.push(Entry {
+                        path: entry.path(),
+                        is_dir: metadata.is_dir(),
+                        len: metadata.len(),
+                        modified: metadata.modified().ok(),
+                    });
+                }
+                Ok(listed)
+            }
+            Files::Memory(files) => {
+                let mut listed = BTreeMap::new();
+                for (path, file) in Self::memory_files(files).iter() {
+                    let Ok(rest) = path.strip_prefix(dir) else {
+                        continue;
+                    };
+                    let mut components = rest.components();
+                    let Some(first) = components.next() else {
+                        continue;
+                    };
+                    let path = dir.join(first);
+                    let entry = match components.next() {
+                        None => Entry { path: path.clone(), is_dir: false, len: file.bytes.len() as u64, modified: Some(file.modified) },
+                        Some(_) => Entry { path: path.clone(), is_dir: true, len: 0, modified: None },
+                    };
+                    listed.insert(path, entry);
+                }
+                Ok(listed.into_values().collect())
+            }
+        }
+    }
+}
@@ -1,18 +1,45 @@
 use anyhow::Result;
 use axum::extract::ws::{Message, WebSocket};
 use futures_util::{SinkExt, StreamExt};
-use mothership_common::protocol::{SyncMessage, FileDiffChange};
+use mothership_common::protocol::{
+    negotiate_protocol_version, protocol_mismatch_message, Conflict, InboxKind, SyncMessage, FileDiff, FileDiffChange,
+    BATCH_TOO_LARGE_ERROR, CHECKPOINT_CONFLICT_ERROR, MESSAGE_TOO_LARGE_ERROR, MIN_PROTOCOL_VERSION, NOTIFICATIONS_VERSION, OBSERVER_ERROR, PROJECT_ARCHIVED_ERROR,
+    PROTOCOL_MISMATCH_ERROR, PROTOCOL_VERSION, PUSH_RULE_ERROR, RATE_LIMITED_ERROR, READ_REPLICA_ERROR, RECONCILIATION_VERSION, RESUMABLE_SESSIONS_VERSION,
+    SECRET_FOUND_WARNING,
+};
+use mothership_common::push_rules::{describe_violations, PushRuleChecker, PushRuleViolation};
 use mothership_common::diff::DiffEngine;
+use mothership_common::features::Feature;
+use mothership_common::reconcile;
+use mothership_common::wire::{self, WireEncoding, WireFrame};
+use chrono::{DateTime, Utc};
+use mothership_common::{CheckpointConflict, ConflictPolicy, ProjectId, RiftId, SecretFinding, SecretScanMode, SparseSpec, TextCRDT, UserId};
 use serde_json;
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
 use std::time::{Duration, Instant};
-use tokio::sync::{broadcast, RwLock};
+use tokio::sync::{broadcast, mpsc, RwLock};
 use tracing::{error, info, warn, debug};
 use uuid::Uuid;
 
+use crate::chat;
+use crate::config::SyncLimitSettings;
+use crate::live_config::LiveConfig;
 use crate::database::Database;
+use crate::event_log::{self, EventLog};
+use crate::inbox;
+use crate::malware_scan::{self, MalwareScanQueue};
+use crate::notifications::{NotificationKind, Notifier};
+use crate::project_settings;
+use crate::secret_scan;
+use crate::relay::SyncRelay;
 use crate::storage::StorageEngine;
+use crate::cursors::{self, Cursors, CURSOR_CHANNEL_PREFIX};
+use crate::file_activity::{self, FileActivities, FILE_ACTIVITY_CHANNEL_PREFIX};
+use crate::presence::{self, Presence};
+use crate::features::FeatureFlags;
+use crate::terminal_share::{self, TerminalShares, TERMINAL_CHANNEL_PREFIX};
 
 /// PERFORMANCE FIX: Batching state for reducing message overhead
 #[derive(Default)]
@@ -24,30 +51,336 @@ struct BatchingState {
 const BATCH_TIMEOUT: Duration = Duration::from_millis(100); // 100ms batching window
 const MAX_BATCH_SIZE: usize = 50; // Maximum changes per batch
 
+/// CO-EDITING: Site id the server uses for edits it makes to shared documents
+/// (e.g. when a whole-file change arrives for a file being co-edited)
+const SERVER_SITE_ID: Uuid = Uuid::nil();
+
+/// RESUMPTION: Broadcasts kept per rift channel for reconnecting clients
+const REPLAY_BUFFER_SIZE: usize = 1000;
+
+/// RESUMPTION: How long after disconnecting a client can resume its session
+const RESUME_WINDOW: Duration = Duration::from_secs(600);
+
+/// RESUMPTION: Most missed broadcasts read back from a rift's event log;
+/// clients further behind get the full rift state instead
+const MAX_LOGGED_REPLAY: usize = 10_000;
+
+/// RESUMPTION: Recent broadcasts of one rift channel, by sequence number
+#[derive(Default)]
+struct ReplayBuffer {
+    last_seq: u64,
+    messages: VecDeque<(u64, SyncMessage)>,
+}
+
+/// RESUMPTION: A session a client may pick up again after reconnecting
+struct ResumableSession {
+    rift_id: RiftId,
+    user_id: UserId,
+    last_seen: Instant,
+}
+
 #[derive(Clone)]
 pub struct SyncState {
     pub db: Database,
     pub storage: Arc<StorageEngine>,
-    pub broadcaster: broadcast::Sender<(String, SyncMessage)>,
+    /// Rift broadcasts: channel, sequence number, message
+    pub broadcaster: broadcast::Sender<(String, u64, SyncMessage)>,
     pub batching_state: Arc<RwLock<BatchingState>>, // PERFORMANCE FIX: Batching support
+    /// server.config and the whitelist, as last reloaded
+    pub settings: LiveConfig,
+    /// Features administrators can turn off
+    pub features: FeatureFlags,
+    /// CO-EDITING: Shared documents of files being co-edited, by rift and path
+    pub text_sessions: Arc<RwLock<HashMap<(RiftId, PathBuf), TextCRDT>>>,
+    /// Terminals being shared into rifts
+    pub terminal_shares: TerminalShares,
+    /// Who is connected to each rift
+    pub presence: Presence,
+    /// Where collaborators' cursors are
+    pub cursors: Cursors,
+    /// Which files collaborators have open or changed
+    pub file_activity: FileActivities,
+    /// Stored files waiting for malware scanning
+    pub malware_scan: MalwareScanQueue,
+    /// Whether clients may negotiate binary (MessagePack) frames
+    pub binary_frames_enabled: bool,
+    /// Email notifications for rift events
+    pub notifier: Notifier,
+    /// Archived (read-only) projects and when they were archived
+    pub archived_projects: Arc<std::sync::RwLock<HashMap<ProjectId, DateTime<Utc>>>>,
+    /// This server is a secondary replica and accepts no changes
+    pub read_only_replica: bool,
+    /// Fan-out to other server instances, when several serve the same rifts.
+    /// Shared by every clone, so it can be attached after background tasks start.
+    pub relay: Arc<OnceLock<SyncRelay>>,
+    /// RESUMPTION: Recent broadcasts per rift channel
+    replay: Arc<std::sync::Mutex<HashMap<String, ReplayBuffer>>>,
+    /// Every numbered broadcast, on its way to its rift's event log
+    event_log: EventLog,
+    /// RESUMPTION: Sessions handed out in RiftJoined
+    resumable_sessions: Arc<std::sync::Mutex<HashMap<Uuid, ResumableSession>>>,
 }
 
 impl SyncState {
-    pub fn new(db: Database, storage: Arc<StorageEngine>) -> Self {
+    pub fn new(
+        db: Database,
+        storage: Arc<StorageEngine>,
+        settings: LiveConfig,
+        features: FeatureFlags,
+        binary_frames_enabled: bool,
+        notifier: Notifier,
+        read_only_replica: bool,
+    ) -> Self {
         let (broadcaster, _) = broadcast::channel(1000);
+        let (malware_scan, scan_jobs) = MalwareScanQueue::new();
+        let (event_log, log_operations) = EventLog::new();
+        event_log::start_writer(storage.clone(), log_operations);
         let sync_state = Self {
             db,
             storage,
             broadcaster,
             batching_state: Arc::new(RwLock::new(BatchingState::default())),
+            settings,
+            features,
+            text_sessions: Arc::new(RwLock::new(HashMap::new())),
+            terminal_shares: TerminalShares::default(),
+            presence: Presence::default(),
+            cursors: Cursors::default(),
+            file_activity: FileActivities::default(),
+            malware_scan,
+            binary_frames_enabled,
+            notifier,
+            archived_projects: Arc::new(std::sync::RwLock::new(HashMap::new())),
+            read_only_replica,
+            relay: Arc::new(OnceLock::new()),
+            replay: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            event_log,
+            resumable_sessions: Arc::new(std::sync::Mutex::new(HashMap::new())),
         };
         
         // PERFORMANCE FIX: Start background batch flusher
         Self::start_batch_flusher(sync_state.clone());
+        malware_scan::start_scanner(sync_state.clone(), scan_jobs);
         
         sync_state
     }
     
+    /// When the project was archived, if it is
+    pub fn archived_at(&self, project_id: ProjectId) -> Option<DateTime<Utc>> {
+        self.archived_projects.read().unwrap().get(&project_id).copied()
+    }
+
+    /// Record a project's archive state after it changes in the database
+    pub fn set_archived(&self, project_id: ProjectId, archived_at: Option<DateTime<Utc>>) {
+        let mut archived = self.archived_projects.write().unwrap();
+        match archived_at {
+            Some(at) => archived.insert(project_id, at),
+            None => archived.remove(&project_id),
+        };
+    }
+
+    /// Send a message to everyone in a rift channel, on this instance and,
+    /// with a relay, on every other instance
+    pub fn broadcast(&self, channel: String, message: SyncMessage) {
+        if let Some(relay) = self.relay.get() {
+            relay.publish(&channel, &message);
+        }
+        self.send_local(channel, message);
+    }
+
+    /// Push a message to every connection a user has open. These aren't
+    /// numbered or kept for replay; clients catch up from the notification store.
+    pub fn send_to_user(&self, user_id: UserId, message: SyncMessage) {
+        self.broadcast_unsequenced(user_channel(user_id), message);
+    }
+
+    /// Send a message outside any rift's sequence, on this instance and,
+    /// with a relay, on every other instance
+    pub fn broadcast_unsequenced(&self, channel: String, message: SyncMessage) {
+        if let Some(relay) = self.relay.get() {
+            relay.publish(&channel, &message);
+        }
+        let _ = self.broadcaster.send((channel, 0, message));
+    }
+
+    /// Number a broadcast, keep it for replay and hand it to this instance's clients
+    fn send_local(&self, channel: String, message: SyncMessage) {
+        // Numbered under the lock so sequence order matches channel order
+        let mut replay = self.replay.lock().unwrap();
+        let buffer = replay.entry(channel.clone()).or_default();
+        buffer.last_seq += 1;
+        let seq = buffer.last_seq;
+        buffer.messages.push_back((seq, message.clone()));
+        if buffer.messages.len() > REPLAY_BUFFER_SIZE {
+            buffer.messages.pop_front();
+        }
+        if let Some(rift_id) = event_log::rift_of_channel(&channel) {
+            self.event_log.record(rift_id, seq, message.clone());
+        }
+        let _ = self.broadcaster.send((channel, seq, message));
+    }
+
+    /// Continue each rift channel's numbering from its event log, so
+    /// sequence numbers keep increasing across restarts
+    pub async fn restore_event_sequences(&self) -> Result<()> {
+        let heads = self.storage.rift_event_heads().await?;
+        let mut replay = self.replay.lock().unwrap();
+        for (rift_id, seq) in heads {
+            replay.entry(format!("rift_{}", rift_id)).or_default().last_seq = seq;
+        }
+        Ok(())
+    }
+
+    /// Sequence number of the latest broadcast on a channel
+    fn current_seq(&self, channel: &str) -> u64 {
+        self.replay.lock().unwrap().get(channel).map_or(0, |buffer| buffer.last_seq)
+    }
+
+    /// Broadcasts on a channel after `last_seq` and the latest sequence number,
+    /// or None if some of them are no longer buffered
+    fn missed_since(&self, channel: &str, last_seq: u64) -> Option<(Vec<(u64, SyncMessage)>, u64)> {
+        let replay = self.replay.lock().unwrap();
+        let Some(buffer) = replay.get(channel) else {
+            return (last_seq == 0).then(|| (Vec::new(), 0));
+        };
+        // A sequence number from the future means a different server life
+        if last_seq > buffer.last_seq {
+            return None;
+        }
+        let oldest = buffer.messages.front().map_or(buffer.last_seq + 1, |(seq, _)| *seq);
+        if last_seq + 1 < oldest {
+            return None;
+        }
+
+        let missed = buffer.messages.iter().filter(|(seq, _)| *seq > last_seq).cloned().collect();
+        Some((missed, buffer.last_seq))
+    }
+
+    /// Like `missed_since`, reading broadcasts no longer buffered back from
+    /// the rift's event log
+    async fn missed_since_logged(&self, rift_id: RiftId, channel: &str, last_seq: u64) -> Option<(Vec<(u64, SyncMessage)>, u64)> {
+        if let Some(missed) = self.missed_since(channel, last_seq) {
+            return Some(missed);
+        }
+        let current = self.current_seq(channel);
+        if last_seq >= current || current - last_seq > MAX_LOGGED_REPLAY as u64 {
+            return None;
+        }
+
+        let logged = match self.storage.read_rift_events(rift_id, last_seq, MAX_LOGGED_REPLAY).await {
+            Ok(events) => events,
+            Err(e) => {
+                warn!("🔁 Failed to read the event log of rift {}: {}", rift_id, e);
+                return None;
+            }
+        };
+        let mut missed: Vec<(u64, SyncMessage)> = logged.into_iter().map(|event| (event.seq, event.message)).collect();
+        let logged_up_to = missed.last().map_or(last_seq, |(seq, _)| *seq);
+        let (buffered, latest) = self.missed_since(channel, logged_up_to)?;
+        missed.extend(buffered);
+
+        // Events still being written leave a gap the client can't skip
+        let contiguous = missed.iter().zip(last_seq + 1..).all(|((seq, _), expected)| *seq == expected);
+        contiguous.then_some((missed, latest))
+    }
+
+    /// Start a resumable session for a client that joined a rift
+    fn open_session(&self, rift_id: RiftId, user_id: UserId) -> Uuid {
+        let mut sessions = self.resumable_sessions.lock().unwrap();
+        sessions.retain(|_, session| session.last_seen.elapsed() < RESUME_WINDOW);
+
+        let session_id = Uuid::new_v4();
+        sessions.insert(session_id, ResumableSession { rift_id, user_id, last_seen: Instant::now() });
+        session_id
+    }
+
+    /// SECURITY CHECK: Only the user who opened a session may resume it, in the same rift
+    fn resume_session(&self, session_id: Uuid, rift_id: RiftId, user_id: UserId) -> bool {
+        let mut sessions = self.resumable_sessions.lock().unwrap();
+        match sessions.get_mut(&session_id) {
+            Some(session) if session.rift_id == rift_id
+                && session.user_id == user_id
+                && session.last_seen.elapsed() < RESUME_WINDOW => {
+                session.last_seen = Instant::now();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Start a user's resume window in a rift when they disconnect
+    fn touch_sessions(&self, rift_id: RiftId, user_id: UserId) {
+        let mut sessions = self.resumable_sessions.lock().unwrap();
+        for session in sessions.values_mut().filter(|s| s.rift_id == rift_id && s.user_id == user_id) {
+            session.last_seen = Instant::now();
+        }
+    }
+
+    /// Drop everything kept in memory for a rift that no longer exists
+    pub async fn forget_rift(&self, rift_id: RiftId) {
+        self.text_sessions.write().await.retain(|(id, _), _| *id != rift_id);
+        self.batching_state.write().await.pending_changes.remove(&rift_id.to_string());
+        self.replay.lock().unwrap().remove(&format!("rift_{}", rift_id));
+        self.event_log.remove(rift_id);
+        self.resumable_sessions.lock().unwrap().retain(|_, s| s.rift_id != rift_id);
+        self.terminal_shares.forget_rift(rift_id);
+        self.presence.forget_rift(rift_id);
+        self.cursors.forget_rift(rift_id);
+        self.file_activity.forget_rift(rift_id);
+    }
+
+    /// Deliver a message another instance broadcast, first bringing this
+    /// instance's working state in line with the change it describes
+    pub async fn deliver_relayed(&self, channel: String, message: SyncMessage) {
+        if channel.starts_with(USER_CHANNEL_PREFIX) {
+            let _ = self.broadcaster.send((channel, 0, message));
+            return;
+        }
+        if channel.starts_with(TERMINAL_CHANNEL_PREFIX) {
+            self.terminal_shares.apply_relayed(&message);
+            let _ = self.broadcaster.send((channel, 0, message));
+            return;
+        }
+        if channel.starts_with(CURSOR_CHANNEL_PREFIX) {
+            self.cursors.apply_relayed(&message);
+            let _ = self.broadcaster.send((channel, 0, message));
+            return;
+        }
+        if channel.starts_with(FILE_ACTIVITY_CHANNEL_PREFIX) {
+            self.file_activity.apply_relayed(&message);
+            let _ = self.broadcaster.send((channel, 0, message));
+            return;
+        }
+
+        match &message {
+            SyncMessage::RiftDiffUpdate { rift_id, diff_changes, .. } => {
+                for change in diff_changes {
+                    if let Err(e) = self.storage.apply_file_diff(*rift_id, &change.path, &change.diff).await {
+                        warn!("📡 Relayed change to {} in rift {} didn't apply here: {}", change.path.display(), rift_id, e);
+                    }
+                }
+            }
+            SyncMessage::TextOperations { rift_id, path, operations, .. } => {
+                let mut sessions = self.text_sessions.write().await;
+                if let Some(document) = sessions.get_mut(&(*rift_id, path.clone())) {
+                    document.apply_remote(operations);
+                    let _ = self.storage.update_live_state(*rift_id, path.clone(), document.text()).await;
+                }
+            }
+            SyncMessage::CollaboratorJoined { .. } => {
+                self.presence.apply_relayed(&message);
+            }
+            SyncMessage::CollaboratorLeft { rift_id, user_id } => {
+                self.presence.apply_relayed(&message);
+                self.cursors.forget_user(*rift_id, *user_id);
+                self.file_activity.forget_user(*rift_id, *user_id);
+            }
+            _ => {}
+        }
+
+        self.send_local(channel, message);
+    }
+
     /// PERFORMANCE FIX: Background task to flush batched changes
     fn start_batch_flusher(state: SyncState) {
         tokio::spawn(async move {
@@ -113,17 +446,129 @@ impl SyncState {
         };
         
         let channel = format!("rift_{}", rift_id);
-        let _ = state.broadcaster.send((channel.clone(), response));
+        state.broadcast(channel.clone(), response);
         
         info!("📤 Sent diff batch to rift channel: {} (compressed: {})", channel, should_compress);
         Ok(())
     }
 }
 
-pub async fn handle_websocket(socket: WebSocket, state: SyncState, rift_id: String) {
+/// LIMITS: Messages a connection may still send right now, refilled at
+/// `max_messages_per_second` up to `message_burst`
+struct MessageBudget {
+    tokens: f64,
+    refilled_at: Instant,
+    /// Whether the client was told it's being throttled since it last ran dry
+    warned: bool,
+}
+
+impl MessageBudget {
+    fn new(limits: &SyncLimitSettings) -> Self {
+        Self {
+            tokens: limits.message_burst as f64,
+            refilled_at: Instant::now(),
+            warned: false,
+        }
+    }
+
+    /// Spend one message; false if there's nothing left to spend
+    fn spend(&mut self, limits: &SyncLimitSettings) -> bool {
+        let now = Instant::now();
+        let burst = limits.message_burst.max(1) as f64;
+        let refill = now.duration_since(self.refilled_at).as_secs_f64() * limits.max_messages_per_second as f64;
+        self.tokens = (self.tokens + refill).min(burst);
+        self.refilled_at = now;
+
+        if self.tokens < 1.0 {
+            return false;
+        }
+        self.tokens -= 1.0;
+        self.warned = false;
+        true
+    }
+}
+
+/// LIMITS: Check a frame before decoding it. A refused frame comes with the
+/// Error to reply with, unless the client was already told.
+fn check_frame(frame: &Message, limits: &SyncLimitSettings, budget: &mut MessageBudget) -> Result<(), Option<SyncMessage>> {
+    let size = match frame {
+        Message::Text(text) => text.len(),
+        Message::Binary(bytes) => bytes.len(),
+        _ => 0,
+    };
+    if size > limits.max_message_bytes {
+        return Err(Some(SyncMessage::Error {
+            message: format!(
+                "Message of {} bytes is over the server's limit of {} bytes. Large or generated files (like node_modules) belong in .mothershipignore.",
+                size, limits.max_message_bytes
+            ),
+            error_code: Some(MESSAGE_TOO_LARGE_ERROR.to_string()),
+        }));
+    }
+
+    if budget.spend(limits) {
+        return Ok(());
+    }
+    // Only the first message over the budget is answered
+    if budget.warned {
+        return Err(None);
+    }
+    budget.warned = true;
+    Err(Some(SyncMessage::Error {
+        message: format!(
+            "Sending faster than the server's limit of {} messages per second; messages are dropped until you slow down",
+            limits.max_messages_per_second
+        ),
+        error_code: Some(RATE_LIMITED_ERROR.to_string()),
+    }))
+}
+
+/// LIMITS: Refuse batches naming more files than the server accepts
+fn check_batch(message: &SyncMessage, limits: &SyncLimitSettings) -> Option<SyncMessage> {
+    let files = match message {
+        SyncMessage::FilesChanged { changes, .. } => changes.len(),
+        SyncMessage::BatchDiffChanges { changes, .. } => changes.len(),
+        _ => return None,
+    };
+    (files > limits.max_batch_files).then(|| SyncMessage::Error {
+        message: format!(
+            "Batch of {} files is over the server's limit of {} files per message. Send it in smaller batches, or leave generated files out with .mothershipignore.",
+            files, limits.max_batch_files
+        ),
+        error_code: Some(BATCH_TOO_LARGE_ERROR.to_string()),
+    })
+}
+
+/// LIMITS: Log a refused message and tell the client why
+fn refuse(reply: &mpsc::UnboundedSender<SyncMessage>, username: &str, rift_id: &str, refusal: SyncMessage) {
+    if let SyncMessage::Error { message, .. } = &refusal {
+        warn!("🚧 Refused a message from {} in rift {}: {}", username, rift_id, message);
+    }
+    let _ = reply.send(refusal);
+}
+
+/// Channels addressed to one user rather than a rift
+const USER_CHANNEL_PREFIX: &str = "user_";
+
+fn user_channel(user_id: UserId) -> String {
+    format!("{}{}", USER_CHANNEL_PREFIX, user_id)
+}
+
+pub async fn handle_websocket(
+    socket: WebSocket,
+    state: SyncState,
+    rift_id: String,
+    project_id: ProjectId,
+    user_id: UserId,
+    username: String,
+    read_only: bool,
+) {
     let (sender, mut receiver) = socket.split();
     let mut broadcast_receiver = state.broadcaster.subscribe();
 
+    // Replies meant only for this client (handshake results, errors)
+    let (reply_sender, mut reply_receiver) = mpsc::unbounded_channel::<SyncMessage>();
+
     // SECURITY FIX: Define the specific rift channel this client should listen to
     let my_rift_channel = format!("rift_{}", rift_id);
     
@@ -133,17 +578,124 @@ pub async fn handle_websocket(socket: WebSocket, state: SyncState, rift_id: Stri
     let sender_task = {
         let mut sender = sender;
         let my_channel = my_rift_channel.clone();
+        let my_user_channel = user_channel(user_id);
+        let my_terminal_channel = terminal_share::terminal_channel(&rift_id);
+        let my_cursor_channel = cursors::cursor_channel(&rift_id);
+        let my_file_activity_channel = file_activity::file_activity_channel(&rift_id);
         tokio::spawn(async move {
             let mut consecutive_errors = 0;
-            while let Ok((channel, message)) = broadcast_receiver.recv().await {
-                // SECURITY FIX: Only process messages for THIS rift
-                if channel != my_channel {
+            let mut encoding = WireEncoding::Json;
+            // RESUMPTION: Whether broadcasts go out wrapped with their sequence number
+            let mut sequenced = false;
+            // NOTIFICATIONS: Whether the client understands pushed notifications
+            let mut notifications = false;
+            // TERMINAL SHARING: Whether the client asked to watch shared terminals
+            let mut watching_terminals = false;
+            // CURSORS: Whether the client asked to see collaborators' cursors
+            let mut watching_cursors = false;
+            // FILE ACTIVITY: Whether the client asked who is working on which file
+            let mut watching_file_activity = false;
+            let mut last_seq = 0;
+            // SPARSE BEAM: Paths this client asked to be limited to
+            let mut sparse = SparseSpec::default();
+            loop {
+                let (channel, seq, message) = tokio::select! {
+                    reply = reply_receiver.recv() => match reply {
+                        Some(message) => (my_channel.clone(), None, message),
+                        None => break,
+                    },
+                    broadcast = broadcast_receiver.recv() => match broadcast {
+                        // Messages for the user aren't part of the rift's sequence
+                        Ok((channel, _, message)) if channel == my_user_channel => {
+                            if !notifications {
+                                continue;
+                            }
+                            (channel, None, message)
+                        }
+                        Ok((channel, _, message)) if channel == my_terminal_channel => {
+                            if !watching_terminals {
+                                continue;
+                            }
+                            (channel, None, message)
+                        }
+                        Ok((channel, _, message)) if channel == my_cursor_channel => {
+                            // Nobody needs to see their own cursor echoed back
+                            let own = matches!(&message, SyncMessage::CursorMoved { cursor, .. } if cursor.user_id == user_id);
+                            if !watching_cursors || own {
+                                continue;
+                            }
+                            (channel, None, message)
+                        }
+                        Ok((channel, _, message)) if channel == my_file_activity_channel => {
+                            let own = matches!(&message, SyncMessage::FileActivityChanged { activity, .. } if activity.user_id == user_id);
+                            if !watching_file_activity || own {
+                                continue;
+                            }
+                            (channel, None, message)
+                        }
+                        Ok((channel, seq, message)) => (channel, Some(seq), message),
+                        Err(_) => break,
+                    },
+                };
+
+                // SECURITY FIX: Only process messages for THIS rift (or this user)
+                if channel != my_channel && channel != my_user_channel && channel != my_terminal_channel
+                    && channel != my_cursor_channel && channel != my_file_activity_channel {
                     // Silently ignore messages from other rifts
                     continue;
                 }
+
+                // RESUMPTION: Skip broadcasts the handshake already accounted for
+                if let Some(seq) = seq {
+                    if seq <= last_seq {
+                        continue;
+                    }
+                    last_seq = seq;
+                }
+
+                // SPARSE BEAM: Drop broadcasts about paths the client isn't syncing
+                let message = if seq.is_some() {
+                    match sparse.filter_message(message) {
+                        Some(message) => message,
+                        None => continue,
+                    }
+                } else {
+                    message
+                };
                 
-                let json = match serde_json::to_string(&message) {
-                    Ok(json) => json,
+                // The handshake reply is always JSON; it announces the encoding used from then on
+                let frame_encoding = match &message {
+                    SyncMessage::RiftJoined { encoding: negotiated, protocol_version, seq, sparse_paths, .. }
+                    | SyncMessage::SessionResumed { encoding: negotiated, protocol_version, seq, sparse_paths, .. } => {
+                        encoding = *negotiated;
+                        sparse = SparseSpec::new(sparse_paths);
+                        sequenced = *protocol_version >= RESUMABLE_SESSIONS_VERSION;
+                        notifications = *protocol_version >= NOTIFICATIONS_VERSION;
+                        last_seq = *seq;
+                        WireEncoding::Json
+                    }
+                    SyncMessage::TerminalShares { .. } => {
+                        watching_terminals = true;
+                        encoding
+                    }
+                    SyncMessage::Cursors { .. } => {
+                        watching_cursors = true;
+                        encoding
+                    }
+                    SyncMessage::FileActivities { .. } => {
+                        watching_file_activity = true;
+                        encoding
+                    }
+                    _ => encoding,
+                };
+                let message = match seq {
+                    Some(seq) if sequenced => SyncMessage::Sequenced { seq, message: Box::new(message) },
+                    _ => message,
+                };
+
+                let frame = match frame_encoding.encode(&message) {
+                    Ok(WireFrame::Text(json)) => Message::Text(json),
+                    Ok(WireFrame::Binary(bytes)) => Message::Binary(bytes),
                     Err(e) => {
                         error!("Failed to serialize message for channel {}: {}", channel, e);
                         consecutive_errors += 1;
@@ -155,7 +707,7 @@ pub async fn handle_websocket(socket: WebSocket, state: SyncState, rift_id: Stri
                     }
                 };
                 
-                match sender.send(Message::Text(json)).await {
+                match sender.send(frame).await {
                     Ok(_) => {
                         consecutive_errors = 0; // Reset on success
                         info!("✅ Message sent to client on channel: {}", channel);
@@ -181,10 +733,69 @@ pub async fn handle_websocket(socket: WebSocket, state: SyncState, rift_id: Stri
 
     // Handle incoming messages
     let mut consecutive_errors = 0;
+    // OBSERVERS: Set once the client joins as an observer, for the rest of the connection
+    let mut observer = false;
+    // TERMINAL SHARING: Shares this connection started, ended if it drops
+    let mut terminal_shares = Vec::new();
+    // PRESENCE: The rift this connection counts toward, once it joins to edit
+    let mut present_in = None;
+    // LIMITS: How many more messages this connection may send right now
+    let mut budget = MessageBudget::new(&state.settings.config().sync_limits);
     while let Some(msg) = receiver.next().await {
         match msg {
-            Ok(Message::Text(text)) => {
-                match handle_sync_message(&text, &state, &rift_id).await {
+            Ok(frame @ (Message::Text(_) | Message::Binary(_))) => {
+                // LIMITS: Read on every frame, so a configuration reload applies to open connections
+                let limits = state.settings.config().sync_limits.clone();
+                if let Err(refusal) = check_frame(&frame, &limits, &mut budget) {
+                    if let Some(refusal) = refusal {
+                        refuse(&reply_sender, &username, &rift_id, refusal);
+                    }
+                    continue;
+                }
+                let Some(decoded) = decode_frame(&frame) else { continue };
+                if let Some(refusal) = decoded.as_ref().ok().and_then(|message| check_batch(message, &limits)) {
+                    refuse(&reply_sender, &username, &rift_id, refusal);
+                    continue;
+                }
+                match &decoded {
+                    Ok(SyncMessage::JoinRift { observer: true, .. }) => observer = true,
+                    Ok(SyncMessage::JoinRift { rift_id: joined, .. }) if !read_only && present_in.is_none() && joined.to_string() == rift_id => {
+                        presence::join(&state, *joined, user_id, &username);
+                        present_in = Some(*joined);
+                    }
+                    Ok(SyncMessage::StartTerminalShare { share_id, .. }) => terminal_shares.push(*share_id),
+                    _ => {}
+                }
+                let handled = match decoded {
+                    Ok(message) if modifies_rift(&message) && observer => {
+                        warn!("👁️ Rejecting change from observer {} in rift {}", username, rift_id);
+                        let _ = reply_sender.send(SyncMessage::Error {
+                            message: "You joined this rift as an observer and can't change it. Beam in without --observe to edit.".to_string(),
+                            error_code: Some(OBSERVER_ERROR.to_string()),
+                        });
+                        Ok(())
+                    }
+                    Ok(message) if modifies_rift(&message) && state.read_only_replica => {
+                        warn!("🛰️ Rejecting change from {} on read-only replica", username);
+                        let _ = reply_sender.send(SyncMessage::Error {
+                            message: "This server is a read-only replica. Connect to the primary to make changes.".to_string(),
+                            error_code: Some(READ_REPLICA_ERROR.to_string()),
+                        });
+                        Ok(())
+                    }
+                    // Archived projects are read-only for everyone, including connections opened before archiving
+                    Ok(message) if modifies_rift(&message) && state.archived_at(project_id).is_some() => {
+                        warn!("🗄️ Rejecting change from {} to archived project {}", username, project_id);
+                        let _ = reply_sender.send(SyncMessage::Error {
+                            message: "This project is archived and read-only. Unarchive it to make changes.".to_string(),
+                            error_code: Some(PROJECT_ARCHIVED_ERROR.to_string()),
+                        });
+                        Ok(())
+                    }
+                    Ok(message) => handle_sync_message(message, &state, &rift_id, user_id, &username, read_only || observer, &reply_sender).await,
+                    Err(e) => Err(e),
+                };
+                match handled {
                     Ok(_) => {
                         consecutive_errors = 0; // Reset on success
                     }
@@ -230,14 +841,69 @@ pub async fn handle_websocket(socket: WebSocket, state: SyncState, rift_id: Stri
 
     info!("WebSocket connection closed for rift: {}", rift_id);
     sender_task.abort();
+
+    if let Ok(rift_id) = rift_id.parse() {
+        state.touch_sessions(rift_id, user_id);
+        for share_id in terminal_shares {
+            terminal_share::end(&state, rift_id, user_id, share_id, None);
+        }
+    }
+    if let Some(rift_id) = present_in {
+        presence::leave(&state, rift_id, user_id);
+    }
+}
+
+/// Decode a data frame. Clients may send either frame type regardless of the
+/// negotiated encoding; the frame type says which decoder to use.
+fn decode_frame(frame: &Message) -> Option<Result<SyncMessage>> {
+    match frame {
+        Message::Text(text) => Some(wire::decode_text(text)),
+        Message::Binary(bytes) => Some(wire::decode_binary(bytes)),
+        _ => None,
+    }
 }
 
-async fn handle_sync_message(message: &str, state: &SyncState, client_rift_id: &str) -> Result<()> {
-    let sync_message: SyncMessage = serde_json::from_str(message)?;
+/// Whether a client message changes rift files or history
+fn modifies_rift(message: &SyncMessage) -> bool {
+    matches!(
+        message,
+        SyncMessage::FileChanged { .. }
+            | SyncMessage::FileDiffChanged { .. }
+            | SyncMessage::FileMoved { .. }
+            | SyncMessage::BatchDiffChanges { .. }
+            | SyncMessage::FilesChanged { .. }
+            | SyncMessage::CreateCheckpoint { .. }
+            | SyncMessage::TextOperations { .. }
+            | SyncMessage::CreateConflictRift { .. }
+    )
+}
+
+pub(crate) async fn handle_sync_message(
+    sync_message: SyncMessage,
+    state: &SyncState,
+    client_rift_id: &str,
+    user_id: UserId,
+    username: &str,
+    read_only: bool,
+    reply: &mpsc::UnboundedSender<SyncMessage>,
+) -> Result<()> {
+    // SECURITY CHECK: Observers receive updates but may not change the rift
+    if read_only && modifies_rift(&sync_message) {
+        warn!("👁️ Ignoring change from read-only observer {} in rift {}", username, client_rift_id);
+        return Ok(());
+    }
     
     match sync_message {
-        SyncMessage::JoinRift { rift_id: msg_rift_id, last_checkpoint } => {
-            info!("Client joining rift: {} (last checkpoint: {:?})", msg_rift_id, last_checkpoint);
+        SyncMessage::JoinRift { rift_id: msg_rift_id, last_checkpoint, protocol_version, min_protocol_version, encodings, resume, sparse_paths, manifest, observer } => {
+            info!("Client joining rift: {} (last checkpoint: {:?}, protocol {}-{})",
+                msg_rift_id, last_checkpoint, min_protocol_version, protocol_version);
+            if observer {
+                info!("👁️ {} is observing rift {}", username, msg_rift_id);
+            }
+            let sparse = SparseSpec::new(&sparse_paths);
+            if !sparse.is_full() {
+                info!("🔭 {} is syncing only {:?}", username, sparse.paths);
+            }
             
             // SECURITY CHECK: Verify client is authorized for this rift
             let msg_rift_id_str = msg_rift_id.to_string();
@@ -245,10 +911,56 @@ async fn handle_sync_message(message: &str, state: &SyncState, client_rift_id: &
                 error!("🚨 SECURITY: Client attempted to join unauthorized rift {} (authorized: {})", msg_rift_id_str, client_rift_id);
                 return Err(anyhow::anyhow!("Unauthorized rift access attempt"));
             }
+
+            // Settle on the highest protocol version both sides speak
+            let Some(negotiated_version) = negotiate_protocol_version(min_protocol_version, protocol_version) else {
+                let message = protocol_mismatch_message(min_protocol_version, protocol_version, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION);
+                warn!("⛔ Rejecting {} from rift {}: {}", username, msg_rift_id, message);
+                let _ = reply.send(SyncMessage::Error {
+                    message,
+                    error_code: Some(PROTOCOL_MISMATCH_ERROR.to_string()),
+                });
+                return Ok(());
+            };
+            let encoding = WireEncoding::negotiate(&encodings, negotiated_version, state.binary_frames_enabled);
+            let channel = format!("rift_{}", msg_rift_id);
+
+            // RESUMPTION: A client that was only briefly away gets just the broadcasts it missed
+            if let Some(resume) = resume.filter(|_| negotiated_version >= RESUMABLE_SESSIONS_VERSION) {
+                let missed = if state.resume_session(resume.session_id, msg_rift_id, user_id) {
+                    state.missed_since_logged(msg_rift_id, &channel, resume.last_seq).await
+                } else {
+                    None
+                };
+                match missed {
+                    Some((missed, seq)) => {
+                        info!("🔁 {} resumed session {} in rift {} ({} missed messages)",
+                            username, resume.session_id, msg_rift_id, missed.len());
+                        let _ = reply.send(SyncMessage::SessionResumed {
+                            rift_id: msg_rift_id,
+                            session_id: resume.session_id,
+                            missed: missed.into_iter()
+                                .filter_map(|(seq, message)| sparse.filter_message(message).map(|message| (seq, message)))
+                                .map(|(seq, message)| SyncMessage::Sequenced { seq, message: Box::new(message) })
+                                .collect(),
+                            seq,
+                            protocol_version: negotiated_version,
+                            encoding,
+                            sparse_paths: sparse.paths,
+                        });
+                        return Ok(());
+                    }
+                    None => info!("🔁 Session {} of {} can't be resumed; sending full rift state", resume.session_id, username),
+                }
+            }
+
+            // Anything broadcast after this point reaches the client live
+            let seq = state.current_seq(&channel);
             
             // Get current live state for the rift
-            let live_files = match state.storage.get_live_state(msg_rift_id).await {
-                Ok(files) => {
+            let mut live_files = match state.storage.get_live_state(msg_rift_id).await {
+                Ok(mut files) => {
+                    files.retain(|path, _| sparse.includes(path));
                     info!("✅ Retrieved {} files from rift {}", files.len(), msg_rift_id);
                     files
                 }
@@ -258,14 +970,42 @@ async fn handle_sync_message(message: &str, state: &SyncState, client_rift_id: &
                 }
             };
 
+            // RECONCILIATION: A client that says what it has only gets what changed on our side.
+            // Observers can't upload anything, so they always get the full state.
+            let mut conflicts = Vec::new();
+            let reconcile = match manifest.filter(|_| negotiated_version >= RECONCILIATION_VERSION && !observer) {
+                Some(mut manifest) => {
+                    manifest.retain(|path, _| sparse.includes(path));
+                    let plan = reconcile::plan(&manifest, &live_files);
+                    info!("🔄 Reconciling {} with rift {}: {} to upload, {} to download, {} to delete, {} conflicts",
+                        username, msg_rift_id, plan.upload.len(), plan.download.len(), plan.delete.len(), plan.conflicts.len());
+                    conflicts = plan.conflicts.iter()
+                        .filter_map(|path| live_files.get(path).map(|content| (path.clone(), content.clone())))
+                        .collect();
+                    live_files.retain(|path, _| plan.download.contains(path));
+                    Some(plan)
+                }
+                None => None,
+            };
+
+            let mut file_metadata = state.storage.get_live_metadata(msg_rift_id).await;
+            file_metadata.retain(|path, _| live_files.contains_key(path));
+
             // CRITICAL FIX: Add delay before sending RiftJoined to ensure connection is stable
             tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
 
             let response = SyncMessage::RiftJoined {
                 rift_id: msg_rift_id,
                 current_files: live_files,
-                participants: vec![], // TODO: Get actual participants
+                participants: state.presence.in_rift(msg_rift_id),
                 last_checkpoint,
+                protocol_version: negotiated_version,
+                encoding,
+                session_id: (negotiated_version >= RESUMABLE_SESSIONS_VERSION).then(|| state.open_session(msg_rift_id, user_id)),
+                seq,
+                sparse_paths: sparse.paths,
+                file_metadata,
+                reconcile,
             };
             
             // Test serialization before sending
@@ -274,13 +1014,12 @@ async fn handle_sync_message(message: &str, state: &SyncState, client_rift_id: &
                     info!("✅ RiftJoined message serialized successfully ({} bytes)", json.len());
                     
                     // Send only to the joining client (not broadcast to all)
-                    let channel = format!("rift_{}", msg_rift_id);
-                    match state.broadcaster.send((channel.clone(), response)) {
+                    match reply.send(response) {
                         Ok(_) => {
-                            info!("✅ RiftJoined message sent to channel: {}", channel);
+                            info!("✅ RiftJoined message sent to {} (protocol {})", username, negotiated_version);
                         }
                         Err(e) => {
-                            error!("❌ Failed to send RiftJoined message to channel {}: {}", channel, e);
+                            error!("❌ Failed to send RiftJoined message to {}: {}", username, e);
                         }
                     }
                 }
@@ -289,9 +1028,19 @@ async fn handle_sync_message(message: &str, state: &SyncState, client_rift_id: &
                     return Err(anyhow::anyhow!("Serialization failed: {}", e));
                 }
             }
+
+            // Files changed on both sides are settled with the project's conflict policy
+            if !conflicts.is_empty() {
+                let policy = rift_conflict_policy(state, msg_rift_id).await?;
+                for (path, server_content) in conflicts {
+                    // We only know the hash of the client's copy
+                    let client_diff = FileDiff::FullContent(String::new());
+                    let _ = reply.send(conflict_detected(msg_rift_id, user_id, path, server_content, client_diff, policy));
+                }
+            }
         }
 
-        SyncMessage::FileChanged { rift_id: msg_rift_id, path, content, timestamp: _ } => {
+        SyncMessage::FileChanged { rift_id: msg_rift_id, path, content, timestamp: _, metadata } => {
             // SECURITY CHECK: Verify client is authorized for this rift
             let msg_rift_id_str = msg_rift_id.to_string();
             if msg_rift_id_str != client_rift_id {
@@ -300,6 +1049,12 @@ async fn handle_sync_message(message: &str, state: &SyncState, client_rift_id: &
             }
             
             info!("📝 File changed in rift {}: {} ({} bytes)", msg_rift_id, path.display(), content.len());
+
+            if let Some(policy) = IngestPolicy::load(state, msg_rift_id).await? {
+                if !policy.admit_file(state, reply, username, msg_rift_id, user_id, IncomingFile { path: &path, size: content.len() as u64, content: Some(&content) }).await {
+                    return Ok(());
+                }
+            }
             
             // PERFORMANCE FIX: Get original content to generate diff
             let original_content = match state.storage.get_file_content(msg_rift_id, &path).await {
@@ -309,6 +1064,8 @@ async fn handle_sync_message(message: &str, state: &SyncState, client_rift_id: &
             
             // Update live working state
             state.storage.update_live_state(msg_rift_id, path.clone(), content.clone()).await?;
+            state.storage.update_file_metadata(msg_rift_id, &path, &metadata).await;
+            malware_scan::queue(state, msg_rift_id, &path, &content, Some(user_id));
             
             // PERFORMANCE FIX: Generate diff instead of sending full content
             let diff_engine = DiffEngine::new();
@@ -317,6 +1074,7 @@ async fn handle_sync_message(message: &str, state: &SyncState, client_rift_id: &
                 path: path.clone(),
                 diff,
                 file_size: content.len() as u64,
+                metadata,
             };
             
             info!("📊 Generated diff for {}: original {} bytes -> new {} bytes", 
@@ -324,6 +1082,7 @@ async fn handle_sync_message(message: &str, state: &SyncState, client_rift_id: &
             
             // PERFORMANCE FIX: Add to batch instead of immediate broadcast
             handle_diff_change_batched(state, msg_rift_id, diff_change).await?;
+            sync_text_session(state, msg_rift_id, &path, &content).await;
             
             // TODO: Implement smart checkpointing
             // Check if we should create automatic checkpoint (every N changes or time-based)
@@ -333,7 +1092,7 @@ async fn handle_sync_message(message: &str, state: &SyncState, client_rift_id: &
             // }
         }
 
-        SyncMessage::FileDiffChanged { rift_id: msg_rift_id, path, diff, file_size, timestamp: _ } => {
+        SyncMessage::FileDiffChanged { rift_id: msg_rift_id, path, diff, file_size, timestamp: _, metadata } => {
             // SECURITY CHECK: Verify client is authorized for this rift
             let msg_rift_id_str = msg_rift_id.to_string();
             if msg_rift_id_str != client_rift_id {
@@ -342,26 +1101,76 @@ async fn handle_sync_message(message: &str, state: &SyncState, client_rift_id: &
             }
             
             info!("📝 Diff change in rift {}: {} ({} bytes)", msg_rift_id, path.display(), file_size);
+
+            let policy = IngestPolicy::load(state, msg_rift_id).await?;
+            if let Some(policy) = &policy {
+                let size = claimed_size(file_size, &diff);
+                let preview = policy.needs_content(state, msg_rift_id, &path, size, &diff).await;
+                if !policy.admit_file(state, reply, username, msg_rift_id, user_id, IncomingFile { path: &path, size, content: preview.as_deref() }).await {
+                    return Ok(());
+                }
+            }
             
-            // PERFORMANCE FIX: Apply diff to get new content
-            let original_content = match state.storage.get_file_content(msg_rift_id, &path).await {
+            // PERFORMANCE FIX: Reconstruct new content from the diff in the storage engine
+            let content = match state.storage.apply_file_diff(msg_rift_id, &path, &diff).await {
                 Ok(content) => content,
-                Err(_) => String::new(), // New file
+                Err(e) => return resolve_diff_conflict(state, reply, msg_rift_id, user_id, path, diff, e).await,
             };
-            
-            let diff_engine = DiffEngine::new();
-            let new_content = diff_engine.apply_diff(&original_content, &diff)?;
-            
-            // Update live working state
-            state.storage.update_live_state(msg_rift_id, path.clone(), new_content).await?;
+            if let Some(policy) = &policy {
+                policy.warn_about_secrets(state, reply, msg_rift_id, user_id, &path, &content).await;
+            }
+            malware_scan::queue(state, msg_rift_id, &path, &content, Some(user_id));
+            sync_text_session(state, msg_rift_id, &path, &content).await;
+            state.storage.update_file_metadata(msg_rift_id, &path, &metadata).await;
             
             // PERFORMANCE FIX: Batch the diff change
-            let diff_change = FileDiffChange { path, diff, file_size };
+            let diff_change = FileDiffChange { path, diff, file_size, metadata };
             handle_diff_change_batched(state, msg_rift_id, diff_change).await?;
             
             info!("✅ Applied diff successfully: {} bytes", file_size);
         }
 
+        SyncMessage::FileMoved { rift_id: msg_rift_id, from, to, timestamp: _ } => {
+            // SECURITY CHECK: Verify client is authorized for this rift
+            let msg_rift_id_str = msg_rift_id.to_string();
+            if msg_rift_id_str != client_rift_id {
+                error!("🚨 SECURITY: Client attempted to move a file in unauthorized rift {} (authorized: {})", msg_rift_id_str, client_rift_id);
+                return Err(anyhow::anyhow!("Unauthorized rift modification attempt"));
+            }
+
+            info!("🚚 File moved in rift {}: {} -> {}", msg_rift_id, from.display(), to.display());
+
+            let Ok(content) = state.storage.get_file_content(msg_rift_id, &from).await else {
+                warn!("⚠️ Ignoring move of {} which isn't in rift {}", from.display(), msg_rift_id);
+                return Ok(());
+            };
+            if let Some(policy) = IngestPolicy::load(state, msg_rift_id).await? {
+                // The content is unchanged, so only the new path needs checking
+                if !policy.admit_file(state, reply, username, msg_rift_id, user_id, IncomingFile { path: &to, size: content.len() as u64, content: None }).await {
+                    return Ok(());
+                }
+            }
+            if state.storage.move_live_file(msg_rift_id, &from, &to).await.is_none() {
+                return Ok(());
+            }
+
+            // Collaborators see the move as the old path's deletion and the new path's content
+            let deletion = FileDiffChange {
+                path: from,
+                diff: FileDiff::Deleted,
+                file_size: 0,
+                metadata: Default::default(),
+            };
+            let creation = FileDiffChange {
+                metadata: state.storage.get_file_metadata(msg_rift_id, &to).await,
+                file_size: content.len() as u64,
+                diff: FileDiff::FullContent(content),
+                path: to,
+            };
+            handle_diff_change_batched(state, msg_rift_id, deletion).await?;
+            handle_diff_change_batched(state, msg_rift_id, creation).await?;
+        }
+
         SyncMessage::BatchDiffChanges { rift_id: msg_rift_id, changes, timestamp: _, compressed } => {
             // SECURITY CHECK: Verify client is authorized for this rift
             let msg_rift_id_str = msg_rift_id.to_string();
@@ -373,24 +1182,57 @@ async fn handle_sync_message(message: &str, state: &SyncState, client_rift_id: &
             info!("📦 Batch diff changes in rift {}: {} changes (compressed: {})", 
                 msg_rift_id, changes.len(), compressed);
             
-            // Clone changes before processing to avoid move issues
-            let changes_for_response = changes.clone();
-            
-            // Process each change in the batch
+            // Process each change in the batch; conflicting ones are settled
+            // with the sender and not forwarded, and ones breaking push rules are dropped
+            let policy = IngestPolicy::load(state, msg_rift_id).await?;
+            let mut changes_for_response = Vec::with_capacity(changes.len());
             for change in changes {
-                // Apply diff to get new content
-                let original_content = match state.storage.get_file_content(msg_rift_id, &change.path).await {
-                    Ok(content) => content,
-                    Err(_) => String::new(), // New file
-                };
-                
-                let diff_engine = DiffEngine::new();
-                let new_content = diff_engine.apply_diff(&original_content, &change.diff)?;
-                
-                // Update live working state
-                state.storage.update_live_state(msg_rift_id, change.path.clone(), new_content).await?;
+                if let Some(policy) = &policy {
+                    let size = claimed_size(change.file_size, &change.diff);
+                    let preview = policy.needs_content(state, msg_rift_id, &change.path, size, &change.diff).await;
+                    if !policy.admit_file(state, reply, username, msg_rift_id, user_id, IncomingFile { path: &change.path, size, content: preview.as_deref() }).await {
+                        continue;
+                    }
+                }
+
+                // Apply diff to the live working state
+                match state.storage.apply_file_diff(msg_rift_id, &change.path, &change.diff).await {
+                    Ok(content) => {
+                        if let Some(policy) = &policy {
+                            policy.warn_about_secrets(state, reply, msg_rift_id, user_id, &change.path, &content).await;
+                        }
+                        malware_scan::queue(state, msg_rift_id, &change.path, &content, Some(user_id));
+                        sync_text_session(state, msg_rift_id, &change.path, &content).await;
+                        state.storage.update_file_metadata(msg_rift_id, &change.path, &change.metadata).await;
+                        changes_for_response.push(change);
+                    }
+                    Err(e) => {
+                        // Only this change is refused; the rest of the batch still goes out
+                        let path = change.path.clone();
+                        if let Err(e) = resolve_diff_conflict(state, reply, msg_rift_id, user_id, change.path, change.diff, e).await {
+                            warn!("⚠️ Dropped change to {} from {}'s batch in rift {}: {}", path.display(), username, msg_rift_id, e);
+                            let _ = reply.send(SyncMessage::Error {
+                                message: format!("Change to {} didn't apply: {}", path.display(), e),
+                                error_code: None,
+                            });
+                        }
+                    }
+                }
             }
-            
+            if changes_for_response.is_empty() {
+                return Ok(());
+            }
+
+            // ORDERING: Changes still waiting in the rift's batch were applied
+            // before these, so they go out first in the same update
+            let mut pending = {
+                let mut batching = state.batching_state.write().await;
+                batching.last_batch_time.remove(client_rift_id);
+                batching.pending_changes.remove(client_rift_id).unwrap_or_default()
+            };
+            pending.extend(changes_for_response);
+            let changes_for_response = pending;
+
             // PERFORMANCE FIX: Forward the batch to other collaborators
             let response = SyncMessage::RiftDiffUpdate {
                 rift_id: msg_rift_id,
@@ -401,7 +1243,7 @@ async fn handle_sync_message(message: &str, state: &SyncState, client_rift_id: &
             };
             
             let channel = format!("rift_{}", msg_rift_id);
-            let _ = state.broadcaster.send((channel.clone(), response));
+            state.broadcast(channel.clone(), response);
             
             info!("📤 Forwarded diff batch to rift channel: {}", channel);
         }
@@ -415,14 +1257,36 @@ async fn handle_sync_message(message: &str, state: &SyncState, client_rift_id: &
             }
             
             info!("📸 Checkpoint requested for rift: {} (message: {:?})", msg_rift_id, message);
+
+            if let Some(rift) = state.db.get_rift(msg_rift_id).await? {
+                let review = project_settings::review_checkpoint(&state.db, &state.storage, rift.project_id, msg_rift_id, user_id, message.as_deref()).await?;
+                if !review.violations.is_empty() {
+                    reject_push(reply, username, &review.violations);
+                    return Ok(());
+                }
+                if !review.secrets.is_empty() {
+                    warn_secrets(reply, &review.secrets);
+                }
+            }
             
             // Create actual checkpoint using storage engine
-            let checkpoint = state.storage.create_checkpoint(
+            let checkpoint = match state.storage.create_checkpoint(
                 msg_rift_id,
                 Uuid::new_v4(), // TODO: Get actual user ID from session
                 message.clone(),
                 false, // Manual checkpoint
-            ).await?;
+            ).await {
+                Ok(checkpoint) => checkpoint,
+                Err(e) if e.is::<CheckpointConflict>() => {
+                    warn!("⚠️ Refused checkpoint from {}: {}", username, e);
+                    let _ = reply.send(SyncMessage::Error {
+                        message: format!("Checkpoint refused: {}", e),
+                        error_code: Some(CHECKPOINT_CONFLICT_ERROR.to_string()),
+                    });
+                    return Ok(());
+                }
+                Err(e) => return Err(e),
+            };
             
             let response = SyncMessage::CheckpointCreated {
                 rift_id: msg_rift_id,
@@ -433,7 +1297,249 @@ async fn handle_sync_message(message: &str, state: &SyncState, client_rift_id: &
             };
             
             let channel = format!("rift_{}", msg_rift_id);
-            let _ = state.broadcaster.send((channel, response));
+            state.broadcast(channel, response);
+            file_activity::checkpointed(state, msg_rift_id, user_id);
+        }
+
+        SyncMessage::JoinTextSession { rift_id: msg_rift_id, path } => {
+            // SECURITY CHECK: Verify client is authorized for this rift
+            let msg_rift_id_str = msg_rift_id.to_string();
+            if msg_rift_id_str != client_rift_id {
+                error!("🚨 SECURITY: Client attempted to co-edit in unauthorized rift {} (authorized: {})", msg_rift_id_str, client_rift_id);
+                return Err(anyhow::anyhow!("Unauthorized co-editing attempt"));
+            }
+
+            // CO-EDITING: Start the shared document from the live content the first time
+            let live_content = state.storage.get_file_content(msg_rift_id, &path).await.unwrap_or_default();
+            let mut sessions = state.text_sessions.write().await;
+            let document = sessions
+                .entry((msg_rift_id, path.clone()))
+                .or_insert_with(|| TextCRDT::from_text(SERVER_SITE_ID, &live_content))
+                .clone();
+
+            info!("✍️ {} joined co-editing of {} in rift {}", username, path.display(), msg_rift_id);
+
+            // Sent while holding the lock so no operation can slip between snapshot and relay
+            let response = SyncMessage::TextSnapshot { rift_id: msg_rift_id, path, document };
+            state.broadcast(format!("rift_{}", msg_rift_id), response);
+        }
+
+        SyncMessage::TextOperations { rift_id: msg_rift_id, path, site_id, operations } => {
+            // SECURITY CHECK: Verify client is authorized for this rift
+            let msg_rift_id_str = msg_rift_id.to_string();
+            if msg_rift_id_str != client_rift_id {
+                error!("🚨 SECURITY: Client attempted to co-edit in unauthorized rift {} (authorized: {})", msg_rift_id_str, client_rift_id);
+                return Err(anyhow::anyhow!("Unauthorized co-editing attempt"));
+            }
+
+            let mut sessions = state.text_sessions.write().await;
+            let Some(document) = sessions.get_mut(&(msg_rift_id, path.clone())) else {
+                warn!("⚠️ Text operations for {} without a co-editing session", path.display());
+                return Ok(());
+            };
+
+            // CO-EDITING: Merge into the shared document and relay to every replica
+            let original_content = document.text();
+            document.apply_remote(&operations);
+            let content = document.text();
+
+            let response = SyncMessage::TextOperations { rift_id: msg_rift_id, path: path.clone(), site_id, operations };
+            state.broadcast(format!("rift_{}", msg_rift_id), response);
+
+            if content != original_content {
+                // Written under the session lock so live state follows the document's order
+                state.storage.update_live_state(msg_rift_id, path.clone(), content.clone()).await?;
+                drop(sessions);
+
+                // Clients syncing whole files (the daemon) get the result as a regular diff
+                let diff_change = FileDiffChange {
+                    metadata: state.storage.get_file_metadata(msg_rift_id, &path).await,
+                    diff: DiffEngine::new().generate_line_diff(&original_content, &content),
+                    file_size: content.len() as u64,
+                    path,
+                };
+                handle_diff_change_batched(state, msg_rift_id, diff_change).await?;
+            }
+        }
+
+        SyncMessage::SendChatMessage { rift_id: msg_rift_id, content } => {
+            // SECURITY CHECK: Verify client is authorized for this rift
+            let msg_rift_id_str = msg_rift_id.to_string();
+            if msg_rift_id_str != client_rift_id {
+                error!("🚨 SECURITY: Client attempted to chat in unauthorized rift {} (authorized: {})", msg_rift_id_str, client_rift_id);
+                return Err(anyhow::anyhow!("Unauthorized chat attempt"));
+            }
+
+            // The sender only sees its message once it is broadcast back
+            if let Err(e) = chat::post_message(state, msg_rift_id, user_id, username.to_string(), &content).await {
+                warn!("💬 Rejected chat message from {} in rift {}: {}", username, msg_rift_id, e);
+            }
+        }
+
+        SyncMessage::CreateConflictRift { original_rift_id, conflict_rift_name, conflicting_files, author: _, timestamp: _ } => {
+            // SECURITY CHECK: Conflict rifts can only be split off the client's own rift
+            if original_rift_id.to_string() != client_rift_id {
+                error!("🚨 SECURITY: Client attempted to create a conflict rift from unauthorized rift {} (authorized: {})", original_rift_id, client_rift_id);
+                return Err(anyhow::anyhow!("Unauthorized conflict rift creation attempt"));
+            }
+
+            let original = state.db.get_rift(original_rift_id).await?
+                .ok_or_else(|| anyhow::anyhow!("Rift {} not found", original_rift_id))?;
+            let conflict_rift = state.db.create_rift(original.project_id, user_id, Some(conflict_rift_name)).await?;
+            state.db.set_rift_parent(conflict_rift.id, Some(original.id)).await?;
+
+            // The fork point holds the original's side of each conflicting file, so
+            // merging the conflict rift back brings only the conflicting changes
+            let original_files = state.storage.get_live_state(original.id).await?;
+            for file in &conflicting_files {
+                if let Some(content) = original_files.get(&file.path) {
+                    state.storage.update_live_state(conflict_rift.id, file.path.clone(), content.clone()).await?;
+                }
+            }
+            let message = format!("Split from rift '{}'", original.name);
+            let fork_point = state.storage.create_checkpoint(conflict_rift.id, user_id, Some(message), false).await?;
+            state.db.set_rift_fork_point(conflict_rift.id, fork_point.id).await?;
+
+            for file in &conflicting_files {
+                state.storage.update_live_state(conflict_rift.id, file.path.clone(), file.content.clone()).await?;
+            }
+            info!("🔀 {} created conflict rift '{}' from rift {} with {} files", username, conflict_rift.name, original.id, conflicting_files.len());
+
+            let _ = reply.send(SyncMessage::ConflictRiftCreated {
+                original_rift_id,
+                new_rift_id: conflict_rift.id,
+                conflict_rift_name: conflict_rift.name.clone(),
+            });
+
+            // The conflict is against the changes of everyone working in the original rift
+            let paths: Vec<String> = conflicting_files.iter().map(|f| f.path.display().to_string()).collect();
+            state.notifier.notify_all(
+                original.collaborators.iter().copied(),
+                user_id,
+                NotificationKind::ConflictRift,
+                &format!("Conflict rift '{}' created from '{}'", conflict_rift.name, original.name),
+                &format!(
+                    "{} created the conflict rift '{}' because their changes conflict with rift '{}'.\n\nConflicting files:\n  {}",
+                    username,
+                    conflict_rift.name,
+                    original.name,
+                    paths.join("\n  ")
+                ),
+            );
+            inbox::deliver_all(
+                state,
+                original.collaborators.iter().copied(),
+                user_id,
+                InboxKind::ConflictRift,
+                Some(original.project_id),
+                &format!("Conflict rift '{}' created from '{}'", conflict_rift.name, original.name),
+                &format!("Conflicting files: {}", paths.join(", ")),
+            );
+        }
+
+        SyncMessage::StartTerminalShare { rift_id: msg_rift_id, share_id, command, cols, rows } => {
+            // SECURITY CHECK: Terminals can only be shared into the client's own rift
+            if msg_rift_id.to_string() != client_rift_id {
+                error!("🚨 SECURITY: Client attempted to share a terminal into unauthorized rift {} (authorized: {})", msg_rift_id, client_rift_id);
+                return Err(anyhow::anyhow!("Unauthorized terminal share attempt"));
+            }
+            if read_only {
+                let _ = reply.send(SyncMessage::Error {
+                    message: "Observers can watch shared terminals but not share their own.".to_string(),
+                    error_code: Some(OBSERVER_ERROR.to_string()),
+                });
+                return Ok(());
+            }
+            if !state.features.is_enabled_in_rift(Feature::TerminalSharing, msg_rift_id).await {
+                let _ = reply.send(SyncMessage::Error {
+                    message: "Terminal sharing is turned off for this project.".to_string(),
+                    error_code: None,
+                });
+                return Ok(());
+            }
+
+            match terminal_share::start(state, msg_rift_id, user_id, username, share_id, &command, cols, rows) {
+                Ok(share) => {
+                    let _ = reply.send(SyncMessage::TerminalShareStarted { rift_id: msg_rift_id, share });
+                }
+                Err(message) => {
+                    warn!("🖥️ Rejected terminal share from {} in rift {}: {}", username, msg_rift_id, message);
+                    let _ = reply.send(SyncMessage::Error { message, error_code: None });
+                }
+            }
+        }
+
+        SyncMessage::TerminalOutput { rift_id: msg_rift_id, share_id, data } => {
+            // Output that doesn't belong to one of the client's shares is dropped
+            if let Err(message) = terminal_share::output(state, msg_rift_id, user_id, share_id, data) {
+                debug!("🖥️ Dropped terminal output from {}: {}", username, message);
+            }
+        }
+
+        SyncMessage::EndTerminalShare { rift_id: msg_rift_id, share_id, exit_code } => {
+            terminal_share::end(state, msg_rift_id, user_id, share_id, exit_code);
+        }
+
+        SyncMessage::WatchTerminalShares { rift_id: msg_rift_id } => {
+            // SECURITY CHECK: Only the terminals of the client's own rift
+            if msg_rift_id.to_string() != client_rift_id {
+                error!("🚨 SECURITY: Client attempted to watch terminals of unauthorized rift {} (authorized: {})", msg_rift_id, client_rift_id);
+                return Err(anyhow::anyhow!("Unauthorized terminal watch attempt"));
+            }
+            terminal_share::watch(state, msg_rift_id, reply);
+        }
+
+        SyncMessage::UpdateCursor { rift_id: msg_rift_id, path, line, column } => {
+            // SECURITY CHECK: Cursors can only be placed in the client's own rift
+            if msg_rift_id.to_string() != client_rift_id {
+                error!("🚨 SECURITY: Client attempted to move a cursor in unauthorized rift {} (authorized: {})", msg_rift_id, client_rift_id);
+                return Err(anyhow::anyhow!("Unauthorized cursor update attempt"));
+            }
+            // Observers look on without a cursor of their own
+            if !read_only {
+                cursors::update(state, msg_rift_id, user_id, username, path, line, column);
+            }
+        }
+
+        SyncMessage::WatchCursors { rift_id: msg_rift_id } => {
+            // SECURITY CHECK: Only the cursors of the client's own rift
+            if msg_rift_id.to_string() != client_rift_id {
+                error!("🚨 SECURITY: Client attempted to watch cursors of unauthorized rift {} (authorized: {})", msg_rift_id, client_rift_id);
+                return Err(anyhow::anyhow!("Unauthorized cursor watch attempt"));
+            }
+            cursors::watch(state, msg_rift_id, reply);
+        }
+
+        SyncMessage::FileOpened { rift_id: msg_rift_id, path, open } => {
+            // SECURITY CHECK: Only files of the client's own rift
+            if msg_rift_id.to_string() != client_rift_id {
+                error!("🚨 SECURITY: Client attempted to open a file in unauthorized rift {} (authorized: {})", msg_rift_id, client_rift_id);
+                return Err(anyhow::anyhow!("Unauthorized file activity update attempt"));
+            }
+            // Observers only look, so their open files don't warn anyone
+            if !read_only {
+                file_activity::opened(state, msg_rift_id, user_id, username, path, open);
+            }
+        }
+
+        SyncMessage::FileDirty { rift_id: msg_rift_id, paths, dirty } => {
+            // SECURITY CHECK: Only files of the client's own rift
+            if msg_rift_id.to_string() != client_rift_id {
+                error!("🚨 SECURITY: Client attempted to mark files changed in unauthorized rift {} (authorized: {})", msg_rift_id, client_rift_id);
+                return Err(anyhow::anyhow!("Unauthorized file activity update attempt"));
+            }
+            if !read_only {
+                file_activity::dirtied(state, msg_rift_id, user_id, username, paths, dirty);
+            }
+        }
+
+        SyncMessage::WatchFileActivity { rift_id: msg_rift_id } => {
+            // SECURITY CHECK: Only the file activity of the client's own rift
+            if msg_rift_id.to_string() != client_rift_id {
+                error!("🚨 SECURITY: Client attempted to watch file activity of unauthorized rift {} (authorized: {})", msg_rift_id, client_rift_id);
+                return Err(anyhow::anyhow!("Unauthorized file activity watch attempt"));
+            }
+            file_activity::watch(state, msg_rift_id, reply);
         }
 
         SyncMessage::Heartbeat => {
@@ -449,6 +1555,223 @@ async fn handle_sync_message(message: &str, state: &SyncState, client_rift_id: &
     Ok(())
 }
 
+/// The size of the file a change produces, as far as the sender says. A block
+/// delta's own size counts too, so a small `file_size` can't sneak a large
+/// delta past the size limit.
+fn claimed_size(file_size: u64, diff: &FileDiff) -> u64 {
+    match diff {
+        FileDiff::BlockDelta { new_size, .. } => file_size.max(*new_size),
+        _ => file_size,
+    }
+}
+
+/// A file a client wants to write, as far as the push rules need to know it.
+/// `content` is there when it is cheap to know, for the secret scan.
+struct IncomingFile<'a> {
+    path: &'a Path,
+    size: u64,
+    content: Option<&'a str>,
+}
+
+/// Push rules and secret scanning of the project a rift belongs to, and the
+/// server's file size limit
+struct IngestPolicy {
+    project_id: ProjectId,
+    checker: PushRuleChecker,
+    secret_scanning: SecretScanMode,
+}
+
+impl IngestPolicy {
+    /// The rift's policy, or `None` if nothing is checked
+    async fn load(state: &SyncState, rift_id: RiftId) -> Result<Option<Self>> {
+        let Some(rift) = state.db.get_rift(rift_id).await? else {
+            return Ok(None);
+        };
+        let settings = state.db.get_project_settings(rift.project_id).await?;
+        let policy = Self {
+            project_id: rift.project_id,
+            checker: PushRuleChecker::new(&settings)
+                .with_server_max_file_size(state.settings.config().sync_limits.max_file_bytes),
+            secret_scanning: settings.secret_scanning,
+        };
+        Ok((policy.checker.is_active() || policy.secret_scanning != SecretScanMode::Off).then_some(policy))
+    }
+
+    /// The content a diff would produce, when secrets have to be found before
+    /// the diff is applied. Changes the push rules refuse anyway aren't built.
+    async fn needs_content(&self, state: &SyncState, rift_id: RiftId, path: &PathBuf, size: u64, diff: &FileDiff) -> Option<String> {
+        if self.secret_scanning != SecretScanMode::Reject || !self.checker.check_file(path, size).is_empty() {
+            return None;
+        }
+        state.storage.preview_file_diff(rift_id, path, diff).await.ok()
+    }
+
+    /// Check a file a client sent against the push rules and, if its content
+    /// is known, scan it for secrets. Refusals and warnings go to the sender;
+    /// returns whether the change may be applied.
+    async fn admit_file(
+        &self,
+        state: &SyncState,
+        reply: &mpsc::UnboundedSender<SyncMessage>,
+        username: &str,
+        rift_id: RiftId,
+        user_id: UserId,
+        file: IncomingFile<'_>,
+    ) -> bool {
+        let mut violations = self.checker.check_file(file.path, file.size);
+
+        if let Some(content) = file.content {
+            let secrets = secret_scan::scan_files([(file.path, content)], rift_id, user_id, self.secret_scanning);
+            secret_scan::record(&state.storage, self.project_id, &secrets).await;
+            if self.secret_scanning == SecretScanMode::Reject {
+                violations.extend(secrets.iter().map(PushRuleViolation::from));
+            } else if violations.is_empty() && !secrets.is_empty() {
+                warn_secrets(reply, &secrets);
+            }
+        }
+
+        if !violations.is_empty() {
+            reject_push(reply, username, &violations);
+            return false;
+        }
+        true
+    }
+
+    /// Scan an applied change in projects that only warn about secrets
+    async fn warn_about_secrets(
+        &self,
+        state: &SyncState,
+        reply: &mpsc::UnboundedSender<SyncMessage>,
+        rift_id: RiftId,
+        user_id: UserId,
+        path: &Path,
+        content: &str,
+    ) {
+        if self.secret_scanning != SecretScanMode::Warn {
+            return;
+        }
+        let secrets = secret_scan::scan_files([(path, content)], rift_id, user_id, self.secret_scanning);
+        secret_scan::record(&state.storage, self.project_id, &secrets).await;
+        if !secrets.is_empty() {
+            warn_secrets(reply, &secrets);
+        }
+    }
+}
+
+/// Warn the sender that a change it made put secrets in the rift
+fn warn_secrets(reply: &mpsc::UnboundedSender<SyncMessage>, secrets: &[SecretFinding]) {
+    let found = secrets.iter().map(SecretFinding::describe).collect::<Vec<_>>().join("; ");
+    let _ = reply.send(SyncMessage::Error {
+        message: format!("Possible secrets synced to the rift: {}. Remove and rotate them before they reach a checkpoint.", found),
+        error_code: Some(SECRET_FOUND_WARNING.to_string()),
+    });
+}
+
+/// Tell the sender its change was refused because it breaks push rules
+fn reject_push(reply: &mpsc::UnboundedSender<SyncMessage>, username: &str, violations: &[PushRuleViolation]) {
+    let summary = describe_violations(violations);
+    warn!("🚫 Rejected change from {}: {}", username, summary);
+    let _ = reply.send(SyncMessage::Error {
+        message: format!("Rejected by push rules: {}", summary),
+        error_code: Some(PUSH_RULE_ERROR.to_string()),
+    });
+}
+
+/// A client's block delta didn't apply to our copy of the file. If we have no
+/// copy, ask for the whole file; otherwise the copies diverged and the
+/// project's conflict policy decides who wins
+async fn resolve_diff_conflict(
+    state: &SyncState,
+    reply: &mpsc::UnboundedSender<SyncMessage>,
+    rift_id: RiftId,
+    user_id: UserId,
+    path: PathBuf,
+    diff: FileDiff,
+    error: anyhow::Error,
+) -> Result<()> {
+    let FileDiff::BlockDelta { base_hash, .. } = &diff else {
+        return Err(error);
+    };
+    warn!("⚠️ Rejected block delta for {}: {}", path.display(), error);
+
+    let server_content = state.storage.get_file_content(rift_id, &path).await.ok();
+    let policy = rift_conflict_policy(state, rift_id).await?;
+
+    let Some(server_content) = server_content.filter(|_| policy != ConflictPolicy::ClientWins) else {
+        // Ask the sender for the full file, which replaces our copy
+        let _ = reply.send(SyncMessage::DeltaRejected { rift_id, path, base_hash: base_hash.clone() });
+        return Ok(());
+    };
+
+    let _ = reply.send(conflict_detected(rift_id, user_id, path, server_content, diff, policy));
+    Ok(())
+}
+
+/// The conflict policy of the project a rift belongs to
+async fn rift_conflict_policy(state: &SyncState, rift_id: RiftId) -> Result<ConflictPolicy> {
+    Ok(match state.db.get_rift(rift_id).await? {
+        Some(rift) => state.db.get_project_settings(rift.project_id).await?.conflict_policy,
+        None => ConflictPolicy::default(),
+    })
+}
+
+/// Tell a client its copy of a file diverged from ours
+fn conflict_detected(
+    rift_id: RiftId,
+    user_id: UserId,
+    path: PathBuf,
+    server_content: String,
+    client_diff: FileDiff,
+    policy: ConflictPolicy,
+) -> SyncMessage {
+    info!("⚔️ Conflict on {} in rift {}, resolving with policy {}", path.display(), rift_id, policy.as_str());
+    let now = chrono::Utc::now();
+    SyncMessage::ConflictDetected {
+        rift_id,
+        path: path.clone(),
+        conflict: Conflict {
+            id: Uuid::new_v4().to_string(),
+            file_path: path,
+            base_content: String::new(),
+            local_content: String::new(),
+            remote_content: server_content.clone(),
+            local_author: user_id,
+            // The server's copy may combine several collaborators' changes
+            remote_author: Uuid::nil(),
+            timestamp: now,
+        },
+        suggestions: Vec::new(),
+        server_content,
+        client_diff,
+        server_timestamp: now,
+        client_timestamp: now,
+        auto_created_rift: None,
+        policy,
+    }
+}
+
+/// CO-EDITING: Fold a whole-file change into the file's shared document (if it is
+/// being co-edited) and send the resulting operations to the co-editors
+async fn sync_text_session(state: &SyncState, rift_id: RiftId, path: &Path, content: &str) {
+    let mut sessions = state.text_sessions.write().await;
+    let Some(document) = sessions.get_mut(&(rift_id, path.to_path_buf())) else {
+        return;
+    };
+
+    let operations = document.update_text(content);
+    if operations.is_empty() {
+        return;
+    }
+
+    let response = SyncMessage::TextOperations {
+        rift_id,
+        path: path.to_path_buf(),
+        site_id: SERVER_SITE_ID,
+        operations,
+    };
+    state.broadcast(format!("rift_{}", rift_id), response);
+}
+
 /// PERFORMANCE FIX: Add diff change to batch (with immediate flush if batch is full)
 async fn handle_diff_change_batched(
     state: &SyncState, 
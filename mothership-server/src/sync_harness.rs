@@ -0,0 +1,288 @@
+//! Deterministic sync scenarios for tests.
+//!
+//! A `Harness` runs a real `SyncState` over an in-memory database and
+//! storage engine, with simulated clients
+//! sending sync messages straight to the message handler, as their
+//! WebSocket connections would. Every broadcast on the rift's channel is
+//! captured, so a test can check that what the server holds, what a client
+//! following the broadcasts would hold, and what the event log replays to
+//! all converge. Recorded event logs can be driven through a fresh server
+//! to check they rebuild the same state.
+
+use mothership_common::protocol::{FileDiffChange, SyncMessage};
+use mothership_common::rift_events::{self, RiftEvent};
+use mothership_common::{DiffEngine, FileDiff, RiftId, UserId, UserRole};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+use uuid::Uuid;
+
+use crate::config::ServerConfig;
+use crate::database::Database;
+use crate::features::FeatureFlags;
+use crate::live_config::{ConfigLoader, LiveConfig};
+use crate::notifications::Notifier;
+use crate::storage::StorageEngine;
+use crate::sync::{handle_sync_message, SyncState};
+
+/// Long enough for the batch flusher and the event log writer to catch up
+const SETTLE_TIME: Duration = Duration::from_millis(300);
+
+/// A server with one project and one rift
+pub struct Harness {
+    pub state: SyncState,
+    pub rift_id: RiftId,
+    project_id: Uuid,
+    broadcasts: broadcast::Receiver<(String, u64, SyncMessage)>,
+}
+
+/// A simulated client connected to the harness's rift
+pub struct Client {
+    pub user_id: UserId,
+    pub username: String,
+    reply: mpsc::UnboundedSender<SyncMessage>,
+    replies: mpsc::UnboundedReceiver<SyncMessage>,
+}
+
+impl Client {
+    /// Messages the server sent only to this client so far
+    pub fn replies(&mut self) -> Vec<SyncMessage> {
+        let mut replies = Vec::new();
+        while let Ok(message) = self.replies.try_recv() {
+            replies.push(message);
+        }
+        replies
+    }
+}
+
+impl Harness {
+    pub async fn new() -> Self {
+        let config = ServerConfig::default();
+        let db = Database::new_in_memory().await.unwrap();
+        let storage = Arc::new(StorageEngine::new_in_memory());
+
+        let loader: ConfigLoader = Arc::new(|| Ok(ServerConfig::default()));
+        let settings = LiveConfig::new(config.clone(), None, loader, db.clone());
+        let notifier = Notifier::new(db.clone(), &config.notifications).unwrap();
+        let state = SyncState::new(
+            db.clone(),
+            storage,
+            settings.clone(),
+            FeatureFlags::new(db.clone(), settings),
+            false,
+            notifier,
+            false,
+        );
+
+        let owner = db.create_user("owner".to_string(), "owner@example.com".to_string(), UserRole::User).await.unwrap();
+        let project = db.create_project("harness".to_string(), String::new(), vec![owner.id]).await.unwrap();
+        let rift = db.create_rift(project.id, owner.id, None).await.unwrap();
+        let broadcasts = state.broadcaster.subscribe();
+
+        Self { state, rift_id: rift.id, project_id: project.id, broadcasts }
+    }
+
+    /// Sign in a new project member
+    pub async fn client(&self, username: &str) -> Client {
+        let user = self.state.db
+            .create_user(username.to_string(), format!("{}@example.com", username), UserRole::User)
+            .await
+            .unwrap();
+        self.state.db.add_project_member(self.project_id, user.id, "member").await.unwrap();
+        let (reply, replies) = mpsc::unbounded_channel();
+        Client { user_id: user.id, username: username.to_string(), reply, replies }
+    }
+
+    /// Handle a message from a client, as its connection would
+    pub async fn send(&self, client: &Client, message: SyncMessage) {
+        handle_sync_message(message, &self.state, &self.rift_id.to_string(), client.user_id, &client.username, false, &client.reply)
+            .await
+            .unwrap();
+    }
+
+    /// A client saves a whole file
+    pub async fn write(&self, client: &Client, path: &str, content: &str) {
+        self.send(client, SyncMessage::FileChanged {
+            rift_id: self.rift_id,
+            path: PathBuf::from(path),
+            content: content.to_string(),
+            timestamp: chrono::Utc::now(),
+            metadata: Default::default(),
+        }).await;
+    }
+
+    /// A client sends a change as a diff from the content it last saw
+    pub async fn write_diff(&self, client: &Client, path: &str, diff: FileDiff) {
+        self.send(client, SyncMessage::FileDiffChanged {
+            rift_id: self.rift_id,
+            path: PathBuf::from(path),
+            file_size: 0,
+            diff,
+            timestamp: chrono::Utc::now(),
+            metadata: Default::default(),
+        }).await;
+    }
+
+    /// Wait for batched broadcasts and event log writes to go out
+    pub async fn settle(&self) {
+        tokio::time::sleep(SETTLE_TIME).await;
+    }
+
+    /// The rift's working files on the server
+    pub async fn server_files(&self) -> HashMap<PathBuf, String> {
+        self.state.storage.get_live_state(self.rift_id).await.unwrap()
+    }
+
+    /// Numbered broadcasts on the rift's channel since the last call
+    pub fn broadcasts(&mut self) -> Vec<RiftEvent> {
+        let channel = format!("rift_{}", self.rift_id);
+        let mut events = Vec::new();
+        while let Ok((broadcast_channel, seq, message)) = self.broadcasts.try_recv() {
+            if broadcast_channel == channel {
+                events.push(RiftEvent { seq, timestamp: chrono::Utc::now(), message });
+            }
+        }
+        events
+    }
+
+    /// Everything written to the rift's event log
+    pub async fn logged_events(&self) -> Vec<RiftEvent> {
+        self.state.storage.read_rift_events(self.rift_id, 0, usize::MAX).await.unwrap()
+    }
+
+    /// Drive recorded file changes through this server, one client sending
+    /// each logged update as the batch it was
+    pub async fn replay_recorded(&self, client: &Client, events: &[RiftEvent]) {
+        for event in events {
+            let SyncMessage::RiftDiffUpdate { diff_changes, .. } = &event.message else {
+                continue;
+            };
+            self.send(client, SyncMessage::BatchDiffChanges {
+                rift_id: self.rift_id,
+                changes: diff_changes.clone(),
+                timestamp: event.timestamp,
+                compressed: false,
+            }).await;
+        }
+    }
+}
+
+/// The files a client following the given broadcasts from an empty rift ends up with
+pub fn follower_files(events: &[RiftEvent]) -> HashMap<PathBuf, String> {
+    let mut files = HashMap::new();
+    rift_events::replay(&mut files, events).unwrap();
+    files
+}
+
+fn change(path: &str, diff: FileDiff) -> FileDiffChange {
+    FileDiffChange { path: PathBuf::from(path), diff, file_size: 0, metadata: Default::default() }
+}
+
+#[tokio::test]
+async fn test_concurrent_edits_converge() {
+    let mut harness = Harness::new().await;
+    let alice = harness.client("alice").await;
+    let bob = harness.client("bob").await;
+
+    harness.write(&alice, "src/main.rs", "fn main() {}\n").await;
+    harness.write(&bob, "README.md", "# Harness\n").await;
+    harness.write(&alice, "src/main.rs", "fn main() {\n    run();\n}\n").await;
+    let diff = DiffEngine::new().generate_line_diff("# Harness\n", "# Harness\n\nSynced.\n");
+    harness.write_diff(&bob, "README.md", diff).await;
+    harness.send(&bob, SyncMessage::BatchDiffChanges {
+        rift_id: harness.rift_id,
+        changes: vec![change("notes.txt", FileDiff::FullContent("todo\n".to_string())), change("README.md", FileDiff::Deleted)],
+        timestamp: chrono::Utc::now(),
+        compressed: false,
+    }).await;
+    harness.settle().await;
+
+    let server = harness.server_files().await;
+    let expected: HashMap<PathBuf, String> = [
+        ("src/main.rs", "fn main() {\n    run();\n}\n"),
+        ("notes.txt", "todo\n"),
+    ].into_iter().map(|(path, content)| (PathBuf::from(path), content.to_string())).collect();
+    assert_eq!(server, expected);
+    assert_eq!(follower_files(&harness.broadcasts()), server);
+    assert_eq!(follower_files(&harness.logged_events().await), server);
+}
+
+#[tokio::test]
+async fn test_stale_block_delta_is_a_conflict() {
+    let harness = Harness::new().await;
+    let alice = harness.client("alice").await;
+    let mut bob = harness.client("bob").await;
+
+    let base: String = (0..500).map(|line| format!("line {}\n", line)).collect();
+    harness.write(&alice, "data.txt", &base).await;
+    let alices = base.replace("line 10\n", "line ten\n");
+    harness.write(&alice, "data.txt", &alices).await;
+
+    // Bob still has the first version
+    let bobs = base.replace("line 400\n", "line four hundred\n");
+    let delta = DiffEngine::new().generate_block_delta(&base, &bobs);
+    assert!(matches!(delta, FileDiff::BlockDelta { .. }));
+    harness.write_diff(&bob, "data.txt", delta).await;
+    harness.settle().await;
+
+    // The server keeps Alice's change and tells only Bob
+    assert_eq!(harness.server_files().await[&PathBuf::from("data.txt")], alices);
+    let conflicts: Vec<_> = bob.replies().into_iter()
+        .filter_map(|reply| match reply {
+            SyncMessage::ConflictDetected { path, server_content, .. } => Some((path, server_content)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(conflicts, vec![(PathBuf::from("data.txt"), alices)]);
+}
+
+#[tokio::test]
+async fn test_unappliable_changes_leave_the_rest_of_the_batch() {
+    let mut harness = Harness::new().await;
+    let mut alice = harness.client("alice").await;
+    let mut bob = harness.client("bob").await;
+
+    // A delta against a file the server doesn't have, and a diff it can't apply
+    let base: String = (0..500).map(|line| format!("line {}\n", line)).collect();
+    let delta = DiffEngine::new().generate_block_delta(&base, &base.replace("line 7\n", "line seven\n"));
+    let binary = FileDiff::BinaryDiff { patches: Vec::new(), original_size: 0, new_size: 0 };
+    harness.send(&alice, SyncMessage::BatchDiffChanges {
+        rift_id: harness.rift_id,
+        changes: vec![change("data.txt", delta), change("image.bin", binary), change("notes.txt", FileDiff::FullContent("todo\n".to_string()))],
+        timestamp: chrono::Utc::now(),
+        compressed: false,
+    }).await;
+    harness.settle().await;
+
+    let server = harness.server_files().await;
+    assert_eq!(server, HashMap::from([(PathBuf::from("notes.txt"), "todo\n".to_string())]));
+    assert_eq!(follower_files(&harness.broadcasts()), server);
+
+    // Only the sender is asked for the whole file
+    let replies = alice.replies();
+    assert!(replies.iter().any(|reply| matches!(reply, SyncMessage::DeltaRejected { path, .. } if path == &PathBuf::from("data.txt"))));
+    assert!(replies.iter().any(|reply| matches!(reply, SyncMessage::Error { .. })));
+    assert!(bob.replies().is_empty());
+}
+
+#[tokio::test]
+async fn test_recorded_log_rebuilds_the_same_state() {
+    let recorded = {
+        let harness = Harness::new().await;
+        let alice = harness.client("alice").await;
+        harness.write(&alice, "a.txt", "one\n").await;
+        harness.write(&alice, "b.txt", "two\n").await;
+        harness.write(&alice, "a.txt", "one\nmore\n").await;
+        harness.settle().await;
+        (harness.logged_events().await, harness.server_files().await)
+    };
+    let (events, recorded_files) = recorded;
+
+    let harness = Harness::new().await;
+    let replayer = harness.client("replayer").await;
+    harness.replay_recorded(&replayer, &events).await;
+    harness.settle().await;
+    assert_eq!(harness.server_files().await, recorded_files);
+}
@@ -0,0 +1,193 @@
+//! Live terminal sharing.
+//!
+//! A client (`mothership share-terminal`) streams a command's output into its
+//! rift. Terminal messages go out on a channel of their own rather than the
+//! rift channel, so they aren't numbered or kept for replay and only reach
+//! connections that asked to watch. The server keeps the tail of each share's
+//! output so someone who starts watching midway sees recent context.
+
+use chrono::Utc;
+use mothership_common::{
+    protocol::{SyncMessage, TerminalShare},
+    RiftId, UserId,
+};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::sync::SyncState;
+
+/// Largest output chunk a client may send at once, in bytes
+const MAX_OUTPUT_CHUNK: usize = 64 * 1024;
+
+/// Output kept per share for watchers who join late, in bytes
+const SCROLLBACK_SIZE: usize = 64 * 1024;
+
+/// Terminals that may be shared into one rift at the same time
+const MAX_SHARES_PER_RIFT: usize = 4;
+
+/// Longest command line shown to watchers, in characters
+const MAX_COMMAND_LENGTH: usize = 200;
+
+/// Channels carrying a rift's terminal shares
+pub const TERMINAL_CHANNEL_PREFIX: &str = "terminal_";
+
+pub fn terminal_channel(rift_id: impl Display) -> String {
+    format!("{}{}", TERMINAL_CHANNEL_PREFIX, rift_id)
+}
+
+struct ActiveShare {
+    rift_id: RiftId,
+    share: TerminalShare,
+    scrollback: String,
+}
+
+impl ActiveShare {
+    fn append(&mut self, data: &str) {
+        self.scrollback.push_str(data);
+        if self.scrollback.len() > SCROLLBACK_SIZE {
+            let mut cut = self.scrollback.len() - SCROLLBACK_SIZE;
+            while !self.scrollback.is_char_boundary(cut) {
+                cut += 1;
+            }
+            self.scrollback.drain(..cut);
+        }
+    }
+}
+
+/// Terminals currently being shared, with their recent output
+#[derive(Clone, Default)]
+pub struct TerminalShares {
+    shares: Arc<Mutex<HashMap<Uuid, ActiveShare>>>,
+}
+
+impl TerminalShares {
+    /// Shares of a rift with their scrollback, oldest first
+    fn in_rift(&self, rift_id: RiftId) -> Vec<(TerminalShare, String)> {
+        let shares = self.shares.lock().unwrap();
+        let mut found: Vec<_> = shares.values()
+            .filter(|active| active.rift_id == rift_id)
+            .map(|active| (active.share.clone(), active.scrollback.clone()))
+            .collect();
+        found.sort_by_key(|(share, _)| share.started_at);
+        found
+    }
+
+    /// Drop every share of a rift that no longer exists
+    pub fn forget_rift(&self, rift_id: RiftId) {
+        self.shares.lock().unwrap().retain(|_, active| active.rift_id != rift_id);
+    }
+
+    /// Mirror a terminal message another instance broadcast
+    pub fn apply_relayed(&self, message: &SyncMessage) {
+        let mut shares = self.shares.lock().unwrap();
+        match message {
+            SyncMessage::TerminalShareStarted { rift_id, share } => {
+                shares.insert(share.id, ActiveShare { rift_id: *rift_id, share: share.clone(), scrollback: String::new() });
+            }
+            SyncMessage::TerminalOutput { share_id, data, .. } => {
+                if let Some(active) = shares.get_mut(share_id) {
+                    active.append(data);
+                }
+            }
+            SyncMessage::TerminalShareEnded { share_id, .. } => {
+                shares.remove(share_id);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Start sharing a terminal into a rift and announce it to watchers.
+/// Returns an error message suitable for the client when rejected.
+#[allow(clippy::too_many_arguments)]
+pub fn start(
+    state: &SyncState,
+    rift_id: RiftId,
+    user_id: UserId,
+    username: &str,
+    share_id: Uuid,
+    command: &str,
+    cols: u16,
+    rows: u16,
+) -> Result<TerminalShare, String> {
+    let share = TerminalShare {
+        id: share_id,
+        user_id,
+        username: username.to_string(),
+        command: command.trim().chars().take(MAX_COMMAND_LENGTH).collect(),
+        cols: cols.clamp(20, 500),
+        rows: rows.clamp(5, 200),
+        started_at: Utc::now(),
+    };
+
+    {
+        let mut shares = state.terminal_shares.shares.lock().unwrap();
+        if shares.contains_key(&share_id) {
+            return Err("A terminal share with this ID already exists".to_string());
+        }
+        if shares.values().filter(|active| active.rift_id == rift_id).count() >= MAX_SHARES_PER_RIFT {
+            return Err(format!("This rift already has {} shared terminals", MAX_SHARES_PER_RIFT));
+        }
+        shares.insert(share_id, ActiveShare { rift_id, share: share.clone(), scrollback: String::new() });
+    }
+
+    info!("🖥️ {} is sharing '{}' in rift {}", username, share.command, rift_id);
+    state.broadcast_unsequenced(terminal_channel(rift_id), SyncMessage::TerminalShareStarted { rift_id, share: share.clone() });
+    Ok(share)
+}
+
+/// Pass output of a share on to watchers. Only the user sharing the terminal
+/// may send its output.
+pub fn output(state: &SyncState, rift_id: RiftId, user_id: UserId, share_id: Uuid, data: String) -> Result<(), String> {
+    if data.len() > MAX_OUTPUT_CHUNK {
+        return Err(format!("Terminal output chunks are limited to {} bytes", MAX_OUTPUT_CHUNK));
+    }
+
+    {
+        let mut shares = state.terminal_shares.shares.lock().unwrap();
+        match shares.get_mut(&share_id) {
+            // SECURITY CHECK: Nobody else may write into someone's terminal share
+            Some(active) if active.rift_id == rift_id && active.share.user_id == user_id => active.append(&data),
+            _ => return Err("No such terminal share".to_string()),
+        }
+    }
+
+    state.broadcast_unsequenced(terminal_channel(rift_id), SyncMessage::TerminalOutput { rift_id, share_id, data });
+    Ok(())
+}
+
+/// Stop a share and tell watchers. Returns whether the user had such a share.
+pub fn end(state: &SyncState, rift_id: RiftId, user_id: UserId, share_id: Uuid, exit_code: Option<i32>) -> bool {
+    {
+        let mut shares = state.terminal_shares.shares.lock().unwrap();
+        match shares.get(&share_id) {
+            Some(active) if active.rift_id == rift_id && active.share.user_id == user_id => {
+                shares.remove(&share_id);
+            }
+            _ => return false,
+        }
+    }
+
+    debug!("🖥️ Terminal share {} in rift {} ended (exit code {:?})", share_id, rift_id, exit_code);
+    state.broadcast_unsequenced(terminal_channel(rift_id), SyncMessage::TerminalShareEnded { rift_id, share_id, exit_code });
+    true
+}
+
+/// Start a connection watching a rift's terminals: list the current shares,
+/// then replay what each has printed recently
+pub fn watch(state: &SyncState, rift_id: RiftId, reply: &mpsc::UnboundedSender<SyncMessage>) {
+    let shares = state.terminal_shares.in_rift(rift_id);
+    let _ = reply.send(SyncMessage::TerminalShares {
+        rift_id,
+        shares: shares.iter().map(|(share, _)| share.clone()).collect(),
+    });
+    for (share, scrollback) in shares {
+        if !scrollback.is_empty() {
+            let _ = reply.send(SyncMessage::TerminalOutput { rift_id, share_id: share.id, data: scrollback });
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! Handler-level tests without PostgreSQL or a storage directory.
+//!
+//! A `TestClient` serves the full router over an in-memory database and
+//! storage engine, sending each request straight to it rather than over a
+//! socket. Signing in mints a token for a new user, sent with every request
+//! after that.
+
+use axum::body::{to_bytes, Body, Bytes};
+use axum::http::{header, Method, Request, StatusCode};
+use axum::Router;
+use mothership_common::auth::Claims;
+use mothership_common::{ApiResponse, Project, User, UserRole};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tower::ServiceExt;
+
+use crate::auth::AuthService;
+use crate::config::ServerConfig;
+use crate::database::Database;
+use crate::features::FeatureFlags;
+use crate::live_config::{ConfigLoader, LiveConfig};
+use crate::notifications::Notifier;
+use crate::oauth::OAuthService;
+use crate::storage::StorageEngine;
+use crate::sync::SyncState;
+use crate::{create_combined_router, git_export, replication, AppState};
+
+/// Responses are small; anything bigger is a bug in the handler under test
+const MAX_BODY_BYTES: usize = 16 * 1024 * 1024;
+
+pub struct TestClient {
+    pub state: AppState,
+    router: Router,
+    token: Option<String>,
+}
+
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub body: Bytes,
+}
+
+impl TestResponse {
+    pub fn json<T: DeserializeOwned>(&self) -> T {
+        serde_json::from_slice(&self.body).unwrap_or_else(|e| {
+            panic!("Response is not the expected JSON ({}): {}", e, String::from_utf8_lossy(&self.body))
+        })
+    }
+
+    /// The `data` of a successful `ApiResponse`
+    pub fn data<T: DeserializeOwned>(&self) -> T {
+        assert_eq!(self.status, StatusCode::OK, "{}", String::from_utf8_lossy(&self.body));
+        self.json::<ApiResponse<T>>().data.expect("Successful response without data")
+    }
+}
+
+impl TestClient {
+    pub async fn new() -> Self {
+        Self::with_config(ServerConfig::default()).await
+    }
+
+    pub async fn with_config(config: ServerConfig) -> Self {
+        let db = Database::new_in_memory().await.unwrap();
+        let storage = Arc::new(StorageEngine::new_in_memory());
+
+        let loader_config = config.clone();
+        let loader: ConfigLoader = Arc::new(move || Ok(loader_config.clone()));
+        let settings = LiveConfig::new(config.clone(), None, loader, db.clone());
+        let notifier = Notifier::new(db.clone(), &config.notifications).unwrap();
+        let sync = SyncState::new(
+            db.clone(),
+            storage,
+            settings.clone(),
+            FeatureFlags::new(db.clone(), settings.clone()),
+            config.features.binary_sync_enabled,
+            notifier,
+            false,
+        );
+
+        let state = AppState {
+            db,
+            auth: AuthService::new("test_client_secret".to_string()),
+            oauth: OAuthService::new().unwrap(),
+            sync,
+            settings,
+            sessions: Arc::new(RwLock::new(HashMap::new())),
+            temp_tokens: Arc::new(RwLock::new(HashMap::new())),
+            replication: replication::FollowerHandle::default(),
+            git_mirrors: git_export::MirrorLocks::default(),
+        };
+
+        Self { router: create_combined_router(state.clone()), state, token: None }
+    }
+
+    /// Create a user and send their token with every request from now on
+    pub async fn sign_in(&mut self, username: &str) -> User {
+        let user = self.state.db
+            .create_user(username.to_string(), format!("{}@example.com", username), UserRole::User)
+            .await
+            .unwrap();
+        let now = chrono::Utc::now();
+        let claims = Claims {
+            sub: user.id.to_string(),
+            machine_id: "test-client".to_string(),
+            username: user.username.clone(),
+            email: Some(user.email.clone()),
+            iat: now.timestamp(),
+            exp: (now + chrono::Duration::hours(1)).timestamp(),
+            aud: "mothership".to_string(),
+            iss: "mothership-server".to_string(),
+            jti: None,
+        };
+        self.token = Some(self.state.auth.encode_token(&claims).unwrap());
+        user
+    }
+
+    /// The token currently sent, to switch back to after signing someone else in
+    pub fn token(&self) -> Option<String> {
+        self.token.clone()
+    }
+
+    /// Send this token with every request instead, e.g. a personal access token
+    pub fn use_token(&mut self, token: String) {
+        self.token = Some(token);
+    }
+
+    /// Send requests without a token again
+    pub fn sign_out(&mut self) {
+        self.token = None;
+    }
+
+    pub async fn get(&self, uri: &str) -> TestResponse {
+        self.request(Method::GET, uri, None).await
+    }
+
+    pub async fn post(&self, uri: &str, body: &impl Serialize) -> TestResponse {
+        self.request(Method::POST, uri, Some(serde_json::to_vec(body).unwrap())).await
+    }
+
+    /// Send raw bytes, as chunk uploads do
+    pub async fn put(&self, uri: &str, bytes: &[u8]) -> TestResponse {
+        self.request(Method::PUT, uri, Some(bytes.to_vec())).await
+    }
+
+    async fn request(&self, method: Method, uri: &str, body: Option<Vec<u8>>) -> TestResponse {
+        let mut request = Request::builder().method(method).uri(uri);
+        if let Some(token) = &self.token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        let request = match body {
+            Some(body) => request.header(header::CONTENT_TYPE, "application/json").body(Body::from(body)),
+            None => request.body(Body::empty()),
+        }
+        .unwrap();
+
+        let response = self.router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), MAX_BODY_BYTES).await.unwrap();
+        TestResponse { status, body }
+    }
+}
+
+#[tokio::test]
+async fn test_health_check() {
+    let client = TestClient::new().await;
+    let message: String = client.get("/health").await.data();
+    assert_eq!(message, "Mothership is operational");
+}
+
+#[tokio::test]
+async fn test_created_gateway_is_readable_by_members_only() {
+    let mut client = TestClient::new().await;
+    let owner = client.sign_in("owner").await;
+    let project: Project = client.post("/gateway/create", &serde_json::json!({
+        "name": "in-memory",
+        "description": "",
+        "project_path": "/tmp/in-memory",
+    })).await.data();
+    assert_eq!(project.members, vec![owner.id]);
+
+    let fetched: Project = client.get(&format!("/projects/{}", project.id)).await.data();
+    assert_eq!(fetched.id, project.id);
+
+    client.sign_in("outsider").await;
+    assert_eq!(client.get(&format!("/projects/{}", project.id)).await.status, StatusCode::FORBIDDEN);
+    client.sign_out();
+    assert_eq!(client.get(&format!("/projects/{}", project.id)).await.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_in_memory_storage_keeps_checkpoints() {
+    let client = TestClient::new().await;
+    let storage = &client.state.sync.storage;
+    let rift_id = uuid::Uuid::new_v4();
+    storage.update_live_state(rift_id, "hello.txt".into(), "hello\n".to_string()).await.unwrap();
+    let checkpoint = storage.create_checkpoint(rift_id, uuid::Uuid::new_v4(), None, false).await.unwrap();
+
+    let hash = &checkpoint.changes[0].content_hash;
+    assert_eq!(storage.get_content(hash).await.unwrap().as_deref(), Some("hello\n"));
+    assert_eq!(storage.get_stats().await.unwrap().checkpoint_files, 1);
+    let (replicated, _) = storage.read_replication_log(0, 10).await.unwrap();
+    assert_eq!(replicated.len(), 1);
+    let report = storage.verify_integrity(Default::default()).await.unwrap();
+    assert_eq!((report.checkpoints_checked, report.blobs_checked), (1, 1));
+    assert!(report.missing_blobs.is_empty() && report.corrupt_blobs.is_empty());
+
+    assert_eq!(storage.remove_rift(rift_id).await.unwrap(), 1);
+    assert_eq!(storage.get_stats().await.unwrap().checkpoint_files, 0);
+}
+
+#[tokio::test]
+async fn test_checkpoint_restore_stays_in_its_project() {
+    let mut client = TestClient::new().await;
+    client.sign_in("owner").await;
+    let gateway = |name: &str| serde_json::json!({ "name": name, "description": "", "project_path": format!("/tmp/{}", name) });
+    let project: Project = client.post("/gateway/create", &gateway("restored")).await.data();
+    let beam: mothership_common::protocol::BeamResponse = client.post(&format!("/projects/{}/beam", project.id), &serde_json::json!({
+        "project_id": project.id,
+        "rift_name": null,
+        "force_sync": false,
+    })).await.data();
+    let storage = client.state.sync.storage.clone();
+    storage.update_live_state(beam.rift_id, "a.txt".into(), "one\n".to_string()).await.unwrap();
+    let checkpoint = storage.create_checkpoint(beam.rift_id, uuid::Uuid::new_v4(), None, false).await.unwrap();
+    storage.update_live_state(beam.rift_id, "a.txt".into(), "two\n".to_string()).await.unwrap();
+
+    // Another project's member can't restore it through their own project
+    client.sign_in("outsider").await;
+    let other: Project = client.post("/gateway/create", &gateway("other")).await.data();
+    let response = client.post(&format!("/projects/{}/checkpoints/{}/restore", other.id, checkpoint.id), &()).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    assert_eq!(storage.list_checkpoints(beam.rift_id).await.unwrap().len(), 1);
+}
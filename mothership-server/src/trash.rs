@@ -0,0 +1,131 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::{DateTime, Duration, Utc};
+use mothership_common::{protocol::ApiResponse, ProjectId};
+use serde::Serialize;
+use tracing::{error, info, warn};
+
+use crate::audit::{self, AuditAction, AuditEvent};
+use crate::database::TrashedProject;
+use crate::handlers::require_admin;
+use crate::AppState;
+
+/// How often expired projects are looked for
+const PURGE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// Deleted project endpoints (admins only)
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/admin/trash", get(list_trash))
+        .route("/admin/trash/:id/restore", post(restore_project))
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrashEntry {
+    #[serde(flatten)]
+    pub project: TrashedProject,
+    /// When the project will be purged for good
+    pub purge_at: DateTime<Utc>,
+}
+
+/// When a project deleted at `deleted_at` is purged
+pub fn purge_at(state: &AppState, deleted_at: DateTime<Utc>) -> DateTime<Utc> {
+    deleted_at + Duration::days(state.config().trash.retention_days)
+}
+
+/// List deleted projects that can still be restored
+async fn list_trash(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<ApiResponse<Vec<TrashEntry>>>, StatusCode> {
+    require_admin(&state, &headers, "access the trash").await?;
+
+    let projects = state.db.list_trashed_projects().await.map_err(|e| {
+        error!("Failed to list the trash: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(ApiResponse::success(projects
+        .into_iter()
+        .map(|project| TrashEntry { purge_at: purge_at(&state, project.deleted_at), project })
+        .collect())))
+}
+
+/// Bring a deleted project back, with its members, rifts and history
+async fn restore_project(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(project_id): Path<ProjectId>,
+) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    let admin_id = require_admin(&state, &headers, "access the trash").await?;
+
+    match state.db.untrash_project(project_id).await {
+        Ok(true) => {}
+        Ok(false) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            error!("Failed to restore project {}: {}", project_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+
+    let name = match state.db.get_project(project_id).await {
+        Ok(Some(project)) => project.name,
+        _ => project_id.to_string(),
+    };
+    info!("♻️ Restored project {} ({}) from the trash", name, project_id);
+    audit::record(&state.db, AuditEvent::new(AuditAction::ProjectRestored, Some(admin_id))
+        .project(project_id)
+        .details(serde_json::json!({ "name": name }))).await;
+
+    Ok(Json(ApiResponse::success(format!("Project '{}' restored from the trash", name))))
+}
+
+/// Periodically purge projects that have been in the trash longer than the
+/// retention period
+pub fn spawn_purger(state: AppState) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+        loop {
+            interval.tick().await;
+            purge_expired(&state).await;
+        }
+    });
+}
+
+async fn purge_expired(state: &AppState) {
+    let cutoff = Utc::now() - Duration::days(state.config().trash.retention_days);
+    let expired = match state.db.expired_trashed_projects(cutoff).await {
+        Ok(expired) => expired,
+        Err(e) => {
+            error!("Failed to look for expired projects in the trash: {}", e);
+            return;
+        }
+    };
+
+    for project_id in expired {
+        // History lives in storage, not the database, so remove it first
+        match state.db.get_project_rifts(project_id).await {
+            Ok(rifts) => {
+                for rift in rifts {
+                    if let Err(e) = state.sync.storage.remove_rift(rift.id).await {
+                        warn!("Failed to remove rift {} of purged project {}: {}", rift.id, project_id, e);
+                    }
+                }
+            }
+            Err(e) => warn!("Failed to load rifts of purged project {}: {}", project_id, e),
+        }
+
+        match state.db.delete_project(project_id).await {
+            Ok(()) => {
+                info!("🗑️ Purged project {} from the trash", project_id);
+                audit::record(&state.db, AuditEvent::new(AuditAction::ProjectPurged, None)
+                    .project(project_id)).await;
+            }
+            Err(e) => error!("Failed to purge project {}: {}", project_id, e),
+        }
+    }
+}
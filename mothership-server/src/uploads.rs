@@ -0,0 +1,366 @@
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, post, put},
+    Json, Router,
+};
+use chrono::Utc;
+use mothership_common::{
+    features::Feature,
+    protocol::ApiResponse,
+    push_rules::PushRuleChecker,
+    upload::{
+        is_valid_content_hash, CreateUploadRequest, UploadCompletion, UploadStatus, UPLOAD_CHUNK_SIZE,
+    },
+    ProjectId, Rift, UserId,
+};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::api_error::ApiError;
+use crate::handlers::authenticate_request;
+use crate::storage::UploadSession;
+use crate::AppState;
+
+/// Upload sessions older than this are discarded
+const UPLOAD_SESSION_TTL_HOURS: i64 = 24;
+
+/// Chunked, resumable file upload endpoints
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/projects/:id/uploads", post(create_upload))
+        .route("/projects/:id/uploads/:upload_id", get(get_upload_status))
+        .route("/projects/:id/uploads/:upload_id/chunks/:hash", put(upload_chunk))
+        .route("/projects/:id/uploads/:upload_id/complete", post(complete_upload))
+}
+
+/// Authenticate the request and check project access
+async fn authorize_project(state: &AppState, headers: &HeaderMap, project_id: ProjectId) -> Result<UserId, StatusCode> {
+    let user_id = authenticate_request(state, headers)?;
+
+    if !state.sync.features.is_enabled(Feature::FileUploads, Some(project_id)).await {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    match state.db.get_project(project_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+
+    if !state.db.user_has_project_access(user_id, project_id).await.unwrap_or(false) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok(user_id)
+}
+
+/// Load a session that belongs to this user and project and has not expired
+async fn load_session(
+    state: &AppState,
+    project_id: ProjectId,
+    user_id: UserId,
+    upload_id: Uuid,
+) -> Result<UploadSession, StatusCode> {
+    let session = match state.sync.storage.load_upload_session(upload_id).await {
+        Ok(Some(session)) => session,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            error!("Failed to load upload session {}: {}", upload_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    if session.project_id != project_id || session.user_id != user_id {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    if Utc::now() - session.created_at > chrono::Duration::hours(UPLOAD_SESSION_TTL_HOURS) {
+        info!("🧹 Discarding expired upload session {}", upload_id);
+        let _ = state.sync.storage.remove_upload_session(upload_id).await;
+        return Err(StatusCode::GONE);
+    }
+
+    Ok(session)
+}
+
+fn upload_status(state: &AppState, session: &UploadSession) -> UploadStatus {
+    let chunks = session.chunk_hashes();
+    UploadStatus {
+        upload_id: session.id,
+        total_chunks: chunks.len(),
+        missing_chunks: chunks.into_iter().filter(|hash| !state.sync.storage.has_chunk(session.project_id, hash)).collect(),
+        rejected: session.rejected.clone(),
+    }
+}
+
+/// What an uploaded file has to pass to be stored in the project: a path
+/// inside it, the project's push rules and the server's file size limit
+pub(crate) async fn file_checker(state: &AppState, project_id: ProjectId) -> Result<PushRuleChecker, StatusCode> {
+    let settings = state.db.get_project_settings(project_id).await.map_err(|e| {
+        error!("Failed to load settings of project {}: {}", project_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(PushRuleChecker::new(&settings).with_server_max_file_size(state.config().sync_limits.max_file_bytes))
+}
+
+/// Get or create the user's main rift, which receives uploaded files
+async fn upload_target_rift(state: &AppState, project_id: ProjectId, user_id: UserId) -> Result<Rift, StatusCode> {
+    match state.db.get_user_rift(project_id, user_id).await {
+        Ok(Some(rift)) => Ok(rift),
+        Ok(None) => state.db.create_rift(project_id, user_id, Some("main".to_string())).await
+            .map_err(|e| {
+                error!("Failed to create main rift for upload: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }),
+        Err(e) => {
+            error!("Failed to look up rift for upload: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Open an upload session from file manifests. The response lists only the
+/// chunks this project has not uploaded yet, and the files it refuses, which
+/// are left out of the session.
+async fn create_upload(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(project_id): Path<ProjectId>,
+    Json(req): Json<CreateUploadRequest>,
+) -> Result<Json<ApiResponse<UploadStatus>>, ApiError> {
+    let user_id = authorize_project(&state, &headers, project_id).await?;
+    crate::archive::ensure_writable(&state, project_id)?;
+    let checker = file_checker(&state, project_id).await?;
+
+    let mut files = Vec::new();
+    let mut rejected = Vec::new();
+    for file in req.files {
+        let violations = checker.check_file(&file.path, file.size);
+        if !violations.is_empty() {
+            rejected.extend(violations);
+            continue;
+        }
+        if !is_valid_content_hash(&file.hash) || !file.chunks.iter().all(|hash| is_valid_content_hash(hash)) {
+            return Err(ApiError::bad_request(format!("Invalid hash in manifest for {}", file.path.display())));
+        }
+        if file.size > (file.chunks.len() * UPLOAD_CHUNK_SIZE) as u64 {
+            return Err(ApiError::bad_request(format!("Manifest for {} has too few chunks", file.path.display())));
+        }
+        files.push(file);
+    }
+
+    let session = UploadSession {
+        id: Uuid::new_v4(),
+        project_id,
+        user_id,
+        files,
+        rejected,
+        created_at: Utc::now(),
+    };
+
+    state.sync.storage.save_upload_session(&session).await
+        .map_err(|e| {
+            error!("Failed to save upload session: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let status = upload_status(&state, &session);
+    info!(
+        "📤 Upload session {} for project {}: {} files ({} refused), {}/{} chunks needed",
+        session.id, project_id, session.files.len(), session.rejected.len(), status.missing_chunks.len(), status.total_chunks
+    );
+
+    Ok(Json(ApiResponse::success(status)))
+}
+
+/// Report which chunks are still missing (used to resume an upload)
+async fn get_upload_status(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((project_id, upload_id)): Path<(ProjectId, Uuid)>,
+) -> Result<Json<ApiResponse<UploadStatus>>, StatusCode> {
+    let user_id = authorize_project(&state, &headers, project_id).await?;
+    let session = load_session(&state, project_id, user_id, upload_id).await?;
+
+    Ok(Json(ApiResponse::success(upload_status(&state, &session))))
+}
+
+/// Store one chunk. The body must hash to the chunk's address.
+async fn upload_chunk(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((project_id, upload_id, hash)): Path<(ProjectId, Uuid, String)>,
+    body: Bytes,
+) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    let user_id = authorize_project(&state, &headers, project_id).await?;
+    let session = load_session(&state, project_id, user_id, upload_id).await?;
+
+    if !is_valid_content_hash(&hash) || !session.files.iter().any(|f| f.chunks.contains(&hash)) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    if body.len() > UPLOAD_CHUNK_SIZE {
+        return Err(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    if let Err(e) = state.sync.storage.store_chunk(project_id, &hash, &body).await {
+        warn!("❌ Rejected chunk {} for upload {}: {}", hash, upload_id, e);
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    Ok(Json(ApiResponse::success(hash)))
+}
+
+/// Reassemble every file, verify it, and write it into the user's rift
+async fn complete_upload(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((project_id, upload_id)): Path<(ProjectId, Uuid)>,
+) -> Result<Json<ApiResponse<UploadCompletion>>, ApiError> {
+    let user_id = authorize_project(&state, &headers, project_id).await?;
+    crate::archive::ensure_writable(&state, project_id)?;
+    let session = load_session(&state, project_id, user_id, upload_id).await?;
+
+    let status = upload_status(&state, &session);
+    if !status.missing_chunks.is_empty() {
+        return Err(ApiError::conflict(format!(
+            "Upload incomplete: {} of {} chunks missing",
+            status.missing_chunks.len(),
+            status.total_chunks
+        )));
+    }
+
+    let rift = upload_target_rift(&state, project_id, user_id).await?;
+    // The project's rules may have changed since the session opened
+    let checker = file_checker(&state, project_id).await?;
+
+    let mut files_stored = 0;
+    let mut skipped = Vec::new();
+    let mut rejected = session.rejected.clone();
+    for manifest in &session.files {
+        let bytes = match state.sync.storage.assemble_chunks(manifest).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                error!("❌ Failed to assemble {}: {}", manifest.path.display(), e);
+                return Err(StatusCode::UNPROCESSABLE_ENTITY.into());
+            }
+        };
+
+        let violations = checker.check_file(&manifest.path, bytes.len() as u64);
+        if !violations.is_empty() {
+            rejected.extend(violations);
+            continue;
+        }
+
+        // Rift state holds text content only
+        let content = match String::from_utf8(bytes) {
+            Ok(content) => content,
+            Err(_) => {
+                skipped.push(manifest.path.clone());
+                continue;
+            }
+        };
+
+        if let Err(e) = state.sync.storage.update_live_state(rift.id, manifest.path.clone(), content.clone()).await {
+            error!("Failed to store uploaded file {}: {}", manifest.path.display(), e);
+            skipped.push(manifest.path.clone());
+        } else {
+            crate::malware_scan::queue(&state.sync, rift.id, &manifest.path, &content, Some(user_id));
+            files_stored += 1;
+        }
+    }
+
+    let _ = state.sync.storage.remove_upload_session(upload_id).await;
+    info!("✅ Upload {} complete: {} files stored in rift {}, {} refused", upload_id, files_stored, rift.id, rejected.len());
+
+    Ok(Json(ApiResponse::success(UploadCompletion { files_stored, skipped, rejected })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_client::TestClient;
+    use mothership_common::upload::UploadFileManifest;
+    use mothership_common::Project;
+
+    async fn create_project(client: &TestClient, name: &str) -> Project {
+        client.post("/gateway/create", &serde_json::json!({
+            "name": name,
+            "description": "",
+            "project_path": format!("/tmp/{}", name),
+        })).await.data()
+    }
+
+    async fn open_upload(client: &TestClient, project: &Project, manifest: &UploadFileManifest) -> UploadStatus {
+        client.post(
+            &format!("/projects/{}/uploads", project.id),
+            &CreateUploadRequest { files: vec![manifest.clone()] },
+        ).await.data()
+    }
+
+    #[tokio::test]
+    async fn test_another_project_cannot_claim_a_chunk_it_never_uploaded() {
+        let mut client = TestClient::new().await;
+        client.sign_in("owner").await;
+        let owner_project = create_project(&client, "owner-uploads").await;
+        let contents = b"secret contents\n";
+        let manifest = UploadFileManifest::from_bytes("secret.txt".into(), contents);
+
+        let status = open_upload(&client, &owner_project, &manifest).await;
+        let chunk = &manifest.chunks[0];
+        let uri = format!("/projects/{}/uploads/{}/chunks/{}", owner_project.id, status.upload_id, chunk);
+        assert_eq!(client.put(&uri, contents).await.status, StatusCode::OK);
+
+        client.sign_in("guesser").await;
+        let guesser_project = create_project(&client, "guesser-uploads").await;
+        let status = open_upload(&client, &guesser_project, &manifest).await;
+        assert_eq!(status.missing_chunks, manifest.chunks, "The chunk store must not reveal other projects' content");
+
+        let complete = format!("/projects/{}/uploads/{}/complete", guesser_project.id, status.upload_id);
+        assert_eq!(client.post(&complete, &()).await.status, StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_upload_resumes_with_the_chunks_still_missing() {
+        let mut client = TestClient::new().await;
+        client.sign_in("owner").await;
+        let project = create_project(&client, "resumed-upload").await;
+        let contents = vec![b'x'; UPLOAD_CHUNK_SIZE + 10];
+        let manifest = UploadFileManifest::from_bytes("big.txt".into(), &contents);
+        assert_eq!(manifest.chunks.len(), 2);
+
+        let status = open_upload(&client, &project, &manifest).await;
+        assert_eq!(status.missing_chunks, manifest.chunks);
+        let uploads = format!("/projects/{}/uploads/{}", project.id, status.upload_id);
+        let first = client.put(&format!("{}/chunks/{}", uploads, manifest.chunks[0]), &contents[..UPLOAD_CHUNK_SIZE]).await;
+        assert_eq!(first.status, StatusCode::OK);
+
+        let resumed: UploadStatus = client.get(&uploads).await.data();
+        assert_eq!(resumed.missing_chunks, vec![manifest.chunks[1].clone()]);
+        let reopened = open_upload(&client, &project, &manifest).await;
+        assert_eq!(reopened.missing_chunks, vec![manifest.chunks[1].clone()]);
+
+        let last = client.put(&format!("{}/chunks/{}", uploads, manifest.chunks[1]), &contents[UPLOAD_CHUNK_SIZE..]).await;
+        assert_eq!(last.status, StatusCode::OK);
+        let completion: UploadCompletion = client.post(&format!("{}/complete", uploads), &()).await.data();
+        assert_eq!(completion.files_stored, 1);
+    }
+
+    #[tokio::test]
+    async fn test_chunk_that_does_not_match_its_hash_is_rejected() {
+        let mut client = TestClient::new().await;
+        client.sign_in("owner").await;
+        let project = create_project(&client, "mismatched-upload").await;
+        let manifest = UploadFileManifest::from_bytes("notes.txt".into(), b"expected\n");
+        let status = open_upload(&client, &project, &manifest).await;
+
+        let uploads = format!("/projects/{}/uploads/{}", project.id, status.upload_id);
+        let chunk = format!("{}/chunks/{}", uploads, manifest.chunks[0]);
+        assert_eq!(client.put(&chunk, b"tampered\n").await.status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        let status: UploadStatus = client.get(&uploads).await.data();
+        assert_eq!(status.missing_chunks, manifest.chunks);
+        assert!(!client.state.sync.storage.has_chunk(project.id, &manifest.chunks[0]));
+    }
+}
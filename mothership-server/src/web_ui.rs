@@ -24,6 +24,8 @@ pub fn routes() -> Router<crate::AppState> {
         .route("/auth/callback", post(auth_callback))
         .route("/auth/finalize", get(auth_finalize))
         .route("/robots.txt", get(robots_txt))
+        // Read-only project browser
+        .merge(crate::project_browser::routes())
         // Serve static files (icon.png, etc.)
         .nest_service("/static", ServeDir::new("content"))
 }
@@ -48,7 +50,7 @@ struct DownloadPageQuery {
 
 /// Main index page
 async fn index_page(State(state): State<crate::AppState>) -> Html<String> {
-    let auth_required = state.config.cli_distribution.require_auth_for_downloads || state.whitelist.is_some();
+    let auth_required = state.config().cli_distribution.require_auth_for_downloads || state.access_restricted();
     
     let html = format!(r#"
 <!DOCTYPE html>
@@ -204,6 +206,7 @@ async fn index_page(State(state): State<crate::AppState>) -> Html<String> {
             <h2>Get Started</h2>
             <p>Download the Mothership CLI to begin collaborating with your team</p>
             {}
+            <a href="/browse" class="btn">Browse Projects</a>
         </div>
     </div>
 </body>
@@ -232,7 +235,7 @@ async fn index_page(State(state): State<crate::AppState>) -> Html<String> {
 
 /// Login page that starts OAuth flow
 async fn login_page(State(state): State<crate::AppState>) -> Result<Html<String>, StatusCode> {
-    if !state.config.features.oauth_enabled {
+    if !state.config().features.oauth_enabled {
         return Ok(Html(format!(r#"
 <!DOCTYPE html>
 <html lang="en">
@@ -538,7 +541,7 @@ async fn login_page(State(state): State<crate::AppState>) -> Result<Html<String>
 
 /// Public download page (when auth not required)
 async fn download_page(State(state): State<crate::AppState>) -> Html<String> {
-    let auth_required = state.config.cli_distribution.require_auth_for_downloads || state.whitelist.is_some();
+    let auth_required = state.config().cli_distribution.require_auth_for_downloads || state.access_restricted();
     
     if auth_required {
         return Html(format!(r#"
@@ -617,6 +620,15 @@ async fn download_page(State(state): State<crate::AppState>) -> Html<String> {
     generate_download_page_html(None, None, None, &state).await
 }
 
+/// The user signed in to the web UI, if their session cookie is valid
+pub async fn session_user(state: &crate::AppState, jar: &CookieJar) -> Option<uuid::Uuid> {
+    let session_id = jar.get("mothership_session")?.value().to_string();
+    let sessions = state.sessions.read().await;
+    sessions.get(&session_id)
+        .filter(|session| chrono::Utc::now() <= session.expires_at)
+        .map(|session| session.user_id)
+}
+
 /// Authenticated download page (after successful OAuth)
 async fn authenticated_download_page(
     jar: CookieJar,
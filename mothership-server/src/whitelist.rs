@@ -0,0 +1,181 @@
+//! The user whitelist.
+//!
+//! With `whitelist_enabled`, only users matching an entry get in: a
+//! username, an email address, an email domain, or an OAuth group (a GitHub
+//! organization or team, or a Google Workspace domain) reported at their
+//! last login. Entries live in the database and admins manage them through
+//! `/admin/whitelist`; the whitelist file only seeds them the first time the
+//! server starts with the whitelist enabled. Members of the organizations
+//! and teams in `[github_access]` get in too (see `github_access`).
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{delete, get},
+    Json, Router,
+};
+use mothership_common::{protocol::ApiResponse, User};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::audit::{self, AuditAction, AuditEvent};
+use crate::config::{self, ServerConfig, UserWhitelist, WhitelistKind};
+use crate::database::{Database, WhitelistEntry};
+use crate::handlers::require_admin;
+use crate::AppState;
+
+/// Whitelist management endpoints (admins only)
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/admin/whitelist", get(list_entries).post(add_entry))
+        .route("/admin/whitelist/:id", delete(remove_entry))
+}
+
+#[derive(Debug, Serialize)]
+pub struct WhitelistListing {
+    /// Whether the server enforces the whitelist (`whitelist_enabled`)
+    pub enabled: bool,
+    pub entries: Vec<WhitelistEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddEntryRequest {
+    /// As in the whitelist file: `alice`, `bob@company.com`,
+    /// `*@company.com` or `group:github:acme/platform`
+    pub entry: String,
+}
+
+/// The whitelist to enforce, or `None` when it's disabled. Seeds the
+/// database from the whitelist file while it has no entries.
+pub async fn load(db: &Database, config: &ServerConfig) -> anyhow::Result<Option<UserWhitelist>> {
+    if !config.auth.whitelist_enabled {
+        return Ok(None);
+    }
+
+    let mut entries = db.list_whitelist_entries().await?;
+    if entries.is_empty() {
+        let Some(seed) = config.load_whitelist()? else {
+            return Ok(None);
+        };
+        for (kind, value) in seed.entries() {
+            db.add_whitelist_entry(kind.as_str(), &value, None).await?;
+        }
+        entries = db.list_whitelist_entries().await?;
+        info!("🌱 Seeded the whitelist with {} entries from {}", entries.len(), config.auth.whitelist_path);
+    }
+
+    Ok(Some(UserWhitelist::from_entries(entries.into_iter().filter_map(|entry| {
+        Some((WhitelistKind::parse(&entry.kind)?, entry.value))
+    }))))
+}
+
+/// Whether a user may use the server: anyone, unless the whitelist is
+/// enabled or `[github_access]` names organizations or teams. Then the user
+/// needs a whitelist entry, or an OAuth group from their last login that
+/// either allows.
+pub async fn user_allowed(state: &AppState, user: &User) -> bool {
+    let config = state.config();
+    let github = &config.github_access;
+    let whitelist = state.whitelist();
+    if whitelist.is_none() && !github.restricts_login() {
+        return true;
+    }
+
+    if let Some(whitelist) = &whitelist {
+        if whitelist.is_user_allowed(&user.username, &user.email) {
+            return true;
+        }
+    }
+    let whitelist_groups = whitelist.as_ref().is_some_and(|whitelist| !whitelist.groups.is_empty());
+    if !whitelist_groups && !github.restricts_login() {
+        return false;
+    }
+
+    match state.db.get_user_oauth_groups(user.id).await {
+        Ok(groups) => {
+            whitelist.is_some_and(|whitelist| whitelist.is_group_allowed(&groups)) || github.allows(&groups)
+        }
+        Err(e) => {
+            warn!("Failed to load OAuth groups of user {}: {}", user.id, e);
+            false
+        }
+    }
+}
+
+fn internal_error(e: anyhow::Error) -> StatusCode {
+    error!("Failed to access the whitelist: {}", e);
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+
+/// Apply a change to the whitelist being enforced
+async fn refresh(state: &AppState) {
+    if let Err(e) = state.settings.refresh_whitelist().await {
+        error!("Failed to reload the whitelist: {}", e);
+    }
+}
+
+/// Every whitelist entry
+async fn list_entries(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<ApiResponse<WhitelistListing>>, StatusCode> {
+    require_admin(&state, &headers, "manage the whitelist").await?;
+
+    let entries = state.db.list_whitelist_entries().await.map_err(internal_error)?;
+    Ok(Json(ApiResponse::success(WhitelistListing {
+        enabled: state.config().auth.whitelist_enabled,
+        entries,
+    })))
+}
+
+async fn add_entry(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<AddEntryRequest>,
+) -> Result<Json<ApiResponse<WhitelistEntry>>, StatusCode> {
+    let admin_id = require_admin(&state, &headers, "manage the whitelist").await?;
+    let Some((kind, value)) = config::parse_whitelist_entry(&request.entry) else {
+        return Ok(Json(ApiResponse::error(format!("Not a whitelist entry: '{}'", request.entry))));
+    };
+
+    let entry = state.db.add_whitelist_entry(kind.as_str(), &value, Some(admin_id)).await.map_err(internal_error)?;
+    refresh(&state).await;
+
+    info!("📋 Admin {} allowed {} {}", admin_id, entry.kind, entry.value);
+    audit::record(&state.db, AuditEvent::new(AuditAction::WhitelistEntryAdded, Some(admin_id))
+        .target(entry.id.to_string())
+        .details(serde_json::json!({ "kind": entry.kind, "value": entry.value }))).await;
+
+    Ok(Json(ApiResponse::success(entry)))
+}
+
+/// Remove an entry. The last one stays: a whitelist without entries would
+/// let nobody in, so turn off `whitelist_enabled` instead.
+async fn remove_entry(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<WhitelistEntry>>, StatusCode> {
+    let admin_id = require_admin(&state, &headers, "manage the whitelist").await?;
+
+    let entries = state.db.list_whitelist_entries().await.map_err(internal_error)?;
+    if !entries.iter().any(|entry| entry.id == id) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    if entries.len() == 1 {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let entry = state.db.remove_whitelist_entry(id).await
+        .map_err(internal_error)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    refresh(&state).await;
+
+    info!("📋 Admin {} removed {} {} from the whitelist", admin_id, entry.kind, entry.value);
+    audit::record(&state.db, AuditEvent::new(AuditAction::WhitelistEntryRemoved, Some(admin_id))
+        .target(entry.id.to_string())
+        .details(serde_json::json!({ "kind": entry.kind, "value": entry.value }))).await;
+
+    Ok(Json(ApiResponse::success(entry)))
+}